@@ -8,14 +8,20 @@ use actix_web::{
     web::{self, FormConfig, JsonConfig, PayloadConfig},
     App, HttpServer, Responder,
 };
-use std::{fs, time::Duration};
+use std::{fs, future::Future, net::SocketAddr, sync::Arc, time::Duration};
 use utoipa_rapidoc::RapiDoc;
 use zino_core::{
-    application::{Application, Plugin, ServerTag},
+    application::{Application, LifecycleHooks, Plugin, ServerTag},
+    error::Error,
     extension::TomlTableExt,
     schedule::AsyncScheduler,
+    state::State,
+    Map,
+};
+use zino_http::{
+    response::{ErrorResponseFormat, Response},
+    static_files::StaticFiles,
 };
-use zino_http::response::Response;
 
 /// An HTTP server cluster.
 #[derive(Default)]
@@ -26,6 +32,77 @@ pub struct Cluster {
     default_routes: Vec<RouterConfigure>,
     /// Tagged routes.
     tagged_routes: Vec<(ServerTag, Vec<RouterConfigure>)>,
+    /// Static file services.
+    static_files: Vec<StaticFiles>,
+    /// Lifecycle hooks.
+    lifecycle_hooks: LifecycleHooks,
+}
+
+impl Cluster {
+    /// Sets the format of the JSON body for error responses.
+    ///
+    /// The default format is problem details defined in RFC 7807.
+    #[inline]
+    pub fn with_error_format(self, format: impl ErrorResponseFormat) -> Self {
+        zino_http::response::set_error_format(format);
+        self
+    }
+
+    /// Serves the static files under the route prefix with the config `[static.{name}]`.
+    /// See [`StaticFiles`] for the supported options.
+    #[inline]
+    pub fn serve_dir(mut self, route_prefix: &str, name: &str) -> Self {
+        let static_files = StaticFiles::with_config_name(route_prefix, name);
+        self.static_files.push(static_files);
+        self
+    }
+
+    /// Registers a hook running after the config is loaded
+    /// but before the connection pools are established.
+    /// A failing hook aborts the startup with the error.
+    #[inline]
+    pub fn on_boot<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(&'static State<Map>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        self.lifecycle_hooks.add_boot_hook(hook);
+        self
+    }
+
+    /// Registers a hook running after the listeners have been bound.
+    #[inline]
+    pub fn on_ready<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(SocketAddr) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.lifecycle_hooks.add_ready_hook(hook);
+        self
+    }
+
+    /// Registers a hook running after the config has been reloaded on `SIGHUP`
+    /// or a change of the config file. See [`Application::reload_config()`] for the details.
+    #[inline]
+    pub fn on_config_reload<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(&'static State<Map>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        self.lifecycle_hooks.add_reload_hook(hook);
+        self
+    }
+
+    /// Registers a hook running after the servers have been stopped gracefully.
+    #[inline]
+    pub fn on_shutdown<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.lifecycle_hooks.add_shutdown_hook(hook);
+        self
+    }
 }
 
 impl Application for Cluster {
@@ -52,9 +129,19 @@ impl Application for Cluster {
     fn run_with<T: AsyncScheduler + Send + 'static>(self, mut scheduler: T) {
         let runtime = Runtime::new().expect("fail to build Tokio runtime for `ActixCluster`");
         let app_env = Self::env();
+        let lifecycle_hooks = Arc::new(self.lifecycle_hooks);
         runtime.block_on(async {
+            if let Err(err) = lifecycle_hooks.run_boot_hooks(Self::shared_state()).await {
+                panic!("fail to run the boot hooks: {err}");
+            }
             #[cfg(feature = "orm")]
             zino_orm::GlobalPool::connect_all().await;
+            #[cfg(feature = "orm")]
+            zino_orm::JobRunStore::init();
+            #[cfg(feature = "orm")]
+            zino_core::application::LoadShedder::set_pool_waiters_source(
+                zino_orm::GlobalPool::waiting_count,
+            );
             Self::load().await;
             app_env.load_plugins(self.custom_plugins).await;
         });
@@ -79,18 +166,61 @@ impl Application for Cluster {
             }
         }
 
+        #[cfg(feature = "orm")]
+        {
+            let mut maintenance_scheduler = zino_orm::MaintenanceScheduler::new();
+            runtime.spawn(async move {
+                loop {
+                    maintenance_scheduler.tick().await;
+                    if let Some(duration) = maintenance_scheduler.time_till_next_job() {
+                        rt::time::sleep(duration).await;
+                    }
+                }
+            });
+        }
+
+        // Config reloading
+        let hooks = lifecycle_hooks.clone();
+        runtime.spawn(async move {
+            hooks.watch_config::<Self, _, _>(rt::time::sleep).await;
+        });
+        #[cfg(unix)]
+        {
+            let hooks = lifecycle_hooks.clone();
+            runtime.spawn(async move {
+                use rt::signal::unix::{signal, SignalKind};
+
+                match signal(SignalKind::hangup()) {
+                    Ok(mut hangup) => {
+                        while hangup.recv().await.is_some() {
+                            hooks.reload_config::<Self>().await;
+                        }
+                    }
+                    Err(err) => tracing::error!("fail to install the hangup signal handler: {err}"),
+                }
+            });
+        }
+
         runtime.block_on(async {
             let default_routes = self.default_routes.leak() as &'static [_];
             let tagged_routes = self.tagged_routes.leak() as &'static [_];
+            let file_services = self.static_files.leak() as &'static [StaticFiles];
             let app_state = Self::shared_state();
             let app_name = Self::name();
             let app_version = Self::version();
             let app_domain = Self::domain();
-            let listeners = app_state.listeners();
-            let has_debug_server = listeners.iter().any(|listener| listener.0.is_debug());
+            let listeners = app_state.server_listeners();
+            let bound_addrs = listeners
+                .iter()
+                .map(|listener| listener.addr())
+                .collect::<Vec<_>>();
+            let has_debug_server = listeners.iter().any(|listener| listener.scope().is_debug());
             let servers = listeners.into_iter().map(|listener| {
-                let server_tag = listener.0;
-                let addr = listener.1;
+                let server_tag = listener.scope().clone();
+                let addr = listener.addr();
+                if listener.tls().is_some() {
+                    panic!("fail to listen on {addr}: TLS is not supported for the listeners");
+                }
                 tracing::warn!(
                     server_tag = server_tag.as_str(),
                     app_env = app_env.as_str(),
@@ -173,11 +303,30 @@ impl Application for Cluster {
                             "Static pages `{public_route_prefix}/**` are registered for `{addr}`"
                         );
                     }
-                    for route in default_routes {
-                        app = app.configure(route);
+                    for files in file_services {
+                        let route_prefix = files.route_prefix();
+                        let wildcard_route = if route_prefix == "/" {
+                            "/{path:.*}".to_owned()
+                        } else {
+                            format!("{route_prefix}/{{path:.*}}")
+                        };
+                        let handler = move |req: Request| async move {
+                            ActixResponse::from(files.serve(req).await)
+                        };
+                        app = app
+                            .route(route_prefix, web::get().to(handler))
+                            .route(&wildcard_route, web::get().to(handler));
+                        tracing::info!(
+                            "Static files `{route_prefix}/**` are registered for `{addr}`"
+                        );
+                    }
+                    if listener.serves_default_routes() {
+                        for route in default_routes {
+                            app = app.configure(route);
+                        }
                     }
                     for (tag, routes) in tagged_routes {
-                        if tag == &server_tag || server_tag.is_debug() {
+                        if listener.serves_routes(tag) {
                             for route in routes {
                                 app = app.configure(route);
                             }
@@ -225,24 +374,42 @@ impl Application for Cluster {
                         .app_data(JsonConfig::default().limit(body_limit))
                         .app_data(PayloadConfig::default().limit(body_limit))
                         .wrap(Compress::default())
+                        .wrap(middleware::MaintenanceGuard)
+                        .wrap(middleware::PanicHandler)
                         .wrap(middleware::RequestContextInitializer)
                         .wrap(middleware::tracing_middleware())
-                        .wrap(middleware::cors_middleware())
+                        .wrap(middleware::CorsHandler)
                         .wrap(middleware::ETagFinalizer)
+                        .wrap(middleware::LoadSheddingGuard)
+                        .wrap(middleware::HostValidator)
+                        .wrap(middleware::SecurityHeadersSetter)
+                        .wrap(middleware::ServerScope::new(server_tag.clone()))
                 })
                 .server_hostname(app_domain)
                 .backlog(backlog)
                 .max_connections(max_connections)
                 .client_request_timeout(request_timeout)
                 .bind(addr)
-                .unwrap_or_else(|err| panic!("fail to create an HTTP server: {err}"))
-                .run()
+                .unwrap_or_else(|err| panic!("fail to listen on {addr}: {err}"))
             });
+
+            // Binds all the listeners before serving, so that a failure aborts the boot.
+            let servers = servers.collect::<Vec<_>>();
+            for addr in bound_addrs {
+                lifecycle_hooks.run_ready_hooks(addr).await;
+            }
+
+            let servers = servers.into_iter().map(|server| server.run());
             for result in futures::future::join_all(servers).await {
                 if let Err(err) = result {
                     tracing::error!("actix server error: {err}");
                 }
             }
+            lifecycle_hooks.run_shutdown_hooks().await;
+
+            // Flushes the pending spans after the servers have been stopped
+            #[cfg(feature = "otel")]
+            zino_core::application::shutdown_tracer_provider();
         });
     }
 }
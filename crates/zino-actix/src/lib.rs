@@ -10,6 +10,9 @@ mod middleware;
 mod request;
 mod response;
 
+#[cfg(feature = "test-utils")]
+pub mod test;
+
 pub use application::Cluster;
 pub use request::Extractor;
 pub use response::{ActixRejection, ActixResponse};
@@ -7,7 +7,7 @@ use std::{
     pin::Pin,
 };
 use tracing::Span;
-use zino_http::request::RequestContext;
+use zino_http::request::{Context, RequestContext};
 
 #[derive(Default)]
 pub struct RequestContextInitializer;
@@ -53,9 +53,18 @@ where
             req.extensions_mut().insert(ctx);
         }
 
+        // Only the identifiers are captured for the tasks spawned in the request flow.
+        let task_context = req
+            .extensions()
+            .get::<Context>()
+            .map(|ctx| ctx.task_context());
         let fut = self.service.call(req);
         Box::pin(async move {
-            let res = fut.await?;
+            let res = if let Some(ctx) = task_context {
+                ctx.scope(fut).await?
+            } else {
+                fut.await?
+            };
             Ok(res)
         })
     }
@@ -1,45 +1,105 @@
-use actix_cors::Cors;
-use actix_web::http::{header::HeaderName, Method};
-use zino_core::{application::Application, extension::TomlTableExt};
-
-/// CORS middleware.
-pub(crate) fn cors_middleware() -> Cors {
-    if let Some(cors) = crate::Cluster::config().get_table("cors") {
-        let allow_methods = cors
-            .get_array("allow-methods")
-            .map(|values| {
-                values
-                    .iter()
-                    .filter_map(|v| v.as_str().and_then(|s| s.parse::<Method>().ok()))
-                    .collect::<Vec<_>>()
-            })
-            .unwrap_or_default();
-        let allow_headers = cors
-            .get_array("allow-headers")
-            .map(|values| {
-                values
-                    .iter()
-                    .filter_map(|v| v.as_str().and_then(|s| s.parse::<HeaderName>().ok()))
-                    .collect::<Vec<_>>()
-            })
-            .unwrap_or_default();
-        let expose_headers = cors
-            .get_array("expose-headers")
-            .map(|values| {
-                values
-                    .iter()
-                    .filter_map(|v| v.as_str().and_then(|s| s.parse::<HeaderName>().ok()))
-                    .collect::<Vec<_>>()
-            })
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderMap, HeaderName, HeaderValue},
+    Error, HttpMessage, HttpResponse,
+};
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+};
+use zino_core::{application::ServerTag, state::Data};
+use zino_http::cors::{self, CorsPolicy};
+
+/// Applies the CORS policy for the request path and the listener scope.
+///
+/// Preflight requests are responded before reaching the routes and their middlewares,
+/// and a disallowed origin gets a response without the CORS headers.
+#[derive(Default)]
+pub struct CorsHandler;
+
+impl<S, B> Transform<S, ServiceRequest> for CorsHandler
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = CorsMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CorsMiddleware { service }))
+    }
+}
+
+pub struct CorsMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for CorsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let headers = req.headers();
+        let Some(origin) = headers.get("origin").and_then(|v| v.to_str().ok()) else {
+            let fut = self.service.call(req);
+            return Box::pin(async move {
+                let res = fut.await?;
+                Ok(res.map_into_left_body())
+            });
+        };
+
+        let server_tag = req
+            .extensions()
+            .get::<Data<ServerTag>>()
+            .map(|data| data.get())
             .unwrap_or_default();
-        let max_age = cors.get_usize("max-age").unwrap_or(60 * 60);
-        Cors::default()
-            .allow_any_origin()
-            .allowed_methods(allow_methods)
-            .allowed_headers(allow_headers)
-            .expose_headers(expose_headers)
-            .max_age(max_age)
-    } else {
-        Cors::permissive()
+        let policy = CorsPolicy::for_scope(&server_tag, req.path());
+        let request_method = headers
+            .get("access-control-request-method")
+            .and_then(|v| v.to_str().ok());
+        if cors::is_preflight_request(req.method().as_str(), Some(origin), request_method) {
+            let request_headers = headers
+                .get("access-control-request-headers")
+                .and_then(|v| v.to_str().ok());
+            let cors_headers = policy.preflight_headers(
+                origin,
+                request_method.unwrap_or_default(),
+                request_headers,
+            );
+            let mut res = HttpResponse::NoContent().finish();
+            append_headers(res.headers_mut(), cors_headers);
+            let res = req.into_response(res).map_into_right_body();
+            return Box::pin(async move { Ok(res) });
+        }
+
+        let cors_headers = policy.response_headers(origin);
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let mut res = fut.await?;
+            append_headers(res.headers_mut(), cors_headers);
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+/// Appends the CORS headers to the header map.
+fn append_headers(header_map: &mut HeaderMap, headers: Vec<(&'static str, String)>) {
+    for (name, value) in headers {
+        if let Ok(value) = HeaderValue::try_from(value) {
+            header_map.append(HeaderName::from_static(name), value);
+        }
     }
 }
@@ -0,0 +1,83 @@
+use crate::response::ActixResponse;
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::StatusCode,
+    Error, HttpMessage, Responder,
+};
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+};
+use zino_core::{application::ServerTag, state::Data};
+use zino_http::{host::HostPolicy, response::Response};
+
+/// Validates the `Host` header and the authority of the request target
+/// with the host policy of the listener.
+#[derive(Default)]
+pub struct HostValidator;
+
+impl<S, B> Transform<S, ServiceRequest> for HostValidator
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = HostValidatorMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(HostValidatorMiddleware { service }))
+    }
+}
+
+pub struct HostValidatorMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for HostValidatorMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let server_tag = req
+            .extensions()
+            .get::<Data<ServerTag>>()
+            .map(|data| data.get())
+            .unwrap_or_default();
+        let policy = HostPolicy::for_scope(&server_tag);
+        let authority = req.uri().authority().map(|authority| authority.as_str());
+        let host = req
+            .headers()
+            .get("host")
+            .map(|v| v.to_str().unwrap_or_default());
+        if let Err(rejection) = policy.validate(req.path(), authority, host) {
+            let status_code =
+                StatusCode::from_u16(rejection.status_code()).unwrap_or(StatusCode::BAD_REQUEST);
+            let (req, _) = req.into_parts();
+            let ctx = crate::Request::from(req.clone());
+            let mut res = Response::new(status_code).context(&ctx);
+            res.set_message(rejection.message());
+            let res = ActixResponse::from(res).respond_to(&req);
+            let res = ServiceResponse::new(req, res).map_into_right_body();
+            Box::pin(async move { Ok(res) })
+        } else {
+            let fut = self.service.call(req);
+            Box::pin(async move {
+                let res = fut.await?;
+                Ok(res.map_into_left_body())
+            })
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::response::ActixResponse;
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::StatusCode,
+    Error, Responder,
+};
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+};
+use zino_core::application::LoadShedder;
+use zino_http::response::Response;
+
+/// Responds with `503 Service Unavailable` for the requests which are shed
+/// by the adaptive load shedding.
+#[derive(Default)]
+pub struct LoadSheddingGuard;
+
+impl<S, B> Transform<S, ServiceRequest> for LoadSheddingGuard
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = LoadSheddingMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(LoadSheddingMiddleware { service }))
+    }
+}
+
+pub struct LoadSheddingMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for LoadSheddingMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if let Some(guard) = LoadShedder::admit(req.path()) {
+            let fut = self.service.call(req);
+            Box::pin(async move {
+                let res = fut.await;
+                drop(guard);
+                Ok(res?.map_into_left_body())
+            })
+        } else {
+            let (req, _) = req.into_parts();
+            let ctx = crate::Request::from(req.clone());
+            let res = Response::new(StatusCode::SERVICE_UNAVAILABLE).shed_load(&ctx);
+            let res = ActixResponse::from(res).respond_to(&req);
+            let res = ServiceResponse::new(req, res).map_into_right_body();
+            Box::pin(async move { Ok(res) })
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use crate::response::ActixResponse;
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::StatusCode,
+    Error, Responder,
+};
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+};
+use zino_core::application::MaintenanceMode;
+use zino_http::response::Response;
+
+#[derive(Default)]
+pub struct MaintenanceGuard;
+
+impl<S, B> Transform<S, ServiceRequest> for MaintenanceGuard
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = MaintenanceMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MaintenanceMiddleware { service }))
+    }
+}
+
+pub struct MaintenanceMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for MaintenanceMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if MaintenanceMode::is_enabled() && !MaintenanceMode::is_allowed(req.path()) {
+            let (req, _) = req.into_parts();
+            let ctx = crate::Request::from(req.clone());
+            let res = Response::new(StatusCode::SERVICE_UNAVAILABLE).maintenance(&ctx);
+            let res = ActixResponse::from(res).respond_to(&req);
+            let res = ServiceResponse::new(req, res).map_into_right_body();
+            Box::pin(async move { Ok(res) })
+        } else {
+            let fut = self.service.call(req);
+            Box::pin(async move {
+                let res = fut.await?;
+                Ok(res.map_into_left_body())
+            })
+        }
+    }
+}
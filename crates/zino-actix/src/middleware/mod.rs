@@ -1,9 +1,21 @@
 mod context;
 mod cors;
 mod etag;
+mod host;
+mod load_shedding;
+mod maintenance;
+mod panic;
+mod scope;
+mod security;
 mod tracing;
 
 pub(crate) use self::context::RequestContextInitializer;
-pub(crate) use self::cors::cors_middleware;
+pub(crate) use self::cors::CorsHandler;
 pub(crate) use self::etag::ETagFinalizer;
+pub(crate) use self::host::HostValidator;
+pub(crate) use self::load_shedding::LoadSheddingGuard;
+pub(crate) use self::maintenance::MaintenanceGuard;
+pub(crate) use self::panic::PanicHandler;
+pub(crate) use self::scope::ServerScope;
+pub(crate) use self::security::SecurityHeadersSetter;
 pub(crate) use self::tracing::tracing_middleware;
@@ -0,0 +1,73 @@
+use crate::response::ActixResponse;
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::StatusCode,
+    Error, HttpRequest, Responder,
+};
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+};
+use zino_http::response::HandlerPanic;
+
+#[derive(Default)]
+pub struct PanicHandler;
+
+impl<S, B> Transform<S, ServiceRequest> for PanicHandler
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = PanicMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(PanicMiddleware { service }))
+    }
+}
+
+pub struct PanicMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for PanicMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let http_req = req.request().clone();
+        match HandlerPanic::catch_call(|| self.service.call(req)) {
+            Ok(fut) => Box::pin(async move {
+                match HandlerPanic::catch(fut).await {
+                    Ok(result) => result.map(ServiceResponse::map_into_left_body),
+                    Err(panic) => Ok(panic_response(http_req, panic)),
+                }
+            }),
+            Err(panic) => {
+                let res = panic_response(http_req, panic);
+                Box::pin(async move { Ok(res) })
+            }
+        }
+    }
+}
+
+/// Converts the panic into a `500 Internal Server Error` response.
+fn panic_response<B>(req: HttpRequest, panic: HandlerPanic) -> ServiceResponse<EitherBody<B>> {
+    let ctx = crate::Request::from(req.clone());
+    let res = panic.into_response::<StatusCode, _>(&ctx);
+    let res = ActixResponse::from(res).respond_to(&req);
+    ServiceResponse::new(req, res).map_into_right_body()
+}
@@ -0,0 +1,59 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error, HttpMessage,
+};
+use std::future::{ready, Ready};
+use zino_core::{application::ServerTag, state::Data};
+
+/// Sets the scope of the listener which accepts the request.
+pub struct ServerScope(ServerTag);
+
+impl ServerScope {
+    /// Creates a new instance.
+    #[inline]
+    pub fn new(server_tag: ServerTag) -> Self {
+        Self(server_tag)
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ServerScope
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ServerScopeMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ServerScopeMiddleware {
+            service,
+            server_tag: self.0.clone(),
+        }))
+    }
+}
+
+pub struct ServerScopeMiddleware<S> {
+    service: S,
+    server_tag: ServerTag,
+}
+
+impl<S, B> Service<ServiceRequest> for ServerScopeMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = S::Future;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        req.extensions_mut()
+            .insert(Data::new(self.server_tag.clone()));
+        self.service.call(req)
+    }
+}
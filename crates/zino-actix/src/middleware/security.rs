@@ -0,0 +1,90 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderMap, HeaderName, HeaderValue},
+    Error, HttpMessage,
+};
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+};
+use zino_core::{application::ServerTag, state::Data};
+use zino_http::security::{CspNonce, SecurityHeaders};
+
+/// Sets the security headers configured for the request path and the listener scope.
+///
+/// A CSP nonce is generated for the request if the policy requires it,
+/// and the headers set explicitly by the handler are not overridden.
+#[derive(Default)]
+pub struct SecurityHeadersSetter;
+
+impl<S, B> Transform<S, ServiceRequest> for SecurityHeadersSetter
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = SecurityHeadersMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(SecurityHeadersMiddleware { service }))
+    }
+}
+
+pub struct SecurityHeadersMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for SecurityHeadersMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let server_tag = req
+            .extensions()
+            .get::<Data<ServerTag>>()
+            .map(|data| data.get())
+            .unwrap_or_default();
+        let Some(preset) = SecurityHeaders::for_scope(&server_tag, req.path()) else {
+            return Box::pin(self.service.call(req));
+        };
+
+        let nonce = preset.requires_nonce().then(CspNonce::new);
+        if let Some(nonce) = nonce.as_ref() {
+            req.extensions_mut().insert(Data::new(nonce.clone()));
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let mut res = fut.await?;
+            let content_type = res
+                .headers()
+                .get("content-type")
+                .and_then(|v| v.to_str().ok());
+            let headers = preset.response_headers(&server_tag, content_type, nonce.as_ref());
+            insert_headers(res.headers_mut(), headers);
+            Ok(res)
+        })
+    }
+}
+
+/// Inserts the headers which are absent in the header map.
+fn insert_headers(header_map: &mut HeaderMap, headers: Vec<(&'static str, String)>) {
+    for (name, value) in headers {
+        let name = HeaderName::from_static(name);
+        if !header_map.contains_key(&name) {
+            if let Ok(value) = HeaderValue::try_from(value) {
+                header_map.insert(name, value);
+            }
+        }
+    }
+}
@@ -41,11 +41,12 @@ impl RootSpanBuilder for CustomRootSpanBuilder {
         let tracestate = headers.get("tracestate").and_then(|v| v.to_str().ok());
         let trace_context = traceparent.and_then(TraceContext::from_traceparent);
         let parent_id = trace_context
+            .as_ref()
             .and_then(|ctx| ctx.parent_id())
             .map(|parent_id| format!("{parent_id:x}"));
         let session_id = headers.get("session-id").and_then(|v| v.to_str().ok());
 
-        if method.is_safe() {
+        let span = if method.is_safe() {
             tracing::info_span!(
                 "HTTP request",
                 "otel.kind" = "server",
@@ -71,6 +72,7 @@ impl RootSpanBuilder for CustomRootSpanBuilder {
                 "context.request_id" = Empty,
                 "context.span_id" = Empty,
                 "context.parent_id" = parent_id,
+                "debug_session" = Empty,
             )
         } else {
             tracing::warn_span!(
@@ -98,8 +100,16 @@ impl RootSpanBuilder for CustomRootSpanBuilder {
                 "context.request_id" = Empty,
                 "context.span_id" = Empty,
                 "context.parent_id" = parent_id,
+                "debug_session" = Empty,
             )
+        };
+
+        // Connects the server span with the caller's trace before any child spans are created
+        #[cfg(feature = "otel")]
+        if let Some(trace_context) = trace_context {
+            trace_context.link_span(&span);
         }
+        span
     }
 
     fn on_request_end<B: MessageBody>(span: Span, outcome: &Result<ServiceResponse<B>, Error>) {
@@ -4,16 +4,22 @@ use actix_web::{
     web::Bytes,
     FromRequest, HttpMessage, HttpRequest,
 };
+use futures::{Stream, StreamExt};
 use std::{
     borrow::Cow,
     convert::Infallible,
-    future,
+    future, mem,
     net::IpAddr,
     ops::{Deref, DerefMut},
 };
 use zino_core::{error::Error, state::Data};
 use zino_http::request::{Context, RequestContext};
 
+#[cfg(feature = "test-utils")]
+use actix_web::test::TestRequest;
+#[cfg(feature = "test-utils")]
+use zino_http::test::{MatchedRoute, MockRequest};
+
 /// An HTTP request extractor.
 pub struct Extractor<T>(T, Payload);
 
@@ -50,10 +56,13 @@ impl RequestContext for Extractor<HttpRequest> {
     #[inline]
     fn matched_route(&self) -> Cow<'_, str> {
         if let Some(path) = self.match_pattern() {
-            path.into()
-        } else {
-            self.uri().path().into()
+            return path.into();
+        }
+        #[cfg(feature = "test-utils")]
+        if let Some(route) = self.extensions().get::<MatchedRoute>() {
+            return route.as_str().to_owned().into();
         }
+        self.uri().path().into()
     }
 
     #[inline]
@@ -86,7 +95,11 @@ impl RequestContext for Extractor<HttpRequest> {
 
     #[inline]
     fn get_data<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
-        self.extensions().get::<Data<T>>().map(|data| data.get())
+        let extensions = self.extensions();
+        let data = extensions.get::<Data<T>>();
+        #[cfg(feature = "test-utils")]
+        let data = data.or_else(|| extensions.get::<http::Extensions>()?.get::<Data<T>>());
+        data.map(|data| data.get())
     }
 
     #[inline]
@@ -103,6 +116,12 @@ impl RequestContext for Extractor<HttpRequest> {
             .map_err(Error::from_error)?;
         Ok(bytes.to_vec())
     }
+
+    #[inline]
+    fn take_body_stream(&mut self) -> impl Stream<Item = Result<Vec<u8>, Error>> + Unpin + 'static {
+        let payload = mem::replace(&mut self.1, Payload::None);
+        payload.map(|result| result.map(|bytes| bytes.to_vec()).map_err(Error::from))
+    }
 }
 
 impl From<ServiceRequest> for Extractor<HttpRequest> {
@@ -120,6 +139,36 @@ impl From<Extractor<HttpRequest>> for ServiceRequest {
     }
 }
 
+#[cfg(feature = "test-utils")]
+impl From<MockRequest> for Extractor<HttpRequest> {
+    /// Converts the mock request into a test request of `actix-web`.
+    /// The request scoped data are kept in the extensions of the request.
+    fn from(request: MockRequest) -> Self {
+        let (mut parts, body) = request.into_http_request().into_parts();
+        let method = Method::from_bytes(parts.method.as_str().as_bytes()).unwrap_or_default();
+        let mut test_request = TestRequest::default()
+            .method(method)
+            .uri(&parts.uri.to_string())
+            .set_payload(body);
+        for (name, value) in parts.headers.iter() {
+            test_request = test_request.append_header((name.as_str(), value.as_bytes()));
+        }
+
+        let (req, payload) = test_request.to_http_parts();
+        {
+            let mut extensions = req.extensions_mut();
+            if let Some(ctx) = parts.extensions.remove::<Context>() {
+                extensions.insert(ctx);
+            }
+            if let Some(route) = parts.extensions.remove::<MatchedRoute>() {
+                extensions.insert(route);
+            }
+            extensions.insert(parts.extensions);
+        }
+        Self(req, payload)
+    }
+}
+
 impl From<HttpRequest> for Extractor<HttpRequest> {
     #[inline]
     fn from(request: HttpRequest) -> Self {
@@ -1,12 +1,13 @@
 use actix_web::{
-    body::BoxBody,
+    body::{BodyStream, BoxBody},
     http::{
         header::{self, HeaderName, HeaderValue},
         StatusCode,
     },
     HttpRequest, HttpResponse, Responder, ResponseError,
 };
-use std::fmt;
+use futures::StreamExt;
+use std::{convert::Infallible, fmt};
 use zino_http::{
     response::{Rejection, Response, ResponseCode},
     timing::TimingMetric,
@@ -119,17 +120,35 @@ impl ResponseError for ActixRejection {
 
 /// Build http response from `zino_core::response::Response`.
 fn build_http_response<S: ResponseCode>(response: &mut Response<S>) -> HttpResponse<BoxBody> {
-    match response.read_bytes() {
-        Ok(data) => {
+    let sse_stream = response.take_sse_stream();
+    let is_sse_response = sse_stream.is_some();
+    let result = if let Some(sse_stream) = sse_stream {
+        let stream = sse_stream
+            .into_bytes_stream(actix_web::rt::time::sleep)
+            .map(Ok::<_, Infallible>);
+        Ok(BoxBody::new(BodyStream::new(stream)))
+    } else if let Some(stream) = response.take_bytes_stream() {
+        let stream = stream.map(Ok::<_, Infallible>);
+        Ok(BoxBody::new(BodyStream::new(stream)))
+    } else {
+        response.read_bytes().map(BoxBody::new)
+    };
+    match result {
+        Ok(body) => {
             let status_code = response
                 .status_code()
                 .try_into()
                 .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
-            let body = BoxBody::new(data);
             let mut res = HttpResponse::with_body(status_code, body);
             if let Ok(header_value) = HeaderValue::try_from(response.content_type()) {
                 res.headers_mut().insert(header::CONTENT_TYPE, header_value);
             }
+            if is_sse_response {
+                // Prevents the event stream from being compressed by the middleware.
+                let header_value = HeaderValue::from_static("identity");
+                res.headers_mut()
+                    .insert(header::CONTENT_ENCODING, header_value);
+            }
             res
         }
         Err(err) => {
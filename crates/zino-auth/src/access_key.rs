@@ -176,6 +176,13 @@ impl AsRef<[u8]> for SecretAccessKey {
     }
 }
 
+impl From<Vec<u8>> for SecretAccessKey {
+    #[inline]
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
 impl From<SecretAccessKey> for Vec<u8> {
     #[inline]
     fn from(s: SecretAccessKey) -> Vec<u8> {
@@ -0,0 +1,85 @@
+use std::time::Duration;
+use zino_core::{datetime::DateTime, error::Error, extension::JsonObjectExt, warn, Map};
+
+/// An access token issued by the OAuth2 token endpoint.
+#[derive(Debug, Clone)]
+pub struct AccessToken {
+    /// Access token.
+    access_token: String,
+    /// Token type.
+    token_type: String,
+    /// Granted scope.
+    scope: Option<String>,
+    /// Expires time.
+    expires_at: DateTime,
+}
+
+impl AccessToken {
+    /// Creates a new instance.
+    #[inline]
+    pub fn new(access_token: impl Into<String>, expires_in: Duration) -> Self {
+        Self {
+            access_token: access_token.into(),
+            token_type: "Bearer".to_owned(),
+            scope: None,
+            expires_at: DateTime::now() + expires_in,
+        }
+    }
+
+    /// Attempts to parse the successful response of the token endpoint.
+    /// The token is assumed to be valid for one hour if `expires_in` is absent.
+    pub fn try_from_response(data: &Map) -> Result<Self, Error> {
+        let access_token = data
+            .get_str("access_token")
+            .ok_or_else(|| warn!("the `access_token` field should be returned"))?;
+        let expires_in = data.get_u64("expires_in").unwrap_or(3600);
+        let mut token = Self::new(access_token, Duration::from_secs(expires_in));
+        if let Some(token_type) = data.get_str("token_type") {
+            token.token_type = token_type.to_owned();
+        }
+        token.scope = data.get_str("scope").map(|s| s.to_owned());
+        Ok(token)
+    }
+
+    /// Returns the access token.
+    #[inline]
+    pub fn access_token(&self) -> &str {
+        &self.access_token
+    }
+
+    /// Returns the token type.
+    #[inline]
+    pub fn token_type(&self) -> &str {
+        &self.token_type
+    }
+
+    /// Returns the granted scope.
+    #[inline]
+    pub fn scope(&self) -> Option<&str> {
+        self.scope.as_deref()
+    }
+
+    /// Returns the expires time.
+    #[inline]
+    pub fn expires_at(&self) -> DateTime {
+        self.expires_at
+    }
+
+    /// Returns the time when the access token will expire in.
+    #[inline]
+    pub fn expires_in(&self) -> Duration {
+        self.expires_at.span_after_now().unwrap_or_default()
+    }
+
+    /// Returns `true` if the access token has expired.
+    #[inline]
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= DateTime::now()
+    }
+
+    /// Returns `true` if the access token will expire within the duration.
+    #[inline]
+    pub fn expires_within(&self, duration: Duration) -> bool {
+        self.expires_at <= DateTime::now() + duration
+    }
+}
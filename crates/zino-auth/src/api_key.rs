@@ -0,0 +1,213 @@
+use hmac::{
+    digest::{KeyInit, Update},
+    Hmac, Mac,
+};
+use rand::{distr::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use std::{error, fmt, iter, sync::OnceLock};
+use zino_core::{
+    application::{Agent, Application},
+    crypto::{self, Digest},
+    encoding::base64,
+    error::Error,
+    BoxFuture, LazyLock,
+};
+
+/// An API key token in the form `zino_<id>_<secret>`,
+/// which is sent as a bearer token in the `authorization` header.
+///
+/// Only the hash of the secret should be persisted, so the plaintext token
+/// can not be recovered once it has been returned to the client.
+#[derive(Debug, Clone)]
+pub struct ApiKeyToken {
+    /// Key ID.
+    key_id: String,
+    /// Plaintext secret.
+    secret: String,
+}
+
+impl ApiKeyToken {
+    /// Generates a new token with random alphanumeric characters.
+    pub fn generate() -> Self {
+        Self {
+            key_id: random_string(16),
+            secret: random_string(32),
+        }
+    }
+
+    /// Parses the token in the form `zino_<id>_<secret>`.
+    pub fn parse(token: &str) -> Result<Self, ParseApiKeyError> {
+        let (key_id, secret) = token
+            .strip_prefix(TOKEN_PREFIX)
+            .and_then(|s| s.split_once('_'))
+            .ok_or(ParseApiKeyError::InvalidFormat)?;
+        if key_id.is_empty() || secret.is_empty() {
+            return Err(ParseApiKeyError::InvalidFormat);
+        }
+        Ok(Self {
+            key_id: key_id.to_owned(),
+            secret: secret.to_owned(),
+        })
+    }
+
+    /// Returns the key ID.
+    #[inline]
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Hashes the secret with a key derived from the application secret.
+    pub fn hash_secret(&self) -> String {
+        base64::encode(self.secret_mac().finalize().into_bytes())
+    }
+
+    /// Verifies the secret against the hash in constant time.
+    pub fn verify_secret(&self, secret_hash: &str) -> bool {
+        base64::decode(secret_hash).is_ok_and(|hash| self.secret_mac().verify_slice(&hash).is_ok())
+    }
+
+    /// Verifies the token by the registered verifier.
+    pub fn verify(&self) -> BoxFuture<'_, Result<ApiKeyPrincipal, Error>> {
+        match API_KEY_VERIFIER.get() {
+            Some(verifier) => verifier.verify(self),
+            None => {
+                Box::pin(async { Err(Error::new("the API key verifier has not been registered")) })
+            }
+        }
+    }
+
+    /// Registers the verifier of API keys.
+    /// It should be called before the server starts.
+    pub fn register_verifier(verifier: impl ApiKeyVerifier) {
+        let verifier: Box<dyn ApiKeyVerifier> = Box::new(verifier);
+        if API_KEY_VERIFIER.set(verifier).is_err() {
+            tracing::warn!("the API key verifier has already been registered");
+        }
+    }
+
+    /// Returns the MAC of the secret.
+    fn secret_mac(&self) -> Hmac<Digest> {
+        let mut mac = <Hmac<Digest> as KeyInit>::new_from_slice(SECRET_KEY.as_ref())
+            .expect("HMAC can take key of any size");
+        Update::update(&mut mac, self.key_id.as_bytes());
+        Update::update(&mut mac, self.secret.as_bytes());
+        mac
+    }
+}
+
+impl fmt::Display for ApiKeyToken {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{TOKEN_PREFIX}{}_{}", self.key_id, self.secret)
+    }
+}
+
+/// The owner and scopes resolved from an API key,
+/// which can be used as request-scoped data for authorization.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiKeyPrincipal {
+    /// Key ID.
+    key_id: String,
+    /// Type of the owner, i.e. `user` or `application`.
+    owner_type: String,
+    /// Owner ID.
+    owner_id: String,
+    /// Scopes.
+    scopes: Vec<String>,
+}
+
+impl ApiKeyPrincipal {
+    /// Creates a new instance.
+    #[inline]
+    pub fn new(
+        key_id: impl Into<String>,
+        owner_type: impl Into<String>,
+        owner_id: impl Into<String>,
+        scopes: Vec<String>,
+    ) -> Self {
+        Self {
+            key_id: key_id.into(),
+            owner_type: owner_type.into(),
+            owner_id: owner_id.into(),
+            scopes,
+        }
+    }
+
+    /// Returns the key ID.
+    #[inline]
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Returns the type of the owner.
+    #[inline]
+    pub fn owner_type(&self) -> &str {
+        &self.owner_type
+    }
+
+    /// Returns the owner ID.
+    #[inline]
+    pub fn owner_id(&self) -> &str {
+        &self.owner_id
+    }
+
+    /// Returns the scopes.
+    #[inline]
+    pub fn scopes(&self) -> &[String] {
+        &self.scopes
+    }
+
+    /// Returns `true` if the principal has the scope.
+    /// The wildcard scope `*` matches any scopes.
+    #[inline]
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope || s == "*")
+    }
+}
+
+/// A verifier of API keys, which resolves the principal from the token.
+pub trait ApiKeyVerifier: Send + Sync + 'static {
+    /// Verifies the token and returns the principal.
+    fn verify<'a>(
+        &'a self,
+        token: &'a ApiKeyToken,
+    ) -> BoxFuture<'a, Result<ApiKeyPrincipal, Error>>;
+}
+
+/// An error which can be returned when parsing an API key token.
+#[derive(Debug)]
+pub enum ParseApiKeyError {
+    /// An error indicating that the token has an invalid format.
+    InvalidFormat,
+}
+
+impl fmt::Display for ParseApiKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidFormat => write!(f, "invalid format, expected `zino_<id>_<secret>`"),
+        }
+    }
+}
+
+impl error::Error for ParseApiKeyError {}
+
+/// Generates a random alphanumeric string.
+fn random_string(length: usize) -> String {
+    let mut rng = rand::rng();
+    iter::repeat(())
+        .map(|_| rng.sample(Alphanumeric))
+        .map(char::from)
+        .take(length)
+        .collect()
+}
+
+/// Prefix of the token.
+const TOKEN_PREFIX: &str = "zino_";
+
+/// Registered verifier of API keys.
+static API_KEY_VERIFIER: OnceLock<Box<dyn ApiKeyVerifier>> = OnceLock::new();
+
+/// Secret key for hashing the secrets.
+static SECRET_KEY: LazyLock<[u8; 64]> =
+    LazyLock::new(|| crypto::derive_key("ZINO:API-KEY", Agent::secret_key()));
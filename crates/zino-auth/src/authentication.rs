@@ -6,6 +6,19 @@ use hmac::{
 use std::time::Duration;
 use zino_core::{datetime::DateTime, encoding::base64, error::Error, validation::Validation, Map};
 
+#[cfg(feature = "http-client")]
+use hmac::Hmac;
+#[cfg(feature = "http-client")]
+use md5::{Digest as _, Md5};
+#[cfg(feature = "http-client")]
+use reqwest_middleware::RequestBuilder;
+#[cfg(feature = "http-client")]
+use zino_core::{
+    application::{Agent, Application},
+    crypto::Digest,
+    warn,
+};
+
 /// HTTP signature using HMAC.
 pub struct Authentication {
     /// Service name.
@@ -100,20 +113,22 @@ impl Authentication {
     }
 
     /// Sets the canonicalized headers.
-    /// The header is matched if it has a prefix in the filter list.
+    /// The header is matched case-insensitively if it has a prefix in the filter list,
+    /// and the matched headers are sorted by the lowercase names.
+    /// The values of the same header are kept in the original order.
     #[inline]
     pub fn set_headers(
         &mut self,
         headers: impl Iterator<Item = (String, String)>,
-        filter: &[&'static str],
+        filter: &[&str],
     ) {
         let mut headers = headers
             .filter_map(|(name, values)| {
-                let key = name.as_str();
+                let key = name.to_ascii_lowercase();
                 filter
                     .iter()
-                    .any(|&s| key.starts_with(s))
-                    .then(|| (key.to_ascii_lowercase(), values.clone()))
+                    .any(|s| key.starts_with(&s.to_ascii_lowercase()))
+                    .then_some((key, values))
             })
             .collect::<Vec<_>>();
         headers.sort_by(|a, b| a.0.cmp(&b.0));
@@ -270,4 +285,101 @@ impl Authentication {
         }
         validation
     }
+
+    /// Signs an outgoing request with the access key, so that it can be verified by
+    /// [`validate_with()`](Self::validate_with) on the server side.
+    ///
+    /// The service name is the uppercase application name, and no headers are canonicalized.
+    /// See [`sign_request_with()`](Self::sign_request_with) for the details.
+    #[cfg(feature = "http-client")]
+    #[inline]
+    pub fn sign_request(
+        request_builder: &mut RequestBuilder,
+        access_key_id: &AccessKeyId,
+        secret_access_key: &SecretAccessKey,
+    ) -> Result<(), Error> {
+        Self::sign_request_with(
+            request_builder,
+            Agent::name(),
+            &[],
+            access_key_id,
+            secret_access_key,
+        )
+    }
+
+    /// Signs an outgoing request with the service name, the prefixes of the headers
+    /// to be canonicalized, and the access key.
+    ///
+    /// The `date` header is set to the current time if absent, which should be
+    /// within 15 minutes of the server time. The `content-md5` header is set
+    /// if absent and the body is nonempty. The canonicalized resource is the request path
+    /// without the query, which is the same as the server-side verification.
+    /// Finally, the `authorization` header is set as `{service_name} {access_key_id}:{signature}`.
+    ///
+    /// It fails if the request body is a stream which can not be read in advance.
+    #[cfg(feature = "http-client")]
+    pub fn sign_request_with(
+        request_builder: &mut RequestBuilder,
+        service_name: &str,
+        header_prefixes: &[&str],
+        access_key_id: &AccessKeyId,
+        secret_access_key: &SecretAccessKey,
+    ) -> Result<(), Error> {
+        let request = request_builder
+            .try_clone()
+            .ok_or_else(|| warn!("fail to sign the request with a streaming body"))?
+            .build()?;
+        let headers = request.headers();
+        let get_header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|s| s.to_owned())
+        };
+        let mut new_headers = Vec::new();
+
+        let mut authentication = Self::new(request.method().as_str());
+        authentication.set_service_name(service_name);
+        authentication.set_access_key_id(access_key_id.clone());
+        if let Some(content_md5) = get_header("content-md5") {
+            authentication.set_content_md5(content_md5);
+        } else if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+            if !body.is_empty() {
+                let content_md5 = base64::encode(Md5::digest(body));
+                authentication.set_content_md5(content_md5.clone());
+                new_headers.push(("content-md5", content_md5));
+            }
+        }
+        authentication.set_content_type(get_header("content-type"));
+        if let Some(date) = get_header("date") {
+            let date = DateTime::parse_utc_str(&date)?;
+            authentication.set_date_header("date", date);
+        } else {
+            let date = DateTime::now();
+            authentication.set_date_header("date", date);
+            new_headers.push(("date", date.to_utc_string()));
+        }
+        if !header_prefixes.is_empty() {
+            let headers = headers.iter().filter_map(|(name, value)| {
+                let value = value.to_str().ok()?;
+                Some((name.as_str().to_owned(), value.to_owned()))
+            });
+            authentication.set_headers(headers, header_prefixes);
+        }
+        authentication.set_resource(request.url().path().to_owned(), None);
+
+        let signature = authentication.sign_with::<Hmac<Digest>>(secret_access_key)?;
+        authentication.set_signature(signature);
+        new_headers.push(("authorization", authentication.authorization()));
+
+        let mut signed_request_builder = request_builder
+            .try_clone()
+            .ok_or_else(|| warn!("fail to sign the request with a streaming body"))?;
+        for (name, value) in new_headers {
+            signed_request_builder = signed_request_builder.header(name, value);
+        }
+        *request_builder = signed_request_builder;
+        Ok(())
+    }
 }
+
@@ -1,6 +1,6 @@
-use super::AuthorizationProvider;
+use super::{AccessToken, AuthorizationProvider, Scopes};
 use parking_lot::RwLock;
-use std::{marker::PhantomData, time::Duration};
+use std::{marker::PhantomData, str::FromStr, time::Duration};
 use toml::Table;
 use zino_core::{
     datetime::DateTime,
@@ -9,7 +9,32 @@ use zino_core::{
     warn, Map, SharedString,
 };
 
+#[cfg(feature = "http-client")]
+use reqwest::Response;
+#[cfg(feature = "http-client")]
+use reqwest_middleware::RequestBuilder;
+#[cfg(feature = "http-client")]
+use std::collections::HashMap;
+#[cfg(feature = "http-client")]
+use zino_core::{application::Agent, bail, state::State, LazyLock};
+
 /// Credentials for the client authentication.
+///
+/// With the `http-client` feature enabled, an access token can be fetched from
+/// the OAuth2 token endpoint with the `client_credentials` grant type.
+/// The named credentials can be configured in the `[[client-credentials]]` tables:
+///
+/// ```toml
+/// [[client-credentials]]
+/// name = "my-upstream"
+/// token-url = "https://auth.example.com/oauth2/token"
+/// client-id = "client-id"
+/// client-secret = "client-secret"
+/// auth-method = "client_secret_basic"
+/// scopes = ["orders:read"]
+/// audience = "https://api.example.com"
+/// expiry-skew = "30s"
+/// ```
 #[derive(Debug)]
 pub struct ClientCredentials<S: ?Sized> {
     /// Client ID.
@@ -22,6 +47,21 @@ pub struct ClientCredentials<S: ?Sized> {
     access_token: RwLock<String>,
     /// Expires time.
     expires_at: RwLock<DateTime>,
+    /// Token endpoint.
+    token_url: SharedString,
+    /// Client authentication method for the token endpoint.
+    auth_method: ClientAuthMethod,
+    /// Requested scopes.
+    scopes: Scopes,
+    /// Requested audience.
+    audience: SharedString,
+    /// Skew before the expiry when the access token should be refreshed.
+    expiry_skew: Duration,
+    /// Cached access token.
+    token: RwLock<Option<AccessToken>>,
+    /// A lock to deduplicate the concurrent refreshes.
+    #[cfg(feature = "http-client")]
+    refresh_lock: tokio::sync::Mutex<()>,
     /// Phantom type of authorization server.
     phantom: PhantomData<S>,
 }
@@ -36,6 +76,14 @@ impl<S: ?Sized> ClientCredentials<S> {
             client_secret: client_secret.into(),
             access_token: RwLock::new(String::new()),
             expires_at: RwLock::new(DateTime::now()),
+            token_url: "".into(),
+            auth_method: ClientAuthMethod::default(),
+            scopes: Scopes::new(),
+            audience: "".into(),
+            expiry_skew: Duration::from_secs(30),
+            token: RwLock::new(None),
+            #[cfg(feature = "http-client")]
+            refresh_lock: tokio::sync::Mutex::new(()),
             phantom: PhantomData,
         }
     }
@@ -49,14 +97,26 @@ impl<S: ?Sized> ClientCredentials<S> {
         let client_secret = config
             .get_str("client-secret")
             .ok_or_else(|| warn!("the `client-secret` field should be specified"))?;
-        Ok(Self {
-            client_id: client_id.into(),
-            client_key: client_key.into(),
-            client_secret: client_secret.into(),
-            access_token: RwLock::new(String::new()),
-            expires_at: RwLock::new(DateTime::now()),
-            phantom: PhantomData,
-        })
+        let mut credentials = Self::new(client_id, client_secret);
+        credentials.client_key = client_key.into();
+        if let Some(token_url) = config.get_str("token-url") {
+            credentials.token_url = token_url.into();
+        }
+        if let Some(auth_method) = config.get_str("auth-method") {
+            credentials.auth_method = auth_method.parse()?;
+        }
+        if let Some(scopes) = config.get_str_array("scopes") {
+            credentials.scopes = scopes.into_iter().collect();
+        } else if let Some(scope) = config.get_str("scopes") {
+            credentials.scopes = Scopes::parse(scope);
+        }
+        if let Some(audience) = config.get_str("audience") {
+            credentials.audience = audience.into();
+        }
+        if let Some(expiry_skew) = config.get_duration("expiry-skew") {
+            credentials.expiry_skew = expiry_skew;
+        }
+        Ok(credentials)
     }
 
     /// Sets the client key.
@@ -65,6 +125,36 @@ impl<S: ?Sized> ClientCredentials<S> {
         self.client_key = client_key.into();
     }
 
+    /// Sets the token endpoint.
+    #[inline]
+    pub fn set_token_url(&mut self, token_url: impl Into<SharedString>) {
+        self.token_url = token_url.into();
+    }
+
+    /// Sets the client authentication method for the token endpoint.
+    #[inline]
+    pub fn set_auth_method(&mut self, auth_method: ClientAuthMethod) {
+        self.auth_method = auth_method;
+    }
+
+    /// Sets the requested scopes.
+    #[inline]
+    pub fn set_scopes(&mut self, scopes: Scopes) {
+        self.scopes = scopes;
+    }
+
+    /// Sets the requested audience.
+    #[inline]
+    pub fn set_audience(&mut self, audience: impl Into<SharedString>) {
+        self.audience = audience.into();
+    }
+
+    /// Sets the skew before the expiry when the access token should be refreshed.
+    #[inline]
+    pub fn set_expiry_skew(&mut self, expiry_skew: Duration) {
+        self.expiry_skew = expiry_skew;
+    }
+
     /// Sets the access token.
     #[inline]
     pub fn set_access_token(&self, access_token: impl ToString) {
@@ -95,6 +185,30 @@ impl<S: ?Sized> ClientCredentials<S> {
         self.client_secret.as_ref()
     }
 
+    /// Returns the token endpoint.
+    #[inline]
+    pub fn token_url(&self) -> &str {
+        self.token_url.as_ref()
+    }
+
+    /// Returns the client authentication method for the token endpoint.
+    #[inline]
+    pub fn auth_method(&self) -> ClientAuthMethod {
+        self.auth_method
+    }
+
+    /// Returns the requested scopes.
+    #[inline]
+    pub fn scopes(&self) -> &Scopes {
+        &self.scopes
+    }
+
+    /// Returns the requested audience.
+    #[inline]
+    pub fn audience(&self) -> &str {
+        self.audience.as_ref()
+    }
+
     /// Returns the access token regardless of whether it has expired.
     #[inline]
     pub fn access_token(&self) -> String {
@@ -148,3 +262,180 @@ impl<S: ?Sized + AuthorizationProvider> ClientCredentials<S> {
         Ok(self.access_token())
     }
 }
+
+#[cfg(feature = "http-client")]
+impl ClientCredentials<()> {
+    /// Returns the named credentials configured in the `[[client-credentials]]` tables.
+    #[inline]
+    pub fn get(name: &str) -> Option<&'static Self> {
+        NAMED_CLIENT_CREDENTIALS.get(name)
+    }
+}
+
+#[cfg(feature = "http-client")]
+impl<S: ?Sized> ClientCredentials<S> {
+    /// Fetches an access token from the token endpoint with the `client_credentials` grant type.
+    /// The cached token is returned until the expiry skew, and the concurrent refreshes
+    /// are deduplicated so that only one request is sent to the token endpoint.
+    pub async fn fetch_token(&self) -> Result<AccessToken, Error> {
+        if let Some(token) = self.cached_token() {
+            return Ok(token);
+        }
+
+        let _guard = self.refresh_lock.lock().await;
+        if let Some(token) = self.cached_token() {
+            return Ok(token);
+        }
+        self.request_token().await
+    }
+
+    /// Forces a refresh of the access token which has been rejected by the upstream.
+    /// It is a no-op if the token has been refreshed by another task.
+    pub async fn refresh_token(&self, rejected_token: &str) -> Result<AccessToken, Error> {
+        let _guard = self.refresh_lock.lock().await;
+        if let Some(token) = self
+            .cached_token()
+            .filter(|token| token.access_token() != rejected_token)
+        {
+            return Ok(token);
+        }
+        self.request_token().await
+    }
+
+    /// Constructs a request builder with the `Authorization: Bearer <token>` header.
+    pub async fn request_builder(
+        &self,
+        url: &str,
+        options: Option<&Map>,
+    ) -> Result<RequestBuilder, Error> {
+        let token = self.fetch_token().await?;
+        Ok(Agent::request_builder(url, options)?.bearer_auth(token.access_token()))
+    }
+
+    /// Makes an HTTP request with the access token, and retries exactly once
+    /// with a refreshed token if the response status is `401 Unauthorized`.
+    pub async fn send(&self, url: &str, options: Option<&Map>) -> Result<Response, Error> {
+        let token = self.fetch_token().await?;
+        let response = Agent::request_builder(url, options)?
+            .bearer_auth(token.access_token())
+            .send()
+            .await?;
+        if response.status().as_u16() != 401 {
+            return Ok(response);
+        }
+
+        let token = self.refresh_token(token.access_token()).await?;
+        Agent::request_builder(url, options)?
+            .bearer_auth(token.access_token())
+            .send()
+            .await
+            .map_err(Error::from)
+    }
+
+    /// Returns the cached access token if it will not expire within the skew.
+    fn cached_token(&self) -> Option<AccessToken> {
+        self.token
+            .read()
+            .as_ref()
+            .filter(|token| !token.expires_within(self.expiry_skew))
+            .cloned()
+    }
+
+    /// Requests an access token from the token endpoint and caches it.
+    async fn request_token(&self) -> Result<AccessToken, Error> {
+        let token_url = self.token_url();
+        if token_url.is_empty() {
+            bail!("the token URL should be specified for the client credentials");
+        }
+
+        let mut body = Map::from_entry("grant_type", "client_credentials");
+        if !self.scopes.is_empty() {
+            body.upsert("scope", self.scopes.to_string());
+        }
+        if !self.audience.is_empty() {
+            body.upsert("audience", self.audience());
+        }
+        if self.auth_method == ClientAuthMethod::ClientSecretPost {
+            body.upsert("client_id", self.client_id());
+            body.upsert("client_secret", self.client_secret());
+        }
+
+        let mut options = Map::new();
+        options.upsert("method", "POST");
+        options.upsert("data_type", "form");
+        options.upsert("body", body);
+
+        let mut request_builder = Agent::request_builder(token_url, Some(&options))?;
+        if self.auth_method == ClientAuthMethod::ClientSecretBasic {
+            request_builder =
+                request_builder.basic_auth(self.client_id(), Some(self.client_secret()));
+        }
+
+        let response = request_builder.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            bail!(
+                "fail to fetch the access token from `{}` with the status `{}`: {}",
+                token_url,
+                status,
+                body
+            );
+        }
+
+        let data = response.json::<Map>().await?;
+        let token = AccessToken::try_from_response(&data)?;
+        self.set_access_token(token.access_token());
+        self.set_expires(token.expires_in());
+        *self.token.write() = Some(token.clone());
+        Ok(token)
+    }
+}
+
+/// Client authentication method for the token endpoint.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ClientAuthMethod {
+    /// Sends the client credentials with the HTTP basic authentication.
+    #[default]
+    ClientSecretBasic,
+    /// Sends the client credentials in the request body.
+    ClientSecretPost,
+}
+
+impl FromStr for ClientAuthMethod {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "client_secret_basic" => Ok(Self::ClientSecretBasic),
+            "client_secret_post" => Ok(Self::ClientSecretPost),
+            _ => Err(warn!("unsupported client authentication method `{}`", s)),
+        }
+    }
+}
+
+/// Named client credentials.
+#[cfg(feature = "http-client")]
+static NAMED_CLIENT_CREDENTIALS: LazyLock<HashMap<&'static str, ClientCredentials<()>>> =
+    LazyLock::new(|| {
+        let mut credentials = HashMap::new();
+        if let Some(configs) = State::shared().config().get_array("client-credentials") {
+            for config in configs.iter().filter_map(|v| v.as_table()) {
+                let Some(name) = config.get_str("name") else {
+                    tracing::warn!(
+                        "the `name` field should be specified for the client credentials"
+                    );
+                    continue;
+                };
+                match ClientCredentials::try_from_config(config) {
+                    Ok(client_credentials) => {
+                        credentials.insert(name, client_credentials);
+                    }
+                    Err(err) => {
+                        tracing::error!(name, "fail to create the client credentials: {err}")
+                    }
+                }
+            }
+        }
+        credentials
+    });
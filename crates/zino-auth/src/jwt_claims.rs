@@ -1,3 +1,4 @@
+use super::Scopes;
 use jwt_simple::{
     algorithms::MACLike,
     claims::{self, Audiences, Claims, JWTClaims},
@@ -162,6 +163,25 @@ impl JwtClaims<Map> {
         self.0.custom.upsert(key.into(), value.into());
     }
 
+    /// Sets the granted scopes as the space-delimited `scope` claim.
+    #[inline]
+    pub fn set_scopes(&mut self, scopes: &Scopes) {
+        self.add_data_entry("scope", scopes.to_string());
+    }
+
+    /// Returns the granted scopes parsed from the space-delimited `scope` claim
+    /// or the `scopes` array claim.
+    pub fn scopes(&self) -> Scopes {
+        let data = &self.0.custom;
+        if let Some(scope) = data.get_str("scope") {
+            Scopes::parse(scope)
+        } else if let Some(scopes) = data.get_str_array("scopes") {
+            scopes.into_iter().collect()
+        } else {
+            Scopes::new()
+        }
+    }
+
     /// Returns the Bearer auth as a JSON object.
     pub fn bearer_auth(self) -> Result<Map, Error> {
         let mut data = Map::new();
@@ -6,17 +6,27 @@
 #![forbid(unsafe_code)]
 
 mod access_key;
+mod access_token;
+mod api_key;
 mod authentication;
 mod authorization_provider;
 mod client_credentials;
+mod remember_me;
+mod scopes;
 mod security_token;
 mod session_id;
 mod user_session;
 
 pub use access_key::{AccessKeyId, SecretAccessKey};
+pub use access_token::AccessToken;
+pub use api_key::{ApiKeyPrincipal, ApiKeyToken, ApiKeyVerifier, ParseApiKeyError};
 pub use authentication::Authentication;
 pub use authorization_provider::AuthorizationProvider;
-pub use client_credentials::ClientCredentials;
+pub use client_credentials::{ClientAuthMethod, ClientCredentials};
+pub use remember_me::{
+    ParseRememberMeError, RememberMeError, RememberMeRecord, RememberMeStore, RememberMeToken,
+};
+pub use scopes::Scopes;
 pub use security_token::{ParseSecurityTokenError, SecurityToken};
 pub use session_id::{ParseSessionIdError, SessionId};
 pub use user_session::UserSession;
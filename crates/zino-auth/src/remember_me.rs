@@ -0,0 +1,522 @@
+use hmac::{
+    digest::{KeyInit, Update},
+    Hmac, Mac,
+};
+use rand::{distr::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use std::{error, fmt, iter, mem, sync::OnceLock, time::Duration};
+use zino_core::{
+    crypto::{self, Digest},
+    datetime::DateTime,
+    encoding::base64,
+    error::Error,
+    extension::TomlTableExt,
+    state::State,
+    BoxFuture, LazyLock,
+};
+
+#[cfg(not(test))]
+use zino_core::application::{Agent, Application};
+
+/// A long-lived remember-me token in the form `<series>:<token>`,
+/// which is usually sent as a private cookie.
+///
+/// The series is fixed for a login, while the token is rotated on every use.
+/// Only the hash of the token is persisted by the [`RememberMeStore`], so that
+/// the plaintext token can not be recovered from the table. If a series is presented
+/// with a wrong token, the token must have been stolen and used by someone else,
+/// in which case the whole series is revoked and the account is flagged.
+///
+/// The token is rotated by a compare-and-swap on the previous token hash, so that
+/// concurrent requests with the same token can not be mistaken for a theft.
+/// A token which has just been rotated is still accepted within the grace period,
+/// but it will not be rotated again.
+///
+/// The lifetime and the grace period of the series can be configured
+/// by the `[remember-me]` table.
+///
+/// # Examples
+///
+/// ```toml
+/// [remember-me]
+/// lifetime = "30d"
+/// grace-period = "10s"
+/// ```
+#[derive(Debug, Clone)]
+pub struct RememberMeToken {
+    /// Series identifier.
+    series: String,
+    /// Plaintext token.
+    token: String,
+}
+
+impl RememberMeToken {
+    /// Generates a new token with a new series.
+    pub fn generate() -> Self {
+        Self {
+            series: random_string(24),
+            token: random_string(32),
+        }
+    }
+
+    /// Parses the token in the form `<series>:<token>`.
+    pub fn parse(token: &str) -> Result<Self, ParseRememberMeError> {
+        let (series, token) = token
+            .split_once(':')
+            .ok_or(ParseRememberMeError::InvalidFormat)?;
+        if series.is_empty() || token.is_empty() {
+            return Err(ParseRememberMeError::InvalidFormat);
+        }
+        Ok(Self {
+            series: series.to_owned(),
+            token: token.to_owned(),
+        })
+    }
+
+    /// Returns the series identifier.
+    #[inline]
+    pub fn series(&self) -> &str {
+        &self.series
+    }
+
+    /// Hashes the token with a key derived from the application secret.
+    pub fn hash_token(&self) -> String {
+        base64::encode(self.token_mac().finalize().into_bytes())
+    }
+
+    /// Verifies the token against the hash in constant time.
+    pub fn verify_token(&self, token_hash: &str) -> bool {
+        base64::decode(token_hash).is_ok_and(|hash| self.token_mac().verify_slice(&hash).is_ok())
+    }
+
+    /// Issues a new series for the user and stores it by the registered store.
+    pub async fn issue(user_id: &str) -> Result<Self, Error> {
+        let store = remember_me_store()?;
+        let remember_me = Self::generate();
+        let record = RememberMeRecord {
+            series: remember_me.series.clone(),
+            token_hash: remember_me.hash_token(),
+            previous_token_hash: None,
+            user_id: user_id.to_owned(),
+            expires_at: DateTime::now() + Self::lifetime(),
+            rotated_at: None,
+        };
+        store.insert(&record).await?;
+        Ok(remember_me)
+    }
+
+    /// Validates the token and rotates it in the same series.
+    /// It returns the user ID with the rotated token which should be sent to the client.
+    /// The rotated token is `None` if the token has just been rotated by a concurrent request,
+    /// in which case the client should keep the token it has received from that request.
+    pub async fn validate(&self) -> Result<(String, Option<Self>), RememberMeError> {
+        let store = remember_me_store()?;
+        let mut rotation_conflicted = false;
+        loop {
+            let Some(mut record) = store.find(&self.series).await? else {
+                return Err(RememberMeError::Invalid);
+            };
+            if record.expires_at <= DateTime::now() {
+                store.remove(&self.series).await?;
+                return Err(RememberMeError::Expired);
+            }
+            if !self.verify_token(&record.token_hash) {
+                if self.is_superseded(&record, rotation_conflicted) {
+                    return Ok((record.user_id, None));
+                }
+                tracing::warn!(
+                    user_id = record.user_id,
+                    series = self.series,
+                    "the remember-me series has been reused with a wrong token",
+                );
+                store.remove(&self.series).await?;
+                store.flag_theft(&record.user_id, &self.series).await?;
+                return Err(RememberMeError::Stolen);
+            }
+
+            let remember_me = Self {
+                series: self.series.clone(),
+                token: random_string(32),
+            };
+            let token_hash = mem::replace(&mut record.token_hash, remember_me.hash_token());
+            record.previous_token_hash = Some(token_hash.clone());
+            record.rotated_at = Some(DateTime::now());
+            if store.update(&record, &token_hash).await? {
+                return Ok((record.user_id, Some(remember_me)));
+            }
+
+            // The token has been rotated by a concurrent request in the meantime,
+            // so the record is reloaded instead of flagging a theft.
+            rotation_conflicted = true;
+        }
+    }
+
+    /// Revokes the series of the token.
+    pub async fn revoke(&self) -> Result<(), Error> {
+        remember_me_store()?.remove(&self.series).await
+    }
+
+    /// Revokes all the series of the user.
+    pub async fn revoke_all(user_id: &str) -> Result<(), Error> {
+        remember_me_store()?.remove_all(user_id).await
+    }
+
+    /// Returns the lifetime of a series.
+    #[inline]
+    pub fn lifetime() -> Duration {
+        *REMEMBER_ME_LIFETIME
+    }
+
+    /// Returns the grace period in which a rotated token is still accepted.
+    #[inline]
+    pub fn grace_period() -> Duration {
+        *REMEMBER_ME_GRACE_PERIOD
+    }
+
+    /// Registers the store of remember-me series.
+    /// It should be called before the server starts.
+    pub fn register_store(store: impl RememberMeStore) {
+        let store: Box<dyn RememberMeStore> = Box::new(store);
+        if REMEMBER_ME_STORE.set(store).is_err() {
+            tracing::warn!("the remember-me store has already been registered");
+        }
+    }
+
+    /// Returns `true` if the token has just been superseded by a rotation.
+    fn is_superseded(&self, record: &RememberMeRecord, rotation_conflicted: bool) -> bool {
+        let Some(previous_token_hash) = record.previous_token_hash.as_deref() else {
+            return false;
+        };
+        let in_grace_period = rotation_conflicted
+            || record
+                .rotated_at
+                .is_some_and(|rotated_at| rotated_at + Self::grace_period() > DateTime::now());
+        in_grace_period && self.verify_token(previous_token_hash)
+    }
+
+    /// Returns the MAC of the token.
+    fn token_mac(&self) -> Hmac<Digest> {
+        let mut mac = <Hmac<Digest> as KeyInit>::new_from_slice(SECRET_KEY.as_ref())
+            .expect("HMAC can take key of any size");
+        Update::update(&mut mac, self.series.as_bytes());
+        Update::update(&mut mac, self.token.as_bytes());
+        mac
+    }
+}
+
+impl fmt::Display for RememberMeToken {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.series, self.token)
+    }
+}
+
+/// A remember-me series persisted by the [`RememberMeStore`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RememberMeRecord {
+    /// Series identifier.
+    pub series: String,
+    /// Hash of the current token.
+    pub token_hash: String,
+    /// Hash of the token before the last rotation.
+    pub previous_token_hash: Option<String>,
+    /// User ID.
+    pub user_id: String,
+    /// Expiration time of the series.
+    pub expires_at: DateTime,
+    /// Time of the last rotation.
+    pub rotated_at: Option<DateTime>,
+}
+
+/// A store of remember-me series, which is usually backed by a table
+/// with a unique index on the `series` column.
+pub trait RememberMeStore: Send + Sync + 'static {
+    /// Finds the record by the series identifier.
+    fn find<'a>(
+        &'a self,
+        series: &'a str,
+    ) -> BoxFuture<'a, Result<Option<RememberMeRecord>, Error>>;
+
+    /// Inserts a new record.
+    fn insert<'a>(&'a self, record: &'a RememberMeRecord) -> BoxFuture<'a, Result<(), Error>>;
+
+    /// Updates the record with the same series only if its token hash is still `token_hash`.
+    /// It returns `false` if the token has been rotated by another request.
+    fn update<'a>(
+        &'a self,
+        record: &'a RememberMeRecord,
+        token_hash: &'a str,
+    ) -> BoxFuture<'a, Result<bool, Error>>;
+
+    /// Removes the record by the series identifier.
+    fn remove<'a>(&'a self, series: &'a str) -> BoxFuture<'a, Result<(), Error>>;
+
+    /// Removes all the records of the user.
+    fn remove_all<'a>(&'a self, user_id: &'a str) -> BoxFuture<'a, Result<(), Error>>;
+
+    /// Flags the account when a theft of the series has been detected.
+    /// The default implementation revokes all the series of the user.
+    fn flag_theft<'a>(
+        &'a self,
+        user_id: &'a str,
+        _series: &'a str,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        self.remove_all(user_id)
+    }
+}
+
+/// An error which can be returned when validating a remember-me token.
+#[derive(Debug)]
+pub enum RememberMeError {
+    /// The series does not exist or has been revoked.
+    Invalid,
+    /// The series has expired.
+    Expired,
+    /// The series has been reused with a wrong token.
+    Stolen,
+    /// An error occurred in the store.
+    Store(Error),
+}
+
+impl From<Error> for RememberMeError {
+    #[inline]
+    fn from(err: Error) -> Self {
+        Self::Store(err)
+    }
+}
+
+impl fmt::Display for RememberMeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Invalid => write!(f, "invalid remember-me series"),
+            Self::Expired => write!(f, "the remember-me series has expired"),
+            Self::Stolen => write!(f, "the remember-me series has been stolen"),
+            Self::Store(err) => write!(f, "fail to access the remember-me store: {err}"),
+        }
+    }
+}
+
+impl error::Error for RememberMeError {}
+
+/// An error which can be returned when parsing a remember-me token.
+#[derive(Debug)]
+pub enum ParseRememberMeError {
+    /// An error indicating that the token has an invalid format.
+    InvalidFormat,
+}
+
+impl fmt::Display for ParseRememberMeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidFormat => write!(f, "invalid format, expected `<series>:<token>`"),
+        }
+    }
+}
+
+impl error::Error for ParseRememberMeError {}
+
+/// Returns the registered store.
+fn remember_me_store() -> Result<&'static dyn RememberMeStore, Error> {
+    REMEMBER_ME_STORE
+        .get()
+        .map(|store| store.as_ref())
+        .ok_or_else(|| Error::new("the remember-me store has not been registered"))
+}
+
+/// Generates a random alphanumeric string.
+fn random_string(length: usize) -> String {
+    let mut rng = rand::rng();
+    iter::repeat(())
+        .map(|_| rng.sample(Alphanumeric))
+        .map(char::from)
+        .take(length)
+        .collect()
+}
+
+/// Registered store of remember-me series.
+static REMEMBER_ME_STORE: OnceLock<Box<dyn RememberMeStore>> = OnceLock::new();
+
+/// Lifetime of a series.
+static REMEMBER_ME_LIFETIME: LazyLock<Duration> = LazyLock::new(|| {
+    State::shared()
+        .get_config("remember-me")
+        .and_then(|config| config.get_duration("lifetime"))
+        .unwrap_or(Duration::from_secs(30 * 24 * 60 * 60))
+});
+
+/// Grace period in which a rotated token is still accepted.
+static REMEMBER_ME_GRACE_PERIOD: LazyLock<Duration> = LazyLock::new(|| {
+    State::shared()
+        .get_config("remember-me")
+        .and_then(|config| config.get_duration("grace-period"))
+        .unwrap_or(Duration::from_secs(10))
+});
+
+/// Secret key for hashing the tokens.
+static SECRET_KEY: LazyLock<[u8; 64]> = LazyLock::new(|| {
+    #[cfg(test)]
+    let secret_key = b"ZINO:REMEMBER-ME:TEST-SECRET-KEY".as_slice();
+    #[cfg(not(test))]
+    let secret_key = Agent::secret_key();
+    crypto::derive_key("ZINO:REMEMBER-ME", secret_key)
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        RememberMeError, RememberMeRecord, RememberMeStore, RememberMeToken, REMEMBER_ME_STORE,
+    };
+    use futures::executor;
+    use std::{collections::HashMap, sync::Mutex, time::Duration};
+    use zino_core::{datetime::DateTime, error::Error, BoxFuture, LazyLock};
+
+    /// In-memory records shared by the tests.
+    static RECORDS: LazyLock<Mutex<HashMap<String, RememberMeRecord>>> =
+        LazyLock::new(Mutex::default);
+
+    /// Series whose next rotation races with a concurrent request.
+    static RACING_SERIES: Mutex<Option<String>> = Mutex::new(None);
+
+    struct MemoryStore;
+
+    impl RememberMeStore for MemoryStore {
+        fn find<'a>(
+            &'a self,
+            series: &'a str,
+        ) -> BoxFuture<'a, Result<Option<RememberMeRecord>, Error>> {
+            Box::pin(async move { Ok(RECORDS.lock().unwrap().get(series).cloned()) })
+        }
+
+        fn insert<'a>(&'a self, record: &'a RememberMeRecord) -> BoxFuture<'a, Result<(), Error>> {
+            Box::pin(async move {
+                RECORDS
+                    .lock()
+                    .unwrap()
+                    .insert(record.series.clone(), record.clone());
+                Ok(())
+            })
+        }
+
+        fn update<'a>(
+            &'a self,
+            record: &'a RememberMeRecord,
+            token_hash: &'a str,
+        ) -> BoxFuture<'a, Result<bool, Error>> {
+            Box::pin(async move {
+                let mut records = RECORDS.lock().unwrap();
+                let Some(current) = records.get_mut(&record.series) else {
+                    return Ok(false);
+                };
+                let mut racing_series = RACING_SERIES.lock().unwrap();
+                if racing_series.as_ref() == Some(&record.series) {
+                    racing_series.take();
+                    let concurrent = RememberMeToken::parse(&format!("{}:racing", record.series))
+                        .map_err(Error::from_error)?;
+                    current.previous_token_hash = Some(current.token_hash.clone());
+                    current.token_hash = concurrent.hash_token();
+                    current.rotated_at = Some(DateTime::now() - Duration::from_secs(60));
+                }
+                if current.token_hash != token_hash {
+                    return Ok(false);
+                }
+                *current = record.clone();
+                Ok(true)
+            })
+        }
+
+        fn remove<'a>(&'a self, series: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+            Box::pin(async move {
+                RECORDS.lock().unwrap().remove(series);
+                Ok(())
+            })
+        }
+
+        fn remove_all<'a>(&'a self, user_id: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+            Box::pin(async move {
+                RECORDS
+                    .lock()
+                    .unwrap()
+                    .retain(|_, record| record.user_id != user_id);
+                Ok(())
+            })
+        }
+    }
+
+    fn issue(user_id: &str) -> RememberMeToken {
+        REMEMBER_ME_STORE.get_or_init(|| Box::new(MemoryStore));
+        executor::block_on(RememberMeToken::issue(user_id)).unwrap()
+    }
+
+    fn update_record(series: &str, f: impl FnOnce(&mut RememberMeRecord)) {
+        f(RECORDS.lock().unwrap().get_mut(series).unwrap());
+    }
+
+    #[test]
+    fn it_verifies_remember_me_tokens() {
+        let remember_me = RememberMeToken::generate();
+        let parsed = RememberMeToken::parse(&remember_me.to_string()).unwrap();
+        assert_eq!(parsed.series(), remember_me.series());
+        assert!(RememberMeToken::parse("series").is_err());
+        assert!(RememberMeToken::parse(":token").is_err());
+    }
+
+    #[test]
+    fn it_rotates_remember_me_tokens() {
+        let remember_me = issue("rotation");
+        let (user_id, rotated) = executor::block_on(remember_me.validate()).unwrap();
+        let rotated = rotated.unwrap();
+        assert_eq!(user_id, "rotation");
+        assert_eq!(rotated.series(), remember_me.series());
+        assert_ne!(rotated.to_string(), remember_me.to_string());
+
+        // The previous token is accepted within the grace period without being rotated.
+        let (user_id, superseded) = executor::block_on(remember_me.validate()).unwrap();
+        assert_eq!(user_id, "rotation");
+        assert!(superseded.is_none());
+
+        let (_, rotated_again) = executor::block_on(rotated.validate()).unwrap();
+        assert!(rotated_again.is_some());
+    }
+
+    #[test]
+    fn it_retries_lost_rotations() {
+        let remember_me = issue("race");
+        RACING_SERIES
+            .lock()
+            .unwrap()
+            .replace(remember_me.series().to_owned());
+        let (user_id, rotated) = executor::block_on(remember_me.validate()).unwrap();
+        assert_eq!(user_id, "race");
+        assert!(rotated.is_none());
+        assert!(RECORDS.lock().unwrap().contains_key(remember_me.series()));
+    }
+
+    #[test]
+    fn it_detects_stolen_remember_me_tokens() {
+        let remember_me = issue("theft");
+        let other = issue("theft");
+        let (_, rotated) = executor::block_on(remember_me.validate()).unwrap();
+        update_record(remember_me.series(), |record| {
+            record.rotated_at = Some(DateTime::now() - Duration::from_secs(60));
+        });
+
+        let result = executor::block_on(remember_me.validate());
+        assert!(matches!(result, Err(RememberMeError::Stolen)));
+        let result = executor::block_on(rotated.unwrap().validate());
+        assert!(matches!(result, Err(RememberMeError::Invalid)));
+        let result = executor::block_on(other.validate());
+        assert!(matches!(result, Err(RememberMeError::Invalid)));
+    }
+
+    #[test]
+    fn it_expires_remember_me_tokens() {
+        let remember_me = issue("expiry");
+        update_record(remember_me.series(), |record| {
+            record.expires_at = DateTime::now() - Duration::from_secs(1);
+        });
+
+        let result = executor::block_on(remember_me.validate());
+        assert!(matches!(result, Err(RememberMeError::Expired)));
+        assert!(!RECORDS.lock().unwrap().contains_key(remember_me.series()));
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt;
+use zino_core::{extension::TomlTableExt, state::State, LazyLock};
+
+/// A set of granted scopes, such as `orders:read` and `orders:write`.
+///
+/// A required scope is granted by the same scope, a wildcard grant such as `orders:*` or `*`,
+/// or one of the superscopes configured by `[auth.scopes] superscopes`.
+///
+/// ```toml
+/// [auth.scopes]
+/// superscopes = ["admin"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scopes(Vec<String>);
+
+impl Scopes {
+    /// Creates a new instance.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses the space-delimited scopes.
+    #[inline]
+    pub fn parse(scope: &str) -> Self {
+        scope.split_ascii_whitespace().collect()
+    }
+
+    /// Adds a scope.
+    #[inline]
+    pub fn add(&mut self, scope: impl Into<String>) {
+        let scope = scope.into();
+        if !self.0.contains(&scope) {
+            self.0.push(scope);
+        }
+    }
+
+    /// Returns an iterator over the scopes.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|s| s.as_str())
+    }
+
+    /// Returns the number of scopes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no scopes.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns `true` if the required scope is granted.
+    pub fn grants(&self, scope: &str) -> bool {
+        self.0.iter().any(|granted| {
+            granted == scope
+                || granted == "*"
+                || SUPERSCOPES.contains(&granted.as_str())
+                || granted
+                    .strip_suffix('*')
+                    .is_some_and(|prefix| prefix.ends_with(':') && scope.starts_with(prefix))
+        })
+    }
+
+    /// Returns `true` if all the required scopes are granted.
+    #[inline]
+    pub fn grants_all(&self, scopes: &[&str]) -> bool {
+        scopes.iter().all(|scope| self.grants(scope))
+    }
+
+    /// Returns the required scopes which are not granted.
+    #[inline]
+    pub fn missing<'a>(&self, scopes: &[&'a str]) -> Vec<&'a str> {
+        scopes
+            .iter()
+            .filter(|scope| !self.grants(scope))
+            .copied()
+            .collect()
+    }
+}
+
+impl fmt::Display for Scopes {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0.join(" "))
+    }
+}
+
+impl<T: Into<String>> FromIterator<T> for Scopes {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut scopes = Self::new();
+        for scope in iter {
+            scopes.add(scope);
+        }
+        scopes
+    }
+}
+
+/// Superscopes which grant all the scopes.
+static SUPERSCOPES: LazyLock<Vec<&'static str>> = LazyLock::new(|| {
+    State::shared()
+        .get_config("auth")
+        .and_then(|config| config.get_table("scopes"))
+        .and_then(|config| config.get_str_array("superscopes"))
+        .unwrap_or_default()
+});
@@ -1,19 +1,28 @@
-use crate::{middleware, AxumResponse, Extractor};
+use super::supervisor;
+use crate::{middleware, AxumResponse, Extractor, Request};
 use axum::{
     error_handling::HandleErrorLayer,
     extract::{rejection::LengthLimitError, DefaultBodyLimit},
     http::{HeaderName, HeaderValue, StatusCode},
-    middleware::from_fn,
+    middleware::{from_fn, from_fn_with_state},
+    routing::get,
     BoxError, Router,
 };
-use std::{any::Any, borrow::Cow, convert::Infallible, fs, net::SocketAddr, time::Duration};
+use futures::future::{BoxFuture, FutureExt};
+use std::{
+    convert::Infallible,
+    fs,
+    future::{Future, IntoFuture},
+    net::SocketAddr,
+    sync::Arc,
+    time::Duration,
+};
 use tokio::{net::TcpListener, runtime::Builder, signal};
 use tower::{
     timeout::{error::Elapsed, TimeoutLayer},
     ServiceBuilder,
 };
 use tower_http::{
-    catch_panic::CatchPanicLayer,
     compression::{predicate::DefaultPredicate, CompressionLayer},
     decompression::DecompressionLayer,
     services::{ServeDir, ServeFile},
@@ -21,12 +30,17 @@ use tower_http::{
 };
 use utoipa_rapidoc::RapiDoc;
 use zino_core::{
-    application::{Application, Plugin, ServerTag},
+    application::{Application, LifecycleHooks, Plugin, ServerTag},
+    error::Error,
     extension::TomlTableExt,
     schedule::AsyncScheduler,
-    LazyLock,
+    state::State,
+    LazyLock, Map,
+};
+use zino_http::{
+    response::{ErrorResponseFormat, Response},
+    static_files::StaticFiles,
 };
-use zino_http::response::Response;
 
 /// An HTTP server cluster.
 #[derive(Default)]
@@ -37,6 +51,87 @@ pub struct Cluster {
     default_routes: Vec<Router>,
     /// Tagged routes.
     tagged_routes: Vec<(ServerTag, Vec<Router>)>,
+    /// Static file services.
+    static_files: Vec<Arc<StaticFiles>>,
+    /// Lifecycle hooks.
+    lifecycle_hooks: LifecycleHooks,
+}
+
+impl Cluster {
+    /// Sets the format of the JSON body for error responses.
+    ///
+    /// The default format is problem details defined in RFC 7807.
+    #[inline]
+    pub fn with_error_format(self, format: impl ErrorResponseFormat) -> Self {
+        zino_http::response::set_error_format(format);
+        self
+    }
+
+    /// Serves the static files under the route prefix with the config `[static.{name}]`.
+    /// See [`StaticFiles`] for the supported options.
+    #[inline]
+    pub fn serve_dir(mut self, route_prefix: &str, name: &str) -> Self {
+        let static_files = StaticFiles::with_config_name(route_prefix, name);
+        self.static_files.push(Arc::new(static_files));
+        self
+    }
+
+    /// Registers a hook running after the config is loaded
+    /// but before the connection pools are established.
+    /// A failing hook aborts the startup with the error.
+    #[inline]
+    pub fn on_boot<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(&'static State<Map>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        self.lifecycle_hooks.add_boot_hook(hook);
+        self
+    }
+
+    /// Registers a hook running after the listeners have been bound.
+    #[inline]
+    pub fn on_ready<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(SocketAddr) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.lifecycle_hooks.add_ready_hook(hook);
+        self
+    }
+
+    /// Registers a hook running after the config has been reloaded on `SIGHUP`
+    /// or a change of the config file. See [`Application::reload_config()`] for the details.
+    #[inline]
+    pub fn on_config_reload<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(&'static State<Map>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        self.lifecycle_hooks.add_reload_hook(hook);
+        self
+    }
+
+    /// Registers a hook running after the servers have been stopped gracefully.
+    #[inline]
+    pub fn on_shutdown<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.lifecycle_hooks.add_shutdown_hook(hook);
+        self
+    }
+
+    /// Runs the application as a Windows service with the name.
+    ///
+    /// The stop and shutdown controls from the service control manager are mapped
+    /// onto the graceful shutdown. If the process is not started by the service
+    /// control manager, the application is run directly.
+    #[cfg(all(windows, feature = "windows-service"))]
+    pub fn run_as_service(self, service_name: &'static str) {
+        super::windows_service::run(service_name, Box::new(move || self.run()));
+    }
 }
 
 impl Application for Cluster {
@@ -69,9 +164,19 @@ impl Application for Cluster {
             .build()
             .expect("fail to build Tokio runtime for `AxumCluster`");
         let app_env = Self::env();
+        let lifecycle_hooks = Arc::new(self.lifecycle_hooks);
         runtime.block_on(async {
+            if let Err(err) = lifecycle_hooks.run_boot_hooks(Self::shared_state()).await {
+                panic!("fail to run the boot hooks: {err}");
+            }
             #[cfg(feature = "orm")]
             zino_orm::GlobalPool::connect_all().await;
+            #[cfg(feature = "orm")]
+            zino_orm::JobRunStore::init();
+            #[cfg(feature = "orm")]
+            zino_core::application::LoadShedder::set_pool_waiters_source(
+                zino_orm::GlobalPool::waiting_count,
+            );
             Self::load().await;
             app_env.load_plugins(self.custom_plugins).await;
         });
@@ -96,17 +201,51 @@ impl Application for Cluster {
             }
         }
 
+        #[cfg(feature = "orm")]
+        {
+            let mut maintenance_scheduler = zino_orm::MaintenanceScheduler::new();
+            runtime.spawn(async move {
+                loop {
+                    maintenance_scheduler.tick().await;
+                    if let Some(duration) = maintenance_scheduler.time_till_next_job() {
+                        tokio::time::sleep(duration).await;
+                    }
+                }
+            });
+        }
+
+        // Config reloading
+        let hooks = lifecycle_hooks.clone();
+        runtime.spawn(async move {
+            hooks.watch_config::<Self, _, _>(tokio::time::sleep).await;
+        });
+        #[cfg(unix)]
+        {
+            let hooks = lifecycle_hooks.clone();
+            runtime.spawn(async move {
+                match signal::unix::signal(signal::unix::SignalKind::hangup()) {
+                    Ok(mut hangup) => {
+                        while hangup.recv().await.is_some() {
+                            hooks.reload_config::<Self>().await;
+                        }
+                    }
+                    Err(err) => tracing::error!("fail to install the hangup signal handler: {err}"),
+                }
+            });
+        }
+
         runtime.block_on(async {
             let default_routes = self.default_routes;
             let tagged_routes = self.tagged_routes;
+            let file_services = self.static_files;
             let app_state = Self::shared_state();
             let app_name = Self::name();
             let app_version = Self::version();
-            let listeners = app_state.listeners();
-            let has_debug_server = listeners.iter().any(|listener| listener.0.is_debug());
-            let servers = listeners.into_iter().map(|listener| {
-                let server_tag = listener.0;
-                let addr = listener.1;
+            let listeners = app_state.server_listeners();
+            let has_debug_server = listeners.iter().any(|listener| listener.scope().is_debug());
+            let apps = listeners.into_iter().enumerate().map(|(index, listener)| {
+                let server_tag = listener.scope().clone();
+                let addr = listener.addr();
                 tracing::warn!(
                     server_tag = server_tag.as_str(),
                     app_env = app_env.as_str(),
@@ -172,11 +311,30 @@ impl Application for Cluster {
                         "Static pages `{public_route_prefix}/**` are registered for `{addr}`"
                     );
                 }
-                for route in &default_routes {
-                    app = app.merge(route.clone());
+                for files in &file_services {
+                    let route_prefix = files.route_prefix();
+                    let wildcard_route = if route_prefix == "/" {
+                        "/{*path}".to_owned()
+                    } else {
+                        format!("{route_prefix}/{{*path}}")
+                    };
+                    let files = files.clone();
+                    let handler = move |req: Request| {
+                        let files = files.clone();
+                        async move { AxumResponse::from(files.serve(req).await) }
+                    };
+                    app = app
+                        .route(route_prefix, get(handler.clone()))
+                        .route(&wildcard_route, get(handler));
+                    tracing::info!("Static files `{route_prefix}/**` are registered for `{addr}`");
+                }
+                if listener.serves_default_routes() {
+                    for route in &default_routes {
+                        app = app.merge(route.clone());
+                    }
                 }
                 for (tag, routes) in &tagged_routes {
-                    if tag == &server_tag || server_tag.is_debug() {
+                    if listener.serves_routes(tag) {
                         for route in routes {
                             app = app.merge(route.clone());
                         }
@@ -221,78 +379,128 @@ impl Application for Cluster {
                     }
                 }
 
-                app = app
-                    .fallback_service(tower::service_fn(|req| async {
-                        let req = Extractor::from(req);
-                        let res = Response::new(StatusCode::NOT_FOUND).context(&req);
-                        Ok::<AxumResponse, Infallible>(res.into())
-                    }))
-                    .layer(
-                        ServiceBuilder::new()
-                            .layer(SetResponseHeaderLayer::if_not_present(
-                                HeaderName::from_static("connection"),
-                                HeaderValue::from_static("keep-alive"),
-                            ))
-                            .layer(SetResponseHeaderLayer::if_not_present(
-                                HeaderName::from_static("keep-alive"),
-                                HeaderValue::from_str(&format!("timeout={keep_alive_timeout}"))
-                                    .expect("fail to set the `keep-alive` header value"),
-                            ))
-                            .layer(DefaultBodyLimit::max(body_limit))
-                            .layer(
-                                CompressionLayer::new()
-                                    .gzip(true)
-                                    .compress_when(DefaultPredicate::new()),
-                            )
-                            .layer(DecompressionLayer::new().gzip(true))
-                            .layer(LazyLock::force(&middleware::TRACING_MIDDLEWARE))
-                            .layer(LazyLock::force(&middleware::CORS_MIDDLEWARE))
-                            .layer(from_fn(middleware::request_context))
-                            .layer(from_fn(middleware::extract_etag))
-                            .layer(HandleErrorLayer::new(|err: BoxError| async move {
-                                let status_code = if err.is::<Elapsed>() {
-                                    StatusCode::REQUEST_TIMEOUT
-                                } else if err.is::<LengthLimitError>() {
-                                    StatusCode::PAYLOAD_TOO_LARGE
-                                } else {
-                                    StatusCode::INTERNAL_SERVER_ERROR
-                                };
-                                let res = Response::new(status_code);
-                                Ok::<AxumResponse, Infallible>(res.into())
-                            }))
-                            .layer(CatchPanicLayer::custom(
-                                |err: Box<dyn Any + Send + 'static>| {
-                                    let details = if let Some(s) = err.downcast_ref::<String>() {
-                                        Cow::Owned(s.to_owned())
-                                    } else if let Some(s) = err.downcast_ref::<&str>() {
-                                        Cow::Borrowed(*s)
-                                    } else {
-                                        Cow::Borrowed("Unknown panic message")
-                                    };
-                                    let mut res = Response::internal_server_error();
-                                    res.set_message(details);
-                                    crate::response::build_http_response(res)
-                                },
-                            ))
-                            .layer(TimeoutLayer::new(request_timeout)),
-                    );
-                Box::pin(async move {
-                    let tcp_listener = TcpListener::bind(&addr)
+                app = app.fallback_service(tower::service_fn(|req| async {
+                    let req = Extractor::from(req);
+                    let res = Response::new(StatusCode::NOT_FOUND).context(&req);
+                    Ok::<AxumResponse, Infallible>(res.into())
+                }));
+                #[cfg(feature = "singleflight")]
+                {
+                    app = app.layer(from_fn(middleware::merge_identical_requests));
+                }
+                app = app.layer(
+                    ServiceBuilder::new()
+                        .layer(SetResponseHeaderLayer::if_not_present(
+                            HeaderName::from_static("connection"),
+                            HeaderValue::from_static("keep-alive"),
+                        ))
+                        .layer(SetResponseHeaderLayer::if_not_present(
+                            HeaderName::from_static("keep-alive"),
+                            HeaderValue::from_str(&format!("timeout={keep_alive_timeout}"))
+                                .expect("fail to set the `keep-alive` header value"),
+                        ))
+                        .layer(DefaultBodyLimit::max(body_limit))
+                        .layer(
+                            CompressionLayer::new()
+                                .gzip(true)
+                                .compress_when(DefaultPredicate::new()),
+                        )
+                        .layer(DecompressionLayer::new().gzip(true))
+                        .layer(from_fn_with_state(server_tag, middleware::set_server_tag))
+                        .layer(from_fn(middleware::set_security_headers))
+                        .layer(from_fn(middleware::validate_host))
+                        .layer(from_fn(middleware::shed_load))
+                        .layer(LazyLock::force(&middleware::TRACING_MIDDLEWARE))
+                        .layer(from_fn(middleware::handle_cors))
+                        .layer(from_fn(middleware::request_context))
+                        .layer(from_fn(middleware::capture_failed_requests))
+                        .layer(from_fn(middleware::catch_panic))
+                        .layer(from_fn(middleware::extract_etag))
+                        .layer(from_fn(middleware::check_maintenance))
+                        .layer(HandleErrorLayer::new(|err: BoxError| async move {
+                            let status_code = if err.is::<Elapsed>() {
+                                StatusCode::REQUEST_TIMEOUT
+                            } else if err.is::<LengthLimitError>() {
+                                StatusCode::PAYLOAD_TOO_LARGE
+                            } else {
+                                StatusCode::INTERNAL_SERVER_ERROR
+                            };
+                            let res = Response::new(status_code);
+                            Ok::<AxumResponse, Infallible>(res.into())
+                        }))
+                        .layer(TimeoutLayer::new(request_timeout)),
+                );
+                (index, listener, app)
+            });
+
+            // Binds all the listeners before serving, so that a failure aborts the boot.
+            let mut servers = Vec::<BoxFuture<'static, std::io::Result<()>>>::new();
+            let mut bound_addrs = Vec::new();
+            for (index, listener, app) in apps {
+                let addr = listener.addr();
+                bound_addrs.push(addr);
+                #[cfg(not(feature = "tls"))]
+                if listener.tls().is_some() {
+                    panic!("fail to listen on {addr}: the `tls` feature should be enabled");
+                }
+
+                // Prefers the socket passed by the systemd socket activation.
+                let tcp_listener = if let Some(listener) = supervisor::take_listener(index) {
+                    tracing::info!("use the activated socket #{index} for `{addr}`");
+                    listener
+                        .set_nonblocking(true)
+                        .and_then(|_| TcpListener::from_std(listener))
+                        .unwrap_or_else(|err| panic!("fail to listen on {addr}: {err}"))
+                } else {
+                    TcpListener::bind(&addr)
                         .await
-                        .unwrap_or_else(|err| panic!("fail to listen on {addr}: {err}"));
-                    axum::serve(
-                        tcp_listener,
-                        app.into_make_service_with_connect_info::<SocketAddr>(),
+                        .unwrap_or_else(|err| panic!("fail to listen on {addr}: {err}"))
+                };
+                let service = app.into_make_service_with_connect_info::<SocketAddr>();
+
+                #[cfg(feature = "tls")]
+                if let Some((cert_file, key_file)) = listener.tls() {
+                    let tls_config = axum_server::tls_rustls::RustlsConfig::from_pem_file(
+                        Self::parse_path(cert_file),
+                        Self::parse_path(key_file),
                     )
-                    .with_graceful_shutdown(Self::shutdown())
                     .await
-                })
-            });
+                    .unwrap_or_else(|err| panic!("fail to load the TLS config for {addr}: {err}"));
+                    let tcp_listener = tcp_listener
+                        .into_std()
+                        .unwrap_or_else(|err| panic!("fail to listen on {addr}: {err}"));
+                    let handle = axum_server::Handle::new();
+                    let shutdown_handle = handle.clone();
+                    tokio::spawn(async move {
+                        Self::shutdown().await;
+                        shutdown_handle.graceful_shutdown(None);
+                    });
+                    let server = axum_server::from_tcp_rustls(tcp_listener, tls_config)
+                        .handle(handle)
+                        .serve(service);
+                    servers.push(server.boxed());
+                    continue;
+                }
+
+                let server = axum::serve(tcp_listener, service)
+                    .with_graceful_shutdown(Self::shutdown())
+                    .into_future();
+                servers.push(server.boxed());
+            }
+            for addr in bound_addrs {
+                lifecycle_hooks.run_ready_hooks(addr).await;
+            }
+            supervisor::notify_ready();
             for result in futures::future::join_all(servers).await {
                 if let Err(err) = result {
                     tracing::error!("axum server error: {err}");
                 }
             }
+            lifecycle_hooks.run_shutdown_hooks().await;
+
+            // Flushes the pending spans after the servers have been stopped
+            #[cfg(feature = "otel")]
+            zino_core::application::shutdown_tracer_provider();
         });
     }
 
@@ -315,10 +523,17 @@ impl Application for Cluster {
         };
         #[cfg(not(unix))]
         let terminate = std::future::pending::<()>();
+        let requested = async {
+            supervisor::shutdown_requested().await;
+            #[cfg(feature = "orm")]
+            zino_orm::GlobalPool::close_all().await;
+        };
         tokio::select! {
             _ = ctrl_c => {},
             _ = terminate => {},
+            _ = requested => {},
         };
+        supervisor::notify_stopping();
         tracing::warn!("signal received, starting graceful shutdown");
     }
 }
@@ -1,3 +1,7 @@
 mod cluster;
+mod supervisor;
+
+#[cfg(all(windows, feature = "windows-service"))]
+mod windows_service;
 
 pub use cluster::Cluster;
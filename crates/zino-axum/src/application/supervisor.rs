@@ -0,0 +1,84 @@
+use std::{
+    net::TcpListener,
+    sync::atomic::{AtomicBool, Ordering::Relaxed},
+};
+use tokio::sync::watch;
+use zino_core::LazyLock;
+
+/// Notifies the supervisor that the service has started up
+/// and spawns the watchdog heartbeats if they are enabled.
+/// It is a no-op when the process is not running under systemd.
+pub(super) fn notify_ready() {
+    #[cfg(all(unix, feature = "systemd"))]
+    {
+        use sd_notify::NotifyState;
+
+        if let Err(err) = sd_notify::notify(false, &[NotifyState::Ready]) {
+            tracing::warn!("fail to notify systemd of the readiness: {err}");
+        }
+
+        let mut usec = 0;
+        if sd_notify::watchdog_enabled(false, &mut usec) && usec > 0 {
+            // Sends the heartbeats at half of the watchdog timeout.
+            let interval = std::time::Duration::from_micros(usec / 2);
+            tokio::spawn(async move {
+                loop {
+                    if let Err(err) = sd_notify::notify(false, &[NotifyState::Watchdog]) {
+                        tracing::warn!("fail to send the watchdog heartbeat: {err}");
+                    }
+                    tokio::time::sleep(interval).await;
+                }
+            });
+            tracing::info!("systemd watchdog is enabled with an interval of {interval:?}");
+        }
+    }
+}
+
+/// Notifies the supervisor that the service is beginning its shutdown.
+/// The notification is sent at most once.
+pub(super) fn notify_stopping() {
+    if !STOPPING.swap(true, Relaxed) {
+        #[cfg(all(unix, feature = "systemd"))]
+        if let Err(err) = sd_notify::notify(false, &[sd_notify::NotifyState::Stopping]) {
+            tracing::warn!("fail to notify systemd of the shutdown: {err}");
+        }
+    }
+}
+
+/// Takes the listener passed by the socket activation (`LISTEN_FDS`) for the index.
+pub(super) fn take_listener(_index: usize) -> Option<TcpListener> {
+    #[cfg(all(unix, feature = "systemd"))]
+    {
+        let mut listen_fds = LISTEN_FDS.lock().unwrap_or_else(|err| err.into_inner());
+        match listen_fds.take_tcp_listener(_index) {
+            Ok(listener) => return listener,
+            Err(err) => tracing::warn!("fail to take the activated socket #{_index}: {err}"),
+        }
+    }
+    None
+}
+
+/// Requests a graceful shutdown of the servers.
+#[cfg(all(windows, feature = "windows-service"))]
+pub(super) fn request_shutdown() {
+    SHUTDOWN_SIGNAL.send_replace(true);
+}
+
+/// Waits until a graceful shutdown is requested by [`request_shutdown()`].
+pub(super) async fn shutdown_requested() {
+    let mut receiver = SHUTDOWN_SIGNAL.subscribe();
+    if receiver.wait_for(|requested| *requested).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Sender of the shutdown signal.
+static SHUTDOWN_SIGNAL: LazyLock<watch::Sender<bool>> = LazyLock::new(|| watch::Sender::new(false));
+
+/// A flag to indicate whether the stopping notification has been sent.
+static STOPPING: AtomicBool = AtomicBool::new(false);
+
+/// File descriptors passed by the socket activation.
+#[cfg(all(unix, feature = "systemd"))]
+static LISTEN_FDS: LazyLock<std::sync::Mutex<listenfd::ListenFd>> =
+    LazyLock::new(|| std::sync::Mutex::new(listenfd::ListenFd::from_env()));
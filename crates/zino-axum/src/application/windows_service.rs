@@ -0,0 +1,105 @@
+use std::{
+    ffi::OsString,
+    sync::{Mutex, OnceLock},
+    time::Duration,
+};
+use windows_service::{
+    define_windows_service,
+    service::{
+        ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceState, ServiceStatus,
+        ServiceType,
+    },
+    service_control_handler::{self, ServiceControlHandlerResult, ServiceStatusHandle},
+    service_dispatcher,
+};
+
+/// Entry point of the service.
+type ServiceEntry = Box<dyn FnOnce() + Send>;
+
+define_windows_service!(ffi_service_main, service_main);
+
+/// Runs the entry as a Windows service with the name.
+/// It runs the entry directly if the process is not started by the service control manager.
+pub(super) fn run(service_name: &'static str, entry: ServiceEntry) {
+    SERVICE_NAME.get_or_init(|| service_name);
+    *SERVICE_ENTRY.lock().unwrap_or_else(|err| err.into_inner()) = Some(entry);
+    if let Err(err) = service_dispatcher::start(service_name, ffi_service_main) {
+        tracing::info!("fail to connect to the service control manager: {err}");
+        if let Some(entry) = take_entry() {
+            entry();
+        }
+    }
+}
+
+/// Runs the service after the dispatcher has connected to the service control manager.
+fn service_main(_arguments: Vec<OsString>) {
+    let Some(entry) = take_entry() else {
+        return;
+    };
+    let service_name = SERVICE_NAME.get().copied().unwrap_or_default();
+    let event_handler = |control| match control {
+        ServiceControl::Stop | ServiceControl::Shutdown => {
+            if let Some(status_handle) = STATUS_HANDLE.get() {
+                set_status(status_handle, ServiceState::StopPending);
+            }
+            super::supervisor::request_shutdown();
+            ServiceControlHandlerResult::NoError
+        }
+        ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+        _ => ServiceControlHandlerResult::NotImplemented,
+    };
+    let status_handle = match service_control_handler::register(service_name, event_handler) {
+        Ok(status_handle) => status_handle,
+        Err(err) => {
+            tracing::error!("fail to register the service control handler: {err}");
+            return;
+        }
+    };
+    let status_handle = STATUS_HANDLE.get_or_init(|| status_handle);
+    set_status(status_handle, ServiceState::Running);
+    entry();
+    set_status(status_handle, ServiceState::Stopped);
+}
+
+/// Takes the entry point of the service.
+fn take_entry() -> Option<ServiceEntry> {
+    SERVICE_ENTRY
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .take()
+}
+
+/// Reports the service state to the service control manager.
+fn set_status(status_handle: &ServiceStatusHandle, state: ServiceState) {
+    let controls_accepted = if state == ServiceState::Running {
+        ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN
+    } else {
+        ServiceControlAccept::empty()
+    };
+    let wait_hint = if state == ServiceState::StopPending {
+        Duration::from_secs(30)
+    } else {
+        Duration::default()
+    };
+    let status = ServiceStatus {
+        service_type: ServiceType::OWN_PROCESS,
+        current_state: state,
+        controls_accepted,
+        exit_code: ServiceExitCode::Win32(0),
+        checkpoint: 0,
+        wait_hint,
+        process_id: None,
+    };
+    if let Err(err) = status_handle.set_service_status(status) {
+        tracing::error!("fail to set the service status: {err}");
+    }
+}
+
+/// Name of the service.
+static SERVICE_NAME: OnceLock<&'static str> = OnceLock::new();
+
+/// Entry point of the service.
+static SERVICE_ENTRY: Mutex<Option<ServiceEntry>> = Mutex::new(None);
+
+/// Status handle of the service.
+static STATUS_HANDLE: OnceLock<ServiceStatusHandle> = OnceLock::new();
@@ -0,0 +1,65 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::MatchedPath,
+    http::{header::CONTENT_LENGTH, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use zino_core::application::Agent;
+use zino_http::request::{CapturedRequest, Context};
+
+/// Captures the requests with `5xx` responses for the replay in tests.
+/// The request body is buffered only if its length is known and within the limit.
+pub(crate) async fn capture_failed_requests(req: Request<Body>, next: Next) -> Response {
+    if !CapturedRequest::is_enabled() {
+        return next.run(req).await;
+    }
+
+    let (parts, body) = req.into_parts();
+    let max_body_size = CapturedRequest::max_body_size();
+    let buffered = parts
+        .headers
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok()?.parse::<usize>().ok())
+        .is_some_and(|length| length <= max_body_size);
+    let (body, bytes) = if buffered {
+        match to_bytes(body, max_body_size).await {
+            Ok(bytes) => (Body::from(bytes.clone()), Some(bytes)),
+            Err(err) => {
+                tracing::warn!("fail to buffer the request body: {err}");
+                return StatusCode::BAD_REQUEST.into_response();
+            }
+        }
+    } else {
+        (body, None)
+    };
+
+    let mut captured = CapturedRequest::new(
+        parts.method.as_str(),
+        &parts.uri.to_string(),
+        &parts.headers,
+    );
+    if let Some(route) = parts.extensions.get::<MatchedPath>() {
+        captured.set_route(route.as_str());
+    }
+    if let Some(ctx) = parts.extensions.get::<Context>() {
+        captured.set_context(ctx);
+    }
+
+    let path = parts.uri.path().to_owned();
+    let res = next.run(Request::from_parts(parts, body)).await;
+    let status_code = res.status().as_u16();
+    if CapturedRequest::should_capture(&path, status_code, captured.request_id()) {
+        captured.set_status_code(status_code);
+        match bytes {
+            Some(bytes) => captured.set_body(&bytes),
+            None => captured.set_body_truncated(true),
+        }
+        Agent::spawn_traced(async move {
+            if let Err(err) = captured.save().await {
+                tracing::error!("fail to save the captured request: {err}");
+            }
+        });
+    }
+    res
+}
@@ -1,6 +1,6 @@
 use axum::{http, middleware::Next, response::Response};
 use tracing::Span;
-use zino_http::request::RequestContext;
+use zino_http::request::{Context, RequestContext};
 
 pub(crate) async fn request_context(req: crate::Request, next: Next) -> Response {
     let new_context = req.get_context().is_none().then(|| req.new_context());
@@ -10,5 +10,15 @@ pub(crate) async fn request_context(req: crate::Request, next: Next) -> Response
         Span::current().record("context.request_id", ctx.request_id().to_string());
         req.extensions_mut().insert(ctx);
     }
-    next.run(req).await
+
+    // Only the identifiers are captured for the tasks spawned in the request flow.
+    let task_context = req
+        .extensions()
+        .get::<Context>()
+        .map(|ctx| ctx.task_context());
+    if let Some(ctx) = task_context {
+        ctx.scope(next.run(req)).await
+    } else {
+        next.run(req).await
+    }
 }
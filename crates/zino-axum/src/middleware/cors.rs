@@ -1,62 +1,56 @@
-use std::time::Duration;
-use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer, ExposeHeaders};
-use zino_core::{application::Application, extension::TomlTableExt, LazyLock};
+use axum::{
+    body::Body,
+    http::{
+        header::{HeaderName, HeaderValue},
+        HeaderMap, Request, StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use zino_core::{application::ServerTag, state::Data};
+use zino_http::cors::{self, CorsPolicy};
 
-/// CORS middleware.
-pub(crate) static CORS_MIDDLEWARE: LazyLock<CorsLayer> = LazyLock::new(|| {
-    if let Some(cors) = crate::Cluster::config().get_table("cors") {
-        let allow_credentials = cors.get_bool("allow-credentials").unwrap_or(false);
-        let allow_origin = cors
-            .get_array("allow-origin")
-            .map(|values| {
-                let origins = values
-                    .iter()
-                    .filter_map(|v| v.as_str().and_then(|s| s.parse().ok()))
-                    .collect::<Vec<_>>();
-                AllowOrigin::list(origins)
-            })
-            .unwrap_or_else(AllowOrigin::mirror_request);
-        let allow_methods = cors
-            .get_array("allow-methods")
-            .map(|values| {
-                let methods = values
-                    .iter()
-                    .filter_map(|v| v.as_str().and_then(|s| s.parse().ok()))
-                    .collect::<Vec<_>>();
-                AllowMethods::list(methods)
-            })
-            .unwrap_or_else(AllowMethods::mirror_request);
-        let allow_headers = cors
-            .get_array("allow-headers")
-            .map(|values| {
-                let header_names = values
-                    .iter()
-                    .filter_map(|v| v.as_str().and_then(|s| s.parse().ok()))
-                    .collect::<Vec<_>>();
-                AllowHeaders::list(header_names)
-            })
-            .unwrap_or_else(AllowHeaders::mirror_request);
-        let expose_headers = cors
-            .get_array("expose-headers")
-            .map(|values| {
-                let header_names = values
-                    .iter()
-                    .filter_map(|v| v.as_str().and_then(|s| s.parse().ok()))
-                    .collect::<Vec<_>>();
-                ExposeHeaders::list(header_names)
-            })
-            .unwrap_or_else(ExposeHeaders::any);
-        let max_age = cors
-            .get_duration("max-age")
-            .unwrap_or_else(|| Duration::from_secs(60 * 60));
-        CorsLayer::new()
-            .allow_credentials(allow_credentials)
-            .allow_origin(allow_origin)
-            .allow_methods(allow_methods)
-            .allow_headers(allow_headers)
-            .expose_headers(expose_headers)
-            .max_age(max_age)
-    } else {
-        CorsLayer::permissive()
+/// Applies the CORS policy for the request path and the listener scope.
+///
+/// Preflight requests are responded before reaching the routes and their middlewares,
+/// and a disallowed origin gets a response without the CORS headers.
+pub(crate) async fn handle_cors(req: Request<Body>, next: Next) -> Response {
+    let headers = req.headers();
+    let Some(origin) = headers.get("origin").and_then(|v| v.to_str().ok()) else {
+        return next.run(req).await;
+    };
+
+    let server_tag = req
+        .extensions()
+        .get::<Data<ServerTag>>()
+        .map(|data| data.get())
+        .unwrap_or_default();
+    let policy = CorsPolicy::for_scope(&server_tag, req.uri().path());
+    let request_method = headers
+        .get("access-control-request-method")
+        .and_then(|v| v.to_str().ok());
+    if cors::is_preflight_request(req.method().as_str(), Some(origin), request_method) {
+        let request_headers = headers
+            .get("access-control-request-headers")
+            .and_then(|v| v.to_str().ok());
+        let cors_headers =
+            policy.preflight_headers(origin, request_method.unwrap_or_default(), request_headers);
+        let mut res = StatusCode::NO_CONTENT.into_response();
+        append_headers(res.headers_mut(), cors_headers);
+        return res;
+    }
+
+    let cors_headers = policy.response_headers(origin);
+    let mut res = next.run(req).await;
+    append_headers(res.headers_mut(), cors_headers);
+    res
+}
+
+/// Appends the CORS headers to the header map.
+fn append_headers(header_map: &mut HeaderMap, headers: Vec<(&'static str, String)>) {
+    for (name, value) in headers {
+        if let Ok(value) = HeaderValue::try_from(value) {
+            header_map.append(HeaderName::from_static(name), value);
+        }
     }
-});
+}
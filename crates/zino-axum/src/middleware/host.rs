@@ -0,0 +1,35 @@
+use crate::{AxumResponse, Extractor};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use zino_core::{application::ServerTag, state::Data};
+use zino_http::host::HostPolicy;
+
+/// Validates the `Host` header and the authority of the request target
+/// with the host policy of the listener.
+pub(crate) async fn validate_host(req: Request<Body>, next: Next) -> Response {
+    let server_tag = req
+        .extensions()
+        .get::<Data<ServerTag>>()
+        .map(|data| data.get())
+        .unwrap_or_default();
+    let policy = HostPolicy::for_scope(&server_tag);
+    let authority = req.uri().authority().map(|authority| authority.as_str());
+    let host = req
+        .headers()
+        .get("host")
+        .map(|v| v.to_str().unwrap_or_default());
+    let Err(rejection) = policy.validate(req.uri().path(), authority, host) else {
+        return next.run(req).await;
+    };
+
+    let status_code =
+        StatusCode::from_u16(rejection.status_code()).unwrap_or(StatusCode::BAD_REQUEST);
+    let req = Extractor::from(req);
+    let mut res = zino_http::response::Response::new(status_code).context(&req);
+    res.set_message(rejection.message());
+    AxumResponse::from(res).into_response()
+}
@@ -0,0 +1,23 @@
+use crate::{AxumResponse, Extractor};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use zino_core::application::LoadShedder;
+
+/// Responds with `503 Service Unavailable` for the requests which are shed
+/// by the adaptive load shedding.
+pub(crate) async fn shed_load(req: Request<Body>, next: Next) -> Response {
+    let Some(guard) = LoadShedder::admit(req.uri().path()) else {
+        let req = Extractor::from(req);
+        let res =
+            zino_http::response::Response::new(StatusCode::SERVICE_UNAVAILABLE).shed_load(&req);
+        return AxumResponse::from(res).into_response();
+    };
+
+    let res = next.run(req).await;
+    drop(guard);
+    res
+}
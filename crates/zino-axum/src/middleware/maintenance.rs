@@ -0,0 +1,20 @@
+use crate::{AxumResponse, Extractor};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use zino_core::application::MaintenanceMode;
+
+/// Responds with `503 Service Unavailable` for the routes which are not allowed
+/// in the maintenance mode.
+pub(crate) async fn check_maintenance(req: Request<Body>, next: Next) -> Response {
+    if !MaintenanceMode::is_enabled() || MaintenanceMode::is_allowed(req.uri().path()) {
+        return next.run(req).await;
+    }
+
+    let req = Extractor::from(req);
+    let res = zino_http::response::Response::new(StatusCode::SERVICE_UNAVAILABLE).maintenance(&req);
+    AxumResponse::from(res).into_response()
+}
@@ -1,11 +1,29 @@
+mod capture;
 mod context;
 mod cors;
 mod etag;
+mod host;
+mod load_shedding;
+mod maintenance;
+mod panic;
+mod scope;
+mod security;
+#[cfg(feature = "singleflight")]
+mod singleflight;
 mod static_pages;
 mod tracing;
 
+pub(crate) use self::capture::capture_failed_requests;
 pub(crate) use self::context::request_context;
-pub(crate) use self::cors::CORS_MIDDLEWARE;
+pub(crate) use self::cors::handle_cors;
 pub(crate) use self::etag::extract_etag;
+pub(crate) use self::host::validate_host;
+pub(crate) use self::load_shedding::shed_load;
+pub(crate) use self::maintenance::check_maintenance;
+pub(crate) use self::panic::catch_panic;
+pub(crate) use self::scope::set_server_tag;
+pub(crate) use self::security::set_security_headers;
+#[cfg(feature = "singleflight")]
+pub(crate) use self::singleflight::merge_identical_requests;
 pub(crate) use self::static_pages::serve_static_pages;
 pub(crate) use self::tracing::TRACING_MIDDLEWARE;
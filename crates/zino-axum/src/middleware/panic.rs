@@ -0,0 +1,98 @@
+use axum::{
+    body::Body,
+    extract::{MatchedPath, OriginalUri},
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use zino_core::Uuid;
+use zino_http::{request::Context, response::HandlerPanic};
+
+/// Catches the panics in the request handlers and converts them into `500` responses,
+/// so that the connection is not torn down and the server keeps serving the requests.
+pub(crate) async fn catch_panic(req: Request<Body>, next: Next) -> Response {
+    // Only the parts for the request context are retained since the request is consumed.
+    let method = req.method().clone();
+    let uri = req.uri().clone();
+    let extensions = req.extensions();
+    let original_uri = extensions.get::<OriginalUri>().cloned();
+    let matched_path = extensions.get::<MatchedPath>().cloned();
+    let headers = CONTEXT_HEADERS.map(|name| req.headers().get(name).cloned());
+    match HandlerPanic::catch(next.run(req)).await {
+        Ok(res) => res,
+        Err(panic) => {
+            let mut head = Request::new(Body::empty());
+            *head.method_mut() = method;
+            *head.uri_mut() = uri;
+            for (name, value) in CONTEXT_HEADERS.into_iter().zip(headers) {
+                if let Some(value) = value {
+                    head.headers_mut().insert(name, value);
+                }
+            }
+            if let Some(original_uri) = original_uri {
+                head.extensions_mut().insert(original_uri);
+            }
+            if let Some(matched_path) = matched_path {
+                head.extensions_mut().insert(matched_path);
+            }
+
+            // The request context has not been created since the middleware is the outer one.
+            let request_id = head
+                .headers()
+                .get("x-request-id")
+                .and_then(|value| value.to_str().ok()?.parse().ok())
+                .unwrap_or_else(Uuid::now_v7);
+            let mut ctx = Context::new(request_id);
+            ctx.set_instance(head.uri().path());
+            head.extensions_mut().insert(ctx);
+
+            let req = crate::Request::from(head);
+            let res = panic.into_response::<StatusCode, _>(&req);
+            crate::response::build_http_response(res)
+        }
+    }
+}
+
+/// Headers read by the request context of the response.
+const CONTEXT_HEADERS: [&str; 4] = ["x-request-id", "x-key-case", "traceparent", "tracestate"];
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+        middleware::from_fn,
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn it_converts_panics_into_responses() {
+        async fn view() -> &'static str {
+            panic!("the handler has panicked");
+        }
+
+        let request_id = "01936a4e-8c4b-7d2a-9f3e-5b6c7d8e9f0a";
+        let app = Router::new()
+            .route("/orders/{id}", get(view))
+            .route("/health", get(|| async { "ok" }))
+            .layer(from_fn(super::catch_panic));
+        let req = Request::get("/orders/1")
+            .header("x-request-id", request_id)
+            .body(Body::empty())
+            .unwrap();
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(res.headers()["x-request-id"], request_id);
+
+        let req = Request::get("/orders/2").body(Body::empty()).unwrap();
+        let res = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(res.headers().contains_key("x-request-id"));
+
+        let req = Request::get("/health").body(Body::empty()).unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
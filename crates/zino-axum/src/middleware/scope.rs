@@ -0,0 +1,12 @@
+use axum::{body::Body, extract::State, http::Request, middleware::Next, response::Response};
+use zino_core::{application::ServerTag, state::Data};
+
+/// Sets the scope of the listener which accepts the request.
+pub(crate) async fn set_server_tag(
+    State(server_tag): State<ServerTag>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Response {
+    req.extensions_mut().insert(Data::new(server_tag));
+    next.run(req).await
+}
@@ -0,0 +1,52 @@
+use axum::{
+    body::Body,
+    http::{
+        header::{HeaderName, HeaderValue},
+        HeaderMap, Request,
+    },
+    middleware::Next,
+    response::Response,
+};
+use zino_core::{application::ServerTag, state::Data};
+use zino_http::security::{CspNonce, SecurityHeaders};
+
+/// Sets the security headers configured for the request path and the listener scope.
+///
+/// A CSP nonce is generated for the request if the policy requires it,
+/// and the headers set explicitly by the handler are not overridden.
+pub(crate) async fn set_security_headers(mut req: Request<Body>, next: Next) -> Response {
+    let server_tag = req
+        .extensions()
+        .get::<Data<ServerTag>>()
+        .map(|data| data.get())
+        .unwrap_or_default();
+    let Some(preset) = SecurityHeaders::for_scope(&server_tag, req.uri().path()) else {
+        return next.run(req).await;
+    };
+
+    let nonce = preset.requires_nonce().then(CspNonce::new);
+    if let Some(nonce) = nonce.as_ref() {
+        req.extensions_mut().insert(Data::new(nonce.clone()));
+    }
+
+    let mut res = next.run(req).await;
+    let content_type = res
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok());
+    let headers = preset.response_headers(&server_tag, content_type, nonce.as_ref());
+    insert_headers(res.headers_mut(), headers);
+    res
+}
+
+/// Inserts the headers which are absent in the header map.
+fn insert_headers(header_map: &mut HeaderMap, headers: Vec<(&'static str, String)>) {
+    for (name, value) in headers {
+        let name = HeaderName::from_static(name);
+        if !header_map.contains_key(&name) {
+            if let Ok(value) = HeaderValue::try_from(value) {
+                header_map.insert(name, value);
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    http::{
+        header::{
+            ACCEPT, ACCEPT_LANGUAGE, AUTHORIZATION, CACHE_CONTROL, COOKIE, HOST,
+            PROXY_AUTHORIZATION, SET_COOKIE,
+        },
+        HeaderMap, Method, Request, StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::{sync::Arc, time::Duration};
+use zino_core::{application::Application, extension::TomlTableExt, LazyLock};
+use zino_extra::singleflight::SingleFlight;
+
+/// A buffered response of the leader request.
+#[derive(Debug)]
+struct SharedResponse {
+    /// Status code.
+    status: StatusCode,
+    /// Headers.
+    headers: HeaderMap,
+    /// Body.
+    body: Bytes,
+    /// A flag to indicate whether the response can be shared with the waiters.
+    shareable: bool,
+}
+
+impl SharedResponse {
+    /// Builds a new response.
+    fn to_response(&self) -> Response {
+        let mut res = Response::new(Body::from(self.body.clone()));
+        *res.status_mut() = self.status;
+        *res.headers_mut() = self.headers.clone();
+        res
+    }
+}
+
+/// Merges the concurrent identical `GET` requests, so that only one handler execution
+/// happens and its response is shared with the waiters.
+pub(crate) async fn merge_identical_requests(req: Request<Body>, next: Next) -> Response {
+    let Some(group) = SINGLEFLIGHT_REQUESTS.as_ref() else {
+        return next.run(req).await;
+    };
+    let Some(key) = request_key(&req) else {
+        return next.run(req).await;
+    };
+
+    let mut request = Some(req);
+    let shared_response = group
+        .run(&key, || {
+            let req = request.take();
+            let next = next.clone();
+            async move {
+                let res = next.run(req?).await;
+                let (parts, body) = res.into_parts();
+                match to_bytes(body, usize::MAX).await {
+                    Ok(body) => {
+                        let shareable = is_shareable(&parts.headers);
+                        let res = SharedResponse {
+                            status: parts.status,
+                            headers: parts.headers,
+                            body,
+                            shareable,
+                        };
+                        Some(Arc::new(res))
+                    }
+                    Err(err) => {
+                        tracing::error!("fail to buffer the response body: {err}");
+                        None
+                    }
+                }
+            }
+        })
+        .await;
+    match (shared_response, request) {
+        (Some(res), None) => res.to_response(),
+        (Some(res), Some(_)) if res.shareable => res.to_response(),
+        (_, Some(req)) => next.run(req).await,
+        (None, None) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+/// Returns the key of the request if it can be merged with the identical requests.
+/// The requests with credentials are never merged.
+fn request_key(req: &Request<Body>) -> Option<String> {
+    if req.method() != Method::GET {
+        return None;
+    }
+
+    let headers = req.headers();
+    if headers.contains_key(AUTHORIZATION)
+        || headers.contains_key(PROXY_AUTHORIZATION)
+        || headers.contains_key(COOKIE)
+    {
+        return None;
+    }
+
+    let accept = headers
+        .get(ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if accept.contains("text/event-stream") {
+        return None;
+    }
+
+    let accept_language = headers
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let host = headers
+        .get(HOST)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let path_and_query = req
+        .uri()
+        .path_and_query()
+        .map(|s| s.as_str())
+        .unwrap_or("/");
+    Some(format!(
+        "GET {host}{path_and_query}|{accept}|{accept_language}"
+    ))
+}
+
+/// Returns `true` if the response can be shared with other users.
+fn is_shareable(headers: &HeaderMap) -> bool {
+    if headers.contains_key(SET_COOKIE) {
+        return false;
+    }
+    headers
+        .get(CACHE_CONTROL)
+        .and_then(|v| v.to_str().ok())
+        .map_or(true, |s| !s.contains("private") && !s.contains("no-store"))
+}
+
+/// Group of the in-flight requests.
+static SINGLEFLIGHT_REQUESTS: LazyLock<Option<SingleFlight<Option<Arc<SharedResponse>>>>> =
+    LazyLock::new(|| {
+        let config = crate::Cluster::config().get_table("singleflight")?;
+        if config.get_bool("merge-requests") != Some(true) {
+            return None;
+        }
+
+        let timeout = config
+            .get_duration("timeout")
+            .unwrap_or_else(|| Duration::from_secs(10));
+        Some(SingleFlight::new("http-requests", timeout))
+    });
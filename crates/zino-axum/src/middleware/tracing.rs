@@ -1,5 +1,6 @@
 use axum::{
     body::{Body, Bytes},
+    extract::MatchedPath,
     http::{HeaderMap, Request, Response},
 };
 use std::time::Duration;
@@ -57,8 +58,12 @@ fn custom_make_span(request: &Request<Body>) -> Span {
     let headers = request.headers();
     let client_ip = headers.get_client_ip().map(|ip| ip.to_string());
     let user_agent = headers.get_str("user-agent");
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched_path| matched_path.as_str());
 
-    if method.is_safe() {
+    let span = if method.is_safe() {
         tracing::info_span!(
             "HTTP request",
             "otel.kind" = "server",
@@ -67,6 +72,7 @@ fn custom_make_span(request: &Request<Body>) -> Span {
             "url.scheme" = scheme,
             "url.path" = path,
             "url.query" = query,
+            "http.route" = route,
             "http.request.method" = method.as_str(),
             "http.request.header.traceparent" = Empty,
             "http.request.header.tracestate" = Empty,
@@ -83,6 +89,7 @@ fn custom_make_span(request: &Request<Body>) -> Span {
             "context.request_id" = Empty,
             "context.span_id" = Empty,
             "context.parent_id" = Empty,
+            "debug_session" = Empty,
         )
     } else {
         tracing::warn_span!(
@@ -93,6 +100,7 @@ fn custom_make_span(request: &Request<Body>) -> Span {
             "url.scheme" = scheme,
             "url.path" = path,
             "url.query" = query,
+            "http.route" = route,
             "http.request.method" = method.as_str(),
             "http.request.header.traceparent" = Empty,
             "http.request.header.tracestate" = Empty,
@@ -109,8 +117,19 @@ fn custom_make_span(request: &Request<Body>) -> Span {
             "context.request_id" = Empty,
             "context.span_id" = Empty,
             "context.parent_id" = Empty,
+            "debug_session" = Empty,
         )
+    };
+
+    // Connects the server span with the caller's trace before any child spans are created
+    #[cfg(feature = "otel")]
+    if let Some(trace_context) = headers
+        .get_str("traceparent")
+        .and_then(TraceContext::from_traceparent)
+    {
+        trace_context.link_span(&span);
     }
+    span
 }
 
 fn custom_on_request(request: &Request<Body>, span: &Span) {
@@ -2,6 +2,7 @@ use axum::{
     extract::{ConnectInfo, FromRequest, MatchedPath, OriginalUri, Request},
     http::{Method, Uri},
 };
+use futures::{Stream, StreamExt};
 use std::{
     borrow::Cow,
     convert::Infallible,
@@ -12,6 +13,11 @@ use std::{
 use zino_core::{error::Error, extension::HeaderMapExt, state::Data};
 use zino_http::request::{Context, RequestContext};
 
+#[cfg(feature = "test-utils")]
+use axum::body::Body;
+#[cfg(feature = "test-utils")]
+use zino_http::test::{MatchedRoute, MockRequest};
+
 /// An HTTP request extractor.
 pub struct Extractor<T>(T);
 
@@ -68,10 +74,13 @@ impl RequestContext for Extractor<Request> {
     #[inline]
     fn matched_route(&self) -> Cow<'_, str> {
         if let Some(path) = self.extensions().get::<MatchedPath>() {
-            path.as_str().into()
-        } else {
-            self.uri().path().into()
+            return path.as_str().into();
+        }
+        #[cfg(feature = "test-utils")]
+        if let Some(route) = self.extensions().get::<MatchedRoute>() {
+            return route.as_str().into();
         }
+        self.uri().path().into()
     }
 
     #[inline]
@@ -121,6 +130,21 @@ impl RequestContext for Extractor<Request> {
         let bytes = axum::body::to_bytes(body, usize::MAX).await?;
         Ok(bytes.to_vec())
     }
+
+    #[inline]
+    fn take_body_stream(&mut self) -> impl Stream<Item = Result<Vec<u8>, Error>> + Unpin + 'static {
+        let body = mem::take(self.body_mut());
+        body.into_data_stream()
+            .map(|result| result.map(|bytes| bytes.to_vec()).map_err(Error::from))
+    }
+}
+
+#[cfg(feature = "test-utils")]
+impl From<MockRequest> for Extractor<Request> {
+    #[inline]
+    fn from(request: MockRequest) -> Self {
+        Self(request.into_http_request().map(Body::from))
+    }
 }
 
 impl FromRequest<()> for Extractor<Request> {
@@ -6,6 +6,8 @@ use axum::{
     },
     response::IntoResponse,
 };
+use futures::StreamExt;
+use std::convert::Infallible;
 use zino_http::response::{Rejection, Response, ResponseCode};
 
 /// An HTTP response for `axum`.
@@ -46,11 +48,22 @@ impl IntoResponse for AxumRejection {
 pub(crate) fn build_http_response<S: ResponseCode>(
     mut response: Response<S>,
 ) -> axum::response::Response {
-    let mut res = match response.read_bytes() {
-        Ok(data) => axum::response::Response::builder()
+    let result = if let Some(sse_stream) = response.take_sse_stream() {
+        let stream = sse_stream
+            .into_bytes_stream(tokio::time::sleep)
+            .map(Ok::<_, Infallible>);
+        Ok(Body::from_stream(stream))
+    } else if let Some(stream) = response.take_bytes_stream() {
+        let stream = stream.map(Ok::<_, Infallible>);
+        Ok(Body::from_stream(stream))
+    } else {
+        response.read_bytes().map(Body::from)
+    };
+    let mut res = match result {
+        Ok(body) => axum::response::Response::builder()
             .status(response.status_code())
             .header(header::CONTENT_TYPE, response.content_type())
-            .body(Body::from(data))
+            .body(body)
             .unwrap_or_default(),
         Err(err) => axum::response::Response::builder()
             .status(S::INTERNAL_SERVER_ERROR.status_code())
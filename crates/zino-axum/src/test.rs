@@ -0,0 +1,134 @@
+//! Utilities for testing handlers without running a server.
+
+use crate::{AxumRejection, Request};
+use axum::{body::Body, response::IntoResponse, Router};
+use std::future::Future;
+use tower::ServiceExt;
+use zino_http::request::CapturedRequest;
+
+#[doc(no_inline)]
+pub use zino_http::test::{MockRequest, MockResponse};
+
+/// Executes the handler with the mock request and collects the response.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino::{prelude::*, test::{self, MockRequest}};
+///
+/// let req = MockRequest::get("/user/list").query(&Map::from_entry("page_size", 10));
+/// let res = test::execute(req, <User as DefaultController<i64>>::list).await;
+/// assert_eq!(res.status_code(), 200);
+/// ```
+pub async fn execute<F, Fut, T>(req: MockRequest, handler: F) -> MockResponse
+where
+    F: FnOnce(Request) -> Fut,
+    Fut: Future<Output = Result<T, AxumRejection>>,
+    T: IntoResponse,
+{
+    let res = match handler(req.into()).await {
+        Ok(res) => res.into_response(),
+        Err(rejection) => rejection.into_response(),
+    };
+    collect_response(res).await
+}
+
+/// Replays the captured request against the handler and collects the response.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino::{prelude::*, test};
+///
+/// let captured = CapturedRequest::load("./local/captures/20250101120000-0194c3a4.json")?;
+/// let res = test::replay(captured, <User as DefaultController<i64>>::update).await;
+/// assert_eq!(res.status_code(), 500);
+/// ```
+#[inline]
+pub async fn replay<F, Fut, T>(captured: CapturedRequest, handler: F) -> MockResponse
+where
+    F: FnOnce(Request) -> Fut,
+    Fut: Future<Output = Result<T, AxumRejection>>,
+    T: IntoResponse,
+{
+    execute(captured.into(), handler).await
+}
+
+/// Replays the captured request against the router in-process and collects the response.
+///
+/// # Panics
+///
+/// It will panic if the router fails to handle the request.
+pub async fn replay_router(captured: CapturedRequest, router: Router) -> MockResponse {
+    let req = MockRequest::from(captured)
+        .into_http_request()
+        .map(Body::from);
+    let res = router
+        .oneshot(req)
+        .await
+        .unwrap_or_else(|err| panic!("fail to replay the captured request: {err}"));
+    collect_response(res).await
+}
+
+/// Collects the response into a mock response.
+async fn collect_response(res: axum::response::Response) -> MockResponse {
+    let (parts, body) = res.into_parts();
+    let bytes = axum::body::to_bytes(body, usize::MAX)
+        .await
+        .unwrap_or_else(|err| panic!("fail to read the response body: {err}"));
+    MockResponse::new(parts.status.as_u16(), parts.headers, bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Request, Response, Result};
+    use http::HeaderMap;
+    use zino_core::{extension::JsonObjectExt, Map};
+    use zino_http::{
+        request::{CapturedRequest, RequestContext},
+        test::MockRequest,
+    };
+
+    async fn view(req: Request) -> Result {
+        let id = req.parse_param::<i64>("id")?;
+        let mut res = Response::default().context(&req);
+        res.set_json_data(Map::from_entry("id", id));
+        Ok(res.into())
+    }
+
+    #[tokio::test]
+    async fn it_executes_handlers() {
+        let req = MockRequest::get("/user/1/view").route("/user/{id}/view");
+        let res = super::execute(req, view).await;
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.json_data().unwrap()["id"], 1);
+
+        let req = MockRequest::get("/user/alice/view").route("/user/{id}/view");
+        let res = super::execute(req, view).await;
+        assert_eq!(res.status_code(), 400);
+        assert!(!res.is_success());
+    }
+
+    #[tokio::test]
+    async fn it_replays_captured_requests() {
+        let mut captured = CapturedRequest::new("GET", "/user/7/view", &HeaderMap::new());
+        captured.set_route("/user/{id}/view");
+        let res = super::replay(captured, view).await;
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.json_data().unwrap()["id"], 7);
+    }
+
+    #[cfg(feature = "orm")]
+    #[tokio::test]
+    #[should_panic(expected = "no database service is configured")]
+    async fn it_panics_without_test_database() {
+        async fn list(req: Request) -> Result {
+            let pool = zino_orm::GlobalPool::get("main");
+            let mut res = Response::default().context(&req);
+            res.set_json_data(Map::from_entry("pool", pool.is_some()));
+            Ok(res.into())
+        }
+
+        super::execute(MockRequest::get("/user/list"), list).await;
+    }
+}
@@ -1,3 +1,4 @@
+use super::SseEvent;
 use serde::{Deserialize, Serialize};
 use zino_core::{datetime::DateTime, JsonValue, Map, SharedString};
 
@@ -119,6 +120,15 @@ impl<T> CloudEvent<T> {
     pub fn stringify_data(&self) -> String {
         self.data.to_string()
     }
+
+    /// Converts `self` into a server-sent event with the same ID and event type.
+    #[inline]
+    pub fn into_sse(self) -> SseEvent {
+        let mut event = SseEvent::new(self.stringify_data());
+        event.set_id(self.id);
+        event.set_event(self.event_type);
+        event
+    }
 }
 
 impl<T: Serialize> CloudEvent<T> {
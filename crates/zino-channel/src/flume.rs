@@ -1,10 +1,27 @@
-use super::{CloudEvent, Subscription};
+use super::{CloudEvent, OverflowPolicy, Subscription};
 use ahash::{HashMap, HashMapExt};
+use flume::r#async::RecvStream;
 use flume::{Receiver, SendError, Sender, TrySendError};
-use futures::{Sink, Stream};
-use parking_lot::RwLock;
-use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
-use zino_core::{extension::TomlTableExt, state::State, LazyLock, Uuid};
+use futures::{
+    future::{self, Either},
+    Sink, Stream,
+};
+use futures_timer::Delay;
+use parking_lot::{Mutex, RwLock};
+use std::{
+    pin::{pin, Pin},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering::Relaxed},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use zino_core::{
+    extension::{JsonObjectExt, TomlTableExt},
+    state::State,
+    LazyLock, Map, Uuid,
+};
 
 /// A emitter is a sender of cloud events.
 type Emitter = Sender<CloudEvent>;
@@ -13,19 +30,75 @@ type Emitter = Sender<CloudEvent>;
 type Listener = Receiver<CloudEvent>;
 
 /// A subscriber of cloud events.
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 struct Subscriber {
+    /// Subscriber ID.
+    id: Uuid,
     /// Emitter.
     emitter: Emitter,
+    /// Listener to drop the oldest events.
+    listener: Listener,
     /// Filter.
     filter: Option<Subscription>,
+    /// Buffer capacity.
+    capacity: usize,
+    /// Overflow policy.
+    overflow_policy: OverflowPolicy,
+    /// Delivery stats.
+    stats: Arc<SubscriberStats>,
+}
+
+/// Delivery stats of a subscriber.
+#[derive(Debug, Default)]
+struct SubscriberStats {
+    /// Number of the dropped events.
+    dropped_count: AtomicU64,
+    /// Number of the events blocked by a full buffer.
+    blocked_count: AtomicU64,
+    /// Instant since which the buffer stays above the high-water mark.
+    high_water_since: Mutex<Option<Instant>>,
+    /// A flag to indicate whether the high-water warning has been emitted.
+    high_water_warned: AtomicBool,
+    /// A flag to indicate whether the subscriber has been disconnected as a slow consumer.
+    disconnected: AtomicBool,
+}
+
+/// Outcome of delivering an event to a subscriber.
+enum Delivery {
+    /// The event has been sent or dropped by the overflow policy.
+    Done,
+    /// The buffer is full and the event has not been sent.
+    Full(Box<CloudEvent>),
+    /// The subscriber has been disconnected.
+    Disconnected,
 }
 
 impl Subscriber {
-    /// Creates a new instance.
-    #[inline]
-    fn new(emitter: Emitter, filter: Option<Subscription>) -> Self {
-        Self { emitter, filter }
+    /// Creates a new instance with the buffer capacity and overflow policy
+    /// requested by the subscription within the limits of the `[channel]` config.
+    fn new(id: Uuid, filter: Option<Subscription>) -> (Self, Listener) {
+        let settings = &*CHANNEL_SETTINGS;
+        let capacity = filter
+            .as_ref()
+            .and_then(|sub| sub.capacity())
+            .map(|capacity| capacity.clamp(1, settings.max_capacity))
+            .unwrap_or(settings.capacity);
+        let overflow_policy = filter
+            .as_ref()
+            .and_then(|sub| sub.overflow_policy())
+            .filter(|policy| settings.overflow_policies.contains(policy))
+            .unwrap_or(settings.overflow_policy);
+        let (emitter, listener) = flume::bounded(capacity);
+        let subscriber = Self {
+            id,
+            emitter,
+            listener: listener.clone(),
+            filter,
+            capacity,
+            overflow_policy,
+            stats: Arc::new(SubscriberStats::default()),
+        };
+        (subscriber, listener)
     }
 
     /// Returns a reference to the emitter.
@@ -34,14 +107,157 @@ impl Subscriber {
         &self.emitter
     }
 
-    /// Returns a reference to the filter.
+    /// Returns `true` if all the receivers except the one kept by `self` have been dropped.
     #[inline]
-    fn filter(&self) -> Option<&Subscription> {
-        self.filter.as_ref()
+    fn is_disconnected(&self) -> bool {
+        self.emitter.receiver_count() <= 1
+    }
+
+    /// Returns `true` if the event matches the filter.
+    fn is_subscribed(&self, event: &CloudEvent) -> bool {
+        if let Some(subscription) = self.filter.as_ref() {
+            let event_session_id = event.session_id();
+            subscription
+                .source()
+                .filter(|&s| event.source() != s)
+                .is_none()
+                && subscription
+                    .topic()
+                    .filter(|&t| event.event_type() != t)
+                    .is_none()
+                && subscription
+                    .session_id()
+                    .filter(|&s| event_session_id.is_some_and(|sid| sid != s))
+                    .is_none()
+        } else {
+            true
+        }
+    }
+
+    /// Attempts to deliver the event without blocking.
+    fn try_deliver(&self, event: CloudEvent) -> Delivery {
+        let delivery = match self.emitter.try_send(event) {
+            Ok(()) => Delivery::Done,
+            Err(TrySendError::Full(event)) => match self.overflow_policy {
+                OverflowPolicy::DropOldest => {
+                    if self.listener.try_recv().is_ok() {
+                        self.record_dropped(1);
+                    }
+                    if self.emitter.try_send(event).is_err() {
+                        // The room has been taken by a concurrent sender.
+                        self.record_dropped(1);
+                    }
+                    Delivery::Done
+                }
+                OverflowPolicy::DropNewest => {
+                    self.record_dropped(1);
+                    Delivery::Done
+                }
+                OverflowPolicy::BlockWithTimeout => {
+                    self.record_blocked();
+                    Delivery::Full(Box::new(event))
+                }
+                OverflowPolicy::DisconnectSlowConsumer => {
+                    self.disconnect();
+                    Delivery::Disconnected
+                }
+            },
+            Err(TrySendError::Disconnected(_)) => Delivery::Disconnected,
+        };
+        self.check_high_water();
+        delivery
+    }
+
+    /// Delivers the event, waiting for the room until the timeout elapses
+    /// if the overflow policy is `block-with-timeout`.
+    async fn deliver(&self, event: CloudEvent) -> Delivery {
+        match self.try_deliver(event) {
+            Delivery::Full(event) => {
+                let timeout = Delay::new(CHANNEL_SETTINGS.block_timeout);
+                let send = pin!(self.emitter.send_async(*event));
+                let delivery = match future::select(send, timeout).await {
+                    Either::Left((Ok(()), _)) => Delivery::Done,
+                    Either::Left((Err(_), _)) => Delivery::Disconnected,
+                    Either::Right(_) => {
+                        self.record_dropped(1);
+                        Delivery::Done
+                    }
+                };
+                self.check_high_water();
+                delivery
+            }
+            delivery => delivery,
+        }
+    }
+
+    /// Disconnects the subscriber as a slow consumer and releases the buffered events.
+    fn disconnect(&self) {
+        self.stats.disconnected.store(true, Relaxed);
+        let num_dropped = self.listener.drain().count() as u64 + 1;
+        self.record_dropped(num_dropped);
+        tracing::warn!(
+            subscriber_id = %self.id,
+            capacity = self.capacity,
+            num_dropped,
+            "the subscriber is disconnected as a slow consumer"
+        );
+    }
+
+    /// Records the dropped events.
+    fn record_dropped(&self, num_dropped: u64) {
+        self.stats.dropped_count.fetch_add(num_dropped, Relaxed);
+        #[cfg(feature = "metrics")]
+        metrics::counter!(
+            "zino_channel_dropped_events_total",
+            "subscriber_id" => self.id.to_string(),
+            "overflow_policy" => self.overflow_policy.as_str(),
+        )
+        .increment(num_dropped);
+    }
+
+    /// Records an event blocked by a full buffer.
+    fn record_blocked(&self) {
+        self.stats.blocked_count.fetch_add(1, Relaxed);
+        #[cfg(feature = "metrics")]
+        metrics::counter!(
+            "zino_channel_blocked_events_total",
+            "subscriber_id" => self.id.to_string(),
+        )
+        .increment(1);
+    }
+
+    /// Emits a warning if the buffer stays above the high-water mark for a sustained period.
+    fn check_high_water(&self) {
+        let settings = &*CHANNEL_SETTINGS;
+        let num_buffered = self.emitter.len();
+        let high_water_mark = (self.capacity as f64 * settings.high_water_mark).ceil() as usize;
+        let mut high_water_since = self.stats.high_water_since.lock();
+        if num_buffered >= high_water_mark.max(1) {
+            let now = Instant::now();
+            let since = *high_water_since.get_or_insert(now);
+            if now.duration_since(since) >= settings.high_water_duration
+                && !self.stats.high_water_warned.swap(true, Relaxed)
+            {
+                let stats = &self.stats;
+                tracing::warn!(
+                    subscriber_id = %self.id,
+                    capacity = self.capacity,
+                    num_buffered,
+                    dropped_count = stats.dropped_count.load(Relaxed),
+                    blocked_count = stats.blocked_count.load(Relaxed),
+                    "the subscriber stays above the high-water mark"
+                );
+            }
+        } else if high_water_since.take().is_some() {
+            self.stats.high_water_warned.store(false, Relaxed);
+        }
     }
 }
 
 /// Message channel for sending and receiving cloud events.
+///
+/// Each subscriber has a bounded buffer whose capacity and overflow policy are specified by
+/// the `[channel]` config, and can be requested by the [`Subscription`] within the limits.
 #[derive(Debug, Clone)]
 pub struct MessageChannel {
     /// Sender ID.
@@ -52,30 +268,47 @@ pub struct MessageChannel {
 
 impl MessageChannel {
     /// Creates a new instance.
+    #[inline]
     pub fn new() -> Self {
-        let (sender, receiver) = flume::bounded(CHANNEL_CAPACITY.load(Relaxed));
-        let sender_id = Uuid::now_v7();
-        let subscriber = Subscriber::new(sender, None);
-        let mut subscribers = CHANNEL_SUBSCRIBERS.write();
-        subscribers.retain(|_, subscriber| !subscriber.emitter().is_disconnected());
-        subscribers.insert(sender_id, subscriber);
-        Self {
-            sender_id,
-            receiver,
-        }
+        Self::register(None).0
     }
 
     /// Creates a new instance with the subscription.
+    #[inline]
     pub fn with_subscription(sub: Subscription) -> Self {
-        let (sender, receiver) = flume::bounded(CHANNEL_CAPACITY.load(Relaxed));
+        Self::register(Some(sub)).0
+    }
+
+    /// Registers a new subscriber and returns the channel with its stats.
+    fn register(filter: Option<Subscription>) -> (Self, Arc<SubscriberStats>) {
         let sender_id = Uuid::now_v7();
-        let subscriber = Subscriber::new(sender, Some(sub));
+        let (subscriber, receiver) = Subscriber::new(sender_id, filter);
+        let stats = subscriber.stats.clone();
         let mut subscribers = CHANNEL_SUBSCRIBERS.write();
-        subscribers.retain(|_, subscriber| !subscriber.emitter().is_disconnected());
-        subscribers.insert(sender_id, subscriber);
-        Self {
+        subscribers.retain(|_, subscriber| !subscriber.is_disconnected());
+        subscribers.insert(sender_id, Arc::new(subscriber));
+        let channel = Self {
             sender_id,
             receiver,
+        };
+        (channel, stats)
+    }
+
+    /// Subscribes the cloud events with the subscription and returns a stream.
+    /// The subscriber is removed as soon as the stream is dropped,
+    /// such as when the client of a server-sent events response disconnects.
+    ///
+    /// If the subscriber is disconnected as a slow consumer, the stream yields
+    /// a final `disconnect` event with the reason before it ends,
+    /// so that the client connection can be closed with a descriptive message.
+    #[inline]
+    pub fn subscribe(sub: Subscription) -> impl Stream<Item = CloudEvent> {
+        let (channel, stats) = Self::register(Some(sub));
+        SubscriptionStream {
+            sender_id: channel.sender_id,
+            stream: channel.receiver.into_stream(),
+            stats,
+            terminated: false,
         }
     }
 
@@ -92,63 +325,46 @@ impl MessageChannel {
     }
 
     /// Attempts to send a message to all receivers in the channel except this one.
-    /// If the channel is full or all receivers have been dropped, an error is returned.
+    ///
+    /// The overflow policy of a subscriber is applied if its buffer is full.
+    /// For the `block-with-timeout` policy, the event is not delivered to the subscriber
+    /// and an error is returned after the other subscribers have been tried.
     pub fn try_send(&self, message: impl Into<CloudEvent>) -> Result<(), TrySendError<CloudEvent>> {
-        let sender_id = &self.sender_id;
         let event = message.into();
-        let source = event.source();
-        let event_type = event.event_type();
-        let event_session_id = event.session_id();
-        let subscribers = CHANNEL_SUBSCRIBERS.read();
-        for (uid, subscriber) in subscribers.iter() {
-            let emitter = subscriber.emitter();
-            if uid != sender_id && !emitter.is_disconnected() {
-                let is_subscribed = if let Some(subscription) = subscriber.filter() {
-                    subscription.source().filter(|&s| source != s).is_none()
-                        && subscription.topic().filter(|&t| event_type != t).is_none()
-                        && subscription
-                            .session_id()
-                            .filter(|&s| event_session_id.is_some_and(|sid| sid != s))
-                            .is_none()
-                } else {
-                    true
-                };
-                if is_subscribed {
-                    emitter.try_send(event.clone())?;
+        let mut full = false;
+        let mut disconnected_ids = Vec::new();
+        for subscriber in self.subscribers(&event) {
+            match subscriber.try_deliver(event.clone()) {
+                Delivery::Done => (),
+                Delivery::Full(_) => {
+                    subscriber.record_dropped(1);
+                    full = true;
                 }
+                Delivery::Disconnected => disconnected_ids.push(subscriber.id),
             }
         }
-        Ok(())
+        remove_subscribers(&disconnected_ids);
+        if full {
+            Err(TrySendError::Full(event))
+        } else {
+            Ok(())
+        }
     }
 
-    /// Asynchronously sends a message to all receivers in the channel except this one,
-    /// returning an error if all receivers have been dropped.
-    /// If the channel is full, it will yield to the async runtime.
+    /// Asynchronously sends a message to all receivers in the channel except this one.
+    ///
+    /// The overflow policy of a subscriber is applied if its buffer is full.
+    /// For the `block-with-timeout` policy, it will yield to the async runtime
+    /// until the `block-timeout` of the `[channel]` config elapses.
     pub async fn send(&self, message: impl Into<CloudEvent>) -> Result<(), SendError<CloudEvent>> {
-        let sender_id = &self.sender_id;
         let event = message.into();
-        let source = event.source();
-        let event_type = event.event_type();
-        let event_session_id = event.session_id();
-        let subscribers = CHANNEL_SUBSCRIBERS.read();
-        for (uid, subscriber) in subscribers.iter() {
-            let emitter = subscriber.emitter();
-            if uid != sender_id && !emitter.is_disconnected() {
-                let is_subscribed = if let Some(subscription) = subscriber.filter() {
-                    subscription.source().filter(|&s| source != s).is_none()
-                        && subscription.topic().filter(|&t| event_type != t).is_none()
-                        && subscription
-                            .session_id()
-                            .filter(|&s| event_session_id.is_some_and(|sid| sid != s))
-                            .is_none()
-                } else {
-                    true
-                };
-                if is_subscribed {
-                    emitter.send_async(event.clone()).await?;
-                }
+        let mut disconnected_ids = Vec::new();
+        for subscriber in self.subscribers(&event) {
+            if let Delivery::Disconnected = subscriber.deliver(event.clone()).await {
+                disconnected_ids.push(subscriber.id);
             }
         }
+        remove_subscribers(&disconnected_ids);
         Ok(())
     }
 
@@ -173,6 +389,80 @@ impl MessageChannel {
     pub fn into_stream(self) -> impl Stream<Item = CloudEvent> {
         self.receiver.into_stream()
     }
+
+    /// Returns the other subscribers of the event.
+    /// The lock is released before the delivery so that a blocked subscriber
+    /// does not prevent the others from subscribing.
+    fn subscribers(&self, event: &CloudEvent) -> Vec<Arc<Subscriber>> {
+        let sender_id = &self.sender_id;
+        CHANNEL_SUBSCRIBERS
+            .read()
+            .iter()
+            .filter(|(uid, subscriber)| {
+                *uid != sender_id
+                    && !subscriber.is_disconnected()
+                    && subscriber.is_subscribed(event)
+            })
+            .map(|(_, subscriber)| subscriber.clone())
+            .collect()
+    }
+}
+
+/// Removes the disconnected subscribers so that their senders are released.
+fn remove_subscribers(ids: &[Uuid]) {
+    if !ids.is_empty() {
+        let mut subscribers = CHANNEL_SUBSCRIBERS.write();
+        for id in ids {
+            subscribers.remove(id);
+        }
+    }
+}
+
+/// A stream of the subscribed cloud events.
+struct SubscriptionStream {
+    /// Sender ID.
+    sender_id: Uuid,
+    /// Stream of the receiver.
+    stream: RecvStream<'static, CloudEvent>,
+    /// Delivery stats of the subscriber.
+    stats: Arc<SubscriberStats>,
+    /// A flag to indicate whether the stream has been terminated.
+    terminated: bool,
+}
+
+impl Stream for SubscriptionStream {
+    type Item = CloudEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.terminated {
+            return Poll::Ready(None);
+        }
+        match Pin::new(&mut self.stream).poll_next(cx) {
+            Poll::Ready(None) => {
+                self.terminated = true;
+                if self.stats.disconnected.load(Relaxed) {
+                    let mut data = Map::new();
+                    data.upsert("reason", "slow-consumer");
+                    data.upsert("message", "the subscriber can not keep up with the events");
+                    data.upsert("dropped_count", self.stats.dropped_count.load(Relaxed));
+
+                    let mut event = CloudEvent::new(Uuid::now_v7(), self.sender_id, "disconnect");
+                    event.set_data(data);
+                    Poll::Ready(Some(event))
+                } else {
+                    Poll::Ready(None)
+                }
+            }
+            poll => poll,
+        }
+    }
+}
+
+impl Drop for SubscriptionStream {
+    #[inline]
+    fn drop(&mut self) {
+        CHANNEL_SUBSCRIBERS.write().remove(&self.sender_id);
+    }
 }
 
 impl Default for MessageChannel {
@@ -181,24 +471,85 @@ impl Default for MessageChannel {
     }
 }
 
-/// Channel capacity.
-static CHANNEL_CAPACITY: AtomicUsize = AtomicUsize::new(10000);
+/// Settings of the message channel.
+#[derive(Debug)]
+struct ChannelSettings {
+    /// Default buffer capacity of a subscriber.
+    capacity: usize,
+    /// Max buffer capacity which can be requested by a subscription.
+    max_capacity: usize,
+    /// Default overflow policy.
+    overflow_policy: OverflowPolicy,
+    /// Overflow policies which can be requested by a subscription.
+    overflow_policies: Vec<OverflowPolicy>,
+    /// Timeout for the `block-with-timeout` policy.
+    block_timeout: Duration,
+    /// Ratio of the buffer capacity as the high-water mark.
+    high_water_mark: f64,
+    /// Duration above the high-water mark after which a warning is emitted.
+    high_water_duration: Duration,
+}
+
+/// Channel settings.
+static CHANNEL_SETTINGS: LazyLock<ChannelSettings> = LazyLock::new(|| {
+    let config = State::shared().get_config("channel");
+    let capacity = config
+        .and_then(|config| config.get_usize("capacity"))
+        .filter(|&capacity| capacity > 0)
+        .unwrap_or(10000);
+    let max_capacity = config
+        .and_then(|config| config.get_usize("max-capacity"))
+        .unwrap_or(capacity)
+        .max(capacity);
+    let overflow_policy = config
+        .and_then(|config| config.get_str("overflow-policy"))
+        .and_then(|policy| {
+            policy
+                .parse()
+                .inspect_err(|err| tracing::warn!("{err}"))
+                .ok()
+        })
+        .unwrap_or_default();
+    let overflow_policies = config
+        .and_then(|config| config.get_str_array("overflow-policies"))
+        .map(|policies| {
+            policies
+                .into_iter()
+                .filter_map(|policy| policy.parse().ok())
+                .collect()
+        })
+        .unwrap_or_else(|| {
+            vec![
+                OverflowPolicy::DropOldest,
+                OverflowPolicy::DropNewest,
+                OverflowPolicy::BlockWithTimeout,
+                OverflowPolicy::DisconnectSlowConsumer,
+            ]
+        });
+    let block_timeout = config
+        .and_then(|config| config.get_duration("block-timeout"))
+        .unwrap_or_else(|| Duration::from_secs(10));
+    let high_water_mark = config
+        .and_then(|config| config.get_f64("high-water-mark"))
+        .filter(|ratio| *ratio > 0.0 && *ratio <= 1.0)
+        .unwrap_or(0.8);
+    let high_water_duration = config
+        .and_then(|config| config.get_duration("high-water-duration"))
+        .unwrap_or_else(|| Duration::from_secs(30));
+    ChannelSettings {
+        capacity,
+        max_capacity,
+        overflow_policy,
+        overflow_policies,
+        block_timeout,
+        high_water_mark,
+        high_water_duration,
+    }
+});
 
 /// Channel subscribers.
-static CHANNEL_SUBSCRIBERS: LazyLock<RwLock<HashMap<Uuid, Subscriber>>> =
+static CHANNEL_SUBSCRIBERS: LazyLock<RwLock<HashMap<Uuid, Arc<Subscriber>>>> =
     LazyLock::new(|| RwLock::new(HashMap::new()));
 
 /// Shared channel.
-static SHARED_CHANNEL: LazyLock<MessageChannel> = LazyLock::new(|| {
-    let capacity = if let Some(channel) = State::shared().config().get("channel") {
-        channel
-            .as_table()
-            .expect("the `channel` field should be a table")
-            .get_usize("capacity")
-            .expect("the `channel.capacity` field should be a positive integer")
-    } else {
-        10000
-    };
-    CHANNEL_CAPACITY.store(capacity, Relaxed);
-    MessageChannel::new()
-});
+static SHARED_CHANNEL: LazyLock<MessageChannel> = LazyLock::new(MessageChannel::new);
@@ -5,9 +5,13 @@
 #![forbid(unsafe_code)]
 
 mod cloud_event;
+mod overflow_policy;
+mod sse_event;
 mod subscription;
 
 pub use cloud_event::CloudEvent;
+pub use overflow_policy::OverflowPolicy;
+pub use sse_event::SseEvent;
 pub use subscription::Subscription;
 
 #[cfg(feature = "flume")]
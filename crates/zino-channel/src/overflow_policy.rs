@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Policy for a subscriber whose buffer of cloud events is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverflowPolicy {
+    /// Drops the oldest event in the buffer to make room for the new one.
+    DropOldest,
+    /// Drops the new event.
+    DropNewest,
+    /// Waits for the room until the timeout elapses, and then drops the new event.
+    #[default]
+    BlockWithTimeout,
+    /// Disconnects the subscriber and releases its buffer.
+    DisconnectSlowConsumer,
+}
+
+impl OverflowPolicy {
+    /// Returns the policy name.
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::DropOldest => "drop-oldest",
+            Self::DropNewest => "drop-newest",
+            Self::BlockWithTimeout => "block-with-timeout",
+            Self::DisconnectSlowConsumer => "disconnect-slow-consumer",
+        }
+    }
+}
+
+impl fmt::Display for OverflowPolicy {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OverflowPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "drop-oldest" => Ok(Self::DropOldest),
+            "drop-newest" => Ok(Self::DropNewest),
+            "block-with-timeout" => Ok(Self::BlockWithTimeout),
+            "disconnect-slow-consumer" => Ok(Self::DisconnectSlowConsumer),
+            _ => Err(format!("invalid overflow policy `{s}`")),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::{fmt, time::Duration};
+use zino_core::SharedString;
+
+/// A server-sent event.
+/// See [the spec](https://html.spec.whatwg.org/multipage/server-sent-events.html).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    /// Event ID.
+    id: Option<String>,
+    /// Event type.
+    event: Option<SharedString>,
+    /// Event data.
+    data: String,
+    /// Reconnection time.
+    retry: Option<Duration>,
+}
+
+impl SseEvent {
+    /// Creates a new instance with the data.
+    #[inline]
+    pub fn new(data: impl Into<String>) -> Self {
+        Self {
+            data: data.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the event ID.
+    #[inline]
+    pub fn set_id(&mut self, id: impl Into<String>) {
+        self.id = Some(id.into());
+    }
+
+    /// Sets the event type.
+    #[inline]
+    pub fn set_event(&mut self, event: impl Into<SharedString>) {
+        self.event = Some(event.into());
+    }
+
+    /// Sets the event data.
+    #[inline]
+    pub fn set_data(&mut self, data: impl Into<String>) {
+        self.data = data.into();
+    }
+
+    /// Sets the reconnection time.
+    #[inline]
+    pub fn set_retry(&mut self, retry: Duration) {
+        self.retry = Some(retry);
+    }
+
+    /// Returns the event ID.
+    #[inline]
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Returns the event type.
+    #[inline]
+    pub fn event(&self) -> Option<&str> {
+        self.event.as_deref()
+    }
+
+    /// Returns the event data.
+    #[inline]
+    pub fn data(&self) -> &str {
+        &self.data
+    }
+
+    /// Returns the reconnection time.
+    #[inline]
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+
+    /// Returns the comment line used to keep the connection alive.
+    #[inline]
+    pub fn keep_alive_comment() -> &'static str {
+        ":\n\n"
+    }
+}
+
+impl fmt::Display for SseEvent {
+    /// Formats the event in the wire format of `text/event-stream`.
+    /// The line breaks in the ID and the event type are removed,
+    /// and the multiline data is split into several `data` fields.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let strip_line_breaks = |s: &str| s.replace(['\r', '\n'], "");
+        if let Some(id) = self.id.as_deref() {
+            writeln!(f, "id: {}", strip_line_breaks(id))?;
+        }
+        if let Some(event) = self.event.as_deref() {
+            writeln!(f, "event: {}", strip_line_breaks(event))?;
+        }
+        if let Some(retry) = self.retry {
+            writeln!(f, "retry: {}", retry.as_millis())?;
+        }
+        for line in self.data.split('\n') {
+            writeln!(f, "data: {}", line.strip_suffix('\r').unwrap_or(line))?;
+        }
+        writeln!(f)
+    }
+}
@@ -1,3 +1,4 @@
+use super::OverflowPolicy;
 use serde::{Deserialize, Serialize};
 
 /// Subscription.
@@ -11,6 +12,10 @@ pub struct Subscription {
     source: Option<String>,
     /// Topic.
     topic: Option<String>,
+    /// Requested buffer capacity.
+    capacity: Option<usize>,
+    /// Requested overflow policy.
+    overflow_policy: Option<OverflowPolicy>,
 }
 
 impl Subscription {
@@ -21,6 +26,8 @@ impl Subscription {
             session_id: None,
             source,
             topic,
+            capacity: None,
+            overflow_policy: None,
         }
     }
 
@@ -42,6 +49,20 @@ impl Subscription {
         self.topic = topic;
     }
 
+    /// Requests the buffer capacity.
+    /// It is limited by the `max-capacity` of the `[channel]` config.
+    #[inline]
+    pub fn set_capacity(&mut self, capacity: Option<usize>) {
+        self.capacity = capacity;
+    }
+
+    /// Requests the overflow policy.
+    /// It is ignored if not allowed by the `overflow-policies` of the `[channel]` config.
+    #[inline]
+    pub fn set_overflow_policy(&mut self, overflow_policy: Option<OverflowPolicy>) {
+        self.overflow_policy = overflow_policy;
+    }
+
     /// Returns the session ID.
     #[inline]
     pub fn session_id(&self) -> Option<&str> {
@@ -59,4 +80,16 @@ impl Subscription {
     pub fn topic(&self) -> Option<&str> {
         self.topic.as_deref()
     }
+
+    /// Returns the requested buffer capacity.
+    #[inline]
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Returns the requested overflow policy.
+    #[inline]
+    pub fn overflow_policy(&self) -> Option<OverflowPolicy> {
+        self.overflow_policy
+    }
 }
@@ -1,7 +1,7 @@
 use self::ChatbotClient::*;
 use super::{ChatbotService, OpenAiChatCompletion};
 use toml::Table;
-use zino_core::{bail, error::Error, extension::TomlTableExt, Map};
+use zino_core::{bail, error::Error, extension::TomlTableExt, JsonValue, Map};
 
 /// Client for supported chatbot services.
 #[non_exhaustive]
@@ -76,4 +76,14 @@ impl ChatbotService for Chatbot {
             OpenAi(chat_completion) => chat_completion.try_send(message, options).await,
         }
     }
+
+    async fn try_complete(
+        &self,
+        messages: &[JsonValue],
+        tools: &[JsonValue],
+    ) -> Result<Map, Error> {
+        match &self.client {
+            OpenAi(chat_completion) => chat_completion.try_complete(messages, tools).await,
+        }
+    }
 }
@@ -7,16 +7,22 @@
 
 use toml::Table;
 use zino_core::{
-    application::StaticRecord, error::Error, extension::TomlTableExt, state::State, LazyLock, Map,
+    application::StaticRecord, error::Error, extension::TomlTableExt, state::State, JsonValue,
+    LazyLock, Map,
 };
 
 mod client;
+mod tool;
 
 /// Supported chatbot services.
 mod openai;
 
 pub use client::Chatbot;
+pub use tool::{ChatbotTool, ToolDispatcher};
+
 use openai::OpenAiChatCompletion;
+#[cfg(feature = "orm")]
+pub use tool::ModelTool;
 
 /// Underlying trait of all chatbot services for implementors.
 pub trait ChatbotService {
@@ -29,6 +35,11 @@ pub trait ChatbotService {
 
     /// Attempts to send a message to generate chat completions.
     async fn try_send(&self, message: String, options: Option<Map>) -> Result<Vec<String>, Error>;
+
+    /// Attempts to create a chat completion with the messages and the tool definitions,
+    /// returning the response message.
+    async fn try_complete(&self, messages: &[JsonValue], tools: &[JsonValue])
+        -> Result<Map, Error>;
 }
 
 /// Global access to the shared chatbot services.
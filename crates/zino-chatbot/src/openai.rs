@@ -3,7 +3,7 @@ use async_openai::{
     config::{Config, OpenAIConfig},
     types::{
         ChatCompletionRequestMessage, ChatCompletionRequestUserMessageArgs,
-        CreateChatCompletionRequestArgs,
+        CreateChatCompletionRequest, CreateChatCompletionRequestArgs,
     },
     Chat, Client,
 };
@@ -12,8 +12,8 @@ use toml::Table;
 use zino_core::{
     application::Agent,
     error::Error,
-    extension::{JsonObjectExt, TomlTableExt},
-    Map,
+    extension::{JsonObjectExt, JsonValueExt, TomlTableExt},
+    warn, JsonValue, Map,
 };
 
 /// OpenAI chat completion.
@@ -117,4 +117,27 @@ impl ChatbotService for OpenAiChatCompletion<OpenAIConfig> {
         }
         Ok(data)
     }
+
+    async fn try_complete(
+        &self,
+        messages: &[JsonValue],
+        tools: &[JsonValue],
+    ) -> Result<Map, Error> {
+        let mut request = Map::new();
+        request.upsert("model", self.model());
+        request.upsert("messages", messages);
+        if !tools.is_empty() {
+            request.upsert("tools", tools);
+        }
+
+        let request: CreateChatCompletionRequest = JsonValue::from(request).deserialize()?;
+        let response = self.chat().create(request).await?;
+        let Some(choice) = response.choices.into_iter().next() else {
+            return Err(warn!("the chat completion should have at least one choice"));
+        };
+        let message = serde_json::to_value(choice.message)?
+            .into_map_opt()
+            .unwrap_or_default();
+        Ok(message)
+    }
 }
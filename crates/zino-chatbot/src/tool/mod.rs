@@ -0,0 +1,203 @@
+//! Tool calling for the chatbot services.
+//!
+//! A tool is described by a function definition with the JSON Schema parameters,
+//! which can be sent along with the messages to the chat completions API.
+//! The [`ToolDispatcher`] takes the tool calls in the response message,
+//! executes the corresponding tools, and returns the tool messages for the follow-up turn.
+//!
+//! ```rust,ignore
+//! use zino_chatbot::{GlobalChatbot, ModelTool, ToolDispatcher};
+//!
+//! let tool = ModelTool::<Order>::new()
+//!     .description("Queries the orders of the current customer")
+//!     .columns(&["id", "status", "amount", "created_at"])
+//!     .scope("customer_id", customer_id)
+//!     .max_limit(20);
+//! let dispatcher = ToolDispatcher::new((tool,));
+//! let chatbot = GlobalChatbot::get("openai").unwrap();
+//!
+//! let mut messages = vec![json!({ "role": "user", "content": question })];
+//! let answer = dispatcher.run(chatbot, &mut messages).await?;
+//! ```
+use super::{Chatbot, ChatbotService};
+use zino_core::{
+    bail,
+    error::{Error, ErrorKind},
+    extension::JsonObjectExt,
+    warn, JsonValue, Map,
+};
+
+#[cfg(feature = "orm")]
+mod model;
+
+#[cfg(feature = "orm")]
+pub use model::ModelTool;
+
+/// Tools which can be called by the chatbot.
+pub trait ChatbotTool {
+    /// Returns the function definitions of the tools.
+    fn definitions(&self) -> Vec<Map>;
+
+    /// Returns `true` if it provides a tool with the name.
+    fn has_tool(&self, name: &str) -> bool;
+
+    /// Calls the tool with the name and the arguments.
+    async fn call(&self, name: &str, arguments: &Map) -> Result<JsonValue, Error>;
+}
+
+macro_rules! impl_chatbot_tool_for_tuple {
+    ($($Tool:ident => $index:tt),+) => {
+        impl<$($Tool: ChatbotTool),+> ChatbotTool for ($($Tool,)+) {
+            fn definitions(&self) -> Vec<Map> {
+                let mut definitions = Vec::new();
+                $(definitions.extend(self.$index.definitions());)+
+                definitions
+            }
+
+            #[inline]
+            fn has_tool(&self, name: &str) -> bool {
+                $(self.$index.has_tool(name))||+
+            }
+
+            async fn call(&self, name: &str, arguments: &Map) -> Result<JsonValue, Error> {
+                $(
+                    if self.$index.has_tool(name) {
+                        return self.$index.call(name, arguments).await;
+                    }
+                )+
+                Err(warn!("tool `{}` does not exist", name).with_kind(ErrorKind::NotFound))
+            }
+        }
+    };
+}
+
+impl_chatbot_tool_for_tuple!(T0 => 0);
+impl_chatbot_tool_for_tuple!(T0 => 0, T1 => 1);
+impl_chatbot_tool_for_tuple!(T0 => 0, T1 => 1, T2 => 2);
+impl_chatbot_tool_for_tuple!(T0 => 0, T1 => 1, T2 => 2, T3 => 3);
+impl_chatbot_tool_for_tuple!(T0 => 0, T1 => 1, T2 => 2, T3 => 3, T4 => 4);
+impl_chatbot_tool_for_tuple!(T0 => 0, T1 => 1, T2 => 2, T3 => 3, T4 => 4, T5 => 5);
+
+/// A dispatcher for the tool calls generated by the chatbot.
+#[derive(Debug, Clone)]
+pub struct ToolDispatcher<T> {
+    /// Tools.
+    tools: T,
+    /// Max number of the completion rounds.
+    max_rounds: usize,
+}
+
+impl<T: ChatbotTool> ToolDispatcher<T> {
+    /// Creates a new instance.
+    #[inline]
+    pub fn new(tools: T) -> Self {
+        Self {
+            tools,
+            max_rounds: 5,
+        }
+    }
+
+    /// Sets the max number of the completion rounds for [`run()`](Self::run).
+    #[inline]
+    pub fn max_rounds(mut self, max_rounds: usize) -> Self {
+        self.max_rounds = max_rounds.max(1);
+        self
+    }
+
+    /// Returns the tool definitions to be used in the chat completions API.
+    pub fn definitions(&self) -> Vec<JsonValue> {
+        self.tools
+            .definitions()
+            .into_iter()
+            .map(|function| {
+                let mut definition = Map::new();
+                definition.upsert("type", "function");
+                definition.upsert("function", function);
+                definition.into()
+            })
+            .collect()
+    }
+
+    /// Dispatches the tool calls in the response message and returns the tool messages.
+    ///
+    /// The failure of a tool call is reported to the chatbot in the tool message,
+    /// so that it can correct the arguments in the next turn.
+    pub async fn dispatch(&self, message: &Map) -> Vec<Map> {
+        let Some(tool_calls) = message.get_map_array("tool_calls") else {
+            return Vec::new();
+        };
+        let mut messages = Vec::with_capacity(tool_calls.len());
+        for tool_call in tool_calls {
+            let tool_call_id = tool_call.get_str("id").unwrap_or_default();
+            let content = match self.call(tool_call).await {
+                Ok(value) => value,
+                Err(err) => {
+                    let mut error = Map::new();
+                    error.upsert("code", err.kind().as_str());
+                    error.upsert("message", err.message());
+                    Map::from_entry("error", error).into()
+                }
+            };
+
+            let mut message = Map::new();
+            message.upsert("role", "tool");
+            message.upsert("tool_call_id", tool_call_id);
+            message.upsert("content", content.to_string());
+            messages.push(message);
+        }
+        messages
+    }
+
+    /// Runs the conversation with the tool calls until the chatbot returns a final answer.
+    /// The messages are extended with the assistant and tool messages.
+    pub async fn run(
+        &self,
+        chatbot: &Chatbot,
+        messages: &mut Vec<JsonValue>,
+    ) -> Result<String, Error> {
+        let tools = self.definitions();
+        for _ in 0..self.max_rounds {
+            let message = chatbot.try_complete(messages, &tools).await?;
+            let tool_messages = self.dispatch(&message).await;
+
+            let mut assistant_message = Map::new();
+            assistant_message.upsert("role", "assistant");
+            if let Some(content) = message.get("content") {
+                assistant_message.upsert("content", content.clone());
+            }
+            if tool_messages.is_empty() {
+                messages.push(assistant_message.into());
+                let content = message.get_str("content").unwrap_or_default();
+                return Ok(content.to_owned());
+            }
+            if let Some(tool_calls) = message.get("tool_calls") {
+                assistant_message.upsert("tool_calls", tool_calls.clone());
+            }
+            messages.push(assistant_message.into());
+            messages.extend(tool_messages.into_iter().map(JsonValue::from));
+        }
+        bail!(
+            "the chatbot does not return an answer after {} rounds",
+            self.max_rounds
+        );
+    }
+
+    /// Calls a tool with the tool call object.
+    async fn call(&self, tool_call: &Map) -> Result<JsonValue, Error> {
+        let Some(function) = tool_call.get_object("function") else {
+            bail!("the tool call should have a `function` object");
+        };
+        let Some(name) = function.get_str("name") else {
+            bail!("the function name should be specified");
+        };
+        let arguments = match function.get("arguments") {
+            Some(JsonValue::String(arguments)) if !arguments.trim().is_empty() => {
+                serde_json::from_str::<Map>(arguments)
+                    .map_err(|err| Error::from(err).with_kind(ErrorKind::Validation))?
+            }
+            Some(JsonValue::Object(arguments)) => arguments.clone(),
+            _ => Map::new(),
+        };
+        self.tools.call(name, &arguments).await
+    }
+}
@@ -0,0 +1,454 @@
+use super::ChatbotTool;
+use std::{fmt::Display, marker::PhantomData};
+use zino_core::{
+    error::{Error, ErrorKind},
+    extension::JsonObjectExt,
+    model::{Column, Mutation},
+    validation::Validation,
+    JsonValue, Map,
+};
+use zino_orm::{ModelAccessor, Schema};
+
+/// A tool which exposes the query operations of a model to the chatbot.
+///
+/// The parameters of the function are derived from the model columns.
+/// By default, the tool is read-only and supports the `find` and `count` operations.
+/// The `update` operation is only available when the mutable columns are specified.
+///
+/// The soft-deleted and expired models are always excluded,
+/// and the scope filters can not be overridden by the arguments.
+pub struct ModelTool<M> {
+    /// Function name.
+    name: String,
+    /// Function description.
+    description: String,
+    /// Queryable columns.
+    columns: Vec<&'static Column<'static>>,
+    /// Mutable columns.
+    mutable_columns: Vec<&'static Column<'static>>,
+    /// Scope filters.
+    scopes: Map,
+    /// Max number of rows returned by the `find` operation.
+    max_limit: usize,
+    /// Phantom type of the model.
+    phantom: PhantomData<M>,
+}
+
+impl<M: Schema> ModelTool<M> {
+    /// Creates a new instance with the queryable columns
+    /// which are not write-only.
+    pub fn new() -> Self {
+        let model_name = M::MODEL_NAME;
+        let columns = M::columns()
+            .iter()
+            .filter(|col| !col.is_write_only() && col.type_name() != "Map")
+            .collect();
+        Self {
+            name: format!("query_{model_name}"),
+            description: format!("Queries the `{model_name}` data"),
+            columns,
+            mutable_columns: Vec::new(),
+            scopes: Map::new(),
+            max_limit: 100,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Sets the function name.
+    #[inline]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Sets the function description.
+    #[inline]
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Restricts the queryable columns to the allow list.
+    pub fn columns(mut self, columns: &[&str]) -> Self {
+        self.columns.retain(|col| columns.contains(&col.name()));
+        self
+    }
+
+    /// Enables the `update` operation for the mutable columns in the allow list.
+    /// The primary key and read-only columns are never mutable.
+    pub fn mutable_columns(mut self, columns: &[&str]) -> Self {
+        self.mutable_columns = M::columns()
+            .iter()
+            .filter(|col| {
+                columns.contains(&col.name()) && !col.is_primary_key() && !col.is_read_only()
+            })
+            .collect();
+        self
+    }
+
+    /// Adds a scope filter which is enforced for all the operations.
+    /// The column is removed from the parameters of the function.
+    #[inline]
+    pub fn scope(mut self, column: impl Into<String>, value: impl Into<JsonValue>) -> Self {
+        self.scopes.upsert(column, value);
+        self
+    }
+
+    /// Sets the max number of rows returned by the `find` operation.
+    #[inline]
+    pub fn max_limit(mut self, max_limit: usize) -> Self {
+        self.max_limit = max_limit.max(1);
+        self
+    }
+
+    /// Returns `true` if the tool supports the `update` operation.
+    #[inline]
+    pub fn is_writable(&self) -> bool {
+        !self.mutable_columns.is_empty()
+    }
+
+    /// Returns the function definition with the JSON Schema parameters.
+    pub fn definition(&self) -> Map {
+        let columns = self.filter_columns().collect::<Vec<_>>();
+        let column_names = columns.iter().map(|col| col.name()).collect::<Vec<_>>();
+        let mut properties = Map::new();
+
+        let mut operations = vec!["find", "count"];
+        if self.is_writable() {
+            operations.push("update");
+        }
+        let mut operation = Map::new();
+        operation.upsert("type", "string");
+        operation.upsert("enum", operations);
+        operation.upsert("description", "Operation on the data");
+        properties.upsert("operation", operation);
+
+        let mut filter_properties = Map::new();
+        for col in columns.iter() {
+            filter_properties.upsert(col.name(), col.definition());
+        }
+        let mut filters = Map::new();
+        filters.upsert("type", "object");
+        filters.upsert("properties", filter_properties);
+        filters.upsert("additionalProperties", false);
+        filters.upsert("description", "Filters to match the column values exactly");
+        properties.upsert("filters", filters);
+
+        let mut fields = Map::new();
+        fields.upsert("type", "array");
+        fields.upsert("items", Map::from_entry("enum", column_names.clone()));
+        fields.upsert(
+            "description",
+            "Columns to be returned by the `find` operation",
+        );
+        properties.upsert("fields", fields);
+
+        let mut order_by = Map::new();
+        order_by.upsert("type", "string");
+        order_by.upsert("enum", column_names);
+        order_by.upsert("description", "Column to sort the results by");
+        properties.upsert("order_by", order_by);
+
+        let mut descending = Map::new();
+        descending.upsert("type", "boolean");
+        descending.upsert("description", "Sorts the results in a descending order");
+        properties.upsert("descending", descending);
+
+        let mut limit = Map::new();
+        limit.upsert("type", "integer");
+        limit.upsert("minimum", 1);
+        limit.upsert("maximum", self.max_limit);
+        limit.upsert(
+            "description",
+            "Max number of rows returned by the `find` operation",
+        );
+        properties.upsert("limit", limit);
+
+        if self.is_writable() {
+            let primary_key_name = M::PRIMARY_KEY_NAME;
+            let mut primary_key = M::primary_key_column().definition();
+            primary_key.upsert(
+                "description",
+                "Primary key of the data to be updated by the `update` operation",
+            );
+            properties.upsert(primary_key_name, primary_key);
+
+            let mut update_properties = Map::new();
+            for col in self.mutable_columns.iter() {
+                update_properties.upsert(col.name(), col.definition());
+            }
+            let mut updates = Map::new();
+            updates.upsert("type", "object");
+            updates.upsert("properties", update_properties);
+            updates.upsert("additionalProperties", false);
+            updates.upsert(
+                "description",
+                "New column values for the `update` operation",
+            );
+            properties.upsert("updates", updates);
+        }
+
+        let mut parameters = Map::new();
+        parameters.upsert("type", "object");
+        parameters.upsert("properties", properties);
+        parameters.upsert("required", vec!["operation"]);
+        parameters.upsert("additionalProperties", false);
+
+        let mut definition = Map::new();
+        definition.upsert("name", self.name.as_str());
+        definition.upsert("description", self.description.as_str());
+        definition.upsert("parameters", parameters);
+        definition
+    }
+
+    /// Validates the arguments against the parameters of the function.
+    #[must_use]
+    pub fn validate_arguments(&self, arguments: &Map) -> Validation {
+        let mut validation = Validation::new();
+        for key in arguments.keys() {
+            let is_valid = match key.as_str() {
+                "operation" | "filters" | "fields" | "order_by" | "descending" | "limit" => true,
+                "updates" => self.is_writable(),
+                _ => self.is_writable() && key == M::PRIMARY_KEY_NAME,
+            };
+            if !is_valid {
+                validation.record(key.to_owned(), "it is an unknown argument");
+            }
+        }
+
+        let operation = arguments.get_str("operation").unwrap_or("find");
+        match operation {
+            "find" | "count" => (),
+            "update" if self.is_writable() => {
+                let primary_key_name = M::PRIMARY_KEY_NAME;
+                match arguments.get(primary_key_name) {
+                    Some(value) => {
+                        let col = M::primary_key_column();
+                        if let Err(message) = validate_value(col, value) {
+                            validation.record(primary_key_name, message);
+                        }
+                    }
+                    None => validation.record(primary_key_name, "it should be specified"),
+                }
+                match arguments.get_object("updates") {
+                    Some(updates) if !updates.is_empty() => {
+                        for (key, value) in updates {
+                            let path = ["updates.", key].concat();
+                            match self.mutable_columns.iter().find(|col| col.name() == key) {
+                                Some(col) => {
+                                    if let Err(message) = validate_value(col, value) {
+                                        validation.record(path, message);
+                                    }
+                                }
+                                None => validation.record(path, "the column is not mutable"),
+                            }
+                        }
+                    }
+                    _ => validation.record("updates", "it should be a nonempty object"),
+                }
+            }
+            _ => validation.record("operation", "it is an unsupported operation"),
+        }
+
+        if let Some(filters) = arguments.get("filters") {
+            if let Some(filters) = filters.as_object() {
+                for (key, value) in filters {
+                    match self.filter_columns().find(|col| col.name() == key) {
+                        Some(col) => {
+                            if let Err(message) = validate_value(col, value) {
+                                validation.record(["filters.", key].concat(), message);
+                            }
+                        }
+                        None => validation
+                            .record(["filters.", key].concat(), "the column can not be filtered"),
+                    }
+                }
+            } else {
+                validation.record("filters", "it should be an object");
+            }
+        }
+        if let Some(fields) = arguments.get("fields") {
+            if let Some(fields) = fields.as_array() {
+                for field in fields {
+                    let is_valid = field
+                        .as_str()
+                        .is_some_and(|field| self.columns.iter().any(|col| col.name() == field));
+                    if !is_valid {
+                        validation.record("fields", format!("the field `{field}` is invalid"));
+                    }
+                }
+            } else {
+                validation.record("fields", "it should be an array");
+            }
+        }
+        if let Some(order_by) = arguments.get("order_by") {
+            let is_valid = order_by
+                .as_str()
+                .is_some_and(|field| self.columns.iter().any(|col| col.name() == field));
+            if !is_valid {
+                validation.record("order_by", "it should be a queryable column");
+            }
+        }
+        if let Some(descending) = arguments.get("descending") {
+            if !descending.is_boolean() {
+                validation.record("descending", "it should be a boolean");
+            }
+        }
+        if let Some(limit) = arguments.get("limit") {
+            let is_valid = limit
+                .as_u64()
+                .is_some_and(|limit| limit >= 1 && limit <= self.max_limit as u64);
+            if !is_valid {
+                validation.record(
+                    "limit",
+                    format!(
+                        "it should be an integer in the range [1, {}]",
+                        self.max_limit
+                    ),
+                );
+            }
+        }
+        validation
+    }
+
+    /// Returns an iterator of the columns which can be filtered.
+    fn filter_columns(&self) -> impl Iterator<Item = &'static Column<'static>> + '_ {
+        self.columns
+            .iter()
+            .copied()
+            .filter(|col| !self.scopes.contains_key(col.name()))
+    }
+}
+
+impl<M: Schema> Default for ModelTool<M> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M, K> ChatbotTool for ModelTool<M>
+where
+    M: Schema<PrimaryKey = K> + ModelAccessor<K>,
+    K: Default + Display + PartialEq,
+{
+    #[inline]
+    fn definitions(&self) -> Vec<Map> {
+        vec![self.definition()]
+    }
+
+    #[inline]
+    fn has_tool(&self, name: &str) -> bool {
+        self.name == name
+    }
+
+    async fn call(&self, _name: &str, arguments: &Map) -> Result<JsonValue, Error> {
+        let validation = self.validate_arguments(arguments);
+        if !validation.is_success() {
+            let message = validation.to_string();
+            return Err(Error::new(message).with_kind(ErrorKind::Validation));
+        }
+
+        let mut query = M::default_list_query();
+        let fields = arguments
+            .get_str_array("fields")
+            .filter(|fields| !fields.is_empty())
+            .unwrap_or_else(|| self.columns.iter().map(|col| col.name()).collect());
+        query.allow_fields(&fields);
+        if let Some(filters) = arguments.get_object("filters") {
+            for (key, value) in filters {
+                query.add_filter(key, value.clone());
+            }
+        }
+        for (key, value) in self.scopes.iter() {
+            query.add_filter(key, value.clone());
+        }
+        match arguments.get_str("operation").unwrap_or("find") {
+            "count" => {
+                let count = M::count(&query).await?;
+                Ok(Map::from_entry("count", count).into())
+            }
+            "update" => {
+                let primary_key_name = M::PRIMARY_KEY_NAME;
+                if let Some(value) = arguments.get(primary_key_name) {
+                    query.add_filter(primary_key_name, value.clone());
+                }
+
+                let mut mutation = Mutation::default();
+                if let Some(updates) = arguments.get_object("updates") {
+                    for (key, value) in updates {
+                        mutation.add_update(key, value.clone());
+                    }
+                }
+                let fields = self
+                    .mutable_columns
+                    .iter()
+                    .map(|col| col.name())
+                    .collect::<Vec<_>>();
+                mutation.allow_fields(&fields);
+
+                let ctx = M::update_one(&query, &mut mutation).await?;
+                let rows_affected = ctx.rows_affected().unwrap_or_default();
+                Ok(Map::from_entry("rows_affected", rows_affected).into())
+            }
+            _ => {
+                let limit = arguments
+                    .get_usize("limit")
+                    .unwrap_or(self.max_limit)
+                    .min(self.max_limit);
+                query.set_limit(limit);
+                if let Some(order_by) = arguments.get_str("order_by") {
+                    let descending = arguments.get_bool("descending").unwrap_or_default();
+                    query.order_by(order_by.to_owned(), descending);
+                }
+
+                let data = M::fetch(&query).await?;
+                let mut result = Map::new();
+                result.upsert("count", data.len());
+                result.upsert("data", data);
+                Ok(result.into())
+            }
+        }
+    }
+}
+
+/// Validates the value against the column definition.
+fn validate_value(col: &Column<'_>, value: &JsonValue) -> Result<(), String> {
+    if value.is_null() {
+        return if col.is_not_null() {
+            Err("it should not be null".to_owned())
+        } else {
+            Ok(())
+        };
+    }
+
+    let definition = col.definition();
+    let is_valid = match definition.get_str("type") {
+        Some("boolean") => value.is_boolean(),
+        Some("integer") => value.is_i64() || value.is_u64(),
+        Some("number") => value.is_number(),
+        Some("string") => value.is_string(),
+        Some("array") => value.is_array(),
+        Some("object") => value.is_object(),
+        _ => true,
+    };
+    if !is_valid {
+        let type_name = col.type_name();
+        return Err(format!("it should be a value of `{type_name}`"));
+    }
+    if let Some(values) = definition.get_array("enum") {
+        if !values.contains(value) {
+            return Err(format!("the value `{value}` is not allowed"));
+        }
+    }
+    if let Some(format @ ("uri" | "uuid")) = definition.get_str("format") {
+        if let Some(value) = value.as_str() {
+            let mut validation = Validation::new();
+            validation.validate_format("value", value, format);
+            if !validation.is_success() {
+                return Err(format!("it should be a `{format}` string"));
+            }
+        }
+    }
+    Ok(())
+}
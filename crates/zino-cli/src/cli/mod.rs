@@ -12,6 +12,7 @@ use zino_core::error::Error;
 mod deploy;
 mod init;
 mod new;
+mod secret;
 mod serve;
 
 /// CLI tool for developing Zino applications.
@@ -48,6 +49,8 @@ pub enum Subcommands {
     Serve(serve::Serve),
     /// Deploy the project.
     Deploy(deploy::Deploy),
+    /// Manage the sealed secrets.
+    Secret(secret::Secret),
 }
 
 /// Default path for temporary template.
@@ -0,0 +1,114 @@
+use clap::{Parser, Subcommand};
+use std::{
+    fs,
+    io::{self, BufRead},
+    path::PathBuf,
+};
+use zino_core::{error::Error, state::Secrets};
+
+/// Manages the sealed secrets.
+#[derive(Parser)]
+#[clap(name = "secret")]
+pub struct Secret {
+    /// The config directory.
+    #[clap(long, default_value = "./config")]
+    config_dir: PathBuf,
+    /// Secret actions.
+    #[clap(subcommand)]
+    action: SecretAction,
+}
+
+/// Secret actions.
+#[derive(Subcommand)]
+enum SecretAction {
+    /// Set a secret value. It is read from the stdin if not specified.
+    Set {
+        /// The secret name, such as `database.password`.
+        name: String,
+        /// The secret value.
+        #[clap(long)]
+        value: Option<String>,
+    },
+    /// Remove a secret.
+    Remove {
+        /// The secret name.
+        name: String,
+    },
+    /// List the secret names.
+    List,
+    /// Re-encrypt the secrets with a new master key.
+    ///
+    /// The new key is read from `ZINO_SECRETS_NEW_KEY`,
+    /// or generated and written to the key file.
+    RotateKey {
+        /// The file to write the generated master key.
+        #[clap(long)]
+        new_key_file: Option<PathBuf>,
+    },
+}
+
+impl Secret {
+    /// Runs the `secret` subcommand.
+    pub fn run(self) -> Result<(), Error> {
+        let config_dir = self.config_dir;
+        let path = config_dir.join("secrets.enc.toml");
+        match self.action {
+            SecretAction::Set { name, value } => {
+                let value = match value {
+                    Some(value) => value,
+                    None => read_stdin_line(&name)?,
+                };
+                let master_key = Secrets::read_master_key(&config_dir)?;
+                let mut secrets = Secrets::open(&path, &master_key)?;
+                secrets.set(name.as_str(), value);
+                secrets.seal(&path, &master_key)?;
+                log::info!("secret `{name}` has been set");
+            }
+            SecretAction::Remove { name } => {
+                let master_key = Secrets::read_master_key(&config_dir)?;
+                let mut secrets = Secrets::open(&path, &master_key)?;
+                if !secrets.remove(&name) {
+                    return Err(Error::new(format!("secret `{name}` does not exist")));
+                }
+                secrets.seal(&path, &master_key)?;
+                log::info!("secret `{name}` has been removed");
+            }
+            SecretAction::List => {
+                for name in Secrets::list_names(&path)? {
+                    println!("{name}");
+                }
+            }
+            SecretAction::RotateKey { new_key_file } => {
+                let master_key = Secrets::read_master_key(&config_dir)?;
+                let secrets = Secrets::open(&path, &master_key)?;
+                let new_master_key = if let Ok(new_key) = std::env::var("ZINO_SECRETS_NEW_KEY") {
+                    new_key
+                } else {
+                    let new_key_file =
+                        new_key_file.unwrap_or_else(|| config_dir.join("secrets.key"));
+                    let new_key = Secrets::generate_master_key();
+                    fs::write(&new_key_file, &new_key)?;
+                    log::info!("new master key written to `{}`", new_key_file.display());
+                    new_key
+                };
+                secrets.seal(&path, &new_master_key)?;
+                log::info!("secrets have been re-encrypted with the new master key");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads a line from the stdin as the secret value.
+fn read_stdin_line(name: &str) -> Result<String, Error> {
+    eprintln!("Enter the value of `{name}`:");
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    let value = line.trim_end_matches(['\r', '\n']);
+    if value.is_empty() {
+        return Err(Error::new(format!(
+            "the value of `{name}` should be nonempty"
+        )));
+    }
+    Ok(value.to_owned())
+}
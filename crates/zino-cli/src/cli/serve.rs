@@ -8,11 +8,21 @@ use serde::{Deserialize, Serialize};
 use std::{env, fs};
 use toml_edit::{Array, DocumentMut as Document};
 use zino::prelude::*;
-use zino_core::error::Error;
+use zino_core::{error::Error, state::ConfigReport};
 
 /// Start the server.
 #[derive(Parser)]
-pub struct Serve {}
+pub struct Serve {
+    /// Validate the config of the project and exit without starting the server.
+    #[clap(long)]
+    check: bool,
+    /// Reject the unknown keys in the config.
+    #[clap(long)]
+    strict: bool,
+    /// The env of the config to validate.
+    #[clap(long, default_value = "dev")]
+    env: String,
+}
 
 /// Resource directory.
 static RESOURCE: Dir = include_dir::include_dir!("public");
@@ -21,6 +31,10 @@ static RESOURCE: Dir = include_dir::include_dir!("public");
 impl Serve {
     /// Runs the `serve` subcommand.
     pub fn run(self) -> Result<(), Error> {
+        if self.check {
+            return self.check_config();
+        }
+
         log::info!("Starting server at: 127.0.0.1:6080/zino-config.html");
 
         env::set_var("CARGO_PKG_NAME", env!("CARGO_PKG_NAME"));
@@ -38,6 +52,24 @@ impl Serve {
             .run();
         Ok(())
     }
+
+    /// Validates the config in the `config` directory of the current project.
+    fn check_config(&self) -> Result<(), Error> {
+        let env = &self.env;
+        let config_file = format!("./config/config.{env}.toml");
+        let content = fs::read_to_string(&config_file)
+            .map_err(|err| Error::new(format!("fail to read `{config_file}`: {err}")))?;
+        let config = content
+            .parse::<toml::Table>()
+            .map_err(|err| Error::new(format!("fail to parse `{config_file}`: {err}")))?;
+        let report = ConfigReport::check(&config, self.strict);
+        print!("{report}");
+        if report.has_errors() {
+            Err(Error::new(format!("invalid config `{config_file}`")))
+        } else {
+            Ok(())
+        }
+    }
 }
 
 /// Returns the content of `Cargo.toml` file in the current directory.
@@ -6,6 +6,7 @@ fn main() {
         Init(opts) => opts.run(),
         New(opts) => opts.run(),
         Serve(opts) => opts.run(),
+        Secret(opts) => opts.run(),
         Deploy(opts) => {
             let rt = tokio::runtime::Runtime::new().expect("failed to create tokio runtime");
             rt.block_on(opts.run());
@@ -14,5 +15,6 @@ fn main() {
     };
     if let Err(err) = result {
         log::error!("fail to run the command: {err}");
+        std::process::exit(1);
     }
 }
@@ -1,12 +1,19 @@
 use datafusion::arrow::{
     array::{
-        self, Array, BinaryArray, BooleanArray, Float32Array, Float64Array, Int32Array, Int64Array,
-        LargeBinaryArray, LargeStringArray, StringArray, UInt16Array, UInt32Array, UInt64Array,
+        self, Array, BinaryArray, BooleanArray, Date32Array, Decimal128Array, Float32Array,
+        Float64Array, Int32Array, Int64Array, LargeBinaryArray, LargeStringArray, StringArray,
+        TimestampMicrosecondArray, UInt16Array, UInt32Array, UInt64Array,
     },
-    datatypes::{DataType, Field},
+    datatypes::{DataType, Field, TimeUnit},
 };
 use std::sync::Arc;
-use zino_core::{bail, error::Error, extension::AvroRecordExt, AvroValue, Record};
+use zino_core::{
+    bail,
+    datetime::{Date, DateTime},
+    error::Error,
+    extension::AvroRecordExt,
+    AvroValue, Record,
+};
 
 /// Extension trait for [`Field`](datafusion::arrow::datatypes::Field).
 pub(super) trait ArrowFieldExt {
@@ -27,6 +34,10 @@ impl ArrowFieldExt for Field {
             AvroValue::Double(_) => DataType::Float64,
             AvroValue::Bytes(_) => DataType::Binary,
             AvroValue::String(_) | AvroValue::Uuid(_) => DataType::Utf8,
+            AvroValue::Date(_) => DataType::Date32,
+            AvroValue::TimestampMillis(_) | AvroValue::TimestampMicros(_) => {
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+            }
             _ => {
                 bail!("fail to construct an Arrow field for the `{}` field", field);
             }
@@ -110,18 +121,144 @@ impl ArrowFieldExt for Field {
             DataType::Utf8 => {
                 let values = records
                     .iter()
-                    .map(|record| record.get_str(field))
+                    .map(|record| record.find(field).and_then(parse_string))
                     .collect::<Vec<_>>();
                 Arc::new(StringArray::from(values))
             }
             DataType::LargeUtf8 => {
                 let values = records
                     .iter()
-                    .map(|record| record.get_str(field))
+                    .map(|record| record.find(field).and_then(parse_string))
                     .collect::<Vec<_>>();
                 Arc::new(LargeStringArray::from(values))
             }
+            DataType::Date32 => {
+                let values = records
+                    .iter()
+                    .map(|record| record.find(field).and_then(parse_date))
+                    .collect::<Vec<_>>();
+                Arc::new(Date32Array::from(values))
+            }
+            DataType::Timestamp(TimeUnit::Microsecond, time_zone) => {
+                let values = records
+                    .iter()
+                    .map(|record| record.find(field).and_then(parse_timestamp_micros))
+                    .collect::<Vec<_>>();
+                let array = TimestampMicrosecondArray::from(values);
+                Arc::new(array.with_timezone_opt(time_zone.clone()))
+            }
+            DataType::Decimal128(precision, scale) => {
+                let values = records
+                    .iter()
+                    .map(|record| {
+                        record
+                            .find(field)
+                            .and_then(|value| parse_decimal(value, *scale))
+                    })
+                    .collect::<Vec<_>>();
+                let array = Decimal128Array::from(values);
+                match array.with_precision_and_scale(*precision, *scale) {
+                    Ok(array) => Arc::new(array),
+                    Err(err) => {
+                        tracing::warn!("fail to collect decimal values: {err}");
+                        array::new_null_array(self.data_type(), records.len())
+                    }
+                }
+            }
             data_type => array::new_null_array(data_type, records.len()),
         }
     }
 }
+
+/// Parses an Avro value as a string.
+fn parse_string(value: &AvroValue) -> Option<String> {
+    match value {
+        AvroValue::String(s) | AvroValue::Enum(_, s) => Some(s.to_owned()),
+        AvroValue::Uuid(uuid) => Some(uuid.to_string()),
+        AvroValue::Union(_, value) => parse_string(value),
+        _ => None,
+    }
+}
+
+/// Parses an Avro value as the number of days since the UNIX epoch.
+fn parse_date(value: &AvroValue) -> Option<i32> {
+    match value {
+        AvroValue::Date(days) | AvroValue::Int(days) => Some(*days),
+        AvroValue::String(s) => {
+            let date = s.get(..10).unwrap_or(s).parse::<Date>().ok()?;
+            Some(date.num_days_from_epoch())
+        }
+        AvroValue::Union(_, value) => parse_date(value),
+        _ => None,
+    }
+}
+
+/// Parses an Avro value as the number of microseconds since the UNIX epoch.
+fn parse_timestamp_micros(value: &AvroValue) -> Option<i64> {
+    match value {
+        AvroValue::TimestampMicros(micros) | AvroValue::LocalTimestampMicros(micros) => {
+            Some(*micros)
+        }
+        AvroValue::TimestampMillis(millis) | AvroValue::LocalTimestampMillis(millis) => {
+            millis.checked_mul(1000)
+        }
+        AvroValue::String(s) => s.parse::<DateTime>().ok().map(|dt| dt.timestamp_micros()),
+        AvroValue::Union(_, value) => parse_timestamp_micros(value),
+        _ => None,
+    }
+}
+
+/// Parses an Avro value as a decimal scaled by `10^scale`.
+fn parse_decimal(value: &AvroValue, scale: i8) -> Option<i128> {
+    match value {
+        AvroValue::String(s) => parse_decimal_str(s, scale),
+        AvroValue::Int(i) => 10_i128
+            .checked_pow(scale.try_into().ok()?)?
+            .checked_mul((*i).into()),
+        AvroValue::Long(i) => 10_i128
+            .checked_pow(scale.try_into().ok()?)?
+            .checked_mul((*i).into()),
+        AvroValue::Double(f) => parse_decimal_str(&f.to_string(), scale),
+        AvroValue::Float(f) => parse_decimal_str(&f.to_string(), scale),
+        AvroValue::Union(_, value) => parse_decimal(value, scale),
+        _ => None,
+    }
+}
+
+/// Parses a decimal string without losing precision, truncating the extra fractional digits.
+fn parse_decimal_str(s: &str, scale: i8) -> Option<i128> {
+    let scale = usize::try_from(scale).ok()?;
+    let s = s.trim();
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (integer, fraction) = s.split_once('.').unwrap_or((s, ""));
+    if integer.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    let mut digits = String::with_capacity(integer.len() + scale);
+    digits.push_str(integer);
+    digits.extend(fraction.chars().chain(std::iter::repeat('0')).take(scale));
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let value = digits.parse::<i128>().ok()?;
+    Some(if negative { -value } else { value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_decimal_str;
+
+    #[test]
+    fn it_parses_decimal_str() {
+        assert_eq!(parse_decimal_str("123.45", 2), Some(12345));
+        assert_eq!(parse_decimal_str("-0.5", 3), Some(-500));
+        assert_eq!(parse_decimal_str("42", 2), Some(4200));
+        assert_eq!(parse_decimal_str("1.23456", 2), Some(123));
+        assert_eq!(parse_decimal_str(".5", 1), Some(5));
+        assert_eq!(parse_decimal_str("1e3", 2), None);
+        assert_eq!(parse_decimal_str("", 2), None);
+    }
+}
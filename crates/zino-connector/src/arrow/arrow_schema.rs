@@ -1,16 +1,18 @@
 use super::ArrowFieldExt;
 use datafusion::arrow::{
     array::Array,
-    datatypes::{DataType, Field, Schema, UnionFields, UnionMode},
+    datatypes::{DataType, Field, Schema, TimeUnit, UnionFields, UnionMode},
 };
 use std::sync::Arc;
 use toml::Table;
-use zino_core::{bail, error::Error, Record, TomlValue};
+use zino_core::{bail, error::Error, extension::AvroRecordExt, AvroValue, Record, TomlValue};
 
 /// Extension trait for [`Schema`](datafusion::arrow::datatypes::Schema).
 pub(super) trait ArrowSchemaExt {
-    /// Attempts to create a `Schema` from an Avro record.
-    fn try_from_avro_record(record: &Record) -> Result<Schema, Error>;
+    /// Attempts to create a `Schema` from the Avro records.
+    /// The data type of each field is derived from the first non-null value,
+    /// and it will be `Utf8` if all the values are null.
+    fn try_from_avro_records(records: &[Record]) -> Result<Schema, Error>;
 
     /// Attempts to create a `Schema` from the TOML table configuration.
     fn try_from_toml_table(table: &Table) -> Result<Schema, Error>;
@@ -23,10 +25,25 @@ pub(super) trait ArrowSchemaExt {
 }
 
 impl ArrowSchemaExt for Schema {
-    fn try_from_avro_record(record: &Record) -> Result<Schema, Error> {
+    fn try_from_avro_records(records: &[Record]) -> Result<Schema, Error> {
+        let Some(record) = records.first() else {
+            return Ok(Schema::empty());
+        };
         let mut fields = Vec::with_capacity(record.len());
-        for (field, value) in record {
-            let field = Field::try_from_avro_record_entry(field, value)?;
+        for (field, _) in record {
+            let value = records.iter().find_map(|record| {
+                record.find(field).and_then(|value| match value {
+                    AvroValue::Null => None,
+                    AvroValue::Union(_, value) if matches!(**value, AvroValue::Null) => None,
+                    AvroValue::Union(_, value) => Some(value.as_ref()),
+                    _ => Some(value),
+                })
+            });
+            let field = if let Some(value) = value {
+                Field::try_from_avro_record_entry(field, value)?
+            } else {
+                Field::new(field, DataType::Utf8, true)
+            };
             fields.push(field);
         }
         Ok(Schema::new(fields))
@@ -95,7 +112,22 @@ fn parse_arrow_data_type(value_type: &str) -> Result<DataType, Error> {
         "float" => DataType::Float32,
         "double" => DataType::Float64,
         "bytes" => DataType::Binary,
-        "string" => DataType::Utf8,
+        "string" | "uuid" => DataType::Utf8,
+        "date" => DataType::Date32,
+        "timestamp" => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+        "decimal" => DataType::Decimal128(38, 10),
+        _ if value_type.starts_with("decimal(") && value_type.ends_with(')') => {
+            let Some((precision, scale)) = value_type[8..value_type.len() - 1].split_once(',')
+            else {
+                bail!("decimal type `{}` should specify the scale", value_type);
+            };
+            let precision = precision.trim().parse::<u8>()?;
+            let scale = scale.trim().parse::<i8>()?;
+            if precision == 0 || precision > 38 || scale < 0 || scale as u8 > precision {
+                bail!("decimal type `{}` is invalid", value_type);
+            }
+            DataType::Decimal128(precision, scale)
+        }
         _ => {
             bail!("parsing `{}` as Arrow data type is unsupported", value_type);
         }
@@ -1,11 +1,11 @@
 //! Utilities for DataFusion.
 
-use super::{Connector, DataSource, DataSourceConnector::Arrow};
+use super::{Connector, DataSource, DataSourceConnector::Arrow, GlobalConnector};
 use crate::helper;
 use datafusion::{
     arrow::{datatypes::Schema, record_batch::RecordBatch},
     dataframe::DataFrame,
-    datasource::file_format::file_compression_type::FileCompressionType,
+    datasource::{file_format::file_compression_type::FileCompressionType, MemTable},
     execution::{
         context::{SessionConfig, SessionContext},
         options::{AvroReadOptions, CsvReadOptions, NdJsonReadOptions, ParquetReadOptions},
@@ -19,7 +19,8 @@ use std::{
     fs::File,
     io::Write,
     path::PathBuf,
-    sync::{Arc, OnceLock},
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
 };
 use toml::value::{Array, Table};
 use zino_core::{
@@ -46,6 +47,29 @@ use scalar_provider::ScalarValueProvider;
 use scalar_value::ScalarValueExt;
 
 /// A connector for Apache Arrow.
+///
+/// Besides the tables backed by files, a table can also be populated by running a query
+/// against another data source, which enables federated queries across the data sources.
+///
+/// ```toml
+/// [[connector]]
+/// type = "arrow"
+/// name = "federation"
+///
+/// [[connector.tables]]
+/// name = "users"
+/// source = "main-postgres"
+/// query = "SELECT id, name, created_at FROM users;"
+/// refresh = "cached"
+/// ttl = "5m"
+///
+/// [connector.tables.schema]
+/// created_at = "timestamp"
+/// ```
+///
+/// The table is refreshed before each query by default. With `refresh = "cached"`,
+/// the rows are kept in memory until the `ttl` expires, which defaults to `1m`.
+/// The data types of columns are derived from the rows unless specified in the schema.
 pub struct ArrowConnector {
     /// Session context.
     context: OnceLock<SessionContext>,
@@ -53,6 +77,8 @@ pub struct ArrowConnector {
     root: PathBuf,
     /// Tables.
     tables: Option<Array>,
+    /// Tables populated from other data sources.
+    source_tables: Vec<SourceTable>,
     /// System variables.
     system_variables: ScalarValueProvider,
     /// User-defined variables.
@@ -67,6 +93,7 @@ impl ArrowConnector {
             context: OnceLock::new(),
             root: Agent::project_dir().to_owned(),
             tables: None,
+            source_tables: Vec::new(),
             system_variables: ScalarValueProvider::default(),
             user_defined_variables: ScalarValueProvider::default(),
         }
@@ -79,10 +106,21 @@ impl ArrowConnector {
         if let Some(variables) = config.get_table("variables") {
             system_variables.read_toml_table(variables);
         }
+        let tables = config.get_array("tables");
+        let source_tables = tables
+            .map(|tables| {
+                tables
+                    .iter()
+                    .filter_map(|v| v.as_table())
+                    .filter_map(SourceTable::with_config)
+                    .collect()
+            })
+            .unwrap_or_default();
         Self {
             context: OnceLock::new(),
             root: Agent::parse_path(root),
-            tables: config.get_array("tables").cloned(),
+            tables: tables.cloned(),
+            source_tables,
             system_variables,
             user_defined_variables: ScalarValueProvider::default(),
         }
@@ -117,6 +155,10 @@ impl ArrowConnector {
         if let Some(tables) = self.tables.as_deref() {
             let root = &self.root;
             for table in tables.iter().filter_map(|v| v.as_table()) {
+                if table.contains_key("source") {
+                    continue;
+                }
+
                 let data_type = table
                     .get_str("type")
                     .ok_or_else(|| warn!("the `type` field should be a str"))?;
@@ -211,15 +253,22 @@ impl ArrowConnector {
         Ok(self.context.get_or_init(|| ctx))
     }
 
+    /// Attempts to get the session context with the tables from other data sources refreshed.
+    async fn try_get_refreshed_context(&self) -> Result<&SessionContext, Error> {
+        let ctx = self.try_get_session_context().await?;
+        for table in &self.source_tables {
+            if table.is_stale()? {
+                table.refresh(ctx).await?;
+            }
+        }
+        Ok(ctx)
+    }
+
     /// Attempts to create a [`DateFrame`](datafusion::dataframe::DataFrame)
     /// from reading Avro records.
     pub async fn read_avro_records(&self, records: &[Record]) -> Result<DataFrame, Error> {
         let ctx = self.try_get_session_context().await?;
-        let schema = if let Some(record) = records.first() {
-            Schema::try_from_avro_record(record)?
-        } else {
-            Schema::empty()
-        };
+        let schema = Schema::try_from_avro_records(records)?;
 
         let columns = schema.collect_columns_from_avro_records(records);
         let batch = RecordBatch::try_new(Arc::new(schema), columns)?;
@@ -244,14 +293,14 @@ impl Connector for ArrowConnector {
     }
 
     async fn execute(&self, query: &str, params: Option<&Map>) -> Result<Option<u64>, Error> {
-        let ctx = self.try_get_session_context().await?;
+        let ctx = self.try_get_refreshed_context().await?;
         let sql = helper::format_query(query, params);
         let df = ctx.sql(&sql).await?;
         df.execute().await
     }
 
     async fn query(&self, query: &str, params: Option<&Map>) -> Result<Vec<Record>, Error> {
-        let ctx = self.try_get_session_context().await?;
+        let ctx = self.try_get_refreshed_context().await?;
         let sql = helper::format_query(query, params);
         let df = ctx.sql(&sql).await?;
         df.query().await
@@ -262,14 +311,14 @@ impl Connector for ArrowConnector {
         query: &str,
         params: Option<&Map>,
     ) -> Result<Vec<T>, Error> {
-        let ctx = self.try_get_session_context().await?;
+        let ctx = self.try_get_refreshed_context().await?;
         let sql = helper::format_query(query, params);
         let df = ctx.sql(&sql).await?;
         df.query_as().await
     }
 
     async fn query_one(&self, query: &str, params: Option<&Map>) -> Result<Option<Record>, Error> {
-        let ctx = self.try_get_session_context().await?;
+        let ctx = self.try_get_refreshed_context().await?;
         let sql = helper::format_query(query, params);
         let df = ctx.sql(&sql).await?;
         df.query_one().await
@@ -280,13 +329,143 @@ impl Connector for ArrowConnector {
         query: &str,
         params: Option<&Map>,
     ) -> Result<Option<T>, Error> {
-        let ctx = self.try_get_session_context().await?;
+        let ctx = self.try_get_refreshed_context().await?;
         let sql = helper::format_query(query, params);
         let df = ctx.sql(&sql).await?;
         df.query_one_as().await
     }
 }
 
+/// A table populated by running a query against another data source.
+struct SourceTable {
+    /// Table name.
+    name: String,
+    /// Name of the data source.
+    source: String,
+    /// The query.
+    query: String,
+    /// Optional schema.
+    schema: Option<Table>,
+    /// Time-to-live of the cached rows. If it is `None`, the table is refreshed per query.
+    ttl: Option<Duration>,
+    /// Last time when the table was refreshed.
+    refreshed_at: Mutex<Option<Instant>>,
+}
+
+impl SourceTable {
+    /// Creates a new instance with the configuration,
+    /// returning `None` if the table is not backed by a data source.
+    fn with_config(config: &Table) -> Option<Self> {
+        let source = config.get_str("source")?;
+        let Some(name) = config.get_str("name") else {
+            tracing::warn!(source, "the `name` field should be a str");
+            return None;
+        };
+        let Some(query) = config.get_str("query") else {
+            tracing::warn!(
+                source,
+                table_name = name,
+                "the `query` field should be a str"
+            );
+            return None;
+        };
+        let ttl = match config.get_str("refresh") {
+            Some("cached") => Some(
+                config
+                    .get_duration("ttl")
+                    .unwrap_or_else(|| Duration::from_secs(60)),
+            ),
+            Some("per-query") | None => None,
+            Some(refresh) => {
+                tracing::warn!(
+                    table_name = name,
+                    "refresh policy `{refresh}` is unsupported, fall back to `per-query`"
+                );
+                None
+            }
+        };
+        Some(Self {
+            name: name.to_owned(),
+            source: source.to_owned(),
+            query: query.to_owned(),
+            schema: config.get_table("schema").cloned(),
+            ttl,
+            refreshed_at: Mutex::new(None),
+        })
+    }
+
+    /// Returns `true` if the table should be refreshed.
+    fn is_stale(&self) -> Result<bool, Error> {
+        let refreshed_at = *self
+            .refreshed_at
+            .lock()
+            .map_err(|err| warn!("fail to acquire the lock: {}", err))?;
+        let stale = match (refreshed_at, self.ttl) {
+            (Some(refreshed_at), Some(ttl)) => refreshed_at.elapsed() >= ttl,
+            _ => true,
+        };
+        Ok(stale)
+    }
+
+    /// Fetches the rows from the data source and registers them as an in-memory table.
+    async fn refresh(&self, ctx: &SessionContext) -> Result<(), Error> {
+        let table_name = self.name.as_str();
+        let source = self.source.as_str();
+        let data_source = GlobalConnector::get(source)
+            .ok_or_else(|| warn!("the data source `{}` does not exist", source))?;
+        if data_source.protocol() == "arrow" {
+            bail!(
+                "the table `{}` can not be backed by an Arrow data source",
+                table_name
+            );
+        }
+
+        let records = Box::pin(data_source.query(&self.query, None)).await?;
+        let mut schema = Schema::try_from_avro_records(&records)?;
+        if let Some(config) = self.schema.as_ref() {
+            let configured_schema = Schema::try_from_toml_table(config)?;
+            if schema.fields().is_empty() {
+                schema = configured_schema;
+            } else {
+                let fields = schema
+                    .fields()
+                    .iter()
+                    .map(|field| {
+                        configured_schema
+                            .field_with_name(field.name())
+                            .unwrap_or(field.as_ref())
+                            .clone()
+                    })
+                    .collect::<Vec<_>>();
+                schema = Schema::new(fields);
+            }
+        }
+        let schema = Arc::new(schema);
+        let columns = schema.collect_columns_from_avro_records(&records);
+        let partition = if columns.is_empty() {
+            Vec::new()
+        } else {
+            vec![RecordBatch::try_new(schema.clone(), columns)?]
+        };
+        let mem_table = MemTable::try_new(schema, vec![partition])?;
+        ctx.deregister_table(table_name)?;
+        ctx.register_table(table_name, Arc::new(mem_table))?;
+
+        let mut refreshed_at = self
+            .refreshed_at
+            .lock()
+            .map_err(|err| warn!("fail to acquire the lock: {}", err))?;
+        *refreshed_at = Some(Instant::now());
+        tracing::debug!(
+            table_name,
+            source,
+            num_rows = records.len(),
+            "table has been refreshed"
+        );
+        Ok(())
+    }
+}
+
 /// Shared session state for DataFusion.
 static SHARED_SESSION_STATE: LazyLock<SessionState> = LazyLock::new(|| {
     SessionStateBuilder::new()
@@ -9,8 +9,9 @@ use serde::{de::DeserializeOwned, Serialize};
 use serde_json::value::RawValue;
 use toml::Table;
 use url::Url;
+use zino_auth::ClientCredentials;
 use zino_core::{
-    application::Agent,
+    application::{Agent, EndpointPool},
     bail,
     error::Error,
     extension::{
@@ -61,6 +62,33 @@ use zino_core::{
 ///     }
 /// }
 /// ```
+///
+/// The connector can be configured with the named client credentials,
+/// which injects the `Authorization: Bearer <token>` header automatically:
+///
+/// ```toml
+/// [[connector]]
+/// type = "http"
+/// name = "orders"
+/// base-url = "https://api.example.com/orders"
+/// auth = "my-upstream"
+/// ```
+///
+/// The requests can be balanced among multiple endpoints of the service,
+/// which are either listed explicitly or resolved from the DNS name of the base URL.
+/// An endpoint is ejected for a cooldown after consecutive failures,
+/// and the chosen endpoint is included in the error message. See [`EndpointPool`] for details.
+///
+/// ```toml
+/// [[connector]]
+/// type = "http"
+/// name = "orders"
+/// base-url = "http://orders.internal:8080/api"
+/// resolve-interval = "30s"
+/// max-failures = 3
+/// ejection-cooldown = "30s"
+/// sticky = true
+/// ```
 #[derive(Debug, Clone)]
 pub struct HttpConnector {
     /// HTTP request method (VERB).
@@ -75,6 +103,10 @@ pub struct HttpConnector {
     body: Option<Box<RawValue>>,
     /// JSON Pointer for looking up a value from the response data.
     json_pointer: Option<String>,
+    /// Name of the client credentials for the bearer authentication.
+    auth: Option<String>,
+    /// Pool of the endpoints.
+    endpoints: Option<EndpointPool>,
 }
 
 impl HttpConnector {
@@ -87,6 +119,8 @@ impl HttpConnector {
             headers: Map::new(),
             body: None,
             json_pointer: None,
+            auth: None,
+            endpoints: None,
         })
     }
 
@@ -110,10 +144,21 @@ impl HttpConnector {
         if let Some(json_pointer) = config.get_str("json-pointer") {
             connector.json_pointer = Some(json_pointer.into());
         }
+        if let Some(auth) = config.get_str("auth") {
+            connector.auth = Some(auth.into());
+        }
+        connector.endpoints = EndpointPool::try_from_config(&connector.base_url, config)?;
 
         Ok(connector)
     }
 
+    /// Sets the pool of the endpoints to balance the requests.
+    #[inline]
+    pub fn endpoints(mut self, endpoints: EndpointPool) -> Self {
+        self.endpoints = Some(endpoints);
+        self
+    }
+
     /// Adds a key/value pair for the request query.
     #[inline]
     pub fn query(mut self, key: &str, value: impl Into<JsonValue>) -> Self {
@@ -179,7 +224,18 @@ impl HttpConnector {
         self.json_pointer = Some(pointer.into());
     }
 
+    /// Sets the name of the client credentials configured in the `[[client-credentials]]` tables
+    /// for the bearer authentication.
+    #[inline]
+    pub fn set_auth(&mut self, name: impl Into<String>) {
+        self.auth = Some(name.into());
+    }
+
     /// Makes an HTTP request with the given query and params.
+    ///
+    /// If the connector is configured with the client credentials,
+    /// the request will be retried exactly once with a refreshed access token
+    /// when the response status is `401 Unauthorized`.
     pub async fn fetch(
         &self,
         query: Option<&str>,
@@ -212,15 +268,106 @@ impl HttpConnector {
             }
         }
 
-        let mut trace_context = TraceContext::new();
-        trace_context.record_trace_state();
-        Agent::request_builder(resource.as_ref(), Some(&options))?
-            .headers(headers)
-            .header("traceparent", trace_context.traceparent())
-            .header("tracestate", trace_context.tracestate())
-            .send()
+        let Some(auth) = self.auth.as_deref() else {
+            return self.send(&resource, &options, headers, None).await;
+        };
+        let credentials = ClientCredentials::get(auth)
+            .ok_or_else(|| warn!("the client credentials `{}` should be configured", auth))?;
+        let token = credentials.fetch_token().await?;
+        let response = self
+            .send(
+                &resource,
+                &options,
+                headers.clone(),
+                Some(token.access_token()),
+            )
+            .await?;
+        if response.status().as_u16() != 401 {
+            return Ok(response);
+        }
+
+        let token = credentials.refresh_token(token.access_token()).await?;
+        self.send(&resource, &options, headers, Some(token.access_token()))
             .await
-            .map_err(Error::from)
+    }
+
+    /// Sends an HTTP request with an optional bearer token.
+    async fn send(
+        &self,
+        resource: &str,
+        options: &Map,
+        headers: HeaderMap,
+        bearer_token: Option<&str>,
+    ) -> Result<Response, Error> {
+        let endpoint = self
+            .endpoints
+            .as_ref()
+            .map(|pool| pool.select())
+            .transpose()?;
+        let resource = if let Some(endpoint) = endpoint.as_ref() {
+            endpoint.rewrite_url(&resource.parse()?).to_string()
+        } else {
+            resource.to_owned()
+        };
+        let request = async {
+            let mut trace_context = TraceContext::new();
+            trace_context.record_trace_state();
+            let mut request_builder = Agent::request_builder(&resource, Some(options))
+                .map_err(|err| match endpoint.as_ref() {
+                    Some(endpoint) => endpoint.wrap_error(err),
+                    None => err,
+                })?
+                .headers(headers)
+                .header("traceparent", trace_context.traceparent())
+                .header("tracestate", trace_context.tracestate());
+            if let Some(host) = endpoint.as_ref().and_then(|endpoint| endpoint.host()) {
+                request_builder = request_builder.header("host", host);
+            }
+            if let Some(token) = bearer_token {
+                request_builder = request_builder.bearer_auth(token);
+            }
+            let result = request_builder.send().await.map_err(Error::from);
+            if let Some(endpoint) = endpoint.as_ref() {
+                endpoint.record_outcome(result)
+            } else {
+                result
+            }
+        };
+
+        // Creates a client span so that the `traceparent` refers to the outgoing request
+        #[cfg(feature = "otel")]
+        let request = {
+            use tracing::{field::Empty, Instrument};
+
+            let span = tracing::info_span!(
+                "HTTP connector request",
+                "otel.kind" = "client",
+                "otel.status_code" = Empty,
+                "http.request.method" = self.method.as_str(),
+                "http.response.status_code" = Empty,
+                "url.full" = resource.as_str(),
+            );
+            async move {
+                let result = request.instrument(span.clone()).await;
+                match &result {
+                    Ok(response) => {
+                        let status_code = response.status();
+                        let otel_status_code = if status_code.is_server_error() {
+                            "ERROR"
+                        } else {
+                            "OK"
+                        };
+                        span.record("http.response.status_code", status_code.as_u16());
+                        span.record("otel.status_code", otel_status_code);
+                    }
+                    Err(_) => {
+                        span.record("otel.status_code", "ERROR");
+                    }
+                }
+                result
+            }
+        };
+        request.await
     }
 
     /// Makes an HTTP request with the given query and params,
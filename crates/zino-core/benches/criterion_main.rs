@@ -1,5 +1,6 @@
 mod base64_simd;
 mod box_error;
+mod definition_cache;
 mod format_duration;
 mod hashmap_vec;
 mod json_raw_value;
@@ -13,6 +14,7 @@ criterion::criterion_group!(
     benches,
     base64_simd::bench,
     box_error::bench,
+    definition_cache::bench,
     format_duration::bench,
     hashmap_vec::bench,
     json_raw_value::bench,
@@ -0,0 +1,61 @@
+use zino_core::{
+    extension::JsonObjectExt,
+    model::{Column, DefinitionCache},
+    JsonValue, Map,
+};
+
+pub fn bench(c: &mut criterion::Criterion) {
+    let columns = [
+        ("id", "Uuid"),
+        ("name", "String"),
+        ("namespace", "String"),
+        ("visibility", "String"),
+        ("status", "String"),
+        ("description", "String"),
+        ("email", "Option<String>"),
+        ("tags", "Vec<String>"),
+        ("score", "f64"),
+        ("balance", "Decimal"),
+        ("extra", "Map"),
+        ("created_at", "DateTime"),
+        ("updated_at", "DateTime"),
+        ("version", "u64"),
+        ("edition", "u32"),
+    ]
+    .into_iter()
+    .map(|(name, type_name)| {
+        let mut column = Column::new(name, type_name, true);
+        column.set_comment("A column for the benchmark");
+        column.set_extra_attribute("label", name);
+        column
+    })
+    .collect::<Vec<_>>();
+    let build_definition = || {
+        let mut definition = Map::new();
+        definition.upsert("type", "object");
+        let required_fields = columns
+            .iter()
+            .filter(|col| col.is_required())
+            .map(|col| col.name())
+            .collect::<Vec<_>>();
+        definition.upsert("required", required_fields);
+
+        let mut properties = Map::new();
+        for col in columns.iter() {
+            properties.upsert(col.name(), col.definition());
+        }
+        definition.upsert("properties", properties);
+        JsonValue::from(definition)
+    };
+    c.bench_function("model_definition_build", |b| b.iter(build_definition));
+
+    let cache = DefinitionCache::new();
+    c.bench_function("model_definition_cached", |b| {
+        b.iter(|| {
+            cache
+                .get_or_insert_with("definition:insert", build_definition)
+                .as_ref()
+                .clone()
+        })
+    });
+}
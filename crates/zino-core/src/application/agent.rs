@@ -1,8 +1,11 @@
-use super::Application;
-use crate::schedule::AsyncScheduler;
+use super::{Application, ComputePool};
+use crate::{error::Error, schedule::AsyncScheduler, trace::TaskContext};
+use std::future::Future;
+use tokio::task::JoinHandle;
+use tracing::Instrument;
 
 #[cfg(feature = "http-client")]
-use crate::{error::Error, Map};
+use crate::Map;
 
 /// An application agent with no routes.
 #[derive(Debug, Clone, Copy)]
@@ -21,6 +24,34 @@ impl Application for Agent {
 }
 
 impl Agent {
+    /// Executes a CPU-bound closure in the shared [`ComputePool`]
+    /// and waits for its output asynchronously.
+    #[inline]
+    pub async fn compute<F, T>(f: F) -> Result<T, Error>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        ComputePool::execute(f).await
+    }
+
+    /// Spawns a future onto the Tokio runtime in the scope of the current [`TaskContext`],
+    /// so that the logs inside the task carry the request ID and trace ID.
+    ///
+    /// The future is instrumented with the current span if there is no task context.
+    pub fn spawn_traced<F>(future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        if let Some(ctx) = TaskContext::current() {
+            let span = ctx.span();
+            tokio::spawn(ctx.scope(future).instrument(span))
+        } else {
+            tokio::spawn(future.in_current_span())
+        }
+    }
+
     /// Gets the shared HTTP client.
     #[cfg(feature = "http-client")]
     #[inline]
@@ -0,0 +1,519 @@
+use crate::{error::Error, extension::TomlTableExt, state::State, warn, LazyLock, Map};
+use parking_lot::Mutex;
+use reqwest::Url;
+use std::{
+    collections::HashMap,
+    fmt,
+    time::{Duration, Instant},
+};
+use toml::value::Table;
+
+/// Global access to the circuit breakers for outgoing HTTP requests.
+///
+/// Failures are tracked per host (with the port if it is explicitly specified),
+/// and all the requests built by [`Agent::request_builder`](super::Agent::request_builder)
+/// share the same breakers. It can be configured in the `[http-client.circuit-breaker]` table:
+///
+/// ```toml
+/// [http-client.circuit-breaker]
+/// failure-threshold = 5
+/// error-rate = 0.5
+/// min-requests = 20
+/// window = "1m"
+/// cooldown = "30s"
+/// half-open-max-probes = 1
+///
+/// [http-client.circuit-breaker.hosts."api.example.com"]
+/// failure-threshold = 3
+/// cooldown = "1m"
+/// ```
+///
+/// Connection errors and responses with a server error status count as failures.
+/// When a breaker is open, the requests fail fast with an error whose context
+/// is [`CircuitOpen`] until the cooldown elapses; then a limited number of probes
+/// are allowed to decide whether the breaker should be closed or reopened.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CircuitBreaker;
+
+impl CircuitBreaker {
+    /// Returns `true` if the circuit breakers are enabled.
+    #[inline]
+    pub fn is_enabled() -> bool {
+        SHARED_CIRCUIT_BREAKER_CONFIG.enabled
+    }
+
+    /// Returns the state of the breaker with the key.
+    pub fn state(key: &str) -> CircuitState {
+        CIRCUIT_ENTRIES
+            .lock()
+            .get(key)
+            .map(|entry| entry.state)
+            .unwrap_or_default()
+    }
+
+    /// Lists the breakers with their states and counters.
+    pub fn list() -> Vec<Map> {
+        let now = Instant::now();
+        let entries = CIRCUIT_ENTRIES.lock();
+        let mut keys = entries.keys().collect::<Vec<_>>();
+        keys.sort();
+        keys.into_iter()
+            .map(|key| entries[key].to_map(key, now))
+            .collect()
+    }
+
+    /// Resets the breaker with the key to the closed state.
+    /// Returns `false` if the breaker can not be found.
+    pub fn reset(key: &str) -> bool {
+        if let Some(entry) = CIRCUIT_ENTRIES.lock().get_mut(key) {
+            if let Some(state) = entry.reset() {
+                emit_transition(key, state);
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Resets all the breakers to the closed state.
+    pub fn reset_all() {
+        for (key, entry) in CIRCUIT_ENTRIES.lock().iter_mut() {
+            if let Some(state) = entry.reset() {
+                emit_transition(key, state);
+            }
+        }
+    }
+
+    /// Checks whether a request to the URL is permitted.
+    pub(crate) fn try_acquire(url: &str) -> Result<(), Error> {
+        let config = &*SHARED_CIRCUIT_BREAKER_CONFIG;
+        if !config.enabled {
+            return Ok(());
+        }
+        let Some(key) = Url::parse(url).ok().and_then(|url| circuit_key(&url)) else {
+            return Ok(());
+        };
+
+        let policy = config.policy(&key);
+        let now = Instant::now();
+        let mut entries = CIRCUIT_ENTRIES.lock();
+        let entry = entries
+            .entry(key.clone())
+            .or_insert_with(|| CircuitEntry::new(now));
+        let state = entry.state;
+        let result = entry.try_acquire(policy, now);
+        if entry.state != state {
+            emit_transition(&key, entry.state);
+        }
+        result.map_err(|retry_after| {
+            let mut err = warn!(
+                "503 Service Unavailable: circuit breaker for `{}` is open",
+                key
+            );
+            err.set_context(CircuitOpen { key, retry_after });
+            err
+        })
+    }
+
+    /// Records the outcome of a request to the URL.
+    pub(crate) fn record(url: &Url, success: bool) {
+        let config = &*SHARED_CIRCUIT_BREAKER_CONFIG;
+        if !config.enabled {
+            return;
+        }
+        let Some(key) = circuit_key(url) else {
+            return;
+        };
+
+        let policy = config.policy(&key);
+        let now = Instant::now();
+        let mut entries = CIRCUIT_ENTRIES.lock();
+        let entry = entries
+            .entry(key.clone())
+            .or_insert_with(|| CircuitEntry::new(now));
+        if let Some(state) = entry.record(policy, success, now) {
+            emit_transition(&key, state);
+        }
+    }
+}
+
+/// State of a circuit breaker.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are permitted and the failures are tracked.
+    #[default]
+    Closed,
+    /// Requests fail fast until the cooldown elapses.
+    Open,
+    /// A limited number of probes are permitted.
+    HalfOpen,
+}
+
+impl CircuitState {
+    /// Returns the state as a `str`.
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::HalfOpen => "half-open",
+        }
+    }
+}
+
+impl fmt::Display for CircuitState {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Context of the error returned when a request is rejected by an open circuit breaker.
+#[derive(Debug, Clone)]
+pub struct CircuitOpen {
+    /// Key of the breaker.
+    key: String,
+    /// Remaining time before a probe is permitted.
+    retry_after: Duration,
+}
+
+impl CircuitOpen {
+    /// Returns the key of the breaker.
+    #[inline]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the remaining time before a probe is permitted.
+    #[inline]
+    pub fn retry_after(&self) -> Duration {
+        self.retry_after
+    }
+}
+
+/// Policy of a circuit breaker.
+#[derive(Debug, Clone, Copy)]
+struct CircuitPolicy {
+    /// Number of consecutive failures to open the breaker.
+    failure_threshold: u32,
+    /// Error rate in the window to open the breaker.
+    error_rate: Option<f64>,
+    /// Min number of requests in the window for the error rate to take effect.
+    min_requests: u32,
+    /// Duration of the window to compute the error rate.
+    window: Duration,
+    /// Duration of the open state.
+    cooldown: Duration,
+    /// Max number of probes in the half-open state.
+    half_open_max_probes: u32,
+}
+
+impl CircuitPolicy {
+    /// Creates a new instance with the default settings.
+    fn new() -> Self {
+        Self {
+            failure_threshold: 5,
+            error_rate: None,
+            min_requests: 20,
+            window: Duration::from_secs(60),
+            cooldown: Duration::from_secs(30),
+            half_open_max_probes: 1,
+        }
+    }
+
+    /// Overrides the settings with the config.
+    fn with_config(mut self, config: &Table) -> Self {
+        if let Some(threshold) = config.get_u32("failure-threshold") {
+            self.failure_threshold = threshold.max(1);
+        }
+        if let Some(error_rate) = config.get_f64("error-rate") {
+            self.error_rate = Some(error_rate.clamp(0.0, 1.0));
+        }
+        if let Some(min_requests) = config.get_u32("min-requests") {
+            self.min_requests = min_requests.max(1);
+        }
+        if let Some(window) = config.get_duration("window") {
+            self.window = window;
+        }
+        if let Some(cooldown) = config.get_duration("cooldown") {
+            self.cooldown = cooldown;
+        }
+        if let Some(max_probes) = config.get_u32("half-open-max-probes") {
+            self.half_open_max_probes = max_probes.max(1);
+        }
+        self
+    }
+}
+
+/// Config of the circuit breakers.
+#[derive(Debug)]
+struct CircuitBreakerConfig {
+    /// A flag to indicate whether the breakers are enabled.
+    enabled: bool,
+    /// Default policy.
+    policy: CircuitPolicy,
+    /// Per-host policies.
+    hosts: HashMap<String, CircuitPolicy>,
+}
+
+impl CircuitBreakerConfig {
+    /// Returns the policy for the key.
+    #[inline]
+    fn policy(&self, key: &str) -> CircuitPolicy {
+        self.hosts.get(key).copied().unwrap_or(self.policy)
+    }
+}
+
+/// Tracked state of a circuit breaker.
+#[derive(Debug)]
+struct CircuitEntry {
+    /// Current state.
+    state: CircuitState,
+    /// Time when the state changed.
+    changed_at: Instant,
+    /// Number of consecutive failures.
+    consecutive_failures: u32,
+    /// Start time of the current window.
+    window_start: Instant,
+    /// Number of requests in the current window.
+    requests: u32,
+    /// Number of failures in the current window.
+    failures: u32,
+    /// Number of probes permitted in the half-open state.
+    probes: u32,
+}
+
+impl CircuitEntry {
+    /// Creates a new instance in the closed state.
+    fn new(now: Instant) -> Self {
+        Self {
+            state: CircuitState::Closed,
+            changed_at: now,
+            consecutive_failures: 0,
+            window_start: now,
+            requests: 0,
+            failures: 0,
+            probes: 0,
+        }
+    }
+
+    /// Tries to permit a request, returning the remaining time of the cooldown if rejected.
+    fn try_acquire(&mut self, policy: CircuitPolicy, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.changed_at);
+        match self.state {
+            CircuitState::Closed => Ok(()),
+            CircuitState::Open => {
+                if elapsed >= policy.cooldown {
+                    self.transit(CircuitState::HalfOpen, now);
+                    self.probes = 1;
+                    Ok(())
+                } else {
+                    Err(policy.cooldown - elapsed)
+                }
+            }
+            CircuitState::HalfOpen => {
+                if self.probes < policy.half_open_max_probes {
+                    self.probes += 1;
+                    Ok(())
+                } else if elapsed >= policy.cooldown {
+                    // The probes have not been recorded, so we permit new ones.
+                    self.changed_at = now;
+                    self.probes = 1;
+                    Ok(())
+                } else {
+                    Err(policy.cooldown - elapsed)
+                }
+            }
+        }
+    }
+
+    /// Records the outcome of a request, returning the new state if it has changed.
+    fn record(
+        &mut self,
+        policy: CircuitPolicy,
+        success: bool,
+        now: Instant,
+    ) -> Option<CircuitState> {
+        match self.state {
+            CircuitState::Closed => {
+                if now.saturating_duration_since(self.window_start) >= policy.window {
+                    self.window_start = now;
+                    self.requests = 0;
+                    self.failures = 0;
+                }
+                self.requests += 1;
+                if success {
+                    self.consecutive_failures = 0;
+                    return None;
+                }
+                self.failures += 1;
+                self.consecutive_failures += 1;
+
+                let exceeds_error_rate = policy.error_rate.is_some_and(|error_rate| {
+                    self.requests >= policy.min_requests
+                        && f64::from(self.failures) >= error_rate * f64::from(self.requests)
+                });
+                if self.consecutive_failures >= policy.failure_threshold || exceeds_error_rate {
+                    self.transit(CircuitState::Open, now);
+                    Some(CircuitState::Open)
+                } else {
+                    None
+                }
+            }
+            CircuitState::HalfOpen => {
+                let state = if success {
+                    CircuitState::Closed
+                } else {
+                    CircuitState::Open
+                };
+                self.transit(state, now);
+                Some(state)
+            }
+            CircuitState::Open => None,
+        }
+    }
+
+    /// Resets to the closed state, returning the new state if it has changed.
+    fn reset(&mut self) -> Option<CircuitState> {
+        let changed = self.state != CircuitState::Closed;
+        self.transit(CircuitState::Closed, Instant::now());
+        changed.then_some(CircuitState::Closed)
+    }
+
+    /// Transits to the state and clears the counters.
+    fn transit(&mut self, state: CircuitState, now: Instant) {
+        self.state = state;
+        self.changed_at = now;
+        self.consecutive_failures = 0;
+        self.window_start = now;
+        self.requests = 0;
+        self.failures = 0;
+        self.probes = 0;
+    }
+
+    /// Converts `self` to a map.
+    fn to_map(&self, key: &str, now: Instant) -> Map {
+        let mut map = Map::new();
+        map.insert("key".to_owned(), key.into());
+        map.insert("state".to_owned(), self.state.as_str().into());
+        map.insert(
+            "elapsed_millis".to_owned(),
+            now.saturating_duration_since(self.changed_at)
+                .as_millis()
+                .to_string()
+                .into(),
+        );
+        map.insert(
+            "consecutive_failures".to_owned(),
+            self.consecutive_failures.into(),
+        );
+        map.insert("requests".to_owned(), self.requests.into());
+        map.insert("failures".to_owned(), self.failures.into());
+        map
+    }
+}
+
+/// Returns the key of the breaker for the URL.
+fn circuit_key(url: &Url) -> Option<String> {
+    let host = url.host_str()?;
+    if let Some(port) = url.port() {
+        Some(format!("{host}:{port}"))
+    } else {
+        Some(host.to_owned())
+    }
+}
+
+/// Emits the tracing event and metrics for a state transition.
+fn emit_transition(key: &str, state: CircuitState) {
+    match state {
+        CircuitState::Open => {
+            tracing::warn!(key, state = state.as_str(), "circuit breaker is open")
+        }
+        _ => tracing::info!(key, state = state.as_str(), "circuit breaker state changed"),
+    }
+    #[cfg(feature = "metrics")]
+    metrics::counter!(
+        "zino_http_client_circuit_transitions_total",
+        "key" => key.to_owned(),
+        "state" => state.as_str(),
+    )
+    .increment(1);
+}
+
+/// Config of the circuit breakers.
+static SHARED_CIRCUIT_BREAKER_CONFIG: LazyLock<CircuitBreakerConfig> = LazyLock::new(|| {
+    let mut config = CircuitBreakerConfig {
+        enabled: false,
+        policy: CircuitPolicy::new(),
+        hosts: HashMap::new(),
+    };
+    if let Some(circuit_breaker) = State::shared()
+        .get_config("http-client")
+        .and_then(|config| config.get_table("circuit-breaker"))
+    {
+        config.enabled = circuit_breaker.get_bool("enabled").unwrap_or(true);
+        config.policy = config.policy.with_config(circuit_breaker);
+        if let Some(hosts) = circuit_breaker.get_table("hosts") {
+            for (host, value) in hosts {
+                if let Some(table) = value.as_table() {
+                    let policy = config.policy.with_config(table);
+                    config.hosts.insert(host.to_owned(), policy);
+                }
+            }
+        }
+    }
+    config
+});
+
+/// Tracked states of the circuit breakers.
+static CIRCUIT_ENTRIES: LazyLock<Mutex<HashMap<String, CircuitEntry>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[cfg(test)]
+mod tests {
+    use super::{CircuitEntry, CircuitPolicy, CircuitState};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn it_opens_and_recovers_the_circuit() {
+        let policy = CircuitPolicy::new();
+        let start = Instant::now();
+        let mut entry = CircuitEntry::new(start);
+        for _ in 0..4 {
+            assert!(entry.try_acquire(policy, start).is_ok());
+            assert_eq!(entry.record(policy, false, start), None);
+        }
+        assert_eq!(entry.record(policy, false, start), Some(CircuitState::Open));
+        assert!(entry.try_acquire(policy, start).is_err());
+
+        let probe_time = start + policy.cooldown;
+        assert!(entry.try_acquire(policy, probe_time).is_ok());
+        assert_eq!(entry.state, CircuitState::HalfOpen);
+        assert!(entry.try_acquire(policy, probe_time).is_err());
+        assert_eq!(
+            entry.record(policy, false, probe_time),
+            Some(CircuitState::Open)
+        );
+
+        let probe_time = probe_time + policy.cooldown + Duration::from_secs(1);
+        assert!(entry.try_acquire(policy, probe_time).is_ok());
+        assert_eq!(
+            entry.record(policy, true, probe_time),
+            Some(CircuitState::Closed)
+        );
+        assert!(entry.try_acquire(policy, probe_time).is_ok());
+    }
+
+    #[test]
+    fn it_opens_the_circuit_by_error_rate() {
+        let mut policy = CircuitPolicy::new();
+        policy.error_rate = Some(0.5);
+        policy.min_requests = 4;
+        let now = Instant::now();
+        let mut entry = CircuitEntry::new(now);
+        assert_eq!(entry.record(policy, true, now), None);
+        assert_eq!(entry.record(policy, false, now), None);
+        assert_eq!(entry.record(policy, true, now), None);
+        assert_eq!(entry.record(policy, false, now), Some(CircuitState::Open));
+    }
+}
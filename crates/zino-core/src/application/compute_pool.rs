@@ -0,0 +1,252 @@
+use crate::{
+    error::{Error, ErrorKind},
+    state::{self, State},
+    warn, LazyLock,
+};
+use parking_lot::Mutex;
+use serde::Deserialize;
+use std::{
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering::Relaxed},
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Arc,
+    },
+    task::{Context, Poll, Waker},
+    thread,
+    time::Instant,
+};
+
+/// A managed pool of worker threads for the CPU-bound tasks.
+///
+/// Hashing passwords, encrypting large files or serializing big exports on the async runtime
+/// threads increases the latency of unrelated requests. The closures executed by
+/// [`Agent::compute()`](super::Agent::compute) run in a bounded pool instead,
+/// which can be configured in the `[runtime]` table:
+///
+/// ```toml
+/// [runtime]
+/// compute-threads = 4
+/// compute-queue-size = 256
+/// compute-offload-threshold = 1048576
+/// ```
+///
+/// The number of threads defaults to the available parallelism, and the queue size defaults to
+/// 64 times the number of threads. When the queue is full, the task is rejected immediately
+/// with an error of [`ErrorKind::Unavailable`] instead of growing unboundedly.
+/// The offload threshold is the size in bytes above which the built-in helpers
+/// prefer the pool to an inline computation.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ComputePool;
+
+impl ComputePool {
+    /// Executes a CPU-bound closure in the pool and waits for its output asynchronously.
+    ///
+    /// It returns an error of [`ErrorKind::Unavailable`] if the queue is full,
+    /// and an error of [`ErrorKind::Internal`] if the closure panics.
+    pub async fn execute<F, T>(f: F) -> Result<T, Error>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let pool = &*SHARED_COMPUTE_POOL;
+        let slot = Arc::new(Mutex::new(TaskSlot::default()));
+        let task_slot = slot.clone();
+        let queued_at = Instant::now();
+        let task: Task = Box::new(move || {
+            let start_time = Instant::now();
+            let result = panic::catch_unwind(AssertUnwindSafe(f));
+            let wait_time = start_time.duration_since(queued_at);
+            let execution_time = start_time.elapsed();
+            let wait_time_millis = wait_time.as_millis();
+            let execution_time_millis = execution_time.as_millis();
+            tracing::debug!(
+                wait_time_millis,
+                execution_time_millis,
+                "compute task finished"
+            );
+            #[cfg(feature = "metrics")]
+            {
+                metrics::histogram!("zino_compute_task_wait_seconds")
+                    .record(wait_time.as_secs_f64());
+                metrics::histogram!("zino_compute_task_duration_seconds")
+                    .record(execution_time.as_secs_f64());
+            }
+
+            SHARED_COMPUTE_POOL.pending_tasks.fetch_sub(1, Relaxed);
+
+            let mut slot = task_slot.lock();
+            slot.result = Some(result.map_err(|_| ()));
+            if let Some(waker) = slot.waker.take() {
+                waker.wake();
+            }
+        });
+
+        pool.pending_tasks.fetch_add(1, Relaxed);
+        match pool.sender.try_send(task) {
+            Ok(()) => (),
+            Err(TrySendError::Full(_)) => {
+                pool.pending_tasks.fetch_sub(1, Relaxed);
+                #[cfg(feature = "metrics")]
+                metrics::counter!("zino_compute_tasks_rejected_total").increment(1);
+                let err = warn!("503 Service Unavailable: the compute pool is overloaded");
+                return Err(err.with_kind(ErrorKind::Unavailable));
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                pool.pending_tasks.fetch_sub(1, Relaxed);
+                let err = warn!("503 Service Unavailable: the compute pool has been shut down");
+                return Err(err.with_kind(ErrorKind::Unavailable));
+            }
+        }
+
+        TaskOutput { slot }
+            .await
+            .map_err(|_| warn!("the compute task panicked"))
+    }
+
+    /// Returns `true` if a computation on the data of the size should be offloaded to the pool.
+    #[inline]
+    pub fn should_offload(size: usize) -> bool {
+        size >= SHARED_COMPUTE_POOL.offload_threshold
+    }
+
+    /// Returns the number of worker threads.
+    #[inline]
+    pub fn num_threads() -> usize {
+        SHARED_COMPUTE_POOL.num_threads
+    }
+
+    /// Returns the max number of the queued tasks.
+    #[inline]
+    pub fn queue_size() -> usize {
+        SHARED_COMPUTE_POOL.queue_size
+    }
+
+    /// Returns the number of the queued or running tasks.
+    #[inline]
+    pub fn pending_tasks() -> usize {
+        SHARED_COMPUTE_POOL.pending_tasks.load(Relaxed)
+    }
+}
+
+/// A type-erased task.
+type Task = Box<dyn FnOnce() + Send>;
+
+/// A slot for the task output.
+struct TaskSlot<T> {
+    /// Task result. The error indicates that the task panicked.
+    result: Option<Result<T, ()>>,
+    /// Waker for the awaiting future.
+    waker: Option<Waker>,
+}
+
+impl<T> Default for TaskSlot<T> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            result: None,
+            waker: None,
+        }
+    }
+}
+
+/// A future for the task output.
+struct TaskOutput<T> {
+    /// Shared slot.
+    slot: Arc<Mutex<TaskSlot<T>>>,
+}
+
+impl<T> Future for TaskOutput<T> {
+    type Output = Result<T, ()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.slot.lock();
+        if let Some(result) = slot.result.take() {
+            Poll::Ready(result)
+        } else {
+            slot.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+/// Shared pool of worker threads.
+struct SharedComputePool {
+    /// Sender of the tasks.
+    sender: SyncSender<Task>,
+    /// Number of worker threads.
+    num_threads: usize,
+    /// Max number of the queued tasks.
+    queue_size: usize,
+    /// Size in bytes above which a computation should be offloaded.
+    offload_threshold: usize,
+    /// Number of the queued or running tasks.
+    pending_tasks: AtomicUsize,
+}
+
+/// Settings of the compute pool in the `[runtime]` config.
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct ComputeSettings {
+    /// Number of worker threads.
+    compute_threads: usize,
+    /// Max number of the queued tasks.
+    compute_queue_size: Option<usize>,
+    /// Size in bytes above which a computation should be offloaded.
+    #[serde(deserialize_with = "state::deserialize_byte_size")]
+    compute_offload_threshold: u64,
+}
+
+impl Default for ComputeSettings {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            compute_threads: 0,
+            compute_queue_size: None,
+            compute_offload_threshold: 1024 * 1024,
+        }
+    }
+}
+
+/// Runs the tasks received by a worker thread.
+fn run_worker(receiver: &Mutex<Receiver<Task>>) {
+    loop {
+        let task = receiver.lock().recv();
+        match task {
+            Ok(task) => task(),
+            Err(_) => break,
+        }
+    }
+}
+
+/// Shared compute pool.
+static SHARED_COMPUTE_POOL: LazyLock<SharedComputePool> = LazyLock::new(|| {
+    let settings = State::shared()
+        .get_config_as::<ComputeSettings>("runtime")
+        .unwrap_or_default();
+    let num_threads = Some(settings.compute_threads)
+        .filter(|&num_threads| num_threads > 0)
+        .unwrap_or_else(|| thread::available_parallelism().map_or(4, |n| n.get()));
+    let queue_size = settings.compute_queue_size.unwrap_or(num_threads * 64);
+    let offload_threshold = settings.compute_offload_threshold as usize;
+
+    let (sender, receiver) = mpsc::sync_channel::<Task>(queue_size);
+    let receiver = Arc::new(Mutex::new(receiver));
+    for index in 0..num_threads {
+        let receiver = receiver.clone();
+        thread::Builder::new()
+            .name(format!("zino-compute-{index}"))
+            .spawn(move || run_worker(&receiver))
+            .unwrap_or_else(|err| panic!("fail to spawn the compute thread: {err}"));
+    }
+    tracing::info!(num_threads, queue_size, "compute pool initialized");
+    SharedComputePool {
+        sender,
+        num_threads,
+        queue_size,
+        offload_threshold,
+        pending_tasks: AtomicUsize::new(0),
+    }
+});
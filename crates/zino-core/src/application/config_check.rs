@@ -0,0 +1,25 @@
+use super::Application;
+use std::{env, process};
+
+/// Validates the config and emits the startup report.
+///
+/// With the `--check-config` flag, it only validates the config and exits
+/// with a non-zero code if there are any errors, so that it can be used to gate the deployments.
+/// The strict mode rejecting the unknown keys is enabled by the `--strict-config` flag
+/// or the environment variable `ZINO_APP_CONFIG_STRICT=true`.
+pub(super) fn init<APP: Application + ?Sized>() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let check_only = args.iter().any(|arg| arg == "--check-config");
+    let strict = args.iter().any(|arg| arg == "--strict-config")
+        || env::var("ZINO_APP_CONFIG_STRICT").is_ok_and(|s| s == "true" || s == "1");
+    let report = APP::shared_state().check_config(strict);
+    if check_only {
+        print!("{report}");
+        process::exit(i32::from(report.has_errors()));
+    }
+
+    report.emit();
+    if strict && report.has_errors() {
+        panic!("invalid config in the strict mode:\n{report}");
+    }
+}
@@ -0,0 +1,477 @@
+use super::http_client;
+use crate::{error::Error, extension::TomlTableExt, trace::TraceContext, warn, JsonValue, Map};
+use parking_lot::RwLock;
+use reqwest::{Response, Url};
+use std::{
+    collections::BTreeSet,
+    net::{IpAddr, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering::Relaxed},
+        Arc, Weak,
+    },
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use toml::Table;
+
+/// A pool of the endpoints for a service with client-side load balancing.
+///
+/// The endpoints are either listed explicitly or resolved from the DNS name of the base URL
+/// periodically, so that the requests are not pinned to a dead IP address. Each endpoint
+/// is ejected for a cooldown after consecutive failures, i.e. transport errors or `5xx` responses,
+/// and the requests are distributed among the healthy endpoints in a round-robin fashion.
+/// If all the endpoints have been ejected, they are tried in turn anyway.
+/// With the `sticky` flag, the requests keep going to the same endpoint until it is ejected.
+///
+/// The resolved endpoints replace the host with the IP address and send the original host
+/// in the `Host` header, so the certificate should cover the IP address for HTTPS.
+///
+/// ```toml
+/// base-url = "http://orders.internal:8080/api"
+/// endpoints = ["http://10.0.0.1:8080", "http://10.0.0.2:8080"]
+/// resolve-interval = "30s"
+/// max-failures = 3
+/// ejection-cooldown = "30s"
+/// sticky = false
+/// ```
+#[derive(Debug, Clone)]
+pub struct EndpointPool {
+    /// Shared state.
+    inner: Arc<PoolInner>,
+}
+
+impl EndpointPool {
+    /// Creates a new instance with the endpoints.
+    pub fn new(endpoints: Vec<Url>) -> Self {
+        let endpoints = endpoints
+            .into_iter()
+            .map(|url| Arc::new(EndpointState::new(url, None)))
+            .collect();
+        Self {
+            inner: Arc::new(PoolInner::new(None, endpoints)),
+        }
+    }
+
+    /// Creates a new instance which resolves the DNS name of the base URL
+    /// and re-resolves it in a background thread with the interval.
+    pub fn resolve(base_url: Url, interval: Duration) -> Self {
+        let inner = Arc::new(PoolInner::new(Some(base_url), Vec::new()));
+        inner.refresh();
+
+        let pool = Arc::downgrade(&inner);
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+
+            let Some(inner) = Weak::upgrade(&pool) else {
+                break;
+            };
+            inner.refresh();
+        });
+        Self { inner }
+    }
+
+    /// Attempts to construct a new instance from the config.
+    /// Returns `None` if neither `endpoints` nor `resolve-interval` is specified.
+    pub fn try_from_config(base_url: &Url, config: &Table) -> Result<Option<Self>, Error> {
+        let pool = if let Some(endpoints) = config.get_str_array("endpoints") {
+            let endpoints = endpoints
+                .into_iter()
+                .map(|s| s.parse())
+                .collect::<Result<Vec<Url>, _>>()?;
+            if endpoints.is_empty() {
+                return Err(warn!("at least one endpoint should be specified"));
+            }
+            Self::new(endpoints)
+        } else if let Some(interval) = config.get_duration("resolve-interval") {
+            Self::resolve(base_url.clone(), interval)
+        } else {
+            return Ok(None);
+        };
+
+        let mut settings = pool.inner.settings.write();
+        if let Some(max_failures) = config.get_u32("max-failures") {
+            settings.max_failures = max_failures.max(1);
+        }
+        if let Some(cooldown) = config.get_duration("ejection-cooldown") {
+            settings.ejection_cooldown = cooldown;
+        }
+        if let Some(sticky) = config.get_bool("sticky") {
+            settings.sticky = sticky;
+        }
+        drop(settings);
+        Ok(Some(pool))
+    }
+
+    /// Sets the number of consecutive failures to eject an endpoint.
+    #[inline]
+    pub fn max_failures(self, max_failures: u32) -> Self {
+        self.inner.settings.write().max_failures = max_failures.max(1);
+        self
+    }
+
+    /// Sets the cooldown of an ejected endpoint.
+    #[inline]
+    pub fn ejection_cooldown(self, cooldown: Duration) -> Self {
+        self.inner.settings.write().ejection_cooldown = cooldown;
+        self
+    }
+
+    /// Sets the flag to keep the requests affine to one endpoint.
+    #[inline]
+    pub fn sticky(self, sticky: bool) -> Self {
+        self.inner.settings.write().sticky = sticky;
+        self
+    }
+
+    /// Selects an endpoint for the next request.
+    pub fn select(&self) -> Result<Endpoint, Error> {
+        let inner = &self.inner;
+        let endpoints = inner.endpoints.read().clone();
+        if endpoints.is_empty() {
+            return Err(warn!("there are no endpoints available"));
+        }
+
+        let now = now_millis();
+        let num_endpoints = endpoints.len();
+        let sticky = inner.settings.read().sticky;
+        let start = if sticky {
+            inner.cursor.load(Relaxed)
+        } else {
+            inner.cursor.fetch_add(1, Relaxed)
+        };
+        let index = (0..num_endpoints)
+            .map(|offset| (start + offset) % num_endpoints)
+            .find(|&index| endpoints[index].is_healthy(now))
+            .unwrap_or(start % num_endpoints);
+        if sticky && index != start % num_endpoints {
+            inner.cursor.store(index, Relaxed);
+        }
+        Ok(Endpoint {
+            state: endpoints[index].clone(),
+            pool: Arc::downgrade(inner),
+        })
+    }
+
+    /// Makes an HTTP request to the path relative to the endpoints.
+    /// The outcome is recorded for the health of the selected endpoint,
+    /// and the endpoint is included in the error message if it fails.
+    pub async fn fetch(&self, path: &str, options: Option<&Map>) -> Result<Response, Error> {
+        let endpoint = self.select()?;
+        let url = endpoint.url().join(path)?;
+        let mut trace_context = TraceContext::new();
+        trace_context.record_trace_state();
+
+        let mut request_builder = http_client::request_builder(url.as_str(), options)
+            .map_err(|err| endpoint.wrap_error(err))?
+            .header("traceparent", trace_context.traceparent())
+            .header("tracestate", trace_context.tracestate());
+        if let Some(host) = endpoint.host() {
+            request_builder = request_builder.header("host", host);
+        }
+        endpoint.record_outcome(request_builder.send().await.map_err(Error::from))
+    }
+
+    /// Returns the health status of the endpoints.
+    pub fn status(&self) -> Vec<Map> {
+        let now = now_millis();
+        self.inner
+            .endpoints
+            .read()
+            .iter()
+            .map(|endpoint| {
+                let mut map = Map::new();
+                map.insert("url".to_owned(), endpoint.url.as_str().into());
+                map.insert("healthy".to_owned(), endpoint.is_healthy(now).into());
+                map.insert(
+                    "consecutive_failures".to_owned(),
+                    endpoint.consecutive_failures.load(Relaxed).into(),
+                );
+                let ejected_until = endpoint.ejected_until.load(Relaxed);
+                let value = if ejected_until > now {
+                    JsonValue::from(ejected_until)
+                } else {
+                    JsonValue::Null
+                };
+                map.insert("ejected_until".to_owned(), value);
+                map
+            })
+            .collect()
+    }
+}
+
+/// An endpoint selected from the [`EndpointPool`].
+#[derive(Debug, Clone)]
+pub struct Endpoint {
+    /// State of the endpoint.
+    state: Arc<EndpointState>,
+    /// Pool of the endpoint.
+    pool: Weak<PoolInner>,
+}
+
+impl Endpoint {
+    /// Returns the URL of the endpoint.
+    #[inline]
+    pub fn url(&self) -> &Url {
+        &self.state.url
+    }
+
+    /// Returns the original host for the `Host` header if the endpoint has been resolved.
+    #[inline]
+    pub fn host(&self) -> Option<&str> {
+        self.state.host.as_deref()
+    }
+
+    /// Rewrites the scheme, host and port of the URL with the endpoint.
+    pub fn rewrite_url(&self, url: &Url) -> Url {
+        let endpoint_url = self.url();
+        let mut url = url.clone();
+        url.set_scheme(endpoint_url.scheme()).ok();
+        url.set_host(endpoint_url.host_str()).ok();
+        url.set_port(endpoint_url.port()).ok();
+        url
+    }
+
+    /// Records a successful request.
+    pub fn record_success(&self) {
+        let failures = self.state.consecutive_failures.swap(0, Relaxed);
+        let ejected_until = self.state.ejected_until.swap(0, Relaxed);
+        if ejected_until > 0 {
+            tracing::warn!(
+                endpoint = self.state.url.as_str(),
+                previous_failures = failures,
+                "endpoint has been restored"
+            );
+        }
+    }
+
+    /// Records a failed request.
+    pub fn record_failure(&self) {
+        let failures = self.state.consecutive_failures.fetch_add(1, Relaxed) + 1;
+        let Some(pool) = self.pool.upgrade() else {
+            return;
+        };
+        let settings = pool.settings.read();
+        if failures >= settings.max_failures {
+            let cooldown =
+                u64::try_from(settings.ejection_cooldown.as_millis()).unwrap_or_default();
+            let ejected_until = now_millis().saturating_add(cooldown);
+            let previous = self.state.ejected_until.swap(ejected_until, Relaxed);
+            if previous <= now_millis() {
+                tracing::warn!(
+                    endpoint = self.state.url.as_str(),
+                    consecutive_failures = failures,
+                    cooldown_millis = cooldown,
+                    "endpoint has been ejected"
+                );
+            }
+        }
+    }
+
+    /// Records the outcome of a request, and includes the endpoint in the error message.
+    /// The transport errors and `5xx` responses are treated as failures.
+    pub fn record_outcome(&self, result: Result<Response, Error>) -> Result<Response, Error> {
+        match result {
+            Ok(response) => {
+                if response.status().is_server_error() {
+                    self.record_failure();
+                } else {
+                    self.record_success();
+                }
+                Ok(response)
+            }
+            Err(err) => {
+                self.record_failure();
+                Err(self.wrap_error(err))
+            }
+        }
+    }
+
+    /// Wraps the error with the endpoint.
+    #[inline]
+    pub fn wrap_error(&self, err: Error) -> Error {
+        warn!("fail to request the endpoint `{}`: {}", self.state.url, err)
+    }
+}
+
+/// Shared state of the pool.
+#[derive(Debug)]
+struct PoolInner {
+    /// Base URL to be resolved.
+    base_url: Option<Url>,
+    /// Endpoints.
+    endpoints: RwLock<Arc<Vec<Arc<EndpointState>>>>,
+    /// Cursor of the round-robin selection.
+    cursor: AtomicUsize,
+    /// Settings.
+    settings: RwLock<PoolSettings>,
+}
+
+impl PoolInner {
+    /// Creates a new instance.
+    fn new(base_url: Option<Url>, endpoints: Vec<Arc<EndpointState>>) -> Self {
+        Self {
+            base_url,
+            endpoints: RwLock::new(Arc::new(endpoints)),
+            cursor: AtomicUsize::new(0),
+            settings: RwLock::new(PoolSettings::default()),
+        }
+    }
+
+    /// Resolves the DNS name of the base URL and updates the endpoints.
+    /// The health states of the endpoints which are still present are preserved.
+    fn refresh(&self) {
+        let Some(base_url) = self.base_url.as_ref() else {
+            return;
+        };
+        let Some(host) = base_url.host_str() else {
+            return;
+        };
+        let port = base_url.port_or_known_default().unwrap_or(80);
+        let addrs = match (host, port).to_socket_addrs() {
+            Ok(addrs) => addrs.map(|addr| addr.ip()).collect::<BTreeSet<IpAddr>>(),
+            Err(err) => {
+                tracing::error!(host, "fail to resolve the endpoints: {err}");
+                return;
+            }
+        };
+        if addrs.is_empty() {
+            tracing::error!(host, "no endpoints have been resolved");
+            return;
+        }
+
+        let previous = self.endpoints.read().clone();
+        let endpoints = addrs
+            .into_iter()
+            .filter_map(|ip| {
+                let mut url = base_url.clone();
+                url.set_ip_host(ip).ok()?;
+                url.set_path("/");
+                url.set_query(None);
+                let endpoint = previous
+                    .iter()
+                    .find(|endpoint| endpoint.url == url)
+                    .cloned()
+                    .unwrap_or_else(|| {
+                        tracing::info!(host, endpoint = url.as_str(), "endpoint has been added");
+                        Arc::new(EndpointState::new(url, Some(host.to_owned())))
+                    });
+                Some(endpoint)
+            })
+            .collect::<Vec<_>>();
+        for endpoint in previous.iter() {
+            if !endpoints.iter().any(|e| e.url == endpoint.url) {
+                tracing::info!(
+                    host,
+                    endpoint = endpoint.url.as_str(),
+                    "endpoint has been removed"
+                );
+            }
+        }
+        *self.endpoints.write() = Arc::new(endpoints);
+    }
+}
+
+/// Settings of the pool.
+#[derive(Debug)]
+struct PoolSettings {
+    /// Number of consecutive failures to eject an endpoint.
+    max_failures: u32,
+    /// Cooldown of an ejected endpoint.
+    ejection_cooldown: Duration,
+    /// A flag to keep the requests affine to one endpoint.
+    sticky: bool,
+}
+
+impl Default for PoolSettings {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_failures: 3,
+            ejection_cooldown: Duration::from_secs(30),
+            sticky: false,
+        }
+    }
+}
+
+/// Health state of an endpoint.
+#[derive(Debug)]
+struct EndpointState {
+    /// URL of the endpoint.
+    url: Url,
+    /// Original host if the endpoint has been resolved.
+    host: Option<String>,
+    /// Number of consecutive failures.
+    consecutive_failures: AtomicU32,
+    /// Timestamp in milliseconds until which the endpoint is ejected.
+    ejected_until: AtomicU64,
+}
+
+impl EndpointState {
+    /// Creates a new instance.
+    fn new(url: Url, host: Option<String>) -> Self {
+        Self {
+            url,
+            host,
+            consecutive_failures: AtomicU32::new(0),
+            ejected_until: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns `true` if the endpoint is not ejected.
+    #[inline]
+    fn is_healthy(&self, now: u64) -> bool {
+        self.ejected_until.load(Relaxed) <= now
+    }
+}
+
+/// Returns the current timestamp in milliseconds.
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .and_then(|duration| u64::try_from(duration.as_millis()).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EndpointPool;
+    use std::time::Duration;
+
+    #[test]
+    fn it_selects_healthy_endpoints() {
+        let urls = ["http://10.0.0.1:8080", "http://10.0.0.2:8080"];
+        let pool = EndpointPool::new(urls.iter().map(|s| s.parse().unwrap()).collect())
+            .max_failures(2)
+            .ejection_cooldown(Duration::from_secs(60));
+        let first = pool.select().unwrap();
+        let second = pool.select().unwrap();
+        assert_ne!(first.url(), second.url());
+
+        first.record_failure();
+        assert_eq!(pool.select().unwrap().url(), first.url());
+        first.record_failure();
+        for _ in 0..4 {
+            assert_eq!(pool.select().unwrap().url(), second.url());
+        }
+
+        first.record_success();
+        let urls = (0..2).map(|_| pool.select().unwrap().url().clone());
+        assert_eq!(urls.collect::<std::collections::HashSet<_>>().len(), 2);
+    }
+
+    #[test]
+    fn it_keeps_sticky_endpoints() {
+        let urls = ["http://10.0.0.1:8080", "http://10.0.0.2:8080"];
+        let pool = EndpointPool::new(urls.iter().map(|s| s.parse().unwrap()).collect())
+            .max_failures(1)
+            .sticky(true);
+        let endpoint = pool.select().unwrap();
+        assert_eq!(pool.select().unwrap().url(), endpoint.url());
+
+        endpoint.record_failure();
+        let next = pool.select().unwrap();
+        assert_ne!(next.url(), endpoint.url());
+        assert_eq!(pool.select().unwrap().url(), next.url());
+    }
+}
@@ -0,0 +1,418 @@
+use super::Application;
+use crate::{error::Error, extension::TomlTableExt, warn, JsonValue, LazyLock, Map};
+use ahash::{HashMap, HashMapExt};
+use arc_swap::ArcSwap;
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, sync::Arc};
+use toml::Value;
+
+/// A feature flag for shipping code dark and enabling it gradually at runtime.
+///
+/// A flag is evaluated as follows:
+///
+/// - it is off if the flag does not exist or `enabled = false`;
+/// - it is on if the subject is in the allowlist of the users or groups;
+/// - it is on for the given percentage of the subjects if `percentage` is set.
+///   The subject ID is hashed with the flag name stably, so that a subject keeps
+///   its assignment as long as the percentage is not decreased;
+/// - otherwise, it is on if neither the percentage nor the allowlist is set.
+///
+/// The flags are defined in the `[features]` table and applied again after the config has been
+/// reloaded. They can also be overridden at runtime via [`apply_overrides()`](Self::apply_overrides),
+/// for example, by the flags polled from a database table.
+/// The flag table is swapped atomically, so the evaluations do not acquire any locks,
+/// and each change of the flags is recorded as a tracing event.
+///
+/// ```toml
+/// [features]
+/// dark-mode = true
+/// new-checkout = { percentage = 20, users = ["alice"], groups = ["beta"] }
+///
+/// [features.search-v2]
+/// enabled = true
+/// percentage = 2.5
+/// description = "The new search engine"
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureFlag {
+    /// Flag name.
+    name: String,
+    /// A switch to turn off the flag for all the subjects.
+    enabled: bool,
+    /// Percentage of the subjects in the rollout.
+    percentage: Option<f64>,
+    /// Allowlist of the user IDs.
+    users: Vec<String>,
+    /// Allowlist of the groups.
+    groups: Vec<String>,
+    /// Description.
+    description: Option<String>,
+    /// Source of the flag.
+    source: &'static str,
+}
+
+impl FeatureFlag {
+    /// Creates a new instance which is enabled for all the subjects.
+    #[inline]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            enabled: true,
+            percentage: None,
+            users: Vec::new(),
+            groups: Vec::new(),
+            description: None,
+            source: "config",
+        }
+    }
+
+    /// Attempts to create a new instance from a boolean or a table in the config.
+    pub fn try_from_config(name: &str, value: &Value) -> Result<Self, Error> {
+        let mut flag = Self::new(name);
+        match value {
+            Value::Boolean(enabled) => flag.enabled = *enabled,
+            Value::Table(config) => {
+                if let Some(enabled) = config.get_bool("enabled") {
+                    flag.enabled = enabled;
+                }
+                if let Some(value) = config.get("percentage") {
+                    let percentage = value
+                        .as_float()
+                        .or_else(|| value.as_integer().map(|i| i as f64))
+                        .ok_or_else(|| warn!("the percentage of `{}` should be a number", name))?;
+                    flag.set_percentage(percentage);
+                }
+                if let Some(users) = config.get_str_array("users") {
+                    flag.users = users.into_iter().map(|s| s.to_owned()).collect();
+                }
+                if let Some(groups) = config.get_str_array("groups") {
+                    flag.groups = groups.into_iter().map(|s| s.to_owned()).collect();
+                }
+                flag.description = config.get_str("description").map(|s| s.to_owned());
+            }
+            _ => return Err(warn!("invalid value for the feature flag `{}`", name)),
+        }
+        Ok(flag)
+    }
+
+    /// Sets the switch of the flag.
+    #[inline]
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Sets the percentage of the subjects in the rollout.
+    /// The value is clamped to the range `0..=100`.
+    #[inline]
+    pub fn set_percentage(&mut self, percentage: f64) {
+        self.percentage = Some(percentage.clamp(0.0, 100.0));
+    }
+
+    /// Sets the allowlist of the user IDs.
+    #[inline]
+    pub fn set_users(&mut self, users: Vec<String>) {
+        self.users = users;
+    }
+
+    /// Sets the allowlist of the groups.
+    #[inline]
+    pub fn set_groups(&mut self, groups: Vec<String>) {
+        self.groups = groups;
+    }
+
+    /// Sets the description.
+    #[inline]
+    pub fn set_description(&mut self, description: impl Into<String>) {
+        self.description = Some(description.into());
+    }
+
+    /// Returns the flag name.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the source of the flag.
+    #[inline]
+    pub fn source(&self) -> &'static str {
+        self.source
+    }
+
+    /// Evaluates the flag for an optional subject.
+    pub fn evaluate(&self, subject: Option<&FeatureSubject>) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if let Some(subject) = subject {
+            if self.users.iter().any(|user| user == subject.id())
+                || subject
+                    .groups()
+                    .iter()
+                    .any(|group| self.groups.contains(group))
+            {
+                return true;
+            }
+        }
+        match self.percentage {
+            Some(percentage) => match subject.filter(|subject| !subject.id().is_empty()) {
+                Some(subject) => rollout_bucket(&self.name, subject.id()) < percentage,
+                None => percentage >= 100.0,
+            },
+            None => self.users.is_empty() && self.groups.is_empty(),
+        }
+    }
+
+    /// Converts `self` to a JSON object.
+    pub fn to_map(&self) -> Map {
+        let mut map = Map::new();
+        map.insert("name".to_owned(), self.name.as_str().into());
+        map.insert("enabled".to_owned(), self.enabled.into());
+        map.insert("percentage".to_owned(), self.percentage.into());
+        map.insert("users".to_owned(), self.users.clone().into());
+        map.insert("groups".to_owned(), self.groups.clone().into());
+        map.insert("description".to_owned(), self.description.as_deref().into());
+        map.insert("source".to_owned(), self.source.into());
+        map
+    }
+
+    /// Returns `true` if the flag is enabled without a subject.
+    #[inline]
+    pub fn enabled(name: &str) -> bool {
+        FEATURE_FLAGS
+            .load()
+            .get(name)
+            .is_some_and(|flag| flag.evaluate(None))
+    }
+
+    /// Returns `true` if the flag is enabled for the subject.
+    #[inline]
+    pub fn enabled_for(name: &str, subject: &FeatureSubject) -> bool {
+        FEATURE_FLAGS
+            .load()
+            .get(name)
+            .is_some_and(|flag| flag.evaluate(Some(subject)))
+    }
+
+    /// Lists the current flags sorted by name.
+    pub fn list() -> Vec<Map> {
+        let flags = FEATURE_FLAGS.load();
+        let mut flags = flags.values().collect::<Vec<_>>();
+        flags.sort_by(|a, b| a.name.cmp(&b.name));
+        flags.into_iter().map(|flag| flag.to_map()).collect()
+    }
+
+    /// Replaces the overrides from the source. The overrides take precedence over
+    /// the flags defined in the config, and the flags removed from the source
+    /// fall back to the config.
+    pub fn apply_overrides(source: &'static str, flags: Vec<FeatureFlag>) {
+        let mut sources = FLAG_SOURCES.lock();
+        let flags = flags
+            .into_iter()
+            .map(|mut flag| {
+                flag.source = source;
+                flag
+            })
+            .collect();
+        sources.overrides.insert(source, flags);
+        sources.publish();
+    }
+}
+
+/// A subject for evaluating the feature flags.
+#[derive(Debug, Clone, Default)]
+pub struct FeatureSubject {
+    /// Subject ID.
+    id: String,
+    /// Groups of the subject.
+    groups: Vec<String>,
+}
+
+impl FeatureSubject {
+    /// Creates a new instance with the subject ID.
+    #[inline]
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            groups: Vec::new(),
+        }
+    }
+
+    /// Sets the groups of the subject.
+    #[inline]
+    pub fn set_groups(&mut self, groups: Vec<String>) {
+        self.groups = groups;
+    }
+
+    /// Returns the subject ID.
+    #[inline]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the groups of the subject.
+    #[inline]
+    pub fn groups(&self) -> &[String] {
+        &self.groups
+    }
+}
+
+impl From<&str> for FeatureSubject {
+    #[inline]
+    fn from(id: &str) -> Self {
+        Self::new(id)
+    }
+}
+
+impl From<String> for FeatureSubject {
+    #[inline]
+    fn from(id: String) -> Self {
+        Self::new(id)
+    }
+}
+
+/// Sources of the feature flags.
+#[derive(Debug, Default)]
+struct FlagSources {
+    /// Flags defined in the config.
+    config: Vec<FeatureFlag>,
+    /// Overrides keyed by the source.
+    overrides: BTreeMap<&'static str, Vec<FeatureFlag>>,
+}
+
+impl FlagSources {
+    /// Merges the flags and swaps the shared flag table atomically.
+    fn publish(&self) {
+        let mut table = HashMap::new();
+        for flag in self.config.iter().chain(self.overrides.values().flatten()) {
+            table.insert(flag.name.clone(), flag.clone());
+        }
+
+        let previous = FEATURE_FLAGS.swap(Arc::new(table));
+        let current = FEATURE_FLAGS.load();
+        for (name, flag) in current.iter() {
+            if previous.get(name) != Some(flag) {
+                let previous_value = previous
+                    .get(name)
+                    .map(|flag| JsonValue::from(flag.to_map()).to_string());
+                let current_value = JsonValue::from(flag.to_map()).to_string();
+                tracing::warn!(
+                    flag_name = name.as_str(),
+                    source = flag.source,
+                    previous = previous_value,
+                    current = current_value,
+                    "feature flag `{name}` has been changed"
+                );
+            }
+        }
+        for (name, flag) in previous.iter() {
+            if !current.contains_key(name) {
+                tracing::warn!(
+                    flag_name = name.as_str(),
+                    source = flag.source,
+                    "feature flag `{name}` has been removed"
+                );
+            }
+        }
+    }
+}
+
+/// Returns the rollout bucket in the range `[0, 100)` for the subject ID.
+fn rollout_bucket(name: &str, id: &str) -> f64 {
+    let mut hasher = Sha256::new();
+    hasher.update(name.as_bytes());
+    hasher.update(b":");
+    hasher.update(id.as_bytes());
+    let digest = hasher.finalize();
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(bytes) % 10_000) as f64 / 100.0
+}
+
+/// Parses the feature flags in the `[features]` table.
+fn parse_config_flags<APP: Application + ?Sized>() -> Vec<FeatureFlag> {
+    let Some(config) = APP::config().get_table("features") else {
+        return Vec::new();
+    };
+    config
+        .iter()
+        .filter_map(
+            |(name, value)| match FeatureFlag::try_from_config(name, value) {
+                Ok(flag) => Some(flag),
+                Err(err) => {
+                    tracing::error!("fail to parse the feature flag: {err}");
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
+/// Initializes the feature flags defined in the config.
+pub(super) fn init<APP: Application + ?Sized>() {
+    let flags = parse_config_flags::<APP>();
+    let mut sources = FLAG_SOURCES.lock();
+    sources.config = flags;
+    sources.publish();
+}
+
+/// Applies the feature flags after the config has been reloaded.
+#[inline]
+pub(super) fn reload<APP: Application + ?Sized>() {
+    init::<APP>();
+}
+
+/// Shared flag table.
+static FEATURE_FLAGS: LazyLock<ArcSwap<HashMap<String, FeatureFlag>>> =
+    LazyLock::new(|| ArcSwap::from_pointee(HashMap::new()));
+
+/// Sources of the feature flags.
+static FLAG_SOURCES: LazyLock<Mutex<FlagSources>> =
+    LazyLock::new(|| Mutex::new(FlagSources::default()));
+
+#[cfg(test)]
+mod tests {
+    use super::{rollout_bucket, FeatureFlag, FeatureSubject};
+
+    #[test]
+    fn it_evaluates_feature_flags() {
+        let flag = FeatureFlag::new("dark-mode");
+        assert!(flag.evaluate(None));
+
+        let mut flag = FeatureFlag::new("new-checkout");
+        flag.set_users(vec!["alice".to_owned()]);
+        flag.set_groups(vec!["beta".to_owned()]);
+        assert!(!flag.evaluate(None));
+        assert!(flag.evaluate(Some(&"alice".into())));
+        assert!(!flag.evaluate(Some(&"bob".into())));
+
+        let mut subject = FeatureSubject::new("bob");
+        subject.set_groups(vec!["beta".to_owned()]);
+        assert!(flag.evaluate(Some(&subject)));
+
+        flag.set_enabled(false);
+        assert!(!flag.evaluate(Some(&subject)));
+    }
+
+    #[test]
+    fn it_buckets_subjects_stably() {
+        let bucket = rollout_bucket("search-v2", "alice");
+        assert!((0.0..100.0).contains(&bucket));
+        assert_eq!(bucket, rollout_bucket("search-v2", "alice"));
+
+        let mut flag = FeatureFlag::new("search-v2");
+        flag.set_percentage(100.0);
+        assert!(flag.evaluate(Some(&"alice".into())));
+        flag.set_percentage(0.0);
+        assert!(!flag.evaluate(Some(&"alice".into())));
+
+        let num_enabled = (0..1000)
+            .filter(|i| {
+                let mut flag = FeatureFlag::new("search-v2");
+                flag.set_percentage(20.0);
+                flag.evaluate(Some(&i.to_string().into()))
+            })
+            .count();
+        assert!((150..250).contains(&num_enabled));
+    }
+}
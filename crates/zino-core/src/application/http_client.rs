@@ -1,6 +1,6 @@
 //! HTTP client.
 
-use super::Application;
+use super::{Application, CircuitBreaker};
 use crate::{
     error::Error,
     extension::{HeaderMapExt, JsonObjectExt, TomlTableExt},
@@ -73,6 +73,7 @@ pub(super) fn init<APP: Application + ?Sized>() {
 
 /// Constructs a request builder.
 pub(crate) fn request_builder(url: &str, options: Option<&Map>) -> Result<RequestBuilder, Error> {
+    CircuitBreaker::try_acquire(url)?;
     if options.is_none() || options.is_some_and(|map| map.is_empty()) {
         let request_builder = SHARED_HTTP_CLIENT_WITH_MIDDLEWARE
             .get()
@@ -173,6 +174,7 @@ impl ReqwestOtelSpanBackend for RequestTiming {
             .map(|parent_id| format!("{parent_id:x}"));
 
         extensions.insert(Instant::now());
+        extensions.insert(RequestUrl(url.clone()));
         if method.is_safe() {
             tracing::info_span!(
                 "HTTP request",
@@ -241,6 +243,16 @@ impl ReqwestOtelSpanBackend for RequestTiming {
             "context.span_id",
             span.id().map(|id| format!("{:x}", id.into_u64())),
         );
+        if let Some(RequestUrl(url)) = extensions.get::<RequestUrl>() {
+            let success = match outcome {
+                Ok(response) => !response.status().is_server_error(),
+                Err(reqwest_middleware::Error::Reqwest(err)) => err
+                    .status()
+                    .is_some_and(|status_code| !status_code.is_server_error()),
+                Err(_) => false,
+            };
+            CircuitBreaker::record(url, success);
+        }
         match outcome {
             Ok(response) => {
                 let headers = response.headers();
@@ -285,6 +297,10 @@ impl ReqwestOtelSpanBackend for RequestTiming {
     }
 }
 
+/// URL of the request, which is used to record the outcome for the circuit breaker.
+#[derive(Clone)]
+struct RequestUrl(Url);
+
 /// Removes the username/password in the url.
 fn remove_credentials(url: &Url) -> Cow<'_, str> {
     if !url.username().is_empty() || url.password().is_some() {
@@ -0,0 +1,167 @@
+use super::Application;
+use crate::{error::Error, extension::TomlTableExt, state::State, BoxFuture, Map};
+use std::{future::Future, net::SocketAddr, path::PathBuf, time::Duration};
+
+/// A hook with the shared application state.
+type StateHook =
+    Box<dyn Fn(&'static State<Map>) -> BoxFuture<'static, Result<(), Error>> + Send + Sync>;
+
+/// A hook with the socket address of a listener.
+type ReadyHook = Box<dyn Fn(SocketAddr) -> BoxFuture<'static> + Send + Sync>;
+
+/// A hook without arguments.
+type ShutdownHook = Box<dyn Fn() -> BoxFuture<'static> + Send + Sync>;
+
+/// Hooks on the lifecycle of an application.
+///
+/// The hooks of the same kind run in the registration order:
+///
+/// - `boot`: after the config is loaded but before the connection pools are established.
+///   A failing hook aborts the startup.
+/// - `ready`: after the listeners have been bound, once for each address.
+/// - `config_reload`: after the config has been reloaded on `SIGHUP` or
+///   a change of the config file when `[server] config-watch-interval` is set.
+///   See [`Application::reload_config()`] for the settings which can be changed at runtime.
+/// - `shutdown`: after the servers have been stopped gracefully.
+#[derive(Default)]
+pub struct LifecycleHooks {
+    /// Boot hooks.
+    boot_hooks: Vec<StateHook>,
+    /// Ready hooks.
+    ready_hooks: Vec<ReadyHook>,
+    /// Config reload hooks.
+    reload_hooks: Vec<StateHook>,
+    /// Shutdown hooks.
+    shutdown_hooks: Vec<ShutdownHook>,
+}
+
+impl LifecycleHooks {
+    /// Creates a new instance.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a hook running at boot.
+    pub fn add_boot_hook<F, Fut>(&mut self, hook: F)
+    where
+        F: Fn(&'static State<Map>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        self.boot_hooks
+            .push(Box::new(move |state| Box::pin(hook(state))));
+    }
+
+    /// Adds a hook running after a listener has been bound.
+    pub fn add_ready_hook<F, Fut>(&mut self, hook: F)
+    where
+        F: Fn(SocketAddr) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.ready_hooks
+            .push(Box::new(move |addr| Box::pin(hook(addr))));
+    }
+
+    /// Adds a hook running after the config has been reloaded.
+    pub fn add_reload_hook<F, Fut>(&mut self, hook: F)
+    where
+        F: Fn(&'static State<Map>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        self.reload_hooks
+            .push(Box::new(move |state| Box::pin(hook(state))));
+    }
+
+    /// Adds a hook running after the servers have been stopped.
+    pub fn add_shutdown_hook<F, Fut>(&mut self, hook: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.shutdown_hooks.push(Box::new(move || Box::pin(hook())));
+    }
+
+    /// Runs the boot hooks and returns the first error.
+    pub async fn run_boot_hooks(&self, state: &'static State<Map>) -> Result<(), Error> {
+        for hook in &self.boot_hooks {
+            hook(state).await?;
+        }
+        Ok(())
+    }
+
+    /// Runs the ready hooks for the listener address.
+    pub async fn run_ready_hooks(&self, addr: SocketAddr) {
+        for hook in &self.ready_hooks {
+            hook(addr).await;
+        }
+    }
+
+    /// Runs the shutdown hooks.
+    pub async fn run_shutdown_hooks(&self) {
+        for hook in &self.shutdown_hooks {
+            hook().await;
+        }
+    }
+
+    /// Reloads the config of the application and runs the config reload hooks.
+    pub async fn reload_config<APP: Application + ?Sized>(&self) {
+        let state = APP::reload_config();
+        for hook in &self.reload_hooks {
+            if let Err(err) = hook(state).await {
+                tracing::error!("fail to run the config reload hook: {err}");
+            }
+        }
+        tracing::warn!("the config has been reloaded");
+    }
+
+    /// Watches the modification of the config file periodically with the interval
+    /// `[server] config-watch-interval`, and reloads the config when it is changed.
+    /// It returns immediately if the interval is not configured.
+    ///
+    /// Since the function is runtime-agnostic, the `sleep` function
+    /// should be provided by the framework adapter.
+    pub async fn watch_config<APP, F, Fut>(&self, sleep: F)
+    where
+        APP: Application + ?Sized,
+        F: Fn(Duration) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let Some(interval) = APP::config()
+            .get_table("server")
+            .and_then(|config| config.get_duration("config-watch-interval"))
+        else {
+            return;
+        };
+        if std::env::var("ZINO_APP_CONFIG_URL").is_ok() {
+            tracing::warn!("the config fetched from a URL can not be watched");
+            return;
+        }
+
+        let config_file = config_file_path::<APP>();
+        let modified_time = || {
+            config_file
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        };
+        let mut last_modified = modified_time();
+        loop {
+            sleep(interval).await;
+
+            let modified = modified_time();
+            if modified.is_some() && modified != last_modified {
+                last_modified = modified;
+                self.reload_config::<APP>().await;
+            }
+        }
+    }
+}
+
+/// Returns the path of the config file for the application env.
+fn config_file_path<APP: Application + ?Sized>() -> PathBuf {
+    let env = APP::env().as_str();
+    let format = std::env::var("ZINO_APP_CONFIG_FORMAT")
+        .map(|s| s.to_ascii_lowercase())
+        .unwrap_or_else(|_| "toml".to_owned());
+    APP::config_dir().join(format!("config.{env}.{format}"))
+}
@@ -0,0 +1,449 @@
+use super::Application;
+use crate::{extension::TomlTableExt, state::State, LazyLock, Map};
+use std::{
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering::Relaxed},
+        OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Adaptive load shedding for the HTTP server clusters.
+///
+/// It tracks the number of in-flight requests, the p95 latency of the recent requests
+/// and the number of the connection pool waiters if available. When any of the thresholds
+/// is exceeded, the admit probability is decreased multiplicatively, and the requests
+/// which are not admitted are responded with `503 Service Unavailable` and
+/// a `Retry-After` header. Otherwise, the admit probability is increased additively,
+/// so that it recovers automatically.
+///
+/// The low-priority requests are shed first: they are admitted with the probability `p²`,
+/// while the normal ones with `p` and the high-priority ones with `√p`.
+/// The exempt routes such as the health checks are always admitted.
+///
+/// ```toml
+/// [server.load-shedding]
+/// max-in-flight = 512
+/// latency-ceiling = "2s"
+/// max-pool-waiters = 64
+/// adjust-interval = "1s"
+/// min-samples = 20
+/// increase-step = 0.05
+/// decrease-factor = 0.5
+/// min-admit-probability = 0.05
+/// retry-after = "5s"
+/// exempt-routes = ["/health"]
+/// priorities = { "/api/reports" = "low", "/api/orders" = "high" }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoadShedder;
+
+impl LoadShedder {
+    /// Returns `true` if the load shedding is enabled.
+    #[inline]
+    pub fn is_enabled() -> bool {
+        SHARED_LOAD_SHEDDING_CONFIG.enabled
+    }
+
+    /// Attempts to admit a request for the route path.
+    /// It returns `None` if the request should be shed.
+    ///
+    /// The returned guard should be held until the response is ready,
+    /// so that the in-flight count and the latency are tracked.
+    pub fn admit(path: &str) -> Option<AdmissionGuard> {
+        let config = &*SHARED_LOAD_SHEDDING_CONFIG;
+        if !config.enabled || config.is_exempt(path) {
+            return Some(AdmissionGuard::new(false));
+        }
+
+        let admit_probability = Self::admit_probability();
+        if admit_probability < 1.0 {
+            let priority = config.priority(path);
+            let probability = match priority {
+                Priority::Low => admit_probability * admit_probability,
+                Priority::Normal => admit_probability,
+                Priority::High => admit_probability.sqrt(),
+            };
+            if rand::random::<f64>() >= probability {
+                #[cfg(feature = "metrics")]
+                metrics::counter!("zino_load_shedding_rejected_total", "priority" => priority.as_str())
+                    .increment(1);
+                return None;
+            }
+        }
+        Some(AdmissionGuard::new(true))
+    }
+
+    /// Returns the current admit probability.
+    #[inline]
+    pub fn admit_probability() -> f64 {
+        f64::from_bits(ADMIT_PROBABILITY.load(Relaxed))
+    }
+
+    /// Returns the number of in-flight requests.
+    #[inline]
+    pub fn in_flight() -> usize {
+        IN_FLIGHT.load(Relaxed)
+    }
+
+    /// Returns the duration for the `Retry-After` header.
+    #[inline]
+    pub fn retry_after() -> Duration {
+        SHARED_LOAD_SHEDDING_CONFIG.retry_after
+    }
+
+    /// Sets the source of the number of the connection pool waiters.
+    /// It is only respected if `max-pool-waiters` has been configured.
+    #[inline]
+    pub fn set_pool_waiters_source(source: fn() -> usize) {
+        if POOL_WAITERS_SOURCE.set(source).is_err() {
+            tracing::warn!("the source of the connection pool waiters has been set");
+        }
+    }
+
+    /// Returns the status of the load shedding.
+    pub fn status() -> Map {
+        let config = &*SHARED_LOAD_SHEDDING_CONFIG;
+        let mut map = Map::new();
+        map.insert("enabled".to_owned(), config.enabled.into());
+        map.insert(
+            "admit_probability".to_owned(),
+            Self::admit_probability().into(),
+        );
+        map.insert("in_flight".to_owned(), Self::in_flight().into());
+        map.insert(
+            "latency_p95_millis".to_owned(),
+            LATENCY_P95_MILLIS.load(Relaxed).into(),
+        );
+        if let Some(source) = POOL_WAITERS_SOURCE.get() {
+            map.insert("pool_waiters".to_owned(), source().into());
+        }
+        map
+    }
+
+    /// Adjusts the admit probability with the signals in the last interval.
+    fn adjust(config: &LoadSheddingConfig) {
+        let peak_in_flight = PEAK_IN_FLIGHT.swap(IN_FLIGHT.load(Relaxed), Relaxed);
+        let latency_p95 = LATENCY_HISTOGRAM.take_percentile(0.95, config.min_samples);
+        if let Some(latency_p95) = latency_p95 {
+            LATENCY_P95_MILLIS.store(latency_p95.as_millis() as u64, Relaxed);
+        }
+
+        let mut overloaded = config
+            .max_in_flight
+            .is_some_and(|max_in_flight| peak_in_flight > max_in_flight);
+        if let (Some(ceiling), Some(latency_p95)) = (config.latency_ceiling, latency_p95) {
+            overloaded |= latency_p95 > ceiling;
+        }
+        if let (Some(max_pool_waiters), Some(source)) =
+            (config.max_pool_waiters, POOL_WAITERS_SOURCE.get())
+        {
+            overloaded |= source() > max_pool_waiters;
+        }
+
+        let previous = Self::admit_probability();
+        let admit_probability = if overloaded {
+            (previous * config.decrease_factor).max(config.min_admit_probability)
+        } else {
+            (previous + config.increase_step).min(1.0)
+        };
+        if admit_probability != previous {
+            ADMIT_PROBABILITY.store(admit_probability.to_bits(), Relaxed);
+            if previous == 1.0 {
+                tracing::warn!(
+                    peak_in_flight,
+                    admit_probability,
+                    "load shedding is activated"
+                );
+            } else if admit_probability == 1.0 {
+                tracing::warn!("load shedding is deactivated");
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        {
+            metrics::gauge!("zino_load_shedding_admit_probability").set(admit_probability);
+            metrics::gauge!("zino_load_shedding_in_flight").set(Self::in_flight() as f64);
+        }
+    }
+}
+
+/// A guard for an admitted request, which tracks the in-flight count and the latency.
+#[derive(Debug)]
+pub struct AdmissionGuard {
+    /// Start time of the request if it is tracked.
+    start_time: Option<Instant>,
+}
+
+impl AdmissionGuard {
+    /// Creates a new instance.
+    fn new(tracked: bool) -> Self {
+        if tracked {
+            let in_flight = IN_FLIGHT.fetch_add(1, Relaxed) + 1;
+            PEAK_IN_FLIGHT.fetch_max(in_flight, Relaxed);
+        }
+        Self {
+            start_time: tracked.then(Instant::now),
+        }
+    }
+}
+
+impl Drop for AdmissionGuard {
+    fn drop(&mut self) {
+        if let Some(start_time) = self.start_time {
+            IN_FLIGHT.fetch_sub(1, Relaxed);
+            LATENCY_HISTOGRAM.record(start_time.elapsed());
+        }
+    }
+}
+
+/// Priority of the requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Priority {
+    /// Low priority.
+    Low,
+    /// Normal priority.
+    Normal,
+    /// High priority.
+    High,
+}
+
+impl Priority {
+    /// Returns the priority as a `str`.
+    #[cfg(feature = "metrics")]
+    #[inline]
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High => "high",
+        }
+    }
+}
+
+/// Number of the latency buckets. The upper bound of the bucket `i` is `2^(i/2)` milliseconds.
+const NUM_LATENCY_BUCKETS: usize = 48;
+
+/// A lock-free histogram of the latencies with two buckets per octave.
+#[derive(Debug)]
+struct LatencyHistogram {
+    /// Counts of the buckets.
+    buckets: [AtomicUsize; NUM_LATENCY_BUCKETS],
+}
+
+impl LatencyHistogram {
+    /// Creates a new instance.
+    fn new() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicUsize::new(0)),
+        }
+    }
+
+    /// Records the latency.
+    fn record(&self, latency: Duration) {
+        let millis = latency.as_secs_f64() * 1000.0;
+        let index = if millis <= 1.0 {
+            0
+        } else {
+            ((2.0 * millis.log2()).ceil() as usize).min(NUM_LATENCY_BUCKETS - 1)
+        };
+        self.buckets[index].fetch_add(1, Relaxed);
+    }
+
+    /// Takes the counts and returns the upper bound of the percentile,
+    /// or `None` if the number of samples is less than `min_samples`.
+    fn take_percentile(&self, percentile: f64, min_samples: usize) -> Option<Duration> {
+        let counts = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.swap(0, Relaxed))
+            .collect::<Vec<_>>();
+        let total = counts.iter().sum::<usize>();
+        if total == 0 || total < min_samples {
+            return None;
+        }
+
+        let rank = (total as f64 * percentile).ceil() as usize;
+        let mut cumulative = 0;
+        let index = counts
+            .iter()
+            .position(|count| {
+                cumulative += count;
+                cumulative >= rank
+            })
+            .unwrap_or(NUM_LATENCY_BUCKETS - 1);
+        let millis = 2f64.powf(index as f64 / 2.0);
+        Some(Duration::from_secs_f64(millis / 1000.0))
+    }
+}
+
+/// Initializes the load shedding and spawns the controller.
+pub(super) fn init<APP: Application + ?Sized>() {
+    let config = &*SHARED_LOAD_SHEDDING_CONFIG;
+    if !config.enabled {
+        return;
+    }
+
+    let interval = config.adjust_interval;
+    thread::spawn(move || loop {
+        thread::sleep(interval);
+        LoadShedder::adjust(config);
+    });
+    tracing::info!(
+        max_in_flight = config.max_in_flight,
+        latency_ceiling_millis = config.latency_ceiling.map(|d| d.as_millis() as u64),
+        max_pool_waiters = config.max_pool_waiters,
+        "load shedding is enabled for `{}`",
+        APP::name(),
+    );
+}
+
+/// Config for the load shedding.
+#[derive(Debug)]
+struct LoadSheddingConfig {
+    /// A flag to indicate whether the load shedding is enabled.
+    enabled: bool,
+    /// Maximum number of in-flight requests.
+    max_in_flight: Option<usize>,
+    /// Ceiling of the p95 latency.
+    latency_ceiling: Option<Duration>,
+    /// Maximum number of the connection pool waiters.
+    max_pool_waiters: Option<usize>,
+    /// Interval for adjusting the admit probability.
+    adjust_interval: Duration,
+    /// Minimum number of samples for the latency signal.
+    min_samples: usize,
+    /// Additive increase step of the admit probability.
+    increase_step: f64,
+    /// Multiplicative decrease factor of the admit probability.
+    decrease_factor: f64,
+    /// Minimum admit probability.
+    min_admit_probability: f64,
+    /// Duration for the `Retry-After` header.
+    retry_after: Duration,
+    /// Routes which are always admitted.
+    exempt_routes: Vec<&'static str>,
+    /// Priorities of the route prefixes.
+    priorities: Vec<(&'static str, Priority)>,
+}
+
+impl LoadSheddingConfig {
+    /// Returns `true` if the route path is always admitted.
+    fn is_exempt(&self, path: &str) -> bool {
+        self.exempt_routes.iter().any(|route| {
+            let route = route.trim_end_matches('/');
+            path.strip_prefix(route)
+                .is_some_and(|s| s.is_empty() || s.starts_with('/'))
+        })
+    }
+
+    /// Returns the priority of the route path with the longest matching prefix.
+    fn priority(&self, path: &str) -> Priority {
+        self.priorities
+            .iter()
+            .filter(|(prefix, _)| path.starts_with(prefix))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|&(_, priority)| priority)
+            .unwrap_or(Priority::Normal)
+    }
+}
+
+/// Shared config for the load shedding.
+static SHARED_LOAD_SHEDDING_CONFIG: LazyLock<LoadSheddingConfig> = LazyLock::new(|| {
+    let config = State::shared()
+        .get_config("server")
+        .and_then(|config| config.get_table("load-shedding"));
+    let priorities = config
+        .and_then(|config| config.get_table("priorities"))
+        .map(|priorities| {
+            priorities
+                .iter()
+                .filter_map(|(prefix, value)| {
+                    let priority = match value.as_str()? {
+                        "low" => Priority::Low,
+                        "normal" => Priority::Normal,
+                        "high" => Priority::High,
+                        priority => {
+                            tracing::warn!("invalid load shedding priority `{priority}`");
+                            return None;
+                        }
+                    };
+                    Some((prefix.as_str(), priority))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    LoadSheddingConfig {
+        enabled: config.is_some_and(|config| config.get_bool("enabled") != Some(false)),
+        max_in_flight: config.and_then(|config| config.get_usize("max-in-flight")),
+        latency_ceiling: config.and_then(|config| config.get_duration("latency-ceiling")),
+        max_pool_waiters: config.and_then(|config| config.get_usize("max-pool-waiters")),
+        adjust_interval: config
+            .and_then(|config| config.get_duration("adjust-interval"))
+            .unwrap_or_else(|| Duration::from_secs(1)),
+        min_samples: config
+            .and_then(|config| config.get_usize("min-samples"))
+            .unwrap_or(20),
+        increase_step: config
+            .and_then(|config| config.get_f64("increase-step"))
+            .unwrap_or(0.05)
+            .clamp(0.001, 1.0),
+        decrease_factor: config
+            .and_then(|config| config.get_f64("decrease-factor"))
+            .unwrap_or(0.5)
+            .clamp(0.0, 0.999),
+        min_admit_probability: config
+            .and_then(|config| config.get_f64("min-admit-probability"))
+            .unwrap_or(0.05)
+            .clamp(0.0, 1.0),
+        retry_after: config
+            .and_then(|config| config.get_duration("retry-after"))
+            .unwrap_or_else(|| Duration::from_secs(5)),
+        exempt_routes: config
+            .and_then(|config| config.get_str_array("exempt-routes"))
+            .unwrap_or_else(|| vec!["/health"]),
+        priorities,
+    }
+});
+
+/// Source of the number of the connection pool waiters.
+static POOL_WAITERS_SOURCE: OnceLock<fn() -> usize> = OnceLock::new();
+
+/// Histogram of the recent latencies.
+static LATENCY_HISTOGRAM: LazyLock<LatencyHistogram> = LazyLock::new(LatencyHistogram::new);
+
+/// Admit probability stored as the bits of `f64`.
+static ADMIT_PROBABILITY: AtomicU64 = AtomicU64::new(0x3FF0_0000_0000_0000);
+
+/// Number of in-flight requests.
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// Peak number of in-flight requests in the current interval.
+static PEAK_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// The p95 latency in milliseconds of the last interval.
+static LATENCY_P95_MILLIS: AtomicU64 = AtomicU64::new(0);
+
+#[cfg(test)]
+mod tests {
+    use super::LatencyHistogram;
+    use std::time::Duration;
+
+    #[test]
+    fn it_estimates_latency_percentile() {
+        let histogram = LatencyHistogram::new();
+        for _ in 0..95 {
+            histogram.record(Duration::from_millis(10));
+        }
+        for _ in 0..5 {
+            histogram.record(Duration::from_secs(3));
+        }
+        let p95 = histogram.take_percentile(0.95, 20).unwrap();
+        assert!(p95 >= Duration::from_millis(10) && p95 < Duration::from_millis(16));
+        assert_eq!(histogram.take_percentile(0.95, 20), None);
+
+        histogram.record(Duration::from_secs(3));
+        assert_eq!(histogram.take_percentile(0.95, 20), None);
+    }
+}
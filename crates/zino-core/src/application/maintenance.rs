@@ -0,0 +1,227 @@
+use super::Application;
+use crate::{extension::TomlTableExt, state::State, LazyLock, Map};
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering::Relaxed},
+    thread,
+    time::Duration,
+};
+
+/// Global switch of the maintenance mode.
+///
+/// When it is enabled, the HTTP server clusters respond with `503 Service Unavailable`
+/// and a `Retry-After` header for all the routes except the allowed ones.
+/// The requests which have been accepted before the switch is turned on are not affected.
+///
+/// It can be turned on at boot with `maintenance = true` in the `[server]` table,
+/// toggled at runtime via [`enable()`](MaintenanceMode::enable) and
+/// [`disable()`](MaintenanceMode::disable), or controlled by a sentinel file
+/// which is checked periodically. The details can be configured in the `[maintenance]` table:
+///
+/// ```toml
+/// [server]
+/// maintenance = false
+///
+/// [maintenance]
+/// retry-after = "5m"
+/// message = "The service is under maintenance"
+/// template = "maintenance.html"
+/// allowed-routes = ["/health", "/maintenance"]
+/// allow-static-assets = true
+/// sentinel-file = "./maintenance.flag"
+/// check-interval = "5s"
+/// ```
+///
+/// The sentinel file turns on the maintenance mode when it is created,
+/// and turns it off when it is removed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaintenanceMode;
+
+impl MaintenanceMode {
+    /// Returns `true` if the maintenance mode is enabled.
+    #[inline]
+    pub fn is_enabled() -> bool {
+        MAINTENANCE_ENABLED.load(Relaxed)
+    }
+
+    /// Enables the maintenance mode.
+    #[inline]
+    pub fn enable() {
+        Self::set_enabled(true);
+    }
+
+    /// Disables the maintenance mode.
+    #[inline]
+    pub fn disable() {
+        Self::set_enabled(false);
+    }
+
+    /// Sets the maintenance mode and returns the previous value.
+    pub fn set_enabled(enabled: bool) -> bool {
+        let previous = MAINTENANCE_ENABLED.swap(enabled, Relaxed);
+        if previous != enabled {
+            if enabled {
+                tracing::warn!("maintenance mode is enabled");
+            } else {
+                tracing::warn!("maintenance mode is disabled");
+            }
+        }
+        previous
+    }
+
+    /// Returns the duration for the `Retry-After` header.
+    #[inline]
+    pub fn retry_after() -> Duration {
+        SHARED_MAINTENANCE_CONFIG.retry_after
+    }
+
+    /// Returns the message of the response.
+    #[inline]
+    pub fn message() -> &'static str {
+        SHARED_MAINTENANCE_CONFIG.message
+    }
+
+    /// Returns the template name for rendering an HTML response.
+    #[inline]
+    pub fn template() -> Option<&'static str> {
+        SHARED_MAINTENANCE_CONFIG.template
+    }
+
+    /// Returns `true` if the route path is allowed in the maintenance mode.
+    pub fn is_allowed(path: &str) -> bool {
+        let config = &*SHARED_MAINTENANCE_CONFIG;
+        let matches = |route: &str| {
+            let route = route.trim_end_matches('/');
+            path.strip_prefix(route)
+                .is_some_and(|s| s.is_empty() || s.starts_with('/'))
+        };
+        config.allowed_routes.iter().any(|route| matches(route))
+            || (config.allow_static_assets
+                && (path == "/favicon.ico" || matches(config.static_route_prefix)))
+    }
+
+    /// Returns the status of the maintenance mode.
+    pub fn status() -> Map {
+        let config = &*SHARED_MAINTENANCE_CONFIG;
+        let mut map = Map::new();
+        map.insert("enabled".to_owned(), Self::is_enabled().into());
+        map.insert(
+            "retry_after".to_owned(),
+            config.retry_after.as_secs().into(),
+        );
+        map.insert("message".to_owned(), config.message.into());
+        map.insert(
+            "allowed_routes".to_owned(),
+            config.allowed_routes.clone().into(),
+        );
+        if let Some(path) = config.sentinel_file.as_ref() {
+            map.insert("sentinel_file".to_owned(), path.to_string_lossy().into());
+        }
+        map
+    }
+}
+
+/// Initializes the maintenance mode and watches the sentinel file.
+pub(super) fn init<APP: Application + ?Sized>() {
+    let enabled = APP::config()
+        .get_table("server")
+        .and_then(|config| config.get_bool("maintenance"))
+        .unwrap_or_default();
+    MAINTENANCE_ENABLED.store(enabled, Relaxed);
+    if enabled {
+        tracing::warn!("maintenance mode is enabled at boot");
+    }
+
+    let Some(config) = APP::config().get_table("maintenance") else {
+        return;
+    };
+    let Some(sentinel_file) = config.get_str("sentinel-file").map(APP::parse_path) else {
+        return;
+    };
+    let interval = config
+        .get_duration("check-interval")
+        .unwrap_or_else(|| Duration::from_secs(5));
+    thread::spawn(move || {
+        let mut exists = sentinel_file.exists();
+        if exists {
+            MaintenanceMode::enable();
+        }
+        loop {
+            thread::sleep(interval);
+
+            // Only the transitions of the file are respected, so that the mode
+            // can still be toggled at runtime in other ways.
+            let file_exists = sentinel_file.exists();
+            if file_exists != exists {
+                exists = file_exists;
+                MaintenanceMode::set_enabled(file_exists);
+            }
+        }
+    });
+}
+
+/// Applies the maintenance flag after the config has been reloaded.
+pub(super) fn reload<APP: Application + ?Sized>() {
+    if let Some(enabled) = APP::config()
+        .get_table("server")
+        .and_then(|config| config.get_bool("maintenance"))
+    {
+        MaintenanceMode::set_enabled(enabled);
+    }
+}
+
+/// Config for the maintenance mode.
+#[derive(Debug)]
+struct MaintenanceConfig {
+    /// Duration for the `Retry-After` header.
+    retry_after: Duration,
+    /// Message of the response.
+    message: &'static str,
+    /// Template name for rendering an HTML response.
+    template: Option<&'static str>,
+    /// Allowed routes.
+    allowed_routes: Vec<&'static str>,
+    /// A flag to indicate whether the static assets are allowed.
+    allow_static_assets: bool,
+    /// Route prefix of the static assets.
+    static_route_prefix: &'static str,
+    /// Sentinel file.
+    sentinel_file: Option<PathBuf>,
+}
+
+/// Shared config for the maintenance mode.
+static SHARED_MAINTENANCE_CONFIG: LazyLock<MaintenanceConfig> = LazyLock::new(|| {
+    let state = State::shared();
+    let mut static_route_prefix = "/public";
+    if let Some(config) = state.get_config("server") {
+        if let Some(route_prefix) = config.get_str("public-route-prefix") {
+            static_route_prefix = route_prefix;
+        } else if config.contains_key("page-dir") {
+            static_route_prefix = "/page";
+        }
+    }
+
+    let config = state.get_config("maintenance");
+    MaintenanceConfig {
+        retry_after: config
+            .and_then(|config| config.get_duration("retry-after"))
+            .unwrap_or_else(|| Duration::from_secs(300)),
+        message: config
+            .and_then(|config| config.get_str("message"))
+            .unwrap_or("The service is under maintenance, please try again later"),
+        template: config.and_then(|config| config.get_str("template")),
+        allowed_routes: config
+            .and_then(|config| config.get_str_array("allowed-routes"))
+            .unwrap_or_else(|| vec!["/health", "/maintenance"]),
+        allow_static_assets: config
+            .and_then(|config| config.get_bool("allow-static-assets"))
+            .unwrap_or(true),
+        static_route_prefix,
+        sentinel_file: config
+            .and_then(|config| config.get_str("sentinel-file"))
+            .map(|path| super::join_path(&super::PROJECT_DIR, path)),
+    }
+});
+
+/// A flag to indicate whether the maintenance mode is enabled.
+static MAINTENANCE_ENABLED: AtomicBool = AtomicBool::new(false);
@@ -46,22 +46,40 @@ use std::{
     borrow::Cow,
     env, fs,
     path::{Component, Path, PathBuf},
+    sync::{PoisonError, RwLock},
     thread,
 };
 use toml::value::Table;
 
 mod agent;
+mod compute_pool;
+mod config_check;
+mod lifecycle;
+mod load_shedding;
+mod maintenance;
 mod plugin;
 mod secret_key;
 mod server_tag;
 mod static_record;
 
+#[cfg(feature = "http-client")]
+mod circuit_breaker;
+
+#[cfg(feature = "http-client")]
+mod endpoint_pool;
+
+#[cfg(feature = "feature-flag")]
+mod feature_flag;
+
 #[cfg(feature = "http-client")]
 pub(crate) mod http_client;
 
 #[cfg(feature = "metrics")]
 mod metrics_exporter;
 
+#[cfg(feature = "otel")]
+mod otlp_exporter;
+
 #[cfg(feature = "sentry")]
 mod sentry_client;
 
@@ -74,10 +92,26 @@ pub(crate) use secret_key::SECRET_KEY;
 use crate::{error::Error, extension::HeaderMapExt, trace::TraceContext};
 
 pub use agent::Agent;
+pub use compute_pool::ComputePool;
+pub use lifecycle::LifecycleHooks;
+pub use load_shedding::{AdmissionGuard, LoadShedder};
+pub use maintenance::MaintenanceMode;
 pub use plugin::Plugin;
 pub use server_tag::ServerTag;
 pub use static_record::StaticRecord;
 
+#[cfg(feature = "http-client")]
+pub use circuit_breaker::{CircuitBreaker, CircuitOpen, CircuitState};
+
+#[cfg(feature = "http-client")]
+pub use endpoint_pool::{Endpoint, EndpointPool};
+
+#[cfg(feature = "feature-flag")]
+pub use feature_flag::{FeatureFlag, FeatureSubject};
+
+#[cfg(feature = "otel")]
+pub use otlp_exporter::shutdown_tracer_provider;
+
 /// Application interfaces.
 pub trait Application {
     /// Routes.
@@ -102,9 +136,22 @@ pub trait Application {
         #[cfg(feature = "tracing-subscriber")]
         tracing_subscriber::init::<Self>();
 
+        // Config validation and startup report
+        config_check::init::<Self>();
+
         // Secret keys
         secret_key::init::<Self>();
 
+        // Maintenance mode
+        maintenance::init::<Self>();
+
+        // Load shedding
+        load_shedding::init::<Self>();
+
+        // Feature flags
+        #[cfg(feature = "feature-flag")]
+        feature_flag::init::<Self>();
+
         // Metrics exporter
         #[cfg(feature = "metrics")]
         metrics_exporter::init::<Self>();
@@ -159,6 +206,19 @@ pub trait Application {
         self.register_with(ServerTag::Debug, routes)
     }
 
+    /// Registers routes for the listeners with the scope.
+    ///
+    /// The scopes `public` and `internal` are aliases of the `main` and `debug` servers,
+    /// so [`register()`](Self::register) and [`register_debug()`](Self::register_debug)
+    /// attach the routes to the public and internal listeners respectively.
+    #[inline]
+    fn register_scoped(self, scope: &str, routes: Self::Routes) -> Self
+    where
+        Self: Sized,
+    {
+        self.register_with(scope.into(), routes)
+    }
+
     /// Adds a custom plugin.
     #[inline]
     fn add_plugin(self, plugin: Plugin) -> Self
@@ -172,25 +232,61 @@ pub trait Application {
     /// Returns a reference to the shared application state.
     #[inline]
     fn shared_state() -> &'static State<Map> {
-        &SHARED_APP_STATE
+        shared_app_state()
+    }
+
+    /// Reloads the config and swaps the shared states atomically.
+    ///
+    /// The following settings are applied at runtime:
+    ///
+    /// - the tracing filter specified by `level` and `filter` in the `[tracing]` table;
+    /// - the `maintenance` flag in the `[server]` table;
+    /// - the feature flags in the `[features]` table;
+    /// - the configs read on demand from the shared states,
+    ///   such as the scoped middleware configs for `cors`, `auth` and `rate-limit`.
+    ///
+    /// The settings which have been initialized at boot can not be changed without a restart,
+    /// including the database driver, connection pools, listeners, the secret key,
+    /// the tracing output, the app name and version, and the `dirs` table.
+    fn reload_config() -> &'static State<Map> {
+        State::reload_shared();
+
+        let booted_at = shared_app_state()
+            .data()
+            .get_str("app.booted_at")
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_else(DateTime::now);
+        let state: &'static State<Map> = Box::leak(Box::new(load_app_state(booted_at)));
+        *SHARED_APP_STATE
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = state;
+
+        // Runtime settings
+        #[cfg(feature = "tracing-subscriber")]
+        tracing_subscriber::reload::<Self>();
+        maintenance::reload::<Self>();
+        #[cfg(feature = "feature-flag")]
+        feature_flag::reload::<Self>();
+
+        state
     }
 
     /// Returns the application env.
     #[inline]
     fn env() -> &'static Env {
-        SHARED_APP_STATE.env()
+        shared_app_state().env()
     }
 
     /// Returns a reference to the shared application config.
     #[inline]
     fn config() -> &'static Table {
-        SHARED_APP_STATE.config()
+        shared_app_state().config()
     }
 
     /// Returns a reference to the shared application state data.
     #[inline]
     fn state_data() -> &'static Map {
-        SHARED_APP_STATE.data()
+        shared_app_state().data()
     }
 
     /// Returns the application name.
@@ -359,7 +455,7 @@ fn join_path(dir: &Path, path: &str) -> PathBuf {
 
 /// App name.
 static APP_NAME: LazyLock<&'static str> = LazyLock::new(|| {
-    SHARED_APP_STATE
+    shared_app_state()
         .config()
         .get_str("name")
         .unwrap_or_else(|| {
@@ -371,7 +467,7 @@ static APP_NAME: LazyLock<&'static str> = LazyLock::new(|| {
 
 /// App version.
 static APP_VERSION: LazyLock<&'static str> = LazyLock::new(|| {
-    SHARED_APP_STATE
+    shared_app_state()
         .config()
         .get_str("version")
         .unwrap_or_else(|| {
@@ -383,7 +479,7 @@ static APP_VERSION: LazyLock<&'static str> = LazyLock::new(|| {
 
 /// App domain.
 static APP_DOMAIN: LazyLock<&'static str> = LazyLock::new(|| {
-    SHARED_APP_STATE
+    shared_app_state()
         .config()
         .get_str("domain")
         .unwrap_or("localhost")
@@ -413,7 +509,7 @@ static CONFIG_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
 /// Shared directories.
 static SHARED_DIRS: LazyLock<HashMap<String, PathBuf>> = LazyLock::new(|| {
     let mut dirs = HashMap::new();
-    if let Some(config) = SHARED_APP_STATE.get_config("dirs") {
+    if let Some(config) = shared_app_state().get_config("dirs") {
         for (key, value) in config {
             if let Some(path) = value.as_str() {
                 dirs.insert(key.to_owned(), join_path(&PROJECT_DIR, path));
@@ -423,8 +519,16 @@ static SHARED_DIRS: LazyLock<HashMap<String, PathBuf>> = LazyLock::new(|| {
     dirs
 });
 
-/// Shared app state.
-static SHARED_APP_STATE: LazyLock<State<Map>> = LazyLock::new(|| {
+/// Returns a reference to the shared app state.
+#[inline]
+fn shared_app_state() -> &'static State<Map> {
+    *SHARED_APP_STATE
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Loads the app state with the data derived from the config.
+fn load_app_state(booted_at: DateTime) -> State<Map> {
     let mut state = State::default();
     state.load_config();
 
@@ -447,7 +551,13 @@ static SHARED_APP_STATE: LazyLock<State<Map>> = LazyLock::new(|| {
     let mut data = Map::new();
     data.upsert("app.name", app_name);
     data.upsert("app.version", app_version);
-    data.upsert("app.booted_at", DateTime::now());
+    data.upsert("app.booted_at", booted_at);
     state.set_data(data);
     state
+}
+
+/// Shared app state.
+static SHARED_APP_STATE: LazyLock<RwLock<&'static State<Map>>> = LazyLock::new(|| {
+    let state = load_app_state(DateTime::now());
+    RwLock::new(Box::leak(Box::new(state)))
 });
@@ -0,0 +1,194 @@
+use super::Application;
+use crate::extension::TomlTableExt;
+use opentelemetry::{trace::TracerProvider as _, KeyValue};
+use opentelemetry_otlp::{
+    Protocol, SpanExporter, WithExportConfig, WithHttpConfig, WithTonicConfig,
+};
+use opentelemetry_sdk::{
+    runtime,
+    trace::{Sampler, Tracer, TracerProvider},
+    Resource,
+};
+use std::{collections::HashMap, sync::OnceLock};
+
+#[cfg(feature = "debug-session")]
+use opentelemetry::{
+    trace::{Link, SamplingDecision, SamplingResult, SpanKind, TraceContextExt, TraceId},
+    Context, Value,
+};
+#[cfg(feature = "debug-session")]
+use opentelemetry_sdk::trace::ShouldSample;
+use tokio::runtime::{Builder, Runtime};
+use tonic::metadata::{AsciiMetadataKey, AsciiMetadataValue, MetadataMap};
+
+/// Initializes the OTLP exporter and returns a tracer if the `tracing.otlp` config exists.
+pub(super) fn init<APP: Application + ?Sized>() -> Option<Tracer> {
+    let config = APP::config().get_table("tracing")?.get_table("otlp")?;
+
+    // The exporter is initialized before the server runtime has been started,
+    // so it runs on a dedicated runtime which outlives the servers.
+    let runtime = EXPORTER_RUNTIME.get_or_init(|| {
+        Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("otlp-exporter")
+            .enable_all()
+            .build()
+            .expect("fail to build the runtime for the OTLP exporter")
+    });
+    let _guard = runtime.enter();
+
+    let protocol = config.get_str("protocol").unwrap_or("grpc");
+    let timeout = config.get_duration("timeout");
+    let headers = config
+        .get_table("headers")
+        .map(|table| {
+            table
+                .iter()
+                .filter_map(|(key, value)| {
+                    value
+                        .as_str()
+                        .map(|value| (key.to_owned(), value.to_owned()))
+                })
+                .collect::<HashMap<_, _>>()
+        })
+        .unwrap_or_default();
+    let result = if protocol == "grpc" {
+        let endpoint = config
+            .get_str("endpoint")
+            .unwrap_or("http://localhost:4317");
+        let mut metadata = MetadataMap::with_capacity(headers.len());
+        for (key, value) in headers {
+            match (
+                AsciiMetadataKey::from_bytes(key.as_bytes()),
+                value.parse::<AsciiMetadataValue>(),
+            ) {
+                (Ok(key), Ok(value)) => {
+                    metadata.insert(key, value);
+                }
+                _ => tracing::warn!("invalid OTLP header `{key}`"),
+            }
+        }
+
+        let mut builder = SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .with_metadata(metadata);
+        if let Some(timeout) = timeout {
+            builder = builder.with_timeout(timeout);
+        }
+        builder.build()
+    } else {
+        let endpoint = config
+            .get_str("endpoint")
+            .unwrap_or("http://localhost:4318/v1/traces");
+        let protocol = if protocol == "http/json" {
+            Protocol::HttpJson
+        } else {
+            Protocol::HttpBinary
+        };
+        let mut builder = SpanExporter::builder()
+            .with_http()
+            .with_endpoint(endpoint)
+            .with_protocol(protocol)
+            .with_headers(headers);
+        if let Some(timeout) = timeout {
+            builder = builder.with_timeout(timeout);
+        }
+        builder.build()
+    };
+    let exporter = match result {
+        Ok(exporter) => exporter,
+        Err(err) => {
+            tracing::error!("fail to build the OTLP span exporter: {err}");
+            return None;
+        }
+    };
+
+    let app_name = APP::name();
+    let app_env = APP::env();
+    let sampling_ratio = config.get_f64("sampling-ratio").unwrap_or(1.0);
+    let sampler = if sampling_ratio >= 1.0 {
+        Sampler::ParentBased(Box::new(Sampler::AlwaysOn))
+    } else {
+        let sampler = Sampler::TraceIdRatioBased(sampling_ratio.max(0.0));
+        Sampler::ParentBased(Box::new(sampler))
+    };
+
+    // Always samples the traces of the debug sessions
+    #[cfg(feature = "debug-session")]
+    let sampler = DebugSessionSampler(sampler);
+
+    let resource = Resource::new([
+        KeyValue::new("service.name", app_name),
+        KeyValue::new("service.version", APP::version()),
+        KeyValue::new("deployment.environment", app_env.as_str()),
+    ]);
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_sampler(sampler)
+        .with_resource(resource)
+        .build();
+    let tracer = provider.tracer(app_name);
+    if TRACER_PROVIDER.set(provider).is_err() {
+        tracing::warn!("OTLP tracer provider has already been initialized");
+        return None;
+    }
+    Some(tracer)
+}
+
+/// Flushes the pending spans and shuts down the OTLP exporter.
+/// It is a no-op if the exporter has not been initialized.
+pub fn shutdown_tracer_provider() {
+    if let Some(provider) = TRACER_PROVIDER.get() {
+        for result in provider.force_flush() {
+            if let Err(err) = result {
+                tracing::error!("fail to flush the OTLP span exporter: {err}");
+            }
+        }
+        if let Err(err) = provider.shutdown() {
+            tracing::error!("fail to shut down the OTLP tracer provider: {err}");
+        }
+    }
+}
+
+/// A sampler which samples the spans with the attribute `debug_session = true`,
+/// and delegates the others to the inner sampler.
+#[cfg(feature = "debug-session")]
+#[derive(Debug, Clone)]
+struct DebugSessionSampler(Sampler);
+
+#[cfg(feature = "debug-session")]
+impl ShouldSample for DebugSessionSampler {
+    fn should_sample(
+        &self,
+        parent_context: Option<&Context>,
+        trace_id: TraceId,
+        name: &str,
+        span_kind: &SpanKind,
+        attributes: &[KeyValue],
+        links: &[Link],
+    ) -> SamplingResult {
+        let debug_session = attributes.iter().any(|attribute| {
+            attribute.key.as_str() == "debug_session" && attribute.value == Value::Bool(true)
+        });
+        if debug_session {
+            let trace_state = parent_context
+                .map(|cx| cx.span().span_context().trace_state().clone())
+                .unwrap_or_default();
+            SamplingResult {
+                decision: SamplingDecision::RecordAndSample,
+                attributes: Vec::new(),
+                trace_state,
+            }
+        } else {
+            self.0
+                .should_sample(parent_context, trace_id, name, span_kind, attributes, links)
+        }
+    }
+}
+
+/// Dedicated runtime for the OTLP exporter.
+static EXPORTER_RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+/// OTLP tracer provider.
+static TRACER_PROVIDER: OnceLock<TracerProvider> = OnceLock::new();
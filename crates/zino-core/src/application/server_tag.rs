@@ -2,6 +2,9 @@ use self::ServerTag::*;
 use std::fmt;
 
 /// A server tag is used to distinguish different servers.
+///
+/// It is also used as the scope of a listener, where `public` and `internal`
+/// are aliases of the `main` and `debug` servers respectively.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum ServerTag {
@@ -56,8 +59,8 @@ impl From<&str> for ServerTag {
     #[inline]
     fn from(tag: &str) -> Self {
         match tag {
-            "main" => Main,
-            "debug" => Debug,
+            "main" | "public" => Main,
+            "debug" | "internal" => Debug,
             _ => Standby(tag.to_owned()),
         }
     }
@@ -1,5 +1,5 @@
 use super::Application;
-use crate::extension::TomlTableExt;
+use crate::{error::Error, extension::TomlTableExt};
 use std::{fs, io, sync::OnceLock, time::Duration};
 use tracing::Level;
 use tracing_appender::{
@@ -10,6 +10,8 @@ use tracing_subscriber::{
     filter::LevelFilter,
     fmt::{time::OffsetTime, writer::MakeWriterExt},
     layer::SubscriberExt,
+    reload::{self, Handle},
+    Registry,
 };
 
 #[cfg(feature = "sentry")]
@@ -18,6 +20,14 @@ use sentry_tracing::EventFilter;
 #[cfg(feature = "env-filter")]
 use tracing_subscriber::filter::EnvFilter;
 
+/// Filter of the tracing subscriber which can be reloaded at runtime.
+#[cfg(feature = "env-filter")]
+type TracingFilter = EnvFilter;
+
+/// Filter of the tracing subscriber which can be reloaded at runtime.
+#[cfg(not(feature = "env-filter"))]
+type TracingFilter = LevelFilter;
+
 /// Initializes the tracing subscriber.
 pub(super) fn init<APP: Application + ?Sized>() {
     if TRACING_APPENDER_GUARD.get().is_some() {
@@ -39,22 +49,11 @@ pub(super) fn init<APP: Application + ?Sized>() {
     let app_env = APP::env();
     let in_dev_mode = app_env.is_dev();
     let mut event_format = if in_dev_mode { "pretty" } else { "json" };
-    let mut level_filter = if in_dev_mode {
-        LevelFilter::INFO
-    } else {
-        LevelFilter::WARN
-    };
     let mut stdout_max_level = if in_dev_mode {
         Level::DEBUG
     } else {
         Level::WARN
     };
-    #[cfg(feature = "env-filter")]
-    let mut env_filter = if in_dev_mode {
-        "info,zino=trace,zino_core=trace"
-    } else {
-        "warn,zino=info,zino_core=info"
-    };
 
     let mut log_dir = "logs";
     let mut log_rotation = "hourly";
@@ -82,11 +81,6 @@ pub(super) fn init<APP: Application + ?Sized>() {
         }
         if let Some(level) = config.get_str("level") {
             stdout_max_level = level.parse().expect("fail to parse the level");
-            level_filter = level.parse().expect("fail to parse the level filter");
-        }
-        #[cfg(feature = "env-filter")]
-        if let Some(filter) = config.get_str("filter") {
-            env_filter = filter;
         }
         ansi_terminal = config.get_bool("ansi").unwrap_or(true);
         display_target = config.get_bool("display-target").unwrap_or(true);
@@ -138,12 +132,11 @@ pub(super) fn init<APP: Application + ?Sized>() {
         .with_timer(local_offset_time)
         .with_writer(stdout.and(non_blocking_appender));
 
+    // Filter layer which can be reloaded at runtime
+    let tracing_filter = new_tracing_filter::<APP>().expect("fail to parse the tracing filter");
+    let (filter_layer, filter_handle) = reload::Layer::new(tracing_filter);
+
     // Optional layers
-    #[cfg(feature = "env-filter")]
-    let env_filter_layer = EnvFilter::builder()
-        .with_default_directive(level_filter.into())
-        .parse(env_filter)
-        .expect("fail to parse the env filter");
     #[cfg(feature = "sentry")]
     let sentry_layer = sentry_tracing::layer()
         .enable_span_attributes()
@@ -152,14 +145,15 @@ pub(super) fn init<APP: Application + ?Sized>() {
             Level::WARN | Level::INFO => EventFilter::Breadcrumb,
             _ => EventFilter::Ignore,
         });
+    #[cfg(feature = "otel")]
+    let otel_layer = super::otlp_exporter::init::<APP>()
+        .map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer));
 
-    let subscriber = tracing_subscriber::registry();
-    #[cfg(feature = "env-filter")]
-    let subscriber = subscriber.with(env_filter_layer);
-    #[cfg(not(feature = "env-filter"))]
-    let subscriber = subscriber.with(level_filter);
+    let subscriber = tracing_subscriber::registry().with(filter_layer);
     #[cfg(feature = "sentry")]
     let subscriber = subscriber.with(sentry_layer);
+    #[cfg(feature = "otel")]
+    let subscriber = subscriber.with(otel_layer);
     match event_format {
         "compact" => {
             let compact_fmt_layer = fmt_layer.compact();
@@ -200,7 +194,67 @@ pub(super) fn init<APP: Application + ?Sized>() {
     TRACING_APPENDER_GUARD
         .set(worker_guard)
         .expect("fail to set the worker guard for the tracing appender");
+    TRACING_FILTER_HANDLE
+        .set(filter_handle)
+        .expect("fail to set the reload handle for the tracing filter");
+}
+
+/// Reloads the tracing filter with the `level` and `filter` in the `[tracing]` table.
+pub(super) fn reload<APP: Application + ?Sized>() {
+    let Some(handle) = TRACING_FILTER_HANDLE.get() else {
+        return;
+    };
+    match new_tracing_filter::<APP>() {
+        Ok(tracing_filter) => {
+            if let Err(err) = handle.reload(tracing_filter) {
+                tracing::error!("fail to reload the tracing filter: {err}");
+            }
+        }
+        Err(err) => tracing::error!("fail to parse the tracing filter: {err}"),
+    }
+}
+
+/// Creates a new tracing filter with the `level` and `filter` in the `[tracing]` table.
+fn new_tracing_filter<APP: Application + ?Sized>() -> Result<TracingFilter, Error> {
+    let in_dev_mode = APP::env().is_dev();
+    let config = APP::config().get_table("tracing");
+    let level_filter = if let Some(level) = config.and_then(|config| config.get_str("level")) {
+        level.parse()?
+    } else if in_dev_mode {
+        LevelFilter::INFO
+    } else {
+        LevelFilter::WARN
+    };
+
+    #[cfg(feature = "env-filter")]
+    {
+        let default_filter = if in_dev_mode {
+            "info,zino=trace,zino_core=trace"
+        } else {
+            "warn,zino=info,zino_core=info"
+        };
+        let filter = config
+            .and_then(|config| config.get_str("filter"))
+            .unwrap_or(default_filter);
+
+        // Elevates the level for the spans and events within the debug sessions
+        #[cfg(feature = "debug-session")]
+        let filter = format!(
+            "{filter},[{{debug_session=true}}]={}",
+            crate::trace::DebugSession::level()
+        );
+
+        let env_filter = EnvFilter::builder()
+            .with_default_directive(level_filter.into())
+            .parse(filter)?;
+        Ok(env_filter)
+    }
+    #[cfg(not(feature = "env-filter"))]
+    Ok(level_filter)
 }
 
 /// Tracing appender guard.
 static TRACING_APPENDER_GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+
+/// Reload handle for the tracing filter.
+static TRACING_FILTER_HANDLE: OnceLock<Handle<TracingFilter, Registry>> = OnceLock::new();
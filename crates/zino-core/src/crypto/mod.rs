@@ -24,8 +24,10 @@ cfg_if::cfg_if! {
 
 mod password;
 mod sha1;
+mod signature;
 
 pub use password::{
     encrypt_hashed_password, encrypt_raw_password, verify_hashed_password, verify_raw_password,
 };
 pub use sha1::checksum;
+pub use signature::{sign, verify_signature};
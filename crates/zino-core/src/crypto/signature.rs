@@ -0,0 +1,17 @@
+use super::Digest;
+use hmac::{Hmac, Mac};
+
+/// Signs the message with the key using HMAC.
+/// The underlying digest is SHA256 or SM3 with the `crypto-sm` feature.
+pub fn sign(message: &[u8], key: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Digest>::new_from_slice(key).expect("HMAC can take key of any size");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Verifies the HMAC signature of the message in constant time.
+pub fn verify_signature(message: &[u8], key: &[u8], signature: &[u8]) -> bool {
+    let mut mac = Hmac::<Digest>::new_from_slice(key).expect("HMAC can take key of any size");
+    mac.update(message);
+    mac.verify_slice(signature).is_ok()
+}
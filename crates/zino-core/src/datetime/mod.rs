@@ -618,6 +618,22 @@ impl DateTime {
             .checked_sub_days(Days::new(u64::from(days)))
             .map(Self)
     }
+
+    /// Adds a duration to the `DateTime`.
+    /// Returns `None` if the resulting date would be out of range.
+    #[inline]
+    pub fn checked_add(self, duration: Duration) -> Option<Self> {
+        let duration = chrono::Duration::from_std(duration).ok()?;
+        self.0.checked_add_signed(duration).map(Self)
+    }
+
+    /// Subtracts a duration from the `DateTime`.
+    /// Returns `None` if the resulting date would be out of range.
+    #[inline]
+    pub fn checked_sub(self, duration: Duration) -> Option<Self> {
+        let duration = chrono::Duration::from_std(duration).ok()?;
+        self.0.checked_sub_signed(duration).map(Self)
+    }
 }
 
 impl Default for DateTime {
@@ -0,0 +1,318 @@
+//! Avro binary encoding and decoding with the Confluent wire format.
+//!
+//! The values of a [`Record`] are resolved against the writer schema before encoding,
+//! so that the records converted from models can be encoded with the schemas derived
+//! from the model definitions directly. In particular, the strings are converted into
+//! the logical types `decimal`, `date`, `timestamp-millis`, `timestamp-micros` and `uuid`.
+
+use crate::{
+    bail,
+    datetime::{Date, DateTime},
+    error::Error,
+    AvroValue, Decimal, Record,
+};
+use apache_avro::schema::{DecimalSchema, FixedSchema, RecordSchema, Schema};
+
+/// Magic byte of the Confluent wire format.
+const MAGIC_BYTE: u8 = 0;
+
+/// Length of the header in the Confluent wire format.
+const HEADER_LENGTH: usize = 5;
+
+/// Encodes the record as Avro binary data with the writer schema.
+pub fn encode(record: Record, schema: &Schema) -> Result<Vec<u8>, Error> {
+    let value = normalize_value(AvroValue::Record(record), schema)?.resolve(schema)?;
+    apache_avro::to_avro_datum(schema, value).map_err(Error::from)
+}
+
+/// Decodes the Avro binary data as a record with the writer schema.
+pub fn decode(bytes: &[u8], schema: &Schema) -> Result<Record, Error> {
+    let mut reader = bytes;
+    match apache_avro::from_avro_datum(schema, &mut reader, None)? {
+        AvroValue::Record(record) => Ok(record),
+        _ => bail!("the Avro data should be a record"),
+    }
+}
+
+/// Encodes the record as Avro binary data with the writer schema,
+/// prefixed with the 5-byte header of the Confluent wire format,
+/// i.e. a magic byte `0` and the schema ID as a big-endian `u32`.
+pub fn encode_with_schema_id(
+    record: Record,
+    schema: &Schema,
+    schema_id: u32,
+) -> Result<Vec<u8>, Error> {
+    let datum = encode(record, schema)?;
+    let mut bytes = Vec::with_capacity(HEADER_LENGTH + datum.len());
+    bytes.push(MAGIC_BYTE);
+    bytes.extend_from_slice(&schema_id.to_be_bytes());
+    bytes.extend_from_slice(&datum);
+    Ok(bytes)
+}
+
+/// Decodes the Avro binary data in the Confluent wire format as a record
+/// with the writer schema. Returns the schema ID and the record.
+pub fn decode_with_schema_id(bytes: &[u8], schema: &Schema) -> Result<(u32, Record), Error> {
+    let (schema_id, datum) = parse_schema_id(bytes)?;
+    let record = decode(datum, schema)?;
+    Ok((schema_id, record))
+}
+
+/// Parses the header of the Confluent wire format.
+/// Returns the schema ID and the remaining Avro binary data.
+pub fn parse_schema_id(bytes: &[u8]) -> Result<(u32, &[u8]), Error> {
+    if bytes.len() < HEADER_LENGTH {
+        bail!("the data is too short for the Confluent wire format");
+    }
+
+    let (header, datum) = bytes.split_at(HEADER_LENGTH);
+    if header[0] != MAGIC_BYTE {
+        bail!(
+            "invalid magic byte `{}` for the Confluent wire format",
+            header[0]
+        );
+    }
+
+    let schema_id = u32::from_be_bytes([header[1], header[2], header[3], header[4]]);
+    Ok((schema_id, datum))
+}
+
+/// Converts the value into the form expected by the schema.
+fn normalize_value(value: AvroValue, schema: &Schema) -> Result<AvroValue, Error> {
+    let value = match (value, schema) {
+        (AvroValue::Record(record), Schema::Record(RecordSchema { fields, lookup, .. })) => {
+            let mut normalized_record = Vec::with_capacity(record.len());
+            for (key, value) in record {
+                let value = if let Some(&index) = lookup.get(&key) {
+                    normalize_value(value, &fields[index].schema)?
+                } else {
+                    value
+                };
+                normalized_record.push((key, value));
+            }
+            AvroValue::Record(normalized_record)
+        }
+        (AvroValue::Array(values), Schema::Array(array_schema)) => values
+            .into_iter()
+            .map(|value| normalize_value(value, &array_schema.items))
+            .collect::<Result<Vec<_>, _>>()
+            .map(AvroValue::Array)?,
+        (AvroValue::Map(map), Schema::Map(map_schema)) => map
+            .into_iter()
+            .map(|(key, value)| Ok((key, normalize_value(value, &map_schema.types)?)))
+            .collect::<Result<_, Error>>()
+            .map(AvroValue::Map)?,
+        (AvroValue::Union(index, value), Schema::Union(union_schema)) => {
+            match union_schema.variants().get(index as usize) {
+                Some(schema) => AvroValue::Union(index, Box::new(normalize_value(*value, schema)?)),
+                None => AvroValue::Union(index, value),
+            }
+        }
+        (AvroValue::Null, Schema::Union(_)) => AvroValue::Null,
+        (value, Schema::Union(union_schema)) => union_schema
+            .variants()
+            .iter()
+            .filter(|schema| !matches!(schema, Schema::Null))
+            .find_map(|schema| {
+                normalize_value(value.clone(), schema)
+                    .ok()
+                    .filter(|value| value.validate(schema))
+            })
+            .unwrap_or(value),
+        (
+            value,
+            Schema::Decimal(DecimalSchema {
+                precision,
+                scale,
+                inner,
+            }),
+        ) => {
+            let decimal = match value {
+                AvroValue::Decimal(_) | AvroValue::Bytes(_) | AvroValue::Fixed(..) => {
+                    return Ok(value);
+                }
+                AvroValue::String(s) => s.parse::<Decimal>()?,
+                AvroValue::Int(i) => i.into(),
+                AvroValue::Long(i) => i.into(),
+                AvroValue::Float(f) => f.try_into()?,
+                AvroValue::Double(f) => f.try_into()?,
+                _ => bail!("invalid value for the Avro `decimal` type"),
+            };
+            let min_len = match inner.as_ref() {
+                Schema::Fixed(FixedSchema { size, .. }) => *size,
+                _ => min_len_for_precision(*precision),
+            };
+            encode_decimal(decimal, *scale, min_len)?
+        }
+        (AvroValue::String(s), Schema::Date) => s.parse::<Date>()?.into(),
+        (AvroValue::String(s), Schema::TimestampMillis) => {
+            AvroValue::TimestampMillis(s.parse::<DateTime>()?.timestamp_millis())
+        }
+        (AvroValue::String(s), Schema::TimestampMicros) => {
+            AvroValue::TimestampMicros(s.parse::<DateTime>()?.timestamp_micros())
+        }
+        (value, _) => value,
+    };
+    Ok(value)
+}
+
+/// Encodes the decimal as the unscaled integer in the big-endian two's-complement representation
+/// with at least `min_len` bytes.
+fn encode_decimal(mut decimal: Decimal, scale: usize, min_len: usize) -> Result<AvroValue, Error> {
+    let scale = u32::try_from(scale)?;
+    if decimal.scale() > scale && decimal.round_dp(scale) != decimal {
+        bail!(
+            "the decimal `{}` can not be represented with the scale {}",
+            decimal,
+            scale
+        );
+    }
+    decimal.rescale(scale);
+    if decimal.scale() != scale {
+        bail!(
+            "the decimal `{}` is out of range for the scale {}",
+            decimal,
+            scale
+        );
+    }
+
+    // Removes the redundant sign-extension bytes, and then pads them to the minimum length
+    // since the decimal values shorter than the precision are rejected by `apache_avro`.
+    let mantissa = decimal.mantissa();
+    let bytes = mantissa.to_be_bytes();
+    let mut start = 0;
+    while start + 1 < bytes.len() {
+        let (byte, next_byte) = (bytes[start], bytes[start + 1]);
+        if (byte == 0x00 && next_byte & 0x80 == 0) || (byte == 0xff && next_byte & 0x80 != 0) {
+            start += 1;
+        } else {
+            break;
+        }
+    }
+
+    let sign_byte = if mantissa < 0 { 0xff } else { 0x00 };
+    let mut decimal_bytes = vec![sign_byte; min_len.saturating_sub(bytes.len() - start)];
+    decimal_bytes.extend_from_slice(&bytes[start..]);
+    Ok(AvroValue::Decimal(decimal_bytes.into()))
+}
+
+/// Returns the minimum number of bytes to hold the unscaled integers of the precision.
+fn min_len_for_precision(precision: usize) -> usize {
+    let mut len = 1;
+    while ((2.0_f64.powi(8 * len - 1) - 1.0).log10().floor() as usize) < precision {
+        len += 1;
+    }
+    len as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{extension::AvroRecordExt, Uuid};
+
+    const SCHEMA: &str = r#"
+        {
+            "type": "record",
+            "name": "Order",
+            "fields": [
+                { "name": "id", "type": { "type": "string", "logicalType": "uuid" } },
+                {
+                    "name": "amount",
+                    "type": { "type": "bytes", "logicalType": "decimal", "precision": 12, "scale": 2 }
+                },
+                { "name": "created_at", "type": { "type": "long", "logicalType": "timestamp-micros" } },
+                { "name": "due_date", "type": { "type": "int", "logicalType": "date" } },
+                { "name": "note", "type": ["null", "string"], "default": null }
+            ]
+        }
+    "#;
+
+    #[test]
+    fn it_encodes_logical_types() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let id = Uuid::new_v4();
+        let created_at = "2025-03-01T08:30:15.123456Z".parse::<DateTime>().unwrap();
+        let record = vec![
+            ("id".to_owned(), id.to_string().into()),
+            ("amount".to_owned(), "-1234.5".into()),
+            ("created_at".to_owned(), created_at.to_string().into()),
+            ("due_date".to_owned(), "2025-03-31".into()),
+            ("note".to_owned(), "urgent".into()),
+        ];
+        let bytes = encode(record, &schema).unwrap();
+        let decoded_record = decode(&bytes, &schema).unwrap();
+        assert_eq!(decoded_record.find("id"), Some(&AvroValue::Uuid(id)));
+        assert_eq!(
+            decoded_record.find("amount"),
+            Some(&AvroValue::Decimal(
+                (-123450_i64).to_be_bytes()[2..].to_vec().into()
+            ))
+        );
+        assert_eq!(
+            decoded_record.find("created_at"),
+            Some(&AvroValue::TimestampMicros(created_at.timestamp_micros()))
+        );
+        assert_eq!(
+            decoded_record.find("due_date"),
+            Some(&"2025-03-31".parse::<Date>().unwrap().into())
+        );
+        assert_eq!(
+            decoded_record.find("note"),
+            Some(&AvroValue::Union(1, Box::new("urgent".into())))
+        );
+
+        let reencoded_bytes = encode(decoded_record, &schema).unwrap();
+        assert_eq!(bytes, reencoded_bytes);
+    }
+
+    #[test]
+    fn it_encodes_decimals() {
+        assert_eq!(
+            encode_decimal(Decimal::new(12345, 2), 2, 1).unwrap(),
+            AvroValue::Decimal(vec![0x30, 0x39].into())
+        );
+        assert_eq!(
+            encode_decimal(Decimal::new(128, 0), 0, 1).unwrap(),
+            AvroValue::Decimal(vec![0x00, 0x80].into())
+        );
+        assert_eq!(
+            encode_decimal(Decimal::new(-128, 0), 0, 1).unwrap(),
+            AvroValue::Decimal(vec![0x80].into())
+        );
+        assert_eq!(
+            encode_decimal(Decimal::new(15, 1), 3, 1).unwrap(),
+            AvroValue::Decimal(vec![0x05, 0xdc].into())
+        );
+        assert_eq!(
+            encode_decimal(Decimal::new(-128, 0), 0, 3).unwrap(),
+            AvroValue::Decimal(vec![0xff, 0xff, 0x80].into())
+        );
+        assert!(encode_decimal(Decimal::new(1234, 3), 2, 1).is_err());
+
+        assert_eq!(min_len_for_precision(2), 1);
+        assert_eq!(min_len_for_precision(4), 2);
+        assert_eq!(min_len_for_precision(12), 6);
+    }
+
+    #[test]
+    fn it_frames_confluent_wire_format() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let record = vec![
+            ("id".to_owned(), Uuid::nil().to_string().into()),
+            ("amount".to_owned(), AvroValue::Long(42)),
+            ("created_at".to_owned(), AvroValue::TimestampMicros(0)),
+            ("due_date".to_owned(), AvroValue::Date(0)),
+        ];
+        let bytes = encode_with_schema_id(record, &schema, 258).unwrap();
+        assert_eq!(bytes[..5], [0, 0, 0, 1, 2]);
+
+        let (schema_id, record) = decode_with_schema_id(&bytes, &schema).unwrap();
+        assert_eq!(schema_id, 258);
+        assert_eq!(
+            record.find("note"),
+            Some(&AvroValue::Union(0, Box::new(AvroValue::Null)))
+        );
+        assert!(parse_schema_id(&[1, 0, 0, 0, 1]).is_err());
+        assert!(parse_schema_id(&[0, 0, 0]).is_err());
+    }
+}
@@ -0,0 +1,359 @@
+//! CSV and TSV encoding.
+
+use crate::{JsonValue, Map};
+use csv::{ByteRecord, QuoteStyle, Writer, WriterBuilder};
+use std::{
+    io::{self, ErrorKind, Write},
+    mem,
+};
+
+/// The UTF-8 byte order mark.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Quoting style of the fields.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CsvQuoteStyle {
+    /// Quotes the fields only when necessary.
+    #[default]
+    Necessary,
+    /// Quotes all the fields.
+    Always,
+    /// Quotes all the non-numeric fields.
+    NonNumeric,
+    /// Never quotes the fields.
+    Never,
+}
+
+/// Layout of the nested objects and arrays.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CsvNestedLayout {
+    /// Stringifies the nested objects and arrays as JSON.
+    #[default]
+    Json,
+    /// Flattens the nested objects with dot-joined headers,
+    /// and stringifies the arrays as JSON.
+    Flatten,
+}
+
+/// Options for the CSV writer.
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    /// Field delimiter.
+    delimiter: u8,
+    /// Quoting style.
+    quote_style: CsvQuoteStyle,
+    /// A flag to write the UTF-8 BOM.
+    bom: bool,
+    /// Representation of the null values.
+    null_value: String,
+    /// Layout of the nested objects and arrays.
+    nested_layout: CsvNestedLayout,
+    /// Preferred order of the columns.
+    columns: Vec<String>,
+}
+
+impl CsvOptions {
+    /// Creates a new instance for CSV.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            delimiter: b',',
+            quote_style: CsvQuoteStyle::default(),
+            bom: false,
+            null_value: String::new(),
+            nested_layout: CsvNestedLayout::default(),
+            columns: Vec::new(),
+        }
+    }
+
+    /// Creates a new instance for TSV.
+    #[inline]
+    pub fn tsv() -> Self {
+        Self::new().delimiter(b'\t')
+    }
+
+    /// Creates a new instance for the format which can be `csv` or `tsv`.
+    #[inline]
+    pub fn with_format(format: &str) -> Option<Self> {
+        match format {
+            "csv" => Some(Self::new()),
+            "tsv" => Some(Self::tsv()),
+            _ => None,
+        }
+    }
+
+    /// Sets the field delimiter.
+    #[inline]
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Sets the quoting style.
+    #[inline]
+    pub fn quote_style(mut self, quote_style: CsvQuoteStyle) -> Self {
+        self.quote_style = quote_style;
+        self
+    }
+
+    /// Writes the UTF-8 BOM at the beginning so that Excel detects the encoding.
+    #[inline]
+    pub fn bom(mut self, bom: bool) -> Self {
+        self.bom = bom;
+        self
+    }
+
+    /// Sets the representation of the null values. It is an empty string by default.
+    #[inline]
+    pub fn null_value(mut self, null_value: impl Into<String>) -> Self {
+        self.null_value = null_value.into();
+        self
+    }
+
+    /// Sets the layout of the nested objects and arrays.
+    #[inline]
+    pub fn nested_layout(mut self, nested_layout: CsvNestedLayout) -> Self {
+        self.nested_layout = nested_layout;
+        self
+    }
+
+    /// Sets the preferred order of the columns, such as the fields of a model.
+    /// The columns missing in the first row are skipped, and the remaining keys
+    /// are appended in the order of the first row.
+    #[inline]
+    pub fn columns<T: Into<String>>(mut self, columns: impl IntoIterator<Item = T>) -> Self {
+        self.columns = columns.into_iter().map(|col| col.into()).collect();
+        self
+    }
+
+    /// Returns `true` if the delimiter is a tab.
+    #[inline]
+    pub fn is_tsv(&self) -> bool {
+        self.delimiter == b'\t'
+    }
+
+    /// Returns the content type.
+    #[inline]
+    pub fn content_type(&self) -> &'static str {
+        if self.is_tsv() {
+            "text/tab-separated-values; charset=utf-8"
+        } else {
+            "text/csv; charset=utf-8"
+        }
+    }
+
+    /// Returns the file extension.
+    #[inline]
+    pub fn file_extension(&self) -> &'static str {
+        if self.is_tsv() {
+            "tsv"
+        } else {
+            "csv"
+        }
+    }
+
+    /// Builds a CSV writer.
+    fn build_writer<W: Write>(&self, writer: W) -> Writer<W> {
+        let quote_style = match self.quote_style {
+            CsvQuoteStyle::Necessary => QuoteStyle::Necessary,
+            CsvQuoteStyle::Always => QuoteStyle::Always,
+            CsvQuoteStyle::NonNumeric => QuoteStyle::NonNumeric,
+            CsvQuoteStyle::Never => QuoteStyle::Never,
+        };
+        WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .quote_style(quote_style)
+            .has_headers(false)
+            .from_writer(writer)
+    }
+}
+
+impl Default for CsvOptions {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A writer which encodes the JSON objects as CSV records.
+///
+/// The headers are derived from the first row, and the rows are written incrementally.
+pub struct CsvWriter<W: Write> {
+    /// Inner writer.
+    writer: Writer<W>,
+    /// Options.
+    options: CsvOptions,
+    /// Headers derived from the first row.
+    headers: Option<Vec<String>>,
+}
+
+impl<W: Write> CsvWriter<W> {
+    /// Creates a new instance, writing the UTF-8 BOM if enabled.
+    pub fn new(mut writer: W, options: CsvOptions) -> Result<Self, csv::Error> {
+        if options.bom {
+            writer.write_all(UTF8_BOM)?;
+        }
+        Ok(Self {
+            writer: options.build_writer(writer),
+            options,
+            headers: None,
+        })
+    }
+
+    /// Returns the headers if the first row has been written.
+    #[inline]
+    pub fn headers(&self) -> Option<&[String]> {
+        self.headers.as_deref()
+    }
+
+    /// Writes a row. The headers are written before the first row.
+    pub fn write_map(&mut self, map: &Map) -> Result<(), csv::Error> {
+        let flattened;
+        let map = if self.options.nested_layout == CsvNestedLayout::Flatten {
+            let mut entries = Map::new();
+            flatten_map(&mut entries, "", map);
+            flattened = entries;
+            &flattened
+        } else {
+            map
+        };
+        let headers = match self.headers.take() {
+            Some(headers) => headers,
+            None => {
+                let headers = self.derive_headers(map);
+                self.writer.write_record(&headers)?;
+                headers
+            }
+        };
+
+        let null_value = self.options.null_value.as_bytes();
+        let mut record = ByteRecord::with_capacity(headers.len() * 8, headers.len());
+        for field in headers.iter() {
+            match map.get(field) {
+                None | Some(JsonValue::Null) => record.push_field(null_value),
+                Some(JsonValue::String(s)) => record.push_field(s.as_bytes()),
+                Some(value) => record.push_field(value.to_string().as_bytes()),
+            }
+        }
+        self.headers = Some(headers);
+        self.writer.write_byte_record(&record)
+    }
+
+    /// Writes the rows of a JSON array or a JSON object.
+    pub fn write_value(&mut self, value: &JsonValue) -> Result<(), csv::Error> {
+        match value {
+            JsonValue::Array(vec) => {
+                for value in vec {
+                    if let JsonValue::Object(map) = value {
+                        self.write_map(map)?;
+                    }
+                }
+                Ok(())
+            }
+            JsonValue::Object(map) => self.write_map(map),
+            _ => Err(io::Error::new(ErrorKind::InvalidData, "invalid JSON value for CSV").into()),
+        }
+    }
+
+    /// Flushes the buffered data to the inner writer.
+    #[inline]
+    pub fn flush(&mut self) -> Result<(), csv::Error> {
+        self.writer.flush().map_err(csv::Error::from)
+    }
+
+    /// Flushes the buffered data and returns the inner writer.
+    #[inline]
+    pub fn into_inner(self) -> Result<W, csv::Error> {
+        self.writer
+            .into_inner()
+            .map_err(|err| err.into_error().into())
+    }
+
+    /// Derives the headers from the first row in the preferred order of the columns.
+    fn derive_headers(&self, map: &Map) -> Vec<String> {
+        let columns = &self.options.columns;
+        let mut headers = Vec::with_capacity(map.len());
+        for col in columns {
+            let prefix = format!("{col}.");
+            for key in map.keys() {
+                if key == col || key.starts_with(&prefix) {
+                    headers.push(key.to_owned());
+                }
+            }
+        }
+        for key in map.keys() {
+            if !headers.contains(key) {
+                headers.push(key.to_owned());
+            }
+        }
+        headers
+    }
+}
+
+impl CsvWriter<Vec<u8>> {
+    /// Takes the encoded bytes, leaving an empty buffer in its place.
+    pub fn take_bytes(&mut self) -> Result<Vec<u8>, csv::Error> {
+        let writer = self.options.build_writer(Vec::new());
+        mem::replace(&mut self.writer, writer)
+            .into_inner()
+            .map_err(|err| err.into_error().into())
+    }
+}
+
+/// Flattens the nested objects with dot-joined keys.
+fn flatten_map(entries: &mut Map, prefix: &str, map: &Map) {
+    for (key, value) in map {
+        let key = if prefix.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        if let JsonValue::Object(map) = value {
+            flatten_map(entries, &key, map);
+        } else {
+            entries.insert(key, value.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CsvNestedLayout, CsvOptions, CsvQuoteStyle, CsvWriter};
+    use crate::json;
+
+    #[test]
+    fn it_writes_csv_rows() {
+        let rows = json!([
+            { "name": "Élise", "id": 1, "tags": ["a", "b"], "address": { "city": "Paris" } },
+            { "name": "Bob", "id": 2, "tags": [], "address": null },
+        ]);
+        let options = CsvOptions::new().columns(["id", "name", "address"]);
+        let mut writer = CsvWriter::new(Vec::new(), options).unwrap();
+        writer.write_value(&rows).unwrap();
+        assert_eq!(
+            String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+            "id,name,address,tags\n\
+                1,Élise,\"{\"\"city\"\":\"\"Paris\"\"}\",\"[\"\"a\"\",\"\"b\"\"]\"\n\
+                2,Bob,,[]\n"
+        );
+
+        let options = CsvOptions::tsv()
+            .bom(true)
+            .null_value("NULL")
+            .quote_style(CsvQuoteStyle::NonNumeric)
+            .nested_layout(CsvNestedLayout::Flatten)
+            .columns(["id", "name", "address"]);
+        let mut writer = CsvWriter::new(Vec::new(), options).unwrap();
+        writer.write_value(&rows).unwrap();
+        let bytes = writer.take_bytes().unwrap();
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            "\u{feff}\"id\"\t\"name\"\t\"address.city\"\t\"tags\"\n\
+                1\t\"Élise\"\t\"Paris\"\t\"[\"\"a\"\",\"\"b\"\"]\"\n\
+                2\t\"Bob\"\t\"NULL\"\t\"[]\"\n"
+        );
+        assert_eq!(writer.take_bytes().unwrap(), b"");
+    }
+}
@@ -1,4 +1,9 @@
 //! Encoding and decoding.
 
+pub mod avro;
 pub mod base64;
+pub mod csv;
 pub mod hex;
+
+#[cfg(feature = "schema-registry")]
+pub mod schema_registry;
@@ -0,0 +1,180 @@
+//! Client for the Confluent schema registry.
+
+use super::{avro, base64};
+use crate::{
+    application::{Agent, Application},
+    error::Error,
+    extension::{JsonObjectExt, TomlTableExt},
+    state::State,
+    warn, JsonValue, LazyLock, Map, Record,
+};
+use apache_avro::Schema;
+use parking_lot::RwLock;
+use std::{collections::HashMap, sync::Arc};
+
+/// A client for the Confluent schema registry with an in-process cache of schema IDs.
+///
+/// # Examples
+///
+/// ```toml
+/// [schema-registry]
+/// base-url = "http://localhost:8081"
+/// username = "registry"
+/// password = "secret"
+/// ```
+#[derive(Debug)]
+pub struct SchemaRegistry {
+    /// Base URL.
+    base_url: String,
+    /// Request options.
+    options: Map,
+    /// Registered schema IDs keyed by the subject and the schema.
+    schema_ids: RwLock<HashMap<(String, String), u32>>,
+    /// Schemas keyed by the ID.
+    schemas: RwLock<HashMap<u32, Arc<Schema>>>,
+}
+
+impl SchemaRegistry {
+    /// Creates a new instance with the base URL.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let mut headers = Map::new();
+        headers.upsert("accept", CONTENT_TYPE);
+        headers.upsert("content-type", CONTENT_TYPE);
+
+        let mut options = Map::new();
+        options.upsert("headers", headers);
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_owned(),
+            options,
+            schema_ids: RwLock::new(HashMap::new()),
+            schemas: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the credentials for the HTTP basic authentication.
+    pub fn set_basic_auth(&mut self, username: &str, password: &str) {
+        let credentials = base64::encode(format!("{username}:{password}"));
+        if let Some(headers) = self
+            .options
+            .get_mut("headers")
+            .and_then(|v| v.as_object_mut())
+        {
+            headers.upsert("authorization", format!("Basic {credentials}"));
+        }
+    }
+
+    /// Returns a reference to the shared schema registry
+    /// with the config `[schema-registry]`.
+    #[inline]
+    pub fn shared() -> &'static Self {
+        &SHARED_SCHEMA_REGISTRY
+    }
+
+    /// Registers the schema under the subject and returns the schema ID.
+    /// It is a no-op if the schema has been registered by the client.
+    pub async fn register(&self, subject: &str, schema: &Schema) -> Result<u32, Error> {
+        let schema_str = serde_json::to_string(schema)?;
+        let key = (subject.to_owned(), schema_str);
+        if let Some(&schema_id) = self.schema_ids.read().get(&key) {
+            return Ok(schema_id);
+        }
+
+        let url = format!("{}/subjects/{subject}/versions", self.base_url);
+        let mut body = Map::new();
+        body.upsert("schema", key.1.as_str());
+
+        let mut options = self.options.clone();
+        options.upsert("method", "POST");
+        options.upsert("data_type", "json");
+        options.upsert("body", body);
+
+        let data = Agent::fetch_json::<Map>(&url, Some(&options)).await?;
+        let schema_id = parse_schema_id(&data)?;
+        self.schema_ids.write().insert(key, schema_id);
+        self.schemas
+            .write()
+            .entry(schema_id)
+            .or_insert_with(|| Arc::new(schema.clone()));
+        Ok(schema_id)
+    }
+
+    /// Fetches the schema by the ID.
+    pub async fn fetch_schema(&self, schema_id: u32) -> Result<Arc<Schema>, Error> {
+        if let Some(schema) = self.schemas.read().get(&schema_id) {
+            return Ok(schema.clone());
+        }
+
+        let url = format!("{}/schemas/ids/{schema_id}", self.base_url);
+        let data = Agent::fetch_json::<Map>(&url, Some(&self.options)).await?;
+        let schema = Arc::new(parse_schema(&data)?);
+        self.schemas.write().insert(schema_id, schema.clone());
+        Ok(schema)
+    }
+
+    /// Fetches the latest version of the schema registered under the subject.
+    /// Returns the schema ID and the schema.
+    pub async fn fetch_latest_schema(&self, subject: &str) -> Result<(u32, Arc<Schema>), Error> {
+        let url = format!("{}/subjects/{subject}/versions/latest", self.base_url);
+        let data = Agent::fetch_json::<Map>(&url, Some(&self.options)).await?;
+        let schema_id = parse_schema_id(&data)?;
+        let schema = Arc::new(parse_schema(&data)?);
+        self.schemas.write().insert(schema_id, schema.clone());
+        Ok((schema_id, schema))
+    }
+
+    /// Registers the writer schema under the subject and encodes the record
+    /// as Avro binary data in the Confluent wire format.
+    pub async fn encode(
+        &self,
+        subject: &str,
+        record: Record,
+        schema: &Schema,
+    ) -> Result<Vec<u8>, Error> {
+        let schema_id = self.register(subject, schema).await?;
+        avro::encode_with_schema_id(record, schema, schema_id)
+    }
+
+    /// Decodes the Avro binary data in the Confluent wire format as a record
+    /// with the writer schema fetched by the schema ID.
+    pub async fn decode(&self, bytes: &[u8]) -> Result<Record, Error> {
+        let (schema_id, datum) = avro::parse_schema_id(bytes)?;
+        let schema = self.fetch_schema(schema_id).await?;
+        avro::decode(datum, &schema)
+    }
+}
+
+/// Parses the schema ID in the response data.
+fn parse_schema_id(data: &Map) -> Result<u32, Error> {
+    data.get_u32("id")
+        .ok_or_else(|| warn!("the schema ID should be returned by the schema registry"))
+}
+
+/// Parses the schema in the response data.
+fn parse_schema(data: &Map) -> Result<Schema, Error> {
+    match data.get("schema") {
+        Some(JsonValue::String(schema)) => Schema::parse_str(schema).map_err(Error::from),
+        _ => Err(warn!(
+            "the schema should be returned by the schema registry"
+        )),
+    }
+}
+
+/// Content type for the schema registry API.
+const CONTENT_TYPE: &str = "application/vnd.schemaregistry.v1+json";
+
+/// Shared schema registry.
+static SHARED_SCHEMA_REGISTRY: LazyLock<SchemaRegistry> = LazyLock::new(|| {
+    let config = State::shared()
+        .get_config("schema-registry")
+        .expect("field `schema-registry` should be a table");
+    let base_url = config
+        .get_str("base-url")
+        .expect("field `base-url` should be a str");
+    let mut registry = SchemaRegistry::new(base_url);
+    if let (Some(username), Some(password)) =
+        (config.get_str("username"), config.get_str("password"))
+    {
+        registry.set_basic_auth(username, password);
+    }
+    registry
+});
@@ -0,0 +1,90 @@
+use crate::SharedString;
+use std::{borrow::Cow, fmt};
+
+/// A list specifying general categories of errors.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The requested resource does not exist.
+    NotFound,
+    /// The operation conflicts with the current state of a resource,
+    /// such as a unique constraint violation.
+    Conflict,
+    /// The input is invalid, such as a foreign key constraint violation.
+    Validation,
+    /// The request lacks valid authentication credentials.
+    Unauthorized,
+    /// The request is authenticated but not permitted.
+    Forbidden,
+    /// The operation does not complete in time.
+    Timeout,
+    /// A dependent service or resource is temporarily unavailable.
+    Unavailable,
+    /// An internal error. It is the default kind.
+    #[default]
+    Internal,
+    /// A custom error kind with the error code.
+    Other(SharedString),
+}
+
+impl ErrorKind {
+    /// Returns the error code as a string.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Other(code) => code.as_ref(),
+            _ => self.static_str(),
+        }
+    }
+
+    /// Returns the error code as a static string for the builtin kinds.
+    fn static_str(&self) -> &'static str {
+        match self {
+            Self::NotFound => "not_found",
+            Self::Conflict => "conflict",
+            Self::Validation => "validation",
+            Self::Unauthorized => "unauthorized",
+            Self::Forbidden => "forbidden",
+            Self::Timeout => "timeout",
+            Self::Unavailable => "unavailable",
+            Self::Internal | Self::Other(_) => "internal",
+        }
+    }
+
+    /// Returns the error code.
+    #[inline]
+    pub fn error_code(&self) -> SharedString {
+        match self {
+            Self::Other(code) => code.clone(),
+            _ => Cow::Borrowed(self.static_str()),
+        }
+    }
+
+    /// Returns the corresponding HTTP status code.
+    #[inline]
+    pub fn status_code(&self) -> u16 {
+        match self {
+            Self::NotFound => 404,
+            Self::Conflict => 409,
+            Self::Validation => 400,
+            Self::Unauthorized => 401,
+            Self::Forbidden => 403,
+            Self::Timeout => 504,
+            Self::Unavailable => 503,
+            Self::Internal | Self::Other(_) => 500,
+        }
+    }
+
+    /// Returns `true` if the error kind is [`ErrorKind::Internal`].
+    #[inline]
+    pub fn is_internal(&self) -> bool {
+        matches!(self, Self::Internal)
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -2,8 +2,11 @@
 use crate::SharedString;
 use std::{any::Any, error, fmt};
 
+mod kind;
 mod source;
 
+pub use kind::ErrorKind;
+
 use source::Source;
 
 /// An error type backed by an allocation-optimized string.
@@ -11,6 +14,8 @@ use source::Source;
 pub struct Error {
     /// Error message.
     message: SharedString,
+    /// Error kind.
+    kind: ErrorKind,
     /// Error source.
     source: Option<Box<Error>>,
     /// Error context.
@@ -22,6 +27,7 @@ impl Clone for Error {
     fn clone(&self) -> Self {
         Self {
             message: self.message.clone(),
+            kind: self.kind.clone(),
             source: self.source.clone(),
             context: None,
         }
@@ -31,27 +37,32 @@ impl Clone for Error {
 impl PartialEq for Error {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
-        self.message == other.message && self.source == other.source
+        self.message == other.message && self.kind == other.kind && self.source == other.source
     }
 }
 
 impl Error {
     /// Creates a new instance with the supplied message.
+    /// The error kind defaults to [`ErrorKind::Internal`].
     #[inline]
     pub fn new(message: impl Into<SharedString>) -> Self {
         Self {
             message: message.into(),
+            kind: ErrorKind::Internal,
             source: None,
             context: None,
         }
     }
 
     /// Creates a new instance with the supplied message and the error source.
+    /// The error kind is inherited from the source.
     #[inline]
     pub fn with_source(message: impl Into<SharedString>, source: impl Into<Error>) -> Self {
+        let source = source.into();
         Self {
             message: message.into(),
-            source: Some(Box::new(source.into())),
+            kind: source.kind.clone(),
+            source: Some(Box::new(source)),
             context: None,
         }
     }
@@ -61,21 +72,37 @@ impl Error {
     pub fn from_error(err: impl error::Error) -> Self {
         Self {
             message: err.to_string().into(),
+            kind: ErrorKind::Internal,
             source: err.source().map(|err| Box::new(Self::new(err.to_string()))),
             context: None,
         }
     }
 
     /// Wraps the error value with additional contextual message.
+    /// The error kind is preserved.
     #[inline]
     pub fn wrap(self, message: impl Into<SharedString>) -> Self {
         Self {
             message: message.into(),
+            kind: self.kind.clone(),
             source: Some(Box::new(self)),
             context: None,
         }
     }
 
+    /// Sets the error kind.
+    #[inline]
+    pub fn set_kind(&mut self, kind: ErrorKind) {
+        self.kind = kind;
+    }
+
+    /// Attaches the kind to the error.
+    #[inline]
+    pub fn with_kind(mut self, kind: ErrorKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
     /// Sets a context for the error.
     #[inline]
     pub fn set_context<T: Send + 'static>(&mut self, context: T) {
@@ -116,6 +143,12 @@ impl Error {
         self.message.as_ref()
     }
 
+    /// Returns the error kind.
+    #[inline]
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
     /// Returns the error source.
     #[inline]
     pub fn source(&self) -> Option<&Error> {
@@ -142,6 +175,7 @@ impl<E: error::Error + Send + 'static> From<E> for Error {
     fn from(err: E) -> Self {
         Self {
             message: err.to_string().into(),
+            kind: ErrorKind::Internal,
             source: err.source().map(|err| Box::new(Self::new(err.to_string()))),
             context: Some(Box::new(err)),
         }
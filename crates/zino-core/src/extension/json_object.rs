@@ -1,10 +1,12 @@
 use super::JsonValueExt;
 use crate::{
     datetime::{self, Date, DateTime, Time},
+    error::Error,
     helper,
     model::Model,
+    money::Money,
     validation::Validation,
-    JsonValue, Map, Record, Uuid,
+    JsonValue, Map, ParseUlidError, Record, Ulid, Uuid,
 };
 use chrono::NaiveDateTime;
 use convert_case::{Case, Casing};
@@ -191,9 +193,16 @@ pub trait JsonObjectExt {
     /// If the `Uuid` is `nil`, it also returns `None`.
     fn parse_uuid(&self, key: &str) -> Option<Result<Uuid, uuid::Error>>;
 
+    /// Extracts the string corresponding to the key and parses it as `Ulid`.
+    /// If the `Ulid` is `nil`, it also returns `None`.
+    fn parse_ulid(&self, key: &str) -> Option<Result<Ulid, ParseUlidError>>;
+
     /// Extracts the string corresponding to the key and parses it as `Decimal`.
     fn parse_decimal(&self, key: &str) -> Option<Result<Decimal, rust_decimal::Error>>;
 
+    /// Extracts the value corresponding to the key and parses it as `Money`.
+    fn parse_money(&self, key: &str) -> Option<Result<Money, Error>>;
+
     /// Extracts the string corresponding to the key and parses it as `Date`.
     fn parse_date(&self, key: &str) -> Option<Result<Date, chrono::format::ParseError>>;
 
@@ -661,11 +670,24 @@ impl JsonObjectExt for Map {
             .map(|s| s.parse())
     }
 
+    fn parse_ulid(&self, key: &str) -> Option<Result<Ulid, ParseUlidError>> {
+        self.get_str(key)
+            .filter(|s| !s.chars().all(|c| c == '0'))
+            .map(|s| s.parse())
+    }
+
     #[inline]
     fn parse_decimal(&self, key: &str) -> Option<Result<Decimal, rust_decimal::Error>> {
         self.get_str(key).map(|s| s.parse())
     }
 
+    #[inline]
+    fn parse_money(&self, key: &str) -> Option<Result<Money, Error>> {
+        self.get(key)
+            .filter(|value| !value.is_null() && value.as_str() != Some(""))
+            .map(Money::try_from)
+    }
+
     #[inline]
     fn parse_date(&self, key: &str) -> Option<Result<Date, chrono::format::ParseError>> {
         self.get_str(key).map(|s| s.parse())
@@ -1,14 +1,12 @@
 use crate::{
     datetime::{self, Date, DateTime, Time},
-    extension::JsonObjectExt,
-    helper, Decimal, JsonValue, Map, Uuid,
+    encoding::csv::{CsvOptions, CsvWriter},
+    helper, Decimal, JsonValue, Map, ParseUlidError, Ulid, Uuid,
 };
 use chrono::NaiveDateTime;
-use csv::{ByteRecord, Writer};
 use serde::de::DeserializeOwned;
 use std::{
     borrow::Cow,
-    io::{self, ErrorKind},
     num::{ParseFloatError, ParseIntError},
     str::{FromStr, ParseBoolError},
     time::Duration,
@@ -142,6 +140,10 @@ pub trait JsonValueExt {
     /// If the `Uuid` is `nil`, it also returns `None`.
     fn parse_uuid(&self) -> Option<Result<Uuid, uuid::Error>>;
 
+    /// Parses the JSON value as `Ulid`.
+    /// If the `Ulid` is `nil`, it also returns `None`.
+    fn parse_ulid(&self) -> Option<Result<Ulid, ParseUlidError>>;
+
     /// Parses the JSON value as `Decimal`.
     fn parse_decimal(&self) -> Option<Result<Decimal, rust_decimal::Error>>;
 
@@ -418,6 +420,12 @@ impl JsonValueExt for JsonValue {
             .map(|s| s.parse())
     }
 
+    fn parse_ulid(&self) -> Option<Result<Ulid, ParseUlidError>> {
+        self.as_str()
+            .filter(|s| !s.chars().all(|c| c == '0'))
+            .map(|s| s.parse())
+    }
+
     #[inline]
     fn parse_decimal(&self) -> Option<Result<Decimal, rust_decimal::Error>> {
         self.as_str().map(|s| s.parse())
@@ -461,53 +469,9 @@ impl JsonValueExt for JsonValue {
     }
 
     fn to_csv(&self, buffer: Vec<u8>) -> Result<Vec<u8>, csv::Error> {
-        match &self {
-            JsonValue::Array(vec) => {
-                let mut wtr = Writer::from_writer(buffer);
-                let mut headers = Vec::new();
-                if let Some(JsonValue::Object(map)) = vec.first() {
-                    for key in map.keys() {
-                        headers.push(key.to_owned());
-                    }
-                }
-                wtr.write_record(&headers)?;
-
-                let num_fields = headers.len();
-                let buffer_size = num_fields * 8;
-                for value in vec {
-                    if let JsonValue::Object(map) = value {
-                        let mut record = ByteRecord::with_capacity(buffer_size, num_fields);
-                        for field in headers.iter() {
-                            let value = map.parse_string(field).unwrap_or("".into());
-                            record.push_field(value.as_ref().as_bytes());
-                        }
-                        wtr.write_byte_record(&record)?;
-                    }
-                }
-                wtr.flush()?;
-                wtr.into_inner().map_err(|err| err.into_error().into())
-            }
-            JsonValue::Object(map) => {
-                let mut wtr = Writer::from_writer(buffer);
-                let mut headers = Vec::new();
-                for key in map.keys() {
-                    headers.push(key.to_owned());
-                }
-                wtr.write_record(&headers)?;
-
-                let num_fields = headers.len();
-                let buffer_size = num_fields * 8;
-                let mut record = ByteRecord::with_capacity(buffer_size, num_fields);
-                for field in headers.iter() {
-                    let value = map.parse_string(field).unwrap_or("".into());
-                    record.push_field(value.as_ref().as_bytes());
-                }
-                wtr.write_byte_record(&record)?;
-                wtr.flush()?;
-                wtr.into_inner().map_err(|err| err.into_error().into())
-            }
-            _ => Err(io::Error::new(ErrorKind::InvalidData, "invalid JSON value for CSV").into()),
-        }
+        let mut writer = CsvWriter::new(buffer, CsvOptions::default())?;
+        writer.write_value(self)?;
+        writer.into_inner()
     }
 
     fn to_jsonlines(&self, mut buffer: Vec<u8>) -> Result<Vec<u8>, serde_json::Error> {
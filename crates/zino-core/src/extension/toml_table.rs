@@ -183,9 +183,13 @@ impl TomlTableExt for Table {
         self.get(key).and_then(|v| v.as_float())
     }
 
-    #[inline]
     fn get_str(&self, key: &str) -> Option<&str> {
-        self.get(key).and_then(|v| v.as_str())
+        let value = self.get(key).and_then(|v| v.as_str())?;
+        #[cfg(feature = "runtime")]
+        if let Some(name) = value.strip_prefix(crate::state::SECRET_PREFIX) {
+            return crate::state::resolve_secret(name);
+        }
+        Some(value)
     }
 
     #[inline]
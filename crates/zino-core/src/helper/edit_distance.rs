@@ -0,0 +1,29 @@
+/// Computes the Levenshtein distance between two strings.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars = b.chars().collect::<Vec<_>>();
+    let mut distances = (0..=b_chars.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut prev_distance = distances[0];
+        distances[0] = i + 1;
+        for (j, &b_char) in b_chars.iter().enumerate() {
+            let substitution = prev_distance + usize::from(a_char != b_char);
+            prev_distance = distances[j + 1];
+            distances[j + 1] = substitution.min(distances[j] + 1).min(prev_distance + 1);
+        }
+    }
+    distances[b_chars.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::edit_distance;
+
+    #[test]
+    fn it_computes_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("port", "port"), 0);
+        assert_eq!(edit_distance("prot", "port"), 2);
+        assert_eq!(edit_distance("body-limt", "body-limit"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
@@ -1,4 +1,5 @@
 /// Masks text with masking options.
+#[cfg(feature = "runtime")]
 pub(crate) fn mask_text(text: &str, num_prefix_chars: usize, num_suffix_chars: usize) -> String {
     let length = text.len();
     let suffix_index = length.saturating_sub(num_suffix_chars);
@@ -12,3 +13,37 @@ pub(crate) fn mask_text(text: &str, num_prefix_chars: usize, num_suffix_chars: u
     }
     masked_text
 }
+
+/// Masks text with a format such as `last4`, `first4` or `all`.
+/// The number of masking chars is fixed so that the length of text is not exposed.
+pub(crate) fn mask_text_with_format(text: &str, format: &str) -> String {
+    let num_chars = text.chars().count();
+    if let Some(Ok(n)) = format.strip_prefix("last").map(|s| s.parse::<usize>()) {
+        if n < num_chars {
+            let suffix = text.chars().skip(num_chars - n).collect::<String>();
+            return ["****", &suffix].concat();
+        }
+    } else if let Some(Ok(n)) = format.strip_prefix("first").map(|s| s.parse::<usize>()) {
+        if n < num_chars {
+            let prefix = text.chars().take(n).collect::<String>();
+            return [&prefix, "****"].concat();
+        }
+    }
+    "****".to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mask_text_with_format;
+
+    #[test]
+    fn it_masks_text_with_format() {
+        assert_eq!(mask_text_with_format("6222021234", "last4"), "****1234");
+        assert_eq!(
+            mask_text_with_format("alice@example.com", "first2"),
+            "al****"
+        );
+        assert_eq!(mask_text_with_format("1234", "last4"), "****");
+        assert_eq!(mask_text_with_format("secret", "all"), "****");
+    }
+}
@@ -4,5 +4,13 @@ mod mask_text;
 mod str_array;
 
 pub(crate) use header::check_json_content_type;
-pub(crate) use mask_text::mask_text;
+pub(crate) use mask_text::mask_text_with_format;
 pub(crate) use str_array::parse_str_array;
+
+#[cfg(feature = "runtime")]
+mod edit_distance;
+
+#[cfg(feature = "runtime")]
+pub(crate) use edit_distance::edit_distance;
+#[cfg(feature = "runtime")]
+pub(crate) use mask_text::mask_text;
@@ -7,22 +7,31 @@
 
 mod helper;
 mod mock;
+mod ulid;
 
+#[cfg(feature = "runtime")]
 pub mod application;
+#[cfg(feature = "runtime")]
 pub mod crypto;
 pub mod datetime;
 pub mod encoding;
 pub mod error;
 pub mod extension;
 pub mod model;
+pub mod money;
+#[cfg(feature = "runtime")]
 pub mod schedule;
+#[cfg(feature = "runtime")]
 pub mod state;
+#[cfg(feature = "runtime")]
 pub mod trace;
 pub mod validation;
 
 #[doc(no_inline)]
 pub use serde_json::json;
 
+pub use ulid::{ParseUlidError, Ulid};
+
 /// A JSON value.
 pub type JsonValue = serde_json::Value;
 
@@ -18,13 +18,10 @@ const SUBDOMAINS: [&str; 6] = [
 const ROOT_DOMAINS: [&str; 7] = ["app", "com", "dev", "edu", "gov", "net", "org"];
 
 /// Generates a random email address.
-pub(crate) fn gen_email() -> String {
-    let mut rng = rand::rng();
+pub(crate) fn gen_email<R: Rng + ?Sized>(rng: &mut R) -> String {
     let num_chars = rng.random_range(1..=16);
-    let username = Alphanumeric
-        .sample_string(&mut rng, num_chars)
-        .to_lowercase();
-    let subdomain = SUBDOMAINS.choose(&mut rng).unwrap_or(&"example");
-    let root_domain = ROOT_DOMAINS.choose(&mut rng).unwrap_or(&"com");
+    let username = Alphanumeric.sample_string(rng, num_chars).to_lowercase();
+    let subdomain = SUBDOMAINS.choose(rng).unwrap_or(&"example");
+    let root_domain = ROOT_DOMAINS.choose(rng).unwrap_or(&"com");
     format!("{username}@{subdomain}.{root_domain}")
 }
@@ -2,8 +2,7 @@ use rand::Rng;
 use std::net::Ipv4Addr;
 
 /// Generates a random IPv4 address.
-pub(crate) fn gen_ipv4() -> String {
-    let mut rng = rand::rng();
+pub(crate) fn gen_ipv4<R: Rng + ?Sized>(rng: &mut R) -> String {
     let a = rng.random::<u8>();
     let b = rng.random::<u8>();
     let c = rng.random::<u8>();
@@ -2,8 +2,7 @@ use rand::Rng;
 use std::net::Ipv6Addr;
 
 /// Generates a random IPv6 address.
-pub(crate) fn gen_ipv6() -> String {
-    let mut rng = rand::rng();
+pub(crate) fn gen_ipv6<R: Rng + ?Sized>(rng: &mut R) -> String {
     let a = rng.random::<u16>();
     let b = rng.random::<u16>();
     let c = rng.random::<u16>();
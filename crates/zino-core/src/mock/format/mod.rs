@@ -10,24 +10,27 @@ mod phone_number;
 mod uri;
 
 /// Generates a random string with the format.
-pub(crate) fn gen_format(format: &str, length: Option<usize>) -> String {
-    let mut rng = rand::rng();
+pub(crate) fn gen_format<R: Rng + ?Sized>(
+    rng: &mut R,
+    format: &str,
+    length: Option<usize>,
+) -> String {
     match format {
-        "email" => email::gen_email(),
+        "email" => email::gen_email(rng),
         "ip" => {
             if rng.random::<bool>() {
-                ipv6::gen_ipv6()
+                ipv6::gen_ipv6(rng)
             } else {
-                ipv4::gen_ipv4()
+                ipv4::gen_ipv4(rng)
             }
         }
-        "ipv4" => ipv4::gen_ipv4(),
-        "ipv6" => ipv6::gen_ipv6(),
-        "phone-number" => phone_number::gen_phone_number(),
-        "uri" => uri::gen_uri(),
+        "ipv4" => ipv4::gen_ipv4(rng),
+        "ipv6" => ipv6::gen_ipv6(rng),
+        "phone-number" => phone_number::gen_phone_number(rng),
+        "uri" => uri::gen_uri(rng),
         _ => {
             let length = length.unwrap_or_else(|| rng.random_range(1..=32));
-            Alphanumeric.sample_string(&mut rng, length)
+            Alphanumeric.sample_string(rng, length)
         }
     }
 }
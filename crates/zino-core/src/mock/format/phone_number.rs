@@ -4,9 +4,8 @@ use rand::{seq::IndexedRandom, Rng};
 const COUNTRY_CODES: [&str; 3] = ["+1", "+49", "+86"];
 
 /// Generates a random phone number.
-pub(crate) fn gen_phone_number() -> String {
-    let mut rng = rand::rng();
-    let country_code = COUNTRY_CODES.choose(&mut rng).unwrap_or(&"+86");
+pub(crate) fn gen_phone_number<R: Rng + ?Sized>(rng: &mut R) -> String {
+    let country_code = COUNTRY_CODES.choose(rng).unwrap_or(&"+86");
     let national_number = match *country_code {
         "+1" => (0..10)
             .map(|i| match i {
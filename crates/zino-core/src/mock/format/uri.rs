@@ -1,6 +1,5 @@
 use rand::{
     distr::{Alphanumeric, SampleString},
-    random,
     seq::IndexedRandom,
     Rng,
 };
@@ -15,19 +14,18 @@ const SUBDOMAINS: [&str; 4] = ["example", "test", "www.example", "www.test"];
 const ROOT_DOMAINS: [&str; 3] = ["com", "net", "org"];
 
 /// Generates a random URI.
-pub(crate) fn gen_uri() -> String {
-    let mut rng = rand::rng();
+pub(crate) fn gen_uri<R: Rng + ?Sized>(rng: &mut R) -> String {
     let num_chars = rng.random_range(1..=16);
-    let mut path = Alphanumeric.sample_string(&mut rng, num_chars);
-    if random::<bool>() {
+    let mut path = Alphanumeric.sample_string(rng, num_chars);
+    if rng.random::<bool>() {
         let num_chars = rng.random_range(1..=16);
-        let segment = Alphanumeric.sample_string(&mut rng, num_chars);
+        let segment = Alphanumeric.sample_string(rng, num_chars);
         path.push('/');
         path.push_str(&segment);
     }
 
-    let scheme = SCHEMES.choose(&mut rng).unwrap_or(&"https");
-    let subdomain = SUBDOMAINS.choose(&mut rng).unwrap_or(&"example");
-    let root_domain = ROOT_DOMAINS.choose(&mut rng).unwrap_or(&"com");
+    let scheme = SCHEMES.choose(rng).unwrap_or(&"https");
+    let subdomain = SUBDOMAINS.choose(rng).unwrap_or(&"example");
+    let root_domain = ROOT_DOMAINS.choose(rng).unwrap_or(&"com");
     format!("{scheme}://{subdomain}.{root_domain}/{path}")
 }
@@ -3,19 +3,25 @@ use rand::{
     Rng,
 };
 
+#[cfg(feature = "locale")]
+use rand::seq::IndexedRandom;
 #[cfg(feature = "locale")]
 use random_word::Lang;
 
 /// Generates a random sentence for the language.
-pub(crate) fn gen_random_sentence(locale: &str, min_length: usize, max_length: usize) -> String {
-    let mut rng = rand::rng();
+pub(crate) fn gen_random_sentence<R: Rng + ?Sized>(
+    rng: &mut R,
+    locale: &str,
+    min_length: usize,
+    max_length: usize,
+) -> String {
     let mut length = rng.random_range(min_length..=max_length);
     let mut sentence = String::with_capacity(min_length);
     match locale {
         #[cfg(feature = "locale-en")]
         "en" | "en-US" => {
             while length > 0 {
-                let word = random_word::gen(Lang::En);
+                let word = gen_word(rng, Lang::En);
                 let word_length = word.len();
                 if let Some(remainder_length) = length.checked_sub(word_length) {
                     sentence.push_str(word);
@@ -32,7 +38,7 @@ pub(crate) fn gen_random_sentence(locale: &str, min_length: usize, max_length: u
         #[cfg(feature = "locale-es")]
         "es" | "es-ES" => {
             while length > 0 {
-                let word = random_word::gen(Lang::Es);
+                let word = gen_word(rng, Lang::Es);
                 let word_length = word.len();
                 if let Some(remainder_length) = length.checked_sub(word_length) {
                     sentence.push_str(word);
@@ -49,7 +55,7 @@ pub(crate) fn gen_random_sentence(locale: &str, min_length: usize, max_length: u
         #[cfg(feature = "locale-de")]
         "de" | "de-DE" => {
             while length > 0 {
-                let word = random_word::gen(Lang::De);
+                let word = gen_word(rng, Lang::De);
                 let word_length = word.len();
                 if let Some(remainder_length) = length.checked_sub(word_length) {
                     sentence.push_str(word);
@@ -66,7 +72,7 @@ pub(crate) fn gen_random_sentence(locale: &str, min_length: usize, max_length: u
         #[cfg(feature = "locale-fr")]
         "fr" | "fr-FR" => {
             while length > 0 {
-                let word = random_word::gen(Lang::Fr);
+                let word = gen_word(rng, Lang::Fr);
                 let word_length = word.len();
                 if let Some(remainder_length) = length.checked_sub(word_length) {
                     sentence.push_str(word);
@@ -83,7 +89,7 @@ pub(crate) fn gen_random_sentence(locale: &str, min_length: usize, max_length: u
         #[cfg(feature = "locale-zh")]
         "zh" | "zh-CN" | "zh-CHS" => {
             while length > 0 {
-                let mut word = random_word::gen(Lang::Zh).trim();
+                let mut word = gen_word(rng, Lang::Zh).trim();
                 if let Some((_, hans)) = word.split_once(' ') {
                     word = hans;
                 }
@@ -103,7 +109,7 @@ pub(crate) fn gen_random_sentence(locale: &str, min_length: usize, max_length: u
         #[cfg(feature = "locale-zh")]
         "zh-HK" | "zh-TW" | "zh-CHT" => {
             while length > 0 {
-                let mut word = random_word::gen(Lang::Zh).trim();
+                let mut word = gen_word(rng, Lang::Zh).trim();
                 if let Some((hant, _)) = word.split_once(' ') {
                     word = hant;
                 }
@@ -123,7 +129,7 @@ pub(crate) fn gen_random_sentence(locale: &str, min_length: usize, max_length: u
         _ => {
             while length > 0 {
                 let num_chars = rng.random_range(1..=16);
-                let word = Alphanumeric.sample_string(&mut rng, num_chars);
+                let word = Alphanumeric.sample_string(rng, num_chars);
                 let word_length = word.len();
                 if let Some(remainder_length) = length.checked_sub(word_length) {
                     sentence.push_str(&word);
@@ -143,3 +149,12 @@ pub(crate) fn gen_random_sentence(locale: &str, min_length: usize, max_length: u
     }
     sentence
 }
+
+/// Generates a random word for the language.
+#[cfg(feature = "locale")]
+fn gen_word<R: Rng + ?Sized>(rng: &mut R, lang: Lang) -> &'static str {
+    random_word::all(lang)
+        .choose(rng)
+        .copied()
+        .unwrap_or_default()
+}
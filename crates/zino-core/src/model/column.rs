@@ -1,17 +1,18 @@
-use super::Reference;
+use super::{DefaultValue, Reference};
 use crate::{
-    datetime::{Date, DateTime, Time},
+    datetime::DateTime,
     extension::{JsonObjectExt, JsonValueExt},
-    mock, Decimal, JsonValue, Map, Uuid,
+    helper, mock, Decimal, JsonValue, Map, Ulid, Uuid,
 };
 use apache_avro::schema::{
-    ArraySchema, MapSchema, Name, RecordField, RecordFieldOrder, Schema, UnionSchema,
+    ArraySchema, DecimalSchema, MapSchema, Name, RecordField, RecordFieldOrder, Schema, UnionSchema,
 };
 use rand::{
     distr::{Alphanumeric, Distribution, SampleString, StandardUniform},
     random,
+    rngs::StdRng,
     seq::IndexedRandom,
-    Rng,
+    Rng, SeedableRng,
 };
 use serde::Serialize;
 use std::collections::BTreeMap;
@@ -148,6 +149,29 @@ impl<'a> Column<'a> {
         self.comment
     }
 
+    /// Returns the human-readable label.
+    #[inline]
+    pub fn label(&self) -> Option<&str> {
+        self.extra.get_str("label")
+    }
+
+    /// Returns the help text.
+    #[inline]
+    pub fn help(&self) -> Option<&str> {
+        self.extra.get_str("help")
+    }
+
+    /// Returns the example value.
+    pub fn example(&self) -> Option<JsonValue> {
+        self.extra.get("example").map(|value| {
+            if self.is_array_type() {
+                value.parse_str_array().into()
+            } else {
+                value.clone()
+            }
+        })
+    }
+
     /// Returns a reference to the extra attributes.
     #[inline]
     pub fn extra(&self) -> &Map {
@@ -186,16 +210,83 @@ impl<'a> Column<'a> {
         self.has_attribute("primary_key")
     }
 
+    /// Returns `true` if the column is required for the insertion.
+    #[inline]
+    pub fn is_required(&self) -> bool {
+        self.is_not_null() && !self.is_primary_key() || self.has_attribute("nonempty")
+    }
+
     /// Returns `true` if the column is read-only.
     #[inline]
     pub fn is_read_only(&self) -> bool {
         self.has_attribute("read_only")
     }
 
+    /// Returns `true` if the column stores the expiration time of the row.
+    #[inline]
+    pub fn is_expiry(&self) -> bool {
+        self.has_attribute("expires_at")
+    }
+
+    /// Returns `true` if the column is included in the full-text search.
+    #[inline]
+    pub fn is_fulltext(&self) -> bool {
+        self.has_attribute("fulltext")
+    }
+
+    /// Returns the language of the full-text search if it has been specified.
+    #[inline]
+    pub fn fulltext_language(&self) -> Option<&str> {
+        self.extra.get_str("fulltext")
+    }
+
+    /// Returns the route parameter name if the column references a parent resource.
+    #[inline]
+    pub fn parent_param(&self) -> Option<&str> {
+        self.extra.get_str("parent")
+    }
+
+    /// Returns the pattern of the sequential numbers if the column is filled by a sequence.
+    #[inline]
+    pub fn sequence_pattern(&self) -> Option<&str> {
+        self.extra.get_str("sequence")
+    }
+
     /// Returns `true` if the column is write-only.
+    /// A `private` column is also write-only.
     #[inline]
     pub fn is_write_only(&self) -> bool {
-        self.has_attribute("write_only")
+        self.has_attribute("write_only") || self.has_attribute("private")
+    }
+
+    /// Returns `true` if the column is sensitive.
+    /// A `private` or `encrypted` column is sensitive and its values should not be logged.
+    #[inline]
+    pub fn is_sensitive(&self) -> bool {
+        self.has_attribute("private") || self.has_attribute("encrypted")
+    }
+
+    /// Returns the masking format if the column has a `masked` attribute.
+    #[inline]
+    pub fn masking_format(&self) -> Option<&str> {
+        self.extra.get("masked").and_then(|value| match value {
+            JsonValue::String(format) => Some(format.as_str()),
+            JsonValue::Bool(true) => Some("all"),
+            _ => None,
+        })
+    }
+
+    /// Masks the value according to the masking format of the column.
+    /// Returns `None` if the column does not have a `masked` attribute.
+    pub fn mask_value(&self, value: &JsonValue) -> Option<JsonValue> {
+        let format = self.masking_format()?;
+        let masked_value = match value {
+            JsonValue::Null => JsonValue::Null,
+            JsonValue::String(s) => helper::mask_text_with_format(s, format).into(),
+            JsonValue::Number(n) => helper::mask_text_with_format(&n.to_string(), format).into(),
+            _ => helper::mask_text_with_format("", "all").into(),
+        };
+        Some(masked_value)
     }
 
     /// Returns `true` if the column is an option type.
@@ -207,6 +298,7 @@ impl<'a> Column<'a> {
         matches!(
             self.type_name(),
             "Option<Uuid>"
+                | "Option<Ulid>"
                 | "Option<String>"
                 | "Option<i64>"
                 | "Option<u64>"
@@ -258,7 +350,15 @@ impl<'a> Column<'a> {
             "i64" | "u64" | "isize" | "usize" => Schema::Long,
             "f32" => Schema::Float,
             "f64" => Schema::Double,
-            "String" => Schema::String,
+            "String" | "Ulid" => Schema::String,
+            "Decimal" => {
+                let extra = self.extra();
+                Schema::Decimal(DecimalSchema {
+                    precision: extra.get_usize("precision").unwrap_or(38),
+                    scale: extra.get_usize("scale").unwrap_or_default(),
+                    inner: Box::new(Schema::Bytes),
+                })
+            }
             "Date" => Schema::Date,
             "DateTime" => Schema::TimestampMicros,
             "Uuid" => Schema::Uuid,
@@ -279,7 +379,7 @@ impl<'a> Column<'a> {
                 items: Box::new(Schema::Int),
                 attributes: BTreeMap::new(),
             }),
-            "Option<String>" => {
+            "Option<String>" | "Option<Ulid>" => {
                 if let Ok(union_schema) = UnionSchema::new(vec![Schema::Null, Schema::String]) {
                     Schema::Union(union_schema)
                 } else {
@@ -328,14 +428,21 @@ impl<'a> Column<'a> {
     /// Returns a field for the record Avro schema.
     pub fn record_field(&self) -> RecordField {
         let schema = self.schema();
-        let default_value = self.default_value().and_then(|s| match schema {
-            Schema::Boolean => s.parse::<bool>().ok().map(|b| b.into()),
-            Schema::Int => s.parse::<i32>().ok().map(|i| i.into()),
-            Schema::Long => s.parse::<i64>().ok().map(|i| i.into()),
-            Schema::Float => s.parse::<f32>().ok().map(|f| f.into()),
-            Schema::Double => s.parse::<f64>().ok().map(|f| f.into()),
-            _ => Some(s.into()),
-        });
+        let default_value = self
+            .default_value()
+            .filter(|s| !DefaultValue::is_dynamic(s))
+            .and_then(|s| match schema {
+                Schema::Boolean => s.parse::<bool>().ok().map(|b| b.into()),
+                Schema::Int => s.parse::<i32>().ok().map(|i| i.into()),
+                Schema::Long => s.parse::<i64>().ok().map(|i| i.into()),
+                Schema::Float => s.parse::<f32>().ok().map(|f| f.into()),
+                Schema::Double => s.parse::<f64>().ok().map(|f| f.into()),
+                _ => Some(s.into()),
+            });
+        let mut custom_attributes = BTreeMap::new();
+        if self.is_write_only() {
+            custom_attributes.insert("writeOnly".to_owned(), true.into());
+        }
         RecordField {
             name: self.name().to_owned(),
             doc: self.comment().map(|s| s.to_owned()),
@@ -344,7 +451,7 @@ impl<'a> Column<'a> {
             schema,
             order: RecordFieldOrder::Ascending,
             position: 0,
-            custom_attributes: BTreeMap::new(),
+            custom_attributes,
         }
     }
 
@@ -427,6 +534,11 @@ impl<'a> Column<'a> {
                 definition.upsert("type", "string");
                 definition.upsert("format", "uuid");
             }
+            "Ulid" | "Option<Ulid>" => {
+                definition.upsert("type", "string");
+                definition.upsert("format", "ulid");
+                definition.upsert("pattern", "^[0-7][0-9A-HJKMNP-TV-Za-hjkmnp-tv-z]{25}$");
+            }
             "Vec<u8>" => {
                 definition.upsert("type", "string");
                 definition.upsert("format", "binary");
@@ -516,13 +628,14 @@ impl<'a> Column<'a> {
         if let Some(value) = extra.get("default") {
             definition.upsert("default", value.clone());
         }
-        if let Some(value) = extra.get("example") {
-            if self.is_array_type() {
-                let values = value.parse_str_array();
-                definition.upsert("example", values);
-            } else {
-                definition.upsert("example", value.clone());
-            }
+        if let Some(value) = self.label() {
+            definition.upsert("label", value);
+        }
+        if let Some(value) = self.help() {
+            definition.upsert("help", value);
+        }
+        if let Some(value) = self.example() {
+            definition.upsert("example", value);
         }
         if let Some(values) = extra.parse_enum_values("examples") {
             definition.upsert("examples", values);
@@ -573,6 +686,29 @@ impl<'a> Column<'a> {
         definition
     }
 
+    /// Returns the entry of the spreadsheet template for importing the model data.
+    pub fn template(&self) -> Map {
+        let definition = self.definition();
+        let label = self.label().or(self.comment()).unwrap_or(self.name());
+        let mut template = Map::from_entry("name", self.name());
+        template.upsert("label", label);
+        template.upsert("type", definition.get_str("type"));
+        if let Some(format) = definition.get_str("format") {
+            template.upsert("format", format);
+        }
+        template.upsert("required", self.is_required());
+        if let Some(values) = self.extra().parse_enum_values("enum_values") {
+            template.upsert("enum", values);
+        }
+        if let Some(value) = self.example() {
+            template.upsert("example", value);
+        }
+        if let Some(value) = self.help() {
+            template.upsert("help", value);
+        }
+        template
+    }
+
     /// Generates a random size of the items.
     pub fn random_size(&self) -> usize {
         if self.is_array_type() {
@@ -593,117 +729,135 @@ impl<'a> Column<'a> {
     }
 
     /// Generates a mocked Json value for the column.
+    #[inline]
     pub fn mock_value(&self) -> JsonValue {
+        self.mock_value_with(&mut rand::rng(), DateTime::now())
+    }
+
+    /// Generates a mocked Json value for the column with a seed.
+    /// The same seed always yields the same value, which is suitable for the documentation.
+    /// The date and time values are relative to `2025-01-01T00:00:00Z`.
+    pub fn mock_value_with_seed(&self, seed: u64) -> JsonValue {
+        let mut rng = StdRng::seed_from_u64(seed);
+        self.mock_value_with(&mut rng, DateTime::from_timestamp(1_735_689_600))
+    }
+
+    /// Generates a mocked Json value for the column with the RNG and the current date time.
+    fn mock_value_with<R: Rng + ?Sized>(&self, rng: &mut R, now: DateTime) -> JsonValue {
         if self.reference().is_some() {
             return JsonValue::Null;
         }
         match self.type_name() {
-            "bool" => random::<bool>().into(),
-            "i8" => self.mock_integer::<i8>(),
-            "i16" => self.mock_integer::<i16>(),
-            "i32" => self.mock_integer::<i32>(),
-            "i64" | "isize" => self.mock_integer::<i64>(),
-            "u8" => self.mock_integer::<u8>(),
-            "u16" => self.mock_integer::<u16>(),
-            "u32" => self.mock_integer::<u32>(),
-            "u64" | "usize" => self.mock_integer::<u64>(),
-            "f32" => random::<f32>().into(),
-            "f64" => random::<f64>().into(),
-            "String" => self.mock_string(),
-            "Date" => Date::today().into(),
-            "Time" => Time::now().into(),
-            "DateTime" => DateTime::now().into(),
-            "Uuid" => Uuid::now_v7().to_string().into(),
+            "bool" => rng.random::<bool>().into(),
+            "i8" => self.mock_integer::<i8, _>(rng),
+            "i16" => self.mock_integer::<i16, _>(rng),
+            "i32" => self.mock_integer::<i32, _>(rng),
+            "i64" | "isize" => self.mock_integer::<i64, _>(rng),
+            "u8" => self.mock_integer::<u8, _>(rng),
+            "u16" => self.mock_integer::<u16, _>(rng),
+            "u32" => self.mock_integer::<u32, _>(rng),
+            "u64" | "usize" => self.mock_integer::<u64, _>(rng),
+            "f32" => rng.random::<f32>().into(),
+            "f64" => rng.random::<f64>().into(),
+            "String" => self.mock_string(rng),
+            "Date" => now.date().into(),
+            "Time" => now.time().into(),
+            "DateTime" => now.into(),
+            "Uuid" => mock_uuid(rng, now).to_string().into(),
+            "Ulid" => mock_ulid(rng, now).into(),
             "Option<i32>" => {
-                if random::<bool>() {
-                    self.mock_integer::<i32>()
+                if rng.random::<bool>() {
+                    self.mock_integer::<i32, _>(rng)
                 } else {
                     JsonValue::Null
                 }
             }
             "Option<i64>" => {
-                if random::<bool>() {
-                    self.mock_integer::<i64>()
+                if rng.random::<bool>() {
+                    self.mock_integer::<i64, _>(rng)
                 } else {
                     JsonValue::Null
                 }
             }
             "Option<u32>" => {
-                if random::<bool>() {
-                    self.mock_integer::<u32>()
+                if rng.random::<bool>() {
+                    self.mock_integer::<u32, _>(rng)
                 } else {
                     JsonValue::Null
                 }
             }
             "Option<u64>" => {
-                if random::<bool>() {
-                    self.mock_integer::<u64>()
+                if rng.random::<bool>() {
+                    self.mock_integer::<u64, _>(rng)
                 } else {
                     JsonValue::Null
                 }
             }
             "Option<String>" => {
-                if random::<bool>() {
-                    self.mock_string()
+                if rng.random::<bool>() {
+                    self.mock_string(rng)
                 } else {
                     JsonValue::Null
                 }
             }
-            "Option<Uuid>" => random::<bool>().then(|| Uuid::now_v7().to_string()).into(),
-            "Vec<i32>" => self.mock_integer_array::<i32>().into(),
-            "Vec<i64>" => self.mock_integer_array::<i64>().into(),
-            "Vec<u32>" => self.mock_integer_array::<u32>().into(),
-            "Vec<u64>" => self.mock_integer_array::<u64>().into(),
-            "Vec<String>" => self.mock_string_array().into(),
+            "Option<Uuid>" => rng
+                .random::<bool>()
+                .then(|| mock_uuid(rng, now).to_string())
+                .into(),
+            "Option<Ulid>" => rng.random::<bool>().then(|| mock_ulid(rng, now)).into(),
+            "Vec<i32>" => self.mock_integer_array::<i32, _>(rng).into(),
+            "Vec<i64>" => self.mock_integer_array::<i64, _>(rng).into(),
+            "Vec<u32>" => self.mock_integer_array::<u32, _>(rng).into(),
+            "Vec<u64>" => self.mock_integer_array::<u64, _>(rng).into(),
+            "Vec<String>" => self.mock_string_array(rng).into(),
+            "Vec<Uuid>" => self.mock_uuid_array(rng, now).into(),
             _ => JsonValue::Null,
         }
     }
 
     /// Generates an integer for the column.
-    fn mock_integer<T>(&self) -> JsonValue
+    fn mock_integer<T, R>(&self, rng: &mut R) -> JsonValue
     where
         StandardUniform: Distribution<T>,
         T: Into<JsonValue>,
+        R: Rng + ?Sized,
     {
         let extra = self.extra();
         if let Some(values) = extra.parse_enum_values("enum_values") {
-            let mut rng = rand::rng();
-            values.choose(&mut rng).cloned().into()
+            values.choose(rng).cloned().into()
         } else {
-            random::<T>().into()
+            rng.random::<T>().into()
         }
     }
 
     /// Generates a string for the column.
-    fn mock_string(&self) -> JsonValue {
+    fn mock_string<R: Rng + ?Sized>(&self, rng: &mut R) -> JsonValue {
         let extra = self.extra();
         if let Some(values) = extra.parse_enum_values("enum_values") {
-            let mut rng = rand::rng();
-            values.choose(&mut rng).cloned().into()
+            values.choose(rng).cloned().into()
         } else if let Some(format) = extra.get_str("format") {
-            mock::gen_format(format, extra.get_usize("length")).into()
+            mock::gen_format(rng, format, extra.get_usize("length")).into()
         } else if self.index_type() == Some("hash") {
-            let mut rng = rand::rng();
             let min_length = extra.get_usize("min_length").unwrap_or(1);
             let max_length = extra.get_usize("max_length").unwrap_or(16);
             let num_chars = rng.random_range(min_length..=max_length);
-            Alphanumeric.sample_string(&mut rng, num_chars).into()
+            Alphanumeric.sample_string(rng, num_chars).into()
         } else {
             let locale = extra.get_str("locale").unwrap_or_default();
             let min_length = extra.get_usize("min_length").unwrap_or(1);
             let max_length = extra.get_usize("max_length").unwrap_or(32);
-            mock::gen_random_sentence(locale, min_length, max_length).into()
+            mock::gen_random_sentence(rng, locale, min_length, max_length).into()
         }
     }
 
     /// Generates an integer array for the column.
-    fn mock_integer_array<T>(&self) -> Vec<JsonValue>
+    fn mock_integer_array<T, R>(&self, rng: &mut R) -> Vec<JsonValue>
     where
         StandardUniform: Distribution<T>,
         T: Into<JsonValue>,
+        R: Rng + ?Sized,
     {
         let extra = self.extra();
-        let mut rng = rand::rng();
         let mut min_items = extra.get_usize("min_items").unwrap_or(0);
         if self.has_attribute("nonempty") {
             min_items = min_items.max(1);
@@ -711,21 +865,19 @@ impl<'a> Column<'a> {
         if let Some(values) = extra.parse_enum_values("enum_values") {
             let max_items = extra.get_usize("max_items").unwrap_or(values.len());
             let num_items = rng.random_range(min_items..=max_items);
-            values
-                .choose_multiple(&mut rng, num_items)
-                .cloned()
-                .collect()
+            values.choose_multiple(rng, num_items).cloned().collect()
         } else {
             let max_items = extra.get_usize("max_items").unwrap_or(8);
             let num_items = rng.random_range(min_items..=max_items);
-            (0..num_items).map(|_| self.mock_integer::<T>()).collect()
+            (0..num_items)
+                .map(|_| self.mock_integer::<T, R>(rng))
+                .collect()
         }
     }
 
     /// Generates a string array for the column.
-    fn mock_string_array(&self) -> Vec<JsonValue> {
+    fn mock_string_array<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<JsonValue> {
         let extra = self.extra();
-        let mut rng = rand::rng();
         let mut min_items = extra.get_usize("min_items").unwrap_or(0);
         if self.has_attribute("nonempty") {
             min_items = min_items.max(1);
@@ -733,14 +885,67 @@ impl<'a> Column<'a> {
         if let Some(values) = extra.parse_enum_values("enum_values") {
             let max_items = extra.get_usize("max_items").unwrap_or(values.len());
             let num_items = rng.random_range(min_items..=max_items);
-            values
-                .choose_multiple(&mut rng, num_items)
-                .cloned()
-                .collect()
+            values.choose_multiple(rng, num_items).cloned().collect()
         } else {
             let max_items = extra.get_usize("max_items").unwrap_or(8);
             let num_items = rng.random_range(min_items..=max_items);
-            (0..num_items).map(|_| self.mock_string()).collect()
+            (0..num_items).map(|_| self.mock_string(rng)).collect()
         }
     }
+
+    /// Generates a UUID array for the column.
+    fn mock_uuid_array<R: Rng + ?Sized>(&self, rng: &mut R, now: DateTime) -> Vec<JsonValue> {
+        let extra = self.extra();
+        let mut min_items = extra.get_usize("min_items").unwrap_or(0);
+        if self.has_attribute("nonempty") {
+            min_items = min_items.max(1);
+        }
+        let max_items = extra.get_usize("max_items").unwrap_or(8);
+        let num_items = rng.random_range(min_items..=max_items);
+        (0..num_items)
+            .map(|_| mock_uuid(rng, now).to_string().into())
+            .collect()
+    }
+}
+
+/// Generates a UUID v7 with the timestamp and the random bits.
+fn mock_uuid<R: Rng + ?Sized>(rng: &mut R, now: DateTime) -> Uuid {
+    let millis = now.timestamp_millis().try_into().unwrap_or_default();
+    uuid::Builder::from_unix_timestamp_millis(millis, &rng.random()).into_uuid()
+}
+
+/// Generates a ULID with the timestamp and the random bits.
+fn mock_ulid<R: Rng + ?Sized>(rng: &mut R, now: DateTime) -> Ulid {
+    let millis = now.timestamp_millis().try_into().unwrap_or_default();
+    Ulid::from_parts(millis, rng.random())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Column;
+
+    #[test]
+    fn it_mocks_values_with_seeds() {
+        let mut column = Column::new("email", "String", true);
+        column.set_extra_attribute("format", "email");
+        let value = column.mock_value_with_seed(42);
+        assert!(value.as_str().is_some_and(|s| s.contains('@')));
+        assert_eq!(column.mock_value_with_seed(42), value);
+
+        let column = Column::new("created_at", "DateTime", true);
+        assert_eq!(
+            column.mock_value_with_seed(1),
+            column.mock_value_with_seed(2)
+        );
+
+        let column = Column::new("id", "Uuid", true);
+        assert_eq!(
+            column.mock_value_with_seed(7),
+            column.mock_value_with_seed(7)
+        );
+        assert_ne!(
+            column.mock_value_with_seed(7),
+            column.mock_value_with_seed(8)
+        );
+    }
 }
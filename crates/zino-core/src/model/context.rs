@@ -1,5 +1,5 @@
-use crate::Uuid;
-use std::time::Instant;
+use crate::{extension::JsonValueExt, JsonValue, LazyLock, Uuid};
+use std::{sync::OnceLock, time::Instant};
 
 /// Data associated with a query.
 #[derive(Debug, Clone)]
@@ -13,7 +13,14 @@ pub struct QueryContext {
     /// A query.
     query: String,
     /// Arguments.
-    arguments: Vec<String>,
+    arguments: Vec<JsonValue>,
+    /// Arguments formatted as strings, which are initialized lazily.
+    formatted_arguments: OnceLock<Vec<String>>,
+    /// A statement with placeholders for logging.
+    statement: Option<String>,
+    /// Values inlined in the query, which are logged as the bind values of the statement.
+    /// The flag indicates whether the value is sensitive.
+    bind_values: Vec<(String, bool)>,
     /// Last insert ID.
     last_insert_id: Option<i64>,
     /// Number of rows affected.
@@ -22,6 +29,8 @@ pub struct QueryContext {
     success: bool,
     /// Indicates the query execution is cancelled or not.
     cancelled: bool,
+    /// Indicates the result set is truncated by the max rows or not.
+    truncated: bool,
 }
 
 impl QueryContext {
@@ -34,10 +43,14 @@ impl QueryContext {
             query_id: Uuid::now_v7(),
             query: String::new(),
             arguments: Vec::new(),
+            formatted_arguments: OnceLock::new(),
+            statement: None,
+            bind_values: Vec::new(),
             last_insert_id: None,
             rows_affected: None,
             success: false,
             cancelled: false,
+            truncated: false,
         }
     }
 
@@ -47,16 +60,46 @@ impl QueryContext {
         self.query = query.into();
     }
 
-    /// Adds an argument to the list of query arguments.
+    /// Adds an argument to the list of query arguments, which is bound as a string.
     #[inline]
     pub fn add_argument(&mut self, value: impl ToString) {
-        self.arguments.push(value.to_string());
+        self.add_argument_value(value.to_string());
     }
 
-    /// Appends the query arguments.
+    /// Adds an argument to the list of query arguments,
+    /// which is bound by the type of the JSON value.
+    #[inline]
+    pub fn add_argument_value(&mut self, value: impl Into<JsonValue>) {
+        self.arguments.push(value.into());
+        self.formatted_arguments.take();
+    }
+
+    /// Appends the query arguments, which are bound as strings.
     #[inline]
     pub fn append_arguments(&mut self, arguments: &mut Vec<String>) {
+        let mut values = arguments.drain(..).map(JsonValue::String).collect();
+        self.append_argument_values(&mut values);
+    }
+
+    /// Appends the query arguments, which are bound by the types of the JSON values.
+    #[inline]
+    pub fn append_argument_values(&mut self, arguments: &mut Vec<JsonValue>) {
         self.arguments.append(arguments);
+        self.formatted_arguments.take();
+    }
+
+    /// Sets the statement with placeholders for logging.
+    /// The query arguments are followed by the bind values in the placeholders.
+    #[inline]
+    pub fn set_statement(&mut self, statement: impl Into<String>) {
+        self.statement = Some(statement.into());
+    }
+
+    /// Adds a bind value of the statement for logging.
+    /// A sensitive value is always logged as `[redacted]`.
+    #[inline]
+    pub fn add_bind_value(&mut self, value: impl ToString, sensitive: bool) {
+        self.bind_values.push((value.to_string(), sensitive));
     }
 
     /// Sets the last insert ID.
@@ -79,6 +122,12 @@ impl QueryContext {
         self.cancelled = true;
     }
 
+    /// Sets the flag to indicate whether the result set is truncated by the max rows.
+    #[inline]
+    pub fn set_truncated(&mut self, truncated: bool) {
+        self.truncated = truncated;
+    }
+
     /// Returns the model name.
     #[inline]
     pub fn model_name(&self) -> &'static str {
@@ -103,9 +152,26 @@ impl QueryContext {
         &self.query
     }
 
-    /// Returns the query arguments.
+    /// Returns the statement for logging, which defaults to the query.
+    #[inline]
+    pub fn statement(&self) -> &str {
+        self.statement.as_deref().unwrap_or(&self.query)
+    }
+
+    /// Returns the query arguments formatted as strings.
     #[inline]
     pub fn arguments(&self) -> &[String] {
+        self.formatted_arguments.get_or_init(|| {
+            self.arguments
+                .iter()
+                .map(|value| value.to_string_unquoted())
+                .collect()
+        })
+    }
+
+    /// Returns the query arguments as JSON values.
+    #[inline]
+    pub fn argument_values(&self) -> &[JsonValue] {
         &self.arguments
     }
 
@@ -127,17 +193,54 @@ impl QueryContext {
         self.cancelled
     }
 
+    /// Returns `true` if the result set is truncated by the max rows.
+    #[inline]
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
     /// Returns `true` if the query execution is successful.
     #[inline]
     pub fn is_success(&self) -> bool {
         self.success
     }
 
-    /// Formats the query arguments as a `String` if they exist.
-    #[inline]
+    /// Formats the query arguments and bind values as a `String` for logging if they exist.
+    ///
+    /// The values are only logged when `log-bind-values` is enabled in the `[database]` table.
+    /// Sensitive values are redacted, and a value longer than `log-value-length`
+    /// (256 characters by default) is truncated with a suffix noting the original size.
     pub fn format_arguments(&self) -> Option<String> {
-        let arguments = self.arguments();
-        (!arguments.is_empty()).then(|| arguments.join(", "))
+        let (enabled, max_length) = *SHARED_LOG_POLICY;
+        enabled.then(|| self.format_values(max_length)).flatten()
+    }
+
+    /// Formats the query arguments and bind values with the max length of a value.
+    fn format_values(&self, max_length: usize) -> Option<String> {
+        let arguments = self
+            .arguments
+            .iter()
+            .map(|value| (value.to_string_unquoted(), false));
+        let bind_values = self
+            .bind_values
+            .iter()
+            .map(|(value, sensitive)| (value.to_owned(), *sensitive));
+        let values = arguments
+            .chain(bind_values)
+            .map(|(value, sensitive)| {
+                if sensitive {
+                    return "[redacted]".to_owned();
+                }
+                match value.char_indices().nth(max_length) {
+                    Some((index, _)) => {
+                        let num_chars = value.chars().count();
+                        format!("{}...({num_chars} chars)", &value[..index])
+                    }
+                    None => value,
+                }
+            })
+            .collect::<Vec<_>>();
+        (!values.is_empty()).then(|| values.join(", "))
     }
 
     /// Records an error message for the query.
@@ -145,7 +248,7 @@ impl QueryContext {
         fn inner(ctx: &QueryContext, message: &str) {
             let model_name = ctx.model_name();
             let query_id = ctx.query_id().to_string();
-            let query = ctx.query();
+            let query = ctx.statement();
             let arguments = ctx.format_arguments();
             if ctx.is_cancelled() {
                 tracing::warn!(
@@ -178,3 +281,62 @@ impl QueryContext {
         inner(self, action.into())
     }
 }
+
+/// Shared policy for logging the bind values.
+static SHARED_LOG_POLICY: LazyLock<(bool, usize)> = LazyLock::new(|| {
+    #[cfg(feature = "runtime")]
+    let (enabled, max_length) = {
+        use crate::{extension::TomlTableExt, state::State};
+
+        let config = State::shared().get_config("database");
+        (
+            config.and_then(|config| config.get_bool("log-bind-values")),
+            config.and_then(|config| config.get_usize("log-value-length")),
+        )
+    };
+    #[cfg(not(feature = "runtime"))]
+    let (enabled, max_length) = (None, None);
+    (enabled.unwrap_or_default(), max_length.unwrap_or(256))
+});
+
+#[cfg(test)]
+mod tests {
+    use super::QueryContext;
+    use serde_json::json;
+
+    #[test]
+    fn it_formats_bind_values() {
+        let mut ctx = QueryContext::new("user");
+        ctx.set_query("INSERT INTO user (id, name, password) VALUES ('1', 'alice', 'hash');");
+        ctx.set_statement("INSERT INTO user (id, name, password) VALUES ($1, $2, $3);");
+        ctx.add_argument("alice");
+        ctx.add_argument_value(42);
+        ctx.add_bind_value("1", false);
+        ctx.add_bind_value("x".repeat(300), false);
+        ctx.add_bind_value("hash", true);
+        assert!(ctx.statement().contains("$3"));
+
+        let values = ctx.format_values(256).unwrap_or_default();
+        assert!(values.starts_with("alice, 42, 1, xxx"));
+        assert!(values.contains("...(300 chars)"));
+        assert!(values.ends_with("[redacted]"));
+        assert!(!values.contains("hash"));
+        assert_eq!(QueryContext::new("user").format_values(256), None);
+    }
+
+    #[test]
+    fn it_keeps_string_arguments() {
+        let mut ctx = QueryContext::new("user");
+        ctx.add_argument(42);
+        ctx.add_argument_value(42);
+        assert_eq!(ctx.arguments(), ["42", "42"]);
+
+        ctx.append_arguments(&mut vec!["true".to_owned()]);
+        ctx.append_argument_values(&mut vec![true.into()]);
+        assert_eq!(ctx.arguments(), ["42", "42", "true", "true"]);
+        assert_eq!(
+            ctx.argument_values(),
+            [json!("42"), json!(42), json!("true"), json!(true)]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{
+    datetime::{self, DateTime},
+    JsonValue, Ulid, Uuid,
+};
+
+/// Default value expressions for the model fields.
+///
+/// Besides the constant values, `#[schema(default_value = "...")]` supports the following
+/// expressions which are evaluated when a model is created:
+///
+/// - `now`: the current date time.
+/// - `now+<duration>` or `now-<duration>`: an offset of the current date time,
+///   where the duration is parsed by [`parse_duration`](crate::datetime::parse_duration),
+///   such as `now+30d` or `now-1h`.
+/// - `uuid`: a new UUID v7.
+/// - `ulid`: a new [`Ulid`](crate::Ulid).
+/// - `snowflake`: a new ID generated by [`SequenceId`](super::SequenceId).
+/// - `fn:<name>`: the value returned by [`Model::default_for()`](super::Model::default_for).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultValue;
+
+impl DefaultValue {
+    /// Returns `true` if the default value is an expression
+    /// which should be evaluated when a model is created.
+    pub fn is_expression(expr: &str) -> bool {
+        matches!(expr, "now" | "uuid" | "ulid" | "snowflake")
+            || expr.starts_with("fn:")
+            || Self::parse_now_offset(expr).is_some()
+    }
+
+    /// Returns `true` if the default value is an expression which can not be
+    /// represented as a `DEFAULT` clause in the column definition.
+    #[inline]
+    pub fn is_dynamic(expr: &str) -> bool {
+        expr != "now" && Self::is_expression(expr)
+    }
+
+    /// Evaluates the built-in default value expression.
+    /// It returns `None` if the expression is a constant or a `fn:` hook.
+    pub fn evaluate(expr: &str) -> Option<JsonValue> {
+        match expr {
+            "now" => Some(DateTime::now().to_utc_timestamp().into()),
+            "uuid" => Some(Uuid::now_v7().to_string().into()),
+            "ulid" => Some(Ulid::new().into()),
+            #[cfg(feature = "runtime")]
+            "snowflake" => Some(super::SequenceId::next().into()),
+            _ => Self::parse_now_offset(expr).map(|dt| dt.to_utc_timestamp().into()),
+        }
+    }
+
+    /// Parses the expressions of the form `now+<duration>` or `now-<duration>`.
+    fn parse_now_offset(expr: &str) -> Option<DateTime> {
+        let offset = expr.strip_prefix("now")?.trim_start();
+        if let Some(duration) = offset.strip_prefix('+') {
+            let duration = datetime::parse_duration(duration.trim()).ok()?;
+            DateTime::now().checked_add(duration)
+        } else if let Some(duration) = offset.strip_prefix('-') {
+            let duration = datetime::parse_duration(duration.trim()).ok()?;
+            DateTime::now().checked_sub(duration)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DefaultValue;
+    use crate::datetime::DateTime;
+
+    #[test]
+    fn it_evaluates_default_values() {
+        assert!(DefaultValue::is_expression("now+30d"));
+        assert!(DefaultValue::is_expression("fn:slug"));
+        assert!(!DefaultValue::is_expression("nowhere"));
+        assert!(!DefaultValue::is_dynamic("now"));
+        assert!(DefaultValue::is_dynamic("uuid"));
+
+        let value = DefaultValue::evaluate("now+30d").unwrap();
+        let datetime = value.as_str().unwrap().parse::<DateTime>().unwrap();
+        assert!(datetime > DateTime::now());
+        assert!(DefaultValue::evaluate("fn:slug").is_none());
+        assert!(DefaultValue::evaluate("active").is_none());
+    }
+}
@@ -0,0 +1,155 @@
+use crate::JsonValue;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering::Relaxed},
+        Arc, PoisonError, RwLock,
+    },
+};
+
+/// Generation of all the definition caches, which is bumped to invalidate them.
+static CACHE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// A cache of the JSON definitions built from the column metadata of a model,
+/// such as the definitions for different actions, API versions and locales.
+///
+/// Since the column metadata is constant, each definition is built only once.
+/// The rare dynamic bits like the translated labels can be refreshed by
+/// [`invalidate()`](Self::invalidate) for a model, or [`invalidate_all()`](Self::invalidate_all)
+/// for all the models.
+///
+/// # Examples
+///
+/// ```rust
+/// use zino_core::{json, model::DefinitionCache};
+///
+/// let cache = DefinitionCache::new();
+/// let definition = cache.get_or_insert_with("definition:insert", || json!({ "type": "object" }));
+/// assert_eq!(definition["type"], "object");
+/// assert!(cache.get("definition:insert").is_some());
+///
+/// DefinitionCache::invalidate_all();
+/// assert!(cache.get("definition:insert").is_none());
+/// ```
+#[derive(Debug, Default)]
+pub struct DefinitionCache {
+    /// Cached definitions with the generations.
+    entries: RwLock<HashMap<String, (u64, Arc<JsonValue>)>>,
+}
+
+impl DefinitionCache {
+    /// Creates a new instance.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached definition for the key.
+    pub fn get(&self, key: &str) -> Option<Arc<JsonValue>> {
+        let generation = CACHE_GENERATION.load(Relaxed);
+        self.entries
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)
+            .filter(|(entry_generation, _)| *entry_generation == generation)
+            .map(|(_, definition)| definition.clone())
+    }
+
+    /// Returns the cached definition for the key,
+    /// or builds it with the closure if it is not cached.
+    pub fn get_or_insert_with<F>(&self, key: &str, f: F) -> Arc<JsonValue>
+    where
+        F: FnOnce() -> JsonValue,
+    {
+        if let Some(definition) = self.get(key) {
+            return definition;
+        }
+
+        // The definition is built outside the lock since it may access other caches.
+        let generation = CACHE_GENERATION.load(Relaxed);
+        let definition = Arc::new(f());
+        self.entries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key.to_owned(), (generation, definition.clone()));
+        definition
+    }
+
+    /// Removes the cached definition for the key.
+    pub fn remove(&self, key: &str) -> Option<Arc<JsonValue>> {
+        self.entries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(key)
+            .map(|(_, definition)| definition)
+    }
+
+    /// Removes all the cached definitions.
+    pub fn invalidate(&self) {
+        self.entries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    /// Invalidates the cached definitions for all the models.
+    /// The stale entries are rebuilt lazily when they are accessed.
+    #[inline]
+    pub fn invalidate_all() {
+        CACHE_GENERATION.fetch_add(1, Relaxed);
+    }
+
+    /// Returns the number of cached definitions, including the stale ones.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Returns `true` if there are no cached definitions.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DefinitionCache;
+    use crate::json;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering::Relaxed},
+        Arc,
+    };
+
+    #[test]
+    fn it_caches_definitions() {
+        let cache = DefinitionCache::new();
+        let num_builds = AtomicUsize::new(0);
+        let build = || {
+            num_builds.fetch_add(1, Relaxed);
+            json!({ "type": "object" })
+        };
+        let definition = cache.get_or_insert_with("definition:insert", build);
+        let cached_definition = cache.get_or_insert_with("definition:insert", build);
+        assert!(Arc::ptr_eq(&definition, &cached_definition));
+        assert_eq!(num_builds.load(Relaxed), 1);
+
+        cache.get_or_insert_with("definition:update", build);
+        assert_eq!(num_builds.load(Relaxed), 2);
+        assert_eq!(cache.len(), 2);
+        assert!(cache.remove("definition:update").is_some());
+        assert!(cache.get("definition:update").is_none());
+
+        DefinitionCache::invalidate_all();
+        assert!(cache.get("definition:insert").is_none());
+        assert_eq!(cache.len(), 1);
+        cache.get_or_insert_with("definition:insert", build);
+        assert_eq!(num_builds.load(Relaxed), 3);
+
+        cache.invalidate();
+        assert!(cache.is_empty());
+    }
+}
@@ -68,7 +68,7 @@ pub trait ModelHooks: Model {
     async fn after_scan(ctx: &QueryContext) -> Result<(), Error> {
         let model_name = ctx.model_name();
         let query_id = ctx.query_id().to_string();
-        let query = ctx.query();
+        let query = ctx.statement();
         let arguments = ctx.format_arguments();
         let message = match ctx.rows_affected() {
             Some(0) => Cow::Borrowed("no rows affected or fetched"),
@@ -214,7 +214,7 @@ pub trait ModelHooks: Model {
     /// A hook running after deleting a model from the table.
     #[inline]
     async fn after_delete(self, ctx: &QueryContext, _data: Self::Data) -> Result<(), Error> {
-        let query = ctx.query();
+        let query = ctx.statement();
         let query_id = ctx.query_id().to_string();
         if ctx.is_success() {
             tracing::warn!(query, query_id, "a model was deleted from the table");
@@ -4,11 +4,17 @@ use serde::{de::DeserializeOwned, Serialize};
 
 mod column;
 mod context;
+mod default_value;
+mod definition;
 mod hook;
 mod mutation;
 mod order;
+mod page;
+mod precondition;
 mod query;
 mod reference;
+#[cfg(feature = "runtime")]
+mod sequence_id;
 mod translation;
 
 #[doc(no_inline)]
@@ -16,11 +22,17 @@ pub use apache_avro::schema;
 
 pub use column::Column;
 pub use context::QueryContext;
+pub use default_value::DefaultValue;
+pub use definition::DefinitionCache;
 pub use hook::ModelHooks;
 pub use mutation::Mutation;
 pub use order::QueryOrder;
+pub use page::{Page, PaginationStyle};
+pub use precondition::Preconditions;
 pub use query::Query;
 pub use reference::Reference;
+#[cfg(feature = "runtime")]
+pub use sequence_id::SequenceId;
 pub use translation::Translation;
 
 /// General data model.
@@ -44,6 +56,14 @@ pub trait Model: Default + Serialize + DeserializeOwned {
         Self::MODEL_NAME
     }
 
+    /// Returns the default value for the field with a `fn:` default value expression.
+    /// It is called after the other fields have been read from the json object,
+    /// so the default value can be computed from the rest of the model.
+    #[inline]
+    fn default_for(&self, _field: &str) -> Option<JsonValue> {
+        None
+    }
+
     /// Updates the model using the json object and returns the validation result.
     #[must_use]
     fn read_map(&mut self, data: &Map) -> Validation {
@@ -1,4 +1,4 @@
-use crate::SharedString;
+use crate::{error::Error, warn, SharedString};
 
 /// The query order.
 #[derive(Debug, Clone)]
@@ -22,6 +22,28 @@ impl QueryOrder {
         }
     }
 
+    /// Parses a sort key in the form `field[:asc|desc][:nulls_first|nulls_last]`.
+    /// The sort order is ascending if the direction is not specified.
+    pub fn parse(sort: &str) -> Result<Self, Error> {
+        let mut parts = sort.trim().split(':');
+        let field = parts.next().unwrap_or_default().trim();
+        if field.is_empty() {
+            return Err(warn!("the sort field of `{}` should be nonempty", sort));
+        }
+
+        let mut order = Self::new(field.to_owned(), false);
+        for part in parts {
+            match part.trim().to_ascii_lowercase().as_str() {
+                "asc" => order.descending = false,
+                "desc" => order.descending = true,
+                "nulls_first" => order.set_nulls_first(),
+                "nulls_last" => order.set_nulls_last(),
+                _ => return Err(warn!("invalid sort option `{}` for `{}`", part, field)),
+            }
+        }
+        Ok(order)
+    }
+
     /// Sets the nulls first.
     #[inline]
     pub fn set_nulls_first(&mut self) {
@@ -64,3 +86,23 @@ impl QueryOrder {
         self.nulls_first.is_some_and(|b| !b)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::QueryOrder;
+
+    #[test]
+    fn it_parses_sort_keys() {
+        let order = QueryOrder::parse("users.name").unwrap();
+        assert_eq!(order.field(), "users.name");
+        assert!(order.is_ascending());
+
+        let order = QueryOrder::parse("created_at:desc:nulls_last").unwrap();
+        assert_eq!(order.field(), "created_at");
+        assert!(order.is_descending());
+        assert!(order.nulls_last());
+
+        assert!(QueryOrder::parse(":desc").is_err());
+        assert!(QueryOrder::parse("name:down").is_err());
+    }
+}
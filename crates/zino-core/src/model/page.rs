@@ -0,0 +1,360 @@
+use super::Query;
+use crate::{JsonValue, LazyLock, Map, SharedString};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::borrow::Cow;
+use url::form_urlencoded;
+
+/// A page of the entries with the pagination metadata.
+///
+/// It is serialized as a JSON object with the following fields,
+/// where `entries` is the item name which can be customized:
+///
+/// | Field          | Style  | Description                                     |
+/// |----------------|--------|-------------------------------------------------|
+/// | `entries`      | All    | Entries of the page.                            |
+/// | `num_entries`  | All    | Number of the entries in the page.              |
+/// | `total_rows`   | All    | Total number of the rows if it is known.        |
+/// | `page_size`    | All    | Page size if it is limited.                     |
+/// | `page_count`   | Offset | Number of the pages if the total is known.      |
+/// | `current_page` | Offset | Current page number starting from `1`.          |
+/// | `next_cursor`  | Cursor | Opaque cursor to fetch the next page.           |
+/// | `has_more`     | Cursor | A flag to indicate whether there are more rows. |
+/// | `aggregates`   | All    | Optional aggregate values.                      |
+/// | `truncated`    | All    | Present if the rows are truncated by the cap.   |
+///
+/// The pagination style defaults to the `pagination-style` in the `[response]` table:
+///
+/// ```toml
+/// [response]
+/// pagination-style = "offset" # or "cursor"
+/// ```
+///
+/// For the cursor style, the `cursor` query parameter is accepted by [`Query`]
+/// and the next cursor defaults to the offset of the next page.
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    /// Name of the entries.
+    item_name: SharedString,
+    /// Entries of the page.
+    entries: Vec<T>,
+    /// Total number of the rows.
+    total: Option<usize>,
+    /// Page size.
+    page_size: Option<usize>,
+    /// Current page number starting from `1`.
+    current_page: Option<usize>,
+    /// Cursor to fetch the next page.
+    next_cursor: Option<String>,
+    /// Aggregate values.
+    aggregates: Map,
+    /// A flag to indicate whether the rows are truncated by the max rows.
+    truncated: bool,
+    /// Pagination style.
+    style: PaginationStyle,
+}
+
+impl<T> Page<T> {
+    /// Creates a new instance with the entries.
+    #[inline]
+    pub fn new(entries: Vec<T>) -> Self {
+        Self {
+            item_name: "entries".into(),
+            entries,
+            total: None,
+            page_size: None,
+            current_page: None,
+            next_cursor: None,
+            aggregates: Map::new(),
+            truncated: false,
+            style: *SHARED_PAGINATION_STYLE,
+        }
+    }
+
+    /// Creates a new instance with the query results.
+    /// The page size and current page are derived from the limit and offset of the query.
+    pub fn from_query_results(entries: Vec<T>, total: Option<usize>, query: &Query) -> Self {
+        let mut page = Self::new(entries);
+        let limit = query.limit();
+        let offset = query.offset();
+        if limit > 0 && limit != usize::MAX {
+            page.page_size = Some(limit);
+            page.current_page = Some(offset / limit + 1);
+
+            let num_entries = page.entries.len();
+            let has_more = if let Some(total) = total {
+                offset + num_entries < total
+            } else {
+                num_entries >= limit
+            };
+            if has_more {
+                page.next_cursor = Some((offset + num_entries).to_string());
+            }
+        }
+        page.total = total;
+        page
+    }
+
+    /// Sets the name of the entries.
+    #[inline]
+    pub fn item_name(mut self, item_name: impl Into<SharedString>) -> Self {
+        self.item_name = item_name.into();
+        self
+    }
+
+    /// Sets the total number of the rows.
+    #[inline]
+    pub fn total(mut self, total: usize) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    /// Sets the page size.
+    #[inline]
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    /// Sets the current page number.
+    #[inline]
+    pub fn current_page(mut self, current_page: usize) -> Self {
+        self.current_page = Some(current_page.max(1));
+        self
+    }
+
+    /// Sets the cursor to fetch the next page.
+    #[inline]
+    pub fn next_cursor(mut self, cursor: impl Into<Option<String>>) -> Self {
+        self.next_cursor = cursor.into();
+        self
+    }
+
+    /// Adds an aggregate value.
+    #[inline]
+    pub fn aggregate(mut self, key: impl Into<String>, value: impl Into<JsonValue>) -> Self {
+        self.aggregates.insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets the flag to indicate whether the rows are truncated by the max rows.
+    #[inline]
+    pub fn truncated(mut self, truncated: bool) -> Self {
+        self.truncated = truncated;
+        self
+    }
+
+    /// Sets the pagination style.
+    #[inline]
+    pub fn style(mut self, style: PaginationStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Returns a reference to the entries.
+    #[inline]
+    pub fn entries(&self) -> &[T] {
+        &self.entries
+    }
+
+    /// Returns the total number of the rows.
+    #[inline]
+    pub fn total_rows(&self) -> Option<usize> {
+        self.total
+    }
+
+    /// Returns the number of the pages if the total and page size are known.
+    #[inline]
+    pub fn page_count(&self) -> Option<usize> {
+        self.total
+            .zip(self.page_size.filter(|&size| size > 0))
+            .map(|(total, page_size)| total.div_ceil(page_size))
+    }
+
+    /// Returns `true` if there are more rows after the page.
+    pub fn has_more(&self) -> bool {
+        match self.style {
+            PaginationStyle::Offset => self
+                .current_page
+                .zip(self.page_count())
+                .map(|(current_page, page_count)| current_page < page_count)
+                .unwrap_or_else(|| self.next_cursor.is_some()),
+            PaginationStyle::Cursor => self.next_cursor.is_some(),
+        }
+    }
+
+    /// Returns the links to the `next` and `prev` pages for the `Link` header.
+    /// The query parameters of the request are retained except for the pagination.
+    pub fn links(&self, path: &str, query: Option<&str>) -> Vec<(&'static str, String)> {
+        let pairs = form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+            .filter(|(key, _)| !matches!(key.as_ref(), "current_page" | "offset" | "cursor"))
+            .collect::<Vec<_>>();
+        let link = |key: &str, value: String| {
+            let mut serializer = form_urlencoded::Serializer::new(String::new());
+            serializer.extend_pairs(pairs.iter());
+            serializer.append_pair(key, &value);
+            format!("{path}?{}", serializer.finish())
+        };
+
+        let mut links = Vec::new();
+        match self.style {
+            PaginationStyle::Offset => {
+                if let Some(current_page) = self.current_page {
+                    if self.has_more() {
+                        links.push(("next", link("current_page", (current_page + 1).to_string())));
+                    }
+                    if current_page > 1 {
+                        links.push(("prev", link("current_page", (current_page - 1).to_string())));
+                    }
+                }
+            }
+            PaginationStyle::Cursor => {
+                if let Some(cursor) = self.next_cursor.as_ref() {
+                    links.push(("next", link("cursor", cursor.to_owned())));
+                }
+            }
+        }
+        links
+    }
+
+    /// Returns the metadata fields in the serialization order.
+    fn metadata(&self) -> Vec<(Cow<'static, str>, JsonValue)> {
+        let mut metadata = Vec::with_capacity(6);
+        metadata.push((
+            ["num", &self.item_name].join("_").into(),
+            self.entries.len().into(),
+        ));
+        if let Some(total) = self.total {
+            metadata.push(("total_rows".into(), total.into()));
+        }
+        if let Some(page_size) = self.page_size {
+            metadata.push(("page_size".into(), page_size.into()));
+        }
+        match self.style {
+            PaginationStyle::Offset => {
+                if let Some(page_count) = self.page_count() {
+                    metadata.push(("page_count".into(), page_count.into()));
+                }
+                if let Some(current_page) = self.current_page {
+                    metadata.push(("current_page".into(), current_page.into()));
+                }
+            }
+            PaginationStyle::Cursor => {
+                metadata.push(("next_cursor".into(), self.next_cursor.clone().into()));
+                metadata.push(("has_more".into(), self.has_more().into()));
+            }
+        }
+        if !self.aggregates.is_empty() {
+            metadata.push(("aggregates".into(), self.aggregates.clone().into()));
+        }
+        if self.truncated {
+            metadata.push(("truncated".into(), true.into()));
+        }
+        metadata
+    }
+}
+
+impl<T: Into<JsonValue>> Page<T> {
+    /// Consumes `self` and returns a JSON object.
+    pub fn into_map(self) -> Map {
+        let mut map = Map::new();
+        for (key, value) in self.metadata() {
+            map.insert(key.into_owned(), value);
+        }
+        map.insert(self.item_name.into_owned(), self.entries.into());
+        map
+    }
+}
+
+impl<T: Into<JsonValue>> From<Page<T>> for Map {
+    #[inline]
+    fn from(page: Page<T>) -> Self {
+        page.into_map()
+    }
+}
+
+impl<T: Into<JsonValue>> From<Page<T>> for JsonValue {
+    #[inline]
+    fn from(page: Page<T>) -> Self {
+        page.into_map().into()
+    }
+}
+
+impl<T: Serialize> Serialize for Page<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let metadata = self.metadata();
+        let mut map = serializer.serialize_map(Some(metadata.len() + 1))?;
+        for (key, value) in metadata.iter() {
+            map.serialize_entry(key, value)?;
+        }
+        map.serialize_entry(self.item_name.as_ref(), &self.entries)?;
+        map.end()
+    }
+}
+
+/// Pagination style of the [`Page`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PaginationStyle {
+    /// Offset-based pagination with the page numbers.
+    #[default]
+    Offset,
+    /// Cursor-based pagination with the opaque cursors.
+    Cursor,
+}
+
+/// Shared pagination style.
+static SHARED_PAGINATION_STYLE: LazyLock<PaginationStyle> = LazyLock::new(|| {
+    #[cfg(feature = "runtime")]
+    let style = {
+        use crate::{extension::TomlTableExt, state::State};
+
+        State::shared()
+            .get_config("response")
+            .and_then(|config| config.get_str("pagination-style"))
+    };
+    #[cfg(not(feature = "runtime"))]
+    let style = None;
+    match style {
+        Some("cursor") => PaginationStyle::Cursor,
+        Some("offset") | None => PaginationStyle::Offset,
+        Some(style) => {
+            tracing::warn!("unsupported pagination style `{style}`");
+            PaginationStyle::Offset
+        }
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{Page, PaginationStyle};
+    use crate::{model::Query, JsonValue};
+
+    #[test]
+    fn it_serializes_pages() {
+        let mut query = Query::default();
+        query.set_limit(2);
+        query.set_offset(2);
+
+        let page = Page::from_query_results(vec![3, 4], Some(5), &query)
+            .item_name("users")
+            .style(PaginationStyle::Offset);
+        let links = page.links("/user/list", Some("page_size=2&current_page=2"));
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].1, "/user/list?page_size=2&current_page=3");
+
+        let data = JsonValue::from(page.clone());
+        assert_eq!(data["num_users"], 2);
+        assert_eq!(data["total_rows"], 5);
+        assert_eq!(data["page_count"], 3);
+        assert_eq!(data["current_page"], 2);
+        assert!(data.get("truncated").is_none());
+        assert_eq!(serde_json::to_value(&page).unwrap(), data);
+
+        let data = JsonValue::from(page.clone().truncated(true));
+        assert_eq!(data["truncated"], true);
+
+        let page = page.style(PaginationStyle::Cursor);
+        let data = JsonValue::from(page);
+        assert_eq!(data["next_cursor"], "4");
+        assert_eq!(data["has_more"], true);
+    }
+}
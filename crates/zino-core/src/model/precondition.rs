@@ -0,0 +1,74 @@
+use super::Query;
+use crate::{datetime::DateTime, extension::JsonObjectExt, Map};
+use std::time::Duration;
+
+/// Preconditions for the optimistic concurrency control of a model,
+/// which are usually parsed from the `if-match` and `if-unmodified-since` headers.
+///
+/// The preconditions are applied as the query filters of the update or delete statement,
+/// so that the comparison is atomic with the mutation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Preconditions {
+    /// Expected versions of the model.
+    versions: Vec<u64>,
+    /// The model should not be modified since the time.
+    unmodified_since: Option<DateTime>,
+}
+
+impl Preconditions {
+    /// Creates a new instance.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an expected version.
+    #[inline]
+    pub fn add_version(&mut self, version: u64) {
+        self.versions.push(version);
+    }
+
+    /// Sets the time since which the model should not be modified.
+    #[inline]
+    pub fn set_unmodified_since(&mut self, unmodified_since: DateTime) {
+        self.unmodified_since = Some(unmodified_since);
+    }
+
+    /// Returns the expected versions.
+    #[inline]
+    pub fn versions(&self) -> &[u64] {
+        &self.versions
+    }
+
+    /// Returns the time since which the model should not be modified.
+    #[inline]
+    pub fn unmodified_since(&self) -> Option<DateTime> {
+        self.unmodified_since
+    }
+
+    /// Returns `true` if there are no preconditions.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.versions.is_empty() && self.unmodified_since.is_none()
+    }
+
+    /// Applies the preconditions to the query filters of the `version` and `updated_at` fields.
+    pub fn apply_to(&self, query: &mut Query) {
+        match self.versions.as_slice() {
+            [] => (),
+            [version] => query.add_filter("version", *version),
+            versions => query.add_filter("version", Map::from_entry("$in", versions)),
+        }
+        if let Some(unmodified_since) = self.unmodified_since {
+            // HTTP dates have a precision of seconds.
+            let deadline = unmodified_since + Duration::from_secs(1);
+            query.add_filter("updated_at", Map::from_entry("$lt", deadline));
+        }
+    }
+
+    /// Returns the strong entity tag for the version of a model.
+    #[inline]
+    pub fn entity_tag(version: u64) -> String {
+        format!(r#""{version}""#)
+    }
+}
@@ -4,6 +4,7 @@ use crate::{
     validation::Validation,
     JsonValue, Map, SharedString,
 };
+use std::time::Duration;
 
 /// A query type for models.
 #[derive(Debug, Clone)]
@@ -20,6 +21,10 @@ pub struct Query {
     limit: usize,
     /// Extra flags.
     extra: Map,
+    /// Statement timeout.
+    timeout: Option<Duration>,
+    /// Max number of rows returned by the query.
+    max_rows: Option<usize>,
 }
 
 impl Query {
@@ -34,6 +39,8 @@ impl Query {
             offset: 0,
             limit: 0,
             extra: Map::new(),
+            timeout: None,
+            max_rows: None,
         }
     }
 
@@ -72,7 +79,19 @@ impl Query {
                         }));
                     }
                 }
-                "offset" | "skip" => {
+                "sort" => {
+                    if let Some(sort) = value.as_str() {
+                        let mut sort_order = Vec::new();
+                        for key in sort.split(',').filter(|s| !s.trim().is_empty()) {
+                            match QueryOrder::parse(key) {
+                                Ok(order) => sort_order.push(order),
+                                Err(err) => validation.record_fail("sort", err),
+                            }
+                        }
+                        self.sort_order = sort_order;
+                    }
+                }
+                "offset" | "skip" | "cursor" => {
                     if let Some(result) = value.parse_usize() {
                         match result {
                             Ok(offset) => self.offset = offset,
@@ -275,6 +294,19 @@ impl Query {
         self.sort_order.push(QueryOrder::new(field, true));
     }
 
+    /// Appends an ascending order of the field as a tiebreaker if the field is not sorted,
+    /// which ensures a stable order for the pagination.
+    pub fn order_tiebreaker(&mut self, field: impl Into<SharedString>) {
+        let field = field.into();
+        if !self
+            .sort_order
+            .iter()
+            .any(|order| order.field() == field.as_ref())
+        {
+            self.sort_order.push(QueryOrder::new(field, false));
+        }
+    }
+
     /// Sets the query offset.
     #[inline]
     pub fn set_offset(&mut self, offset: usize) {
@@ -293,6 +325,28 @@ impl Query {
         self.limit = 0;
     }
 
+    /// Sets the statement timeout for the query execution.
+    /// The query will be cancelled with an error of `ErrorKind::Timeout`
+    /// if it does not complete in time.
+    #[inline]
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    /// Sets the max number of rows returned by the query, which overrides
+    /// the `max-rows` of the database. A value of `0` disables the cap.
+    /// The result set exceeding the cap is truncated and signaled by the query context.
+    #[inline]
+    pub fn set_max_rows(&mut self, max_rows: usize) {
+        self.max_rows = Some(max_rows);
+    }
+
+    /// Disables the cap of the max number of rows returned by the query.
+    #[inline]
+    pub fn disable_max_rows(&mut self) {
+        self.max_rows = Some(0);
+    }
+
     /// Returns a reference to the projection fields.
     #[inline]
     pub fn fields(&self) -> &[String] {
@@ -323,6 +377,30 @@ impl Query {
         self.limit
     }
 
+    /// Returns the statement timeout.
+    #[inline]
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns the max number of rows returned by the query if it has been set.
+    #[inline]
+    pub fn max_rows(&self) -> Option<usize> {
+        self.max_rows
+    }
+
+    /// Returns `true` if the cap of the max number of rows has been disabled.
+    #[inline]
+    pub fn max_rows_disabled(&self) -> bool {
+        self.max_rows == Some(0)
+    }
+
+    /// Returns a reference to the extra flags.
+    #[inline]
+    pub fn extra_flags(&self) -> &Map {
+        &self.extra
+    }
+
     /// Returns `true` if the `flag` has been enabled.
     #[inline]
     pub fn enabled(&self, flag: &str) -> bool {
@@ -370,6 +448,8 @@ impl Default for Query {
             offset: 0,
             limit: 10,
             extra: Map::new(),
+            timeout: None,
+            max_rows: None,
         }
     }
 }
@@ -0,0 +1,217 @@
+use crate::{datetime::DateTime, extension::TomlTableExt, state::State, LazyLock};
+use std::{
+    env, fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::atomic::{AtomicI64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A generator of roughly-sortable 64-bit IDs in the Snowflake layout.
+///
+/// An ID consists of a 41-bit timestamp in milliseconds since `2020-01-01T00:00:00Z`,
+/// a 10-bit node ID and a 12-bit sequence number, so it fits in a `BIGINT` column.
+/// The node ID is read from the `node-id` of the `[database]` config,
+/// or derived from the hostname if absent.
+///
+/// The IDs are strictly monotonic within a process. Like a hybrid logical clock,
+/// the last timestamp is reused when the system clock moves backwards,
+/// and it is advanced logically when the sequence numbers are exhausted,
+/// so the generation never blocks.
+///
+/// A model can use it for the primary key by `#[schema(primary_key, auto = "snowflake")]`
+/// on an `i64` field, then [`Model::new()`](super::Model::new) will fill it automatically.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SequenceId;
+
+impl SequenceId {
+    /// Generates a new ID.
+    #[inline]
+    pub fn next() -> i64 {
+        SHARED_GENERATOR.generate(current_millis())
+    }
+
+    /// Returns the node ID of the current process.
+    #[inline]
+    pub fn current_node_id() -> u16 {
+        SHARED_GENERATOR.node_id
+    }
+
+    /// Extracts the embedded timestamp of the ID.
+    #[inline]
+    pub fn timestamp(id: i64) -> DateTime {
+        DateTime::from_timestamp_millis((id >> TIMESTAMP_SHIFT) + EPOCH_MILLIS)
+    }
+
+    /// Extracts the node ID of the ID.
+    #[inline]
+    pub fn node_id(id: i64) -> u16 {
+        ((id >> NODE_ID_SHIFT) & MAX_NODE_ID) as u16
+    }
+
+    /// Extracts the sequence number of the ID.
+    #[inline]
+    pub fn sequence(id: i64) -> u16 {
+        (id & MAX_SEQUENCE) as u16
+    }
+}
+
+/// A generator with the node ID and the last state.
+#[derive(Debug)]
+struct SequenceGenerator {
+    /// Node ID.
+    node_id: u16,
+    /// Last timestamp and sequence number packed as `timestamp << 12 | sequence`.
+    state: AtomicI64,
+}
+
+impl SequenceGenerator {
+    /// Creates a new instance.
+    fn new(node_id: u16) -> Self {
+        Self {
+            node_id: node_id & (MAX_NODE_ID as u16),
+            state: AtomicI64::new(0),
+        }
+    }
+
+    /// Generates a new ID with the current timestamp in milliseconds since the custom epoch.
+    fn generate(&self, now: i64) -> i64 {
+        let mut last_state = self.state.load(Ordering::Relaxed);
+        loop {
+            let last_timestamp = last_state >> NODE_ID_SHIFT;
+            let next_state = if now > last_timestamp {
+                now << NODE_ID_SHIFT
+            } else if last_state & MAX_SEQUENCE < MAX_SEQUENCE {
+                last_state + 1
+            } else {
+                (last_timestamp + 1) << NODE_ID_SHIFT
+            };
+            match self.state.compare_exchange_weak(
+                last_state,
+                next_state,
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    let timestamp = next_state >> NODE_ID_SHIFT;
+                    let sequence = next_state & MAX_SEQUENCE;
+                    let node_id = i64::from(self.node_id);
+                    return (timestamp << TIMESTAMP_SHIFT) | (node_id << NODE_ID_SHIFT) | sequence;
+                }
+                Err(state) => last_state = state,
+            }
+        }
+    }
+}
+
+/// Returns the milliseconds since the custom epoch.
+fn current_millis() -> i64 {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default();
+    (millis - EPOCH_MILLIS).max(0)
+}
+
+/// Derives a node ID from the hostname.
+fn derive_node_id() -> u16 {
+    let hostname = env::var("HOSTNAME")
+        .or_else(|_| env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|s| s.trim().to_owned())
+        .filter(|s| !s.is_empty());
+    if let Some(hostname) = hostname {
+        let mut hasher = DefaultHasher::new();
+        hostname.hash(&mut hasher);
+        (hasher.finish() & (MAX_NODE_ID as u64)) as u16
+    } else {
+        tracing::warn!("fail to derive the node ID from the hostname");
+        0
+    }
+}
+
+/// Custom epoch `2020-01-01T00:00:00Z` in milliseconds.
+const EPOCH_MILLIS: i64 = 1_577_836_800_000;
+
+/// Number of bits for the node ID.
+const NODE_ID_BITS: u32 = 10;
+
+/// Number of bits for the sequence number.
+const SEQUENCE_BITS: u32 = 12;
+
+/// Shift of the node ID.
+const NODE_ID_SHIFT: u32 = SEQUENCE_BITS;
+
+/// Shift of the timestamp.
+const TIMESTAMP_SHIFT: u32 = NODE_ID_BITS + SEQUENCE_BITS;
+
+/// Max value of the node ID.
+const MAX_NODE_ID: i64 = (1 << NODE_ID_BITS) - 1;
+
+/// Max value of the sequence number.
+const MAX_SEQUENCE: i64 = (1 << SEQUENCE_BITS) - 1;
+
+/// Shared generator.
+static SHARED_GENERATOR: LazyLock<SequenceGenerator> = LazyLock::new(|| {
+    let node_id = State::shared()
+        .get_config("database")
+        .and_then(|config| config.get_u16("node-id"))
+        .inspect(|&node_id| {
+            if i64::from(node_id) > MAX_NODE_ID {
+                tracing::warn!("the node ID `{node_id}` is truncated to {NODE_ID_BITS} bits");
+            }
+        })
+        .unwrap_or_else(derive_node_id);
+    SequenceGenerator::new(node_id)
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{SequenceGenerator, SequenceId, EPOCH_MILLIS};
+    use std::{collections::HashSet, sync::Arc, thread};
+
+    #[test]
+    fn it_generates_unique_ids_across_threads() {
+        let generator = Arc::new(SequenceGenerator::new(7));
+        let handles = (0..8)
+            .map(|_| {
+                let generator = generator.clone();
+                thread::spawn(move || {
+                    (0..10_000)
+                        .map(|_| generator.generate(super::current_millis()))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut ids = HashSet::new();
+        for handle in handles {
+            let thread_ids = handle.join().unwrap();
+            assert!(thread_ids.windows(2).all(|w| w[0] < w[1]));
+            for id in thread_ids {
+                assert!(id > 0);
+                assert_eq!(SequenceId::node_id(id), 7);
+                assert!(ids.insert(id));
+            }
+        }
+        assert_eq!(ids.len(), 80_000);
+    }
+
+    #[test]
+    fn it_handles_clock_rollback_and_sequence_overflow() {
+        let generator = SequenceGenerator::new(1);
+        let first_id = generator.generate(1_000);
+        let second_id = generator.generate(900);
+        assert!(second_id > first_id);
+        assert_eq!(SequenceId::sequence(second_id), 1);
+
+        let mut last_id = second_id;
+        for _ in 0..5_000 {
+            let id = generator.generate(1_000);
+            assert!(id > last_id);
+            last_id = id;
+        }
+        let timestamp = SequenceId::timestamp(last_id).timestamp_millis() - EPOCH_MILLIS;
+        assert_eq!(timestamp, 1_001);
+    }
+}
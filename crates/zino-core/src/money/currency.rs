@@ -0,0 +1,80 @@
+use crate::LazyLock;
+use rust_decimal::RoundingStrategy;
+
+#[cfg(feature = "runtime")]
+use crate::{extension::TomlTableExt, state::State};
+
+/// Returns `true` if the string is a well-formed ISO 4217 currency code.
+#[inline]
+pub(super) fn is_currency_code(code: &str) -> bool {
+    code.len() == 3 && code.bytes().all(|b| b.is_ascii_uppercase())
+}
+
+/// Returns the number of digits after the decimal separator for the currency.
+pub(super) fn minor_unit_exponent(code: &str) -> u32 {
+    match code {
+        "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF" | "UGX"
+        | "UYI" | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        "CLF" | "UYW" => 4,
+        _ => 2,
+    }
+}
+
+/// Parses the rounding strategy.
+#[cfg(feature = "runtime")]
+pub(super) fn parse_rounding_strategy(strategy: &str) -> Option<RoundingStrategy> {
+    match strategy {
+        "half-even" | "bankers" => Some(RoundingStrategy::MidpointNearestEven),
+        "half-up" => Some(RoundingStrategy::MidpointAwayFromZero),
+        "half-down" => Some(RoundingStrategy::MidpointTowardZero),
+        "up" => Some(RoundingStrategy::AwayFromZero),
+        "down" | "truncate" => Some(RoundingStrategy::ToZero),
+        "ceiling" => Some(RoundingStrategy::ToPositiveInfinity),
+        "floor" => Some(RoundingStrategy::ToNegativeInfinity),
+        _ => None,
+    }
+}
+
+/// Returns the rounding strategy for the currency exponent.
+pub(super) fn rounding_strategy(exponent: u32) -> RoundingStrategy {
+    let (default_strategy, strategies) = &*ROUNDING_STRATEGIES;
+    strategies
+        .iter()
+        .find_map(|&(e, strategy)| (e == exponent).then_some(strategy))
+        .unwrap_or(*default_strategy)
+}
+
+/// Rounding strategies.
+///
+/// The `rounding` of the `[money]` config is either a strategy for all currencies
+/// or a table of the strategies keyed by the currency exponent.
+static ROUNDING_STRATEGIES: LazyLock<(RoundingStrategy, Vec<(u32, RoundingStrategy)>)> =
+    LazyLock::new(|| {
+        #[cfg_attr(not(feature = "runtime"), allow(unused_mut))]
+        let mut default_strategy = RoundingStrategy::MidpointNearestEven;
+        #[cfg_attr(not(feature = "runtime"), allow(unused_mut))]
+        let mut strategies = Vec::new();
+        #[cfg(feature = "runtime")]
+        if let Some(config) = State::shared().get_config("money") {
+            if let Some(strategy) = config.get_str("rounding") {
+                if let Some(strategy) = parse_rounding_strategy(strategy) {
+                    default_strategy = strategy;
+                } else {
+                    tracing::warn!("invalid rounding strategy `{strategy}`");
+                }
+            } else if let Some(table) = config.get_table("rounding") {
+                for (key, value) in table {
+                    let strategy = value.as_str().and_then(parse_rounding_strategy);
+                    match (key.parse::<u32>(), strategy) {
+                        (Ok(exponent), Some(strategy)) => strategies.push((exponent, strategy)),
+                        (Err(_), Some(strategy)) if key == "default" => {
+                            default_strategy = strategy;
+                        }
+                        _ => tracing::warn!("invalid rounding strategy for the exponent `{key}`"),
+                    }
+                }
+            }
+        }
+        (default_strategy, strategies)
+    });
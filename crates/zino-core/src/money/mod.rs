@@ -0,0 +1,331 @@
+//! Monetary amounts with ISO 4217 currency codes.
+//!
+//! The amounts are rounded to the minor unit of the currency with the strategy
+//! specified by the `rounding` of the `[money]` config, which defaults to `half-even`.
+//! The strategy can also be specified per currency exponent:
+//!
+//! ```toml
+//! [money.rounding]
+//! default = "half-even"
+//! 0 = "half-up"
+//! ```
+
+use crate::{error::Error, extension::JsonObjectExt, Decimal, JsonValue, Map};
+use serde::{de, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, ops::Neg, str::FromStr};
+
+mod currency;
+
+pub use rust_decimal::RoundingStrategy;
+
+/// A monetary amount in a currency.
+///
+/// Arithmetic operations never mix currencies: adding or subtracting amounts
+/// in different currencies is an error.
+///
+/// It is serialized as `{"amount": "12.34", "currency": "USD"}` and can be deserialized
+/// from either the object or the compact string form `12.34 USD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Money {
+    /// Amount.
+    amount: Decimal,
+    /// ISO 4217 currency code.
+    currency: [u8; 3],
+}
+
+impl Money {
+    /// Creates a new instance with the amount and the ISO 4217 currency code.
+    pub fn new(amount: Decimal, currency: &str) -> Result<Self, Error> {
+        let code = currency.trim().to_ascii_uppercase();
+        if !currency::is_currency_code(&code) {
+            let message = format!("`{currency}` is not a valid ISO 4217 currency code");
+            return Err(Error::new(message));
+        }
+
+        let mut bytes = [0; 3];
+        bytes.copy_from_slice(code.as_bytes());
+        Ok(Self {
+            amount,
+            currency: bytes,
+        })
+    }
+
+    /// Creates a zero amount in the currency.
+    #[inline]
+    pub fn zero(currency: &str) -> Result<Self, Error> {
+        Self::new(Decimal::ZERO, currency)
+    }
+
+    /// Returns the amount.
+    #[inline]
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+
+    /// Returns the ISO 4217 currency code.
+    #[inline]
+    pub fn currency(&self) -> &str {
+        std::str::from_utf8(&self.currency).unwrap_or_default()
+    }
+
+    /// Returns the number of digits after the decimal separator for the currency.
+    #[inline]
+    pub fn exponent(&self) -> u32 {
+        currency::minor_unit_exponent(self.currency())
+    }
+
+    /// Returns `true` if the amount is zero.
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        self.amount.is_zero()
+    }
+
+    /// Returns `true` if the amount is negative.
+    #[inline]
+    pub fn is_negative(&self) -> bool {
+        self.amount.is_sign_negative() && !self.amount.is_zero()
+    }
+
+    /// Returns `true` if the two amounts are in the same currency.
+    #[inline]
+    pub fn is_same_currency(&self, other: &Self) -> bool {
+        self.currency == other.currency
+    }
+
+    /// Rounds the amount to the minor unit of the currency
+    /// with the configured rounding strategy.
+    #[inline]
+    pub fn round(&self) -> Self {
+        let exponent = self.exponent();
+        self.round_with(currency::rounding_strategy(exponent))
+    }
+
+    /// Rounds the amount to the minor unit of the currency with the rounding strategy.
+    #[inline]
+    pub fn round_with(&self, strategy: RoundingStrategy) -> Self {
+        let amount = self
+            .amount
+            .round_dp_with_strategy(self.exponent(), strategy);
+        Self {
+            amount,
+            currency: self.currency,
+        }
+    }
+
+    /// Adds the other amount, returning an error if the currencies are different.
+    pub fn checked_add(&self, other: &Self) -> Result<Self, Error> {
+        self.check_currency(other, "add")?;
+        let amount = self
+            .amount
+            .checked_add(other.amount)
+            .ok_or_else(|| Error::new(format!("fail to add `{other}` to `{self}`: overflow")))?;
+        Ok(Self {
+            amount,
+            currency: self.currency,
+        })
+    }
+
+    /// Subtracts the other amount, returning an error if the currencies are different.
+    pub fn checked_sub(&self, other: &Self) -> Result<Self, Error> {
+        self.check_currency(other, "subtract")?;
+        let amount = self.amount.checked_sub(other.amount).ok_or_else(|| {
+            Error::new(format!(
+                "fail to subtract `{other}` from `{self}`: overflow"
+            ))
+        })?;
+        Ok(Self {
+            amount,
+            currency: self.currency,
+        })
+    }
+
+    /// Multiplies the amount by a factor without rounding.
+    pub fn checked_mul(&self, factor: Decimal) -> Result<Self, Error> {
+        let amount = self.amount.checked_mul(factor).ok_or_else(|| {
+            Error::new(format!("fail to multiply `{self}` by `{factor}`: overflow"))
+        })?;
+        Ok(Self {
+            amount,
+            currency: self.currency,
+        })
+    }
+
+    /// Sums the amounts in the currency, returning an error if any currencies are different.
+    pub fn sum<'a>(
+        currency: &str,
+        values: impl IntoIterator<Item = &'a Self>,
+    ) -> Result<Self, Error> {
+        values
+            .into_iter()
+            .try_fold(Self::zero(currency)?, |sum, value| sum.checked_add(value))
+    }
+
+    /// Returns an error if the currencies are different.
+    fn check_currency(&self, other: &Self, operation: &str) -> Result<(), Error> {
+        if self.is_same_currency(other) {
+            Ok(())
+        } else {
+            let message = format!(
+                "fail to {operation} the amounts `{self}` and `{other}` in different currencies"
+            );
+            Err(Error::new(message))
+        }
+    }
+}
+
+impl Default for Money {
+    /// Returns a zero amount with the code `XXX` which denotes no currency.
+    #[inline]
+    fn default() -> Self {
+        Self {
+            amount: Decimal::ZERO,
+            currency: *b"XXX",
+        }
+    }
+}
+
+impl fmt::Display for Money {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.amount, self.currency())
+    }
+}
+
+impl FromStr for Money {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((amount, currency)) = s.trim().rsplit_once(' ') else {
+            return Err(Error::new(format!(
+                "`{s}` should be formatted as `12.34 USD`"
+            )));
+        };
+        let amount = amount
+            .trim()
+            .parse::<Decimal>()
+            .map_err(|err| Error::new(format!("invalid amount `{amount}`: {err}")))?;
+        Self::new(amount, currency)
+    }
+}
+
+impl TryFrom<&Map> for Money {
+    type Error = Error;
+
+    fn try_from(map: &Map) -> Result<Self, Self::Error> {
+        let amount = match map.get("amount") {
+            Some(JsonValue::String(value)) => value
+                .parse::<Decimal>()
+                .map_err(|err| Error::new(format!("invalid amount `{value}`: {err}")))?,
+            Some(JsonValue::Number(value)) => {
+                let value = value.to_string();
+                value
+                    .parse::<Decimal>()
+                    .or_else(|_| Decimal::from_scientific(&value))
+                    .map_err(|err| Error::new(format!("invalid amount `{value}`: {err}")))?
+            }
+            _ => return Err(Error::new("the `amount` should be a string or a number")),
+        };
+        let currency = map
+            .get_str("currency")
+            .ok_or_else(|| Error::new("the `currency` should be a string"))?;
+        Self::new(amount, currency)
+    }
+}
+
+impl TryFrom<&JsonValue> for Money {
+    type Error = Error;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            JsonValue::String(value) => value.parse(),
+            JsonValue::Object(map) => map.try_into(),
+            _ => Err(Error::new("the money should be a string or an object")),
+        }
+    }
+}
+
+impl From<Money> for Map {
+    #[inline]
+    fn from(value: Money) -> Self {
+        let mut map = Map::new();
+        map.upsert("amount", value.amount.to_string());
+        map.upsert("currency", value.currency());
+        map
+    }
+}
+
+impl From<Money> for JsonValue {
+    #[inline]
+    fn from(value: Money) -> Self {
+        Map::from(value).into()
+    }
+}
+
+impl Neg for Money {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self::Output {
+        Self {
+            amount: -self.amount,
+            currency: self.currency,
+        }
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Money", 2)?;
+        state.serialize_field("amount", &self.amount.to_string())?;
+        state.serialize_field("currency", self.currency())?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = JsonValue::deserialize(deserializer)?;
+        Self::try_from(&value).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Money, RoundingStrategy};
+    use crate::{Decimal, JsonValue};
+
+    #[test]
+    fn it_operates_money() {
+        let price = "12.345 usd".parse::<Money>().unwrap();
+        assert_eq!(price.currency(), "USD");
+        assert_eq!(price.round().to_string(), "12.34 USD");
+        assert_eq!(
+            price
+                .round_with(RoundingStrategy::MidpointAwayFromZero)
+                .amount(),
+            Decimal::new(1235, 2)
+        );
+        assert_eq!(
+            "1234.5 JPY".parse::<Money>().unwrap().round().to_string(),
+            "1234 JPY"
+        );
+
+        let fee = Money::new(Decimal::new(66, 2), "USD").unwrap();
+        assert_eq!(
+            price.checked_add(&fee).unwrap().amount(),
+            Decimal::new(13005, 3)
+        );
+        assert!(price.checked_sub(&Money::zero("EUR").unwrap()).is_err());
+        assert!(Money::sum("USD", &[price, fee]).is_ok());
+        assert!(Money::new(Decimal::ONE, "US").is_err());
+
+        let value = serde_json::to_value(fee).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "amount": "0.66", "currency": "USD" })
+        );
+        assert_eq!(serde_json::from_value::<Money>(value).unwrap(), fee);
+        let value = JsonValue::from("0.66 USD");
+        assert_eq!(serde_json::from_value::<Money>(value).unwrap(), fee);
+    }
+}
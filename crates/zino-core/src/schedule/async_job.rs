@@ -1,10 +1,18 @@
 //! Scheduler for sync and async cron jobs.
 
-use super::{AsyncScheduler, JobContext, DEFAULT_TICK_INTERVAL};
-use crate::{datetime::DateTime, extension::TomlTableExt, BoxFuture, Uuid};
+use super::{
+    AsyncScheduler, JobContext, JobRegistry, JobRunRecord, OverlapPolicy, DEFAULT_TICK_INTERVAL,
+    MAX_TICK_INTERVAL,
+};
+use crate::{datetime::DateTime, extension::TomlTableExt, BoxFuture, SharedString, Uuid};
 use chrono::Local;
 use cron::Schedule;
-use std::{io, str::FromStr, time::Duration};
+use std::{
+    io,
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+    time::Duration,
+};
 use toml::Table;
 
 /// A function pointer of the async cron job.
@@ -49,12 +57,29 @@ impl AsyncJob {
         let schedule = Schedule::from_str(cron_expr)
             .unwrap_or_else(|err| panic!("invalid cron expression `{cron_expr}`: {err}"));
         let mut context = JobContext::new();
+        context.set_source(cron_expr);
+        if let Some(name) = config.get_str("name") {
+            context.set_name(name.to_owned());
+        }
+        if let Some(policy) = config.get_str("overlap") {
+            if let Some(policy) = OverlapPolicy::parse(policy) {
+                context.set_overlap_policy(policy);
+            } else {
+                tracing::warn!("invalid overlap policy `{policy}` for the job");
+            }
+        }
         if let Some(disabled) = config.get_bool("disable") {
             context.set_disabled_status(disabled);
         }
         if let Some(immediate) = config.get_bool("immediate") {
             context.set_immediate_mode(immediate);
         }
+        if let Some(exclusive) = config.get_bool("exclusive") {
+            context.set_exclusive(exclusive);
+        }
+        if let Some(lock_name) = config.get_str("lock") {
+            context.set_lock_name(lock_name.to_owned());
+        }
         if let Some(ticks) = config
             .get_bool("once")
             .and_then(|b| b.then_some(1))
@@ -71,11 +96,18 @@ impl AsyncJob {
 
     /// Sets the job name.
     #[inline]
-    pub fn name(mut self, name: &'static str) -> Self {
+    pub fn name(mut self, name: impl Into<SharedString>) -> Self {
         self.context.set_name(name);
         self
     }
 
+    /// Sets the overlap policy.
+    #[inline]
+    pub fn overlap_policy(mut self, policy: OverlapPolicy) -> Self {
+        self.context.set_overlap_policy(policy);
+        self
+    }
+
     /// Sets the initial job data.
     #[inline]
     pub fn data<T: Send + 'static>(mut self, data: T) -> Self {
@@ -111,6 +143,22 @@ impl AsyncJob {
         self
     }
 
+    /// Enables the flag to indicate whether the job is run by only one instance at a time.
+    /// It requires a job store which supports the distributed lock.
+    #[inline]
+    pub fn exclusive(mut self, exclusive: bool) -> Self {
+        self.context.set_exclusive(exclusive);
+        self
+    }
+
+    /// Sets the name of the distributed lock for an exclusive job.
+    /// It defaults to the job name.
+    #[inline]
+    pub fn lock_name(mut self, lock_name: impl Into<SharedString>) -> Self {
+        self.context.set_lock_name(lock_name);
+        self
+    }
+
     /// Pauses the job by setting the `disabled` flag to `true`.
     #[inline]
     pub fn pause(&mut self) {
@@ -129,25 +177,29 @@ impl AsyncJob {
         let upcoming = self.upcoming();
         let ctx = &mut self.context;
         let run = self.run;
-        if ctx.is_immediate() && !ctx.is_disabled() && !ctx.is_fused() {
-            ctx.start();
-            ctx.set_next_tick(upcoming);
-            run(ctx).await;
-            ctx.finish();
+        if ctx.job_name().is_some_and(JobRegistry::take_trigger) {
+            ctx.set_manual_mode(true);
+            if !run_job(ctx, run, upcoming).await {
+                // Keeps the trigger pending until the previous run has finished.
+                ctx.set_manual_mode(false);
+                if let Some(job_name) = ctx.job_name() {
+                    JobRegistry::trigger(job_name);
+                }
+            }
+        } else if ctx.is_immediate() && !ctx.is_disabled() && !ctx.is_fused() {
+            run_job(ctx, run, upcoming).await;
         } else if let Some(last_tick) = ctx.last_tick().map(|dt| dt.into()) {
             for event in self.schedule.after(&last_tick) {
                 if event > now || ctx.is_fused() {
                     break;
                 }
-                if !ctx.is_disabled() {
-                    ctx.start();
-                    ctx.set_next_tick(upcoming);
-                    run(ctx).await;
-                    ctx.finish();
+                if !ctx.is_disabled() && !run_job(ctx, run, upcoming).await {
+                    break;
                 }
             }
         } else {
             ctx.set_last_tick(now.into());
+            JobRegistry::register(ctx);
         }
     }
 
@@ -156,10 +208,12 @@ impl AsyncJob {
         let upcoming = self.upcoming();
         let ctx = &mut self.context;
         let run = self.run;
+        ctx.set_manual_mode(true);
         ctx.start();
         ctx.set_next_tick(upcoming);
         run(ctx).await;
         ctx.finish();
+        record_run(ctx.job_name(), ctx.last_run().cloned()).await;
     }
 
     /// Returns a reference to the job context.
@@ -196,8 +250,16 @@ impl AsyncJobScheduler {
     }
 
     /// Adds an async job to the scheduler and returns the job ID.
-    pub fn add(&mut self, job: AsyncJob) -> Uuid {
-        let job_id = job.context().job_id();
+    pub fn add(&mut self, mut job: AsyncJob) -> Uuid {
+        let ctx = job.context_mut();
+        if ctx.job_name().is_none() {
+            // Assigns a name which is unique across all the schedulers.
+            let job_name = format!("async-job-{}", ASYNC_JOB_COUNTER.fetch_add(1, Relaxed));
+            ctx.set_name(job_name);
+        }
+        JobRegistry::register(ctx);
+
+        let job_id = ctx.job_id();
         self.jobs.push(job);
         job_id
     }
@@ -257,7 +319,7 @@ impl AsyncJobScheduler {
                     }
                 }
             }
-            duration
+            duration.min(MAX_TICK_INTERVAL)
         }
     }
 
@@ -313,3 +375,84 @@ impl AsyncScheduler for AsyncJobScheduler {
         Ok(())
     }
 }
+
+/// Runs the async job according to the overlap policy.
+/// Returns `false` if the run is deferred until the previous one has finished.
+async fn run_job(ctx: &mut JobContext, run: AsyncCronJob, upcoming: Option<DateTime>) -> bool {
+    let mut locked = ctx.try_lock();
+    let mut distributed_lock = None;
+    if locked {
+        if let Some(store) = JobRegistry::store() {
+            if let Some(lock_name) = ctx.lock_name().filter(|_| ctx.is_exclusive()) {
+                match store.try_lock_exclusive(lock_name).await {
+                    Ok(true) => distributed_lock = Some((store, lock_name.to_owned(), true)),
+                    Ok(false) => {
+                        // The job is being executed by another instance.
+                        ctx.unlock();
+                        ctx.skip();
+                        return true;
+                    }
+                    Err(err) => {
+                        tracing::error!(lock_name, "fail to acquire the exclusive lock: {err}");
+                        ctx.unlock();
+                        ctx.skip();
+                        return true;
+                    }
+                }
+            } else if ctx.overlap_policy() != OverlapPolicy::Allow {
+                if let Some(job_name) = ctx.job_name() {
+                    match store.try_lock(job_name).await {
+                        Ok(true) => distributed_lock = Some((store, job_name.to_owned(), false)),
+                        Ok(false) => {
+                            ctx.unlock();
+                            locked = false;
+                        }
+                        Err(err) => {
+                            tracing::error!(
+                                job_name,
+                                "fail to acquire the distributed lock: {err}"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+    if !locked {
+        if ctx.overlap_policy() == OverlapPolicy::Skip {
+            ctx.skip();
+            return true;
+        }
+        return false;
+    }
+
+    ctx.start();
+    ctx.set_next_tick(upcoming);
+    run(ctx).await;
+    ctx.finish();
+    ctx.unlock();
+    if let Some((store, lock_name, exclusive)) = distributed_lock {
+        let result = if exclusive {
+            store.unlock_exclusive(&lock_name).await
+        } else {
+            store.unlock(&lock_name).await
+        };
+        if let Err(err) = result {
+            tracing::error!(lock_name, "fail to release the distributed lock: {err}");
+        }
+    }
+    record_run(ctx.job_name(), ctx.last_run().cloned()).await;
+    true
+}
+
+/// Records the last run of the async job with the job store.
+async fn record_run(job_name: Option<&str>, record: Option<JobRunRecord>) {
+    if let (Some(store), Some(record)) = (JobRegistry::store(), record) {
+        if let Err(err) = store.record(&record).await {
+            tracing::error!(job_name, "fail to record the job run: {err}");
+        }
+    }
+}
+
+/// Counter for the names of unnamed async jobs.
+static ASYNC_JOB_COUNTER: AtomicUsize = AtomicUsize::new(0);
@@ -1,24 +1,75 @@
-use crate::{datetime::DateTime, error::Error, Uuid};
+use super::{JobOutcome, JobRegistry, JobRunRecord};
+use crate::{datetime::DateTime, error::Error, SharedString, Uuid};
 use std::{
     any::Any,
     time::{Duration, Instant},
 };
 
+/// Policy for a job run which is due while another run of the same job is in progress.
+///
+/// For multi-instance deployments with a distributed lock provided by the [`JobStore`],
+/// the `Skip` policy ensures that each scheduled run is executed by one instance only.
+///
+/// [`JobStore`]: super::JobStore
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverlapPolicy {
+    /// Defers the run until the previous one has finished.
+    #[default]
+    Queue,
+    /// Skips the run and records it as skipped.
+    Skip,
+    /// Allows the runs to overlap without any locking.
+    Allow,
+}
+
+impl OverlapPolicy {
+    /// Parses the overlap policy from a `str`.
+    #[inline]
+    pub fn parse(policy: &str) -> Option<Self> {
+        match policy {
+            "queue" => Some(Self::Queue),
+            "skip" => Some(Self::Skip),
+            "allow" => Some(Self::Allow),
+            _ => None,
+        }
+    }
+
+    /// Returns the overlap policy as a `str`.
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Queue => "queue",
+            Self::Skip => "skip",
+            Self::Allow => "allow",
+        }
+    }
+}
+
 /// Data associated with a job.
 #[derive(Debug)]
 pub struct JobContext {
     /// Job ID.
     job_id: Uuid,
     /// Job name.
-    job_name: Option<&'static str>,
+    job_name: Option<SharedString>,
     /// The source.
     source: String,
     /// The start time.
     start_time: Instant,
+    /// The start date-time.
+    started_at: DateTime,
+    /// Overlap policy.
+    overlap_policy: OverlapPolicy,
+    /// Flag to indicate whether the current run is triggered manually.
+    manual: bool,
     /// Flag to indicate whether the job is disabled.
     disabled: bool,
     /// Flag to indicate whether the job is executed immediately.
     immediate: bool,
+    /// Flag to indicate whether the job is run by only one instance at a time.
+    exclusive: bool,
+    /// Name of the distributed lock for an exclusive job.
+    lock_name: Option<SharedString>,
     /// Remaining ticks.
     remaining_ticks: Option<usize>,
     /// Last time when running the job.
@@ -27,6 +78,8 @@ pub struct JobContext {
     next_tick: Option<DateTime>,
     /// An error occurred in the job execution.
     execution_error: Option<Error>,
+    /// The last run record.
+    last_run: Option<JobRunRecord>,
     /// Optional job data.
     job_data: Option<Box<dyn Any + Send>>,
 }
@@ -40,12 +93,18 @@ impl JobContext {
             job_name: None,
             source: String::new(),
             start_time: Instant::now(),
+            started_at: DateTime::now(),
+            overlap_policy: OverlapPolicy::default(),
+            manual: false,
             disabled: false,
             immediate: false,
+            exclusive: false,
+            lock_name: None,
             remaining_ticks: None,
             last_tick: None,
             next_tick: None,
             execution_error: None,
+            last_run: None,
             job_data: None,
         }
     }
@@ -54,6 +113,8 @@ impl JobContext {
     #[inline]
     pub fn start(&mut self) {
         self.start_time = Instant::now();
+        self.started_at = DateTime::now();
+        self.execution_error = None;
     }
 
     /// Finishes the job.
@@ -63,13 +124,28 @@ impl JobContext {
         }
 
         let job_id = self.job_id.to_string();
-        let job_name = self.job_name;
+        let job_name = self.job_name.as_deref();
         let remaining_ticks = self.remaining_ticks;
         let last_tick = self.last_tick.map(|dt| dt.to_string());
         let next_tick = self.next_tick.map(|dt| dt.to_string());
         let execution_time = self.start_time.elapsed();
         let execution_time_millis = execution_time.as_millis();
+        let manual = self.manual;
+        let outcome = if self.execution_error.is_some() {
+            JobOutcome::Failed
+        } else {
+            JobOutcome::Succeeded
+        };
+        let mut record = JobRunRecord::new(
+            self.job_id,
+            self.job_name.clone(),
+            self.started_at,
+            execution_time,
+            outcome,
+        );
+        record.set_manual(manual);
         if let Some(error) = self.execution_error.as_ref() {
+            record.set_error_message(error.to_string());
             tracing::error!(
                 job_id,
                 job_name,
@@ -77,6 +153,8 @@ impl JobContext {
                 last_tick,
                 next_tick,
                 execution_time_millis,
+                manual,
+                outcome = outcome.as_str(),
                 "{error}"
             );
         } else {
@@ -87,13 +165,15 @@ impl JobContext {
                 last_tick,
                 next_tick,
                 execution_time_millis,
+                manual,
+                outcome = outcome.as_str(),
             );
         }
         #[cfg(feature = "metrics")]
         if let Some(name) = job_name {
             metrics::histogram!(
                 "zino_job_execution_duration_seconds",
-                "job_name" => name,
+                "job_name" => name.to_owned(),
             )
             .record(execution_time.as_secs_f64());
         } else {
@@ -104,6 +184,52 @@ impl JobContext {
             .record(execution_time.as_secs_f64());
         }
         self.set_last_tick(DateTime::now());
+        self.manual = false;
+        JobRegistry::push_record(&record);
+        JobRegistry::register(self);
+        self.last_run = Some(record);
+    }
+
+    /// Skips the current run of the job due to the overlap policy.
+    pub fn skip(&mut self) {
+        let job_id = self.job_id;
+        let job_name = self.job_name.clone();
+        let manual = self.manual;
+        let outcome = JobOutcome::Skipped;
+        let mut record =
+            JobRunRecord::new(job_id, job_name, DateTime::now(), Duration::ZERO, outcome);
+        record.set_manual(manual);
+        tracing::warn!(
+            job_id = job_id.to_string(),
+            job_name = self.job_name.as_deref(),
+            manual,
+            outcome = outcome.as_str(),
+            "job run is skipped because the previous one is still in progress"
+        );
+        self.set_last_tick(DateTime::now());
+        self.manual = false;
+        JobRegistry::push_record(&record);
+        JobRegistry::register(self);
+        self.last_run = Some(record);
+    }
+
+    /// Tries to acquire the in-process lock for the job according to the overlap policy.
+    pub(super) fn try_lock(&self) -> bool {
+        match self.job_name.as_deref() {
+            Some(job_name) if self.overlap_policy != OverlapPolicy::Allow => {
+                JobRegistry::try_acquire(job_name)
+            }
+            _ => true,
+        }
+    }
+
+    /// Releases the in-process lock for the job.
+    pub(super) fn unlock(&self) {
+        if let Some(job_name) = self.job_name.as_deref() {
+            if self.overlap_policy != OverlapPolicy::Allow {
+                JobRegistry::release(job_name);
+            }
+        }
     }
 
     /// Records an error occurred in the job execution.
@@ -120,8 +246,20 @@ impl JobContext {
 
     /// Sets the job name.
     #[inline]
-    pub fn set_name(&mut self, name: &'static str) {
-        self.job_name = Some(name);
+    pub fn set_name(&mut self, name: impl Into<SharedString>) {
+        self.job_name = Some(name.into());
+    }
+
+    /// Sets the overlap policy.
+    #[inline]
+    pub fn set_overlap_policy(&mut self, policy: OverlapPolicy) {
+        self.overlap_policy = policy;
+    }
+
+    /// Sets the manual mode for the current run.
+    #[inline]
+    pub fn set_manual_mode(&mut self, manual: bool) {
+        self.manual = manual;
     }
 
     /// Sets the source.
@@ -160,6 +298,18 @@ impl JobContext {
         self.immediate = immediate;
     }
 
+    /// Sets the flag to indicate whether the job is run by only one instance at a time.
+    #[inline]
+    pub fn set_exclusive(&mut self, exclusive: bool) {
+        self.exclusive = exclusive;
+    }
+
+    /// Sets the name of the distributed lock for an exclusive job.
+    #[inline]
+    pub fn set_lock_name(&mut self, lock_name: impl Into<SharedString>) {
+        self.lock_name = Some(lock_name.into());
+    }
+
     /// Sets the job data.
     #[inline]
     pub fn set_data<T: Send + 'static>(&mut self, data: T) {
@@ -198,8 +348,8 @@ impl JobContext {
 
     /// Returns the job name.
     #[inline]
-    pub fn job_name(&self) -> Option<&'static str> {
-        self.job_name
+    pub fn job_name(&self) -> Option<&str> {
+        self.job_name.as_deref()
     }
 
     /// Returns a reference to the source, *i.e.* the cron expression for a cron job.
@@ -214,6 +364,31 @@ impl JobContext {
         self.start_time
     }
 
+    /// Returns the overlap policy.
+    #[inline]
+    pub fn overlap_policy(&self) -> OverlapPolicy {
+        self.overlap_policy
+    }
+
+    /// Returns `true` if the job is run by only one instance at a time.
+    #[inline]
+    pub fn is_exclusive(&self) -> bool {
+        self.exclusive
+    }
+
+    /// Returns the name of the distributed lock for an exclusive job.
+    /// It defaults to the job name.
+    #[inline]
+    pub fn lock_name(&self) -> Option<&str> {
+        self.lock_name.as_deref().or(self.job_name.as_deref())
+    }
+
+    /// Returns `true` if the current run is triggered manually.
+    #[inline]
+    pub fn is_manual(&self) -> bool {
+        self.manual
+    }
+
     /// Returns `true` if the job is disabled.
     #[inline]
     pub fn is_disabled(&self) -> bool {
@@ -249,6 +424,12 @@ impl JobContext {
     pub fn execution_error(&self) -> Option<&Error> {
         self.execution_error.as_ref()
     }
+
+    /// Returns a reference to the last run record.
+    #[inline]
+    pub fn last_run(&self) -> Option<&JobRunRecord> {
+        self.last_run.as_ref()
+    }
 }
 
 impl Default for JobContext {
@@ -1,10 +1,16 @@
 //! Scheduler for sync and async cron jobs.
 
-use super::{JobContext, Scheduler, DEFAULT_TICK_INTERVAL};
-use crate::{datetime::DateTime, extension::TomlTableExt, Uuid};
+use super::{
+    JobContext, JobRegistry, OverlapPolicy, Scheduler, DEFAULT_TICK_INTERVAL, MAX_TICK_INTERVAL,
+};
+use crate::{datetime::DateTime, extension::TomlTableExt, SharedString, Uuid};
 use chrono::Local;
 use cron::Schedule;
-use std::{str::FromStr, time::Duration};
+use std::{
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+    time::Duration,
+};
 use toml::Table;
 
 /// A function pointer of the cron job.
@@ -49,6 +55,17 @@ impl Job {
         let schedule = Schedule::from_str(cron_expr)
             .unwrap_or_else(|err| panic!("invalid cron expression `{cron_expr}`: {err}"));
         let mut context = JobContext::new();
+        context.set_source(cron_expr);
+        if let Some(name) = config.get_str("name") {
+            context.set_name(name.to_owned());
+        }
+        if let Some(policy) = config.get_str("overlap") {
+            if let Some(policy) = OverlapPolicy::parse(policy) {
+                context.set_overlap_policy(policy);
+            } else {
+                tracing::warn!("invalid overlap policy `{policy}` for the job");
+            }
+        }
         if let Some(disabled) = config.get_bool("disable") {
             context.set_disabled_status(disabled);
         }
@@ -71,11 +88,18 @@ impl Job {
 
     /// Sets the job name.
     #[inline]
-    pub fn name(mut self, name: &'static str) -> Self {
+    pub fn name(mut self, name: impl Into<SharedString>) -> Self {
         self.context.set_name(name);
         self
     }
 
+    /// Sets the overlap policy.
+    #[inline]
+    pub fn overlap_policy(mut self, policy: OverlapPolicy) -> Self {
+        self.context.set_overlap_policy(policy);
+        self
+    }
+
     /// Sets the initial job data.
     #[inline]
     pub fn data<T: Send + 'static>(mut self, data: T) -> Self {
@@ -129,25 +153,29 @@ impl Job {
         let upcoming = self.upcoming();
         let ctx = &mut self.context;
         let run = self.run;
-        if ctx.is_immediate() && !ctx.is_disabled() && !ctx.is_fused() {
-            ctx.start();
-            ctx.set_next_tick(upcoming);
-            run(ctx);
-            ctx.finish();
+        if ctx.job_name().is_some_and(JobRegistry::take_trigger) {
+            ctx.set_manual_mode(true);
+            if !run_job(ctx, run, upcoming) {
+                // Keeps the trigger pending until the previous run has finished.
+                ctx.set_manual_mode(false);
+                if let Some(job_name) = ctx.job_name() {
+                    JobRegistry::trigger(job_name);
+                }
+            }
+        } else if ctx.is_immediate() && !ctx.is_disabled() && !ctx.is_fused() {
+            run_job(ctx, run, upcoming);
         } else if let Some(last_tick) = ctx.last_tick().map(|dt| dt.into()) {
             for event in self.schedule.after(&last_tick) {
                 if event > now || ctx.is_fused() {
                     break;
                 }
-                if !ctx.is_disabled() {
-                    ctx.start();
-                    ctx.set_next_tick(upcoming);
-                    run(ctx);
-                    ctx.finish();
+                if !ctx.is_disabled() && !run_job(ctx, run, upcoming) {
+                    break;
                 }
             }
         } else {
             ctx.set_last_tick(now.into());
+            JobRegistry::register(ctx);
         }
     }
 
@@ -156,6 +184,7 @@ impl Job {
         let upcoming = self.upcoming();
         let ctx = &mut self.context;
         let run = self.run;
+        ctx.set_manual_mode(true);
         ctx.start();
         ctx.set_next_tick(upcoming);
         run(ctx);
@@ -196,8 +225,16 @@ impl JobScheduler {
     }
 
     /// Adds a job to the scheduler and returns the job ID.
-    pub fn add(&mut self, job: Job) -> Uuid {
-        let job_id = job.context().job_id();
+    pub fn add(&mut self, mut job: Job) -> Uuid {
+        let ctx = job.context_mut();
+        if ctx.job_name().is_none() {
+            // Assigns a name which is unique across all the schedulers.
+            let job_name = format!("job-{}", JOB_COUNTER.fetch_add(1, Relaxed));
+            ctx.set_name(job_name);
+        }
+        JobRegistry::register(ctx);
+
+        let job_id = ctx.job_id();
         self.jobs.push(job);
         job_id
     }
@@ -257,7 +294,7 @@ impl JobScheduler {
                     }
                 }
             }
-            duration
+            duration.min(MAX_TICK_INTERVAL)
         }
     }
 
@@ -303,3 +340,24 @@ impl Scheduler for JobScheduler {
         self.tick();
     }
 }
+
+/// Runs the job according to the overlap policy.
+/// Returns `false` if the run is deferred until the previous one has finished.
+fn run_job(ctx: &mut JobContext, run: CronJob, upcoming: Option<DateTime>) -> bool {
+    if !ctx.try_lock() {
+        if ctx.overlap_policy() == OverlapPolicy::Skip {
+            ctx.skip();
+            return true;
+        }
+        return false;
+    }
+    ctx.start();
+    ctx.set_next_tick(upcoming);
+    run(ctx);
+    ctx.finish();
+    ctx.unlock();
+    true
+}
+
+/// Counter for the names of unnamed jobs.
+static JOB_COUNTER: AtomicUsize = AtomicUsize::new(0);
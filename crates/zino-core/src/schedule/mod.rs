@@ -5,10 +5,14 @@ use std::{future::Future, io, time::Duration};
 mod async_job;
 mod context;
 mod job;
+mod record;
+mod registry;
 
 pub use async_job::{AsyncCronJob, AsyncJob, AsyncJobScheduler};
-pub use context::JobContext;
+pub use context::{JobContext, OverlapPolicy};
 pub use job::{CronJob, Job, JobScheduler};
+pub use record::{JobOutcome, JobRunRecord};
+pub use registry::{JobRegistry, JobStore};
 
 /// An interface for scheduling sync jobs.
 pub trait Scheduler {
@@ -68,3 +72,6 @@ impl AsyncScheduler for apalis::prelude::Monitor {
 
 /// The default tick interval.
 const DEFAULT_TICK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The max tick interval so that manually triggered jobs can be picked up in time.
+const MAX_TICK_INTERVAL: Duration = Duration::from_secs(1);
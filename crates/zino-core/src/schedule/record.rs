@@ -0,0 +1,155 @@
+use crate::{datetime::DateTime, extension::JsonObjectExt, Map, SharedString, Uuid};
+use std::time::Duration;
+
+/// Outcome of a job run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobOutcome {
+    /// The job run succeeded.
+    Succeeded,
+    /// The job run failed with an error.
+    Failed,
+    /// The job run was skipped due to the overlap policy.
+    Skipped,
+}
+
+impl JobOutcome {
+    /// Returns the outcome as a `str`.
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::Skipped => "skipped",
+        }
+    }
+}
+
+/// A record for a single run of the job.
+#[derive(Debug, Clone)]
+pub struct JobRunRecord {
+    /// Run ID.
+    run_id: Uuid,
+    /// Job ID.
+    job_id: Uuid,
+    /// Job name.
+    job_name: Option<SharedString>,
+    /// The start time.
+    started_at: DateTime,
+    /// The end time.
+    finished_at: DateTime,
+    /// The execution time.
+    execution_time: Duration,
+    /// The outcome.
+    outcome: JobOutcome,
+    /// An optional error message.
+    error_message: Option<String>,
+    /// Flag to indicate whether the run is triggered manually.
+    manual: bool,
+}
+
+impl JobRunRecord {
+    /// Creates a new instance.
+    pub(super) fn new(
+        job_id: Uuid,
+        job_name: Option<SharedString>,
+        started_at: DateTime,
+        execution_time: Duration,
+        outcome: JobOutcome,
+    ) -> Self {
+        Self {
+            run_id: Uuid::now_v7(),
+            job_id,
+            job_name,
+            started_at,
+            finished_at: DateTime::now(),
+            execution_time,
+            outcome,
+            error_message: None,
+            manual: false,
+        }
+    }
+
+    /// Sets the error message.
+    #[inline]
+    pub(super) fn set_error_message(&mut self, message: impl Into<String>) {
+        self.error_message = Some(message.into());
+    }
+
+    /// Sets the flag to indicate whether the run is triggered manually.
+    #[inline]
+    pub(super) fn set_manual(&mut self, manual: bool) {
+        self.manual = manual;
+    }
+
+    /// Returns the run ID.
+    #[inline]
+    pub fn run_id(&self) -> Uuid {
+        self.run_id
+    }
+
+    /// Returns the job ID.
+    #[inline]
+    pub fn job_id(&self) -> Uuid {
+        self.job_id
+    }
+
+    /// Returns the job name.
+    #[inline]
+    pub fn job_name(&self) -> Option<&str> {
+        self.job_name.as_deref()
+    }
+
+    /// Returns the start time.
+    #[inline]
+    pub fn started_at(&self) -> DateTime {
+        self.started_at
+    }
+
+    /// Returns the end time.
+    #[inline]
+    pub fn finished_at(&self) -> DateTime {
+        self.finished_at
+    }
+
+    /// Returns the execution time.
+    #[inline]
+    pub fn execution_time(&self) -> Duration {
+        self.execution_time
+    }
+
+    /// Returns the outcome.
+    #[inline]
+    pub fn outcome(&self) -> JobOutcome {
+        self.outcome
+    }
+
+    /// Returns the error message.
+    #[inline]
+    pub fn error_message(&self) -> Option<&str> {
+        self.error_message.as_deref()
+    }
+
+    /// Returns `true` if the run is triggered manually.
+    #[inline]
+    pub fn is_manual(&self) -> bool {
+        self.manual
+    }
+
+    /// Converts `self` to a JSON object.
+    pub fn to_map(&self) -> Map {
+        let mut map = Map::new();
+        map.upsert("run_id", self.run_id.to_string());
+        map.upsert("job_id", self.job_id.to_string());
+        map.upsert("job_name", self.job_name.as_deref());
+        map.upsert("started_at", self.started_at);
+        map.upsert("finished_at", self.finished_at);
+        map.upsert(
+            "execution_time_millis",
+            self.execution_time.as_millis() as u64,
+        );
+        map.upsert("outcome", self.outcome.as_str());
+        map.upsert("error_message", self.error_message.as_deref());
+        map.upsert("manual", self.manual);
+        map
+    }
+}
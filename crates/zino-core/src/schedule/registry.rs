@@ -0,0 +1,233 @@
+use super::{JobContext, JobRunRecord, OverlapPolicy};
+use crate::{
+    datetime::DateTime, error::Error, extension::JsonObjectExt, BoxFuture, LazyLock, Map,
+    SharedString,
+};
+use parking_lot::RwLock;
+use std::{collections::VecDeque, sync::OnceLock};
+
+/// An interface for persisting job runs and coordinating jobs across multiple instances.
+///
+/// The store is only used by async jobs. Run records of sync jobs are emitted as
+/// structured tracing events and kept in the in-memory job registry.
+pub trait JobStore: Send + Sync {
+    /// Records a job run.
+    fn record<'a>(&'a self, record: &'a JobRunRecord) -> BoxFuture<'a, Result<(), Error>>;
+
+    /// Tries to acquire a lock for the job shared by all instances,
+    /// returning `false` if it has been acquired by others.
+    fn try_lock<'a>(&'a self, job_name: &'a str) -> BoxFuture<'a, Result<bool, Error>> {
+        let _ = job_name;
+        Box::pin(async { Ok(true) })
+    }
+
+    /// Releases the lock for the job.
+    fn unlock<'a>(&'a self, job_name: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+        let _ = job_name;
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Tries to acquire the lock for an exclusive job shared by all instances,
+    /// returning `false` if it has been acquired by others.
+    fn try_lock_exclusive<'a>(&'a self, lock_name: &'a str) -> BoxFuture<'a, Result<bool, Error>> {
+        self.try_lock(lock_name)
+    }
+
+    /// Releases the lock for an exclusive job.
+    fn unlock_exclusive<'a>(&'a self, lock_name: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+        self.unlock(lock_name)
+    }
+}
+
+/// Global access to the registered jobs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JobRegistry;
+
+impl JobRegistry {
+    /// Sets the job store used to persist runs and acquire distributed locks.
+    /// It can only be set once.
+    pub fn set_store(store: impl JobStore + 'static) {
+        if JOB_STORE.set(Box::new(store)).is_err() {
+            tracing::warn!("the job store has already been set");
+        }
+    }
+
+    /// Returns a reference to the job store if it has been set.
+    #[inline]
+    pub fn store() -> Option<&'static dyn JobStore> {
+        JOB_STORE.get().map(|store| store.as_ref())
+    }
+
+    /// Lists the registered jobs with their schedules and last runs.
+    pub fn list() -> Vec<Map> {
+        JOB_ENTRIES
+            .read()
+            .iter()
+            .map(|entry| entry.to_map())
+            .collect()
+    }
+
+    /// Returns the recent runs of the job with the name, from the latest to the earliest.
+    pub fn history(job_name: &str) -> Vec<JobRunRecord> {
+        JOB_ENTRIES
+            .read()
+            .iter()
+            .find(|entry| entry.job_name == job_name)
+            .map(|entry| entry.runs.iter().rev().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Triggers the job with the name to run immediately on the next tick of the scheduler.
+    /// Returns `false` if the job can not be found.
+    pub fn trigger(job_name: &str) -> bool {
+        if let Some(entry) = JOB_ENTRIES
+            .write()
+            .iter_mut()
+            .find(|entry| entry.job_name == job_name)
+        {
+            entry.triggered = true;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Registers or updates the job with the context.
+    pub(super) fn register(ctx: &JobContext) {
+        let Some(job_name) = ctx.job_name() else {
+            return;
+        };
+        let mut entries = JOB_ENTRIES.write();
+        if let Some(entry) = entries.iter_mut().find(|entry| entry.job_name == job_name) {
+            entry.update(ctx);
+        } else {
+            let mut entry = JobEntry::new(job_name.to_owned().into());
+            entry.update(ctx);
+            entries.push(entry);
+        }
+    }
+
+    /// Takes the pending trigger of the job.
+    pub(super) fn take_trigger(job_name: &str) -> bool {
+        JOB_ENTRIES
+            .write()
+            .iter_mut()
+            .find(|entry| entry.job_name == job_name)
+            .is_some_and(|entry| std::mem::take(&mut entry.triggered))
+    }
+
+    /// Tries to mark the job as running, returning `false` if it is already running.
+    pub(super) fn try_acquire(job_name: &str) -> bool {
+        let mut entries = JOB_ENTRIES.write();
+        match entries.iter_mut().find(|entry| entry.job_name == job_name) {
+            Some(entry) if entry.running => false,
+            Some(entry) => {
+                entry.running = true;
+                true
+            }
+            None => true,
+        }
+    }
+
+    /// Marks the job as not running.
+    pub(super) fn release(job_name: &str) {
+        if let Some(entry) = JOB_ENTRIES
+            .write()
+            .iter_mut()
+            .find(|entry| entry.job_name == job_name)
+        {
+            entry.running = false;
+        }
+    }
+
+    /// Appends a run record of the job.
+    pub(super) fn push_record(record: &JobRunRecord) {
+        let Some(job_name) = record.job_name() else {
+            return;
+        };
+        if let Some(entry) = JOB_ENTRIES
+            .write()
+            .iter_mut()
+            .find(|entry| entry.job_name == job_name)
+        {
+            if entry.runs.len() >= MAX_JOB_RUNS {
+                entry.runs.pop_front();
+            }
+            entry.runs.push_back(record.clone());
+        }
+    }
+}
+
+/// An entry of the job registry.
+struct JobEntry {
+    /// Job name.
+    job_name: SharedString,
+    /// The source.
+    source: String,
+    /// Overlap policy.
+    overlap_policy: OverlapPolicy,
+    /// Flag to indicate whether the job is disabled.
+    disabled: bool,
+    /// Flag to indicate whether the job is running.
+    running: bool,
+    /// Flag to indicate whether the job has been triggered manually.
+    triggered: bool,
+    /// Last time when running the job.
+    last_tick: Option<DateTime>,
+    /// Next time when running the job.
+    next_tick: Option<DateTime>,
+    /// Recent runs.
+    runs: VecDeque<JobRunRecord>,
+}
+
+impl JobEntry {
+    /// Creates a new instance.
+    fn new(job_name: SharedString) -> Self {
+        Self {
+            job_name,
+            source: String::new(),
+            overlap_policy: OverlapPolicy::default(),
+            disabled: false,
+            running: false,
+            triggered: false,
+            last_tick: None,
+            next_tick: None,
+            runs: VecDeque::new(),
+        }
+    }
+
+    /// Updates the entry with the job context.
+    fn update(&mut self, ctx: &JobContext) {
+        if self.source != ctx.source() {
+            self.source = ctx.source().to_owned();
+        }
+        self.overlap_policy = ctx.overlap_policy();
+        self.disabled = ctx.is_disabled();
+        self.last_tick = ctx.last_tick();
+        self.next_tick = ctx.next_tick();
+    }
+
+    /// Converts `self` to a JSON object.
+    fn to_map(&self) -> Map {
+        let mut map = Map::new();
+        map.upsert("job_name", self.job_name.as_ref());
+        map.upsert("schedule", self.source.as_str());
+        map.upsert("overlap_policy", self.overlap_policy.as_str());
+        map.upsert("disabled", self.disabled);
+        map.upsert("running", self.running);
+        map.upsert("triggered", self.triggered);
+        map.upsert("last_tick", self.last_tick);
+        map.upsert("next_tick", self.next_tick);
+        map.upsert("last_run", self.runs.back().map(|record| record.to_map()));
+        map
+    }
+}
+
+/// Max number of recent runs kept for each job.
+const MAX_JOB_RUNS: usize = 20;
+
+/// Registered jobs.
+static JOB_ENTRIES: LazyLock<RwLock<Vec<JobEntry>>> = LazyLock::new(|| RwLock::new(Vec::new()));
+
+/// Job store.
+static JOB_STORE: OnceLock<Box<dyn JobStore>> = OnceLock::new();
@@ -0,0 +1,217 @@
+use crate::{application::ServerTag, error::Error, extension::TomlTableExt};
+use std::net::{IpAddr, SocketAddr};
+use toml::value::Table;
+
+/// A listener of the HTTP server with a scope.
+///
+/// The listeners are configured by the `[[server.listener]]` tables.
+/// The routes registered by [`register_scoped()`](crate::application::Application::register_scoped)
+/// are only attached to the listeners with the same scope, where `public` and `internal`
+/// are aliases of the `main` and `debug` servers. The middleware config such as `[cors]`
+/// can be overridden for a listener by specifying a table with the same name.
+///
+/// If there is no `[[server.listener]]` table, the listeners are derived from
+/// the `[main]`, `[debug]` and `[[standby]]` tables.
+///
+/// # Examples
+///
+/// ```toml
+/// [[server.listener]]
+/// scope = "public"
+/// address = "0.0.0.0"
+/// port = 8080
+/// tls = { cert-file = "./local/certs/server.crt", key-file = "./local/certs/server.key" }
+///
+/// [[server.listener]]
+/// scope = "internal"
+/// address = "127.0.0.1"
+/// port = 9090
+///
+/// [server.listener.cors]
+/// allow-origin = ["http://localhost:3000"]
+/// ```
+#[derive(Debug, Clone)]
+pub struct ServerListener {
+    /// Scope of the listener.
+    scope: ServerTag,
+    /// Socket address.
+    addr: SocketAddr,
+    /// TLS certificate file and private key file.
+    tls: Option<(String, String)>,
+    /// Listener config.
+    config: Table,
+    /// A flag to indicate whether the listener is configured by `[[server.listener]]`.
+    scoped: bool,
+}
+
+impl ServerListener {
+    /// Creates a new instance for the legacy server config.
+    #[inline]
+    pub(super) fn new(scope: ServerTag, addr: SocketAddr) -> Self {
+        Self {
+            scope,
+            addr,
+            tls: None,
+            config: Table::new(),
+            scoped: false,
+        }
+    }
+
+    /// Parses the `[[server.listener]]` table.
+    pub(super) fn with_config(config: &Table) -> Result<Self, Error> {
+        let scope = config.get_str("scope").unwrap_or("public");
+        let host = config
+            .get_str("address")
+            .or_else(|| config.get_str("host"))
+            .unwrap_or("127.0.0.1");
+        let ip = host.parse::<IpAddr>().map_err(|err| {
+            Error::new(format!(
+                "invalid address `{host}` of the `{scope}` listener: {err}"
+            ))
+        })?;
+        let port = config.get_u16("port").ok_or_else(|| {
+            Error::new(format!(
+                "the `port` of the `{scope}` listener should be an integer"
+            ))
+        })?;
+        let addr = SocketAddr::from((ip, port));
+        let tls = if let Some(tls) = config.get_table("tls") {
+            if tls.get_bool("enabled") == Some(false) {
+                None
+            } else {
+                let cert_file = tls.get_str("cert-file").ok_or_else(|| {
+                    Error::new(format!("the `tls.cert-file` of `{addr}` should be a str"))
+                })?;
+                let key_file = tls.get_str("key-file").ok_or_else(|| {
+                    Error::new(format!("the `tls.key-file` of `{addr}` should be a str"))
+                })?;
+                Some((cert_file.to_owned(), key_file.to_owned()))
+            }
+        } else {
+            None
+        };
+        Ok(Self {
+            scope: scope.into(),
+            addr,
+            tls,
+            config: config.clone(),
+            scoped: true,
+        })
+    }
+
+    /// Returns the scope of the listener.
+    #[inline]
+    pub fn scope(&self) -> &ServerTag {
+        &self.scope
+    }
+
+    /// Returns the socket address.
+    #[inline]
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns the TLS certificate file and private key file.
+    #[inline]
+    pub fn tls(&self) -> Option<(&str, &str)> {
+        self.tls
+            .as_ref()
+            .map(|(cert_file, key_file)| (cert_file.as_str(), key_file.as_str()))
+    }
+
+    /// Returns the listener config.
+    #[inline]
+    pub fn config(&self) -> &Table {
+        &self.config
+    }
+
+    /// Returns the listener config corresponding to the `key`.
+    #[inline]
+    pub fn get_config(&self, key: &str) -> Option<&Table> {
+        self.config.get_table(key)
+    }
+
+    /// Returns `true` if the listener is configured by `[[server.listener]]`.
+    #[inline]
+    pub fn is_scoped(&self) -> bool {
+        self.scoped
+    }
+
+    /// Returns `true` if the default routes should be attached to the listener.
+    ///
+    /// For the scoped listeners, only the `public` scope serves the default routes.
+    #[inline]
+    pub fn serves_default_routes(&self) -> bool {
+        !self.scoped || self.scope.is_main()
+    }
+
+    /// Returns `true` if the routes registered with the server tag
+    /// should be attached to the listener.
+    ///
+    /// For the legacy listeners, the `debug` server serves all the tagged routes.
+    #[inline]
+    pub fn serves_routes(&self, server_tag: &ServerTag) -> bool {
+        &self.scope == server_tag || (!self.scoped && self.scope.is_debug())
+    }
+}
+
+/// Parses the `[[server.listener]]` tables.
+pub(super) fn parse_listeners(config: &Table) -> Result<Vec<ServerListener>, Error> {
+    let mut listeners = Vec::<ServerListener>::new();
+    let tables = config
+        .get_table("server")
+        .and_then(|t| t.get_array("listener"))
+        .into_iter()
+        .flatten();
+    for table in tables {
+        let table = table.as_table().ok_or_else(|| {
+            Error::new("the `server.listener` field should be an array of tables")
+        })?;
+        let listener = ServerListener::with_config(table)?;
+        let addr = listener.addr;
+        if listeners.iter().any(|l| l.addr == addr) {
+            return Err(Error::new(format!("the listener `{addr}` is duplicated")));
+        }
+        listeners.push(listener);
+    }
+    Ok(listeners)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_listeners;
+    use crate::application::ServerTag;
+
+    #[test]
+    fn it_parses_scoped_listeners() {
+        let config = r#"
+            [[server.listener]]
+            scope = "public"
+            address = "0.0.0.0"
+            port = 8080
+
+            [[server.listener]]
+            scope = "internal"
+            address = "127.0.0.1"
+            port = 9090
+
+            [server.listener.cors]
+            allow-origin = "any"
+        "#;
+        let listeners = parse_listeners(&config.parse().unwrap()).unwrap();
+        assert_eq!(listeners.len(), 2);
+        assert!(listeners[0].serves_default_routes());
+        assert!(!listeners[1].serves_default_routes());
+        assert!(listeners[1].serves_routes(&ServerTag::Debug));
+        assert!(!listeners[1].serves_routes(&"admin".into()));
+        assert!(listeners[1].get_config("cors").is_some());
+        assert_eq!(listeners[1].addr().to_string(), "127.0.0.1:9090");
+
+        let config = r#"
+            [[server.listener]]
+            address = "localhost"
+            port = 8080
+        "#;
+        assert!(parse_listeners(&config.parse().unwrap()).is_err());
+    }
+}
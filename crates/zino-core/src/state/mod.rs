@@ -60,24 +60,38 @@ use crate::{
     application::{self, Agent, Application, ServerTag},
     crypto,
     encoding::base64,
+    error::Error,
     extension::TomlTableExt,
     helper, LazyLock,
 };
+use serde::de::DeserializeOwned;
 use std::{
     borrow::Cow,
+    fmt,
     net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::{PoisonError, RwLock},
 };
 use toml::value::Table;
 
+mod check;
 mod config;
 mod data;
 mod env;
+mod listener;
+mod secrets;
+mod settings;
 
+pub(crate) use secrets::{resolve_secret, SECRET_PREFIX};
+
+pub use check::{ConfigIssue, ConfigIssueLevel, ConfigReport};
 pub use data::{Data, SharedData};
 pub use env::Env;
+pub use listener::ServerListener;
+pub use secrets::Secrets;
+pub use settings::{deserialize_byte_size, deserialize_duration, parse_byte_size};
 
 /// A state is a record of the env, config and associated data.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct State<T = ()> {
     /// Environment.
     env: Env,
@@ -106,6 +120,13 @@ impl<T> State<T> {
     /// which can be specified by the environment variable `ZINO_APP_CONFIG_FORMAT`.
     /// By default, it reads the config from a local file. If `ZINO_APP_CONFIG_URL` is set,
     /// it will fetch the config from the URL instead.
+    ///
+    /// The sealed secrets in `secrets.enc.toml` are merged into the config
+    /// under the `secrets` table. See [`Secrets`] for the details.
+    ///
+    /// # Panics
+    ///
+    /// It panics if the sealed secrets can not be unsealed with the master key.
     pub fn load_config(&mut self) {
         let env = self.env.as_str();
         let config_table = if let Ok(config_url) = std::env::var("ZINO_APP_CONFIG_URL") {
@@ -138,6 +159,10 @@ impl<T> State<T> {
             }
         };
         self.config = config_table;
+        if let Some(secrets) = secrets::load_secrets(Agent::config_dir()) {
+            self.config
+                .insert("secrets".to_owned(), secrets.to_table().into());
+        }
     }
 
     /// Set the state data.
@@ -164,6 +189,59 @@ impl<T> State<T> {
         self.config().get_table(key)
     }
 
+    /// Deserializes the config corresponding to the `key` into a user-defined type.
+    /// The key can be a dotted path such as `server.tls`.
+    ///
+    /// If the config is absent, it will be deserialized from an empty table,
+    /// so the fields with `#[serde(default)]` can still be populated.
+    /// The error names the exact key path and the expected type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use serde::Deserialize;
+    /// use std::time::Duration;
+    /// use zino_core::state::State;
+    ///
+    /// #[derive(Deserialize)]
+    /// #[serde(rename_all = "kebab-case")]
+    /// struct TlsSettings {
+    ///     cert_file: String,
+    ///     #[serde(default, deserialize_with = "zino_core::state::deserialize_duration")]
+    ///     handshake_timeout: Duration,
+    /// }
+    ///
+    /// let settings = State::shared().get_config_as::<TlsSettings>("server.tls")?;
+    /// ```
+    #[inline]
+    pub fn get_config_as<C: DeserializeOwned>(&self, key: &str) -> Result<C, Error> {
+        settings::deserialize_config(self.config(), key)
+    }
+
+    /// Validates the config and generates a startup report with the listeners.
+    /// See [`ConfigReport`] for the details.
+    pub fn check_config(&self, strict: bool) -> ConfigReport {
+        let mut report = ConfigReport::check(self.config(), strict);
+        if let Err(err) = listener::parse_listeners(self.config()) {
+            report.add_error("server.listener", err.to_string());
+        }
+
+        let listener_keys = ["main", "debug", "standby", "server.listener"];
+        if !listener_keys.iter().any(|key| report.has_issues_for(key)) {
+            for listener in self.server_listeners() {
+                let scope = listener.scope().as_str();
+                let addr = listener.addr();
+                let protocol = if listener.tls().is_some() {
+                    "https"
+                } else {
+                    "http"
+                };
+                report.add_overview("listener", format!("{scope} => {protocol}://{addr}"));
+            }
+        }
+        report
+    }
+
     /// Returns a reference to the config corresponding to the `extension`.
     #[inline]
     pub fn get_extension_config(&self, extension: &str) -> Option<&Table> {
@@ -183,8 +261,28 @@ impl<T> State<T> {
     }
 
     /// Returns a list of listeners.
+    #[inline]
     pub fn listeners(&self) -> Vec<(ServerTag, SocketAddr)> {
+        self.server_listeners()
+            .into_iter()
+            .map(|listener| (listener.scope().clone(), listener.addr()))
+            .collect()
+    }
+
+    /// Returns a list of listeners with the scopes.
+    /// See [`ServerListener`] for the config.
+    ///
+    /// # Panics
+    ///
+    /// It panics if the `[[server.listener]]` tables are invalid.
+    pub fn server_listeners(&self) -> Vec<ServerListener> {
         let config = self.config();
+        let listeners = listener::parse_listeners(config)
+            .unwrap_or_else(|err| panic!("invalid `server.listener` config: {err}"));
+        if !listeners.is_empty() {
+            return listeners;
+        }
+
         let mut listeners = Vec::new();
 
         // Debug server
@@ -196,7 +294,10 @@ impl<T> State<T> {
             let debug_port = debug_server
                 .get_u16("port")
                 .expect("the `debug.port` field should be an integer");
-            listeners.push((ServerTag::Debug, (debug_host, debug_port).into()));
+            listeners.push(ServerListener::new(
+                ServerTag::Debug,
+                (debug_host, debug_port).into(),
+            ));
         }
 
         // Main server
@@ -208,7 +309,10 @@ impl<T> State<T> {
             let main_port = main_server
                 .get_u16("port")
                 .expect("the `main.port` field should be an integer");
-            listeners.push((ServerTag::Main, (main_host, main_port).into()));
+            listeners.push(ServerListener::new(
+                ServerTag::Main,
+                (main_host, main_port).into(),
+            ));
         }
 
         // Standbys
@@ -225,24 +329,62 @@ impl<T> State<T> {
                 let standby_port = standby
                     .get_u16("port")
                     .expect("the `standby.port` field should be an integer");
-                listeners.push((server_tag.into(), (standby_host, standby_port).into()));
+                listeners.push(ServerListener::new(
+                    server_tag.into(),
+                    (standby_host, standby_port).into(),
+                ));
             }
         }
 
         // Ensure that there is at least one listener
         if listeners.is_empty() {
-            listeners.push((ServerTag::Main, (Ipv4Addr::LOCALHOST, 6080).into()));
+            listeners.push(ServerListener::new(
+                ServerTag::Main,
+                (Ipv4Addr::LOCALHOST, 6080).into(),
+            ));
         }
 
         listeners
     }
+
+    /// Returns a reference to the config corresponding to the `key` for the listener scope.
+    ///
+    /// The table specified in the `[[server.listener]]` with the same scope takes precedence,
+    /// otherwise it falls back to the global config. It can be used to resolve
+    /// the middleware config such as `cors`, `auth` and `rate-limit` per scope.
+    pub fn get_scoped_config(&self, scope: &ServerTag, key: &str) -> Option<&Table> {
+        let scoped_config = self
+            .config()
+            .get_table("server")
+            .and_then(|t| t.get_array("listener"))
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_table())
+            .filter(|t| &ServerTag::from(t.get_str("scope").unwrap_or("public")) == scope)
+            .find_map(|t| t.get_table(key));
+        scoped_config.or_else(|| self.get_config(key))
+    }
 }
 
 impl State {
     /// Returns a reference to the shared state.
     #[inline]
     pub fn shared() -> &'static Self {
-        &SHARED_STATE
+        *SHARED_STATE.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Reloads the config and swaps the shared state atomically.
+    ///
+    /// The previous state is not deallocated since there may be references to it,
+    /// so it should only be used for infrequent reloading such as on `SIGHUP`.
+    /// The values which have been derived from the previous state are not affected.
+    pub fn reload_shared() -> &'static Self {
+        let mut state = State::default();
+        state.load_config();
+
+        let state: &'static Self = Box::leak(Box::new(state));
+        *SHARED_STATE.write().unwrap_or_else(PoisonError::into_inner) = state;
+        state
     }
 
     /// Encrypts the password in the config.
@@ -262,6 +404,13 @@ impl State {
     /// Decrypts the password in the config.
     pub fn decrypt_password(config: &Table) -> Option<Cow<'_, str>> {
         let password = config.get_str("password")?;
+        if config
+            .get("password")
+            .and_then(|v| v.as_str())
+            .is_some_and(|s| s.starts_with(SECRET_PREFIX))
+        {
+            return Some(password.into());
+        }
         if let Ok(data) = base64::decode(password) {
             if let Some(key) = application::SECRET_KEY.get() {
                 if let Ok(plaintext) = crypto::decrypt(&data, key) {
@@ -308,6 +457,33 @@ impl State {
     }
 }
 
+/// Global access to the config of the shared state.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GlobalConfig;
+
+impl GlobalConfig {
+    /// Deserializes the config corresponding to the `key` into a user-defined type.
+    /// See [`State::get_config_as()`] for the details.
+    #[inline]
+    pub fn get_as<T: DeserializeOwned>(key: &str) -> Result<T, Error> {
+        State::shared().get_config_as(key)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for State<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut config = self.config.clone();
+        if let Some(secrets) = config.get_mut("secrets") {
+            *secrets = "[REDACTED]".into();
+        }
+        f.debug_struct("State")
+            .field("env", &self.env)
+            .field("config", &config)
+            .field("data", &self.data)
+            .finish()
+    }
+}
+
 impl<T: Default> Default for State<T> {
     #[inline]
     fn default() -> Self {
@@ -335,8 +511,8 @@ static DEFAULT_ENV: LazyLock<Env> = LazyLock::new(|| {
 });
 
 /// Shared application state.
-static SHARED_STATE: LazyLock<State> = LazyLock::new(|| {
+static SHARED_STATE: LazyLock<RwLock<&'static State>> = LazyLock::new(|| {
     let mut state = State::default();
     state.load_config();
-    state
+    RwLock::new(Box::leak(Box::new(state)))
 });
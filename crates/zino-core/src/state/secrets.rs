@@ -0,0 +1,293 @@
+use crate::{
+    application::{Agent, Application},
+    bail, crypto,
+    encoding::base64,
+    error::Error,
+    extension::TomlTableExt,
+    warn,
+};
+use rand::Rng;
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+use toml::{value::Table, Value};
+
+/// Prefix of the string values which reference a secret.
+pub(crate) const SECRET_PREFIX: &str = "secret:";
+
+/// Plaintext used to check the master key.
+const KEY_CHECK_PLAINTEXT: &[u8] = b"zino-secrets";
+
+/// Application secrets sealed in an encrypted file with a master key.
+///
+/// The master key is read from the `ZINO_SECRETS_KEY` environment variable,
+/// or the file specified by `ZINO_SECRETS_KEY_FILE` which defaults to
+/// `secrets.key` in the config directory. The secret names are stored in plaintext,
+/// while the values are encrypted with a key derived from the master key.
+///
+/// The secrets are decrypted at boot and merged into the config under the `secrets` table.
+/// A string value in the form `"secret:{name}"` in the config is resolved
+/// to the secret value by the config getters.
+///
+/// # Examples
+///
+/// ```toml
+/// # config/config.dev.toml
+///
+/// [postgres]
+/// host = "127.0.0.1"
+/// username = "postgres"
+/// password = "secret:database.password"
+/// ```
+#[derive(Default, Clone)]
+pub struct Secrets {
+    /// Secret values.
+    entries: BTreeMap<String, String>,
+}
+
+impl Secrets {
+    /// Creates a new instance.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the default path of the sealed secrets file.
+    #[inline]
+    pub fn default_path() -> PathBuf {
+        Agent::config_dir().join("secrets.enc.toml")
+    }
+
+    /// Reads the master key from the environment variables or the key file
+    /// in the config directory.
+    pub fn read_master_key(config_dir: &Path) -> Result<String, Error> {
+        if let Ok(master_key) = std::env::var("ZINO_SECRETS_KEY") {
+            return Ok(master_key);
+        }
+
+        let key_file = std::env::var("ZINO_SECRETS_KEY_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| config_dir.join("secrets.key"));
+        match fs::read_to_string(&key_file) {
+            Ok(master_key) => Ok(master_key.trim().to_owned()),
+            Err(err) => bail!(
+                "fail to read the master key from `ZINO_SECRETS_KEY` or `{}`: {}",
+                key_file.display(),
+                err
+            ),
+        }
+    }
+
+    /// Generates a random master key.
+    pub fn generate_master_key() -> String {
+        let mut rng = rand::rng();
+        let mut bytes = [0u8; 32];
+        rng.fill(&mut bytes);
+        base64::encode(bytes)
+    }
+
+    /// Returns the secret names in the sealed file without decrypting the values.
+    pub fn list_names(path: &Path) -> Result<Vec<String>, Error> {
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let table = fs::read_to_string(path)?.parse::<Table>()?;
+        let names = table
+            .get_table("secrets")
+            .map(|secrets| secrets.keys().cloned().collect())
+            .unwrap_or_default();
+        Ok(names)
+    }
+
+    /// Opens the sealed file with the master key.
+    /// It returns an empty instance if the file does not exist.
+    pub fn open(path: &Path, master_key: &str) -> Result<Self, Error> {
+        let mut secrets = Self::new();
+        if !path.exists() {
+            return Ok(secrets);
+        }
+
+        let table = fs::read_to_string(path)?.parse::<Table>()?;
+        let key = derive_key(master_key);
+        let key_check = table
+            .get_str("key-check")
+            .ok_or_else(|| warn!("the `key-check` field is missing in `{}`", path.display()))?;
+        let key_matched = base64::decode(key_check)
+            .ok()
+            .and_then(|data| crypto::decrypt(&data, &key).ok())
+            .is_some_and(|plaintext| plaintext == KEY_CHECK_PLAINTEXT);
+        if !key_matched {
+            bail!(
+                "the master key is wrong: it does not match the key used to seal `{}`",
+                path.display()
+            );
+        }
+        if let Some(entries) = table.get_table("secrets") {
+            for (name, value) in entries {
+                let data = value
+                    .as_str()
+                    .and_then(|s| base64::decode(s).ok())
+                    .ok_or_else(|| warn!("invalid encoding for the secret `{}`", name))?;
+                let plaintext = crypto::decrypt(&data, &key)
+                    .map_err(|_| warn!("fail to decrypt the secret `{}`", name))?;
+                let value = String::from_utf8(plaintext)
+                    .map_err(|_| warn!("the secret `{}` is not valid UTF-8", name))?;
+                secrets.entries.insert(name.to_owned(), value);
+            }
+        }
+        Ok(secrets)
+    }
+
+    /// Seals the secrets into the file with the master key.
+    pub fn seal(&self, path: &Path, master_key: &str) -> Result<(), Error> {
+        let key = derive_key(master_key);
+        let key_check = crypto::encrypt(KEY_CHECK_PLAINTEXT, &key)?;
+        let key_check = base64::encode(key_check);
+
+        // The file is formatted manually since the TOML serializer is not enabled.
+        // A JSON string is also a valid TOML basic string.
+        let mut content = format!("key-check = \"{key_check}\"\n\n[secrets]\n");
+        for (name, value) in self.entries.iter() {
+            let data = crypto::encrypt(value.as_bytes(), &key)?;
+            let name = serde_json::to_string(name)?;
+            let value = base64::encode(data);
+            content.push_str(&format!("{name} = \"{value}\"\n"));
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Sets a secret value for the name.
+    #[inline]
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.entries.insert(name.into(), value.into());
+    }
+
+    /// Removes a secret and returns `true` if it exists.
+    #[inline]
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.entries.remove(name).is_some()
+    }
+
+    /// Returns the secret value for the name.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries.get(name).map(|s| s.as_str())
+    }
+
+    /// Returns an iterator over the secret names.
+    #[inline]
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(|s| s.as_str())
+    }
+
+    /// Converts the secrets into a table, with the dotted names as nested tables.
+    pub fn to_table(&self) -> Table {
+        let mut table = Table::new();
+        for (name, value) in self.entries.iter() {
+            let mut segments = name.split('.').collect::<Vec<_>>();
+            let Some(key) = segments.pop() else {
+                continue;
+            };
+
+            let mut current = &mut table;
+            for segment in segments {
+                let entry = current
+                    .entry(segment)
+                    .or_insert_with(|| Value::Table(Table::new()));
+                if !entry.is_table() {
+                    *entry = Value::Table(Table::new());
+                }
+                let Value::Table(next) = entry else {
+                    unreachable!();
+                };
+                current = next;
+            }
+            current.insert(key.to_owned(), value.to_owned().into());
+        }
+        table
+    }
+}
+
+impl fmt::Debug for Secrets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.names()).finish()
+    }
+}
+
+/// Loads the secrets if the sealed file exists in the config directory.
+///
+/// # Panics
+///
+/// It panics if the secrets can not be unsealed, such as a wrong master key.
+pub(super) fn load_secrets(config_dir: &Path) -> Option<&'static Secrets> {
+    let path = config_dir.join("secrets.enc.toml");
+    if !path.exists() {
+        return None;
+    }
+
+    let secrets = Secrets::read_master_key(config_dir)
+        .and_then(|master_key| Secrets::open(&path, &master_key))
+        .unwrap_or_else(|err| panic!("fail to unseal the secrets `{}`: {err}", path.display()));
+    tracing::info!("`{}` unsealed", path.display());
+    Some(SECRETS.get_or_init(|| secrets))
+}
+
+/// Resolves the value of a secret reference.
+pub(crate) fn resolve_secret(name: &str) -> Option<&'static str> {
+    let value = SECRETS.get().and_then(|secrets| secrets.get(name));
+    if value.is_none() {
+        tracing::warn!("secret `{name}` can not be resolved");
+    }
+    value
+}
+
+/// Derives the encryption key from the master key.
+#[inline]
+fn derive_key(master_key: &str) -> [u8; 32] {
+    crypto::digest(master_key.as_bytes())
+}
+
+/// Unsealed secrets.
+static SECRETS: OnceLock<Secrets> = OnceLock::new();
+
+#[cfg(test)]
+mod tests {
+    use super::Secrets;
+
+    #[test]
+    fn it_seals_secrets() {
+        let path = std::env::temp_dir().join("zino-secrets.enc.toml");
+        let master_key = Secrets::generate_master_key();
+        let mut secrets = Secrets::new();
+        secrets.set("database.password", "p@ssw0rd");
+        secrets.set("jwt.secret", "s3cret");
+        secrets.seal(&path, &master_key).unwrap();
+
+        let names = Secrets::list_names(&path).unwrap();
+        assert_eq!(names, ["database.password", "jwt.secret"]);
+        assert!(Secrets::open(&path, "wrong key").is_err());
+
+        let secrets = Secrets::open(&path, &master_key).unwrap();
+        assert_eq!(secrets.get("database.password"), Some("p@ssw0rd"));
+        assert_eq!(
+            format!("{secrets:?}"),
+            r#"{"database.password", "jwt.secret"}"#
+        );
+
+        let table = secrets.to_table();
+        let database = table.get("database").and_then(|v| v.as_table()).unwrap();
+        assert_eq!(
+            database.get("password").and_then(|v| v.as_str()),
+            Some("p@ssw0rd")
+        );
+        std::fs::remove_file(path).ok();
+    }
+}
@@ -0,0 +1,214 @@
+use crate::{datetime, error::Error, warn};
+use serde::de::{self, Deserialize, DeserializeOwned, Deserializer};
+use std::{fmt, time::Duration};
+use toml::{value::Table, Value};
+
+/// Deserializes the config value corresponding to the `key` as `T`.
+/// The key can be a dotted path such as `server.tls`.
+///
+/// If the value is absent, it will be deserialized from an empty table,
+/// so that the `#[serde(default)]` attributes take effect.
+pub(super) fn deserialize_config<T: DeserializeOwned>(
+    config: &Table,
+    key: &str,
+) -> Result<T, Error> {
+    let mut table = config;
+    let mut value = None;
+    let mut path = String::new();
+    let mut segments = key.split('.').peekable();
+    while let Some(segment) = segments.next() {
+        if !path.is_empty() {
+            path.push('.');
+        }
+        path.push_str(segment);
+        match table.get(segment) {
+            Some(Value::Table(t)) => {
+                table = t;
+                value = Some(Value::Table(t.clone()));
+            }
+            Some(v) if segments.peek().is_none() => value = Some(v.clone()),
+            Some(v) => {
+                return Err(warn!(
+                    "config `{}` should be a table, found {}",
+                    path,
+                    v.type_str()
+                ));
+            }
+            None => {
+                value = None;
+                break;
+            }
+        }
+    }
+
+    let value = value.unwrap_or_else(|| Value::Table(Table::new()));
+    serde_path_to_error::deserialize(value).map_err(|err| {
+        let path = err.path().to_string();
+        let path = if path == "." {
+            key.to_owned()
+        } else {
+            format!("{key}.{path}")
+        };
+        warn!("invalid config `{}`: {}", path, err.into_inner())
+    })
+}
+
+/// Deserializes a [`Duration`] from a string such as `30s` and `5m`,
+/// or an integer in seconds.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use serde::Deserialize;
+/// use std::time::Duration;
+///
+/// #[derive(Deserialize)]
+/// struct Settings {
+///     #[serde(deserialize_with = "zino_core::state::deserialize_duration")]
+///     timeout: Duration,
+/// }
+/// ```
+pub fn deserialize_duration<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Duration, D::Error> {
+    struct DurationVisitor;
+
+    impl de::Visitor<'_> for DurationVisitor {
+        type Value = Duration;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a duration string like `30s` or an integer in seconds")
+        }
+
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+            Ok(Duration::from_secs(value))
+        }
+
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+            u64::try_from(value)
+                .map(Duration::from_secs)
+                .map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self))
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            datetime::parse_duration(value)
+                .map_err(|err| E::custom(format!("invalid duration `{value}`: {err}")))
+        }
+    }
+
+    deserializer.deserialize_any(DurationVisitor)
+}
+
+/// Deserializes a byte size from a string such as `512KB` and `10MB`, or an integer in bytes.
+/// See [`parse_byte_size()`] for the supported units.
+pub fn deserialize_byte_size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum ByteSize {
+        Integer(u64),
+        String(String),
+    }
+
+    match ByteSize::deserialize(deserializer)? {
+        ByteSize::Integer(size) => Ok(size),
+        ByteSize::String(s) => parse_byte_size(&s).ok_or_else(|| {
+            de::Error::custom(format!(
+                "invalid byte size `{s}`, expected a string like `10MB` or an integer in bytes"
+            ))
+        }),
+    }
+}
+
+/// Parses a byte size from a string.
+///
+/// The input string is specified as a number followed by an optional unit:
+///
+/// - `B` - bytes
+/// - `KB` or `KiB` - 1024 bytes
+/// - `MB` or `MiB` - 1024 KB
+/// - `GB` or `GiB` - 1024 MB
+/// - `TB` or `TiB` - 1024 GB
+///
+/// The units are case-insensitive and the number can be fractional.
+pub fn parse_byte_size(input: &str) -> Option<u64> {
+    let input = input.trim();
+    let index = input
+        .find(|ch: char| !(ch.is_ascii_digit() || ch == '.'))
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(index);
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        "t" | "tb" | "tib" => 1 << 40,
+        _ => return None,
+    };
+    if let Ok(number) = number.parse::<u64>() {
+        number.checked_mul(multiplier)
+    } else {
+        let number = number.parse::<f64>().ok()?;
+        let size = number * multiplier as f64;
+        (size.is_finite() && size >= 0.0 && size < u64::MAX as f64).then_some(size as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{deserialize_config, parse_byte_size};
+    use serde::Deserialize;
+    use std::time::Duration;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    struct TlsSettings {
+        cert_file: String,
+        #[serde(default)]
+        enabled: bool,
+        #[serde(default, deserialize_with = "super::deserialize_duration")]
+        handshake_timeout: Duration,
+        #[serde(default, deserialize_with = "super::deserialize_byte_size")]
+        max_fragment_size: u64,
+    }
+
+    #[test]
+    fn it_deserializes_config() {
+        let config = r#"
+            [server.tls]
+            cert-file = "./private/cert.pem"
+            handshake-timeout = "1m30s"
+            max-fragment-size = "16KB"
+
+            [database]
+            port = "5432x"
+        "#
+        .parse::<toml::Table>()
+        .unwrap();
+        let tls = deserialize_config::<TlsSettings>(&config, "server.tls").unwrap();
+        assert_eq!(tls.cert_file, "./private/cert.pem");
+        assert!(!tls.enabled);
+        assert_eq!(tls.handshake_timeout, Duration::from_secs(90));
+        assert_eq!(tls.max_fragment_size, 16 * 1024);
+
+        #[derive(Debug, Deserialize)]
+        struct DatabaseSettings {
+            #[allow(dead_code)]
+            port: u16,
+        }
+        let err = deserialize_config::<DatabaseSettings>(&config, "database").unwrap_err();
+        assert!(err.to_string().contains("`database.port`"));
+
+        let err = deserialize_config::<TlsSettings>(&config, "server.http").unwrap_err();
+        assert!(err.to_string().contains("missing field `cert-file`"));
+    }
+
+    #[test]
+    fn it_parses_byte_sizes() {
+        assert_eq!(parse_byte_size("1024"), Some(1024));
+        assert_eq!(parse_byte_size("10MB"), Some(10 * 1024 * 1024));
+        assert_eq!(parse_byte_size("1.5 KiB"), Some(1536));
+        assert_eq!(parse_byte_size("2gb"), Some(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_byte_size("10XB"), None);
+        assert_eq!(parse_byte_size("MB"), None);
+    }
+}
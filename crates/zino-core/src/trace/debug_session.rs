@@ -0,0 +1,387 @@
+use crate::{
+    datetime::DateTime, error::Error, extension::TomlTableExt, state::State, warn, LazyLock, Map,
+};
+use ahash::HashMap;
+use arc_swap::ArcSwap;
+use std::{fmt, time::Duration};
+
+/// Kind of the debug session targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DebugTarget {
+    /// The session ID in the `x-session-id` header.
+    SessionId,
+    /// The user ID in the `x-user-id` header.
+    UserId,
+    /// The token in the `x-debug-token` header.
+    DebugToken,
+}
+
+impl DebugTarget {
+    /// Returns the target kind as a `str`.
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SessionId => "session_id",
+            Self::UserId => "user_id",
+            Self::DebugToken => "debug_token",
+        }
+    }
+}
+
+impl fmt::Display for DebugTarget {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for DebugTarget {
+    type Error = Error;
+
+    fn try_from(kind: &str) -> Result<Self, Self::Error> {
+        match kind {
+            "session_id" => Ok(Self::SessionId),
+            "user_id" => Ok(Self::UserId),
+            "debug_token" => Ok(Self::DebugToken),
+            _ => Err(warn!("invalid debug target `{}`", kind)),
+        }
+    }
+}
+
+/// A debug session for tracing the requests of specific sessions, users or tokens
+/// at an elevated level without turning on the debug logging globally.
+///
+/// The request span is recorded with `debug_session = true` when any of the `x-session-id`,
+/// `x-user-id` and `x-debug-token` header values matches an entry,
+/// so that the child spans (including the ORM query spans) inherit the elevated level,
+/// and the trace is always sampled by the OTLP exporter.
+///
+/// Every entry expires after a TTL to avoid forgotten firehoses.
+/// The entries can be defined in the `[tracing.debug-sessions]` table, which expire
+/// after the TTL since startup, or be added and removed at runtime:
+///
+/// ```toml
+/// [tracing.debug-sessions]
+/// level = "debug"
+/// ttl = "30m"
+/// session-ids = ["0193c4a0-e9c9-7ef2-9e6c-7a3b4d5e6f70"]
+/// user-ids = ["alice"]
+/// debug-tokens = ["ticket-1024"]
+/// ```
+///
+/// The lookup loads a small table which is swapped atomically,
+/// so the hot path does not acquire any locks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugSession {
+    /// Target kind.
+    target: DebugTarget,
+    /// Target value.
+    value: String,
+    /// Expiration time.
+    expires_at: DateTime,
+    /// Source of the entry.
+    source: &'static str,
+}
+
+impl DebugSession {
+    /// Creates a new instance which expires after the TTL.
+    #[inline]
+    pub fn new(target: DebugTarget, value: impl Into<String>, ttl: Duration) -> Self {
+        Self {
+            target,
+            value: value.into(),
+            expires_at: DateTime::now() + ttl,
+            source: "runtime",
+        }
+    }
+
+    /// Returns the target kind.
+    #[inline]
+    pub fn target(&self) -> DebugTarget {
+        self.target
+    }
+
+    /// Returns the target value.
+    #[inline]
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns the expiration time.
+    #[inline]
+    pub fn expires_at(&self) -> DateTime {
+        self.expires_at
+    }
+
+    /// Returns `true` if the entry has expired.
+    #[inline]
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= DateTime::now()
+    }
+
+    /// Converts `self` to a JSON object.
+    /// The value of a debug token is masked.
+    pub fn to_map(&self) -> Map {
+        let value = if self.target == DebugTarget::DebugToken {
+            mask_token(&self.value)
+        } else {
+            self.value.clone()
+        };
+        let mut map = Map::new();
+        map.insert("target".to_owned(), self.target.as_str().into());
+        map.insert("value".to_owned(), value.into());
+        map.insert("expires_at".to_owned(), self.expires_at.to_string().into());
+        map.insert("source".to_owned(), self.source.into());
+        map
+    }
+
+    /// Returns the default TTL of the entries.
+    #[inline]
+    pub fn default_ttl() -> Duration {
+        SHARED_DEBUG_SESSION_CONFIG.ttl
+    }
+
+    /// Returns the tracing level of the debug sessions.
+    #[inline]
+    pub fn level() -> &'static str {
+        SHARED_DEBUG_SESSION_CONFIG.level
+    }
+
+    /// Returns `true` if any of the session ID, user ID and debug token matches
+    /// an entry which has not expired.
+    pub fn matches(
+        session_id: Option<&str>,
+        user_id: Option<&str>,
+        debug_token: Option<&str>,
+    ) -> bool {
+        let table = DEBUG_SESSIONS.load();
+        if table.is_empty() {
+            return false;
+        }
+        [
+            (DebugTarget::SessionId, session_id),
+            (DebugTarget::UserId, user_id),
+            (DebugTarget::DebugToken, debug_token),
+        ]
+        .into_iter()
+        .any(|(target, value)| {
+            value
+                .and_then(|value| table.get(target, value))
+                .is_some_and(|expires_at| expires_at > DateTime::now())
+        })
+    }
+
+    /// Lists the entries which have not expired.
+    pub fn list() -> Vec<Map> {
+        let table = DEBUG_SESSIONS.load();
+        let mut sessions = table
+            .iter()
+            .filter(|session| !session.is_expired())
+            .collect::<Vec<_>>();
+        sessions.sort_by_key(|session| session.expires_at);
+        sessions
+            .into_iter()
+            .map(|session| session.to_map())
+            .collect()
+    }
+
+    /// Adds an entry, replacing the existing one with the same target.
+    /// The expired entries are removed at the same time.
+    pub fn add(session: DebugSession) {
+        let target = session.target;
+        let expires_at = session.expires_at;
+        DEBUG_SESSIONS.rcu(|table| {
+            let mut table = table.retain_active();
+            table.insert(session.clone());
+            table
+        });
+        tracing::warn!(
+            debug_target = target.as_str(),
+            expires_at = expires_at.to_string(),
+            "debug session has been added"
+        );
+    }
+
+    /// Removes an entry and returns `true` if it exists.
+    /// The expired entries are removed at the same time.
+    pub fn remove(target: DebugTarget, value: &str) -> bool {
+        let previous = DEBUG_SESSIONS.rcu(|table| {
+            let mut table = table.retain_active();
+            table.entries_mut(target).remove(value);
+            table
+        });
+        let removed = previous
+            .get(target, value)
+            .is_some_and(|expires_at| expires_at > DateTime::now());
+        if removed {
+            tracing::warn!(
+                debug_target = target.as_str(),
+                "debug session has been removed"
+            );
+        }
+        removed
+    }
+}
+
+/// Table of the debug sessions.
+#[derive(Debug, Default)]
+struct DebugSessionTable {
+    /// Entries keyed by the session ID.
+    session_ids: HashMap<String, DebugSession>,
+    /// Entries keyed by the user ID.
+    user_ids: HashMap<String, DebugSession>,
+    /// Entries keyed by the debug token.
+    debug_tokens: HashMap<String, DebugSession>,
+}
+
+impl DebugSessionTable {
+    /// Returns `true` if there are no entries.
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.session_ids.is_empty() && self.user_ids.is_empty() && self.debug_tokens.is_empty()
+    }
+
+    /// Returns the entries for the target kind.
+    #[inline]
+    fn entries(&self, target: DebugTarget) -> &HashMap<String, DebugSession> {
+        match target {
+            DebugTarget::SessionId => &self.session_ids,
+            DebugTarget::UserId => &self.user_ids,
+            DebugTarget::DebugToken => &self.debug_tokens,
+        }
+    }
+
+    /// Returns the mutable entries for the target kind.
+    #[inline]
+    fn entries_mut(&mut self, target: DebugTarget) -> &mut HashMap<String, DebugSession> {
+        match target {
+            DebugTarget::SessionId => &mut self.session_ids,
+            DebugTarget::UserId => &mut self.user_ids,
+            DebugTarget::DebugToken => &mut self.debug_tokens,
+        }
+    }
+
+    /// Returns an iterator over all the entries.
+    #[inline]
+    fn iter(&self) -> impl Iterator<Item = &DebugSession> {
+        self.session_ids
+            .values()
+            .chain(self.user_ids.values())
+            .chain(self.debug_tokens.values())
+    }
+
+    /// Returns the expiration time of the entry.
+    #[inline]
+    fn get(&self, target: DebugTarget, value: &str) -> Option<DateTime> {
+        self.entries(target)
+            .get(value)
+            .map(|session| session.expires_at)
+    }
+
+    /// Inserts an entry.
+    #[inline]
+    fn insert(&mut self, session: DebugSession) {
+        let value = session.value.clone();
+        self.entries_mut(session.target).insert(value, session);
+    }
+
+    /// Returns a new table with the entries which have not expired.
+    fn retain_active(&self) -> Self {
+        let mut table = Self::default();
+        for session in self.iter().filter(|session| !session.is_expired()) {
+            table.insert(session.clone());
+        }
+        table
+    }
+}
+
+/// Masks a token except for the first and last characters.
+fn mask_token(token: &str) -> String {
+    let chars = token.chars().collect::<Vec<_>>();
+    match chars.as_slice() {
+        [first, .., last] if chars.len() > 4 => format!("{first}***{last}"),
+        _ => "***".to_owned(),
+    }
+}
+
+/// Debug session config.
+#[derive(Debug)]
+struct DebugSessionConfig {
+    /// Tracing level.
+    level: &'static str,
+    /// Default TTL of the entries.
+    ttl: Duration,
+}
+
+/// Shared debug session config.
+static SHARED_DEBUG_SESSION_CONFIG: LazyLock<DebugSessionConfig> = LazyLock::new(|| {
+    let config = State::shared()
+        .get_config("tracing")
+        .and_then(|config| config.get_table("debug-sessions"));
+    DebugSessionConfig {
+        level: config
+            .and_then(|config| config.get_str("level"))
+            .unwrap_or("debug"),
+        ttl: config
+            .and_then(|config| config.get_duration("ttl"))
+            .unwrap_or_else(|| Duration::from_secs(30 * 60)),
+    }
+});
+
+/// Shared debug sessions, initialized with the entries in the config.
+static DEBUG_SESSIONS: LazyLock<ArcSwap<DebugSessionTable>> = LazyLock::new(|| {
+    let mut table = DebugSessionTable::default();
+    if let Some(config) = State::shared()
+        .get_config("tracing")
+        .and_then(|config| config.get_table("debug-sessions"))
+    {
+        let ttl = DebugSession::default_ttl();
+        for (target, key) in [
+            (DebugTarget::SessionId, "session-ids"),
+            (DebugTarget::UserId, "user-ids"),
+            (DebugTarget::DebugToken, "debug-tokens"),
+        ] {
+            for value in config.get_str_array(key).unwrap_or_default() {
+                let mut session = DebugSession::new(target, value, ttl);
+                session.source = "config";
+                table.insert(session);
+            }
+        }
+    }
+    ArcSwap::from_pointee(table)
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{mask_token, DebugSession, DebugTarget};
+    use std::time::Duration;
+
+    #[test]
+    fn it_matches_debug_sessions() {
+        let ttl = Duration::from_secs(60);
+        DebugSession::add(DebugSession::new(DebugTarget::UserId, "alice", ttl));
+        DebugSession::add(DebugSession::new(
+            DebugTarget::DebugToken,
+            "ticket-1024",
+            ttl,
+        ));
+        assert!(DebugSession::matches(None, Some("alice"), None));
+        assert!(DebugSession::matches(Some("s1"), None, Some("ticket-1024")));
+        assert!(!DebugSession::matches(Some("alice"), Some("bob"), None));
+
+        DebugSession::add(DebugSession::new(
+            DebugTarget::UserId,
+            "bob",
+            Duration::ZERO,
+        ));
+        assert!(!DebugSession::matches(None, Some("bob"), None));
+        assert!(DebugSession::remove(DebugTarget::UserId, "alice"));
+        assert!(!DebugSession::remove(DebugTarget::UserId, "bob"));
+        assert!(!DebugSession::matches(None, Some("alice"), None));
+        assert_eq!(DebugSession::list().len(), 1);
+
+        assert_eq!(mask_token("ticket-1024"), "t***4");
+        assert_eq!(mask_token("abc"), "***");
+    }
+}
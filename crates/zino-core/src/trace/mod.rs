@@ -1,7 +1,13 @@
 //! HTTP headers for trace coontext.
 
+#[cfg(feature = "debug-session")]
+mod debug_session;
+mod task_context;
 mod trace_context;
 mod trace_state;
 
+#[cfg(feature = "debug-session")]
+pub use debug_session::{DebugSession, DebugTarget};
+pub use task_context::TaskContext;
 pub use trace_context::TraceContext;
 pub use trace_state::TraceState;
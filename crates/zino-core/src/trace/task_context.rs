@@ -0,0 +1,118 @@
+use super::TraceContext;
+use crate::Uuid;
+use std::future::Future;
+use tracing::{field::Empty, Span};
+
+tokio::task_local! {
+    /// Task-local context of a request-response lifecycle.
+    static TASK_CONTEXT: TaskContext;
+}
+
+/// Identifiers of a request-response lifecycle which are propagated to the spawned tasks.
+///
+/// Only the identifiers are captured, so the request data is not kept alive by the tasks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskContext {
+    /// Request ID.
+    request_id: Uuid,
+    /// Trace ID.
+    trace_id: Uuid,
+    /// Span ID of the request.
+    parent_id: Option<u64>,
+    /// Trace flags.
+    trace_flags: u8,
+}
+
+impl TaskContext {
+    /// Creates a new instance with the request ID and the trace context.
+    pub fn new(request_id: Uuid, trace_context: &TraceContext) -> Self {
+        Self {
+            request_id,
+            trace_id: Uuid::from_u128(trace_context.trace_id()),
+            parent_id: Some(trace_context.span_id()),
+            trace_flags: trace_context.trace_flags(),
+        }
+    }
+
+    /// Returns the context of the current task if it is executed in a scope.
+    #[inline]
+    pub fn current() -> Option<Self> {
+        TASK_CONTEXT.try_with(|ctx| *ctx).ok()
+    }
+
+    /// Executes the future in the scope of the context.
+    #[inline]
+    pub fn scope<F: Future>(self, future: F) -> impl Future<Output = F::Output> {
+        TASK_CONTEXT.scope(self, future)
+    }
+
+    /// Returns the request ID.
+    #[inline]
+    pub fn request_id(&self) -> Uuid {
+        self.request_id
+    }
+
+    /// Returns the trace ID.
+    #[inline]
+    pub fn trace_id(&self) -> Uuid {
+        self.trace_id
+    }
+
+    /// Returns the span ID of the request.
+    #[inline]
+    pub fn parent_id(&self) -> Option<u64> {
+        self.parent_id
+    }
+
+    /// Constructs a child trace context for the outgoing requests in the task.
+    #[inline]
+    pub fn trace_context(&self) -> TraceContext {
+        TraceContext::with_parent(self.trace_id.as_u128(), self.parent_id, self.trace_flags)
+    }
+
+    /// Creates a root span which carries the identifiers of the request.
+    /// It does not refer to the request span, since the task may outlive the request.
+    pub fn span(&self) -> Span {
+        let span = tracing::info_span!(
+            parent: None,
+            "spawned task",
+            "otel.kind" = "internal",
+            "context.trace_id" = self.trace_id.to_string(),
+            "context.request_id" = self.request_id.to_string(),
+            "context.span_id" = Empty,
+            "context.parent_id" = self.parent_id.map(|parent_id| format!("{parent_id:x}")),
+        );
+        span.record(
+            "context.span_id",
+            span.id().map(|id| format!("{:x}", id.into_u64())),
+        );
+
+        #[cfg(feature = "otel")]
+        self.trace_context().link_span(&span);
+
+        span
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TaskContext, TraceContext};
+    use crate::Uuid;
+
+    #[test]
+    fn it_scopes_task_context() {
+        let runtime = tokio::runtime::Builder::new_multi_thread().build().unwrap();
+        let trace_context = TraceContext::new();
+        let ctx = TaskContext::new(Uuid::now_v7(), &trace_context);
+        assert_eq!(TaskContext::current(), None);
+        assert_eq!(
+            runtime.block_on(ctx.scope(async { TaskContext::current() })),
+            Some(ctx),
+        );
+        assert_eq!(ctx.trace_context().trace_id(), trace_context.trace_id(),);
+        assert_eq!(
+            ctx.trace_context().parent_id(),
+            Some(trace_context.span_id())
+        );
+    }
+}
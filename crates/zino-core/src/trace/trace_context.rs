@@ -34,14 +34,11 @@ pub struct TraceContext {
 impl TraceContext {
     /// Creates a new instance without parent.
     pub fn new() -> Self {
-        let span_id = Span::current()
-            .id()
-            .map(|id| id.into_u64())
-            .unwrap_or_else(rand::random);
+        let span_id = current_span_id();
         Self {
             span_id,
             version: 0,
-            trace_id: Uuid::now_v7().as_u128(),
+            trace_id: current_trace_id(),
             parent_id: None,
             trace_flags: FLAG_SAMPLED | FLAG_RANDOM_TRACE_ID,
             trace_state: TraceState::new(),
@@ -50,10 +47,7 @@ impl TraceContext {
 
     /// Creates a new instance with the specific `trace-id`.
     pub fn with_trace_id(trace_id: Uuid) -> Self {
-        let span_id = Span::current()
-            .id()
-            .map(|id| id.into_u64())
-            .unwrap_or_else(rand::random);
+        let span_id = current_span_id();
         Self {
             span_id,
             version: 0,
@@ -66,10 +60,7 @@ impl TraceContext {
 
     /// Creates a child of the current trace context.
     pub fn child(&self) -> Self {
-        let span_id = Span::current()
-            .id()
-            .map(|id| id.into_u64())
-            .unwrap_or_else(rand::random);
+        let span_id = current_span_id();
         Self {
             span_id,
             version: self.version,
@@ -80,12 +71,22 @@ impl TraceContext {
         }
     }
 
+    /// Creates a new instance with the `trace-id`, `parent-id` and `trace-flags`.
+    pub(super) fn with_parent(trace_id: u128, parent_id: Option<u64>, trace_flags: u8) -> Self {
+        let span_id = current_span_id();
+        Self {
+            span_id,
+            version: 0,
+            trace_id,
+            parent_id,
+            trace_flags,
+            trace_state: TraceState::new(),
+        }
+    }
+
     /// Constructs an instance from the `traceparent` header value.
     pub fn from_traceparent(traceparent: &str) -> Option<Self> {
-        let span_id = Span::current()
-            .id()
-            .map(|id| id.into_u64())
-            .unwrap_or_else(rand::random);
+        let span_id = current_span_id();
         let parts = traceparent.split('-').collect::<Vec<_>>();
         (parts.len() == 4).then_some(Self {
             span_id,
@@ -107,6 +108,34 @@ impl TraceContext {
         Some(trace_context)
     }
 
+    /// Sets the remote parent of the span to the `parent-id` of the trace context,
+    /// so that the exported spans are connected with the caller's trace.
+    /// It is a no-op if there is no `parent-id`.
+    #[cfg(feature = "otel")]
+    pub fn link_span(&self, span: &Span) {
+        use opentelemetry::trace::{
+            SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState as OtelTraceState,
+        };
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        if let Some(parent_id) = self.parent_id {
+            let trace_flags = if self.sampled() {
+                TraceFlags::SAMPLED
+            } else {
+                TraceFlags::default()
+            };
+            let span_context = SpanContext::new(
+                TraceId::from(self.trace_id),
+                SpanId::from(parent_id),
+                trace_flags,
+                true,
+                OtelTraceState::default(),
+            );
+            let context = opentelemetry::Context::new().with_remote_span_context(span_context);
+            span.set_parent(context);
+        }
+    }
+
     /// Returns the `span-id`.
     #[inline]
     pub fn span_id(&self) -> u64 {
@@ -191,6 +220,41 @@ impl TraceContext {
     }
 }
 
+/// Returns the trace ID of the current span or generates a new one.
+fn current_trace_id() -> u128 {
+    #[cfg(feature = "otel")]
+    {
+        use opentelemetry::trace::TraceContextExt;
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        let context = Span::current().context();
+        let span_context = context.span().span_context().clone();
+        if span_context.is_valid() {
+            return u128::from_be_bytes(span_context.trace_id().to_bytes());
+        }
+    }
+    Uuid::now_v7().as_u128()
+}
+
+/// Returns the span ID of the current span.
+fn current_span_id() -> u64 {
+    #[cfg(feature = "otel")]
+    {
+        use opentelemetry::trace::TraceContextExt;
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        let context = Span::current().context();
+        let span_context = context.span().span_context().clone();
+        if span_context.is_valid() {
+            return u64::from_be_bytes(span_context.span_id().to_bytes());
+        }
+    }
+    Span::current()
+        .id()
+        .map(|id| id.into_u64())
+        .unwrap_or_else(rand::random)
+}
+
 impl Default for TraceContext {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,345 @@
+use crate::{datetime::DateTime, JsonValue, Uuid};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    error, fmt,
+    str::FromStr,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A Universally Unique Lexicographically Sortable Identifier (ULID).
+/// See [the spec](https://github.com/ulid/spec).
+///
+/// A ULID consists of a 48-bit timestamp in milliseconds since the Unix epoch
+/// and 80 bits of randomness. It is encoded as 26 characters of Crockford's base32,
+/// so the text representation is lexicographically sortable and fits in a `CHAR(26)` column.
+///
+/// The IDs generated by [`Ulid::new()`] are strictly monotonic within a process.
+/// If the timestamp does not advance, the random part of the last ID is incremented,
+/// and the timestamp is advanced logically when the random part overflows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ulid(u128);
+
+impl Ulid {
+    /// Length of the text representation.
+    pub const LENGTH: usize = 26;
+
+    /// Generates a new ULID.
+    #[inline]
+    pub fn new() -> Self {
+        SHARED_GENERATOR.generate(current_millis())
+    }
+
+    /// Returns the nil ULID with all bits set to zero.
+    #[inline]
+    pub const fn nil() -> Self {
+        Self(0)
+    }
+
+    /// Creates a new instance from the timestamp in milliseconds and the random bits.
+    /// The bits exceeding 48 for the timestamp or 80 for the randomness are truncated.
+    #[inline]
+    pub const fn from_parts(timestamp_ms: u64, random: u128) -> Self {
+        let timestamp = (timestamp_ms as u128) & TIMESTAMP_MASK;
+        Self((timestamp << RANDOM_BITS) | (random & RANDOM_MASK))
+    }
+
+    /// Creates a new instance from a 128-bit value.
+    #[inline]
+    pub const fn from_u128(value: u128) -> Self {
+        Self(value)
+    }
+
+    /// Creates a new instance from the big-endian bytes.
+    #[inline]
+    pub const fn from_bytes(bytes: [u8; 16]) -> Self {
+        Self(u128::from_be_bytes(bytes))
+    }
+
+    /// Returns the 128-bit value.
+    #[inline]
+    pub const fn as_u128(&self) -> u128 {
+        self.0
+    }
+
+    /// Returns the big-endian bytes.
+    #[inline]
+    pub const fn to_bytes(&self) -> [u8; 16] {
+        self.0.to_be_bytes()
+    }
+
+    /// Returns `true` if all the bits are zero.
+    #[inline]
+    pub const fn is_nil(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns the timestamp in milliseconds since the Unix epoch.
+    #[inline]
+    pub const fn timestamp_ms(&self) -> u64 {
+        (self.0 >> RANDOM_BITS) as u64
+    }
+
+    /// Returns the random bits.
+    #[inline]
+    pub const fn random(&self) -> u128 {
+        self.0 & RANDOM_MASK
+    }
+
+    /// Returns the embedded timestamp as `DateTime`.
+    #[inline]
+    pub fn datetime(&self) -> DateTime {
+        DateTime::from_timestamp_millis(self.timestamp_ms() as i64)
+    }
+
+    /// Converts `self` into a UUID with the same 128 bits.
+    #[inline]
+    pub const fn to_uuid(&self) -> Uuid {
+        Uuid::from_u128(self.0)
+    }
+
+    /// Encodes `self` as Crockford's base32 characters.
+    pub fn encode(&self) -> [u8; Self::LENGTH] {
+        let mut chars = [0; Self::LENGTH];
+        let mut value = self.0;
+        for c in chars.iter_mut().rev() {
+            *c = ALPHABET[(value & 0x1f) as usize];
+            value >>= 5;
+        }
+        chars
+    }
+}
+
+impl fmt::Display for Ulid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chars = self.encode();
+        let s = std::str::from_utf8(&chars).map_err(|_| fmt::Error)?;
+        f.write_str(s)
+    }
+}
+
+impl FromStr for Ulid {
+    type Err = ParseUlidError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != Self::LENGTH {
+            return Err(ParseUlidError::InvalidLength(s.len()));
+        }
+
+        let mut value = 0u128;
+        for (index, c) in s.chars().enumerate() {
+            let digit = decode_char(c).ok_or(ParseUlidError::InvalidChar(c, index))?;
+            if index == 0 && digit > 7 {
+                return Err(ParseUlidError::Overflow);
+            }
+            value = (value << 5) | u128::from(digit);
+        }
+        Ok(Self(value))
+    }
+}
+
+impl From<Uuid> for Ulid {
+    #[inline]
+    fn from(uuid: Uuid) -> Self {
+        Self(uuid.as_u128())
+    }
+}
+
+impl From<Ulid> for Uuid {
+    #[inline]
+    fn from(ulid: Ulid) -> Self {
+        ulid.to_uuid()
+    }
+}
+
+impl From<Ulid> for JsonValue {
+    #[inline]
+    fn from(ulid: Ulid) -> Self {
+        ulid.to_string().into()
+    }
+}
+
+impl Serialize for Ulid {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Ulid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <&str>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl<DB> sqlx::Type<DB> for Ulid
+where
+    DB: sqlx::Database,
+    String: sqlx::Type<DB>,
+{
+    #[inline]
+    fn type_info() -> <DB as sqlx::Database>::TypeInfo {
+        <String as sqlx::Type<DB>>::type_info()
+    }
+
+    #[inline]
+    fn compatible(ty: &<DB as sqlx::Database>::TypeInfo) -> bool {
+        <String as sqlx::Type<DB>>::compatible(ty)
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl<'r, DB> sqlx::Decode<'r, DB> for Ulid
+where
+    DB: sqlx::Database,
+    &'r str: sqlx::Decode<'r, DB>,
+{
+    #[inline]
+    fn decode(value: <DB as sqlx::Database>::ValueRef<'r>) -> Result<Self, crate::BoxError> {
+        let s = <&str as sqlx::Decode<'r, DB>>::decode(value)?;
+        s.trim_end()
+            .parse()
+            .map_err(|err: ParseUlidError| err.into())
+    }
+}
+
+/// An error which can be returned when parsing a ULID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseUlidError {
+    /// The length is not 26.
+    InvalidLength(usize),
+    /// An invalid character at the index.
+    InvalidChar(char, usize),
+    /// The value exceeds 128 bits.
+    Overflow,
+}
+
+impl fmt::Display for ParseUlidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength(len) => {
+                write!(f, "invalid ULID length: expected 26, found {len}")
+            }
+            Self::InvalidChar(c, index) => {
+                write!(f, "invalid ULID character `{c}` at index {index}")
+            }
+            Self::Overflow => write!(f, "the ULID exceeds 128 bits"),
+        }
+    }
+}
+
+impl error::Error for ParseUlidError {}
+
+/// A generator with the last ULID.
+#[derive(Debug, Default)]
+struct UlidGenerator {
+    /// Last ULID.
+    last: Mutex<u128>,
+}
+
+impl UlidGenerator {
+    /// Generates a new ULID with the current timestamp in milliseconds.
+    fn generate(&self, now: u64) -> Ulid {
+        let mut last = self.last.lock().unwrap_or_else(|err| err.into_inner());
+        let last_ulid = Ulid(*last);
+        let last_timestamp = last_ulid.timestamp_ms();
+        let ulid = if now > last_timestamp {
+            Ulid::from_parts(now, rand::random())
+        } else if last_ulid.random() < RANDOM_MASK {
+            Ulid(last_ulid.0 + 1)
+        } else {
+            Ulid::from_parts(last_timestamp + 1, rand::random())
+        };
+        *last = ulid.0;
+        ulid
+    }
+}
+
+/// Decodes a character of Crockford's base32, which is case-insensitive
+/// and maps `I`, `L` to `1` and `O` to `0`.
+fn decode_char(c: char) -> Option<u8> {
+    let digit = match c.to_ascii_uppercase() {
+        c @ '0'..='9' => c as u8 - b'0',
+        'O' => 0,
+        'I' | 'L' => 1,
+        c @ 'A'..='Z' => {
+            let index = ALPHABET.iter().position(|&b| b == c as u8)?;
+            index as u8
+        }
+        _ => return None,
+    };
+    Some(digit)
+}
+
+/// Returns the milliseconds since the Unix epoch.
+fn current_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Crockford's base32 alphabet.
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Number of bits for the randomness.
+const RANDOM_BITS: u32 = 80;
+
+/// Mask of the timestamp.
+const TIMESTAMP_MASK: u128 = (1 << 48) - 1;
+
+/// Mask of the randomness.
+const RANDOM_MASK: u128 = (1 << RANDOM_BITS) - 1;
+
+/// Shared generator.
+static SHARED_GENERATOR: UlidGenerator = UlidGenerator {
+    last: Mutex::new(0),
+};
+
+#[cfg(test)]
+mod tests {
+    use super::{ParseUlidError, Ulid, UlidGenerator, RANDOM_MASK};
+    use crate::Uuid;
+
+    #[test]
+    fn it_encodes_and_parses_ulids() {
+        let ulid = "01ARZ3NDEKTSV4RRFFQ69G5FAV".parse::<Ulid>().unwrap();
+        assert_eq!(ulid.to_string(), "01ARZ3NDEKTSV4RRFFQ69G5FAV");
+        assert_eq!(ulid.timestamp_ms(), 1_469_922_850_259);
+        assert_eq!("01arz3ndektsv4rrffq69g5fav".parse::<Ulid>(), Ok(ulid));
+        assert_eq!(Ulid::from(ulid.to_uuid()), ulid);
+        assert_eq!(Uuid::from(ulid).as_u128(), ulid.as_u128());
+        assert_eq!(
+            "8ZZZZZZZZZZZZZZZZZZZZZZZZZ".parse::<Ulid>(),
+            Err(ParseUlidError::Overflow)
+        );
+        assert_eq!(
+            "01ARZ3NDEKTSV4RRFFQ69G5FAU".parse::<Ulid>(),
+            Err(ParseUlidError::InvalidChar('U', 25))
+        );
+        assert_eq!(Ulid::nil().to_string(), "00000000000000000000000000");
+    }
+
+    #[test]
+    fn it_generates_monotonic_ulids() {
+        let generator = UlidGenerator::default();
+        let first_ulid = generator.generate(1_000);
+        let mut last_ulid = first_ulid;
+        for _ in 0..1_000 {
+            let ulid = generator.generate(1_000);
+            assert!(ulid > last_ulid);
+            assert!(ulid.to_string() > last_ulid.to_string());
+            last_ulid = ulid;
+        }
+        assert_eq!(last_ulid.timestamp_ms(), 1_000);
+
+        let rollback_ulid = generator.generate(900);
+        assert!(rollback_ulid > last_ulid);
+
+        *generator.last.lock().unwrap() = Ulid::from_parts(2_000, RANDOM_MASK).as_u128();
+        let overflow_ulid = generator.generate(2_000);
+        assert_eq!(overflow_ulid.timestamp_ms(), 2_001);
+    }
+}
@@ -1,10 +1,13 @@
 //! Generic validator and common validation rules.
-use crate::{error::Error, extension::JsonObjectExt, Map, SharedString};
+use crate::{error::Error, extension::JsonObjectExt, JsonValue, Map, SharedString};
 use smallvec::SmallVec;
 use std::fmt;
 
+mod normalized;
 mod validator;
 
+pub use normalized::WebUrl;
+
 pub use validator::{
     AlphabeticValidator, AlphanumericValidator, AsciiAlphabeticValidator,
     AsciiAlphanumericValidator, AsciiDigitValidator, AsciiHexdigitValidator,
@@ -14,6 +17,11 @@ pub use validator::{
     UriValidator, UuidValidator, Validator,
 };
 
+#[cfg(feature = "validator-email")]
+pub use normalized::EmailAddress;
+#[cfg(feature = "validator-phone-number")]
+pub use normalized::PhoneNumber;
+
 #[cfg(feature = "validator-credit-card")]
 pub use validator::CreditCardValidator;
 #[cfg(feature = "validator-email")]
@@ -24,6 +32,9 @@ pub use validator::PhoneNumberValidator;
 pub use validator::RegexValidator;
 
 /// A record of validation results.
+///
+/// The entries are keyed by the field paths such as `address.city` or `items[2].quantity`,
+/// and a field can have multiple failed entries.
 #[derive(Debug, Default)]
 pub struct Validation {
     failed_entries: SmallVec<[(SharedString, Error); 4]>,
@@ -48,6 +59,47 @@ impl Validation {
         }
     }
 
+    /// Creates a new instance with the entries of the child validation
+    /// nested under the path prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use zino_core::validation::Validation;
+    ///
+    /// let mut address = Validation::new();
+    /// address.record("city", "should be nonempty");
+    ///
+    /// let validation = Validation::nested("address", address);
+    /// assert!(validation.contains_key("address.city"));
+    /// ```
+    pub fn nested(prefix: &str, inner: Validation) -> Self {
+        let failed_entries = inner
+            .failed_entries
+            .into_iter()
+            .map(|(key, err)| (join_field_path(prefix, &key).into(), err))
+            .collect();
+        Self { failed_entries }
+    }
+
+    /// Creates a new instance by validating each element of the array with the function `f`.
+    /// The failed entries are nested under the path prefix `{field}[{index}]`.
+    pub fn for_each_array<T>(
+        field: &str,
+        items: &[T],
+        mut f: impl FnMut(&T) -> Validation,
+    ) -> Self {
+        let mut validation = Self::new();
+        for (index, item) in items.iter().enumerate() {
+            let inner = f(item);
+            if !inner.is_success() {
+                let prefix = format!("{field}[{index}]");
+                validation.extend(Self::nested(&prefix, inner));
+            }
+        }
+        validation
+    }
+
     /// Records an entry with the supplied message.
     #[inline]
     pub fn record(&mut self, key: impl Into<SharedString>, message: impl Into<SharedString>) {
@@ -60,6 +112,12 @@ impl Validation {
         self.failed_entries.push((key.into(), err.into()));
     }
 
+    /// Appends the entries of another validation.
+    #[inline]
+    pub fn extend(&mut self, other: Validation) {
+        self.failed_entries.extend(other.failed_entries);
+    }
+
     /// Validates the string value with a specific format.
     pub fn validate_format(&mut self, key: impl Into<SharedString>, value: &str, format: &str) {
         match format {
@@ -210,6 +268,16 @@ impl Validation {
         self.failed_entries.iter().any(|(field, _)| field == key)
     }
 
+    /// Returns a list of messages for the specified key.
+    #[inline]
+    pub fn messages(&self, key: &str) -> Vec<&str> {
+        self.failed_entries
+            .iter()
+            .filter(|(field, _)| field == key)
+            .map(|(_, err)| err.message())
+            .collect()
+    }
+
     /// Returns `true` if the validation is success.
     #[inline]
     pub fn is_success(&self) -> bool {
@@ -226,13 +294,23 @@ impl Validation {
     }
 
     /// Consumes the validation and returns as a json object.
+    /// The messages are grouped as an array if there are multiple entries for a field.
     #[must_use]
     pub fn into_map(self) -> Map {
         let mut map = Map::new();
         for (key, err) in self.failed_entries {
             let message = err.message();
             tracing::warn!("invalid value for `{key}`: {message}");
-            map.upsert(key, message);
+            match map.get_mut(&*key) {
+                Some(JsonValue::Array(messages)) => messages.push(message.into()),
+                Some(value) => {
+                    let messages = vec![value.take(), message.into()];
+                    *value = messages.into();
+                }
+                None => {
+                    map.upsert(key, message);
+                }
+            }
         }
         map
     }
@@ -250,3 +328,57 @@ impl fmt::Display for Validation {
         write!(f, "{}", errors.join(","))
     }
 }
+
+/// Joins the field path with the prefix.
+fn join_field_path(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_owned()
+    } else if key.is_empty() {
+        prefix.to_owned()
+    } else if key.starts_with('[') {
+        format!("{prefix}{key}")
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Validation;
+    use crate::{extension::JsonObjectExt, JsonValue};
+
+    #[test]
+    fn it_nests_validation_entries() {
+        let mut address = Validation::new();
+        address.record("city", "should be nonempty");
+        address.record("zip", "should be numeric");
+        address.record("zip", "should have 6 digits");
+
+        let quantities = [1, 0, 3, -1];
+        let items = Validation::for_each_array("items", &quantities, |&quantity| {
+            let mut validation = Validation::new();
+            if quantity <= 0 {
+                validation.record("quantity", "should be positive");
+            }
+            validation
+        });
+
+        let mut validation = Validation::nested("address", address);
+        validation.extend(items);
+        assert!(validation.contains_key("address.city"));
+        assert!(validation.contains_key("items[1].quantity"));
+        assert!(validation.contains_key("items[3].quantity"));
+        assert!(!validation.contains_key("items[2].quantity"));
+        assert_eq!(validation.messages("address.zip").len(), 2);
+
+        let map = validation.into_map();
+        assert_eq!(map.get_str("address.city"), Some("should be nonempty"));
+        assert_eq!(
+            map.get("address.zip"),
+            Some(&JsonValue::from(vec![
+                "should be numeric",
+                "should have 6 digits"
+            ]))
+        );
+    }
+}
@@ -0,0 +1,314 @@
+//! String wrappers with validation and normalization.
+
+use crate::error::Error;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{borrow::Borrow, fmt, ops::Deref, str::FromStr};
+use url::Url;
+
+#[cfg(feature = "validator-email")]
+use super::{EmailValidator, Validator};
+
+/// Implements the common traits for a string wrapper.
+macro_rules! impl_normalized_string {
+    ($($Ty:ty),+ $(,)?) => {
+        $(
+            impl $Ty {
+                /// Returns the normalized value as a string slice.
+                #[inline]
+                pub fn as_str(&self) -> &str {
+                    &self.0
+                }
+
+                /// Returns `true` if the value is empty.
+                #[inline]
+                pub fn is_empty(&self) -> bool {
+                    self.0.is_empty()
+                }
+
+                /// Consumes `self` and returns the normalized value.
+                #[inline]
+                pub fn into_string(self) -> String {
+                    self.0
+                }
+            }
+
+            impl fmt::Display for $Ty {
+                #[inline]
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str(&self.0)
+                }
+            }
+
+            impl AsRef<str> for $Ty {
+                #[inline]
+                fn as_ref(&self) -> &str {
+                    &self.0
+                }
+            }
+
+            impl Borrow<str> for $Ty {
+                #[inline]
+                fn borrow(&self) -> &str {
+                    &self.0
+                }
+            }
+
+            impl Deref for $Ty {
+                type Target = str;
+
+                #[inline]
+                fn deref(&self) -> &Self::Target {
+                    &self.0
+                }
+            }
+
+            impl From<$Ty> for String {
+                #[inline]
+                fn from(value: $Ty) -> Self {
+                    value.0
+                }
+            }
+
+            impl From<$Ty> for crate::JsonValue {
+                #[inline]
+                fn from(value: $Ty) -> Self {
+                    value.0.into()
+                }
+            }
+
+            impl TryFrom<String> for $Ty {
+                type Error = Error;
+
+                #[inline]
+                fn try_from(value: String) -> Result<Self, Self::Error> {
+                    value.parse()
+                }
+            }
+
+            impl Serialize for $Ty {
+                #[inline]
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.serialize_str(&self.0)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $Ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let value = String::deserialize(deserializer)?;
+                    if value.is_empty() {
+                        Ok(Self::default())
+                    } else {
+                        value.parse().map_err(de::Error::custom)
+                    }
+                }
+            }
+
+            #[cfg(feature = "sqlx")]
+            impl<DB> sqlx::Type<DB> for $Ty
+            where
+                DB: sqlx::Database,
+                String: sqlx::Type<DB>,
+            {
+                #[inline]
+                fn type_info() -> <DB as sqlx::Database>::TypeInfo {
+                    <String as sqlx::Type<DB>>::type_info()
+                }
+
+                #[inline]
+                fn compatible(ty: &<DB as sqlx::Database>::TypeInfo) -> bool {
+                    <String as sqlx::Type<DB>>::compatible(ty)
+                }
+            }
+
+            #[cfg(feature = "sqlx")]
+            impl<'r, DB> sqlx::Decode<'r, DB> for $Ty
+            where
+                DB: sqlx::Database,
+                String: sqlx::Decode<'r, DB>,
+            {
+                fn decode(
+                    value: <DB as sqlx::Database>::ValueRef<'r>,
+                ) -> Result<Self, crate::BoxError> {
+                    let value = <String as sqlx::Decode<'r, DB>>::decode(value)?;
+                    if value.is_empty() {
+                        Ok(Self::default())
+                    } else {
+                        value.parse().map_err(|err: Error| err.to_string().into())
+                    }
+                }
+            }
+
+            #[cfg(feature = "sqlx")]
+            impl<'q, DB> sqlx::Encode<'q, DB> for $Ty
+            where
+                DB: sqlx::Database,
+                String: sqlx::Encode<'q, DB>,
+            {
+                #[inline]
+                fn encode_by_ref(
+                    &self,
+                    buf: &mut <DB as sqlx::Database>::ArgumentBuffer<'q>,
+                ) -> Result<sqlx::encode::IsNull, crate::BoxError> {
+                    <String as sqlx::Encode<'q, DB>>::encode_by_ref(&self.0, buf)
+                }
+            }
+        )+
+    };
+}
+
+/// A normalized email address.
+///
+/// The value is trimmed and lowercased when parsing, since the email addresses are treated
+/// case-insensitively, so `Foo@Bar.com ` and `foo@bar.com` are the same address.
+#[cfg(feature = "validator-email")]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EmailAddress(String);
+
+#[cfg(feature = "validator-email")]
+impl EmailAddress {
+    /// Returns the user part of the email address.
+    #[inline]
+    pub fn user(&self) -> &str {
+        self.0
+            .split_once('@')
+            .map(|(user, _)| user)
+            .unwrap_or_default()
+    }
+
+    /// Returns the domain part of the email address.
+    #[inline]
+    pub fn domain(&self) -> &str {
+        self.0
+            .split_once('@')
+            .map(|(_, domain)| domain)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "validator-email")]
+impl FromStr for EmailAddress {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let email = s.trim().to_lowercase();
+        EmailValidator
+            .validate(email.as_str())
+            .map_err(|err| Error::new(format!("invalid email address `{s}`: {err}")))?;
+        Ok(Self(email))
+    }
+}
+
+/// A phone number normalized in the E.164 format.
+///
+/// A number without the leading `+` is parsed with the default country code,
+/// which can be configured by `default-country-code` in the `[validation]` table.
+#[cfg(feature = "validator-phone-number")]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PhoneNumber(String);
+
+#[cfg(feature = "validator-phone-number")]
+impl FromStr for PhoneNumber {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use phonenumber::Mode;
+
+        let number = phonenumber::parse(*DEFAULT_COUNTRY_CODE, s.trim())
+            .map_err(|err| Error::new(format!("invalid phone number `{s}`: {err}")))?;
+        if !number.is_valid() {
+            return Err(Error::new(format!("invalid phone number `{s}`")));
+        }
+        Ok(Self(number.format().mode(Mode::E164).to_string()))
+    }
+}
+
+/// A normalized URL with the `http` or `https` scheme.
+///
+/// The value is trimmed when parsing, and the scheme and the host are lowercased.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WebUrl(String);
+
+impl WebUrl {
+    /// Returns the parsed URL.
+    #[inline]
+    pub fn to_url(&self) -> Option<Url> {
+        Url::parse(&self.0).ok()
+    }
+}
+
+impl FromStr for WebUrl {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let url =
+            Url::parse(s.trim()).map_err(|err| Error::new(format!("invalid URL `{s}`: {err}")))?;
+        if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+            return Err(Error::new(format!("invalid web URL `{s}`")));
+        }
+        Ok(Self(url.into()))
+    }
+}
+
+impl From<Url> for WebUrl {
+    #[inline]
+    fn from(url: Url) -> Self {
+        Self(url.into())
+    }
+}
+
+impl_normalized_string!(WebUrl);
+
+#[cfg(feature = "validator-email")]
+impl_normalized_string!(EmailAddress);
+
+#[cfg(feature = "validator-phone-number")]
+impl_normalized_string!(PhoneNumber);
+
+/// Default country code for parsing the phone numbers.
+#[cfg(feature = "validator-phone-number")]
+static DEFAULT_COUNTRY_CODE: crate::LazyLock<Option<phonenumber::country::Id>> =
+    crate::LazyLock::new(default_country_code);
+
+/// Reads the default country code from the `[validation]` config.
+#[cfg(all(feature = "validator-phone-number", feature = "runtime"))]
+fn default_country_code() -> Option<phonenumber::country::Id> {
+    use crate::{extension::TomlTableExt, state::State};
+
+    let country_code = State::shared()
+        .get_config("validation")?
+        .get_str("default-country-code")?;
+    let id = country_code.to_uppercase().parse().ok();
+    if id.is_none() {
+        tracing::warn!("invalid default country code `{country_code}`");
+    }
+    id
+}
+
+/// Reads the default country code from the `[validation]` config.
+#[cfg(all(feature = "validator-phone-number", not(feature = "runtime")))]
+fn default_country_code() -> Option<phonenumber::country::Id> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WebUrl;
+
+    #[test]
+    fn it_normalizes_strings() {
+        #[cfg(feature = "validator-email")]
+        {
+            use super::EmailAddress;
+
+            let email = " Foo@Bar.com ".parse::<EmailAddress>().unwrap();
+            assert_eq!(email, "foo@bar.com".parse::<EmailAddress>().unwrap());
+            assert_eq!(email.domain(), "bar.com");
+            assert!("foo.bar.com".parse::<EmailAddress>().is_err());
+        }
+
+        let url = " HTTPS://Example.COM/path ".parse::<WebUrl>().unwrap();
+        assert_eq!(url.as_str(), "https://example.com/path");
+        assert!("ftp://example.com".parse::<WebUrl>().is_err());
+        assert!("example".parse::<WebUrl>().is_err());
+    }
+}
@@ -24,14 +24,29 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
     // Parsing field attributes
     let mut decode_model_fields = Vec::new();
     for field in parser::parse_struct_fields(input.data) {
+        if parser::check_flatten_field(&field) {
+            let field_type = &field.ty;
+            if let Some(ident) = field.ident.as_ref() {
+                decode_model_fields.push(quote! {
+                    model.#ident = <#field_type as zino_orm::DecodeRow<zino_orm::DatabaseRow>>::decode_row(row)?;
+                });
+            }
+            continue;
+        }
+
         let type_name = parser::get_type_name(&field.ty);
+        let money_json_layout =
+            parser::check_money_type(&type_name).then(|| parser::check_money_json_layout(&field));
         if let Some(ident) = field.ident {
             let name = ident.to_string();
             let mut ignore = false;
             'inner: for attr in field.attrs.iter() {
                 let arguments = parser::parse_schema_attr(attr);
                 for (key, _value) in arguments.iter() {
-                    if key == "ignore" || key == "write_only" {
+                    if matches!(
+                        key.as_str(),
+                        "ignore" | "write_only" | "private" | "computed" | "computed_async"
+                    ) {
                         ignore = true;
                         break 'inner;
                     }
@@ -40,7 +55,38 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
             if ignore {
                 continue;
             }
-            if type_name == "Uuid" {
+            if let Some(json_layout) = money_json_layout {
+                let value = if parser::check_option_type(&type_name) {
+                    quote! { Some(value) }
+                } else {
+                    quote! { value }
+                };
+                let field_decoder = if json_layout {
+                    quote! {
+                        if let Some(value) = zino_orm::decode_optional::<JsonValue>(row, #name)?
+                            .filter(|value| !value.is_null())
+                        {
+                            let value = zino_core::money::Money::try_from(&value)?;
+                            model.#ident = #value;
+                        }
+                    }
+                } else {
+                    quote! {
+                        if let Some(value) = zino_orm::decode_money(row, #name)? {
+                            model.#ident = #value;
+                        }
+                    }
+                };
+                decode_model_fields.push(field_decoder);
+            } else if parser::check_attachment_type(&type_name) {
+                decode_model_fields.push(quote! {
+                    if let Some(value) = zino_orm::decode_optional::<JsonValue>(row, #name)?
+                        .filter(|value| !value.is_null())
+                    {
+                        model.#ident = value.deserialize()?;
+                    }
+                });
+            } else if type_name == "Uuid" {
                 decode_model_fields.push(quote! {
                     model.#ident = zino_orm::decode_uuid(row, #name)?;
                 });
@@ -49,6 +95,15 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                     let value = zino_orm::decode_uuid(row, #name)?;
                     model.#ident = (!value.is_nil()).then_some(value);
                 });
+            } else if type_name == "Ulid" {
+                decode_model_fields.push(quote! {
+                    model.#ident = zino_orm::decode_ulid(row, #name)?;
+                });
+            } else if type_name == "Option<Ulid>" {
+                decode_model_fields.push(quote! {
+                    let value = zino_orm::decode_ulid(row, #name)?;
+                    model.#ident = (!value.is_nil()).then_some(value);
+                });
             } else if type_name == "Decimal" {
                 decode_model_fields.push(quote! {
                     model.#ident = zino_orm::decode_decimal(row, #name)?;
@@ -0,0 +1,293 @@
+use super::{parser, schema};
+use convert_case::{Case, Casing};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::DeriveInput;
+
+/// Parses the token stream for the `EmbeddedSchema` trait derivation.
+pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
+    // Model name
+    let name = input.ident;
+    let model_name = name.to_string();
+
+    // Parsing field attributes
+    let fields = parser::parse_struct_fields(input.data);
+    let mut column_methods = Vec::new();
+    let mut snapshot_fields = Vec::new();
+    let mut snapshot_entries = Vec::new();
+    let mut list_query_methods = Vec::new();
+    let mut soft_delete_updates = Vec::new();
+    let mut lock_updates = Vec::new();
+    let mut archive_updates = Vec::new();
+    for field in fields.iter() {
+        let type_name = parser::get_type_name(&field.ty);
+        let Some(ident) = field.ident.as_ref() else {
+            continue;
+        };
+        let arguments = field
+            .attrs
+            .iter()
+            .flat_map(parser::parse_schema_attr)
+            .collect::<Vec<_>>();
+        if arguments.iter().any(|(key, _)| key == "ignore") {
+            continue;
+        }
+
+        let field_name = ident.to_string();
+        if type_name == "DateTime" && arguments.iter().any(|(key, _)| key == "expires_at") {
+            list_query_methods.push(quote! {
+                query.add_filter(#field_name, Map::from_entry("$gt", "now"));
+            });
+        }
+        let mut snapshot_field = false;
+        match field_name.as_str() {
+            "name" if type_name == "String" => {
+                column_methods.push(quote! {
+                    #[inline]
+                    fn name(&self) -> Option<&str> {
+                        Some(self.#ident.as_ref())
+                    }
+                });
+                snapshot_field = true;
+            }
+            "status" if type_name == "String" => {
+                column_methods.push(quote! {
+                    #[inline]
+                    fn status(&self) -> Option<&str> {
+                        Some(self.#ident.as_ref())
+                    }
+                });
+                snapshot_field = true;
+                list_query_methods.push(quote! {
+                    query.add_filter(#field_name, Map::from_entry("$ne", "Deleted"));
+                });
+                soft_delete_updates.push(quote! {
+                    updates.upsert(#field_name, "Deleted");
+                });
+                lock_updates.push(quote! {
+                    updates.upsert(#field_name, "Locked");
+                });
+                archive_updates.push(quote! {
+                    updates.upsert(#field_name, "Archived");
+                });
+            }
+            "created_at" if type_name == "DateTime" => {
+                column_methods.push(quote! {
+                    #[inline]
+                    fn created_at(&self) -> Option<zino_core::datetime::DateTime> {
+                        Some(self.#ident)
+                    }
+                });
+            }
+            "updated_at" if type_name == "DateTime" => {
+                column_methods.push(quote! {
+                    #[inline]
+                    fn updated_at(&self) -> Option<zino_core::datetime::DateTime> {
+                        Some(self.#ident)
+                    }
+                });
+                snapshot_field = true;
+                list_query_methods.push(quote! {
+                    query.order_desc(#field_name);
+                });
+            }
+            "deleted_at" if type_name == "Option<DateTime>" => {
+                column_methods.push(quote! {
+                    #[inline]
+                    fn deleted_at(&self) -> Option<zino_core::datetime::DateTime> {
+                        self.#ident
+                    }
+                });
+                list_query_methods.push(quote! {
+                    query.add_filter(#field_name, "null");
+                });
+                soft_delete_updates.push(quote! {
+                    updates.upsert(#field_name, DateTime::now().into_sql_value());
+                });
+            }
+            "version" if type_name == "u64" => {
+                column_methods.push(quote! {
+                    #[inline]
+                    fn version(&self) -> Option<u64> {
+                        Some(self.#ident)
+                    }
+                });
+                snapshot_field = true;
+            }
+            "edition" if type_name == "u32" => {
+                column_methods.push(quote! {
+                    #[inline]
+                    fn edition(&self) -> Option<u32> {
+                        Some(self.#ident)
+                    }
+                });
+            }
+            "is_deleted" if type_name == "bool" => {
+                list_query_methods.push(quote! {
+                    query.add_filter(#field_name, false);
+                });
+                soft_delete_updates.push(quote! {
+                    updates.upsert(#field_name, true);
+                });
+            }
+            "is_locked" if type_name == "bool" => {
+                lock_updates.push(quote! {
+                    updates.upsert(#field_name, true);
+                });
+            }
+            "is_archived" if type_name == "bool" => {
+                archive_updates.push(quote! {
+                    updates.upsert(#field_name, true);
+                });
+            }
+            _ => (),
+        }
+        if snapshot_field {
+            snapshot_entries.push(quote! {
+                snapshot.upsert(#field_name, self.#ident.clone());
+            });
+            snapshot_fields.push(field_name);
+        }
+    }
+
+    let schema::SchemaColumns {
+        primary_key_type,
+        primary_key_name,
+        primary_key_column,
+        columns,
+        read_only_fields,
+        write_only_fields,
+        field_names,
+        flattened_fields,
+        ..
+    } = schema::parse_schema_columns(&model_name, fields, None);
+    if let Some((_, field)) = flattened_fields.first() {
+        return syn::Error::new_spanned(field, "nested flattened fields are not supported")
+            .to_compile_error();
+    }
+
+    // Output
+    let model_name_upper_snake = model_name.to_case(Case::UpperSnake);
+    let embedded_columns = format_ident!("{}_EMBEDDED_COLUMNS", model_name_upper_snake);
+    let num_columns = columns.len();
+    let field_names = field_names.into_iter().map(|(field, _)| field);
+    let embedded_primary_key = if let Some(primary_key_column) = primary_key_column {
+        let embedded_primary_key_column =
+            format_ident!("{}_EMBEDDED_PRIMARY_KEY_COLUMN", model_name_upper_snake);
+        let primary_key_type_ident = format_ident!("{}", primary_key_type);
+        let primary_key_ident = format_ident!("{}", primary_key_name);
+        let primary_key_value = if matches!(primary_key_type.as_str(), "Uuid" | "Ulid") {
+            quote! { self.#primary_key_ident.to_string().into() }
+        } else {
+            quote! { self.#primary_key_ident.clone().into() }
+        };
+        quote! {
+            static #embedded_primary_key_column: zino_core::LazyLock<zino_core::model::Column> =
+                zino_core::LazyLock::new(|| #primary_key_column);
+
+            impl zino_orm::EmbeddedPrimaryKey for #name {
+                type PrimaryKey = #primary_key_type_ident;
+
+                const PRIMARY_KEY_NAME: &'static str = #primary_key_name;
+
+                #[inline]
+                fn primary_key(&self) -> &Self::PrimaryKey {
+                    &self.#primary_key_ident
+                }
+
+                #[inline]
+                fn primary_key_value(&self) -> zino_core::JsonValue {
+                    #primary_key_value
+                }
+
+                #[inline]
+                fn primary_key_column() -> &'static zino_core::model::Column<'static> {
+                    &#embedded_primary_key_column
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let mut embedded_methods = Vec::new();
+    if !snapshot_entries.is_empty() {
+        embedded_methods.push(quote! {
+            fn append_snapshot(&self, snapshot: &mut zino_core::Map) {
+                use zino_core::extension::JsonObjectExt;
+
+                #(#snapshot_entries)*
+            }
+        });
+    }
+    if !list_query_methods.is_empty() {
+        embedded_methods.push(quote! {
+            fn apply_list_query(query: &mut zino_core::model::Query) {
+                use zino_core::{extension::JsonObjectExt, Map};
+
+                #(#list_query_methods)*
+            }
+        });
+    }
+    if !soft_delete_updates.is_empty() {
+        embedded_methods.push(quote! {
+            fn append_soft_delete_updates(updates: &mut zino_core::Map) {
+                use zino_core::{datetime::DateTime, extension::JsonObjectExt};
+                use zino_orm::IntoSqlValue;
+
+                #(#soft_delete_updates)*
+            }
+        });
+    }
+    if !lock_updates.is_empty() {
+        embedded_methods.push(quote! {
+            fn append_lock_updates(updates: &mut zino_core::Map) {
+                use zino_core::extension::JsonObjectExt;
+
+                #(#lock_updates)*
+            }
+        });
+    }
+    if !archive_updates.is_empty() {
+        embedded_methods.push(quote! {
+            fn append_archive_updates(updates: &mut zino_core::Map) {
+                use zino_core::extension::JsonObjectExt;
+
+                #(#archive_updates)*
+            }
+        });
+    }
+    quote! {
+        static #embedded_columns: zino_core::LazyLock<[zino_core::model::Column; #num_columns]> =
+            zino_core::LazyLock::new(|| [#(#columns),*]);
+
+        impl zino_orm::EmbeddedSchema for #name {
+            const FIELDS: &'static [&'static str] = &[#(#field_names),*];
+
+            #[inline]
+            fn columns() -> &'static [zino_core::model::Column<'static>] {
+                #embedded_columns.as_slice()
+            }
+
+            #[inline]
+            fn read_only_fields() -> &'static [&'static str] {
+                &[#(#read_only_fields),*]
+            }
+
+            #[inline]
+            fn write_only_fields() -> &'static [&'static str] {
+                &[#(#write_only_fields),*]
+            }
+
+            #[inline]
+            fn snapshot_fields() -> &'static [&'static str] {
+                &[#(#snapshot_fields),*]
+            }
+
+            #(#column_methods)*
+
+            #(#embedded_methods)*
+        }
+
+        #embedded_primary_key
+    }
+}
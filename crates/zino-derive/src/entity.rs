@@ -12,7 +12,16 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
     let mut primary_key_name = String::from("id");
     let mut model_column_variants = Vec::new();
     let mut model_column_mappings = Vec::new();
+    let mut has_flattened_fields = false;
+    let mut field_names = Vec::new();
     for field in parser::parse_struct_fields(input.data) {
+        if parser::check_flatten_field(&field) {
+            has_flattened_fields = true;
+            continue;
+        }
+        let type_name = parser::get_type_name(&field.ty);
+        let money_split_layout =
+            parser::check_money_type(&type_name) && !parser::check_money_json_layout(&field);
         if let Some(ident) = field.ident {
             let name = ident.to_string().trim_start_matches("r#").to_owned();
             let variant = format_ident!("{}", name.to_case(Case::Pascal));
@@ -34,8 +43,32 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
             model_column_mappings.push(quote! {
                 #variant => #name,
             });
+            if money_split_layout {
+                // Money values are stored as the amount and currency columns.
+                for suffix in ["amount", "currency"] {
+                    let column_name = format!("{name}_{suffix}");
+                    let column_variant = format_ident!("{}", column_name.to_case(Case::Pascal));
+                    model_column_variants.push(quote! {
+                        #column_variant,
+                    });
+                    model_column_mappings.push(quote! {
+                        #column_variant => #column_name,
+                    });
+                }
+            }
+            field_names.push(name);
         }
     }
+    if has_flattened_fields && !field_names.contains(&primary_key_name) {
+        // The primary key lives in a flattened field
+        let variant = format_ident!("{}", primary_key_name.to_case(Case::Pascal));
+        model_column_variants.push(quote! {
+            #variant,
+        });
+        model_column_mappings.push(quote! {
+            #variant => #primary_key_name,
+        });
+    }
 
     let model_column_type = format_ident!("{}Column", name);
     let primary_key_variant = format_ident!("{}", primary_key_name.to_case(Case::Pascal));
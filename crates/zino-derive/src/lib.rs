@@ -7,12 +7,14 @@ use proc_macro::TokenStream;
 use syn::{parse_macro_input, DeriveInput};
 
 mod decode_row;
+mod embedded_schema;
 mod entity;
 mod model;
 mod model_accessor;
 mod model_hooks;
 mod parser;
 mod schema;
+mod typescript;
 
 #[doc = include_str!("../docs/entity.md")]
 #[proc_macro_derive(Entity, attributes(schema))]
@@ -30,6 +32,14 @@ pub fn derive_schema(item: TokenStream) -> TokenStream {
     TokenStream::from(output)
 }
 
+#[doc = include_str!("../docs/embedded_schema.md")]
+#[proc_macro_derive(EmbeddedSchema, attributes(schema))]
+pub fn derive_embedded_schema(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    let output = embedded_schema::parse_token_stream(input);
+    TokenStream::from(output)
+}
+
 #[doc = include_str!("../docs/model_accessor.md")]
 #[proc_macro_derive(ModelAccessor, attributes(schema))]
 pub fn derive_model_accessor(item: TokenStream) -> TokenStream {
@@ -3,6 +3,7 @@ use convert_case::{Boundary, Case, Casing};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 use syn::DeriveInput;
+use zino_core::model::DefaultValue;
 
 /// Reserved fields
 const RESERVED_FIELDS: [&str; 8] = [
@@ -47,12 +48,36 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
     // Parsing field attributes
     let mut field_constructors = Vec::new();
     let mut field_setters = Vec::new();
+    let mut default_setters = Vec::new();
     for field in parser::parse_struct_fields(input.data) {
+        if parser::check_flatten_field(&field) {
+            let field_type = &field.ty;
+            if let Some(ident) = field.ident.as_ref() {
+                field_constructors.push(quote! {
+                    model.#ident = <#field_type as zino_core::model::Model>::new();
+                });
+                field_setters.push(quote! {
+                    validation.extend(zino_core::model::Model::read_map(&mut self.#ident, data));
+                });
+            }
+            continue;
+        }
+
         let type_name = parser::get_type_name(&field.ty);
         if let Some(ident) = field.ident {
             let name = ident.to_string();
             let mut enable_setter = true;
             let mut is_inherent = false;
+            let mut default_hooks = Vec::new();
+            let mut nullable_defaults = Vec::new();
+            let mut has_constructor = false;
+            let mut is_ulid_primary_key = false;
+            let is_not_null = !parser::check_option_type(&type_name)
+                || field.attrs.iter().any(|attr| {
+                    parser::parse_schema_attr(attr)
+                        .iter()
+                        .any(|(key, _)| key == "not_null" || key == "primary_key")
+                });
             for attr in field.attrs.iter() {
                 let arguments = parser::parse_schema_attr(attr);
                 for (key, value) in arguments.into_iter() {
@@ -72,6 +97,7 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                                         }
                                     };
                                     field_constructors.push(constructor);
+                                    has_constructor = true;
                                 }
                             }
                         }
@@ -81,19 +107,15 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                                     if let Some(objects) = data.get_map_array(#name) {
                                         let num_objects = objects.len();
                                         let mut models = Vec::with_capacity(num_objects);
-                                        let mut errors = Vec::new();
                                         for (index, object) in objects.iter().enumerate() {
                                             match object.read_as_model() {
                                                 Ok(model) => models.push(model),
                                                 Err(err) => {
-                                                    let message = format!("#{index}: {err}");
-                                                    errors.push(message);
+                                                    let prefix = format!("{}[{index}]", #name);
+                                                    validation.extend(zino_core::validation::Validation::nested(&prefix, err));
                                                 },
                                             }
                                         }
-                                        if !errors.is_empty() {
-                                            validation.record(#name, errors.join(";"));
-                                        }
                                         self.#ident = models;
                                     }
                                 }
@@ -102,7 +124,7 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                                     if let Some(object) = data.parse_object(#name) {
                                         match object.read_as_model() {
                                             Ok(model) => self.#ident = Some(model),
-                                            Err(err) => validation.record(#name, err.to_string()),
+                                            Err(err) => validation.extend(zino_core::validation::Validation::nested(#name, err)),
                                         }
                                     }
                                 }
@@ -112,7 +134,7 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                                         match object.read_as_model() {
                                             Ok(model) => self.#ident = model,
                                             Err(err) => {
-                                                validation.record(#name, err.to_string());
+                                                validation.extend(zino_core::validation::Validation::nested(#name, err));
                                             },
                                         }
                                     }
@@ -122,7 +144,20 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                         }
                         "default_value" => {
                             if let Some(value) = value {
-                                if let Some((type_name, type_fn)) = value.split_once("::") {
+                                if value.starts_with("fn:") {
+                                    default_hooks.push((ident.clone(), name.clone()));
+                                } else if DefaultValue::is_expression(&value) {
+                                    field_constructors.push(quote! {
+                                        if let Some(value) = zino_core::model::DefaultValue::evaluate(#value) {
+                                            if let Ok(value) = zino_core::extension::JsonValueExt::deserialize(value) {
+                                                model.#ident = value;
+                                            }
+                                        }
+                                    });
+                                    if parser::check_option_type(&type_name) {
+                                        nullable_defaults.push((ident.clone(), name.clone()));
+                                    }
+                                } else if let Some((type_name, type_fn)) = value.split_once("::") {
                                     let type_name_ident = format_ident!("{}", type_name);
                                     let type_fn_ident = format_ident!("{}", type_fn);
                                     field_constructors.push(quote! {
@@ -196,7 +231,48 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                                 }
                             }
                         }
-                        "ignore" | "read_only" | "generated" | "reserved" => {
+                        "auto"
+                            if value.as_deref() == Some("snowflake")
+                                && cfg!(feature = "runtime") =>
+                        {
+                            match type_name.as_str() {
+                                "i64" => {
+                                    field_constructors.push(quote! {
+                                        model.#ident = zino_core::model::SequenceId::next();
+                                    });
+                                }
+                                "u64" => {
+                                    field_constructors.push(quote! {
+                                        model.#ident = zino_core::model::SequenceId::next() as u64;
+                                    });
+                                }
+                                _ => {
+                                    let message = format!(
+                                        "`auto = \"snowflake\"` requires the field `{name}` \
+                                            to be `i64` or `u64`, found `{type_name}`"
+                                    );
+                                    return syn::Error::new_spanned(&field.ty, message)
+                                        .to_compile_error();
+                                }
+                            }
+                        }
+                        "auto" if value.as_deref() == Some("ulid") => {
+                            let constructor = match type_name.as_str() {
+                                "Ulid" => quote! { zino_core::Ulid::new() },
+                                "Option<Ulid>" => quote! { Some(zino_core::Ulid::new()) },
+                                "String" => quote! { zino_core::Ulid::new().to_string() },
+                                _ => continue,
+                            };
+                            field_constructors.push(quote! {
+                                model.#ident = #constructor;
+                            });
+                            has_constructor = true;
+                        }
+                        "primary_key" if type_name == "Ulid" => {
+                            is_ulid_primary_key = true;
+                        }
+                        "ignore" | "read_only" | "generated" | "reserved" | "computed"
+                        | "computed_async" => {
                             enable_setter = false;
                         }
                         "inherent" => {
@@ -206,6 +282,15 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                     }
                 }
             }
+            if parser::check_attachment_type(&type_name) {
+                // Attachments are written by `ModelAccessor::attach()` instead.
+                enable_setter = false;
+            }
+            if is_ulid_primary_key && !has_constructor {
+                field_constructors.push(quote! {
+                    model.#ident = zino_core::Ulid::new();
+                });
+            }
             if enable_setter && !RESERVED_FIELDS.contains(&name.as_str()) {
                 let setter = if type_name == "String" {
                     if is_inherent {
@@ -221,7 +306,7 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                                 }
                             }
                         }
-                    } else if name == "password" {
+                    } else if name == "password" && cfg!(feature = "orm") {
                         quote! {
                             if let Some(password) = data.parse_string(#name) {
                                 use zino_orm::ModelHelper;
@@ -250,6 +335,20 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                             self.#ident = Some(value.into_owned());
                         }
                     }
+                } else if parser::get_normalized_string_format(&type_name).is_some() {
+                    let value = if parser::check_option_type(&type_name) {
+                        quote! { Some(value) }
+                    } else {
+                        quote! { value }
+                    };
+                    quote! {
+                        if let Some(value) = data.parse_string(#name) {
+                            match value.parse() {
+                                Ok(value) => self.#ident = #value,
+                                Err(err) => validation.record_fail(#name, err),
+                            }
+                        }
+                    }
                 } else if type_name == "Map" {
                     quote! {
                         if let Some(object) = data.parse_object(#name) {
@@ -308,6 +407,33 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                 };
                 field_setters.push(setter);
             }
+            if !is_not_null {
+                for (ident, name) in nullable_defaults {
+                    field_setters.push(quote! {
+                        if data.get(#name).is_some_and(|value| value.is_null()) {
+                            self.#ident = None;
+                        }
+                    });
+                }
+            }
+            for (ident, name) in default_hooks {
+                default_setters.push(quote! {
+                    let is_omitted = data
+                        .get(#name)
+                        .map_or(true, |value| #is_not_null && value.is_null());
+                    let is_unset = zino_core::extension::JsonValueExt::is_ignorable(
+                        &zino_core::json!(self.#ident),
+                    );
+                    if is_omitted && is_unset {
+                        if let Some(value) = zino_core::model::Model::default_for(self, #name) {
+                            match zino_core::extension::JsonValueExt::deserialize(value) {
+                                Ok(value) => self.#ident = value,
+                                Err(err) => validation.record_fail(#name, err),
+                            }
+                        }
+                    }
+                });
+            }
         }
     }
 
@@ -340,6 +466,7 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                     validation.record("data", "should be nonempty");
                 } else {
                     #(#field_setters)*
+                    #(#default_setters)*
                 }
                 validation
             }
@@ -4,6 +4,13 @@ use quote::{format_ident, quote};
 use std::collections::HashMap;
 use syn::DeriveInput;
 
+/// Types whose values can be compared by `PartialEq`, including `Option<T>` and `Vec<T>`.
+const COMPARABLE_TYPES: [&str; 24] = [
+    "String", "bool", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64",
+    "i128", "isize", "f32", "f64", "Uuid", "Ulid", "Decimal", "Money", "DateTime", "Date", "Time",
+    "Map",
+];
+
 /// Parses the token stream for the `ModelAccessor` trait derivation.
 pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
     // Model name
@@ -61,31 +68,48 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
     let mut primary_key_name = String::from("id");
     let mut model_references: HashMap<String, Vec<String>> = HashMap::new();
     let mut populated_field_mappings: HashMap<String, String> = HashMap::new();
+    let mut own_fields = Vec::new();
+    let mut flattened_fields = Vec::new();
+    let mut diff_comparisons = Vec::new();
+    let mut json_diff_fields = Vec::new();
     for field in parser::parse_struct_fields(input.data) {
+        if parser::check_flatten_field(&field) {
+            if let Some(ident) = field.ident {
+                flattened_fields.push((ident, field.ty));
+            }
+            continue;
+        }
+
         let type_name = parser::get_type_name(&field.ty);
         if let Some(ident) = field.ident {
             let name = ident.to_string();
             let mut field_alias = None;
+            let mut ignore_diff = false;
             for attr in field.attrs.iter() {
                 let type_name = type_name.as_str();
                 let arguments = parser::parse_schema_attr(attr);
-                let is_readable = arguments.iter().all(|arg| arg.0 != "write_only");
+                let is_readable = arguments
+                    .iter()
+                    .all(|arg| arg.0 != "write_only" && arg.0 != "private");
                 for (key, value) in arguments.into_iter() {
                     match key.as_str() {
                         "alias" => {
                             field_alias = value;
                         }
+                        "ignore" | "ignore_diff" | "computed" | "computed_async" => {
+                            ignore_diff = true;
+                        }
                         "primary_key" => {
                             primary_key_name.clone_from(&name);
                         }
                         "snapshot" => {
                             let field = name.clone();
                             let field_ident = format_ident!("{}", field);
-                            if matches!(type_name, "Uuid" | "Decimal") {
+                            if matches!(type_name, "Uuid" | "Ulid" | "Decimal") {
                                 snapshot_entries.push(quote! {
                                     snapshot.upsert(#field, self.#field_ident.to_string());
                                 });
-                            } else if type_name == "Option<Uuid>" {
+                            } else if matches!(type_name, "Option<Uuid>" | "Option<Ulid>") {
                                 snapshot_entries.push(quote! {
                                     let snapshot_value = self.#field_ident
                                         .map(|v| v.to_string());
@@ -108,7 +132,7 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                         "reference" => {
                             if let Some(value) = value {
                                 let model_ident = format_ident!("{}", value);
-                                if type_name == "Uuid" {
+                                if matches!(type_name, "Uuid" | "Ulid") {
                                     field_constraints.push(quote! {
                                         let values = vec![self.#ident.to_string()];
                                         let data = <#model_ident>::filter(values).await?;
@@ -116,7 +140,10 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                                             validation.record(#name, "it is a nonexistent value");
                                         }
                                     });
-                                } else if matches!(type_name, "Option<Uuid>" | "Option<String>") {
+                                } else if matches!(
+                                    type_name,
+                                    "Option<Uuid>" | "Option<Ulid>" | "Option<String>"
+                                ) {
                                     field_constraints.push(quote! {
                                             if let Some(value) = self.#ident {
                                                 let values = vec![value.to_string()];
@@ -204,6 +231,11 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                                 }
                             }
                         }
+                        "expires_at" if type_name == "DateTime" => {
+                            list_query_methods.push(quote! {
+                                query.add_filter(#name, Map::from_entry("$gt", "now"));
+                            });
+                        }
                         "fetch_as" => {
                             if let Some(value) = value {
                                 let populated_field = [&name, "_populated"].concat();
@@ -211,7 +243,7 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                             }
                         }
                         "unique" => {
-                            if type_name == "Uuid" {
+                            if matches!(type_name, "Uuid" | "Ulid") {
                                 field_constraints.push(quote! {
                                         let value = self.#ident;
                                         if !value.is_nil() {
@@ -233,6 +265,23 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                                             }
                                         }
                                     });
+                            } else if parser::get_normalized_string_format(type_name).is_some() {
+                                // Normalized values are compared, so the equivalent inputs collide.
+                                let value = if parser::check_option_type(type_name) {
+                                    quote! { self.#ident.as_ref().map(|v| v.as_str()).unwrap_or_default() }
+                                } else {
+                                    quote! { self.#ident.as_str() }
+                                };
+                                field_constraints.push(quote! {
+                                        let value = #value;
+                                        if !value.is_empty() {
+                                            let columns = vec![(#name, value)];
+                                            if !self.is_unique_on(columns).await? {
+                                                let message = format!("the value `{value}` is not unique");
+                                                validation.record(#name, message);
+                                            }
+                                        }
+                                    });
                             } else if type_name == "Option<String>" {
                                 field_constraints.push(quote! {
                                         if let Some(value) = self.#ident.as_deref() && !value.is_empty() {
@@ -243,7 +292,7 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                                             }
                                         }
                                     });
-                            } else if type_name == "Option<Uuid>" {
+                            } else if matches!(type_name, "Option<Uuid>" | "Option<Ulid>") {
                                 field_constraints.push(quote! {
                                         if let Some(value) = self.#ident && !value.is_nil() {
                                             let columns = vec![(#name, value)];
@@ -281,7 +330,7 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                                         validation.record(#name, "it should be nonempty");
                                     }
                                 });
-                            } else if type_name == "Uuid" {
+                            } else if matches!(type_name, "Uuid" | "Ulid") {
                                 field_constraints.push(quote! {
                                     if self.#ident.is_nil() {
                                         validation.record(#name, "it should not be nil");
@@ -542,6 +591,23 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                     }
                 }
             }
+            if !ignore_diff {
+                // The typed values are compared if the types are known to implement `PartialEq`.
+                let inner_type_name = parser::parse_option_type(&type_name)
+                    .or_else(|| parser::parse_vec_type(&type_name))
+                    .unwrap_or(&type_name);
+                if COMPARABLE_TYPES.contains(&inner_type_name) {
+                    let field_ident = format_ident!("{}", name);
+                    diff_comparisons.push(quote! {
+                        if self.#field_ident != other.#field_ident {
+                            fields.push(#name);
+                        }
+                    });
+                } else {
+                    json_diff_fields.push(name.clone());
+                }
+            }
+            own_fields.push(field_alias.clone().unwrap_or_else(|| name.clone()));
             if primary_key_name == name {
                 primary_key_type = type_name;
             } else {
@@ -775,9 +841,124 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
     fetched_queries.push(quote! { Ok(models) });
     fetched_one_queries.push(quote! { Ok(model) });
 
+    // Flattened fields
+    let flattened_idents = flattened_fields
+        .iter()
+        .map(|(ident, _)| ident)
+        .collect::<Vec<_>>();
+    let flattened_types = flattened_fields
+        .iter()
+        .map(|(_, field_type)| field_type)
+        .collect::<Vec<_>>();
+    let embedded_accessors = [
+        ("name", quote! { &str }, quote! { .unwrap_or("") }),
+        ("status", quote! { &str }, quote! { .unwrap_or("Active") }),
+        (
+            "created_at",
+            quote! { zino_core::datetime::DateTime },
+            quote! { .unwrap_or_default() },
+        ),
+        (
+            "updated_at",
+            quote! { zino_core::datetime::DateTime },
+            quote! { .unwrap_or_default() },
+        ),
+        (
+            "deleted_at",
+            quote! { Option<zino_core::datetime::DateTime> },
+            quote! {},
+        ),
+        ("version", quote! { u64 }, quote! { .unwrap_or_default() }),
+        ("edition", quote! { u32 }, quote! { .unwrap_or_default() }),
+    ];
+    if !flattened_fields.is_empty() {
+        for (method, return_type, fallback) in embedded_accessors {
+            if !own_fields.iter().any(|field| field == method) {
+                let method_ident = format_ident!("{}", method);
+                let embedded_values = flattened_idents.iter().map(|ident| {
+                    quote! { zino_orm::EmbeddedSchema::#method_ident(&self.#ident) }
+                });
+                column_methods.push(quote! {
+                    #[inline]
+                    fn #method_ident(&self) -> #return_type {
+                        None #(.or_else(|| #embedded_values))* #fallback
+                    }
+                });
+            }
+        }
+        snapshot_entries.push(quote! {
+            #(zino_orm::EmbeddedSchema::append_snapshot(&self.#flattened_idents, &mut snapshot);)*
+        });
+        soft_delete_updates.push(quote! {
+            #(<#flattened_types as zino_orm::EmbeddedSchema>::append_soft_delete_updates(&mut updates);)*
+        });
+        lock_updates.push(quote! {
+            #(<#flattened_types as zino_orm::EmbeddedSchema>::append_lock_updates(&mut updates);)*
+        });
+        archive_updates.push(quote! {
+            #(<#flattened_types as zino_orm::EmbeddedSchema>::append_archive_updates(&mut updates);)*
+        });
+        list_query_methods.insert(
+            0,
+            quote! {
+                #(<#flattened_types as zino_orm::EmbeddedSchema>::apply_list_query(&mut query);)*
+            },
+        );
+    }
+
     // Output
-    let model_primary_key_type = format_ident!("{}", primary_key_type);
-    let model_primary_key = format_ident!("{}", primary_key_name);
+    let (model_primary_key_type, model_primary_key) = match flattened_fields.first() {
+        Some((field_ident, field_type)) if !own_fields.contains(&primary_key_name) => {
+            // The primary key lives in the first flattened field
+            let primary_key_type = quote! {
+                <#field_type as zino_orm::EmbeddedPrimaryKey>::PrimaryKey
+            };
+            let primary_key = quote! {
+                zino_orm::EmbeddedPrimaryKey::primary_key(&self.#field_ident)
+            };
+            (primary_key_type, primary_key)
+        }
+        _ => {
+            let primary_key_type_ident = format_ident!("{}", primary_key_type);
+            let primary_key_ident = format_ident!("{}", primary_key_name);
+            (
+                quote! { #primary_key_type_ident },
+                quote! { &self.#primary_key_ident },
+            )
+        }
+    };
+    let snapshot_query_fields = if flattened_fields.is_empty() {
+        quote! {
+            let fields = [
+                Self::PRIMARY_KEY_NAME,
+                #(#snapshot_fields),*
+            ];
+        }
+    } else {
+        quote! {
+            let fields = [
+                &[Self::PRIMARY_KEY_NAME, #(#snapshot_fields),*],
+                #(<#flattened_types as zino_orm::EmbeddedSchema>::snapshot_fields(),)*
+            ].concat();
+        }
+    };
+    let json_diff_fields_extension = if flattened_fields.is_empty() {
+        if json_diff_fields.is_empty() {
+            quote! {}
+        } else {
+            quote! {
+                fields.extend(zino_orm::diff_json_fields(self, other, &[#(#json_diff_fields),*]));
+            }
+        }
+    } else {
+        quote! {
+            let json_fields = [
+                &[#(#json_diff_fields),*],
+                #(<#flattened_types as zino_orm::EmbeddedSchema>::FIELDS,)*
+            ].concat();
+            fields.extend(zino_orm::diff_json_fields(self, other, &json_fields));
+        }
+    };
     quote! {
         use zino_core::{
             model::{Mutation, Query},
@@ -789,7 +970,7 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
         impl zino_orm::ModelAccessor<#model_primary_key_type> for #name {
             #[inline]
             fn id(&self) -> &#model_primary_key_type {
-                &self.#model_primary_key
+                #model_primary_key
             }
 
             #(#column_methods)*
@@ -801,6 +982,13 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                 snapshot
             }
 
+            fn changed_fields(&self, other: &Self) -> Vec<&'static str> {
+                let mut fields = Vec::new();
+                #(#diff_comparisons)*
+                #json_diff_fields_extension
+                fields
+            }
+
             fn soft_delete_mutation(&self) -> Mutation {
                 let mut mutation = Self::default_mutation();
                 let mut updates = self.next_edition_updates();
@@ -827,10 +1015,7 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
 
             fn default_snapshot_query() -> Query {
                 let mut query = Query::default();
-                let fields = [
-                    Self::PRIMARY_KEY_NAME,
-                    #(#snapshot_fields),*
-                ];
+                #snapshot_query_fields
                 query.allow_fields(&fields);
                 query.deny_fields(Self::write_only_fields());
                 query
@@ -21,6 +21,38 @@ pub(super) fn parse_option_type(type_name: &str) -> Option<&str> {
         .map(|(_, s)| s.trim_end_matches('>'))
 }
 
+/// Returns the string format if the type is a normalized string wrapper
+/// such as `EmailAddress`, `PhoneNumber` or `WebUrl`.
+pub(super) fn get_normalized_string_format(type_name: &str) -> Option<&'static str> {
+    match parse_option_type(type_name).unwrap_or(type_name) {
+        "EmailAddress" => Some("email"),
+        "PhoneNumber" => Some("phone-number"),
+        "WebUrl" => Some("uri"),
+        _ => None,
+    }
+}
+
+/// Returns `true` if the type is `Money` or `Option<Money>`.
+pub(super) fn check_money_type(type_name: &str) -> bool {
+    parse_option_type(type_name).unwrap_or(type_name) == "Money"
+}
+
+/// Returns `true` if the type is `Attachment`, `Option<Attachment>` or `Vec<Attachment>`.
+pub(super) fn check_attachment_type(type_name: &str) -> bool {
+    parse_option_type(type_name)
+        .or_else(|| parse_vec_type(type_name))
+        .unwrap_or(type_name)
+        == "Attachment"
+}
+
+/// Parses the `Vec<T>` type.
+pub(super) fn parse_vec_type(type_name: &str) -> Option<&str> {
+    type_name
+        .split_once('<')
+        .filter(|&(t, s)| t == "Vec" && s.ends_with('>'))
+        .map(|(_, s)| s.trim_end_matches('>'))
+}
+
 /// Returns `true` if the type is `Vec<T>`.
 pub(super) fn check_vec_type(type_name: &str) -> bool {
     type_name
@@ -81,6 +113,49 @@ pub(super) fn parse_schema_attr(attr: &Attribute) -> Vec<(String, Option<String>
     arguments
 }
 
+/// Returns the doc comments joined by spaces, with the blank lines as paragraph breaks.
+pub(super) fn get_doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let mut lines = Vec::new();
+    for attr in attrs {
+        if let Meta::NameValue(name_value) = &attr.meta {
+            if name_value.path.is_ident("doc") {
+                if let Expr::Lit(expr_lit) = &name_value.value {
+                    if let Lit::Str(ref lit_str) = expr_lit.lit {
+                        lines.push(lit_str.value().trim().to_owned());
+                    }
+                }
+            }
+        }
+    }
+
+    let comment = lines
+        .split(|line| line.is_empty())
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| paragraph.join(" "))
+        .collect::<Vec<_>>()
+        .join("\n");
+    (!comment.is_empty()).then_some(comment)
+}
+
+/// Returns `true` if the field has a `#[schema(flatten)]` attribute.
+pub(super) fn check_flatten_field(field: &Field) -> bool {
+    field.attrs.iter().any(|attr| {
+        parse_schema_attr(attr)
+            .iter()
+            .any(|(key, _)| key == "flatten")
+    })
+}
+
+/// Returns `true` if the money field is stored as a JSON column
+/// instead of the amount and currency columns.
+pub(super) fn check_money_json_layout(field: &Field) -> bool {
+    field.attrs.iter().any(|attr| {
+        parse_schema_attr(attr)
+            .iter()
+            .any(|(key, value)| key == "money_layout" && value.as_deref() == Some("json"))
+    })
+}
+
 /// Parses the struct data and returns a list of fields.
 pub(super) fn parse_struct_fields(data: Data) -> Vec<Field> {
     if let Data::Struct(data) = data {
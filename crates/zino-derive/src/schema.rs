@@ -1,8 +1,8 @@
-use super::parser;
+use super::{parser, typescript};
 use convert_case::{Case, Casing};
-use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
-use syn::{Data, DeriveInput, Fields};
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{DeriveInput, Field};
 
 // Integer types
 const INTEGER_TYPES: [&str; 10] = [
@@ -10,16 +10,21 @@ const INTEGER_TYPES: [&str; 10] = [
 ];
 
 // Special attributes
-const SPECIAL_ATTRIBUTES: [&str; 9] = [
+const SPECIAL_ATTRIBUTES: [&str; 14] = [
     "ignore",
     "type_name",
     "not_null",
     "default_value",
+    "index",
     "index_type",
     "reference",
     "comment",
     "less_than",
     "greater_than",
+    "parent",
+    "computed",
+    "computed_async",
+    "depends_on",
 ];
 
 // Reserved fields
@@ -35,9 +40,92 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
     let mut reader_name = String::from("main");
     let mut writer_name = String::from("main");
     let mut table_name = None;
-    let mut model_comment = None;
+    let mut partition_by = None;
+    let mut scope_name = None;
+    let mut model_comment = parser::get_doc_comment(&input.attrs);
+    let mut child_relations = Vec::new();
+    let mut composite_indexes = Vec::new();
+    let mut computed_fields = Vec::new();
+    let mut typescript = false;
+    let mut read_only = false;
+    let mut view_definition = None;
     for attr in input.attrs.iter() {
-        for (key, value) in parser::parse_schema_attr(attr).into_iter() {
+        let arguments = parser::parse_schema_attr(attr);
+        if arguments.iter().any(|(key, _)| key == "typescript") {
+            typescript = true;
+        }
+        if arguments.iter().any(|(key, _)| key == "view") {
+            read_only = true;
+        }
+        let computed = arguments
+            .iter()
+            .find_map(|(key, value)| match key.as_str() {
+                "computed" | "computed_async" => {
+                    value.clone().map(|value| (value, key == "computed_async"))
+                }
+                _ => None,
+            });
+        if let Some((compute_fn, is_async)) = computed {
+            // The field options are specified in the same attribute.
+            let mut type_name = String::from("String");
+            let mut dependencies = None;
+            for (key, value) in arguments.iter() {
+                if let Some(value) = value {
+                    match key.as_str() {
+                        "type_name" => type_name.clone_from(value),
+                        "depends_on" => dependencies = Some(value.to_owned()),
+                        _ => (),
+                    }
+                }
+            }
+            let name = compute_fn
+                .rsplit("::")
+                .next()
+                .unwrap_or_default()
+                .to_owned();
+            computed_fields.push(ComputedField {
+                name,
+                type_name,
+                compute_fn,
+                dependencies,
+                comment: None,
+                is_async,
+            });
+            continue;
+        }
+        let composite_index = arguments
+            .iter()
+            .find_map(|(key, value)| (key == "composite_index").then_some(value.as_ref()))
+            .flatten();
+        if let Some(columns) = composite_index {
+            // The index options are specified in the same attribute.
+            let mut index_setters = Vec::new();
+            for (key, value) in arguments.iter() {
+                if let Some(value) = value {
+                    match key.as_str() {
+                        "index" | "index_type" => {
+                            index_setters.push(quote! { index.set_index_type(#value); });
+                        }
+                        "index_where" => {
+                            index_setters.push(quote! { index.set_predicate(#value); });
+                        }
+                        "index_include" => {
+                            index_setters.push(quote! { index.set_include_columns(#value); });
+                        }
+                        "index_name" => {
+                            index_setters.push(quote! { index.set_name(#value); });
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            composite_indexes.push(quote! {{
+                let mut index = zino_orm::Index::new(#columns);
+                #(#index_setters)*
+                index
+            }});
+        }
+        for (key, value) in arguments.into_iter() {
             if let Some(value) = value {
                 match key.as_str() {
                     "model_name" => {
@@ -52,216 +140,146 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                     "table_name" => {
                         table_name = Some(value);
                     }
-                    "comment" => {
-                        model_comment = Some(value);
+                    "partition_by" => {
+                        let strategy = value.split_once('(').map(|(s, _)| s.trim());
+                        if !matches!(strategy, Some("range" | "list" | "hash"))
+                            || !value.trim_end().ends_with(')')
+                        {
+                            let message = "`partition_by` should be formatted as \
+                                `range(column)`, `list(column)` or `hash(column)`";
+                            return syn::Error::new_spanned(attr, message).to_compile_error();
+                        }
+                        partition_by = Some(value);
                     }
-                    _ => (),
-                }
-            }
-        }
-    }
-
-    // Parsing field attributes
-    let mut primary_key_type = String::from("Uuid");
-    let mut primary_key_name = String::from("id");
-    let mut primary_key_value = None;
-    let mut primary_key_column = None;
-    let mut columns = Vec::new();
-    let mut column_fields = Vec::new();
-    let mut read_only_fields = Vec::new();
-    let mut write_only_fields = Vec::new();
-    if let Data::Struct(data) = input.data {
-        if let Fields::Named(fields) = data.fields {
-            for field in fields.named.into_iter() {
-                let mut type_name = parser::get_type_name(&field.ty);
-                if let Some(ident) = field.ident {
-                    let name = ident.to_string().trim_start_matches("r#").to_owned();
-                    let mut column_name = name.clone();
-                    let mut ignore = false;
-                    let mut not_null = false;
-                    let mut column_type = None;
-                    let mut default_value = None;
-                    let mut index_type = None;
-                    let mut reference = None;
-                    let mut comment = None;
-                    let mut extra_attributes = Vec::new();
-                    'inner: for attr in field.attrs.iter() {
-                        let arguments = parser::parse_schema_attr(attr);
-                        for (key, value) in arguments.into_iter() {
-                            let key = key.as_str();
-                            if !SPECIAL_ATTRIBUTES.contains(&key) {
-                                let attribute_setter = if let Some(value) = value.as_ref() {
-                                    if let Ok(value) = value.parse::<i64>() {
-                                        quote! { column.set_extra_attribute(#key, #value); }
-                                    } else if let Ok(value) = value.parse::<bool>() {
-                                        quote! { column.set_extra_attribute(#key, #value); }
-                                    } else {
-                                        quote! { column.set_extra_attribute(#key, #value); }
-                                    }
-                                } else {
-                                    quote! { column.set_extra_attribute(#key, true); }
-                                };
-                                extra_attributes.push(attribute_setter);
-                            }
-                            if RESERVED_FIELDS.contains(&name.as_str()) {
-                                extra_attributes.push(quote! {
-                                    column.set_extra_attribute("reserved", true);
-                                });
-                            }
-                            match key {
-                                "ignore" => {
-                                    ignore = true;
-                                    break 'inner;
-                                }
-                                "type_name" => {
-                                    if let Some(value) = value {
-                                        type_name = value;
-                                    }
-                                }
-                                "column_name" => {
-                                    if let Some(value) = value {
-                                        let table_alias = model_name.to_case(Case::Snake);
-                                        column_name =
-                                            format!("{column_name}:{table_alias}.{value}");
-                                    }
-                                }
-                                "column_type" => {
-                                    column_type = value;
-                                }
-                                "length" if type_name == "String" => {
-                                    if let Some(value) = value {
-                                        column_type = Some(format!("CHAR({value})"));
-                                    }
-                                }
-                                "max_length" if type_name == "String" => {
-                                    if let Some(value) = value {
-                                        column_type = Some(format!("VARCHAR({value})"));
-                                    }
-                                }
-                                "not_null" => {
-                                    not_null = true;
-                                }
-                                "default_value" => {
-                                    default_value = value;
-                                }
-                                "auto_increment" => {
-                                    default_value = Some("auto_increment".to_owned());
-                                }
-                                "auto_random" => {
-                                    default_value = Some("auto_random".to_owned());
-                                }
-                                "index_type" => {
-                                    index_type = value;
-                                }
-                                "reference" => {
-                                    reference = value;
-                                }
-                                "comment" => {
-                                    comment = value;
-                                }
-                                "primary_key" => {
-                                    primary_key_name.clone_from(&name);
-                                }
-                                "read_only" => {
-                                    read_only_fields.push(quote! { #name });
+                    "scope_name" => {
+                        scope_name = Some(value);
+                    }
+                    "children" => {
+                        for child in value.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+                            let segments = child.split(':').map(|s| s.trim()).collect::<Vec<_>>();
+                            let (type_name, foreign_key, relation_name) = match segments[..] {
+                                [type_name, foreign_key] => {
+                                    let model_type =
+                                        type_name.rsplit("::").next().unwrap_or_default();
+                                    (
+                                        type_name,
+                                        foreign_key,
+                                        pluralize(&model_type.to_case(Case::Snake)),
+                                    )
                                 }
-                                "write_only" => {
-                                    write_only_fields.push(quote! { #name });
+                                [type_name, foreign_key, relation_name] => {
+                                    (type_name, foreign_key, relation_name.to_owned())
                                 }
-                                "constructor" | "validator" => {
-                                    extra_attributes.push(quote! {
-                                        column.set_extra_attribute(#key, true);
-                                    });
+                                _ => {
+                                    let message = "`children` should be formatted as \
+                                        `Model:foreign_key` or `Model:foreign_key:name`";
+                                    return syn::Error::new_spanned(attr, message)
+                                        .to_compile_error();
                                 }
-                                _ => (),
-                            }
+                            };
+                            let child_type = match syn::parse_str::<syn::Type>(type_name) {
+                                Ok(child_type) => child_type,
+                                Err(err) => return err.to_compile_error(),
+                            };
+                            child_relations.push(quote! {
+                                zino_orm::ModelRelation::child::<#child_type>(#relation_name, #foreign_key)
+                            });
                         }
                     }
-                    if ignore {
-                        continue;
+                    "comment" => {
+                        model_comment = Some(value);
                     }
-                    if primary_key_name == name {
-                        primary_key_type.clone_from(&type_name);
-                        not_null = true;
-                        extra_attributes.push(quote! {
-                            column.set_extra_attribute("primary_key", true);
-                        });
-                    } else if parser::check_option_type(&type_name) {
-                        not_null = false;
-                    } else if INTEGER_TYPES.contains(&type_name.as_str()) {
-                        default_value = default_value.or_else(|| Some("0".to_owned()));
-                    } else if let Some(value) = column_type {
-                        extra_attributes.push(quote! {
-                            column.set_extra_attribute("column_type", #value);
-                        });
+                    "view_sql" => {
+                        read_only = true;
+                        view_definition = Some(quote! { #value });
                     }
-                    let quote_value = if let Some(value) = default_value {
-                        if let Some((type_name, type_fn)) = value.split_once("::") {
-                            let type_name_ident = format_ident!("{}", type_name);
-                            let type_fn_ident = format_ident!("{}", type_fn);
-                            extra_attributes.push(quote! {
-                                let value = <#type_name_ident>::#type_fn_ident();
-                                column.set_extra_attribute("default", value);
-                            });
-                            quote! { Some(<#type_name_ident>::#type_fn_ident().into()) }
-                        } else {
-                            extra_attributes.push(quote! {
-                                column.set_extra_attribute("default", #value);
-                            });
-                            quote! { Some(#value) }
-                        }
-                    } else {
-                        quote! { None }
-                    };
-                    let quote_index = parser::quote_option_string(index_type);
-                    let quote_reference = if let Some(ref model_name) = reference {
-                        let model_ident = format_ident!("{}", model_name);
-                        quote! {{
-                            let table_name = <#model_ident>::table_name();
-                            let column_name = <#model_ident>::PRIMARY_KEY_NAME;
-                            Some(zino_core::model::Reference::new(table_name, column_name))
-                        }}
-                    } else {
-                        quote! { None }
-                    };
-                    let quote_comment = parser::quote_option_string(comment);
-                    let column = quote! {{
-                        let mut column = zino_core::model::Column::new(#name, #type_name, #not_null);
-                        if let Some(default_value) = #quote_value {
-                            column.set_default_value(default_value);
-                        }
-                        if let Some(index_type) = #quote_index {
-                            column.set_index_type(index_type);
-                        }
-                        if let Some(reference) = #quote_reference {
-                            column.set_reference(reference);
-                        }
-                        if let Some(comment) = #quote_comment {
-                            column.set_comment(comment);
-                        }
-                        #(#extra_attributes)*
-                        column
-                    }};
-                    if primary_key_name == name {
-                        let primary_key = if primary_key_type == "Uuid" {
-                            quote! { self.primary_key().to_string() }
-                        } else {
-                            quote! { self.primary_key().clone() }
-                        };
-                        primary_key_value = Some(primary_key);
-                        primary_key_column = Some(column.clone());
+                    "view_file" => {
+                        read_only = true;
+                        view_definition = Some(quote! {
+                            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", #value))
+                        });
                     }
-                    columns.push(column);
-                    column_fields.push(quote! { #column_name });
+                    _ => (),
                 }
             }
         }
     }
 
+    // Parsing field attributes
+    let fields = parser::parse_struct_fields(input.data);
+    let typescript_definitions = typescript.then(|| {
+        let computed_fields = computed_fields
+            .iter()
+            .map(|field| (field.name.clone(), field.type_name.clone()))
+            .collect::<Vec<_>>();
+        typescript::render_definitions(
+            &name.to_string(),
+            model_comment.as_deref(),
+            &fields,
+            &computed_fields,
+        )
+    });
+    let SchemaColumns {
+        primary_key_type,
+        primary_key_name,
+        primary_key_value,
+        primary_key_column,
+        columns,
+        column_fields,
+        read_only_fields,
+        write_only_fields,
+        field_names,
+        flattened_fields,
+        computed_fields: computed_columns,
+    } = {
+        let primary_key_name = if read_only {
+            find_view_primary_key(&fields)
+        } else {
+            None
+        };
+        parse_schema_columns(&model_name, fields, primary_key_name)
+    };
+    computed_fields.extend(computed_columns);
+
+    // Flattened fields
+    let mut collision_checks = Vec::new();
+    for (index, (_, field)) in flattened_fields.iter().enumerate() {
+        let field_type = &field.ty;
+        let field_ident = field.ident.as_ref().expect("the field should be named");
+        for (field_name, span) in field_names.iter() {
+            let message = format!(
+                "the column `{field_name}` collides with the flattened field `{field_ident}`"
+            );
+            collision_checks.push(quote_spanned! {*span=>
+                const _: () = assert!(
+                    !zino_orm::contains_field(<#field_type as zino_orm::EmbeddedSchema>::FIELDS, #field_name),
+                    #message
+                );
+            });
+        }
+        for (_, other_field) in flattened_fields.iter().take(index) {
+            let other_field_type = &other_field.ty;
+            let other_field_ident = other_field
+                .ident
+                .as_ref()
+                .expect("the field should be named");
+            let message = format!(
+                "the flattened field `{field_ident}` has columns colliding with `{other_field_ident}`"
+            );
+            collision_checks.push(quote_spanned! {field_ident.span()=>
+                const _: () = assert!(
+                    !zino_orm::contains_any_field(
+                        <#other_field_type as zino_orm::EmbeddedSchema>::FIELDS,
+                        <#field_type as zino_orm::EmbeddedSchema>::FIELDS,
+                    ),
+                    #message
+                );
+            });
+        }
+    }
+
     // Output
     let model_name_upper_snake = model_name.to_case(Case::UpperSnake);
-    let schema_primary_key_type = format_ident!("{}", primary_key_type);
-    let schema_primary_key = format_ident!("{}", primary_key_name);
     let schema_primary_key_column = format_ident!("{}_PRIMARY_KEY_COLUMN", model_name_upper_snake);
     let schema_columns = format_ident!("{}_COLUMNS", model_name_upper_snake);
     let schema_fields = format_ident!("{}_FIELDS", model_name_upper_snake);
@@ -271,16 +289,235 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
     let schema_writer = format_ident!("{}_WRITER", model_name_upper_snake);
     let schema_table_name = format_ident!("{}_TABLE_NAME", model_name_upper_snake);
     let schema_model_namespace = format_ident!("{}_MODEL_NAMESPACE", model_name_upper_snake);
+    let schema_definition_cache = format_ident!("{}_DEFINITION_CACHE", model_name_upper_snake);
     let avro_schema = format_ident!("{}_AVRO_SCHEMA", model_name_upper_snake);
-    let num_columns = columns.len();
-    let num_read_only_fields = read_only_fields.len();
-    let num_write_only_fields = write_only_fields.len();
     let quote_table_name = parser::quote_option_string(table_name);
+    let quote_partition_by = parser::quote_option_string(partition_by);
+    let quote_scope_name = parser::quote_option_string(scope_name);
     let quote_model_comment = parser::quote_option_string(model_comment);
+    let quote_view_definition = match view_definition {
+        Some(definition) => quote! { Some(#definition) },
+        None => quote! { None },
+    };
+    let (schema_composite_indexes_static, schema_composite_indexes_fn) =
+        if composite_indexes.is_empty() {
+            (quote! {}, quote! {})
+        } else {
+            let schema_composite_indexes =
+                format_ident!("{}_COMPOSITE_INDEXES", model_name_upper_snake);
+            let indexes_static = quote! {
+                static #schema_composite_indexes: zino_core::LazyLock<Vec<zino_orm::Index>> =
+                    zino_core::LazyLock::new(|| vec![#(#composite_indexes),*]);
+            };
+            let indexes_fn = quote! {
+                #[inline]
+                fn composite_indexes() -> &'static [zino_orm::Index] {
+                    #schema_composite_indexes.as_slice()
+                }
+            };
+            (indexes_static, indexes_fn)
+        };
+    let (schema_children_static, schema_children_fn) = if child_relations.is_empty() {
+        (quote! {}, quote! {})
+    } else {
+        let schema_children = format_ident!("{}_CHILDREN", model_name_upper_snake);
+        let children_static = quote! {
+            static #schema_children: zino_core::LazyLock<Vec<zino_orm::ModelRelation>> =
+                zino_core::LazyLock::new(|| vec![#(#child_relations),*]);
+        };
+        let children_fn = quote! {
+            #[inline]
+            fn children() -> &'static [zino_orm::ModelRelation] {
+                #schema_children.as_slice()
+            }
+        };
+        (children_static, children_fn)
+    };
+    let (schema_computed_fields_static, schema_computed_fields_fn) = if computed_fields.is_empty() {
+        (quote! {}, quote! {})
+    } else {
+        let mut computed_field_entries = Vec::with_capacity(computed_fields.len());
+        for field in computed_fields.iter() {
+            match field.quote_entry(&name) {
+                Ok(entry) => computed_field_entries.push(entry),
+                Err(err) => return err.to_compile_error(),
+            }
+        }
+
+        let schema_computed_fields = format_ident!("{}_COMPUTED_FIELDS", model_name_upper_snake);
+        let computed_fields_static = quote! {
+            static #schema_computed_fields: zino_core::LazyLock<Vec<zino_orm::ComputedField>> =
+                zino_core::LazyLock::new(|| vec![#(#computed_field_entries),*]);
+        };
+        let computed_fields_fn = quote! {
+            #[inline]
+            fn computed_fields() -> &'static [zino_orm::ComputedField] {
+                #schema_computed_fields.as_slice()
+            }
+        };
+        (computed_fields_static, computed_fields_fn)
+    };
+    let schema_column_statics = if flattened_fields.is_empty() {
+        let num_columns = columns.len();
+        let num_read_only_fields = read_only_fields.len();
+        let num_write_only_fields = write_only_fields.len();
+        quote! {
+            static #schema_columns: zino_core::LazyLock<[Column; #num_columns]> =
+                zino_core::LazyLock::new(|| [#(#columns),*]);
+            static #schema_fields: [&str; #num_columns] = [#(#column_fields),*];
+            static #schema_read_only_fields: [&str; #num_read_only_fields] = [#(#read_only_fields),*];
+            static #schema_write_only_fields: [&str; #num_write_only_fields] = [#(#write_only_fields),*];
+        }
+    } else {
+        let mut column_pushes = Vec::new();
+        let mut field_pushes = Vec::new();
+        let mut flattened_types = Vec::new();
+        let mut start = 0;
+        for (position, field) in flattened_fields.iter() {
+            let field_type = &field.ty;
+            let own_columns = &columns[start..*position];
+            let own_fields = &column_fields[start..*position];
+            column_pushes.push(quote! {
+                #(columns.push(#own_columns);)*
+                columns.extend_from_slice(<#field_type as zino_orm::EmbeddedSchema>::columns());
+            });
+            field_pushes.push(quote! {
+                #(fields.push(#own_fields);)*
+                fields.extend_from_slice(<#field_type as zino_orm::EmbeddedSchema>::FIELDS);
+            });
+            flattened_types.push(field_type);
+            start = *position;
+        }
+        let own_columns = &columns[start..];
+        let own_fields = &column_fields[start..];
+        quote! {
+            #(#collision_checks)*
+
+            static #schema_columns: zino_core::LazyLock<Vec<Column>> =
+                zino_core::LazyLock::new(|| {
+                    let mut columns = Vec::new();
+                    #(#column_pushes)*
+                    #(columns.push(#own_columns);)*
+                    columns
+                });
+            static #schema_fields: zino_core::LazyLock<Vec<&str>> =
+                zino_core::LazyLock::new(|| {
+                    let mut fields = Vec::new();
+                    #(#field_pushes)*
+                    #(fields.push(#own_fields);)*
+                    fields
+                });
+            static #schema_read_only_fields: zino_core::LazyLock<Vec<&str>> =
+                zino_core::LazyLock::new(|| {
+                    let mut fields = vec![#(#read_only_fields),*];
+                    #(fields.extend_from_slice(<#flattened_types as zino_orm::EmbeddedSchema>::read_only_fields());)*
+                    fields
+                });
+            static #schema_write_only_fields: zino_core::LazyLock<Vec<&str>> =
+                zino_core::LazyLock::new(|| {
+                    let mut fields = vec![#(#write_only_fields),*];
+                    #(fields.extend_from_slice(<#flattened_types as zino_orm::EmbeddedSchema>::write_only_fields());)*
+                    fields
+                });
+        }
+    };
+    let schema_typescript = typescript_definitions.map(|definitions| {
+        let file_name = typescript::file_name(&name.to_string());
+        quote! {
+            impl #name {
+                /// File name of the TypeScript definitions.
+                pub const TYPESCRIPT_FILE_NAME: &'static str = #file_name;
+
+                /// TypeScript definitions of the model.
+                pub const TYPESCRIPT_DEFINITIONS: &'static str = #definitions;
+            }
+        }
+    });
+    let (schema_primary_key_static, schema_primary_key_items, schema_primary_key_eq) =
+        match flattened_fields.first() {
+            Some((_, field)) if primary_key_column.is_none() => {
+                // The primary key lives in the first flattened field
+                let field_type = &field.ty;
+                let field_ident = field.ident.as_ref();
+                let items = quote! {
+                    type PrimaryKey = <#field_type as zino_orm::EmbeddedPrimaryKey>::PrimaryKey;
+
+                    const PRIMARY_KEY_NAME: &'static str =
+                        <#field_type as zino_orm::EmbeddedPrimaryKey>::PRIMARY_KEY_NAME;
+                    const READER_NAME: &'static str = #reader_name;
+                    const WRITER_NAME: &'static str = #writer_name;
+                    const TABLE_NAME: Option<&'static str> = #quote_table_name;
+                    const PARTITION_BY: Option<&'static str> = #quote_partition_by;
+                    const SCOPE_NAME: Option<&'static str> = #quote_scope_name;
+                    const TABLE_COMMENT: Option<&'static str> = #quote_model_comment;
+                    const READ_ONLY: bool = #read_only;
+                    const VIEW_DEFINITION: Option<&'static str> = #quote_view_definition;
+
+                    #[inline]
+                    fn primary_key(&self) -> &Self::PrimaryKey {
+                        zino_orm::EmbeddedPrimaryKey::primary_key(&self.#field_ident)
+                    }
+
+                    #[inline]
+                    fn primary_key_value(&self) -> zino_core::JsonValue {
+                        zino_orm::EmbeddedPrimaryKey::primary_key_value(&self.#field_ident)
+                    }
+
+                    #[inline]
+                    fn primary_key_column() -> &'static Column<'static> {
+                        <#field_type as zino_orm::EmbeddedPrimaryKey>::primary_key_column()
+                    }
+                };
+                let eq = quote! {
+                    Schema::primary_key(self) == Schema::primary_key(other)
+                };
+                (quote! {}, items, eq)
+            }
+            _ => {
+                let schema_primary_key_type = format_ident!("{}", primary_key_type);
+                let schema_primary_key = format_ident!("{}", primary_key_name);
+                let primary_key_static = quote! {
+                    static #schema_primary_key_column: zino_core::LazyLock<Column> =
+                        zino_core::LazyLock::new(|| #primary_key_column);
+                };
+                let items = quote! {
+                    type PrimaryKey = #schema_primary_key_type;
+
+                    const PRIMARY_KEY_NAME: &'static str = #primary_key_name;
+                    const READER_NAME: &'static str = #reader_name;
+                    const WRITER_NAME: &'static str = #writer_name;
+                    const TABLE_NAME: Option<&'static str> = #quote_table_name;
+                    const PARTITION_BY: Option<&'static str> = #quote_partition_by;
+                    const SCOPE_NAME: Option<&'static str> = #quote_scope_name;
+                    const TABLE_COMMENT: Option<&'static str> = #quote_model_comment;
+                    const READ_ONLY: bool = #read_only;
+                    const VIEW_DEFINITION: Option<&'static str> = #quote_view_definition;
+
+                    #[inline]
+                    fn primary_key(&self) -> &Self::PrimaryKey {
+                        &self.#schema_primary_key
+                    }
+
+                    #[inline]
+                    fn primary_key_value(&self) -> zino_core::JsonValue {
+                        #primary_key_value.into()
+                    }
+
+                    #[inline]
+                    fn primary_key_column() -> &'static Column<'static> {
+                        &#schema_primary_key_column
+                    }
+                };
+                let eq = quote! {
+                    self.#schema_primary_key == other.#schema_primary_key
+                };
+                (primary_key_static, items, eq)
+            }
+        };
     quote! {
         use zino_core::{
             error::Error as ZinoError,
-            model::{schema, Column},
+            model::{schema, Column, DefinitionCache},
         };
         use zino_orm::{ConnectionPool, Schema};
 
@@ -298,47 +535,27 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                     namespace: Some(<#name>::model_namespace().to_owned()),
                 },
                 aliases: None,
-                doc: #quote_model_comment,
+                doc: <#name as Schema>::TABLE_COMMENT.map(|s| s.to_owned()),
                 fields,
                 lookup: std::collections::BTreeMap::new(),
                 attributes: std::collections::BTreeMap::new(),
             };
             schema::Schema::Record(record_schema)
         });
-        static #schema_primary_key_column: zino_core::LazyLock<Column> =
-            zino_core::LazyLock::new(|| #primary_key_column);
-        static #schema_columns: zino_core::LazyLock<[Column; #num_columns]> =
-            zino_core::LazyLock::new(|| [#(#columns),*]);
-        static #schema_fields: [&str; #num_columns] = [#(#column_fields),*];
-        static #schema_read_only_fields: [&str; #num_read_only_fields] = [#(#read_only_fields),*];
-        static #schema_write_only_fields: [&str; #num_write_only_fields] = [#(#write_only_fields),*];
+        #schema_primary_key_static
+        #schema_column_statics
+        #schema_composite_indexes_static
+        #schema_children_static
+        #schema_computed_fields_static
         static #schema_reader: std::sync::OnceLock<&ConnectionPool> = std::sync::OnceLock::new();
         static #schema_writer: std::sync::OnceLock<&ConnectionPool> = std::sync::OnceLock::new();
         static #schema_table_name: std::sync::OnceLock<&str> = std::sync::OnceLock::new();
         static #schema_model_namespace: std::sync::OnceLock<&str> = std::sync::OnceLock::new();
+        static #schema_definition_cache: zino_core::LazyLock<DefinitionCache> =
+            zino_core::LazyLock::new(DefinitionCache::new);
 
         impl Schema for #name {
-            type PrimaryKey = #schema_primary_key_type;
-
-            const PRIMARY_KEY_NAME: &'static str = #primary_key_name;
-            const READER_NAME: &'static str = #reader_name;
-            const WRITER_NAME: &'static str = #writer_name;
-            const TABLE_NAME: Option<&'static str> = #quote_table_name;
-
-            #[inline]
-            fn primary_key(&self) -> &Self::PrimaryKey {
-                &self.#schema_primary_key
-            }
-
-            #[inline]
-            fn primary_key_value(&self) -> zino_core::JsonValue {
-                #primary_key_value.into()
-            }
-
-            #[inline]
-            fn primary_key_column() -> &'static Column<'static> {
-                &#schema_primary_key_column
-            }
+            #schema_primary_key_items
 
             #[inline]
             fn schema() -> &'static schema::Schema {
@@ -365,6 +582,17 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
                 #schema_write_only_fields.as_slice()
             }
 
+            #[inline]
+            fn definition_cache() -> &'static DefinitionCache {
+                &#schema_definition_cache
+            }
+
+            #schema_composite_indexes_fn
+
+            #schema_children_fn
+
+            #schema_computed_fields_fn
+
             async fn acquire_reader() -> Result<&'static ConnectionPool, ZinoError> {
                 use zino_core::{bail, error::Error, warn};
                 use zino_orm::PoolManager;
@@ -485,10 +713,451 @@ pub(super) fn parse_token_stream(input: DeriveInput) -> TokenStream {
         impl PartialEq for #name {
             #[inline]
             fn eq(&self, other: &Self) -> bool {
-                self.#schema_primary_key == other.#schema_primary_key
+                #schema_primary_key_eq
             }
         }
 
         impl Eq for #name {}
+
+        #schema_typescript
+    }
+}
+
+/// Columns parsed from the struct fields.
+pub(super) struct SchemaColumns {
+    /// Primary key type.
+    pub(super) primary_key_type: String,
+    /// Primary key name.
+    pub(super) primary_key_name: String,
+    /// Primary key value if the primary key is not flattened.
+    pub(super) primary_key_value: Option<TokenStream>,
+    /// Primary key column if the primary key is not flattened.
+    pub(super) primary_key_column: Option<TokenStream>,
+    /// Column definitions.
+    pub(super) columns: Vec<TokenStream>,
+    /// Column fields.
+    pub(super) column_fields: Vec<TokenStream>,
+    /// Read-only fields.
+    pub(super) read_only_fields: Vec<TokenStream>,
+    /// Write-only fields.
+    pub(super) write_only_fields: Vec<TokenStream>,
+    /// Field names with the spans.
+    pub(super) field_names: Vec<(String, Span)>,
+    /// Flattened fields with the number of columns preceding them.
+    pub(super) flattened_fields: Vec<(usize, Field)>,
+    /// Computed fields which map to no database columns.
+    pub(super) computed_fields: Vec<ComputedField>,
+}
+
+/// Computed field parsed from the attributes.
+pub(super) struct ComputedField {
+    /// Field name.
+    name: String,
+    /// Type name used in the definition.
+    type_name: String,
+    /// Path of the compute function.
+    compute_fn: String,
+    /// Comma-separated columns the value depends on.
+    dependencies: Option<String>,
+    /// Comment.
+    comment: Option<String>,
+    /// A flag to indicate whether the values are computed for a batch of models.
+    is_async: bool,
+}
+
+impl ComputedField {
+    /// Returns the tokens to construct a `zino_orm::ComputedField`.
+    /// A function name without the path refers to an associated function of the model.
+    fn quote_entry(&self, model: &syn::Ident) -> Result<TokenStream, syn::Error> {
+        let name = &self.name;
+        let type_name = &self.type_name;
+        let compute_fn = syn::parse_str::<syn::Path>(&self.compute_fn)?;
+        let compute_fn = if compute_fn.segments.len() == 1 {
+            quote! { <#model>::#compute_fn }
+        } else {
+            quote! { #compute_fn }
+        };
+        let constructor = if self.is_async {
+            quote! {
+                zino_orm::ComputedField::with_batch(#name, #type_name, |models| {
+                    Box::pin(#compute_fn(models))
+                })
+            }
+        } else {
+            quote! {
+                zino_orm::ComputedField::new(#name, #type_name, |model| #compute_fn(model).into())
+            }
+        };
+        let dependencies = self
+            .dependencies
+            .as_ref()
+            .map(|value| quote! { field.set_dependencies(#value); });
+        let comment = self
+            .comment
+            .as_ref()
+            .map(|value| quote! { field.set_comment(#value); });
+        Ok(quote! {{
+            let mut field = #constructor;
+            #dependencies
+            #comment
+            field
+        }})
+    }
+}
+
+/// Returns the first column declared by `#[schema(unique)]` for a view,
+/// if neither `#[schema(primary_key)]` nor the `id` field has been declared.
+fn find_view_primary_key(fields: &[Field]) -> Option<String> {
+    let mut unique_field = None;
+    for field in fields {
+        let Some(ident) = field.ident.as_ref() else {
+            continue;
+        };
+        let name = ident.to_string().trim_start_matches("r#").to_owned();
+        let mut is_unique = false;
+        for attr in field.attrs.iter() {
+            for (key, value) in parser::parse_schema_attr(attr) {
+                match key.as_str() {
+                    "primary_key" => return None,
+                    "unique" => is_unique = true,
+                    "index" | "index_type" if value.as_deref() == Some("unique") => {
+                        is_unique = true;
+                    }
+                    _ => (),
+                }
+            }
+        }
+        if name == "id" {
+            return None;
+        }
+        if is_unique && unique_field.is_none() {
+            unique_field = Some(name);
+        }
+    }
+    unique_field
+}
+
+/// Parses the struct fields and returns the columns.
+/// The primary key name defaults to `id` if it is not specified.
+pub(super) fn parse_schema_columns(
+    model_name: &str,
+    fields: Vec<Field>,
+    primary_key_name: Option<String>,
+) -> SchemaColumns {
+    let mut primary_key_type = String::from("Uuid");
+    let mut primary_key_name = primary_key_name.unwrap_or_else(|| String::from("id"));
+    let mut primary_key_value = None;
+    let mut primary_key_column = None;
+    let mut columns = Vec::new();
+    let mut column_fields = Vec::new();
+    let mut read_only_fields = Vec::new();
+    let mut write_only_fields = Vec::new();
+    let mut field_names = Vec::new();
+    let mut flattened_fields = Vec::new();
+    let mut computed_fields = Vec::new();
+    for field in fields.into_iter() {
+        if parser::check_flatten_field(&field) {
+            flattened_fields.push((columns.len(), field));
+            continue;
+        }
+        let mut type_name = parser::get_type_name(&field.ty);
+        let money_json_layout =
+            parser::check_money_type(&type_name).then(|| parser::check_money_json_layout(&field));
+        if let Some(ident) = field.ident {
+            let name = ident.to_string().trim_start_matches("r#").to_owned();
+            let mut column_name = name.clone();
+            let mut ignore = false;
+            let mut not_null = false;
+            let mut column_type = None;
+            let mut default_value = None;
+            let mut index_type = None;
+            let mut reference = None;
+            let mut parent = None;
+            let mut comment = parser::get_doc_comment(&field.attrs);
+            let mut computed = None;
+            let mut dependencies = None;
+            let mut extra_attributes = Vec::new();
+            if let Some(format) = parser::get_normalized_string_format(&type_name) {
+                // Normalized string wrappers are stored as text columns.
+                type_name = if parser::check_option_type(&type_name) {
+                    "Option<String>".to_owned()
+                } else {
+                    "String".to_owned()
+                };
+                extra_attributes.push(quote! {
+                    column.set_extra_attribute("format", #format);
+                });
+            } else if money_json_layout == Some(true) {
+                // Money values are stored as JSON objects.
+                type_name = "Map".to_owned();
+                extra_attributes.push(quote! {
+                    column.set_extra_attribute("format", "money");
+                });
+            } else if parser::check_attachment_type(&type_name) {
+                // Attachments are stored as JSON objects or arrays of JSON objects.
+                let multiple = parser::check_vec_type(&type_name);
+                type_name = "Map".to_owned();
+                extra_attributes.push(quote! {
+                    column.set_extra_attribute("format", "attachment");
+                    column.set_extra_attribute("multiple", #multiple);
+                });
+            }
+            'inner: for attr in field.attrs.iter() {
+                let arguments = parser::parse_schema_attr(attr);
+                for (key, value) in arguments.into_iter() {
+                    let key = key.as_str();
+                    if !SPECIAL_ATTRIBUTES.contains(&key) {
+                        let attribute_setter = if let Some(value) = value.as_ref() {
+                            if let Ok(value) = value.parse::<i64>() {
+                                quote! { column.set_extra_attribute(#key, #value); }
+                            } else if let Ok(value) = value.parse::<bool>() {
+                                quote! { column.set_extra_attribute(#key, #value); }
+                            } else {
+                                quote! { column.set_extra_attribute(#key, #value); }
+                            }
+                        } else {
+                            quote! { column.set_extra_attribute(#key, true); }
+                        };
+                        extra_attributes.push(attribute_setter);
+                    }
+                    if RESERVED_FIELDS.contains(&name.as_str()) {
+                        extra_attributes.push(quote! {
+                            column.set_extra_attribute("reserved", true);
+                        });
+                    }
+                    match key {
+                        "ignore" => {
+                            ignore = true;
+                            break 'inner;
+                        }
+                        "type_name" => {
+                            if let Some(value) = value {
+                                type_name = value;
+                            }
+                        }
+                        "column_name" => {
+                            if let Some(value) = value {
+                                let table_alias = model_name.to_case(Case::Snake);
+                                column_name = format!("{column_name}:{table_alias}.{value}");
+                            }
+                        }
+                        "column_type" => {
+                            column_type = value;
+                        }
+                        "length" if type_name == "String" => {
+                            if let Some(value) = value {
+                                column_type = Some(format!("CHAR({value})"));
+                            }
+                        }
+                        "max_length" if type_name == "String" => {
+                            if let Some(value) = value {
+                                column_type = Some(format!("VARCHAR({value})"));
+                            }
+                        }
+                        "not_null" => {
+                            not_null = true;
+                        }
+                        "default_value" => {
+                            default_value = value;
+                        }
+                        "auto_increment" => {
+                            default_value = Some("auto_increment".to_owned());
+                        }
+                        "auto_random" => {
+                            default_value = Some("auto_random".to_owned());
+                        }
+                        "index" | "index_type" => {
+                            index_type = value.or_else(|| Some("btree".to_owned()));
+                        }
+                        "expires_at" if index_type.is_none() => {
+                            index_type = Some("btree".to_owned());
+                        }
+                        "reference" => {
+                            reference = value;
+                        }
+                        "parent" => {
+                            parent = Some(value);
+                        }
+                        "comment" if value.is_some() => {
+                            comment = value;
+                        }
+                        "computed" | "computed_async" => {
+                            if let Some(value) = value {
+                                computed = Some((value, key == "computed_async"));
+                            }
+                        }
+                        "depends_on" => {
+                            dependencies = value;
+                        }
+                        "primary_key" => {
+                            primary_key_name.clone_from(&name);
+                        }
+                        "read_only" => {
+                            read_only_fields.push(quote! { #name });
+                        }
+                        "write_only" | "private" => {
+                            write_only_fields.push(quote! { #name });
+                        }
+                        "constructor" | "validator" => {
+                            extra_attributes.push(quote! {
+                                column.set_extra_attribute(#key, true);
+                            });
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            if ignore {
+                continue;
+            }
+            if let Some((compute_fn, is_async)) = computed {
+                computed_fields.push(ComputedField {
+                    name,
+                    type_name,
+                    compute_fn,
+                    dependencies,
+                    comment,
+                    is_async,
+                });
+                continue;
+            }
+            field_names.push((name.clone(), ident.span()));
+            if money_json_layout == Some(false) {
+                // Money values are stored as the amount and currency columns.
+                let amount_name = format!("{name}_amount");
+                let currency_name = format!("{name}_currency");
+                let quote_comment = parser::quote_option_string(comment);
+                columns.push(quote! {{
+                    let mut column = zino_core::model::Column::new(#amount_name, "Decimal", #not_null);
+                    if let Some(comment) = #quote_comment {
+                        column.set_comment(comment);
+                    }
+                    #(#extra_attributes)*
+                    column.set_extra_attribute("money_field", #name);
+                    column
+                }});
+                columns.push(quote! {{
+                    let mut column = zino_core::model::Column::new(#currency_name, "String", #not_null);
+                    column.set_extra_attribute("column_type", "CHAR(3)");
+                    column.set_extra_attribute("money_field", #name);
+                    column
+                }});
+                column_fields.push(quote! { #amount_name });
+                column_fields.push(quote! { #currency_name });
+                continue;
+            }
+            if primary_key_name == name {
+                primary_key_type.clone_from(&type_name);
+                not_null = true;
+                extra_attributes.push(quote! {
+                    column.set_extra_attribute("primary_key", true);
+                });
+            } else if parser::check_option_type(&type_name) {
+                not_null = false;
+            } else if INTEGER_TYPES.contains(&type_name.as_str()) {
+                default_value = default_value.or_else(|| Some("0".to_owned()));
+            } else if let Some(value) = column_type {
+                extra_attributes.push(quote! {
+                    column.set_extra_attribute("column_type", #value);
+                });
+            }
+            let quote_value = if let Some(value) = default_value {
+                if let Some((type_name, type_fn)) = value.split_once("::") {
+                    let type_name_ident = format_ident!("{}", type_name);
+                    let type_fn_ident = format_ident!("{}", type_fn);
+                    extra_attributes.push(quote! {
+                        let value = <#type_name_ident>::#type_fn_ident();
+                        column.set_extra_attribute("default", value);
+                    });
+                    quote! { Some(<#type_name_ident>::#type_fn_ident().into()) }
+                } else {
+                    extra_attributes.push(quote! {
+                        column.set_extra_attribute("default", #value);
+                    });
+                    quote! { Some(#value) }
+                }
+            } else {
+                quote! { None }
+            };
+            if let Some(param) = parent {
+                let param = param
+                    .or_else(|| {
+                        reference
+                            .as_ref()
+                            .map(|model_name| format!("{}_id", model_name.to_case(Case::Snake)))
+                    })
+                    .unwrap_or_else(|| name.clone());
+                extra_attributes.push(quote! {
+                    column.set_extra_attribute("parent", #param);
+                });
+            }
+            let quote_index = parser::quote_option_string(index_type);
+            let quote_reference = if let Some(ref model_name) = reference {
+                let model_ident = format_ident!("{}", model_name);
+                quote! {{
+                    let table_name = <#model_ident>::table_name();
+                    let column_name = <#model_ident>::PRIMARY_KEY_NAME;
+                    Some(zino_core::model::Reference::new(table_name, column_name))
+                }}
+            } else {
+                quote! { None }
+            };
+            let quote_comment = parser::quote_option_string(comment);
+            let column = quote! {{
+                let mut column = zino_core::model::Column::new(#name, #type_name, #not_null);
+                if let Some(default_value) = #quote_value {
+                    column.set_default_value(default_value);
+                }
+                if let Some(index_type) = #quote_index {
+                    column.set_index_type(index_type);
+                }
+                if let Some(reference) = #quote_reference {
+                    column.set_reference(reference);
+                }
+                if let Some(comment) = #quote_comment {
+                    column.set_comment(comment);
+                }
+                #(#extra_attributes)*
+                column
+            }};
+            if primary_key_name == name {
+                let primary_key = if matches!(primary_key_type.as_str(), "Uuid" | "Ulid") {
+                    quote! { self.primary_key().to_string() }
+                } else {
+                    quote! { self.primary_key().clone() }
+                };
+                primary_key_value = Some(primary_key);
+                primary_key_column = Some(column.clone());
+            }
+            columns.push(column);
+            column_fields.push(quote! { #column_name });
+        }
+    }
+    SchemaColumns {
+        primary_key_type,
+        primary_key_name,
+        primary_key_value,
+        primary_key_column,
+        columns,
+        column_fields,
+        read_only_fields,
+        write_only_fields,
+        field_names,
+        flattened_fields,
+        computed_fields,
+    }
+}
+
+/// Returns the plural form of the snake-cased model name.
+fn pluralize(name: &str) -> String {
+    if name.ends_with('s') || name.ends_with('x') || name.ends_with("ch") || name.ends_with("sh") {
+        format!("{name}es")
+    } else if let Some(stem) = name
+        .strip_suffix('y')
+        .filter(|stem| !stem.ends_with(['a', 'e', 'i', 'o', 'u']))
+    {
+        format!("{stem}ies")
+    } else {
+        format!("{name}s")
     }
 }
@@ -0,0 +1,272 @@
+use super::parser;
+use convert_case::{Case, Casing};
+use std::{collections::BTreeSet, fmt::Write};
+use syn::Field;
+
+// Integer and float types
+const NUMBER_TYPES: [&str; 12] = [
+    "u64", "i64", "u32", "i32", "u16", "i16", "u8", "i8", "usize", "isize", "f64", "f32",
+];
+
+// Reserved fields which are maintained by the server
+const RESERVED_FIELDS: [&str; 4] = ["created_at", "updated_at", "version", "edition"];
+
+/// A property of the TypeScript interfaces.
+struct Property {
+    /// Property name.
+    name: String,
+    /// TypeScript type.
+    ts_type: String,
+    /// Doc comment.
+    comment: Option<String>,
+    /// A flag to indicate whether the property can be absent.
+    optional: bool,
+    /// A flag to indicate whether the property can be omitted in the requests.
+    has_default: bool,
+    /// A flag to indicate whether the property is read-only.
+    read_only: bool,
+    /// A flag to indicate whether the property is excluded from the responses.
+    write_only: bool,
+    /// A flag to indicate whether the property is excluded from the requests.
+    response_only: bool,
+}
+
+/// Returns the file name of the TypeScript definitions for the model.
+pub(super) fn file_name(type_name: &str) -> String {
+    format!("{}.d.ts", type_name.to_case(Case::Snake))
+}
+
+/// Renders the TypeScript definitions of the model, which consist of the primary key type,
+/// the response interface `{Model}` and the request interface `{Model}Request`.
+///
+/// The output only depends on the struct definition, so that it is stable across builds.
+pub(super) fn render_definitions(
+    type_name: &str,
+    model_comment: Option<&str>,
+    fields: &[Field],
+    computed_fields: &[(String, String)],
+) -> String {
+    let mut imports = BTreeSet::new();
+    let mut properties = Vec::new();
+    let mut primary_key_name = String::from("id");
+    let mut primary_key_type = String::from("string");
+    for field in fields {
+        if parser::check_flatten_field(field) {
+            continue;
+        }
+        let Some(ident) = field.ident.as_ref() else {
+            continue;
+        };
+        let name = ident.to_string().trim_start_matches("r#").to_owned();
+        let mut field_type = parser::get_type_name(&field.ty);
+        let mut comment = parser::get_doc_comment(&field.attrs);
+        let mut enum_values = None;
+        let mut reference = None;
+        let mut ignore = false;
+        let mut is_primary_key = name == primary_key_name;
+        let mut has_default = false;
+        let mut read_only = false;
+        let mut write_only = false;
+        for attr in field.attrs.iter() {
+            for (key, value) in parser::parse_schema_attr(attr) {
+                match key.as_str() {
+                    "ignore" | "computed" | "computed_async" => ignore = true,
+                    "type_name" => {
+                        if let Some(value) = value {
+                            field_type = value;
+                        }
+                    }
+                    "enum_values" => enum_values = value,
+                    "reference" => reference = value,
+                    "comment" if value.is_some() => comment = value,
+                    "primary_key" => {
+                        primary_key_name.clone_from(&name);
+                        is_primary_key = true;
+                    }
+                    "default_value" | "auto_increment" | "auto_random" | "auto" => {
+                        has_default = true;
+                    }
+                    "read_only" => read_only = true,
+                    "write_only" | "private" => write_only = true,
+                    _ => (),
+                }
+            }
+        }
+        if ignore {
+            continue;
+        }
+
+        let (optional, field_type) = match parser::parse_option_type(&field_type) {
+            Some(inner_type) => (true, inner_type.to_owned()),
+            None => (false, field_type),
+        };
+        let (ts_type, format) = if let Some(model) = reference.as_deref() {
+            let model = model.rsplit("::").next().unwrap_or_default();
+            let id_type = format!("{model}Id");
+            if model != type_name {
+                imports.insert((id_type.clone(), model.to_case(Case::Snake)));
+            }
+            if parser::check_vec_type(&field_type) {
+                (format!("{id_type}[]"), None)
+            } else {
+                (id_type, None)
+            }
+        } else {
+            parse_ts_type(&field_type, enum_values.as_deref())
+        };
+        if is_primary_key {
+            primary_key_type.clone_from(&ts_type);
+        }
+        let comment = match (comment, format) {
+            (Some(comment), Some(format)) => Some(format!("{comment} ({format})")),
+            (comment, format) => comment.or(format.map(|s| s.to_owned())),
+        };
+        properties.push(Property {
+            has_default: has_default
+                || is_primary_key
+                || NUMBER_TYPES.contains(&field_type.as_str()),
+            response_only: RESERVED_FIELDS.contains(&name.as_str()),
+            name,
+            ts_type,
+            comment,
+            optional,
+            read_only,
+            write_only,
+        });
+    }
+    for property in properties.iter_mut() {
+        if property.name == primary_key_name {
+            property.ts_type = format!("{type_name}Id");
+            property.has_default = true;
+        }
+    }
+    for (name, field_type) in computed_fields {
+        let (optional, field_type) = match parser::parse_option_type(field_type) {
+            Some(inner_type) => (true, inner_type),
+            None => (false, field_type.as_str()),
+        };
+        let (ts_type, format) = parse_ts_type(field_type, None);
+        properties.push(Property {
+            name: name.to_owned(),
+            ts_type,
+            comment: format.map(|s| s.to_owned()),
+            optional,
+            has_default: true,
+            read_only: true,
+            write_only: false,
+            response_only: true,
+        });
+    }
+
+    let mut output = String::from("// This file is generated by `zino-derive`. Do not edit.\n");
+    if !imports.is_empty() {
+        output.push('\n');
+        for (id_type, file_stem) in imports {
+            let _ = writeln!(
+                output,
+                "import type {{ {id_type} }} from \"./{file_stem}\";"
+            );
+        }
+    }
+
+    output.push('\n');
+    let _ = writeln!(output, "/** Primary key of `{type_name}`. */");
+    let _ = writeln!(output, "export type {type_name}Id = {primary_key_type};");
+
+    output.push('\n');
+    if let Some(comment) = model_comment {
+        write_comment(&mut output, comment, "");
+    }
+    let _ = writeln!(output, "export interface {type_name} {{");
+    for property in properties.iter().filter(|p| !p.write_only) {
+        if let Some(comment) = property.comment.as_deref() {
+            write_comment(&mut output, comment, "  ");
+        }
+        let readonly = if property.read_only { "readonly " } else { "" };
+        let optional = if property.optional { "?" } else { "" };
+        let _ = writeln!(
+            output,
+            "  {readonly}{}{optional}: {};",
+            property.name, property.ts_type
+        );
+    }
+    output.push_str("}\n");
+
+    output.push('\n');
+    let _ = writeln!(output, "/** Request body of `{type_name}`. */");
+    let _ = writeln!(output, "export interface {type_name}Request {{");
+    for property in properties.iter().filter(|p| !p.response_only) {
+        if let Some(comment) = property.comment.as_deref() {
+            write_comment(&mut output, comment, "  ");
+        }
+        let optional = if property.optional || property.has_default {
+            "?"
+        } else {
+            ""
+        };
+        let _ = writeln!(
+            output,
+            "  {}{optional}: {};",
+            property.name, property.ts_type
+        );
+    }
+    output.push_str("}\n");
+    output
+}
+
+/// Parses the TypeScript type and the string format for the Rust type name.
+fn parse_ts_type(type_name: &str, enum_values: Option<&str>) -> (String, Option<&'static str>) {
+    if let Some(values) = enum_values {
+        let variants = values
+            .split('|')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| format!("\"{s}\""))
+            .collect::<Vec<_>>();
+        if !variants.is_empty() {
+            let union_type = variants.join(" | ");
+            return if parser::check_vec_type(type_name) {
+                (format!("({union_type})[]"), None)
+            } else {
+                (union_type, None)
+            };
+        }
+    }
+    if let Some(item_type) = parser::parse_vec_type(type_name) {
+        let (ts_type, format) = parse_ts_type(item_type, None);
+        return if ts_type.contains(' ') {
+            (format!("({ts_type})[]"), format)
+        } else {
+            (format!("{ts_type}[]"), format)
+        };
+    }
+    match type_name {
+        "String" | "str" | "Uuid" | "Ulid" => ("string".to_owned(), None),
+        "EmailAddress" => ("string".to_owned(), Some("email")),
+        "PhoneNumber" => ("string".to_owned(), Some("phone number")),
+        "WebUrl" => ("string".to_owned(), Some("URI")),
+        "DateTime" => ("string".to_owned(), Some("RFC 3339 date-time")),
+        "Date" | "NaiveDate" => ("string".to_owned(), Some("ISO 8601 date")),
+        "Time" | "NaiveTime" => ("string".to_owned(), Some("ISO 8601 time")),
+        "Decimal" => ("string".to_owned(), Some("decimal number")),
+        "Money" => ("{ amount: string; currency: string }".to_owned(), None),
+        "bool" => ("boolean".to_owned(), None),
+        "Map" | "Attachment" => ("Record<string, unknown>".to_owned(), None),
+        _ if NUMBER_TYPES.contains(&type_name) => ("number".to_owned(), None),
+        _ => ("unknown".to_owned(), None),
+    }
+}
+
+/// Writes the doc comment with the indentation.
+fn write_comment(output: &mut String, comment: &str, indent: &str) {
+    let comment = comment.replace("*/", "*\\/");
+    if comment.contains('\n') {
+        let _ = writeln!(output, "{indent}/**");
+        for line in comment.lines() {
+            let _ = writeln!(output, "{indent} * {line}");
+        }
+        let _ = writeln!(output, "{indent} */");
+    } else {
+        let _ = writeln!(output, "{indent}/** {comment} */");
+    }
+}
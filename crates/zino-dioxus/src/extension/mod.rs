@@ -1,7 +1,9 @@
 //! Extension traits and helper utilities.
 
 mod form_data;
+mod sync_cache;
 mod vnode;
 
 pub use form_data::FormDataExt;
+pub use sync_cache::SyncCache;
 pub use vnode::VNodeExt;
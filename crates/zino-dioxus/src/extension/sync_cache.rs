@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+use zino_core::{
+    extension::{JsonObjectExt, JsonValueExt},
+    Map,
+};
+
+/// A local cache of the models synced incrementally from the `/{model}/changes` endpoint.
+///
+/// It stores the sync token and merges the batches of changes into the cached entries.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino_dioxus::extension::SyncCache;
+///
+/// let mut cache = SyncCache::new("id");
+/// loop {
+///     let url = match cache.token() {
+///         Some(token) => format!("/task/changes?since={token}"),
+///         None => "/task/changes".to_owned(),
+///     };
+///     let data = fetch_json_data(&url).await?;
+///     if !cache.merge(&data) {
+///         break;
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SyncCache {
+    /// Primary key name.
+    primary_key_name: String,
+    /// Sync token.
+    token: Option<String>,
+    /// Cached entries indexed by the primary key.
+    entries: BTreeMap<String, Map>,
+}
+
+impl SyncCache {
+    /// Creates a new instance with the primary key name.
+    #[inline]
+    pub fn new(primary_key_name: impl Into<String>) -> Self {
+        Self {
+            primary_key_name: primary_key_name.into(),
+            token: None,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Restores the cache with the persisted token and entries.
+    pub fn restore(&mut self, token: Option<String>, entries: Vec<Map>) {
+        self.token = token.filter(|s| !s.is_empty());
+        self.entries.clear();
+        for entry in entries {
+            self.upsert_entry(entry);
+        }
+    }
+
+    /// Returns the sync token for the next request.
+    #[inline]
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+
+    /// Returns an entry by the primary key.
+    #[inline]
+    pub fn get(&self, id: &str) -> Option<&Map> {
+        self.entries.get(id)
+    }
+
+    /// Returns an iterator over the cached entries ordered by the primary key.
+    #[inline]
+    pub fn entries(&self) -> impl Iterator<Item = &Map> {
+        self.entries.values()
+    }
+
+    /// Returns the number of the cached entries.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no cached entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Clears the token and the entries so that the models will be synced from scratch.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.token = None;
+        self.entries.clear();
+    }
+
+    /// Merges a batch of changes into the cache and updates the token.
+    /// The cache is reset if a full resync is required.
+    ///
+    /// It returns `true` if there are more changes to be fetched.
+    pub fn merge(&mut self, data: &Map) -> bool {
+        if data.get_bool("full_resync_required") == Some(true) {
+            self.reset();
+            return true;
+        }
+        if let Some(entries) = data.get_array("entries") {
+            for entry in entries.iter().filter_map(|v| v.as_object()) {
+                self.upsert_entry(entry.clone());
+            }
+        }
+        if let Some(ids) = data.get_array("deleted_ids") {
+            for id in ids {
+                self.entries.remove(&id.to_string_unquoted());
+            }
+        }
+        if let Some(token) = data.get_str("next_token") {
+            self.token = Some(token.to_owned());
+        }
+        data.get_bool("has_more") == Some(true)
+    }
+
+    /// Inserts or replaces an entry indexed by the primary key.
+    fn upsert_entry(&mut self, entry: Map) {
+        if let Some(id) = entry.get(&self.primary_key_name) {
+            self.entries.insert(id.to_string_unquoted(), entry);
+        }
+    }
+}
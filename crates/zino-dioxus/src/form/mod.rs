@@ -7,6 +7,7 @@ mod checkbox;
 mod field;
 mod file;
 mod input;
+mod model_form;
 mod progress;
 mod radio;
 mod select;
@@ -23,6 +24,7 @@ pub use field::{
 };
 pub use file::{FileTree, FileTreeProps, FileUpload, FileUploadProps};
 pub use input::{Input, InputProps};
+pub use model_form::{ModelField, ModelForm, ModelFormProps};
 pub use progress::{Progress, ProgressProps};
 pub use radio::{Radio, RadioProps};
 pub use select::{DataSelect, DataSelectProps};
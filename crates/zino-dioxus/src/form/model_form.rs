@@ -0,0 +1,536 @@
+use super::{Button, Checkbox, DataSelect, FormField, FormFieldContainer, Input, Textarea};
+use crate::class::Class;
+use dioxus::prelude::*;
+use std::{collections::HashSet, str::FromStr};
+use zino_core::{
+    datetime::DateTime,
+    extension::{JsonObjectExt, JsonValueExt},
+    validation::Validation,
+    JsonValue, Map, SharedString,
+};
+
+/// A form generated from the JSON definition of a model,
+/// which is responded by the `definition` endpoint of the default controller.
+///
+/// The values are validated against the definition before submission,
+/// and the validation messages responded by the server can be mapped onto the fields.
+pub fn ModelForm(props: ModelFormProps) -> Element {
+    let mut edits = use_signal(Map::new);
+    let mut touched_fields = use_signal(HashSet::<String>::new);
+    let mut client_errors = use_signal(Map::new);
+
+    let definition = props.definition.clone();
+    let properties = definition
+        .get_object("properties")
+        .cloned()
+        .unwrap_or_default();
+    let required_fields = definition
+        .get_str_array("required")
+        .unwrap_or_default()
+        .into_iter()
+        .map(|s| s.to_owned())
+        .collect::<HashSet<_>>();
+    let data = props
+        .data
+        .as_ref()
+        .map(|data| data.get_object("item").unwrap_or(data));
+    let initial_values = format_initial_values(&properties, data);
+    let server_errors = props.errors.clone().unwrap_or_default();
+    let update_mode = props.mode == "update";
+
+    let mut fields = Vec::new();
+    for (name, value) in properties.iter() {
+        let Some(field_definition) = value.as_object() else {
+            continue;
+        };
+        if field_definition.get_bool("readOnly") == Some(true) {
+            continue;
+        }
+
+        let value = edits
+            .read()
+            .get_str(name)
+            .or_else(|| initial_values.get_str(name))
+            .unwrap_or_default()
+            .to_owned();
+        let error = client_errors
+            .read()
+            .get_str(name)
+            .or_else(|| {
+                if touched_fields.read().contains(name) {
+                    None
+                } else {
+                    server_errors.get_str(name)
+                }
+            })
+            .map(|s| s.to_owned());
+        let field_name = name.to_owned();
+        let field = ModelField {
+            name: name.to_owned(),
+            definition: field_definition.clone(),
+            value,
+            required: required_fields.contains(name),
+            error,
+            on_change: EventHandler::new(move |value: String| {
+                touched_fields.write().insert(field_name.clone());
+                client_errors.write().remove(&field_name);
+                edits.write().upsert(field_name.clone(), value);
+            }),
+        };
+        fields.push(field);
+    }
+
+    let submitted_fields = fields.clone();
+    let elements = fields.into_iter().map(|field| {
+        props
+            .render_field
+            .as_ref()
+            .and_then(|render| render.call(field.clone()))
+            .unwrap_or_else(|| {
+                rsx! {
+                    ModelFormField {
+                        key: "{field.name()}",
+                        field: field,
+                    }
+                }
+            })
+    });
+    rsx! {
+        form {
+            class: props.class,
+            novalidate: true,
+            onsubmit: move |event| {
+                event.prevent_default();
+
+                let mut values = Map::new();
+                let mut validation = Validation::new();
+                for field in submitted_fields.iter() {
+                    let name = field.name();
+                    if update_mode && initial_values.get_str(name).unwrap_or_default() == field.value() {
+                        continue;
+                    }
+                    if let Some(value) = field.parse_value(&mut validation) {
+                        values.upsert(name, value);
+                    }
+                }
+                touched_fields.write().clear();
+                if validation.is_success() {
+                    client_errors.write().clear();
+                    if let Some(handler) = props.on_submit.as_ref() {
+                        handler.call(values);
+                    }
+                } else {
+                    client_errors.set(validation.into_map());
+                }
+            },
+            { elements }
+            FormField {
+                Button {
+                    color: "link",
+                    r#type: "submit",
+                    { props.submit_label }
+                }
+            }
+        }
+    }
+}
+
+/// The [`ModelForm`] properties struct for the configuration of the component.
+#[derive(Clone, PartialEq, Props)]
+pub struct ModelFormProps {
+    /// The class attribute for the component.
+    #[props(into, default = "form")]
+    pub class: Class,
+    /// The JSON definition of the model.
+    pub definition: Map,
+    /// The mode of the form: `create` | `update`.
+    #[props(into, default = "create")]
+    pub mode: SharedString,
+    /// The model data to pre-fill the form. It can also be a `data_item` payload.
+    #[props(into)]
+    pub data: Option<Map>,
+    /// The validation messages responded by the server.
+    #[props(into)]
+    pub errors: Option<Map>,
+    /// The label text for the submit button.
+    #[props(into, default = "Submit")]
+    pub submit_label: SharedString,
+    /// A slot to override the rendering of individual fields.
+    /// The default rendering is used if it returns `None`.
+    pub render_field: Option<Callback<ModelField, Option<Element>>>,
+    /// An event handler to be called with the typed values when the form is submitted.
+    /// Only the changed values are submitted in the `update` mode.
+    pub on_submit: Option<EventHandler<Map>>,
+}
+
+/// A field of the model form.
+#[derive(Clone, PartialEq)]
+pub struct ModelField {
+    /// The field name.
+    name: String,
+    /// The JSON definition of the field.
+    definition: Map,
+    /// The raw value of the control.
+    value: String,
+    /// A flag to determine whether the field is required or not.
+    required: bool,
+    /// The validation message.
+    error: Option<String>,
+    /// An event handler to be called when the value is changed.
+    on_change: EventHandler<String>,
+}
+
+impl ModelField {
+    /// Returns the field name.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the JSON definition of the field.
+    #[inline]
+    pub fn definition(&self) -> &Map {
+        &self.definition
+    }
+
+    /// Returns the raw value of the control.
+    #[inline]
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns `true` if the field is required.
+    #[inline]
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    /// Returns the validation message.
+    #[inline]
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Returns the label text of the field.
+    #[inline]
+    pub fn label(&self) -> &str {
+        self.definition
+            .get_str("title")
+            .or_else(|| self.definition.get_str("description"))
+            .unwrap_or(&self.name)
+    }
+
+    /// Sets the raw value of the control.
+    #[inline]
+    pub fn set_value(&self, value: impl Into<String>) {
+        self.on_change.call(value.into());
+    }
+
+    /// Parses the raw value as a typed value and validates it against the definition.
+    fn parse_value(&self, validation: &mut Validation) -> Option<JsonValue> {
+        let name = self.name.as_str();
+        let value = self.value.trim();
+        if value.is_empty() {
+            if self.required {
+                validation.record(name.to_owned(), "it should be nonempty");
+            }
+            return None;
+        }
+        parse_field_value(name, &self.definition, value, validation)
+    }
+}
+
+/// The default rendering of a model field.
+fn ModelFormField(props: ModelFormFieldProps) -> Element {
+    let field = props.field;
+    let definition = field.definition();
+    let name = field.name().to_owned();
+    let value = field.value().to_owned();
+    let required = field.is_required();
+    let state = if field.error().is_some() {
+        "danger"
+    } else {
+        ""
+    };
+    let control = if let Some(values) = definition.parse_str_array("enum") {
+        let options = values
+            .into_iter()
+            .map(|s| (s.to_owned(), s.to_owned()))
+            .collect::<Vec<_>>();
+        let field = field.clone();
+        rsx! {
+            DataSelect {
+                options: options,
+                selected: value,
+                name: name,
+                required: required,
+                fullwidth: true,
+                on_select: move |(value, _label): (String, String)| {
+                    field.set_value(value);
+                },
+            }
+        }
+    } else {
+        let field = field.clone();
+        match definition.get_str("type") {
+            Some("boolean") => rsx! {
+                Checkbox {
+                    name: name,
+                    checked: value == "true",
+                    on_change: move |value: String| {
+                        field.set_value(value);
+                    },
+                }
+            },
+            Some("object") => rsx! {
+                Textarea {
+                    color: state,
+                    name: name,
+                    required: required,
+                    initial_value: value,
+                    on_change: move |value: String| {
+                        field.set_value(value);
+                    },
+                }
+            },
+            Some(kind @ ("integer" | "number")) => {
+                let step = if kind == "integer" { "1" } else { "any" };
+                rsx! {
+                    Input {
+                        color: state,
+                        r#type: "number",
+                        name: name,
+                        required: required,
+                        step: step,
+                        min: definition.get_f64("minimum").map(|n| n.to_string()),
+                        max: definition.get_f64("maximum").map(|n| n.to_string()),
+                        initial_value: value,
+                        on_change: move |value: String| {
+                            field.set_value(value);
+                        },
+                    }
+                }
+            }
+            _ => {
+                let input_type = match definition.get_str("format") {
+                    Some("date") => "date",
+                    Some("date-time") => "datetime-local",
+                    Some("time") => "time",
+                    Some("email") => "email",
+                    Some("password") => "password",
+                    Some("uri") => "url",
+                    _ => "text",
+                };
+                rsx! {
+                    Input {
+                        color: state,
+                        r#type: input_type,
+                        name: name,
+                        required: required,
+                        minlength: definition.get_u64("minLength").map(|n| n.to_string()),
+                        maxlength: definition.get_u64("maxLength").map(|n| n.to_string()),
+                        initial_value: value,
+                        on_change: move |value: String| {
+                            field.set_value(value);
+                        },
+                    }
+                }
+            }
+        }
+    };
+    let help = field.error().map(|error| {
+        rsx! {
+            p {
+                class: "help is-danger",
+                { error.to_owned() }
+            }
+        }
+    });
+    rsx! {
+        FormFieldContainer {
+            label: field.label().to_owned(),
+            FormField {
+                { control }
+                { help }
+            }
+        }
+    }
+}
+
+/// The [`ModelFormField`] properties struct for the configuration of the component.
+#[derive(Clone, PartialEq, Props)]
+struct ModelFormFieldProps {
+    /// The model field.
+    field: ModelField,
+}
+
+/// Formats the initial values of the controls with the model data or the default values.
+fn format_initial_values(properties: &Map, data: Option<&Map>) -> Map {
+    let mut values = Map::new();
+    for (name, definition) in properties.iter() {
+        let Some(definition) = definition.as_object() else {
+            continue;
+        };
+        let value = data
+            .and_then(|data| data.get(name))
+            .or_else(|| definition.get("default"));
+        if let Some(value) = value {
+            values.upsert(name, format_field_value(definition, value));
+        }
+    }
+    values
+}
+
+/// Formats a JSON value as the raw value of the control.
+fn format_field_value(definition: &Map, value: &JsonValue) -> String {
+    match value {
+        JsonValue::Null => String::new(),
+        JsonValue::Array(vec) => vec
+            .iter()
+            .map(|v| v.to_string_unquoted())
+            .collect::<Vec<_>>()
+            .join(", "),
+        JsonValue::Object(_) => value.to_string(),
+        JsonValue::String(s) if definition.get_str("format") == Some("date-time") => {
+            DateTime::from_str(s)
+                .map(|dt| dt.format("%Y-%m-%dT%H:%M:%S"))
+                .unwrap_or_else(|_| s.to_owned())
+        }
+        _ => value.to_string_unquoted(),
+    }
+}
+
+/// Parses the raw value of a field and validates it against the definition.
+fn parse_field_value(
+    name: &str,
+    definition: &Map,
+    value: &str,
+    validation: &mut Validation,
+) -> Option<JsonValue> {
+    if let Some(values) = definition.parse_str_array("enum") {
+        if !values.contains(&value) {
+            validation.record(name.to_owned(), "it should be one of the enum values");
+            return None;
+        }
+    }
+    match definition.get_str("type") {
+        Some("boolean") => Some(matches!(value, "true" | "on").into()),
+        Some("integer") => match value.parse::<i64>() {
+            Ok(number) => check_number_bounds(name, definition, number as f64, validation)
+                .then(|| number.into()),
+            Err(_) => {
+                validation.record(name.to_owned(), "it should be an integer");
+                None
+            }
+        },
+        Some("number") => match value.parse::<f64>() {
+            Ok(number) => {
+                check_number_bounds(name, definition, number, validation).then(|| number.into())
+            }
+            Err(_) => {
+                validation.record(name.to_owned(), "it should be a number");
+                None
+            }
+        },
+        Some("object") => match value.parse::<JsonValue>() {
+            Ok(value) if value.is_object() => Some(value),
+            _ => {
+                validation.record(name.to_owned(), "it should be a JSON object");
+                None
+            }
+        },
+        Some("array") => {
+            let items = definition.get_object("items").cloned().unwrap_or_default();
+            let values = value
+                .split(',')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .filter_map(|s| parse_field_value(name, &items, s, validation))
+                .collect::<Vec<_>>();
+            let num_items = values.len();
+            if let Some(min_items) = definition.get_usize("minItems") {
+                if num_items < min_items {
+                    let message = format!("it should have at least {min_items} items");
+                    validation.record(name.to_owned(), message);
+                }
+            }
+            if let Some(max_items) = definition.get_usize("maxItems") {
+                if num_items > max_items {
+                    let message = format!("it should have at most {max_items} items");
+                    validation.record(name.to_owned(), message);
+                }
+            }
+            if definition.get_bool("uniqueItems") == Some(true) {
+                let unique_items = values.iter().map(|v| v.to_string()).collect::<HashSet<_>>();
+                if unique_items.len() < num_items {
+                    validation.record(name.to_owned(), "the items should be unique");
+                }
+            }
+            Some(values.into())
+        }
+        _ => {
+            let num_chars = value.chars().count();
+            if let Some(min_length) = definition.get_usize("minLength") {
+                if num_chars < min_length {
+                    let message = format!("the length should be at least {min_length}");
+                    validation.record(name.to_owned(), message);
+                }
+            }
+            if let Some(max_length) = definition.get_usize("maxLength") {
+                if num_chars > max_length {
+                    let message = format!("the length should be at most {max_length}");
+                    validation.record(name.to_owned(), message);
+                }
+            }
+            match definition.get_str("format") {
+                Some("date-time") => {
+                    let value = if value.len() == 16 {
+                        [value, ":00"].concat()
+                    } else {
+                        value.to_owned()
+                    };
+                    match DateTime::from_str(&value) {
+                        Ok(dt) => Some(dt.to_string().into()),
+                        Err(err) => {
+                            validation.record_fail(name.to_owned(), err);
+                            None
+                        }
+                    }
+                }
+                Some("password" | "binary") | None => Some(value.into()),
+                Some(format) => {
+                    validation.validate_format(name.to_owned(), value, format);
+                    Some(value.into())
+                }
+            }
+        }
+    }
+}
+
+/// Checks the bounds of a number. It returns `true` if the number is within the bounds.
+fn check_number_bounds(
+    name: &str,
+    definition: &Map,
+    number: f64,
+    validation: &mut Validation,
+) -> bool {
+    let message = if let Some(minimum) = definition.get_f64("minimum").filter(|&n| number < n) {
+        format!("it should be at least {minimum}")
+    } else if let Some(maximum) = definition.get_f64("maximum").filter(|&n| number > n) {
+        format!("it should be at most {maximum}")
+    } else if let Some(minimum) = definition
+        .get_f64("exclusiveMinimum")
+        .filter(|&n| number <= n)
+    {
+        format!("it should be greater than {minimum}")
+    } else if let Some(maximum) = definition
+        .get_f64("exclusiveMaximum")
+        .filter(|&n| number >= n)
+    {
+        format!("it should be less than {maximum}")
+    } else {
+        return true;
+    };
+    validation.record(name.to_owned(), message);
+    false
+}
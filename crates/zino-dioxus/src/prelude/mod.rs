@@ -6,7 +6,7 @@ pub use crate::{
     feedback::{Message, ModalCard, ModalData, Notification, OperationResult},
     form::{
         Button, Buttons, Checkbox, DataEntry, DataSelect, FileTree, FileUpload, FormAddons,
-        FormField, FormFieldContainer, FormGroup, Input, Progress, Radio, Textarea,
+        FormField, FormFieldContainer, FormGroup, Input, ModelForm, Progress, Radio, Textarea,
     },
     icon::{Icon, IconText, SvgIcon},
     layout::{Columns, Container, FluidContainer, MainContainer},
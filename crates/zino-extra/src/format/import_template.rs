@@ -0,0 +1,102 @@
+use csv::Writer;
+use zino_core::{error::Error, extension::JsonObjectExt, JsonValue, Map};
+
+/// A spreadsheet template for importing the model data,
+/// which consists of a header row and an example row.
+#[derive(Debug, Clone, Default)]
+pub struct ImportTemplate {
+    /// Column names.
+    headers: Vec<String>,
+    /// Example values.
+    examples: Vec<String>,
+}
+
+impl ImportTemplate {
+    /// Creates a new instance.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new instance from the template columns
+    /// with the `name` and `example` fields.
+    pub fn from_columns(columns: &[Map]) -> Self {
+        let mut template = Self::new();
+        for col in columns {
+            if let Some(name) = col.get_str("name") {
+                template.add_column(name, col.get("example"));
+            }
+        }
+        template
+    }
+
+    /// Adds a column with an optional example value.
+    pub fn add_column(&mut self, name: impl Into<String>, example: Option<&JsonValue>) {
+        let example = match example {
+            Some(JsonValue::String(s)) => s.to_owned(),
+            Some(JsonValue::Array(vec)) => vec
+                .iter()
+                .map(|v| v.as_str().map(|s| s.to_owned()).unwrap_or_else(|| v.to_string()))
+                .collect::<Vec<_>>()
+                .join(","),
+            Some(JsonValue::Null) | None => String::new(),
+            Some(value) => value.to_string(),
+        };
+        self.headers.push(name.into());
+        self.examples.push(example);
+    }
+
+    /// Returns `true` if the template has any example values.
+    #[inline]
+    pub fn has_examples(&self) -> bool {
+        self.examples.iter().any(|s| !s.is_empty())
+    }
+
+    /// Writes the template as the CSV bytes.
+    pub fn to_csv(&self) -> Result<Vec<u8>, Error> {
+        let mut wtr = Writer::from_writer(Vec::new());
+        wtr.write_record(&self.headers)?;
+        if self.has_examples() {
+            wtr.write_record(&self.examples)?;
+        }
+        wtr.into_inner().map_err(|err| err.into_error().into())
+    }
+
+    /// Writes the template as the XLSX bytes.
+    #[cfg(feature = "format-xlsx")]
+    pub fn to_xlsx(&self) -> Result<Vec<u8>, Error> {
+        use rust_xlsxwriter::{Format, Workbook};
+
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet();
+        let header_format = Format::new().set_bold();
+        for (col, header) in (0u16..).zip(self.headers.iter()) {
+            worksheet.write_string_with_format(0, col, header, &header_format)?;
+            worksheet.set_column_width(col, header.len().max(12) as f64)?;
+        }
+        if self.has_examples() {
+            for (col, example) in (0u16..).zip(self.examples.iter()) {
+                worksheet.write_string(1, col, example)?;
+            }
+        }
+        workbook.save_to_buffer().map_err(Error::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ImportTemplate;
+    use zino_core::{extension::JsonObjectExt, Map};
+
+    #[test]
+    fn it_writes_csv_template() {
+        let mut name = Map::from_entry("name", "name");
+        name.upsert("example", "Alice");
+        let mut tags = Map::from_entry("name", "tags");
+        tags.upsert("example", vec!["a", "b"]);
+        let age = Map::from_entry("name", "age");
+        let template = ImportTemplate::from_columns(&[name, tags, age]);
+        let csv = template.to_csv().unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "name,tags,age\nAlice,\"a,b\",\n");
+    }
+}
@@ -7,9 +7,14 @@
 //! | Name          | Description                                          | Default? |
 //! |---------------|------------------------------------------------------|----------|
 //! | `format-pdf`  | Enables the support for `PDF` documents.             | No       |
+//! | `format-xlsx` | Enables the support for `XLSX` spreadsheets.         | No       |
+
+mod import_template;
 
 #[cfg(feature = "format-pdf")]
 mod pdf_document;
 
+pub use import_template::ImportTemplate;
+
 #[cfg(feature = "format-pdf")]
 pub use pdf_document::PdfDocument;
@@ -0,0 +1,250 @@
+//! Deduplication of concurrent identical calls.
+
+use parking_lot::Mutex;
+use std::{
+    any::{self, Any},
+    collections::HashMap,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering::Relaxed},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::watch;
+use zino_core::{extension::TomlTableExt, state::State, LazyLock};
+
+/// A group of calls in which the concurrent calls with the same key are merged,
+/// so that only one execution happens and its result is shared with the waiters.
+///
+/// A waiter stops waiting for the leader after the timeout, and then executes the call
+/// by itself. It also happens when the leader has been cancelled before it finishes.
+#[derive(Debug)]
+pub struct SingleFlight<T> {
+    /// Name of the group.
+    name: &'static str,
+    /// Timeout for the waiters.
+    timeout: Duration,
+    /// In-flight calls.
+    calls: Mutex<HashMap<String, watch::Receiver<Option<T>>>>,
+    /// Number of the executions.
+    executions: AtomicU64,
+    /// Number of the merged calls.
+    merged_calls: AtomicU64,
+    /// Number of the waiters which have timed out.
+    timeouts: AtomicU64,
+}
+
+impl<T: Clone + Send + Sync> SingleFlight<T> {
+    /// Creates a new instance.
+    #[inline]
+    pub fn new(name: &'static str, timeout: Duration) -> Self {
+        Self {
+            name,
+            timeout,
+            calls: Mutex::new(HashMap::new()),
+            executions: AtomicU64::new(0),
+            merged_calls: AtomicU64::new(0),
+            timeouts: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the name of the group.
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the timeout for the waiters.
+    #[inline]
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Returns the number of the executions.
+    #[inline]
+    pub fn executions(&self) -> u64 {
+        self.executions.load(Relaxed)
+    }
+
+    /// Returns the number of the calls which have been merged into another execution.
+    #[inline]
+    pub fn merged_calls(&self) -> u64 {
+        self.merged_calls.load(Relaxed)
+    }
+
+    /// Returns the number of the waiters which have timed out.
+    #[inline]
+    pub fn timeouts(&self) -> u64 {
+        self.timeouts.load(Relaxed)
+    }
+
+    /// Returns the number of the in-flight calls.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.calls.lock().len()
+    }
+
+    /// Returns `true` if there are no in-flight calls.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.calls.lock().is_empty()
+    }
+
+    /// Executes the call for the key, or waits for the result of an in-flight call
+    /// with the same key.
+    pub async fn run<F, Fut>(&self, key: &str, f: F) -> T
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let sender = {
+            let mut calls = self.calls.lock();
+            if let Some(receiver) = calls.get(key) {
+                Err(receiver.clone())
+            } else {
+                let (sender, receiver) = watch::channel(None);
+                calls.insert(key.to_owned(), receiver);
+                Ok(sender)
+            }
+        };
+        match sender {
+            Ok(sender) => {
+                let _guard = CallGuard {
+                    calls: &self.calls,
+                    key,
+                };
+                self.executions.fetch_add(1, Relaxed);
+                let value = f().await;
+                sender.send_replace(Some(value.clone()));
+                value
+            }
+            Err(mut receiver) => {
+                let name = self.name;
+                let wait_for_leader = receiver.wait_for(Option::is_some);
+                let shared_value = match tokio::time::timeout(self.timeout, wait_for_leader).await {
+                    Ok(Ok(value)) => value.clone(),
+                    Ok(Err(_)) => {
+                        tracing::warn!(name, key, "the leader of the call has been cancelled");
+                        None
+                    }
+                    Err(_) => {
+                        self.timeouts.fetch_add(1, Relaxed);
+                        #[cfg(feature = "metrics")]
+                        metrics::counter!("zino_singleflight_timeouts_total", "group" => name)
+                            .increment(1);
+                        tracing::warn!(name, key, "timed out waiting for the leader of the call");
+                        None
+                    }
+                };
+                if let Some(value) = shared_value {
+                    self.merged_calls.fetch_add(1, Relaxed);
+                    #[cfg(feature = "metrics")]
+                    metrics::counter!("zino_singleflight_merged_calls_total", "group" => name)
+                        .increment(1);
+                    return value;
+                }
+                self.executions.fetch_add(1, Relaxed);
+                f().await
+            }
+        }
+    }
+}
+
+/// A guard to remove the in-flight call when the leader finishes or is cancelled.
+struct CallGuard<'a, T> {
+    /// In-flight calls.
+    calls: &'a Mutex<HashMap<String, watch::Receiver<Option<T>>>>,
+    /// Key of the call.
+    key: &'a str,
+}
+
+impl<T> Drop for CallGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.calls.lock().remove(self.key);
+    }
+}
+
+/// Executes the call for the key in the global group, or waits for the result of
+/// an in-flight call with the same key. The type of the value is a part of the key.
+///
+/// The timeout for the waiters can be configured in the `[singleflight]` table.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino_extra::singleflight::singleflight;
+///
+/// let key = format!("top-users:{limit}");
+/// let users = singleflight(&key, || async {
+///     User::find::<Map>(&query).await.map_err(|err| err.to_string())
+/// })
+/// .await?;
+/// ```
+pub async fn singleflight<T, F, Fut>(key: &str, f: F) -> T
+where
+    T: Clone + Send + Sync + 'static,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = T>,
+{
+    let key = [any::type_name::<T>(), ":", key].concat();
+    let value = GLOBAL_SINGLEFLIGHT
+        .run(&key, || async {
+            let value: Arc<dyn Any + Send + Sync> = Arc::new(f().await);
+            value
+        })
+        .await;
+    value
+        .downcast_ref::<T>()
+        .cloned()
+        .expect("the type of the value should be consistent with the key")
+}
+
+/// Global group of calls.
+static GLOBAL_SINGLEFLIGHT: LazyLock<SingleFlight<Arc<dyn Any + Send + Sync>>> =
+    LazyLock::new(|| {
+        let timeout = State::shared()
+            .get_config("singleflight")
+            .and_then(|config| config.get_duration("timeout"))
+            .unwrap_or_else(|| Duration::from_secs(10));
+        SingleFlight::new("global", timeout)
+    });
+
+#[cfg(test)]
+mod tests {
+    use super::SingleFlight;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn it_merges_concurrent_calls() {
+        let group = SingleFlight::new("test", Duration::from_secs(1));
+        let call = || async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            42
+        };
+        let (a, b, c) = tokio::join!(
+            group.run("answer", call),
+            group.run("answer", call),
+            group.run("answer", call),
+        );
+        assert_eq!((a, b, c), (42, 42, 42));
+        assert_eq!(group.executions(), 1);
+        assert_eq!(group.merged_calls(), 2);
+        assert!(group.is_empty());
+    }
+
+    #[tokio::test]
+    async fn it_stops_waiting_after_timeout() {
+        let group = SingleFlight::new("test", Duration::from_millis(10));
+        let slow_call = || async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            1
+        };
+        let fast_call = || async { 2 };
+        let (a, b) = tokio::join!(group.run("key", slow_call), group.run("key", fast_call));
+        assert_eq!((a, b), (1, 2));
+        assert_eq!(group.executions(), 2);
+        assert_eq!(group.timeouts(), 1);
+    }
+}
@@ -0,0 +1,354 @@
+//! Cross-origin resource sharing (CORS) policies.
+
+use std::time::Duration;
+use toml::Table;
+use zino_core::{application::ServerTag, extension::TomlTableExt, state::State, LazyLock};
+
+mod origin;
+
+use origin::AllowOrigin;
+
+/// A CORS policy which is applied to the routes under a path prefix.
+///
+/// The default policy is configured by the `[cors]` table, and the overrides
+/// for the route groups can be specified by the `[[cors.scope]]` tables.
+/// The missing fields of a scope are inherited from the default policy.
+///
+/// # Examples
+///
+/// ```toml
+/// [cors]
+/// allow-origin = ["https://zino.cc", "https://*.zino.cc"]
+/// allow-methods = ["GET", "POST", "PUT", "DELETE"]
+/// allow-credentials = true
+/// max-age = "1h"
+///
+/// [[cors.scope]]
+/// name = "widget"
+/// path-prefix = "/widget"
+/// allow-origin = "any"
+/// allow-credentials = false
+/// ```
+///
+/// A listener configured by `[[server.listener]]` can override the policy
+/// with its own `cors` table, whose missing fields are inherited from `[cors]`.
+#[derive(Debug, Clone)]
+pub struct CorsPolicy {
+    /// Scope name.
+    name: String,
+    /// Path prefix of the routes.
+    path_prefix: String,
+    /// Allowed origins.
+    allow_origin: AllowOrigin,
+    /// Allowed methods. An empty list means mirroring the request.
+    allow_methods: Vec<String>,
+    /// Allowed headers. An empty list means mirroring the request.
+    allow_headers: Vec<String>,
+    /// Exposed headers.
+    expose_headers: Vec<String>,
+    /// A flag to indicate whether the credentials are allowed.
+    allow_credentials: bool,
+    /// Max age for caching the preflight responses.
+    max_age: Duration,
+}
+
+impl CorsPolicy {
+    /// Creates a new instance with the configuration.
+    pub fn with_config(config: &Table) -> Self {
+        let mut policy = Self::default();
+        policy.read_config(config);
+        policy
+    }
+
+    /// Returns the policy for the request path.
+    ///
+    /// The scope with the longest matching path prefix is selected,
+    /// otherwise the default policy is used.
+    pub fn for_path(path: &str) -> &'static Self {
+        let (default_policy, scopes) = &*CORS_POLICIES;
+        select_policy(default_policy, scopes, path)
+    }
+
+    /// Returns the policy for the request path accepted by the listener with the scope.
+    ///
+    /// It falls back to [`for_path()`](Self::for_path) if the listener
+    /// does not override the `cors` config.
+    pub fn for_scope(scope: &ServerTag, path: &str) -> &'static Self {
+        SCOPED_CORS_POLICIES
+            .iter()
+            .find(|(server_tag, ..)| server_tag == scope)
+            .map(|(_, default_policy, scopes)| select_policy(default_policy, scopes, path))
+            .unwrap_or_else(|| Self::for_path(path))
+    }
+
+    /// Returns the scope name.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the path prefix of the routes.
+    #[inline]
+    pub fn path_prefix(&self) -> &str {
+        &self.path_prefix
+    }
+
+    /// Returns `true` if the origin is allowed.
+    #[inline]
+    pub fn is_origin_allowed(&self, origin: &str) -> bool {
+        self.allow_origin.matches(origin)
+    }
+
+    /// Returns the CORS headers for a preflight request.
+    ///
+    /// It is empty if the origin, the request method or any of the request headers
+    /// is not allowed, in which case the browser will block the actual request.
+    pub fn preflight_headers(
+        &self,
+        origin: &str,
+        request_method: &str,
+        request_headers: Option<&str>,
+    ) -> Vec<(&'static str, String)> {
+        if !self.is_origin_allowed(origin) {
+            return Vec::new();
+        }
+
+        let request_method = request_method.trim();
+        if !self.allow_methods.is_empty()
+            && !self
+                .allow_methods
+                .iter()
+                .any(|method| method.eq_ignore_ascii_case(request_method))
+        {
+            return Vec::new();
+        }
+
+        let request_headers = request_headers
+            .map(|s| {
+                s.split(',')
+                    .map(|header| header.trim().to_ascii_lowercase())
+                    .filter(|header| !header.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        if !self.allow_headers.is_empty()
+            && !request_headers
+                .iter()
+                .all(|header| self.allow_headers.contains(header))
+        {
+            return Vec::new();
+        }
+
+        let mut headers = self.origin_headers(origin);
+        let allow_methods = if self.allow_methods.is_empty() {
+            request_method.to_owned()
+        } else {
+            self.allow_methods.join(", ")
+        };
+        headers.push(("access-control-allow-methods", allow_methods));
+        let allow_headers = if self.allow_headers.is_empty() {
+            request_headers.join(", ")
+        } else {
+            self.allow_headers.join(", ")
+        };
+        if !allow_headers.is_empty() {
+            headers.push(("access-control-allow-headers", allow_headers));
+        }
+        headers.push(("access-control-max-age", self.max_age.as_secs().to_string()));
+        headers.push(("vary", "access-control-request-method".to_owned()));
+        headers.push(("vary", "access-control-request-headers".to_owned()));
+        headers
+    }
+
+    /// Returns the CORS headers for an actual request.
+    /// It is empty if the origin is not allowed.
+    pub fn response_headers(&self, origin: &str) -> Vec<(&'static str, String)> {
+        if !self.is_origin_allowed(origin) {
+            return Vec::new();
+        }
+
+        let mut headers = self.origin_headers(origin);
+        if !self.expose_headers.is_empty() {
+            headers.push((
+                "access-control-expose-headers",
+                self.expose_headers.join(", "),
+            ));
+        }
+        headers
+    }
+
+    /// Returns the headers of the allowed origin and credentials.
+    fn origin_headers(&self, origin: &str) -> Vec<(&'static str, String)> {
+        let mut headers = Vec::with_capacity(8);
+        if self.allow_origin.is_any() && !self.allow_credentials {
+            headers.push(("access-control-allow-origin", "*".to_owned()));
+        } else {
+            headers.push(("access-control-allow-origin", origin.to_owned()));
+            headers.push(("vary", "origin".to_owned()));
+        }
+        if self.allow_credentials {
+            headers.push(("access-control-allow-credentials", "true".to_owned()));
+        }
+        headers
+    }
+
+    /// Reads the configuration and overrides the fields.
+    fn read_config(&mut self, config: &Table) {
+        if let Some(name) = config.get_str("name") {
+            self.name = name.to_owned();
+        }
+        if let Some(path_prefix) = config.get_str("path-prefix") {
+            self.path_prefix = path_prefix.to_owned();
+        }
+        if let Some(allow_origin) = config.get("allow-origin") {
+            self.allow_origin = AllowOrigin::with_value(allow_origin);
+        }
+        if let Some(methods) = config.get_str_array("allow-methods") {
+            self.allow_methods = methods
+                .into_iter()
+                .map(|method| method.to_ascii_uppercase())
+                .collect();
+        }
+        if let Some(headers) = config.get_str_array("allow-headers") {
+            self.allow_headers = headers
+                .into_iter()
+                .map(|header| header.to_ascii_lowercase())
+                .collect();
+        }
+        if let Some(headers) = config.get_str_array("expose-headers") {
+            self.expose_headers = headers
+                .into_iter()
+                .map(|header| header.to_ascii_lowercase())
+                .collect();
+        }
+        if let Some(allow_credentials) = config.get_bool("allow-credentials") {
+            self.allow_credentials = allow_credentials;
+        }
+        if let Some(max_age) = config
+            .get_duration("max-age")
+            .or_else(|| config.get_u64("max-age").map(Duration::from_secs))
+        {
+            self.max_age = max_age;
+        }
+    }
+}
+
+impl Default for CorsPolicy {
+    fn default() -> Self {
+        Self {
+            name: "default".to_owned(),
+            path_prefix: "/".to_owned(),
+            allow_origin: AllowOrigin::Any,
+            allow_methods: Vec::new(),
+            allow_headers: Vec::new(),
+            expose_headers: vec!["x-request-id".to_owned(), "server-timing".to_owned()],
+            allow_credentials: false,
+            max_age: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+/// Returns `true` if the request is a CORS preflight request.
+#[inline]
+pub fn is_preflight_request(
+    method: &str,
+    origin: Option<&str>,
+    request_method: Option<&str>,
+) -> bool {
+    method.eq_ignore_ascii_case("OPTIONS") && origin.is_some() && request_method.is_some()
+}
+
+/// Selects the scope with the longest matching path prefix,
+/// otherwise the default policy is used.
+fn select_policy<'a>(
+    default_policy: &'a CorsPolicy,
+    scopes: &'a [CorsPolicy],
+    path: &str,
+) -> &'a CorsPolicy {
+    scopes
+        .iter()
+        .filter(|scope| path.starts_with(scope.path_prefix.as_str()))
+        .max_by_key(|scope| scope.path_prefix.len())
+        .unwrap_or(default_policy)
+}
+
+/// Parses the default CORS policy and the scopes inheriting from the base policy.
+fn parse_policies(base_policy: &CorsPolicy, config: &Table) -> (CorsPolicy, Vec<CorsPolicy>) {
+    let mut default_policy = base_policy.clone();
+    default_policy.read_config(config);
+
+    let mut scopes = Vec::new();
+    if let Some(tables) = config.get_array("scope") {
+        for scope in tables.iter().filter_map(|v| v.as_table()) {
+            let mut policy = default_policy.clone();
+            policy.read_config(scope);
+            if scope.get_str("path-prefix").is_none() {
+                tracing::warn!(
+                    "the `path-prefix` of the CORS scope `{}` should be specified",
+                    policy.name
+                );
+            }
+            scopes.push(policy);
+        }
+    }
+    (default_policy, scopes)
+}
+
+/// The default CORS policy and the scopes.
+static CORS_POLICIES: LazyLock<(CorsPolicy, Vec<CorsPolicy>)> = LazyLock::new(|| {
+    let Some(config) = State::shared().get_config("cors") else {
+        return (CorsPolicy::default(), Vec::new());
+    };
+    parse_policies(&CorsPolicy::default(), config)
+});
+
+/// The CORS policies overridden by the listeners.
+static SCOPED_CORS_POLICIES: LazyLock<Vec<(ServerTag, CorsPolicy, Vec<CorsPolicy>)>> =
+    LazyLock::new(|| {
+        let base_policy = &CORS_POLICIES.0;
+        State::shared()
+            .server_listeners()
+            .into_iter()
+            .filter_map(|listener| {
+                let config = listener.get_config("cors")?;
+                let (default_policy, scopes) = parse_policies(base_policy, config);
+                Some((listener.scope().clone(), default_policy, scopes))
+            })
+            .collect()
+    });
+
+#[cfg(test)]
+mod tests {
+    use super::CorsPolicy;
+
+    #[test]
+    fn it_builds_cors_headers() {
+        let config = r#"
+            allow-origin = ["https://zino.cc", "https://*.zino.cc"]
+            allow-methods = ["GET", "POST"]
+            allow-credentials = true
+            max-age = "10m"
+        "#;
+        let policy = CorsPolicy::with_config(&config.parse().unwrap());
+        assert!(policy.is_origin_allowed("https://zino.cc"));
+        assert!(policy.is_origin_allowed("https://api.zino.cc"));
+        assert!(!policy.is_origin_allowed("https://evil.cc"));
+
+        let headers = policy.preflight_headers("https://api.zino.cc", "POST", None);
+        assert!(headers.contains(&(
+            "access-control-allow-origin",
+            "https://api.zino.cc".to_owned()
+        )));
+        assert!(headers.contains(&("access-control-max-age", "600".to_owned())));
+        assert!(policy
+            .preflight_headers("https://api.zino.cc", "DELETE", None)
+            .is_empty());
+        assert!(policy.response_headers("https://evil.cc").is_empty());
+
+        let headers = policy.response_headers("https://zino.cc");
+        assert!(headers.contains(&(
+            "access-control-expose-headers",
+            "x-request-id, server-timing".to_owned()
+        )));
+    }
+}
@@ -0,0 +1,78 @@
+use toml::Value;
+
+/// Allowed origins of a CORS policy.
+#[derive(Debug, Clone)]
+pub(super) enum AllowOrigin {
+    /// Any origin is allowed.
+    Any,
+    /// A list of exact origins or wildcard subdomain patterns
+    /// such as `https://*.zino.cc`.
+    List(Vec<String>),
+}
+
+impl AllowOrigin {
+    /// Creates a new instance with the config value.
+    pub(super) fn with_value(value: &Value) -> Self {
+        match value {
+            Value::String(s) if is_any(s) => Self::Any,
+            Value::String(s) => Self::List(vec![normalize_origin(s)]),
+            Value::Array(vec) => {
+                let origins = vec.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>();
+                if origins.iter().any(|s| is_any(s)) {
+                    Self::Any
+                } else {
+                    Self::List(origins.into_iter().map(normalize_origin).collect())
+                }
+            }
+            _ => {
+                tracing::warn!("invalid `allow-origin` value for the CORS policy: {value:?}");
+                Self::List(Vec::new())
+            }
+        }
+    }
+
+    /// Returns `true` if any origin is allowed.
+    #[inline]
+    pub(super) fn is_any(&self) -> bool {
+        matches!(self, Self::Any)
+    }
+
+    /// Returns `true` if the origin is allowed.
+    pub(super) fn matches(&self, origin: &str) -> bool {
+        match self {
+            Self::Any => true,
+            Self::List(patterns) => {
+                let origin = origin.trim().to_ascii_lowercase();
+                patterns
+                    .iter()
+                    .any(|pattern| match_pattern(pattern, &origin))
+            }
+        }
+    }
+}
+
+/// Returns `true` if the value allows any origin.
+#[inline]
+fn is_any(value: &str) -> bool {
+    value == "*" || value.eq_ignore_ascii_case("any")
+}
+
+/// Normalizes the origin by removing the trailing slash.
+#[inline]
+fn normalize_origin(origin: &str) -> String {
+    origin.trim().trim_end_matches('/').to_ascii_lowercase()
+}
+
+/// Matches the origin with an exact origin or a wildcard subdomain pattern.
+fn match_pattern(pattern: &str, origin: &str) -> bool {
+    if let Some((scheme, domain)) = pattern.split_once("://*.") {
+        origin
+            .strip_prefix(scheme)
+            .and_then(|s| s.strip_prefix("://"))
+            .and_then(|host| host.strip_suffix(domain))
+            .and_then(|subdomain| subdomain.strip_suffix('.'))
+            .is_some_and(|subdomain| !subdomain.is_empty())
+    } else {
+        pattern == origin
+    }
+}
@@ -0,0 +1,103 @@
+/// Selects a content type from the supported ones by parsing the `accept` header value.
+/// The media ranges are weighted by the quality values, and the most specific range
+/// takes precedence over the wildcards. If multiple content types have the same quality,
+/// the first one in the supported list is selected.
+pub(crate) fn select_content_type<'a>(accept: &str, supported: &[&'a str]) -> Option<&'a str> {
+    let media_ranges = accept
+        .split(',')
+        .filter_map(|s| {
+            let mut parts = s.split(';');
+            let media_range = parts.next()?.trim();
+            if media_range.is_empty() {
+                return None;
+            }
+
+            let mut quality = 1.0;
+            for param in parts {
+                if let Some((key, value)) = param.split_once('=') {
+                    if key.trim().eq_ignore_ascii_case("q") {
+                        quality = value.trim().parse::<f32>().ok()?;
+                    }
+                }
+            }
+            Some((media_range, quality))
+        })
+        .collect::<Vec<_>>();
+
+    let mut selected = None;
+    let mut max_quality = 0.0;
+    for &content_type in supported {
+        let essence = content_type
+            .split_once(';')
+            .map(|(essence, _)| essence)
+            .unwrap_or(content_type)
+            .trim();
+        let main_type = essence
+            .split_once('/')
+            .map(|(main_type, _)| main_type)
+            .unwrap_or(essence);
+
+        let mut max_specificity = 0;
+        let mut quality = 0.0;
+        for &(media_range, q) in &media_ranges {
+            let specificity = if media_range.eq_ignore_ascii_case(essence) {
+                3
+            } else if media_range
+                .strip_suffix("/*")
+                .is_some_and(|s| s.eq_ignore_ascii_case(main_type))
+            {
+                2
+            } else if media_range == "*/*" {
+                1
+            } else {
+                0
+            };
+            if specificity > max_specificity {
+                max_specificity = specificity;
+                quality = q;
+            }
+        }
+        if max_specificity > 0 && quality > max_quality {
+            selected = Some(content_type);
+            max_quality = quality;
+        }
+    }
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::select_content_type;
+
+    #[test]
+    fn it_selects_content_type() {
+        let supported = [
+            "application/json",
+            "application/problem+json",
+            "text/csv",
+            "application/jsonlines",
+        ];
+        assert_eq!(
+            select_content_type("text/csv", &supported),
+            Some("text/csv"),
+        );
+        assert_eq!(
+            select_content_type("*/*", &supported),
+            Some("application/json"),
+        );
+        assert_eq!(
+            select_content_type("text/*;q=0.9, application/json;q=0.5", &supported),
+            Some("text/csv"),
+        );
+        assert_eq!(
+            select_content_type("application/*;q=0.2, text/csv;q=0", &supported),
+            Some("application/json"),
+        );
+        assert_eq!(
+            select_content_type("application/jsonlines, */*;q=0.1", &supported),
+            Some("application/jsonlines"),
+        );
+        assert_eq!(select_content_type("image/png", &supported), None);
+        assert_eq!(select_content_type("text/csv;q=0", &supported), None);
+    }
+}
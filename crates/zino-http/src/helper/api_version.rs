@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use zino_core::{extension::TomlTableExt, state::State, LazyLock};
+
+/// Parses the API version from the `x-api-version` header or the `version` parameter
+/// of the `accept` header. The former takes precedence over the latter.
+pub(crate) fn parse_api_version(
+    version_header: Option<&str>,
+    accept_header: Option<&str>,
+) -> Option<u32> {
+    if let Some(version) = version_header.and_then(parse_version) {
+        return Some(version);
+    }
+    accept_header?.split(',').find_map(|media_range| {
+        media_range.split(';').skip(1).find_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            if key.trim().eq_ignore_ascii_case("version") {
+                parse_version(value.trim_matches(|c: char| c.is_whitespace() || c == '"'))
+            } else {
+                None
+            }
+        })
+    })
+}
+
+/// Parses a version number with an optional `v` prefix.
+fn parse_version(version: &str) -> Option<u32> {
+    let version = version.trim();
+    version
+        .strip_prefix(['v', 'V'])
+        .unwrap_or(version)
+        .parse()
+        .ok()
+}
+
+/// Returns the default API version for the route.
+pub(crate) fn default_api_version(route: &str) -> Option<u32> {
+    let versioning = &*API_VERSIONING;
+    versioning
+        .route_versions
+        .get(route)
+        .copied()
+        .or(versioning.default_version)
+}
+
+/// Returns the minimum supported API version.
+#[inline]
+pub(crate) fn min_api_version() -> Option<u32> {
+    API_VERSIONING.min_version
+}
+
+/// API versioning settings.
+#[derive(Debug, Default)]
+struct ApiVersioning {
+    /// Default version.
+    default_version: Option<u32>,
+    /// Minimum supported version.
+    min_version: Option<u32>,
+    /// Default versions for the routes.
+    route_versions: HashMap<String, u32>,
+}
+
+/// API versioning settings.
+static API_VERSIONING: LazyLock<ApiVersioning> = LazyLock::new(|| {
+    let Some(config) = State::shared()
+        .get_config("api")
+        .and_then(|config| config.get_table("versioning"))
+    else {
+        return ApiVersioning::default();
+    };
+
+    let mut route_versions = HashMap::new();
+    if let Some(routes) = config.get_table("routes") {
+        for (route, version) in routes {
+            if let Some(version) = version.as_integer().and_then(|v| u32::try_from(v).ok()) {
+                route_versions.insert(route.to_owned(), version);
+            } else {
+                tracing::warn!("invalid API version for the route `{route}`");
+            }
+        }
+    }
+    ApiVersioning {
+        default_version: config.get_u32("default-version"),
+        min_version: config.get_u32("min-version"),
+        route_versions,
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::parse_api_version;
+
+    #[test]
+    fn it_parses_api_versions() {
+        assert_eq!(parse_api_version(Some("2"), None), Some(2));
+        assert_eq!(
+            parse_api_version(Some("v3"), Some("*/*; version=1")),
+            Some(3)
+        );
+        assert_eq!(
+            parse_api_version(None, Some("application/json; version=2")),
+            Some(2)
+        );
+        assert_eq!(
+            parse_api_version(
+                None,
+                Some("text/html, application/json;q=0.9;version=\"1\"")
+            ),
+            Some(1)
+        );
+        assert_eq!(parse_api_version(None, Some("application/json")), None);
+        assert_eq!(parse_api_version(Some("latest"), None), None);
+    }
+}
@@ -0,0 +1,117 @@
+use cookie::{Cookie, SameSite};
+use std::time::Duration;
+use zino_core::{
+    application::{Agent, Application},
+    crypto,
+    encoding::base64,
+    error::Error,
+    extension::TomlTableExt,
+    state::State,
+    LazyLock, SharedString,
+};
+
+/// Cookie attributes configured by the `[cookie]` table.
+#[derive(Debug)]
+struct CookieSettings {
+    /// The `SameSite` attribute.
+    same_site: SameSite,
+    /// The `Domain` attribute.
+    domain: Option<String>,
+    /// The `Path` attribute. The request path is used if it is not specified.
+    path: Option<String>,
+    /// The `Secure` attribute.
+    secure: bool,
+    /// The `HttpOnly` attribute.
+    http_only: bool,
+}
+
+/// Builds a new cookie with the configured attributes.
+pub(crate) fn build_cookie(
+    name: SharedString,
+    value: SharedString,
+    request_path: &str,
+    max_age: Option<Duration>,
+) -> Cookie<'static> {
+    let settings = &*COOKIE_SETTINGS;
+    let path = settings.path.as_deref().unwrap_or(request_path).to_owned();
+    let mut cookie_builder = Cookie::build((name, value))
+        .http_only(settings.http_only)
+        .secure(settings.secure)
+        .same_site(settings.same_site)
+        .path(path);
+    if let Some(domain) = settings.domain.as_ref() {
+        cookie_builder = cookie_builder.domain(domain.to_owned());
+    }
+    if let Some(max_age) = max_age.and_then(|d| d.try_into().ok()) {
+        cookie_builder = cookie_builder.max_age(max_age);
+    }
+    cookie_builder.build()
+}
+
+/// Encrypts the cookie value bound to the cookie name.
+pub(crate) fn encrypt_cookie_value(name: &str, value: &str) -> Result<String, Error> {
+    let plaintext = format!("{name}={value}");
+    let ciphertext = crypto::encrypt(plaintext.as_bytes(), SECRET_KEY.as_ref())?;
+    Ok(base64::encode(ciphertext))
+}
+
+/// Decrypts the cookie value and verifies that it is bound to the cookie name.
+pub(crate) fn decrypt_cookie_value(name: &str, value: &str) -> Result<String, Error> {
+    let ciphertext = base64::decode(value)?;
+    let plaintext = crypto::decrypt(&ciphertext, SECRET_KEY.as_ref())?;
+    let plaintext = String::from_utf8(plaintext)?;
+    plaintext
+        .strip_prefix(name)
+        .and_then(|s| s.strip_prefix('='))
+        .map(|s| s.to_owned())
+        .ok_or_else(|| Error::new(format!("the cookie value is not bound to `{name}`")))
+}
+
+/// Parses the `SameSite` attribute.
+fn parse_same_site(value: &str) -> Option<SameSite> {
+    match value.to_ascii_lowercase().as_str() {
+        "strict" => Some(SameSite::Strict),
+        "lax" => Some(SameSite::Lax),
+        "none" => Some(SameSite::None),
+        _ => None,
+    }
+}
+
+/// Cookie settings.
+static COOKIE_SETTINGS: LazyLock<CookieSettings> = LazyLock::new(|| {
+    let mut settings = CookieSettings {
+        same_site: SameSite::Lax,
+        domain: None,
+        path: None,
+        secure: true,
+        http_only: true,
+    };
+    if let Some(config) = State::shared().get_config("cookie") {
+        if let Some(same_site) = config.get_str("same-site") {
+            match parse_same_site(same_site) {
+                Some(same_site) => settings.same_site = same_site,
+                None => tracing::warn!("invalid `same-site` value `{same_site}` for the cookies"),
+            }
+        }
+        if let Some(domain) = config.get_str("domain") {
+            settings.domain = Some(domain.to_owned());
+        }
+        if let Some(path) = config.get_str("path") {
+            settings.path = Some(path.to_owned());
+        }
+        if let Some(secure) = config.get_bool("secure") {
+            settings.secure = secure;
+        }
+        if let Some(http_only) = config.get_bool("http-only") {
+            settings.http_only = http_only;
+        }
+        if settings.same_site == SameSite::None && !settings.secure {
+            tracing::warn!("the cookies with `SameSite=None` should be `Secure`");
+        }
+    }
+    settings
+});
+
+/// Secret key for encrypting the private cookies.
+static SECRET_KEY: LazyLock<[u8; 64]> =
+    LazyLock::new(|| crypto::derive_key("ZINO:PRIVATE-COOKIE", Agent::secret_key()));
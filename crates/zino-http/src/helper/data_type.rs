@@ -0,0 +1,181 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, PoisonError, RwLock},
+};
+use zino_core::{error::Error, warn, JsonValue, LazyLock, Map};
+
+/// A deserializer which parses the body bytes into a JSON value.
+pub(crate) type DataDeserializer = Arc<dyn Fn(&[u8]) -> Result<JsonValue, Error> + Send + Sync>;
+
+/// Built-in data types keyed by the essence of the content type or a structural suffix.
+const BUILTIN_DATA_TYPES: [(&str, &str); 13] = [
+    ("application/json", "json"),
+    ("application/jsonlines", "ndjson"),
+    ("application/x-ndjson", "ndjson"),
+    ("application/octet-stream", "bytes"),
+    ("application/x-www-form-urlencoded", "form"),
+    ("application/xml", "xml"),
+    ("multipart/form-data", "multipart"),
+    ("text/csv", "csv"),
+    ("text/tab-separated-values", "tsv"),
+    ("text/plain", "text"),
+    ("text/xml", "xml"),
+    ("+json", "json"),
+    ("+xml", "xml"),
+];
+
+/// Registry of the data types.
+#[derive(Default)]
+struct DataTypeRegistry {
+    /// Custom data types keyed by the essence of the content type or a structural suffix.
+    data_types: HashMap<String, &'static str>,
+    /// Custom deserializers keyed by the data type.
+    deserializers: HashMap<&'static str, DataDeserializer>,
+}
+
+/// Shared registry of the data types.
+static DATA_TYPE_REGISTRY: LazyLock<RwLock<DataTypeRegistry>> =
+    LazyLock::new(|| RwLock::new(DataTypeRegistry::default()));
+
+/// Registers a data type for the content type.
+///
+/// The content type can be an essence like `application/vnd.acme+json`,
+/// or a structural suffix like `+yaml` which applies to all the content types ending with it.
+/// A registered data type takes precedence over the built-in ones.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino_http::request::register_data_type;
+///
+/// register_data_type("application/vnd.acme+json", "json");
+/// register_data_type("application/vnd.acme.report", "csv");
+/// ```
+pub fn register_data_type(content_type: &str, data_type: &'static str) {
+    let content_type = content_type.trim().to_ascii_lowercase();
+    DATA_TYPE_REGISTRY
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .data_types
+        .insert(content_type, data_type);
+}
+
+/// Registers a deserializer for the data type,
+/// which is used to parse the request body of a genuinely new format.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino_http::request::{register_data_deserializer, register_data_type};
+///
+/// register_data_type("application/yaml", "yaml");
+/// register_data_type("+yaml", "yaml");
+/// register_data_deserializer("yaml", |bytes| Ok(serde_yaml::from_slice(bytes)?));
+/// ```
+pub fn register_data_deserializer<F>(data_type: &'static str, deserializer: F)
+where
+    F: Fn(&[u8]) -> Result<JsonValue, Error> + Send + Sync + 'static,
+{
+    DATA_TYPE_REGISTRY
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .deserializers
+        .insert(data_type, Arc::new(deserializer));
+}
+
+/// Gets the data type of the content type.
+/// If the data type is unknown, the essence of the content type will be returned.
+pub(crate) fn get_data_type(content_type: &str) -> &str {
+    let essence = content_type
+        .split_once(';')
+        .map_or(content_type, |(essence, _)| essence)
+        .trim();
+    let key = essence.to_ascii_lowercase();
+    let suffix = key.rfind('+').map(|index| &key[index..]);
+    let registry = DATA_TYPE_REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner);
+    let data_types = &registry.data_types;
+    let builtin_data_type = |key: &str| {
+        BUILTIN_DATA_TYPES
+            .iter()
+            .find_map(|&(content_type, data_type)| (content_type == key).then_some(data_type))
+    };
+    data_types
+        .get(key.as_str())
+        .copied()
+        .or_else(|| builtin_data_type(&key))
+        .or_else(|| suffix.and_then(|suffix| data_types.get(suffix).copied()))
+        .or_else(|| suffix.and_then(builtin_data_type))
+        .unwrap_or(essence)
+}
+
+/// Gets the deserializer for the data type.
+pub(crate) fn get_data_deserializer(data_type: &str) -> Option<DataDeserializer> {
+    DATA_TYPE_REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .deserializers
+        .get(data_type)
+        .cloned()
+}
+
+/// Format of the request body.
+pub(crate) enum BodyFormat {
+    /// URL-encoded form.
+    Form,
+    /// JSON.
+    Json,
+    /// A format parsed by the custom deserializer.
+    Custom(DataDeserializer),
+}
+
+impl BodyFormat {
+    /// Gets the body format for the data type,
+    /// and returns `None` if the deserialization is unsupported.
+    pub(crate) fn with_data_type(data_type: &str) -> Option<Self> {
+        match data_type {
+            "form" => Some(Self::Form),
+            "json" => Some(Self::Json),
+            _ => get_data_deserializer(data_type).map(Self::Custom),
+        }
+    }
+
+    /// Parses the bytes as a JSON object.
+    pub(crate) fn parse_map(&self, bytes: &[u8]) -> Result<Map, Error> {
+        match self {
+            Self::Form => Ok(serde_qs::from_bytes(bytes)?),
+            Self::Json => Ok(serde_json::from_slice(bytes)?),
+            Self::Custom(deserializer) => match deserializer(bytes)? {
+                JsonValue::Object(map) => Ok(map),
+                _ => Err(warn!("the request body should be an object")),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        get_data_deserializer, get_data_type, register_data_deserializer, register_data_type,
+    };
+
+    #[test]
+    fn it_gets_data_type() {
+        assert_eq!(get_data_type("application/json; charset=utf-8"), "json");
+        assert_eq!(get_data_type("application/problem+json"), "json");
+        assert_eq!(get_data_type("Application/LD+JSON"), "json");
+        assert_eq!(get_data_type("application/atom+xml"), "xml");
+        assert_eq!(get_data_type("text/csv; header=present"), "csv");
+        assert_eq!(get_data_type("application/x-ndjson"), "ndjson");
+        assert_eq!(get_data_type("application/yaml"), "application/yaml");
+
+        register_data_type("application/vnd.acme", "json");
+        register_data_type("+yaml", "yaml");
+        register_data_deserializer("yaml", |_| Ok(Default::default()));
+        assert_eq!(get_data_type("application/vnd.acme"), "json");
+        assert_eq!(get_data_type("application/vnd.acme+yaml"), "yaml");
+        assert!(get_data_deserializer("yaml").is_some());
+        assert!(get_data_deserializer("toml").is_none());
+    }
+}
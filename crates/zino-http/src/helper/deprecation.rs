@@ -0,0 +1,136 @@
+use std::sync::Arc;
+use toml::Value;
+use url::Url;
+use zino_core::{datetime::DateTime, extension::TomlTableExt, state::State, LazyLock};
+
+/// Deprecation of a route.
+#[derive(Debug, Clone)]
+pub(crate) struct RouteDeprecation {
+    /// Route prefix.
+    pub(crate) prefix: String,
+    /// Date when the route is deprecated.
+    pub(crate) date: DateTime,
+    /// Link to the deprecation documentation.
+    pub(crate) link: Option<Url>,
+    /// Date when the route will be removed.
+    pub(crate) sunset: Option<DateTime>,
+}
+
+/// Returns the deprecation of the route with the longest matched prefix.
+pub(crate) fn get_route_deprecation(path: &str) -> Option<Arc<RouteDeprecation>> {
+    DEPRECATION_SETTINGS
+        .routes
+        .iter()
+        .filter(|deprecation| matches_prefix(path, &deprecation.prefix))
+        .max_by_key(|deprecation| deprecation.prefix.len())
+        .cloned()
+}
+
+/// Returns `true` if the warnings should be appended to the JSON responses.
+#[inline]
+pub(crate) fn body_warnings_enabled() -> bool {
+    DEPRECATION_SETTINGS.body_warnings
+}
+
+/// Formats the warning message for the deprecation.
+pub(crate) fn format_deprecation_warning(
+    date: &DateTime,
+    link: Option<&Url>,
+    sunset: Option<&DateTime>,
+) -> String {
+    let mut warning = format!("this endpoint is deprecated since {}", date.to_utc_string());
+    if let Some(sunset) = sunset {
+        warning.push_str(&format!(
+            ", and will be removed after {}",
+            sunset.to_utc_string()
+        ));
+    }
+    if let Some(link) = link {
+        warning.push_str(&format!(", see {link}"));
+    }
+    warning
+}
+
+/// Returns `true` if the path matches the prefix at a segment boundary.
+fn matches_prefix(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    path.strip_prefix(prefix)
+        .is_some_and(|suffix| suffix.is_empty() || suffix.starts_with('/') || prefix.is_empty())
+}
+
+/// Parses a date from a string or a TOML datetime.
+fn parse_date(value: &Value) -> Option<DateTime> {
+    let date = match value {
+        Value::String(s) => s.parse().ok(),
+        Value::Datetime(dt) => dt.to_string().parse().ok(),
+        _ => None,
+    };
+    if date.is_none() {
+        tracing::warn!("invalid date `{value:?}` for the deprecation");
+    }
+    date
+}
+
+/// Deprecation settings.
+#[derive(Debug, Default)]
+struct DeprecationSettings {
+    /// A flag to append the warnings to the JSON responses.
+    body_warnings: bool,
+    /// Deprecations of the routes.
+    routes: Vec<Arc<RouteDeprecation>>,
+}
+
+/// Deprecation settings configured by `[api.deprecations]`.
+static DEPRECATION_SETTINGS: LazyLock<DeprecationSettings> = LazyLock::new(|| {
+    let Some(config) = State::shared()
+        .get_config("api")
+        .and_then(|config| config.get_table("deprecations"))
+    else {
+        return DeprecationSettings::default();
+    };
+
+    let mut routes = Vec::new();
+    if let Some(table) = config.get_table("routes") {
+        for (prefix, value) in table {
+            let Some(route) = value.as_table() else {
+                tracing::warn!("invalid deprecation for the route `{prefix}`");
+                continue;
+            };
+            let Some(date) = route.get("date").and_then(parse_date) else {
+                tracing::warn!("the deprecation date for the route `{prefix}` should be specified");
+                continue;
+            };
+            let link = route.get_str("link").and_then(|s| {
+                s.parse::<Url>()
+                    .inspect_err(|err| tracing::warn!("invalid deprecation link `{s}`: {err}"))
+                    .ok()
+            });
+            let sunset = route.get("sunset").and_then(parse_date);
+            routes.push(Arc::new(RouteDeprecation {
+                prefix: prefix.to_owned(),
+                date,
+                link,
+                sunset,
+            }));
+        }
+    }
+    DeprecationSettings {
+        body_warnings: config.get_bool("body-warnings").unwrap_or_default(),
+        routes,
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::matches_prefix;
+
+    #[test]
+    fn it_matches_route_prefixes() {
+        assert!(matches_prefix("/v1/users", "/v1"));
+        assert!(matches_prefix("/v1/users", "/v1/"));
+        assert!(matches_prefix("/v1", "/v1"));
+        assert!(!matches_prefix("/v10/users", "/v1"));
+        assert!(!matches_prefix("/v2/users", "/v1"));
+        assert!(matches_prefix("/v2/users", "/"));
+    }
+}
@@ -0,0 +1,170 @@
+use std::mem;
+use zino_core::{extension::TomlTableExt, state::State, JsonValue, LazyLock, Map};
+
+/// Case of the keys in JSON objects.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum KeyCase {
+    /// The `snake_case` keys used by the models.
+    #[default]
+    Snake,
+    /// The `camelCase` keys.
+    Camel,
+}
+
+impl KeyCase {
+    /// Parses the key case.
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "snake" | "snake_case" => Some(Self::Snake),
+            "camel" | "camelCase" => Some(Self::Camel),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the key case with an optional value of the `x-key-case` header.
+pub(crate) fn get_key_case(header_value: Option<&str>) -> KeyCase {
+    header_value
+        .and_then(KeyCase::parse)
+        .unwrap_or(*DEFAULT_KEY_CASE)
+}
+
+/// Translates the keys of JSON objects recursively in place.
+/// The values of the excluded keys are retained as they are.
+pub(crate) fn translate_keys(value: &mut JsonValue, key_case: KeyCase) {
+    match value {
+        JsonValue::Object(map) => translate_object_keys(map, key_case),
+        JsonValue::Array(vec) => {
+            for value in vec {
+                translate_keys(value, key_case);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Translates the keys of a JSON object recursively in place.
+/// The values of the excluded keys are retained as they are.
+pub(crate) fn translate_object_keys(map: &mut Map, key_case: KeyCase) {
+    let exclusions = KEY_CASE_EXCLUSIONS.as_slice();
+    for (key, mut value) in mem::take(map) {
+        let key = match key_case {
+            KeyCase::Snake => {
+                let key = to_snake_case(key);
+                if !exclusions.contains(&key.as_str()) {
+                    translate_keys(&mut value, key_case);
+                }
+                key
+            }
+            KeyCase::Camel => {
+                if !exclusions.contains(&key.as_str()) {
+                    translate_keys(&mut value, key_case);
+                }
+                to_camel_case(key)
+            }
+        };
+        map.insert(key, value);
+    }
+}
+
+/// Converts a `snake_case` key into `camelCase`.
+/// An underscore is retained if it is not followed by a lowercase letter.
+fn to_camel_case(key: String) -> String {
+    if !key.contains('_') {
+        return key;
+    }
+
+    let mut camel_key = String::with_capacity(key.len());
+    let mut chars = key.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '_' && !camel_key.is_empty() {
+            if let Some(&next) = chars.peek() {
+                if next.is_ascii_lowercase() {
+                    camel_key.push(next.to_ascii_uppercase());
+                    chars.next();
+                    continue;
+                }
+            }
+        }
+        camel_key.push(c);
+    }
+    camel_key
+}
+
+/// Converts a `camelCase` key into `snake_case`.
+/// The consecutive uppercase letters are treated as a single word.
+fn to_snake_case(key: String) -> String {
+    if !key.chars().any(|c| c.is_ascii_uppercase()) {
+        return key;
+    }
+
+    let chars = key.chars().collect::<Vec<_>>();
+    let mut snake_key = String::with_capacity(key.len() + 4);
+    for (index, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() {
+            if index > 0 {
+                let prev = chars[index - 1];
+                let next_is_lowercase = chars
+                    .get(index + 1)
+                    .is_some_and(|next| next.is_ascii_lowercase());
+                if prev.is_ascii_lowercase()
+                    || prev.is_ascii_digit()
+                    || (prev.is_ascii_uppercase() && next_is_lowercase)
+                {
+                    snake_key.push('_');
+                }
+            }
+            snake_key.push(c.to_ascii_lowercase());
+        } else {
+            snake_key.push(c);
+        }
+    }
+    snake_key
+}
+
+/// Default key case.
+static DEFAULT_KEY_CASE: LazyLock<KeyCase> = LazyLock::new(|| {
+    State::shared()
+        .get_config("response")
+        .and_then(|config| config.get_str("key-case"))
+        .and_then(|key_case| {
+            let value = KeyCase::parse(key_case);
+            if value.is_none() {
+                tracing::warn!("unsupported key case `{key_case}`");
+            }
+            value
+        })
+        .unwrap_or_default()
+});
+
+/// Keys whose values are excluded from the key translation.
+static KEY_CASE_EXCLUSIONS: LazyLock<Vec<&'static str>> = LazyLock::new(|| {
+    State::shared()
+        .get_config("response")
+        .and_then(|config| config.get_str_array("key-case-exclusions"))
+        .unwrap_or_default()
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{to_camel_case, to_snake_case};
+
+    #[test]
+    fn it_translates_key_cases() {
+        let keys = [
+            ("created_at", "createdAt"),
+            ("user_id", "userId"),
+            ("num_items", "numItems"),
+            ("address_1", "address_1"),
+            ("_id", "_id"),
+            ("name", "name"),
+            ("$search", "$search"),
+        ];
+        for (snake_key, camel_key) in keys {
+            assert_eq!(to_camel_case(snake_key.to_owned()), camel_key);
+            assert_eq!(to_snake_case(camel_key.to_owned()), snake_key);
+        }
+        assert_eq!(to_snake_case("requestID".to_owned()), "request_id");
+        assert_eq!(to_snake_case("HTTPServer".to_owned()), "http_server");
+    }
+}
@@ -1,14 +1,36 @@
 /// Helper utilities.
+mod accept;
+mod api_version;
+mod data_type;
+mod deprecation;
 mod form_data;
 mod header;
+mod key_case;
+mod ndjson;
+mod precondition;
 mod query;
 
+#[cfg(feature = "cookie")]
+mod cookie;
 #[cfg(feature = "i18n")]
 mod language;
 
+pub(crate) use accept::select_content_type;
+pub(crate) use api_version::{default_api_version, min_api_version, parse_api_version};
+pub(crate) use data_type::{get_data_type, BodyFormat};
+pub(crate) use deprecation::{
+    body_warnings_enabled, format_deprecation_warning, get_route_deprecation,
+};
 pub(crate) use form_data::parse_form_data;
-pub(crate) use header::{check_json_content_type, displayed_inline, get_data_type};
+pub(crate) use header::displayed_inline;
+pub(crate) use key_case::{get_key_case, translate_keys, translate_object_keys, KeyCase};
+pub(crate) use ndjson::parse_ndjson_lines;
+pub(crate) use precondition::{parse_if_match, require_preconditions};
 pub(crate) use query::format_query;
 
+pub use data_type::{register_data_deserializer, register_data_type};
+
+#[cfg(feature = "cookie")]
+pub(crate) use cookie::{build_cookie, decrypt_cookie_value, encrypt_cookie_value};
 #[cfg(feature = "i18n")]
 pub(crate) use language::select_language;
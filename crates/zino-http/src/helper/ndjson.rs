@@ -0,0 +1,163 @@
+use super::KeyCase;
+use futures::{stream, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use zino_core::{
+    error::{Error, ErrorKind},
+    extension::TomlTableExt,
+    state::State,
+    LazyLock,
+};
+
+/// Parses the NDJSON body stream line by line.
+///
+/// Each item is paired with its line number. A line which can not be deserialized
+/// yields an error without terminating the stream, while the body errors and the violations
+/// of the line size limit or the line count limit are the last items of the stream.
+pub(crate) fn parse_ndjson_lines<T, S>(
+    body: S,
+    key_case: KeyCase,
+) -> impl Stream<Item = (usize, Result<T, Error>)>
+where
+    T: DeserializeOwned,
+    S: Stream<Item = Result<Vec<u8>, Error>> + Unpin,
+{
+    let (max_line_size, max_lines) = *NDJSON_LIMITS;
+    let state = NdjsonState {
+        body,
+        buffer: Vec::new(),
+        line_number: 0,
+        eof: false,
+        done: false,
+    };
+    stream::unfold(state, move |mut state| async move {
+        loop {
+            if state.done {
+                return None;
+            }
+
+            let line = if let Some(index) = state.buffer.iter().position(|&b| b == b'\n') {
+                state.buffer.drain(..=index).collect::<Vec<_>>()
+            } else if state.buffer.len() > max_line_size {
+                state.done = true;
+                let line_number = state.line_number + 1;
+                let message =
+                    format!("line {line_number} exceeds the size limit of {max_line_size} bytes");
+                let err = Error::new(message).with_kind(ErrorKind::Validation);
+                return Some(((line_number, Err(err)), state));
+            } else if state.eof {
+                if state.buffer.is_empty() {
+                    return None;
+                }
+                state.buffer.split_off(0)
+            } else {
+                match state.body.next().await {
+                    Some(Ok(chunk)) => state.buffer.extend_from_slice(&chunk),
+                    Some(Err(err)) => {
+                        state.done = true;
+                        return Some(((state.line_number, Err(err)), state));
+                    }
+                    None => state.eof = true,
+                }
+                continue;
+            };
+
+            state.line_number += 1;
+            let line_number = state.line_number;
+            if line.len() > max_line_size + 1 {
+                state.done = true;
+                let message =
+                    format!("line {line_number} exceeds the size limit of {max_line_size} bytes");
+                let err = Error::new(message).with_kind(ErrorKind::Validation);
+                return Some(((line_number, Err(err)), state));
+            }
+            if line_number > max_lines {
+                state.done = true;
+                let message = format!("the body exceeds the limit of {max_lines} lines");
+                let err = Error::new(message).with_kind(ErrorKind::Validation);
+                return Some(((line_number, Err(err)), state));
+            }
+
+            let line = line.trim_ascii();
+            if !line.is_empty() {
+                let result = parse_line(line, key_case).map_err(|err| {
+                    let message = format!("fail to parse line {line_number}: {err}");
+                    Error::new(message).with_kind(ErrorKind::Validation)
+                });
+                return Some(((line_number, result), state));
+            }
+        }
+    })
+}
+
+/// Deserializes a line of the NDJSON body.
+fn parse_line<T: DeserializeOwned>(line: &[u8], key_case: KeyCase) -> Result<T, serde_json::Error> {
+    if key_case == KeyCase::Camel {
+        let mut data = serde_json::from_slice(line)?;
+        super::translate_keys(&mut data, KeyCase::Snake);
+        serde_json::from_value(data)
+    } else {
+        serde_json::from_slice(line)
+    }
+}
+
+/// Internal state of the NDJSON parser.
+struct NdjsonState<S> {
+    /// Body stream.
+    body: S,
+    /// Buffered bytes which have not been parsed.
+    buffer: Vec<u8>,
+    /// Number of lines which have been read.
+    line_number: usize,
+    /// A flag to indicate whether the body stream is exhausted.
+    eof: bool,
+    /// A flag to indicate whether the parsing should be stopped.
+    done: bool,
+}
+
+/// Limits of the line size and the line count for the NDJSON body.
+static NDJSON_LIMITS: LazyLock<(usize, usize)> = LazyLock::new(|| {
+    let mut max_line_size = 1024 * 1024; // 1MB
+    let mut max_lines = 1_000_000;
+    if let Some(config) = State::shared()
+        .get_config("api")
+        .and_then(|config| config.get_table("ndjson"))
+    {
+        if let Some(size) = config.get_usize("max-line-size") {
+            max_line_size = size;
+        }
+        if let Some(lines) = config.get_usize("max-lines") {
+            max_lines = lines;
+        }
+    }
+    (max_line_size, max_lines)
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_ndjson_lines, KeyCase};
+    use futures::{executor, stream, StreamExt};
+    use zino_core::{error::Error, Map};
+
+    #[test]
+    fn it_parses_ndjson_lines() {
+        let chunks: Vec<Result<Vec<u8>, Error>> = vec![
+            Ok(br#"{"name": "a"}"#.to_vec()),
+            Ok(b"\n\n{\"name\": ".to_vec()),
+            Ok(b"\"b\"}\r\nnot json\n".to_vec()),
+            Ok(br#"{"name": "c"}"#.to_vec()),
+        ];
+        let body = stream::iter(chunks);
+        let lines = executor::block_on(
+            parse_ndjson_lines::<Map, _>(body, KeyCase::Snake).collect::<Vec<_>>(),
+        );
+        let line_numbers = lines.iter().map(|(n, _)| *n).collect::<Vec<_>>();
+        assert_eq!(line_numbers, vec![1, 3, 4, 5]);
+        assert!(lines[0].1.is_ok());
+        assert_eq!(
+            lines[1].1.as_ref().ok().and_then(|m| m.get("name")),
+            Some(&"b".into())
+        );
+        assert!(lines[2].1.is_err());
+        assert!(lines[3].1.is_ok());
+    }
+}
@@ -0,0 +1,57 @@
+use zino_core::{extension::TomlTableExt, state::State, LazyLock};
+
+/// Parses the versions from the `if-match` header.
+///
+/// It returns `None` for the wildcard `*`, and an error if none of the entity tags
+/// can match a version since the weak comparison is not allowed.
+pub(crate) fn parse_if_match(if_match: &str) -> Result<Option<Vec<u64>>, ()> {
+    let if_match = if_match.trim();
+    if if_match == "*" {
+        return Ok(None);
+    }
+
+    let versions = if_match
+        .split(',')
+        .filter_map(|etag| {
+            let etag = etag.trim();
+            if etag.starts_with("W/") {
+                None
+            } else {
+                etag.trim_matches('"').parse().ok()
+            }
+        })
+        .collect::<Vec<_>>();
+    if versions.is_empty() {
+        Err(())
+    } else {
+        Ok(Some(versions))
+    }
+}
+
+/// Returns `true` if the preconditions are required for the update and delete requests.
+#[inline]
+pub(crate) fn require_preconditions() -> bool {
+    *REQUIRE_PRECONDITIONS
+}
+
+/// A flag to indicate whether the preconditions are required.
+static REQUIRE_PRECONDITIONS: LazyLock<bool> = LazyLock::new(|| {
+    State::shared()
+        .get_config("api")
+        .and_then(|config| config.get_bool("require-preconditions"))
+        .unwrap_or(false)
+});
+
+#[cfg(test)]
+mod tests {
+    use super::parse_if_match;
+
+    #[test]
+    fn it_parses_if_match() {
+        assert_eq!(parse_if_match("*"), Ok(None));
+        assert_eq!(parse_if_match(r#""3""#), Ok(Some(vec![3])));
+        assert_eq!(parse_if_match(r#""3", W/"4", "5""#), Ok(Some(vec![3, 5])));
+        assert_eq!(parse_if_match(r#"W/"3""#), Err(()));
+        assert_eq!(parse_if_match(r#""abc""#), Err(()));
+    }
+}
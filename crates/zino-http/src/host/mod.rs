@@ -0,0 +1,299 @@
+//! Host header validation against DNS rebinding and cache poisoning.
+
+use std::fmt;
+use toml::Table;
+use zino_core::{application::ServerTag, extension::TomlTableExt, state::State, LazyLock};
+
+mod pattern;
+
+use pattern::HostPattern;
+
+/// A policy which validates the `Host` header (or the `:authority` pseudo-header)
+/// before any handler runs.
+///
+/// The default policy is configured by the `[server]` table. The validation is disabled
+/// if `allowed-hosts` is not specified, or `allow-any-host` is turned on for development.
+///
+/// # Examples
+///
+/// ```toml
+/// [server]
+/// allowed-hosts = ["api.example.com", "*.internal.example.com", "localhost:6080"]
+/// allow-any-host = false
+/// host-exempt-routes = ["/health"]
+/// ```
+///
+/// A pattern without the port matches any port, and `*.internal.example.com` matches
+/// the subdomains of `internal.example.com` but not itself.
+/// A listener configured by `[[server.listener]]` can have its own `allowed-hosts`,
+/// `allow-any-host` and `host-exempt-routes`, whose missing fields are inherited
+/// from `[server]`, so that an internal listener for health checks and metrics can be
+/// exempted or restricted to its own list.
+#[derive(Debug, Clone)]
+pub struct HostPolicy {
+    /// Allowed host patterns.
+    allowed_hosts: Vec<HostPattern>,
+    /// A flag to indicate whether any host is allowed.
+    allow_any_host: bool,
+    /// Routes which are exempt from the validation.
+    exempt_routes: Vec<String>,
+}
+
+impl HostPolicy {
+    /// Creates a new instance with the configuration.
+    pub fn with_config(config: &Table) -> Self {
+        let mut policy = Self::default();
+        policy.read_config(config);
+        policy
+    }
+
+    /// Returns the default policy configured by the `[server]` table.
+    #[inline]
+    pub fn shared() -> &'static Self {
+        &HOST_POLICY
+    }
+
+    /// Returns the policy for the listener with the scope.
+    ///
+    /// It falls back to [`shared()`](Self::shared) if the listener
+    /// does not override the config.
+    pub fn for_scope(scope: &ServerTag) -> &'static Self {
+        SCOPED_HOST_POLICIES
+            .iter()
+            .find(|(server_tag, _)| server_tag == scope)
+            .map(|(_, policy)| policy)
+            .unwrap_or_else(Self::shared)
+    }
+
+    /// Returns `true` if the host validation is enabled.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        !self.allow_any_host && !self.allowed_hosts.is_empty()
+    }
+
+    /// Returns `true` if the route path is exempt from the validation.
+    pub fn is_exempt(&self, path: &str) -> bool {
+        self.exempt_routes.iter().any(|route| {
+            let route = route.trim_end_matches('/');
+            path.strip_prefix(route)
+                .is_some_and(|s| s.is_empty() || s.starts_with('/'))
+        })
+    }
+
+    /// Returns `true` if the host is allowed. The port portion is optional.
+    pub fn is_host_allowed(&self, host: &str) -> bool {
+        if !self.is_enabled() {
+            return true;
+        }
+        pattern::split_host_port(host).is_some_and(|(hostname, port)| {
+            self.allowed_hosts
+                .iter()
+                .any(|pattern| pattern.matches(&hostname, port))
+        })
+    }
+
+    /// Returns the first allowed host without wildcards,
+    /// which can be used to generate the absolute URLs.
+    pub fn canonical_host(&self) -> Option<String> {
+        self.allowed_hosts
+            .iter()
+            .find_map(|pattern| pattern.exact_host())
+    }
+
+    /// Validates the request with the route path, the authority of the request target
+    /// and the `Host` header.
+    ///
+    /// The authority is present for an absolute-form request target in HTTP/1.1,
+    /// or as the `:authority` pseudo-header in HTTP/2, and it takes precedence over
+    /// the `Host` header. If both of them are present, they should be identical.
+    pub fn validate(
+        &self,
+        path: &str,
+        authority: Option<&str>,
+        host: Option<&str>,
+    ) -> Result<(), HostRejection> {
+        if !self.is_enabled() || self.is_exempt(path) {
+            return Ok(());
+        }
+
+        let (target, header) = match (authority, host) {
+            (Some(authority), host) => (authority, host),
+            (None, Some(host)) => (host, None),
+            (None, None) => return Err(HostRejection::Missing),
+        };
+        let (hostname, port) = pattern::split_host_port(target).ok_or(HostRejection::Malformed)?;
+        if let Some(header) = header {
+            if pattern::split_host_port(header).as_ref() != Some(&(hostname.clone(), port)) {
+                return Err(HostRejection::Mismatched);
+            }
+        }
+        if self
+            .allowed_hosts
+            .iter()
+            .any(|pattern| pattern.matches(&hostname, port))
+        {
+            Ok(())
+        } else {
+            Err(HostRejection::Misdirected)
+        }
+    }
+
+    /// Reads the configuration and overrides the fields.
+    fn read_config(&mut self, config: &Table) {
+        if let Some(hosts) = config.get_str_array("allowed-hosts") {
+            self.allowed_hosts = hosts
+                .into_iter()
+                .filter_map(|host| {
+                    let pattern = HostPattern::parse(host);
+                    if pattern.is_none() {
+                        tracing::warn!("invalid host pattern `{host}` in `allowed-hosts`");
+                    }
+                    pattern
+                })
+                .collect();
+        }
+        if let Some(allow_any_host) = config.get_bool("allow-any-host") {
+            self.allow_any_host = allow_any_host;
+        }
+        if let Some(routes) = config.get_str_array("host-exempt-routes") {
+            self.exempt_routes = routes.into_iter().map(|route| route.to_owned()).collect();
+        }
+    }
+}
+
+impl Default for HostPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_hosts: Vec::new(),
+            allow_any_host: false,
+            exempt_routes: vec!["/health".to_owned()],
+        }
+    }
+}
+
+/// Reason for rejecting a request by the [`HostPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HostRejection {
+    /// Neither the `Host` header nor the authority is present.
+    Missing,
+    /// The host is malformed.
+    Malformed,
+    /// The `Host` header does not match the authority of the request target.
+    Mismatched,
+    /// The host is not allowed.
+    Misdirected,
+}
+
+impl HostRejection {
+    /// Returns the status code of the response:
+    /// `421 Misdirected Request` for a disallowed host, or `400 Bad Request` otherwise.
+    #[inline]
+    pub fn status_code(&self) -> u16 {
+        match self {
+            Self::Misdirected => 421,
+            _ => 400,
+        }
+    }
+
+    /// Returns the message of the response.
+    #[inline]
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::Missing => "the `host` header is missing",
+            Self::Malformed => "the `host` header is malformed",
+            Self::Mismatched => "the `host` header does not match the request target",
+            Self::Misdirected => "the host is not allowed",
+        }
+    }
+}
+
+impl fmt::Display for HostRejection {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+/// The default host policy.
+static HOST_POLICY: LazyLock<HostPolicy> = LazyLock::new(|| {
+    State::shared()
+        .get_config("server")
+        .map(HostPolicy::with_config)
+        .unwrap_or_default()
+});
+
+/// The host policies overridden by the listeners.
+static SCOPED_HOST_POLICIES: LazyLock<Vec<(ServerTag, HostPolicy)>> = LazyLock::new(|| {
+    let keys = ["allowed-hosts", "allow-any-host", "host-exempt-routes"];
+    State::shared()
+        .server_listeners()
+        .into_iter()
+        .filter_map(|listener| {
+            let config = listener.config();
+            if !keys.iter().any(|key| config.contains_key(*key)) {
+                return None;
+            }
+
+            let mut policy = HOST_POLICY.clone();
+            policy.read_config(config);
+            Some((listener.scope().clone(), policy))
+        })
+        .collect()
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{HostPolicy, HostRejection};
+
+    #[test]
+    fn it_validates_hosts() {
+        let config = r#"
+            allowed-hosts = ["api.example.com", "*.internal.example.com", "[::1]:6080"]
+        "#;
+        let policy = HostPolicy::with_config(&config.parse().unwrap());
+        assert!(policy.is_host_allowed("api.example.com"));
+        assert!(policy.is_host_allowed("API.Example.com:8443"));
+        assert!(policy.is_host_allowed("db.internal.example.com."));
+        assert!(policy.is_host_allowed("[::1]:6080"));
+        assert!(!policy.is_host_allowed("[::1]:6081"));
+        assert!(!policy.is_host_allowed("internal.example.com"));
+        assert!(!policy.is_host_allowed("api.example.com.evil.cc"));
+        assert!(!policy.is_host_allowed("evil.cc@api.example.com"));
+        assert_eq!(policy.canonical_host().as_deref(), Some("api.example.com"));
+
+        assert_eq!(
+            policy.validate("/users", None, Some("api.example.com")),
+            Ok(())
+        );
+        assert_eq!(policy.validate("/health", None, Some("10.0.0.1")), Ok(()));
+        assert_eq!(
+            policy.validate("/users", None, Some("evil.cc")),
+            Err(HostRejection::Misdirected)
+        );
+        assert_eq!(
+            policy.validate("/users", None, None),
+            Err(HostRejection::Missing)
+        );
+        assert_eq!(
+            policy.validate("/users", None, Some("api.example.com:http")),
+            Err(HostRejection::Malformed)
+        );
+        assert_eq!(
+            policy.validate("/users", Some("api.example.com"), Some("evil.cc")),
+            Err(HostRejection::Mismatched)
+        );
+        assert_eq!(
+            policy.validate("/users", Some("api.example.com:443"), None),
+            Ok(())
+        );
+
+        let config = r#"
+            allowed-hosts = ["api.example.com"]
+            allow-any-host = true
+        "#;
+        let policy = HostPolicy::with_config(&config.parse().unwrap());
+        assert!(!policy.is_enabled());
+        assert_eq!(policy.validate("/users", None, Some("evil.cc")), Ok(()));
+    }
+}
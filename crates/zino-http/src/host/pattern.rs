@@ -0,0 +1,89 @@
+use std::net::Ipv6Addr;
+
+/// A pattern of the allowed hosts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum HostPattern {
+    /// An exact hostname such as `api.example.com`.
+    Exact(String, Option<u16>),
+    /// A wildcard subdomain pattern such as `*.internal.example.com`,
+    /// where the suffix is stored with the leading dot.
+    Wildcard(String, Option<u16>),
+}
+
+impl HostPattern {
+    /// Parses the pattern. A pattern without the port matches any port.
+    pub(super) fn parse(pattern: &str) -> Option<Self> {
+        let pattern = pattern.trim();
+        if let Some(pattern) = pattern.strip_prefix("*.") {
+            let (hostname, port) = split_host_port(pattern)?;
+            Some(Self::Wildcard(format!(".{hostname}"), port))
+        } else {
+            let (hostname, port) = split_host_port(pattern)?;
+            Some(Self::Exact(hostname, port))
+        }
+    }
+
+    /// Returns `true` if the hostname and the port match the pattern.
+    pub(super) fn matches(&self, hostname: &str, port: Option<u16>) -> bool {
+        let (matched, expected_port) = match self {
+            Self::Exact(name, expected_port) => (hostname == name, expected_port),
+            Self::Wildcard(suffix, expected_port) => {
+                let matched = hostname.len() > suffix.len() && hostname.ends_with(suffix.as_str());
+                (matched, expected_port)
+            }
+        };
+        matched && (expected_port.is_none() || port == *expected_port)
+    }
+
+    /// Returns the host if it is an exact pattern.
+    pub(super) fn exact_host(&self) -> Option<String> {
+        match self {
+            Self::Exact(hostname, Some(port)) => Some(format!("{hostname}:{port}")),
+            Self::Exact(hostname, None) => Some(hostname.clone()),
+            Self::Wildcard(..) => None,
+        }
+    }
+}
+
+/// Splits the host into the lowercase hostname and the optional port.
+/// It returns `None` if the host is malformed.
+pub(super) fn split_host_port(host: &str) -> Option<(String, Option<u16>)> {
+    let host = host.trim();
+    let (hostname, port) = if let Some(host) = host.strip_prefix('[') {
+        let (ip, port) = host.split_once(']')?;
+        ip.parse::<Ipv6Addr>().ok()?;
+        let port = if port.is_empty() {
+            None
+        } else {
+            Some(port.strip_prefix(':')?)
+        };
+        (format!("[{}]", ip.to_ascii_lowercase()), port)
+    } else {
+        let (hostname, port) = match host.rsplit_once(':') {
+            Some((hostname, port)) => (hostname, Some(port)),
+            None => (host, None),
+        };
+        let hostname = hostname.strip_suffix('.').unwrap_or(hostname);
+        let is_valid = !hostname.is_empty()
+            && hostname.len() <= 253
+            && hostname.split('.').all(|label| {
+                !label.is_empty()
+                    && label.len() <= 63
+                    && label
+                        .bytes()
+                        .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+            });
+        if !is_valid {
+            return None;
+        }
+        (hostname.to_ascii_lowercase(), port)
+    };
+    let port = match port {
+        Some(port) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => {
+            Some(port.parse::<u16>().ok()?)
+        }
+        Some(_) => return None,
+        None => None,
+    };
+    Some((hostname, port))
+}
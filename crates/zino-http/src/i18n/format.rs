@@ -0,0 +1,109 @@
+use unic_langid::LanguageIdentifier;
+use zino_core::{datetime::DateTime, money::Money};
+
+/// Formats the number with the grouping and decimal separators of the locale.
+pub fn format_number(locale: &LanguageIdentifier, number: f64) -> String {
+    if !number.is_finite() {
+        return number.to_string();
+    }
+
+    let negative = number.is_sign_negative() && number != 0.0;
+    format_digits(locale, &number.abs().to_string(), negative)
+}
+
+/// Formats the money with the separators of the locale,
+/// rounding the amount to the minor unit of the currency.
+pub fn format_money(locale: &LanguageIdentifier, money: &Money) -> String {
+    let money = money.round();
+    let amount = money.amount();
+    let digits = format!("{:.*}", money.exponent() as usize, amount.abs());
+    let number = format_digits(locale, &digits, money.is_negative());
+    format!("{number} {}", money.currency())
+}
+
+/// Formats the digits with the grouping and decimal separators of the locale.
+fn format_digits(locale: &LanguageIdentifier, digits: &str, negative: bool) -> String {
+    let (group_separator, decimal_separator) = number_separators(locale);
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let mut output = String::with_capacity(digits.len() + integer.len() / 3 * 3 + 1);
+    if negative {
+        output.push('-');
+    }
+    for (index, ch) in integer.char_indices() {
+        if index > 0 && (integer.len() - index) % 3 == 0 {
+            output.push_str(group_separator);
+        }
+        output.push(ch);
+    }
+    if !fraction.is_empty() {
+        output.push_str(decimal_separator);
+        output.push_str(fraction);
+    }
+    output
+}
+
+/// Formats the date-time with the conventional pattern of the locale.
+pub fn format_datetime(locale: &LanguageIdentifier, datetime: &DateTime) -> String {
+    datetime.format(datetime_pattern(locale))
+}
+
+/// Returns the grouping and decimal separators of the locale.
+fn number_separators(locale: &LanguageIdentifier) -> (&'static str, &'static str) {
+    let region = locale.region.as_ref().map(|region| region.as_str());
+    match (locale.language.as_str(), region) {
+        ("de" | "it", Some("CH" | "LI")) => ("\u{2019}", "."),
+        ("fr", _) => ("\u{202f}", ","),
+        ("pt", Some("PT")) => ("\u{a0}", ","),
+        (
+            "bg" | "cs" | "et" | "fi" | "hu" | "lt" | "lv" | "nb" | "no" | "pl" | "ru" | "sk"
+            | "sv" | "uk",
+            _,
+        ) => ("\u{a0}", ","),
+        (
+            "da" | "de" | "el" | "es" | "hr" | "id" | "it" | "nl" | "pt" | "ro" | "sl" | "sr"
+            | "tr" | "vi",
+            _,
+        ) => (".", ","),
+        _ => (",", "."),
+    }
+}
+
+/// Returns the date-time pattern of the locale.
+fn datetime_pattern(locale: &LanguageIdentifier) -> &'static str {
+    let region = locale.region.as_ref().map(|region| region.as_str());
+    match (locale.language.as_str(), region) {
+        ("en", Some("US") | None) => "%m/%d/%Y, %I:%M:%S %p",
+        ("en", _) => "%d/%m/%Y, %H:%M:%S",
+        ("zh" | "ja", _) => "%Y/%m/%d %H:%M:%S",
+        ("ko", _) => "%Y. %m. %d. %H:%M:%S",
+        ("nl", _) => "%d-%m-%Y %H:%M:%S",
+        ("cs" | "de" | "fi" | "nb" | "no" | "pl" | "ro" | "ru" | "tr" | "uk", _) => {
+            "%d.%m.%Y, %H:%M:%S"
+        }
+        ("el" | "es" | "fr" | "id" | "it" | "pt" | "vi", _) => "%d/%m/%Y %H:%M:%S",
+        _ => "%Y-%m-%d %H:%M:%S",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_money, format_number};
+    use unic_langid::LanguageIdentifier;
+
+    #[test]
+    fn it_formats_numbers() {
+        let en = "en-US".parse::<LanguageIdentifier>().unwrap();
+        let de = "de-DE".parse::<LanguageIdentifier>().unwrap();
+        let fr = "fr".parse::<LanguageIdentifier>().unwrap();
+        assert_eq!(format_number(&en, 1234567.5), "1,234,567.5");
+        assert_eq!(format_number(&en, -1234.0), "-1,234");
+        assert_eq!(format_number(&en, 999.0), "999");
+        assert_eq!(format_number(&de, 1234567.25), "1.234.567,25");
+        assert_eq!(format_number(&fr, 12345.0), "12\u{202f}345");
+        assert_eq!(format_number(&en, f64::NAN), "NaN");
+
+        let money = "-1234567.125 EUR".parse().unwrap();
+        assert_eq!(format_money(&en, &money), "-1,234,567.12 EUR");
+        assert_eq!(format_money(&de, &money), "-1.234.567,12 EUR");
+    }
+}
@@ -1,35 +1,177 @@
 //! Internationalization and localization.
 
-use fluent::{bundle::FluentBundle, FluentArgs, FluentResource};
+use fluent::{bundle::FluentBundle, FluentArgs, FluentResource, FluentValue};
+use fluent_syntax::ast::Entry;
 use intl_memoizer::concurrent::IntlLangMemoizer;
-use std::{fs, io::ErrorKind};
+use std::{
+    collections::HashMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+};
 use unic_langid::LanguageIdentifier;
 use zino_core::{
     application::{Agent, Application},
     bail,
+    datetime::DateTime,
     error::Error,
     extension::TomlTableExt,
+    model::DefinitionCache,
     state::State,
-    warn, LazyLock, SharedString,
+    warn, JsonValue, LazyLock, Map, SharedString,
 };
 
+mod format;
+
+#[cfg(feature = "i18n-watch")]
+mod watcher;
+
+pub use format::{format_datetime, format_money, format_number};
+
 /// Translates the localization message.
 pub fn translate(
     locale: &LanguageIdentifier,
     message: &str,
     args: Option<FluentArgs<'_>>,
 ) -> Result<SharedString, Error> {
-    let bundle = LOCALIZATION
+    let localization = Localization::current();
+    let bundle = localization
+        .find_bundle(locale)
+        .or_else(|| localization.default_bundle())
+        .ok_or_else(|| warn!("the localization bundle does not exits"))?;
+    let result = format_message(bundle, message, args.as_ref());
+    if result.is_err() {
+        record_missing_translation(locale);
+    }
+    result
+}
+
+/// Translates the localization message with a fallback.
+///
+/// If the locale or the message is missing, it returns the message of the default locale
+/// with a warning. If the message is still missing, the message ID itself is returned.
+pub fn translate_or_fallback(
+    locale: &LanguageIdentifier,
+    message: &str,
+    args: Option<FluentArgs<'_>>,
+) -> SharedString {
+    let localization = Localization::current();
+    if let Some(bundle) = localization.find_bundle(locale) {
+        match format_message(bundle, message, args.as_ref()) {
+            Ok(value) => return value,
+            Err(err) => tracing::warn!(locale = %locale, message, "{err}"),
+        }
+    } else {
+        tracing::warn!(locale = %locale, message, "the localization bundle does not exits");
+    }
+    record_missing_translation(locale);
+    localization
+        .default_bundle()
+        .and_then(|bundle| format_message(bundle, message, args.as_ref()).ok())
+        .unwrap_or_else(|| message.to_owned().into())
+}
+
+/// Translates the localization message if it exists in the bundle of the locale.
+///
+/// Unlike [`translate()`], a missing message is not recorded as a missing translation,
+/// thus it can be used for the text which may or may not be a message ID.
+pub fn try_translate(locale: &LanguageIdentifier, message: &str) -> Option<SharedString> {
+    let localization = Localization::current();
+    let bundle = localization.find_bundle(locale)?;
+    if bundle.has_message(message) {
+        format_message(bundle, message, None).ok()
+    } else {
+        None
+    }
+}
+
+/// Translates the localization message for the plural form,
+/// where the count is injected as the `$count` argument.
+///
+/// # Examples
+///
+/// ```ftl
+/// unread-emails =
+///     { $count ->
+///         [one] You have one unread email.
+///        *[other] You have { $count } unread emails.
+///     }
+/// ```
+pub fn translate_plural<'a>(
+    locale: &LanguageIdentifier,
+    message: &str,
+    count: impl Into<FluentValue<'a>>,
+    args: Option<FluentArgs<'a>>,
+) -> SharedString {
+    let mut args = args.unwrap_or_default();
+    args.set("count", count);
+    translate_or_fallback(locale, message, Some(args))
+}
+
+/// Returns the stats of the loaded locales, message IDs and missing translations.
+pub fn stats() -> Map {
+    let localization = Localization::current();
+    let missing_translations = MISSING_TRANSLATIONS
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone();
+    let locales = localization
+        .bundles
         .iter()
-        .find_map(|(lang_id, bundle)| (lang_id == locale).then_some(bundle))
-        .or_else(|| {
-            let lang = locale.language;
-            LOCALIZATION
+        .map(|locale_bundle| {
+            let locale = locale_bundle.locale.to_string();
+            let num_missing = missing_translations.get(&locale).copied().unwrap_or(0);
+            let mut map = Map::new();
+            map.insert("locale".to_owned(), locale.into());
+            map.insert("num_keys".to_owned(), locale_bundle.keys.len().into());
+            map.insert("keys".to_owned(), locale_bundle.keys.clone().into());
+            map.insert("missing_translations".to_owned(), num_missing.into());
+            JsonValue::Object(map)
+        })
+        .collect::<Vec<_>>();
+    let unknown_locales = missing_translations
+        .into_iter()
+        .filter(|(locale, _)| {
+            !localization
+                .bundles
                 .iter()
-                .find_map(|(lang_id, bundle)| (lang_id.language == lang).then_some(bundle))
+                .any(|locale_bundle| locale_bundle.locale.to_string() == *locale)
         })
-        .or(*DEFAULT_BUNDLE)
-        .ok_or_else(|| warn!("the localization bundle does not exits"))?;
+        .map(|(locale, num_missing)| (locale, num_missing.into()))
+        .collect::<Map>();
+
+    let mut map = Map::new();
+    map.insert("default_locale".to_owned(), (*DEFAULT_LOCALE).into());
+    map.insert("loaded_at".to_owned(), localization.loaded_at.into());
+    map.insert("locales".to_owned(), locales.into());
+    map.insert("unknown_locales".to_owned(), unknown_locales.into());
+    map
+}
+
+/// Reloads the localization files and swaps the bundles atomically.
+/// The previous bundles are retained if the files can not be loaded,
+/// otherwise the cached model definitions with the translated labels are invalidated.
+pub fn reload() -> Result<(), Error> {
+    let bundles = load_bundles(&LOCALE_DIR)?;
+    let localization = Arc::new(Localization::new(bundles));
+    *LOCALIZATION.write().unwrap_or_else(|err| err.into_inner()) = localization;
+    DefinitionCache::invalidate_all();
+    tracing::info!("localization files have been reloaded");
+    Ok(())
+}
+
+/// Returns the default locale.
+pub(crate) fn default_locale() -> LanguageIdentifier {
+    DEFAULT_LOCALE.parse().unwrap_or_default()
+}
+
+/// Formats the message in the bundle.
+fn format_message(
+    bundle: &Translation,
+    message: &str,
+    args: Option<&FluentArgs<'_>>,
+) -> Result<SharedString, Error> {
     let pattern = bundle
         .get_message(message)
         .ok_or_else(|| warn!("fail to get the localization message for `{}`", message))?
@@ -44,7 +186,7 @@ pub fn translate(
     let mut errors = vec![];
     if let Some(args) = args {
         let mut value = String::new();
-        bundle.write_pattern(&mut value, pattern, Some(&args), &mut errors)?;
+        bundle.write_pattern(&mut value, pattern, Some(args), &mut errors)?;
         if errors.is_empty() {
             Ok(value.into())
         } else {
@@ -53,66 +195,159 @@ pub fn translate(
     } else {
         let value = bundle.format_pattern(pattern, None, &mut errors);
         if errors.is_empty() {
-            Ok(value)
+            Ok(value.into_owned().into())
         } else {
             bail!("{:?}", errors);
         }
     }
 }
 
-/// Translation type.
-type Translation = FluentBundle<FluentResource, IntlLangMemoizer>;
+/// Records a missing translation for the locale.
+fn record_missing_translation(locale: &LanguageIdentifier) {
+    let mut missing_translations = MISSING_TRANSLATIONS
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    *missing_translations.entry(locale.to_string()).or_default() += 1;
+}
 
-/// Localization.
-static LOCALIZATION: LazyLock<Vec<(LanguageIdentifier, Translation)>> = LazyLock::new(|| {
-    let mut locales = Vec::new();
-    let locale_dir = Agent::config_dir().join("locale");
-    match fs::read_dir(locale_dir) {
-        Ok(entries) => {
-            let files = entries.filter_map(|entry| entry.ok());
-            for file in files {
-                let locale_file = file.path();
-                let ftl_string = fs::read_to_string(&locale_file).unwrap_or_else(|err| {
-                    let locale_file = locale_file.display();
-                    panic!("fail to read `{locale_file}`: {err}");
-                });
-                let resource =
-                    FluentResource::try_new(ftl_string).expect("fail to parse an FTL string");
-                if let Some(locale) = file
-                    .file_name()
-                    .to_str()
-                    .map(|s| s.trim_end_matches(".ftl"))
-                {
-                    let lang = locale
-                        .parse::<LanguageIdentifier>()
-                        .unwrap_or_else(|_| panic!("fail to language identifier `{locale}`"));
-
-                    let mut bundle = FluentBundle::new_concurrent(vec![lang.clone()]);
-                    bundle.set_use_isolating(false);
-                    bundle
-                        .add_resource(resource)
-                        .expect("fail to add FTL resources to the bundle");
-                    locales.push((lang, bundle));
-                }
-            }
-        }
+/// Loads the localization bundles from the `.ftl` files in the directory.
+fn load_bundles(locale_dir: &Path) -> Result<Vec<LocaleBundle>, Error> {
+    let mut bundles = Vec::new();
+    let entries = match fs::read_dir(locale_dir) {
+        Ok(entries) => entries,
         Err(err) => {
             if err.kind() != ErrorKind::NotFound {
                 tracing::error!("{err}");
             }
+            return Ok(bundles);
         }
+    };
+    for file in entries.filter_map(|entry| entry.ok()) {
+        let locale_file = file.path();
+        let Some(locale) = file
+            .file_name()
+            .to_str()
+            .and_then(|s| s.strip_suffix(".ftl"))
+            .map(|s| s.to_owned())
+        else {
+            continue;
+        };
+        let ftl_string = fs::read_to_string(&locale_file)
+            .map_err(|err| warn!("fail to read `{}`: {}", locale_file.display(), err))?;
+        let resource = FluentResource::try_new(ftl_string).map_err(|(_, errors)| {
+            warn!("fail to parse `{}`: {:?}", locale_file.display(), errors)
+        })?;
+        let lang = locale
+            .parse::<LanguageIdentifier>()
+            .map_err(|_| warn!("fail to language identifier `{}`", locale))?;
+        let keys = resource
+            .entries()
+            .filter_map(|entry| match entry {
+                Entry::Message(message) => Some(message.id.name.to_owned()),
+                _ => None,
+            })
+            .collect();
+
+        let mut bundle = FluentBundle::new_concurrent(vec![lang.clone()]);
+        bundle.set_use_isolating(false);
+        bundle
+            .add_resource(resource)
+            .map_err(|errors| warn!("fail to add FTL resources to the bundle: {:?}", errors))?;
+        bundles.push(LocaleBundle {
+            locale: lang,
+            keys,
+            bundle,
+        });
     }
-    locales
-});
+    Ok(bundles)
+}
 
-/// Default bundle.
-static DEFAULT_BUNDLE: LazyLock<Option<&'static Translation>> = LazyLock::new(|| {
-    let default_locale = LazyLock::force(&DEFAULT_LOCALE);
-    LOCALIZATION
-        .iter()
-        .find_map(|(lang_id, bundle)| (lang_id == default_locale).then_some(bundle))
+/// Translation type.
+type Translation = FluentBundle<FluentResource, IntlLangMemoizer>;
+
+/// A localization bundle for the locale.
+struct LocaleBundle {
+    /// Locale.
+    locale: LanguageIdentifier,
+    /// Message IDs.
+    keys: Vec<String>,
+    /// Translation bundle.
+    bundle: Translation,
+}
+
+/// A snapshot of the localization bundles.
+struct Localization {
+    /// Localization bundles.
+    bundles: Vec<LocaleBundle>,
+    /// Load time.
+    loaded_at: DateTime,
+}
+
+impl Localization {
+    /// Creates a new instance.
+    fn new(bundles: Vec<LocaleBundle>) -> Self {
+        Self {
+            bundles,
+            loaded_at: DateTime::now(),
+        }
+    }
+
+    /// Returns the current snapshot.
+    /// The in-flight translations keep using the snapshot even if it is reloaded.
+    fn current() -> Arc<Self> {
+        LOCALIZATION
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    /// Finds the bundle for the locale, falling back to the bundle with the same language.
+    fn find_bundle(&self, locale: &LanguageIdentifier) -> Option<&Translation> {
+        self.bundles
+            .iter()
+            .find(|b| &b.locale == locale)
+            .or_else(|| {
+                let lang = locale.language;
+                self.bundles.iter().find(|b| b.locale.language == lang)
+            })
+            .map(|b| &b.bundle)
+    }
+
+    /// Returns the bundle for the default locale.
+    fn default_bundle(&self) -> Option<&Translation> {
+        let default_locale = *DEFAULT_LOCALE;
+        self.bundles
+            .iter()
+            .find(|b| b.locale.to_string() == default_locale)
+            .map(|b| &b.bundle)
+    }
+}
+
+/// Directory of the localization files.
+static LOCALE_DIR: LazyLock<PathBuf> = LazyLock::new(|| Agent::config_dir().join("locale"));
+
+/// Localization.
+static LOCALIZATION: LazyLock<RwLock<Arc<Localization>>> = LazyLock::new(|| {
+    let bundles = load_bundles(&LOCALE_DIR).unwrap_or_else(|err| panic!("{err}"));
+
+    #[cfg(feature = "i18n-watch")]
+    {
+        let watch = State::shared()
+            .get_config("i18n")
+            .and_then(|config| config.get_bool("watch"))
+            .unwrap_or_else(|| Agent::env().is_dev());
+        if watch {
+            watcher::watch(&LOCALE_DIR);
+        }
+    }
+
+    RwLock::new(Arc::new(Localization::new(bundles)))
 });
 
+/// Counts of the missing translations for each locale.
+static MISSING_TRANSLATIONS: LazyLock<Mutex<HashMap<String, usize>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
 /// Default locale.
 pub(crate) static DEFAULT_LOCALE: LazyLock<&'static str> = LazyLock::new(|| {
     if let Some(i18n) = State::shared().get_config("i18n") {
@@ -123,11 +358,13 @@ pub(crate) static DEFAULT_LOCALE: LazyLock<&'static str> = LazyLock::new(|| {
 });
 
 /// Supported locales.
+/// The list is determined at the first access and does not change when the files are reloaded.
 pub(crate) static SUPPORTED_LOCALES: LazyLock<Vec<&'static str>> = LazyLock::new(|| {
-    LOCALIZATION
+    Localization::current()
+        .bundles
         .iter()
-        .map(|(key, _)| {
-            let language: &'static str = key.to_string().leak();
+        .map(|b| {
+            let language: &'static str = b.locale.to_string().leak();
             language
         })
         .collect::<Vec<_>>()
@@ -0,0 +1,45 @@
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    path::Path,
+    sync::{Mutex, OnceLock},
+};
+
+/// Watches the `.ftl` files in the directory and reloads them when they change.
+pub(super) fn watch(locale_dir: &Path) {
+    let event_handler = |result: notify::Result<Event>| match result {
+        Ok(event) => {
+            let is_changed = matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            );
+            let has_ftl_files = event
+                .paths
+                .iter()
+                .any(|path| path.extension().is_some_and(|ext| ext == "ftl"));
+            if is_changed && has_ftl_files {
+                if let Err(err) = super::reload() {
+                    tracing::error!("fail to reload the localization files: {err}");
+                }
+            }
+        }
+        Err(err) => tracing::error!("fail to watch the localization files: {err}"),
+    };
+    let mut watcher = match notify::recommended_watcher(event_handler) {
+        Ok(watcher) => watcher,
+        Err(err) => {
+            tracing::error!("fail to create a watcher for the localization files: {err}");
+            return;
+        }
+    };
+    if let Err(err) = watcher.watch(locale_dir, RecursiveMode::NonRecursive) {
+        let locale_dir = locale_dir.display();
+        tracing::warn!("fail to watch the directory `{locale_dir}`: {err}");
+        return;
+    }
+    if LOCALE_WATCHER.set(Mutex::new(watcher)).is_ok() {
+        tracing::info!("watching the localization files for changes");
+    }
+}
+
+/// Watcher for the localization files.
+static LOCALE_WATCHER: OnceLock<Mutex<RecommendedWatcher>> = OnceLock::new();
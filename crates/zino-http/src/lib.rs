@@ -7,13 +7,20 @@
 
 mod helper;
 
+pub mod cors;
+pub mod host;
 pub mod request;
 pub mod response;
+pub mod security;
+pub mod static_files;
 pub mod timing;
 
 #[cfg(feature = "i18n")]
 pub mod i18n;
 
+#[cfg(feature = "test-utils")]
+pub mod test;
+
 #[cfg(feature = "view")]
 pub mod view;
 
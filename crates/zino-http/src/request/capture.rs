@@ -0,0 +1,464 @@
+use super::Context;
+use http::{header, HeaderMap};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+use zino_core::{
+    datetime::DateTime, encoding::base64, error::Error, extension::TomlTableExt, state::State,
+    JsonValue, LazyLock, Uuid,
+};
+
+/// A failed request captured for the debugging, which can be replayed in tests.
+///
+/// The capture is enabled by the `[tracing.capture]` config. Only the requests
+/// with `5xx` responses (including panics) are captured. The values of the `authorization`,
+/// `proxy-authorization`, `cookie` and `set-cookie` headers are always redacted,
+/// as well as the headers, query parameters and JSON fields in the `redactions` list.
+///
+/// # Examples
+///
+/// ```toml
+/// [tracing.capture]
+/// enabled = true
+/// sampling-rate = 0.5
+/// paths = ["/order"]
+/// exclude-paths = ["/order/export"]
+/// max-body-size = 65536
+/// redactions = ["password", "x-api-key"]
+/// directory = "./local/captures"
+/// ```
+///
+/// The records are written to the `directory`, or through the
+/// [`GlobalAccessor`](zino_storage::GlobalAccessor) operator specified by `accessor`
+/// if the `accessor` feature is enabled.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CapturedRequest {
+    /// Request method.
+    method: String,
+    /// Request URI.
+    uri: String,
+    /// Matched route.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    route: Option<String>,
+    /// Request headers.
+    headers: Vec<(String, String)>,
+    /// Request body.
+    body: String,
+    /// Encoding of the request body.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body_encoding: Option<String>,
+    /// A flag to indicate whether the request body is truncated.
+    body_truncated: bool,
+    /// Request ID.
+    request_id: Uuid,
+    /// Trace ID.
+    trace_id: Uuid,
+    /// Status code of the response.
+    status_code: u16,
+    /// Capture time.
+    captured_at: DateTime,
+}
+
+impl CapturedRequest {
+    /// Creates a new instance with the method, URI and headers.
+    pub fn new(method: &str, uri: &str, headers: &HeaderMap) -> Self {
+        let redactions = redactions();
+        let headers = headers
+            .iter()
+            .map(|(name, value)| {
+                let name = name.as_str();
+                let value = if is_redacted(name, redactions) {
+                    REDACTED_VALUE.to_owned()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                };
+                (name.to_owned(), value)
+            })
+            .collect();
+        Self {
+            method: method.to_owned(),
+            uri: redact_query(uri, redactions),
+            headers,
+            captured_at: DateTime::now(),
+            ..Self::default()
+        }
+    }
+
+    /// Loads a capture record from the JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|err| {
+            Error::new(format!(
+                "fail to read the capture `{}`: {err}",
+                path.display()
+            ))
+        })?;
+        serde_json::from_slice(&bytes).map_err(Error::from)
+    }
+
+    /// Sets the matched route.
+    #[inline]
+    pub fn set_route(&mut self, route: impl Into<String>) {
+        self.route = Some(route.into());
+    }
+
+    /// Sets the request ID and trace ID with the request context.
+    #[inline]
+    pub fn set_context(&mut self, ctx: &Context) {
+        self.request_id = ctx.request_id();
+        self.trace_id = ctx.trace_id();
+    }
+
+    /// Sets the status code of the response.
+    #[inline]
+    pub fn set_status_code(&mut self, status_code: u16) {
+        self.status_code = status_code;
+    }
+
+    /// Sets the request body. It is truncated if the size exceeds the `max-body-size`,
+    /// and the fields in the `redactions` list are redacted for a JSON body.
+    pub fn set_body(&mut self, body: &[u8]) {
+        let max_body_size = Self::max_body_size();
+        let body = if body.len() > max_body_size {
+            self.body_truncated = true;
+            &body[..max_body_size]
+        } else {
+            body
+        };
+        let is_json = self
+            .get_header(header::CONTENT_TYPE.as_str())
+            .is_some_and(|content_type| content_type.contains("json"));
+        if is_json && !self.body_truncated {
+            if let Ok(mut data) = serde_json::from_slice::<JsonValue>(body) {
+                redact_json_value(&mut data, redactions());
+                self.body = data.to_string();
+                return;
+            }
+        }
+        match std::str::from_utf8(body) {
+            Ok(body) if !is_json => self.body = body.to_owned(),
+            _ => {
+                // The JSON body which can not be redacted is never stored as plain text.
+                self.body = base64::encode(body);
+                self.body_encoding = Some("base64".to_owned());
+            }
+        }
+    }
+
+    /// Sets the flag to indicate whether the request body is truncated.
+    #[inline]
+    pub fn set_body_truncated(&mut self, truncated: bool) {
+        self.body_truncated = truncated;
+    }
+
+    /// Returns the request method.
+    #[inline]
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// Returns the request URI.
+    #[inline]
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Returns the matched route.
+    #[inline]
+    pub fn route(&self) -> Option<&str> {
+        self.route.as_deref()
+    }
+
+    /// Returns the request headers.
+    #[inline]
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// Returns the value of the header.
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find_map(|(key, value)| key.eq_ignore_ascii_case(name).then_some(value.as_str()))
+    }
+
+    /// Returns the request body as bytes.
+    pub fn body_bytes(&self) -> Result<Vec<u8>, Error> {
+        if self.body_encoding.as_deref() == Some("base64") {
+            base64::decode(&self.body).map_err(|err| Error::new(err.to_string()))
+        } else {
+            Ok(self.body.as_bytes().to_vec())
+        }
+    }
+
+    /// Returns `true` if the request body is truncated.
+    #[inline]
+    pub fn body_truncated(&self) -> bool {
+        self.body_truncated
+    }
+
+    /// Returns the request ID.
+    #[inline]
+    pub fn request_id(&self) -> Uuid {
+        self.request_id
+    }
+
+    /// Returns the trace ID.
+    #[inline]
+    pub fn trace_id(&self) -> Uuid {
+        self.trace_id
+    }
+
+    /// Returns the status code of the response.
+    #[inline]
+    pub fn status_code(&self) -> u16 {
+        self.status_code
+    }
+
+    /// Returns the capture time.
+    #[inline]
+    pub fn captured_at(&self) -> DateTime {
+        self.captured_at
+    }
+
+    /// Writes the capture record to the directory or the storage accessor.
+    pub async fn save(&self) -> Result<(), Error> {
+        let Some(config) = CAPTURE_CONFIG.as_ref() else {
+            return Ok(());
+        };
+        let file_name = format!(
+            "{}-{}.json",
+            self.captured_at.format("%Y%m%d%H%M%S"),
+            self.request_id
+        );
+        let bytes = serde_json::to_vec_pretty(self)?;
+        if let Some(name) = config.accessor.as_deref() {
+            #[cfg(feature = "accessor")]
+            {
+                let Some(operator) = zino_storage::GlobalAccessor::get(name) else {
+                    return Err(Error::new(format!(
+                        "storage accessor `{name}` is not found"
+                    )));
+                };
+                operator.write(&file_name, bytes).await?;
+                return Ok(());
+            }
+            #[cfg(not(feature = "accessor"))]
+            {
+                return Err(Error::new(format!(
+                    "storage accessor `{name}` is not supported"
+                )));
+            }
+        }
+
+        let dir = Path::new(&config.directory);
+        if !dir.exists() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(dir.join(file_name), bytes)?;
+        Ok(())
+    }
+
+    /// Returns `true` if the failed request should be captured.
+    /// The sampling is determined by the request ID.
+    pub fn should_capture(path: &str, status_code: u16, request_id: Uuid) -> bool {
+        let Some(config) = CAPTURE_CONFIG.as_ref() else {
+            return false;
+        };
+        if status_code < 500 {
+            return false;
+        }
+        if !config.paths.is_empty() && !config.paths.iter().any(|p| path.starts_with(p.as_str())) {
+            return false;
+        }
+        if config
+            .exclude_paths
+            .iter()
+            .any(|p| path.starts_with(p.as_str()))
+        {
+            return false;
+        }
+
+        let (_, random_bits) = request_id.as_u64_pair();
+        let sample = (random_bits % 10_000) as f64 / 10_000.0;
+        sample < config.sampling_rate
+    }
+
+    /// Returns `true` if the capture is enabled.
+    #[inline]
+    pub fn is_enabled() -> bool {
+        CAPTURE_CONFIG.is_some()
+    }
+
+    /// Returns the maximum size of the request body to be captured.
+    #[inline]
+    pub fn max_body_size() -> usize {
+        CAPTURE_CONFIG
+            .as_ref()
+            .map(|config| config.max_body_size)
+            .unwrap_or(DEFAULT_MAX_BODY_SIZE)
+    }
+}
+
+/// Config of the request capture.
+#[derive(Debug)]
+struct CaptureConfig {
+    /// Sampling rate.
+    sampling_rate: f64,
+    /// Path prefixes to be captured.
+    paths: Vec<String>,
+    /// Path prefixes to be excluded.
+    exclude_paths: Vec<String>,
+    /// Maximum size of the request body.
+    max_body_size: usize,
+    /// Names of the headers, query parameters and JSON fields to be redacted.
+    redactions: Vec<String>,
+    /// Directory of the capture records.
+    directory: String,
+    /// Name of the storage accessor.
+    accessor: Option<String>,
+}
+
+/// Returns the names to be redacted.
+fn redactions() -> &'static [String] {
+    CAPTURE_CONFIG
+        .as_ref()
+        .map(|config| config.redactions.as_slice())
+        .unwrap_or_default()
+}
+
+/// Returns `true` if the value of the header, query parameter or JSON field is redacted.
+fn is_redacted(name: &str, redactions: &[String]) -> bool {
+    ALWAYS_REDACTED_HEADERS
+        .iter()
+        .any(|redaction| redaction.eq_ignore_ascii_case(name))
+        || redactions
+            .iter()
+            .any(|redaction| redaction.eq_ignore_ascii_case(name))
+}
+
+/// Redacts the query parameters of the URI.
+fn redact_query(uri: &str, redactions: &[String]) -> String {
+    let Some((path, query)) = uri.split_once('?') else {
+        return uri.to_owned();
+    };
+    let query = query
+        .split('&')
+        .map(|pair| {
+            let key = pair.split_once('=').map_or(pair, |(key, _)| key);
+            if is_redacted(key, redactions) {
+                format!("{key}={REDACTED_VALUE}")
+            } else {
+                pair.to_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    format!("{path}?{query}")
+}
+
+/// Redacts the fields of the JSON value recursively.
+fn redact_json_value(value: &mut JsonValue, redactions: &[String]) {
+    match value {
+        JsonValue::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_redacted(key, redactions) {
+                    *value = REDACTED_VALUE.into();
+                } else {
+                    redact_json_value(value, redactions);
+                }
+            }
+        }
+        JsonValue::Array(values) => {
+            for value in values {
+                redact_json_value(value, redactions);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Headers whose values are always redacted regardless of the config.
+const ALWAYS_REDACTED_HEADERS: [&str; 4] = [
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+];
+
+/// The placeholder of a redacted value.
+const REDACTED_VALUE: &str = "[REDACTED]";
+
+/// Default maximum size of the request body.
+const DEFAULT_MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// Request capture config.
+static CAPTURE_CONFIG: LazyLock<Option<CaptureConfig>> = LazyLock::new(|| {
+    let config = State::shared()
+        .get_config("tracing")?
+        .get_table("capture")?;
+    if config.get_bool("enabled") != Some(true) {
+        return None;
+    }
+
+    let string_array = |key: &str| {
+        config
+            .get_str_array(key)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|s| s.to_owned())
+            .collect::<Vec<_>>()
+    };
+    let sampling_rate = config.get_f64("sampling-rate").unwrap_or(1.0);
+    let max_body_size = config
+        .get_usize("max-body-size")
+        .unwrap_or(DEFAULT_MAX_BODY_SIZE);
+    let directory = config
+        .get_str("directory")
+        .unwrap_or("./local/captures")
+        .to_owned();
+    let accessor = config.get_str("accessor").map(|s| s.to_owned());
+    Some(CaptureConfig {
+        sampling_rate,
+        paths: string_array("paths"),
+        exclude_paths: string_array("exclude-paths"),
+        max_body_size,
+        redactions: string_array("redactions"),
+        directory,
+        accessor,
+    })
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{redact_json_value, redact_query, CapturedRequest, REDACTED_VALUE};
+    use http::{HeaderMap, HeaderValue};
+    use zino_core::json;
+
+    #[test]
+    fn it_redacts_captured_requests() {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", HeaderValue::from_static("Bearer secret"));
+        headers.insert("cookie", HeaderValue::from_static("session_id=secret"));
+        headers.insert("content-type", HeaderValue::from_static("application/json"));
+        let mut capture = CapturedRequest::new("POST", "/user/1/update?page=1", &headers);
+        assert_eq!(capture.get_header("authorization"), Some(REDACTED_VALUE));
+        assert_eq!(capture.get_header("Cookie"), Some(REDACTED_VALUE));
+        assert_eq!(capture.uri(), "/user/1/update?page=1");
+
+        capture.set_body(br#"{"name":"alice"}"#);
+        assert_eq!(capture.body_bytes().unwrap(), br#"{"name":"alice"}"#);
+
+        let redactions = ["password".to_owned()];
+        let mut data = json!({ "users": [{ "name": "alice", "Password": "secret" }] });
+        redact_json_value(&mut data, &redactions);
+        assert_eq!(
+            data,
+            json!({ "users": [{ "name": "alice", "Password": REDACTED_VALUE }] })
+        );
+        assert_eq!(
+            redact_query("/login?password=secret&next=/", &redactions),
+            "/login?password=[REDACTED]&next=/"
+        );
+    }
+}
@@ -1,5 +1,9 @@
-use std::time::Instant;
-use zino_core::Uuid;
+use crate::helper::KeyCase;
+use std::{future::Future, time::Instant};
+use zino_core::{
+    trace::{TaskContext, TraceContext},
+    Uuid,
+};
 
 #[cfg(feature = "i18n")]
 use unic_langid::LanguageIdentifier;
@@ -17,6 +21,8 @@ pub struct Context {
     trace_id: Uuid,
     /// Session ID.
     session_id: Option<String>,
+    /// Case of the keys in JSON objects.
+    key_case: KeyCase,
     /// Locale.
     #[cfg(feature = "i18n")]
     locale: Option<LanguageIdentifier>,
@@ -31,6 +37,7 @@ impl Context {
             request_id,
             trace_id: Uuid::nil(),
             session_id: None,
+            key_case: KeyCase::default(),
             #[cfg(feature = "i18n")]
             locale: None,
         }
@@ -54,6 +61,12 @@ impl Context {
         self.session_id = session_id;
     }
 
+    /// Sets the case of the keys in JSON objects.
+    #[inline]
+    pub(crate) fn set_key_case(&mut self, key_case: KeyCase) {
+        self.key_case = key_case;
+    }
+
     /// Sets the locale.
     #[cfg(feature = "i18n")]
     #[inline]
@@ -94,6 +107,26 @@ impl Context {
         self.session_id.as_deref()
     }
 
+    /// Returns the case of the keys in JSON objects.
+    #[inline]
+    pub(crate) fn key_case(&self) -> KeyCase {
+        self.key_case
+    }
+
+    /// Returns the identifiers of the context to be propagated to the spawned tasks.
+    #[inline]
+    pub fn task_context(&self) -> TaskContext {
+        let trace_context = TraceContext::with_trace_id(self.trace_id);
+        TaskContext::new(self.request_id, &trace_context)
+    }
+
+    /// Executes the future in the scope of the context, so that the tasks spawned by
+    /// `Agent::spawn_traced` carry the request ID and trace ID.
+    #[inline]
+    pub fn scope<F: Future>(&self, future: F) -> impl Future<Output = F::Output> {
+        self.task_context().scope(future)
+    }
+
     /// Returns the locale.
     #[cfg(feature = "i18n")]
     pub fn locale(&self) -> Option<&LanguageIdentifier> {
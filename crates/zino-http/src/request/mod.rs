@@ -1,49 +1,67 @@
 //! Request context and validation.
 
 use crate::{
-    helper,
+    helper::{self, BodyFormat, KeyCase},
+    host::HostPolicy,
     response::{Rejection, Response, ResponseCode},
 };
+use futures::{Stream, StreamExt};
 use multer::Multipart;
 use serde::de::DeserializeOwned;
 use std::{borrow::Cow, net::IpAddr, str::FromStr, time::Instant};
+use toml::Table;
 use zino_channel::{CloudEvent, Subscription};
 use zino_core::{
-    application::Agent,
+    application::{Agent, ServerTag},
     error::Error,
     extension::HeaderMapExt,
-    model::{ModelHooks, Query},
+    model::{ModelHooks, Preconditions, Query},
+    state::State,
     trace::{TraceContext, TraceState},
     warn, JsonValue, Map, SharedString, Uuid,
 };
 use zino_storage::NamedFile;
 
+#[cfg(feature = "debug-session")]
+use zino_core::trace::DebugSession;
+
 #[cfg(feature = "auth")]
-use zino_auth::{AccessKeyId, Authentication, ParseSecurityTokenError, SecurityToken, SessionId};
+use zino_auth::{
+    AccessKeyId, ApiKeyPrincipal, ApiKeyToken, Authentication, ParseSecurityTokenError,
+    SecretAccessKey, SecurityToken, SessionId,
+};
 
 #[cfg(feature = "auth")]
-use zino_core::{datetime::DateTime, extension::JsonObjectExt, validation::Validation};
+use zino_core::{
+    application::Application, datetime::DateTime, extension::JsonObjectExt, validation::Validation,
+};
 
 #[cfg(feature = "cookie")]
-use cookie::{Cookie, SameSite};
+use cookie::Cookie;
 
 #[cfg(feature = "jwt")]
 use jwt_simple::algorithms::MACLike;
 #[cfg(feature = "jwt")]
-use zino_auth::JwtClaims;
+use zino_auth::{JwtClaims, Scopes};
 
 #[cfg(any(feature = "cookie", feature = "jwt"))]
 use std::time::Duration;
 
+#[cfg(feature = "feature-flag")]
+use zino_core::application::{FeatureFlag, FeatureSubject};
+
 #[cfg(feature = "i18n")]
 use crate::i18n;
 #[cfg(feature = "i18n")]
-use fluent::FluentArgs;
+use fluent::{FluentArgs, FluentValue};
 #[cfg(feature = "i18n")]
 use unic_langid::LanguageIdentifier;
 
+mod capture;
 mod context;
 
+pub use crate::helper::{register_data_deserializer, register_data_type};
+pub use capture::CapturedRequest;
 pub use context::Context;
 
 /// Request context.
@@ -87,6 +105,9 @@ pub trait RequestContext {
     /// Reads the entire request body into a byte buffer.
     async fn read_body_bytes(&mut self) -> Result<Vec<u8>, Error>;
 
+    /// Takes the request body as a stream of byte chunks.
+    fn take_body_stream(&mut self) -> impl Stream<Item = Result<Vec<u8>, Error>> + Unpin + 'static;
+
     /// Returns the request path segments.
     #[inline]
     fn path_segments(&self) -> Vec<&str> {
@@ -103,6 +124,7 @@ pub trait RequestContext {
                 "zino_http_requests_total",
                 "method" => self.request_method().as_ref().to_owned(),
                 "route" => self.matched_route().into_owned(),
+                "scope" => self.server_tag().as_str().to_owned(),
             )
             .increment(1);
         }
@@ -112,19 +134,32 @@ pub trait RequestContext {
             .get_header("x-request-id")
             .and_then(|s| s.parse().ok())
             .unwrap_or_else(Uuid::now_v7);
-        let trace_id = self
-            .get_trace_context()
-            .map_or_else(Uuid::now_v7, |t| Uuid::from_u128(t.trace_id()));
         let session_id = self
             .get_header("x-session-id")
-            .or_else(|| self.get_header("session_id"))
-            .and_then(|s| s.parse().ok());
+            .or_else(|| self.get_header("session_id"));
+
+        // Elevate the tracing level before the trace context is sampled.
+        #[cfg(feature = "debug-session")]
+        if DebugSession::matches(
+            session_id,
+            self.get_header("x-user-id"),
+            self.get_header("x-debug-token"),
+        ) {
+            tracing::Span::current().record("debug_session", true);
+        }
+
+        let trace_id = self.get_trace_context().map_or_else(
+            || Uuid::from_u128(TraceContext::new().trace_id()),
+            |t| Uuid::from_u128(t.trace_id()),
+        );
+        let session_id = session_id.and_then(|s| s.parse().ok());
 
         // Generate new context.
         let mut ctx = Context::new(request_id);
         ctx.set_instance(self.request_path());
         ctx.set_trace_id(trace_id);
         ctx.set_session_id(session_id);
+        ctx.set_key_case(helper::get_key_case(self.get_header("x-key-case")));
 
         // Set locale.
         #[cfg(feature = "i18n")]
@@ -171,6 +206,10 @@ pub trait RequestContext {
     }
 
     /// Creates a new cookie with the given name and value.
+    ///
+    /// The `SameSite`, `Domain`, `Path`, `Secure` and `HttpOnly` attributes can be
+    /// configured by the `[cookie]` table, and the request path is used
+    /// if the `path` is not specified.
     #[cfg(feature = "cookie")]
     fn new_cookie(
         &self,
@@ -178,22 +217,33 @@ pub trait RequestContext {
         value: SharedString,
         max_age: Option<Duration>,
     ) -> Cookie<'static> {
-        let mut cookie_builder = Cookie::build((name, value))
-            .http_only(true)
-            .secure(true)
-            .same_site(SameSite::Lax)
-            .path(self.request_path().to_owned());
-        if let Some(max_age) = max_age.and_then(|d| d.try_into().ok()) {
-            cookie_builder = cookie_builder.max_age(max_age);
-        }
-        cookie_builder.build()
+        helper::build_cookie(name, value, self.request_path(), max_age)
+    }
+
+    /// Creates a new private cookie with the given name and value, which is encrypted
+    /// and authenticated with a key derived from the application secret,
+    /// so that the value can neither be read nor tampered with by the client.
+    #[cfg(feature = "cookie")]
+    fn new_private_cookie(
+        &self,
+        name: SharedString,
+        value: &str,
+        max_age: Option<Duration>,
+    ) -> Result<Cookie<'static>, Error> {
+        let value = helper::encrypt_cookie_value(&name, value)?;
+        Ok(helper::build_cookie(
+            name,
+            value.into(),
+            self.request_path(),
+            max_age,
+        ))
     }
 
     /// Gets a cookie with the given name.
     #[cfg(feature = "cookie")]
     fn get_cookie(&self, name: &str) -> Option<Cookie<'_>> {
         self.get_header("cookie")?.split(';').find_map(|cookie| {
-            if let Some((key, value)) = cookie.split_once('=') {
+            if let Some((key, value)) = cookie.trim().split_once('=') {
                 (key == name).then(|| Cookie::new(key, value))
             } else {
                 None
@@ -201,6 +251,20 @@ pub trait RequestContext {
         })
     }
 
+    /// Gets a private cookie with the given name and decrypts the value.
+    /// It returns `None` if the cookie does not exist or the value has been tampered with.
+    #[cfg(feature = "cookie")]
+    fn get_private_cookie(&self, name: &str) -> Option<Cookie<'static>> {
+        let cookie = self.get_cookie(name)?;
+        match helper::decrypt_cookie_value(name, cookie.value()) {
+            Ok(value) => Some(Cookie::new(name.to_owned(), value)),
+            Err(err) => {
+                tracing::warn!("fail to decrypt the private cookie `{name}`: {err}");
+                None
+            }
+        }
+    }
+
     /// Returns the start time.
     #[inline]
     fn start_time(&self) -> Instant {
@@ -217,6 +281,32 @@ pub trait RequestContext {
             .unwrap_or_else(|| self.request_path().to_owned())
     }
 
+    /// Returns the scope of the listener which accepts the request.
+    #[inline]
+    fn server_tag(&self) -> ServerTag {
+        self.get_data::<ServerTag>().unwrap_or_default()
+    }
+
+    /// Returns a reference to the config corresponding to the `key` for the listener scope.
+    /// See [`State::get_scoped_config()`] for the details.
+    #[inline]
+    fn get_scoped_config(&self, key: &str) -> Option<&'static Table> {
+        State::shared().get_scoped_config(&self.server_tag(), key)
+    }
+
+    /// Returns the host for generating the absolute URLs.
+    ///
+    /// The `Host` header is only used if it is allowed by the [`HostPolicy`] of the listener,
+    /// otherwise the canonical host of the policy is returned, so that an attacker-controlled
+    /// value is never reflected. The forwarded headers are not trusted here.
+    fn host(&self) -> Option<Cow<'_, str>> {
+        let policy = HostPolicy::for_scope(&self.server_tag());
+        match self.get_header("host") {
+            Some(host) if policy.is_host_allowed(host) => Some(Cow::Borrowed(host)),
+            _ => policy.canonical_host().map(Cow::Owned),
+        }
+    }
+
     /// Returns the request ID.
     #[inline]
     fn request_id(&self) -> Uuid {
@@ -240,6 +330,15 @@ pub trait RequestContext {
             .and_then(|ctx| ctx.session_id().map(|s| s.to_owned()))
     }
 
+    /// Returns the ID of the last server-sent event received by the client,
+    /// which is sent with the `last-event-id` header when reconnecting.
+    #[inline]
+    fn last_event_id(&self) -> Option<&str> {
+        self.get_header("last-event-id")
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+    }
+
     /// Returns the locale.
     #[cfg(feature = "i18n")]
     #[inline]
@@ -252,17 +351,51 @@ pub trait RequestContext {
     /// # Note
     ///
     /// Currently, we support the following values: `bytes` | `csv` | `form` | `json` | `multipart`
-    /// | `ndjson` | `text`.
+    /// | `ndjson` | `text` | `tsv` | `xml`. The structural suffixes `+json` and `+xml` are
+    /// recognized for any content type, and the custom ones can be added by
+    /// [`register_data_type()`]. For an unknown content type, its essence is returned.
     fn data_type(&self) -> Option<&str> {
-        self.get_header("content-type")
-            .map(|content_type| {
-                if let Some((essence, _)) = content_type.split_once(';') {
-                    essence
-                } else {
-                    content_type
-                }
-            })
-            .map(helper::get_data_type)
+        self.get_header("content-type").map(helper::get_data_type)
+    }
+
+    /// Negotiates the response content type with the supported ones
+    /// by parsing the `accept` header, where the quality values and wildcards are respected.
+    /// If the header is absent, the first supported content type will be returned.
+    fn negotiate_content_type<'a>(&self, supported: &[&'a str]) -> Option<&'a str> {
+        if let Some(accept) = self.get_header("accept").filter(|s| !s.trim().is_empty()) {
+            helper::select_content_type(accept, supported)
+        } else {
+            supported.first().copied()
+        }
+    }
+
+    /// Returns the API version requested by the client.
+    ///
+    /// The version is parsed from the `x-api-version` header or the `version` parameter
+    /// of the `accept` header, such as `application/json; version=2`.
+    /// If neither is present, the default version for the matched route
+    /// or the global one in the `[api.versioning]` config will be used.
+    fn api_version(&self) -> Option<u32> {
+        helper::parse_api_version(self.get_header("x-api-version"), self.get_header("accept"))
+            .or_else(|| helper::default_api_version(&self.matched_route()))
+    }
+
+    /// Checks whether the API version is supported and returns it.
+    /// It will be rejected with a `406 Not Acceptable` response
+    /// if the version is older than the configured `min-version`.
+    fn check_api_version(&self) -> Result<Option<u32>, Rejection> {
+        let version = self.api_version();
+        if let (Some(version), Some(min_version)) = (version, helper::min_api_version()) {
+            if version < min_version {
+                let err = warn!(
+                    "API version `{}` is no longer supported, please upgrade to version `{}` or later",
+                    version,
+                    min_version
+                );
+                return Err(Rejection::not_acceptable(err).context(self));
+            }
+        }
+        Ok(version)
     }
 
     /// Gets the route parameter by name.
@@ -371,6 +504,45 @@ pub trait RequestContext {
         }
     }
 
+    /// Parses the `if-match` and `if-unmodified-since` headers as the preconditions
+    /// for the optimistic concurrency control.
+    ///
+    /// The entity tags are strong versions emitted by the `etag` header,
+    /// and the `if-unmodified-since` header is ignored when `if-match` is present.
+    /// It rejects the request with `428 Precondition Required` if both headers are absent
+    /// and the preconditions are required by `[api] require-preconditions`.
+    fn parse_preconditions(&self) -> Result<Preconditions, Rejection> {
+        let mut preconditions = Preconditions::new();
+        if let Some(if_match) = self.get_header("if-match") {
+            match helper::parse_if_match(if_match) {
+                Ok(Some(versions)) => {
+                    for version in versions {
+                        preconditions.add_version(version);
+                    }
+                }
+                Ok(None) => (),
+                Err(_) => {
+                    let err = warn!(
+                        "412 Precondition Failed: entity tags `{}` do not match",
+                        if_match
+                    );
+                    return Err(Rejection::precondition_failed(err).context(self));
+                }
+            }
+        } else if let Some(if_unmodified_since) = self.get_header("if-unmodified-since") {
+            if let Ok(dt) = zino_core::datetime::DateTime::parse_utc_str(if_unmodified_since) {
+                preconditions.set_unmodified_since(dt);
+            }
+        } else if helper::require_preconditions() {
+            let err = warn!(
+                "428 Precondition Required: the `{}` header is required",
+                "if-match"
+            );
+            return Err(Rejection::precondition_required(err).context(self));
+        }
+        Ok(preconditions)
+    }
+
     /// Parses the request body as an instance of type `T`.
     ///
     /// # Note
@@ -378,33 +550,76 @@ pub trait RequestContext {
     /// Currently, we have built-in support for the following `content-type` header values:
     ///
     /// - `application/json`
-    /// - `application/problem+json`
+    /// - `application/problem+json` and other content types with the `+json` suffix
     /// - `application/x-www-form-urlencoded`
+    ///
+    /// The other content types are supported if their data types have been registered
+    /// by [`register_data_type()`] with `json` or `form`, or with a custom deserializer
+    /// by [`register_data_deserializer()`].
+    ///
+    /// The keys of a JSON body are translated into `snake_case`
+    /// if the `camelCase` keys are configured by `[response] key-case`
+    /// or requested by the `x-key-case` header.
     async fn parse_body<T: DeserializeOwned>(&mut self) -> Result<T, Rejection> {
         let data_type = self.data_type().unwrap_or("form");
-        if data_type.contains('/') {
+        let Some(body_format) = BodyFormat::with_data_type(data_type) else {
+            let content_type = self.get_header("content-type").unwrap_or_default();
             let err = warn!(
-                "deserialization of the data type `{}` is unsupported",
-                data_type
+                "deserialization of the content type `{}` is unsupported",
+                content_type
             );
             let rejection = Rejection::from_validation_entry("data_type", err).context(self);
             return Err(rejection);
-        }
+        };
 
-        let is_form = data_type == "form";
         let bytes = self
             .read_body_bytes()
             .await
             .map_err(|err| Rejection::from_validation_entry("body", err).context(self))?;
-        if is_form {
-            serde_qs::from_bytes(&bytes)
-                .map_err(|err| Rejection::from_validation_entry("body", err).context(self))
-        } else {
-            serde_json::from_slice(&bytes)
-                .map_err(|err| Rejection::from_validation_entry("body", err).context(self))
+        let translate_keys = helper::get_key_case(self.get_header("x-key-case")) == KeyCase::Camel;
+        match body_format {
+            BodyFormat::Form => serde_qs::from_bytes(&bytes)
+                .map_err(|err| Rejection::from_validation_entry("body", err).context(self)),
+            BodyFormat::Json if !translate_keys => serde_json::from_slice(&bytes)
+                .map_err(|err| Rejection::from_validation_entry("body", err).context(self)),
+            BodyFormat::Json => serde_json::from_slice(&bytes)
+                .map_err(Error::from)
+                .and_then(|mut data| {
+                    helper::translate_keys(&mut data, KeyCase::Snake);
+                    Ok(serde_json::from_value(data)?)
+                })
+                .map_err(|err| Rejection::from_validation_entry("body", err).context(self)),
+            BodyFormat::Custom(deserializer) => deserializer(&bytes)
+                .and_then(|mut data| {
+                    if translate_keys {
+                        helper::translate_keys(&mut data, KeyCase::Snake);
+                    }
+                    Ok(serde_json::from_value(data)?)
+                })
+                .map_err(|err| Rejection::from_validation_entry("body", err).context(self)),
         }
     }
 
+    /// Parses the NDJSON request body as a stream of instances of type `T`.
+    ///
+    /// The body is parsed line by line as it arrives, so it is never buffered entirely.
+    /// The size of a line and the number of lines are limited by
+    /// `[api.ndjson] max-line-size` and `max-lines`, and the errors report the line number.
+    fn parse_ndjson_stream<T: DeserializeOwned + 'static>(
+        &mut self,
+    ) -> impl Stream<Item = Result<T, Error>> + 'static {
+        self.parse_ndjson_lines().map(|(_, result)| result)
+    }
+
+    /// Parses the NDJSON request body as a stream of instances of type `T`
+    /// paired with the line numbers.
+    fn parse_ndjson_lines<T: DeserializeOwned + 'static>(
+        &mut self,
+    ) -> impl Stream<Item = (usize, Result<T, Error>)> + 'static {
+        let key_case = helper::get_key_case(self.get_header("x-key-case"));
+        helper::parse_ndjson_lines(self.take_body_stream(), key_case)
+    }
+
     /// Parses the request body as a multipart, which is commonly used with file uploads.
     async fn parse_multipart(&mut self) -> Result<Multipart, Rejection> {
         let Some(content_type) = self.get_header("content-type") else {
@@ -549,6 +764,31 @@ pub trait RequestContext {
         }
     }
 
+    /// Attempts to resolve the API key principal from an HTTP request.
+    /// The token is extracted from the `authorization` header in the form
+    /// `Bearer zino_<id>_<secret>` and verified by the registered verifier.
+    ///
+    /// The principal is also inserted into the request-scoped data,
+    /// which can be retrieved by `get_data::<ApiKeyPrincipal>()` for authorization.
+    #[cfg(feature = "auth")]
+    async fn parse_api_key(&mut self) -> Result<ApiKeyPrincipal, Rejection> {
+        let Some(token) = self
+            .get_header("authorization")
+            .and_then(|authorization| authorization.strip_prefix("Bearer "))
+        else {
+            let err = warn!("a bearer token is required in the `authorization` header");
+            return Err(Rejection::unauthorized(err).context(self));
+        };
+        let token =
+            ApiKeyToken::parse(token).map_err(|err| Rejection::unauthorized(err).context(self))?;
+        let principal = token
+            .verify()
+            .await
+            .map_err(|err| Rejection::unauthorized(err).context(self))?;
+        self.set_data(principal.clone());
+        Ok(principal)
+    }
+
     /// Attempts to construct an instance of `SecurityToken` from an HTTP request.
     /// The value is extracted from the `x-security-token` header.
     #[cfg(feature = "auth")]
@@ -650,6 +890,64 @@ pub trait RequestContext {
         }
     }
 
+    /// Checks whether the required scopes are granted for the request.
+    /// The granted scopes are obtained from the request-scoped data `Scopes`
+    /// if it has been inserted by a middleware, otherwise they are extracted from
+    /// the JWT claims verified by the shared key.
+    ///
+    /// A `403 Forbidden` rejection listing the missing scopes is returned if unsatisfied.
+    #[cfg(feature = "jwt")]
+    fn require_scopes(&self, scopes: &[&str]) -> Result<(), Rejection> {
+        let granted_scopes = match self.get_data::<Scopes>() {
+            Some(scopes) => scopes,
+            None => self
+                .parse_jwt_claims::<Map, _>(JwtClaims::shared_key())?
+                .scopes(),
+        };
+        let missing_scopes = granted_scopes.missing(scopes);
+        if missing_scopes.is_empty() {
+            Ok(())
+        } else {
+            let err = warn!(
+                "insufficient scopes, missing `{}`",
+                missing_scopes.join("`, `")
+            );
+            Err(Rejection::forbidden(err).context(self))
+        }
+    }
+
+    /// Returns `true` if the feature flag is enabled for the request.
+    /// The subject is obtained from the request-scoped data `FeatureSubject`
+    /// if it has been inserted by a middleware, such as the one for the user session,
+    /// otherwise it is derived from the subject and the `groups` or `roles`
+    /// of the JWT claims verified by the shared key. The flag is evaluated
+    /// without a subject if neither of them is available.
+    #[cfg(feature = "feature-flag")]
+    fn feature_enabled(&self, name: &str) -> bool {
+        if let Some(subject) = self.get_data::<FeatureSubject>() {
+            return FeatureFlag::enabled_for(name, &subject);
+        }
+        #[cfg(feature = "jwt")]
+        if let Ok(claims) = self.parse_jwt_claims::<Map, _>(JwtClaims::shared_key()) {
+            let data = claims.data();
+            let subject_id = claims
+                .subject()
+                .or_else(|| data.get_str("uid"))
+                .unwrap_or_default();
+            if !subject_id.is_empty() {
+                let mut subject = FeatureSubject::new(subject_id);
+                if let Some(groups) = data
+                    .get_str_array("groups")
+                    .or_else(|| data.get_str_array("roles"))
+                {
+                    subject.set_groups(groups.into_iter().map(|s| s.to_owned()).collect());
+                }
+                return FeatureFlag::enabled_for(name, &subject);
+            }
+        }
+        FeatureFlag::enabled(name)
+    }
+
     /// Returns a `Response` or `Rejection` from a model query validation.
     /// The data is extracted from [`parse_query()`](RequestContext::parse_query).
     fn query_validation<S>(&self, query: &mut Query) -> Result<Response<S>, Rejection>
@@ -672,91 +970,158 @@ pub trait RequestContext {
 
     /// Returns a `Response` or `Rejection` from a model validation.
     /// The data is extracted from [`parse_body()`](RequestContext::parse_body).
+    /// The keys of the data are translated into `snake_case` as the model fields
+    /// if the `camelCase` keys are used.
+    #[inline]
     async fn model_validation<M, S>(&mut self, model: &mut M) -> Result<Response<S>, Rejection>
+    where
+        Self: Sized,
+        M: ModelHooks,
+        S: ResponseCode,
+    {
+        self.model_validation_with(model, Map::new()).await
+    }
+
+    /// Returns a `Response` or `Rejection` from a model validation,
+    /// with the entries overriding the data before validation.
+    /// It is used to set the fields which are not supplied by the body,
+    /// such as the parent ids from the route params.
+    async fn model_validation_with<M, S>(
+        &mut self,
+        model: &mut M,
+        mut entries: Map,
+    ) -> Result<Response<S>, Rejection>
     where
         Self: Sized,
         M: ModelHooks,
         S: ResponseCode,
     {
         let data_type = self.data_type().unwrap_or("form");
-        if data_type.contains('/') {
+        let Some(body_format) = BodyFormat::with_data_type(data_type) else {
+            let content_type = self.get_header("content-type").unwrap_or_default();
             let err = warn!(
-                "deserialization of the data type `{}` is unsupported",
-                data_type
+                "deserialization of the content type `{}` is unsupported",
+                content_type
             );
             let rejection = Rejection::from_validation_entry("data_type", err).context(self);
             return Err(rejection);
-        }
+        };
         M::before_extract()
             .await
             .map_err(|err| Rejection::from_error(err).context(self))?;
 
-        let is_form = data_type == "form";
         let bytes = self
             .read_body_bytes()
             .await
             .map_err(|err| Rejection::from_validation_entry("body", err).context(self))?;
         let extension = self.get_data::<M::Extension>();
-        if is_form {
-            let mut data = serde_qs::from_bytes(&bytes)
-                .map_err(|err| Rejection::from_validation_entry("body", err).context(self))?;
-            match M::before_validation(&mut data, extension.as_ref()).await {
-                Ok(()) => {
-                    let validation = model.read_map(&data);
+        let translate_keys = helper::get_key_case(self.get_header("x-key-case")) == KeyCase::Camel;
+        let mut data = body_format
+            .parse_map(&bytes)
+            .map_err(|err| Rejection::from_validation_entry("body", err).context(self))?;
+        if translate_keys {
+            helper::translate_object_keys(&mut data, KeyCase::Snake);
+        }
+        data.append(&mut entries);
+        match M::before_validation(&mut data, extension.as_ref()).await {
+            Ok(()) => {
+                let validation = model.read_map(&data);
+                model
+                    .after_validation(&mut data)
+                    .await
+                    .map_err(|err| Rejection::from_error(err).context(self))?;
+                if let Some(extension) = extension {
                     model
-                        .after_validation(&mut data)
+                        .after_extract(extension)
                         .await
                         .map_err(|err| Rejection::from_error(err).context(self))?;
-                    if let Some(extension) = extension {
-                        model
-                            .after_extract(extension)
-                            .await
-                            .map_err(|err| Rejection::from_error(err).context(self))?;
-                    }
-                    if validation.is_success() {
-                        Ok(Response::with_context(S::OK, self))
-                    } else {
-                        Err(Rejection::bad_request(validation).context(self))
-                    }
                 }
-                Err(err) => Err(Rejection::from_error(err).context(self)),
-            }
-        } else {
-            let mut data = serde_json::from_slice(&bytes)
-                .map_err(|err| Rejection::from_validation_entry("body", err).context(self))?;
-            match M::before_validation(&mut data, extension.as_ref()).await {
-                Ok(()) => {
-                    let validation = model.read_map(&data);
-                    model
-                        .after_validation(&mut data)
-                        .await
-                        .map_err(|err| Rejection::from_error(err).context(self))?;
-                    if let Some(extension) = extension {
-                        model
-                            .after_extract(extension)
-                            .await
-                            .map_err(|err| Rejection::from_error(err).context(self))?;
-                    }
-                    if validation.is_success() {
-                        Ok(Response::with_context(S::OK, self))
-                    } else {
-                        Err(Rejection::bad_request(validation).context(self))
-                    }
+                if validation.is_success() {
+                    Ok(Response::with_context(S::OK, self))
+                } else {
+                    Err(Rejection::bad_request(validation).context(self))
                 }
-                Err(err) => Err(Rejection::from_error(err).context(self)),
             }
+            Err(err) => Err(Rejection::from_error(err).context(self)),
         }
     }
 
     /// Makes an HTTP request to the provided URL.
+    ///
+    /// With the `auth` feature, the request is signed by [`Authentication::sign_request_with()`]
+    /// if the `auth` option is an object with the fields `access_key_id`, `secret`
+    /// and the optional `service` and `headers` (the prefixes of canonicalized headers).
     async fn fetch(&self, url: &str, options: Option<&Map>) -> Result<reqwest::Response, Error> {
-        let trace_context = self.new_trace_context();
-        Agent::request_builder(url, options)?
-            .header("traceparent", trace_context.traceparent())
-            .header("tracestate", trace_context.tracestate())
-            .send()
-            .await
-            .map_err(Error::from)
+        let request = async {
+            let trace_context = self.new_trace_context();
+            #[allow(unused_mut)]
+            let mut request_builder = Agent::request_builder(url, options)?
+                .header("traceparent", trace_context.traceparent())
+                .header("tracestate", trace_context.tracestate());
+            #[cfg(feature = "auth")]
+            if let Some(auth) = options.and_then(|map| map.get_object("auth")) {
+                let Some(access_key_id) = auth.get_str("access_key_id") else {
+                    return Err(warn!("the `access_key_id` should be specified for signing"));
+                };
+                let Some(secret) = auth.get_str("secret") else {
+                    return Err(warn!("the `secret` should be specified for signing"));
+                };
+                let service_name = auth
+                    .get_str("service")
+                    .map(|s| s.to_owned())
+                    .unwrap_or_else(|| Agent::name().to_owned());
+                let header_prefixes = auth.get_str_array("headers").unwrap_or_default();
+                let access_key_id = AccessKeyId::from(access_key_id);
+                let secret_access_key = SecretAccessKey::from(secret.as_bytes().to_vec());
+                Authentication::sign_request_with(
+                    &mut request_builder,
+                    &service_name,
+                    &header_prefixes,
+                    &access_key_id,
+                    &secret_access_key,
+                )?;
+            }
+            request_builder.send().await.map_err(Error::from)
+        };
+
+        // Creates a client span so that the `traceparent` refers to the outgoing request
+        #[cfg(feature = "otel")]
+        let request = {
+            use tracing::{field::Empty, Instrument};
+            use zino_core::extension::JsonObjectExt;
+
+            let method = options
+                .and_then(|map| map.get_str("method"))
+                .unwrap_or("GET");
+            let span = tracing::info_span!(
+                "HTTP client request",
+                "otel.kind" = "client",
+                "otel.status_code" = Empty,
+                "http.request.method" = method,
+                "http.response.status_code" = Empty,
+                "url.full" = url,
+            );
+            async move {
+                let result = request.instrument(span.clone()).await;
+                match &result {
+                    Ok(response) => {
+                        let status_code = response.status();
+                        let otel_status_code = if status_code.is_server_error() {
+                            "ERROR"
+                        } else {
+                            "OK"
+                        };
+                        span.record("http.response.status_code", status_code.as_u16());
+                        span.record("otel.status_code", otel_status_code);
+                    }
+                    Err(_) => {
+                        span.record("otel.status_code", "ERROR");
+                    }
+                }
+                result
+            }
+        };
+        request.await
     }
 
     /// Makes an HTTP request to the provided URL and
@@ -787,6 +1152,34 @@ pub trait RequestContext {
         }
     }
 
+    /// Translates the localization message for the plural form,
+    /// where the count is injected as the `$count` argument.
+    /// It falls back to the default locale with a warning if the message is missing.
+    #[cfg(feature = "i18n")]
+    fn translate_plural<'a>(
+        &self,
+        message: &str,
+        count: impl Into<FluentValue<'a>>,
+        args: Option<FluentArgs<'a>>,
+    ) -> SharedString {
+        let locale = self.locale().unwrap_or_else(i18n::default_locale);
+        i18n::translate_plural(&locale, message, count, args)
+    }
+
+    /// Formats the number with the conventions of the locale.
+    #[cfg(feature = "i18n")]
+    fn format_number(&self, number: f64) -> String {
+        let locale = self.locale().unwrap_or_else(i18n::default_locale);
+        i18n::format_number(&locale, number)
+    }
+
+    /// Formats the date-time with the conventions of the locale.
+    #[cfg(feature = "i18n")]
+    fn format_datetime(&self, datetime: &zino_core::datetime::DateTime) -> String {
+        let locale = self.locale().unwrap_or_else(i18n::default_locale);
+        i18n::format_datetime(&locale, datetime)
+    }
+
     /// Constructs a new subscription instance.
     fn subscription(&self) -> Subscription {
         let mut subscription = self.parse_query::<Subscription>().unwrap_or_default();
@@ -810,3 +1203,100 @@ pub trait RequestContext {
         event
     }
 }
+
+#[cfg(all(test, feature = "auth", feature = "test-utils"))]
+mod tests {
+    use super::RequestContext;
+    use crate::test::MockRequest;
+    use hmac::Hmac;
+    use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+    use std::time::Duration;
+    use zino_auth::{AccessKeyId, Authentication, SecretAccessKey};
+    use zino_core::{crypto::Digest, datetime::DateTime};
+
+    fn sign(
+        client: &ClientWithMiddleware,
+        path: &str,
+        date: Option<DateTime>,
+        header_prefixes: &[&str],
+    ) -> reqwest::Request {
+        let mut request_builder = client
+            .post(format!("http://localhost{path}"))
+            .header("content-type", "application/json")
+            .header("X-Zino-Nonce", "abc")
+            .header("x-zino-app", " demo ")
+            .body(r#"{"name":"alice"}"#);
+        if let Some(date) = date {
+            request_builder = request_builder.header("date", date.to_utc_string());
+        }
+        Authentication::sign_request_with(
+            &mut request_builder,
+            "ZINO",
+            header_prefixes,
+            &AccessKeyId::from("test-key"),
+            &SecretAccessKey::from(b"test-secret".to_vec()),
+        )
+        .unwrap();
+        request_builder.build().unwrap()
+    }
+
+    fn verify(request: &reqwest::Request, path: &str, header_prefixes: &[&str]) -> Vec<String> {
+        let mut req = MockRequest::new(request.method().clone(), path);
+        for (name, value) in request.headers() {
+            req = req.header(name.as_str(), value.to_str().unwrap());
+        }
+
+        let mut authentication = req.parse_authentication().unwrap();
+        assert_eq!(authentication.service_name(), "ZINO");
+        assert_eq!(authentication.access_key_id(), "test-key");
+        if !header_prefixes.is_empty() {
+            let mut headers = request
+                .headers()
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_str().unwrap().to_owned()))
+                .collect::<Vec<_>>();
+            headers.reverse();
+            authentication.set_headers(headers.into_iter(), header_prefixes);
+        }
+
+        let secret_access_key = SecretAccessKey::from(b"test-secret".to_vec());
+        let validation = authentication.validate_with::<Hmac<Digest>>(&secret_access_key);
+        validation
+            .invalid_params()
+            .into_iter()
+            .map(|param| param.to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn it_verifies_signed_requests() {
+        let client = ClientBuilder::new(reqwest::Client::new()).build();
+
+        let request = sign(&client, "/user/new", None, &[]);
+        assert!(request.headers().contains_key("date"));
+        assert!(request.headers().contains_key("content-md5"));
+        assert!(verify(&request, "/user/new", &[]).is_empty());
+        assert_eq!(verify(&request, "/user/update", &[]), ["signature"]);
+
+        // The query is not a part of the canonicalized resource.
+        let request = sign(&client, "/user/new?fields=id", None, &[]);
+        assert!(verify(&request, "/user/new?fields=id", &[]).is_empty());
+
+        // The canonicalized headers are matched case-insensitively
+        // and sorted by names regardless of the order in which they are sent.
+        let request = sign(&client, "/user/new", None, &["x-zino-"]);
+        assert!(verify(&request, "/user/new", &["X-Zino-"]).is_empty());
+        assert_eq!(verify(&request, "/user/new", &[]), ["signature"]);
+
+        // An existing `date` header is signed as it is,
+        // and it should be within 15 minutes of the server time.
+        let date = DateTime::now() - Duration::from_secs(10 * 60);
+        let request = sign(&client, "/user/new", Some(date), &[]);
+        assert_eq!(request.headers()["date"], date.to_utc_string().as_str());
+        assert!(verify(&request, "/user/new", &[]).is_empty());
+
+        let date = DateTime::now() + Duration::from_secs(20 * 60);
+        let request = sign(&client, "/user/new", Some(date), &[]);
+        assert_eq!(verify(&request, "/user/new", &[]), ["date"]);
+    }
+}
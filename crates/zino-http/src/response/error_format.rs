@@ -0,0 +1,218 @@
+use std::sync::OnceLock;
+use zino_core::{extension::TomlTableExt, state::State, JsonValue, Map, Uuid};
+
+/// Context of an error response passed to the [`ErrorResponseFormat`].
+#[derive(Debug, Clone)]
+pub struct ErrorContext<'a> {
+    /// Status code.
+    pub(super) status_code: u16,
+    /// Error code.
+    pub(super) error_code: Option<JsonValue>,
+    /// A URI reference that identifies the problem type.
+    pub(super) type_uri: Option<&'a str>,
+    /// A short, human-readable summary of the problem type.
+    pub(super) title: Option<&'a str>,
+    /// A human-readable explanation specific to this occurrence of the problem.
+    pub(super) detail: Option<&'a str>,
+    /// A URI reference that identifies the specific occurrence of the problem.
+    pub(super) instance: Option<&'a str>,
+    /// Request ID.
+    pub(super) request_id: Uuid,
+    /// Validation entries in the form `(field, message)`.
+    pub(super) validation_entries: &'a [(String, String)],
+    /// JSON data.
+    pub(super) data: &'a JsonValue,
+}
+
+impl<'a> ErrorContext<'a> {
+    /// Returns the status code.
+    #[inline]
+    pub fn status_code(&self) -> u16 {
+        self.status_code
+    }
+
+    /// Returns the error code.
+    #[inline]
+    pub fn error_code(&self) -> Option<&JsonValue> {
+        self.error_code.as_ref()
+    }
+
+    /// Returns the URI reference that identifies the problem type.
+    #[inline]
+    pub fn type_uri(&self) -> Option<&'a str> {
+        self.type_uri
+    }
+
+    /// Returns the short, human-readable summary of the problem type.
+    #[inline]
+    pub fn title(&self) -> Option<&'a str> {
+        self.title
+    }
+
+    /// Returns the human-readable explanation specific to this occurrence of the problem.
+    #[inline]
+    pub fn detail(&self) -> Option<&'a str> {
+        self.detail
+    }
+
+    /// Returns the URI reference that identifies the specific occurrence of the problem.
+    #[inline]
+    pub fn instance(&self) -> Option<&'a str> {
+        self.instance
+    }
+
+    /// Returns the request ID.
+    #[inline]
+    pub fn request_id(&self) -> Uuid {
+        self.request_id
+    }
+
+    /// Returns the validation entries in the form `(field, message)`.
+    #[inline]
+    pub fn validation_entries(&self) -> &'a [(String, String)] {
+        self.validation_entries
+    }
+
+    /// Returns the JSON data.
+    #[inline]
+    pub fn data(&self) -> &'a JsonValue {
+        self.data
+    }
+}
+
+/// Format of the JSON body for error responses.
+///
+/// A custom format can be registered at boot time by `Cluster::with_error_format()`,
+/// or a builtin format can be selected by the `error-format` of the `[response]` config.
+pub trait ErrorResponseFormat: Send + Sync + 'static {
+    /// Returns the content type of the error response.
+    fn content_type(&self) -> &'static str;
+
+    /// Formats the error response as a JSON value.
+    /// Returns `None` to use the default problem details.
+    fn format(&self, ctx: &ErrorContext<'_>) -> Option<JsonValue>;
+}
+
+/// Problem details for HTTP APIs defined in [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807).
+/// This is the default format.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProblemDetails;
+
+impl ErrorResponseFormat for ProblemDetails {
+    #[inline]
+    fn content_type(&self) -> &'static str {
+        "application/problem+json; charset=utf-8"
+    }
+
+    #[inline]
+    fn format(&self, _ctx: &ErrorContext<'_>) -> Option<JsonValue> {
+        None
+    }
+}
+
+/// A compact format in the form `{ code, message, details }`.
+///
+/// The `code` is the error code if present, or the status code otherwise.
+/// The validation entries are mapped into the `details` array as `{ field, message }`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompactErrorFormat;
+
+impl ErrorResponseFormat for CompactErrorFormat {
+    #[inline]
+    fn content_type(&self) -> &'static str {
+        "application/json; charset=utf-8"
+    }
+
+    fn format(&self, ctx: &ErrorContext<'_>) -> Option<JsonValue> {
+        let mut body = Map::new();
+        let code = ctx
+            .error_code()
+            .cloned()
+            .unwrap_or_else(|| ctx.status_code().into());
+        body.insert("code".to_owned(), code);
+
+        let message = ctx.detail().or_else(|| ctx.title()).unwrap_or_default();
+        body.insert("message".to_owned(), message.into());
+
+        let details = ctx
+            .validation_entries()
+            .iter()
+            .map(|(field, message)| {
+                let mut detail = Map::new();
+                detail.insert("field".to_owned(), field.as_str().into());
+                detail.insert("message".to_owned(), message.as_str().into());
+                JsonValue::Object(detail)
+            })
+            .collect::<Vec<_>>();
+        body.insert("details".to_owned(), details.into());
+
+        let request_id = ctx.request_id();
+        if !request_id.is_nil() {
+            body.insert("request_id".to_owned(), request_id.to_string().into());
+        }
+        Some(body.into())
+    }
+}
+
+/// Sets the format of the error response.
+/// It should be called before the server starts.
+pub fn set_error_format(format: impl ErrorResponseFormat) {
+    let format: Box<dyn ErrorResponseFormat> = Box::new(format);
+    if ERROR_FORMAT.set(Some(format)).is_err() {
+        tracing::warn!("the error response format has already been initialized");
+    }
+}
+
+/// Returns the custom format of the error response.
+/// It is `None` if the default problem details are used.
+pub(super) fn get_error_format() -> Option<&'static dyn ErrorResponseFormat> {
+    ERROR_FORMAT
+        .get_or_init(|| {
+            let format = State::shared()
+                .get_config("response")
+                .and_then(|config| config.get_str("error-format"))?;
+            match format {
+                "problem" | "rfc7807" => None,
+                "compact" => Some(Box::new(CompactErrorFormat) as Box<dyn ErrorResponseFormat>),
+                _ => {
+                    tracing::warn!("unsupported error response format `{format}`");
+                    None
+                }
+            }
+        })
+        .as_deref()
+}
+
+/// Custom format of the error response.
+static ERROR_FORMAT: OnceLock<Option<Box<dyn ErrorResponseFormat>>> = OnceLock::new();
+
+#[cfg(test)]
+mod tests {
+    use super::{CompactErrorFormat, ErrorContext, ErrorResponseFormat};
+    use zino_core::{JsonValue, Uuid};
+
+    #[test]
+    fn it_formats_compact_errors() {
+        let entries = vec![
+            ("name".to_owned(), "should be nonempty".to_owned()),
+            ("age".to_owned(), "should be positive".to_owned()),
+        ];
+        let ctx = ErrorContext {
+            status_code: 400,
+            error_code: None,
+            type_uri: None,
+            title: Some("BadRequest"),
+            detail: None,
+            instance: None,
+            request_id: Uuid::nil(),
+            validation_entries: &entries,
+            data: &JsonValue::Null,
+        };
+        let body = CompactErrorFormat.format(&ctx).unwrap();
+        assert_eq!(body["code"], 400);
+        assert_eq!(body["message"], "BadRequest");
+        assert_eq!(body["details"][1]["field"], "age");
+        assert_eq!(body["details"][1]["message"], "should be positive");
+        assert!(body.get("request_id").is_none());
+    }
+}
@@ -1,34 +1,57 @@
 //! Constructing responses and rejections.
 
 use crate::{
-    helper,
+    helper::{self, KeyCase},
     request::RequestContext,
     timing::{ServerTiming, TimingMetric},
 };
 use bytes::Bytes;
 use etag::EntityTag;
+use futures::{stream::BoxStream, Stream};
 use serde::Serialize;
 use smallvec::SmallVec;
 use std::{
     marker::PhantomData,
     time::{Duration, Instant},
 };
+use url::Url;
 use zino_core::{
-    error::Error, extension::JsonValueExt, trace::TraceContext, validation::Validation, JsonValue,
-    SharedString, Uuid,
+    application::{ComputePool, LoadShedder, MaintenanceMode},
+    datetime::DateTime,
+    encoding::csv::{CsvOptions, CsvWriter},
+    error::Error,
+    extension::JsonValueExt,
+    model::Page,
+    trace::TraceContext,
+    validation::Validation,
+    JsonValue, SharedString, Uuid,
 };
 use zino_storage::NamedFile;
 
 #[cfg(feature = "cookie")]
 use cookie::Cookie;
 
+#[cfg(feature = "view")]
+use crate::security::CspNonce;
+
+mod error_format;
+mod panic;
 mod rejection;
 mod response_code;
+mod sse;
+mod stream;
 mod webhook;
 
+pub use error_format::{
+    set_error_format, CompactErrorFormat, ErrorContext, ErrorResponseFormat, ProblemDetails,
+};
+pub use panic::HandlerPanic;
 pub use rejection::{ExtractRejection, Rejection};
 pub use response_code::ResponseCode;
+pub use sse::SseStream;
+pub use stream::BytesStream;
 pub use webhook::WebHook;
+pub use zino_channel::SseEvent;
 
 /// An HTTP status code for http v0.2.
 #[cfg(feature = "http02")]
@@ -84,6 +107,12 @@ pub struct Response<S: ResponseCode> {
     #[serde(rename = "data")]
     #[serde(skip_serializing_if = "JsonValue::is_null")]
     json_data: JsonValue,
+    /// Warnings for the client.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<SharedString>,
+    /// Indicates the JSON data is a map of failed validation entries or not.
+    #[serde(skip)]
+    validation_failed: bool,
     /// Bytes data.
     #[serde(skip)]
     bytes_data: Bytes,
@@ -93,15 +122,31 @@ pub struct Response<S: ResponseCode> {
     /// Content type.
     #[serde(skip)]
     content_type: Option<SharedString>,
+    /// Options for the CSV data.
+    #[serde(skip)]
+    csv_options: Option<CsvOptions>,
+    /// Case of the keys in JSON data.
+    #[serde(skip)]
+    key_case: KeyCase,
     /// Trace context.
     #[serde(skip)]
     trace_context: Option<TraceContext>,
+    /// Nonce for the `Content-Security-Policy` header.
+    #[cfg(feature = "view")]
+    #[serde(skip)]
+    csp_nonce: Option<CspNonce>,
     /// Server timing.
     #[serde(skip)]
     server_timing: ServerTiming,
     /// Custom headers.
     #[serde(skip)]
     headers: SmallVec<[(SharedString, String); 8]>,
+    /// Stream of server-sent events.
+    #[serde(skip)]
+    sse_stream: Option<SseStream>,
+    /// Stream of bytes.
+    #[serde(skip)]
+    bytes_stream: Option<BytesStream>,
     /// Phantom type of response code.
     #[serde(skip)]
     phantom: PhantomData<S>,
@@ -125,12 +170,20 @@ impl<S: ResponseCode> Response<S> {
             start_time: Instant::now(),
             request_id: Uuid::nil(),
             json_data: JsonValue::Null,
+            warnings: Vec::new(),
+            validation_failed: false,
             bytes_data: Bytes::new(),
             data_transformer: None,
             content_type: None,
+            csv_options: None,
+            key_case: helper::get_key_case(None),
             trace_context: None,
+            #[cfg(feature = "view")]
+            csp_nonce: None,
             server_timing: ServerTiming::new(),
             headers: SmallVec::new(),
+            sse_stream: None,
+            bytes_stream: None,
             phantom: PhantomData,
         };
         if success {
@@ -158,12 +211,20 @@ impl<S: ResponseCode> Response<S> {
             start_time: ctx.start_time(),
             request_id: ctx.request_id(),
             json_data: JsonValue::Null,
+            warnings: Vec::new(),
+            validation_failed: false,
             bytes_data: Bytes::new(),
             data_transformer: None,
             content_type: None,
+            csv_options: None,
+            key_case: helper::get_key_case(ctx.get_header("x-key-case")),
             trace_context: None,
+            #[cfg(feature = "view")]
+            csp_nonce: ctx.get_data::<CspNonce>(),
             server_timing: ServerTiming::new(),
             headers: SmallVec::new(),
+            sse_stream: None,
+            bytes_stream: None,
             phantom: PhantomData,
         };
         if success {
@@ -172,6 +233,7 @@ impl<S: ResponseCode> Response<S> {
             res.detail = message;
         }
         res.trace_context = Some(ctx.new_trace_context());
+        res.apply_route_deprecation(ctx.request_path());
         res
     }
 
@@ -180,24 +242,90 @@ impl<S: ResponseCode> Response<S> {
         self.instance = (!self.is_success()).then(|| ctx.instance().into());
         self.start_time = ctx.start_time();
         self.request_id = ctx.request_id();
+        self.key_case = helper::get_key_case(ctx.get_header("x-key-case"));
         self.trace_context = Some(ctx.new_trace_context());
+        #[cfg(feature = "view")]
+        {
+            self.csp_nonce = ctx.get_data::<CspNonce>();
+        }
+        self.apply_route_deprecation(ctx.request_path());
         self
     }
 
+    /// Provides the request context and sets the response for the maintenance mode,
+    /// which has a `Retry-After` header. The response code should be `503 Service Unavailable`.
+    /// An HTML page will be rendered if the template has been configured
+    /// and the client prefers `text/html`.
+    pub fn maintenance<Ctx: RequestContext>(self, ctx: &Ctx) -> Self {
+        let mut res = self.context(ctx);
+        res.insert_header("retry-after", MaintenanceMode::retry_after().as_secs());
+        res.set_message(MaintenanceMode::message());
+        #[cfg(feature = "view")]
+        if let Some(template_name) = MaintenanceMode::template() {
+            let supported = ["application/json", "text/html"];
+            if ctx.negotiate_content_type(&supported) == Some("text/html") {
+                let mut data = zino_core::Map::new();
+                data.insert("message".to_owned(), MaintenanceMode::message().into());
+                data.insert(
+                    "retry_after".to_owned(),
+                    MaintenanceMode::retry_after().as_secs().into(),
+                );
+                res = res.render(template_name, data);
+            }
+        }
+        res
+    }
+
+    /// Provides the request context and sets the response for a request shed by
+    /// the load shedding, which has a `Retry-After` header.
+    /// The response code should be `503 Service Unavailable`.
+    pub fn shed_load<Ctx: RequestContext>(self, ctx: &Ctx) -> Self {
+        let mut res = self.context(ctx);
+        res.insert_header("retry-after", LoadShedder::retry_after().as_secs().max(1));
+        res.set_message("The service is overloaded, please try again later");
+        res
+    }
+
     /// Renders a template with the data and sets it as the reponse.
+    /// The CSP nonce of the request is available as `csp_nonce` in the template.
     #[cfg(feature = "view")]
-    pub fn render<T: Serialize>(mut self, template_name: &str, data: T) -> Self {
-        let result = serde_json::to_value(data)
-            .map_err(|err| err.into())
-            .and_then(|mut value| {
-                if let Some(data) = value.as_object_mut() {
-                    let mut map = zino_core::Map::new();
-                    map.append(data);
-                    crate::view::render(template_name, map)
-                } else {
-                    Err(zino_core::warn!("invalid template data"))
-                }
-            });
+    pub fn render<T: Serialize>(self, template_name: &str, data: T) -> Self {
+        let result = self
+            .template_data(data)
+            .and_then(|data| crate::view::render(template_name, data));
+        self.set_render_result(result)
+    }
+
+    /// Renders a template source with the data and sets it as the reponse.
+    /// The CSP nonce of the request is available as `csp_nonce` in the template.
+    #[cfg(feature = "view")]
+    pub fn render_str<T: Serialize>(self, source: &str, data: T) -> Self {
+        let result = self
+            .template_data(data)
+            .and_then(|data| crate::view::render_str(source, data));
+        self.set_render_result(result)
+    }
+
+    /// Converts the data into a JSON object for the template.
+    #[cfg(feature = "view")]
+    fn template_data<T: Serialize>(&self, data: T) -> Result<zino_core::Map, Error> {
+        let mut value = serde_json::to_value(data)?;
+        if let Some(data) = value.as_object_mut() {
+            let mut map = zino_core::Map::new();
+            map.append(data);
+            if let Some(nonce) = self.csp_nonce.as_ref() {
+                map.entry("csp_nonce")
+                    .or_insert_with(|| nonce.as_str().into());
+            }
+            Ok(map)
+        } else {
+            Err(zino_core::warn!("invalid template data"))
+        }
+    }
+
+    /// Sets the rendered content as the response, or an internal server error.
+    #[cfg(feature = "view")]
+    fn set_render_result(mut self, result: Result<String, Error>) -> Self {
         match result {
             Ok(content) => {
                 self.json_data = content.into();
@@ -300,6 +428,7 @@ impl<S: ResponseCode> Response<S> {
         match serde_json::to_value(data) {
             Ok(value) => {
                 self.json_data = value;
+                self.validation_failed = false;
                 self.bytes_data = Bytes::new();
             }
             Err(err) => self.set_error_message(err),
@@ -310,13 +439,38 @@ impl<S: ResponseCode> Response<S> {
     #[inline]
     pub fn set_json_data(&mut self, data: impl Into<JsonValue>) {
         self.json_data = data.into();
+        self.validation_failed = false;
         self.bytes_data = Bytes::new();
     }
 
+    /// Sets the page as the JSON data, and exposes the pagination metadata
+    /// in the `X-Total-Count` and `Link` headers.
+    pub fn set_page_data<T, Ctx>(&mut self, page: Page<T>, ctx: &Ctx)
+    where
+        T: Into<JsonValue>,
+        Ctx: RequestContext,
+    {
+        if let Some(total) = page.total_rows() {
+            self.insert_header("x-total-count", total);
+        }
+
+        let links = page.links(ctx.request_path(), ctx.get_query_string());
+        if !links.is_empty() {
+            let link = links
+                .into_iter()
+                .map(|(rel, uri)| format!(r#"<{uri}>; rel="{rel}""#))
+                .collect::<Vec<_>>()
+                .join(", ");
+            self.insert_header("link", link);
+        }
+        self.set_json_data(page);
+    }
+
     /// Sets the bytes data.
     #[inline]
     pub fn set_bytes_data(&mut self, data: impl Into<Bytes>) {
         self.json_data = JsonValue::Null;
+        self.validation_failed = false;
         self.bytes_data = data.into();
     }
 
@@ -324,6 +478,7 @@ impl<S: ResponseCode> Response<S> {
     #[inline]
     pub fn set_validation_data(&mut self, validation: Validation) {
         self.json_data = validation.into_map().into();
+        self.validation_failed = true;
         self.bytes_data = Bytes::new();
     }
 
@@ -331,6 +486,7 @@ impl<S: ResponseCode> Response<S> {
     #[inline]
     pub fn set_data_transformer(&mut self, transformer: DataTransformer) {
         self.data_transformer = Some(transformer);
+        self.csv_options = None;
     }
 
     /// Sets the content type.
@@ -347,6 +503,12 @@ impl<S: ResponseCode> Response<S> {
     /// - `text/csv`
     /// - `text/html`
     /// - `text/plain`
+    /// - `text/tab-separated-values`
+    ///
+    /// The content types with the `+json` suffix are serialized as JSON,
+    /// and so are the ones registered by [`register_data_type()`] with the data type `json`.
+    ///
+    /// [`register_data_type()`]: crate::request::register_data_type
     #[inline]
     pub fn set_content_type(&mut self, content_type: impl Into<SharedString>) {
         self.content_type = Some(content_type.into());
@@ -391,12 +553,39 @@ impl<S: ResponseCode> Response<S> {
     /// Sets the CSV data as the response body.
     #[inline]
     pub fn set_csv_response(&mut self, data: impl Into<JsonValue>) {
-        fn inner<S: ResponseCode>(res: &mut Response<S>, data: JsonValue) {
-            res.set_json_data(data);
-            res.set_content_type("text/csv; charset=utf-8");
-            res.set_data_transformer(|data| Ok(data.to_csv(Vec::new())?.into()));
-        }
-        inner::<S>(self, data.into())
+        self.set_csv_response_with(data, CsvOptions::default());
+    }
+
+    /// Sets the CSV or TSV data as the response body with the options.
+    /// The content type is derived from the delimiter.
+    #[inline]
+    pub fn set_csv_response_with(&mut self, data: impl Into<JsonValue>, options: CsvOptions) {
+        self.set_json_data(data);
+        self.set_content_type(options.content_type());
+        self.csv_options = Some(options);
+        self.data_transformer = None;
+    }
+
+    /// Sets the stream of rows as the response body, which are encoded as CSV or TSV
+    /// incrementally instead of being buffered.
+    pub fn set_csv_stream_response(
+        &mut self,
+        rows: impl Stream<Item = zino_core::Map> + Send + 'static,
+        options: CsvOptions,
+    ) {
+        self.set_content_type(options.content_type());
+        self.bytes_stream = Some(BytesStream::with_csv_rows(rows, options));
+    }
+
+    /// Sets the stream of bytes as the response body.
+    #[inline]
+    pub fn set_stream_response(
+        &mut self,
+        content_type: impl Into<SharedString>,
+        chunks: impl Stream<Item = Bytes> + Send + 'static,
+    ) {
+        self.set_content_type(content_type);
+        self.bytes_stream = Some(BytesStream::new(chunks));
     }
 
     /// Sets the plain text as the response body.
@@ -413,12 +602,39 @@ impl<S: ResponseCode> Response<S> {
         self.set_content_type("application/octet-stream");
     }
 
+    /// Sets the stream of server-sent events as the response body.
+    ///
+    /// The events are sent as `text/event-stream` without buffering or compression,
+    /// and a keep-alive comment is sent after the interval of silence
+    /// configured by `[response] sse-keep-alive` (15s by default).
+    pub fn set_sse_response(&mut self, events: impl Stream<Item = SseEvent> + Send + 'static) {
+        self.set_content_type("text/event-stream");
+        self.insert_header("cache-control", "no-cache");
+        self.insert_header("x-accel-buffering", "no");
+        self.sse_stream = Some(SseStream::new(events));
+    }
+
+    /// Sets the keep-alive interval for the server-sent events.
+    /// A zero duration disables the keep-alive comments.
+    #[inline]
+    pub fn set_sse_keep_alive(&mut self, keep_alive: Duration) {
+        if let Some(sse_stream) = self.sse_stream.as_mut() {
+            sse_stream.set_keep_alive(keep_alive);
+        }
+    }
+
     /// Sets the request ID.
     #[inline]
     pub(crate) fn set_request_id(&mut self, request_id: Uuid) {
         self.request_id = request_id;
     }
 
+    /// Sets the case of the keys in JSON data.
+    #[inline]
+    pub(crate) fn set_key_case(&mut self, key_case: KeyCase) {
+        self.key_case = key_case;
+    }
+
     /// Sets the trace context from headers.
     #[inline]
     pub(crate) fn set_trace_context(&mut self, trace_context: Option<TraceContext>) {
@@ -463,6 +679,41 @@ impl<S: ResponseCode> Response<S> {
         self.headers.push((name.into(), value.to_string()));
     }
 
+    /// Marks the endpoint as deprecated since the date, which emits the `Deprecation` header,
+    /// the `Link` header with `rel="deprecation"`, and the `Sunset` header if provided.
+    /// A warning will also be added to the JSON response if `body-warnings` has been enabled
+    /// in the `[api.deprecations]` config.
+    pub fn set_deprecation(&mut self, date: DateTime, link: Option<Url>, sunset: Option<DateTime>) {
+        self.headers.retain(|(key, value)| match key.as_ref() {
+            "deprecation" | "sunset" => false,
+            "link" => !value.ends_with(r#"rel="deprecation""#),
+            _ => true,
+        });
+        self.insert_header("deprecation", date.to_utc_string());
+        if let Some(sunset) = sunset {
+            self.insert_header("sunset", sunset.to_utc_string());
+        }
+        if let Some(link) = &link {
+            self.insert_header("link", format!(r#"<{link}>; rel="deprecation""#));
+        }
+        if helper::body_warnings_enabled() {
+            let warning = helper::format_deprecation_warning(&date, link.as_ref(), sunset.as_ref());
+            self.add_warning(warning);
+        }
+    }
+
+    /// Adds a warning which will be appended to the JSON response.
+    #[inline]
+    pub fn add_warning(&mut self, warning: impl Into<SharedString>) {
+        self.warnings.push(warning.into());
+    }
+
+    /// Returns the warnings.
+    #[inline]
+    pub fn warnings(&self) -> &[SharedString] {
+        &self.warnings
+    }
+
     /// Gets a custome header with the given name.
     #[inline]
     pub fn get_header(&self, name: &str) -> Option<&str> {
@@ -534,6 +785,8 @@ impl<S: ResponseCode> Response<S> {
                 "application/octet-stream"
             } else if self.is_success() {
                 "application/json; charset=utf-8"
+            } else if let Some(format) = error_format::get_error_format() {
+                format.content_type()
             } else {
                 "application/problem+json; charset=utf-8"
             }
@@ -546,6 +799,24 @@ impl<S: ResponseCode> Response<S> {
         &self.headers
     }
 
+    /// Takes the stream of bytes, leaving `None` in its place.
+    #[inline]
+    pub fn take_bytes_stream(&mut self) -> Option<BoxStream<'static, Bytes>> {
+        self.bytes_stream.take().and_then(|stream| stream.take())
+    }
+
+    /// Returns `true` if the response body is a stream of server-sent events.
+    #[inline]
+    pub fn is_sse_response(&self) -> bool {
+        self.sse_stream.is_some()
+    }
+
+    /// Takes the stream of server-sent events, leaving `None` in its place.
+    #[inline]
+    pub fn take_sse_stream(&mut self) -> Option<SseStream> {
+        self.sse_stream.take()
+    }
+
     /// Returns the trace context in the form `(traceparent, tracestate)`.
     pub fn trace_context(&self) -> (String, String) {
         if let Some(ref trace_context) = self.trace_context {
@@ -563,10 +834,68 @@ impl<S: ResponseCode> Response<S> {
         self.server_timing.to_string()
     }
 
+    /// Transforms the JSON data into a byte buffer in the [`ComputePool`]
+    /// if the estimated size of the payload exceeds the offload threshold.
+    ///
+    /// It only applies to the data transformer and the CSV or JSON Lines responses,
+    /// so that the serialization in [`read_bytes()`](Self::read_bytes) will not block
+    /// the async runtime for large exports.
+    pub async fn transform_data(&mut self) -> Result<(), Error> {
+        if self.json_data.is_null() || !self.bytes_data.is_empty() {
+            return Ok(());
+        }
+
+        let transformer = self.data_transformer;
+        let content_type = self.content_type().to_owned();
+        let data_type = helper::get_data_type(&content_type);
+        let csv_options = self.csv_options.clone().or_else(|| match data_type {
+            "csv" => Some(CsvOptions::default()),
+            "tsv" => Some(CsvOptions::tsv()),
+            _ => None,
+        });
+        let is_csv = csv_options.is_some();
+        let is_jsonlines = data_type == "ndjson";
+        if transformer.is_none() && !is_csv && !is_jsonlines {
+            return Ok(());
+        }
+
+        let estimated_size = match &self.json_data {
+            JsonValue::Array(vec) => vec.first().map_or(0, |v| v.to_string().len()) * vec.len(),
+            JsonValue::String(s) => s.len(),
+            _ => 0,
+        };
+        if !ComputePool::should_offload(estimated_size) {
+            return Ok(());
+        }
+
+        let mut data = self.json_data.take();
+        let key_case = self.key_case;
+        let bytes = ComputePool::execute(move || {
+            if key_case != KeyCase::Snake {
+                helper::translate_keys(&mut data, key_case);
+            }
+            if let Some(transformer) = transformer {
+                transformer(&data)
+            } else if let Some(options) = csv_options {
+                Ok(encode_csv(&data, options)?.into())
+            } else {
+                Ok(data.to_jsonlines(Vec::new())?.into())
+            }
+        })
+        .await??;
+        self.bytes_data = bytes;
+        self.data_transformer = None;
+        self.content_type = Some(content_type.into());
+        Ok(())
+    }
+
     /// Reads the response into a byte buffer.
     pub fn read_bytes(&mut self) -> Result<Bytes, Error> {
         let has_bytes_data = !self.bytes_data.is_empty();
         let has_json_data = !self.json_data.is_null();
+        if has_json_data && !has_bytes_data && self.key_case != KeyCase::Snake {
+            helper::translate_keys(&mut self.json_data, self.key_case);
+        }
         let bytes_opt = if has_bytes_data {
             Some(self.bytes_data.clone())
         } else if has_json_data {
@@ -579,13 +908,22 @@ impl<S: ResponseCode> Response<S> {
             None
         };
         if let Some(bytes) = bytes_opt {
-            let etag = EntityTag::from_data(&bytes);
-            self.insert_header("x-etag", etag);
+            if self.get_header("x-etag").is_none() {
+                let etag = EntityTag::from_data(&bytes);
+                self.insert_header("x-etag", etag);
+            }
             return Ok(bytes);
         }
 
-        let content_type = self.content_type();
-        let (bytes, etag_opt) = if crate::helper::check_json_content_type(content_type) {
+        let data_type = helper::get_data_type(self.content_type());
+        let (bytes, etag_opt) = if data_type == "json" {
+            if let Some(body) = self.format_error() {
+                let bytes = serde_json::to_vec(&body)?;
+                let etag = EntityTag::from_data(&bytes);
+                self.insert_header("x-etag", etag);
+                return Ok(bytes.into());
+            }
+
             let (capacity, etag_opt) = if has_json_data {
                 let data = serde_json::to_vec(&self.json_data)?;
                 let etag = EntityTag::from_data(&data);
@@ -598,9 +936,13 @@ impl<S: ResponseCode> Response<S> {
             (bytes, etag_opt)
         } else if has_json_data {
             let value = &self.json_data;
-            let bytes = if content_type.starts_with("text/csv") {
+            let bytes = if let Some(options) = self.csv_options.clone() {
+                encode_csv(value, options)?
+            } else if data_type == "csv" {
                 value.to_csv(Vec::new())?
-            } else if content_type.starts_with("application/jsonlines") {
+            } else if data_type == "tsv" {
+                encode_csv(value, CsvOptions::tsv())?
+            } else if data_type == "ndjson" {
                 value.to_jsonlines(Vec::new())?
             } else if let JsonValue::String(s) = value {
                 s.as_bytes().to_vec()
@@ -611,11 +953,68 @@ impl<S: ResponseCode> Response<S> {
         } else {
             (Vec::new(), None)
         };
-        let etag = etag_opt.unwrap_or_else(|| EntityTag::from_data(&bytes));
-        self.insert_header("x-etag", etag);
+        if self.get_header("x-etag").is_none() {
+            let etag = etag_opt.unwrap_or_else(|| EntityTag::from_data(&bytes));
+            self.insert_header("x-etag", etag);
+        }
         Ok(bytes.into())
     }
 
+    /// Formats the body of an error response with the custom [`ErrorResponseFormat`].
+    /// Returns `None` if the response is successful or the default problem details are used.
+    fn format_error(&self) -> Option<JsonValue> {
+        if self.is_success() {
+            return None;
+        }
+
+        let format = error_format::get_error_format()?;
+        let validation_entries = match &self.json_data {
+            JsonValue::Object(map) if self.validation_failed => map
+                .iter()
+                .map(|(key, value)| {
+                    let message = value
+                        .as_str()
+                        .map(|s| s.to_owned())
+                        .unwrap_or_else(|| value.to_string());
+                    (key.to_owned(), message)
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let ctx = ErrorContext {
+            status_code: self.status_code,
+            error_code: self
+                .error_code
+                .as_ref()
+                .and_then(|code| serde_json::to_value(code).ok()),
+            type_uri: self.type_uri.as_deref(),
+            title: self.title.as_deref(),
+            detail: self.detail.as_deref(),
+            instance: self.instance.as_deref(),
+            request_id: self.request_id,
+            validation_entries: &validation_entries,
+            data: &self.json_data,
+        };
+        format.format(&ctx)
+    }
+
+    /// Applies the deprecation configured for the route.
+    pub(crate) fn apply_route_deprecation(&mut self, path: &str) {
+        if let Some(deprecation) = helper::get_route_deprecation(path) {
+            #[cfg(feature = "metrics")]
+            metrics::counter!(
+                "zino_http_deprecated_requests_total",
+                "route" => deprecation.prefix.clone(),
+            )
+            .increment(1);
+            self.set_deprecation(
+                deprecation.date,
+                deprecation.link.clone(),
+                deprecation.sunset,
+            );
+        }
+    }
+
     /// Gets the response time.
     ///
     /// # Note
@@ -685,6 +1084,12 @@ impl Response<StatusCode> {
         Response::new(StatusCode::CREATED)
     }
 
+    /// Constructs a new response with status `304 Not Modified`.
+    #[inline]
+    pub fn not_modified() -> Self {
+        Response::new(StatusCode::NOT_MODIFIED)
+    }
+
     /// Constructs a new response with status `400 Bad Request`.
     #[inline]
     pub fn bad_request() -> Self {
@@ -722,3 +1127,10 @@ impl<S: ResponseCode> From<Validation> for Response<S> {
         }
     }
 }
+
+/// Encodes the JSON data as CSV with the options.
+fn encode_csv(data: &JsonValue, options: CsvOptions) -> Result<Vec<u8>, Error> {
+    let mut writer = CsvWriter::new(Vec::new(), options)?;
+    writer.write_value(data)?;
+    Ok(writer.into_inner()?)
+}
@@ -0,0 +1,172 @@
+use super::{Response, ResponseCode};
+use crate::request::RequestContext;
+use futures::future;
+use std::{
+    any::Any,
+    backtrace::Backtrace,
+    cell::{Cell, RefCell},
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::pin,
+    sync::Once,
+    task::Poll,
+};
+use zino_core::{
+    application::{Agent, Application},
+    extension::TomlTableExt,
+    state::State,
+    LazyLock,
+};
+
+/// A panic caught in the request handler.
+///
+/// The handler future is polled inside of [`std::panic::catch_unwind`].
+/// It is asserted to be unwind safe, since the future is dropped after a panic
+/// and the shared states should be guarded by the poisoning locks or atomics.
+#[derive(Debug)]
+pub struct HandlerPanic {
+    /// Panic message.
+    message: String,
+    /// Backtrace captured by the panic hook.
+    backtrace: Option<Backtrace>,
+}
+
+impl HandlerPanic {
+    /// Polls the future to completion and catches the panic if it occurs.
+    pub async fn catch<F: Future>(fut: F) -> Result<F::Output, Self> {
+        let mut fut = pin!(fut);
+        future::poll_fn(|cx| match Self::catch_call(|| fut.as_mut().poll(cx)) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(panic) => Poll::Ready(Err(panic)),
+        })
+        .await
+    }
+
+    /// Invokes the closure and catches the panic if it occurs.
+    pub fn catch_call<T>(f: impl FnOnce() -> T) -> Result<T, Self> {
+        PANIC_HOOK.call_once(install_panic_hook);
+
+        let capturing = CAPTURING.replace(true);
+        let result = panic::catch_unwind(AssertUnwindSafe(f));
+        CAPTURING.set(capturing);
+        result.map_err(Self::from_payload)
+    }
+
+    /// Creates a new instance from the panic payload.
+    fn from_payload(payload: Box<dyn Any + Send>) -> Self {
+        let message = if let Some(s) = payload.downcast_ref::<String>() {
+            s.to_owned()
+        } else if let Some(s) = payload.downcast_ref::<&str>() {
+            (*s).to_owned()
+        } else {
+            "unknown panic message".to_owned()
+        };
+        Self {
+            message,
+            backtrace: BACKTRACE.take(),
+        }
+    }
+
+    /// Returns the panic message.
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the backtrace if it has been captured.
+    #[inline]
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        self.backtrace.as_ref()
+    }
+
+    /// Logs the panic and converts it into a `500 Internal Server Error` response
+    /// with the request context. The panic message is only included in the response
+    /// if `expose-panic-message` is enabled in the `[server]` table,
+    /// which defaults to `true` for the `dev` environment.
+    pub fn into_response<S, Ctx>(self, ctx: &Ctx) -> Response<S>
+    where
+        S: ResponseCode,
+        Ctx: RequestContext,
+    {
+        let mut res = Response::new(S::INTERNAL_SERVER_ERROR).context(ctx);
+        let request_id = res.request_id().to_string();
+        let trace_id = res.trace_id().to_string();
+        let route = ctx.matched_route();
+        let message = self.message;
+        let backtrace = self.backtrace.map(|backtrace| backtrace.to_string());
+        tracing::error!(
+            request_id,
+            trace_id,
+            route = route.as_ref(),
+            backtrace,
+            "request handler panicked: {message}"
+        );
+        #[cfg(feature = "metrics")]
+        metrics::counter!(
+            "zino_http_panics_total",
+            "route" => route.into_owned(),
+        )
+        .increment(1);
+
+        if *EXPOSE_PANIC_MESSAGE {
+            res.set_message(message);
+        }
+        res
+    }
+}
+
+/// Installs a panic hook to capture the backtraces of the handler panics.
+/// The previous hook is still called for the panics outside of the handlers.
+fn install_panic_hook() {
+    let prev_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if CAPTURING.get() {
+            BACKTRACE.set(Some(Backtrace::force_capture()));
+        } else {
+            prev_hook(info);
+        }
+    }));
+}
+
+thread_local! {
+    /// Flag to indicate whether a handler future is being polled on the current thread.
+    static CAPTURING: Cell<bool> = const { Cell::new(false) };
+
+    /// Backtrace of the last panic in the handler on the current thread.
+    static BACKTRACE: RefCell<Option<Backtrace>> = const { RefCell::new(None) };
+}
+
+/// Flag to install the panic hook only once.
+static PANIC_HOOK: Once = Once::new();
+
+/// Flag to indicate whether the panic message is included in the response.
+static EXPOSE_PANIC_MESSAGE: LazyLock<bool> = LazyLock::new(|| {
+    State::shared()
+        .get_config("server")
+        .and_then(|config| config.get_bool("expose-panic-message"))
+        .unwrap_or_else(|| Agent::env().is_dev())
+});
+
+#[cfg(test)]
+mod tests {
+    use super::HandlerPanic;
+    use futures::executor;
+
+    #[test]
+    fn it_catches_handler_panics() {
+        let result = executor::block_on(HandlerPanic::catch(async { 42 }));
+        assert_eq!(result.ok(), Some(42));
+
+        let result = executor::block_on(HandlerPanic::catch(async {
+            let data: Vec<u8> = Vec::new();
+            data[1]
+        }));
+        let panic = result.expect_err("the handler should panic");
+        assert!(panic.message().contains("index out of bounds"));
+        assert!(panic.backtrace().is_some());
+
+        let result = executor::block_on(HandlerPanic::catch(async { "ok" }));
+        assert_eq!(result.ok(), Some("ok"));
+    }
+}
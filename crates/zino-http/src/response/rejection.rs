@@ -1,7 +1,13 @@
 use self::RejectionKind::*;
 use super::Response;
 use crate::request::{Context, RequestContext};
-use zino_core::{error::Error, trace::TraceContext, validation::Validation, warn, SharedString};
+use std::borrow::Cow;
+use zino_core::{
+    error::{Error, ErrorKind},
+    trace::TraceContext,
+    validation::Validation,
+    warn, SharedString,
+};
 
 /// A rejection response type.
 #[derive(Debug)]
@@ -20,6 +26,8 @@ pub struct Rejection {
 enum RejectionKind {
     /// 400 Bad Request
     BadRequest(Validation),
+    /// 400 Bad Request
+    InvalidRequest(Error),
     /// 401 Unauthorized
     Unauthorized(Error),
     /// 403 Forbidden
@@ -28,12 +36,22 @@ enum RejectionKind {
     NotFound(Error),
     /// 405 Method Not Allowed
     MethodNotAllowed(Error),
+    /// 406 Not Acceptable
+    NotAcceptable(Error),
     /// 409 Conflict
     Conflict(Error),
+    /// 409 Conflict
+    ConflictingRequest(Validation),
+    /// 412 Precondition Failed
+    PreconditionFailed(Error),
+    /// 428 Precondition Required
+    PreconditionRequired(Error),
     /// 500 Internal Server Error
     InternalServerError(Error),
     /// 503 Service Unavailable
     ServiceUnavailable(Error),
+    /// 504 Gateway Timeout
+    GatewayTimeout(Error),
 }
 
 impl Rejection {
@@ -87,6 +105,16 @@ impl Rejection {
         }
     }
 
+    /// Creates a `406 Not Acceptable` rejection.
+    #[inline]
+    pub fn not_acceptable(err: impl Into<Error>) -> Self {
+        Self {
+            kind: NotAcceptable(err.into()),
+            context: None,
+            trace_context: None,
+        }
+    }
+
     /// Creates a `409 Conflict` rejection.
     #[inline]
     pub fn conflict(err: impl Into<Error>) -> Self {
@@ -97,6 +125,36 @@ impl Rejection {
         }
     }
 
+    /// Creates a `409 Conflict` rejection with the validation entries for the conflicting fields.
+    #[inline]
+    pub fn conflicting_request(validation: Validation) -> Self {
+        Self {
+            kind: ConflictingRequest(validation),
+            context: None,
+            trace_context: None,
+        }
+    }
+
+    /// Creates a `412 Precondition Failed` rejection.
+    #[inline]
+    pub fn precondition_failed(err: impl Into<Error>) -> Self {
+        Self {
+            kind: PreconditionFailed(err.into()),
+            context: None,
+            trace_context: None,
+        }
+    }
+
+    /// Creates a `428 Precondition Required` rejection.
+    #[inline]
+    pub fn precondition_required(err: impl Into<Error>) -> Self {
+        Self {
+            kind: PreconditionRequired(err.into()),
+            context: None,
+            trace_context: None,
+        }
+    }
+
     /// Creates a `500 Internal Server Error` rejection.
     #[inline]
     pub fn internal_server_error(err: impl Into<Error>) -> Self {
@@ -117,6 +175,16 @@ impl Rejection {
         }
     }
 
+    /// Creates a `504 Gateway Timeout` rejection.
+    #[inline]
+    pub fn gateway_timeout(err: impl Into<Error>) -> Self {
+        Self {
+            kind: GatewayTimeout(err.into()),
+            context: None,
+            trace_context: None,
+        }
+    }
+
     /// Creates a new instance with the validation entry.
     #[inline]
     pub fn from_validation_entry(key: impl Into<SharedString>, err: impl Into<Error>) -> Self {
@@ -124,9 +192,27 @@ impl Rejection {
         Self::bad_request(validation)
     }
 
-    /// Creates a new instance from an error classified by the error message.
+    /// Creates a new instance from an error classified by the error kind.
+    /// For the errors of [`ErrorKind::Internal`], it falls back to the error message.
     pub fn from_error(err: impl Into<Error>) -> Self {
         fn inner(err: Error) -> Rejection {
+            match err.kind() {
+                ErrorKind::NotFound => return Rejection::not_found(err),
+                ErrorKind::Conflict => return Rejection::conflict(err),
+                ErrorKind::Validation => {
+                    return Rejection {
+                        kind: InvalidRequest(err),
+                        context: None,
+                        trace_context: None,
+                    };
+                }
+                ErrorKind::Unauthorized => return Rejection::unauthorized(err),
+                ErrorKind::Forbidden => return Rejection::forbidden(err),
+                ErrorKind::Timeout => return Rejection::gateway_timeout(err),
+                ErrorKind::Unavailable => return Rejection::service_unavailable(err),
+                _ => (),
+            }
+
             let message = err.message();
             if message.starts_with("401 Unauthorized") {
                 Rejection::unauthorized(err)
@@ -136,8 +222,14 @@ impl Rejection {
                 Rejection::not_found(err)
             } else if message.starts_with("405 Method Not Allowed") {
                 Rejection::method_not_allowed(err)
+            } else if message.starts_with("406 Not Acceptable") {
+                Rejection::not_acceptable(err)
             } else if message.starts_with("409 Conflict") {
                 Rejection::conflict(err)
+            } else if message.starts_with("412 Precondition Failed") {
+                Rejection::precondition_failed(err)
+            } else if message.starts_with("428 Precondition Required") {
+                Rejection::precondition_required(err)
             } else if message.starts_with("503 Service Unavailable") {
                 Rejection::service_unavailable(err)
             } else {
@@ -165,14 +257,59 @@ impl Rejection {
     #[inline]
     pub fn status_code(&self) -> u16 {
         match &self.kind {
-            BadRequest(_) => 400,
+            BadRequest(_) | InvalidRequest(_) => 400,
             Unauthorized(_) => 401,
             Forbidden(_) => 403,
             NotFound(_) => 404,
             MethodNotAllowed(_) => 405,
-            Conflict(_) => 409,
+            NotAcceptable(_) => 406,
+            Conflict(_) | ConflictingRequest(_) => 409,
+            PreconditionFailed(_) => 412,
+            PreconditionRequired(_) => 428,
             InternalServerError(_) => 500,
             ServiceUnavailable(_) => 503,
+            GatewayTimeout(_) => 504,
+        }
+    }
+
+    /// Returns the error code.
+    ///
+    /// It is derived from the error kind if the kind is not [`ErrorKind::Internal`],
+    /// otherwise it is determined by the rejection kind.
+    pub fn error_code(&self) -> SharedString {
+        let err = match &self.kind {
+            BadRequest(_) => return ErrorKind::Validation.error_code(),
+            ConflictingRequest(_) => return ErrorKind::Conflict.error_code(),
+            InvalidRequest(err)
+            | Unauthorized(err)
+            | Forbidden(err)
+            | NotFound(err)
+            | MethodNotAllowed(err)
+            | NotAcceptable(err)
+            | Conflict(err)
+            | PreconditionFailed(err)
+            | PreconditionRequired(err)
+            | InternalServerError(err)
+            | ServiceUnavailable(err)
+            | GatewayTimeout(err) => err,
+        };
+        let kind = err.kind();
+        if !kind.is_internal() {
+            return kind.error_code();
+        }
+        match &self.kind {
+            BadRequest(_) | InvalidRequest(_) => ErrorKind::Validation.error_code(),
+            Unauthorized(_) => ErrorKind::Unauthorized.error_code(),
+            Forbidden(_) => ErrorKind::Forbidden.error_code(),
+            NotFound(_) => ErrorKind::NotFound.error_code(),
+            MethodNotAllowed(_) => Cow::Borrowed("method_not_allowed"),
+            NotAcceptable(_) => Cow::Borrowed("not_acceptable"),
+            Conflict(_) | ConflictingRequest(_) => ErrorKind::Conflict.error_code(),
+            PreconditionFailed(_) => Cow::Borrowed("precondition_failed"),
+            PreconditionRequired(_) => Cow::Borrowed("precondition_required"),
+            InternalServerError(_) => ErrorKind::Internal.error_code(),
+            ServiceUnavailable(_) => ErrorKind::Unavailable.error_code(),
+            GatewayTimeout(_) => ErrorKind::Timeout.error_code(),
         }
     }
 }
@@ -181,12 +318,18 @@ macro_rules! impl_from_rejection {
     ($Ty:ty) => {
         impl From<Rejection> for Response<$Ty> {
             fn from(rejection: Rejection) -> Self {
+                let error_code = rejection.error_code();
                 let mut res = match rejection.kind {
                     BadRequest(validation) => {
                         let mut res = Response::new(<$Ty>::BAD_REQUEST);
                         res.set_validation_data(validation);
                         res
                     }
+                    InvalidRequest(err) => {
+                        let mut res = Response::new(<$Ty>::BAD_REQUEST);
+                        res.set_error_message(err);
+                        res
+                    }
                     Unauthorized(err) => {
                         let mut res = Response::new(<$Ty>::UNAUTHORIZED);
                         res.set_error_message(err);
@@ -207,11 +350,31 @@ macro_rules! impl_from_rejection {
                         res.set_error_message(err);
                         res
                     }
+                    NotAcceptable(err) => {
+                        let mut res = Response::new(<$Ty>::NOT_ACCEPTABLE);
+                        res.set_error_message(err);
+                        res
+                    }
                     Conflict(err) => {
                         let mut res = Response::new(<$Ty>::CONFLICT);
                         res.set_error_message(err);
                         res
                     }
+                    ConflictingRequest(validation) => {
+                        let mut res = Response::new(<$Ty>::CONFLICT);
+                        res.set_validation_data(validation);
+                        res
+                    }
+                    PreconditionFailed(err) => {
+                        let mut res = Response::new(<$Ty>::PRECONDITION_FAILED);
+                        res.set_error_message(err);
+                        res
+                    }
+                    PreconditionRequired(err) => {
+                        let mut res = Response::new(<$Ty>::PRECONDITION_REQUIRED);
+                        res.set_error_message(err);
+                        res
+                    }
                     InternalServerError(err) => {
                         let mut res = Response::new(<$Ty>::INTERNAL_SERVER_ERROR);
                         res.set_error_message(err);
@@ -222,11 +385,21 @@ macro_rules! impl_from_rejection {
                         res.set_error_message(err);
                         res
                     }
+                    GatewayTimeout(err) => {
+                        let mut res = Response::new(<$Ty>::GATEWAY_TIMEOUT);
+                        res.set_error_message(err);
+                        res
+                    }
                 };
+                res.set_error_code(error_code);
                 if let Some(ctx) = rejection.context {
                     res.set_instance(ctx.instance().to_owned());
                     res.set_start_time(ctx.start_time());
                     res.set_request_id(ctx.request_id());
+                    res.set_key_case(ctx.key_case());
+
+                    let path = ctx.instance().split('?').next().unwrap_or_default();
+                    res.apply_route_deprecation(path);
                 }
                 res.set_trace_context(rejection.trace_context);
                 res
@@ -294,3 +467,35 @@ macro_rules! reject {
         return Err(Rejection::$kind(err).context(&$ctx).into());
     }};
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Rejection;
+    use zino_core::error::{Error, ErrorKind};
+
+    #[test]
+    fn it_classifies_errors_by_kind() {
+        let err = Error::new("duplicate key").with_kind(ErrorKind::Conflict);
+        let rejection = Rejection::from_error(err.wrap("fail to insert the user"));
+        assert_eq!(rejection.status_code(), 409);
+        assert_eq!(rejection.error_code(), "conflict");
+
+        let err = Error::new("fail to connect").with_kind(ErrorKind::Timeout);
+        let rejection = Rejection::from_error(err);
+        assert_eq!(rejection.status_code(), 504);
+        assert_eq!(rejection.error_code(), "timeout");
+
+        let err = Error::new("quota exceeded").with_kind(ErrorKind::Other("quota".into()));
+        let rejection = Rejection::from_error(err);
+        assert_eq!(rejection.status_code(), 500);
+        assert_eq!(rejection.error_code(), "quota");
+
+        let rejection = Rejection::from_error(Error::new("404 Not Found: missing user"));
+        assert_eq!(rejection.status_code(), 404);
+        assert_eq!(rejection.error_code(), "not_found");
+
+        let rejection = Rejection::with_message("unexpected error");
+        assert_eq!(rejection.status_code(), 500);
+        assert_eq!(rejection.error_code(), "internal");
+    }
+}
@@ -0,0 +1,126 @@
+use bytes::Bytes;
+use futures::{
+    future::{self, Either},
+    stream::{self, BoxStream},
+    Stream, StreamExt,
+};
+use std::{
+    fmt,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use zino_channel::SseEvent;
+use zino_core::{extension::TomlTableExt, state::State, LazyLock};
+
+/// A stream of server-sent events for the response body.
+///
+/// The stream is shared by the clones of a response and can be taken only once.
+#[derive(Clone)]
+pub struct SseStream {
+    /// Stream of events.
+    events: Arc<Mutex<Option<BoxStream<'static, SseEvent>>>>,
+    /// Interval of silence after which a keep-alive comment is sent.
+    keep_alive: Duration,
+}
+
+impl SseStream {
+    /// Creates a new instance with the stream of events.
+    #[inline]
+    pub fn new(events: impl Stream<Item = SseEvent> + Send + 'static) -> Self {
+        Self {
+            events: Arc::new(Mutex::new(Some(events.boxed()))),
+            keep_alive: *DEFAULT_KEEP_ALIVE,
+        }
+    }
+
+    /// Sets the keep-alive interval. A zero duration disables the keep-alive comments.
+    #[inline]
+    pub fn set_keep_alive(&mut self, keep_alive: Duration) {
+        self.keep_alive = keep_alive;
+    }
+
+    /// Returns the keep-alive interval.
+    #[inline]
+    pub fn keep_alive(&self) -> Duration {
+        self.keep_alive
+    }
+
+    /// Takes the stream of events, leaving `None` in its place.
+    #[inline]
+    pub fn take(&self) -> Option<BoxStream<'static, SseEvent>> {
+        self.events.lock().ok().and_then(|mut events| events.take())
+    }
+
+    /// Converts the events into a stream of bytes in the wire format of `text/event-stream`.
+    ///
+    /// Since `zino-http` is runtime-agnostic, the `sleep` function should be provided
+    /// by the framework adapter to inject keep-alive comments after the interval of silence.
+    /// The events stream is dropped as soon as the returned stream is dropped.
+    pub fn into_bytes_stream<F, Fut>(self, sleep: F) -> impl Stream<Item = Bytes>
+    where
+        F: Fn(Duration) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let keep_alive = self.keep_alive;
+        let events = self.take().unwrap_or_else(|| stream::empty().boxed());
+        stream::unfold((events, sleep), move |(mut events, sleep)| async move {
+            let next_event = if keep_alive.is_zero() {
+                events.next().await
+            } else {
+                let timeout = Box::pin(sleep(keep_alive));
+                match future::select(events.next(), timeout).await {
+                    Either::Left((event, _)) => event,
+                    Either::Right(_) => {
+                        let comment = Bytes::from_static(SseEvent::keep_alive_comment().as_bytes());
+                        return Some((comment, (events, sleep)));
+                    }
+                }
+            };
+            next_event.map(|event| (Bytes::from(event.to_string()), (events, sleep)))
+        })
+    }
+}
+
+impl fmt::Debug for SseStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SseStream")
+            .field("keep_alive", &self.keep_alive)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Default keep-alive interval for server-sent events.
+static DEFAULT_KEEP_ALIVE: LazyLock<Duration> = LazyLock::new(|| {
+    State::shared()
+        .get_config("response")
+        .and_then(|config| config.get_duration("sse-keep-alive"))
+        .unwrap_or_else(|| Duration::from_secs(15))
+});
+
+#[cfg(test)]
+mod tests {
+    use super::SseStream;
+    use futures::{executor, future, stream, StreamExt};
+    use zino_channel::SseEvent;
+
+    #[test]
+    fn it_formats_sse_events() {
+        let mut event = SseEvent::new("line1\nline2");
+        event.set_id("42");
+        event.set_event("progress");
+        let events = stream::iter([event, SseEvent::new("done")]);
+        let sse_stream = SseStream::new(events);
+        let chunks = executor::block_on(
+            sse_stream
+                .into_bytes_stream(|_| future::pending::<()>())
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(
+            chunks[0].as_ref(),
+            b"id: 42\nevent: progress\ndata: line1\ndata: line2\n\n"
+        );
+        assert_eq!(chunks[1].as_ref(), b"data: done\n\n");
+    }
+}
@@ -0,0 +1,97 @@
+use bytes::Bytes;
+use futures::{
+    future,
+    stream::{BoxStream, Stream, StreamExt},
+};
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+use zino_core::{
+    encoding::csv::{CsvOptions, CsvWriter},
+    Map,
+};
+
+/// Maximum number of the ready rows encoded in a chunk.
+const MAX_CHUNK_ROWS: usize = 256;
+
+/// A stream of bytes for the response body.
+///
+/// The stream is shared by the clones of a response and can be taken only once.
+#[derive(Clone)]
+pub struct BytesStream {
+    /// Stream of chunks.
+    chunks: Arc<Mutex<Option<BoxStream<'static, Bytes>>>>,
+}
+
+impl BytesStream {
+    /// Creates a new instance with the stream of chunks.
+    #[inline]
+    pub fn new(chunks: impl Stream<Item = Bytes> + Send + 'static) -> Self {
+        Self {
+            chunks: Arc::new(Mutex::new(Some(chunks.boxed()))),
+        }
+    }
+
+    /// Creates a new instance which encodes the rows as CSV incrementally.
+    /// The stream ends early if a row fails to be encoded.
+    pub fn with_csv_rows(
+        rows: impl Stream<Item = Map> + Send + 'static,
+        options: CsvOptions,
+    ) -> Self {
+        let writer = CsvWriter::new(Vec::new(), options).ok();
+        let chunks = rows
+            .ready_chunks(MAX_CHUNK_ROWS)
+            .scan(writer, |writer, rows| {
+                let chunk = writer.as_mut().and_then(|writer| {
+                    for row in rows.iter() {
+                        if let Err(err) = writer.write_map(row) {
+                            tracing::error!("fail to encode the CSV row: {err}");
+                            return None;
+                        }
+                    }
+                    writer
+                        .take_bytes()
+                        .inspect_err(|err| tracing::error!("fail to encode the CSV rows: {err}"))
+                        .ok()
+                });
+                future::ready(chunk.map(Bytes::from))
+            });
+        Self::new(chunks)
+    }
+
+    /// Takes the stream of chunks, leaving `None` in its place.
+    #[inline]
+    pub fn take(&self) -> Option<BoxStream<'static, Bytes>> {
+        self.chunks.lock().ok().and_then(|mut chunks| chunks.take())
+    }
+}
+
+impl fmt::Debug for BytesStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BytesStream").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BytesStream;
+    use futures::{executor, stream, StreamExt};
+    use zino_core::{encoding::csv::CsvOptions, extension::JsonObjectExt, Map};
+
+    #[test]
+    fn it_streams_csv_rows() {
+        let rows = (1..=3).map(|i| {
+            let mut map = Map::new();
+            map.upsert("id", i);
+            map.upsert("name", format!("user{i}"));
+            map
+        });
+        let options = CsvOptions::tsv().columns(["name", "id"]);
+        let stream = BytesStream::with_csv_rows(stream::iter(rows), options);
+        let chunks = executor::block_on(stream.take().unwrap().collect::<Vec<_>>());
+        let bytes = chunks.concat();
+        assert_eq!(bytes, b"name\tid\nuser1\t1\nuser2\t2\nuser3\t3\n");
+        assert!(stream.take().is_none());
+    }
+}
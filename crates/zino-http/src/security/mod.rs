@@ -0,0 +1,431 @@
+//! Security headers for the responses.
+
+use std::fmt;
+use toml::{Table, Value};
+use zino_core::{application::ServerTag, extension::TomlTableExt, state::State, LazyLock, Uuid};
+
+/// A preset of the security headers which is applied to the routes under a path prefix.
+///
+/// The default preset is configured by the `[server.headers]` table, and the overrides
+/// for the route groups can be specified by the `[[server.headers.route]]` tables.
+/// The missing fields of a route are inherited from the default preset. A header can be
+/// disabled by setting it to `false` or an empty string.
+///
+/// # Examples
+///
+/// ```toml
+/// [server.headers]
+/// strict-transport-security = "max-age=31536000; includeSubDomains"
+/// behind-proxy = true
+/// frame-options = "DENY"
+/// referrer-policy = "no-referrer"
+/// permissions-policy = "camera=(), microphone=(), geolocation=()"
+/// noindex-scopes = ["internal"]
+///
+/// [[server.headers.route]]
+/// path-prefix = "/admin"
+/// content-security-policy = "default-src 'self'; script-src 'self' 'nonce-{nonce}'"
+/// frame-ancestors = "'self'"
+/// ```
+///
+/// The `Strict-Transport-Security` header is only emitted for a listener with TLS,
+/// or when `behind-proxy` is turned on for a TLS-terminating reverse proxy.
+/// The `Content-Security-Policy` header is only emitted for the content types
+/// specified by `csp-content-types`, which defaults to `["text/html"]`, and the `{nonce}`
+/// placeholder is replaced by a per-response [`CspNonce`] which is exposed to the templates
+/// as `csp_nonce`. If `frame-ancestors` is specified, it is appended to the CSP
+/// and the `X-Frame-Options` header is omitted. The `X-Robots-Tag: noindex` header is
+/// emitted for the listeners whose scopes are specified by `noindex-scopes`.
+///
+/// A listener configured by `[[server.listener]]` can override the preset
+/// with its own `headers` table, whose missing fields are inherited from `[server.headers]`.
+/// The headers set explicitly by a handler always take precedence over the preset.
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders {
+    /// Path prefix of the routes.
+    path_prefix: String,
+    /// Value of the `Strict-Transport-Security` header.
+    strict_transport_security: Option<String>,
+    /// A flag to indicate whether the server is behind a TLS-terminating proxy.
+    behind_proxy: bool,
+    /// A flag to indicate whether the listener has TLS enabled.
+    tls: bool,
+    /// Value of the `X-Content-Type-Options` header.
+    content_type_options: Option<String>,
+    /// Value of the `X-Frame-Options` header.
+    frame_options: Option<String>,
+    /// Sources of the `frame-ancestors` directive.
+    frame_ancestors: Option<String>,
+    /// Value of the `Content-Security-Policy` header.
+    content_security_policy: Option<String>,
+    /// Content types which the CSP is applied to.
+    csp_content_types: Vec<String>,
+    /// Value of the `Referrer-Policy` header.
+    referrer_policy: Option<String>,
+    /// Value of the `Permissions-Policy` header.
+    permissions_policy: Option<String>,
+    /// Scopes of the listeners which emit the `X-Robots-Tag: noindex` header.
+    noindex_scopes: Vec<ServerTag>,
+}
+
+impl SecurityHeaders {
+    /// Creates a new instance with the configuration.
+    pub fn with_config(config: &Table) -> Self {
+        let mut preset = Self::default();
+        preset.read_config(config);
+        preset
+    }
+
+    /// Returns the preset for the request path, or `None` if `[server.headers]`
+    /// is not configured.
+    ///
+    /// The route with the longest matching path prefix is selected,
+    /// otherwise the default preset is used.
+    pub fn for_path(path: &str) -> Option<&'static Self> {
+        let (default_preset, routes) = SECURITY_HEADERS.as_ref()?;
+        Some(select_preset(default_preset, routes, path))
+    }
+
+    /// Returns the preset for the request path accepted by the listener with the scope.
+    ///
+    /// It falls back to [`for_path()`](Self::for_path) if the listener
+    /// does not override the `headers` config.
+    pub fn for_scope(scope: &ServerTag, path: &str) -> Option<&'static Self> {
+        SCOPED_SECURITY_HEADERS
+            .iter()
+            .find(|(server_tag, ..)| server_tag == scope)
+            .map(|(_, default_preset, routes)| select_preset(default_preset, routes, path))
+            .or_else(|| Self::for_path(path))
+    }
+
+    /// Returns the path prefix of the routes.
+    #[inline]
+    pub fn path_prefix(&self) -> &str {
+        &self.path_prefix
+    }
+
+    /// Returns `true` if the CSP contains the `{nonce}` placeholder.
+    #[inline]
+    pub fn requires_nonce(&self) -> bool {
+        self.content_security_policy
+            .as_ref()
+            .is_some_and(|csp| csp.contains("{nonce}"))
+    }
+
+    /// Returns `true` if the CSP should be applied to the content type.
+    pub fn is_csp_applied(&self, content_type: Option<&str>) -> bool {
+        if self.csp_content_types.iter().any(|s| s == "*") {
+            return true;
+        }
+        content_type.is_some_and(|content_type| {
+            let essence = content_type
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase();
+            self.csp_content_types.contains(&essence)
+        })
+    }
+
+    /// Returns the security headers for a response of the content type
+    /// sent by the listener with the scope.
+    pub fn response_headers(
+        &self,
+        scope: &ServerTag,
+        content_type: Option<&str>,
+        nonce: Option<&CspNonce>,
+    ) -> Vec<(&'static str, String)> {
+        let mut headers = Vec::with_capacity(8);
+        if self.tls || self.behind_proxy {
+            if let Some(hsts) = self.strict_transport_security.as_ref() {
+                headers.push(("strict-transport-security", hsts.to_owned()));
+            }
+        }
+        if let Some(options) = self.content_type_options.as_ref() {
+            headers.push(("x-content-type-options", options.to_owned()));
+        }
+        if self.frame_ancestors.is_none() {
+            if let Some(options) = self.frame_options.as_ref() {
+                headers.push(("x-frame-options", options.to_owned()));
+            }
+        }
+        if self.is_csp_applied(content_type) {
+            if let Some(csp) = self.content_security_policy(nonce) {
+                headers.push(("content-security-policy", csp));
+            }
+        }
+        if let Some(policy) = self.referrer_policy.as_ref() {
+            headers.push(("referrer-policy", policy.to_owned()));
+        }
+        if let Some(policy) = self.permissions_policy.as_ref() {
+            headers.push(("permissions-policy", policy.to_owned()));
+        }
+        if self.noindex_scopes.contains(scope) {
+            headers.push(("x-robots-tag", "noindex".to_owned()));
+        }
+        headers
+    }
+
+    /// Returns the value of the `Content-Security-Policy` header,
+    /// where the `{nonce}` placeholder is replaced by the nonce.
+    fn content_security_policy(&self, nonce: Option<&CspNonce>) -> Option<String> {
+        let mut csp = self.content_security_policy.clone().unwrap_or_default();
+        if csp.contains("{nonce}") {
+            let nonce = nonce.map(|nonce| nonce.as_str()).unwrap_or_default();
+            csp = csp.replace("{nonce}", nonce);
+        }
+        if let Some(sources) = self.frame_ancestors.as_ref() {
+            let csp = csp.trim_end().trim_end_matches(';');
+            return if csp.is_empty() {
+                Some(format!("frame-ancestors {sources}"))
+            } else {
+                Some(format!("{csp}; frame-ancestors {sources}"))
+            };
+        }
+        (!csp.is_empty()).then_some(csp)
+    }
+
+    /// Reads the configuration and overrides the fields.
+    fn read_config(&mut self, config: &Table) {
+        if let Some(path_prefix) = config.get_str("path-prefix") {
+            self.path_prefix = path_prefix.to_owned();
+        }
+        if let Some(value) = parse_header_value(config, "strict-transport-security") {
+            self.strict_transport_security = value;
+        }
+        if let Some(behind_proxy) = config.get_bool("behind-proxy") {
+            self.behind_proxy = behind_proxy;
+        }
+        if let Some(value) = parse_header_value(config, "content-type-options") {
+            self.content_type_options = value;
+        }
+        if let Some(value) = parse_header_value(config, "frame-options") {
+            self.frame_options = value;
+        }
+        if let Some(value) = parse_header_value(config, "frame-ancestors") {
+            self.frame_ancestors = value;
+        }
+        if let Some(value) = parse_header_value(config, "content-security-policy") {
+            self.content_security_policy = value;
+        }
+        if let Some(content_types) = config.get_str_array("csp-content-types") {
+            self.csp_content_types = content_types
+                .into_iter()
+                .map(|content_type| content_type.to_ascii_lowercase())
+                .collect();
+        }
+        if let Some(value) = parse_header_value(config, "referrer-policy") {
+            self.referrer_policy = value;
+        }
+        if let Some(value) = parse_header_value(config, "permissions-policy") {
+            self.permissions_policy = value;
+        }
+        if let Some(scopes) = config.get_str_array("noindex-scopes") {
+            self.noindex_scopes = scopes.into_iter().map(ServerTag::from).collect();
+        }
+    }
+}
+
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        Self {
+            path_prefix: "/".to_owned(),
+            strict_transport_security: Some("max-age=31536000; includeSubDomains".to_owned()),
+            behind_proxy: false,
+            tls: false,
+            content_type_options: Some("nosniff".to_owned()),
+            frame_options: Some("DENY".to_owned()),
+            frame_ancestors: None,
+            content_security_policy: None,
+            csp_content_types: vec!["text/html".to_owned()],
+            referrer_policy: Some("strict-origin-when-cross-origin".to_owned()),
+            permissions_policy: None,
+            noindex_scopes: Vec::new(),
+        }
+    }
+}
+
+/// A per-response nonce for the `Content-Security-Policy` header.
+///
+/// It is stored as the request scoped data, so that it can be used
+/// by the inline scripts and styles in the templates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CspNonce(String);
+
+impl CspNonce {
+    /// Creates a new instance with a random value.
+    #[inline]
+    pub fn new() -> Self {
+        Self(Uuid::new_v4().simple().to_string())
+    }
+
+    /// Returns the nonce as `&str`.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl Default for CspNonce {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for CspNonce {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parses the header value, where `false` or an empty string disables the header.
+fn parse_header_value(config: &Table, key: &str) -> Option<Option<String>> {
+    match config.get(key)? {
+        Value::String(value) if !value.trim().is_empty() => Some(Some(value.trim().to_owned())),
+        Value::String(_) | Value::Boolean(false) => Some(None),
+        _ => {
+            tracing::warn!("the `{key}` of the security headers should be a str");
+            None
+        }
+    }
+}
+
+/// Selects the route with the longest matching path prefix,
+/// otherwise the default preset is used.
+fn select_preset<'a>(
+    default_preset: &'a SecurityHeaders,
+    routes: &'a [SecurityHeaders],
+    path: &str,
+) -> &'a SecurityHeaders {
+    routes
+        .iter()
+        .filter(|route| path.starts_with(route.path_prefix.as_str()))
+        .max_by_key(|route| route.path_prefix.len())
+        .unwrap_or(default_preset)
+}
+
+/// Parses the default preset and the routes inheriting from the base preset.
+fn parse_presets(
+    base_preset: &SecurityHeaders,
+    config: &Table,
+) -> (SecurityHeaders, Vec<SecurityHeaders>) {
+    let mut default_preset = base_preset.clone();
+    default_preset.read_config(config);
+    default_preset.path_prefix = "/".to_owned();
+
+    let mut routes = Vec::new();
+    if let Some(tables) = config.get_array("route") {
+        for route in tables.iter().filter_map(|v| v.as_table()) {
+            if route.get_str("path-prefix").is_none() {
+                tracing::warn!(
+                    "the `path-prefix` of the security headers route should be specified"
+                );
+                continue;
+            }
+
+            let mut preset = default_preset.clone();
+            preset.read_config(route);
+            routes.push(preset);
+        }
+    }
+    (default_preset, routes)
+}
+
+/// The default security headers and the routes.
+static SECURITY_HEADERS: LazyLock<Option<(SecurityHeaders, Vec<SecurityHeaders>)>> =
+    LazyLock::new(|| {
+        let config = State::shared().get_config("server")?.get_table("headers")?;
+        Some(parse_presets(&SecurityHeaders::default(), config))
+    });
+
+/// The security headers overridden by the listeners or attached to the listeners with TLS.
+static SCOPED_SECURITY_HEADERS: LazyLock<Vec<(ServerTag, SecurityHeaders, Vec<SecurityHeaders>)>> =
+    LazyLock::new(|| {
+        State::shared()
+            .server_listeners()
+            .into_iter()
+            .filter_map(|listener| {
+                let tls = listener.tls().is_some();
+                let (mut default_preset, mut routes) =
+                    match (listener.get_config("headers"), SECURITY_HEADERS.as_ref()) {
+                        (Some(config), Some((base_preset, _))) => {
+                            parse_presets(base_preset, config)
+                        }
+                        (Some(config), None) => parse_presets(&SecurityHeaders::default(), config),
+                        (None, Some(presets)) if tls => presets.clone(),
+                        _ => return None,
+                    };
+                default_preset.tls = tls;
+                routes.iter_mut().for_each(|route| route.tls = tls);
+                Some((listener.scope().clone(), default_preset, routes))
+            })
+            .collect()
+    });
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_presets, select_preset, CspNonce, SecurityHeaders};
+    use zino_core::application::ServerTag;
+
+    #[test]
+    fn it_builds_security_headers() {
+        let config = r#"
+            behind-proxy = true
+            permissions-policy = "camera=()"
+            noindex-scopes = ["internal"]
+
+            [[route]]
+            path-prefix = "/admin"
+            content-security-policy = "script-src 'self' 'nonce-{nonce}';"
+            frame-ancestors = "'self'"
+            referrer-policy = false
+        "#;
+        let (default_preset, routes) =
+            parse_presets(&SecurityHeaders::default(), &config.parse().unwrap());
+        let preset = select_preset(&default_preset, &routes, "/users");
+        assert!(!preset.requires_nonce());
+
+        let headers = preset.response_headers(&ServerTag::Main, Some("application/json"), None);
+        assert!(headers.contains(&(
+            "strict-transport-security",
+            "max-age=31536000; includeSubDomains".to_owned()
+        )));
+        assert!(headers.contains(&("x-content-type-options", "nosniff".to_owned())));
+        assert!(headers.contains(&("x-frame-options", "DENY".to_owned())));
+        assert!(headers.contains(&("permissions-policy", "camera=()".to_owned())));
+        assert!(!headers.iter().any(|(name, _)| *name == "x-robots-tag"));
+
+        let headers = preset.response_headers(&ServerTag::Debug, None, None);
+        assert!(headers.contains(&("x-robots-tag", "noindex".to_owned())));
+
+        let preset = select_preset(&default_preset, &routes, "/admin/users");
+        assert!(preset.requires_nonce());
+
+        let nonce = CspNonce::new();
+        let headers = preset.response_headers(&ServerTag::Main, Some("application/json"), None);
+        assert!(!headers
+            .iter()
+            .any(|(name, _)| *name == "content-security-policy"));
+
+        let headers = preset.response_headers(
+            &ServerTag::Main,
+            Some("text/html; charset=utf-8"),
+            Some(&nonce),
+        );
+        assert!(headers.contains(&(
+            "content-security-policy",
+            format!("script-src 'self' 'nonce-{nonce}'; frame-ancestors 'self'")
+        )));
+        assert!(!headers.iter().any(|(name, _)| *name == "x-frame-options"));
+        assert!(!headers.iter().any(|(name, _)| *name == "referrer-policy"));
+
+        let preset = SecurityHeaders::default();
+        let headers = preset.response_headers(&ServerTag::Main, Some("text/html"), None);
+        assert!(!headers
+            .iter()
+            .any(|(name, _)| *name == "strict-transport-security"));
+    }
+}
@@ -0,0 +1,447 @@
+//! Serving static files from a local directory or a storage accessor.
+
+use crate::{
+    request::RequestContext,
+    response::{Response, StatusCode},
+};
+use bytes::{Bytes, BytesMut};
+use futures::stream::{self, BoxStream, StreamExt};
+use percent_encoding::percent_decode_str;
+use std::{
+    io::{self, SeekFrom},
+    ops::Range,
+    path::{Path, PathBuf},
+    time::{Duration, UNIX_EPOCH},
+};
+use tokio::{
+    fs::{self, File},
+    io::{AsyncReadExt, AsyncSeekExt},
+};
+use toml::Table;
+use zino_core::{
+    application::{Agent, Application},
+    error::Error,
+    extension::TomlTableExt,
+    state::State,
+};
+
+mod range;
+
+use range::parse_byte_range;
+
+/// Size of the chunks read from a local file.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A service for serving the static files under a route prefix.
+///
+/// The files are read from a local directory or the operator of a
+/// [`GlobalAccessor`](zino_storage::GlobalAccessor) if the `accessor` feature is enabled.
+/// It supports the HTTP range requests and the conditional requests via `ETag`.
+///
+/// # Examples
+///
+/// ```toml
+/// [static.assets]
+/// root = "./assets"
+/// index-file = "index.html"
+/// spa-fallback = true
+/// max-age = "1d"
+/// allowed-extensions = ["html", "css", "js", "png", "mp4"]
+/// ```
+#[derive(Debug, Clone)]
+pub struct StaticFiles {
+    /// Route prefix.
+    route_prefix: String,
+    /// Root directory.
+    root: PathBuf,
+    /// Name of the storage accessor.
+    accessor: Option<String>,
+    /// Index file.
+    index_file: String,
+    /// A flag to indicate whether the index file is served for unknown paths.
+    spa_fallback: bool,
+    /// Value of the `cache-control` header.
+    cache_control: Option<String>,
+    /// Allowed file extensions.
+    allowed_extensions: Vec<String>,
+}
+
+impl StaticFiles {
+    /// Creates a new instance for the route prefix and the root directory.
+    pub fn new(route_prefix: &str, root: impl AsRef<Path>) -> Self {
+        Self {
+            route_prefix: normalize_route_prefix(route_prefix),
+            root: Agent::parse_path(&root.as_ref().to_string_lossy()),
+            accessor: None,
+            index_file: "index.html".to_owned(),
+            spa_fallback: false,
+            cache_control: None,
+            allowed_extensions: Vec::new(),
+        }
+    }
+
+    /// Creates a new instance with the configuration.
+    pub fn with_config(route_prefix: &str, config: &Table) -> Self {
+        let root = config.get_str("root").unwrap_or("public");
+        let mut static_files = Self::new(route_prefix, root);
+        if let Some(accessor) = config.get_str("accessor") {
+            static_files.accessor = Some(accessor.to_owned());
+        }
+        if let Some(index_file) = config.get_str("index-file") {
+            static_files.index_file = index_file.trim_start_matches('/').to_owned();
+        }
+        if let Some(spa_fallback) = config.get_bool("spa-fallback") {
+            static_files.spa_fallback = spa_fallback;
+        }
+        if let Some(cache_control) = config.get_str("cache-control") {
+            static_files.cache_control = Some(cache_control.to_owned());
+        } else if let Some(max_age) = config.get_duration("max-age") {
+            static_files.set_max_age(max_age);
+        }
+        if let Some(extensions) = config.get_str_array("allowed-extensions") {
+            static_files.allowed_extensions = extensions
+                .into_iter()
+                .map(|ext| ext.trim_start_matches('.').to_ascii_lowercase())
+                .collect();
+        }
+        static_files
+    }
+
+    /// Creates a new instance with the config `[static.{name}]`.
+    /// If the config does not exist, the directory `name` is used as the root.
+    pub fn with_config_name(route_prefix: &str, name: &str) -> Self {
+        let config = State::shared()
+            .get_config("static")
+            .and_then(|config| config.get_table(name));
+        if let Some(config) = config {
+            Self::with_config(route_prefix, config)
+        } else {
+            Self::new(route_prefix, name)
+        }
+    }
+
+    /// Sets the index file.
+    #[inline]
+    pub fn set_index_file(&mut self, index_file: &str) {
+        self.index_file = index_file.trim_start_matches('/').to_owned();
+    }
+
+    /// Enables the fallback to the index file for unknown paths.
+    #[inline]
+    pub fn enable_spa_fallback(&mut self, enabled: bool) {
+        self.spa_fallback = enabled;
+    }
+
+    /// Sets the `max-age` of the `cache-control` header.
+    #[inline]
+    pub fn set_max_age(&mut self, max_age: Duration) {
+        self.cache_control = Some(format!("public, max-age={}", max_age.as_secs()));
+    }
+
+    /// Sets the allowed file extensions. All files are allowed if it is empty.
+    #[inline]
+    pub fn set_allowed_extensions(&mut self, extensions: &[&str]) {
+        self.allowed_extensions = extensions
+            .iter()
+            .map(|ext| ext.trim_start_matches('.').to_ascii_lowercase())
+            .collect();
+    }
+
+    /// Returns the route prefix.
+    #[inline]
+    pub fn route_prefix(&self) -> &str {
+        &self.route_prefix
+    }
+
+    /// Serves the file corresponding to the request path.
+    pub async fn serve<Ctx: RequestContext>(&self, ctx: Ctx) -> Response<StatusCode> {
+        let request_path = ctx.request_path();
+        let Some(relative_path) = request_path
+            .strip_prefix(self.route_prefix.as_str())
+            .filter(|path| path.is_empty() || path.starts_with('/') || self.route_prefix == "/")
+        else {
+            return Response::with_context(StatusCode::NOT_FOUND, &ctx);
+        };
+        let Some(mut path) = sanitize_path(relative_path) else {
+            let mut res = Response::with_context(StatusCode::BAD_REQUEST, &ctx);
+            res.set_message("the request path is invalid");
+            return res;
+        };
+        if path.is_empty() || relative_path.ends_with('/') {
+            path = join_path(&path, &self.index_file);
+        }
+
+        let mut metadata = match self.stat(&path).await {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                let mut res = Response::with_context(StatusCode::INTERNAL_SERVER_ERROR, &ctx);
+                res.set_error_message(err);
+                return res;
+            }
+        };
+        if metadata.as_ref().is_some_and(|m| m.is_dir) {
+            path = join_path(&path, &self.index_file);
+            metadata = self.stat(&path).await.ok().flatten();
+        }
+        if metadata.is_none() && self.spa_fallback {
+            path = self.index_file.clone();
+            metadata = self.stat(&path).await.ok().flatten();
+        }
+        let Some(metadata) = metadata.filter(|m| !m.is_dir) else {
+            return Response::with_context(StatusCode::NOT_FOUND, &ctx);
+        };
+        if !self.is_allowed(&path) {
+            return Response::with_context(StatusCode::FORBIDDEN, &ctx);
+        }
+
+        let file_size = metadata.size;
+        let etag = metadata.etag;
+        let range = ctx
+            .get_header("range")
+            .filter(|_| {
+                ctx.get_header("if-range")
+                    .map_or(true, |if_range| if_range == etag)
+            })
+            .and_then(|range| parse_byte_range(range, file_size));
+        let (mut res, range) = match range {
+            Some(Ok(range)) => {
+                let mut res = Response::with_context(StatusCode::PARTIAL_CONTENT, &ctx);
+                let content_range = format!(
+                    "bytes {}-{}/{file_size}",
+                    range.start,
+                    range.end.saturating_sub(1)
+                );
+                res.insert_header("content-range", content_range);
+                (res, range)
+            }
+            Some(Err(_)) => {
+                let mut res = Response::with_context(StatusCode::RANGE_NOT_SATISFIABLE, &ctx);
+                res.insert_header("content-range", format!("bytes */{file_size}"));
+                return res;
+            }
+            None => (Response::with_context(StatusCode::OK, &ctx), 0..file_size),
+        };
+        match self.read(&path, range).await {
+            Ok(chunks) => {
+                let content_type = mime_guess::from_path(&path).first_or_octet_stream();
+                res.set_stream_response(content_type.to_string(), chunks);
+            }
+            Err(err) => {
+                let mut res = Response::with_context(StatusCode::INTERNAL_SERVER_ERROR, &ctx);
+                res.set_error_message(err);
+                return res;
+            }
+        }
+        res.insert_header("accept-ranges", "bytes");
+        res.insert_header("x-etag", etag);
+        if let Some(cache_control) = self.cache_control.as_ref() {
+            res.insert_header("cache-control", cache_control);
+        }
+        res
+    }
+
+    /// Returns `true` if the extension of the file is allowed.
+    fn is_allowed(&self, path: &str) -> bool {
+        if self.allowed_extensions.is_empty() {
+            return true;
+        }
+
+        let extension = Path::new(path)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        self.allowed_extensions.contains(&extension)
+    }
+
+    /// Returns the metadata of the file, or `None` if it does not exist.
+    async fn stat(&self, path: &str) -> Result<Option<FileMetadata>, Error> {
+        if let Some(name) = self.accessor.as_deref() {
+            #[cfg(feature = "accessor")]
+            {
+                use opendal::ErrorKind;
+
+                let Some(operator) = zino_storage::GlobalAccessor::get(name) else {
+                    return Err(Error::new(format!(
+                        "storage accessor `{name}` is not found"
+                    )));
+                };
+                let file_path = join_path(&self.root.to_string_lossy(), path);
+                return match operator.stat(&file_path).await {
+                    Ok(metadata) => {
+                        let size = metadata.content_length();
+                        let etag = if let Some(etag) = metadata.etag() {
+                            format!(r#""{}""#, etag.trim_matches('"'))
+                        } else {
+                            let modified = metadata
+                                .last_modified()
+                                .map(|dt| dt.timestamp())
+                                .unwrap_or_default();
+                            format!(r#"W/"{size:x}-{modified:x}""#)
+                        };
+                        Ok(Some(FileMetadata {
+                            is_dir: metadata.is_dir(),
+                            size,
+                            etag,
+                        }))
+                    }
+                    Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+                    Err(err) => Err(err.into()),
+                };
+            }
+            #[cfg(not(feature = "accessor"))]
+            {
+                return Err(Error::new(format!(
+                    "storage accessor `{name}` is not supported"
+                )));
+            }
+        }
+
+        match fs::metadata(self.root.join(path)).await {
+            Ok(metadata) => {
+                let modified = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default();
+                let size = metadata.len();
+                Ok(Some(FileMetadata {
+                    is_dir: metadata.is_dir(),
+                    size,
+                    etag: format!(r#"W/"{size:x}-{modified:x}""#),
+                }))
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Reads the bytes of the file in the range as a stream of chunks.
+    async fn read(
+        &self,
+        path: &str,
+        range: Range<u64>,
+    ) -> Result<BoxStream<'static, Bytes>, Error> {
+        if self.accessor.is_some() {
+            #[cfg(feature = "accessor")]
+            if let Some(operator) = self
+                .accessor
+                .as_deref()
+                .and_then(zino_storage::GlobalAccessor::get)
+            {
+                let file_path = join_path(&self.root.to_string_lossy(), path);
+                let reader = operator.reader_with(&file_path).await?;
+                let chunks = reader
+                    .into_bytes_stream(range)
+                    .await?
+                    .scan((), |_, result| {
+                        let chunk = result
+                            .inspect_err(|err| {
+                                tracing::error!("fail to read the static file: {err}")
+                            })
+                            .ok();
+                        futures::future::ready(chunk)
+                    });
+                return Ok(chunks.boxed());
+            }
+            return Err(Error::new("storage accessor is not available"));
+        }
+
+        let mut file = File::open(self.root.join(path)).await?;
+        file.seek(SeekFrom::Start(range.start)).await?;
+        let reader = file.take(range.end - range.start);
+        let chunks = stream::unfold(reader, |mut reader| async move {
+            let mut buffer = BytesMut::with_capacity(CHUNK_SIZE);
+            match reader.read_buf(&mut buffer).await {
+                Ok(0) => None,
+                Ok(_) => Some((buffer.freeze(), reader)),
+                Err(err) => {
+                    tracing::error!("fail to read the static file: {err}");
+                    None
+                }
+            }
+        });
+        Ok(chunks.boxed())
+    }
+}
+
+/// Metadata of a file.
+#[derive(Debug)]
+struct FileMetadata {
+    /// A flag to indicate whether it is a directory.
+    is_dir: bool,
+    /// File size in bytes.
+    size: u64,
+    /// Entity tag.
+    etag: String,
+}
+
+/// Normalizes the route prefix without the trailing slash.
+fn normalize_route_prefix(route_prefix: &str) -> String {
+    let route_prefix = route_prefix.trim_end_matches('/');
+    if route_prefix.is_empty() {
+        "/".to_owned()
+    } else if route_prefix.starts_with('/') {
+        route_prefix.to_owned()
+    } else {
+        format!("/{route_prefix}")
+    }
+}
+
+/// Sanitizes the relative path of a request.
+/// Returns `None` if it contains encoded slashes or attempts to traverse the parent directory.
+fn sanitize_path(path: &str) -> Option<String> {
+    let lowercase_path = path.to_ascii_lowercase();
+    if ["%2f", "%5c", "%00"]
+        .iter()
+        .any(|s| lowercase_path.contains(s))
+    {
+        return None;
+    }
+
+    let decoded_path = percent_decode_str(path).decode_utf8().ok()?;
+    let mut segments = Vec::new();
+    for segment in decoded_path.split('/') {
+        if segment.is_empty() || segment == "." {
+            continue;
+        }
+        if segment == ".." || segment.contains(['\\', '\0', ':']) {
+            return None;
+        }
+        segments.push(segment);
+    }
+    Some(segments.join("/"))
+}
+
+/// Joins the file name to a relative path.
+fn join_path(path: &str, file_name: &str) -> String {
+    let path = path.trim_end_matches('/');
+    if path.is_empty() {
+        file_name.to_owned()
+    } else {
+        format!("{path}/{file_name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_route_prefix, sanitize_path};
+
+    #[test]
+    fn it_sanitizes_paths() {
+        assert_eq!(sanitize_path("").as_deref(), Some(""));
+        assert_eq!(sanitize_path("/js/app.js").as_deref(), Some("js/app.js"));
+        assert_eq!(sanitize_path("/a/./b//c.png").as_deref(), Some("a/b/c.png"));
+        assert_eq!(sanitize_path("/a%20b.txt").as_deref(), Some("a b.txt"));
+        assert_eq!(sanitize_path("/../etc/passwd"), None);
+        assert_eq!(sanitize_path("/a/%2e%2e/%2E%2E/secret"), None);
+        assert_eq!(sanitize_path("/a%2Fb"), None);
+        assert_eq!(sanitize_path("/a%5c..%5cb"), None);
+        assert_eq!(sanitize_path("/a\\..\\b"), None);
+        assert_eq!(sanitize_path("/C:/windows"), None);
+
+        assert_eq!(normalize_route_prefix("/assets/"), "/assets");
+        assert_eq!(normalize_route_prefix("assets"), "/assets");
+        assert_eq!(normalize_route_prefix("/"), "/");
+    }
+}
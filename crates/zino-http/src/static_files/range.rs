@@ -0,0 +1,61 @@
+use std::ops::Range;
+
+/// Parses a single byte range of the `range` header value for a file with the size.
+///
+/// Returns `None` if the header should be ignored, i.e. it is malformed
+/// or has multiple ranges, and `Some(Err(()))` if the range is not satisfiable.
+pub(super) fn parse_byte_range(header: &str, file_size: u64) -> Option<Result<Range<u64>, ()>> {
+    let spec = header.trim().strip_prefix("bytes=")?.trim();
+    if spec.contains(',') {
+        return None;
+    }
+
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+    let range = if start.is_empty() {
+        let suffix_length = end.parse::<u64>().ok()?;
+        if suffix_length == 0 {
+            return Some(Err(()));
+        }
+        file_size.saturating_sub(suffix_length)..file_size
+    } else {
+        let start = start.parse::<u64>().ok()?;
+        let end = if end.is_empty() {
+            file_size
+        } else {
+            let end = end.parse::<u64>().ok()?;
+            if end < start {
+                return None;
+            }
+            end.saturating_add(1).min(file_size)
+        };
+        start..end
+    };
+    if range.start >= file_size {
+        Some(Err(()))
+    } else {
+        Some(Ok(range))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_byte_range;
+
+    #[test]
+    fn it_parses_byte_ranges() {
+        assert_eq!(parse_byte_range("bytes=0-99", 1000), Some(Ok(0..100)));
+        assert_eq!(parse_byte_range("bytes=500-", 1000), Some(Ok(500..1000)));
+        assert_eq!(parse_byte_range("bytes=-200", 1000), Some(Ok(800..1000)));
+        assert_eq!(
+            parse_byte_range("bytes=900-2000", 1000),
+            Some(Ok(900..1000))
+        );
+        assert_eq!(parse_byte_range("bytes=-2000", 1000), Some(Ok(0..1000)));
+        assert_eq!(parse_byte_range("bytes=1000-", 1000), Some(Err(())));
+        assert_eq!(parse_byte_range("bytes=-0", 1000), Some(Err(())));
+        assert_eq!(parse_byte_range("bytes=0-1,5-9", 1000), None);
+        assert_eq!(parse_byte_range("bytes=9-1", 1000), None);
+        assert_eq!(parse_byte_range("items=0-1", 1000), None);
+    }
+}
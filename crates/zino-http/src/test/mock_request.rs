@@ -0,0 +1,461 @@
+use crate::request::{CapturedRequest, Context, RequestContext};
+use futures::{stream, Stream};
+use http::{
+    header::{self, HeaderName, HeaderValue},
+    Extensions, HeaderMap, Method, Request, Uri,
+};
+use serde::Serialize;
+use std::{borrow::Cow, mem, net::IpAddr};
+use zino_core::{error::Error, extension::HeaderMapExt, state::Data};
+
+/// A mock request which can be used as the request context in tests.
+#[derive(Debug)]
+pub struct MockRequest {
+    /// Request method.
+    method: Method,
+    /// Request URI.
+    uri: Uri,
+    /// Matched route.
+    route: Option<String>,
+    /// Request headers.
+    headers: HeaderMap,
+    /// Request scoped data.
+    extensions: Extensions,
+    /// Request body.
+    body: Vec<u8>,
+    /// Parts of the multipart body.
+    multipart_parts: Vec<u8>,
+    /// Client's remote IP.
+    client_ip: Option<IpAddr>,
+}
+
+impl MockRequest {
+    /// Creates a new instance with the method and URI.
+    ///
+    /// # Panics
+    ///
+    /// It will panic if the URI is invalid.
+    pub fn new(method: Method, uri: &str) -> Self {
+        let uri = uri
+            .parse()
+            .unwrap_or_else(|err| panic!("invalid URI `{uri}` for the mock request: {err}"));
+        Self {
+            method,
+            uri,
+            route: None,
+            headers: HeaderMap::new(),
+            extensions: Extensions::new(),
+            body: Vec::new(),
+            multipart_parts: Vec::new(),
+            client_ip: None,
+        }
+    }
+
+    /// Creates a new instance with the `GET` method.
+    #[inline]
+    pub fn get(uri: &str) -> Self {
+        Self::new(Method::GET, uri)
+    }
+
+    /// Creates a new instance with the `POST` method.
+    #[inline]
+    pub fn post(uri: &str) -> Self {
+        Self::new(Method::POST, uri)
+    }
+
+    /// Creates a new instance with the `PUT` method.
+    #[inline]
+    pub fn put(uri: &str) -> Self {
+        Self::new(Method::PUT, uri)
+    }
+
+    /// Creates a new instance with the `PATCH` method.
+    #[inline]
+    pub fn patch(uri: &str) -> Self {
+        Self::new(Method::PATCH, uri)
+    }
+
+    /// Creates a new instance with the `DELETE` method.
+    #[inline]
+    pub fn delete(uri: &str) -> Self {
+        Self::new(Method::DELETE, uri)
+    }
+
+    /// Sets the matched route, such as `/user/{id}/view`,
+    /// which is used to extract the route parameters from the request path.
+    #[inline]
+    pub fn route(mut self, route: impl Into<String>) -> Self {
+        self.route = Some(route.into());
+        self
+    }
+
+    /// Sets the query string by serializing the value.
+    ///
+    /// # Panics
+    ///
+    /// It will panic if the value can not be serialized as a query string.
+    pub fn query<T: Serialize>(mut self, query: &T) -> Self {
+        let query = serde_qs::to_string(query)
+            .unwrap_or_else(|err| panic!("fail to serialize the query string: {err}"));
+        let path = self.uri.path();
+        let uri = if query.is_empty() {
+            path.to_owned()
+        } else {
+            format!("{path}?{query}")
+        };
+        self.uri = uri
+            .parse()
+            .unwrap_or_else(|err| panic!("invalid URI `{uri}` for the mock request: {err}"));
+        self
+    }
+
+    /// Inserts a header.
+    ///
+    /// # Panics
+    ///
+    /// It will panic if the header name or value is invalid.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        let header_name = HeaderName::from_bytes(name.as_bytes())
+            .unwrap_or_else(|err| panic!("invalid header name `{name}`: {err}"));
+        let header_value = HeaderValue::from_str(value)
+            .unwrap_or_else(|err| panic!("invalid value for the header `{name}`: {err}"));
+        self.headers.insert(header_name, header_value);
+        self
+    }
+
+    /// Appends a cookie to the `cookie` header.
+    pub fn cookie(self, name: &str, value: &str) -> Self {
+        let cookie = if let Some(cookies) = self.get_header("cookie") {
+            format!("{cookies}; {name}={value}")
+        } else {
+            format!("{name}={value}")
+        };
+        self.header("cookie", &cookie)
+    }
+
+    /// Sets the client's remote IP.
+    #[inline]
+    pub fn client_ip(mut self, ip: IpAddr) -> Self {
+        self.client_ip = Some(ip);
+        self
+    }
+
+    /// Sets the request scoped data.
+    #[inline]
+    pub fn data<T: Clone + Send + Sync + 'static>(mut self, value: T) -> Self {
+        self.extensions.insert(Data::new(value));
+        self
+    }
+
+    /// Sets the request context.
+    #[inline]
+    pub fn context(mut self, ctx: Context) -> Self {
+        self.extensions.insert(ctx);
+        self
+    }
+
+    /// Sets the raw request body.
+    #[inline]
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Sets the request body as JSON.
+    ///
+    /// # Panics
+    ///
+    /// It will panic if the value can not be serialized as JSON.
+    pub fn json<T: Serialize>(mut self, data: &T) -> Self {
+        self.body = serde_json::to_vec(data)
+            .unwrap_or_else(|err| panic!("fail to serialize the JSON body: {err}"));
+        self.header(header::CONTENT_TYPE.as_str(), "application/json")
+    }
+
+    /// Sets the request body as `application/x-www-form-urlencoded`.
+    ///
+    /// # Panics
+    ///
+    /// It will panic if the value can not be serialized as a form.
+    pub fn form<T: Serialize>(mut self, data: &T) -> Self {
+        self.body = serde_qs::to_string(data)
+            .unwrap_or_else(|err| panic!("fail to serialize the form body: {err}"))
+            .into_bytes();
+        self.header(
+            header::CONTENT_TYPE.as_str(),
+            "application/x-www-form-urlencoded",
+        )
+    }
+
+    /// Appends a text field to the `multipart/form-data` body.
+    pub fn multipart_field(self, name: &str, value: &str) -> Self {
+        let disposition = format!("form-data; name=\"{name}\"");
+        self.push_multipart_part(&disposition, None, value.as_bytes())
+    }
+
+    /// Appends a file to the `multipart/form-data` body.
+    pub fn multipart_file(
+        self,
+        name: &str,
+        file_name: &str,
+        content_type: &str,
+        bytes: impl AsRef<[u8]>,
+    ) -> Self {
+        let disposition = format!("form-data; name=\"{name}\"; filename=\"{file_name}\"");
+        self.push_multipart_part(&disposition, Some(content_type), bytes.as_ref())
+    }
+
+    /// Converts `self` into an HTTP request.
+    /// A new request context will be created if it has not been set.
+    pub fn into_http_request(mut self) -> Request<Vec<u8>> {
+        if self.get_context().is_none() {
+            let ctx = self.new_context();
+            self.extensions.insert(ctx);
+        }
+        if let Some(route) = self.route.take() {
+            self.extensions.insert(MatchedRoute(route));
+        }
+
+        let mut req = Request::new(self.body);
+        *req.method_mut() = self.method;
+        *req.uri_mut() = self.uri;
+        *req.headers_mut() = self.headers;
+        *req.extensions_mut() = self.extensions;
+        req
+    }
+
+    /// Appends a part to the multipart body.
+    fn push_multipart_part(
+        mut self,
+        disposition: &str,
+        content_type: Option<&str>,
+        bytes: &[u8],
+    ) -> Self {
+        let parts = &mut self.multipart_parts;
+        parts.extend_from_slice(format!("--{MULTIPART_BOUNDARY}\r\n").as_bytes());
+        parts.extend_from_slice(format!("Content-Disposition: {disposition}\r\n").as_bytes());
+        if let Some(content_type) = content_type {
+            parts.extend_from_slice(format!("Content-Type: {content_type}\r\n").as_bytes());
+        }
+        parts.extend_from_slice(b"\r\n");
+        parts.extend_from_slice(bytes);
+        parts.extend_from_slice(b"\r\n");
+
+        let mut body = parts.clone();
+        body.extend_from_slice(format!("--{MULTIPART_BOUNDARY}--\r\n").as_bytes());
+        self.body = body;
+
+        let content_type = format!("multipart/form-data; boundary={MULTIPART_BOUNDARY}");
+        self.header(header::CONTENT_TYPE.as_str(), &content_type)
+    }
+}
+
+impl From<CapturedRequest> for MockRequest {
+    /// Rebuilds the mock request from a capture record.
+    /// The redacted headers are skipped and should be set again if necessary.
+    ///
+    /// # Panics
+    ///
+    /// It will panic if the method, URI, headers or body of the record are invalid.
+    fn from(captured: CapturedRequest) -> Self {
+        let method = captured.method();
+        let method = Method::from_bytes(method.as_bytes())
+            .unwrap_or_else(|err| panic!("invalid method `{method}` for the mock request: {err}"));
+        let mut req = Self::new(method, captured.uri());
+        if let Some(route) = captured.route() {
+            req = req.route(route);
+        }
+        for (name, value) in captured.headers() {
+            if value != "[REDACTED]" && !name.eq_ignore_ascii_case("content-length") {
+                req = req.header(name, value);
+            }
+        }
+        if req.get_header("x-request-id").is_none() && !captured.request_id().is_nil() {
+            req = req.header("x-request-id", &captured.request_id().to_string());
+        }
+
+        let body = captured
+            .body_bytes()
+            .unwrap_or_else(|err| panic!("invalid body of the capture record: {err}"));
+        req.body(body)
+    }
+}
+
+impl RequestContext for MockRequest {
+    type Method = Method;
+    type Uri = Uri;
+
+    #[inline]
+    fn request_method(&self) -> &Self::Method {
+        &self.method
+    }
+
+    #[inline]
+    fn original_uri(&self) -> &Self::Uri {
+        &self.uri
+    }
+
+    #[inline]
+    fn matched_route(&self) -> Cow<'_, str> {
+        if let Some(route) = self.route.as_deref() {
+            route.into()
+        } else {
+            self.uri.path().into()
+        }
+    }
+
+    #[inline]
+    fn request_path(&self) -> &str {
+        self.uri.path()
+    }
+
+    #[inline]
+    fn get_query_string(&self) -> Option<&str> {
+        self.uri.query()
+    }
+
+    #[inline]
+    fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name)?.to_str().ok()
+    }
+
+    #[inline]
+    fn client_ip(&self) -> Option<IpAddr> {
+        self.headers.get_client_ip().or(self.client_ip)
+    }
+
+    #[inline]
+    fn get_context(&self) -> Option<Context> {
+        self.extensions.get::<Context>().cloned()
+    }
+
+    #[inline]
+    fn get_data<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+        self.extensions.get::<Data<T>>().map(|data| data.get())
+    }
+
+    #[inline]
+    fn set_data<T: Clone + Send + Sync + 'static>(&mut self, value: T) -> Option<T> {
+        self.extensions
+            .insert(Data::new(value))
+            .map(|data| data.into_inner())
+    }
+
+    #[inline]
+    async fn read_body_bytes(&mut self) -> Result<Vec<u8>, Error> {
+        Ok(mem::take(&mut self.body))
+    }
+
+    #[inline]
+    fn take_body_stream(&mut self) -> impl Stream<Item = Result<Vec<u8>, Error>> + Unpin + 'static {
+        stream::iter([Ok(mem::take(&mut self.body))])
+    }
+}
+
+/// The route matched by a mock request, which is stored in the request extensions.
+#[derive(Debug, Clone)]
+pub struct MatchedRoute(String);
+
+impl MatchedRoute {
+    /// Returns the route as a `str`.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Boundary for the multipart body.
+const MULTIPART_BOUNDARY: &str = "zino-mock-boundary";
+
+#[cfg(test)]
+mod tests {
+    use super::MockRequest;
+    use crate::request::RequestContext;
+    use futures::executor;
+    use http::Method;
+    use zino_core::{extension::JsonObjectExt, Map};
+
+    #[test]
+    fn it_extracts_params_and_queries() {
+        let req = MockRequest::get("/user/42/view")
+            .route("/user/{id}/view")
+            .query(&Map::from_entry("page_size", 10))
+            .header("x-request-id", "0194c3a4-8c5a-7c9a-bd1b-3f0b1d0c5a4e");
+        assert_eq!(req.request_method(), Method::GET);
+        assert_eq!(req.matched_route(), "/user/{id}/view");
+        assert_eq!(req.parse_param::<i64>("id").ok(), Some(42));
+        assert!(req.parse_param::<i64>("name").is_err());
+        assert_eq!(req.get_query("page_size"), Some("10"));
+        assert!(req.request_id().is_nil());
+        assert_eq!(
+            req.new_context().request_id().to_string(),
+            "0194c3a4-8c5a-7c9a-bd1b-3f0b1d0c5a4e"
+        );
+
+        let req = MockRequest::get("/user/list");
+        assert_eq!(req.matched_route(), "/user/list");
+        assert_eq!(req.get_query_string(), None);
+    }
+
+    #[test]
+    fn it_appends_cookies() {
+        let req = MockRequest::get("/")
+            .cookie("session-id", "abc")
+            .cookie("locale", "en-US");
+        assert_eq!(
+            req.get_header("cookie"),
+            Some("session-id=abc; locale=en-US")
+        );
+    }
+
+    #[test]
+    fn it_parses_json_and_form_bodies() {
+        let mut data = Map::new();
+        data.upsert("name", "alice");
+        data.upsert("age", 18);
+
+        let mut req = MockRequest::post("/user/new").json(&data);
+        assert_eq!(req.data_type(), Some("json"));
+        let body = executor::block_on(req.parse_body::<Map>()).unwrap();
+        assert_eq!(body, data);
+
+        let mut req = MockRequest::post("/user/new").form(&Map::from_entry("name", "alice"));
+        assert_eq!(req.data_type(), Some("form"));
+        let body = executor::block_on(req.parse_body::<Map>()).unwrap();
+        assert_eq!(body.get_str("name"), Some("alice"));
+    }
+
+    #[test]
+    fn it_parses_multipart_bodies() {
+        let mut req = MockRequest::post("/file/upload")
+            .multipart_field("name", "alice")
+            .multipart_file("avatar", "avatar.png", "image/png", b"\x89PNG")
+            .multipart_file("resume", "resume.txt", "text/plain", "hello");
+        let (data, files) = executor::block_on(req.parse_form_data::<Map>()).unwrap();
+        assert_eq!(data.get_str("name"), Some("alice"));
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].file_name(), Some("avatar.png"));
+        assert_eq!(files[0].bytes().as_ref(), b"\x89PNG");
+        assert_eq!(files[1].field_name(), Some("resume"));
+        assert_eq!(files[1].bytes().as_ref(), b"hello");
+    }
+
+    #[test]
+    fn it_converts_into_http_requests() {
+        let req = MockRequest::put("/user/1/update")
+            .route("/user/{id}/update")
+            .data(7_u32)
+            .body("{}");
+        let req = req.into_http_request();
+        assert_eq!(req.method(), Method::PUT);
+        assert_eq!(req.uri().path(), "/user/1/update");
+        assert_eq!(req.body().as_slice(), b"{}");
+        assert!(req.extensions().get::<super::Context>().is_some());
+        assert_eq!(
+            req.extensions()
+                .get::<super::MatchedRoute>()
+                .map(|route| route.as_str()),
+            Some("/user/{id}/update")
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use bytes::Bytes;
+use http::HeaderMap;
+use serde::de::DeserializeOwned;
+use std::borrow::Cow;
+use zino_core::{error::Error, JsonValue};
+
+/// A response returned by the handler in tests.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    /// Status code.
+    status_code: u16,
+    /// Response headers.
+    headers: HeaderMap,
+    /// Response body.
+    body: Bytes,
+}
+
+impl MockResponse {
+    /// Creates a new instance.
+    #[inline]
+    pub fn new(status_code: u16, headers: HeaderMap, body: impl Into<Bytes>) -> Self {
+        Self {
+            status_code,
+            headers,
+            body: body.into(),
+        }
+    }
+
+    /// Returns the status code.
+    #[inline]
+    pub fn status_code(&self) -> u16 {
+        self.status_code
+    }
+
+    /// Returns `true` if the status code is in the range `200-299`.
+    #[inline]
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status_code)
+    }
+
+    /// Returns a reference to the response headers.
+    #[inline]
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Gets a header value with the given name.
+    #[inline]
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name)?.to_str().ok()
+    }
+
+    /// Returns the response body.
+    #[inline]
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Returns the response body as text.
+    #[inline]
+    pub fn text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.body)
+    }
+
+    /// Parses the response body as an instance of type `T`.
+    #[inline]
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        serde_json::from_slice(&self.body).map_err(Error::from)
+    }
+
+    /// Parses the response body as JSON and returns the `data` field.
+    pub fn json_data(&self) -> Result<JsonValue, Error> {
+        let mut value = self.json::<JsonValue>()?;
+        Ok(value
+            .get_mut("data")
+            .map(JsonValue::take)
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MockResponse;
+    use http::{header, HeaderMap, HeaderValue};
+    use zino_core::{extension::JsonObjectExt, Map};
+
+    #[test]
+    fn it_parses_response_bodies() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+        let body = r#"{"status":200,"success":true,"data":{"name":"alice"}}"#;
+        let res = MockResponse::new(200, headers, body);
+        assert!(res.is_success());
+        assert_eq!(res.get_header("content-type"), Some("application/json"));
+        assert_eq!(res.text(), body);
+        assert_eq!(res.json::<Map>().unwrap().get_u16("status"), Some(200));
+        assert_eq!(res.json_data().unwrap()["name"], "alice");
+
+        let res = MockResponse::new(404, HeaderMap::new(), "not found");
+        assert!(!res.is_success());
+        assert!(res.json::<Map>().is_err());
+    }
+}
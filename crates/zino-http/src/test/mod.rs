@@ -0,0 +1,29 @@
+//! Utilities for testing handlers without running a server.
+//!
+//! # Examples
+//!
+//! ```rust,ignore
+//! use zino_http::{request::RequestContext, test::MockRequest};
+//!
+//! let mut req = MockRequest::post("/user/1/update")
+//!     .route("/user/{id}/update")
+//!     .header("x-request-id", "0194c3a4-8c5a-7c9a-bd1b-3f0b1d0c5a4e")
+//!     .json(&serde_json::json!({ "name": "alice" }));
+//! assert_eq!(req.parse_param::<i64>("id").ok(), Some(1));
+//! ```
+//!
+//! # Note
+//!
+//! Handlers which do not touch the ORM can be tested without any configuration.
+//! Otherwise, a test database should be configured in `config.{env}.toml`
+//! for the environment specified by `ZINO_APP_ENV`, such as `config.test.toml`.
+//! Without it, the handler panics with a message naming the missing config
+//! once it touches the ORM.
+//!
+//! The handlers can be executed by the `test` module of `zino-actix`, `zino-axum` or `zino-ntex`.
+
+mod mock_request;
+mod mock_response;
+
+pub use mock_request::{MatchedRoute, MockRequest};
+pub use mock_response::MockResponse;
@@ -14,6 +14,14 @@ pub fn render(template_name: &str, data: Map) -> Result<String, Error> {
     template.render(data).map_err(Error::from)
 }
 
+/// Renders a template source with the given data using [`minijinja`](https://crates.io/crates/minijinja).
+/// It is useful for the HTML templates embedded in the binary, and the auto-escaping is enabled.
+pub fn render_str(source: &str, data: Map) -> Result<String, Error> {
+    SHARED_VIEW_ENGINE
+        .render_named_str("embedded.html", source, data)
+        .map_err(Error::from)
+}
+
 /// Shared view engine.
 static SHARED_VIEW_ENGINE: LazyLock<Environment> = LazyLock::new(|| {
     let app_state = Agent::shared_state();
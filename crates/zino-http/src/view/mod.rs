@@ -13,10 +13,10 @@ cfg_if::cfg_if! {
     if #[cfg(feature = "view-tera")] {
         mod tera;
 
-        pub use self::tera::render;
+        pub use self::tera::{render, render_str};
     } else {
         mod minijinja;
 
-        pub use self::minijinja::render;
+        pub use self::minijinja::{render, render_str};
     }
 }
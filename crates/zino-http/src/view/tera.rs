@@ -15,6 +15,13 @@ pub fn render(template_name: &str, data: Map) -> Result<String, Error> {
         .map_err(Error::from)
 }
 
+/// Renders a template source with the given data using [`tera`](https://crates.io/crates/tera).
+/// It is useful for the HTML templates embedded in the binary, and the auto-escaping is enabled.
+pub fn render_str(source: &str, data: Map) -> Result<String, Error> {
+    let context = Context::from_value(data.into())?;
+    Tera::one_off(source, &context, true).map_err(Error::from)
+}
+
 /// Shared view engine.
 static SHARED_VIEW_ENGINE: LazyLock<Tera> = LazyLock::new(|| {
     let app_state = Agent::shared_state();
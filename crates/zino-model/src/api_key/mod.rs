@@ -0,0 +1,200 @@
+//! The `api_key` model and related services.
+
+use serde::{Deserialize, Serialize};
+use zino_auth::{ApiKeyPrincipal, ApiKeyToken};
+use zino_core::{
+    datetime::DateTime,
+    error::Error,
+    extension::JsonObjectExt,
+    model::{Model, ModelHooks},
+    validation::Validation,
+    Map, Uuid,
+};
+use zino_derive::{DecodeRow, Entity, ModelAccessor, Schema};
+
+mod store;
+
+pub use store::ApiKeyStore;
+
+/// The `api_key` model for the machine-to-machine authentication.
+///
+/// Only the hash of the secret is persisted, and the plaintext token is returned
+/// by [`ApiKey::generate()`] once.
+#[derive(
+    Debug, Clone, Default, Serialize, Deserialize, DecodeRow, Entity, Schema, ModelAccessor,
+)]
+#[serde(default)]
+#[schema(auto_rename)]
+pub struct ApiKey {
+    // Basic fields.
+    #[schema(read_only)]
+    id: Uuid,
+    #[schema(not_null)]
+    name: String,
+    #[schema(default_value = "Active", index_type = "hash")]
+    status: String,
+    description: String,
+
+    // Info fields.
+    #[schema(not_null, unique, read_only)]
+    key_id: String,
+    #[schema(not_null, write_only)]
+    secret_hash: String,
+    #[schema(enum_values = "user | application", default_value = "user")]
+    owner_type: String,
+    #[schema(not_null, index_type = "hash")]
+    owner_id: Uuid, // user.id or application.id
+    #[schema(unique_items, index_type = "gin")]
+    scopes: Vec<String>,
+    expires_at: Option<DateTime>,
+    #[schema(read_only)]
+    last_used_at: DateTime,
+
+    // Extensions.
+    extra: Map,
+
+    // Revisions.
+    #[schema(read_only, default_value = "now", index_type = "btree")]
+    created_at: DateTime,
+    #[schema(default_value = "now", index_type = "btree")]
+    updated_at: DateTime,
+    version: u64,
+}
+
+impl Model for ApiKey {
+    const MODEL_NAME: &'static str = "api_key";
+
+    #[inline]
+    fn new() -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            ..Self::default()
+        }
+    }
+
+    fn read_map(&mut self, data: &Map) -> Validation {
+        let mut validation = Validation::new();
+        if let Some(result) = data.parse_uuid("id") {
+            match result {
+                Ok(id) => self.id = id,
+                Err(err) => validation.record_fail("id", err),
+            }
+        }
+        if let Some(name) = data.parse_string("name") {
+            self.name = name.into_owned();
+        }
+        if let Some(description) = data.parse_string("description") {
+            self.description = description.into_owned();
+        }
+        if let Some(owner_type) = data.parse_string("owner_type") {
+            self.owner_type = owner_type.into_owned();
+        }
+        if let Some(result) = data.parse_uuid("owner_id") {
+            match result {
+                Ok(owner_id) => self.owner_id = owner_id,
+                Err(err) => validation.record_fail("owner_id", err),
+            }
+        }
+        if let Some(scopes) = data.parse_str_array("scopes") {
+            self.scopes = scopes.into_iter().map(|s| s.to_owned()).collect();
+        }
+        if let Some(result) = data.parse_date_time("expires_at") {
+            match result {
+                Ok(expires_at) => self.expires_at = Some(expires_at),
+                Err(err) => validation.record_fail("expires_at", err),
+            }
+        }
+        validation
+    }
+}
+
+impl ModelHooks for ApiKey {
+    type Data = ();
+    type Extension = ();
+}
+
+impl ApiKey {
+    /// Generates a new API key for the owner.
+    /// It returns the model to be inserted and the plaintext token,
+    /// which should be shown to the client only once.
+    pub fn generate(
+        owner_type: impl Into<String>,
+        owner_id: Uuid,
+        scopes: Vec<String>,
+    ) -> (Self, String) {
+        let token = ApiKeyToken::generate();
+        let mut api_key = Self::new();
+        api_key.key_id = token.key_id().to_owned();
+        api_key.secret_hash = token.hash_secret();
+        api_key.owner_type = owner_type.into();
+        api_key.owner_id = owner_id;
+        api_key.scopes = scopes;
+        (api_key, token.to_string())
+    }
+
+    /// Sets the name.
+    #[inline]
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = name.into();
+    }
+
+    /// Sets the expiration time.
+    #[inline]
+    pub fn set_expires_at(&mut self, expires_at: DateTime) {
+        self.expires_at = Some(expires_at);
+    }
+
+    /// Returns the key ID.
+    #[inline]
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Returns the owner ID.
+    #[inline]
+    pub fn owner_id(&self) -> &Uuid {
+        &self.owner_id
+    }
+
+    /// Returns the scopes.
+    #[inline]
+    pub fn scopes(&self) -> &[String] {
+        &self.scopes
+    }
+
+    /// Returns the expiration time.
+    #[inline]
+    pub fn expires_at(&self) -> Option<DateTime> {
+        self.expires_at
+    }
+
+    /// Returns `true` if the key is active and has not expired.
+    pub fn is_valid(&self) -> bool {
+        self.status == "Active"
+            && self
+                .expires_at
+                .map_or(true, |expires_at| expires_at > DateTime::now())
+    }
+
+    /// Converts `self` into the principal for authorization.
+    pub fn to_principal(&self) -> ApiKeyPrincipal {
+        ApiKeyPrincipal::new(
+            self.key_id.as_str(),
+            self.owner_type.as_str(),
+            self.owner_id.to_string(),
+            self.scopes.clone(),
+        )
+    }
+
+    /// Revokes the API key by the key ID.
+    /// It returns `false` if the key does not exist or has been revoked.
+    pub async fn revoke(key_id: &str) -> Result<bool, Error> {
+        let mut query = Query::default();
+        query.add_filter("key_id", key_id);
+        query.add_filter("status", "Active");
+        let mut mutation = Mutation::from_entry("status", "Revoked");
+        let ctx = Self::update_one(&query, &mut mutation).await?;
+        ApiKeyStore::invalidate(key_id);
+        Ok(ctx.rows_affected() == Some(1))
+    }
+}
@@ -0,0 +1,159 @@
+use super::ApiKey;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use zino_auth::{ApiKeyPrincipal, ApiKeyToken, ApiKeyVerifier};
+use zino_core::{
+    datetime::DateTime,
+    error::Error,
+    extension::TomlTableExt,
+    model::{Mutation, Query},
+    state::State,
+    warn, BoxFuture, LazyLock,
+};
+use zino_orm::{IntoSqlValue, Schema};
+
+/// A verifier of API keys backed by the `api_key` table.
+///
+/// The keys are cached in-process to avoid a database query per request,
+/// and the `last_used_at` field is updated at most once per interval.
+/// It can be registered by `ApiKeyToken::register_verifier(ApiKeyStore::with_config())`.
+///
+/// It can be configured in the `[auth.api-key]` table:
+///
+/// ```toml
+/// [auth.api-key]
+/// cache-ttl = "1m"
+/// last-used-interval = "1m"
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ApiKeyStore {
+    /// Time-to-live of the cached keys.
+    cache_ttl: Duration,
+    /// Minimum interval between the updates of `last_used_at`.
+    last_used_interval: Duration,
+}
+
+impl ApiKeyStore {
+    /// Creates a new instance with the default settings.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            cache_ttl: Duration::from_secs(60),
+            last_used_interval: Duration::from_secs(60),
+        }
+    }
+
+    /// Creates a new instance with the configuration.
+    pub fn with_config() -> Self {
+        let mut store = Self::new();
+        if let Some(config) = State::shared()
+            .get_config("auth")
+            .and_then(|config| config.get_table("api-key"))
+        {
+            if let Some(cache_ttl) = config.get_duration("cache-ttl") {
+                store.cache_ttl = cache_ttl;
+            }
+            if let Some(interval) = config.get_duration("last-used-interval") {
+                store.last_used_interval = interval;
+            }
+        }
+        store
+    }
+
+    /// Removes the key from the cache so that the next verification reloads it.
+    #[inline]
+    pub fn invalidate(key_id: &str) {
+        API_KEY_CACHE
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(key_id);
+    }
+
+    /// Verifies the token and returns the principal.
+    async fn verify_token(&self, token: &ApiKeyToken) -> Result<ApiKeyPrincipal, Error> {
+        let key_id = token.key_id();
+        let cached_key = API_KEY_CACHE
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(key_id)
+            .cloned();
+        let mut cached_key = match cached_key {
+            Some(cached_key) if cached_key.cached_at.elapsed() < self.cache_ttl => cached_key,
+            stale_key => {
+                let mut query = Query::default();
+                query.add_filter("key_id", key_id);
+                let api_key = ApiKey::find_one::<ApiKey>(&query)
+                    .await?
+                    .ok_or_else(|| warn!("invalid API key"))?;
+                CachedApiKey {
+                    api_key,
+                    cached_at: Instant::now(),
+                    last_used_at: stale_key.and_then(|cached_key| cached_key.last_used_at),
+                }
+            }
+        };
+
+        let api_key = &cached_key.api_key;
+        if !token.verify_secret(&api_key.secret_hash) {
+            return Err(warn!("invalid API key"));
+        }
+        if !api_key.is_valid() {
+            return Err(warn!(
+                "the API key `{}` has been revoked or expired",
+                key_id
+            ));
+        }
+
+        let principal = api_key.to_principal();
+        let needs_update = cached_key.last_used_at.map_or(true, |last_used_at| {
+            last_used_at.elapsed() >= self.last_used_interval
+        });
+        if needs_update {
+            let mut query = Query::default();
+            query.add_filter("key_id", key_id);
+            let mut mutation =
+                Mutation::from_entry("last_used_at", DateTime::now().into_sql_value());
+            ApiKey::update_one(&query, &mut mutation).await?;
+            cached_key.last_used_at = Some(Instant::now());
+        }
+        API_KEY_CACHE
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(key_id.to_owned(), cached_key);
+        Ok(principal)
+    }
+}
+
+impl Default for ApiKeyStore {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApiKeyVerifier for ApiKeyStore {
+    fn verify<'a>(
+        &'a self,
+        token: &'a ApiKeyToken,
+    ) -> BoxFuture<'a, Result<ApiKeyPrincipal, Error>> {
+        Box::pin(self.verify_token(token))
+    }
+}
+
+/// A cached API key.
+#[derive(Debug, Clone)]
+struct CachedApiKey {
+    /// API key.
+    api_key: ApiKey,
+    /// Time when the key is cached.
+    cached_at: Instant,
+    /// Time when the `last_used_at` field has been updated by this instance.
+    last_used_at: Option<Instant>,
+}
+
+/// Cache of the API keys.
+static API_KEY_CACHE: LazyLock<Mutex<HashMap<String, CachedApiKey>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
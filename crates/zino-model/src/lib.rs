@@ -11,9 +11,11 @@ pub mod resource;
 pub mod tag;
 pub mod user;
 
+pub mod api_key;
 pub mod application;
 pub mod message;
 pub mod order;
+pub mod remember_me;
 
 pub mod collection;
 pub mod dataset;
@@ -30,9 +32,11 @@ pub use resource::{Resource, ResourceColumn};
 pub use tag::{Tag, TagColumn};
 pub use user::{User, UserColumn};
 
+pub use api_key::{ApiKey, ApiKeyColumn};
 pub use application::{Application, ApplicationColumn};
 pub use message::{Message, MessageColumn};
 pub use order::{Order, OrderColumn};
+pub use remember_me::{RememberMe, RememberMeColumn};
 
 pub use collection::{Collection, CollectionColumn};
 pub use dataset::{Dataset, DatasetColumn};
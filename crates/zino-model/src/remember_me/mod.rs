@@ -0,0 +1,114 @@
+//! The `remember_me` model and related services.
+
+use serde::{Deserialize, Serialize};
+use zino_auth::RememberMeRecord;
+use zino_core::{
+    datetime::DateTime,
+    error::Error,
+    extension::JsonObjectExt,
+    model::{Model, ModelHooks},
+    validation::Validation,
+    Map, Uuid,
+};
+use zino_derive::{DecodeRow, Entity, ModelAccessor, Schema};
+
+mod store;
+
+pub use store::RememberMeTable;
+
+/// The `remember_me` model for the persistent login series.
+///
+/// Only the hash of the token is persisted, and the token is rotated on every use.
+#[derive(
+    Debug, Clone, Default, Serialize, Deserialize, DecodeRow, Entity, Schema, ModelAccessor,
+)]
+#[serde(default)]
+#[schema(auto_rename)]
+pub struct RememberMe {
+    // Basic fields.
+    #[schema(read_only)]
+    id: Uuid,
+
+    // Info fields.
+    #[schema(not_null, unique, read_only)]
+    series: String,
+    #[schema(not_null, write_only)]
+    token_hash: String,
+    #[schema(write_only)]
+    previous_token_hash: Option<String>,
+    #[schema(not_null, index_type = "hash")]
+    user_id: String,
+    #[schema(not_null)]
+    expires_at: DateTime,
+    rotated_at: Option<DateTime>,
+
+    // Revisions.
+    #[schema(read_only, default_value = "now", index_type = "btree")]
+    created_at: DateTime,
+    #[schema(default_value = "now", index_type = "btree")]
+    updated_at: DateTime,
+    version: u64,
+}
+
+impl Model for RememberMe {
+    const MODEL_NAME: &'static str = "remember_me";
+
+    #[inline]
+    fn new() -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            ..Self::default()
+        }
+    }
+
+    fn read_map(&mut self, data: &Map) -> Validation {
+        let mut validation = Validation::new();
+        if let Some(result) = data.parse_uuid("id") {
+            match result {
+                Ok(id) => self.id = id,
+                Err(err) => validation.record_fail("id", err),
+            }
+        }
+        if let Some(user_id) = data.parse_string("user_id") {
+            self.user_id = user_id.into_owned();
+        }
+        if let Some(result) = data.parse_date_time("expires_at") {
+            match result {
+                Ok(expires_at) => self.expires_at = expires_at,
+                Err(err) => validation.record_fail("expires_at", err),
+            }
+        }
+        validation
+    }
+}
+
+impl ModelHooks for RememberMe {
+    type Data = ();
+    type Extension = ();
+}
+
+impl From<&RememberMeRecord> for RememberMe {
+    fn from(record: &RememberMeRecord) -> Self {
+        let mut remember_me = Self::new();
+        remember_me.series = record.series.clone();
+        remember_me.token_hash = record.token_hash.clone();
+        remember_me.previous_token_hash = record.previous_token_hash.clone();
+        remember_me.user_id = record.user_id.clone();
+        remember_me.expires_at = record.expires_at;
+        remember_me.rotated_at = record.rotated_at;
+        remember_me
+    }
+}
+
+impl From<RememberMe> for RememberMeRecord {
+    fn from(remember_me: RememberMe) -> Self {
+        Self {
+            series: remember_me.series,
+            token_hash: remember_me.token_hash,
+            previous_token_hash: remember_me.previous_token_hash,
+            user_id: remember_me.user_id,
+            expires_at: remember_me.expires_at,
+            rotated_at: remember_me.rotated_at,
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use super::RememberMe;
+use zino_auth::{RememberMeRecord, RememberMeStore};
+use zino_core::{
+    datetime::DateTime,
+    error::Error,
+    model::{Mutation, Query},
+    BoxFuture, Map,
+};
+use zino_orm::{IntoSqlValue, Schema};
+
+/// A store of remember-me series backed by the `remember_me` table.
+///
+/// The token is rotated by a conditional update on the previous token hash,
+/// so that only one of the concurrent requests with the same token wins.
+/// It can be registered by `RememberMeToken::register_store(RememberMeTable)`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RememberMeTable;
+
+impl RememberMeTable {
+    /// Finds the record by the series identifier.
+    async fn find_record(series: &str) -> Result<Option<RememberMeRecord>, Error> {
+        let mut query = Query::default();
+        query.add_filter("series", series);
+        let remember_me = RememberMe::find_one::<RememberMe>(&query).await?;
+        Ok(remember_me.map(RememberMeRecord::from))
+    }
+
+    /// Inserts a new record.
+    async fn insert_record(record: &RememberMeRecord) -> Result<(), Error> {
+        RememberMe::from(record).insert().await?;
+        Ok(())
+    }
+
+    /// Updates the record only if its token hash is still `token_hash`.
+    async fn update_record(record: &RememberMeRecord, token_hash: &str) -> Result<bool, Error> {
+        let mut query = Query::default();
+        query.add_filter("series", record.series.as_str());
+        query.add_filter("token_hash", token_hash);
+
+        let mut updates = Map::new();
+        updates.insert("token_hash".to_owned(), record.token_hash.as_str().into());
+        updates.insert(
+            "previous_token_hash".to_owned(),
+            record.previous_token_hash.clone().into_sql_value(),
+        );
+        updates.insert("rotated_at".to_owned(), record.rotated_at.into_sql_value());
+        updates.insert("updated_at".to_owned(), DateTime::now().into_sql_value());
+
+        let mut mutation = Mutation::new(updates);
+        let ctx = RememberMe::update_one(&query, &mut mutation).await?;
+        Ok(ctx.rows_affected() == Some(1))
+    }
+
+    /// Deletes the records with the filter.
+    async fn delete_records(key: &str, value: &str) -> Result<(), Error> {
+        let mut query = Query::default();
+        query.add_filter(key, value);
+        RememberMe::delete_many(&query).await?;
+        Ok(())
+    }
+}
+
+impl RememberMeStore for RememberMeTable {
+    fn find<'a>(
+        &'a self,
+        series: &'a str,
+    ) -> BoxFuture<'a, Result<Option<RememberMeRecord>, Error>> {
+        Box::pin(Self::find_record(series))
+    }
+
+    fn insert<'a>(&'a self, record: &'a RememberMeRecord) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(Self::insert_record(record))
+    }
+
+    fn update<'a>(
+        &'a self,
+        record: &'a RememberMeRecord,
+        token_hash: &'a str,
+    ) -> BoxFuture<'a, Result<bool, Error>> {
+        Box::pin(Self::update_record(record, token_hash))
+    }
+
+    fn remove<'a>(&'a self, series: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(Self::delete_records("series", series))
+    }
+
+    fn remove_all<'a>(&'a self, user_id: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(Self::delete_records("user_id", user_id))
+    }
+}
@@ -158,3 +158,73 @@ impl ModelHooks for Tag {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Tag;
+    use std::sync::Arc;
+    use zino_core::{extension::JsonObjectExt, json, model::Model, Map};
+    use zino_orm::{ModelAccessor, Schema};
+
+    #[test]
+    fn it_caches_definitions() {
+        let definition = Tag::definition_cached("insert");
+        assert!(Arc::ptr_eq(&definition, &Tag::definition_cached("insert")));
+        assert_eq!(definition["required"], json!(["name", "category"]));
+
+        let definition = Tag::definition_cached("list");
+        assert!(definition.get("required").is_none());
+        assert_eq!(definition["properties"]["id"]["readOnly"], true);
+        assert_eq!(definition["properties"]["status"]["default"], "Active");
+    }
+
+    #[test]
+    fn it_diffs_tags() {
+        let mut data = Map::new();
+        data.upsert("name", "alpha");
+        data.upsert("category", "color");
+
+        let mut tag = Tag::new();
+        assert!(tag.read_map(&data).is_success());
+
+        let current = tag.clone();
+        assert!(current.changed_fields(&tag).is_empty());
+        assert!(current.diff(&tag).is_empty());
+
+        data.upsert("name", "beta");
+        assert!(tag.read_map(&data).is_success());
+        assert_eq!(current.changed_fields(&tag), ["name"]);
+        assert_eq!(current.diff(&tag).get_str("name"), Some("beta"));
+        assert_eq!(tag.changed_fields_since(&current.into_map()), ["name"]);
+    }
+
+    #[tokio::test]
+    async fn it_updates_changed_columns() {
+        let mut data = Map::new();
+        data.upsert("name", "alpha");
+        data.upsert("category", "color");
+
+        let mut tag = Tag::new();
+        assert!(tag.read_map(&data).is_success());
+
+        let snapshot = tag.clone().into_map();
+        data.upsert("name", "beta");
+        assert!(tag.read_map(&data).is_success());
+
+        let changed_fields = tag.changed_fields_since(&snapshot);
+        data.retain(|key, _value| changed_fields.contains(&key.as_str()));
+
+        let query = tag.current_version_query();
+        let mut mutation = tag.next_version_mutation(&mut data);
+        let ctx = Tag::prepare_update_one(&query, &mut mutation)
+            .await
+            .unwrap();
+        let sql = ctx.query();
+        assert!(
+            sql.contains("SET `name` = 'beta', `updated_at` = "),
+            "{sql}"
+        );
+        assert!(sql.contains("`version` = 1 WHERE"), "{sql}");
+        assert!(!sql.contains("`category` ="), "{sql}");
+    }
+}
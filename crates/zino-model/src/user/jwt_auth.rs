@@ -1,14 +1,17 @@
+use super::LockoutPolicy;
 use std::{fmt::Display, str::FromStr};
 use zino_auth::JwtClaims;
+use zino_channel::{CloudEvent, MessageChannel};
 use zino_core::{
+    application::Agent,
     bail,
     datetime::DateTime,
     error::Error,
     extension::{JsonObjectExt, JsonValueExt},
-    model::Query,
+    model::{Mutation, Query},
     warn, Map, Uuid,
 };
-use zino_orm::{ModelAccessor, ModelHelper};
+use zino_orm::{IntoSqlValue, ModelAccessor, ModelHelper};
 
 /// JWT authentication service.
 pub trait JwtAuthService<K = Uuid>
@@ -29,6 +32,10 @@ where
     const LOGIN_AT_FIELD: Option<&'static str> = None;
     /// Login-IP field name.
     const LOGIN_IP_FIELD: Option<&'static str> = None;
+    /// Field name for the number of consecutive login failures.
+    const LOGIN_FAILURES_FIELD: Option<&'static str> = None;
+    /// Field name for the time of the last login failure.
+    const LOGIN_FAILURE_AT_FIELD: Option<&'static str> = None;
 
     /// Consumes the user into standard claims without a `sub` field,
     /// which can be used to create a [`JwtClaims`] and generate an ID token.
@@ -137,6 +144,12 @@ where
         if let Some(login_ip_field) = Self::LOGIN_IP_FIELD {
             fields.push(login_ip_field);
         }
+        if let Some(login_failures_field) = Self::LOGIN_FAILURES_FIELD {
+            fields.push(login_failures_field);
+        }
+        if let Some(login_failure_at_field) = Self::LOGIN_FAILURE_AT_FIELD {
+            fields.push(login_failure_at_field);
+        }
         query.allow_fields(&fields);
         query.add_filter("status", Map::from_entry("$nin", vec!["Locked", "Deleted"]));
         query.add_filter(Self::ACCOUNT_FIELD, account);
@@ -144,19 +157,39 @@ where
         let mut user: Map = Self::find_one(&query)
             .await?
             .ok_or_else(|| warn!("404 Not Found: invalid user account or password"))?;
+
+        // Cann't use `get_str` because the primary key may be an integer
+        let user_id = user
+            .parse_string(Self::PRIMARY_KEY_NAME)
+            .ok_or_else(|| warn!("404 Not Found: user id is absent"))?
+            .into_owned();
+        if let (Some(login_failures_field), Some(login_failure_at_field)) =
+            (Self::LOGIN_FAILURES_FIELD, Self::LOGIN_FAILURE_AT_FIELD)
+        {
+            let failures = user.get_u32(login_failures_field).unwrap_or_default();
+            let last_failure_at = user
+                .get_str(login_failure_at_field)
+                .and_then(|s| s.parse::<DateTime>().ok());
+            if let Some(last_failure_at) = last_failure_at {
+                let policy = LockoutPolicy::shared();
+                if policy.is_locked(failures, last_failure_at, DateTime::now()) {
+                    bail!("403 Forbidden: account is locked due to too many failed login attempts");
+                }
+            }
+        }
+
         let encrypted_password = user
             .get_str(Self::PASSWORD_FIELD)
             .ok_or_else(|| warn!("404 Not Found: user password is absent"))?;
-        if Self::verify_password(passowrd, encrypted_password)
-            .map_err(|_| warn!("401 Unauthorized: invalid user account or password"))?
-        {
-            // Cann't use `get_str` because the primary key may be an integer
-            let user_id = user
-                .parse_string(Self::PRIMARY_KEY_NAME)
-                .ok_or_else(|| warn!("404 Not Found: user id is absent"))?;
-            let mut claims = JwtClaims::new(user_id.as_ref());
+        let password_verified =
+            compute_password_verification(Self::verify_password, passowrd, encrypted_password)
+                .await
+                .map_err(|_| warn!("401 Unauthorized: invalid user account or password"))?;
+        let user_id = user_id.parse::<K>()?;
+        if password_verified {
+            Self::record_login_success(&user_id).await?;
 
-            let user_id = user_id.parse()?;
+            let mut claims = JwtClaims::new(user_id.to_string());
             if let Some(role_field) = Self::ROLE_FIELD.filter(|&field| user.contains_key(field)) {
                 claims.add_data_entry("roles", user.parse_str_array(role_field));
             }
@@ -177,8 +210,102 @@ where
             }
             Ok((user_id, data))
         } else {
-            Err(warn!("fail to generate access token"))
+            Self::record_login_failure(&user_id).await?;
+            Err(warn!("401 Unauthorized: invalid user account or password"))
+        }
+    }
+
+    /// Records a login failure of the user, returning `true` if the account has been locked.
+    ///
+    /// The updates are performed with atomic statements and never touch the `updated_at`
+    /// and `version` fields, so they do not interfere with the optimistic locking.
+    async fn record_login_failure(user_id: &K) -> Result<bool, Error> {
+        let (Some(login_failures_field), Some(login_failure_at_field)) =
+            (Self::LOGIN_FAILURES_FIELD, Self::LOGIN_FAILURE_AT_FIELD)
+        else {
+            return Ok(false);
+        };
+
+        let policy = LockoutPolicy::shared();
+        let user_id = user_id.to_string();
+        let now = DateTime::now();
+
+        // Restarts the counting if the last failure is out of the window
+        // or the cooldown period of a lockout has elapsed.
+        let mut expired_filter = Map::new();
+        expired_filter.upsert(
+            login_failure_at_field,
+            Map::from_entry("$lt", (now - policy.window()).into_sql_value()),
+        );
+        let mut cooled_down_filter = Map::new();
+        cooled_down_filter.upsert(
+            login_failure_at_field,
+            Map::from_entry("$lt", (now - policy.cooldown()).into_sql_value()),
+        );
+        cooled_down_filter.upsert(
+            login_failures_field,
+            Map::from_entry("$ge", policy.max_attempts()),
+        );
+        let mut query = Query::default();
+        query.add_filter(Self::PRIMARY_KEY_NAME, user_id.as_str());
+        query.add_filter("$or", vec![expired_filter, cooled_down_filter]);
+        let mut mutation = Mutation::from_entry(login_failures_field, 0);
+        Self::update_one(&query, &mut mutation).await?;
+
+        let mut query = Query::default();
+        query.add_filter(Self::PRIMARY_KEY_NAME, user_id.as_str());
+        let mut updates = Map::new();
+        updates.upsert("$inc", Map::from_entry(login_failures_field, 1));
+        updates.upsert(login_failure_at_field, now.into_sql_value());
+        let mut mutation = Mutation::new(updates);
+        Self::update_one(&query, &mut mutation).await?;
+
+        query.allow_fields(&[login_failures_field]);
+        let failures = Self::find_one::<Map>(&query)
+            .await?
+            .and_then(|user| user.get_u32(login_failures_field))
+            .unwrap_or_default();
+        if !policy.is_enabled() || failures != policy.max_attempts() {
+            return Ok(false);
+        }
+
+        let locked_until = now + policy.cooldown();
+        tracing::warn!(
+            user_id = user_id.as_str(),
+            failures,
+            locked_until = locked_until.to_string(),
+            "account is locked due to too many failed login attempts",
+        );
+
+        let mut data = Map::new();
+        data.upsert("user_id", user_id.as_str());
+        data.upsert("failures", failures);
+        data.upsert("locked_until", locked_until);
+
+        let source = format!("/{}", Self::model_name());
+        let mut event = CloudEvent::new(Uuid::now_v7(), source, "user.locked");
+        event.set_subject(user_id);
+        event.set_data(data);
+        if let Err(err) = MessageChannel::shared().try_send(event) {
+            tracing::error!("fail to emit the lockout event: {err}");
         }
+        Ok(true)
+    }
+
+    /// Records a login success of the user, which resets the number of consecutive failures.
+    ///
+    /// The `updated_at` and `version` fields are not touched.
+    async fn record_login_success(user_id: &K) -> Result<(), Error> {
+        let Some(login_failures_field) = Self::LOGIN_FAILURES_FIELD else {
+            return Ok(());
+        };
+
+        let mut query = Query::default();
+        query.add_filter(Self::PRIMARY_KEY_NAME, user_id.to_string());
+        query.add_filter(login_failures_field, Map::from_entry("$gt", 0));
+        let mut mutation = Mutation::from_entry(login_failures_field, 0);
+        Self::update_one(&query, &mut mutation).await?;
+        Ok(())
     }
 
     /// Refreshes the access token.
@@ -311,14 +438,29 @@ where
             data.upsert("account_verified", account_verified);
         }
         if let Some(encrypted_password) = user.get_str(Self::PASSWORD_FIELD) {
-            let password_verified = Self::verify_password(password, encrypted_password)?;
+            let password_verified =
+                compute_password_verification(Self::verify_password, password, encrypted_password)
+                    .await?;
             data.upsert("password_verified", password_verified);
         }
         Ok(data)
     }
 }
 
+/// Verifies the password in the compute pool since password hashing is CPU-bound.
+async fn compute_password_verification(
+    verifier: fn(&str, &str) -> Result<bool, Error>,
+    password: &str,
+    encrypted_password: &str,
+) -> Result<bool, Error> {
+    let password = password.to_owned();
+    let encrypted_password = encrypted_password.to_owned();
+    Agent::compute(move || verifier(&password, &encrypted_password)).await?
+}
+
 impl JwtAuthService<Uuid> for super::User {
     const LOGIN_AT_FIELD: Option<&'static str> = Some("current_login_at");
     const LOGIN_IP_FIELD: Option<&'static str> = Some("current_login_ip");
+    const LOGIN_FAILURES_FIELD: Option<&'static str> = Some("consecutive_login_failures");
+    const LOGIN_FAILURE_AT_FIELD: Option<&'static str> = Some("last_login_failure_at");
 }
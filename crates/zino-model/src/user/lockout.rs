@@ -0,0 +1,123 @@
+use std::time::Duration;
+use zino_core::{datetime::DateTime, extension::TomlTableExt, state::State, LazyLock};
+
+/// Account lockout policy for failed login attempts.
+///
+/// It can be configured in the `[auth.lockout]` table:
+///
+/// ```toml
+/// [auth.lockout]
+/// enabled = true
+/// max-attempts = 5
+/// window = "15m"
+/// cooldown = "15m"
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct LockoutPolicy {
+    /// Flag to indicate whether the lockout is enabled.
+    enabled: bool,
+    /// Max number of consecutive failed attempts before the account is locked.
+    max_attempts: u32,
+    /// Time window in which the failed attempts are counted.
+    window: Duration,
+    /// Cooldown period after which the account is unlocked automatically.
+    cooldown: Duration,
+}
+
+impl LockoutPolicy {
+    /// Creates a new instance with the default settings.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            max_attempts: 5,
+            window: Duration::from_secs(15 * 60),
+            cooldown: Duration::from_secs(15 * 60),
+        }
+    }
+
+    /// Returns a reference to the shared lockout policy.
+    #[inline]
+    pub fn shared() -> &'static Self {
+        &SHARED_LOCKOUT_POLICY
+    }
+
+    /// Returns `true` if the lockout is enabled.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns the max number of consecutive failed attempts.
+    #[inline]
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Returns the time window in which the failed attempts are counted.
+    #[inline]
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Returns the cooldown period.
+    #[inline]
+    pub fn cooldown(&self) -> Duration {
+        self.cooldown
+    }
+
+    /// Returns `true` if the account with the number of consecutive failures
+    /// and the time of the last failure is locked at the time.
+    pub fn is_locked(&self, failures: u32, last_failure_at: DateTime, now: DateTime) -> bool {
+        self.enabled
+            && failures >= self.max_attempts
+            && now.duration_since(last_failure_at) < self.cooldown
+    }
+}
+
+impl Default for LockoutPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Shared lockout policy.
+static SHARED_LOCKOUT_POLICY: LazyLock<LockoutPolicy> = LazyLock::new(|| {
+    let mut policy = LockoutPolicy::new();
+    if let Some(config) = State::shared()
+        .get_config("auth")
+        .and_then(|config| config.get_table("lockout"))
+    {
+        if let Some(enabled) = config.get_bool("enabled") {
+            policy.enabled = enabled;
+        }
+        if let Some(max_attempts) = config.get_u32("max-attempts") {
+            policy.max_attempts = max_attempts.max(1);
+        }
+        if let Some(window) = config.get_duration("window") {
+            policy.window = window;
+        }
+        if let Some(cooldown) = config.get_duration("cooldown") {
+            policy.cooldown = cooldown;
+        }
+    }
+    policy
+});
+
+#[cfg(test)]
+mod tests {
+    use super::LockoutPolicy;
+    use std::time::Duration;
+    use zino_core::datetime::DateTime;
+
+    #[test]
+    fn it_checks_account_lockout() {
+        let policy = LockoutPolicy::new();
+        let now = DateTime::now();
+        assert!(!policy.is_locked(4, now, now));
+        assert!(policy.is_locked(5, now, now));
+        assert!(policy.is_locked(5, now - Duration::from_secs(60), now));
+        assert!(!policy.is_locked(5, now - Duration::from_secs(15 * 60), now));
+    }
+}
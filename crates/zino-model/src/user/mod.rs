@@ -18,9 +18,11 @@ use zino_orm::ModelHelper;
 use crate::tag::Tag;
 
 mod jwt_auth;
+mod lockout;
 mod status;
 
 pub use jwt_auth::JwtAuthService;
+pub use lockout::LockoutPolicy;
 pub use status::UserStatus;
 
 #[cfg(feature = "visibility")]
@@ -89,6 +91,8 @@ pub struct User {
     current_login_ip: String,
     login_count: u32,
     failed_login_count: u8,
+    consecutive_login_failures: u32,
+    last_login_failure_at: DateTime,
 
     // Extensions.
     extra: Map,
@@ -1,4 +1,4 @@
-use crate::RouterConfigure;
+use crate::{middleware, NtexResponse, Request, RouterConfigure};
 use ntex::{
     rt::System,
     time::{self, Seconds},
@@ -10,11 +10,16 @@ use ntex::{
     },
 };
 use ntex_files::{Files, NamedFile};
+use std::{future::Future, net::SocketAddr, sync::Arc};
 use zino_core::{
-    application::{Application, Plugin, ServerTag},
+    application::{Application, LifecycleHooks, Plugin, ServerTag},
+    error::Error,
     extension::TomlTableExt,
     schedule::AsyncScheduler,
+    state::State,
+    Map,
 };
+use zino_http::{response::ErrorResponseFormat, static_files::StaticFiles};
 
 /// An HTTP server cluster.
 #[derive(Default)]
@@ -25,6 +30,77 @@ pub struct Cluster {
     default_routes: Vec<RouterConfigure>,
     /// Tagged routes.
     tagged_routes: Vec<(ServerTag, Vec<RouterConfigure>)>,
+    /// Static file services.
+    static_files: Vec<StaticFiles>,
+    /// Lifecycle hooks.
+    lifecycle_hooks: LifecycleHooks,
+}
+
+impl Cluster {
+    /// Sets the format of the JSON body for error responses.
+    ///
+    /// The default format is problem details defined in RFC 7807.
+    #[inline]
+    pub fn with_error_format(self, format: impl ErrorResponseFormat) -> Self {
+        zino_http::response::set_error_format(format);
+        self
+    }
+
+    /// Serves the static files under the route prefix with the config `[static.{name}]`.
+    /// See [`StaticFiles`] for the supported options.
+    #[inline]
+    pub fn serve_dir(mut self, route_prefix: &str, name: &str) -> Self {
+        let static_files = StaticFiles::with_config_name(route_prefix, name);
+        self.static_files.push(static_files);
+        self
+    }
+
+    /// Registers a hook running after the config is loaded
+    /// but before the connection pools are established.
+    /// A failing hook aborts the startup with the error.
+    #[inline]
+    pub fn on_boot<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(&'static State<Map>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        self.lifecycle_hooks.add_boot_hook(hook);
+        self
+    }
+
+    /// Registers a hook running after the listeners have been bound.
+    #[inline]
+    pub fn on_ready<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(SocketAddr) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.lifecycle_hooks.add_ready_hook(hook);
+        self
+    }
+
+    /// Registers a hook running after the config has been reloaded on `SIGHUP`
+    /// or a change of the config file. See [`Application::reload_config()`] for the details.
+    #[inline]
+    pub fn on_config_reload<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(&'static State<Map>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        self.lifecycle_hooks.add_reload_hook(hook);
+        self
+    }
+
+    /// Registers a hook running after the servers have been stopped gracefully.
+    #[inline]
+    pub fn on_shutdown<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.lifecycle_hooks.add_shutdown_hook(hook);
+        self
+    }
 }
 
 impl Application for Cluster {
@@ -50,9 +126,20 @@ impl Application for Cluster {
 
     fn run_with<T: AsyncScheduler + Send + 'static>(self, mut scheduler: T) {
         let app_env = Self::env();
-        System::new("prelude").block_on(async {
+        let lifecycle_hooks = Arc::new(self.lifecycle_hooks);
+        let hooks = lifecycle_hooks.clone();
+        System::new("prelude").block_on(async move {
+            if let Err(err) = hooks.run_boot_hooks(Self::shared_state()).await {
+                panic!("fail to run the boot hooks: {err}");
+            }
             #[cfg(feature = "orm")]
             zino_orm::GlobalPool::connect_all().await;
+            #[cfg(feature = "orm")]
+            zino_orm::JobRunStore::init();
+            #[cfg(feature = "orm")]
+            zino_core::application::LoadShedder::set_pool_waiters_source(
+                zino_orm::GlobalPool::waiting_count,
+            );
             Self::load().await;
             app_env.load_plugins(self.custom_plugins).await;
         });
@@ -73,18 +160,65 @@ impl Application for Cluster {
                     }
                 }));
         }
+        #[cfg(feature = "orm")]
+        {
+            let mut maintenance_scheduler = zino_orm::MaintenanceScheduler::new();
+            System::new("maintenance")
+                .system()
+                .arbiter()
+                .spawn(Box::pin(async move {
+                    loop {
+                        maintenance_scheduler.tick().await;
+                        if let Some(duration) = maintenance_scheduler.time_till_next_job() {
+                            time::sleep(duration).await;
+                        }
+                    }
+                }));
+        }
+
+        System::new("main").block_on(async move {
+            // Config reloading
+            let hooks = lifecycle_hooks.clone();
+            ntex::rt::spawn(async move {
+                hooks.watch_config::<Self, _, _>(time::sleep).await;
+            });
+            #[cfg(unix)]
+            {
+                let hooks = lifecycle_hooks.clone();
+                ntex::rt::spawn(async move {
+                    use tokio::signal::unix::{signal, SignalKind};
+
+                    match signal(SignalKind::hangup()) {
+                        Ok(mut hangup) => {
+                            while hangup.recv().await.is_some() {
+                                hooks.reload_config::<Self>().await;
+                            }
+                        }
+                        Err(err) => {
+                            tracing::error!("fail to install the hangup signal handler: {err}")
+                        }
+                    }
+                });
+            }
 
-        System::new("main").block_on(async {
             let default_routes = self.default_routes.leak() as &'static [_];
             let tagged_routes = self.tagged_routes.leak() as &'static [_];
+            let file_services = self.static_files.leak() as &'static [StaticFiles];
             let app_state = Self::shared_state();
             let app_name = Self::name();
             let app_version = Self::version();
             let app_domain = Self::domain();
-            let listeners = app_state.listeners();
+            let listeners = app_state.server_listeners();
+            let bound_addrs = listeners
+                .iter()
+                .map(|listener| listener.addr())
+                .collect::<Vec<_>>();
             let servers = listeners.into_iter().map(|listener| {
-                let server_tag = listener.0;
-                let addr = listener.1;
+                let server_tag = listener.scope().clone();
+                let addr = listener.addr();
+                if listener.tls().is_some() {
+                    panic!("fail to listen on {addr}: TLS is not supported for the listeners");
+                }
                 tracing::warn!(
                     server_tag = server_tag.as_str(),
                     app_env = app_env.as_str(),
@@ -153,11 +287,30 @@ impl Application for Cluster {
                             "Static pages `{public_route_prefix}/**` are registered for `{addr}`"
                         );
                     }
-                    for route in default_routes {
-                        app = app.configure(route);
+                    for files in file_services {
+                        let route_prefix = files.route_prefix();
+                        let wildcard_route = if route_prefix == "/" {
+                            "/{path}*".to_owned()
+                        } else {
+                            format!("{route_prefix}/{{path}}*")
+                        };
+                        let handler = move |req: Request| async move {
+                            NtexResponse::from(files.serve(req).await)
+                        };
+                        app = app
+                            .route(route_prefix, web::get().to(handler))
+                            .route(&wildcard_route, web::get().to(handler));
+                        tracing::info!(
+                            "Static files `{route_prefix}/**` are registered for `{addr}`"
+                        );
+                    }
+                    if listener.serves_default_routes() {
+                        for route in default_routes {
+                            app = app.configure(route);
+                        }
                     }
                     for (tag, routes) in tagged_routes {
-                        if tag == &server_tag || server_tag.is_debug() {
+                        if listener.serves_routes(tag) {
                             for route in routes {
                                 app = app.configure(route);
                             }
@@ -167,6 +320,13 @@ impl Application for Cluster {
                     app.state(FormConfig::default().limit(body_limit))
                         .state(JsonConfig::default().limit(body_limit))
                         .state(PayloadConfig::default().limit(body_limit))
+                        .wrap(middleware::ServerScope::new(server_tag.clone()))
+                        .wrap(middleware::SecurityHeadersSetter)
+                        .wrap(middleware::HostValidator)
+                        .wrap(middleware::LoadSheddingGuard)
+                        .wrap(middleware::MaintenanceGuard)
+                        .wrap(middleware::PanicHandler)
+                        .wrap(middleware::CorsHandler)
                         .wrap(Compress::default())
                 })
                 .stop_runtime()
@@ -176,14 +336,26 @@ impl Application for Cluster {
                 .maxconn(max_connections)
                 .client_timeout(Seconds(request_timeout))
                 .bind(addr)
-                .unwrap_or_else(|err| panic!("fail to create an HTTP server: {err}"))
-                .run()
+                .unwrap_or_else(|err| panic!("fail to listen on {addr}: {err}"))
             });
+
+            // Binds all the listeners before serving, so that a failure aborts the boot.
+            let servers = servers.collect::<Vec<_>>();
+            for addr in bound_addrs {
+                lifecycle_hooks.run_ready_hooks(addr).await;
+            }
+
+            let servers = servers.into_iter().map(|server| server.run());
             for result in futures::future::join_all(servers).await {
                 if let Err(err) = result {
                     tracing::error!("ntex server error: {err}");
                 }
             }
+            lifecycle_hooks.run_shutdown_hooks().await;
+
+            // Flushes the pending spans after the servers have been stopped
+            #[cfg(feature = "otel")]
+            zino_core::application::shutdown_tracer_provider();
         });
     }
 }
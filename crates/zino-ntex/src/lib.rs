@@ -6,9 +6,13 @@
 #![forbid(unsafe_code)]
 
 mod application;
+mod middleware;
 mod request;
 mod response;
 
+#[cfg(feature = "test-utils")]
+pub mod test;
+
 pub use application::Cluster;
 pub use request::Extractor;
 pub use response::{NtexRejection, NtexResponse};
@@ -0,0 +1,87 @@
+use ntex::{
+    http::header::{HeaderMap, HeaderName, HeaderValue},
+    service::{Middleware, Service, ServiceCtx},
+    web::{self, error::ErrorRenderer, HttpResponse, WebRequest, WebResponse},
+};
+use zino_core::{application::ServerTag, state::Data};
+use zino_http::cors::{self, CorsPolicy};
+
+/// Applies the CORS policy for the request path and the listener scope.
+///
+/// Preflight requests are responded before reaching the routes and their middlewares,
+/// and a disallowed origin gets a response without the CORS headers.
+#[derive(Default)]
+pub struct CorsHandler;
+
+impl<S> Middleware<S> for CorsHandler {
+    type Service = CorsMiddleware<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        CorsMiddleware { service }
+    }
+}
+
+pub struct CorsMiddleware<S> {
+    service: S,
+}
+
+impl<S, Err> Service<WebRequest<Err>> for CorsMiddleware<S>
+where
+    S: Service<WebRequest<Err>, Response = WebResponse, Error = web::Error>,
+    Err: ErrorRenderer,
+{
+    type Response = WebResponse;
+    type Error = web::Error;
+
+    ntex::forward_ready!(service);
+
+    async fn call(
+        &self,
+        req: WebRequest<Err>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let headers = req.headers();
+        let Some(origin) = headers.get("origin").and_then(|v| v.to_str().ok()) else {
+            return ctx.call(&self.service, req).await;
+        };
+
+        let server_tag = req
+            .extensions()
+            .get::<Data<ServerTag>>()
+            .map(|data| data.get())
+            .unwrap_or_default();
+        let policy = CorsPolicy::for_scope(&server_tag, req.path());
+        let request_method = headers
+            .get("access-control-request-method")
+            .and_then(|v| v.to_str().ok());
+        if cors::is_preflight_request(req.method().as_str(), Some(origin), request_method) {
+            let request_headers = headers
+                .get("access-control-request-headers")
+                .and_then(|v| v.to_str().ok());
+            let cors_headers = policy.preflight_headers(
+                origin,
+                request_method.unwrap_or_default(),
+                request_headers,
+            );
+            let mut res = HttpResponse::NoContent().finish();
+            append_headers(res.headers_mut(), cors_headers);
+
+            let (req, _) = req.into_parts();
+            return Ok(WebResponse::new(res, req));
+        }
+
+        let cors_headers = policy.response_headers(origin);
+        let mut res = ctx.call(&self.service, req).await?;
+        append_headers(res.headers_mut(), cors_headers);
+        Ok(res)
+    }
+}
+
+/// Appends the CORS headers to the header map.
+fn append_headers(header_map: &mut HeaderMap, headers: Vec<(&'static str, String)>) {
+    for (name, value) in headers {
+        if let Ok(value) = HeaderValue::try_from(value) {
+            header_map.append(HeaderName::from_static(name), value);
+        }
+    }
+}
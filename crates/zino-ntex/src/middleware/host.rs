@@ -0,0 +1,66 @@
+use crate::response::NtexResponse;
+use ntex::{
+    http::StatusCode,
+    service::{Middleware, Service, ServiceCtx},
+    web::{self, error::ErrorRenderer, Responder, WebRequest, WebResponse},
+};
+use zino_core::{application::ServerTag, state::Data};
+use zino_http::{host::HostPolicy, response::Response};
+
+/// Validates the `Host` header and the authority of the request target
+/// with the host policy of the listener.
+#[derive(Default)]
+pub struct HostValidator;
+
+impl<S> Middleware<S> for HostValidator {
+    type Service = HostValidatorMiddleware<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        HostValidatorMiddleware { service }
+    }
+}
+
+pub struct HostValidatorMiddleware<S> {
+    service: S,
+}
+
+impl<S, Err> Service<WebRequest<Err>> for HostValidatorMiddleware<S>
+where
+    S: Service<WebRequest<Err>, Response = WebResponse, Error = web::Error>,
+    Err: ErrorRenderer,
+{
+    type Response = WebResponse;
+    type Error = web::Error;
+
+    ntex::forward_ready!(service);
+
+    async fn call(
+        &self,
+        req: WebRequest<Err>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let server_tag = req
+            .extensions()
+            .get::<Data<ServerTag>>()
+            .map(|data| data.get())
+            .unwrap_or_default();
+        let policy = HostPolicy::for_scope(&server_tag);
+        let authority = req.uri().authority().map(|authority| authority.as_str());
+        let host = req
+            .headers()
+            .get("host")
+            .map(|v| v.to_str().unwrap_or_default());
+        if let Err(rejection) = policy.validate(req.path(), authority, host) {
+            let status_code =
+                StatusCode::from_u16(rejection.status_code()).unwrap_or(StatusCode::BAD_REQUEST);
+            let (req, _) = req.into_parts();
+            let extractor = crate::Request::from(req.clone());
+            let mut res = Response::new(status_code).context(&extractor);
+            res.set_message(rejection.message());
+            let res = NtexResponse::from(res).respond_to(&req).await;
+            Ok(WebResponse::new(res, req))
+        } else {
+            ctx.call(&self.service, req).await
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::response::NtexResponse;
+use ntex::{
+    http::StatusCode,
+    service::{Middleware, Service, ServiceCtx},
+    web::{self, error::ErrorRenderer, Responder, WebRequest, WebResponse},
+};
+use zino_core::application::LoadShedder;
+use zino_http::response::Response;
+
+/// Responds with `503 Service Unavailable` for the requests which are shed
+/// by the adaptive load shedding.
+#[derive(Default)]
+pub struct LoadSheddingGuard;
+
+impl<S> Middleware<S> for LoadSheddingGuard {
+    type Service = LoadSheddingMiddleware<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        LoadSheddingMiddleware { service }
+    }
+}
+
+pub struct LoadSheddingMiddleware<S> {
+    service: S,
+}
+
+impl<S, Err> Service<WebRequest<Err>> for LoadSheddingMiddleware<S>
+where
+    S: Service<WebRequest<Err>, Response = WebResponse, Error = web::Error>,
+    Err: ErrorRenderer,
+{
+    type Response = WebResponse;
+    type Error = web::Error;
+
+    ntex::forward_ready!(service);
+
+    async fn call(
+        &self,
+        req: WebRequest<Err>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let Some(guard) = LoadShedder::admit(req.path()) else {
+            let (req, _) = req.into_parts();
+            let extractor = crate::Request::from(req.clone());
+            let res = Response::new(StatusCode::SERVICE_UNAVAILABLE).shed_load(&extractor);
+            let res = NtexResponse::from(res).respond_to(&req).await;
+            return Ok(WebResponse::new(res, req));
+        };
+
+        let res = ctx.call(&self.service, req).await;
+        drop(guard);
+        res
+    }
+}
@@ -0,0 +1,50 @@
+use crate::response::NtexResponse;
+use ntex::{
+    http::StatusCode,
+    service::{Middleware, Service, ServiceCtx},
+    web::{self, error::ErrorRenderer, Responder, WebRequest, WebResponse},
+};
+use zino_core::application::MaintenanceMode;
+use zino_http::response::Response;
+
+#[derive(Default)]
+pub struct MaintenanceGuard;
+
+impl<S> Middleware<S> for MaintenanceGuard {
+    type Service = MaintenanceMiddleware<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        MaintenanceMiddleware { service }
+    }
+}
+
+pub struct MaintenanceMiddleware<S> {
+    service: S,
+}
+
+impl<S, Err> Service<WebRequest<Err>> for MaintenanceMiddleware<S>
+where
+    S: Service<WebRequest<Err>, Response = WebResponse, Error = web::Error>,
+    Err: ErrorRenderer,
+{
+    type Response = WebResponse;
+    type Error = web::Error;
+
+    ntex::forward_ready!(service);
+
+    async fn call(
+        &self,
+        req: WebRequest<Err>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        if MaintenanceMode::is_enabled() && !MaintenanceMode::is_allowed(req.path()) {
+            let (req, _) = req.into_parts();
+            let extractor = crate::Request::from(req.clone());
+            let res = Response::new(StatusCode::SERVICE_UNAVAILABLE).maintenance(&extractor);
+            let res = NtexResponse::from(res).respond_to(&req).await;
+            Ok(WebResponse::new(res, req))
+        } else {
+            ctx.call(&self.service, req).await
+        }
+    }
+}
@@ -0,0 +1,15 @@
+mod cors;
+mod host;
+mod load_shedding;
+mod maintenance;
+mod panic;
+mod scope;
+mod security;
+
+pub(crate) use self::cors::CorsHandler;
+pub(crate) use self::host::HostValidator;
+pub(crate) use self::load_shedding::LoadSheddingGuard;
+pub(crate) use self::maintenance::MaintenanceGuard;
+pub(crate) use self::panic::PanicHandler;
+pub(crate) use self::scope::ServerScope;
+pub(crate) use self::security::SecurityHeadersSetter;
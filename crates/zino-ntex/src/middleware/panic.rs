@@ -0,0 +1,72 @@
+use crate::response::NtexRejection;
+use ntex::{
+    http::{ResponseError, StatusCode},
+    service::{Middleware, Service, ServiceCtx},
+    web::{
+        self, error::ErrorRenderer, HttpRequest, HttpResponse, WebRequest, WebResponse,
+        WebResponseError,
+    },
+};
+use std::{cell::RefCell, fmt};
+use zino_http::response::HandlerPanic;
+
+#[derive(Default)]
+pub struct PanicHandler;
+
+impl<S> Middleware<S> for PanicHandler {
+    type Service = PanicMiddleware<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        PanicMiddleware { service }
+    }
+}
+
+pub struct PanicMiddleware<S> {
+    service: S,
+}
+
+impl<S, Err> Service<WebRequest<Err>> for PanicMiddleware<S>
+where
+    S: Service<WebRequest<Err>, Response = WebResponse, Error = web::Error>,
+    Err: ErrorRenderer,
+{
+    type Response = WebResponse;
+    type Error = web::Error;
+
+    ntex::forward_ready!(service);
+
+    async fn call(
+        &self,
+        req: WebRequest<Err>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        match HandlerPanic::catch(ctx.call(&self.service, req)).await {
+            Ok(result) => result,
+            Err(panic) => Err(PanicError(RefCell::new(Some(panic))).into()),
+        }
+    }
+}
+
+/// A caught panic rendered as a `500 Internal Server Error` response.
+///
+/// The request has been consumed by the inner service, so the response is built
+/// when the error is rendered with the request retained by `ntex`.
+#[derive(Debug)]
+struct PanicError(RefCell<Option<HandlerPanic>>);
+
+impl fmt::Display for PanicError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+impl WebResponseError for PanicError {
+    fn error_response(&self, req: &HttpRequest) -> HttpResponse {
+        let Some(panic) = self.0.borrow_mut().take() else {
+            return HttpResponse::InternalServerError().finish();
+        };
+        let ctx = crate::Request::from(req.clone());
+        let res = panic.into_response::<StatusCode, _>(&ctx);
+        ResponseError::error_response(&NtexRejection::from(res))
+    }
+}
@@ -0,0 +1,53 @@
+use ntex::{
+    service::{Middleware, Service, ServiceCtx},
+    web::{self, error::ErrorRenderer, WebRequest, WebResponse},
+};
+use zino_core::{application::ServerTag, state::Data};
+
+/// Sets the scope of the listener which accepts the request.
+pub struct ServerScope(ServerTag);
+
+impl ServerScope {
+    /// Creates a new instance.
+    #[inline]
+    pub fn new(server_tag: ServerTag) -> Self {
+        Self(server_tag)
+    }
+}
+
+impl<S> Middleware<S> for ServerScope {
+    type Service = ServerScopeMiddleware<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        ServerScopeMiddleware {
+            service,
+            server_tag: self.0.clone(),
+        }
+    }
+}
+
+pub struct ServerScopeMiddleware<S> {
+    service: S,
+    server_tag: ServerTag,
+}
+
+impl<S, Err> Service<WebRequest<Err>> for ServerScopeMiddleware<S>
+where
+    S: Service<WebRequest<Err>, Response = WebResponse, Error = web::Error>,
+    Err: ErrorRenderer,
+{
+    type Response = WebResponse;
+    type Error = web::Error;
+
+    ntex::forward_ready!(service);
+
+    async fn call(
+        &self,
+        req: WebRequest<Err>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        req.extensions_mut()
+            .insert(Data::new(self.server_tag.clone()));
+        ctx.call(&self.service, req).await
+    }
+}
@@ -0,0 +1,78 @@
+use ntex::{
+    http::header::{HeaderMap, HeaderName, HeaderValue},
+    service::{Middleware, Service, ServiceCtx},
+    web::{self, error::ErrorRenderer, WebRequest, WebResponse},
+};
+use zino_core::{application::ServerTag, state::Data};
+use zino_http::security::{CspNonce, SecurityHeaders};
+
+/// Sets the security headers configured for the request path and the listener scope.
+///
+/// A CSP nonce is generated for the request if the policy requires it,
+/// and the headers set explicitly by the handler are not overridden.
+#[derive(Default)]
+pub struct SecurityHeadersSetter;
+
+impl<S> Middleware<S> for SecurityHeadersSetter {
+    type Service = SecurityHeadersMiddleware<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        SecurityHeadersMiddleware { service }
+    }
+}
+
+pub struct SecurityHeadersMiddleware<S> {
+    service: S,
+}
+
+impl<S, Err> Service<WebRequest<Err>> for SecurityHeadersMiddleware<S>
+where
+    S: Service<WebRequest<Err>, Response = WebResponse, Error = web::Error>,
+    Err: ErrorRenderer,
+{
+    type Response = WebResponse;
+    type Error = web::Error;
+
+    ntex::forward_ready!(service);
+
+    async fn call(
+        &self,
+        req: WebRequest<Err>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let server_tag = req
+            .extensions()
+            .get::<Data<ServerTag>>()
+            .map(|data| data.get())
+            .unwrap_or_default();
+        let Some(preset) = SecurityHeaders::for_scope(&server_tag, req.path()) else {
+            return ctx.call(&self.service, req).await;
+        };
+
+        let nonce = preset.requires_nonce().then(CspNonce::new);
+        if let Some(nonce) = nonce.as_ref() {
+            req.extensions_mut().insert(Data::new(nonce.clone()));
+        }
+
+        let mut res = ctx.call(&self.service, req).await?;
+        let content_type = res
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok());
+        let headers = preset.response_headers(&server_tag, content_type, nonce.as_ref());
+        insert_headers(res.headers_mut(), headers);
+        Ok(res)
+    }
+}
+
+/// Inserts the headers which are absent in the header map.
+fn insert_headers(header_map: &mut HeaderMap, headers: Vec<(&'static str, String)>) {
+    for (name, value) in headers {
+        let name = HeaderName::from_static(name);
+        if !header_map.contains_key(&name) {
+            if let Ok(value) = HeaderValue::try_from(value) {
+                header_map.insert(name, value);
+            }
+        }
+    }
+}
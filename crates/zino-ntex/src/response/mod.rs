@@ -1,12 +1,14 @@
+use futures::StreamExt;
 use ntex::{
     http::{
-        body::Body,
+        body::{Body, BodyStream},
         header::{self, HeaderName, HeaderValue},
         ResponseError, StatusCode,
     },
+    util::Bytes,
     web::{HttpRequest, HttpResponse, Responder, WebResponseError},
 };
-use std::fmt;
+use std::{convert::Infallible, fmt};
 use zino_http::{
     response::{Rejection, Response, ResponseCode},
     timing::TimingMetric,
@@ -65,6 +67,13 @@ impl From<Rejection> for NtexRejection {
     }
 }
 
+impl From<Response<StatusCode>> for NtexRejection {
+    #[inline]
+    fn from(response: Response<StatusCode>) -> Self {
+        Self(response)
+    }
+}
+
 impl ResponseError for NtexRejection {
     fn error_response(&self) -> HttpResponse {
         let mut response = self.0.clone();
@@ -115,17 +124,38 @@ impl WebResponseError for NtexRejection {
 
 /// Build http response from `zino_core::response::Response`.
 fn build_http_response<S: ResponseCode>(response: &mut Response<S>) -> HttpResponse {
-    match response.read_bytes() {
-        Ok(data) => {
+    let sse_stream = response.take_sse_stream();
+    let is_sse_response = sse_stream.is_some();
+    let result = if let Some(sse_stream) = sse_stream {
+        let stream = sse_stream
+            .into_bytes_stream(ntex::time::sleep)
+            .map(|data| Ok::<_, Infallible>(Bytes::from(data.to_vec())))
+            .boxed_local();
+        Ok(Body::from_message(BodyStream::new(stream)))
+    } else if let Some(stream) = response.take_bytes_stream() {
+        let stream = stream
+            .map(|data| Ok::<_, Infallible>(Bytes::from(data.to_vec())))
+            .boxed_local();
+        Ok(Body::from_message(BodyStream::new(stream)))
+    } else {
+        response.read_bytes().map(|data| Body::from(data.to_vec()))
+    };
+    match result {
+        Ok(body) => {
             let status_code = response
                 .status_code()
                 .try_into()
                 .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
-            let body = Body::from(data.to_vec());
             let mut res = HttpResponse::with_body(status_code, body);
             if let Ok(header_value) = HeaderValue::try_from(response.content_type()) {
                 res.headers_mut().insert(header::CONTENT_TYPE, header_value);
             }
+            if is_sse_response {
+                // Prevents the event stream from being compressed by the middleware.
+                let header_value = HeaderValue::from_static("identity");
+                res.headers_mut()
+                    .insert(header::CONTENT_ENCODING, header_value);
+            }
             res
         }
         Err(err) => {
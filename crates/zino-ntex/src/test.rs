@@ -0,0 +1,132 @@
+//! Utilities for testing handlers without running a server.
+
+use crate::{NtexRejection, Request};
+use http::{HeaderMap, HeaderName, HeaderValue};
+use ntex::{
+    http::ResponseError,
+    util::{stream_recv, BytesMut},
+    web::{HttpRequest, HttpResponse, Responder},
+};
+use std::future::Future;
+use zino_http::request::CapturedRequest;
+
+#[doc(no_inline)]
+pub use zino_http::test::{MockRequest, MockResponse};
+
+/// Executes the handler with the mock request and collects the response.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino::{prelude::*, test::{self, MockRequest}};
+///
+/// let req = MockRequest::get("/user/list").query(&Map::from_entry("page_size", 10));
+/// let res = test::execute(req, <User as DefaultController<i64>>::list).await;
+/// assert_eq!(res.status_code(), 200);
+/// ```
+pub async fn execute<F, Fut, T>(req: MockRequest, handler: F) -> MockResponse
+where
+    F: FnOnce(Request) -> Fut,
+    Fut: Future<Output = Result<T, NtexRejection>>,
+    T: Responder,
+{
+    let req = Request::from(req);
+    let http_request = HttpRequest::clone(&req);
+    let res = match handler(req).await {
+        Ok(res) => res.respond_to(&http_request).await,
+        Err(rejection) => rejection.error_response(),
+    };
+    collect_response(res).await
+}
+
+/// Replays the captured request against the handler and collects the response.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino::{prelude::*, test};
+///
+/// let captured = CapturedRequest::load("./local/captures/20250101120000-0194c3a4.json")?;
+/// let res = test::replay(captured, <User as DefaultController<i64>>::update).await;
+/// assert_eq!(res.status_code(), 500);
+/// ```
+#[inline]
+pub async fn replay<F, Fut, T>(captured: CapturedRequest, handler: F) -> MockResponse
+where
+    F: FnOnce(Request) -> Fut,
+    Fut: Future<Output = Result<T, NtexRejection>>,
+    T: Responder,
+{
+    execute(captured.into(), handler).await
+}
+
+/// Collects the response into a mock response.
+async fn collect_response(mut res: HttpResponse) -> MockResponse {
+    let status_code = res.status().as_u16();
+    let mut headers = HeaderMap::new();
+    for (name, value) in res.headers().iter() {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_str().as_bytes()),
+            HeaderValue::from_bytes(value.as_bytes()),
+        ) {
+            headers.append(name, value);
+        }
+    }
+
+    let mut body = res.take_body();
+    let mut bytes = BytesMut::new();
+    while let Some(item) = stream_recv(&mut body).await {
+        let chunk = item.unwrap_or_else(|err| panic!("fail to read the response body: {err}"));
+        bytes.extend_from_slice(&chunk);
+    }
+    MockResponse::new(status_code, headers, bytes.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Request, Response, Result};
+    use futures::executor;
+    use zino_core::{extension::JsonObjectExt, Map};
+    use zino_http::{
+        request::{CapturedRequest, RequestContext},
+        test::MockRequest,
+    };
+
+    async fn view(req: Request) -> Result {
+        let id = req.parse_param::<i64>("id")?;
+        let mut res = Response::default().context(&req);
+        res.set_json_data(Map::from_entry("id", id));
+        if let Some(tag) = req.get_data::<&'static str>() {
+            res.set_json_data(Map::from_entry("tag", tag));
+        }
+        Ok(res.into())
+    }
+
+    #[test]
+    fn it_executes_handlers() {
+        let req = MockRequest::get("/user/1/view").route("/user/{id}/view");
+        let res = executor::block_on(super::execute(req, view));
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.json_data().unwrap()["id"], 1);
+
+        let req = MockRequest::get("/user/1/view")
+            .route("/user/{id}/view")
+            .data("admin");
+        let res = executor::block_on(super::execute(req, view));
+        assert_eq!(res.json_data().unwrap()["tag"], "admin");
+
+        let req = MockRequest::get("/user/alice/view").route("/user/{id}/view");
+        let res = executor::block_on(super::execute(req, view));
+        assert_eq!(res.status_code(), 400);
+        assert!(!res.is_success());
+    }
+
+    #[test]
+    fn it_replays_captured_requests() {
+        let mut captured = CapturedRequest::new("GET", "/user/7/view", &Default::default());
+        captured.set_route("/user/{id}/view");
+        let res = executor::block_on(super::replay(captured, view));
+        assert_eq!(res.status_code(), 200);
+        assert_eq!(res.json_data().unwrap()["id"], 7);
+    }
+}
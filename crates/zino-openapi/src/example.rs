@@ -0,0 +1,295 @@
+use ahash::HashMap;
+use convert_case::{Case, Casing};
+use serde_json::json;
+use toml::Table;
+use utoipa::openapi::{
+    content::ContentBuilder,
+    path::Operation,
+    response::ResponseBuilder,
+    schema::{Ref, Schema},
+    RefOr,
+};
+use zino_core::{
+    application::{Agent, Application},
+    extension::{JsonObjectExt, TomlTableExt, TomlValueExt},
+    model::{Column, Page},
+    JsonValue, LazyLock, Map, TomlValue,
+};
+
+/// Attaches the mocked example to the component schema if it has no examples.
+pub(super) fn attach_schema_example(schema: &mut Schema, name: &str, config: &Table) {
+    if !*EXAMPLES_ENABLED {
+        return;
+    }
+    match schema {
+        Schema::Object(object) if object.examples.is_empty() => {
+            if let Some(example) = mock_schema(name, config, false) {
+                object.examples.push(example);
+            }
+        }
+        Schema::Array(array) if array.examples.is_empty() => {
+            if let Some(example) = mock_schema(name, config, false) {
+                array.examples.push(example);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Attaches the mocked examples to the request body of the `new` and `update` routes,
+/// and to the responses of the `list` and `view` routes of the default controller.
+pub(super) fn attach_operation_examples(
+    operation: &mut Operation,
+    path: &str,
+    config: &Table,
+    schemas: &HashMap<String, Table>,
+    model_schemas: &HashMap<String, String>,
+) {
+    if !*EXAMPLES_ENABLED {
+        return;
+    }
+
+    let Some((model_name, action)) = parse_model_route(path) else {
+        return;
+    };
+    match action {
+        "new" | "update" => {
+            let Some(body) = config.get_table("body") else {
+                return;
+            };
+            let Some(request_body) = operation.request_body.as_mut() else {
+                return;
+            };
+            let example = if let Some(schema) = body.get_str("schema") {
+                let schema_name = schema.to_case(Case::Camel);
+                schemas
+                    .get(&schema_name)
+                    .and_then(|config| mock_schema(&schema_name, config, false))
+            } else {
+                mock_schema(&[model_name, "_", action].concat(), body, false)
+            };
+            if let Some(example) = example {
+                for content in request_body.content.values_mut() {
+                    if content.example.is_none() && content.examples.is_empty() {
+                        content.example = Some(example.clone());
+                    }
+                }
+            }
+        }
+        "list" | "view" => {
+            if operation.responses.responses.contains_key("200") {
+                return;
+            }
+
+            let schema_name = model_name.to_case(Case::Camel);
+            let schema_name = if schemas.contains_key(&schema_name) {
+                &schema_name
+            } else if let Some(schema_name) = model_schemas.get(model_name) {
+                schema_name
+            } else {
+                return;
+            };
+            let Some(entry) = schemas
+                .get(schema_name)
+                .and_then(|config| mock_schema(schema_name, config, true))
+                .map(|example| match example {
+                    JsonValue::Array(mut vec) if !vec.is_empty() => vec.swap_remove(0),
+                    _ => example,
+                })
+            else {
+                return;
+            };
+
+            let data = if action == "list" {
+                Page::new(vec![entry])
+                    .total(1)
+                    .page_size(10)
+                    .current_page(1)
+                    .into_map()
+            } else {
+                Map::from_entry("entry", entry)
+            };
+            let request_id = Column::new("request_id", "Uuid", true)
+                .mock_value_with_seed(generate_seed(&[model_name, ".", action].concat()));
+            let example = json!({
+                "status": 200,
+                "success": true,
+                "message": "OK",
+                "request_id": request_id,
+                "data": data,
+            });
+            let content = ContentBuilder::new()
+                .schema(Some(Ref::from_schema_name("defaultResponse")))
+                .example(Some(example))
+                .build();
+            let response = ResponseBuilder::new()
+                .description("OK")
+                .content("application/json", content)
+                .build();
+            operation
+                .responses
+                .responses
+                .insert("200".to_owned(), RefOr::T(response));
+        }
+        _ => (),
+    }
+}
+
+/// Parses the model name and the action for the route of the default controller,
+/// such as `("user", "view")` for `/user/{user_id}/view`.
+fn parse_model_route(path: &str) -> Option<(&str, &str)> {
+    let mut segments = path.trim_matches('/').split('/');
+    let model_name = segments.next().filter(|s| !s.starts_with('{'))?;
+    let action = segments.next_back()?;
+    match segments.next() {
+        None if matches!(action, "new" | "list") => Some((model_name, action)),
+        Some(id) if id.starts_with('{') && matches!(action, "update" | "view") => {
+            Some((model_name, action))
+        }
+        _ => None,
+    }
+}
+
+/// Generates an example for the schema.
+/// The private and write-only properties are excluded from the response examples.
+fn mock_schema(name: &str, config: &Table, is_response: bool) -> Option<JsonValue> {
+    if let Some(example) = config.get("example").or_else(|| config.get("default")) {
+        return Some(example.to_json_value());
+    }
+    if let Some(value) = config.get_array("enum").and_then(|vec| vec.first()) {
+        return Some(value.to_json_value());
+    }
+
+    let schema_type = config.get_str("type").unwrap_or("object");
+    let items = config.get("items");
+    if schema_type == "array" && items.and_then(|v| v.as_str()) == Some("object") {
+        let entry = mock_object(name, config, is_response);
+        return Some(vec![entry].into());
+    }
+    match schema_type {
+        "object" => Some(mock_object(name, config, is_response).into()),
+        "array" => {
+            if let Some(items) = items.and_then(|v| v.as_table()) {
+                let item = mock_schema(name, items, is_response)?;
+                Some(vec![item].into())
+            } else {
+                let items = items.and_then(|v| v.as_str()).unwrap_or("string");
+                mock_column(name, array_type_name(items), config)
+            }
+        }
+        _ => {
+            let format = config.get_str("format");
+            mock_column(name, type_name(schema_type, format), config)
+        }
+    }
+}
+
+/// Generates an object with the mocked values of the properties.
+fn mock_object(name: &str, config: &Table, is_response: bool) -> Map {
+    const SPECIAL_KEYS: [&str; 8] = [
+        "type",
+        "items",
+        "schema",
+        "content_type",
+        "format",
+        "title",
+        "description",
+        "pattern",
+    ];
+
+    let mut object = Map::new();
+    for (key, value) in config {
+        let field = [name, ".", key].concat();
+        match value {
+            TomlValue::String(schema_type) if !SPECIAL_KEYS.contains(&key.as_str()) => {
+                let type_name = type_name(schema_type, None);
+                if let Some(value) = mock_column(&field, type_name, &Table::new()) {
+                    object.insert(key.to_owned(), value);
+                }
+            }
+            TomlValue::Table(config) => {
+                let is_write_only = config.get_bool("write_only") == Some(true)
+                    || config.get_bool("private") == Some(true);
+                if is_response && is_write_only {
+                    continue;
+                }
+                if !is_response && config.get_bool("read_only") == Some(true) {
+                    continue;
+                }
+                if let Some(value) = mock_schema(&field, config, is_response) {
+                    object.insert(key.to_owned(), value);
+                }
+            }
+            _ => (),
+        }
+    }
+    object
+}
+
+/// Generates a value with the mock data of the column.
+/// The seed is derived from the field name so that the example is stable across builds.
+fn mock_column(field: &str, type_name: &str, config: &Table) -> Option<JsonValue> {
+    const EXTRA_KEYS: [&str; 6] = [
+        "format",
+        "min_length",
+        "max_length",
+        "min_items",
+        "max_items",
+        "locale",
+    ];
+
+    let name = field.rsplit('.').next().unwrap_or(field);
+    let mut column = Column::new(name, type_name, true);
+    column.set_extra_attribute("min_items", 1);
+    for key in EXTRA_KEYS {
+        if let Some(value) = config.get(key) {
+            column.set_extra_attribute(key, value.to_json_value());
+        }
+    }
+    let value = column.mock_value_with_seed(generate_seed(field));
+    (!value.is_null()).then_some(value)
+}
+
+/// Returns the column type name for the schema type.
+fn type_name(schema_type: &str, format: Option<&str>) -> &'static str {
+    match (schema_type, format) {
+        ("boolean", _) => "bool",
+        ("integer", Some("int32")) => "i32",
+        ("integer", Some("uint32")) => "u32",
+        ("integer", Some("uint64")) => "u64",
+        ("integer", _) => "i64",
+        ("number", Some("float")) => "f32",
+        ("number", _) => "f64",
+        ("string", Some("uuid")) => "Uuid",
+        ("string", Some("ulid")) => "Ulid",
+        ("string", Some("date")) => "Date",
+        ("string", Some("time")) => "Time",
+        ("string", Some("date-time")) => "DateTime",
+        _ => "String",
+    }
+}
+
+/// Returns the column type name for the array items.
+fn array_type_name(items: &str) -> &'static str {
+    match items {
+        "integer" => "Vec<i64>",
+        "uuid" => "Vec<Uuid>",
+        _ => "Vec<String>",
+    }
+}
+
+/// Generates a seed with the FNV-1a hash of the key,
+/// which is stable across builds and platforms.
+fn generate_seed(key: &str) -> u64 {
+    key.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// A flag to enable the examples generated from the mock data.
+static EXAMPLES_ENABLED: LazyLock<bool> = LazyLock::new(|| {
+    Agent::config()
+        .get_table("openapi")
+        .and_then(|config| config.get_bool("examples"))
+        .unwrap_or(true)
+});
@@ -28,6 +28,7 @@ use zino_core::{
     LazyLock, Uuid,
 };
 
+mod example;
 mod model;
 mod parser;
 
@@ -252,16 +253,48 @@ static OPENAPI_PATHS: LazyLock<BTreeMap<String, PathItem>> = LazyLock::new(|| {
             let mut components_builder = ComponentsBuilder::new();
             let files = entries
                 .filter_map(|entry| entry.ok())
-                .filter(|entry| entry.file_type().is_ok_and(|f| f.is_file()));
-            for file in files {
-                let openapi_file = file.path();
-                let openapi_config = fs::read_to_string(&openapi_file)
-                    .unwrap_or_else(|err| {
-                        let openapi_file = openapi_file.display();
-                        panic!("fail to read the OpenAPI file `{openapi_file}`: {err}");
-                    })
-                    .parse::<Table>()
-                    .expect("fail to parse the OpenAPI file as a TOML table");
+                .filter(|entry| entry.file_type().is_ok_and(|f| f.is_file()))
+                .map(|file| {
+                    let openapi_file = file.path();
+                    let openapi_config = fs::read_to_string(&openapi_file)
+                        .unwrap_or_else(|err| {
+                            let openapi_file = openapi_file.display();
+                            panic!("fail to read the OpenAPI file `{openapi_file}`: {err}");
+                        })
+                        .parse::<Table>()
+                        .expect("fail to parse the OpenAPI file as a TOML table");
+                    (file, openapi_config)
+                })
+                .collect::<Vec<_>>();
+
+            // Schemas in all the files are collected first for the examples of the endpoints
+            let mut schema_configs = HashMap::new();
+            let mut model_schemas = HashMap::new();
+            for (_, openapi_config) in files.iter() {
+                if let Some(schemas) = openapi_config.get_table("schemas") {
+                    for (key, value) in schemas.iter() {
+                        if let Some(config) = value.as_table() {
+                            schema_configs.insert(key.to_case(Case::Camel), config.clone());
+                        }
+                    }
+                }
+                if let Some(endpoints) = openapi_config.get_array("endpoints") {
+                    for endpoint in endpoints.iter().filter_map(|v| v.as_table()) {
+                        let path = endpoint.get_str("path").unwrap_or("/");
+                        let schema = endpoint
+                            .get_table("body")
+                            .and_then(|body| body.get_str("schema"));
+                        if let (Some(model_name), Some(schema)) =
+                            (path.strip_suffix("/new"), schema)
+                        {
+                            let model_name = model_name.trim_start_matches('/').to_owned();
+                            model_schemas.insert(model_name, schema.to_case(Case::Camel));
+                        }
+                    }
+                }
+            }
+
+            for (file, openapi_config) in files {
                 if file.file_name() == "OPENAPI.toml" {
                     if let Some(info_config) = openapi_config.get_table("info") {
                         if OPENAPI_INFO.set(info_config.clone()).is_err() {
@@ -326,8 +359,15 @@ static OPENAPI_PATHS: LazyLock<BTreeMap<String, PathItem>> = LazyLock::new(|| {
                             .unwrap_or_default()
                             .to_ascii_uppercase();
                         let http_method = parser::parse_http_method(&method);
-                        let operation =
+                        let mut operation =
                             parser::parse_operation(&name, path, endpoint, ignore_securities);
+                        example::attach_operation_examples(
+                            &mut operation,
+                            path,
+                            endpoint,
+                            &schema_configs,
+                            &model_schemas,
+                        );
                         let path_item = PathItem::new(http_method, operation);
                         if let Some(item) = paths.get_mut(path) {
                             item.merge_operations(path_item);
@@ -340,7 +380,8 @@ static OPENAPI_PATHS: LazyLock<BTreeMap<String, PathItem>> = LazyLock::new(|| {
                     for (key, value) in schemas.iter() {
                         if let Some(config) = value.as_table() {
                             let name = key.to_case(Case::Camel);
-                            let schema = parser::parse_schema(config);
+                            let mut schema = parser::parse_schema(config);
+                            example::attach_schema_example(&mut schema, &name, config);
                             components_builder = components_builder.schema(name, schema);
                         }
                     }
@@ -3,6 +3,7 @@ use std::collections::BTreeMap;
 use toml::Table;
 use utoipa::openapi::{
     content::{Content, ContentBuilder},
+    extensions::ExtensionsBuilder,
     external_docs::ExternalDocs,
     header::Header,
     path::{HttpMethod, Operation, OperationBuilder, Parameter, ParameterBuilder, ParameterIn},
@@ -21,8 +22,9 @@ use utoipa::openapi::{
     Deprecated, RefOr, Required,
 };
 use zino_core::{
+    application::{Agent, Application},
     extension::{TomlTableExt, TomlValueExt},
-    TomlValue,
+    LazyLock, TomlValue,
 };
 
 /// Parses the tag.
@@ -136,9 +138,42 @@ pub(super) fn parse_operation(
         let request_body = parse_request_body(body);
         operation_builder = operation_builder.request_body(Some(request_body));
     }
+    let required_scopes = config
+        .get_str_array("scopes")
+        .map(|scopes| scopes.into_iter().map(|s| s.to_owned()).collect::<Vec<_>>())
+        .or_else(|| infer_model_scope(path).map(|scope| vec![scope]));
+    if let Some(scopes) = required_scopes.filter(|scopes| !scopes.is_empty()) {
+        let extensions = ExtensionsBuilder::new()
+            .add("x-required-scopes", scopes)
+            .build();
+        operation_builder = operation_builder.extensions(Some(extensions));
+    }
     operation_builder.build()
 }
 
+/// Infers the required scope for the route of the default controller,
+/// such as `tag:read` for `/tag/{tag_id}/view`,
+/// if the model scopes are enabled by `[auth.scopes] model-scopes`.
+fn infer_model_scope(path: &str) -> Option<String> {
+    if !*MODEL_SCOPES_ENABLED {
+        return None;
+    }
+
+    let mut segments = path
+        .trim_matches('/')
+        .rsplit('/')
+        .filter(|s| !s.starts_with('{'));
+    let access = match segments.next()? {
+        "new" | "delete" | "update" | "soft_delete" | "lock" | "archive" | "batch_insert"
+        | "batch_delete" | "batch_update" | "import" => "write",
+        "view" | "list" | "fetch" | "export" | "create_export_job" | "tree" | "stats"
+        | "changes" => "read",
+        _ => return None,
+    };
+    let model_name = segments.next()?;
+    Some(format!("{model_name}:{access}"))
+}
+
 /// Parses the response.
 pub(super) fn parse_response(config: &Table) -> Response {
     let mut response_builder = ResponseBuilder::new();
@@ -265,7 +300,7 @@ pub(super) fn parse_schema(config: &Table) -> Schema {
                     _ => (),
                 },
                 TomlValue::Boolean(value) => match key.as_str() {
-                    "write_only" => {
+                    "write_only" | "private" => {
                         object_builder = object_builder.write_only(Some(*value));
                     }
                     "read_only" => {
@@ -768,3 +803,13 @@ pub(super) fn parse_external_docs(config: &Table) -> ExternalDocs {
         ExternalDocs::default()
     }
 }
+
+/// Flag to indicate whether the required scopes of the models are checked
+/// for the default controller.
+static MODEL_SCOPES_ENABLED: LazyLock<bool> = LazyLock::new(|| {
+    Agent::config()
+        .get_table("auth")
+        .and_then(|config| config.get_table("scopes"))
+        .and_then(|config| config.get_bool("model-scopes"))
+        .unwrap_or_default()
+});
@@ -0,0 +1,83 @@
+use sqlx::postgres::{PgPoolCopyExt, PgPoolOptions};
+use std::fmt::Write;
+use zino_orm::{ConnectionPool, Executor};
+
+/// Number of rows loaded in each iteration.
+const NUM_ROWS: usize = 10_000;
+
+/// Compares the multi-row `INSERT` built by `insert_many()` with the `COPY` used by `copy_in()`.
+/// It runs against the PostgreSQL database specified by `ZINO_BENCH_POSTGRES_URL`.
+pub fn bench(c: &mut criterion::Criterion) {
+    let Ok(database_url) = std::env::var("ZINO_BENCH_POSTGRES_URL") else {
+        eprintln!("skip the `copy_in` benchmark because `ZINO_BENCH_POSTGRES_URL` is not set");
+        return;
+    };
+    let runtime = tokio::runtime::Runtime::new().expect("fail to build the runtime");
+    let cp = runtime.block_on(async {
+        let pool = PgPoolOptions::new()
+            .max_connections(1)
+            .connect(&database_url)
+            .await
+            .expect("fail to connect to the database");
+        let cp = ConnectionPool::new("bench", "bench", pool);
+        cp.execute(
+            "CREATE TABLE IF NOT EXISTS zino_bench_users (
+                id BIGINT PRIMARY KEY,
+                name TEXT NOT NULL,
+                tags TEXT[] NOT NULL,
+                balance NUMERIC NOT NULL,
+                extra JSONB,
+                created_at TIMESTAMPTZ NOT NULL
+            )",
+        )
+        .await
+        .expect("fail to create the table");
+        cp
+    });
+
+    let mut values = Vec::with_capacity(NUM_ROWS);
+    let mut csv = String::new();
+    for id in 0..NUM_ROWS {
+        values.push(format!(
+            "({id}, 'user-{id}', ARRAY['a','b'], {id}.25, '{{\"id\":{id}}}', \
+                '2025-01-01 00:00:00+00')"
+        ));
+        let _ = writeln!(
+            csv,
+            "{id},user-{id},\"{{\"\"a\"\",\"\"b\"\"}}\",{id}.25,\
+                \"{{\"\"id\"\":{id}}}\",2025-01-01 00:00:00+00"
+        );
+    }
+    let insert_sql = format!(
+        "INSERT INTO zino_bench_users (id, name, tags, balance, extra, created_at) VALUES {};",
+        values.join(", ")
+    );
+    let copy_sql = "COPY zino_bench_users (id, name, tags, balance, extra, created_at) \
+        FROM STDIN WITH (FORMAT csv)";
+
+    let mut group = c.benchmark_group("bulk_load");
+    group.sample_size(10);
+    group.bench_function("insert_many", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                cp.execute("TRUNCATE zino_bench_users").await.unwrap();
+                cp.execute(&insert_sql).await.unwrap()
+            })
+        })
+    });
+    group.bench_function("copy_in", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                cp.execute("TRUNCATE zino_bench_users").await.unwrap();
+                let mut copy = cp.pool().copy_in_raw(copy_sql).await.unwrap();
+                copy.send(csv.as_bytes()).await.unwrap();
+                copy.finish().await.unwrap()
+            })
+        })
+    });
+    group.finish();
+
+    runtime.block_on(async {
+        cp.execute("DROP TABLE zino_bench_users").await.ok();
+    });
+}
@@ -0,0 +1,4 @@
+mod copy_in;
+
+criterion::criterion_group!(benches, copy_in::bench);
+criterion::criterion_main!(benches);
@@ -1,13 +1,13 @@
-use super::{IntoSqlValue, ModelHelper, Schema};
+use super::{search, upsert, IntoSqlValue, ModelHelper, Schema, UpsertOutcome};
 use std::fmt::Display;
 use zino_core::{
     bail,
     datetime::DateTime,
     error::Error,
     extension::{JsonObjectExt, JsonValueExt},
-    model::{ModelHooks, Mutation, Query},
+    model::{ModelHooks, Mutation, Preconditions, Query, QueryOrder},
     validation::Validation,
-    warn, JsonValue, Map,
+    warn, Decimal, JsonValue, Map,
 };
 
 /// Access model fields.
@@ -97,6 +97,40 @@ where
         snapshot
     }
 
+    /// Returns the names of the fields whose values differ from the other instance.
+    /// The fields designated by `#[schema(ignore_diff)]` are excluded.
+    ///
+    /// The derived implementation compares the typed values, so that the decimals
+    /// such as `0.50` and `0.5` are considered equal.
+    #[inline]
+    fn changed_fields(&self, other: &Self) -> Vec<&'static str> {
+        diff_json_fields(self, other, Self::fields())
+    }
+
+    /// Returns the names of the fields whose values differ from the JSON object
+    /// of a previous snapshot, such as the model fetched from the database.
+    /// The fields designated by `#[schema(ignore_diff)]` are excluded.
+    #[inline]
+    fn changed_fields_since(&self, snapshot: &Map) -> Vec<&'static str> {
+        diff_json_map(snapshot, self, Self::fields())
+    }
+
+    /// Returns the fields whose values differ from the other instance,
+    /// with the values taken from `other`.
+    fn diff(&self, other: &Self) -> Map {
+        let fields = self.changed_fields(other);
+        if fields.is_empty() {
+            return Map::new();
+        }
+        match serde_json::to_value(other) {
+            Ok(JsonValue::Object(mut map)) => {
+                map.retain(|key, _| fields.contains(&key.as_str()));
+                map
+            }
+            _ => Map::new(),
+        }
+    }
+
     /// Returns `true` if the `name` is nonempty.
     #[inline]
     fn has_name(&self) -> bool {
@@ -337,10 +371,70 @@ where
         query.allow_fields(Self::fields());
         query.deny_fields(&ignored_fields);
         query.add_filter("status", Map::from_entry("$ne", "Deleted"));
+        if let Some(col) = Self::expiry_column() {
+            query.add_filter(col.name(), Map::from_entry("$gt", "now"));
+        }
         query.order_desc("updated_at");
         query
     }
 
+    /// Applies the full-text search of the term to the `Query`.
+    ///
+    /// The rank of relevance is selected as the `search_score` field, and it takes precedence
+    /// over the other sort orders. It has no effects if the model has no fulltext columns.
+    fn apply_search(query: &mut Query, term: &str) {
+        let Some(score) = search::format_search_score::<Self>(term) else {
+            return;
+        };
+        if query.fields().is_empty() {
+            query.allow_fields(Self::fields());
+            query.deny_fields(Self::write_only_fields());
+        }
+        query.add_field_alias(score, search::SEARCH_SCORE_FIELD);
+        query.add_filter("$search", term);
+
+        let mut sort_order = vec![QueryOrder::new(search::SEARCH_SCORE_FIELD, true)];
+        sort_order.extend(
+            query
+                .sort_order()
+                .iter()
+                .filter(|order| order.field() != search::SEARCH_SCORE_FIELD)
+                .cloned(),
+        );
+        query.set_order(sort_order);
+    }
+
+    /// Validates the sort order of the `Query` and appends the primary key
+    /// as a tiebreaker so that the pagination is stable.
+    ///
+    /// The sort fields should be the columns of the model, which can be qualified
+    /// with the model name. Unknown fields are recorded as the failures of `sort`.
+    fn validate_sort_order(query: &mut Query) -> Validation {
+        let mut validation = Validation::new();
+        for order in query.sort_order() {
+            let field = order.field();
+            if !Self::has_column(field) {
+                let message = format!("sort field `{field}` is not a column of the model");
+                validation.record("sort", message);
+            }
+        }
+        if validation.is_success() {
+            query.order_tiebreaker(Self::PRIMARY_KEY_NAME);
+        }
+        validation
+    }
+
+    /// Adapts the model data in the response for an older API version,
+    /// such as renaming, removing or reshaping the fields.
+    ///
+    /// It is called for each model in the default controller handlers
+    /// and also for the properties in the model definition,
+    /// when the API version of the request is specified.
+    #[inline]
+    fn adapt_response(version: u32, data: &mut Map) {
+        let _ = (version, data);
+    }
+
     /// Checks the constraints for the model.
     async fn check_constraints(&self) -> Result<Validation, Error> {
         let mut validation = Validation::new();
@@ -361,6 +455,15 @@ where
         Ok(models)
     }
 
+    /// Searches the models by the full-text search term.
+    /// The search predicate is merged with the filters of the `Query`,
+    /// and the models are sorted by the rank of relevance by default.
+    async fn search(term: &str, query: &Query) -> Result<Vec<Map>, Error> {
+        let mut query = query.clone();
+        Self::apply_search(&mut query, term);
+        Self::fetch(&query).await
+    }
+
     /// Fetches the data of a model seleted by the primary key.
     async fn fetch_by_id(id: &K) -> Result<Map, Error> {
         let mut model = Self::find_by_id::<Map>(id)
@@ -372,17 +475,63 @@ where
     }
 
     /// Deletes a model of the primary key by setting the status as `Deleted`.
+    #[inline]
     async fn soft_delete_by_id(id: &K) -> Result<(), Error> {
+        Self::soft_delete_by_id_if(id, &Preconditions::default()).await
+    }
+
+    /// Deletes a model of the primary key by setting the status as `Deleted`
+    /// if the preconditions are satisfied.
+    async fn soft_delete_by_id_if(id: &K, preconditions: &Preconditions) -> Result<(), Error> {
         let mut model = Self::try_get_model(id).await?;
         let model_data = model.before_soft_delete().await?;
 
-        let query = model.current_version_query();
+        let mut query = model.current_version_query();
+        preconditions.apply_to(&mut query);
+
         let mut mutation = model.soft_delete_mutation();
         let ctx = Self::update_one(&query, &mut mutation).await?;
+        if !preconditions.is_empty() && ctx.rows_affected() != Some(1) {
+            bail!(
+                "412 Precondition Failed: the model `{}` has been modified",
+                id
+            );
+        }
+        #[cfg(feature = "webhook")]
+        super::webhook::emit_model_event("soft_delete", &model).await;
+        #[cfg(feature = "accessor")]
+        super::attachment::remove_model_attachments(&model).await;
         Self::after_soft_delete(&ctx, model_data).await?;
         Ok(())
     }
 
+    /// Deletes a model of the primary key permanently if the preconditions are satisfied.
+    async fn delete_by_id_if(id: &K, preconditions: &Preconditions) -> Result<(), Error> {
+        let mut model = Self::try_get_model(id).await?;
+        let model_data = model.before_delete().await?;
+
+        let mut query = model.current_version_query();
+        preconditions.apply_to(&mut query);
+
+        let ctx = Self::delete_one(&query).await?;
+        if ctx.rows_affected() != Some(1) {
+            if preconditions.is_empty() {
+                bail!("404 Not Found: cannot find the model `{}`", id);
+            } else {
+                bail!(
+                    "412 Precondition Failed: the model `{}` has been modified",
+                    id
+                );
+            }
+        }
+        #[cfg(feature = "webhook")]
+        super::webhook::emit_model_event("delete", &model).await;
+        #[cfg(feature = "accessor")]
+        super::attachment::remove_model_attachments(&model).await;
+        model.after_delete(&ctx, model_data).await?;
+        Ok(())
+    }
+
     /// Locks a model of the primary key by setting the status as `Locked`.
     async fn lock_by_id(id: &K) -> Result<(), Error> {
         let mut model = Self::try_get_model(id).await?;
@@ -391,6 +540,8 @@ where
         let query = model.current_version_query();
         let mut mutation = model.lock_mutation();
         let ctx = Self::update_one(&query, &mut mutation).await?;
+        #[cfg(feature = "webhook")]
+        super::webhook::emit_model_event("lock", &model).await;
         Self::after_lock(&ctx, model_data).await?;
         Ok(())
     }
@@ -403,19 +554,40 @@ where
         let query = model.current_version_query();
         let mut mutation = model.archive_mutation();
         let ctx = Self::update_one(&query, &mut mutation).await?;
+        #[cfg(feature = "webhook")]
+        super::webhook::emit_model_event("archive", &model).await;
         Self::after_archive(&ctx, model_data).await?;
         Ok(())
     }
 
     /// Mutates a model of the primary key with the JSON data and the optional extension.
+    #[inline]
     async fn mutate_by_id(
         id: &K,
         data: &mut Map,
         extension: Option<<Self as ModelHooks>::Extension>,
+    ) -> Result<(Validation, Self), Error> {
+        Self::mutate_by_id_if(id, data, extension, &Preconditions::default()).await
+    }
+
+    /// Mutates a model of the primary key with the JSON data and the optional extension
+    /// if the preconditions are satisfied.
+    ///
+    /// The preconditions are checked in the `WHERE` clause of the update statement,
+    /// thus a concurrent modification results in a `412 Precondition Failed` error.
+    async fn mutate_by_id_if(
+        id: &K,
+        data: &mut Map,
+        extension: Option<<Self as ModelHooks>::Extension>,
+        preconditions: &Preconditions,
     ) -> Result<(Validation, Self), Error> {
         Self::before_extract().await?;
 
         let mut model = Self::try_get_model(id).await?;
+        let current = match serde_json::to_value(&model) {
+            Ok(JsonValue::Object(map)) => map,
+            _ => bail!("fail to convert the model `{}` to a json object", id),
+        };
         let version = model.version();
         if data.get_u64("version").is_some_and(|v| version != v) {
             bail!(
@@ -437,6 +609,12 @@ where
         if !validation.is_success() {
             return Ok((validation, model));
         }
+
+        // Only the changed columns are updated instead of rewriting every column.
+        let changed_fields = model.changed_fields_since(&current);
+        data.retain(|key, _value| {
+            key.starts_with('$') || !Self::has_column(key) || changed_fields.contains(&key.as_str())
+        });
         if model.is_deleted() {
             data.retain(|key, _value| key == "status");
         } else if model.is_locked() {
@@ -446,22 +624,132 @@ where
         }
         model.after_validation(data).await?;
 
-        let query = model.current_version_query();
-        let mut mutation = model.next_version_mutation(data);
+        let mut query = model.current_version_query();
+        preconditions.apply_to(&mut query);
 
+        let mut mutation = model.next_version_mutation(data);
         let model_data = model.before_update().await?;
         let ctx = Self::update_one(&query, &mut mutation).await?;
         if ctx.rows_affected() != Some(1) {
+            if !preconditions.is_empty() {
+                bail!(
+                    "412 Precondition Failed: the model `{}` has been modified",
+                    id
+                );
+            }
             bail!(
                 "404 Not Found: there is no version `{}` for the model `{}`",
                 version,
                 id,
             );
         }
+        #[cfg(feature = "webhook")]
+        super::webhook::emit_model_event("update", &model).await;
         Self::after_update(&ctx, model_data).await?;
         Ok((validation, model))
     }
 
+    /// Applies the mutation to a model of the primary key without fetching the model first,
+    /// and returns the number of rows affected.
+    ///
+    /// It is designed for the atomic updates built by [`MutationBuilder`](crate::MutationBuilder),
+    /// thus zero rows will be affected if the guards of the mutation are not satisfied.
+    async fn apply_mutation_by_id(id: &K, mutation: &mut Mutation) -> Result<u64, Error> {
+        let query = Query::from_entry(Self::PRIMARY_KEY_NAME, id.to_string());
+        let ctx = Self::update_one(&query, mutation).await?;
+        Ok(ctx.rows_affected().unwrap_or_default())
+    }
+
+    /// Updates or inserts a model keyed by a unique column instead of the primary key.
+    /// On conflict, all the writable columns are updated except for the primary key,
+    /// the conflict target and `created_at`.
+    ///
+    /// It fails fast if the column does not have a unique index.
+    async fn upsert_by(column: &str, model: Self) -> Result<UpsertOutcome, Error> {
+        let mut outcomes = Self::upsert_many_by(column, vec![model]).await?;
+        outcomes
+            .pop()
+            .ok_or_else(|| warn!("fail to upsert the model `{}`", Self::model_name()))
+    }
+
+    /// Updates or inserts many models keyed by a unique column instead of the primary key,
+    /// and returns the outcomes in the same order as the models.
+    /// See [`upsert_by()`](Self::upsert_by) for the details.
+    async fn upsert_many_by(column: &str, models: Vec<Self>) -> Result<Vec<UpsertOutcome>, Error> {
+        upsert::check_conflict_target::<Self>(column)?;
+
+        let mut maps = Vec::with_capacity(models.len());
+        let mut model_data = Vec::with_capacity(models.len());
+        for mut model in models {
+            model_data.push(model.before_upsert().await?);
+            maps.push(model.into_map());
+        }
+
+        let (ctx, outcomes) = upsert::upsert_by::<Self>(maps, column).await?;
+        if ctx.is_cancelled() {
+            return Ok(outcomes);
+        }
+        Self::after_scan(&ctx).await?;
+        for data in model_data {
+            Self::after_upsert(&ctx, data).await?;
+        }
+        Ok(outcomes)
+    }
+
+    /// Updates the expiration time of a model for the sliding expiration.
+    async fn touch_expiry(id: &K, expires_at: DateTime) -> Result<(), Error> {
+        let Some(col) = Self::expiry_column() else {
+            bail!(
+                "the model `{}` does not have an expiry column",
+                Self::model_name()
+            );
+        };
+
+        let query = Query::from_entry(Self::PRIMARY_KEY_NAME, id.to_string());
+        let mut mutation = Mutation::from_entry(col.name(), expires_at.into_sql_value());
+        let ctx = Self::update_one(&query, &mut mutation).await?;
+        if ctx.rows_affected() != Some(1) {
+            bail!("404 Not Found: cannot find the model `{}`", id);
+        }
+        Ok(())
+    }
+
+    /// Attaches the file to an attachment field of the model, and returns the attachment.
+    ///
+    /// The file is written through the storage accessor and the column is updated
+    /// with the optimistic lock of the version. For a single-valued field,
+    /// the previous file is removed after the update.
+    #[cfg(feature = "accessor")]
+    #[inline]
+    async fn attach(
+        id: &K,
+        field: &str,
+        file: zino_storage::NamedFile,
+    ) -> Result<super::Attachment, Error> {
+        super::attachment::attach::<Self, K>(id, field, file).await
+    }
+
+    /// Detaches the file of the path from an attachment field of the model,
+    /// and returns `true` if it has been removed.
+    #[cfg(feature = "accessor")]
+    #[inline]
+    async fn detach(id: &K, field: &str, path: &str) -> Result<bool, Error> {
+        super::attachment::detach::<Self, K>(id, field, path).await
+    }
+
+    /// Returns a download URL for an attachment field of the model,
+    /// which is presigned or proxied and expires after the TTL.
+    /// For a multi-valued field, the latest attachment is used.
+    #[cfg(feature = "accessor")]
+    #[inline]
+    async fn attachment_url(
+        id: &K,
+        field: &str,
+        ttl: std::time::Duration,
+    ) -> Result<String, Error> {
+        super::attachment::attachment_url::<Self, K>(id, field, ttl).await
+    }
+
     /// Generates random associations for the model.
     async fn random_associations() -> Result<Map, Error> {
         let mut associations = Map::new();
@@ -511,3 +799,93 @@ where
         Ok((validation, model))
     }
 }
+
+/// Returns the names of the fields whose JSON values differ,
+/// excluding the fields designated by `#[schema(ignore_diff)]`.
+/// Numbers and decimal strings are compared by values.
+#[doc(hidden)]
+pub fn diff_json_fields<M: Schema>(
+    current: &M,
+    updated: &M,
+    fields: &[&'static str],
+) -> Vec<&'static str> {
+    match serde_json::to_value(current) {
+        Ok(JsonValue::Object(current)) => diff_json_map(&current, updated, fields),
+        _ => fields.to_vec(),
+    }
+}
+
+/// Returns the names of the fields whose JSON values differ from the current ones,
+/// excluding the fields designated by `#[schema(ignore_diff)]`.
+fn diff_json_map<M: Schema>(
+    current: &Map,
+    updated: &M,
+    fields: &[&'static str],
+) -> Vec<&'static str> {
+    let Ok(JsonValue::Object(updated)) = serde_json::to_value(updated) else {
+        return fields.to_vec();
+    };
+    fields
+        .iter()
+        .filter(|&&field| {
+            let column = M::get_column(field);
+            if column.is_some_and(|col| col.extra().contains_key("ignore_diff")) {
+                return false;
+            }
+
+            let is_decimal = column.is_some_and(|col| col.type_name() == "Decimal");
+            is_json_value_changed(current.get(field), updated.get(field), is_decimal)
+        })
+        .copied()
+        .collect()
+}
+
+/// Returns `true` if the JSON values differ.
+/// Numbers and decimal strings are compared by values.
+fn is_json_value_changed(
+    current: Option<&JsonValue>,
+    updated: Option<&JsonValue>,
+    is_decimal: bool,
+) -> bool {
+    match (current, updated) {
+        (Some(JsonValue::Number(a)), Some(JsonValue::Number(b))) if a != b => {
+            match (a.as_f64(), b.as_f64()) {
+                (Some(a), Some(b)) => a != b,
+                _ => true,
+            }
+        }
+        (Some(JsonValue::String(a)), Some(JsonValue::String(b))) if is_decimal && a != b => {
+            match (a.parse::<Decimal>(), b.parse::<Decimal>()) {
+                (Ok(a), Ok(b)) => a != b,
+                _ => true,
+            }
+        }
+        (a, b) => a != b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_json_value_changed;
+    use serde_json::json;
+
+    #[test]
+    fn it_compares_json_values() {
+        let (a, b) = (json!(0.50), json!(0.5));
+        assert!(!is_json_value_changed(Some(&a), Some(&b), false));
+
+        let (a, b) = (json!(1), json!(1.0));
+        assert!(!is_json_value_changed(Some(&a), Some(&b), false));
+
+        let (a, b) = (json!("0.50"), json!("0.5"));
+        assert!(!is_json_value_changed(Some(&a), Some(&b), true));
+        assert!(is_json_value_changed(Some(&a), Some(&b), false));
+
+        let (a, b) = (json!("0.50"), json!("0.51"));
+        assert!(is_json_value_changed(Some(&a), Some(&b), true));
+
+        let a = json!("alice");
+        assert!(is_json_value_changed(Some(&a), None, false));
+        assert!(!is_json_value_changed(None, None, false));
+    }
+}
@@ -146,3 +146,84 @@ impl<E: Entity> Aggregation<E> {
         }
     }
 }
+
+/// Units of the date truncation for grouping rows by a datetime column.
+///
+/// The truncated value is formatted as a string such as `2025-03-17` for `Day` and `Week`,
+/// `2025-03` for `Month`, `2025` for `Year` and `2025-03-17 08:00` for `Hour`.
+/// A week starts on Monday. The values are computed in the session time zone
+/// for MySQL and PostgreSQL, and shifted by the `time-zone` of the `[database]` config
+/// for SQLite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DateTrunc {
+    /// Truncates to the hour.
+    Hour,
+    /// Truncates to the day.
+    Day,
+    /// Truncates to the week.
+    Week,
+    /// Truncates to the month.
+    Month,
+    /// Truncates to the year.
+    Year,
+}
+
+impl DateTrunc {
+    /// Parses the unit from a string.
+    pub fn parse(unit: &str) -> Option<Self> {
+        match unit {
+            "hour" => Some(DateTrunc::Hour),
+            "day" => Some(DateTrunc::Day),
+            "week" => Some(DateTrunc::Week),
+            "month" => Some(DateTrunc::Month),
+            "year" => Some(DateTrunc::Year),
+            _ => None,
+        }
+    }
+
+    /// Returns the SQL expression to truncate the field.
+    pub fn expr(&self, field: &str) -> String {
+        if cfg!(any(
+            feature = "orm-mariadb",
+            feature = "orm-mysql",
+            feature = "orm-tidb"
+        )) {
+            match self {
+                DateTrunc::Hour => format!("DATE_FORMAT({field}, '%Y-%m-%d %H:00')"),
+                DateTrunc::Day => format!("DATE_FORMAT({field}, '%Y-%m-%d')"),
+                DateTrunc::Week => {
+                    format!(
+                        "DATE_FORMAT(DATE_SUB({field}, INTERVAL WEEKDAY({field}) DAY), '%Y-%m-%d')"
+                    )
+                }
+                DateTrunc::Month => format!("DATE_FORMAT({field}, '%Y-%m')"),
+                DateTrunc::Year => format!("DATE_FORMAT({field}, '%Y')"),
+            }
+        } else if cfg!(feature = "orm-postgres") {
+            let (unit, format) = match self {
+                DateTrunc::Hour => ("hour", "YYYY-MM-DD HH24:00"),
+                DateTrunc::Day => ("day", "YYYY-MM-DD"),
+                DateTrunc::Week => ("week", "YYYY-MM-DD"),
+                DateTrunc::Month => ("month", "YYYY-MM"),
+                DateTrunc::Year => ("year", "YYYY"),
+            };
+            format!("to_char(date_trunc('{unit}', {field}), '{format}')")
+        } else {
+            let modifier = match super::TIME_ZONE.get().copied() {
+                Some("local" | "localtime") => ", 'localtime'".to_owned(),
+                Some(tz) if tz.starts_with(['+', '-']) => format!(", '{tz}'"),
+                _ => String::new(),
+            };
+            match self {
+                DateTrunc::Hour => format!("strftime('%Y-%m-%d %H:00', {field}{modifier})"),
+                DateTrunc::Day => format!("strftime('%Y-%m-%d', {field}{modifier})"),
+                DateTrunc::Week => {
+                    format!("strftime('%Y-%m-%d', {field}{modifier}, '-6 days', 'weekday 1')")
+                }
+                DateTrunc::Month => format!("strftime('%Y-%m', {field}{modifier})"),
+                DateTrunc::Year => format!("strftime('%Y', {field}{modifier})"),
+            }
+        }
+    }
+}
@@ -0,0 +1,489 @@
+use super::{helper, ModelAccessor, Schema};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, mem, time::Duration};
+use zino_core::{
+    bail, crypto,
+    datetime::DateTime,
+    encoding::hex,
+    error::Error,
+    extension::{JsonObjectExt, TomlTableExt},
+    model::Column,
+    state::State,
+    warn, JsonValue, LazyLock, Map,
+};
+use zino_storage::{GlobalAccessor, NamedFile};
+
+/// A file attached to a model, which is stored in a JSON column.
+///
+/// The bytes are written through the [`GlobalAccessor`], and only the metadata
+/// is stored in the database. A field of the type `Attachment`, `Option<Attachment>`
+/// or `Vec<Attachment>` is mapped to a JSON column by the derives, and it should be
+/// written by [`ModelAccessor::attach()`] instead of the request data.
+/// The size and the content type can be restricted by the `max_size` and `content_types`
+/// attributes, and the storage accessor can be overridden by the `accessor` attribute.
+///
+/// The default settings can be configured in the `[attachment]` table:
+///
+/// ```toml
+/// [attachment]
+/// accessor = "local"
+/// path-template = "attachments/{model}/{id}/{field}/{checksum}/{file_name}"
+/// proxy-url = "/attachments"
+/// ```
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino_orm::{Attachment, ModelAccessor};
+///
+/// #[derive(Debug, Clone, Default, Serialize, Deserialize, Schema, ModelAccessor)]
+/// pub struct User {
+///     #[schema(primary_key)]
+///     id: Uuid,
+///     #[schema(max_size = 1048576, content_types = "image/png, image/jpeg")]
+///     avatar: Option<Attachment>,
+///     #[schema(accessor = "s3", max_size = 10485760)]
+///     receipts: Vec<Attachment>,
+/// }
+///
+/// let avatar = User::attach(&user_id, "avatar", file).await?;
+/// let url = User::attachment_url(&user_id, "avatar", Duration::from_secs(3600)).await?;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Attachment {
+    /// File name.
+    file_name: String,
+    /// Content type.
+    content_type: String,
+    /// File size.
+    file_size: u64,
+    /// Name of the storage accessor.
+    accessor: String,
+    /// Path in the storage service.
+    path: String,
+    /// Content address of the file.
+    content_address: String,
+    /// Upload time.
+    uploaded_at: DateTime,
+}
+
+impl Attachment {
+    /// Returns the file name.
+    #[inline]
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    /// Returns the content type.
+    #[inline]
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    /// Returns the file size.
+    #[inline]
+    pub fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    /// Returns the name of the storage accessor.
+    #[inline]
+    pub fn accessor(&self) -> &str {
+        &self.accessor
+    }
+
+    /// Returns the path in the storage service.
+    #[inline]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the content address of the file,
+    /// such as `sha1:2fd4e1c67a2d28fced849ee1bb76e7391b93eb12`.
+    #[inline]
+    pub fn content_address(&self) -> &str {
+        &self.content_address
+    }
+
+    /// Returns the upload time.
+    #[inline]
+    pub fn uploaded_at(&self) -> DateTime {
+        self.uploaded_at
+    }
+
+    /// Reads the file from the storage service.
+    pub async fn read(&self) -> Result<NamedFile, Error> {
+        let operator = GlobalAccessor::get(&self.accessor)
+            .ok_or_else(|| warn!("storage accessor `{}` is unavailable", self.accessor))?;
+        let buffer = operator.read(&self.path).await?;
+        let mut file = NamedFile::new(&self.file_name);
+        file.set_bytes(buffer.to_bytes());
+        if let Ok(content_type) = self.content_type.parse() {
+            file.set_content_type(content_type);
+        }
+        Ok(file)
+    }
+
+    /// Removes the file from the storage service.
+    pub async fn remove(&self) -> Result<(), Error> {
+        let operator = GlobalAccessor::get(&self.accessor)
+            .ok_or_else(|| warn!("storage accessor `{}` is unavailable", self.accessor))?;
+        operator.delete(&self.path).await?;
+        Ok(())
+    }
+
+    /// Returns a download URL which expires after the TTL.
+    ///
+    /// The URL is presigned if the storage service supports it.
+    /// Otherwise, a URL signed for the `proxy-url` is returned,
+    /// which should be checked by [`verify_proxy_url()`](Self::verify_proxy_url)
+    /// before the file is served.
+    pub async fn download_url(&self, ttl: Duration) -> Result<String, Error> {
+        let operator = GlobalAccessor::get(&self.accessor)
+            .ok_or_else(|| warn!("storage accessor `{}` is unavailable", self.accessor))?;
+        if operator.info().full_capability().presign_read {
+            let presigned = operator.presign_read(&self.path, ttl).await?;
+            return Ok(presigned.uri().to_string());
+        }
+
+        let Some(proxy_url) = ATTACHMENT_SETTINGS.proxy_url else {
+            bail!(
+                "storage accessor `{}` does not support presigning and `proxy-url` is not set",
+                self.accessor
+            );
+        };
+        let expires = DateTime::now().timestamp() + i64::try_from(ttl.as_secs())?;
+        let signature = sign_proxy_url(&self.path, expires);
+        let proxy_url = proxy_url.trim_end_matches('/');
+        Ok(format!(
+            "{proxy_url}/{}?expires={expires}&signature={signature}",
+            self.path
+        ))
+    }
+
+    /// Returns `true` if the signature of the proxied URL is valid and has not expired.
+    pub fn verify_proxy_url(path: &str, expires: i64, signature: &str) -> bool {
+        if expires < DateTime::now().timestamp() {
+            return false;
+        }
+        hex::decode(signature).is_ok_and(|signature| {
+            let message = format!("{path}:{expires}");
+            crypto::verify_signature(message.as_bytes(), &*helper::SECRET_KEY, &signature)
+        })
+    }
+}
+
+/// Attaches the file to a field of the model.
+pub(super) async fn attach<M, K>(id: &K, field: &str, file: NamedFile) -> Result<Attachment, Error>
+where
+    M: ModelAccessor<K>,
+    K: Default + Display + PartialEq,
+{
+    let col = get_attachment_column::<M>(field)?;
+    validate_file(col, &file)?;
+
+    let model = M::try_get_model(id).await?;
+    let current_value = serde_json::to_value(&model)?
+        .as_object_mut()
+        .and_then(|data| data.remove(field))
+        .unwrap_or_default();
+
+    let settings = &*ATTACHMENT_SETTINGS;
+    let accessor = col.extra().get_str("accessor").unwrap_or(settings.accessor);
+    let operator = GlobalAccessor::get(accessor)
+        .ok_or_else(|| warn!("storage accessor `{}` is unavailable", accessor))?;
+    let content_address = file.content_address_async().await?;
+    let file_name = file.file_name().unwrap_or_default();
+    let path = settings
+        .path_template
+        .replace("{model}", M::model_name())
+        .replace("{id}", &id.to_string())
+        .replace("{field}", field)
+        .replace(
+            "{checksum}",
+            content_address
+                .split_once(':')
+                .map_or(content_address.as_str(), |(_, checksum)| checksum),
+        )
+        .replace("{file_name}", &sanitize_file_name(file_name));
+    let content_type = file
+        .content_type()
+        .map(|mime| mime.essence_str().to_owned())
+        .unwrap_or_else(|| "application/octet-stream".to_owned());
+    operator
+        .write_with(&path, file.bytes())
+        .content_type(&content_type)
+        .await?;
+
+    let attachment = Attachment {
+        file_name: file_name.to_owned(),
+        content_type,
+        file_size: file.file_size(),
+        accessor: accessor.to_owned(),
+        path,
+        content_address,
+        uploaded_at: DateTime::now(),
+    };
+    let multiple = col.extra().get_bool("multiple").unwrap_or_default();
+    let mut previous_attachments = parse_attachments(current_value);
+    let is_new_path = !previous_attachments
+        .iter()
+        .any(|a| a.path == attachment.path);
+    let value = if multiple {
+        let mut attachments = mem::take(&mut previous_attachments);
+        attachments.retain(|a| a.path != attachment.path);
+        attachments.push(attachment.clone());
+        serde_json::to_value(attachments)?
+    } else {
+        serde_json::to_value(&attachment)?
+    };
+
+    // The update is guarded by the version to avoid overwriting a concurrent attachment.
+    let query = model.current_version_query();
+    let mut mutation = model.next_version_mutation(&mut Map::from_entry(field, value));
+    let ctx = M::update_one(&query, &mut mutation).await?;
+    if ctx.rows_affected() != Some(1) {
+        if is_new_path {
+            remove_attachments(&[attachment]).await;
+        }
+        bail!(
+            "409 Conflict: the model `{}` has been modified concurrently",
+            id
+        );
+    }
+
+    previous_attachments.retain(|a| a.path != attachment.path);
+    remove_attachments(&previous_attachments).await;
+    Ok(attachment)
+}
+
+/// Detaches the file of the path from a field of the model,
+/// and removes it from the storage service.
+pub(super) async fn detach<M, K>(id: &K, field: &str, path: &str) -> Result<bool, Error>
+where
+    M: ModelAccessor<K>,
+    K: Default + Display + PartialEq,
+{
+    let col = get_attachment_column::<M>(field)?;
+    let model = M::try_get_model(id).await?;
+    let current_value = serde_json::to_value(&model)?
+        .as_object_mut()
+        .and_then(|data| data.remove(field))
+        .unwrap_or_default();
+    let mut attachments = parse_attachments(current_value);
+    let Some(index) = attachments.iter().position(|a| a.path == path) else {
+        return Ok(false);
+    };
+    let attachment = attachments.remove(index);
+    let value = if col.extra().get_bool("multiple").unwrap_or_default() {
+        serde_json::to_value(attachments)?
+    } else {
+        JsonValue::Null
+    };
+
+    let query = model.current_version_query();
+    let mut mutation = model.next_version_mutation(&mut Map::from_entry(field, value));
+    let ctx = M::update_one(&query, &mut mutation).await?;
+    if ctx.rows_affected() != Some(1) {
+        bail!(
+            "409 Conflict: the model `{}` has been modified concurrently",
+            id
+        );
+    }
+    remove_attachments(&[attachment]).await;
+    Ok(true)
+}
+
+/// Returns the download URL of the attachment for a field of the model.
+/// For a multi-valued field, the latest attachment is used.
+pub(super) async fn attachment_url<M, K>(
+    id: &K,
+    field: &str,
+    ttl: Duration,
+) -> Result<String, Error>
+where
+    M: ModelAccessor<K>,
+    K: Default + Display + PartialEq,
+{
+    get_attachment_column::<M>(field)?;
+
+    let model = M::try_get_model(id).await?;
+    let current_value = serde_json::to_value(&model)?
+        .as_object_mut()
+        .and_then(|data| data.remove(field))
+        .unwrap_or_default();
+    let Some(attachment) = parse_attachments(current_value).pop() else {
+        bail!(
+            "404 Not Found: cannot find the attachment `{}` for the model `{}`",
+            field,
+            id
+        );
+    };
+    attachment.download_url(ttl).await
+}
+
+/// Removes the attachments of the model from the storage services.
+/// The failures are logged instead of being returned since the model has been changed.
+pub(super) async fn remove_model_attachments<M: Schema>(model: &M) {
+    let data = match serde_json::to_value(model) {
+        Ok(JsonValue::Object(data)) => data,
+        _ => return,
+    };
+    for col in M::columns() {
+        if col.extra().get_str("format") == Some("attachment") {
+            if let Some(value) = data.get(col.name()) {
+                remove_attachments(&parse_attachments(value.clone())).await;
+            }
+        }
+    }
+}
+
+/// Removes the attachments from the storage services.
+async fn remove_attachments(attachments: &[Attachment]) {
+    for attachment in attachments {
+        if let Err(err) = attachment.remove().await {
+            tracing::warn!(
+                accessor = attachment.accessor.as_str(),
+                path = attachment.path.as_str(),
+                "fail to remove the attachment: {err}"
+            );
+        }
+    }
+}
+
+/// Parses the attachments from the column value.
+fn parse_attachments(value: JsonValue) -> Vec<Attachment> {
+    match value {
+        JsonValue::Array(values) => values
+            .into_iter()
+            .filter_map(|value| serde_json::from_value(value).ok())
+            .collect(),
+        JsonValue::Object(_) => serde_json::from_value(value).into_iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Gets the column of an attachment field.
+fn get_attachment_column<M: Schema>(field: &str) -> Result<&Column<'static>, Error> {
+    M::get_column(field)
+        .filter(|col| col.extra().get_str("format") == Some("attachment"))
+        .ok_or_else(|| {
+            warn!(
+                "the field `{}` of the model `{}` is not an attachment",
+                field,
+                M::model_name()
+            )
+        })
+}
+
+/// Validates the file against the `max_size` and `content_types` attributes of the column.
+fn validate_file(col: &Column, file: &NamedFile) -> Result<(), Error> {
+    let file_size = file.file_size();
+    if let Some(max_size) = col.extra().get_u64("max_size") {
+        if file_size > max_size {
+            bail!(
+                "413 Payload Too Large: the size of the attachment `{}` should be at most {} bytes",
+                col.name(),
+                max_size
+            );
+        }
+    }
+    if let Some(content_types) = col.extra().get_str("content_types") {
+        let essence = file
+            .content_type()
+            .map(|mime| mime.essence_str())
+            .unwrap_or_default();
+        if !check_content_type(essence, content_types) {
+            bail!(
+                "415 Unsupported Media Type: the content type of the attachment `{}` should be one of `{}`",
+                col.name(),
+                content_types
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Returns `true` if the content type matches one of the comma-separated patterns,
+/// where a pattern like `image/*` matches any subtype.
+fn check_content_type(content_type: &str, patterns: &str) -> bool {
+    !content_type.is_empty()
+        && patterns.split(',').map(|s| s.trim()).any(|pattern| {
+            if let Some(prefix) = pattern.strip_suffix("/*") {
+                content_type
+                    .split_once('/')
+                    .is_some_and(|(t, _)| t.eq_ignore_ascii_case(prefix))
+            } else {
+                content_type.eq_ignore_ascii_case(pattern)
+            }
+        })
+}
+
+/// Replaces the characters which are not safe in a path.
+fn sanitize_file_name(file_name: &str) -> String {
+    let file_name = file_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    let file_name = file_name.trim_start_matches('.');
+    if file_name.is_empty() {
+        "file".to_owned()
+    } else {
+        file_name.to_owned()
+    }
+}
+
+/// Signs the proxied URL of the path with the expiration time.
+fn sign_proxy_url(path: &str, expires: i64) -> String {
+    let message = format!("{path}:{expires}");
+    hex::encode(crypto::sign(message.as_bytes(), &*helper::SECRET_KEY))
+}
+
+/// Settings for the attachments.
+#[derive(Debug)]
+struct AttachmentSettings {
+    /// Name of the default storage accessor.
+    accessor: &'static str,
+    /// Template of the path in the storage service.
+    path_template: &'static str,
+    /// Base URL for proxying the downloads.
+    proxy_url: Option<&'static str>,
+}
+
+/// Shared settings for the attachments.
+static ATTACHMENT_SETTINGS: LazyLock<AttachmentSettings> = LazyLock::new(|| {
+    let config = State::shared().get_config("attachment");
+    AttachmentSettings {
+        accessor: config
+            .and_then(|config| config.get_str("accessor"))
+            .unwrap_or("local"),
+        path_template: config
+            .and_then(|config| config.get_str("path-template"))
+            .unwrap_or("attachments/{model}/{id}/{field}/{checksum}/{file_name}"),
+        proxy_url: config.and_then(|config| config.get_str("proxy-url")),
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{check_content_type, sanitize_file_name};
+
+    #[test]
+    fn it_checks_attachments() {
+        assert!(check_content_type("image/png", "image/png, image/jpeg"));
+        assert!(check_content_type("image/webp", "image/*"));
+        assert!(!check_content_type("text/html", "image/*, application/pdf"));
+        assert!(!check_content_type("", "image/*"));
+
+        assert_eq!(sanitize_file_name("my receipt.pdf"), "my_receipt.pdf");
+        assert_eq!(sanitize_file_name("../../etc/passwd"), "_.._etc_passwd");
+        assert_eq!(sanitize_file_name(""), "file");
+    }
+}
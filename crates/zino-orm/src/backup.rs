@@ -0,0 +1,138 @@
+use super::{executor::Executor, query::QueryExt, GlobalPool};
+use std::path::Path;
+use zino_core::{
+    application::{Agent, Application},
+    datetime::DateTime,
+    error::Error,
+    model::Query,
+    schedule::{AsyncJob, JobContext, OverlapPolicy},
+    warn, BoxFuture,
+};
+
+/// Online backup of a SQLite database.
+///
+/// The backup is made by `VACUUM INTO`, which creates a consistent and compacted copy
+/// of the database without blocking the concurrent readers and writers for long.
+/// The `{timestamp}` placeholder in the destination path is replaced by the current time,
+/// and a relative path is resolved against the project directory.
+/// With the `accessor` feature, the backup can be uploaded to a storage service
+/// of the [`GlobalAccessor`](zino_storage::GlobalAccessor) instead.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino_orm::SqliteBackup;
+///
+/// let backup = SqliteBackup::new("main");
+/// backup.run("backups/main-{timestamp}.db").await?;
+///
+/// let mut scheduler = AsyncJobScheduler::new();
+/// scheduler.add(backup.job("0 0 3 * * *", "backups/main-{timestamp}.db"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SqliteBackup {
+    /// Name of the connection pool.
+    pool_name: &'static str,
+    /// Name of the storage accessor.
+    #[cfg(feature = "accessor")]
+    accessor: Option<&'static str>,
+}
+
+impl SqliteBackup {
+    /// Creates a new instance for the connection pool.
+    #[inline]
+    pub fn new(pool_name: &'static str) -> Self {
+        Self {
+            pool_name,
+            #[cfg(feature = "accessor")]
+            accessor: None,
+        }
+    }
+
+    /// Sets the name of the storage accessor to upload the backup.
+    #[cfg(feature = "accessor")]
+    #[inline]
+    pub fn accessor(mut self, accessor: &'static str) -> Self {
+        self.accessor = Some(accessor);
+        self
+    }
+
+    /// Backs up the database to the destination path and returns the path of the backup.
+    /// If the storage accessor has been set, the path is relative to the storage service.
+    pub async fn run(&self, dest_path: &str) -> Result<String, Error> {
+        let pool_name = self.pool_name;
+        let pool = GlobalPool::get(pool_name)
+            .ok_or_else(|| warn!("connection pool `{}` is unavailable", pool_name))?;
+        let timestamp = DateTime::now().format("%Y%m%d%H%M%S");
+        let dest_path = dest_path.replace("{timestamp}", &timestamp);
+
+        #[cfg(feature = "accessor")]
+        if let Some(accessor) = self.accessor {
+            let operator = zino_storage::GlobalAccessor::get(accessor)
+                .ok_or_else(|| warn!("storage accessor `{}` is unavailable", accessor))?;
+            let file_name = dest_path.replace(['/', '\\'], "_");
+            let temp_path = std::env::temp_dir().join(format!("zino-backup-{file_name}"));
+            vacuum_into(pool, &temp_path).await?;
+
+            let file_path = temp_path.clone();
+            let bytes = tokio::task::spawn_blocking(move || std::fs::read(file_path)).await??;
+            let result = operator.write(&dest_path, bytes).await;
+            if let Err(err) = std::fs::remove_file(&temp_path) {
+                tracing::warn!("fail to remove the temporary backup file: {err}");
+            }
+            result?;
+            tracing::info!(
+                pool_name,
+                accessor,
+                dest_path,
+                "the database has been backed up"
+            );
+            return Ok(dest_path);
+        }
+
+        let path = Agent::parse_path(&dest_path);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        vacuum_into(pool, &path).await?;
+
+        let dest_path = path.to_string_lossy().into_owned();
+        tracing::info!(pool_name, dest_path, "the database has been backed up");
+        Ok(dest_path)
+    }
+
+    /// Creates an async job which backs up the database into `dest_path`
+    /// with the cron expression. A run is skipped if the previous backup is still in progress.
+    pub fn job(self, cron_expr: &str, dest_path: impl Into<String>) -> AsyncJob {
+        AsyncJob::new(cron_expr, run_backup)
+            .name("sqlite_backup")
+            .overlap_policy(OverlapPolicy::Skip)
+            .data((self, dest_path.into()))
+    }
+}
+
+/// Creates a backup of the database with `VACUUM INTO`.
+async fn vacuum_into(pool: &super::ConnectionPool, path: &Path) -> Result<(), Error> {
+    if path.exists() {
+        return Err(warn!("backup file `{}` already exists", path.display()));
+    }
+
+    let sql = format!(
+        "VACUUM INTO {};",
+        Query::escape_string(path.to_string_lossy())
+    );
+    pool.pool().execute(&sql).await?;
+    Ok(())
+}
+
+/// Runs the backup job.
+fn run_backup(ctx: &mut JobContext) -> BoxFuture<'_> {
+    Box::pin(async move {
+        if let Some((backup, dest_path)) = ctx.get_data::<(SqliteBackup, String)>() {
+            if let Err(err) = backup.run(dest_path).await {
+                let pool_name = backup.pool_name;
+                tracing::error!(pool_name, "fail to back up the database: {err}");
+            }
+        }
+    })
+}
@@ -3,8 +3,8 @@ use convert_case::{Case, Casing};
 use std::borrow::Cow;
 use zino_core::{
     extension::JsonObjectExt,
-    model::{Column, Query},
-    JsonValue,
+    model::{Column, DefaultValue, Query},
+    JsonValue, Map,
 };
 
 /// Encodes the column to be sent to the database.
@@ -35,6 +35,22 @@ pub(super) trait ColumnExt {
 
     /// Returns the constraints.
     fn constraints(&self) -> Vec<String>;
+
+    /// Returns the collation declared by `#[schema(collation = "...")]`.
+    /// For PostgreSQL, the `citext` collation is mapped to the column type instead.
+    fn collation(&self) -> Option<&str>;
+
+    /// Returns `true` if the string values are compared case-insensitively,
+    /// which is declared by `#[schema(case_insensitive)]`.
+    fn is_case_insensitive(&self) -> bool;
+
+    /// Formats a filter which compares the lowercase values on both sides.
+    /// It returns `None` if the column or the filter is not case-insensitive.
+    fn format_case_insensitive_filter(&self, field: &str, value: &JsonValue) -> Option<String>;
+
+    /// Returns the value of the column in the model data.
+    /// The amount and currency columns of a money field are extracted from the nested object.
+    fn get_value<'a>(&self, data: &'a Map) -> Option<&'a JsonValue>;
 }
 
 impl ColumnExt for Column<'_> {
@@ -51,8 +67,11 @@ impl ColumnExt for Column<'_> {
             "INT UNSIGNED" => data_type == "INT",
             "BIGINT UNSIGNED" => data_type == "BIGINT",
             "TEXT" => data_type == "VARCHAR",
+            "CITEXT" => data_type == "USER-DEFINED",
             _ => {
-                if cfg!(feature = "orm-postgres") && column_type.ends_with("[]") {
+                if cfg!(feature = "orm-postgres") && self.extra().contains_key("pg_enum") {
+                    data_type == "USER-DEFINED"
+                } else if cfg!(feature = "orm-postgres") && column_type.ends_with("[]") {
                     data_type == "ARRAY"
                 } else if column_type.starts_with("TIMESTAMP") {
                     data_type.starts_with("TIMESTAMP")
@@ -72,6 +91,7 @@ impl ColumnExt for Column<'_> {
         if cfg!(feature = "orm-postgres") {
             match self.column_type() {
                 "UUID" => "::UUID",
+                "CITEXT" => "::CITEXT",
                 "BIGINT" | "BIGSERIAL" => "::BIGINT",
                 "INT" | "SERIAL" => "::INT",
                 "SMALLINT" | "SMALLSERIAL" => "::SMALLINT",
@@ -90,10 +110,29 @@ impl ColumnExt for Column<'_> {
         let column_field = Query::format_field(column_name);
         let column_type = self.column_type();
         let mut definition = format!("{column_field} {column_type}");
+        if let Some(charset) = self.extra().get_str("charset").filter(|_| {
+            cfg!(any(
+                feature = "orm-mariadb",
+                feature = "orm-mysql",
+                feature = "orm-tidb"
+            ))
+        }) {
+            definition = format!("{definition} CHARACTER SET {charset}");
+        }
+        if let Some(collation) = self.collation() {
+            definition = if cfg!(feature = "orm-postgres") {
+                format!(r#"{definition} COLLATE "{collation}""#)
+            } else {
+                format!("{definition} COLLATE {collation}")
+            };
+        }
         if column_name == primary_key_name {
             definition += " PRIMARY KEY";
         }
-        if let Some(value) = self.default_value() {
+        let default_value = self
+            .default_value()
+            .filter(|value| !DefaultValue::is_dynamic(value));
+        if let Some(value) = default_value {
             if self.auto_increment() {
                 definition += if cfg!(any(
                     feature = "orm-mariadb",
@@ -123,6 +162,16 @@ impl ColumnExt for Column<'_> {
         } else if self.is_not_null() {
             definition += " NOT NULL";
         }
+        if let Some(comment) = self.comment().filter(|_| {
+            cfg!(any(
+                feature = "orm-mariadb",
+                feature = "orm-mysql",
+                feature = "orm-tidb"
+            ))
+        }) {
+            let comment = Query::escape_string(comment);
+            definition = format!("{definition} COMMENT {comment}");
+        }
         definition
     }
 
@@ -154,4 +203,87 @@ impl ColumnExt for Column<'_> {
         }
         constraints
     }
+
+    fn collation(&self) -> Option<&str> {
+        let collation = self.extra().get_str("collation")?;
+        if cfg!(feature = "orm-postgres") && collation.eq_ignore_ascii_case("citext") {
+            None
+        } else {
+            Some(collation)
+        }
+    }
+
+    #[inline]
+    fn is_case_insensitive(&self) -> bool {
+        self.has_attribute("case_insensitive")
+            && matches!(self.type_name(), "String" | "Option<String>")
+    }
+
+    fn format_case_insensitive_filter(&self, field: &str, value: &JsonValue) -> Option<String> {
+        if !self.is_case_insensitive() || self.fuzzy_search() {
+            return None;
+        }
+
+        let field = Query::format_field(field);
+        let format_condition = |operator: &str, value: &JsonValue| -> Option<String> {
+            let values = if let Some(values) = value.as_array() {
+                values
+                    .iter()
+                    .map(|v| v.as_str())
+                    .collect::<Option<Vec<_>>>()?
+            } else {
+                vec![value.as_str()?]
+            };
+            let values = values
+                .into_iter()
+                .map(|value| format!("LOWER({})", Query::escape_string(value)))
+                .collect::<Vec<_>>();
+            let condition = match operator {
+                "=" | "<>" if values.len() == 1 => {
+                    format!("LOWER({field}) {operator} {}", values[0])
+                }
+                "=" | "IN" => format!("LOWER({field}) IN ({})", values.join(", ")),
+                _ => format!("LOWER({field}) NOT IN ({})", values.join(", ")),
+            };
+            Some(condition)
+        };
+        match value {
+            JsonValue::String(s) => {
+                if matches!(s.as_str(), "null" | "not_null") || s.starts_with(['!', '~', '*']) {
+                    None
+                } else {
+                    let values = s.split(',').map(JsonValue::from).collect::<Vec<_>>();
+                    format_condition("=", &values.into())
+                }
+            }
+            JsonValue::Object(filter) => {
+                let mut conditions = Vec::with_capacity(filter.len());
+                for (name, value) in filter {
+                    let operator = match name.as_str() {
+                        "$eq" => "=",
+                        "$ne" => "<>",
+                        "$in" => "IN",
+                        "$nin" => "NOT IN",
+                        _ => return None,
+                    };
+                    if value.is_array() != operator.ends_with("IN") {
+                        return None;
+                    }
+                    conditions.push(format_condition(operator, value)?);
+                }
+                (!conditions.is_empty()).then(|| conditions.join(" AND "))
+            }
+            _ => None,
+        }
+    }
+
+    fn get_value<'a>(&self, data: &'a Map) -> Option<&'a JsonValue> {
+        let name = self.name();
+        if let Some(field) = self.extra().get_str("money_field") {
+            let key = name.strip_prefix(field)?.strip_prefix('_')?;
+            data.get_object(field).and_then(|money| money.get(key))
+        } else {
+            data.get(name)
+        }
+    }
 }
@@ -0,0 +1,207 @@
+use zino_core::{
+    error::Error,
+    extension::JsonObjectExt,
+    model::{Column, Query},
+    warn, BoxFuture, JsonValue, Map,
+};
+
+/// Function to compute the value from the model data.
+pub type ComputeFn = fn(&Map) -> JsonValue;
+
+/// Function to compute the values for a batch of models.
+pub type BatchComputeFn = for<'a> fn(&'a [Map]) -> BoxFuture<'a, Result<Vec<JsonValue>, Error>>;
+
+/// A computed field declared by `#[schema(computed = "fn_name")]`
+/// or `#[schema(computed_async = "fn_name")]`.
+///
+/// The values are never stored in the database. They are inserted into the model data
+/// before the `before_respond` hook, and the field is listed as `readOnly` in the definition.
+/// An async computed field is evaluated once for all the models in a response,
+/// so that the lookups can be batched to avoid the N+1 queries.
+#[derive(Debug)]
+pub struct ComputedField {
+    /// Column definition.
+    column: Column<'static>,
+    /// Columns the value depends on.
+    dependencies: Vec<&'static str>,
+    /// Compute function.
+    compute: Compute,
+}
+
+/// Compute function of the field.
+#[derive(Debug, Clone, Copy)]
+enum Compute {
+    /// Computes the value for each model.
+    Sync(ComputeFn),
+    /// Computes the values for a batch of models.
+    Batch(BatchComputeFn),
+}
+
+impl ComputedField {
+    /// Creates a new instance with the function to compute the value for each model.
+    #[inline]
+    pub fn new(name: &'static str, type_name: &'static str, compute: ComputeFn) -> Self {
+        Self::with_compute(name, type_name, Compute::Sync(compute))
+    }
+
+    /// Creates a new instance with the function to compute the values for a batch of models.
+    /// It should return the values in the same order as the models.
+    #[inline]
+    pub fn with_batch(
+        name: &'static str,
+        type_name: &'static str,
+        compute: BatchComputeFn,
+    ) -> Self {
+        Self::with_compute(name, type_name, Compute::Batch(compute))
+    }
+
+    /// Creates a new instance with the compute function.
+    fn with_compute(name: &'static str, type_name: &'static str, compute: Compute) -> Self {
+        let mut column = Column::new(name, type_name, false);
+        column.set_extra_attribute("computed", true);
+        column.set_extra_attribute("read_only", true);
+        Self {
+            column,
+            dependencies: Vec::new(),
+            compute,
+        }
+    }
+
+    /// Sets the comma-separated columns the value depends on.
+    #[inline]
+    pub fn set_dependencies(&mut self, dependencies: &'static str) {
+        self.dependencies = dependencies
+            .split(',')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .collect();
+    }
+
+    /// Sets the comment.
+    #[inline]
+    pub fn set_comment(&mut self, comment: &'static str) {
+        self.column.set_comment(comment);
+    }
+
+    /// Returns the field name.
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.column.name()
+    }
+
+    /// Returns a reference to the column definition.
+    #[inline]
+    pub fn column(&self) -> &Column<'static> {
+        &self.column
+    }
+
+    /// Returns the columns the value depends on.
+    #[inline]
+    pub fn dependencies(&self) -> &[&'static str] {
+        &self.dependencies
+    }
+
+    /// Returns `true` if the values are computed for a batch of models.
+    #[inline]
+    pub fn is_async(&self) -> bool {
+        matches!(self.compute, Compute::Batch(_))
+    }
+
+    /// Computes the values and inserts them into the model data.
+    /// The models missing any of the dependencies are skipped,
+    /// which is the case when the field is excluded by a sparse fieldset.
+    pub async fn compute(&self, models: &mut [Map]) -> Result<(), Error> {
+        let name = self.name();
+        let dependencies = self.dependencies();
+        let is_computable =
+            |model: &Map| dependencies.iter().all(|&field| model.contains_key(field));
+        match self.compute {
+            Compute::Sync(compute) => {
+                for model in models.iter_mut().filter(|model| is_computable(model)) {
+                    let value = compute(model);
+                    model.upsert(name, value);
+                }
+            }
+            Compute::Batch(compute) => {
+                let (computable_models, indices): (Vec<_>, Vec<_>) = models
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, model)| is_computable(model))
+                    .map(|(index, model)| (model.clone(), index))
+                    .unzip();
+                if computable_models.is_empty() {
+                    return Ok(());
+                }
+
+                let values = compute(&computable_models).await?;
+                if values.len() != indices.len() {
+                    return Err(warn!(
+                        "the computed field `{}` expects {} values, but got {}",
+                        name,
+                        indices.len(),
+                        values.len()
+                    ));
+                }
+                for (index, value) in indices.into_iter().zip(values) {
+                    models[index].upsert(name, value);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Replaces the computed fields in the projection with the columns they depend on,
+/// so that the source columns are selected when a computed field is requested.
+pub(super) fn expand_fields(query: &mut Query, computed_fields: &[ComputedField]) {
+    if computed_fields.is_empty() || query.fields().is_empty() {
+        return;
+    }
+
+    let mut fields = Vec::<String>::new();
+    for field in query.fields() {
+        if let Some(computed_field) = computed_fields.iter().find(|f| f.name() == field) {
+            for &dependency in computed_field.dependencies() {
+                if !fields.iter().any(|f| f == dependency) {
+                    fields.push(dependency.to_owned());
+                }
+            }
+        } else if !fields.contains(field) {
+            fields.push(field.to_owned());
+        }
+    }
+    query.set_fields(fields);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_fields, ComputedField};
+    use zino_core::{extension::JsonObjectExt, model::Query, JsonValue, Map};
+
+    fn full_name(model: &Map) -> JsonValue {
+        let first_name = model.get_str("first_name").unwrap_or_default();
+        let last_name = model.get_str("last_name").unwrap_or_default();
+        format!("{first_name} {last_name}").into()
+    }
+
+    #[test]
+    fn it_computes_fields() {
+        let mut field = ComputedField::new("full_name", "String", full_name);
+        field.set_dependencies("first_name, last_name");
+        assert!(field.column().is_read_only());
+        assert_eq!(field.dependencies(), ["first_name", "last_name"]);
+
+        let mut query = Query::default();
+        query.set_fields(vec!["id".to_owned(), "full_name".to_owned()]);
+        expand_fields(&mut query, std::slice::from_ref(&field));
+        assert_eq!(query.fields(), ["id", "first_name", "last_name"]);
+
+        let mut model = Map::new();
+        model.upsert("first_name", "Ada");
+        model.upsert("last_name", "Lovelace");
+        let mut models = vec![model, Map::from_entry("id", 1)];
+        futures::executor::block_on(field.compute(&mut models)).unwrap();
+        assert_eq!(models[0].get_str("full_name"), Some("Ada Lovelace"));
+        assert!(!models[1].contains_key("full_name"));
+    }
+}
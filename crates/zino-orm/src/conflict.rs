@@ -0,0 +1,441 @@
+use super::Schema;
+use zino_core::{
+    error::{Error, ErrorKind},
+    validation::Validation,
+};
+
+/// Details of a unique constraint violation.
+///
+/// It is attached as the context of the [`Error`] of kind [`ErrorKind::Conflict`]
+/// when an insertion or an upsert violates a unique constraint,
+/// and the offending columns are resolved from the constraint name if possible.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UniqueViolation {
+    /// Error code reported by the database.
+    code: Option<String>,
+    /// Constraint or index name.
+    constraint: Option<String>,
+    /// Table name.
+    table: Option<String>,
+    /// Offending fields.
+    fields: Vec<String>,
+}
+
+impl UniqueViolation {
+    /// Parses the driver-specific error message and the optional constraint name
+    /// reported by the database.
+    pub fn parse(message: &str, constraint: Option<&str>) -> Self {
+        let mut violation = parse_postgres_message(message)
+            .or_else(|| parse_mysql_message(message))
+            .or_else(|| parse_sqlite_message(message))
+            .unwrap_or_default();
+        if let Some(constraint) = constraint.filter(|s| !s.is_empty()) {
+            violation.constraint = Some(constraint.to_owned());
+        }
+        violation
+    }
+
+    /// Parses the unique violation reported by the database driver.
+    #[cfg(feature = "orm-sqlx")]
+    pub(crate) fn from_database_error(db_err: &dyn sqlx::error::DatabaseError) -> Self {
+        let mut violation = Self::parse(db_err.message(), db_err.constraint());
+        violation.code = db_err.code().map(|code| code.into_owned());
+        if let Some(table) = db_err.table() {
+            violation.table = Some(table.to_owned());
+        }
+        violation
+    }
+
+    /// Returns a reference to the details if the error is caused by a unique violation.
+    #[inline]
+    pub fn from_error(err: &Error) -> Option<&Self> {
+        if err.kind() == &ErrorKind::Conflict {
+            err.get_context::<Self>()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the error code reported by the database, such as the SQLSTATE `23505`
+    /// for PostgreSQL, `23000` for MySQL, or the extended result code `2067` for SQLite.
+    #[inline]
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    /// Returns the constraint or index name.
+    #[inline]
+    pub fn constraint(&self) -> Option<&str> {
+        self.constraint.as_deref()
+    }
+
+    /// Returns the offending fields.
+    /// It is empty if the constraint can not be mapped to the columns.
+    #[inline]
+    pub fn fields(&self) -> &[String] {
+        &self.fields
+    }
+
+    /// Converts `self` into a validation with the messages for each field.
+    /// If the fields are unknown, the message is recorded for the constraint name.
+    pub fn to_validation(&self) -> Validation {
+        let mut validation = Validation::new();
+        if self.fields.is_empty() {
+            let key = self
+                .constraint
+                .clone()
+                .unwrap_or_else(|| "unique".to_owned());
+            validation.record(key, "the value conflicts with an existing record");
+        } else {
+            for field in self.fields.iter() {
+                validation.record(field.clone(), "the value already exists");
+            }
+        }
+        validation
+    }
+
+    /// Resolves the offending fields with the columns of the model.
+    fn resolve_fields<M: Schema>(&mut self) {
+        let columns = M::columns();
+        let mut table_name = M::table_name();
+        if let Some((_, suffix)) = table_name.rsplit_once('.') {
+            table_name = suffix;
+        }
+        let table = self
+            .table
+            .as_deref()
+            .map(|table| table.rsplit_once('.').map_or(table, |(_, suffix)| suffix));
+        if table.is_some_and(|table| table != table_name) {
+            self.fields.clear();
+            return;
+        }
+        if !self.fields.is_empty() {
+            self.fields
+                .retain(|field| columns.iter().any(|col| col.name() == field));
+            return;
+        }
+
+        let Some(constraint) = self.constraint.as_deref() else {
+            return;
+        };
+        if constraint.eq_ignore_ascii_case("PRIMARY") || constraint == format!("{table_name}_pkey")
+        {
+            self.fields.push(M::PRIMARY_KEY_NAME.to_owned());
+            return;
+        }
+
+        if let Some(index) = M::indexes()
+            .into_iter()
+            .find(|index| index.name(table_name) == constraint)
+        {
+            self.fields
+                .extend(index.column_names().into_iter().map(|name| name.to_owned()));
+            return;
+        }
+
+        // Index names are generated as `{table_name}_{column_name}_index` by the derive,
+        // and Postgres names the unique constraints as `{table_name}_{column_name}_key`.
+        let name = constraint.strip_prefix(table_name).unwrap_or(constraint);
+        let name = name.strip_prefix('_').unwrap_or(name);
+        let name = ["_index", "_key", "_unique", "_idx"]
+            .iter()
+            .find_map(|suffix| name.strip_suffix(suffix))
+            .unwrap_or(name);
+        if let Some(col) = columns.iter().find(|col| col.name() == name) {
+            self.fields.push(col.name().to_owned());
+        }
+    }
+}
+
+/// Attaches the offending fields of the model to the unique violation error.
+/// Other errors are returned unchanged.
+pub(crate) fn resolve_unique_violation<M: Schema>(mut err: Error) -> Error {
+    if let Some(violation) = err.get_context_mut::<UniqueViolation>() {
+        violation.resolve_fields::<M>();
+    }
+    err
+}
+
+/// Parses the Postgres message in the form:
+/// `duplicate key value violates unique constraint "users_email_key"`.
+fn parse_postgres_message(message: &str) -> Option<UniqueViolation> {
+    let (_, constraint) = message.split_once("violates unique constraint ")?;
+    let constraint = constraint.trim().trim_matches('"');
+    Some(UniqueViolation {
+        constraint: Some(constraint.to_owned()),
+        ..UniqueViolation::default()
+    })
+}
+
+/// Parses the MySQL message in the form:
+/// `Duplicate entry 'alice@example.com' for key 'users.users_email_index'`.
+/// The table prefix of the key is absent for MySQL versions before 8.0.19.
+fn parse_mysql_message(message: &str) -> Option<UniqueViolation> {
+    if !message.starts_with("Duplicate entry") {
+        return None;
+    }
+
+    let (_, key) = message.rsplit_once(" for key ")?;
+    let key = key.trim().trim_matches(['\'', '`']);
+    let (table, constraint) = match key.rsplit_once('.') {
+        Some((table, constraint)) => (Some(table.to_owned()), constraint),
+        None => (None, key),
+    };
+    Some(UniqueViolation {
+        constraint: Some(constraint.to_owned()),
+        table,
+        ..UniqueViolation::default()
+    })
+}
+
+/// Parses the SQLite message in the form:
+/// `UNIQUE constraint failed: users.email, users.name`.
+fn parse_sqlite_message(message: &str) -> Option<UniqueViolation> {
+    let (_, columns) = message.split_once("UNIQUE constraint failed: ")?;
+    let mut table = None;
+    let fields = columns
+        .split(',')
+        .filter_map(|column| {
+            let column = column.trim();
+            match column.rsplit_once('.') {
+                Some((table_name, field)) => {
+                    table = Some(table_name.to_owned());
+                    Some(field.to_owned())
+                }
+                None => (!column.is_empty()).then(|| column.to_owned()),
+            }
+        })
+        .collect();
+    Some(UniqueViolation {
+        table,
+        fields,
+        ..UniqueViolation::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UniqueViolation;
+
+    #[test]
+    fn it_parses_unique_violations() {
+        let message = r#"duplicate key value violates unique constraint "users_email_key""#;
+        let violation = UniqueViolation::parse(message, None);
+        assert_eq!(violation.constraint(), Some("users_email_key"));
+
+        let violation = UniqueViolation::parse(message, Some("users_email_index"));
+        assert_eq!(violation.constraint(), Some("users_email_index"));
+
+        let message = "Duplicate entry 'alice@example.com' for key 'users.users_email_index'";
+        let violation = UniqueViolation::parse(message, None);
+        assert_eq!(violation.constraint(), Some("users_email_index"));
+        assert_eq!(violation.table.as_deref(), Some("users"));
+
+        let message = "Duplicate entry '1' for key 'PRIMARY'";
+        let violation = UniqueViolation::parse(message, None);
+        assert_eq!(violation.constraint(), Some("PRIMARY"));
+        assert_eq!(violation.table, None);
+
+        let message = "UNIQUE constraint failed: users.email, users.name";
+        let violation = UniqueViolation::parse(message, None);
+        assert_eq!(violation.constraint(), None);
+        assert_eq!(violation.table.as_deref(), Some("users"));
+        assert_eq!(violation.fields(), ["email", "name"]);
+
+        let message = "connection reset by peer";
+        assert_eq!(
+            UniqueViolation::parse(message, None),
+            UniqueViolation::default()
+        );
+    }
+
+    /// Error code, message, and the expected constraint, table and fields.
+    #[cfg(feature = "orm-sqlx")]
+    type Fixture = (
+        &'static str,
+        &'static str,
+        Option<&'static str>,
+        Option<&'static str>,
+        &'static [&'static str],
+    );
+
+    /// Unique violations reported by each driver.
+    #[cfg(feature = "orm-sqlx")]
+    const FIXTURES: [Fixture; 7] = [
+        // PostgreSQL reports the SQLSTATE `23505`.
+        (
+            "23505",
+            r#"duplicate key value violates unique constraint "users_email_key""#,
+            Some("users_email_key"),
+            None,
+            &[],
+        ),
+        (
+            "23505",
+            r#"duplicate key value violates unique constraint "users_pkey""#,
+            Some("users_pkey"),
+            None,
+            &[],
+        ),
+        // MySQL reports the SQLSTATE `23000` with the error number `1062`.
+        (
+            "23000",
+            "Duplicate entry 'alice@example.com' for key 'users.users_email_index'",
+            Some("users_email_index"),
+            Some("users"),
+            &[],
+        ),
+        (
+            "23000",
+            "Duplicate entry 'alice@example.com' for key 'users_email_index'",
+            Some("users_email_index"),
+            None,
+            &[],
+        ),
+        (
+            "23000",
+            "Duplicate entry '1' for key 'PRIMARY'",
+            Some("PRIMARY"),
+            None,
+            &[],
+        ),
+        // SQLite reports the extended result codes `2067` and `1555`.
+        (
+            "2067",
+            "UNIQUE constraint failed: users.email, users.name",
+            None,
+            Some("users"),
+            &["email", "name"],
+        ),
+        (
+            "1555",
+            "UNIQUE constraint failed: users.id",
+            None,
+            Some("users"),
+            &["id"],
+        ),
+    ];
+
+    /// A database error with the fixture values.
+    #[cfg(feature = "orm-sqlx")]
+    #[derive(Debug)]
+    struct MockDatabaseError {
+        code: &'static str,
+        message: &'static str,
+        unique: bool,
+    }
+
+    #[cfg(feature = "orm-sqlx")]
+    impl std::fmt::Display for MockDatabaseError {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "{}", self.message)
+        }
+    }
+
+    #[cfg(feature = "orm-sqlx")]
+    impl std::error::Error for MockDatabaseError {}
+
+    #[cfg(feature = "orm-sqlx")]
+    impl sqlx::error::DatabaseError for MockDatabaseError {
+        fn message(&self) -> &str {
+            self.message
+        }
+
+        fn code(&self) -> Option<std::borrow::Cow<'_, str>> {
+            Some(self.code.into())
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> sqlx::error::ErrorKind {
+            if self.unique {
+                sqlx::error::ErrorKind::UniqueViolation
+            } else {
+                sqlx::error::ErrorKind::ForeignKeyViolation
+            }
+        }
+    }
+
+    #[cfg(feature = "orm-sqlx")]
+    #[test]
+    fn it_maps_driver_errors() {
+        use crate::executor::classify_error;
+        use zino_core::error::ErrorKind;
+
+        for (code, message, constraint, table, fields) in FIXTURES {
+            let db_err = MockDatabaseError {
+                code,
+                message,
+                unique: true,
+            };
+            let err = classify_error(sqlx::Error::Database(Box::new(db_err)));
+            assert_eq!(err.kind(), &ErrorKind::Conflict, "{message}");
+
+            let violation = UniqueViolation::from_error(&err).expect(message);
+            assert_eq!(violation.code(), Some(code));
+            assert_eq!(violation.constraint(), constraint, "{message}");
+            assert_eq!(violation.table.as_deref(), table, "{message}");
+            assert_eq!(violation.fields(), fields, "{message}");
+        }
+
+        let db_err = MockDatabaseError {
+            code: "23503",
+            message: r#"insert or update on table "orders" violates foreign key constraint"#,
+            unique: false,
+        };
+        let err = classify_error(sqlx::Error::Database(Box::new(db_err)));
+        assert_eq!(err.kind(), &ErrorKind::Validation);
+        assert!(UniqueViolation::from_error(&err).is_none());
+    }
+
+    #[cfg(not(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb",
+        feature = "orm-postgres"
+    )))]
+    #[tokio::test]
+    async fn it_maps_sqlite_errors() {
+        use crate::executor::classify_error;
+        use sqlx::{Executor, SqlitePool};
+
+        let pool = SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("fail to connect to the in-memory database");
+        pool.execute("CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT UNIQUE)")
+            .await
+            .expect("fail to create the table");
+        pool.execute("INSERT INTO users VALUES (1, 'alice@example.com')")
+            .await
+            .expect("fail to insert a row");
+
+        let err = pool
+            .execute("INSERT INTO users VALUES (2, 'alice@example.com')")
+            .await
+            .expect_err("the email should be unique");
+        let err = classify_error(err);
+        let violation = UniqueViolation::from_error(&err).expect("it should be a conflict");
+        assert_eq!(violation.code(), Some("2067"));
+        assert_eq!(violation.table.as_deref(), Some("users"));
+        assert_eq!(violation.fields(), ["email"]);
+
+        let err = pool
+            .execute("INSERT INTO users VALUES (1, 'bob@example.com')")
+            .await
+            .expect_err("the id should be unique");
+        let err = classify_error(err);
+        let violation = UniqueViolation::from_error(&err).expect("it should be a conflict");
+        assert_eq!(violation.code(), Some("1555"));
+        assert_eq!(violation.fields(), ["id"]);
+    }
+}
@@ -0,0 +1,210 @@
+use super::{column::ColumnExt, executor::classify_error, query::QueryExt, Schema};
+use futures::{Stream, StreamExt};
+use sqlx::postgres::{PgDatabaseError, PgPoolCopyExt};
+use std::fmt::Write;
+use zino_core::{
+    error::Error,
+    model::{Column, Query},
+    JsonValue, Map,
+};
+
+/// Number of bytes buffered before sending the data to the `COPY` stream.
+const COPY_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Loads the models into the table with `COPY ... FROM STDIN` in the CSV format.
+pub(super) async fn copy_in<M, S>(rows: S) -> Result<u64, Error>
+where
+    M: Schema,
+    S: Stream<Item = M> + Send,
+{
+    let columns = M::columns()
+        .iter()
+        .filter(|col| !col.auto_increment())
+        .collect::<Vec<_>>();
+    let table_name = Query::table_name_escaped::<M>();
+    let fields = columns
+        .iter()
+        .map(|col| Query::format_field(col.name()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!("COPY {table_name} ({fields}) FROM STDIN WITH (FORMAT csv)");
+    let mut ctx = M::before_scan(&sql).await?;
+    ctx.set_query(sql);
+
+    let pool = M::acquire_writer().await?.pool();
+    let mut copy = pool
+        .copy_in_raw(ctx.query())
+        .await
+        .map_err(classify_error)?;
+    let mut buffer = String::with_capacity(COPY_BUFFER_SIZE);
+    let mut rows = std::pin::pin!(rows);
+    while let Some(mut model) = rows.next().await {
+        if let Err(err) = model.before_insert().await {
+            if let Err(err) = copy.abort(err.message()).await {
+                tracing::warn!("fail to abort the copy: {err}");
+            }
+            return Err(err);
+        }
+
+        let map = model.into_map();
+        write_csv_record(&mut buffer, &columns, &map);
+        if buffer.len() >= COPY_BUFFER_SIZE {
+            copy.send(buffer.as_bytes())
+                .await
+                .map_err(resolve_copy_error)?;
+            buffer.clear();
+        }
+    }
+    if !buffer.is_empty() {
+        copy.send(buffer.as_bytes())
+            .await
+            .map_err(resolve_copy_error)?;
+    }
+
+    let rows_affected = copy.finish().await.map_err(resolve_copy_error)?;
+    ctx.set_query_result(rows_affected, true);
+    M::after_scan(&ctx).await?;
+    Ok(rows_affected)
+}
+
+/// Writes a CSV record for the columns.
+fn write_csv_record(buffer: &mut String, columns: &[&Column<'_>], map: &Map) {
+    for (index, col) in columns.iter().enumerate() {
+        if index > 0 {
+            buffer.push(',');
+        }
+        if let Some(value) = encode_csv_value(col, col.get_value(map)) {
+            if value.is_empty() || value.contains([',', '"', '\n', '\r']) {
+                buffer.push('"');
+                buffer.push_str(&value.replace('"', "\"\""));
+                buffer.push('"');
+            } else {
+                buffer.push_str(&value);
+            }
+        }
+    }
+    buffer.push('\n');
+}
+
+/// Encodes a column value as a text field, where `None` represents `NULL`.
+/// The encoding is consistent with the literals generated by `EncodeColumn`.
+fn encode_csv_value(col: &Column<'_>, value: Option<&JsonValue>) -> Option<String> {
+    let value = match value {
+        Some(value) => value,
+        None => return col.default_value().map(|value| value.to_owned()),
+    };
+    match value {
+        JsonValue::Null => None,
+        JsonValue::Bool(value) => Some(if *value { "t" } else { "f" }.to_owned()),
+        JsonValue::Number(value) => Some(value.to_string()),
+        JsonValue::String(value) => {
+            if value.is_empty() {
+                col.default_value()
+                    .map(|value| value.to_owned())
+                    .or_else(|| Some(String::new()))
+            } else if value == "null" {
+                None
+            } else {
+                format_csv_value(col, value)
+            }
+        }
+        JsonValue::Array(values) => {
+            let mut array = String::from("{");
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    array.push(',');
+                }
+                match value {
+                    JsonValue::Null => array.push_str("NULL"),
+                    JsonValue::String(value) => {
+                        let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+                        let _ = write!(array, "\"{value}\"");
+                    }
+                    _ => {
+                        let _ = write!(array, "\"{value}\"");
+                    }
+                }
+            }
+            array.push('}');
+            Some(array)
+        }
+        JsonValue::Object(_) => Some(value.to_string()),
+    }
+}
+
+/// Formats a string value for the column.
+fn format_csv_value(col: &Column<'_>, value: &str) -> Option<String> {
+    match col.type_name() {
+        "bool" => Some(if value == "true" { "t" } else { "f" }.to_owned()),
+        "u64" | "u32" | "u16" | "u8" | "usize" | "Option<u64>" | "Option<u32>" => {
+            value.parse::<u64>().ok().map(|_| value.to_owned())
+        }
+        "i64" | "i32" | "i16" | "i8" | "isize" | "Option<i64>" | "Option<i32>" => {
+            value.parse::<i64>().ok().map(|_| value.to_owned())
+        }
+        "f64" | "f32" | "Decimal" => value.parse::<f64>().ok().map(|_| value.to_owned()),
+        "Time" | "NaiveTime" if value == "midnight" => Some("allballs".to_owned()),
+        "Vec<u8>" => Some(format!(r"\x{value}")),
+        "Vec<Uuid>" | "Vec<String>" | "Vec<u64>" | "Vec<i64>" | "Vec<u32>" | "Vec<i32>" => {
+            let values = value
+                .split(',')
+                .map(|s| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")))
+                .collect::<Vec<_>>();
+            Some(format!("{{{}}}", values.join(",")))
+        }
+        _ => Some(value.to_owned()),
+    }
+}
+
+/// Resolves the error reported by the `COPY` stream with the offending line number.
+fn resolve_copy_error(err: sqlx::Error) -> Error {
+    let line = err
+        .as_database_error()
+        .and_then(|db_err| db_err.try_downcast_ref::<PgDatabaseError>())
+        .and_then(|db_err| db_err.r#where())
+        .and_then(parse_copy_line);
+    let err = classify_error(err);
+    if let Some(line) = line {
+        let message = format!("fail to copy the row at line {line}: {}", err.message());
+        err.wrap(message)
+    } else {
+        err
+    }
+}
+
+/// Parses the line number from the context of a `COPY` error, such as
+/// `COPY users, line 3, column age: "abc"`.
+fn parse_copy_line(context: &str) -> Option<usize> {
+    context
+        .split(',')
+        .find_map(|s| s.trim().strip_prefix("line "))
+        .and_then(|s| s.split_whitespace().next())
+        .and_then(|s| s.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_csv_value, parse_copy_line};
+    use zino_core::{model::Column, JsonValue};
+
+    #[test]
+    fn it_encodes_csv_values() {
+        let column = Column::new("tags", "Vec<String>", true);
+        let value = JsonValue::from(vec!["a,b", "c\"d"]);
+        assert_eq!(
+            encode_csv_value(&column, Some(&value)).as_deref(),
+            Some(r#"{"a,b","c\"d"}"#)
+        );
+        assert_eq!(encode_csv_value(&column, Some(&JsonValue::Null)), None);
+
+        let column = Column::new("age", "i32", true);
+        let value = JsonValue::from("abc");
+        assert_eq!(encode_csv_value(&column, Some(&value)), None);
+
+        assert_eq!(
+            parse_copy_line(r#"COPY users, line 3, column age: "abc""#),
+            Some(3)
+        );
+        assert_eq!(parse_copy_line("COPY users, line 12"), Some(12));
+    }
+}
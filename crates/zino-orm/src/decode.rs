@@ -1,6 +1,9 @@
 use super::{DatabaseDriver, DatabaseRow};
 use sqlx::{Database, Decode, Row, ValueRef};
-use zino_core::{error::Error, warn, Decimal, Uuid};
+use zino_core::{error::Error, money::Money, warn, Decimal, Ulid, Uuid};
+
+#[cfg(feature = "orm-postgres")]
+use sqlx::TypeInfo;
 
 /// Decodes a single value as `T` for the field in a row.
 #[inline]
@@ -85,16 +88,19 @@ pub fn decode_decimal(row: &DatabaseRow, field: &str) -> Result<Decimal, Error>
 }
 
 /// Decodes a single value as `Uuid` for the field in a row.
+/// A `CHAR(26)` column storing a ULID is also supported.
 #[cfg(feature = "orm-postgres")]
-#[inline]
 pub fn decode_uuid(row: &DatabaseRow, field: &str) -> Result<Uuid, Error> {
     match row.try_get_raw(field) {
         Ok(value) => {
             if value.is_null() {
                 Ok(Uuid::nil())
-            } else {
+            } else if value.type_info().name() == "UUID" {
                 let id = decode_raw(field, value)?;
                 Ok(id)
+            } else {
+                let id = decode_raw::<&str>(field, value)?;
+                parse_uuid(field, id.trim_end())
             }
         }
         Err(err) => {
@@ -108,14 +114,68 @@ pub fn decode_uuid(row: &DatabaseRow, field: &str) -> Result<Uuid, Error> {
 }
 
 /// Decodes a single value as `Uuid` for the field in a row.
+/// A `CHAR(26)` column storing a ULID is also supported.
 #[cfg(not(feature = "orm-postgres"))]
 #[inline]
 pub fn decode_uuid(row: &DatabaseRow, field: &str) -> Result<Uuid, Error> {
     let Some(value) = decode_optional::<String>(row, field)? else {
         return Ok(Uuid::nil());
     };
-    value
-        .parse()
+    parse_uuid(field, value.trim_end())
+}
+
+/// Decodes a single value as `Ulid` for the field in a row.
+/// A `UUID` column is also supported, which keeps the same 128 bits.
+#[cfg(feature = "orm-postgres")]
+pub fn decode_ulid(row: &DatabaseRow, field: &str) -> Result<Ulid, Error> {
+    match row.try_get_raw(field) {
+        Ok(value) => {
+            if value.is_null() {
+                Ok(Ulid::nil())
+            } else if value.type_info().name() == "UUID" {
+                let id = decode_raw::<Uuid>(field, value)?;
+                Ok(id.into())
+            } else {
+                let id = decode_raw::<&str>(field, value)?;
+                parse_ulid(field, id.trim_end())
+            }
+        }
+        Err(err) => {
+            if let sqlx::Error::ColumnNotFound(_) = err {
+                Ok(Ulid::nil())
+            } else {
+                Err(warn!("fail to get the `{}` field: {}", field, err))
+            }
+        }
+    }
+}
+
+/// Decodes a single value as `Ulid` for the field in a row.
+/// A `UUID` column is also supported, which keeps the same 128 bits.
+#[cfg(not(feature = "orm-postgres"))]
+#[inline]
+pub fn decode_ulid(row: &DatabaseRow, field: &str) -> Result<Ulid, Error> {
+    let Some(value) = decode_optional::<String>(row, field)? else {
+        return Ok(Ulid::nil());
+    };
+    parse_ulid(field, value.trim_end())
+}
+
+/// Decodes the `{field}_amount` and `{field}_currency` columns as `Money` in a row,
+/// returning `None` if the currency is null.
+pub fn decode_money(row: &DatabaseRow, field: &str) -> Result<Option<Money>, Error> {
+    let currency_field = [field, "_currency"].concat();
+    let Some(currency) = decode_optional::<String>(row, &currency_field)? else {
+        return Ok(None);
+    };
+    if currency.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let amount_field = [field, "_amount"].concat();
+    let amount = decode_decimal(row, &amount_field)?;
+    Money::new(amount, &currency)
+        .map(Some)
         .map_err(|err| warn!("fail to decode the `{}` field: {}", field, err))
 }
 
@@ -201,3 +261,27 @@ where
         }
     })
 }
+
+/// Parses the value as `Uuid`, falling back to the text representation of a ULID.
+fn parse_uuid(field: &str, value: &str) -> Result<Uuid, Error> {
+    if value.len() == Ulid::LENGTH {
+        if let Ok(id) = value.parse::<Ulid>() {
+            return Ok(id.into());
+        }
+    }
+    value
+        .parse()
+        .map_err(|err| warn!("fail to decode the `{}` field: {}", field, err))
+}
+
+/// Parses the value as `Ulid`, falling back to the text representation of a UUID.
+fn parse_ulid(field: &str, value: &str) -> Result<Ulid, Error> {
+    if value.len() != Ulid::LENGTH {
+        if let Ok(id) = value.parse::<Uuid>() {
+            return Ok(id.into());
+        }
+    }
+    value
+        .parse()
+        .map_err(|err| warn!("fail to decode the `{}` field: {}", field, err))
+}
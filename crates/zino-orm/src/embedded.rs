@@ -0,0 +1,188 @@
+use std::fmt::Display;
+use zino_core::{
+    datetime::DateTime,
+    model::{Column, Model, Query},
+    JsonValue, Map,
+};
+
+/// Columns shared by models via a `#[schema(flatten)]` field.
+///
+/// The columns are inlined into the parent model, so that they participate in
+/// the schema, the auto migration, the validation and the row decoding of the model.
+///
+/// This trait can be derived by `zino_derive::EmbeddedSchema`.
+pub trait EmbeddedSchema: Model {
+    /// Names of the columns.
+    const FIELDS: &'static [&'static str];
+
+    /// Returns a reference to the columns.
+    fn columns() -> &'static [Column<'static>];
+
+    /// Returns a reference to the read-only fields.
+    #[inline]
+    fn read_only_fields() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Returns a reference to the write-only fields.
+    #[inline]
+    fn write_only_fields() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Returns a reference to the fields in the snapshot of the model.
+    #[inline]
+    fn snapshot_fields() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Returns the `name` field if it is embedded.
+    #[inline]
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the `status` field if it is embedded.
+    #[inline]
+    fn status(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the `created_at` field if it is embedded.
+    #[inline]
+    fn created_at(&self) -> Option<DateTime> {
+        None
+    }
+
+    /// Returns the `updated_at` field if it is embedded.
+    #[inline]
+    fn updated_at(&self) -> Option<DateTime> {
+        None
+    }
+
+    /// Returns the `deleted_at` field if it is embedded.
+    #[inline]
+    fn deleted_at(&self) -> Option<DateTime> {
+        None
+    }
+
+    /// Returns the `version` field if it is embedded.
+    #[inline]
+    fn version(&self) -> Option<u64> {
+        None
+    }
+
+    /// Returns the `edition` field if it is embedded.
+    #[inline]
+    fn edition(&self) -> Option<u32> {
+        None
+    }
+
+    /// Appends the embedded fields to the snapshot of the model.
+    #[inline]
+    fn append_snapshot(&self, snapshot: &mut Map) {
+        let _ = snapshot;
+    }
+
+    /// Applies the filters and sorting orders to the default list query of the model.
+    #[inline]
+    fn apply_list_query(query: &mut Query) {
+        let _ = query;
+    }
+
+    /// Appends the updates for the soft deletion of the model.
+    #[inline]
+    fn append_soft_delete_updates(updates: &mut Map) {
+        let _ = updates;
+    }
+
+    /// Appends the updates for locking the model.
+    #[inline]
+    fn append_lock_updates(updates: &mut Map) {
+        let _ = updates;
+    }
+
+    /// Appends the updates for archiving the model.
+    #[inline]
+    fn append_archive_updates(updates: &mut Map) {
+        let _ = updates;
+    }
+}
+
+/// Embedded columns which contain the primary key of the model.
+///
+/// This trait is derived by `zino_derive::EmbeddedSchema` if there is a primary key.
+pub trait EmbeddedPrimaryKey: EmbeddedSchema {
+    /// Primary key.
+    type PrimaryKey: Default + Display + PartialEq;
+
+    /// Primary key name.
+    const PRIMARY_KEY_NAME: &'static str;
+
+    /// Returns a reference to the primary key.
+    fn primary_key(&self) -> &Self::PrimaryKey;
+
+    /// Returns the primary key as a JSON value.
+    fn primary_key_value(&self) -> JsonValue;
+
+    /// Returns a reference to the primary key column.
+    fn primary_key_column() -> &'static Column<'static>;
+}
+
+/// Returns `true` if the fields contain the field.
+/// It is used to detect the collisions of column names at compile time.
+#[doc(hidden)]
+pub const fn contains_field(fields: &[&str], field: &str) -> bool {
+    let mut index = 0;
+    while index < fields.len() {
+        if eq_str(fields[index], field) {
+            return true;
+        }
+        index += 1;
+    }
+    false
+}
+
+/// Returns `true` if the two lists of fields have any field in common.
+/// It is used to detect the collisions of column names at compile time.
+#[doc(hidden)]
+pub const fn contains_any_field(fields: &[&str], other_fields: &[&str]) -> bool {
+    let mut index = 0;
+    while index < other_fields.len() {
+        if contains_field(fields, other_fields[index]) {
+            return true;
+        }
+        index += 1;
+    }
+    false
+}
+
+/// Compares two strings in a const context.
+const fn eq_str(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut index = 0;
+    while index < a.len() {
+        if a[index] != b[index] {
+            return false;
+        }
+        index += 1;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{contains_any_field, contains_field};
+
+    #[test]
+    fn it_detects_field_collisions() {
+        const FIELDS: &[&str] = &["id", "created_at", "updated_at", "version"];
+        const { assert!(contains_field(FIELDS, "version")) };
+        assert!(!contains_field(FIELDS, "versions"));
+        assert!(contains_any_field(FIELDS, &["name", "updated_at"]));
+        assert!(!contains_any_field(FIELDS, &["name", "status"]));
+    }
+}
@@ -1,4 +1,4 @@
-use zino_core::error::Error;
+use zino_core::{error::Error, extension::JsonValueExt, warn, JsonValue};
 
 /// Executing queries against the database.
 pub trait Executor {
@@ -12,34 +12,280 @@ pub trait Executor {
     async fn execute(self, sql: &str) -> Result<Self::QueryResult, Error>;
 
     /// Executes the query with arguments and return the total number of rows affected.
+    /// The arguments are bound as strings.
     async fn execute_with<T: ToString>(
         self,
         sql: &str,
         arguments: &[T],
     ) -> Result<Self::QueryResult, Error>;
 
+    /// Executes the query with arguments bound by the types of the JSON values
+    /// and return the total number of rows affected.
+    async fn execute_with_values(
+        self,
+        sql: &str,
+        arguments: &[JsonValue],
+    ) -> Result<Self::QueryResult, Error>
+    where
+        Self: Sized,
+    {
+        self.execute_with(sql, &format_values(arguments)).await
+    }
+
     /// Executes the query and return all the generated results.
     async fn fetch(self, sql: &str) -> Result<Vec<Self::Row>, Error>;
 
     /// Executes the query with arguments and return all the generated results.
+    /// The arguments are bound as strings.
     async fn fetch_with<T: ToString>(
         self,
         sql: &str,
         arguments: &[T],
     ) -> Result<Vec<Self::Row>, Error>;
 
+    /// Executes the query with arguments bound by the types of the JSON values
+    /// and return all the generated results.
+    async fn fetch_with_values(
+        self,
+        sql: &str,
+        arguments: &[JsonValue],
+    ) -> Result<Vec<Self::Row>, Error>
+    where
+        Self: Sized,
+    {
+        self.fetch_with(sql, &format_values(arguments)).await
+    }
+
     /// Executes the query and returns exactly one row.
     async fn fetch_one(self, sql: &str) -> Result<Self::Row, Error>;
 
+    /// Executes the query with arguments and returns exactly one row.
+    /// The arguments are bound as strings.
+    async fn fetch_one_with<T: ToString>(
+        self,
+        sql: &str,
+        arguments: &[T],
+    ) -> Result<Self::Row, Error>;
+
+    /// Executes the query with arguments bound by the types of the JSON values
+    /// and returns exactly one row.
+    async fn fetch_one_with_values(
+        self,
+        sql: &str,
+        arguments: &[JsonValue],
+    ) -> Result<Self::Row, Error>
+    where
+        Self: Sized,
+    {
+        self.fetch_one_with(sql, &format_values(arguments)).await
+    }
+
     /// Executes the query and returns at most one row.
     async fn fetch_optional(self, sql: &str) -> Result<Option<Self::Row>, Error>;
 
     /// Executes the query with arguments and returns at most one row.
+    /// The arguments are bound as strings.
     async fn fetch_optional_with<T: ToString>(
         self,
         sql: &str,
         arguments: &[T],
     ) -> Result<Option<Self::Row>, Error>;
+
+    /// Executes the query with arguments bound by the types of the JSON values
+    /// and returns at most one row.
+    async fn fetch_optional_with_values(
+        self,
+        sql: &str,
+        arguments: &[JsonValue],
+    ) -> Result<Option<Self::Row>, Error>
+    where
+        Self: Sized,
+    {
+        self.fetch_optional_with(sql, &format_values(arguments))
+            .await
+    }
+}
+
+/// Formats the JSON values as the string arguments.
+fn format_values(values: &[JsonValue]) -> Vec<String> {
+    values
+        .iter()
+        .map(|value| value.to_string_unquoted())
+        .collect()
+}
+
+/// Converts a `sqlx` error into an [`Error`] with the classified kind.
+#[cfg(feature = "orm-sqlx")]
+pub(crate) fn classify_error(err: sqlx::Error) -> Error {
+    use super::UniqueViolation;
+    use sqlx::error::{Error as SqlxError, ErrorKind as SqlxErrorKind};
+    use zino_core::error::ErrorKind;
+
+    let kind = match &err {
+        SqlxError::Database(db_err) => match db_err.kind() {
+            SqlxErrorKind::UniqueViolation => {
+                let violation = UniqueViolation::from_database_error(&**db_err);
+                let mut err =
+                    Error::new(db_err.message().to_owned()).with_kind(ErrorKind::Conflict);
+                err.set_context(violation);
+                return err;
+            }
+            SqlxErrorKind::ForeignKeyViolation
+            | SqlxErrorKind::NotNullViolation
+            | SqlxErrorKind::CheckViolation => ErrorKind::Validation,
+            _ if super::timeout::is_statement_timeout(&**db_err) => ErrorKind::Timeout,
+            _ => ErrorKind::Internal,
+        },
+        SqlxError::RowNotFound => ErrorKind::NotFound,
+        SqlxError::PoolTimedOut | SqlxError::PoolClosed => ErrorKind::Unavailable,
+        _ => ErrorKind::Internal,
+    };
+    Error::from(err).with_kind(kind)
+}
+
+/// Encodes the query arguments according to the types of the JSON values.
+/// Arrays and objects are encoded as JSON texts.
+#[cfg(feature = "orm-sqlx")]
+pub(crate) fn encode_arguments(
+    values: &[JsonValue],
+) -> Result<<super::DatabaseDriver as sqlx::Database>::Arguments<'_>, Error> {
+    use sqlx::Arguments;
+
+    let mut arguments = <super::DatabaseDriver as sqlx::Database>::Arguments::default();
+    for value in values {
+        let result = match value {
+            JsonValue::Null => arguments.add(None::<String>),
+            JsonValue::Bool(b) => arguments.add(*b),
+            JsonValue::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    arguments.add(i)
+                } else if let Some(f) = n.as_f64().filter(|_| n.is_f64()) {
+                    arguments.add(f)
+                } else {
+                    arguments.add(n.to_string())
+                }
+            }
+            JsonValue::String(s) => arguments.add(s.as_str()),
+            _ => arguments.add(value.to_string()),
+        };
+        result.map_err(|err| warn!("fail to encode the query argument `{}`: {}", value, err))?;
+    }
+    Ok(arguments)
+}
+
+/// Converts the arguments into JSON strings, which are bound as the string arguments.
+#[cfg(feature = "orm-sqlx")]
+fn string_values<T: ToString>(arguments: &[T]) -> Vec<JsonValue> {
+    arguments
+        .iter()
+        .map(|arg| JsonValue::String(arg.to_string()))
+        .collect()
+}
+
+/// Instruments the statement with a client span if the `otel` feature is enabled.
+#[cfg(feature = "orm-sqlx")]
+macro_rules! traced_statement {
+    ($sql:expr, { $($body:tt)* }) => {{
+        let future = async move { $($body)* };
+        #[cfg(feature = "otel")]
+        let future = tracing::Instrument::instrument(future, statement_span($sql));
+        future.await
+    }};
+}
+
+/// Creates a client span for the SQL statement with the table name and sanitized SQL.
+#[cfg(all(feature = "orm-sqlx", feature = "otel"))]
+fn statement_span(sql: &str) -> tracing::Span {
+    /// Maximum number of characters of the SQL statement to be recorded.
+    const MAX_STATEMENT_LENGTH: usize = 2048;
+
+    let operation = sql
+        .split_whitespace()
+        .next()
+        .map(|word| word.trim_end_matches(';').to_ascii_uppercase())
+        .unwrap_or_default();
+    let table_name = parse_table_name(sql);
+
+    let statement = sanitize_statement(sql, MAX_STATEMENT_LENGTH);
+    let otel_name = if let Some(table_name) = table_name {
+        format!("{operation} {table_name}")
+    } else {
+        operation.clone()
+    };
+    tracing::info_span!(
+        "SQL statement",
+        "otel.kind" = "client",
+        "otel.name" = otel_name,
+        "db.system" = super::DRIVER_NAME,
+        "db.operation.name" = operation,
+        "db.collection.name" = table_name,
+        "db.query.text" = statement,
+    )
+}
+
+/// Parses the table name from the SQL statement.
+#[cfg(feature = "orm-sqlx")]
+pub(crate) fn parse_table_name(sql: &str) -> Option<&str> {
+    let mut words = sql.split_whitespace();
+    words.find(|word| {
+        ["FROM", "INTO", "UPDATE", "TABLE"]
+            .iter()
+            .any(|keyword| word.eq_ignore_ascii_case(keyword))
+    })?;
+    words
+        .next()
+        .map(|name| {
+            name.split(['(', ',', ';'])
+                .next()
+                .unwrap_or_default()
+                .trim_matches(['`', '"'])
+        })
+        .filter(|name| !name.is_empty())
+}
+
+/// Records the truncation of the result set which exceeds the max rows.
+/// A warning is emitted if there is no scope to signal the truncation.
+#[cfg(feature = "orm-sqlx")]
+pub(crate) fn record_truncation(sql: &str, max_rows: usize) {
+    if !super::row_limit::mark_truncated() {
+        let table_name = parse_table_name(sql).unwrap_or_default();
+        tracing::warn!(
+            table_name,
+            max_rows,
+            "the result set has been truncated by the max rows"
+        );
+    }
+}
+
+/// Replaces the string literals inlined in the SQL with placeholders
+/// so that the bind values are not recorded, and truncates it to the max length.
+#[cfg(all(feature = "orm-sqlx", feature = "otel"))]
+fn sanitize_statement(sql: &str, max_length: usize) -> String {
+    let mut statement = String::with_capacity(sql.len().min(max_length));
+    let mut chars = sql.chars().peekable();
+    let mut num_chars = 0;
+    while let Some(ch) = chars.next() {
+        if num_chars >= max_length {
+            break;
+        }
+        if ch == '\'' {
+            // Skips the literal with the escaped quotes
+            while let Some(ch) = chars.next() {
+                if ch == '\'' {
+                    if chars.peek() == Some(&'\'') {
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+            }
+            statement.push('?');
+        } else {
+            statement.push(ch);
+        }
+        num_chars += 1;
+    }
+    statement
 }
 
 #[cfg(feature = "orm-sqlx")]
@@ -49,15 +295,17 @@ macro_rules! impl_sqlx_executor {
         type QueryResult = <super::DatabaseDriver as sqlx::Database>::QueryResult;
 
         async fn execute(self, sql: &str) -> Result<Self::QueryResult, Error> {
-            match sqlx::query(sql).execute(self).await {
-                Ok(result) => Ok(result),
-                Err(err) => {
-                    if matches!(err, sqlx::error::Error::PoolTimedOut) {
-                        super::GlobalPool::connect_all().await;
+            traced_statement!(sql, {
+                match sqlx::query(sql).execute(self).await {
+                    Ok(result) => Ok(result),
+                    Err(err) => {
+                        if matches!(err, sqlx::error::Error::PoolTimedOut) {
+                            super::GlobalPool::connect_all().await;
+                        }
+                        Err(classify_error(err))
                     }
-                    Err(err.into())
                 }
-            }
+            })
         }
 
         async fn execute_with<T: ToString>(
@@ -65,44 +313,53 @@ macro_rules! impl_sqlx_executor {
             sql: &str,
             arguments: &[T],
         ) -> Result<Self::QueryResult, Error> {
-            let mut query = sqlx::query(sql);
-            for arg in arguments {
-                query = query.bind(arg.to_string());
-            }
-            match query.execute(self).await {
-                Ok(result) => Ok(result),
-                Err(err) => {
-                    if matches!(err, sqlx::error::Error::PoolTimedOut) {
-                        super::GlobalPool::connect_all().await;
-                    }
-                    Err(err.into())
-                }
-            }
+            self.execute_with_values(sql, &string_values(arguments))
+                .await
         }
 
-        async fn fetch(self, sql: &str) -> Result<Vec<Self::Row>, Error> {
-            use futures::StreamExt;
-            use std::sync::atomic::Ordering::Relaxed;
-
-            let mut stream = sqlx::query(sql).fetch(self);
-            let mut max_rows = super::MAX_ROWS.load(Relaxed);
-            let mut rows = Vec::with_capacity(stream.size_hint().0.min(max_rows));
-            while let Some(result) = stream.next().await {
-                match result {
-                    Ok(row) if max_rows > 0 => {
-                        rows.push(row);
-                        max_rows -= 1;
-                    }
+        async fn execute_with_values(
+            self,
+            sql: &str,
+            arguments: &[JsonValue],
+        ) -> Result<Self::QueryResult, Error> {
+            traced_statement!(sql, {
+                let query = sqlx::query_with(sql, encode_arguments(arguments)?);
+                match query.execute(self).await {
+                    Ok(result) => Ok(result),
                     Err(err) => {
                         if matches!(err, sqlx::error::Error::PoolTimedOut) {
                             super::GlobalPool::connect_all().await;
                         }
-                        return Err(err.into());
+                        Err(classify_error(err))
                     }
-                    _ => break,
                 }
-            }
-            Ok(rows)
+            })
+        }
+
+        async fn fetch(self, sql: &str) -> Result<Vec<Self::Row>, Error> {
+            traced_statement!(sql, {
+                use futures::StreamExt;
+
+                let mut stream = sqlx::query(sql).fetch(self);
+                let max_rows = super::row_limit::max_rows();
+                let mut rows = Vec::with_capacity(stream.size_hint().0.min(max_rows));
+                while let Some(result) = stream.next().await {
+                    match result {
+                        Ok(_) if max_rows > 0 && rows.len() >= max_rows => {
+                            record_truncation(sql, max_rows);
+                            break;
+                        }
+                        Ok(row) => rows.push(row),
+                        Err(err) => {
+                            if matches!(err, sqlx::error::Error::PoolTimedOut) {
+                                super::GlobalPool::connect_all().await;
+                            }
+                            return Err(classify_error(err));
+                        }
+                    }
+                }
+                Ok(rows)
+            })
         }
 
         async fn fetch_with<T: ToString>(
@@ -110,57 +367,93 @@ macro_rules! impl_sqlx_executor {
             sql: &str,
             arguments: &[T],
         ) -> Result<Vec<Self::Row>, Error> {
-            use futures::StreamExt;
-            use std::sync::atomic::Ordering::Relaxed;
+            self.fetch_with_values(sql, &string_values(arguments)).await
+        }
 
-            let mut query = sqlx::query(sql);
-            for arg in arguments {
-                query = query.bind(arg.to_string());
-            }
+        async fn fetch_with_values(
+            self,
+            sql: &str,
+            arguments: &[JsonValue],
+        ) -> Result<Vec<Self::Row>, Error> {
+            traced_statement!(sql, {
+                use futures::StreamExt;
 
-            let mut stream = query.fetch(self);
-            let mut max_rows = super::MAX_ROWS.load(Relaxed);
-            let mut rows = Vec::with_capacity(stream.size_hint().0.min(max_rows));
-            while let Some(result) = stream.next().await {
-                match result {
-                    Ok(row) if max_rows > 0 => {
-                        rows.push(row);
-                        max_rows -= 1;
+                let mut stream = sqlx::query_with(sql, encode_arguments(arguments)?).fetch(self);
+                let max_rows = super::row_limit::max_rows();
+                let mut rows = Vec::with_capacity(stream.size_hint().0.min(max_rows));
+                while let Some(result) = stream.next().await {
+                    match result {
+                        Ok(_) if max_rows > 0 && rows.len() >= max_rows => {
+                            record_truncation(sql, max_rows);
+                            break;
+                        }
+                        Ok(row) => rows.push(row),
+                        Err(err) => {
+                            if matches!(err, sqlx::error::Error::PoolTimedOut) {
+                                super::GlobalPool::connect_all().await;
+                            }
+                            return Err(classify_error(err));
+                        }
                     }
+                }
+                Ok(rows)
+            })
+        }
+
+        async fn fetch_one(self, sql: &str) -> Result<Self::Row, Error> {
+            traced_statement!(sql, {
+                match sqlx::query(sql).fetch_one(self).await {
+                    Ok(row) => Ok(row),
                     Err(err) => {
                         if matches!(err, sqlx::error::Error::PoolTimedOut) {
                             super::GlobalPool::connect_all().await;
                         }
-                        return Err(err.into());
+                        Err(classify_error(err))
                     }
-                    _ => break,
                 }
-            }
-            Ok(rows)
+            })
         }
 
-        async fn fetch_one(self, sql: &str) -> Result<Self::Row, Error> {
-            match sqlx::query(sql).fetch_one(self).await {
-                Ok(row) => Ok(row),
-                Err(err) => {
-                    if matches!(err, sqlx::error::Error::PoolTimedOut) {
-                        super::GlobalPool::connect_all().await;
+        async fn fetch_one_with<T: ToString>(
+            self,
+            sql: &str,
+            arguments: &[T],
+        ) -> Result<Self::Row, Error> {
+            self.fetch_one_with_values(sql, &string_values(arguments))
+                .await
+        }
+
+        async fn fetch_one_with_values(
+            self,
+            sql: &str,
+            arguments: &[JsonValue],
+        ) -> Result<Self::Row, Error> {
+            traced_statement!(sql, {
+                let query = sqlx::query_with(sql, encode_arguments(arguments)?);
+                match query.fetch_one(self).await {
+                    Ok(row) => Ok(row),
+                    Err(err) => {
+                        if matches!(err, sqlx::error::Error::PoolTimedOut) {
+                            super::GlobalPool::connect_all().await;
+                        }
+                        Err(classify_error(err))
                     }
-                    Err(err.into())
                 }
-            }
+            })
         }
 
         async fn fetch_optional(self, sql: &str) -> Result<Option<Self::Row>, Error> {
-            match sqlx::query(sql).fetch_optional(self).await {
-                Ok(row) => Ok(row),
-                Err(err) => {
-                    if matches!(err, sqlx::error::Error::PoolTimedOut) {
-                        super::GlobalPool::connect_all().await;
+            traced_statement!(sql, {
+                match sqlx::query(sql).fetch_optional(self).await {
+                    Ok(row) => Ok(row),
+                    Err(err) => {
+                        if matches!(err, sqlx::error::Error::PoolTimedOut) {
+                            super::GlobalPool::connect_all().await;
+                        }
+                        Err(classify_error(err))
                     }
-                    Err(err.into())
                 }
-            }
+            })
         }
 
         async fn fetch_optional_with<T: ToString>(
@@ -168,19 +461,27 @@ macro_rules! impl_sqlx_executor {
             sql: &str,
             arguments: &[T],
         ) -> Result<Option<Self::Row>, Error> {
-            let mut query = sqlx::query(sql);
-            for arg in arguments {
-                query = query.bind(arg.to_string());
-            }
-            match query.fetch_optional(self).await {
-                Ok(row) => Ok(row),
-                Err(err) => {
-                    if matches!(err, sqlx::error::Error::PoolTimedOut) {
-                        super::GlobalPool::connect_all().await;
+            self.fetch_optional_with_values(sql, &string_values(arguments))
+                .await
+        }
+
+        async fn fetch_optional_with_values(
+            self,
+            sql: &str,
+            arguments: &[JsonValue],
+        ) -> Result<Option<Self::Row>, Error> {
+            traced_statement!(sql, {
+                let query = sqlx::query_with(sql, encode_arguments(arguments)?);
+                match query.fetch_optional(self).await {
+                    Ok(row) => Ok(row),
+                    Err(err) => {
+                        if matches!(err, sqlx::error::Error::PoolTimedOut) {
+                            super::GlobalPool::connect_all().await;
+                        }
+                        Err(classify_error(err))
                     }
-                    Err(err.into())
                 }
-            }
+            })
         }
     };
 }
@@ -194,3 +495,111 @@ impl Executor for &sqlx::Pool<super::DatabaseDriver> {
 impl Executor for &mut super::DatabaseConnection {
     impl_sqlx_executor!();
 }
+
+/// Executes the statement on a connection acquired from the pool,
+/// which is guarded against the cancellation and the statement timeout.
+#[cfg(feature = "orm-sqlx")]
+macro_rules! guarded_statement {
+    ($pool:expr, $sql:expr, $method:ident($($arg:expr),*)) => {{
+        let connection = $pool.acquire().await?;
+        let mut guard = super::timeout::StatementGuard::new(connection, $sql).await?;
+        let sql = guard.prepare_statement($sql);
+        let result = guard.connection().$method(&sql $(, $arg)*).await;
+        guard.finish(result).await
+    }};
+}
+
+/// Statements executed via the connection pool are guarded: if the future is dropped
+/// before completion, the in-flight statement is cancelled and the connection is closed
+/// instead of being returned to the pool. The statement timeout set by
+/// [`Query::set_timeout()`](zino_core::model::Query::set_timeout) is also applied.
+#[cfg(feature = "orm-sqlx")]
+impl Executor for &super::ConnectionPool {
+    type Row = super::DatabaseRow;
+    type QueryResult = <super::DatabaseDriver as sqlx::Database>::QueryResult;
+
+    async fn execute(self, sql: &str) -> Result<Self::QueryResult, Error> {
+        let _permit = self.acquire_write_permit().await?;
+        guarded_statement!(self, sql, execute())
+    }
+
+    async fn execute_with<T: ToString>(
+        self,
+        sql: &str,
+        arguments: &[T],
+    ) -> Result<Self::QueryResult, Error> {
+        self.execute_with_values(sql, &string_values(arguments))
+            .await
+    }
+
+    async fn execute_with_values(
+        self,
+        sql: &str,
+        arguments: &[JsonValue],
+    ) -> Result<Self::QueryResult, Error> {
+        let _permit = self.acquire_write_permit().await?;
+        guarded_statement!(self, sql, execute_with_values(arguments))
+    }
+
+    async fn fetch(self, sql: &str) -> Result<Vec<Self::Row>, Error> {
+        guarded_statement!(self, sql, fetch())
+    }
+
+    async fn fetch_with<T: ToString>(
+        self,
+        sql: &str,
+        arguments: &[T],
+    ) -> Result<Vec<Self::Row>, Error> {
+        self.fetch_with_values(sql, &string_values(arguments)).await
+    }
+
+    async fn fetch_with_values(
+        self,
+        sql: &str,
+        arguments: &[JsonValue],
+    ) -> Result<Vec<Self::Row>, Error> {
+        guarded_statement!(self, sql, fetch_with_values(arguments))
+    }
+
+    async fn fetch_one(self, sql: &str) -> Result<Self::Row, Error> {
+        guarded_statement!(self, sql, fetch_one())
+    }
+
+    async fn fetch_one_with<T: ToString>(
+        self,
+        sql: &str,
+        arguments: &[T],
+    ) -> Result<Self::Row, Error> {
+        self.fetch_one_with_values(sql, &string_values(arguments))
+            .await
+    }
+
+    async fn fetch_one_with_values(
+        self,
+        sql: &str,
+        arguments: &[JsonValue],
+    ) -> Result<Self::Row, Error> {
+        guarded_statement!(self, sql, fetch_one_with_values(arguments))
+    }
+
+    async fn fetch_optional(self, sql: &str) -> Result<Option<Self::Row>, Error> {
+        guarded_statement!(self, sql, fetch_optional())
+    }
+
+    async fn fetch_optional_with<T: ToString>(
+        self,
+        sql: &str,
+        arguments: &[T],
+    ) -> Result<Option<Self::Row>, Error> {
+        self.fetch_optional_with_values(sql, &string_values(arguments))
+            .await
+    }
+
+    async fn fetch_optional_with_values(
+        self,
+        sql: &str,
+        arguments: &[JsonValue],
+    ) -> Result<Option<Self::Row>, Error> {
+        guarded_statement!(self, sql, fetch_optional_with_values(arguments))
+    }
+}
@@ -0,0 +1,389 @@
+use super::{
+    decode, executor::Executor, query::QueryExt, DatabaseRow, DecodeRow, GlobalPool, Schema,
+};
+use std::{
+    sync::atomic::{AtomicBool, Ordering::Relaxed},
+    time::Duration,
+};
+use zino_core::{
+    datetime::DateTime, error::Error, extension::TomlTableExt, model::Query, state::State, warn,
+    LazyLock, Map, Uuid,
+};
+
+/// An async export job persisted in the `_zino_export_jobs` table.
+///
+/// The job is created with the model name, the query filters and the format,
+/// then claimed by a worker which streams the query results into a file.
+/// The status transitions from `pending` to `running`, and ends up with
+/// `succeeded`, `failed` or `cancelled`.
+///
+/// The database can be configured in the `[export]` table:
+///
+/// ```toml
+/// [export]
+/// database = "main"
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ExportJob {
+    /// Job ID.
+    id: String,
+    /// Model name.
+    model_name: String,
+    /// Query filters.
+    filters: Map,
+    /// Export format.
+    format: String,
+    /// Status.
+    status: String,
+    /// Number of the exported rows.
+    progress: i64,
+    /// Path of the result file.
+    result_path: Option<String>,
+    /// Error message.
+    error_message: Option<String>,
+    /// Creation time in milliseconds.
+    created_at: i64,
+    /// Update time in milliseconds.
+    updated_at: i64,
+}
+
+impl ExportJob {
+    /// Creates a new instance with the `pending` status.
+    pub fn new(model_name: impl Into<String>, filters: Map, format: impl Into<String>) -> Self {
+        let now = DateTime::now().timestamp_millis();
+        Self {
+            id: Uuid::now_v7().to_string(),
+            model_name: model_name.into(),
+            filters,
+            format: format.into(),
+            status: "pending".to_owned(),
+            progress: 0,
+            result_path: None,
+            error_message: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Returns the job ID.
+    #[inline]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the model name.
+    #[inline]
+    pub fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    /// Returns the query filters.
+    #[inline]
+    pub fn filters(&self) -> &Map {
+        &self.filters
+    }
+
+    /// Returns the export format.
+    #[inline]
+    pub fn format(&self) -> &str {
+        &self.format
+    }
+
+    /// Returns the status.
+    #[inline]
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    /// Returns the number of the exported rows.
+    #[inline]
+    pub fn progress(&self) -> i64 {
+        self.progress
+    }
+
+    /// Returns the path of the result file.
+    #[inline]
+    pub fn result_path(&self) -> Option<&str> {
+        self.result_path.as_deref()
+    }
+
+    /// Returns the error message.
+    #[inline]
+    pub fn error_message(&self) -> Option<&str> {
+        self.error_message.as_deref()
+    }
+
+    /// Returns the creation time.
+    #[inline]
+    pub fn created_at(&self) -> DateTime {
+        DateTime::from_timestamp_millis(self.created_at)
+    }
+
+    /// Returns the update time.
+    #[inline]
+    pub fn updated_at(&self) -> DateTime {
+        DateTime::from_timestamp_millis(self.updated_at)
+    }
+
+    /// Returns `true` if the job has succeeded.
+    #[inline]
+    pub fn is_succeeded(&self) -> bool {
+        self.status == "succeeded"
+    }
+
+    /// Returns `true` if the job has finished regardless of the outcome.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        matches!(self.status.as_str(), "succeeded" | "failed" | "cancelled")
+    }
+
+    /// Converts `self` to a JSON object.
+    pub fn to_map(&self) -> Map {
+        let mut map = Map::new();
+        map.insert("id".to_owned(), self.id.clone().into());
+        map.insert("model_name".to_owned(), self.model_name.clone().into());
+        map.insert("filters".to_owned(), self.filters.clone().into());
+        map.insert("format".to_owned(), self.format.clone().into());
+        map.insert("status".to_owned(), self.status.clone().into());
+        map.insert("progress".to_owned(), self.progress.into());
+        if let Some(error_message) = self.error_message.as_ref() {
+            map.insert("error_message".to_owned(), error_message.clone().into());
+        }
+        map.insert("created_at".to_owned(), self.created_at().into());
+        map.insert("updated_at".to_owned(), self.updated_at().into());
+        map
+    }
+
+    /// Inserts the job into the table.
+    pub async fn insert(&self) -> Result<(), Error> {
+        create_table().await?;
+
+        let id = Query::escape_string(&self.id);
+        let model_name = Query::escape_string(&self.model_name);
+        let filters = Query::escape_string(serde_json::to_string(&self.filters)?);
+        let format = Query::escape_string(&self.format);
+        let status = Query::escape_string(&self.status);
+        let created_at = self.created_at;
+        let updated_at = self.updated_at;
+        let sql = format!(
+            "INSERT INTO {EXPORT_JOBS_TABLE} \
+                (id, model_name, filters, format, status, progress, created_at, updated_at) \
+                VALUES ({id}, {model_name}, {filters}, {format}, {status}, 0, \
+                    {created_at}, {updated_at});"
+        );
+        connection_pool()?.pool().execute(&sql).await?;
+        Ok(())
+    }
+
+    /// Finds a job by the ID.
+    pub async fn find_by_id(id: &str) -> Result<Option<Self>, Error> {
+        create_table().await?;
+
+        let id = Query::escape_string(id);
+        let sql = format!("SELECT * FROM {EXPORT_JOBS_TABLE} WHERE id = {id};");
+        if let Some(row) = connection_pool()?.pool().fetch_optional(&sql).await? {
+            Self::decode_row(&row).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Claims the oldest pending job of the model by setting the status to `running`.
+    /// It returns `None` if there are no pending jobs or the job is claimed by others.
+    pub async fn claim_pending(model_name: &str) -> Result<Option<Self>, Error> {
+        create_table().await?;
+
+        let pool = connection_pool()?.pool();
+        let escaped_model_name = Query::escape_string(model_name);
+        let sql = format!(
+            "SELECT * FROM {EXPORT_JOBS_TABLE} \
+                WHERE model_name = {escaped_model_name} AND status = 'pending' \
+                ORDER BY created_at ASC LIMIT 1;"
+        );
+        let Some(row) = pool.fetch_optional(&sql).await? else {
+            return Ok(None);
+        };
+
+        let mut job = Self::decode_row(&row)?;
+        let id = Query::escape_string(&job.id);
+        let now = DateTime::now().timestamp_millis();
+        let sql = format!(
+            "UPDATE {EXPORT_JOBS_TABLE} SET status = 'running', updated_at = {now} \
+                WHERE id = {id} AND status = 'pending';"
+        );
+        if pool.execute(&sql).await?.rows_affected() == 1 {
+            job.status = "running".to_owned();
+            job.updated_at = now;
+            Ok(Some(job))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Reports the progress of a running job.
+    /// It returns `false` if the job is no longer running, i.e. it has been cancelled.
+    pub async fn report_progress(id: &str, progress: i64) -> Result<bool, Error> {
+        let id = Query::escape_string(id);
+        let now = DateTime::now().timestamp_millis();
+        let sql = format!(
+            "UPDATE {EXPORT_JOBS_TABLE} SET progress = {progress}, updated_at = {now} \
+                WHERE id = {id} AND status = 'running';"
+        );
+        let query_result = connection_pool()?.pool().execute(&sql).await?;
+        Ok(query_result.rows_affected() == 1)
+    }
+
+    /// Marks a running job as succeeded with the result path.
+    /// It returns `false` if the job is no longer running.
+    pub async fn complete(id: &str, result_path: &str, progress: i64) -> Result<bool, Error> {
+        let id = Query::escape_string(id);
+        let result_path = Query::escape_string(result_path);
+        let now = DateTime::now().timestamp_millis();
+        let sql = format!(
+            "UPDATE {EXPORT_JOBS_TABLE} SET status = 'succeeded', progress = {progress}, \
+                result_path = {result_path}, updated_at = {now} \
+                WHERE id = {id} AND status = 'running';"
+        );
+        let query_result = connection_pool()?.pool().execute(&sql).await?;
+        Ok(query_result.rows_affected() == 1)
+    }
+
+    /// Marks a running job as failed with the error message.
+    pub async fn fail(id: &str, error_message: &str) -> Result<(), Error> {
+        let id = Query::escape_string(id);
+        let error_message = Query::escape_string(error_message);
+        let now = DateTime::now().timestamp_millis();
+        let sql = format!(
+            "UPDATE {EXPORT_JOBS_TABLE} SET status = 'failed', \
+                error_message = {error_message}, updated_at = {now} \
+                WHERE id = {id} AND status = 'running';"
+        );
+        connection_pool()?.pool().execute(&sql).await?;
+        Ok(())
+    }
+
+    /// Cancels a pending or running job.
+    /// It returns `false` if the job does not exist or has finished.
+    pub async fn cancel(id: &str) -> Result<bool, Error> {
+        create_table().await?;
+
+        let id = Query::escape_string(id);
+        let now = DateTime::now().timestamp_millis();
+        let sql = format!(
+            "UPDATE {EXPORT_JOBS_TABLE} SET status = 'cancelled', updated_at = {now} \
+                WHERE id = {id} AND status IN ('pending', 'running');"
+        );
+        let query_result = connection_pool()?.pool().execute(&sql).await?;
+        Ok(query_result.rows_affected() == 1)
+    }
+
+    /// Lists the finished jobs which have not been updated within the retention period.
+    pub async fn list_expired(retention: Duration) -> Result<Vec<Self>, Error> {
+        create_table().await?;
+
+        let retention_millis = i64::try_from(retention.as_millis())?;
+        let deadline = DateTime::now().timestamp_millis() - retention_millis;
+        let sql = format!(
+            "SELECT * FROM {EXPORT_JOBS_TABLE} \
+                WHERE status IN ('succeeded', 'failed', 'cancelled') \
+                AND updated_at < {deadline};"
+        );
+        let rows = connection_pool()?.pool().fetch(&sql).await?;
+        rows.iter().map(Self::decode_row).collect()
+    }
+
+    /// Deletes a job by the ID.
+    pub async fn delete_by_id(id: &str) -> Result<(), Error> {
+        let id = Query::escape_string(id);
+        let sql = format!("DELETE FROM {EXPORT_JOBS_TABLE} WHERE id = {id};");
+        connection_pool()?.pool().execute(&sql).await?;
+        Ok(())
+    }
+
+    /// Fetches a batch of the model data selected by the query without running the hooks,
+    /// so that the returned future is `Send` and can be executed by the async jobs.
+    pub async fn fetch_batch<M: Schema>(query: &Query) -> Result<Vec<Map>, Error> {
+        let table_name = query.format_table_name::<M>();
+        let projection = query.format_table_fields::<M>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<M>(&mut arguments);
+        let sort = query.format_sort();
+        let pagination = query.format_pagination();
+        let sql = format!("SELECT {projection} FROM {table_name} {filters} {sort} {pagination};");
+        let pool = M::init_reader()?.pool();
+
+        // The batch is bounded by the limit, which should never be truncated.
+        let (rows, _) =
+            super::with_max_rows(Some(0), pool.fetch_with_values(&sql, &arguments)).await;
+        rows?.iter().map(Map::decode_row).collect()
+    }
+}
+
+impl DecodeRow<DatabaseRow> for ExportJob {
+    type Error = Error;
+
+    fn decode_row(row: &DatabaseRow) -> Result<Self, Self::Error> {
+        let filters = decode::<String>(row, "filters")?;
+        Ok(Self {
+            id: decode(row, "id")?,
+            model_name: decode(row, "model_name")?,
+            filters: serde_json::from_str(&filters)?,
+            format: decode(row, "format")?,
+            status: decode(row, "status")?,
+            progress: decode(row, "progress")?,
+            result_path: decode(row, "result_path")?,
+            error_message: decode(row, "error_message")?,
+            created_at: decode(row, "created_at")?,
+            updated_at: decode(row, "updated_at")?,
+        })
+    }
+}
+
+/// Creates the `_zino_export_jobs` table if it does not exist.
+async fn create_table() -> Result<(), Error> {
+    if EXPORT_JOBS_TABLE_CREATED.load(Relaxed) || !super::AUTO_MIGRATION.load(Relaxed) {
+        return Ok(());
+    }
+
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {EXPORT_JOBS_TABLE} (\n  \
+            id VARCHAR(36) NOT NULL PRIMARY KEY,\n  \
+            model_name VARCHAR(255) NOT NULL,\n  \
+            filters TEXT NOT NULL,\n  \
+            format VARCHAR(16) NOT NULL,\n  \
+            status VARCHAR(16) NOT NULL,\n  \
+            progress BIGINT NOT NULL DEFAULT 0,\n  \
+            result_path TEXT,\n  \
+            error_message TEXT,\n  \
+            created_at BIGINT NOT NULL,\n  \
+            updated_at BIGINT NOT NULL\n\
+        );"
+    );
+    let pool = connection_pool()?.pool();
+    if let Err(err) = pool.execute(&sql).await {
+        tracing::error!(table_name = EXPORT_JOBS_TABLE, "fail to execute `{sql}`");
+        return Err(err);
+    }
+    EXPORT_JOBS_TABLE_CREATED.store(true, Relaxed);
+    Ok(())
+}
+
+/// Returns the connection pool.
+fn connection_pool() -> Result<&'static super::ConnectionPool, Error> {
+    GlobalPool::get(*EXPORT_DATABASE)
+        .ok_or_else(|| warn!("connection to the database is unavailable"))
+}
+
+/// Table name for the export jobs.
+const EXPORT_JOBS_TABLE: &str = "_zino_export_jobs";
+
+/// Flag to indicate whether the table for the export jobs has been created.
+static EXPORT_JOBS_TABLE_CREATED: AtomicBool = AtomicBool::new(false);
+
+/// Name of the connection pool for the export jobs.
+static EXPORT_DATABASE: LazyLock<&'static str> = LazyLock::new(|| {
+    State::shared()
+        .get_config("export")
+        .and_then(|config| config.get_str("database"))
+        .unwrap_or("main")
+});
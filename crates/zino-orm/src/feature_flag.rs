@@ -0,0 +1,267 @@
+use super::{
+    decode, decode_optional, executor::Executor, ttl::interval_cron_expr, DatabaseRow, DecodeRow,
+    GlobalPool,
+};
+use std::{
+    sync::atomic::{AtomicBool, Ordering::Relaxed},
+    time::Duration,
+};
+use zino_core::{
+    application::FeatureFlag,
+    error::Error,
+    extension::TomlTableExt,
+    schedule::{AsyncJob, JobContext, OverlapPolicy},
+    state::State,
+    warn, BoxFuture, LazyLock,
+};
+
+/// A source of the feature flags persisted in the `_zino_feature_flags` table,
+/// which overrides the flags defined in the `[features]` table.
+///
+/// The table is polled by the [`sync_job()`](Self::sync_job), so that the flags can be flipped
+/// by updating the rows without restarting the application. The allowlists of the users
+/// and groups are stored as comma-separated values.
+///
+/// It can be configured in the `[feature-flag]` table:
+///
+/// ```toml
+/// [feature-flag]
+/// database = "main"
+/// poll-interval = "30s"
+/// ```
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino_orm::FeatureFlagTable;
+///
+/// let mut scheduler = AsyncJobScheduler::new();
+/// scheduler.add(FeatureFlagTable::sync_job());
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FeatureFlagTable;
+
+impl FeatureFlagTable {
+    /// Loads the flags from the table and applies them as the overrides.
+    /// Returns the number of flags loaded.
+    pub async fn sync() -> Result<usize, Error> {
+        create_table().await?;
+
+        let sql = format!(
+            "SELECT name, enabled, percentage, allowed_users, allowed_groups, description \
+                FROM {FEATURE_FLAGS_TABLE};"
+        );
+        let rows = connection_pool()?.pool().fetch(&sql).await?;
+        let mut flags = Vec::with_capacity(rows.len());
+        for row in rows.iter() {
+            flags.push(FeatureFlagRow::decode_row(row)?.into());
+        }
+
+        let num_flags = flags.len();
+        FeatureFlag::apply_overrides("database", flags);
+        Ok(num_flags)
+    }
+
+    /// Creates an async job which polls the table for the flags every `poll-interval`.
+    /// It also runs immediately so that the overrides are applied at startup.
+    pub fn sync_job() -> AsyncJob {
+        let cron_expr = interval_cron_expr(FEATURE_FLAG_SETTINGS.poll_interval);
+        AsyncJob::new(&cron_expr, sync_flags)
+            .name("feature_flag_sync")
+            .overlap_policy(OverlapPolicy::Skip)
+            .immediate(true)
+    }
+}
+
+/// A row of the feature flags table.
+#[derive(Debug, Default)]
+struct FeatureFlagRow {
+    /// Flag name.
+    name: String,
+    /// A flag to indicate whether it is enabled.
+    enabled: bool,
+    /// Percentage of the rollout.
+    percentage: Option<f64>,
+    /// Comma-separated allowlist of the users.
+    allowed_users: Option<String>,
+    /// Comma-separated allowlist of the groups.
+    allowed_groups: Option<String>,
+    /// Description.
+    description: Option<String>,
+}
+
+impl DecodeRow<DatabaseRow> for FeatureFlagRow {
+    type Error = Error;
+
+    fn decode_row(row: &DatabaseRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            name: decode(row, "name")?,
+            enabled: decode(row, "enabled")?,
+            percentage: decode_optional(row, "percentage")?,
+            allowed_users: decode_optional(row, "allowed_users")?,
+            allowed_groups: decode_optional(row, "allowed_groups")?,
+            description: decode_optional(row, "description")?,
+        })
+    }
+}
+
+impl From<FeatureFlagRow> for FeatureFlag {
+    fn from(row: FeatureFlagRow) -> Self {
+        let mut flag = FeatureFlag::new(row.name);
+        flag.set_enabled(row.enabled);
+        if let Some(percentage) = row.percentage {
+            flag.set_percentage(percentage);
+        }
+        if let Some(users) = row.allowed_users {
+            flag.set_users(split_values(&users));
+        }
+        if let Some(groups) = row.allowed_groups {
+            flag.set_groups(split_values(&groups));
+        }
+        if let Some(description) = row.description {
+            flag.set_description(description);
+        }
+        flag
+    }
+}
+
+/// Splits the comma-separated values.
+fn split_values(s: &str) -> Vec<String> {
+    s.split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_owned())
+        .collect()
+}
+
+/// Polls the table for the flags.
+fn sync_flags(_ctx: &mut JobContext) -> BoxFuture<'_> {
+    Box::pin(async {
+        if let Err(err) = FeatureFlagTable::sync().await {
+            tracing::error!("fail to sync the feature flags: {err}");
+        }
+    })
+}
+
+/// Creates the `_zino_feature_flags` table if it does not exist.
+async fn create_table() -> Result<(), Error> {
+    if FEATURE_FLAGS_TABLE_CREATED.load(Relaxed) || !super::AUTO_MIGRATION.load(Relaxed) {
+        return Ok(());
+    }
+
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {FEATURE_FLAGS_TABLE} (\n  \
+            name VARCHAR(255) NOT NULL PRIMARY KEY,\n  \
+            enabled BOOLEAN NOT NULL DEFAULT TRUE,\n  \
+            percentage DOUBLE PRECISION,\n  \
+            allowed_users TEXT,\n  \
+            allowed_groups TEXT,\n  \
+            description TEXT\n\
+        );"
+    );
+    let pool = connection_pool()?.pool();
+    if let Err(err) = pool.execute(&sql).await {
+        tracing::error!(table_name = FEATURE_FLAGS_TABLE, "fail to execute `{sql}`");
+        return Err(err);
+    }
+    FEATURE_FLAGS_TABLE_CREATED.store(true, Relaxed);
+    Ok(())
+}
+
+/// Returns the connection pool.
+fn connection_pool() -> Result<&'static super::ConnectionPool, Error> {
+    GlobalPool::get(FEATURE_FLAG_SETTINGS.database)
+        .ok_or_else(|| warn!("connection to the database is unavailable"))
+}
+
+/// Table name for the feature flags.
+const FEATURE_FLAGS_TABLE: &str = "_zino_feature_flags";
+
+/// Flag to indicate whether the table for the feature flags has been created.
+static FEATURE_FLAGS_TABLE_CREATED: AtomicBool = AtomicBool::new(false);
+
+/// Settings for the feature flags table.
+#[derive(Debug)]
+struct FeatureFlagSettings {
+    /// Name of the connection pool.
+    database: &'static str,
+    /// Interval for polling the table.
+    poll_interval: Duration,
+}
+
+/// Shared settings for the feature flags table.
+static FEATURE_FLAG_SETTINGS: LazyLock<FeatureFlagSettings> = LazyLock::new(|| {
+    let config = State::shared().get_config("feature-flag");
+    FeatureFlagSettings {
+        database: config
+            .and_then(|config| config.get_str("database"))
+            .unwrap_or("main"),
+        poll_interval: config
+            .and_then(|config| config.get_duration("poll-interval"))
+            .unwrap_or(Duration::from_secs(30)),
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::split_values;
+
+    #[test]
+    fn it_splits_values() {
+        assert_eq!(split_values("alice, bob,,"), vec!["alice", "bob"]);
+        assert!(split_values(" ").is_empty());
+    }
+
+    #[cfg(not(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb",
+        feature = "orm-postgres"
+    )))]
+    #[tokio::test]
+    async fn it_decodes_feature_flag_rows() {
+        use super::{FeatureFlagRow, FEATURE_FLAGS_TABLE};
+        use crate::DecodeRow;
+        use sqlx::{sqlite::SqliteConnectOptions, Executor, SqlitePool};
+        use zino_core::application::FeatureFlag;
+
+        let pool =
+            SqlitePool::connect_with("sqlite::memory:".parse::<SqliteConnectOptions>().unwrap())
+                .await
+                .expect("fail to connect to the in-memory database");
+        let sql = format!(
+            "CREATE TABLE {FEATURE_FLAGS_TABLE} (
+                name VARCHAR(255) NOT NULL PRIMARY KEY,
+                enabled BOOLEAN NOT NULL DEFAULT TRUE,
+                percentage DOUBLE PRECISION,
+                allowed_users TEXT,
+                allowed_groups TEXT,
+                description TEXT
+            );
+            INSERT INTO {FEATURE_FLAGS_TABLE} VALUES
+                ('new-search', TRUE, 2.5, 'alice, bob,', 'beta', 'The new search engine'),
+                ('dark-mode', FALSE, NULL, NULL, NULL, NULL);"
+        );
+        pool.execute(sql.as_str()).await.unwrap();
+
+        let sql = format!("SELECT * FROM {FEATURE_FLAGS_TABLE} ORDER BY name;");
+        let flags = pool
+            .fetch_all(sql.as_str())
+            .await
+            .unwrap()
+            .iter()
+            .map(|row| FeatureFlagRow::decode_row(row).map(FeatureFlag::from))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let mut dark_mode = FeatureFlag::new("dark-mode");
+        dark_mode.set_enabled(false);
+
+        let mut new_search = FeatureFlag::new("new-search");
+        new_search.set_percentage(2.5);
+        new_search.set_users(vec!["alice".to_owned(), "bob".to_owned()]);
+        new_search.set_groups(vec!["beta".to_owned()]);
+        new_search.set_description("The new search engine");
+        assert_eq!(flags, [dark_mode, new_search]);
+    }
+}
@@ -1,10 +1,11 @@
-use super::Schema;
+use super::{computed, Schema};
 use std::fmt::Display;
 use zino_core::{
     crypto,
     encoding::base64,
     error::Error,
     extension::{JsonObjectExt, TomlTableExt},
+    model::Query,
     state::State,
     warn, LazyLock, Map,
 };
@@ -65,6 +66,38 @@ where
             }
         }
     }
+
+    /// Replaces the computed fields in the projection with the columns they depend on.
+    #[inline]
+    fn expand_computed_fields(query: &mut Query) {
+        computed::expand_fields(query, Self::computed_fields());
+    }
+
+    /// Computes the values of the computed fields and inserts them into the model data.
+    /// It should be called once for all the models in a response,
+    /// so that the async computed fields are evaluated in a batch.
+    async fn compute_fields(models: &mut [Map]) -> Result<(), Error> {
+        for field in Self::computed_fields() {
+            field.compute(models).await?;
+        }
+        Ok(())
+    }
+
+    /// Redacts the model data before it is sent out.
+    /// The `write_only` and `private` fields are removed,
+    /// and the values of `masked` fields are replaced with the masked forms.
+    fn redact_model(model: &mut Map) {
+        for col in Self::columns() {
+            let field = col.name();
+            if col.is_write_only() {
+                model.remove(field);
+            } else if let Some(value) = model.get_mut(field) {
+                if let Some(masked_value) = col.mask_value(value) {
+                    *value = masked_value;
+                }
+            }
+        }
+    }
 }
 
 impl<M, K> ModelHelper<K> for M
@@ -75,7 +108,7 @@ where
 }
 
 /// Secret key.
-static SECRET_KEY: LazyLock<[u8; 64]> = LazyLock::new(|| {
+pub(crate) static SECRET_KEY: LazyLock<[u8; 64]> = LazyLock::new(|| {
     let app_config = State::shared().config();
     let config = app_config.get_table("database").unwrap_or(app_config);
     let checksum: [u8; 32] = config
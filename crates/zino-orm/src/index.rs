@@ -0,0 +1,517 @@
+use super::column::ColumnExt;
+use regex::Regex;
+use zino_core::{extension::JsonObjectExt, model::Column, LazyLock, Map};
+
+/// An index definition for the model.
+///
+/// The single-column indexes are derived from `#[schema(index = "type")]` on the struct fields,
+/// and the composite indexes are declared by `#[schema(composite_index = "columns")]`
+/// on the struct. Both of them support the following options:
+///
+/// - `index_where`: the predicate of a partial index. It is ignored by MySQL.
+/// - `index_include`: the covering columns. It is only supported by PostgreSQL.
+/// - `index_name`: the index name which overrides the default `{table}_{columns}_index`.
+///
+/// A column declared by `#[schema(unique, case_insensitive)]` has a unique index
+/// on the lowercase values, such as `CREATE UNIQUE INDEX ... (LOWER(email))`.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino_derive::{Model, Schema};
+///
+/// #[derive(Default, Model, Schema)]
+/// #[schema(
+///     composite_index = "org_id, created_at desc",
+///     index_include = "name",
+///     index_where = "status <> 'Deleted'"
+/// )]
+/// pub struct Order {
+///     #[schema(primary_key)]
+///     id: Uuid,
+///     #[schema(index = "btree", index_where = "status <> 'Deleted'")]
+///     status: String,
+///     org_id: Uuid,
+///     name: String,
+///     created_at: DateTime,
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Index {
+    /// Index type.
+    index_type: Option<String>,
+    /// Indexed columns with the optional sort orders.
+    columns: Vec<String>,
+    /// Covering columns.
+    include_columns: Vec<String>,
+    /// Predicate of a partial index.
+    predicate: Option<String>,
+    /// Custom index name.
+    name: Option<String>,
+    /// A flag to index the lowercase values of the columns.
+    case_insensitive: bool,
+}
+
+impl Index {
+    /// Creates a new instance with the comma-separated columns,
+    /// where each column can be followed by a sort order such as `created_at desc`.
+    pub fn new(columns: &str) -> Self {
+        Self {
+            columns: parse_columns(columns),
+            ..Self::default()
+        }
+    }
+
+    /// Creates a new instance for the column with an index type.
+    ///
+    /// It returns `None` if the column is not indexed or the index is used for the text search,
+    /// which is created separately. A unique index is implied for the case-insensitive column
+    /// with the `unique` attribute.
+    pub fn with_column(col: &Column<'_>) -> Option<Self> {
+        let case_insensitive = col.is_case_insensitive();
+        let index_type = col
+            .index_type()
+            .or_else(|| (case_insensitive && col.has_attribute("unique")).then_some("unique"))?;
+        let is_text_search = if cfg!(any(
+            feature = "orm-mariadb",
+            feature = "orm-mysql",
+            feature = "orm-tidb"
+        )) {
+            matches!(index_type, "fulltext" | "text")
+        } else if cfg!(feature = "orm-postgres") {
+            index_type.starts_with("text")
+        } else {
+            false
+        };
+        if is_text_search {
+            return None;
+        }
+
+        let column_name = col.name();
+        let column = if cfg!(feature = "orm-postgres") && index_type == "btree" {
+            format!("{column_name} DESC")
+        } else {
+            column_name.to_owned()
+        };
+        let extra = col.extra();
+        let mut index = Self {
+            index_type: Some(index_type.to_owned()),
+            columns: vec![column],
+            case_insensitive: case_insensitive && index_type == "unique",
+            ..Self::default()
+        };
+        index.read_options(extra);
+        Some(index)
+    }
+
+    /// Sets the index type.
+    #[inline]
+    pub fn set_index_type(&mut self, index_type: &str) {
+        self.index_type = (!index_type.is_empty()).then(|| index_type.to_owned());
+    }
+
+    /// Sets the comma-separated covering columns.
+    #[inline]
+    pub fn set_include_columns(&mut self, columns: &str) {
+        self.include_columns = parse_columns(columns);
+    }
+
+    /// Sets the predicate of a partial index.
+    #[inline]
+    pub fn set_predicate(&mut self, predicate: &str) {
+        let predicate = predicate.trim();
+        self.predicate = (!predicate.is_empty()).then(|| predicate.to_owned());
+    }
+
+    /// Sets the index name.
+    #[inline]
+    pub fn set_name(&mut self, name: &str) {
+        let name = name.trim();
+        self.name = (!name.is_empty()).then(|| name.to_owned());
+    }
+
+    /// Sets the flag to index the lowercase values of the columns.
+    #[inline]
+    pub fn set_case_insensitive(&mut self, case_insensitive: bool) {
+        self.case_insensitive = case_insensitive;
+    }
+
+    /// Returns the index type.
+    #[inline]
+    pub fn index_type(&self) -> Option<&str> {
+        self.index_type.as_deref()
+    }
+
+    /// Returns the indexed columns with the optional sort orders.
+    #[inline]
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Returns the names of the indexed columns without the sort orders.
+    #[inline]
+    pub fn column_names(&self) -> Vec<&str> {
+        self.columns
+            .iter()
+            .map(|column| column_name(column))
+            .collect()
+    }
+
+    /// Returns the covering columns.
+    #[inline]
+    pub fn include_columns(&self) -> &[String] {
+        &self.include_columns
+    }
+
+    /// Returns the predicate of a partial index.
+    #[inline]
+    pub fn predicate(&self) -> Option<&str> {
+        self.predicate.as_deref()
+    }
+
+    /// Returns `true` if the lowercase values of the columns are indexed.
+    #[inline]
+    pub fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    /// Returns `true` if it is a unique index.
+    #[inline]
+    pub fn is_unique(&self) -> bool {
+        self.index_type() == Some("unique")
+    }
+
+    /// Returns the index name for the table.
+    pub fn name(&self, table_name: &str) -> String {
+        if let Some(name) = self.name.as_ref() {
+            return name.to_owned();
+        }
+
+        let column_names = self.column_names().join("_");
+        format!("{table_name}_{column_names}_index")
+    }
+
+    /// Reads the index options from the attributes.
+    fn read_options(&mut self, options: &Map) {
+        if let Some(predicate) = options.get_str("index_where") {
+            self.set_predicate(predicate);
+        }
+        if let Some(columns) = options.get_str("index_include") {
+            self.set_include_columns(columns);
+        }
+        if let Some(name) = options.get_str("index_name") {
+            self.set_name(name);
+        }
+    }
+
+    /// Formats the SQL statement to create the index.
+    ///
+    /// The options unsupported by the database driver are ignored with a warning.
+    /// It returns `None` if the index type is unsupported.
+    pub(crate) fn format_create_sql(
+        &self,
+        table_name: &str,
+        table_name_escaped: &str,
+    ) -> Option<String> {
+        let index_name = self.name(table_name);
+        let is_mysql = cfg!(any(
+            feature = "orm-mariadb",
+            feature = "orm-mysql",
+            feature = "orm-tidb"
+        ));
+        if let Some(predicate) = self.predicate().filter(|_| is_mysql) {
+            tracing::warn!(
+                table_name,
+                index_name,
+                predicate,
+                "partial indexes are unsupported, the predicate of `{index_name}` is ignored",
+            );
+        }
+        if !(self.include_columns.is_empty() || cfg!(feature = "orm-postgres")) {
+            tracing::warn!(
+                table_name,
+                index_name,
+                "covering columns are unsupported, the `INCLUDE` of `{index_name}` is ignored",
+            );
+        }
+        self.build_create_sql(&index_name, table_name_escaped)
+    }
+
+    /// Builds the SQL statement to create the index without the unsupported options.
+    fn build_create_sql(&self, index_name: &str, table_name_escaped: &str) -> Option<String> {
+        let columns = self.key_parts().join(", ");
+        if cfg!(any(
+            feature = "orm-mariadb",
+            feature = "orm-mysql",
+            feature = "orm-tidb"
+        )) {
+            match self.index_type() {
+                Some(index_type @ ("unique" | "spatial")) => {
+                    let index_type = index_type.to_uppercase();
+                    Some(format!(
+                        "CREATE {index_type} INDEX {index_name} ON {table_name_escaped} ({columns});"
+                    ))
+                }
+                Some(index_type @ ("btree" | "hash")) => {
+                    let index_type = index_type.to_uppercase();
+                    Some(format!(
+                        "CREATE INDEX {index_name} ON {table_name_escaped} ({columns}) \
+                            USING {index_type};"
+                    ))
+                }
+                None => Some(format!(
+                    "CREATE INDEX {index_name} ON {table_name_escaped} ({columns});"
+                )),
+                _ => None,
+            }
+        } else if cfg!(feature = "orm-postgres") {
+            let mut sql = if self.is_unique() {
+                format!(
+                    "CREATE UNIQUE INDEX IF NOT EXISTS {index_name} \
+                        ON {table_name_escaped} ({columns})"
+                )
+            } else {
+                let index_type = self.index_type().unwrap_or("btree");
+                format!(
+                    "CREATE INDEX IF NOT EXISTS {index_name} \
+                        ON {table_name_escaped} USING {index_type}({columns})"
+                )
+            };
+            if !self.include_columns.is_empty() {
+                let include_columns = self.include_columns.join(", ");
+                sql.push_str(&format!(" INCLUDE ({include_columns})"));
+            }
+            if let Some(predicate) = self.predicate() {
+                sql.push_str(&format!(" WHERE {predicate}"));
+            }
+            sql.push(';');
+            Some(sql)
+        } else {
+            let unique = if self.is_unique() { "UNIQUE " } else { "" };
+            let mut sql = format!(
+                "CREATE {unique}INDEX IF NOT EXISTS {index_name} ON {table_name_escaped} ({columns})"
+            );
+            if let Some(predicate) = self.predicate() {
+                sql.push_str(&format!(" WHERE {predicate}"));
+            }
+            sql.push(';');
+            Some(sql)
+        }
+    }
+
+    /// Returns `true` if the index matches the definition in the database.
+    ///
+    /// The definition is `indexdef` of `pg_indexes` for PostgreSQL, `sql` of `sqlite_master`
+    /// for SQLite, and the comma-separated columns prefixed by `UNIQUE ` for MySQL.
+    pub(crate) fn matches_definition(&self, table_name: &str, definition: &str) -> bool {
+        let definition = normalize_definition(definition);
+        if cfg!(any(
+            feature = "orm-mariadb",
+            feature = "orm-mysql",
+            feature = "orm-tidb"
+        )) {
+            let columns = if self.case_insensitive {
+                self.column_names()
+                    .into_iter()
+                    .map(|column| format!("LOWER({column})"))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            } else {
+                self.column_names().join(",")
+            };
+            let expected = if self.is_unique() {
+                format!("unique {columns}")
+            } else {
+                columns
+            };
+            definition == normalize_definition(&expected)
+        } else if cfg!(feature = "orm-postgres") {
+            // The predicate is rewritten by PostgreSQL, so only its presence is compared.
+            let (definition, predicate) = match definition.split_once(" where") {
+                Some((definition, predicate)) => (definition, Some(predicate)),
+                None => (definition.as_str(), None),
+            };
+            let Some((_, using)) = definition.split_once(" using ") else {
+                return false;
+            };
+            // The argument of `lower()` is cast to `text` by PostgreSQL.
+            let using = if self.case_insensitive {
+                let using = using.replace("::text", "");
+                LOWER_CAST_PATTERN
+                    .replace_all(&using, "lower($1)")
+                    .into_owned()
+            } else {
+                using.to_owned()
+            };
+            let index_type = self.index_type().filter(|_| !self.is_unique());
+            let columns = self
+                .key_parts()
+                .into_iter()
+                .map(|column| match column.strip_suffix(" ASC") {
+                    Some(column) => column.to_owned(),
+                    None => column,
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            let mut expected = format!("{}({columns})", index_type.unwrap_or("btree"));
+            if !self.include_columns.is_empty() {
+                let include_columns = self.include_columns.join(", ");
+                expected.push_str(&format!(" INCLUDE ({include_columns})"));
+            }
+            definition.starts_with("create unique") == self.is_unique()
+                && using == normalize_definition(&expected)
+                && predicate.is_some() == self.predicate.is_some()
+        } else {
+            self.build_create_sql(&self.name(table_name), table_name)
+                .is_some_and(|sql| definition == normalize_definition(&sql))
+        }
+    }
+
+    /// Returns the key parts with the optional sort orders.
+    /// The columns are wrapped by `LOWER()` if the index is case-insensitive.
+    fn key_parts(&self) -> Vec<String> {
+        if !self.case_insensitive {
+            return self.columns.clone();
+        }
+        self.columns
+            .iter()
+            .map(|column| {
+                let (name, order) = column.split_once(' ').unwrap_or((column, ""));
+                let expr = format!("LOWER({name})");
+                // A functional key part must be enclosed within parentheses in MySQL.
+                let expr = if cfg!(any(
+                    feature = "orm-mariadb",
+                    feature = "orm-mysql",
+                    feature = "orm-tidb"
+                )) {
+                    format!("({expr})")
+                } else {
+                    expr
+                };
+                if order.is_empty() {
+                    expr
+                } else {
+                    format!("{expr} {order}")
+                }
+            })
+            .collect()
+    }
+}
+
+/// Parses the comma-separated columns.
+fn parse_columns(columns: &str) -> Vec<String> {
+    columns
+        .split(',')
+        .map(|column| column.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|column| !column.is_empty())
+        .map(|column| match column.rsplit_once(' ') {
+            Some((name, order)) if order.eq_ignore_ascii_case("asc") => {
+                format!("{name} ASC")
+            }
+            Some((name, order)) if order.eq_ignore_ascii_case("desc") => {
+                format!("{name} DESC")
+            }
+            _ => column,
+        })
+        .collect()
+}
+
+/// Returns the column name without the sort order.
+fn column_name(column: &str) -> &str {
+    column.split_once(' ').map_or(column, |(name, _)| name)
+}
+
+/// Normalizes the index definition for the comparison.
+fn normalize_definition(definition: &str) -> String {
+    let definition = definition
+        .trim()
+        .trim_end_matches(';')
+        .to_ascii_lowercase()
+        .replace(['"', '`'], "")
+        .replace(" if not exists", "")
+        .replace(" asc)", ")")
+        .replace(" asc,", ",");
+    let definition = definition.split_whitespace().collect::<Vec<_>>().join(" ");
+    definition
+        .replace("( ", "(")
+        .replace(" )", ")")
+        .replace(" (", "(")
+        .replace(", ", ",")
+}
+
+/// Regex for the casted argument of `lower()` in the PostgreSQL index definition.
+static LOWER_CAST_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"lower\(\(([^()]+)\)\)").expect("fail to create a regex for the `lower()` cast")
+});
+
+#[cfg(test)]
+mod tests {
+    use super::Index;
+
+    #[test]
+    fn it_formats_index_definitions() {
+        let mut index = Index::new("org_id,  created_at desc");
+        assert_eq!(index.columns(), ["org_id", "created_at DESC"]);
+        assert_eq!(index.name("order"), "order_org_id_created_at_index");
+
+        index.set_predicate("status <> 'Deleted'");
+        index.set_name("order_active_index");
+        assert_eq!(index.name("order"), "order_active_index");
+
+        let sql = index.format_create_sql("order", "\"order\"").unwrap();
+        if cfg!(feature = "orm-postgres") {
+            assert!(sql.ends_with("WHERE status <> 'Deleted';"));
+            assert!(index.matches_definition(
+                "order",
+                "CREATE INDEX order_active_index ON public.\"order\" \
+                    USING btree (org_id, created_at DESC) WHERE ((status)::text <> 'Deleted'::text)"
+            ));
+        } else if !cfg!(any(
+            feature = "orm-mariadb",
+            feature = "orm-mysql",
+            feature = "orm-tidb"
+        )) {
+            assert!(index.matches_definition(
+                "order",
+                "CREATE INDEX order_active_index ON \"order\" (org_id, created_at DESC) \
+                    WHERE status <> 'Deleted'"
+            ));
+            assert!(!index.matches_definition(
+                "order",
+                "CREATE INDEX order_active_index ON \"order\" (org_id, created_at DESC)"
+            ));
+        }
+    }
+
+    #[test]
+    fn it_formats_case_insensitive_indexes() {
+        let mut index = Index::new("email");
+        index.set_index_type("unique");
+        index.set_case_insensitive(true);
+        assert_eq!(index.name("user"), "user_email_index");
+
+        let sql = index.format_create_sql("user", "\"user\"").unwrap();
+        if cfg!(feature = "orm-postgres") {
+            assert!(sql.ends_with("(LOWER(email));"));
+            assert!(index.matches_definition(
+                "user",
+                "CREATE UNIQUE INDEX user_email_index ON public.\"user\" \
+                    USING btree (lower((email)::text))"
+            ));
+        } else if cfg!(any(
+            feature = "orm-mariadb",
+            feature = "orm-mysql",
+            feature = "orm-tidb"
+        )) {
+            assert!(sql.ends_with("((LOWER(email)));"));
+            assert!(index.matches_definition("user", "UNIQUE lower(`email`)"));
+            assert!(!index.matches_definition("user", "UNIQUE email"));
+        } else {
+            assert!(sql.ends_with("(LOWER(email));"));
+            assert!(index.matches_definition(
+                "user",
+                "CREATE UNIQUE INDEX user_email_index ON \"user\" (LOWER(email))"
+            ));
+        }
+    }
+}
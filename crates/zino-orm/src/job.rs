@@ -0,0 +1,285 @@
+use super::{executor::Executor, query::QueryExt, DatabaseDriver, DistributedLock, GlobalPool};
+use sqlx::pool::PoolConnection;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering::Relaxed},
+        Mutex,
+    },
+    time::Duration,
+};
+use zino_core::{
+    error::Error,
+    extension::TomlTableExt,
+    model::Query,
+    schedule::{JobRegistry, JobRunRecord, JobStore},
+    state::State,
+    warn, BoxFuture, LazyLock,
+};
+
+/// A job store which persists the runs of async jobs into the `_zino_job_runs` table
+/// and acquires database advisory locks for multi-instance deployments.
+/// The exclusive jobs are guarded by a [`DistributedLock`] regardless of the `advisory-lock` flag.
+///
+/// It can be configured in the `[scheduler]` table:
+///
+/// ```toml
+/// [scheduler]
+/// database = "main"
+/// record-runs = true
+/// advisory-lock = false
+/// lock-ttl = "1m"
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct JobRunStore {
+    /// Name of the connection pool.
+    pool_name: &'static str,
+    /// Flag to indicate whether the job runs are persisted.
+    record_runs: bool,
+    /// Flag to indicate whether the database advisory lock is used.
+    advisory_lock: bool,
+    /// Time-to-live of the lease for the exclusive jobs.
+    lock_ttl: Duration,
+}
+
+impl JobRunStore {
+    /// Creates a new instance with the connection pool.
+    #[inline]
+    pub fn new(pool_name: &'static str) -> Self {
+        Self {
+            pool_name,
+            record_runs: true,
+            advisory_lock: false,
+            lock_ttl: Duration::from_secs(60),
+        }
+    }
+
+    /// Creates a new instance with the configuration.
+    pub fn with_config() -> Self {
+        let mut store = Self::new("main");
+        if let Some(config) = State::shared().get_config("scheduler") {
+            if let Some(pool_name) = config.get_str("database") {
+                store.pool_name = pool_name.to_owned().leak();
+            }
+            if let Some(record_runs) = config.get_bool("record-runs") {
+                store.record_runs = record_runs;
+            }
+            if let Some(advisory_lock) = config.get_bool("advisory-lock") {
+                store.advisory_lock = advisory_lock;
+            }
+            if let Some(lock_ttl) = config.get_duration("lock-ttl") {
+                store.lock_ttl = lock_ttl;
+            }
+        }
+        store
+    }
+
+    /// Enables the flag to indicate whether the database advisory lock is used.
+    #[inline]
+    pub fn advisory_lock(mut self, enabled: bool) -> Self {
+        self.advisory_lock = enabled;
+        self
+    }
+
+    /// Sets the time-to-live of the lease for the exclusive jobs.
+    #[inline]
+    pub fn lock_ttl(mut self, ttl: Duration) -> Self {
+        self.lock_ttl = ttl;
+        self
+    }
+
+    /// Registers the job store with the configuration as the global job store.
+    #[inline]
+    pub fn init() {
+        JobRegistry::set_store(Self::with_config());
+    }
+
+    /// Creates the `_zino_job_runs` table if it does not exist.
+    async fn create_table(&self) -> Result<(), Error> {
+        if JOB_RUNS_TABLE_CREATED.load(Relaxed) || !super::AUTO_MIGRATION.load(Relaxed) {
+            return Ok(());
+        }
+
+        let datetime_type = if cfg!(any(
+            feature = "orm-mariadb",
+            feature = "orm-mysql",
+            feature = "orm-tidb"
+        )) {
+            "TIMESTAMP(6)"
+        } else if cfg!(feature = "orm-postgres") {
+            "TIMESTAMPTZ"
+        } else {
+            "DATETIME"
+        };
+        let sql = format!(
+            "CREATE TABLE IF NOT EXISTS {JOB_RUNS_TABLE} (\n  \
+                id VARCHAR(36) NOT NULL PRIMARY KEY,\n  \
+                job_id VARCHAR(36) NOT NULL,\n  \
+                job_name VARCHAR(255) NOT NULL,\n  \
+                started_at {datetime_type} NOT NULL,\n  \
+                finished_at {datetime_type} NOT NULL,\n  \
+                execution_time_millis BIGINT NOT NULL,\n  \
+                outcome VARCHAR(16) NOT NULL,\n  \
+                error_message TEXT,\n  \
+                manual BOOLEAN NOT NULL DEFAULT FALSE\n\
+            );"
+        );
+        let pool = self.connection_pool()?.pool();
+        if let Err(err) = pool.execute(&sql).await {
+            tracing::error!(table_name = JOB_RUNS_TABLE, "fail to execute `{sql}`");
+            return Err(err);
+        }
+        JOB_RUNS_TABLE_CREATED.store(true, Relaxed);
+        Ok(())
+    }
+
+    /// Returns the connection pool.
+    fn connection_pool(&self) -> Result<&'static super::ConnectionPool, Error> {
+        GlobalPool::get(self.pool_name)
+            .ok_or_else(|| warn!("connection to the database is unavailable"))
+    }
+}
+
+impl Default for JobRunStore {
+    #[inline]
+    fn default() -> Self {
+        Self::new("main")
+    }
+}
+
+impl JobStore for JobRunStore {
+    fn record<'a>(&'a self, record: &'a JobRunRecord) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            if !self.record_runs {
+                return Ok(());
+            }
+            self.create_table().await?;
+
+            let id = record.run_id();
+            let job_id = record.job_id();
+            let job_name = Query::escape_string(record.job_name().unwrap_or_default());
+            let started_at = Query::escape_string(record.started_at().to_utc_timestamp());
+            let finished_at = Query::escape_string(record.finished_at().to_utc_timestamp());
+            let execution_time_millis = record.execution_time().as_millis();
+            let outcome = record.outcome().as_str();
+            let error_message = record
+                .error_message()
+                .map(Query::escape_string)
+                .unwrap_or_else(|| "NULL".to_owned());
+            let manual = if record.is_manual() { "TRUE" } else { "FALSE" };
+            let sql = format!(
+                "INSERT INTO {JOB_RUNS_TABLE} \
+                    (id, job_id, job_name, started_at, finished_at, \
+                        execution_time_millis, outcome, error_message, manual) \
+                    VALUES ('{id}', '{job_id}', {job_name}, {started_at}, {finished_at}, \
+                        {execution_time_millis}, '{outcome}', {error_message}, {manual});"
+            );
+            self.connection_pool()?.pool().execute(&sql).await?;
+            Ok(())
+        })
+    }
+
+    fn try_lock<'a>(&'a self, job_name: &'a str) -> BoxFuture<'a, Result<bool, Error>> {
+        Box::pin(async move {
+            let supported = cfg!(any(
+                feature = "orm-mariadb",
+                feature = "orm-mysql",
+                feature = "orm-postgres",
+                feature = "orm-tidb"
+            ));
+            if !self.advisory_lock || !supported {
+                return Ok(true);
+            }
+
+            // Advisory locks are bound to the session, so the connection is held until unlocked.
+            let mut connection = self.connection_pool()?.pool().acquire().await?;
+            let locked = if cfg!(feature = "orm-postgres") {
+                sqlx::query_scalar::<_, bool>("SELECT pg_try_advisory_lock(hashtext($1));")
+                    .bind(job_name)
+                    .fetch_one(&mut *connection)
+                    .await?
+            } else {
+                sqlx::query_scalar::<_, Option<i64>>("SELECT GET_LOCK(?, 0);")
+                    .bind(job_name)
+                    .fetch_one(&mut *connection)
+                    .await?
+                    == Some(1)
+            };
+            if locked {
+                ADVISORY_LOCK_CONNECTIONS
+                    .lock()
+                    .map_err(|err| warn!("fail to acquire the lock: {}", err))?
+                    .insert(job_name.to_owned(), connection);
+            }
+            Ok(locked)
+        })
+    }
+
+    fn unlock<'a>(&'a self, job_name: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let connection = ADVISORY_LOCK_CONNECTIONS
+                .lock()
+                .map_err(|err| warn!("fail to acquire the lock: {}", err))?
+                .remove(job_name);
+            if let Some(mut connection) = connection {
+                let sql = if cfg!(feature = "orm-postgres") {
+                    "SELECT pg_advisory_unlock(hashtext($1));"
+                } else {
+                    "SELECT RELEASE_LOCK(?);"
+                };
+                sqlx::query(sql)
+                    .bind(job_name)
+                    .execute(&mut *connection)
+                    .await?;
+            }
+            Ok(())
+        })
+    }
+
+    fn try_lock_exclusive<'a>(&'a self, lock_name: &'a str) -> BoxFuture<'a, Result<bool, Error>> {
+        Box::pin(async move {
+            let lock = DistributedLock::try_acquire_with_pool(
+                self.pool_name,
+                lock_name.to_owned(),
+                self.lock_ttl,
+            )
+            .await?;
+            if let Some(lock) = lock {
+                EXCLUSIVE_LOCKS
+                    .lock()
+                    .map_err(|err| warn!("fail to acquire the lock: {}", err))?
+                    .insert(lock_name.to_owned(), lock);
+                Ok(true)
+            } else {
+                Ok(false)
+            }
+        })
+    }
+
+    fn unlock_exclusive<'a>(&'a self, lock_name: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let lock = EXCLUSIVE_LOCKS
+                .lock()
+                .map_err(|err| warn!("fail to acquire the lock: {}", err))?
+                .remove(lock_name);
+            if let Some(lock) = lock {
+                lock.release().await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Table name for the job runs.
+const JOB_RUNS_TABLE: &str = "_zino_job_runs";
+
+/// Flag to indicate whether the table for the job runs has been created.
+static JOB_RUNS_TABLE_CREATED: AtomicBool = AtomicBool::new(false);
+
+/// Connections holding the advisory locks.
+static ADVISORY_LOCK_CONNECTIONS: LazyLock<Mutex<HashMap<String, PoolConnection<DatabaseDriver>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Distributed locks held by the exclusive jobs.
+static EXCLUSIVE_LOCKS: LazyLock<Mutex<HashMap<String, DistributedLock>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
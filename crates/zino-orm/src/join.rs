@@ -1,6 +1,10 @@
 use super::{query::QueryExt, Entity, Schema};
 use std::marker::PhantomData;
-use zino_core::model::Query;
+use zino_core::{
+    error::{Error, ErrorKind},
+    model::Query,
+    warn,
+};
 
 /// Variants for `JOIN` types.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -147,6 +151,38 @@ impl<L: Schema, R: Schema> JoinOn<L, R> {
     pub(super) fn format_conditions(&self) -> String {
         self.conditions.join(" AND ")
     }
+
+    /// Validates the sort fields of the query against the joined models.
+    ///
+    /// A qualified sort field should reference a column of the model whose name
+    /// is used as the table alias, and an unqualified one is resolved to the left model.
+    pub(super) fn validate_sort_order(&self, query: &Query) -> Result<(), Error> {
+        for order in query.sort_order() {
+            let field = order.field();
+            let valid = match field.split_once('.') {
+                Some((alias, col)) if alias == L::model_name() => L::has_column(col),
+                Some((alias, col)) if alias == R::model_name() => R::has_column(col),
+                Some(_) => false,
+                None => {
+                    L::has_column(field)
+                        || query.fields().iter().any(|s| {
+                            s.split_once(':')
+                                .is_some_and(|(alias, _)| alias.trim() == field)
+                        })
+                }
+            };
+            if !valid {
+                let err = warn!(
+                    "sort field `{}` does not reference a column of `{}` or `{}`",
+                    field,
+                    L::model_name(),
+                    R::model_name()
+                );
+                return Err(err.with_kind(ErrorKind::Validation));
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<L: Entity + Schema, R: Entity + Schema> JoinOn<L, R> {
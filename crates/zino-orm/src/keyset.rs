@@ -0,0 +1,272 @@
+use super::{query::QueryExt, DatabaseRow, DecodeRow, Executor, Schema};
+use std::{marker::PhantomData, sync::atomic::Ordering::Relaxed, time::Duration};
+use zino_core::{
+    error::Error,
+    extension::{JsonObjectExt, TomlTableExt},
+    model::{Query, QueryOrder},
+    state::State,
+    warn, JsonValue, LazyLock, Map,
+};
+
+/// Iteration over the models in batches with the keyset pagination.
+///
+/// The rows are walked in the order of the primary key, and each batch is selected by
+/// `WHERE {primary_key} > {checkpoint} ORDER BY {primary_key} LIMIT {batch_size}`,
+/// so that the rows inserted or deleted during the iteration never cause
+/// the other rows to be skipped or visited twice.
+///
+/// The filters of the query are respected as they are. In particular, the soft-deleted rows
+/// are not excluded unless the query has a filter on the `status` column.
+/// The batch size is capped by the `max-rows` of the database, which prevents
+/// a batch from being truncated silently.
+///
+/// It can be configured in the `[database.iteration]` table:
+///
+/// ```toml
+/// [database.iteration]
+/// batch-size = 1000
+/// throttle = "100ms"
+/// ```
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino_orm::KeysetIterator;
+///
+/// let mut iterator = KeysetIterator::<User>::new(query).resume_after(checkpoint);
+/// while let Some(users) = iterator.next_batch::<User>().await? {
+///     for user in users {
+///         // Processes the user.
+///     }
+///     if let Some(checkpoint) = iterator.checkpoint() {
+///         // Persists the checkpoint.
+///     }
+/// }
+/// ```
+pub struct KeysetIterator<M: Schema> {
+    /// Query.
+    query: Query,
+    /// Max number of rows in a batch.
+    batch_size: usize,
+    /// Sleep duration between the batches.
+    throttle: Duration,
+    /// Primary key of the last row visited.
+    checkpoint: Option<JsonValue>,
+    /// Number of batches fetched.
+    num_batches: usize,
+    /// Total number of rows fetched.
+    total_rows: u64,
+    /// A flag to indicate whether the iteration has been exhausted.
+    exhausted: bool,
+    /// Phantom type of the model.
+    phantom: PhantomData<M>,
+}
+
+impl<M: Schema> KeysetIterator<M> {
+    /// Creates a new instance with the query and the shared settings.
+    pub fn new(query: Query) -> Self {
+        let settings = &*ITERATION_SETTINGS;
+        Self {
+            query,
+            batch_size: settings.0,
+            throttle: settings.1,
+            checkpoint: None,
+            num_batches: 0,
+            total_rows: 0,
+            exhausted: false,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Sets the max number of rows in a batch.
+    #[inline]
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Sets the sleep duration between the batches.
+    #[inline]
+    pub fn throttle(mut self, throttle: Duration) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    /// Resumes the iteration after the primary key persisted as a checkpoint.
+    #[inline]
+    pub fn resume_after(mut self, checkpoint: impl Into<JsonValue>) -> Self {
+        self.checkpoint = Some(checkpoint.into()).filter(|v| !v.is_null());
+        self
+    }
+
+    /// Returns the primary key of the last row visited.
+    #[inline]
+    pub fn checkpoint(&self) -> Option<&JsonValue> {
+        self.checkpoint.as_ref()
+    }
+
+    /// Returns the total number of rows fetched.
+    #[inline]
+    pub fn total_rows(&self) -> u64 {
+        self.total_rows
+    }
+
+    /// Returns `true` if there are no more rows.
+    #[inline]
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    /// Fetches the next batch of the rows and decodes them as `Vec<T>`.
+    /// Returns `None` if there are no more rows.
+    ///
+    /// The hooks of the model are not executed, so that the returned future is `Send`
+    /// and can be executed by the async jobs.
+    pub async fn next_batch<T>(&mut self) -> Result<Option<Vec<T>>, Error>
+    where
+        T: DecodeRow<DatabaseRow, Error = Error>,
+    {
+        if self.exhausted {
+            return Ok(None);
+        }
+        if self.num_batches > 0 && !self.throttle.is_zero() {
+            tokio::time::sleep(self.throttle).await;
+        }
+
+        let primary_key_name = M::PRIMARY_KEY_NAME;
+        let batch_size = self.effective_batch_size();
+        let query = self.batch_query(batch_size);
+        let table_name = query.format_table_name::<M>();
+        let projection = query.format_table_fields::<M>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<M>(&mut arguments);
+        let sort = query.format_sort();
+        let pagination = query.format_pagination();
+        let sql = format!("SELECT {projection} FROM {table_name} {filters} {sort} {pagination};");
+        let pool = M::init_reader()?.pool();
+
+        // The batch is bounded by the limit, which should never be truncated.
+        let (rows, _) =
+            super::with_max_rows(Some(0), pool.fetch_with_values(&sql, &arguments)).await;
+        let rows = rows?;
+
+        let num_rows = rows.len();
+        self.num_batches += 1;
+        if num_rows < batch_size {
+            self.exhausted = true;
+        }
+        let Some(last_row) = rows.last() else {
+            return Ok(None);
+        };
+        let checkpoint = Map::decode_row(last_row)?
+            .remove(primary_key_name)
+            .filter(|v| !v.is_null())
+            .ok_or_else(|| {
+                warn!(
+                    "the primary key `{}` should be selected for the keyset pagination",
+                    primary_key_name
+                )
+            })?;
+
+        let mut data = Vec::with_capacity(num_rows);
+        for row in rows.iter() {
+            data.push(T::decode_row(row)?);
+        }
+        self.checkpoint = Some(checkpoint);
+        self.total_rows += u64::try_from(num_rows)?;
+        Ok(Some(data))
+    }
+
+    /// Returns the batch size capped by the `max-rows` of the database.
+    fn effective_batch_size(&self) -> usize {
+        let max_rows = super::MAX_ROWS.load(Relaxed);
+        if max_rows > 0 {
+            self.batch_size.min(max_rows)
+        } else {
+            self.batch_size
+        }
+    }
+
+    /// Constructs the query for the next batch.
+    fn batch_query(&self, batch_size: usize) -> Query {
+        let primary_key_name = M::PRIMARY_KEY_NAME;
+        let mut query = self.query.clone();
+        let fields = query.fields();
+        if !fields.is_empty() && !fields.iter().any(|field| field == primary_key_name) {
+            let mut fields = fields.to_vec();
+            fields.push(primary_key_name.to_owned());
+            query.set_fields(fields);
+        }
+        if let Some(checkpoint) = self.checkpoint.clone() {
+            add_keyset_filter(&mut query, primary_key_name, checkpoint);
+        }
+        query.set_order(vec![QueryOrder::new(primary_key_name, false)]);
+        query.set_offset(0);
+        query.set_limit(batch_size);
+        query
+    }
+}
+
+/// Adds a filter to select the rows after the checkpoint. An existing filter
+/// on the primary key is preserved by combining them with `$and`.
+fn add_keyset_filter(query: &mut Query, primary_key_name: &str, checkpoint: JsonValue) {
+    let keyset_filter = Map::from_entry("$gt", checkpoint);
+    match query.remove_filter(primary_key_name) {
+        Some(filter) => {
+            let mut logical_and = match query.remove_filter("$and") {
+                Some(JsonValue::Array(filters)) => filters,
+                _ => Vec::new(),
+            };
+            logical_and.push(Map::from_entry(primary_key_name, filter).into());
+            logical_and.push(Map::from_entry(primary_key_name, keyset_filter).into());
+            query.add_filter("$and", logical_and);
+        }
+        None => query.add_filter(primary_key_name, keyset_filter),
+    }
+}
+
+/// Shared settings of the batch size and the throttle for the keyset iteration.
+static ITERATION_SETTINGS: LazyLock<(usize, Duration)> = LazyLock::new(|| {
+    let mut batch_size = 1000;
+    let mut throttle = Duration::ZERO;
+    if let Some(config) = State::shared()
+        .get_config("database")
+        .and_then(|config| config.get_table("iteration"))
+    {
+        if let Some(size) = config.get_usize("batch-size") {
+            batch_size = size.max(1);
+        }
+        if let Some(duration) = config.get_duration("throttle") {
+            throttle = duration;
+        }
+    }
+    (batch_size, throttle)
+});
+
+#[cfg(test)]
+mod tests {
+    use super::add_keyset_filter;
+    use zino_core::{json, model::Query};
+
+    #[test]
+    fn it_adds_keyset_filter() {
+        let mut query = Query::new(json!({ "status": "Active" }));
+        add_keyset_filter(&mut query, "id", 10.into());
+        assert_eq!(query.filters().get("id"), Some(&json!({ "$gt": 10 })));
+
+        let mut query = Query::new(json!({
+            "id": { "$lt": 100 },
+            "$and": [{ "name": { "$ne": "" } }],
+        }));
+        add_keyset_filter(&mut query, "id", 10.into());
+        assert!(query.filters().get("id").is_none());
+        assert_eq!(
+            query.filters().get("$and"),
+            Some(&json!([
+                { "name": { "$ne": "" } },
+                { "id": { "$lt": 100 } },
+                { "id": { "$gt": 10 } },
+            ]))
+        );
+    }
+}
@@ -12,49 +12,139 @@ use std::sync::{
 };
 use zino_core::{extension::TomlTableExt, state::State, LazyLock};
 
+#[cfg(test)]
+#[macro_use]
+mod testing;
+
 mod accessor;
 mod aggregate;
 mod column;
+mod computed;
+mod conflict;
+mod embedded;
 mod entity;
 mod executor;
 mod helper;
+mod index;
 mod join;
 mod manager;
+mod money;
 mod mutation;
 mod pool;
 mod query;
+mod relation;
 mod row;
+mod savepoint;
 mod schema;
+mod search;
+mod session;
 mod transaction;
+mod upsert;
 mod value;
 mod window;
 
 pub use accessor::ModelAccessor;
-pub use aggregate::Aggregation;
+pub use aggregate::{Aggregation, DateTrunc};
 pub use column::EncodeColumn;
+pub use computed::{BatchComputeFn, ComputeFn, ComputedField};
+pub use conflict::UniqueViolation;
+pub use embedded::{EmbeddedPrimaryKey, EmbeddedSchema};
 pub use entity::Entity;
 pub use executor::Executor;
 pub use helper::ModelHelper;
+pub use index::Index;
 pub use join::JoinOn;
 pub use manager::PoolManager;
 pub use mutation::MutationBuilder;
 pub use pool::ConnectionPool;
 pub use query::QueryBuilder;
+pub use relation::ModelRelation;
 pub use row::DecodeRow;
+pub use savepoint::Savepoint;
 pub use schema::Schema;
+pub use search::sanitize_search_term;
+pub use session::SessionSettings;
 pub use transaction::Transaction;
+pub use upsert::UpsertOutcome;
 pub use value::IntoSqlValue;
 pub use window::Window;
 
+#[doc(hidden)]
+pub use accessor::diff_json_fields;
+#[doc(hidden)]
+pub use embedded::{contains_any_field, contains_field};
+
+#[cfg(feature = "accessor")]
+mod attachment;
+#[cfg(feature = "orm-postgres")]
+mod copy;
 #[cfg(feature = "orm-sqlx")]
 mod decode;
 #[cfg(feature = "orm-sqlx")]
+mod export;
+#[cfg(feature = "feature-flag")]
+mod feature_flag;
+#[cfg(feature = "orm-sqlx")]
+mod job;
+#[cfg(feature = "orm-sqlx")]
+mod keyset;
+#[cfg(feature = "orm-sqlx")]
+mod lock;
+#[cfg(feature = "orm-sqlx")]
+mod maintenance;
+#[cfg(feature = "outbox")]
+mod outbox;
+#[cfg(feature = "orm-postgres")]
+mod partition;
+#[cfg(feature = "orm-sqlx")]
+mod row_limit;
+#[cfg(feature = "orm-sqlx")]
 mod scalar;
+#[cfg(feature = "orm-sqlx")]
+mod sequence;
+#[cfg(feature = "sync")]
+mod sync;
+#[cfg(feature = "orm-sqlx")]
+mod timeout;
+#[cfg(feature = "orm-sqlx")]
+mod ttl;
+#[cfg(feature = "webhook")]
+mod webhook;
 
+#[cfg(feature = "accessor")]
+pub use attachment::Attachment;
+#[cfg(feature = "orm-sqlx")]
+pub use decode::{
+    decode, decode_array, decode_decimal, decode_money, decode_optional, decode_ulid, decode_uuid,
+};
+#[cfg(feature = "orm-sqlx")]
+pub use export::ExportJob;
+#[cfg(feature = "feature-flag")]
+pub use feature_flag::FeatureFlagTable;
 #[cfg(feature = "orm-sqlx")]
-pub use decode::{decode, decode_array, decode_decimal, decode_optional, decode_uuid};
+pub use job::JobRunStore;
+#[cfg(feature = "orm-sqlx")]
+pub use keyset::KeysetIterator;
+#[cfg(feature = "orm-sqlx")]
+pub use lock::DistributedLock;
+#[cfg(feature = "orm-sqlx")]
+pub use maintenance::MaintenanceScheduler;
+#[cfg(feature = "outbox")]
+pub use outbox::Outbox;
+#[cfg(feature = "orm-postgres")]
+pub use partition::{PartitionGranularity, PartitionManager};
+#[cfg(feature = "orm-sqlx")]
+pub use row_limit::with_max_rows;
 #[cfg(feature = "orm-sqlx")]
 pub use scalar::ScalarQuery;
+#[cfg(feature = "orm-sqlx")]
+pub use sequence::{SequenceCounter, SequencePattern};
+#[cfg(feature = "sync")]
+pub use sync::{ChangeFeed, ChangeSet, SyncToken, Tombstone};
+#[cfg(feature = "orm-sqlx")]
+pub use ttl::ExpiryCleanup;
+#[cfg(feature = "webhook")]
+pub use webhook::{Webhook, WebhookDelivery, WebhookSubscription};
 
 cfg_if::cfg_if! {
     if #[cfg(any(feature = "orm-mariadb", feature = "orm-mysql", feature = "orm-tidb"))] {
@@ -100,6 +190,12 @@ cfg_if::cfg_if! {
     } else {
         mod sqlite;
 
+        #[cfg(feature = "orm-sqlx")]
+        mod backup;
+
+        #[cfg(feature = "orm-sqlx")]
+        pub use backup::SqliteBackup;
+
         /// Driver name.
         static DRIVER_NAME: &str = "sqlite";
 
@@ -148,14 +244,26 @@ impl GlobalPool {
     }
 
     /// Iterates over the shared connection pools and
-    /// attempts to establish a database connection for each of them.
+    /// attempts to establish the minimum number of connections for each of them.
     #[inline]
     pub async fn connect_all() {
         for cp in SHARED_CONNECTION_POOLS.0.iter() {
-            cp.check_availability().await;
+            if cp.check_availability().await {
+                cp.warm_up().await;
+            }
         }
     }
 
+    /// Returns the total number of the concurrent acquire waiters for the shared connection pools.
+    #[inline]
+    pub fn waiting_count() -> usize {
+        SHARED_CONNECTION_POOLS
+            .0
+            .iter()
+            .map(|cp| cp.waiting_count())
+            .sum()
+    }
+
     /// Shuts down the shared connection pools to ensure all connections are gracefully closed.
     #[inline]
     pub async fn close_all() {
@@ -187,15 +295,24 @@ static SHARED_CONNECTION_POOLS: LazyLock<ConnectionPools> = LazyLock::new(|| {
         if let Some(debug_only) = database.get_bool("debug-only") {
             DEBUG_ONLY.store(debug_only, Relaxed);
         }
+        if let Some(create_enum_types) = database.get_bool("create-enum-types") {
+            CREATE_ENUM_TYPES.store(create_enum_types, Relaxed);
+        }
     }
 
     // Database connection pools.
-    let databases = config.get_array(database_type).unwrap_or_else(|| {
-        panic!(
-            "the `{database_type}` field should be an array of tables; \
-                please use `[[{database_type}]]` to configure a list of database services"
-        )
-    });
+    let databases = config
+        .get_array(database_type)
+        .filter(|databases| !databases.is_empty())
+        .unwrap_or_else(|| {
+            // A handler touching the ORM should fail fast in tests without a test database.
+            let env = State::shared().env();
+            panic!(
+                "no database service is configured for the `{env}` environment; \
+                    please use `[[{database_type}]]` in `config.{env}.toml` \
+                    to configure a list of database services"
+            )
+        });
     let pools = databases
         .iter()
         .filter_map(|v| v.as_table())
@@ -250,3 +367,6 @@ static AUTO_MIGRATION: AtomicBool = AtomicBool::new(true);
 
 /// Debug-only mode.
 static DEBUG_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Creating the missing enum types for PostgreSQL.
+static CREATE_ENUM_TYPES: AtomicBool = AtomicBool::new(false);
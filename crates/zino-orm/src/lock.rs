@@ -0,0 +1,431 @@
+use super::{DatabaseDriver, DatabasePool, GlobalPool};
+use sqlx::pool::PoolConnection;
+use std::{
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering::Relaxed},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{runtime::Handle, task::JoinHandle};
+use zino_core::{
+    application::Agent, error::Error, extension::TomlTableExt, state::State, warn, LazyLock, Uuid,
+};
+
+/// A lock shared by all instances of the application, which is backed by the database.
+///
+/// It uses the session-level advisory lock in PostgreSQL, which is released automatically
+/// when the connection is closed. For MySQL and SQLite, the lock is a row in the
+/// `_zino_locks` table with an owner and an expiration time, and the lease is extended
+/// by a heartbeat task until the lock is released. An expired lock held by
+/// a crashed process can be acquired by others.
+///
+/// The lock is released when the guard is dropped. It is recommended to call
+/// [`release()`](DistributedLock::release) explicitly since `Drop` can not be async.
+///
+/// It can be configured in the `[distributed-lock]` table:
+///
+/// ```toml
+/// [distributed-lock]
+/// database = "main"
+/// retry-interval = "200ms"
+/// ```
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use std::time::Duration;
+/// use zino_orm::DistributedLock;
+///
+/// let lock = DistributedLock::acquire(format!("order:{order_id}"), Duration::from_secs(30)).await?;
+/// process_order(order_id).await?;
+/// lock.release().await?;
+/// ```
+#[derive(Debug)]
+pub struct DistributedLock {
+    /// Lock name.
+    name: String,
+    /// Owner ID.
+    owner: String,
+    /// Database pool.
+    pool: DatabasePool,
+    /// Connection holding the advisory lock.
+    connection: Option<PoolConnection<DatabaseDriver>>,
+    /// Heartbeat task to extend the lease.
+    heartbeat: Option<JoinHandle<()>>,
+    /// A flag to indicate whether the lease has been lost.
+    lease_lost: Arc<AtomicBool>,
+    /// A flag to indicate whether the lock has been released.
+    released: bool,
+}
+
+impl DistributedLock {
+    /// Acquires the lock with the time-to-live of the lease,
+    /// waiting until it has been released by others.
+    pub async fn acquire(name: impl Into<String>, ttl: Duration) -> Result<Self, Error> {
+        let name = name.into();
+        loop {
+            if let Some(lock) = Self::try_acquire(name.as_str(), ttl).await? {
+                return Ok(lock);
+            }
+            tokio::time::sleep(*RETRY_INTERVAL).await;
+        }
+    }
+
+    /// Attempts to acquire the lock with the time-to-live of the lease,
+    /// returning `None` if it has been acquired by others.
+    #[inline]
+    pub async fn try_acquire(
+        name: impl Into<String>,
+        ttl: Duration,
+    ) -> Result<Option<Self>, Error> {
+        Self::try_acquire_with_pool(*LOCK_DATABASE, name.into(), ttl).await
+    }
+
+    /// Attempts to acquire the lock in the connection pool with the specific name.
+    pub(crate) async fn try_acquire_with_pool(
+        pool_name: &str,
+        name: String,
+        ttl: Duration,
+    ) -> Result<Option<Self>, Error> {
+        let pool = GlobalPool::get(pool_name)
+            .ok_or_else(|| warn!("connection to the database is unavailable"))?
+            .pool()
+            .clone();
+        Self::try_acquire_in(pool, name, ttl).await
+    }
+
+    /// Attempts to acquire the lock in the database pool.
+    async fn try_acquire_in(
+        pool: DatabasePool,
+        name: String,
+        ttl: Duration,
+    ) -> Result<Option<Self>, Error> {
+        let owner = Uuid::now_v7().to_string();
+        let mut lock = Self {
+            name,
+            owner,
+            pool,
+            connection: None,
+            heartbeat: None,
+            lease_lost: Arc::new(AtomicBool::new(false)),
+            released: false,
+        };
+        if cfg!(feature = "orm-postgres") {
+            // Advisory locks are bound to the session, so the connection is held until released.
+            let mut connection = lock.pool.acquire().await?;
+            let locked =
+                sqlx::query_scalar::<_, bool>("SELECT pg_try_advisory_lock(hashtext($1));")
+                    .bind(lock.name.as_str())
+                    .fetch_one(&mut *connection)
+                    .await?;
+            if !locked {
+                lock.released = true;
+                return Ok(None);
+            }
+            lock.connection = Some(connection);
+        } else {
+            create_table(&lock.pool).await?;
+            if !try_lock_row(&lock.pool, &lock.name, &lock.owner, ttl).await? {
+                lock.released = true;
+                return Ok(None);
+            }
+            lock.heartbeat = Some(spawn_heartbeat(&lock, ttl));
+        }
+        Ok(Some(lock))
+    }
+
+    /// Returns the lock name.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the owner ID.
+    #[inline]
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    /// Returns `true` if the lease has expired and the lock may have been acquired by others.
+    #[inline]
+    pub fn is_lost(&self) -> bool {
+        self.lease_lost.load(Relaxed)
+    }
+
+    /// Releases the lock.
+    pub async fn release(mut self) -> Result<(), Error> {
+        if let Some(heartbeat) = self.heartbeat.take() {
+            heartbeat.abort();
+        }
+        self.released = true;
+
+        let name = mem::take(&mut self.name);
+        let owner = mem::take(&mut self.owner);
+        release_lock(&self.pool, &name, &owner, self.connection.take()).await
+    }
+}
+
+impl Drop for DistributedLock {
+    fn drop(&mut self) {
+        if let Some(heartbeat) = self.heartbeat.take() {
+            heartbeat.abort();
+        }
+        if self.released {
+            return;
+        }
+
+        let name = mem::take(&mut self.name);
+        let owner = mem::take(&mut self.owner);
+        let connection = self.connection.take();
+        if let Ok(handle) = Handle::try_current() {
+            let pool = self.pool.clone();
+            handle.spawn(async move {
+                if let Err(err) = release_lock(&pool, &name, &owner, connection).await {
+                    tracing::error!(lock_name = name.as_str(), "fail to release the lock: {err}");
+                }
+            });
+        } else if let Some(connection) = connection {
+            // Closing the connection releases the advisory lock.
+            drop(connection.detach());
+        }
+    }
+}
+
+/// Spawns a heartbeat task to extend the lease of the lock periodically.
+fn spawn_heartbeat(lock: &DistributedLock, ttl: Duration) -> JoinHandle<()> {
+    let pool = lock.pool.clone();
+    let name = lock.name.clone();
+    let owner = lock.owner.clone();
+    let lease_lost = lock.lease_lost.clone();
+    let interval = (ttl / 3).max(Duration::from_millis(100));
+    Agent::spawn_traced(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            match extend_lease(&pool, &name, &owner, ttl).await {
+                Ok(true) => (),
+                Ok(false) => {
+                    lease_lost.store(true, Relaxed);
+                    tracing::warn!(
+                        lock_name = name.as_str(),
+                        "the lease of the lock has been lost"
+                    );
+                    break;
+                }
+                Err(err) => {
+                    tracing::error!(lock_name = name.as_str(), "fail to extend the lease: {err}");
+                }
+            }
+        }
+    })
+}
+
+/// Attempts to insert or take over the lock row atomically.
+async fn try_lock_row(
+    pool: &DatabasePool,
+    name: &str,
+    owner: &str,
+    ttl: Duration,
+) -> Result<bool, Error> {
+    let now = current_millis_expr();
+    let ttl_millis = ttl.as_millis();
+    let sql = format!(
+        "UPDATE {LOCKS_TABLE} SET owner = ?, expires_at = {now} + {ttl_millis} \
+            WHERE name = ? AND (expires_at < {now} OR owner = ?);"
+    );
+    let result = sqlx::query(&sql)
+        .bind(owner)
+        .bind(name)
+        .bind(owner)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() > 0 {
+        return Ok(true);
+    }
+
+    let insert_ignore = if cfg!(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb"
+    )) {
+        "INSERT IGNORE"
+    } else {
+        "INSERT OR IGNORE"
+    };
+    let sql = format!(
+        "{insert_ignore} INTO {LOCKS_TABLE} (name, owner, expires_at) \
+            VALUES (?, ?, {now} + {ttl_millis});"
+    );
+    let result = sqlx::query(&sql)
+        .bind(name)
+        .bind(owner)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Extends the lease of the lock row, returning `false` if it is no longer owned.
+async fn extend_lease(
+    pool: &DatabasePool,
+    name: &str,
+    owner: &str,
+    ttl: Duration,
+) -> Result<bool, Error> {
+    let now = current_millis_expr();
+    let ttl_millis = ttl.as_millis();
+    let sql = format!(
+        "UPDATE {LOCKS_TABLE} SET expires_at = {now} + {ttl_millis} \
+            WHERE name = ? AND owner = ?;"
+    );
+    let result = sqlx::query(&sql)
+        .bind(name)
+        .bind(owner)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Releases the advisory lock or deletes the lock row.
+async fn release_lock(
+    pool: &DatabasePool,
+    name: &str,
+    owner: &str,
+    connection: Option<PoolConnection<DatabaseDriver>>,
+) -> Result<(), Error> {
+    if let Some(mut connection) = connection {
+        sqlx::query("SELECT pg_advisory_unlock(hashtext($1));")
+            .bind(name)
+            .execute(&mut *connection)
+            .await?;
+    } else if !cfg!(feature = "orm-postgres") {
+        let sql = format!("DELETE FROM {LOCKS_TABLE} WHERE name = ? AND owner = ?;");
+        sqlx::query(&sql)
+            .bind(name)
+            .bind(owner)
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Creates the `_zino_locks` table if it does not exist.
+async fn create_table(pool: &DatabasePool) -> Result<(), Error> {
+    if LOCKS_TABLE_CREATED.load(Relaxed) || !super::AUTO_MIGRATION.load(Relaxed) {
+        return Ok(());
+    }
+
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {LOCKS_TABLE} (\n  \
+            name VARCHAR(255) NOT NULL PRIMARY KEY,\n  \
+            owner VARCHAR(36) NOT NULL,\n  \
+            expires_at BIGINT NOT NULL\n\
+        );"
+    );
+    if let Err(err) = sqlx::query(&sql).execute(pool).await {
+        tracing::error!(table_name = LOCKS_TABLE, "fail to execute `{sql}`");
+        return Err(err.into());
+    }
+    LOCKS_TABLE_CREATED.store(true, Relaxed);
+    Ok(())
+}
+
+/// Returns the SQL expression of the current timestamp in milliseconds,
+/// so that the expiration time does not depend on the clocks of the instances.
+fn current_millis_expr() -> &'static str {
+    if cfg!(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb"
+    )) {
+        "CAST(UNIX_TIMESTAMP(CURRENT_TIMESTAMP(3)) * 1000 AS SIGNED)"
+    } else {
+        "CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)"
+    }
+}
+
+/// Table name for the locks.
+const LOCKS_TABLE: &str = "_zino_locks";
+
+/// Flag to indicate whether the table for the locks has been created.
+static LOCKS_TABLE_CREATED: AtomicBool = AtomicBool::new(false);
+
+/// Name of the connection pool for the locks.
+static LOCK_DATABASE: LazyLock<&'static str> = LazyLock::new(|| {
+    State::shared()
+        .get_config("distributed-lock")
+        .and_then(|config| config.get_str("database"))
+        .unwrap_or("main")
+});
+
+/// Interval between the attempts to acquire the lock.
+static RETRY_INTERVAL: LazyLock<Duration> = LazyLock::new(|| {
+    State::shared()
+        .get_config("distributed-lock")
+        .and_then(|config| config.get_duration("retry-interval"))
+        .unwrap_or_else(|| Duration::from_millis(200))
+});
+
+#[cfg(test)]
+mod tests {
+    // The test runs against an in-memory database when SQLite is the driver.
+    #[cfg(not(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-postgres",
+        feature = "orm-tidb"
+    )))]
+    #[tokio::test]
+    async fn it_acquires_contends_and_expires_locks() {
+        use super::{spawn_heartbeat, DistributedLock};
+        use sqlx::{pool::PoolOptions, sqlite::SqliteConnectOptions};
+        use std::time::Duration;
+
+        let pool = PoolOptions::<sqlx::Sqlite>::new()
+            .max_connections(1)
+            .connect_with("sqlite::memory:".parse::<SqliteConnectOptions>().unwrap())
+            .await
+            .expect("fail to connect to the in-memory database");
+        let try_acquire = |name: &str, ttl: Duration| {
+            DistributedLock::try_acquire_in(pool.clone(), name.to_owned(), ttl)
+        };
+        let ttl = Duration::from_secs(10);
+
+        // The lock is exclusive until it has been released.
+        let lock = try_acquire("reconcile", ttl).await.unwrap().unwrap();
+        assert_eq!(lock.name(), "reconcile");
+        assert!(try_acquire("reconcile", ttl).await.unwrap().is_none());
+        assert!(try_acquire("order:1", ttl).await.unwrap().is_some());
+        lock.release().await.unwrap();
+
+        // The lock is released when the guard is dropped.
+        let lock = try_acquire("reconcile", ttl).await.unwrap().unwrap();
+        drop(lock);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let lock = try_acquire("reconcile", ttl).await.unwrap().unwrap();
+        lock.release().await.unwrap();
+
+        // The heartbeat extends the lease beyond the time-to-live.
+        let ttl = Duration::from_millis(300);
+        let lock = try_acquire("reconcile", ttl).await.unwrap().unwrap();
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert!(try_acquire("reconcile", ttl).await.unwrap().is_none());
+        assert!(!lock.is_lost());
+
+        // An expired lock held by a crashed process can be acquired by others,
+        // and the previous owner is notified that the lease has been lost.
+        let mut crashed_lock = lock;
+        crashed_lock.heartbeat.take().unwrap().abort();
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        let lock = try_acquire("reconcile", ttl).await.unwrap().unwrap();
+        crashed_lock.heartbeat = Some(spawn_heartbeat(&crashed_lock, ttl));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(crashed_lock.is_lost());
+        assert!(!lock.is_lost());
+
+        // Releasing a lost lock does not release the lock of the new owner.
+        crashed_lock.release().await.unwrap();
+        assert!(try_acquire("reconcile", ttl).await.unwrap().is_none());
+        lock.release().await.unwrap();
+    }
+}
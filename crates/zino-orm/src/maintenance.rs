@@ -0,0 +1,170 @@
+use super::{ttl::ExpiryCleanup, Schema};
+use std::{io, sync::Mutex, time::Duration};
+use zino_core::{
+    extension::TomlTableExt,
+    schedule::{AsyncJob, AsyncJobScheduler, AsyncScheduler},
+    state::State,
+};
+
+/// A scheduler for the maintenance jobs of the database, which are registered from the config.
+///
+/// The application clusters run it alongside the scheduler of the application
+/// when the `orm` feature is enabled, so the jobs need not be added manually:
+///
+/// - `[database.ttl]`: the cleanup of expired rows for the models with
+///   a `#[schema(expires_at)]` column. See [`ExpiryCleanup`].
+/// - `[database.partitioning]`: the management of the range partitions for the models
+///   with a `#[schema(partition_by)]` attribute in PostgreSQL.
+///   See [`PartitionManager`](crate::PartitionManager).
+/// - `[database.outbox]`: the relay and cleanup of the outbox events.
+///   See [`Outbox`](crate::Outbox).
+///
+/// The jobs of a model are registered once the table of the model has been initialized.
+pub struct MaintenanceScheduler {
+    /// The inner scheduler.
+    scheduler: AsyncJobScheduler,
+}
+
+impl MaintenanceScheduler {
+    /// Creates a new instance with the jobs which are not bound to the models.
+    pub fn new() -> Self {
+        #[cfg(feature = "outbox")]
+        if has_database_config("outbox") {
+            Self::register(super::Outbox::relay_job());
+            Self::register(super::Outbox::cleanup_job());
+        }
+        Self {
+            scheduler: AsyncJobScheduler::new(),
+        }
+    }
+
+    /// Registers a maintenance job, which will be added to the scheduler on the next tick.
+    /// The job with the same name is registered only once.
+    pub fn register(job: AsyncJob) {
+        let Some(job_name) = job.context().job_name() else {
+            tracing::warn!("the maintenance job should have a name");
+            return;
+        };
+        let mut registry = MAINTENANCE_JOBS
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        if !registry.job_names.iter().any(|name| name == job_name) {
+            registry.job_names.push(job_name.to_owned());
+            registry.pending_jobs.push(job);
+        }
+    }
+
+    /// Registers the maintenance jobs of the model according to the config.
+    pub(super) fn register_model<M: Schema>() {
+        if M::expiry_column().is_some() && has_database_config("ttl") {
+            Self::register(ExpiryCleanup::job::<M>());
+        }
+        #[cfg(feature = "orm-postgres")]
+        if M::partition_key().is_some_and(|(strategy, _)| strategy.eq_ignore_ascii_case("range"))
+            && has_database_config("partitioning")
+        {
+            Self::register(super::PartitionManager::job::<M>());
+        }
+    }
+
+    /// Adds the pending jobs to the inner scheduler.
+    fn add_pending_jobs(&mut self) {
+        let pending_jobs = {
+            let mut registry = MAINTENANCE_JOBS
+                .lock()
+                .unwrap_or_else(|err| err.into_inner());
+            std::mem::take(&mut registry.pending_jobs)
+        };
+        for job in pending_jobs {
+            self.scheduler.add(job);
+        }
+    }
+}
+
+impl Default for MaintenanceScheduler {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AsyncScheduler for MaintenanceScheduler {
+    #[inline]
+    fn is_ready(&self) -> bool {
+        // The jobs of the models can be registered after the scheduler has been started.
+        true
+    }
+
+    #[inline]
+    fn is_blocking(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn time_till_next_job(&self) -> Option<Duration> {
+        Some(self.scheduler.time_till_next_job())
+    }
+
+    async fn tick(&mut self) {
+        self.add_pending_jobs();
+        self.scheduler.tick().await;
+    }
+
+    #[inline]
+    async fn run(self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Returns `true` if the table for the key is present in the `[database]` config.
+fn has_database_config(key: &str) -> bool {
+    State::shared()
+        .get_config("database")
+        .is_some_and(|config| config.get_table(key).is_some())
+}
+
+/// Registry of the maintenance jobs.
+struct MaintenanceRegistry {
+    /// Names of the registered jobs.
+    job_names: Vec<String>,
+    /// Jobs which have not been added to the scheduler.
+    pending_jobs: Vec<AsyncJob>,
+}
+
+/// Registered maintenance jobs.
+static MAINTENANCE_JOBS: Mutex<MaintenanceRegistry> = Mutex::new(MaintenanceRegistry {
+    job_names: Vec::new(),
+    pending_jobs: Vec::new(),
+});
+
+#[cfg(test)]
+mod tests {
+    use super::MaintenanceScheduler;
+    use zino_core::{
+        schedule::{AsyncJob, AsyncScheduler, JobContext},
+        BoxFuture,
+    };
+
+    fn noop(_ctx: &mut JobContext) -> BoxFuture<'_> {
+        Box::pin(async {})
+    }
+
+    #[tokio::test]
+    async fn it_registers_jobs_once() {
+        let pending_count = || super::MAINTENANCE_JOBS.lock().unwrap().pending_jobs.len();
+        let mut scheduler = MaintenanceScheduler::new();
+        assert!(scheduler.is_ready());
+        assert!(!scheduler.scheduler.is_ready());
+
+        MaintenanceScheduler::register(AsyncJob::new("0 0 * * * *", noop).name("test_cleanup"));
+        MaintenanceScheduler::register(AsyncJob::new("0 0 * * * *", noop).name("test_cleanup"));
+        assert_eq!(pending_count(), 1);
+
+        scheduler.tick().await;
+        assert_eq!(pending_count(), 0);
+        assert!(scheduler.scheduler.is_ready());
+
+        MaintenanceScheduler::register(AsyncJob::new("0 0 * * * *", noop).name("test_cleanup"));
+        assert_eq!(pending_count(), 0);
+    }
+}
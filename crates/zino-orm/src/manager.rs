@@ -1,5 +1,5 @@
-use super::{pool::ConnectionPool, DatabasePool};
-use std::time::Duration;
+use super::{pool::ConnectionPool, DatabasePool, SessionSettings};
+use std::{sync::Arc, time::Duration};
 use toml::value::Table;
 use zino_core::extension::TomlTableExt;
 
@@ -11,6 +11,9 @@ pub trait PoolManager {
     /// Checks the availability of the connection pool.
     async fn check_availability(&self) -> bool;
 
+    /// Establishes the minimum number of connections eagerly.
+    async fn warm_up(&self);
+
     /// Shuts down the connection pool.
     async fn close(&self);
 }
@@ -18,7 +21,7 @@ pub trait PoolManager {
 #[cfg(feature = "orm-sqlx")]
 impl PoolManager for ConnectionPool<DatabasePool> {
     fn with_config(config: &'static Table) -> Self {
-        use sqlx::{pool::PoolOptions, Connection, Executor};
+        use sqlx::{pool::PoolOptions, Connection};
 
         let name = config.get_str("name").unwrap_or("main");
 
@@ -44,6 +47,8 @@ impl PoolManager for ConnectionPool<DatabasePool> {
             .get_duration("acquire-timeout")
             .unwrap_or_else(|| Duration::from_secs(60));
         let health_check_interval = config.get_u64("health-check-interval").unwrap_or(60);
+        let session_settings = Arc::new(SessionSettings::with_config(config));
+        let session = session_settings.clone();
         let pool = PoolOptions::<super::DatabaseDriver>::new()
             .max_connections(max_connections)
             .min_connections(min_connections)
@@ -70,26 +75,25 @@ impl PoolManager for ConnectionPool<DatabasePool> {
                     Ok(true)
                 })
             })
-            .after_connect(|conn, _meta| {
-                Box::pin(async move {
-                    if let Some(time_zone) = super::TIME_ZONE.get() {
-                        if cfg!(any(
-                            feature = "orm-mariadb",
-                            feature = "orm-mysql",
-                            feature = "orm-tidb"
-                        )) {
-                            let sql = format!("SET time_zone = '{time_zone}';");
-                            conn.execute(sql.as_str()).await?;
-                        } else if cfg!(feature = "orm-postgres") {
-                            let sql = format!("SET TIME ZONE '{time_zone}';");
-                            conn.execute(sql.as_str()).await?;
-                        }
-                    }
-                    Ok(())
-                })
+            .after_connect(move |conn, _meta| {
+                let session = session.clone();
+                Box::pin(async move { session.apply(conn).await })
             })
             .connect_lazy_with(connect_options);
-        Self::new(name, database, pool)
+        let mut cp = Self::new(name, database, pool);
+        cp.set_session_settings(session_settings);
+        if let Some(max_waiting) = config.get_usize("max-waiting") {
+            cp.set_max_waiting(max_waiting);
+        }
+        if let Some(max_timeouts) = config.get_usize("max-acquire-timeouts") {
+            cp.set_max_timeouts(max_timeouts);
+        }
+
+        let single_writer = config
+            .get_bool("single-writer")
+            .unwrap_or(super::DRIVER_NAME == "sqlite");
+        cp.set_single_writer(single_writer);
+        cp
     }
 
     async fn check_availability(&self) -> bool {
@@ -104,6 +108,25 @@ impl PoolManager for ConnectionPool<DatabasePool> {
         }
     }
 
+    async fn warm_up(&self) {
+        let pool = self.pool();
+        let min_connections = pool.options().get_min_connections();
+        let num_connections = pool.size();
+        if min_connections > num_connections {
+            let connections = futures::future::join_all(
+                (num_connections..min_connections).map(|_| pool.acquire()),
+            )
+            .await;
+            let name = self.name();
+            let num_established = connections.iter().filter(|r| r.is_ok()).count();
+            tracing::info!(
+                min_connections,
+                num_established,
+                "warm up the connection pool for the `{name}` service"
+            );
+        }
+    }
+
     async fn close(&self) {
         let name = self.name();
         tracing::warn!("closing the connection pool for the `{name}` service");
@@ -171,18 +194,159 @@ cfg_if::cfg_if! {
             connect_options
         }
     } else {
-        use sqlx::sqlite::SqliteConnectOptions;
+        use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqliteSynchronous};
         use zino_core::application::{Agent, Application};
 
         /// Options and flags which can be used to configure a SQLite connection.
+        ///
+        /// The pragmas are applied on connect, and the defaults are tuned for concurrency:
+        ///
+        /// ```toml
+        /// [[sqlite]]
+        /// database = "data/main.db"
+        /// journal-mode = "wal"
+        /// synchronous = "normal"
+        /// busy-timeout = "5s"
+        /// foreign-keys = true
+        /// mmap-size = 268435456
+        /// cache-size = -64000
+        /// ```
         fn new_connect_options(database: &'static str, config: &'static Table) -> SqliteConnectOptions {
-            let mut connect_options = SqliteConnectOptions::new().create_if_missing(true);
+            let journal_mode = config
+                .get_str("journal-mode")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(SqliteJournalMode::Wal);
+            let synchronous = config
+                .get_str("synchronous")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(SqliteSynchronous::Normal);
+            let busy_timeout = config
+                .get_duration("busy-timeout")
+                .unwrap_or_else(|| Duration::from_secs(5));
+            let foreign_keys = config.get_bool("foreign-keys").unwrap_or(true);
+            let mut connect_options = SqliteConnectOptions::new()
+                .create_if_missing(true)
+                .journal_mode(journal_mode)
+                .synchronous(synchronous)
+                .busy_timeout(busy_timeout)
+                .foreign_keys(foreign_keys);
             if let Some(read_only) = config.get_bool("read-only") {
                 connect_options = connect_options.read_only(read_only);
             }
+            if let Some(mmap_size) = config.get_i64("mmap-size") {
+                connect_options = connect_options.pragma("mmap_size", mmap_size.to_string());
+            }
+            if let Some(cache_size) = config.get_i64("cache-size") {
+                connect_options = connect_options.pragma("cache_size", cache_size.to_string());
+            }
 
             let database_path = Agent::parse_path(database);
             connect_options.filename(database_path)
         }
     }
 }
+
+#[cfg(test)]
+#[cfg(all(
+    feature = "orm-sqlx",
+    not(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb",
+        feature = "orm-postgres"
+    ))
+))]
+mod tests {
+    use super::{ConnectionPool, PoolManager};
+    use crate::executor::Executor;
+    use sqlx::Row;
+    use toml::Table;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn it_handles_concurrent_reads_and_writes() {
+        let path = std::env::temp_dir().join(format!("zino-orm-{}.db", std::process::id()));
+        let database = path.to_string_lossy();
+        let config = format!(r#"database = "{database}""#);
+        let config: &'static Table = Box::leak(Box::new(
+            config
+                .parse()
+                .expect("the config should be a valid TOML table"),
+        ));
+        let cp: &'static ConnectionPool = Box::leak(Box::new(ConnectionPool::with_config(config)));
+        cp.execute("CREATE TABLE IF NOT EXISTS events (id INTEGER PRIMARY KEY, task INTEGER)")
+            .await
+            .expect("fail to create the table");
+
+        // Hammer the pool with the default pragmas and the single-writer strategy.
+        let writers = (0..16).map(|task| {
+            tokio::spawn(async move {
+                for _ in 0..50 {
+                    cp.execute_with("INSERT INTO events (task) VALUES (?)", &[task])
+                        .await?;
+                }
+                Ok::<_, zino_core::error::Error>(())
+            })
+        });
+        let readers = (0..16).map(|_| {
+            tokio::spawn(async move {
+                for _ in 0..50 {
+                    cp.fetch("SELECT count(*) FROM events").await?;
+                }
+                Ok::<_, zino_core::error::Error>(())
+            })
+        });
+        let handles = writers.chain(readers).collect::<Vec<_>>();
+        for result in futures::future::join_all(handles).await {
+            let result = result.expect("the task should not panic");
+            if let Err(err) = result {
+                panic!("fail to read or write concurrently: {err}");
+            }
+        }
+
+        let row = cp
+            .fetch_one("SELECT count(*) FROM events")
+            .await
+            .expect("fail to count the rows");
+        assert_eq!(row.get::<i64, _>(0), 800);
+
+        cp.close().await;
+        for suffix in ["", "-wal", "-shm"] {
+            std::fs::remove_file(format!("{database}{suffix}")).ok();
+        }
+    }
+
+    #[tokio::test]
+    async fn it_skips_init_sql_on_second_checkout() {
+        let path = std::env::temp_dir().join(format!("zino-orm-session-{}.db", std::process::id()));
+        let database = path.to_string_lossy();
+        let config = format!(
+            r#"
+                database = "{database}"
+                max-connections = 1
+                init-sql = [
+                    "CREATE TEMP TABLE session_init (id INTEGER)",
+                    "INSERT INTO session_init VALUES (1)",
+                ]
+            "#
+        );
+        let config: &'static Table = Box::leak(Box::new(
+            config
+                .parse()
+                .expect("the config should be a valid TOML table"),
+        ));
+        let cp = ConnectionPool::with_config(config);
+        for _ in 0..2 {
+            let row = cp
+                .fetch_one("SELECT count(*) FROM session_init")
+                .await
+                .expect("fail to query the session table");
+            assert_eq!(row.get::<i64, _>(0), 1);
+            assert_eq!(cp.session_settings().initialized_connections(), 1);
+        }
+
+        cp.close().await;
+        for suffix in ["", "-wal", "-shm"] {
+            std::fs::remove_file(format!("{database}{suffix}")).ok();
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use super::Schema;
+use zino_core::{
+    error::Error,
+    extension::{JsonObjectExt, JsonValueExt},
+    model::Query,
+    Map,
+};
+
+/// Checks that the sums of the money columns are restricted to a single currency,
+/// i.e. the currency column is filtered or used in the `GROUP BY` clause.
+pub(super) fn check_aggregation<M: Schema>(query: &Query) -> Result<(), Error> {
+    for col in M::columns() {
+        let Some(field) = col.extra().get_str("money_field") else {
+            continue;
+        };
+        let amount_field = col.name();
+        if !amount_field.ends_with("_amount") {
+            continue;
+        }
+
+        let summed = query.fields().iter().any(|projection| {
+            let expr = projection
+                .split_once(':')
+                .map_or(projection.as_str(), |(_, expr)| expr);
+            expr.trim_start().to_ascii_lowercase().starts_with("sum(")
+                && contains_field(expr, amount_field)
+        });
+        if summed {
+            let currency_field = [field, "_currency"].concat();
+            let filters = query.filters();
+            let grouped = filters.get_array("$group").is_some_and(|values| {
+                values
+                    .iter()
+                    .filter_map(|value| value.as_str())
+                    .any(|group_field| contains_field(group_field, &currency_field))
+            });
+            if !grouped && !has_filter(filters, &currency_field) {
+                let message = format!(
+                    "the sum of the money field `{field}` should be filtered \
+                        or grouped by the `{currency_field}` column"
+                );
+                return Err(Error::new(message));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns `true` if the filters restrict the field in the logical `AND` conditions.
+fn has_filter(filters: &Map, field: &str) -> bool {
+    filters.iter().any(|(key, value)| {
+        if key == "$and" {
+            value.as_array().is_some_and(|conditions| {
+                conditions
+                    .iter()
+                    .filter_map(|condition| condition.as_object())
+                    .any(|condition| has_filter(condition, field))
+            })
+        } else {
+            !key.starts_with('$') && !value.is_ignorable() && contains_field(key, field)
+        }
+    })
+}
+
+/// Returns `true` if the expression refers to the field.
+fn contains_field(expr: &str, field: &str) -> bool {
+    expr.match_indices(field).any(|(index, _)| {
+        let is_boundary = |c: char| !(c.is_ascii_alphanumeric() || c == '_');
+        let before = expr[..index].chars().next_back().map_or(true, is_boundary);
+        let after = expr[index + field.len()..]
+            .chars()
+            .next()
+            .map_or(true, is_boundary);
+        before && after
+    })
+}
@@ -4,7 +4,7 @@ use std::marker::PhantomData;
 use zino_core::{
     datetime::DateTime,
     extension::JsonObjectExt,
-    model::{Mutation, Query},
+    model::{Column, Mutation, Query},
     JsonValue, Map,
 };
 
@@ -25,6 +25,14 @@ use zino_core::{
 ///     .inc_one(UserColumn::Version)
 ///     .build();
 /// let ctx = User::update_one(&query, &mut mutation).await?;
+///
+/// let mut mutation = MutationBuilder::<User>::new()
+///     .dec(UserColumn::Balance, 100)
+///     .where_gte(UserColumn::Balance, 100)
+///     .append_array(UserColumn::Tags, "vip")
+///     .json_set(UserColumn::Extra, "profile.level", 2)
+///     .build();
+/// let rows_affected = User::apply_mutation_by_id(&user_id, &mut mutation).await?;
 /// ```
 #[derive(Debug, Clone)]
 pub struct MutationBuilder<E: Entity> {
@@ -38,6 +46,18 @@ pub struct MutationBuilder<E: Entity> {
     min_ops: Map,
     /// `$max` operations.
     max_ops: Map,
+    /// `$dec` operations.
+    dec_ops: Map,
+    /// `$push` operations.
+    push_ops: Map,
+    /// `$pull` operations.
+    pull_ops: Map,
+    /// `$jsonSet` operations.
+    json_set_ops: Map,
+    /// `$coalesce` operations.
+    coalesce_ops: Map,
+    /// Guards of the mutation.
+    guards: Map,
     /// The phantom data.
     phantom: PhantomData<E>,
 }
@@ -52,6 +72,12 @@ impl<E: Entity> MutationBuilder<E> {
             mul_ops: Map::new(),
             min_ops: Map::new(),
             max_ops: Map::new(),
+            dec_ops: Map::new(),
+            push_ops: Map::new(),
+            pull_ops: Map::new(),
+            json_set_ops: Map::new(),
+            coalesce_ops: Map::new(),
+            guards: Map::new(),
             phantom: PhantomData,
         }
     }
@@ -111,6 +137,20 @@ impl<E: Entity> MutationBuilder<E> {
         self
     }
 
+    /// Decrements the value of a column.
+    #[inline]
+    pub fn dec(mut self, col: E::Column, value: impl IntoSqlValue) -> Self {
+        self.dec_ops.upsert(col.as_ref(), value.into_sql_value());
+        self
+    }
+
+    /// Decrements the value of a column by 1.
+    #[inline]
+    pub fn dec_one(mut self, col: E::Column) -> Self {
+        self.dec_ops.upsert(col.as_ref(), 1);
+        self
+    }
+
     /// Multiplies the value of a column by a number.
     #[inline]
     pub fn mul(mut self, col: E::Column, value: impl IntoSqlValue) -> Self {
@@ -134,6 +174,62 @@ impl<E: Entity> MutationBuilder<E> {
         self
     }
 
+    /// Appends a value to the array of a column.
+    /// All the elements will be appended if the value is an array.
+    #[inline]
+    pub fn append_array(mut self, col: E::Column, value: impl IntoSqlValue) -> Self {
+        self.push_ops.upsert(col.as_ref(), value.into_sql_value());
+        self
+    }
+
+    /// Removes all the occurrences of a value from the array of a column.
+    /// All the elements will be removed if the value is an array.
+    #[inline]
+    pub fn remove_array(mut self, col: E::Column, value: impl IntoSqlValue) -> Self {
+        self.pull_ops.upsert(col.as_ref(), value.into_sql_value());
+        self
+    }
+
+    /// Sets the value of a JSON column at the path, such as `profile.level`.
+    /// The missing objects along the path will be created.
+    pub fn json_set(mut self, col: E::Column, path: &str, value: impl IntoSqlValue) -> Self {
+        let key = col.as_ref();
+        let value = value.into_sql_value();
+        if let Some(paths) = self
+            .json_set_ops
+            .get_mut(key)
+            .and_then(|v| v.as_object_mut())
+        {
+            paths.upsert(path, value);
+        } else {
+            self.json_set_ops.upsert(key, Map::from_entry(path, value));
+        }
+        self
+    }
+
+    /// Sets the value of a column if the current value is null.
+    #[inline]
+    pub fn set_if_null(mut self, col: E::Column, value: impl IntoSqlValue) -> Self {
+        self.coalesce_ops
+            .upsert(col.as_ref(), value.into_sql_value());
+        self
+    }
+
+    /// Guards the mutation by requiring the current value of a column
+    /// to be greater than or equal to a specified value. If the guard is not satisfied,
+    /// the mutation affects zero rows, which is useful to prevent the negative balances.
+    /// The guards are applied by [`Schema::update_one`] and [`Schema::update_many`].
+    pub fn where_gte(mut self, col: E::Column, value: impl IntoSqlValue) -> Self {
+        let key = col.as_ref();
+        let value = value.into_sql_value();
+        if let Some(guard) = self.guards.get_mut(key).and_then(|v| v.as_object_mut()) {
+            guard.upsert("$ge", value);
+        } else {
+            self.guards.upsert(key, Map::from_entry("$ge", value));
+        }
+        self
+    }
+
     /// Builds the model mutation.
     pub fn build(self) -> Mutation {
         let mut updates = self.updates;
@@ -141,6 +237,12 @@ impl<E: Entity> MutationBuilder<E> {
         let mul_ops = self.mul_ops;
         let min_ops = self.min_ops;
         let max_ops = self.max_ops;
+        let dec_ops = self.dec_ops;
+        let push_ops = self.push_ops;
+        let pull_ops = self.pull_ops;
+        let json_set_ops = self.json_set_ops;
+        let coalesce_ops = self.coalesce_ops;
+        let guards = self.guards;
         if !inc_ops.is_empty() {
             updates.upsert("$inc", inc_ops);
         }
@@ -153,6 +255,24 @@ impl<E: Entity> MutationBuilder<E> {
         if !max_ops.is_empty() {
             updates.upsert("$max", max_ops);
         }
+        if !dec_ops.is_empty() {
+            updates.upsert("$dec", dec_ops);
+        }
+        if !push_ops.is_empty() {
+            updates.upsert("$push", push_ops);
+        }
+        if !pull_ops.is_empty() {
+            updates.upsert("$pull", pull_ops);
+        }
+        if !json_set_ops.is_empty() {
+            updates.upsert("$jsonSet", json_set_ops);
+        }
+        if !coalesce_ops.is_empty() {
+            updates.upsert("$coalesce", coalesce_ops);
+        }
+        if !guards.is_empty() {
+            updates.upsert("$guard", guards);
+        }
         Mutation::new(updates)
     }
 }
@@ -168,6 +288,10 @@ impl<E: Entity> Default for MutationBuilder<E> {
 pub(super) trait MutationExt<DB> {
     /// Formats the updates to generate SQL `SET` expression.
     fn format_updates<M: Schema>(&self) -> String;
+
+    /// Returns a new query with the guards of the mutation applied,
+    /// or `None` if there are no guards.
+    fn guarded_query(&self, query: &Query) -> Option<Query>;
 }
 
 impl MutationExt<DatabaseDriver> for Mutation {
@@ -246,6 +370,76 @@ impl MutationExt<DatabaseDriver> for Mutation {
                         }
                     }
                 }
+                "$dec" => {
+                    if let Some(update) = value.as_object() {
+                        for (key, value) in update.iter() {
+                            if permissive || fields.contains(key) {
+                                if let Some(col) = M::get_writable_column(key) {
+                                    let key = Query::format_field(key);
+                                    let value = col.encode_value(Some(value));
+                                    let mutation = format!(r#"{key} = {key} - {value}"#);
+                                    mutations.push(mutation);
+                                }
+                            }
+                        }
+                    }
+                }
+                "$push" => {
+                    if let Some(update) = value.as_object() {
+                        for (key, value) in update.iter() {
+                            if permissive || fields.contains(key) {
+                                if let Some(col) = M::get_writable_column(key) {
+                                    let field = Query::format_field(key);
+                                    let mutation = format_array_append(col, &field, value);
+                                    mutations.push(mutation);
+                                }
+                            }
+                        }
+                    }
+                }
+                "$pull" => {
+                    if let Some(update) = value.as_object() {
+                        for (key, value) in update.iter() {
+                            if permissive || fields.contains(key) {
+                                if let Some(col) = M::get_writable_column(key) {
+                                    let field = Query::format_field(key);
+                                    let mutation = format_array_remove(col, &field, value);
+                                    mutations.push(mutation);
+                                }
+                            }
+                        }
+                    }
+                }
+                "$jsonSet" => {
+                    if let Some(update) = value.as_object() {
+                        for (key, value) in update.iter() {
+                            if permissive || fields.contains(key) {
+                                if let Some(paths) = value.as_object() {
+                                    if M::get_writable_column(key).is_some() {
+                                        let field = Query::format_field(key);
+                                        let mutation = format_json_set(&field, paths);
+                                        mutations.push(mutation);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                "$coalesce" => {
+                    if let Some(update) = value.as_object() {
+                        for (key, value) in update.iter() {
+                            if permissive || fields.contains(key) {
+                                if let Some(col) = M::get_writable_column(key) {
+                                    let key = Query::format_field(key);
+                                    let value = col.encode_value(Some(value));
+                                    let mutation = format!(r#"{key} = COALESCE({key}, {value})"#);
+                                    mutations.push(mutation);
+                                }
+                            }
+                        }
+                    }
+                }
+                "$guard" => {}
                 _ => {
                     if permissive || fields.contains(key) {
                         if let Some(col) = M::get_writable_column(key) {
@@ -266,4 +460,304 @@ impl MutationExt<DatabaseDriver> for Mutation {
         }
         mutations.join(", ")
     }
+
+    fn guarded_query(&self, query: &Query) -> Option<Query> {
+        let guards = self.updates().get_object("$guard")?;
+        let mut query = query.clone();
+        query.append_filters(&mut guards.clone());
+        Some(query)
+    }
+}
+
+/// Formats the SQL expression to append the elements to an array column.
+fn format_array_append(col: &Column<'_>, field: &str, value: &JsonValue) -> String {
+    let elements = match value {
+        JsonValue::Array(vec) => vec.clone(),
+        _ => vec![value.clone()],
+    };
+    if cfg!(feature = "orm-postgres") {
+        let array = JsonValue::Array(elements);
+        let values = col.encode_value(Some(&array));
+        format!(r#"{field} = array_cat({field}, {values})"#)
+    } else if cfg!(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb"
+    )) {
+        let values = elements
+            .iter()
+            .map(|v| format!("'$', {}", encode_element(col, v)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(r#"{field} = JSON_ARRAY_APPEND(COALESCE({field}, JSON_ARRAY()), {values})"#)
+    } else {
+        let values = elements
+            .iter()
+            .map(|v| format!("'$[#]', {}", encode_element(col, v)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(r#"{field} = json_insert(COALESCE({field}, '[]'), {values})"#)
+    }
+}
+
+/// Formats the SQL expression to remove the elements from an array column.
+fn format_array_remove(col: &Column<'_>, field: &str, value: &JsonValue) -> String {
+    let elements = match value {
+        JsonValue::Array(vec) => vec.clone(),
+        _ => vec![value.clone()],
+    };
+    if cfg!(feature = "orm-postgres") {
+        let expr = elements.into_iter().fold(field.to_owned(), |expr, v| {
+            let array = JsonValue::Array(vec![v]);
+            let value = col.encode_value(Some(&array));
+            format!("array_remove({expr}, ({value})[1])")
+        });
+        format!(r#"{field} = {expr}"#)
+    } else if cfg!(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb"
+    )) {
+        let array = JsonValue::Array(elements);
+        let values = col.encode_value(Some(&array));
+        format!(
+            r#"{field} = COALESCE((SELECT JSON_ARRAYAGG(t.v) FROM JSON_TABLE({field}, '$[*]' COLUMNS (v JSON PATH '$')) AS t WHERE NOT JSON_CONTAINS({values}, t.v)), JSON_ARRAY())"#
+        )
+    } else {
+        let values = elements
+            .iter()
+            .map(|v| encode_element(col, v))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            r#"{field} = (SELECT json_group_array(value) FROM json_each({field}) WHERE value NOT IN ({values}))"#
+        )
+    }
+}
+
+/// Formats the SQL expression to set the values at the paths of a JSON column.
+fn format_json_set(field: &str, paths: &Map) -> String {
+    let expr = if cfg!(feature = "orm-postgres") {
+        paths.iter().fold(
+            format!("COALESCE({field}, '{{}}'::jsonb)"),
+            |expr, (path, value)| {
+                let path = path.trim_start_matches("$.").replace('.', ",");
+                let path = Query::escape_string(format!("{{{path}}}"));
+                let value = Query::escape_string(value);
+                format!("jsonb_set({expr}, {path}, {value}::jsonb, true)")
+            },
+        )
+    } else if cfg!(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb"
+    )) {
+        let values = paths
+            .iter()
+            .map(|(path, value)| {
+                let path = Query::escape_string(format_json_path(path));
+                let value = Query::escape_string(value);
+                format!("{path}, CAST({value} AS JSON)")
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("JSON_SET(COALESCE({field}, JSON_OBJECT()), {values})")
+    } else {
+        let values = paths
+            .iter()
+            .map(|(path, value)| {
+                let path = Query::escape_string(format_json_path(path));
+                let value = Query::escape_string(value);
+                format!("{path}, json({value})")
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("json_set(COALESCE({field}, '{{}}'), {values})")
+    };
+    format!(r#"{field} = {expr}"#)
+}
+
+/// Formats the JSON path for MySQL and SQLite.
+fn format_json_path(path: &str) -> String {
+    if path.starts_with('$') {
+        path.to_owned()
+    } else {
+        format!("$.{path}")
+    }
+}
+
+/// Encodes an element of the array stored as JSON.
+fn encode_element(col: &Column<'_>, value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(value) => Query::escape_string(value),
+        _ => col.encode_value(Some(value)).into_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MutationBuilder, MutationExt};
+    use serde::{Deserialize, Serialize};
+    use zino_core::{extension::JsonObjectExt, json, model::Mutation, Map};
+
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    struct Account {
+        id: i64,
+        balance: i64,
+        tags: Vec<String>,
+        extra: Map,
+        nickname: String,
+    }
+
+    impl_test_schema!(Account, AccountColumn, "account", "accounts", [
+        Id => ("id", "i64"),
+        Balance => ("balance", "i64"),
+        Tags => ("tags", "Vec<String>"),
+        Extra => ("extra", "Map"),
+        Nickname => ("nickname", "String"),
+    ]);
+
+    #[test]
+    fn it_composes_operations() {
+        let mutation = MutationBuilder::<Account>::new()
+            .set(AccountColumn::Nickname, "alice")
+            .dec(AccountColumn::Balance, 30)
+            .where_gte(AccountColumn::Balance, 30)
+            .append_array(AccountColumn::Tags, ["b", "c"])
+            .json_set(AccountColumn::Extra, "profile.level", 2)
+            .json_set(AccountColumn::Extra, "profile.active", true)
+            .build();
+        let updates = mutation.updates();
+        assert_eq!(updates.get_str("nickname"), Some("alice"));
+        assert_eq!(updates.get("$dec"), Some(&json!({ "balance": 30 })));
+        assert_eq!(
+            updates.get("$guard"),
+            Some(&json!({ "balance": { "$ge": 30 } }))
+        );
+        assert_eq!(updates.get("$push"), Some(&json!({ "tags": ["b", "c"] })));
+        assert_eq!(
+            updates.get("$jsonSet"),
+            Some(&json!({ "extra": { "profile.level": 2, "profile.active": true } }))
+        );
+
+        let sql = mutation.format_updates::<Account>();
+        let expected = if cfg!(feature = "orm-postgres") {
+            r#""balance" = "balance" - 30, "extra" = jsonb_set(jsonb_set(COALESCE("extra", '{}'::jsonb), '{profile,active}', 'true'::jsonb, true), '{profile,level}', '2'::jsonb, true), "tags" = array_cat("tags", ARRAY['b','c']::TEXT[]), "nickname" = 'alice'"#
+        } else if cfg!(any(
+            feature = "orm-mariadb",
+            feature = "orm-mysql",
+            feature = "orm-tidb"
+        )) {
+            r#"`balance` = `balance` - 30, `extra` = JSON_SET(COALESCE(`extra`, JSON_OBJECT()), '$.profile.active', CAST('true' AS JSON), '$.profile.level', CAST('2' AS JSON)), `tags` = JSON_ARRAY_APPEND(COALESCE(`tags`, JSON_ARRAY()), '$', 'b', '$', 'c'), `nickname` = 'alice'"#
+        } else {
+            r#"`balance` = `balance` - 30, `extra` = json_set(COALESCE(`extra`, '{}'), '$.profile.active', json('true'), '$.profile.level', json('2')), `tags` = json_insert(COALESCE(`tags`, '[]'), '$[#]', 'b', '$[#]', 'c'), `nickname` = 'alice'"#
+        };
+        assert_eq!(sql, expected);
+        assert!(Mutation::default()
+            .guarded_query(&Default::default())
+            .is_none());
+    }
+
+    // The test runs against an in-memory database when SQLite is the driver.
+    #[cfg(not(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-postgres",
+        feature = "orm-tidb"
+    )))]
+    #[tokio::test]
+    async fn it_applies_atomic_operations() {
+        use crate::{executor::encode_arguments, query::QueryExt};
+        use sqlx::{sqlite::SqliteConnectOptions, Executor, Row, SqlitePool};
+        use zino_core::{model::Query, JsonValue};
+
+        let pool =
+            SqlitePool::connect_with("sqlite::memory:".parse::<SqliteConnectOptions>().unwrap())
+                .await
+                .expect("fail to connect to the in-memory database");
+        pool.execute(
+            "CREATE TABLE accounts (id INTEGER PRIMARY KEY, balance INTEGER NOT NULL, \
+                tags TEXT NOT NULL, extra TEXT, nickname TEXT);
+            INSERT INTO accounts VALUES (1, 100, '[\"a\"]', NULL, NULL);",
+        )
+        .await
+        .expect("fail to prepare the table");
+
+        // Executes the mutation in the same way as `Schema::update_one`.
+        let update = |mutation: Mutation| {
+            let pool = &pool;
+            async move {
+                let query = Query::from_entry("id", 1);
+                let query = mutation.guarded_query(&query).unwrap_or(query);
+                let mut arguments = Vec::new();
+                let filters = query.format_filters::<Account>(&mut arguments);
+                let updates = mutation.format_updates::<Account>();
+                let sql = format!("UPDATE accounts SET {updates} {filters};");
+                let arguments = encode_arguments(&arguments).expect("fail to encode the arguments");
+                sqlx::query_with(&sql, arguments)
+                    .execute(pool)
+                    .await
+                    .expect("fail to execute the mutation")
+                    .rows_affected()
+            }
+        };
+        let fetch = || async {
+            let row = sqlx::query("SELECT * FROM accounts WHERE id = 1;")
+                .fetch_one(&pool)
+                .await
+                .expect("fail to fetch the account");
+            let tags = row.get::<String, _>("tags");
+            let extra = row.get::<Option<String>, _>("extra");
+            (
+                row.get::<i64, _>("balance"),
+                serde_json::from_str::<JsonValue>(&tags).unwrap(),
+                extra.map(|s| serde_json::from_str::<JsonValue>(&s).unwrap()),
+                row.get::<Option<String>, _>("nickname"),
+            )
+        };
+
+        let mutation = MutationBuilder::<Account>::new()
+            .dec(AccountColumn::Balance, 30)
+            .where_gte(AccountColumn::Balance, 30)
+            .append_array(AccountColumn::Tags, ["b", "c"])
+            .json_set(AccountColumn::Extra, "profile.level", 2)
+            .set_if_null(AccountColumn::Nickname, "alice")
+            .build();
+        assert_eq!(update(mutation).await, 1);
+        assert_eq!(
+            fetch().await,
+            (
+                70,
+                json!(["a", "b", "c"]),
+                Some(json!({ "profile": { "level": 2 } })),
+                Some("alice".to_owned())
+            )
+        );
+
+        // The operations compose with each other on the current values.
+        let mutation = MutationBuilder::<Account>::new()
+            .inc(AccountColumn::Balance, 5)
+            .remove_array(AccountColumn::Tags, "a")
+            .json_set(AccountColumn::Extra, "profile.active", true)
+            .set_if_null(AccountColumn::Nickname, "bob")
+            .build();
+        assert_eq!(update(mutation).await, 1);
+        assert_eq!(
+            fetch().await,
+            (
+                75,
+                json!(["b", "c"]),
+                Some(json!({ "profile": { "level": 2, "active": true } })),
+                Some("alice".to_owned())
+            )
+        );
+
+        // The guard prevents the balance from going below zero.
+        let mutation = MutationBuilder::<Account>::new()
+            .dec(AccountColumn::Balance, 100)
+            .where_gte(AccountColumn::Balance, 100)
+            .build();
+        assert_eq!(update(mutation).await, 0);
+        assert_eq!(fetch().await.0, 75);
+    }
 }
@@ -48,6 +48,7 @@ impl EncodeColumn<DatabaseDriver> for Column<'_> {
                     "CHAR(36)"
                 }
             }
+            "Ulid" | "Option<Ulid>" => "CHAR(26)",
             "Vec<u8>" => "BLOB",
             "Vec<String>" | "Vec<Uuid>" | "Vec<u64>" | "Vec<i64>" | "Vec<u32>" | "Vec<i32>"
             | "Map" => "JSON",
@@ -79,6 +80,9 @@ impl EncodeColumn<DatabaseDriver> for Column<'_> {
                         self.format_value(value)
                     }
                 }
+                JsonValue::Array(_) if self.type_name() == "Map" => {
+                    Query::escape_string(value).into()
+                }
                 JsonValue::Array(value) => {
                     let values = value
                         .iter()
@@ -190,6 +194,9 @@ impl EncodeColumn<DatabaseDriver> for Column<'_> {
                         "$rlike" => "RLIKE",
                         "$is" => "IS",
                         "$size" => "json_length",
+                        "$size_gt" => "json_length_gt",
+                        "$contains" => "json_contains",
+                        "$overlaps" => "json_overlaps",
                         _ => {
                             if cfg!(debug_assertions) && name.starts_with('$') {
                                 tracing::warn!("unsupported operator `{name}` for MySQL");
@@ -238,6 +245,15 @@ impl EncodeColumn<DatabaseDriver> for Column<'_> {
                             let condition = format!(r#"json_length({field}) = {length}"#);
                             conditions.push(condition);
                         }
+                    } else if operator == "json_length_gt" {
+                        if let Some(Ok(length)) = value.parse_usize() {
+                            let condition = format!(r#"json_length({field}) > {length}"#);
+                            conditions.push(condition);
+                        }
+                    } else if operator == "json_contains" || operator == "json_overlaps" {
+                        let value = self.encode_value(Some(value));
+                        let condition = format!(r#"{operator}({field}, {value})"#);
+                        conditions.push(condition);
                     } else {
                         let value = self.encode_value(Some(value));
                         let condition = format!(r#"{field} {operator} {value}"#);
@@ -380,7 +396,7 @@ impl EncodeColumn<DatabaseDriver> for Column<'_> {
                     format!(r#"{field} = {value}"#)
                 }
             }
-            "Uuid" | "Option<Uuid>" => {
+            "Uuid" | "Option<Uuid>" | "Ulid" | "Option<Ulid>" => {
                 if let Some(value) = value.as_str() {
                     if value == "null" {
                         format!(r#"{field} IS NULL"#)
@@ -614,6 +630,11 @@ impl QueryExt<DatabaseDriver> for Query {
         self.limit()
     }
 
+    #[inline]
+    fn query_extra_flags(&self) -> &Map {
+        self.extra_flags()
+    }
+
     #[inline]
     fn placeholder(_n: usize) -> SharedString {
         "?".into()
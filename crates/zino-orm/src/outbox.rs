@@ -0,0 +1,421 @@
+use super::{
+    decode, executor::Executor, query::QueryExt, ttl, DatabaseDriver, DatabaseRow, DecodeRow,
+    GlobalPool,
+};
+use std::{
+    collections::HashSet,
+    sync::atomic::{AtomicBool, Ordering::Relaxed},
+    time::Duration,
+};
+use zino_channel::{CloudEvent, MessageChannel};
+use zino_core::{
+    datetime::DateTime,
+    error::Error,
+    extension::TomlTableExt,
+    model::Query,
+    schedule::{AsyncJob, JobContext, OverlapPolicy},
+    state::State,
+    warn, BoxFuture, LazyLock, Uuid,
+};
+
+/// A transactional outbox for cloud events persisted in the `_zino_outbox` table.
+///
+/// An event is enqueued inside of the same transaction as the business write,
+/// so that it is published if and only if the transaction has been committed.
+/// The [`relay_job()`](Self::relay_job) polls the unpublished events in order
+/// and publishes them through the shared [`MessageChannel`] without blocking,
+/// so a full channel is treated as a failure to be retried. The delivery is at-least-once,
+/// and the events of the same aggregate, i.e. the session ID or the source of the event,
+/// are published sequentially: a failed event is retried with exponential backoff
+/// and blocks the subsequent events of its aggregate until it succeeds
+/// or is marked as `dead` after the maximum attempts.
+///
+/// It can be configured in the `[database.outbox]` table:
+///
+/// ```toml
+/// [database.outbox]
+/// database = "main"
+/// batch-size = 100
+/// poll-interval = "1s"
+/// retry-interval = "1s"
+/// max-backoff = "5m"
+/// max-attempts = 10
+/// retention = "7d"
+/// ```
+///
+/// When the table is present, the relay and cleanup jobs are registered with
+/// the [`MaintenanceScheduler`](crate::MaintenanceScheduler) at the startup of the cluster.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino_channel::CloudEvent;
+/// use zino_orm::{Outbox, Schema, Transaction};
+///
+/// let order_ctx = order.prepare_insert()?;
+/// let mut event = CloudEvent::new(order.id(), "orders", "order.created");
+/// event.set_session_id(order.customer_id());
+/// event.set_data(order.snapshot());
+///
+/// Order::transaction(move |tx| Box::pin(async move {
+///     tx.execute(order_ctx.query()).await?;
+///     Outbox::enqueue(event, tx).await?;
+///     Ok(())
+/// })).await?;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Outbox;
+
+impl Outbox {
+    /// Enqueues the event inside of the transaction.
+    pub async fn enqueue(
+        event: CloudEvent,
+        tx: &mut sqlx::Transaction<'_, DatabaseDriver>,
+    ) -> Result<(), Error> {
+        // The table should not be created inside of the transaction,
+        // since DDL statements cause an implicit commit in MySQL.
+        create_table().await?;
+
+        let id = Uuid::now_v7().to_string();
+        let aggregate_key = event.session_id().unwrap_or_else(|| event.source());
+        let event_id = Query::escape_string(event.id());
+        let aggregate_key = Query::escape_string(aggregate_key);
+        let event_type = Query::escape_string(event.event_type());
+        let payload = Query::escape_string(serde_json::to_string(&event)?);
+        let now = DateTime::now().timestamp_millis();
+        let sql = format!(
+            "INSERT INTO {OUTBOX_TABLE} \
+                (id, event_id, aggregate_key, event_type, payload, status, attempts, \
+                    next_attempt_at, created_at) \
+                VALUES ('{id}', {event_id}, {aggregate_key}, {event_type}, {payload}, \
+                    'pending', 0, {now}, {now});"
+        );
+        (&mut **tx).execute(&sql).await?;
+        Ok(())
+    }
+
+    /// Publishes a batch of the pending events in order.
+    /// It returns the number of the published events.
+    pub async fn relay() -> Result<usize, Error> {
+        create_table().await?;
+
+        let settings = &*OUTBOX_SETTINGS;
+        let pool = connection_pool()?.pool();
+        let batch_size = settings.batch_size;
+        let sql = format!(
+            "SELECT * FROM {OUTBOX_TABLE} WHERE status = 'pending' \
+                ORDER BY created_at ASC, id ASC LIMIT {batch_size};"
+        );
+        let rows = pool.fetch(&sql).await?;
+        let channel = MessageChannel::shared();
+        let now = DateTime::now().timestamp_millis();
+        let mut blocked_aggregates = HashSet::new();
+        let mut num_published = 0;
+        for row in rows {
+            let message = OutboxMessage::decode_row(&row)?;
+            let aggregate_key = message.aggregate_key;
+            if blocked_aggregates.contains(&aggregate_key) {
+                continue;
+            }
+            if message.next_attempt_at > now {
+                blocked_aggregates.insert(aggregate_key);
+                continue;
+            }
+
+            let id = Query::escape_string(&message.id);
+            let result = match serde_json::from_str::<CloudEvent>(&message.payload) {
+                Ok(event) => channel
+                    .try_send(event)
+                    .map_err(|err| warn!("fail to send the event: {}", err)),
+                Err(err) => Err(err.into()),
+            };
+            match result {
+                Ok(()) => {
+                    let published_at = DateTime::now().timestamp_millis();
+                    let sql = format!(
+                        "UPDATE {OUTBOX_TABLE} SET status = 'published', \
+                            published_at = {published_at}, last_error = NULL \
+                            WHERE id = {id} AND status = 'pending';"
+                    );
+                    pool.execute(&sql).await?;
+                    num_published += 1;
+
+                    #[cfg(feature = "metrics")]
+                    metrics::counter!("zino_outbox_published_total").increment(1);
+                }
+                Err(err) => {
+                    let attempts = message.attempts + 1;
+                    let status = if settings.max_attempts > 0 && attempts >= settings.max_attempts {
+                        tracing::error!(
+                            event_id = message.event_id,
+                            attempts,
+                            "the outbox event is dead: {err}"
+                        );
+                        "dead"
+                    } else {
+                        tracing::warn!(
+                            event_id = message.event_id,
+                            attempts,
+                            "fail to publish the outbox event: {err}"
+                        );
+                        blocked_aggregates.insert(aggregate_key);
+                        "pending"
+                    };
+                    let backoff =
+                        retry_backoff(settings.retry_interval, settings.max_backoff, attempts);
+                    let next_attempt_at = now + i64::try_from(backoff.as_millis())?;
+                    let last_error = Query::escape_string(err.message());
+                    let sql = format!(
+                        "UPDATE {OUTBOX_TABLE} SET status = '{status}', attempts = {attempts}, \
+                            next_attempt_at = {next_attempt_at}, last_error = {last_error} \
+                            WHERE id = {id} AND status = 'pending';"
+                    );
+                    pool.execute(&sql).await?;
+
+                    #[cfg(feature = "metrics")]
+                    metrics::counter!("zino_outbox_failures_total").increment(1);
+                }
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        {
+            let (num_pending, lag) = Self::lag().await?;
+            metrics::gauge!("zino_outbox_pending_events").set(num_pending as f64);
+            metrics::gauge!("zino_outbox_lag_seconds").set(lag.as_secs_f64());
+        }
+        Ok(num_published)
+    }
+
+    /// Returns the number of the pending events and the age of the oldest one.
+    pub async fn lag() -> Result<(u64, Duration), Error> {
+        create_table().await?;
+
+        let sql = format!(
+            "SELECT count(*) AS num_pending, min(created_at) AS oldest_created_at \
+                FROM {OUTBOX_TABLE} WHERE status = 'pending';"
+        );
+        let row = connection_pool()?.pool().fetch_one(&sql).await?;
+        let num_pending = decode::<i64>(&row, "num_pending")?;
+        let lag = decode::<Option<i64>>(&row, "oldest_created_at")?
+            .map(|created_at| {
+                let millis = DateTime::now().timestamp_millis() - created_at;
+                Duration::from_millis(millis.try_into().unwrap_or_default())
+            })
+            .unwrap_or_default();
+        Ok((num_pending.try_into().unwrap_or_default(), lag))
+    }
+
+    /// Deletes the published events which are older than the retention period.
+    /// It returns the number of the deleted events.
+    pub async fn purge_published(retention: Duration) -> Result<u64, Error> {
+        create_table().await?;
+
+        let retention_millis = i64::try_from(retention.as_millis())?;
+        let deadline = DateTime::now().timestamp_millis() - retention_millis;
+        let sql = format!(
+            "DELETE FROM {OUTBOX_TABLE} \
+                WHERE status = 'published' AND published_at < {deadline};"
+        );
+        let query_result = connection_pool()?.pool().execute(&sql).await?;
+        Ok(query_result.rows_affected())
+    }
+
+    /// Creates an async job which relays the pending events every `poll-interval`.
+    ///
+    /// It is exclusive so that only one instance relays the events at a time,
+    /// which requires a job store supporting the distributed lock for multi-instance deployments.
+    pub fn relay_job() -> AsyncJob {
+        let cron_expr = ttl::interval_cron_expr(OUTBOX_SETTINGS.poll_interval);
+        AsyncJob::new(&cron_expr, relay_events)
+            .name("outbox_relay")
+            .overlap_policy(OverlapPolicy::Skip)
+            .exclusive(true)
+    }
+
+    /// Creates an async job which purges the published events after the retention period.
+    /// It runs hourly.
+    pub fn cleanup_job() -> AsyncJob {
+        AsyncJob::new("0 0 * * * *", purge_events).name("outbox_cleanup")
+    }
+}
+
+/// A message in the outbox.
+#[derive(Debug, Default)]
+struct OutboxMessage {
+    /// Message ID.
+    id: String,
+    /// Event ID.
+    event_id: String,
+    /// Aggregate key for the ordering.
+    aggregate_key: String,
+    /// Serialized event.
+    payload: String,
+    /// Number of the attempts.
+    attempts: u32,
+    /// Time of the next attempt in milliseconds.
+    next_attempt_at: i64,
+}
+
+impl DecodeRow<DatabaseRow> for OutboxMessage {
+    type Error = Error;
+
+    fn decode_row(row: &DatabaseRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: decode(row, "id")?,
+            event_id: decode(row, "event_id")?,
+            aggregate_key: decode(row, "aggregate_key")?,
+            payload: decode(row, "payload")?,
+            attempts: decode::<i64>(row, "attempts")?.try_into()?,
+            next_attempt_at: decode(row, "next_attempt_at")?,
+        })
+    }
+}
+
+/// Relays the pending events until there are no more events to publish.
+fn relay_events(_ctx: &mut JobContext) -> BoxFuture<'_> {
+    Box::pin(async {
+        loop {
+            match Outbox::relay().await {
+                Ok(num_published) if num_published > 0 => continue,
+                Ok(_) => break,
+                Err(err) => {
+                    tracing::error!("fail to relay the outbox events: {err}");
+                    break;
+                }
+            }
+        }
+    })
+}
+
+/// Purges the published events after the retention period.
+fn purge_events(_ctx: &mut JobContext) -> BoxFuture<'_> {
+    Box::pin(async {
+        match Outbox::purge_published(OUTBOX_SETTINGS.retention).await {
+            Ok(num_deleted) => tracing::info!(num_deleted, "outbox events have been purged"),
+            Err(err) => tracing::error!("fail to purge the outbox events: {err}"),
+        }
+    })
+}
+
+/// Returns the exponential backoff for the attempts.
+fn retry_backoff(retry_interval: Duration, max_backoff: Duration, attempts: u32) -> Duration {
+    let exponent = attempts.saturating_sub(1).min(31);
+    retry_interval
+        .saturating_mul(1 << exponent)
+        .min(max_backoff)
+}
+
+/// Creates the `_zino_outbox` table if it does not exist.
+async fn create_table() -> Result<(), Error> {
+    if OUTBOX_TABLE_CREATED.load(Relaxed) || !super::AUTO_MIGRATION.load(Relaxed) {
+        return Ok(());
+    }
+
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {OUTBOX_TABLE} (\n  \
+            id VARCHAR(36) NOT NULL PRIMARY KEY,\n  \
+            event_id VARCHAR(255) NOT NULL,\n  \
+            aggregate_key VARCHAR(255) NOT NULL,\n  \
+            event_type VARCHAR(255) NOT NULL,\n  \
+            payload TEXT NOT NULL,\n  \
+            status VARCHAR(16) NOT NULL,\n  \
+            attempts BIGINT NOT NULL DEFAULT 0,\n  \
+            next_attempt_at BIGINT NOT NULL,\n  \
+            last_error TEXT,\n  \
+            created_at BIGINT NOT NULL,\n  \
+            published_at BIGINT\n\
+        );"
+    );
+    let pool = connection_pool()?.pool();
+    if let Err(err) = pool.execute(&sql).await {
+        tracing::error!(table_name = OUTBOX_TABLE, "fail to execute `{sql}`");
+        return Err(err);
+    }
+    OUTBOX_TABLE_CREATED.store(true, Relaxed);
+    Ok(())
+}
+
+/// Returns the connection pool.
+fn connection_pool() -> Result<&'static super::ConnectionPool, Error> {
+    GlobalPool::get(OUTBOX_SETTINGS.database)
+        .ok_or_else(|| warn!("connection to the database is unavailable"))
+}
+
+/// Table name for the outbox.
+const OUTBOX_TABLE: &str = "_zino_outbox";
+
+/// Flag to indicate whether the table for the outbox has been created.
+static OUTBOX_TABLE_CREATED: AtomicBool = AtomicBool::new(false);
+
+/// Settings for the outbox.
+#[derive(Debug)]
+struct OutboxSettings {
+    /// Name of the connection pool.
+    database: &'static str,
+    /// Number of events fetched in a batch.
+    batch_size: usize,
+    /// Interval for polling the pending events.
+    poll_interval: Duration,
+    /// Initial interval for retrying a failed event.
+    retry_interval: Duration,
+    /// Maximum backoff for retrying a failed event.
+    max_backoff: Duration,
+    /// Maximum attempts before an event is dead. `0` means unlimited.
+    max_attempts: u32,
+    /// Retention period of the published events.
+    retention: Duration,
+}
+
+/// Shared settings for the outbox.
+static OUTBOX_SETTINGS: LazyLock<OutboxSettings> = LazyLock::new(|| {
+    let config = State::shared()
+        .get_config("database")
+        .and_then(|config| config.get_table("outbox"));
+    OutboxSettings {
+        database: config
+            .and_then(|config| config.get_str("database"))
+            .unwrap_or("main"),
+        batch_size: config
+            .and_then(|config| config.get_usize("batch-size"))
+            .unwrap_or(100)
+            .max(1),
+        poll_interval: config
+            .and_then(|config| ttl::parse_interval(config, "poll-interval"))
+            .unwrap_or(Duration::from_secs(1)),
+        retry_interval: config
+            .and_then(|config| config.get_duration("retry-interval"))
+            .unwrap_or(Duration::from_secs(1)),
+        max_backoff: config
+            .and_then(|config| config.get_duration("max-backoff"))
+            .unwrap_or(Duration::from_secs(5 * 60)),
+        max_attempts: config
+            .and_then(|config| config.get_u32("max-attempts"))
+            .unwrap_or(10),
+        retention: config
+            .and_then(|config| config.get_duration("retention"))
+            .unwrap_or(Duration::from_secs(7 * 24 * 60 * 60)),
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::retry_backoff;
+    use std::time::Duration;
+
+    #[test]
+    fn it_computes_retry_backoff() {
+        let retry_interval = Duration::from_secs(1);
+        let max_backoff = Duration::from_secs(60);
+        assert_eq!(
+            retry_backoff(retry_interval, max_backoff, 1),
+            retry_interval
+        );
+        assert_eq!(
+            retry_backoff(retry_interval, max_backoff, 4),
+            Duration::from_secs(8)
+        );
+        assert_eq!(retry_backoff(retry_interval, max_backoff, 10), max_backoff);
+        assert_eq!(retry_backoff(retry_interval, max_backoff, 100), max_backoff);
+    }
+}
@@ -0,0 +1,384 @@
+use super::{executor::Executor, ttl, DecodeRow, Schema};
+use std::time::Duration;
+use zino_core::{
+    bail,
+    datetime::Date,
+    error::Error,
+    extension::{JsonObjectExt, TomlTableExt},
+    schedule::{AsyncJob, JobContext},
+    state::State,
+    BoxFuture, LazyLock, Map,
+};
+
+/// Granularity of the child partitions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionGranularity {
+    /// Weekly partitions starting on Monday, named as `{table}_p{YYYYMMDD}`.
+    Week,
+    /// Monthly partitions, named as `{table}_p{YYYYMM}`.
+    Month,
+}
+
+impl PartitionGranularity {
+    /// Returns the start date of the period containing the date.
+    fn period_start(self, date: Date) -> Date {
+        match self {
+            Self::Week => {
+                let days = u32::from(date.iso_day_of_week()) - 1;
+                date.checked_sub_days(days).unwrap_or(date)
+            }
+            Self::Month => date.start_of_current_month(),
+        }
+    }
+
+    /// Returns the start date of the next period.
+    fn next_period_start(self, start: Date) -> Option<Date> {
+        match self {
+            Self::Week => start.checked_add_days(7),
+            Self::Month => start.checked_add_months(1),
+        }
+    }
+
+    /// Formats the suffix of the partition name.
+    fn format_suffix(self, start: Date) -> String {
+        match self {
+            Self::Week => start.format("%Y%m%d"),
+            Self::Month => start.format("%Y%m"),
+        }
+    }
+
+    /// Parses the start date from the suffix of the partition name.
+    fn parse_suffix(self, suffix: &str) -> Option<Date> {
+        let expected_len = if self == Self::Week { 8 } else { 6 };
+        if suffix.len() != expected_len || !suffix.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let year = suffix[..4].parse().ok()?;
+        let month = suffix[4..6].parse().ok()?;
+        let day = if self == Self::Week {
+            suffix[6..].parse().ok()?
+        } else {
+            1
+        };
+        Date::try_new(year, month, day).ok()
+    }
+}
+
+/// Management of the range partitions for the models with a `#[schema(partition_by)]` attribute.
+/// It is only supported for PostgreSQL.
+///
+/// It can be configured in the `[database.partitioning]` table:
+///
+/// ```toml
+/// [database.partitioning]
+/// granularity = "month"
+/// horizon = "90d"
+/// retention = "365d"
+/// drop-detached = false
+/// interval = "1h"
+/// ```
+///
+/// The child partitions inherit the indexes of the parent table. Only the partitions
+/// following the naming convention are managed, and the others are left untouched.
+///
+/// When the table is present, the management job of a range-partitioned model is registered
+/// with the [`MaintenanceScheduler`](crate::MaintenanceScheduler) once the table
+/// of the model has been initialized.
+#[derive(Debug, Clone, Copy)]
+pub struct PartitionManager {
+    /// Granularity of the child partitions.
+    granularity: PartitionGranularity,
+    /// Time span of the partitions created ahead of time.
+    horizon: Duration,
+    /// Retention period of the partitions.
+    retention: Option<Duration>,
+    /// A flag to indicate whether the detached partitions should be dropped.
+    drop_detached: bool,
+    /// Interval between the runs.
+    interval: Duration,
+}
+
+impl PartitionManager {
+    /// Creates a new instance with the default settings.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            granularity: PartitionGranularity::Month,
+            horizon: Duration::from_secs(90 * 24 * 60 * 60),
+            retention: None,
+            drop_detached: false,
+            interval: Duration::from_secs(60 * 60),
+        }
+    }
+
+    /// Returns a reference to the shared settings.
+    #[inline]
+    pub fn shared() -> &'static Self {
+        &SHARED_PARTITION_MANAGER
+    }
+
+    /// Sets the granularity of the child partitions.
+    #[inline]
+    pub fn set_granularity(&mut self, granularity: PartitionGranularity) {
+        self.granularity = granularity;
+    }
+
+    /// Sets the flag to indicate whether the detached partitions should be dropped.
+    #[inline]
+    pub fn set_drop_detached(&mut self, drop_detached: bool) {
+        self.drop_detached = drop_detached;
+    }
+
+    /// Returns the granularity of the child partitions.
+    #[inline]
+    pub fn granularity(&self) -> PartitionGranularity {
+        self.granularity
+    }
+
+    /// Returns the time span of the partitions created ahead of time.
+    #[inline]
+    pub fn horizon(&self) -> Duration {
+        self.horizon
+    }
+
+    /// Returns the retention period of the partitions.
+    #[inline]
+    pub fn retention(&self) -> Option<Duration> {
+        self.retention
+    }
+
+    /// Returns `true` if the detached partitions should be dropped.
+    #[inline]
+    pub fn drop_detached(&self) -> bool {
+        self.drop_detached
+    }
+
+    /// Returns the interval between the runs.
+    #[inline]
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Creates the child partitions of the model from the current period up to the horizon.
+    /// It returns the names of the partitions created.
+    pub async fn ensure_partitions<M: Schema>(
+        &self,
+        horizon: Duration,
+    ) -> Result<Vec<String>, Error> {
+        let partition_column = range_partition_column::<M>()?;
+        let existing_partitions = list_partitions::<M>().await?;
+        let (table_schema, table_name) = split_table_name(M::table_name());
+        let parent_table = escape_table_name(table_schema, table_name);
+        let pool = M::init_writer()?.pool();
+
+        let granularity = self.granularity;
+        let today = Date::today();
+        let end_date = today + horizon;
+        let mut start = granularity.period_start(today);
+        let mut created_partitions = Vec::new();
+        while start <= end_date {
+            let Some(end) = granularity.next_period_start(start) else {
+                break;
+            };
+            let partition_name = format!("{table_name}_p{}", granularity.format_suffix(start));
+            if !existing_partitions.contains(&partition_name) {
+                let partition_table = escape_table_name(table_schema, &partition_name);
+                let sql = format!(
+                    "CREATE TABLE IF NOT EXISTS {partition_table} PARTITION OF {parent_table} \
+                        FOR VALUES FROM ('{start}') TO ('{end}');"
+                );
+                pool.execute(&sql).await?;
+                tracing::info!(
+                    table_name,
+                    partition_name,
+                    partition_column,
+                    "partition `{partition_name}` has been created",
+                );
+                created_partitions.push(partition_name);
+            }
+            start = end;
+        }
+        Ok(created_partitions)
+    }
+
+    /// Detaches the child partitions of the model whose ranges end before the retention period,
+    /// and drops them if `drop-detached` is enabled. It returns the names of the partitions detached.
+    pub async fn detach_partitions_older_than<M: Schema>(
+        &self,
+        retention: Duration,
+    ) -> Result<Vec<String>, Error> {
+        range_partition_column::<M>()?;
+
+        let (table_schema, table_name) = split_table_name(M::table_name());
+        let parent_table = escape_table_name(table_schema, table_name);
+        let pool = M::init_writer()?.pool();
+        let prefix = format!("{table_name}_p");
+        let granularity = self.granularity;
+        let cutoff = Date::today() - retention;
+        let mut detached_partitions = Vec::new();
+        for partition_name in list_partitions::<M>().await? {
+            let end = partition_name
+                .strip_prefix(&prefix)
+                .and_then(|suffix| granularity.parse_suffix(suffix))
+                .and_then(|start| granularity.next_period_start(start));
+            if end.is_some_and(|end| end <= cutoff) {
+                let partition_table = escape_table_name(table_schema, &partition_name);
+                let sql = format!("ALTER TABLE {parent_table} DETACH PARTITION {partition_table};");
+                pool.execute(&sql).await?;
+                if self.drop_detached {
+                    let sql = format!("DROP TABLE IF EXISTS {partition_table};");
+                    pool.execute(&sql).await?;
+                }
+                tracing::warn!(
+                    table_name,
+                    partition_name,
+                    dropped = self.drop_detached,
+                    "partition `{partition_name}` has been detached",
+                );
+                detached_partitions.push(partition_name);
+            }
+        }
+        Ok(detached_partitions)
+    }
+
+    /// Creates an async job which creates the partitions ahead of time
+    /// and detaches the expired partitions with the shared settings.
+    /// It also runs immediately so that the current partitions exist before the first write.
+    /// The job is registered automatically if the `[database.partitioning]` table is present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use zino_orm::PartitionManager;
+    ///
+    /// let mut scheduler = AsyncJobScheduler::new();
+    /// scheduler.add(PartitionManager::job::<Event>());
+    /// ```
+    pub fn job<M: Schema>() -> AsyncJob {
+        let cron_expr = ttl::interval_cron_expr(Self::shared().interval);
+        let job_name = format!("{}_partition_management", M::model_name());
+        AsyncJob::new(&cron_expr, manage_partitions::<M>)
+            .name(job_name)
+            .immediate(true)
+    }
+}
+
+impl Default for PartitionManager {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Creates and detaches the partitions of the model with the shared settings.
+fn manage_partitions<M: Schema>(_ctx: &mut JobContext) -> BoxFuture<'_> {
+    Box::pin(async {
+        let model_name = M::model_name();
+        let manager = PartitionManager::shared();
+        if let Err(err) = manager.ensure_partitions::<M>(manager.horizon).await {
+            tracing::error!(model_name, "fail to create partitions: {err}");
+        }
+        if let Some(retention) = manager.retention {
+            if let Err(err) = manager.detach_partitions_older_than::<M>(retention).await {
+                tracing::error!(model_name, "fail to detach partitions: {err}");
+            }
+        }
+    })
+}
+
+/// Returns the partition column if the model is partitioned by range.
+fn range_partition_column<M: Schema>() -> Result<&'static str, Error> {
+    match M::partition_key() {
+        Some((strategy, column)) if strategy.eq_ignore_ascii_case("range") => Ok(column),
+        _ => bail!("model `{}` is not partitioned by range", M::model_name()),
+    }
+}
+
+/// Lists the names of the child partitions of the model.
+async fn list_partitions<M: Schema>() -> Result<Vec<String>, Error> {
+    let (table_schema, table_name) = split_table_name(M::table_name());
+    let sql = format!(
+        "SELECT c.relname::text AS partition_name FROM pg_inherits i \
+            JOIN pg_class c ON c.oid = i.inhrelid \
+            JOIN pg_class p ON p.oid = i.inhparent \
+            JOIN pg_namespace n ON n.oid = p.relnamespace \
+                WHERE p.relname = '{table_name}' AND n.nspname = '{table_schema}';"
+    );
+    let pool = M::init_reader()?.pool();
+    let mut partitions = Vec::new();
+    for row in pool.fetch(&sql).await? {
+        let data = Map::decode_row(&row)?;
+        if let Some(partition_name) = data.get_str("partition_name") {
+            partitions.push(partition_name.to_owned());
+        }
+    }
+    Ok(partitions)
+}
+
+/// Splits the table name into the schema and the table.
+fn split_table_name(table_name: &str) -> (&str, &str) {
+    table_name
+        .rsplit_once('.')
+        .unwrap_or(("public", table_name))
+}
+
+/// Escapes the table name with the schema.
+fn escape_table_name(table_schema: &str, table_name: &str) -> String {
+    format!(r#""{table_schema}"."{table_name}""#)
+}
+
+/// Shared settings for the partition management.
+static SHARED_PARTITION_MANAGER: LazyLock<PartitionManager> = LazyLock::new(|| {
+    let mut manager = PartitionManager::new();
+    if let Some(config) = State::shared()
+        .get_config("database")
+        .and_then(|config| config.get_table("partitioning"))
+    {
+        match config.get_str("granularity") {
+            Some("week") => manager.granularity = PartitionGranularity::Week,
+            Some("month") | None => (),
+            Some(granularity) => {
+                tracing::warn!("unsupported partition granularity `{granularity}`");
+            }
+        }
+        if let Some(horizon) = config.get_duration("horizon") {
+            manager.horizon = horizon;
+        }
+        if let Some(retention) = config.get_duration("retention") {
+            manager.retention = Some(retention);
+        }
+        if let Some(drop_detached) = config.get_bool("drop-detached") {
+            manager.drop_detached = drop_detached;
+        }
+        if let Some(interval) = ttl::parse_interval(config, "interval") {
+            manager.interval = interval;
+        }
+    }
+    manager
+});
+
+#[cfg(test)]
+mod tests {
+    use super::PartitionGranularity;
+    use zino_core::datetime::Date;
+
+    #[test]
+    fn it_formats_partition_names() {
+        let date = Date::try_new(2024, 12, 18).unwrap();
+        let month = PartitionGranularity::Month;
+        let start = month.period_start(date);
+        assert_eq!(month.format_suffix(start), "202412");
+        assert_eq!(month.parse_suffix("202412"), Some(start));
+        assert_eq!(
+            month.next_period_start(start),
+            Date::try_new(2025, 1, 1).ok()
+        );
+
+        let week = PartitionGranularity::Week;
+        let start = week.period_start(date);
+        assert_eq!(week.format_suffix(start), "20241216");
+        assert_eq!(week.parse_suffix("20241216"), Some(start));
+        assert_eq!(week.parse_suffix("202412"), None);
+    }
+}
@@ -1,5 +1,14 @@
-use super::DatabasePool;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering::Relaxed};
+use super::{DatabasePool, SessionSettings};
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering::Relaxed},
+    Arc,
+};
+
+#[cfg(feature = "orm-sqlx")]
+use zino_core::{
+    error::{Error, ErrorKind},
+    warn,
+};
 
 /// A database connection pool with metadata.
 #[derive(Debug)]
@@ -14,6 +23,19 @@ pub struct ConnectionPool<P = DatabasePool> {
     available: AtomicBool,
     /// Missed count.
     missed_count: AtomicUsize,
+    /// Number of the concurrent acquire waiters.
+    waiting_count: AtomicUsize,
+    /// Maximum number of the concurrent acquire waiters. A zero value means no limit.
+    max_waiting: usize,
+    /// Number of the consecutive acquire timeouts.
+    timeout_count: AtomicUsize,
+    /// Maximum number of the consecutive acquire timeouts before being unavailable.
+    max_timeouts: usize,
+    /// Session settings.
+    session_settings: Arc<SessionSettings>,
+    /// Semaphore to serialize the writes if the single-writer strategy is enabled.
+    #[cfg(feature = "orm-sqlx")]
+    writer: Option<tokio::sync::Semaphore>,
 }
 
 impl<P> ConnectionPool<P> {
@@ -26,9 +48,48 @@ impl<P> ConnectionPool<P> {
             pool,
             available: AtomicBool::new(true),
             missed_count: AtomicUsize::new(0),
+            waiting_count: AtomicUsize::new(0),
+            max_waiting: 0,
+            timeout_count: AtomicUsize::new(0),
+            max_timeouts: 3,
+            session_settings: Arc::default(),
+            #[cfg(feature = "orm-sqlx")]
+            writer: None,
         }
     }
 
+    /// Sets the maximum number of the concurrent acquire waiters.
+    /// A zero value means no limit.
+    #[inline]
+    pub fn set_max_waiting(&mut self, max_waiting: usize) {
+        self.max_waiting = max_waiting;
+    }
+
+    /// Sets the maximum number of the consecutive acquire timeouts
+    /// before the connection pool is marked as unavailable.
+    #[inline]
+    pub fn set_max_timeouts(&mut self, max_timeouts: usize) {
+        self.max_timeouts = max_timeouts.max(1);
+    }
+
+    /// Sets the session settings applied to every new connection.
+    #[inline]
+    pub fn set_session_settings(&mut self, session_settings: Arc<SessionSettings>) {
+        self.session_settings = session_settings;
+    }
+
+    /// Enables or disables the single-writer strategy.
+    ///
+    /// If enabled, the statements executed via the connection pool are serialized
+    /// through a write semaphore, so that concurrent writes wait in the queue instead of
+    /// failing with `database is locked`. It is enabled by default for SQLite.
+    /// Transactions are not serialized and rely on the `busy-timeout` instead.
+    #[cfg(feature = "orm-sqlx")]
+    #[inline]
+    pub fn set_single_writer(&mut self, single_writer: bool) {
+        self.writer = single_writer.then(|| tokio::sync::Semaphore::new(1));
+    }
+
     /// Returns `true` if the connection pool is available.
     #[inline]
     pub fn is_available(&self) -> bool {
@@ -40,6 +101,7 @@ impl<P> ConnectionPool<P> {
         self.available.store(available, Relaxed);
         if available {
             self.reset_missed_count();
+            self.timeout_count.store(0, Relaxed);
         } else {
             self.increment_missed_count();
         }
@@ -70,6 +132,12 @@ impl<P> ConnectionPool<P> {
         missed_count > 2 && missed_count.is_power_of_two()
     }
 
+    /// Returns the number of the concurrent acquire waiters.
+    #[inline]
+    pub fn waiting_count(&self) -> usize {
+        self.waiting_count.load(Relaxed)
+    }
+
     /// Returns the name.
     #[inline]
     pub fn name(&self) -> &'static str {
@@ -82,9 +150,124 @@ impl<P> ConnectionPool<P> {
         self.database
     }
 
+    /// Returns a reference to the session settings.
+    #[inline]
+    pub fn session_settings(&self) -> &SessionSettings {
+        &self.session_settings
+    }
+
     /// Returns a reference to the pool.
     #[inline]
     pub fn pool(&self) -> &P {
         &self.pool
     }
 }
+
+#[cfg(feature = "orm-sqlx")]
+impl ConnectionPool<DatabasePool> {
+    /// Acquires a connection from the pool.
+    ///
+    /// It fails fast with an error of [`ErrorKind::Unavailable`] if the number of
+    /// concurrent acquire waiters exceeds the `max-waiting` limit. The connection pool
+    /// will be marked as unavailable after the consecutive acquire timeouts
+    /// reach the `max-acquire-timeouts` limit, and recovered by the availability check.
+    pub async fn acquire(
+        &self,
+    ) -> Result<sqlx::pool::PoolConnection<super::DatabaseDriver>, Error> {
+        let name = self.name;
+        let waiter = Waiter::new(self);
+        if self.max_waiting > 0 && waiter.position > self.max_waiting {
+            drop(waiter);
+            #[cfg(feature = "metrics")]
+            metrics::counter!("zino_db_pool_rejected_total", "pool" => name).increment(1);
+            let err = warn!(
+                "503 Service Unavailable: too many waiters for the connection pool of the `{}` service",
+                name
+            );
+            return Err(err.with_kind(ErrorKind::Unavailable));
+        }
+
+        let start_time = std::time::Instant::now();
+        let result = self.pool.acquire().await;
+        drop(waiter);
+
+        let acquire_time = start_time.elapsed();
+        #[cfg(feature = "metrics")]
+        metrics::histogram!("zino_db_pool_acquire_duration_seconds", "pool" => name)
+            .record(acquire_time.as_secs_f64());
+        match result {
+            Ok(connection) => {
+                self.timeout_count.store(0, Relaxed);
+                Ok(connection)
+            }
+            Err(err) => {
+                if matches!(err, sqlx::Error::PoolTimedOut) {
+                    #[cfg(feature = "metrics")]
+                    metrics::counter!("zino_db_pool_acquire_timeouts_total", "pool" => name)
+                        .increment(1);
+                    let timeout_count = self.timeout_count.fetch_add(1, Relaxed) + 1;
+                    if timeout_count >= self.max_timeouts && self.is_available() {
+                        let acquire_time_millis = acquire_time.as_millis();
+                        tracing::error!(
+                            timeout_count,
+                            acquire_time_millis,
+                            "the connection pool for the `{name}` service is marked as unavailable"
+                        );
+                        self.store_availability(false);
+                    }
+                }
+                Err(super::executor::classify_error(err))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "orm-sqlx")]
+impl ConnectionPool<DatabasePool> {
+    /// Acquires a permit to write if the single-writer strategy is enabled.
+    /// Returns `None` if it is disabled.
+    pub async fn acquire_write_permit(
+        &self,
+    ) -> Result<Option<tokio::sync::SemaphorePermit<'_>>, Error> {
+        if let Some(writer) = self.writer.as_ref() {
+            let permit = writer
+                .acquire()
+                .await
+                .map_err(|err| warn!("fail to acquire a write permit: {}", err))?;
+            Ok(Some(permit))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// A waiter for acquiring a connection from the pool.
+#[cfg(feature = "orm-sqlx")]
+struct Waiter<'a> {
+    /// Connection pool.
+    pool: &'a ConnectionPool,
+    /// Position in the queue of waiters.
+    position: usize,
+}
+
+#[cfg(feature = "orm-sqlx")]
+impl<'a> Waiter<'a> {
+    /// Creates a new instance and increments the number of waiters.
+    #[inline]
+    fn new(pool: &'a ConnectionPool) -> Self {
+        let position = pool.waiting_count.fetch_add(1, Relaxed) + 1;
+        #[cfg(feature = "metrics")]
+        metrics::gauge!("zino_db_pool_waiters", "pool" => pool.name).increment(1.0);
+        Self { pool, position }
+    }
+}
+
+#[cfg(feature = "orm-sqlx")]
+impl Drop for Waiter<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        self.pool.waiting_count.fetch_sub(1, Relaxed);
+        #[cfg(feature = "metrics")]
+        metrics::gauge!("zino_db_pool_waiters", "pool" => self.pool.name).decrement(1.0);
+    }
+}
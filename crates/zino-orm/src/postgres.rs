@@ -17,6 +17,16 @@ impl EncodeColumn<DatabaseDriver> for Column<'_> {
         if let Some(column_type) = self.extra().get_str("column_type") {
             return column_type;
         }
+        if let Some(type_name) = self.extra().get_str("pg_enum") {
+            return type_name;
+        }
+        if self
+            .extra()
+            .get_str("collation")
+            .is_some_and(|collation| collation.eq_ignore_ascii_case("citext"))
+        {
+            return "CITEXT";
+        }
         match self.type_name() {
             "bool" => "BOOLEAN",
             "u64" | "i64" | "usize" | "isize" | "Option<u64>" | "Option<i64>" => {
@@ -42,6 +52,7 @@ impl EncodeColumn<DatabaseDriver> for Column<'_> {
             "DateTime" => "TIMESTAMPTZ",
             "NaiveDateTime" => "TIMESTAMP",
             "Uuid" | "Option<Uuid>" => "UUID",
+            "Ulid" | "Option<Ulid>" => "CHAR(26)",
             "Vec<u8>" => "BYTEA",
             "Vec<String>" => "TEXT[]",
             "Vec<Uuid>" => "UUID[]",
@@ -65,6 +76,9 @@ impl EncodeColumn<DatabaseDriver> for Column<'_> {
                     if value.is_empty() {
                         if let Some(value) = self.default_value() {
                             self.format_value(value).into_owned().into()
+                        } else if self.extra().contains_key("pg_enum") {
+                            // An empty string is not a valid label of the enum type.
+                            "NULL".into()
                         } else {
                             "''".into()
                         }
@@ -76,6 +90,9 @@ impl EncodeColumn<DatabaseDriver> for Column<'_> {
                         self.format_value(value)
                     }
                 }
+                JsonValue::Array(_) if self.type_name() == "Map" => {
+                    format!("{}::{}", Query::escape_string(value), self.column_type()).into()
+                }
                 JsonValue::Array(value) => {
                     let values = value
                         .iter()
@@ -98,6 +115,10 @@ impl EncodeColumn<DatabaseDriver> for Column<'_> {
     }
 
     fn format_value<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        if let Some(type_name) = self.extra().get_str("pg_enum") {
+            let value = Query::escape_string(value);
+            return format!("{value}::{type_name}").into();
+        }
         match self.type_name() {
             "bool" => {
                 let value = if value == "true" { "TRUE" } else { "FALSE" };
@@ -193,6 +214,9 @@ impl EncodeColumn<DatabaseDriver> for Column<'_> {
                         "$rlike" => "~*",
                         "$is" => "IS",
                         "$size" => "array_length",
+                        "$size_gt" => "array_length_gt",
+                        "$contains" => "@>",
+                        "$overlaps" => "&&",
                         _ => {
                             if cfg!(debug_assertions) && name.starts_with('$') {
                                 tracing::warn!("unsupported operator `{name}` for PostgreSQL");
@@ -241,6 +265,12 @@ impl EncodeColumn<DatabaseDriver> for Column<'_> {
                             let condition = format!(r#"array_length({field}, 1) = {length}"#);
                             conditions.push(condition);
                         }
+                    } else if operator == "array_length_gt" {
+                        if let Some(Ok(length)) = value.parse_usize() {
+                            let condition =
+                                format!(r#"coalesce(array_length({field}, 1), 0) > {length}"#);
+                            conditions.push(condition);
+                        }
                     } else {
                         let value = self.encode_value(Some(value));
                         let condition = format!(r#"{field} {operator} {value}"#);
@@ -270,6 +300,26 @@ impl EncodeColumn<DatabaseDriver> for Column<'_> {
             let min_value = self.format_value(min_value);
             let max_value = self.format_value(max_value);
             return format!(r#"{field} >= {min_value} AND {field} < {max_value}"#);
+        } else if let Some(value) = value
+            .as_str()
+            .filter(|_| self.extra().contains_key("pg_enum"))
+        {
+            // The labels of an enum type can not be compared with an empty string.
+            return if value == "null" {
+                format!(r#"{field} IS NULL"#)
+            } else if value == "not_null" {
+                format!(r#"{field} IS NOT NULL"#)
+            } else if value.contains(',') {
+                let value = value
+                    .split(',')
+                    .map(|s| self.format_value(s))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!(r#"{field} IN ({value})"#)
+            } else {
+                let value = self.format_value(value);
+                format!(r#"{field} = {value}"#)
+            };
         }
 
         match type_name {
@@ -390,7 +440,7 @@ impl EncodeColumn<DatabaseDriver> for Column<'_> {
                     format!(r#"{field} = {value}"#)
                 }
             }
-            "Uuid" | "Option<Uuid>" => {
+            "Uuid" | "Option<Uuid>" | "Ulid" | "Option<Ulid>" => {
                 if let Some(value) = value.as_str() {
                     if value == "null" {
                         format!(r#"{field} IS NULL"#)
@@ -498,6 +548,7 @@ impl DecodeRow<DatabaseRow> for Map {
                             .into()
                     }
                     "JSONB" | "JSON" => decode_raw::<JsonValue>(field, raw_value)?,
+                    "TSVECTOR" => continue,
                     _ => decode_raw::<String>(field, raw_value)?.into(),
                 }
             };
@@ -604,6 +655,11 @@ impl QueryExt<DatabaseDriver> for Query {
         self.limit()
     }
 
+    #[inline]
+    fn query_extra_flags(&self) -> &Map {
+        self.extra_flags()
+    }
+
     #[inline]
     fn placeholder(n: usize) -> SharedString {
         if n == 1 {
@@ -69,39 +69,94 @@
 //!
 //! # Query operators
 //!
-//! | Name       | MySQL               | PostgreSQL       | SQLite                |
-//! |------------|---------------------|------------------|-----------------------|
-//! | `$and`     | `AND`               | `AND`            | `AND`                 |
-//! | `$or`      | `OR`                | `OR`             | `OR`                  |
-//! | `$not`     | `NOT`               | `NOT`            | `NOT`                 |
-//! | `$rand`    | `rand()`            | `random()`       | `abs(random())`       |
-//! | `$text`    | `match() against()` | `to_tsvector()`  | `MATCH`               |
-//! | `$eq`      | `=`                 | `=`              | `=`                   |
-//! | `$ne`      | `<>`                | `<>`             | `<>`                  |
-//! | `$lt`      | `<`                 | `<`              | `<`                   |
-//! | `$le`      | `<=`                | `<=`             | `<=`                  |
-//! | `$gt`      | `>`                 | `>`              | `>`                   |
-//! | `$ge`      | `>=`                | `>=`             | `>=`                  |
-//! | `$in`      | `IN`                | `IN`             | `IN`                  |
-//! | `$nin`     | `NOT IN`            | `NOT IN`         | `NOT IN`              |
-//! | `$betw`    | `BETWEEN AND`       | `BETWEEN AND`    | `BETWEEN AND`         |
-//! | `$like`    | `LIKE`              | `LIKE`           | `LIKE`                |
-//! | `$ilike`   | `ILIKE`             | `ILIKE`          | `LOWER() LIKE`        |
-//! | `$rlike`   | `RLIKE`             | `~*`             | `REGEXP`              |
-//! | `$is`      | `IS`                | `IS`             | `IS`                  |
-//! | `$size`    | `json_length()`     | `array_length()` | `json_array_length()` |
+//! | Name        | MySQL               | PostgreSQL       | SQLite                |
+//! |-------------|---------------------|------------------|-----------------------|
+//! | `$and`      | `AND`               | `AND`            | `AND`                 |
+//! | `$or`       | `OR`                | `OR`             | `OR`                  |
+//! | `$not`      | `NOT`               | `NOT`            | `NOT`                 |
+//! | `$rand`     | `rand()`            | `random()`       | `abs(random())`       |
+//! | `$text`     | `match() against()` | `to_tsvector()`  | `MATCH`               |
+//! | `$search`   | `match() against()` | `to_tsquery()`   | `fts5 MATCH`          |
+//! | `$eq`       | `=`                 | `=`              | `=`                   |
+//! | `$ne`       | `<>`                | `<>`             | `<>`                  |
+//! | `$lt`       | `<`                 | `<`              | `<`                   |
+//! | `$le`       | `<=`                | `<=`             | `<=`                  |
+//! | `$gt`       | `>`                 | `>`              | `>`                   |
+//! | `$ge`       | `>=`                | `>=`             | `>=`                  |
+//! | `$in`       | `IN`                | `IN`             | `IN`                  |
+//! | `$nin`      | `NOT IN`            | `NOT IN`         | `NOT IN`              |
+//! | `$betw`     | `BETWEEN AND`       | `BETWEEN AND`    | `BETWEEN AND`         |
+//! | `$like`     | `LIKE`              | `LIKE`           | `LIKE`                |
+//! | `$ilike`    | `ILIKE`             | `ILIKE`          | `LOWER() LIKE`        |
+//! | `$rlike`    | `RLIKE`             | `~*`             | `REGEXP`              |
+//! | `$is`       | `IS`                | `IS`             | `IS`                  |
+//! | `$size`     | `json_length()`     | `array_length()` | `json_array_length()` |
+//! | `$size_gt`  | `json_length()`     | `array_length()` | `json_array_length()` |
+//! | `$contains` | `json_contains()`   | `@>`             | `json_each()`         |
+//! | `$overlaps` | `json_overlaps()`   | `&&`             | `json_each()`         |
+//!
+//! # Raw SQL fragments
+//!
+//! Expressions which can not be produced by the builder can be added with
+//! [`QueryBuilder::and_raw`] or [`QueryBuilder::or_raw`]. The fragment uses `?` or
+//! numbered placeholders `$N` for the parameters, which are always bound as arguments
+//! and never interpolated into the SQL text.
+//!
+//! ```rust,ignore
+//! let query = QueryBuilder::<User>::new()
+//!     .and_eq(UserColumn::Status, "Active")
+//!     .and_raw("coalesce(nickname, name) ILIKE ?", ["%alice%"])
+//!     .build();
+//! let users: Vec<User> = User::find(&query).await?;
+//! ```
+//!
+//! Array parameters are bound as PostgreSQL array literals,
+//! so they can be cast to native arrays such as `tags && $1::text[]`.
+//!
+//! # Array columns
+//!
+//! The columns of `Vec<String>`, `Vec<i64>` and `Vec<Uuid>` are stored as native arrays
+//! in PostgreSQL and as JSON arrays in MySQL and SQLite.
+//!
+//! ```rust,ignore
+//! let query = QueryBuilder::<Post>::new()
+//!     .and_array_contains(PostColumn::Tags, "rust")
+//!     .and_array_overlaps(PostColumn::Labels, ["orm", "web"])
+//!     .and_array_length_gt(PostColumn::Tags, 1)
+//!     .build();
+//! let posts: Vec<Post> = Post::find(&query).await?;
+//! ```
+//!
+//! # Subqueries
+//!
+//! A subquery can reference the columns of the outer query with [`QueryBuilder::col_outer`],
+//! which produces a correlated `EXISTS` or `IN` expression. The parameters of the raw SQL
+//! fragments in the subquery are merged with the outer query.
+//!
+//! ```rust,ignore
+//! let recent_orders = QueryBuilder::<Order>::new()
+//!     .and_eq(OrderColumn::UserId, QueryBuilder::<User>::col_outer(UserColumn::Id))
+//!     .and_ge(OrderColumn::CreatedAt, DateTime::now() - Duration::from_secs(30 * 86400));
+//! let query = QueryBuilder::<User>::new()
+//!     .and_exists(recent_orders)
+//!     .build();
+//! let users: Vec<User> = User::find(&query).await?;
+//! ```
 //!
 //! [`Mongoose`]: https://mongoosejs.com/
 //! [`Prisma`]: https://www.prisma.io/
 //! [`TypeORM`]: https://typeorm.io/
 //! [`PostgREST`]: https://postgrest.org/
 
-use super::{Aggregation, EncodeColumn, Entity, IntoSqlValue, Schema, Window};
+use super::{
+    column::ColumnExt, search, Aggregation, EncodeColumn, Entity, IntoSqlValue, Schema, Window,
+};
 use regex::{Captures, Regex};
-use std::{borrow::Cow, fmt::Display, marker::PhantomData};
+use std::{borrow::Cow, fmt::Display, marker::PhantomData, time::Duration};
 use zino_core::{
     extension::{JsonObjectExt, JsonValueExt},
     model::{Query, QueryOrder},
+    money::Money,
     JsonValue, LazyLock, Map, SharedString,
 };
 
@@ -145,6 +200,12 @@ pub struct QueryBuilder<E: Entity> {
     offset: usize,
     /// Limit.
     limit: usize,
+    /// Raw SQL fragments.
+    raw_fragments: Vec<String>,
+    /// Statement timeout.
+    timeout: Option<Duration>,
+    /// Max number of rows.
+    max_rows: Option<usize>,
     /// The phantom data.
     phantom: PhantomData<E>,
 }
@@ -163,6 +224,9 @@ impl<E: Entity> QueryBuilder<E> {
             sort_order: Vec::new(),
             offset: 0,
             limit: 0,
+            raw_fragments: Vec::new(),
+            timeout: None,
+            max_rows: None,
             phantom: PhantomData,
         }
     }
@@ -302,6 +366,7 @@ impl<E: Entity> QueryBuilder<E> {
         }
         self.fields.append(&mut other.fields);
         self.group_by_fields.append(&mut other.group_by_fields);
+        self.raw_fragments.append(&mut other.raw_fragments);
         self
     }
 
@@ -317,6 +382,7 @@ impl<E: Entity> QueryBuilder<E> {
         }
         self.fields.append(&mut other.fields);
         self.group_by_fields.append(&mut other.group_by_fields);
+        self.raw_fragments.append(&mut other.raw_fragments);
         self
     }
 
@@ -432,18 +498,8 @@ impl<E: Entity> QueryBuilder<E> {
         C: Into<Vec<E::Column>>,
         M: Entity + Schema,
     {
-        let cols = cols
-            .into()
-            .into_iter()
-            .map(|col| {
-                let col_name = E::format_column(&col);
-                Query::format_field(&col_name).into_owned()
-            })
-            .collect::<Vec<_>>()
-            .join(", ");
-        let field = format!("({cols})");
-        let condition = Map::from_entry("$in", subquery.into_sql_value());
-        self.logical_and.push(Map::from_entry(field, condition));
+        let condition = self.push_subquery_condition(cols.into(), "$in", subquery);
+        self.logical_and.push(condition);
         self
     }
 
@@ -453,18 +509,22 @@ impl<E: Entity> QueryBuilder<E> {
         C: Into<Vec<E::Column>>,
         M: Entity + Schema,
     {
-        let cols = cols
-            .into()
-            .into_iter()
-            .map(|col| {
-                let col_name = E::format_column(&col);
-                Query::format_field(&col_name).into_owned()
-            })
-            .collect::<Vec<_>>()
-            .join(", ");
-        let field = format!("({cols})");
-        let condition = Map::from_entry("$nin", subquery.into_sql_value());
-        self.logical_and.push(Map::from_entry(field, condition));
+        let condition = self.push_subquery_condition(cols.into(), "$nin", subquery);
+        self.logical_and.push(condition);
+        self
+    }
+
+    /// Adds a logical `AND` condition for the subquery which returns any rows.
+    pub fn and_exists<M: Entity + Schema>(mut self, subquery: QueryBuilder<M>) -> Self {
+        let condition = self.push_exists_condition("EXISTS", subquery);
+        self.logical_and.push(condition);
+        self
+    }
+
+    /// Adds a logical `AND` condition for the subquery which returns no rows.
+    pub fn and_not_exists<M: Entity + Schema>(mut self, subquery: QueryBuilder<M>) -> Self {
+        let condition = self.push_exists_condition("NOT EXISTS", subquery);
+        self.logical_and.push(condition);
         self
     }
 
@@ -566,6 +626,46 @@ impl<E: Entity> QueryBuilder<E> {
         self
     }
 
+    /// Adds a logical `AND` condition for the array column which contains all the values.
+    #[inline]
+    pub fn and_array_contains(self, col: E::Column, values: impl IntoSqlValue) -> Self {
+        let values = Self::array_value(values);
+        self.push_logical_and(col, "$contains", values)
+    }
+
+    /// Adds a logical `AND` condition for the array column which has any values in common.
+    #[inline]
+    pub fn and_array_overlaps(self, col: E::Column, values: impl IntoSqlValue) -> Self {
+        let values = Self::array_value(values);
+        self.push_logical_and(col, "$overlaps", values)
+    }
+
+    /// Adds a logical `AND` condition for the array column whose length is greater than `n`.
+    #[inline]
+    pub fn and_array_length_gt(self, col: E::Column, n: usize) -> Self {
+        self.push_logical_and(col, "$size_gt", n.into())
+    }
+
+    /// Adds a logical `AND` condition with a raw SQL fragment.
+    ///
+    /// The fragment uses `?` or numbered placeholders `$N` for the parameters,
+    /// which are bound as arguments instead of being interpolated into the SQL.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fragment contains a semicolon or
+    /// if there are not enough parameters for the placeholders.
+    #[inline]
+    pub fn and_raw<T, V>(mut self, fragment: &str, params: V) -> Self
+    where
+        T: IntoSqlValue,
+        V: Into<Vec<T>>,
+    {
+        let condition = self.push_raw_fragment(fragment, params.into().into_sql_value());
+        self.logical_and.push(condition);
+        self
+    }
+
     /// Adds a logical `OR` condition by merging the other query builder.
     pub fn or<M: Entity>(mut self, mut other: QueryBuilder<M>) -> Self {
         let mut logical_and = other.logical_and;
@@ -578,6 +678,7 @@ impl<E: Entity> QueryBuilder<E> {
         }
         self.fields.append(&mut other.fields);
         self.group_by_fields.append(&mut other.group_by_fields);
+        self.raw_fragments.append(&mut other.raw_fragments);
         self
     }
 
@@ -593,6 +694,7 @@ impl<E: Entity> QueryBuilder<E> {
         }
         self.fields.append(&mut other.fields);
         self.group_by_fields.append(&mut other.group_by_fields);
+        self.raw_fragments.append(&mut other.raw_fragments);
         self
     }
 
@@ -708,18 +810,8 @@ impl<E: Entity> QueryBuilder<E> {
         C: Into<Vec<E::Column>>,
         M: Entity + Schema,
     {
-        let cols = cols
-            .into()
-            .into_iter()
-            .map(|col| {
-                let col_name = E::format_column(&col);
-                Query::format_field(&col_name).into_owned()
-            })
-            .collect::<Vec<_>>()
-            .join(", ");
-        let field = format!("({cols})");
-        let condition = Map::from_entry("$in", subquery.into_sql_value());
-        self.logical_or.push(Map::from_entry(field, condition));
+        let condition = self.push_subquery_condition(cols.into(), "$in", subquery);
+        self.logical_or.push(condition);
         self
     }
 
@@ -729,18 +821,22 @@ impl<E: Entity> QueryBuilder<E> {
         C: Into<Vec<E::Column>>,
         M: Entity + Schema,
     {
-        let cols = cols
-            .into()
-            .into_iter()
-            .map(|col| {
-                let col_name = E::format_column(&col);
-                Query::format_field(&col_name).into_owned()
-            })
-            .collect::<Vec<_>>()
-            .join(", ");
-        let field = format!("({cols})");
-        let condition = Map::from_entry("$nin", subquery.into_sql_value());
-        self.logical_or.push(Map::from_entry(field, condition));
+        let condition = self.push_subquery_condition(cols.into(), "$nin", subquery);
+        self.logical_or.push(condition);
+        self
+    }
+
+    /// Adds a logical `OR` condition for the subquery which returns any rows.
+    pub fn or_exists<M: Entity + Schema>(mut self, subquery: QueryBuilder<M>) -> Self {
+        let condition = self.push_exists_condition("EXISTS", subquery);
+        self.logical_or.push(condition);
+        self
+    }
+
+    /// Adds a logical `OR` condition for the subquery which returns no rows.
+    pub fn or_not_exists<M: Entity + Schema>(mut self, subquery: QueryBuilder<M>) -> Self {
+        let condition = self.push_exists_condition("NOT EXISTS", subquery);
+        self.logical_or.push(condition);
         self
     }
 
@@ -842,6 +938,46 @@ impl<E: Entity> QueryBuilder<E> {
         self
     }
 
+    /// Adds a logical `OR` condition for the array column which contains all the values.
+    #[inline]
+    pub fn or_array_contains(self, col: E::Column, values: impl IntoSqlValue) -> Self {
+        let values = Self::array_value(values);
+        self.push_logical_or(col, "$contains", values)
+    }
+
+    /// Adds a logical `OR` condition for the array column which has any values in common.
+    #[inline]
+    pub fn or_array_overlaps(self, col: E::Column, values: impl IntoSqlValue) -> Self {
+        let values = Self::array_value(values);
+        self.push_logical_or(col, "$overlaps", values)
+    }
+
+    /// Adds a logical `OR` condition for the array column whose length is greater than `n`.
+    #[inline]
+    pub fn or_array_length_gt(self, col: E::Column, n: usize) -> Self {
+        self.push_logical_or(col, "$size_gt", n.into())
+    }
+
+    /// Adds a logical `OR` condition with a raw SQL fragment.
+    ///
+    /// The fragment uses `?` or numbered placeholders `$N` for the parameters,
+    /// which are bound as arguments instead of being interpolated into the SQL.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fragment contains a semicolon or
+    /// if there are not enough parameters for the placeholders.
+    #[inline]
+    pub fn or_raw<T, V>(mut self, fragment: &str, params: V) -> Self
+    where
+        T: IntoSqlValue,
+        V: Into<Vec<T>>,
+    {
+        let condition = self.push_raw_fragment(fragment, params.into().into_sql_value());
+        self.logical_or.push(condition);
+        self
+    }
+
     /// Adds a query order.
     #[inline]
     pub fn order_by(mut self, col: impl ToString, descending: bool) -> Self {
@@ -897,6 +1033,28 @@ impl<E: Entity> QueryBuilder<E> {
         self
     }
 
+    /// Sets the statement timeout for the query execution.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the max number of rows returned by the query. A value of `0` disables the cap.
+    #[inline]
+    pub fn max_rows(mut self, max_rows: usize) -> Self {
+        self.max_rows = Some(max_rows);
+        self
+    }
+
+    /// Returns a reference to the column of this model in the outer query,
+    /// which can be used as a value in a correlated subquery.
+    #[inline]
+    pub fn col_outer(col: E::Column) -> JsonValue {
+        let field = E::format_column(&col);
+        Map::from_entry("$subquery", Query::format_field(&field)).into()
+    }
+
     /// Builds the model query.
     pub fn build(self) -> Query {
         let mut filters = self.filters;
@@ -922,9 +1080,87 @@ impl<E: Entity> QueryBuilder<E> {
         query.set_order(self.sort_order);
         query.set_offset(self.offset);
         query.set_limit(self.limit);
+        if let Some(timeout) = self.timeout {
+            query.set_timeout(timeout);
+        }
+        if let Some(max_rows) = self.max_rows {
+            query.set_max_rows(max_rows);
+        }
+        if !self.raw_fragments.is_empty() {
+            query.set_extra_flag("$raw", self.raw_fragments);
+        }
         query
     }
 
+    /// Registers a raw SQL fragment and returns the condition for it.
+    fn push_raw_fragment(&mut self, fragment: &str, params: JsonValue) -> Map {
+        assert!(
+            !fragment.contains(';'),
+            "the raw SQL fragment `{fragment}` should not contain a semicolon"
+        );
+
+        let num_params = params.as_array().map(|v| v.len()).unwrap_or_default();
+        let mut num_placeholders = 0;
+        replace_sql_placeholders(fragment, |index| {
+            num_placeholders = num_placeholders.max(index + 1);
+            "".into()
+        });
+        assert!(
+            num_placeholders <= num_params,
+            "the raw SQL fragment `{fragment}` expects {num_placeholders} parameters, \
+                but {num_params} parameters are provided"
+        );
+
+        if !self.raw_fragments.iter().any(|s| s == fragment) {
+            self.raw_fragments.push(fragment.to_owned());
+        }
+
+        let mut condition = Map::new();
+        condition.upsert("$sql", fragment);
+        condition.upsert("$params", params);
+        Map::from_entry("$raw", condition)
+    }
+
+    /// Registers an `EXISTS` subquery as a raw SQL fragment and returns the condition for it,
+    /// so that the parameters of the subquery are merged with the outer query.
+    fn push_exists_condition<M: Entity + Schema>(
+        &mut self,
+        operator: &str,
+        subquery: QueryBuilder<M>,
+    ) -> Map {
+        let (subquery, arguments) = subquery.prepare_subquery(true);
+        let fragment = format!("{operator} {subquery}");
+        self.push_raw_fragment(&fragment, arguments.into())
+    }
+
+    /// Returns the condition for the columns `IN` or `NOT IN` a subquery.
+    /// The subquery is registered as a raw SQL fragment if it has parameters.
+    fn push_subquery_condition<M: Entity + Schema>(
+        &mut self,
+        cols: Vec<E::Column>,
+        operator: &str,
+        subquery: QueryBuilder<M>,
+    ) -> Map {
+        let cols = cols
+            .into_iter()
+            .map(|col| {
+                let col_name = E::format_column(&col);
+                Query::format_field(&col_name).into_owned()
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let field = format!("({cols})");
+        let (subquery, arguments) = subquery.prepare_subquery(false);
+        if arguments.is_empty() {
+            let condition = Map::from_entry(operator, Map::from_entry("$subquery", subquery));
+            Map::from_entry(field, condition)
+        } else {
+            let operator = if operator == "$nin" { "NOT IN" } else { "IN" };
+            let fragment = format!("{field} {operator} {subquery}");
+            self.push_raw_fragment(&fragment, arguments.into())
+        }
+    }
+
     /// Adds a `HAVING` condition for non-equal parts.
     fn push_having_condition(
         mut self,
@@ -938,6 +1174,14 @@ impl<E: Entity> QueryBuilder<E> {
         self
     }
 
+    /// Converts the value into a JSON array, wrapping a single value if necessary.
+    fn array_value(value: impl IntoSqlValue) -> JsonValue {
+        match value.into_sql_value() {
+            JsonValue::Array(values) => values.into(),
+            value => vec![value].into(),
+        }
+    }
+
     /// Pushes a logical `AND` condition for the column and expressions.
     fn push_logical_and(mut self, col: E::Column, operator: &str, value: JsonValue) -> Self {
         let condition = Map::from_entry(operator, value);
@@ -957,15 +1201,117 @@ impl<E: Entity> QueryBuilder<E> {
 
 impl<E: Entity + Schema> QueryBuilder<E> {
     /// Builds a subquery SQL expression.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query builder has raw SQL fragments with parameters,
+    /// since the arguments can not be bound in a subquery.
     #[inline]
     pub fn build_subquery(self) -> String {
+        let (subquery, arguments) = self.prepare_subquery(false);
+        assert!(
+            arguments.is_empty(),
+            "raw SQL fragments with parameters are not supported in a subquery"
+        );
+        subquery
+    }
+
+    /// Prepares the subquery SQL expression and the arguments for the raw SQL fragments.
+    /// The projection fields and sort order are omitted for an `EXISTS` subquery.
+    fn prepare_subquery(self, exists: bool) -> (String, Vec<JsonValue>) {
         let query = self.build();
         let table_name = query.format_table_name::<E>();
-        let projection = query.format_table_fields::<E>();
-        let filters = query.format_filters::<E>();
-        let sort = query.format_sort();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<E>(&mut arguments);
         let pagination = query.format_pagination();
-        format!("(SELECT {projection} FROM {table_name} {filters} {sort} {pagination})")
+        let sql = if exists {
+            format!("SELECT 1 FROM {table_name} {filters} {pagination}")
+        } else {
+            let projection = query.format_table_fields::<E>();
+            let sort = query.format_sort();
+            format!("SELECT {projection} FROM {table_name} {filters} {sort} {pagination}")
+        };
+        (format!("({})", sql.trim_end()), arguments)
+    }
+}
+
+impl<E: Entity + Schema> QueryBuilder<E> {
+    /// Adds a full-text search of the term on the columns designated by `#[schema(fulltext)]`.
+    ///
+    /// The operators of the full-text search are stripped from the term, and the rank of
+    /// relevance is selected as the `search_score` field, which can be used for sorting.
+    /// If no fields have been added, all the columns of the model are selected.
+    pub fn search(mut self, term: &str) -> Self {
+        if let Some(score) = search::format_search_score::<E>(term) {
+            if self.fields.is_empty() {
+                self.fields
+                    .extend(E::fields().iter().map(|&field| field.to_owned()));
+            }
+            self.fields
+                .push([search::SEARCH_SCORE_FIELD, ":", &score].concat());
+            self.filters.upsert("$search", term);
+        }
+        self
+    }
+}
+
+impl<E: Entity + Schema> QueryBuilder<E> {
+    /// Adds a logical `AND` condition for the money column equal to the value.
+    #[inline]
+    pub fn and_money_eq(self, col: E::Column, money: Money) -> Self {
+        self.push_money_condition(col, "$eq", money)
+    }
+
+    /// Adds a logical `AND` condition for the money column less than the value.
+    #[inline]
+    pub fn and_money_lt(self, col: E::Column, money: Money) -> Self {
+        self.push_money_condition(col, "$lt", money)
+    }
+
+    /// Adds a logical `AND` condition for the money column not greater than the value.
+    #[inline]
+    pub fn and_money_le(self, col: E::Column, money: Money) -> Self {
+        self.push_money_condition(col, "$le", money)
+    }
+
+    /// Adds a logical `AND` condition for the money column greater than the value.
+    #[inline]
+    pub fn and_money_gt(self, col: E::Column, money: Money) -> Self {
+        self.push_money_condition(col, "$gt", money)
+    }
+
+    /// Adds a logical `AND` condition for the money column not less than the value.
+    #[inline]
+    pub fn and_money_ge(self, col: E::Column, money: Money) -> Self {
+        self.push_money_condition(col, "$ge", money)
+    }
+
+    /// Pushes the conditions for the amount column and the currency column,
+    /// so that only the amounts in the same currency are compared.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the column is not a money field stored as the amount and currency columns.
+    fn push_money_condition(mut self, col: E::Column, operator: &str, money: Money) -> Self {
+        let name = col.as_ref();
+        let amount_name = [name, "_amount"].concat();
+        assert!(
+            E::get_column(&amount_name)
+                .is_some_and(|column| column.extra().get_str("money_field") == Some(name)),
+            "the field `{name}` should be a money field with the amount and currency columns"
+        );
+
+        let field = E::format_column(&col);
+        let amount_field = [&field, "_amount"].concat();
+        let currency_field = [&field, "_currency"].concat();
+        let amount = money.amount().to_string();
+        let amount_condition = Map::from_entry(operator, amount);
+        let currency_condition = Map::from_entry("$eq", money.currency());
+        self.logical_and
+            .push(Map::from_entry(amount_field, amount_condition));
+        self.logical_and
+            .push(Map::from_entry(currency_field, currency_condition));
+        self
     }
 }
 
@@ -999,6 +1345,9 @@ pub(super) trait QueryExt<DB> {
     /// Returns the query limit.
     fn query_limit(&self) -> usize;
 
+    /// Returns a reference to the extra flags.
+    fn query_extra_flags(&self) -> &Map;
+
     /// Returns a placeholder for the n-th parameter.
     fn placeholder(n: usize) -> SharedString;
 
@@ -1052,7 +1401,9 @@ pub(super) trait QueryExt<DB> {
     }
 
     /// Formats the query filters to generate SQL `WHERE` expression.
-    fn format_filters<M: Schema>(&self) -> String {
+    ///
+    /// The arguments for the raw SQL fragments will be appended to `arguments`.
+    fn format_filters<M: Schema>(&self, arguments: &mut Vec<JsonValue>) -> String {
         let filters = self.query_filters();
         if filters.is_empty() {
             return String::new();
@@ -1064,19 +1415,22 @@ pub(super) trait QueryExt<DB> {
             match key.as_str() {
                 "$and" => {
                     if let Some(filters) = value.as_array() {
-                        let condition = Self::format_logical_filters::<M>(filters, " AND ");
+                        let condition =
+                            self.format_logical_filters::<M>(filters, " AND ", arguments);
                         logical_and_conditions.push(condition);
                     }
                 }
                 "$not" => {
                     if let Some(filters) = value.as_array() {
-                        let condition = Self::format_logical_filters::<M>(filters, " AND ");
+                        let condition =
+                            self.format_logical_filters::<M>(filters, " AND ", arguments);
                         logical_and_conditions.push(format!("(NOT {condition})"));
                     }
                 }
                 "$or" => {
                     if let Some(filters) = value.as_array() {
-                        let condition = Self::format_logical_filters::<M>(filters, " OR ");
+                        let condition =
+                            self.format_logical_filters::<M>(filters, " OR ", arguments);
                         logical_and_conditions.push(condition);
                     }
                 }
@@ -1102,6 +1456,19 @@ pub(super) trait QueryExt<DB> {
                         logical_and_conditions.push(condition);
                     }
                 }
+                "$search" => {
+                    if let Some(condition) = value
+                        .as_str()
+                        .and_then(search::format_search_condition::<M>)
+                    {
+                        logical_and_conditions.push(condition);
+                    }
+                }
+                "$raw" => {
+                    if let Some(condition) = self.format_raw_fragment(value, arguments) {
+                        logical_and_conditions.push(condition);
+                    }
+                }
                 _ => {
                     if let Some(col) = M::get_column(key) {
                         let condition = if let Some(subquery) =
@@ -1110,7 +1477,8 @@ pub(super) trait QueryExt<DB> {
                             let key = Query::format_field(key);
                             format!(r#"{key} = {subquery}"#)
                         } else {
-                            col.format_filter(key, value)
+                            col.format_case_insensitive_filter(key, value)
+                                .unwrap_or_else(|| col.format_filter(key, value))
                         };
                         if !condition.is_empty() {
                             logical_and_conditions.push(condition);
@@ -1135,7 +1503,7 @@ pub(super) trait QueryExt<DB> {
                 .join(", ");
             expression += &format!(" GROUP BY {groups}");
             if let Some(filters) = filters.get_array("$having") {
-                let condition = Self::format_logical_filters::<M>(filters, " AND ");
+                let condition = self.format_logical_filters::<M>(filters, " AND ", arguments);
                 expression += &format!(" HAVING {condition}");
             }
         }
@@ -1143,7 +1511,12 @@ pub(super) trait QueryExt<DB> {
     }
 
     // Formats the filters with a logic operator.
-    fn format_logical_filters<M: Schema>(filters: &[JsonValue], operator: &str) -> String {
+    fn format_logical_filters<M: Schema>(
+        &self,
+        filters: &[JsonValue],
+        operator: &str,
+        arguments: &mut Vec<JsonValue>,
+    ) -> String {
         let mut conditions = Vec::with_capacity(filters.len());
         for filter in filters {
             if let JsonValue::Object(filter) = filter {
@@ -1152,25 +1525,34 @@ pub(super) trait QueryExt<DB> {
                     match key.as_str() {
                         "$and" => {
                             if let Some(filters) = value.as_array() {
-                                let condition = Self::format_logical_filters::<M>(filters, " AND ");
+                                let condition =
+                                    self.format_logical_filters::<M>(filters, " AND ", arguments);
                                 logical_and_conditions.push(condition);
                             }
                         }
                         "$not" => {
                             if let Some(filters) = value.as_array() {
-                                let condition = Self::format_logical_filters::<M>(filters, " AND ");
+                                let condition =
+                                    self.format_logical_filters::<M>(filters, " AND ", arguments);
                                 logical_and_conditions.push(format!("(NOT {condition})"));
                             }
                         }
                         "$nor" => {
                             if let Some(filters) = value.as_array() {
-                                let condition = Self::format_logical_filters::<M>(filters, " OR ");
+                                let condition =
+                                    self.format_logical_filters::<M>(filters, " OR ", arguments);
                                 logical_and_conditions.push(format!("(NOT {condition})"));
                             }
                         }
                         "$or" => {
                             if let Some(filters) = value.as_array() {
-                                let condition = Self::format_logical_filters::<M>(filters, " OR ");
+                                let condition =
+                                    self.format_logical_filters::<M>(filters, " OR ", arguments);
+                                logical_and_conditions.push(condition);
+                            }
+                        }
+                        "$raw" => {
+                            if let Some(condition) = self.format_raw_fragment(value, arguments) {
                                 logical_and_conditions.push(condition);
                             }
                         }
@@ -1182,7 +1564,8 @@ pub(super) trait QueryExt<DB> {
                                     let key = Query::format_field(key);
                                     format!(r#"{key} = {subquery}"#)
                                 } else {
-                                    col.format_filter(key, value)
+                                    col.format_case_insensitive_filter(key, value)
+                                        .unwrap_or_else(|| col.format_filter(key, value))
                                 };
                                 if !condition.is_empty() {
                                     logical_and_conditions.push(condition);
@@ -1205,6 +1588,42 @@ pub(super) trait QueryExt<DB> {
         Self::join_conditions(conditions, operator)
     }
 
+    /// Formats a raw SQL fragment registered by the query builder.
+    /// The parameters are appended to `arguments` and never interpolated into the SQL.
+    fn format_raw_fragment(
+        &self,
+        value: &JsonValue,
+        arguments: &mut Vec<JsonValue>,
+    ) -> Option<String> {
+        let raw = value.as_object()?;
+        let fragment = raw.get_str("$sql")?;
+        let registered = self
+            .query_extra_flags()
+            .get_array("$raw")
+            .is_some_and(|fragments| fragments.iter().any(|s| s.as_str() == Some(fragment)));
+        if !registered {
+            tracing::warn!(fragment, "the unregistered raw SQL fragment is ignored");
+            return None;
+        }
+
+        let params = raw
+            .get_array("$params")
+            .map(|v| v.as_slice())
+            .unwrap_or_default();
+        let sql = replace_sql_placeholders(fragment, |index| {
+            let value = params.get(index).unwrap_or(&JsonValue::Null);
+            let argument = match value {
+                JsonValue::Array(values) if cfg!(feature = "orm-postgres") => {
+                    format_array_literal(values).into()
+                }
+                _ => value.clone(),
+            };
+            arguments.push(argument);
+            Self::placeholder(arguments.len())
+        });
+        Some(format!("({sql})"))
+    }
+
     /// Formats a query filter.
     fn format_filter(key: &str, value: &JsonValue) -> String {
         if let Some(filter) = value.as_object() {
@@ -1265,10 +1684,17 @@ pub(super) trait QueryExt<DB> {
                     } else {
                         format!("{sort_field} ASC")
                     };
-                    if order.nulls_first() {
-                        expr.push_str(" NULLS FIRST");
+                    if cfg!(feature = "orm-postgres") {
+                        if order.nulls_first() {
+                            expr.push_str(" NULLS FIRST");
+                        } else if order.nulls_last() {
+                            expr.push_str(" NULLS LAST");
+                        }
+                    } else if order.nulls_first() {
+                        // Emulates `NULLS FIRST` since it is unsupported by MySQL.
+                        expr = format!("{sort_field} IS NULL DESC, {expr}");
                     } else if order.nulls_last() {
-                        expr.push_str(" NULLS LAST");
+                        expr = format!("{sort_field} IS NULL ASC, {expr}");
                     }
                     expr
                 })
@@ -1348,6 +1774,68 @@ pub(crate) fn prepare_sql_query<'a>(
     }
 }
 
+/// Replaces the placeholders `?` and `$N` outside of the quoted strings in a raw SQL fragment.
+/// The closure receives the zero-based index of the parameter for each placeholder.
+pub(crate) fn replace_sql_placeholders(
+    fragment: &str,
+    mut placeholder: impl FnMut(usize) -> SharedString,
+) -> String {
+    let mut sql = String::with_capacity(fragment.len());
+    let mut chars = fragment.chars().peekable();
+    let mut quote = None;
+    let mut next_index = 0;
+    while let Some(c) = chars.next() {
+        if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+            sql.push(c);
+            continue;
+        }
+        match c {
+            '\'' | '"' | '`' => {
+                quote = Some(c);
+                sql.push(c);
+            }
+            '?' => {
+                sql.push_str(&placeholder(next_index));
+                next_index += 1;
+            }
+            '$' if chars.peek().is_some_and(|c| c.is_ascii_digit()) => {
+                let mut n = 0;
+                while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+                    n = n * 10 + digit.to_digit(10).unwrap_or_default() as usize;
+                }
+                sql.push_str(&placeholder(n.saturating_sub(1)));
+            }
+            _ => sql.push(c),
+        }
+    }
+    sql
+}
+
+/// Formats the values as a PostgreSQL array literal, which can be cast to a native array
+/// by the placeholder such as `$1::text[]`.
+pub(crate) fn format_array_literal(values: &[JsonValue]) -> String {
+    let elements = values
+        .iter()
+        .map(|value| match value {
+            JsonValue::Null => "NULL".to_owned(),
+            JsonValue::Bool(_) | JsonValue::Number(_) => value.to_string(),
+            JsonValue::String(s) => {
+                let s = s.replace('\\', r"\\").replace('"', r#"\""#);
+                format!(r#""{s}""#)
+            }
+            JsonValue::Array(values) => format_array_literal(values),
+            JsonValue::Object(_) => {
+                let s = value.to_string().replace('\\', r"\\").replace('"', r#"\""#);
+                format!(r#""{s}""#)
+            }
+        })
+        .collect::<Vec<_>>();
+    format!("{{{}}}", elements.join(","))
+}
+
 /// Regex for the interpolation parameter.
 static INTERPOLATION_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"\$\{\s*([a-zA-Z]+[\w\.]*)\s*\}")
@@ -1362,7 +1850,54 @@ static STATEMENT_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
 
 #[cfg(test)]
 mod tests {
-    use zino_core::{extension::JsonObjectExt, Map};
+    use super::{QueryBuilder, QueryExt};
+    use crate::Schema;
+    use serde::{Deserialize, Serialize};
+    use zino_core::{extension::JsonObjectExt, json, model::Query, JsonValue, Map};
+
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    struct User {
+        id: i64,
+        name: String,
+    }
+
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    struct Order {
+        id: i64,
+        user_id: i64,
+        amount: i64,
+    }
+
+    impl_test_schema!(User, UserColumn, "user", "users", [
+        Id => ("id", "i64"),
+        Name => ("name", "String"),
+    ]);
+
+    impl_test_schema!(Order, OrderColumn, "order", "orders", [
+        Id => ("id", "i64"),
+        UserId => ("user_id", "i64"),
+        Amount => ("amount", "i64"),
+    ], max_rows = 2);
+
+    /// Formats the SQL `SELECT` statement in the same way as `Schema::find`.
+    fn format_select<M: Schema>(query: &Query) -> (String, Vec<JsonValue>) {
+        let table_name = query.format_table_name::<M>();
+        let projection = query.format_table_fields::<M>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<M>(&mut arguments);
+        let sql = format!("SELECT {projection} FROM {table_name} {filters}");
+        (sql, arguments)
+    }
+
+    /// Returns the orders of the outer user with an amount greater than the value.
+    fn orders_with_amount_gt(amount: i64) -> QueryBuilder<Order> {
+        QueryBuilder::<Order>::new()
+            .and_eq(
+                OrderColumn::UserId,
+                QueryBuilder::<User>::col_outer(UserColumn::Id),
+            )
+            .and_raw("amount > ?", [amount])
+    }
 
     #[test]
     fn it_formats_query_params() {
@@ -1399,4 +1934,226 @@ mod tests {
         );
         assert_eq!(values[0], 18);
     }
+
+    #[test]
+    fn it_replaces_sql_placeholders() {
+        let fragment = "coalesce(nickname, name) ILIKE ? AND note <> '?' AND age > ?";
+        let sql = super::replace_sql_placeholders(fragment, |n| format!("${}", n + 1).into());
+        assert_eq!(
+            sql,
+            "coalesce(nickname, name) ILIKE $1 AND note <> '?' AND age > $2"
+        );
+
+        let fragment = "score BETWEEN $2 AND $1";
+        let sql = super::replace_sql_placeholders(fragment, |_| "?".into());
+        assert_eq!(sql, "score BETWEEN ? AND ?");
+    }
+
+    #[test]
+    fn it_formats_array_literal() {
+        let values = vec![
+            JsonValue::from("rust"),
+            JsonValue::from(r#"say "hi""#),
+            JsonValue::Null,
+        ];
+        let literal = super::format_array_literal(&values);
+        assert_eq!(literal, r#"{"rust","say \"hi\"",NULL}"#);
+
+        let values = vec![JsonValue::from(1), JsonValue::from(2)];
+        assert_eq!(super::format_array_literal(&values), "{1,2}");
+    }
+
+    #[test]
+    fn it_maps_columns_to_fields() {
+        assert_eq!(UserColumn::Name.as_ref(), "name");
+        assert_eq!(OrderColumn::Amount.to_string(), "amount");
+        assert_eq!(User::fields(), ["id", "name"]);
+        assert_eq!(Order::fields(), ["id", "user_id", "amount"]);
+    }
+
+    #[test]
+    fn it_formats_correlated_exists_subquery() {
+        let query = QueryBuilder::<User>::new()
+            .and_raw("name <> ?", ["bob"])
+            .and_exists(orders_with_amount_gt(100))
+            .build();
+        let (sql, arguments) = format_select::<User>(&query);
+        let expected = if cfg!(feature = "orm-postgres") {
+            r#"SELECT * FROM "users" AS "user" WHERE ((name <> $1) AND (EXISTS (SELECT 1 FROM "orders" AS "order" WHERE ("order"."user_id" = "user"."id" AND (amount > $2)))))"#
+        } else {
+            "SELECT * FROM `users` AS `user` WHERE ((name <> ?) AND \
+                (EXISTS (SELECT 1 FROM `orders` AS `order` \
+                    WHERE (`order`.`user_id` = `user`.`id` AND (amount > ?)))))"
+        };
+        assert_eq!(sql, expected);
+        assert_eq!(arguments, [json!("bob"), json!(100)]);
+    }
+
+    #[test]
+    fn it_formats_in_subquery_with_parameters() {
+        let subquery = QueryBuilder::<Order>::new()
+            .field(OrderColumn::UserId)
+            .and_raw("amount > ?", [100]);
+        let query = QueryBuilder::<User>::new()
+            .and_not_exists(orders_with_amount_gt(500))
+            .and_in_subquery([UserColumn::Id], subquery)
+            .build();
+        let (sql, arguments) = format_select::<User>(&query);
+        let expected = if cfg!(feature = "orm-postgres") {
+            r#"SELECT * FROM "users" AS "user" WHERE ((NOT EXISTS (SELECT 1 FROM "orders" AS "order" WHERE ("order"."user_id" = "user"."id" AND (amount > $1)))) AND (("user"."id") IN (SELECT "order"."user_id" FROM "orders" AS "order" WHERE (amount > $2))))"#
+        } else {
+            "SELECT * FROM `users` AS `user` WHERE ((NOT EXISTS (SELECT 1 FROM `orders` AS `order` \
+                WHERE (`order`.`user_id` = `user`.`id` AND (amount > ?)))) AND \
+                ((`user`.`id`) IN (SELECT `order`.`user_id` FROM `orders` AS `order` \
+                    WHERE (amount > ?))))"
+        };
+        assert_eq!(sql, expected);
+        assert_eq!(arguments, [json!(500), json!(100)]);
+    }
+
+    #[cfg(not(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb",
+        feature = "orm-postgres"
+    )))]
+    #[tokio::test]
+    async fn it_executes_correlated_subqueries() {
+        use crate::executor::encode_arguments;
+        use sqlx::{sqlite::SqliteConnectOptions, Executor, Row, SqlitePool};
+
+        let pool =
+            SqlitePool::connect_with("sqlite::memory:".parse::<SqliteConnectOptions>().unwrap())
+                .await
+                .expect("fail to connect to the in-memory database");
+        pool.execute(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL);
+            CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER NOT NULL, amount INTEGER NOT NULL);
+            INSERT INTO users VALUES (1, 'alice'), (2, 'bob'), (3, 'carol');
+            INSERT INTO orders VALUES (1, 1, 150), (2, 2, 200), (3, 3, 50);",
+        )
+        .await
+        .expect("fail to prepare the tables");
+
+        let exists_query = QueryBuilder::<User>::new()
+            .and_raw("name <> ?", ["bob"])
+            .and_exists(orders_with_amount_gt(100))
+            .build();
+        let not_exists_query = QueryBuilder::<User>::new()
+            .and_raw("name <> ?", ["bob"])
+            .and_not_exists(orders_with_amount_gt(100))
+            .build();
+        for (query, expected_name) in [(exists_query, "alice"), (not_exists_query, "carol")] {
+            let (sql, arguments) = format_select::<User>(&query);
+            let arguments = encode_arguments(&arguments).expect("fail to encode the arguments");
+            let rows = sqlx::query_with(&sql, arguments)
+                .fetch_all(&pool)
+                .await
+                .expect("fail to execute the query");
+            let names = rows
+                .iter()
+                .map(|row| row.get::<String, _>("name"))
+                .collect::<Vec<_>>();
+            assert_eq!(names, [expected_name]);
+        }
+    }
+
+    #[test]
+    fn it_keeps_limit_and_max_rows_apart() {
+        let query = QueryBuilder::<User>::new()
+            .offset(8)
+            .limit(4)
+            .max_rows(3)
+            .build();
+        assert_eq!(query.limit(), 4);
+        assert_eq!(query.max_rows(), Some(3));
+        assert!(!query.max_rows_disabled());
+        assert_eq!(query.format_pagination(), "LIMIT 4 OFFSET 8");
+
+        let mut query = QueryBuilder::<User>::new().build();
+        assert_eq!(query.max_rows(), None);
+        query.disable_limit();
+        query.disable_max_rows();
+        assert!(query.max_rows_disabled());
+        assert_eq!(query.format_pagination(), "");
+    }
+
+    #[cfg(not(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb",
+        feature = "orm-postgres"
+    )))]
+    #[tokio::test]
+    async fn it_paginates_under_max_rows() {
+        use crate::{row_limit::with_query_max_rows, ConnectionPool, Executor};
+        use sqlx::{pool::PoolOptions, sqlite::SqliteConnectOptions};
+
+        let pool = PoolOptions::<sqlx::Sqlite>::new()
+            .max_connections(1)
+            .connect_with("sqlite::memory:".parse::<SqliteConnectOptions>().unwrap())
+            .await
+            .expect("fail to connect to the in-memory database");
+        let pool = ConnectionPool::new("main", "main", pool);
+        pool.execute(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL);
+            WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c WHERE x < 10)
+            INSERT INTO users SELECT x, 'user' || x FROM c;",
+        )
+        .await
+        .expect("fail to prepare the table");
+
+        // Returns the sizes of the pages with the truncation flags.
+        let paginate = |limit: usize, max_rows: Option<usize>| {
+            let pool = &pool;
+            async move {
+                let mut pages = Vec::new();
+                for offset in (0..10).step_by(limit) {
+                    let mut builder = QueryBuilder::<User>::new().offset(offset).limit(limit);
+                    if let Some(max_rows) = max_rows {
+                        builder = builder.max_rows(max_rows);
+                    }
+                    let query = builder.build();
+                    let sql = format!(
+                        "SELECT id FROM users ORDER BY id {};",
+                        query.format_pagination()
+                    );
+                    let (rows, truncated) =
+                        with_query_max_rows::<User, _, _>(&query, pool.fetch(&sql)).await;
+                    pages.push((rows.unwrap().len(), truncated));
+                }
+                pages
+            }
+        };
+
+        // Pages within the cap are never truncated, including the last partial page.
+        assert_eq!(
+            paginate(3, Some(3)).await,
+            [(3, false), (3, false), (3, false), (1, false)]
+        );
+
+        // Pages exceeding the cap are truncated, while the last page within the cap is not.
+        assert_eq!(
+            paginate(4, Some(3)).await,
+            [(3, true), (3, true), (2, false)]
+        );
+
+        // The limit of a page is not affected when the cap is disabled.
+        assert_eq!(
+            paginate(4, Some(0)).await,
+            [(4, false), (4, false), (2, false)]
+        );
+
+        // The max rows of the model apply unless the query overrides them.
+        let sql = "SELECT id FROM users ORDER BY id LIMIT 4 OFFSET 0;";
+        let query = QueryBuilder::<Order>::new().limit(4).build();
+        let (rows, truncated) = with_query_max_rows::<Order, _, _>(&query, pool.fetch(sql)).await;
+        assert_eq!(rows.unwrap().len(), 2);
+        assert!(truncated);
+
+        let query = QueryBuilder::<Order>::new().limit(4).max_rows(5).build();
+        let (rows, truncated) = with_query_max_rows::<Order, _, _>(&query, pool.fetch(sql)).await;
+        assert_eq!(rows.unwrap().len(), 4);
+        assert!(!truncated);
+    }
 }
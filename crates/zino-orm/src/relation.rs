@@ -0,0 +1,435 @@
+use super::{
+    column::ColumnExt, query::QueryExt, ConnectionPool, DatabaseConnection, DecodeRow,
+    EncodeColumn, Executor, Schema,
+};
+use sqlx::Acquire;
+use std::collections::HashMap;
+use zino_core::{
+    bail,
+    error::Error,
+    extension::{JsonObjectExt, JsonValueExt},
+    model::Query,
+    validation::Validation,
+    warn, BoxFuture, JsonValue, Map,
+};
+
+/// Max depth of the nested documents.
+const MAX_DEPTH: usize = 4;
+
+/// A relationship between a model and its children declared by
+/// `#[schema(children = "Task:project_id")]`, where the child model `Task`
+/// refers to the parent with the column `project_id`.
+///
+/// # Nested documents
+///
+/// The nested format is stable for backups. A document is the model data
+/// with an additional array field for each included relationship,
+/// which is named by the relationship and contains the child documents recursively:
+///
+/// ```json
+/// {
+///     "id": "0193d8e6-2970-7b52-bc06-80a981212aa9",
+///     "name": "Apollo",
+///     "tasks": [
+///         { "id": "0193c06d-bee6-7070-a5e7-9659161bddb5", "project_id": "0193d8e6-...", "name": "Launch" }
+///     ],
+///     "datasets": []
+/// }
+/// ```
+///
+/// The foreign keys of the child documents are rewritten to the primary keys assigned
+/// to the parents when the documents are imported. The depth of the nested documents
+/// is bounded, and a relationship to a model on the path from the root is rejected.
+#[derive(Debug, Clone, Copy)]
+pub struct ModelRelation {
+    /// Relationship name.
+    name: &'static str,
+    /// Column referring to the parent.
+    foreign_key: &'static str,
+    /// Model name.
+    model_name: &'static str,
+    /// Primary key name.
+    primary_key_name: &'static str,
+    /// A flag to indicate whether the primary key is auto-incremented.
+    auto_increment: bool,
+    /// Function to return the child relationships.
+    children: fn() -> &'static [ModelRelation],
+    /// Function to construct a default query.
+    default_query: fn() -> Query,
+    /// Function to prepare the SQL for a query.
+    prepare_select: fn(&Query, &mut Vec<JsonValue>) -> String,
+    /// Function to prepare the SQL for inserting a model.
+    prepare_insert: fn(&Map) -> String,
+    /// Function to read the model data.
+    read_model: fn(&Map) -> Result<Map, Validation>,
+    /// Function to get the model reader.
+    reader: fn() -> Result<&'static ConnectionPool, Error>,
+    /// Function to get the model writer.
+    writer: fn() -> Result<&'static ConnectionPool, Error>,
+}
+
+impl ModelRelation {
+    /// Creates a new instance for the model as the root of nested documents.
+    #[inline]
+    pub fn root<M: Schema>() -> Self {
+        Self::child::<M>(M::MODEL_NAME, "")
+    }
+
+    /// Creates a new instance for the child model with the relationship name
+    /// and the column referring to the parent.
+    pub fn child<M: Schema>(name: &'static str, foreign_key: &'static str) -> Self {
+        Self {
+            name,
+            foreign_key,
+            model_name: M::MODEL_NAME,
+            primary_key_name: M::PRIMARY_KEY_NAME,
+            auto_increment: M::primary_key_column().auto_increment(),
+            children: M::children,
+            default_query: M::default_query,
+            prepare_select: prepare_select::<M>,
+            prepare_insert: prepare_insert::<M>,
+            read_model: read_model::<M>,
+            reader: M::init_reader,
+            writer: M::init_writer,
+        }
+    }
+
+    /// Returns the relationship name.
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the column referring to the parent.
+    #[inline]
+    pub fn foreign_key(&self) -> &'static str {
+        self.foreign_key
+    }
+
+    /// Returns the model name.
+    #[inline]
+    pub fn model_name(&self) -> &'static str {
+        self.model_name
+    }
+
+    /// Returns the child relationships.
+    #[inline]
+    pub fn children(&self) -> &'static [ModelRelation] {
+        (self.children)()
+    }
+
+    /// Returns the child relationship with the name.
+    #[inline]
+    pub fn get_child(&self, name: &str) -> Option<&'static ModelRelation> {
+        self.children().iter().find(|child| child.name == name)
+    }
+
+    /// Embeds the children of the models as nested arrays for the included relationships.
+    /// The nested relationships can be included by the dotted paths such as `tasks.comments`.
+    pub async fn embed_children(&self, models: &mut [Map], include: &[&str]) -> Result<(), Error> {
+        let paths = include
+            .iter()
+            .map(|path| path.trim())
+            .filter(|path| !path.is_empty())
+            .map(|path| path.to_owned())
+            .collect::<Vec<_>>();
+        embed_nested(self, models, paths, vec![self.model_name]).await
+    }
+
+    /// Inserts the nested documents inside of a transaction. The parents are inserted first,
+    /// and the foreign keys of the children are rewritten to the primary keys of the parents.
+    ///
+    /// It returns the number of rows inserted and the validation errors with the nested paths
+    /// such as `[0].tasks[2]`. If there are any errors, the transaction will be rolled back.
+    pub async fn import_nested(&self, documents: Vec<Map>) -> Result<(u64, Vec<Map>), Error> {
+        let mut transaction = (self.writer)()?.pool().begin().await?;
+        let connection = transaction.acquire().await?;
+
+        let mut total_rows = 0;
+        let mut validations = Vec::new();
+        for (index, document) in documents.into_iter().enumerate() {
+            let path = format!("[{index}]");
+            let ancestors = vec![self.model_name];
+            total_rows += insert_nested(
+                self,
+                connection,
+                document,
+                None,
+                path,
+                ancestors,
+                &mut validations,
+            )
+            .await?;
+        }
+        if validations.is_empty() {
+            transaction.commit().await?;
+            Ok((total_rows, validations))
+        } else {
+            transaction.rollback().await?;
+            Ok((0, validations))
+        }
+    }
+}
+
+/// Embeds the children of the models recursively.
+fn embed_nested<'a>(
+    relation: &'a ModelRelation,
+    models: &'a mut [Map],
+    paths: Vec<String>,
+    ancestors: Vec<&'static str>,
+) -> BoxFuture<'a, Result<(), Error>> {
+    Box::pin(async move {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        if ancestors.len() > MAX_DEPTH {
+            bail!(
+                "the nested documents should not be deeper than {} levels",
+                MAX_DEPTH
+            );
+        }
+
+        let mut included_paths = Vec::<(&str, Vec<String>)>::new();
+        for path in paths.iter() {
+            let (name, subpath) = match path.split_once('.') {
+                Some((name, subpath)) => (name, Some(subpath)),
+                None => (path.as_str(), None),
+            };
+            let position = match included_paths.iter().position(|(n, _)| *n == name) {
+                Some(position) => position,
+                None => {
+                    included_paths.push((name, Vec::new()));
+                    included_paths.len() - 1
+                }
+            };
+            if let Some(subpath) = subpath {
+                included_paths[position].1.push(subpath.to_owned());
+            }
+        }
+
+        let primary_key_name = relation.primary_key_name;
+        let primary_key_values = models
+            .iter()
+            .filter_map(|model| model.get(primary_key_name).cloned())
+            .collect::<Vec<_>>();
+        for (name, subpaths) in included_paths {
+            let child = relation.get_child(name).ok_or_else(|| {
+                warn!(
+                    "the relationship `{}` is not declared for the model `{}`",
+                    name, relation.model_name
+                )
+            })?;
+            if ancestors.contains(&child.model_name) {
+                bail!(
+                    "the relationship `{}` forms a cycle of the model `{}`",
+                    name,
+                    child.model_name
+                );
+            }
+
+            let mut children = if primary_key_values.is_empty() {
+                Vec::new()
+            } else {
+                child.find_children(primary_key_values.clone()).await?
+            };
+            let mut child_ancestors = ancestors.clone();
+            child_ancestors.push(child.model_name);
+            embed_nested(child, &mut children, subpaths, child_ancestors).await?;
+
+            let foreign_key = child.foreign_key;
+            let mut grouped_children = HashMap::<String, Vec<JsonValue>>::new();
+            for data in children {
+                if let Some(value) = data.get(foreign_key) {
+                    let key = value.to_string_unquoted();
+                    grouped_children.entry(key).or_default().push(data.into());
+                }
+            }
+            for model in models.iter_mut() {
+                let nested_documents = model
+                    .get(primary_key_name)
+                    .and_then(|value| grouped_children.remove(&value.to_string_unquoted()))
+                    .unwrap_or_default();
+                model.upsert(name, nested_documents);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Inserts the nested document recursively.
+fn insert_nested<'a>(
+    relation: &'a ModelRelation,
+    connection: &'a mut DatabaseConnection,
+    mut document: Map,
+    parent_key: Option<JsonValue>,
+    path: String,
+    ancestors: Vec<&'static str>,
+    validations: &'a mut Vec<Map>,
+) -> BoxFuture<'a, Result<u64, Error>> {
+    Box::pin(async move {
+        if ancestors.len() > MAX_DEPTH {
+            bail!(
+                "the nested documents should not be deeper than {} levels",
+                MAX_DEPTH
+            );
+        }
+
+        let mut nested_documents = Vec::new();
+        for child in relation.children() {
+            match document.remove(child.name) {
+                Some(JsonValue::Array(documents)) => {
+                    if ancestors.contains(&child.model_name) {
+                        bail!(
+                            "the relationship `{}` forms a cycle of the model `{}`",
+                            child.name,
+                            child.model_name
+                        );
+                    }
+                    nested_documents.push((child, documents));
+                }
+                Some(JsonValue::Null) | None => (),
+                Some(_) => {
+                    let mut validation = Validation::new();
+                    validation.record(child.name, "it should be an array");
+                    let mut map = validation.into_map();
+                    map.upsert("path", path.as_str());
+                    validations.push(map);
+                }
+            }
+        }
+        if let Some(parent_key) = parent_key {
+            document.upsert(relation.foreign_key, parent_key);
+        }
+
+        let data = match (relation.read_model)(&document) {
+            Ok(data) => data,
+            Err(validation) => {
+                let mut map = validation.into_map();
+                map.upsert("path", path);
+                validations.push(map);
+                return Ok(0);
+            }
+        };
+        let sql = (relation.prepare_insert)(&data);
+        let primary_key_name = relation.primary_key_name;
+        let primary_key = if relation.auto_increment && cfg!(feature = "orm-postgres") {
+            let row = (&mut *connection)
+                .fetch_optional(&sql)
+                .await?
+                .ok_or_else(|| warn!("the primary key should be returned"))?;
+            Map::decode_row(&row)?.remove(primary_key_name)
+        } else if relation.auto_increment {
+            let query_result = (&mut *connection).execute(&sql).await?;
+            Query::parse_query_result(query_result)
+                .0
+                .map(JsonValue::from)
+        } else {
+            (&mut *connection).execute(&sql).await?;
+            data.get(primary_key_name).cloned()
+        };
+        let primary_key = primary_key
+            .filter(|value| !value.is_null())
+            .ok_or_else(|| warn!("the primary key of `{}` should be assigned", path))?;
+
+        let mut total_rows = 1;
+        for (child, documents) in nested_documents {
+            for (index, document) in documents.into_iter().enumerate() {
+                let Some(document) = document.into_map_opt() else {
+                    let mut validation = Validation::new();
+                    validation.record(child.name, "the nested document should be an object");
+                    let mut map = validation.into_map();
+                    map.upsert("path", format!("{path}.{}[{index}]", child.name));
+                    validations.push(map);
+                    continue;
+                };
+                let child_path = format!("{path}.{}[{index}]", child.name);
+                let mut child_ancestors = ancestors.clone();
+                child_ancestors.push(child.model_name);
+                total_rows += insert_nested(
+                    child,
+                    &mut *connection,
+                    document,
+                    Some(primary_key.clone()),
+                    child_path,
+                    child_ancestors,
+                    &mut *validations,
+                )
+                .await?;
+            }
+        }
+        Ok(total_rows)
+    })
+}
+
+impl ModelRelation {
+    /// Finds the children referring to the parents.
+    async fn find_children(&self, parent_keys: Vec<JsonValue>) -> Result<Vec<Map>, Error> {
+        let mut query = (self.default_query)();
+        query.add_filter(self.foreign_key, Map::from_entry("$in", parent_keys));
+        query.order_asc(self.primary_key_name);
+        query.disable_limit();
+
+        let mut arguments = Vec::new();
+        let sql = (self.prepare_select)(&query, &mut arguments);
+        let pool = (self.reader)()?.pool();
+        let (rows, truncated) =
+            super::with_max_rows(None, pool.fetch_with_values(&sql, &arguments)).await;
+        let rows = rows?;
+        if truncated {
+            bail!(
+                "the number of `{}` exceeds the max rows {} for the nested documents",
+                self.name,
+                super::row_limit::max_rows()
+            );
+        }
+        rows.iter().map(Map::decode_row).collect()
+    }
+}
+
+/// Prepares the SQL for the query.
+fn prepare_select<M: Schema>(query: &Query, arguments: &mut Vec<JsonValue>) -> String {
+    let table_name = query.format_table_name::<M>();
+    let projection = query.format_table_fields::<M>();
+    let filters = query.format_filters::<M>(arguments);
+    let sort = query.format_sort();
+    format!("SELECT {projection} FROM {table_name} {filters} {sort};")
+}
+
+/// Prepares the SQL for inserting the model data.
+fn prepare_insert<M: Schema>(data: &Map) -> String {
+    let columns = M::columns();
+    let mut fields = Vec::with_capacity(columns.len());
+    let values = columns
+        .iter()
+        .filter_map(|col| {
+            if col.auto_increment() {
+                None
+            } else {
+                fields.push(Query::format_field(col.name()));
+                Some(col.encode_value(col.get_value(data)))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let fields = fields.join(", ");
+    let table_name = Query::table_name_escaped::<M>();
+    if M::primary_key_column().auto_increment() && cfg!(feature = "orm-postgres") {
+        let primary_key_name = M::PRIMARY_KEY_NAME;
+        format!(
+            "INSERT INTO {table_name} ({fields}) VALUES ({values}) RETURNING {primary_key_name};"
+        )
+    } else {
+        format!("INSERT INTO {table_name} ({fields}) VALUES ({values});")
+    }
+}
+
+/// Reads the model data and returns the data to be inserted.
+fn read_model<M: Schema>(data: &Map) -> Result<Map, Validation> {
+    let mut model = M::new();
+    let validation = model.read_map(data);
+    if validation.is_success() {
+        Ok(model.into_map())
+    } else {
+        Err(validation)
+    }
+}
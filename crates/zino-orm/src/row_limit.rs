@@ -0,0 +1,146 @@
+use super::Schema;
+use std::{
+    future::Future,
+    sync::atomic::{AtomicBool, Ordering::Relaxed},
+};
+use zino_core::model::Query;
+
+tokio::task_local! {
+    /// Row limit for the queries executed in the scope.
+    static ROW_LIMIT: RowLimit;
+}
+
+/// Max number of rows with a flag to indicate whether a result set has been truncated.
+struct RowLimit {
+    /// Max number of rows. A value of `0` disables the cap.
+    max_rows: usize,
+    /// A flag to indicate whether a result set has been truncated.
+    truncated: AtomicBool,
+}
+
+/// Executes the future with the max number of rows returned by each statement,
+/// and returns the output with a flag indicating whether any result set has been truncated.
+///
+/// The max rows are inherited from the enclosing scope or the `max-rows` of the database
+/// if it is `None`, and a value of `0` disables the cap. The truncation is also propagated
+/// to the enclosing scope, so that the caller can be notified of the truncation in
+/// the nested queries.
+pub async fn with_max_rows<F: Future>(max_rows: Option<usize>, future: F) -> (F::Output, bool) {
+    let row_limit = RowLimit {
+        max_rows: max_rows.unwrap_or_else(self::max_rows),
+        truncated: AtomicBool::new(false),
+    };
+    let (output, truncated) = ROW_LIMIT
+        .scope(row_limit, async {
+            let output = future.await;
+            let truncated = ROW_LIMIT.with(|row_limit| row_limit.truncated.load(Relaxed));
+            (output, truncated)
+        })
+        .await;
+    if truncated {
+        mark_truncated();
+    }
+    (output, truncated)
+}
+
+/// Executes the future with the max rows of the query for the model.
+/// The `Query::max_rows()` takes precedence over the `Schema::MAX_ROWS`.
+pub(crate) async fn with_query_max_rows<M, T, F>(query: &Query, future: F) -> (T, bool)
+where
+    M: Schema,
+    F: Future<Output = T>,
+{
+    let (output, truncated) = with_max_rows(query.max_rows().or(M::MAX_ROWS), future).await;
+    if truncated {
+        let filters = query.filters().keys().collect::<Vec<_>>();
+        tracing::warn!(
+            model_name = M::model_name(),
+            table_name = M::table_name(),
+            ?filters,
+            max_rows = query.max_rows().or(M::MAX_ROWS).unwrap_or_else(max_rows),
+            "the result set has been truncated by the max rows",
+        );
+    }
+    (output, truncated)
+}
+
+/// Returns the max number of rows returned by a statement in the current scope.
+/// A value of `0` disables the cap.
+pub(crate) fn max_rows() -> usize {
+    ROW_LIMIT
+        .try_with(|row_limit| row_limit.max_rows)
+        .unwrap_or_else(|_| super::MAX_ROWS.load(Relaxed))
+}
+
+/// Marks the result set as truncated in the current scope.
+/// Returns `false` if there is no scope to record the truncation.
+pub(crate) fn mark_truncated() -> bool {
+    ROW_LIMIT
+        .try_with(|row_limit| row_limit.truncated.store(true, Relaxed))
+        .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    // The test runs against an in-memory database when SQLite is the driver.
+    #[cfg(not(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-postgres",
+        feature = "orm-tidb"
+    )))]
+    #[tokio::test]
+    async fn it_signals_truncated_result_sets() {
+        use super::with_max_rows;
+        use crate::{ConnectionPool, Executor};
+        use sqlx::{pool::PoolOptions, sqlite::SqliteConnectOptions};
+
+        let pool = PoolOptions::<sqlx::Sqlite>::new()
+            .max_connections(1)
+            .connect_with("sqlite::memory:".parse::<SqliteConnectOptions>().unwrap())
+            .await
+            .expect("fail to connect to the in-memory database");
+        let pool = ConnectionPool::new("main", "main", pool);
+        let sql = |pagination: &str| {
+            format!(
+                "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c WHERE x < 5) \
+                    SELECT x FROM c {pagination};"
+            )
+        };
+
+        // The cap applies when there is no limit.
+        let (rows, truncated) = with_max_rows(Some(3), pool.fetch(&sql(""))).await;
+        assert_eq!(rows.unwrap().len(), 3);
+        assert!(truncated);
+
+        // A limit equal to the cap is not truncated.
+        let (rows, truncated) = with_max_rows(Some(3), pool.fetch(&sql("LIMIT 3"))).await;
+        assert_eq!(rows.unwrap().len(), 3);
+        assert!(!truncated);
+
+        // A limit exceeding the cap is truncated regardless of the offset.
+        let (rows, truncated) = with_max_rows(Some(3), pool.fetch(&sql("LIMIT 4 OFFSET 1"))).await;
+        assert_eq!(rows.unwrap().len(), 3);
+        assert!(truncated);
+
+        // A page within the cap is not truncated.
+        let (rows, truncated) = with_max_rows(Some(3), pool.fetch(&sql("LIMIT 2 OFFSET 4"))).await;
+        assert_eq!(rows.unwrap().len(), 1);
+        assert!(!truncated);
+
+        // The cap is disabled by `0`.
+        let (rows, truncated) = with_max_rows(Some(0), pool.fetch(&sql(""))).await;
+        assert_eq!(rows.unwrap().len(), 5);
+        assert!(!truncated);
+
+        // The max rows are inherited, and the truncation is propagated to the enclosing scope.
+        let (result, truncated) = with_max_rows(Some(2), async {
+            let (rows, truncated) = with_max_rows(None, pool.fetch(&sql(""))).await;
+            assert!(truncated);
+            rows
+        })
+        .await;
+        assert_eq!(result.unwrap().len(), 2);
+        assert!(truncated);
+    }
+}
@@ -0,0 +1,152 @@
+use std::ops::{Deref, DerefMut};
+
+#[cfg(feature = "orm-sqlx")]
+use super::{executor::Executor, DatabaseDriver};
+
+#[cfg(feature = "orm-sqlx")]
+use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+#[cfg(feature = "orm-sqlx")]
+use zino_core::error::Error;
+
+/// A savepoint inside of a transaction.
+///
+/// Rolling back to a savepoint discards the changes made after it was created
+/// while keeping the rest of the transaction, which is useful for a partial rollback.
+/// A savepoint can be nested by [`savepoint()`](Self::savepoint), and the names
+/// are made unique automatically so that the nested savepoints never collide.
+/// It dereferences to the underlying transaction, so the queries can be executed
+/// through the savepoint in the same way as the transaction.
+///
+/// A savepoint which is neither released nor rolled back is kept until
+/// the transaction ends.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino_orm::{Executor, Schema, Transaction};
+///
+/// let contexts = prepare_order_inserts(orders).await?;
+/// let failed_rows = Order::transaction(move |tx| Box::pin(async move {
+///     let mut failed_rows = Vec::new();
+///     for (index, ctx) in contexts.into_iter().enumerate() {
+///         let mut savepoint = Order::savepoint(tx, "order").await?;
+///         match savepoint.execute(ctx.query()).await {
+///             Ok(_) => savepoint.release().await?,
+///             Err(err) => {
+///                 savepoint.rollback_to().await?;
+///                 failed_rows.push((index, err));
+///             }
+///         }
+///     }
+///     Ok(failed_rows)
+/// })).await?;
+/// ```
+#[derive(Debug)]
+pub struct Savepoint<'t, Tx> {
+    /// The underlying transaction.
+    tx: &'t mut Tx,
+    /// Unique savepoint name.
+    name: String,
+}
+
+impl<Tx> Savepoint<'_, Tx> {
+    /// Returns the savepoint name.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(feature = "orm-sqlx")]
+impl<'t, 'c> Savepoint<'t, sqlx::Transaction<'c, DatabaseDriver>> {
+    /// Creates a savepoint inside of the transaction.
+    pub async fn new(
+        tx: &'t mut sqlx::Transaction<'c, DatabaseDriver>,
+        name: &str,
+    ) -> Result<Self, Error> {
+        let name = unique_name(name);
+        let sql = format!("SAVEPOINT {name};");
+        (&mut **tx).execute(&sql).await?;
+        Ok(Self { tx, name })
+    }
+
+    /// Creates a nested savepoint.
+    #[inline]
+    pub async fn savepoint(
+        &mut self,
+        name: &str,
+    ) -> Result<Savepoint<'_, sqlx::Transaction<'c, DatabaseDriver>>, Error> {
+        Savepoint::new(&mut *self.tx, name).await
+    }
+
+    /// Rolls back the changes made after the savepoint was created.
+    /// The savepoint is released so that it can not be used again.
+    pub async fn rollback_to(self) -> Result<(), Error> {
+        let name = self.name;
+        let connection = &mut **self.tx;
+        connection
+            .execute(&format!("ROLLBACK TO SAVEPOINT {name};"))
+            .await?;
+        connection
+            .execute(&format!("RELEASE SAVEPOINT {name};"))
+            .await?;
+        Ok(())
+    }
+
+    /// Releases the savepoint and keeps the changes made after it was created.
+    pub async fn release(self) -> Result<(), Error> {
+        let name = self.name;
+        let sql = format!("RELEASE SAVEPOINT {name};");
+        (&mut **self.tx).execute(&sql).await?;
+        Ok(())
+    }
+}
+
+impl<Tx> Deref for Savepoint<'_, Tx> {
+    type Target = Tx;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.tx
+    }
+}
+
+impl<Tx> DerefMut for Savepoint<'_, Tx> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.tx
+    }
+}
+
+/// Generates a unique savepoint name with the prefix.
+#[cfg(feature = "orm-sqlx")]
+fn unique_name(prefix: &str) -> String {
+    let prefix = prefix
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect::<String>();
+    let prefix = if prefix.is_empty() { "sp" } else { &prefix };
+    let id = SAVEPOINT_ID.fetch_add(1, Relaxed);
+    format!("{prefix}_{id}")
+}
+
+/// Sequence number for the savepoint names.
+#[cfg(feature = "orm-sqlx")]
+static SAVEPOINT_ID: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(test)]
+#[cfg(feature = "orm-sqlx")]
+mod tests {
+    use super::unique_name;
+
+    #[test]
+    fn it_generates_unique_savepoint_names() {
+        let name = unique_name("import-row");
+        let nested_name = unique_name("import-row");
+        assert!(name.starts_with("importrow_"));
+        assert_ne!(name, nested_name);
+        assert!(unique_name("; DROP TABLE").starts_with("DROPTABLE_"));
+        assert!(unique_name("").starts_with("sp_"));
+    }
+}
@@ -1,8 +1,10 @@
-use super::{column::ColumnExt, query::QueryExt, schema::Schema, DatabaseDriver};
+use super::{
+    column::ColumnExt, executor::encode_arguments, query::QueryExt, schema::Schema, DatabaseDriver,
+};
 use futures::TryStreamExt;
 use sqlx::{Decode, Row, Type};
-use std::{fmt::Display, sync::atomic::Ordering::Relaxed};
-use zino_core::{error::Error, extension::JsonValueExt, model::Query, Map};
+use std::fmt::Display;
+use zino_core::{error::Error, model::Query, Map};
 
 /// Query on scalar values.
 pub trait ScalarQuery<K>: Schema<PrimaryKey = K>
@@ -19,14 +21,18 @@ where
 
         let table_name = query.format_table_name::<Self>();
         let projection = query.format_projection();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let sort = query.format_sort();
         let sql = format!("SELECT {projection} FROM {table_name} {filters} {sort} LIMIT 1;");
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql);
+        ctx.append_argument_values(&mut arguments);
 
-        let pool = Self::acquire_reader().await?.pool();
-        let scalar = sqlx::query_scalar(ctx.query()).fetch_one(pool).await?;
+        let query = sqlx::query_scalar_with(ctx.query(), encode_arguments(ctx.argument_values())?);
+
+        let mut connection = Self::acquire_reader().await?.acquire().await?;
+        let scalar = query.fetch_one(&mut *connection).await?;
         ctx.set_query_result(1, true);
         Self::after_scan(&ctx).await?;
         Self::after_query(&ctx).await?;
@@ -43,24 +49,29 @@ where
 
         let table_name = query.format_table_name::<Self>();
         let projection = query.format_projection();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let sort = query.format_sort();
         let pagination = query.format_pagination();
         let sql = format!("SELECT {projection} FROM {table_name} {filters} {sort} {pagination};");
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(&sql);
+        ctx.append_argument_values(&mut arguments);
+
+        let arguments = ctx.argument_values().to_vec();
+        let query = sqlx::query_with(&sql, encode_arguments(&arguments)?);
 
-        let pool = Self::acquire_reader().await?.pool();
-        let mut rows = sqlx::query(&sql).fetch(pool);
+        let mut connection = Self::acquire_reader().await?.acquire().await?;
+        let mut rows = query.fetch(&mut *connection);
         let mut data = Vec::new();
-        let mut max_rows = super::MAX_ROWS.load(Relaxed);
+        let max_rows = super::row_limit::max_rows();
         while let Some(row) = rows.try_next().await? {
-            if max_rows > 0 {
-                data.push(row.try_get_unchecked(0)?);
-                max_rows -= 1;
-            } else {
+            if max_rows > 0 && data.len() >= max_rows {
+                super::executor::record_truncation(&sql, max_rows);
+                ctx.set_truncated(true);
                 break;
             }
+            data.push(row.try_get_unchecked(0)?);
         }
         ctx.set_query_result(u64::try_from(data.len())?, true);
         Self::after_scan(&ctx).await?;
@@ -78,7 +89,8 @@ where
 
         let table_name = query.format_table_name::<Self>();
         let projection = query.format_projection();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let sort = query.format_sort();
         let pagination = query.format_pagination();
         let sql = format!(
@@ -87,18 +99,22 @@ where
         );
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(&sql);
+        ctx.append_argument_values(&mut arguments);
 
-        let pool = Self::acquire_reader().await?.pool();
-        let mut rows = sqlx::query(&sql).fetch(pool);
+        let arguments = ctx.argument_values().to_vec();
+        let query = sqlx::query_with(&sql, encode_arguments(&arguments)?);
+
+        let mut connection = Self::acquire_reader().await?.acquire().await?;
+        let mut rows = query.fetch(&mut *connection);
         let mut data = Vec::new();
-        let mut max_rows = super::MAX_ROWS.load(Relaxed);
+        let max_rows = super::row_limit::max_rows();
         while let Some(row) = rows.try_next().await? {
-            if max_rows > 0 {
-                data.push(row.try_get_unchecked(0)?);
-                max_rows -= 1;
-            } else {
+            if max_rows > 0 && data.len() >= max_rows {
+                super::executor::record_truncation(&sql, max_rows);
+                ctx.set_truncated(true);
                 break;
             }
+            data.push(row.try_get_unchecked(0)?);
         }
         ctx.set_query_result(u64::try_from(data.len())?, true);
         Self::after_scan(&ctx).await?;
@@ -115,16 +131,12 @@ where
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql);
 
-        let mut query = sqlx::query_scalar(ctx.query());
-        let mut arguments = Vec::with_capacity(values.len());
-        for value in values {
-            query = query.bind(value.to_string_unquoted());
-            arguments.push(value.to_string_unquoted());
-        }
+        let mut arguments = values.into_iter().cloned().collect::<Vec<_>>();
+        let query = sqlx::query_scalar_with(ctx.query(), encode_arguments(&arguments)?);
 
-        let pool = Self::acquire_reader().await?.pool();
-        let scalar = query.fetch_one(pool).await?;
-        ctx.append_arguments(&mut arguments);
+        let mut connection = Self::acquire_reader().await?.acquire().await?;
+        let scalar = query.fetch_one(&mut *connection).await?;
+        ctx.append_argument_values(&mut arguments);
         ctx.set_query_result(1, true);
         Self::after_scan(&ctx).await?;
         Ok(scalar)
@@ -139,26 +151,23 @@ where
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql.as_ref());
 
-        let mut query = sqlx::query(&sql);
-        let mut arguments = Vec::with_capacity(values.len());
-        for value in values {
-            query = query.bind(value.to_string_unquoted());
-            arguments.push(value.to_string_unquoted());
-        }
+        let mut arguments = values.into_iter().cloned().collect::<Vec<_>>();
+        let query = sqlx::query_with(&sql, encode_arguments(&arguments)?);
 
-        let pool = Self::acquire_reader().await?.pool();
-        let mut rows = query.fetch(pool);
+        let mut connection = Self::acquire_reader().await?.acquire().await?;
+        let mut rows = query.fetch(&mut *connection);
         let mut data = Vec::new();
-        let mut max_rows = super::MAX_ROWS.load(Relaxed);
+        let max_rows = super::row_limit::max_rows();
         while let Some(row) = rows.try_next().await? {
-            if max_rows > 0 {
-                data.push(row.try_get_unchecked(0)?);
-                max_rows -= 1;
-            } else {
+            if max_rows > 0 && data.len() >= max_rows {
+                super::executor::record_truncation(&sql, max_rows);
+                ctx.set_truncated(true);
                 break;
             }
+            data.push(row.try_get_unchecked(0)?);
         }
-        ctx.append_arguments(&mut arguments);
+        drop(rows);
+        ctx.append_argument_values(&mut arguments);
         ctx.set_query_result(u64::try_from(data.len())?, true);
         Self::after_scan(&ctx).await?;
         Ok(data)
@@ -189,9 +198,9 @@ where
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql);
 
-        let pool = Self::acquire_reader().await?.pool();
+        let mut connection = Self::acquire_reader().await?.acquire().await?;
         let query = sqlx::query_scalar(ctx.query()).bind(primary_key.to_string());
-        let scalar = query.fetch_one(pool).await?;
+        let scalar = query.fetch_one(&mut *connection).await?;
         ctx.set_query_result(1, true);
         Self::after_scan(&ctx).await?;
         Self::after_query(&ctx).await?;
@@ -207,14 +216,18 @@ where
 
         let projection = Self::PRIMARY_KEY_NAME;
         let table_name = query.format_table_name::<Self>();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let sort = query.format_sort();
         let sql = format!("SELECT {projection} FROM {table_name} {filters} {sort} LIMIT 1;");
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql);
+        ctx.append_argument_values(&mut arguments);
+
+        let query = sqlx::query_scalar_with(ctx.query(), encode_arguments(ctx.argument_values())?);
 
-        let pool = Self::acquire_reader().await?.pool();
-        let scalar = sqlx::query_scalar(ctx.query()).fetch_one(pool).await?;
+        let mut connection = Self::acquire_reader().await?.acquire().await?;
+        let scalar = query.fetch_one(&mut *connection).await?;
         ctx.set_query_result(1, true);
         Self::after_scan(&ctx).await?;
         Self::after_query(&ctx).await?;
@@ -230,24 +243,29 @@ where
 
         let projection = Self::PRIMARY_KEY_NAME;
         let table_name = query.format_table_name::<Self>();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let sort = query.format_sort();
         let pagination = query.format_pagination();
         let sql = format!("SELECT {projection} FROM {table_name} {filters} {sort} {pagination};");
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(&sql);
+        ctx.append_argument_values(&mut arguments);
+
+        let arguments = ctx.argument_values().to_vec();
+        let query = sqlx::query_with(&sql, encode_arguments(&arguments)?);
 
-        let pool = Self::acquire_reader().await?.pool();
-        let mut rows = sqlx::query(&sql).fetch(pool);
+        let mut connection = Self::acquire_reader().await?.acquire().await?;
+        let mut rows = query.fetch(&mut *connection);
         let mut data = Vec::new();
-        let mut max_rows = super::MAX_ROWS.load(Relaxed);
+        let max_rows = super::row_limit::max_rows();
         while let Some(row) = rows.try_next().await? {
-            if max_rows > 0 {
-                data.push(row.try_get_unchecked(0)?);
-                max_rows -= 1;
-            } else {
+            if max_rows > 0 && data.len() >= max_rows {
+                super::executor::record_truncation(&sql, max_rows);
+                ctx.set_truncated(true);
                 break;
             }
+            data.push(row.try_get_unchecked(0)?);
         }
         ctx.set_query_result(u64::try_from(data.len())?, true);
         Self::after_scan(&ctx).await?;
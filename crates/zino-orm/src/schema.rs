@@ -1,16 +1,22 @@
 use super::{
-    column::ColumnExt, mutation::MutationExt, query::QueryExt, ConnectionPool, DatabaseRow,
-    DecodeRow, EncodeColumn, Entity, Executor, GlobalPool, IntoSqlValue, JoinOn, ModelHelper,
-    QueryBuilder,
+    column::ColumnExt, computed::ComputedField, conflict::resolve_unique_violation, money,
+    mutation::MutationExt, query::QueryExt, row_limit::with_query_max_rows, search, sequence,
+    timeout::with_statement_timeout, ConnectionPool, DatabaseRow, DateTrunc, DecodeRow,
+    EncodeColumn, Entity, Executor, GlobalPool, Index, IntoSqlValue, JoinOn, KeysetIterator,
+    ModelHelper, ModelRelation, QueryBuilder, SequenceCounter,
 };
 use serde::de::DeserializeOwned;
 use sqlx::Acquire;
-use std::{fmt::Display, sync::atomic::Ordering::Relaxed};
+use std::{
+    fmt::Display,
+    future::Future,
+    sync::{atomic::Ordering::Relaxed, Arc},
+};
 use zino_core::{
     bail,
     error::Error,
     extension::{JsonObjectExt, JsonValueExt},
-    model::{Column, ModelHooks, Mutation, Query, QueryContext},
+    model::{Column, DefinitionCache, ModelHooks, Mutation, Query, QueryContext},
     warn, JsonValue, Map,
 };
 
@@ -29,6 +35,26 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
     const WRITER_NAME: &'static str = "main";
     /// Optional custom table name.
     const TABLE_NAME: Option<&'static str> = None;
+    /// Optional partitioning declared by `#[schema(partition_by = "range(created_at)")]`.
+    /// It is only supported for PostgreSQL.
+    const PARTITION_BY: Option<&'static str> = None;
+    /// Optional scope name declared by `#[schema(scope_name = "name")]`.
+    /// It is used for the required scopes `{scope_name}:read` and `{scope_name}:write`
+    /// of the default controller, and defaults to the model name.
+    const SCOPE_NAME: Option<&'static str> = None;
+    /// Optional table comment declared by `#[schema(comment = "...")]`
+    /// or the doc comments of the model.
+    const TABLE_COMMENT: Option<&'static str> = None;
+    /// Optional max number of rows returned by a query, which overrides
+    /// the `max-rows` of the database. A value of `0` disables the cap.
+    const MAX_ROWS: Option<usize> = None;
+    /// A flag to indicate whether the model is read-only, which is declared by
+    /// `#[schema(view)]` for a database view. The writes are rejected for such a model.
+    const READ_ONLY: bool = false;
+    /// Optional definition of the view declared by `#[schema(view_sql = "...")]`
+    /// or `#[schema(view_file = "...")]`. It can be either a `SELECT` query
+    /// or a complete `CREATE VIEW` statement.
+    const VIEW_DEFINITION: Option<&'static str> = None;
 
     /// Returns the primary key.
     fn primary_key(&self) -> &Self::PrimaryKey;
@@ -131,6 +157,127 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
             .find(|col| col.name() == key && !col.is_read_only())
     }
 
+    /// Returns `true` if the column for the field is sensitive,
+    /// whose values are redacted in the query logs.
+    #[inline]
+    fn is_sensitive_field(key: &str) -> bool {
+        Self::get_column(key).is_some_and(|col| col.is_sensitive())
+    }
+
+    /// Returns the column designated by `#[schema(expires_at)]` if it exists.
+    #[inline]
+    fn expiry_column() -> Option<&'static Column<'static>> {
+        Self::columns().iter().find(|col| col.is_expiry())
+    }
+
+    /// Returns the partition strategy and the partition column if the table is partitioned.
+    #[inline]
+    fn partition_key() -> Option<(&'static str, &'static str)> {
+        let (strategy, column) = Self::PARTITION_BY?
+            .trim()
+            .strip_suffix(')')?
+            .split_once('(')?;
+        Some((strategy.trim(), column.trim()))
+    }
+
+    /// Returns the columns designated by `#[schema(fulltext)]`.
+    #[inline]
+    fn fulltext_columns() -> Vec<&'static Column<'static>> {
+        Self::columns()
+            .iter()
+            .filter(|col| col.is_fulltext())
+            .collect()
+    }
+
+    /// Returns the columns designated by `#[schema(parent)]` for the nested resources.
+    #[inline]
+    fn parent_columns() -> Vec<&'static Column<'static>> {
+        Self::columns()
+            .iter()
+            .filter(|col| col.parent_param().is_some())
+            .collect()
+    }
+
+    /// Returns the child relationships declared by `#[schema(children = "Task:project_id")]`.
+    #[inline]
+    fn children() -> &'static [ModelRelation] {
+        &[]
+    }
+
+    /// Returns the computed fields declared by `#[schema(computed = "fn_name")]`
+    /// or `#[schema(computed_async = "fn_name")]`.
+    #[inline]
+    fn computed_fields() -> &'static [ComputedField] {
+        &[]
+    }
+
+    /// Returns a reference to the cache of the model definitions.
+    fn definition_cache() -> &'static DefinitionCache;
+
+    /// Returns the cached definition of the model for the action,
+    /// which is built from the columns only once.
+    ///
+    /// The default values of the expressions are not included
+    /// since they should be evaluated for each request.
+    fn definition_cached(action: &str) -> Arc<JsonValue> {
+        let key = ["definition:", action].concat();
+        Self::definition_cache().get_or_insert_with(&key, || {
+            let columns = Self::columns();
+            let mut definition = Map::new();
+            definition.upsert("type", "object");
+            if matches!(action, "insert" | "import") {
+                let required_fields = columns
+                    .iter()
+                    .filter(|&col| col.is_required())
+                    .map(|col| col.name())
+                    .collect::<Vec<_>>();
+                definition.upsert("required", required_fields);
+            }
+
+            let exclusive_attributes = if action == "update" {
+                vec!["read_only", "generated", "reserved"]
+            } else {
+                vec!["read_only", "generated", "reserved", "auto_initialized"]
+            };
+            let is_readable = matches!(action, "list" | "view" | "export" | "tree");
+            let mut properties = Map::new();
+            for col in columns {
+                if !col.has_any_attributes(&exclusive_attributes) && col.comment().is_some()
+                    || is_readable
+                {
+                    properties.upsert(col.name(), col.definition());
+                }
+            }
+            if is_readable {
+                for field in Self::computed_fields() {
+                    properties.upsert(field.name(), field.column().definition());
+                }
+            }
+            definition.upsert("properties", properties);
+
+            let parameters = Self::parent_columns()
+                .into_iter()
+                .filter_map(|col| {
+                    let mut parameter = Map::from_entry("name", col.parent_param()?);
+                    parameter.upsert("in", "path");
+                    parameter.upsert("required", true);
+                    parameter.upsert("schema", col.definition());
+                    Some(parameter)
+                })
+                .collect::<Vec<_>>();
+            if !parameters.is_empty() {
+                definition.upsert("parameters", parameters);
+            }
+            definition.into()
+        })
+    }
+
+    /// Returns `true` if the model has any columns for the full-text search.
+    #[inline]
+    fn has_fulltext_columns() -> bool {
+        Self::columns().iter().any(|col| col.is_fulltext())
+    }
+
     /// Returns `true` if the model has a column for the specific field.
     #[inline]
     fn has_column(key: &str) -> bool {
@@ -146,6 +293,25 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         Self::columns().iter().any(|col| col.name() == key)
     }
 
+    /// Returns a reference to the composite indexes
+    /// declared by `#[schema(composite_index = "columns")]`.
+    #[inline]
+    fn composite_indexes() -> &'static [Index] {
+        &[]
+    }
+
+    /// Returns the indexes derived from the columns and the composite indexes.
+    /// The indexes for the text search are excluded since they are created separately.
+    #[inline]
+    fn indexes() -> Vec<Index> {
+        let mut indexes = Self::columns()
+            .iter()
+            .filter_map(Index::with_column)
+            .collect::<Vec<_>>();
+        indexes.extend_from_slice(Self::composite_indexes());
+        indexes
+    }
+
     /// Constructs a default `Query` for the model.
     #[inline]
     fn default_query() -> Query {
@@ -179,20 +345,48 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
     }
 
     /// Creates a database table for the model.
+    ///
+    /// For a read-only model, no table is created. Instead, the view is created
+    /// if its definition has been declared. The maintenance jobs of the model
+    /// are registered according to the config.
     async fn create_table() -> Result<(), Error> {
+        #[cfg(feature = "orm-sqlx")]
+        super::MaintenanceScheduler::register_model::<Self>();
         if !super::AUTO_MIGRATION.load(Relaxed) {
             return Ok(());
         }
         Self::before_create_table().await?;
+        if Self::READ_ONLY {
+            create_view::<Self>().await?;
+            Self::after_create_table().await?;
+            return Ok(());
+        }
 
         let primary_key_name = Self::PRIMARY_KEY_NAME;
         let table_name = Self::table_name();
         let table_name_escaped = Query::table_name_escaped::<Self>();
         let columns = Self::columns();
+        let partition_key = Self::partition_key().filter(|_| cfg!(feature = "orm-postgres"));
         let mut definitions = columns
             .iter()
-            .map(|col| col.field_definition(primary_key_name))
+            .map(|col| {
+                if partition_key.is_some() {
+                    // The primary key is declared as a table constraint instead.
+                    col.field_definition("")
+                } else {
+                    col.field_definition(primary_key_name)
+                }
+            })
             .collect::<Vec<_>>();
+        if let Some((_, partition_column)) = partition_key {
+            // The primary key of a partitioned table should include the partition column.
+            let primary_key = if partition_column == primary_key_name {
+                format!("PRIMARY KEY ({primary_key_name})")
+            } else {
+                format!("PRIMARY KEY ({primary_key_name}, {partition_column})")
+            };
+            definitions.push(primary_key);
+        }
         for col in columns {
             let mut constraints = col.constraints();
             if !constraints.is_empty() {
@@ -201,19 +395,53 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         }
 
         let definitions = definitions.join(",\n  ");
-        let sql = format!("CREATE TABLE IF NOT EXISTS {table_name_escaped} (\n  {definitions}\n);");
-        let pool = Self::init_writer()?.pool();
+        let sql = if let Some((strategy, partition_column)) = partition_key {
+            let strategy = strategy.to_ascii_uppercase();
+            format!(
+                "CREATE TABLE IF NOT EXISTS {table_name_escaped} (\n  {definitions}\n) \
+                    PARTITION BY {strategy} ({partition_column});"
+            )
+        } else if let Some(comment) = Self::TABLE_COMMENT.filter(|_| {
+            cfg!(any(
+                feature = "orm-mariadb",
+                feature = "orm-mysql",
+                feature = "orm-tidb"
+            ))
+        }) {
+            let comment = Query::escape_string(comment);
+            format!(
+                "CREATE TABLE IF NOT EXISTS {table_name_escaped} (\n  {definitions}\n) \
+                    COMMENT = {comment};"
+            )
+        } else {
+            format!("CREATE TABLE IF NOT EXISTS {table_name_escaped} (\n  {definitions}\n);")
+        };
+        let connection_pool = Self::init_writer()?;
+        check_citext_extension(connection_pool, table_name, columns).await?;
+        check_enum_types(connection_pool, table_name, columns).await?;
+
+        let pool = connection_pool.pool();
         if let Err(err) = pool.execute(&sql).await {
             tracing::error!(table_name, "fail to execute `{sql}`");
             return Err(err);
         }
+        if cfg!(feature = "orm-postgres") {
+            // PostgreSQL does not support the `COMMENT` clause in the definitions.
+            if let Some(comment) = Self::TABLE_COMMENT {
+                pool.execute(&format_table_comment::<Self>(comment)).await?;
+            }
+            for sql in columns.iter().filter_map(format_column_comment::<Self>) {
+                pool.execute(&sql).await?;
+            }
+        }
         Self::after_create_table().await?;
         Ok(())
     }
 
     /// Synchronizes the table schema for the model.
+    /// The columns of a read-only model are left alone.
     async fn synchronize_schema() -> Result<(), Error> {
-        if !super::AUTO_MIGRATION.load(Relaxed) {
+        if !super::AUTO_MIGRATION.load(Relaxed) || Self::READ_ONLY {
             return Ok(());
         }
 
@@ -224,6 +452,9 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
             table_name = suffix;
         }
 
+        check_citext_extension(connection_pool, table_name, Self::columns()).await?;
+        check_enum_types(connection_pool, table_name, Self::columns()).await?;
+
         let table_name_escaped = Query::table_name_escaped::<Self>();
         let sql = if cfg!(any(
             feature = "orm-mariadb",
@@ -232,13 +463,16 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         )) {
             let table_schema = connection_pool.database();
             format!(
-                "SELECT column_name, data_type, column_default, is_nullable \
+                "SELECT column_name, data_type, column_type, column_default, is_nullable, \
+                        column_comment \
                     FROM information_schema.columns \
                         WHERE table_schema = '{table_schema}' AND table_name = '{table_name}';"
             )
         } else if cfg!(feature = "orm-postgres") {
             format!(
-                "SELECT column_name, data_type, column_default, is_nullable \
+                "SELECT column_name, data_type, column_default, is_nullable, \
+                        col_description(format('%I.%I', table_schema, table_name)::regclass, \
+                            ordinal_position) AS column_comment \
                     FROM information_schema.columns \
                         WHERE table_schema = 'public' AND table_name = '{table_name}';"
             )
@@ -282,7 +516,31 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
                 } else {
                     d.get_str("is_not_null") == Some("1")
                 };
-                if !data_type.is_some_and(|t| col.is_compatible(t)) {
+                let is_json_array = cfg!(feature = "orm-postgres")
+                    && column_type.ends_with("[]")
+                    && data_type.is_some_and(|t| {
+                        ["json", "jsonb", "text", "character varying"]
+                            .iter()
+                            .any(|s| t.eq_ignore_ascii_case(s))
+                    });
+                if is_json_array {
+                    // The data is never converted automatically since it may not be valid JSON arrays.
+                    let migration_sql = format!(
+                        "ALTER TABLE {table_name_escaped} ALTER COLUMN {column_name} \
+                            TYPE {column_type} USING ARRAY(\
+                                SELECT jsonb_array_elements_text({column_name}::jsonb)\
+                            )::{column_type};"
+                    );
+                    tracing::warn!(
+                        model_name,
+                        table_name,
+                        column_name,
+                        column_type,
+                        data_type,
+                        migration_sql,
+                        "JSON column `{column_name}` should be migrated to a native array manually",
+                    );
+                } else if !data_type.is_some_and(|t| col.is_compatible(t)) {
                     tracing::warn!(
                         model_name,
                         table_name,
@@ -303,6 +561,38 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
                         is_not_null,
                         "`NOT NULL` constraint of `{column_name}` should be consistent",
                     );
+                } else if let Some(sql) = format_column_comment::<Self>(col).filter(|_| {
+                    let column_comment = d
+                        .get_str("column_comment")
+                        .or_else(|| d.get_str("COLUMN_COMMENT"))
+                        .filter(|s| !s.is_empty());
+                    col.comment() != column_comment
+                }) {
+                    // MySQL requires the full definition to modify the comment,
+                    // so it is only applied when the column type is unchanged.
+                    let is_modifiable = cfg!(feature = "orm-postgres")
+                        || d.get_str("column_type")
+                            .or_else(|| d.get_str("COLUMN_TYPE"))
+                            .is_some_and(|t| t.eq_ignore_ascii_case(column_type));
+                    if is_modifiable {
+                        pool.execute(&sql).await?;
+                        tracing::warn!(
+                            model_name,
+                            table_name,
+                            column_name,
+                            "comment of `{column_name}` has been updated",
+                        );
+                    } else {
+                        tracing::warn!(
+                            model_name,
+                            table_name,
+                            column_name,
+                            column_type,
+                            data_type,
+                            migration_sql = sql,
+                            "comment of `{column_name}` should be updated manually",
+                        );
+                    }
                 }
             } else {
                 let column_definition = col.field_definition(primary_key_name);
@@ -316,18 +606,69 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
                     column_type,
                     "a new column `{column_name}` has been added",
                 );
+                if cfg!(feature = "orm-postgres") {
+                    if let Some(sql) = format_column_comment::<Self>(col) {
+                        pool.execute(&sql).await?;
+                    }
+                }
+            }
+        }
+
+        // Table comment
+        if let Some(comment) = Self::TABLE_COMMENT {
+            let sql = if cfg!(any(
+                feature = "orm-mariadb",
+                feature = "orm-mysql",
+                feature = "orm-tidb"
+            )) {
+                let table_schema = connection_pool.database();
+                Some(format!(
+                    "SELECT table_comment FROM information_schema.tables \
+                        WHERE table_schema = '{table_schema}' AND table_name = '{table_name}';"
+                ))
+            } else if cfg!(feature = "orm-postgres") {
+                Some(format!(
+                    "SELECT obj_description(format('%I.%I', 'public', '{table_name}')::regclass, \
+                        'pg_class') AS table_comment;"
+                ))
+            } else {
+                // SQLite does not support comments.
+                None
+            };
+            if let Some(sql) = sql {
+                let table_comment = match pool.fetch_optional(&sql).await? {
+                    Some(row) => {
+                        let data = Map::decode_row(&row)?;
+                        data.get_str("table_comment")
+                            .or_else(|| data.get_str("TABLE_COMMENT"))
+                            .map(|s| s.to_owned())
+                    }
+                    None => None,
+                };
+                if table_comment.as_deref() != Some(comment) {
+                    pool.execute(&format_table_comment::<Self>(comment)).await?;
+                    tracing::warn!(
+                        model_name,
+                        table_name,
+                        "comment of the table has been updated"
+                    );
+                }
             }
         }
         Ok(())
     }
 
     /// Creates indexes for the model.
+    ///
+    /// The existing indexes are compared with the definitions, and the drift is reported
+    /// instead of re-creating the indexes.
     async fn create_indexes() -> Result<u64, Error> {
-        if !super::AUTO_MIGRATION.load(Relaxed) {
+        if !super::AUTO_MIGRATION.load(Relaxed) || Self::READ_ONLY {
             return Ok(0);
         }
 
         let pool = Self::init_writer()?.pool();
+        let model_name = Self::model_name();
         let columns = Self::columns();
         let mut table_name = Self::table_name();
         if let Some((_, suffix)) = table_name.rsplit_once('.') {
@@ -335,73 +676,121 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         }
 
         let table_name_escaped = Query::table_name_escaped::<Self>();
-        let mut rows = 0;
-        if cfg!(any(
+        let is_mysql = cfg!(any(
             feature = "orm-mariadb",
             feature = "orm-mysql",
             feature = "orm-tidb"
-        )) {
-            let sql = format!("SHOW INDEXES FROM {table_name_escaped}");
-            if pool.fetch(&sql).await?.len() > 1 {
-                return Ok(0);
+        ));
+        let sql = if is_mysql {
+            format!("SHOW INDEXES FROM {table_name_escaped};")
+        } else if cfg!(feature = "orm-postgres") {
+            format!(
+                "SELECT indexname::text AS index_name, indexdef AS definition FROM pg_indexes \
+                    WHERE schemaname = 'public' AND tablename = '{table_name}';"
+            )
+        } else {
+            format!(
+                "SELECT name AS index_name, sql AS definition FROM sqlite_master \
+                    WHERE type = 'index' AND tbl_name = '{table_name}' AND sql IS NOT NULL;"
+            )
+        };
+        let mut definitions = Vec::<(String, String)>::new();
+        for row in pool.fetch(&sql).await? {
+            let data = Map::decode_row(&row)?;
+            if is_mysql {
+                // The definition is represented as the comma-separated columns.
+                let Some(index_name) = data.get_str("Key_name") else {
+                    continue;
+                };
+                // The key part of a functional index has an expression instead of a column.
+                let column_name = data
+                    .get_str("Column_name")
+                    .or_else(|| data.get_str("Expression"))
+                    .unwrap_or_default();
+                if let Some((_, definition)) =
+                    definitions.iter_mut().find(|(name, _)| name == index_name)
+                {
+                    definition.push(',');
+                    definition.push_str(column_name);
+                } else {
+                    let non_unique = data
+                        .get("Non_unique")
+                        .and_then(|v| v.as_u64().or_else(|| v.as_str()?.parse().ok()));
+                    let definition = if non_unique == Some(0) {
+                        format!("UNIQUE {column_name}")
+                    } else {
+                        column_name.to_owned()
+                    };
+                    definitions.push((index_name.to_owned(), definition));
+                }
+            } else if let (Some(index_name), Some(definition)) =
+                (data.get_str("index_name"), data.get_str("definition"))
+            {
+                definitions.push((index_name.to_owned(), definition.to_owned()));
             }
+        }
 
-            let mut text_search_columns = Vec::new();
-            for col in columns {
-                if let Some(index_type) = col.index_type() {
-                    let column_name = col.name();
-                    if matches!(index_type, "fulltext" | "text") {
-                        text_search_columns.push(column_name);
-                    } else if matches!(index_type, "unique" | "spatial") {
-                        let index_type = index_type.to_uppercase();
-                        let sql = format!(
-                            "CREATE {index_type} INDEX {table_name}_{column_name}_index \
-                                ON {table_name_escaped} ({column_name});"
-                        );
-                        rows = pool.execute(&sql).await?.rows_affected().max(rows);
-                    } else if matches!(index_type, "btree" | "hash") {
-                        let index_type = index_type.to_uppercase();
-                        let sql = format!(
-                            "CREATE INDEX {table_name}_{column_name}_index \
-                                ON {table_name_escaped} ({column_name}) USING {index_type};"
-                        );
-                        rows = pool.execute(&sql).await?.rows_affected().max(rows);
-                    }
+        let mut rows = 0;
+        for index in Self::indexes() {
+            let index_name = index.name(table_name);
+            if let Some((_, definition)) = definitions.iter().find(|(name, _)| name == &index_name)
+            {
+                if !index.matches_definition(table_name, definition) {
+                    tracing::warn!(
+                        model_name,
+                        table_name,
+                        index_name,
+                        definition,
+                        "definition of the index `{index_name}` should be altered",
+                    );
                 }
+            } else if let Some(sql) = index.format_create_sql(table_name, &table_name_escaped) {
+                rows = pool.execute(&sql).await?.rows_affected().max(rows);
             }
-            if !text_search_columns.is_empty() {
+        }
+
+        let has_index = |index_name: &str| definitions.iter().any(|(name, _)| name == index_name);
+        if is_mysql {
+            let text_search_columns = columns
+                .iter()
+                .filter(|col| matches!(col.index_type(), Some("fulltext" | "text")))
+                .map(|col| col.name())
+                .collect::<Vec<_>>();
+            let index_name = format!("{table_name}_text_search_index");
+            if !(text_search_columns.is_empty() || has_index(&index_name)) {
                 let text_search_columns = text_search_columns.join(", ");
                 let sql = format!(
-                    "CREATE FULLTEXT INDEX {table_name}_text_search_index \
+                    "CREATE FULLTEXT INDEX {index_name} \
                         ON {table_name_escaped} ({text_search_columns});"
                 );
                 rows = pool.execute(&sql).await?.rows_affected().max(rows);
             }
+
+            let fulltext_columns = Self::fulltext_columns();
+            let index_name = format!("{table_name}_fulltext_index");
+            if !(fulltext_columns.is_empty() || has_index(&index_name)) {
+                let fulltext_columns = fulltext_columns
+                    .iter()
+                    .map(|col| col.name())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let sql = format!(
+                    "CREATE FULLTEXT INDEX {index_name} \
+                        ON {table_name_escaped} ({fulltext_columns});"
+                );
+                rows = pool.execute(&sql).await?.rows_affected().max(rows);
+            }
         } else if cfg!(feature = "orm-postgres") {
             let mut text_search_columns = Vec::new();
             let mut text_search_languages = Vec::new();
             for col in columns {
                 if let Some(index_type) = col.index_type() {
-                    let column_name = col.name();
                     if index_type.starts_with("text") {
+                        let column_name = col.name();
                         let language = index_type.strip_prefix("text:").unwrap_or("english");
                         let column = format!("coalesce({column_name}, '')");
                         text_search_languages.push(language);
                         text_search_columns.push((language, column));
-                    } else if index_type == "unique" {
-                        let sql = format!(
-                            "CREATE UNIQUE INDEX IF NOT EXISTS {table_name}_{column_name}_index \
-                                ON {table_name_escaped} ({column_name});"
-                        );
-                        rows = pool.execute(&sql).await?.rows_affected().max(rows);
-                    } else {
-                        let sort_order = if index_type == "btree" { " DESC" } else { "" };
-                        let sql = format!(
-                            "CREATE INDEX IF NOT EXISTS {table_name}_{column_name}_index \
-                                ON {table_name_escaped} \
-                                    USING {index_type}({column_name}{sort_order});"
-                        );
-                        rows = pool.execute(&sql).await?.rows_affected().max(rows);
                     }
                 }
             }
@@ -418,15 +807,79 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
                 );
                 rows = pool.execute(&sql).await?.rows_affected().max(rows);
             }
+
+            let fulltext_columns = Self::fulltext_columns();
+            if !fulltext_columns.is_empty() {
+                let language = search::search_language::<Self>();
+                let text = fulltext_columns
+                    .iter()
+                    .map(|col| format!("coalesce({}, '')", col.name()))
+                    .collect::<Vec<_>>()
+                    .join(" || ' ' || ");
+                let search_vector = search::SEARCH_VECTOR_COLUMN;
+                let sql = format!(
+                    "ALTER TABLE {table_name_escaped} ADD COLUMN IF NOT EXISTS {search_vector} \
+                        tsvector GENERATED ALWAYS AS (to_tsvector('{language}', {text})) STORED;"
+                );
+                pool.execute(&sql).await?;
+
+                let sql = format!(
+                    "CREATE INDEX IF NOT EXISTS {table_name}_{search_vector}_index \
+                        ON {table_name_escaped} USING gin({search_vector});"
+                );
+                rows = pool.execute(&sql).await?.rows_affected().max(rows);
+            }
         } else {
-            for col in columns {
-                if let Some(index_type) = col.index_type() {
-                    let column_name = col.name();
-                    let index_type = if index_type == "unique" { "UNIQUE" } else { "" };
-                    let sql = format!(
-                        "CREATE {index_type} INDEX IF NOT EXISTS {table_name}_{column_name}_index \
-                            ON {table_name_escaped} ({column_name});"
-                    );
+            let fulltext_columns = Self::fulltext_columns();
+            let fts_table_name = format!("{table_name}_fts");
+            let sql = format!(
+                "SELECT name FROM sqlite_master \
+                    WHERE type = 'table' AND name = '{fts_table_name}';"
+            );
+            if !fulltext_columns.is_empty() && pool.fetch(&sql).await?.is_empty() {
+                let fts_table = search::fts_table_name_escaped::<Self>();
+                let fields = fulltext_columns
+                    .iter()
+                    .map(|col| col.name())
+                    .collect::<Vec<_>>();
+                let columns = fields.join(", ");
+                let new_values = fields
+                    .iter()
+                    .map(|field| format!("new.{field}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let old_values = fields
+                    .iter()
+                    .map(|field| format!("old.{field}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let insert_sql = format!(
+                    "INSERT INTO {fts_table}(rowid, {columns}) VALUES (new.rowid, {new_values});"
+                );
+                let delete_sql = format!(
+                    "INSERT INTO {fts_table}({fts_table}, rowid, {columns}) \
+                        VALUES ('delete', old.rowid, {old_values});"
+                );
+                let statements = [
+                    format!(
+                        "CREATE VIRTUAL TABLE IF NOT EXISTS {fts_table} \
+                            USING fts5({columns}, content='{table_name}', content_rowid='rowid');"
+                    ),
+                    format!(
+                        "CREATE TRIGGER IF NOT EXISTS {fts_table_name}_insert \
+                            AFTER INSERT ON {table_name_escaped} BEGIN {insert_sql} END;"
+                    ),
+                    format!(
+                        "CREATE TRIGGER IF NOT EXISTS {fts_table_name}_delete \
+                            AFTER DELETE ON {table_name_escaped} BEGIN {delete_sql} END;"
+                    ),
+                    format!(
+                        "CREATE TRIGGER IF NOT EXISTS {fts_table_name}_update \
+                            AFTER UPDATE ON {table_name_escaped} BEGIN {delete_sql} {insert_sql} END;"
+                    ),
+                    format!("INSERT INTO {fts_table}({fts_table}) VALUES ('rebuild');"),
+                ];
+                for sql in statements {
                     rows = pool.execute(&sql).await?.rows_affected().max(rows);
                 }
             }
@@ -436,45 +889,30 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
 
     /// Prepares the SQL to insert the model into the table.
     async fn prepare_insert(self) -> Result<QueryContext, Error> {
-        let map = self.into_map();
-        let table_name = Query::table_name_escaped::<Self>();
-        let columns = Self::columns();
-
-        let mut fields = Vec::with_capacity(columns.len());
-        let values = columns
-            .iter()
-            .filter_map(|col| {
-                if col.auto_increment() {
-                    None
-                } else {
-                    let name = col.name();
-                    let field = Query::format_field(name);
-                    fields.push(field);
-                    Some(col.encode_value(map.get(name)))
-                }
-            })
-            .collect::<Vec<_>>()
-            .join(", ");
-        let fields = fields.join(", ");
-        let sql = format!("INSERT INTO {table_name} ({fields}) VALUES ({values});");
-        let mut ctx = Self::before_scan(&sql).await?;
-        ctx.set_query(sql);
-        if cfg!(debug_assertions) && super::DEBUG_ONLY.load(Relaxed) {
-            ctx.cancel();
-        }
-        Ok(ctx)
+        prepare_insert_map::<Self>(self.into_map()).await
     }
 
     /// Inserts the model into the table.
     async fn insert(mut self) -> Result<QueryContext, Error> {
         let model_data = self.before_insert().await?;
+        if sequence::has_sequence_columns::<Self>() {
+            return insert_with_sequences(self, model_data).await;
+        }
+
+        #[cfg(feature = "webhook")]
+        let event_data = serde_json::to_value(&self)?
+            .into_map_opt()
+            .unwrap_or_default();
         let mut ctx = self.prepare_insert().await?;
         if ctx.is_cancelled() {
             return Ok(ctx);
         }
 
-        let pool = Self::acquire_writer().await?.pool();
-        let query_result = pool.execute(ctx.query()).await?;
+        let pool = Self::acquire_writer().await?;
+        let query_result = pool
+            .execute(ctx.query())
+            .await
+            .map_err(resolve_unique_violation::<Self>)?;
         let (last_insert_id, rows_affected) = Query::parse_query_result(query_result);
         let success = rows_affected == 1;
         if let Some(last_insert_id) = last_insert_id {
@@ -484,6 +922,8 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         Self::after_scan(&ctx).await?;
         Self::after_insert(&ctx, model_data).await?;
         if success {
+            #[cfg(feature = "webhook")]
+            super::webhook::emit_model_data_event::<Self>("create", event_data).await;
             Ok(ctx)
         } else {
             bail!(
@@ -495,22 +935,32 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
 
     /// Prepares the SQL to insert many models into the table.
     async fn prepare_insert_many(models: Vec<Self>) -> Result<QueryContext, Error> {
+        check_writable::<Self>()?;
         if models.is_empty() {
             bail!("the list of models to be inserted should be nonempty");
         }
 
         let columns = Self::columns();
+        let num_columns = columns.len();
         let mut values = Vec::with_capacity(models.len());
+        let mut placeholders = Vec::with_capacity(models.len());
+        let mut bind_values = Vec::with_capacity(models.len() * num_columns);
         for mut model in models.into_iter() {
             let _model_data = model.before_insert().await?;
 
             let map = model.into_map();
             let entries = columns
                 .iter()
-                .map(|col| col.encode_value(map.get(col.name())))
+                .map(|col| {
+                    let value = col.encode_value(col.get_value(&map)).into_owned();
+                    bind_values.push((value.clone(), col.is_sensitive()));
+                    value
+                })
                 .collect::<Vec<_>>()
                 .join(", ");
+            let offset = bind_values.len() - num_columns;
             values.push(format!("({entries})"));
+            placeholders.push(format!("({})", format_placeholders(offset, num_columns)));
         }
 
         let table_name = Query::table_name_escaped::<Self>();
@@ -520,9 +970,15 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
             .collect::<Vec<_>>()
             .join(", ");
         let values = values.join(", ");
+        let placeholders = placeholders.join(", ");
         let sql = format!("INSERT INTO {table_name} ({fields}) VALUES {values};");
-        let mut ctx = Self::before_scan(&sql).await?;
+        let statement = format!("INSERT INTO {table_name} ({fields}) VALUES {placeholders};");
+        let mut ctx = Self::before_scan(&statement).await?;
         ctx.set_query(sql);
+        ctx.set_statement(statement);
+        for (value, sensitive) in bind_values {
+            ctx.add_bind_value(value, sensitive);
+        }
         if cfg!(debug_assertions) && super::DEBUG_ONLY.load(Relaxed) {
             ctx.cancel();
         }
@@ -536,13 +992,43 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
             return Ok(ctx);
         }
 
-        let pool = Self::acquire_writer().await?.pool();
-        let query_result = pool.execute(ctx.query()).await?;
+        let pool = Self::acquire_writer().await?;
+        let query_result = pool
+            .execute(ctx.query())
+            .await
+            .map_err(resolve_unique_violation::<Self>)?;
         ctx.set_query_result(query_result.rows_affected(), true);
         Self::after_scan(&ctx).await?;
         Ok(ctx)
     }
 
+    /// Loads the models into the table with the PostgreSQL `COPY` protocol
+    /// and returns the number of rows affected.
+    ///
+    /// It is an order of magnitude faster than [`insert_many()`](Self::insert_many)
+    /// for millions of rows. The rows are streamed in the CSV format,
+    /// and the copy is aborted if any of them fails, in which case the error reports
+    /// the offending line number starting from `1`.
+    /// It is only supported by the `postgres` driver.
+    async fn copy_in<S>(rows: S) -> Result<u64, Error>
+    where
+        S: futures::Stream<Item = Self> + Send,
+    {
+        check_writable::<Self>()?;
+        #[cfg(feature = "orm-postgres")]
+        {
+            super::copy::copy_in::<Self, S>(rows).await
+        }
+        #[cfg(not(feature = "orm-postgres"))]
+        {
+            let _ = rows;
+            bail!(
+                "`COPY` is not supported by the `{}` driver",
+                Self::driver_name()
+            );
+        }
+    }
+
     /// Prepares the SQL to insert models selected by a subquery.
     async fn prepare_insert_from_subquery<C, E>(
         columns: &[C],
@@ -552,6 +1038,7 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         C: AsRef<str>,
         E: Entity + Schema,
     {
+        check_writable::<Self>()?;
         if columns.is_empty() {
             bail!("a list of columns should be nonempty");
         }
@@ -586,7 +1073,7 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
             return Ok(ctx);
         }
 
-        let pool = Self::acquire_writer().await?.pool();
+        let pool = Self::acquire_writer().await?;
         let query_result = pool.execute(ctx.query()).await?;
         ctx.set_query_result(query_result.rows_affected(), true);
         Self::after_scan(&ctx).await?;
@@ -595,6 +1082,7 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
 
     /// Prepares the SQL to update the model in the table.
     async fn prepare_update(self) -> Result<QueryContext, Error> {
+        check_writable::<Self>()?;
         let primary_key_name = Self::PRIMARY_KEY_NAME;
         let table_name = Query::table_name_escaped::<Self>();
         let primary_key = Query::escape_string(self.primary_key());
@@ -602,21 +1090,34 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         let read_only_fields = Self::read_only_fields();
         let num_writable_fields = Self::fields().len() - read_only_fields.len();
         let mut mutations = Vec::with_capacity(num_writable_fields);
+        let mut placeholders = Vec::with_capacity(num_writable_fields);
+        let mut bind_values = Vec::with_capacity(num_writable_fields);
         for col in Self::columns() {
             let field = col.name();
             if !read_only_fields.contains(&field) {
-                let value = col.encode_value(map.get(field));
+                let value = col.encode_value(col.get_value(&map));
                 let field = Query::format_field(field);
+                let placeholder = Query::placeholder(bind_values.len() + 1);
                 mutations.push(format!("{field} = {value}"));
+                placeholders.push(format!("{field} = {placeholder}"));
+                bind_values.push((value, col.is_sensitive()));
             }
         }
 
         let mutations = mutations.join(", ");
+        let placeholders = placeholders.join(", ");
         let sql = format!(
             "UPDATE {table_name} SET {mutations} WHERE {primary_key_name} = {primary_key};"
         );
-        let mut ctx = Self::before_scan(&sql).await?;
+        let statement = format!(
+            "UPDATE {table_name} SET {placeholders} WHERE {primary_key_name} = {primary_key};"
+        );
+        let mut ctx = Self::before_scan(&statement).await?;
         ctx.set_query(sql);
+        ctx.set_statement(statement);
+        for (value, sensitive) in bind_values {
+            ctx.add_bind_value(value, sensitive);
+        }
         if cfg!(debug_assertions) && super::DEBUG_ONLY.load(Relaxed) {
             ctx.cancel();
         }
@@ -631,7 +1132,7 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
             return Ok(ctx);
         }
 
-        let pool = Self::acquire_writer().await?.pool();
+        let pool = Self::acquire_writer().await?;
         let query_result = pool.execute(ctx.query()).await?;
         let rows_affected = query_result.rows_affected();
         let success = rows_affected == 1;
@@ -653,29 +1154,43 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         self,
         columns: &[C],
     ) -> Result<QueryContext, Error> {
+        check_writable::<Self>()?;
         let primary_key_name = Self::PRIMARY_KEY_NAME;
         let table_name = Query::table_name_escaped::<Self>();
         let primary_key = Query::escape_string(self.primary_key());
         let map = self.into_map();
         let read_only_fields = Self::read_only_fields();
         let mut mutations = Vec::with_capacity(columns.len());
+        let mut placeholders = Vec::with_capacity(columns.len());
+        let mut bind_values = Vec::with_capacity(columns.len());
         for col in columns {
             let field = col.as_ref();
             if !read_only_fields.contains(&field) {
                 if let Some(col) = Self::columns().iter().find(|col| col.name() == field) {
-                    let value = col.encode_value(map.get(field));
+                    let value = col.encode_value(col.get_value(&map));
                     let field = Query::format_field(field);
+                    let placeholder = Query::placeholder(bind_values.len() + 1);
                     mutations.push(format!("{field} = {value}"));
+                    placeholders.push(format!("{field} = {placeholder}"));
+                    bind_values.push((value, col.is_sensitive()));
                 }
             }
         }
 
         let mutations = mutations.join(", ");
+        let placeholders = placeholders.join(", ");
         let sql = format!(
             "UPDATE {table_name} SET {mutations} WHERE {primary_key_name} = {primary_key};"
         );
-        let mut ctx = Self::before_scan(&sql).await?;
+        let statement = format!(
+            "UPDATE {table_name} SET {placeholders} WHERE {primary_key_name} = {primary_key};"
+        );
+        let mut ctx = Self::before_scan(&statement).await?;
         ctx.set_query(sql);
+        ctx.set_statement(statement);
+        for (value, sensitive) in bind_values {
+            ctx.add_bind_value(value, sensitive);
+        }
         if cfg!(debug_assertions) && super::DEBUG_ONLY.load(Relaxed) {
             ctx.cancel();
         }
@@ -690,7 +1205,7 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
             return Ok(ctx);
         }
 
-        let pool = Self::acquire_writer().await?.pool();
+        let pool = Self::acquire_writer().await?;
         let query_result = pool.execute(ctx.query()).await?;
         let rows_affected = query_result.rows_affected();
         let success = rows_affected == 1;
@@ -712,11 +1227,15 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         query: &Query,
         mutation: &mut Mutation,
     ) -> Result<QueryContext, Error> {
+        check_writable::<Self>()?;
         Self::before_mutation(query, mutation).await?;
 
+        let guarded_query = mutation.guarded_query(query);
+        let query = guarded_query.as_ref().unwrap_or(query);
         let primary_key_name = Self::PRIMARY_KEY_NAME;
         let table_name = query.format_table_name::<Self>();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let updates = mutation.format_updates::<Self>();
         let sql = if cfg!(any(
             feature = "orm-mariadb",
@@ -739,6 +1258,7 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         };
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql);
+        ctx.append_argument_values(&mut arguments);
         if cfg!(debug_assertions) && super::DEBUG_ONLY.load(Relaxed) {
             ctx.cancel();
         }
@@ -752,11 +1272,22 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
             return Ok(ctx);
         }
 
-        let pool = Self::acquire_writer().await?.pool();
-        let query_result = pool.execute(ctx.query()).await?;
+        #[cfg(feature = "sync")]
+        let deleted_ids = super::sync::select_deleted_ids::<Self>(query).await?;
+
+        let pool = Self::acquire_writer().await?;
+        let query_result = with_statement_timeout(
+            query.timeout(),
+            pool.execute_with_values(ctx.query(), ctx.argument_values()),
+        )
+        .await?;
         let rows_affected = query_result.rows_affected();
         let success = rows_affected <= 1;
         ctx.set_query_result(rows_affected, success);
+        #[cfg(feature = "sync")]
+        if success {
+            super::sync::record_tombstones(Self::model_name(), &deleted_ids).await;
+        }
         Self::after_scan(&ctx).await?;
         Self::after_mutation(&ctx).await?;
         if success {
@@ -774,14 +1305,19 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         query: &Query,
         mutation: &mut Mutation,
     ) -> Result<QueryContext, Error> {
+        check_writable::<Self>()?;
         Self::before_mutation(query, mutation).await?;
 
+        let guarded_query = mutation.guarded_query(query);
+        let query = guarded_query.as_ref().unwrap_or(query);
         let table_name = query.format_table_name::<Self>();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let updates = mutation.format_updates::<Self>();
         let sql = format!("UPDATE {table_name} SET {updates} {filters};");
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql);
+        ctx.append_argument_values(&mut arguments);
         if cfg!(debug_assertions) && super::DEBUG_ONLY.load(Relaxed) {
             ctx.cancel();
         }
@@ -795,8 +1331,12 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
             return Ok(ctx);
         }
 
-        let pool = Self::acquire_writer().await?.pool();
-        let query_result = pool.execute(ctx.query()).await?;
+        let pool = Self::acquire_writer().await?;
+        let query_result = with_statement_timeout(
+            query.timeout(),
+            pool.execute_with_values(ctx.query(), ctx.argument_values()),
+        )
+        .await?;
         ctx.set_query_result(query_result.rows_affected(), true);
         Self::after_scan(&ctx).await?;
         Self::after_mutation(&ctx).await?;
@@ -805,6 +1345,7 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
 
     /// Prepares the SQL to update or insert the model into the table.
     async fn prepare_upsert(self) -> Result<QueryContext, Error> {
+        check_writable::<Self>()?;
         let map = self.into_map();
         let table_name = Query::table_name_escaped::<Self>();
         let num_fields = Self::fields().len();
@@ -813,40 +1354,61 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         let mut fields = Vec::with_capacity(num_fields);
         let mut values = Vec::with_capacity(num_fields);
         let mut mutations = Vec::with_capacity(num_writable_fields);
+        let mut placeholders = Vec::with_capacity(num_writable_fields);
+        let mut bind_values = Vec::with_capacity(num_fields);
         for col in Self::columns() {
             let name = col.name();
             let field = Query::format_field(name);
-            let value = col.encode_value(map.get(name));
+            let value = col.encode_value(col.get_value(&map));
             if !read_only_fields.contains(&name) {
+                let placeholder = Query::placeholder(bind_values.len() + 1);
                 mutations.push(format!("{field} = {value}"));
+                placeholders.push(format!("{field} = {placeholder}"));
             }
             fields.push(field);
-            values.push(value);
+            values.push(value.clone());
+            bind_values.push((value, col.is_sensitive()));
         }
 
         let fields = fields.join(", ");
         let values = values.join(", ");
         let mutations = mutations.join(", ");
-        let sql = if cfg!(any(
+        let placeholders = placeholders.join(", ");
+        let value_placeholders = format_placeholders(0, bind_values.len());
+        let (sql, statement) = if cfg!(any(
             feature = "orm-mariadb",
             feature = "orm-mysql",
             feature = "orm-tidb"
         )) {
-            format!(
+            let sql = format!(
                 "INSERT INTO {table_name} ({fields}) VALUES ({values}) \
                     ON DUPLICATE KEY UPDATE {mutations};"
-            )
+            );
+            let statement = format!(
+                "INSERT INTO {table_name} ({fields}) VALUES ({value_placeholders}) \
+                    ON DUPLICATE KEY UPDATE {placeholders};"
+            );
+            (sql, statement)
         } else {
             let primary_key_name = Self::PRIMARY_KEY_NAME;
 
             // Both PostgreQL and SQLite (3.24+) support this syntax.
-            format!(
+            let sql = format!(
                 "INSERT INTO {table_name} ({fields}) VALUES ({values}) \
                     ON CONFLICT ({primary_key_name}) DO UPDATE SET {mutations};"
-            )
+            );
+            let statement = format!(
+                "INSERT INTO {table_name} ({fields}) VALUES ({value_placeholders}) \
+                    ON CONFLICT ({primary_key_name}) DO UPDATE SET {placeholders};"
+            );
+            (sql, statement)
         };
-        let mut ctx = Self::before_scan(&sql).await?;
+        let mut ctx = Self::before_scan(&statement).await?;
         ctx.set_query(sql);
+        ctx.set_statement(statement);
+        for (value, sensitive) in bind_values {
+            ctx.add_bind_value(value, sensitive);
+        }
         if cfg!(debug_assertions) && super::DEBUG_ONLY.load(Relaxed) {
             ctx.cancel();
         }
@@ -861,8 +1423,11 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
             return Ok(ctx);
         }
 
-        let pool = Self::acquire_writer().await?.pool();
-        let query_result = pool.execute(ctx.query()).await?;
+        let pool = Self::acquire_writer().await?;
+        let query_result = pool
+            .execute(ctx.query())
+            .await
+            .map_err(resolve_unique_violation::<Self>)?;
         let (last_insert_id, rows_affected) = Query::parse_query_result(query_result);
         let success = rows_affected == 1;
         if let Some(last_insert_id) = last_insert_id {
@@ -883,6 +1448,7 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
 
     /// Prepares the SQL to delete the model in the table.
     async fn prepare_delete() -> Result<QueryContext, Error> {
+        check_writable::<Self>()?;
         let primary_key_name = Self::PRIMARY_KEY_NAME;
         let table_name = Query::table_name_escaped::<Self>();
         let placeholder = Query::placeholder(1);
@@ -911,7 +1477,7 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
             return Ok(ctx);
         }
 
-        let pool = Self::acquire_writer().await?.pool();
+        let pool = Self::acquire_writer().await?;
         let primary_key = self.primary_key();
         let query_result = pool.execute_with(ctx.query(), &[primary_key]).await?;
         let rows_affected = query_result.rows_affected();
@@ -932,11 +1498,13 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
 
     /// Prepares the SQL to delete at most one model selected by the query in the table.
     async fn prepare_delete_one(query: &Query) -> Result<QueryContext, Error> {
+        check_writable::<Self>()?;
         Self::before_query(query).await?;
 
         let primary_key_name = Self::PRIMARY_KEY_NAME;
         let table_name = query.format_table_name::<Self>();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let sort = query.format_sort();
         let sql = format!(
             "DELETE FROM {table_name} WHERE {primary_key_name} IN \
@@ -944,6 +1512,7 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         );
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql);
+        ctx.append_argument_values(&mut arguments);
         if cfg!(debug_assertions) && super::DEBUG_ONLY.load(Relaxed) {
             ctx.cancel();
         }
@@ -957,8 +1526,12 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
             return Ok(ctx);
         }
 
-        let pool = Self::acquire_writer().await?.pool();
-        let query_result = pool.execute(ctx.query()).await?;
+        let pool = Self::acquire_writer().await?;
+        let query_result = with_statement_timeout(
+            query.timeout(),
+            pool.execute_with_values(ctx.query(), ctx.argument_values()),
+        )
+        .await?;
         let rows_affected = query_result.rows_affected();
         let success = rows_affected <= 1;
         ctx.set_query_result(rows_affected, success);
@@ -976,19 +1549,66 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
 
     /// Prepares the SQL to delete many models selected by the query in the table.
     async fn prepare_delete_many(query: &Query) -> Result<QueryContext, Error> {
+        check_writable::<Self>()?;
         Self::before_query(query).await?;
 
         let table_name = query.format_table_name::<Self>();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let sql = format!("DELETE FROM {table_name} {filters};");
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql);
+        ctx.append_argument_values(&mut arguments);
         if cfg!(debug_assertions) && super::DEBUG_ONLY.load(Relaxed) {
             ctx.cancel();
         }
         Ok(ctx)
     }
 
+    /// Prepares the SQL to delete a batch of expired models in the table.
+    /// The number of rows affected is bounded by the `batch_size`.
+    fn prepare_delete_expired(batch_size: usize) -> Result<String, Error> {
+        check_writable::<Self>()?;
+        let Some(col) = Self::expiry_column() else {
+            bail!(
+                "the model `{}` does not have an expiry column",
+                Self::model_name()
+            );
+        };
+
+        let table_name = Query::table_name_escaped::<Self>();
+        let column_name = col.name();
+        let now = col.format_value("now");
+        let sql = if cfg!(any(
+            feature = "orm-mariadb",
+            feature = "orm-mysql",
+            feature = "orm-tidb"
+        )) {
+            format!(
+                "DELETE FROM {table_name} WHERE {column_name} <= {now} \
+                    ORDER BY {column_name} LIMIT {batch_size};"
+            )
+        } else {
+            let primary_key_name = Self::PRIMARY_KEY_NAME;
+            format!(
+                "DELETE FROM {table_name} WHERE {primary_key_name} IN \
+                    (SELECT {primary_key_name} FROM {table_name} \
+                        WHERE {column_name} <= {now} \
+                        ORDER BY {column_name} LIMIT {batch_size});"
+            )
+        };
+        Ok(sql)
+    }
+
+    /// Deletes a batch of expired models in the table,
+    /// returning the number of rows affected.
+    async fn delete_expired(batch_size: usize) -> Result<u64, Error> {
+        let sql = Self::prepare_delete_expired(batch_size)?;
+        let pool = Self::acquire_writer().await?;
+        let query_result = pool.execute(&sql).await?;
+        Ok(query_result.rows_affected())
+    }
+
     /// Deletes many models selected by the query in the table.
     async fn delete_many(query: &Query) -> Result<QueryContext, Error> {
         let mut ctx = Self::prepare_delete_many(query).await?;
@@ -996,9 +1616,18 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
             return Ok(ctx);
         }
 
-        let pool = Self::acquire_writer().await?.pool();
-        let query_result = pool.execute(ctx.query()).await?;
+        #[cfg(feature = "sync")]
+        let deleted_ids = super::sync::select_deleted_ids::<Self>(query).await?;
+
+        let pool = Self::acquire_writer().await?;
+        let query_result = with_statement_timeout(
+            query.timeout(),
+            pool.execute_with_values(ctx.query(), ctx.argument_values()),
+        )
+        .await?;
         ctx.set_query_result(query_result.rows_affected(), true);
+        #[cfg(feature = "sync")]
+        super::sync::record_tombstones(Self::model_name(), &deleted_ids).await;
         Self::after_scan(&ctx).await?;
         Self::after_query(&ctx).await?;
         Ok(ctx)
@@ -1013,6 +1642,7 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         C: AsRef<str>,
         E: Entity + Schema,
     {
+        check_writable::<Self>()?;
         if columns.is_empty() {
             bail!("a list of columns should be nonempty");
         }
@@ -1047,7 +1677,7 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
             return Ok(ctx);
         }
 
-        let pool = Self::acquire_writer().await?.pool();
+        let pool = Self::acquire_writer().await?;
         let query_result = pool.execute(ctx.query()).await?;
         ctx.set_query_result(query_result.rows_affected(), true);
         Self::after_scan(&ctx).await?;
@@ -1065,15 +1695,26 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
 
         let table_name = query.format_table_name::<Self>();
         let projection = query.format_table_fields::<Self>();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let sort = query.format_sort();
         let pagination = query.format_pagination();
         let sql = format!("SELECT {projection} FROM {table_name} {filters} {sort} {pagination};");
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(&sql);
-
-        let pool = Self::acquire_reader().await?.pool();
-        let rows = pool.fetch(ctx.query()).await?;
+        ctx.append_argument_values(&mut arguments);
+
+        let pool = Self::acquire_reader().await?;
+        let (rows, truncated) = with_query_max_rows::<Self, _, _>(
+            query,
+            with_statement_timeout(
+                query.timeout(),
+                pool.fetch_with_values(ctx.query(), ctx.argument_values()),
+            ),
+        )
+        .await;
+        let rows = rows?;
+        ctx.set_truncated(truncated);
         let mut data = Vec::with_capacity(rows.len());
         for row in rows {
             data.push(T::decode_row(&row)?);
@@ -1096,6 +1737,26 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         serde_json::from_value(data.into()).map_err(Error::from)
     }
 
+    /// Iterates over the models selected by the query in batches of `batch_size`
+    /// with the keyset pagination in the order of the primary key,
+    /// and invokes the callback with each batch of the decoded models.
+    /// Returns the total number of models processed.
+    ///
+    /// See [`KeysetIterator`](crate::KeysetIterator) for resuming the iteration
+    /// from a checkpoint and throttling the batches.
+    async fn iterate<T, F, Fut>(query: &Query, batch_size: usize, mut f: F) -> Result<u64, Error>
+    where
+        T: DecodeRow<DatabaseRow, Error = Error>,
+        F: FnMut(Vec<T>) -> Fut,
+        Fut: Future<Output = Result<(), Error>>,
+    {
+        let mut iterator = KeysetIterator::<Self>::new(query.clone()).batch_size(batch_size);
+        while let Some(data) = iterator.next_batch::<T>().await? {
+            f(data).await?;
+        }
+        Ok(iterator.total_rows())
+    }
+
     /// Finds one model selected by the query in the table,
     /// and decodes it as an instance of type `T`.
     async fn find_one<T>(query: &Query) -> Result<Option<T>, Error>
@@ -1106,14 +1767,21 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
 
         let table_name = query.format_table_name::<Self>();
         let projection = query.format_table_fields::<Self>();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let sort = query.format_sort();
         let sql = format!("SELECT {projection} FROM {table_name} {filters} {sort} LIMIT 1;");
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql);
-
-        let pool = Self::acquire_reader().await?.pool();
-        let (num_rows, data) = if let Some(row) = pool.fetch_optional(ctx.query()).await? {
+        ctx.append_argument_values(&mut arguments);
+
+        let pool = Self::acquire_reader().await?;
+        let optional_row = with_statement_timeout(
+            query.timeout(),
+            pool.fetch_optional_with_values(ctx.query(), ctx.argument_values()),
+        )
+        .await?;
+        let (num_rows, data) = if let Some(row) = optional_row {
             (1, Some(T::decode_row(&row)?))
         } else {
             (0, None)
@@ -1176,13 +1844,17 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
 
         let table_name = query.format_table_name::<Self>();
         let projection = query.format_table_fields::<Self>();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let sql = format!("SELECT {projection} FROM {table_name} {filters};");
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(&sql);
+        ctx.append_argument_values(&mut arguments);
 
-        let pool = Self::acquire_reader().await?.pool();
-        let rows = pool.fetch(ctx.query()).await?;
+        let pool = Self::acquire_reader().await?;
+        let rows = pool
+            .fetch_with_values(ctx.query(), ctx.argument_values())
+            .await?;
         let translate_enabled = query.translate_enabled();
         let mut associations = Vec::with_capacity(num_values);
         for row in rows {
@@ -1268,13 +1940,17 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
 
         let table_name = query.format_table_name::<Self>();
         let projection = query.format_projection();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let sql = format!("SELECT {projection} FROM {table_name} {filters};");
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(&sql);
+        ctx.append_argument_values(&mut arguments);
 
-        let pool = Self::acquire_reader().await?.pool();
-        let rows = pool.fetch(ctx.query()).await?;
+        let pool = Self::acquire_reader().await?;
+        let rows = pool
+            .fetch_with_values(ctx.query(), ctx.argument_values())
+            .await?;
         let translate_enabled = query.translate_enabled();
         let mut associations = Vec::with_capacity(num_values);
         for row in rows {
@@ -1329,11 +2005,13 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         T: DecodeRow<DatabaseRow, Error = Error>,
     {
         Self::before_query(query).await?;
+        join_on.validate_sort_order(query)?;
 
         let table_name = query.format_table_name::<Self>();
         let other_table_name = query.format_table_name::<M>();
         let projection = query.format_table_fields::<Self>();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let sort = query.format_sort();
         let pagination = query.format_pagination();
         let join_type = join_on.join_type().as_str();
@@ -1345,9 +2023,19 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         );
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(&sql);
-
-        let pool = Self::acquire_reader().await?.pool();
-        let rows = pool.fetch(ctx.query()).await?;
+        ctx.append_argument_values(&mut arguments);
+
+        let pool = Self::acquire_reader().await?;
+        let (rows, truncated) = with_query_max_rows::<Self, _, _>(
+            query,
+            with_statement_timeout(
+                query.timeout(),
+                pool.fetch_with_values(ctx.query(), ctx.argument_values()),
+            ),
+        )
+        .await;
+        let rows = rows?;
+        ctx.set_truncated(truncated);
         let mut data = Vec::with_capacity(rows.len());
         for row in rows {
             data.push(T::decode_row(&row)?);
@@ -1379,13 +2067,19 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         Self::before_query(query).await?;
 
         let table_name = query.format_table_name::<Self>();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let sql = format!("SELECT 1 FROM {table_name} {filters} LIMIT 1;");
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql);
-
-        let pool = Self::acquire_reader().await?.pool();
-        let optional_row = pool.fetch_optional(ctx.query()).await?;
+        ctx.append_argument_values(&mut arguments);
+
+        let pool = Self::acquire_reader().await?;
+        let optional_row = with_statement_timeout(
+            query.timeout(),
+            pool.fetch_optional_with_values(ctx.query(), ctx.argument_values()),
+        )
+        .await?;
         let num_rows = if optional_row.is_some() { 1 } else { 0 };
         ctx.set_query_result(num_rows, true);
         Self::after_scan(&ctx).await?;
@@ -1398,13 +2092,19 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         Self::before_count(query).await?;
 
         let table_name = query.format_table_name::<Self>();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let sql = format!("SELECT count(*) AS count FROM {table_name} {filters};");
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql);
-
-        let pool = Self::acquire_reader().await?.pool();
-        let row = pool.fetch_one(ctx.query()).await?;
+        ctx.append_argument_values(&mut arguments);
+
+        let pool = Self::acquire_reader().await?;
+        let row = with_statement_timeout(
+            query.timeout(),
+            pool.fetch_one_with_values(ctx.query(), ctx.argument_values()),
+        )
+        .await?;
         let map = Map::decode_row(&row)?;
 
         // SQLite may return a string value for the count value.
@@ -1415,6 +2115,48 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         Ok(count)
     }
 
+    /// Returns a fingerprint of the rows selected by the query in the table,
+    /// which consists of the max value of `updated_at` and the number of rows.
+    /// It is used to detect the changes of the query result cheaply.
+    /// The limit and offset of the query are ignored.
+    ///
+    /// It returns `None` if the model does not have an `updated_at` column.
+    async fn fingerprint(query: &Query) -> Result<Option<String>, Error> {
+        if !Self::has_column("updated_at") {
+            return Ok(None);
+        }
+        Self::before_count(query).await?;
+
+        let table_name = query.format_table_name::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
+        let field = Query::format_field("updated_at");
+        let projection = format!("max({field}) AS max_updated_at, count(*) AS count");
+        let sql = format!("SELECT {projection} FROM {table_name} {filters};");
+        let mut ctx = Self::before_scan(&sql).await?;
+        ctx.set_query(sql);
+        ctx.append_argument_values(&mut arguments);
+
+        let pool = Self::acquire_reader().await?;
+        let row = with_statement_timeout(
+            query.timeout(),
+            pool.fetch_one_with_values(ctx.query(), ctx.argument_values()),
+        )
+        .await?;
+        let map = Map::decode_row(&row)?;
+
+        // SQLite may return a string value for the count value.
+        let count = map.parse_u64("count").transpose()?.unwrap_or_default();
+        let max_updated_at = map
+            .get("max_updated_at")
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        ctx.set_query_result(1, true);
+        Self::after_scan(&ctx).await?;
+        Self::after_count(&ctx).await?;
+        Ok(Some(format!("{max_updated_at}:{count}")))
+    }
+
     /// Counts the number of rows selected by the query in the table.
     /// The boolean value determines whether it only counts distinct values or not.
     async fn count_many<C, T>(query: &Query, columns: &[(C, bool)]) -> Result<T, Error>
@@ -1425,7 +2167,8 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         Self::before_count(query).await?;
 
         let table_name = query.format_table_name::<Self>();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let projection = columns
             .iter()
             .map(|(col, distinct)| {
@@ -1446,9 +2189,14 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         let sql = format!("SELECT {projection} FROM {table_name} {filters};");
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql);
-
-        let pool = Self::acquire_reader().await?.pool();
-        let row = pool.fetch_one(ctx.query()).await?;
+        ctx.append_argument_values(&mut arguments);
+
+        let pool = Self::acquire_reader().await?;
+        let row = with_statement_timeout(
+            query.timeout(),
+            pool.fetch_one_with_values(ctx.query(), ctx.argument_values()),
+        )
+        .await?;
         ctx.set_query_result(1, true);
         Self::after_scan(&ctx).await?;
         Self::after_count(&ctx).await?;
@@ -1466,24 +2214,116 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         serde_json::from_value(map.into()).map_err(Error::from)
     }
 
+    /// Counts the number of rows selected by the query in the table for each group.
+    ///
+    /// A group column can be suffixed with a [`DateTrunc`] unit such as `created_at:day`
+    /// to group rows by the truncated value of a datetime column.
+    /// Each entry contains the group columns and the `count` field.
+    /// The number of groups is only limited by an explicit limit of the query.
+    async fn count_grouped<C, T>(query: &Query, group_columns: &[C]) -> Result<Vec<T>, Error>
+    where
+        C: AsRef<str>,
+        T: DecodeRow<DatabaseRow, Error = Error>,
+    {
+        if group_columns.is_empty() {
+            bail!("group columns should be nonempty");
+        }
+        Self::before_count(query).await?;
+
+        let mut projection = Vec::with_capacity(group_columns.len() + 1);
+        for group_column in group_columns {
+            let group_column = group_column.as_ref();
+            let (col_name, unit) = match group_column.split_once(':') {
+                Some((col_name, unit)) => (col_name, Some(unit)),
+                None => (group_column, None),
+            };
+            let Some(col) = Self::get_column(col_name) else {
+                bail!("column `{}` does not exist", col_name);
+            };
+            let field = Query::format_field(col_name);
+            let expr = if let Some(unit) = unit {
+                let Some(date_trunc) = DateTrunc::parse(unit) else {
+                    bail!("unit `{}` of the date truncation is unsupported", unit);
+                };
+                if !col.is_datetime_type() {
+                    bail!("column `{}` should be a datetime type", col_name);
+                }
+                date_trunc.expr(&field)
+            } else {
+                field.into_owned()
+            };
+            projection.push(format!("{expr} AS {col_name}"));
+        }
+        projection.push("count(*) AS count".to_owned());
+
+        let table_name = query.format_table_name::<Self>();
+        let projection = projection.join(", ");
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
+        let positions = (1..=group_columns.len())
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let pagination = query.format_pagination();
+        let sql = format!(
+            "SELECT {projection} FROM {table_name} {filters} \
+                GROUP BY {positions} ORDER BY {positions} {pagination};"
+        );
+        let mut ctx = Self::before_scan(&sql).await?;
+        ctx.set_query(sql);
+        ctx.append_argument_values(&mut arguments);
+
+        let pool = Self::acquire_reader().await?;
+        let (rows, truncated) = with_query_max_rows::<Self, _, _>(
+            query,
+            with_statement_timeout(
+                query.timeout(),
+                pool.fetch_with_values(ctx.query(), ctx.argument_values()),
+            ),
+        )
+        .await;
+        let rows = rows?;
+        ctx.set_truncated(truncated);
+        let mut data = Vec::with_capacity(rows.len());
+        for row in rows {
+            data.push(T::decode_row(&row)?);
+        }
+        ctx.set_query_result(u64::try_from(data.len())?, true);
+        Self::after_scan(&ctx).await?;
+        Self::after_count(&ctx).await?;
+        Ok(data)
+    }
+
     /// Aggregates the rows selected by the query in the table.
     async fn aggregate<T>(query: &Query) -> Result<Vec<T>, Error>
     where
         T: DecodeRow<DatabaseRow, Error = Error>,
     {
         Self::before_aggregate(query).await?;
+        money::check_aggregation::<Self>(query)?;
 
         let table_name = query.format_table_name::<Self>();
         let projection = query.format_table_fields::<Self>();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let sort = query.format_sort();
         let pagination = query.format_pagination();
         let sql = format!("SELECT {projection} FROM {table_name} {filters} {sort} {pagination};");
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql);
-
-        let pool = Self::acquire_reader().await?.pool();
-        let rows = pool.fetch(ctx.query()).await?;
+        ctx.append_argument_values(&mut arguments);
+
+        let pool = Self::acquire_reader().await?;
+        let (rows, truncated) = with_query_max_rows::<Self, _, _>(
+            query,
+            with_statement_timeout(
+                query.timeout(),
+                pool.fetch_with_values(ctx.query(), ctx.argument_values()),
+            ),
+        )
+        .await;
+        let rows = rows?;
+        ctx.set_truncated(truncated);
         let mut data = Vec::with_capacity(rows.len());
         for row in rows {
             data.push(T::decode_row(&row)?);
@@ -1513,13 +2353,10 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
             return Ok(ctx);
         }
 
-        let mut arguments = values
-            .iter()
-            .map(|v| v.to_string_unquoted())
-            .collect::<Vec<_>>();
-        let pool = Self::acquire_writer().await?.pool();
-        let query_result = pool.execute_with(ctx.query(), &arguments).await?;
-        ctx.append_arguments(&mut arguments);
+        let mut arguments = values.iter().map(|&v| v.clone()).collect::<Vec<_>>();
+        let pool = Self::acquire_writer().await?;
+        let query_result = pool.execute_with_values(ctx.query(), &arguments).await?;
+        ctx.append_argument_values(&mut arguments);
         ctx.set_query_result(query_result.rows_affected(), true);
         Self::after_scan(&ctx).await?;
         Ok(ctx)
@@ -1534,17 +2371,14 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql);
 
-        let mut arguments = values
-            .iter()
-            .map(|v| v.to_string_unquoted())
-            .collect::<Vec<_>>();
-        let pool = Self::acquire_reader().await?.pool();
-        let rows = pool.fetch_with(ctx.query(), &arguments).await?;
+        let mut arguments = values.iter().map(|&v| v.clone()).collect::<Vec<_>>();
+        let pool = Self::acquire_reader().await?;
+        let rows = pool.fetch_with_values(ctx.query(), &arguments).await?;
         let mut data = Vec::with_capacity(rows.len());
         for row in rows {
             data.push(T::decode_row(&row)?);
         }
-        ctx.append_arguments(&mut arguments);
+        ctx.append_argument_values(&mut arguments);
         ctx.set_query_result(u64::try_from(data.len())?, true);
         Self::after_scan(&ctx).await?;
         Ok(data)
@@ -1571,18 +2405,17 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql);
 
-        let mut arguments = values
-            .iter()
-            .map(|v| v.to_string_unquoted())
-            .collect::<Vec<_>>();
-        let pool = Self::acquire_reader().await?.pool();
-        let optional_row = pool.fetch_optional_with(ctx.query(), &arguments).await?;
+        let mut arguments = values.iter().map(|&v| v.clone()).collect::<Vec<_>>();
+        let pool = Self::acquire_reader().await?;
+        let optional_row = pool
+            .fetch_optional_with_values(ctx.query(), &arguments)
+            .await?;
         let (num_rows, data) = if let Some(row) = optional_row {
             (1, Some(T::decode_row(&row)?))
         } else {
             (0, None)
         };
-        ctx.append_arguments(&mut arguments);
+        ctx.append_argument_values(&mut arguments);
         ctx.set_query_result(num_rows, true);
         Self::after_scan(&ctx).await?;
         Ok(data)
@@ -1604,6 +2437,7 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
 
     /// Prepares the SQL to delete a model selected by the primary key in the table.
     async fn prepare_delete_by_id() -> Result<QueryContext, Error> {
+        check_writable::<Self>()?;
         let primary_key_name = Self::PRIMARY_KEY_NAME;
         let table_name = Query::table_name_escaped::<Self>();
         let placeholder = Query::placeholder(1);
@@ -1631,7 +2465,7 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
             return Ok(ctx);
         }
 
-        let pool = Self::acquire_writer().await?.pool();
+        let pool = Self::acquire_writer().await?;
         let query_result = pool.execute_with(ctx.query(), &[primary_key]).await?;
         let rows_affected = query_result.rows_affected();
         let success = rows_affected == 1;
@@ -1650,6 +2484,7 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
 
     /// Prepares the SQL to update a model selected by the primary key in the table.
     async fn prepare_update_by_id(mutation: &mut Mutation) -> Result<QueryContext, Error> {
+        check_writable::<Self>()?;
         let primary_key_name = Self::PRIMARY_KEY_NAME;
         let table_name = Query::table_name_escaped::<Self>();
         let updates = mutation.format_updates::<Self>();
@@ -1759,7 +2594,7 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql);
 
-        let pool = Self::acquire_reader().await?.pool();
+        let pool = Self::acquire_reader().await?;
         let optional_row = pool
             .fetch_optional_with(ctx.query(), &[primary_key])
             .await?;
@@ -1797,7 +2632,7 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         ctx.set_query(sql);
         ctx.add_argument(primary_key);
 
-        let pool = Self::acquire_reader().await?.pool();
+        let pool = Self::acquire_reader().await?;
         let optional_row = pool
             .fetch_optional_with(ctx.query(), &[primary_key])
             .await?;
@@ -1916,3 +2751,416 @@ pub trait Schema: 'static + Send + Sync + ModelHooks {
         }
     }
 }
+
+/// Checks whether the `citext` extension is installed if it is required by the columns.
+/// It is only applicable to PostgreSQL.
+async fn check_citext_extension(
+    connection_pool: &ConnectionPool,
+    table_name: &str,
+    columns: &[Column<'_>],
+) -> Result<(), Error> {
+    if !cfg!(feature = "orm-postgres") || !columns.iter().any(|col| col.column_type() == "CITEXT") {
+        return Ok(());
+    }
+
+    let sql = "SELECT extname FROM pg_extension WHERE extname = 'citext';";
+    let rows = connection_pool.pool().fetch(sql).await?;
+    if rows.is_empty() {
+        bail!(
+            "the `citext` extension is required by the table `{}`, \
+                run `CREATE EXTENSION IF NOT EXISTS citext;` in the database `{}` first",
+            table_name,
+            connection_pool.database(),
+        );
+    }
+    Ok(())
+}
+
+/// Checks the PostgreSQL enum types declared by `#[schema(pg_enum = "name")]`.
+///
+/// A missing type is created from the `enum_values` if `create-enum-types` is enabled,
+/// and the new variants are added to an existing type. The labels in the database
+/// which are missing in the model are reported instead of being removed.
+async fn check_enum_types(
+    connection_pool: &ConnectionPool,
+    table_name: &str,
+    columns: &[Column<'_>],
+) -> Result<(), Error> {
+    if !cfg!(feature = "orm-postgres") {
+        return Ok(());
+    }
+
+    let pool = connection_pool.pool();
+    for col in columns {
+        let Some(type_name) = col.extra().get_str("pg_enum") else {
+            continue;
+        };
+        let column_name = col.name();
+        let variants = col
+            .extra()
+            .parse_enum_values("enum_values")
+            .unwrap_or_default()
+            .iter()
+            .map(|v| v.to_string_unquoted())
+            .collect::<Vec<_>>();
+        let (type_schema, type_name_unqualified) =
+            type_name.rsplit_once('.').unwrap_or(("public", type_name));
+        let sql = format!(
+            "SELECT e.enumlabel AS label FROM pg_type t \
+                JOIN pg_namespace n ON n.oid = t.typnamespace \
+                JOIN pg_enum e ON e.enumtypid = t.oid \
+                    WHERE n.nspname = {} AND t.typname = {} ORDER BY e.enumsortorder;",
+            Query::escape_string(type_schema),
+            Query::escape_string(type_name_unqualified),
+        );
+        let rows = pool.fetch(&sql).await?;
+        let mut labels = Vec::with_capacity(rows.len());
+        for row in rows {
+            if let Some(label) = Map::decode_row(&row)?.get_str("label") {
+                labels.push(label.to_owned());
+            }
+        }
+        if labels.is_empty() {
+            let values = variants
+                .iter()
+                .map(Query::escape_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            let sql = format!("CREATE TYPE {type_name} AS ENUM ({values});");
+            if !super::CREATE_ENUM_TYPES.load(Relaxed) || variants.is_empty() {
+                bail!(
+                    "the enum type `{}` is required by the column `{}` of the table `{}`, \
+                        run `{}` in the database `{}` first or enable `create-enum-types`",
+                    type_name,
+                    column_name,
+                    table_name,
+                    sql,
+                    connection_pool.database(),
+                );
+            }
+            pool.execute(&sql).await?;
+            tracing::warn!(
+                table_name,
+                column_name,
+                type_name,
+                "a new enum type `{type_name}` has been created",
+            );
+            continue;
+        }
+        for variant in variants.iter() {
+            if !labels.contains(variant) {
+                let value = Query::escape_string(variant);
+                let sql = format!("ALTER TYPE {type_name} ADD VALUE IF NOT EXISTS {value};");
+                pool.execute(&sql).await?;
+                tracing::warn!(
+                    table_name,
+                    column_name,
+                    type_name,
+                    "a new variant `{variant}` has been added to the enum type `{type_name}`",
+                );
+            }
+        }
+        for label in labels.iter() {
+            if !variants.contains(label) {
+                tracing::warn!(
+                    table_name,
+                    column_name,
+                    type_name,
+                    "the variant `{label}` of the enum type `{type_name}` is missing in the model",
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Formats the SQL to set the comment of the table.
+fn format_table_comment<M: Schema>(comment: &str) -> String {
+    let table_name = Query::table_name_escaped::<M>();
+    let comment = Query::escape_string(comment);
+    if cfg!(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb"
+    )) {
+        format!("ALTER TABLE {table_name} COMMENT = {comment};")
+    } else {
+        format!("COMMENT ON TABLE {table_name} IS {comment};")
+    }
+}
+
+/// Formats the SQL to set the comment of the column.
+/// It returns `None` if the column has no comment or the database is SQLite.
+fn format_column_comment<M: Schema>(col: &Column<'_>) -> Option<String> {
+    let comment = col.comment()?;
+    let table_name = Query::table_name_escaped::<M>();
+    if cfg!(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb"
+    )) {
+        // The primary key is kept since it is not a part of the definition.
+        let definition = col.field_definition("");
+        Some(format!(
+            "ALTER TABLE {table_name} MODIFY COLUMN {definition};"
+        ))
+    } else if cfg!(feature = "orm-postgres") {
+        let column_name = col
+            .extra()
+            .get_str("column_name")
+            .unwrap_or_else(|| col.name());
+        let column_field = Query::format_field(column_name);
+        let comment = Query::escape_string(comment);
+        Some(format!(
+            "COMMENT ON COLUMN {table_name}.{column_field} IS {comment};"
+        ))
+    } else {
+        None
+    }
+}
+
+/// Returns an error if the model is read-only.
+pub(super) fn check_writable<M: Schema>() -> Result<(), Error> {
+    if M::READ_ONLY {
+        bail!(
+            "405 Method Not Allowed: writes are unsupported for the read-only model `{}`",
+            M::model_name()
+        );
+    }
+    Ok(())
+}
+
+/// Creates or replaces the view for a read-only model if its definition has been declared.
+async fn create_view<M: Schema>() -> Result<(), Error> {
+    let Some(definition) = M::VIEW_DEFINITION.map(|s| s.trim().trim_end_matches(';')) else {
+        return Ok(());
+    };
+
+    let table_name = M::table_name();
+    let table_name_escaped = Query::table_name_escaped::<M>();
+    let is_statement = definition
+        .get(..6)
+        .is_some_and(|s| s.eq_ignore_ascii_case("CREATE"));
+    let statements = if is_statement {
+        vec![format!("{definition};")]
+    } else if cfg!(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-postgres",
+        feature = "orm-tidb"
+    )) {
+        vec![format!(
+            "CREATE OR REPLACE VIEW {table_name_escaped} AS {definition};"
+        )]
+    } else {
+        // SQLite does not support the `CREATE OR REPLACE VIEW` statement.
+        vec![
+            format!("DROP VIEW IF EXISTS {table_name_escaped};"),
+            format!("CREATE VIEW {table_name_escaped} AS {definition};"),
+        ]
+    };
+    let pool = M::init_writer()?.pool();
+    for sql in statements {
+        if let Err(err) = pool.execute(&sql).await {
+            tracing::error!(table_name, "fail to execute `{sql}`");
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+/// Prepares the SQL to insert the model data into the table.
+async fn prepare_insert_map<M: Schema>(map: Map) -> Result<QueryContext, Error> {
+    check_writable::<M>()?;
+    let table_name = Query::table_name_escaped::<M>();
+    let columns = M::columns();
+
+    let mut fields = Vec::with_capacity(columns.len());
+    let mut bind_values = Vec::with_capacity(columns.len());
+    let values = columns
+        .iter()
+        .filter_map(|col| {
+            if col.auto_increment() {
+                None
+            } else {
+                let name = col.name();
+                let field = Query::format_field(name);
+                let value = col.encode_value(col.get_value(&map));
+                fields.push(field);
+                bind_values.push((value.clone(), col.is_sensitive()));
+                Some(value)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let fields = fields.join(", ");
+    let placeholders = format_placeholders(0, bind_values.len());
+    let sql = format!("INSERT INTO {table_name} ({fields}) VALUES ({values});");
+    let statement = format!("INSERT INTO {table_name} ({fields}) VALUES ({placeholders});");
+    let mut ctx = M::before_scan(&statement).await?;
+    ctx.set_query(sql);
+    ctx.set_statement(statement);
+    for (value, sensitive) in bind_values {
+        ctx.add_bind_value(value, sensitive);
+    }
+    if cfg!(debug_assertions) && super::DEBUG_ONLY.load(Relaxed) {
+        ctx.cancel();
+    }
+    Ok(ctx)
+}
+
+/// Inserts the model into the table with the sequence columns filled.
+///
+/// The sequential numbers are taken in the same transaction as the insertion,
+/// so that they are returned to the counters if the insertion fails.
+async fn insert_with_sequences<M: Schema>(
+    model: M,
+    model_data: M::Data,
+) -> Result<QueryContext, Error> {
+    let pool = M::acquire_writer().await?;
+    SequenceCounter::with_pool(pool).create_table().await?;
+
+    let mut map = model.into_map();
+    let mut transaction = pool.pool().begin().await?;
+    let connection = transaction.acquire().await?;
+    sequence::fill_sequence_columns::<M>(pool, connection, &mut map).await?;
+
+    #[cfg(feature = "webhook")]
+    let event_data = map.clone();
+    let mut ctx = prepare_insert_map::<M>(map).await?;
+    if ctx.is_cancelled() {
+        return Ok(ctx);
+    }
+
+    let query_result = connection
+        .execute(ctx.query())
+        .await
+        .map_err(resolve_unique_violation::<M>)?;
+    transaction.commit().await?;
+
+    let (last_insert_id, rows_affected) = Query::parse_query_result(query_result);
+    let success = rows_affected == 1;
+    if let Some(last_insert_id) = last_insert_id {
+        ctx.set_last_insert_id(last_insert_id);
+    }
+    ctx.set_query_result(rows_affected, success);
+    M::after_scan(&ctx).await?;
+    M::after_insert(&ctx, model_data).await?;
+    if success {
+        #[cfg(feature = "webhook")]
+        super::webhook::emit_model_data_event::<M>("create", event_data).await;
+        Ok(ctx)
+    } else {
+        bail!(
+            "{} rows are affected while it is expected to affect 1 row",
+            rows_affected
+        );
+    }
+}
+
+/// Formats the placeholders of the bind values for a statement.
+fn format_placeholders(offset: usize, num_values: usize) -> String {
+    (1..=num_values)
+        .map(|n| Query::placeholder(offset + n))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Schema;
+    use crate::ConnectionPool;
+    use serde::{Deserialize, Serialize};
+    use std::sync::OnceLock;
+
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    struct OrderSummary {
+        id: i64,
+        total: i64,
+    }
+
+    static REPORTS_POOL: OnceLock<ConnectionPool> = OnceLock::new();
+
+    impl_test_schema!(
+        OrderSummary,
+        OrderSummaryColumn,
+        "order_summary",
+        "v_order_summary",
+        [
+            Id => ("id", "i64"),
+            Total => ("total", "i64"),
+        ],
+        view = "SELECT user_id AS id, SUM(amount) AS total FROM orders GROUP BY user_id",
+        pool = REPORTS_POOL
+    );
+
+    #[tokio::test]
+    async fn it_rejects_writes_to_read_only_models() {
+        use zino_core::model::{Mutation, Query};
+
+        // The writes are rejected without acquiring the connection pool.
+        let summary = OrderSummary { id: 3, total: 0 };
+        let err = summary.insert().await.unwrap_err();
+        assert!(err.to_string().contains("405 Method Not Allowed"));
+
+        let query = Query::from_entry("id", 1);
+        let mut mutation = Mutation::from_entry("total", 0);
+        assert!(OrderSummary::update_one(&query, &mut mutation)
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("405 Method Not Allowed"));
+        assert!(OrderSummary::delete_one(&query)
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("405 Method Not Allowed"));
+        assert!(OrderSummary::delete_by_id(&1)
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("405 Method Not Allowed"));
+    }
+
+    // The test runs against an in-memory database when SQLite is the driver.
+    #[cfg(not(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-postgres",
+        feature = "orm-tidb"
+    )))]
+    #[tokio::test]
+    async fn it_creates_and_queries_read_only_views() {
+        use crate::Executor;
+        use sqlx::{pool::PoolOptions, sqlite::SqliteConnectOptions};
+        use zino_core::{extension::JsonObjectExt, model::Query, Map};
+
+        let pool = PoolOptions::<sqlx::Sqlite>::new()
+            .max_connections(1)
+            .connect_with("sqlite::memory:".parse::<SqliteConnectOptions>().unwrap())
+            .await
+            .expect("fail to connect to the in-memory database");
+        let pool = REPORTS_POOL.get_or_init(|| ConnectionPool::new("main", "main", pool));
+        pool.execute(
+            "CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER NOT NULL, amount INTEGER NOT NULL);
+            INSERT INTO orders VALUES (1, 1, 150), (2, 2, 200), (3, 1, 50);",
+        )
+        .await
+        .expect("fail to prepare the table");
+
+        // The view is created instead of a table, and it can be recreated.
+        OrderSummary::create_table().await.unwrap();
+        OrderSummary::create_table().await.unwrap();
+
+        let mut query = Query::default();
+        query.order_asc("id");
+        let rows = OrderSummary::find::<Map>(&query).await.unwrap();
+        let totals = rows
+            .iter()
+            .map(|row| (row.get_i64("id"), row.get_i64("total")))
+            .collect::<Vec<_>>();
+        assert_eq!(totals, [(Some(1), Some(200)), (Some(2), Some(200))]);
+
+        assert_eq!(OrderSummary::count(&Query::default()).await.unwrap(), 2);
+    }
+}
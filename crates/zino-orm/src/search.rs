@@ -0,0 +1,155 @@
+use super::{query::QueryExt, Schema};
+use zino_core::model::{Column, Query};
+
+/// Max number of words retained in a search term.
+const MAX_SEARCH_WORDS: usize = 32;
+
+/// Name of the generated `tsvector` column for the full-text search in PostgreSQL.
+pub(crate) const SEARCH_VECTOR_COLUMN: &str = "search_vector";
+
+/// Alias of the selectable column for the rank of relevance.
+pub(crate) const SEARCH_SCORE_FIELD: &str = "search_score";
+
+/// Sanitizes the raw search term by stripping the operators of the full-text search.
+///
+/// Only the alphanumeric words are retained, so that the user input can never be
+/// interpreted as the `tsquery` operators in PostgreSQL, the boolean mode in MySQL
+/// or the query syntax of FTS5 in SQLite.
+pub fn sanitize_search_term(term: &str) -> String {
+    term.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| !word.is_empty())
+        .take(MAX_SEARCH_WORDS)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns the language of the full-text search for the model.
+pub(crate) fn search_language<M: Schema>() -> &'static str {
+    M::columns()
+        .iter()
+        .find_map(|col| col.fulltext_language())
+        .unwrap_or("english")
+}
+
+/// Returns the escaped name of the FTS5 shadow table in SQLite.
+pub(crate) fn fts_table_name_escaped<M: Schema>() -> String {
+    let table_name = [M::table_name(), "_fts"].concat();
+    table_name
+        .split('.')
+        .map(|s| ["`", s, "`"].concat())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Formats the condition of the full-text search for the term.
+/// It returns `None` if the model has no fulltext columns or the sanitized term is empty.
+pub(crate) fn format_search_condition<M: Schema>(term: &str) -> Option<String> {
+    let search = sanitize_search_term(term);
+    if search.is_empty() {
+        return None;
+    }
+
+    let columns = M::fulltext_columns();
+    if columns.is_empty() {
+        return None;
+    }
+
+    let condition = if cfg!(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb"
+    )) {
+        format_match_against(&columns, &search)
+    } else if cfg!(feature = "orm-postgres") {
+        let tsquery = format_tsquery::<M>(&search);
+        format!("{SEARCH_VECTOR_COLUMN} @@ {tsquery}")
+    } else {
+        let fts_table = fts_table_name_escaped::<M>();
+        let fts_query = Query::escape_string(format_fts5_query(&search));
+        format!("rowid IN (SELECT rowid FROM {fts_table} WHERE {fts_table} MATCH {fts_query})")
+    };
+    Some(condition)
+}
+
+/// Formats the expression of the rank of relevance for the term.
+/// A higher score indicates a more relevant row for all the drivers.
+pub(crate) fn format_search_score<M: Schema>(term: &str) -> Option<String> {
+    let search = sanitize_search_term(term);
+    if search.is_empty() {
+        return None;
+    }
+
+    let columns = M::fulltext_columns();
+    if columns.is_empty() {
+        return None;
+    }
+
+    let score = if cfg!(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb"
+    )) {
+        format_match_against(&columns, &search)
+    } else if cfg!(feature = "orm-postgres") {
+        let tsquery = format_tsquery::<M>(&search);
+        format!("ts_rank({SEARCH_VECTOR_COLUMN}, {tsquery})")
+    } else {
+        let model_name = M::model_name();
+        let fts_table = fts_table_name_escaped::<M>();
+        let fts_query = Query::escape_string(format_fts5_query(&search));
+        format!(
+            "(SELECT -bm25({fts_table}) FROM {fts_table} \
+                WHERE {fts_table} MATCH {fts_query} AND {fts_table}.rowid = `{model_name}`.rowid)"
+        )
+    };
+    Some(score)
+}
+
+/// Formats the `MATCH ... AGAINST` expression in MySQL.
+fn format_match_against(columns: &[&Column<'_>], search: &str) -> String {
+    let fields = columns
+        .iter()
+        .map(|col| col.name())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let search = Query::escape_string(search);
+    format!("MATCH({fields}) AGAINST({search} IN NATURAL LANGUAGE MODE)")
+}
+
+/// Formats the `to_tsquery` expression in PostgreSQL.
+fn format_tsquery<M: Schema>(search: &str) -> String {
+    let language = search_language::<M>();
+    let tsquery = Query::escape_string(search.replace(' ', " & "));
+    format!("to_tsquery('{language}', {tsquery})")
+}
+
+/// Formats the FTS5 query in SQLite by quoting each word as a phrase,
+/// so that the keywords such as `AND`, `OR`, `NOT` and `NEAR` are matched literally.
+fn format_fts5_query(search: &str) -> String {
+    search
+        .split(' ')
+        .map(|word| ["\"", word, "\""].concat())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_fts5_query, sanitize_search_term};
+
+    #[test]
+    fn it_sanitizes_search_terms() {
+        assert_eq!(sanitize_search_term("rust & (web | !orm)"), "rust web orm");
+        assert_eq!(sanitize_search_term("'fat':* <-> rat"), "fat rat");
+        assert_eq!(
+            sanitize_search_term("  +zino -axum \"actix\" "),
+            "zino axum actix"
+        );
+        assert_eq!(
+            sanitize_search_term("数据库 search_term"),
+            "数据库 search_term"
+        );
+        assert_eq!(sanitize_search_term("&|!()"), "");
+        assert_eq!(format_fts5_query("cats OR dogs"), r#""cats" "OR" "dogs""#);
+    }
+}
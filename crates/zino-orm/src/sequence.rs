@@ -0,0 +1,415 @@
+use super::{query::QueryExt, schema::Schema, ConnectionPool, DatabaseConnection, GlobalPool};
+use std::{ops::RangeInclusive, sync::Mutex};
+use zino_core::{
+    datetime::DateTime,
+    error::Error,
+    extension::{JsonObjectExt, JsonValueExt, TomlTableExt},
+    model::Query,
+    state::State,
+    warn, JsonValue, LazyLock, Map,
+};
+
+/// A counter of human-friendly sequential numbers such as `INV-2024-000123`,
+/// which is backed by the `_zino_sequences` table with a row for each scope.
+///
+/// The counter is incremented atomically by an upsert with `RETURNING` in PostgreSQL
+/// and SQLite, or an upsert with `LAST_INSERT_ID(expr)` on the same connection
+/// in MySQL. It can be configured in the `[sequence-counter]` table:
+///
+/// ```toml
+/// [sequence-counter]
+/// database = "main"
+/// ```
+///
+/// # Guarantees
+///
+/// The numbers are unique and increasing within a scope, and they are never reused.
+/// However, strict gaplessness is impossible to guarantee: a number obtained by
+/// [`next()`](Self::next) or [`reserve()`](Self::reserve) is committed immediately,
+/// so it is burned if the caller fails afterwards, and a reserved range may not be
+/// used up. A number obtained by [`next_in()`](Self::next_in) inside of a transaction
+/// is returned to the counter if the transaction is rolled back, at the cost of
+/// serializing the writers of the same scope until the transaction completes.
+/// Deleting a row always leaves a gap.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino_orm::{SequenceCounter, SequencePattern};
+///
+/// let counter = SequenceCounter::new()?;
+/// let pattern = SequencePattern::new("INV-{year}-{seq:06}").reset("yearly");
+/// let now = DateTime::now();
+/// let scope = pattern.scope_key(&format!("invoice:{org_id}"), &now);
+/// let invoice_number = pattern.format(counter.next(&scope).await?, &now);
+///
+/// // Reserves 1000 numbers for a bulk import.
+/// let range = counter.reserve(&scope, 1000).await?;
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SequenceCounter {
+    /// Connection pool.
+    pool: &'static ConnectionPool,
+}
+
+impl SequenceCounter {
+    /// Creates a new instance with the connection pool configured
+    /// by the `[sequence-counter]` table.
+    pub fn new() -> Result<Self, Error> {
+        GlobalPool::get(*SEQUENCE_DATABASE)
+            .map(Self::with_pool)
+            .ok_or_else(|| warn!("connection to the database is unavailable"))
+    }
+
+    /// Creates a new instance with the connection pool.
+    #[inline]
+    pub fn with_pool(pool: &'static ConnectionPool) -> Self {
+        Self { pool }
+    }
+
+    /// Returns the next number of the scope.
+    #[inline]
+    pub async fn next(&self, scope: &str) -> Result<u64, Error> {
+        self.reserve(scope, 1).await.map(|range| *range.end())
+    }
+
+    /// Reserves a range of `n` numbers for the scope.
+    pub async fn reserve(&self, scope: &str, n: u64) -> Result<RangeInclusive<u64>, Error> {
+        self.create_table().await?;
+        let mut connection = self.pool.pool().acquire().await?;
+        increment(&mut connection, scope, n).await
+    }
+
+    /// Returns the next number of the scope inside of a transaction.
+    /// The increment is rolled back along with the transaction.
+    #[inline]
+    pub async fn next_in(
+        &self,
+        connection: &mut DatabaseConnection,
+        scope: &str,
+    ) -> Result<u64, Error> {
+        self.reserve_in(connection, scope, 1)
+            .await
+            .map(|range| *range.end())
+    }
+
+    /// Reserves a range of `n` numbers for the scope inside of a transaction.
+    /// The increment is rolled back along with the transaction.
+    pub async fn reserve_in(
+        &self,
+        connection: &mut DatabaseConnection,
+        scope: &str,
+        n: u64,
+    ) -> Result<RangeInclusive<u64>, Error> {
+        self.create_table().await?;
+        increment(connection, scope, n).await
+    }
+
+    /// Returns the current number of the scope without incrementing it.
+    pub async fn current(&self, scope: &str) -> Result<Option<u64>, Error> {
+        self.create_table().await?;
+        let placeholder = Query::placeholder(1);
+        let sql =
+            format!("SELECT current_value FROM {SEQUENCES_TABLE} WHERE scope = {placeholder};");
+        let value = sqlx::query_scalar::<_, i64>(&sql)
+            .bind(scope)
+            .fetch_optional(self.pool.pool())
+            .await?;
+        Ok(value.and_then(|value| value.try_into().ok()))
+    }
+
+    /// Creates the `_zino_sequences` table if it does not exist.
+    /// It should be called outside of a transaction, since a DDL statement
+    /// causes an implicit commit in MySQL.
+    pub(crate) async fn create_table(&self) -> Result<(), Error> {
+        let pool_name = self.pool.name();
+        if !super::AUTO_MIGRATION.load(std::sync::atomic::Ordering::Relaxed)
+            || SEQUENCES_TABLE_CREATED
+                .lock()
+                .is_ok_and(|pools| pools.contains(&pool_name))
+        {
+            return Ok(());
+        }
+
+        let sql = format!(
+            "CREATE TABLE IF NOT EXISTS {SEQUENCES_TABLE} (\n  \
+                scope VARCHAR(255) NOT NULL PRIMARY KEY,\n  \
+                current_value BIGINT NOT NULL\n\
+            );"
+        );
+        if let Err(err) = sqlx::query(&sql).execute(self.pool.pool()).await {
+            tracing::error!(table_name = SEQUENCES_TABLE, "fail to execute `{sql}`");
+            return Err(err.into());
+        }
+        if let Ok(mut pools) = SEQUENCES_TABLE_CREATED.lock() {
+            pools.push(pool_name);
+        }
+        Ok(())
+    }
+}
+
+/// A pattern of the sequential numbers, such as `INV-{year}-{seq:06}`.
+///
+/// The supported placeholders are `{year}`, `{month}`, `{day}`, and `{seq}`
+/// with an optional zero-padded width like `{seq:06}`. The counter is reset
+/// for each period if the `reset` is configured as `yearly`, `monthly` or `daily`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequencePattern {
+    /// Pattern.
+    pattern: String,
+    /// Reset period.
+    reset: Option<&'static str>,
+}
+
+impl SequencePattern {
+    /// Creates a new instance.
+    #[inline]
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            reset: None,
+        }
+    }
+
+    /// Resets the counter for each period: `yearly` | `monthly` | `daily` | `never`.
+    pub fn reset(mut self, period: &str) -> Self {
+        self.reset = match period {
+            "yearly" => Some("yearly"),
+            "monthly" => Some("monthly"),
+            "daily" => Some("daily"),
+            "never" => None,
+            _ => {
+                tracing::warn!("unsupported reset period `{period}` for the sequence");
+                None
+            }
+        };
+        self
+    }
+
+    /// Returns the scope key of the counter for the date,
+    /// which contains the current period if the counter is reset periodically.
+    pub fn scope_key(&self, scope: &str, date: &DateTime) -> String {
+        match self.reset {
+            Some("yearly") => format!("{scope}@{}", date.year()),
+            Some("monthly") => format!("{scope}@{}-{:02}", date.year(), date.month()),
+            Some("daily") => format!(
+                "{scope}@{}-{:02}-{:02}",
+                date.year(),
+                date.month(),
+                date.day()
+            ),
+            _ => scope.to_owned(),
+        }
+    }
+
+    /// Formats the sequential number for the date.
+    pub fn format(&self, seq: u64, date: &DateTime) -> String {
+        let mut output = String::with_capacity(self.pattern.len() + 8);
+        let mut rest = self.pattern.as_str();
+        while let Some(start) = rest.find('{') {
+            output.push_str(&rest[..start]);
+            let Some(end) = rest[start..].find('}') else {
+                break;
+            };
+            let placeholder = &rest[start + 1..start + end];
+            match placeholder {
+                "year" => output.push_str(&date.year().to_string()),
+                "month" => output.push_str(&format!("{:02}", date.month())),
+                "day" => output.push_str(&format!("{:02}", date.day())),
+                "seq" => output.push_str(&seq.to_string()),
+                _ => {
+                    if let Some(width) = placeholder
+                        .strip_prefix("seq:")
+                        .and_then(|s| s.parse::<usize>().ok())
+                    {
+                        output.push_str(&format!("{seq:0width$}"));
+                    } else {
+                        output.push_str(&rest[start..=start + end]);
+                    }
+                }
+            }
+            rest = &rest[start + end + 1..];
+        }
+        output.push_str(rest);
+        output
+    }
+}
+
+/// Returns `true` if the model has any columns filled by a sequence.
+#[inline]
+pub(crate) fn has_sequence_columns<M: Schema>() -> bool {
+    M::columns()
+        .iter()
+        .any(|col| col.sequence_pattern().is_some())
+}
+
+/// Fills the empty sequence columns of the model data inside of a transaction.
+///
+/// The counter is scoped by the table name, the column name, the values of the columns
+/// specified by `sequence_scope` and the period specified by `sequence_reset`.
+pub(crate) async fn fill_sequence_columns<M: Schema>(
+    pool: &'static ConnectionPool,
+    connection: &mut DatabaseConnection,
+    data: &mut Map,
+) -> Result<(), Error> {
+    let counter = SequenceCounter::with_pool(pool);
+    let now = DateTime::now();
+    for col in M::columns() {
+        let Some(pattern) = col.sequence_pattern() else {
+            continue;
+        };
+
+        let name = col.name();
+        if data
+            .get(name)
+            .is_some_and(|value| !value.is_null() && !value.is_ignorable())
+        {
+            continue;
+        }
+
+        let mut scope = format!("{}.{name}", M::table_name());
+        if let Some(fields) = col.extra().get_str("sequence_scope") {
+            for field in fields.split(',').map(|s| s.trim()) {
+                let value = data
+                    .get(field)
+                    .map(|value| value.to_string_unquoted())
+                    .unwrap_or_default();
+                scope.push(':');
+                scope.push_str(&value);
+            }
+        }
+
+        let mut pattern = SequencePattern::new(pattern);
+        if let Some(period) = col.extra().get_str("sequence_reset") {
+            pattern = pattern.reset(period);
+        }
+        let scope = pattern.scope_key(&scope, &now);
+        let seq = counter.next_in(connection, &scope).await?;
+        let value = if pattern.pattern == "{seq}" && !col.type_name().contains("String") {
+            JsonValue::from(seq)
+        } else {
+            JsonValue::from(pattern.format(seq, &now))
+        };
+        data.upsert(name, value);
+    }
+    Ok(())
+}
+
+/// Increments the counter of the scope by `n` and returns the reserved range.
+async fn increment(
+    connection: &mut DatabaseConnection,
+    scope: &str,
+    n: u64,
+) -> Result<RangeInclusive<u64>, Error> {
+    if n == 0 {
+        return Err(warn!(
+            "the number of the reserved values should be positive"
+        ));
+    }
+
+    let step = i64::try_from(n)?;
+    let value = if cfg!(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb"
+    )) {
+        let sql = format!(
+            "INSERT INTO {SEQUENCES_TABLE} (scope, current_value) VALUES (?, LAST_INSERT_ID(?)) \
+                ON DUPLICATE KEY UPDATE current_value = LAST_INSERT_ID(current_value + ?);"
+        );
+        sqlx::query(&sql)
+            .bind(scope)
+            .bind(step)
+            .bind(step)
+            .execute(&mut *connection)
+            .await?;
+        sqlx::query_scalar::<_, i64>("SELECT CAST(LAST_INSERT_ID() AS SIGNED);")
+            .fetch_one(&mut *connection)
+            .await?
+    } else {
+        let (scope_placeholder, step_placeholder) = (Query::placeholder(1), Query::placeholder(2));
+        let sql = format!(
+            "INSERT INTO {SEQUENCES_TABLE} (scope, current_value) \
+                VALUES ({scope_placeholder}, {step_placeholder}) \
+                ON CONFLICT (scope) DO UPDATE \
+                SET current_value = {SEQUENCES_TABLE}.current_value + excluded.current_value \
+                RETURNING current_value;"
+        );
+        sqlx::query_scalar::<_, i64>(&sql)
+            .bind(scope)
+            .bind(step)
+            .fetch_one(&mut *connection)
+            .await?
+    };
+    let end = u64::try_from(value)?;
+    Ok((end + 1 - n)..=end)
+}
+
+/// Table name for the sequences.
+const SEQUENCES_TABLE: &str = "_zino_sequences";
+
+/// Names of the connection pools where the table for the sequences has been created.
+static SEQUENCES_TABLE_CREATED: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+/// Name of the connection pool for the sequences.
+static SEQUENCE_DATABASE: LazyLock<&'static str> = LazyLock::new(|| {
+    State::shared()
+        .get_config("sequence-counter")
+        .and_then(|config| config.get_str("database"))
+        .unwrap_or("main")
+});
+
+#[cfg(test)]
+mod tests {
+    use super::SequencePattern;
+    use zino_core::datetime::DateTime;
+
+    #[test]
+    fn it_formats_sequence_patterns() {
+        let date = "2024-03-05T08:00:00Z".parse::<DateTime>().unwrap();
+        let pattern = SequencePattern::new("INV-{year}-{seq:06}").reset("yearly");
+        assert_eq!(pattern.format(123, &date), "INV-2024-000123");
+        assert_eq!(pattern.scope_key("invoice", &date), "invoice@2024");
+
+        let pattern = SequencePattern::new("{year}{month}{day}-{seq}-{unknown}");
+        assert_eq!(pattern.format(7, &date), "20240305-7-{unknown}");
+        assert_eq!(pattern.scope_key("order", &date), "order");
+    }
+
+    // The test runs against an in-memory database when SQLite is the driver.
+    #[cfg(not(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-postgres",
+        feature = "orm-tidb"
+    )))]
+    #[tokio::test]
+    async fn it_increments_sequence_counters() {
+        use super::SequenceCounter;
+        use crate::ConnectionPool;
+        use sqlx::{pool::PoolOptions, sqlite::SqliteConnectOptions};
+
+        let pool = PoolOptions::<sqlx::Sqlite>::new()
+            .max_connections(1)
+            .connect_with("sqlite::memory:".parse::<SqliteConnectOptions>().unwrap())
+            .await
+            .expect("fail to connect to the in-memory database");
+        let pool = Box::leak(Box::new(ConnectionPool::new("sequence", "main", pool)));
+        let counter = SequenceCounter::with_pool(pool);
+        assert_eq!(counter.current("invoice").await.unwrap(), None);
+        assert_eq!(counter.next("invoice").await.unwrap(), 1);
+        assert_eq!(counter.next("invoice").await.unwrap(), 2);
+        assert_eq!(counter.reserve("invoice", 10).await.unwrap(), 3..=12);
+        assert_eq!(counter.next("order").await.unwrap(), 1);
+        assert_eq!(counter.current("invoice").await.unwrap(), Some(12));
+
+        // The increment is rolled back along with the transaction.
+        let mut transaction = pool.pool().begin().await.unwrap();
+        assert_eq!(
+            counter.next_in(&mut transaction, "invoice").await.unwrap(),
+            13
+        );
+        transaction.rollback().await.unwrap();
+        assert_eq!(counter.next("invoice").await.unwrap(), 13);
+    }
+}
@@ -0,0 +1,200 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+    time::Duration,
+};
+use toml::value::Table;
+use zino_core::extension::TomlTableExt;
+
+/// Session settings applied to every new connection of a pool.
+///
+/// They can be configured in the table of a database service:
+///
+/// ```toml
+/// [[postgres]]
+/// statement-timeout = "5s"
+/// search-path = ["app", "public"]
+/// init-sql = ["SET application_name = 'zino'"]
+///
+/// [[mysql]]
+/// statement-timeout = "5s"
+/// sql-mode = "STRICT_TRANS_TABLES,NO_ZERO_DATE"
+/// ```
+///
+/// The statements are executed once per connection when it has been established,
+/// and a connection failing to run them will be closed instead of being handed out.
+#[derive(Debug, Default)]
+pub struct SessionSettings {
+    /// Time zone.
+    time_zone: Option<String>,
+    /// Timeout for a statement.
+    statement_timeout: Option<Duration>,
+    /// Schema search path.
+    search_path: Option<String>,
+    /// SQL mode.
+    sql_mode: Option<String>,
+    /// Custom SQL statements.
+    init_sql: Vec<String>,
+    /// SQL statements executed on every new connection.
+    statements: Vec<String>,
+    /// Number of the initialized connections.
+    initialized_connections: AtomicUsize,
+}
+
+impl SessionSettings {
+    /// Creates a new instance with the configuration.
+    pub fn with_config(config: &Table) -> Self {
+        let search_path = if let Some(search_path) = config.get_str_array("search-path") {
+            Some(search_path.join(", "))
+        } else {
+            config.get_str("search-path").map(|s| s.to_owned())
+        };
+        let mut settings = Self {
+            time_zone: super::TIME_ZONE.get().map(|&s| s.to_owned()),
+            statement_timeout: config.get_duration("statement-timeout"),
+            search_path,
+            sql_mode: config.get_str("sql-mode").map(|s| s.to_owned()),
+            init_sql: config
+                .get_str_array("init-sql")
+                .map(|v| v.into_iter().map(|s| s.to_owned()).collect())
+                .unwrap_or_default(),
+            statements: Vec::new(),
+            initialized_connections: AtomicUsize::new(0),
+        };
+        settings.statements = settings.format_statements();
+        settings
+    }
+
+    /// Returns the SQL statements executed on every new connection.
+    #[inline]
+    pub fn statements(&self) -> &[String] {
+        &self.statements
+    }
+
+    /// Returns the number of the connections initialized with the settings.
+    #[inline]
+    pub fn initialized_connections(&self) -> usize {
+        self.initialized_connections.load(Relaxed)
+    }
+
+    /// Formats the SQL statements for the database driver.
+    fn format_statements(&self) -> Vec<String> {
+        let mut statements = Vec::new();
+        if cfg!(any(
+            feature = "orm-mariadb",
+            feature = "orm-mysql",
+            feature = "orm-tidb"
+        )) {
+            if let Some(time_zone) = self.time_zone.as_deref() {
+                let time_zone = escape_string(time_zone);
+                statements.push(format!("SET time_zone = {time_zone};"));
+            }
+            if let Some(timeout) = self.statement_timeout {
+                if cfg!(feature = "orm-mariadb") {
+                    let secs = timeout.as_secs_f64();
+                    statements.push(format!("SET SESSION max_statement_time = {secs};"));
+                } else {
+                    let millis = timeout.as_millis();
+                    statements.push(format!("SET SESSION max_execution_time = {millis};"));
+                }
+            }
+            if let Some(sql_mode) = self.sql_mode.as_deref() {
+                let sql_mode = escape_string(sql_mode);
+                statements.push(format!("SET SESSION sql_mode = {sql_mode};"));
+            }
+            if self.search_path.is_some() {
+                tracing::warn!("the `search-path` setting is only supported by PostgreSQL");
+            }
+        } else if cfg!(feature = "orm-postgres") {
+            if let Some(time_zone) = self.time_zone.as_deref() {
+                let time_zone = escape_string(time_zone);
+                statements.push(format!("SET TIME ZONE {time_zone};"));
+            }
+            if let Some(timeout) = self.statement_timeout {
+                let millis = timeout.as_millis();
+                statements.push(format!("SET statement_timeout = {millis};"));
+            }
+            if let Some(search_path) = self.search_path.as_deref() {
+                statements.push(format!("SET search_path TO {search_path};"));
+            }
+            if self.sql_mode.is_some() {
+                tracing::warn!("the `sql-mode` setting is only supported by MySQL");
+            }
+        } else if self.statement_timeout.is_some()
+            || self.search_path.is_some()
+            || self.sql_mode.is_some()
+        {
+            tracing::warn!("only the `init-sql` setting is supported by SQLite");
+        }
+        statements.extend(self.init_sql.iter().cloned());
+        statements
+    }
+
+    /// Executes the statements on a new connection.
+    #[cfg(feature = "orm-sqlx")]
+    pub(crate) async fn apply(
+        &self,
+        conn: &mut super::DatabaseConnection,
+    ) -> Result<(), sqlx::Error> {
+        use sqlx::Executor;
+
+        for sql in self.statements.iter() {
+            if let Err(err) = conn.execute(sql.as_str()).await {
+                tracing::error!("fail to execute `{sql}` on a new connection: {err}");
+                return Err(err);
+            }
+        }
+        self.initialized_connections.fetch_add(1, Relaxed);
+        Ok(())
+    }
+}
+
+/// Escapes a string literal.
+#[inline]
+fn escape_string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SessionSettings;
+
+    fn session_settings(config: &str) -> SessionSettings {
+        let config = config
+            .parse()
+            .expect("the config should be a valid TOML table");
+        SessionSettings::with_config(&config)
+    }
+
+    #[test]
+    fn it_formats_session_statements() {
+        let settings = session_settings(
+            r#"
+                statement-timeout = "5s"
+                search-path = ["app", "public"]
+                sql-mode = "STRICT_TRANS_TABLES"
+                init-sql = ["SELECT 1"]
+            "#,
+        );
+        let statements = settings.statements();
+        if cfg!(any(
+            feature = "orm-mariadb",
+            feature = "orm-mysql",
+            feature = "orm-tidb"
+        )) {
+            assert!(
+                statements.contains(&"SET SESSION sql_mode = 'STRICT_TRANS_TABLES';".to_owned())
+            );
+        } else if cfg!(feature = "orm-postgres") {
+            assert_eq!(
+                statements,
+                [
+                    "SET statement_timeout = 5000;",
+                    "SET search_path TO app, public;",
+                    "SELECT 1",
+                ]
+            );
+        } else {
+            assert_eq!(statements, ["SELECT 1"]);
+        }
+    }
+}
@@ -53,6 +53,9 @@ impl EncodeColumn<DatabaseDriver> for Column<'_> {
                         self.format_value(value)
                     }
                 }
+                JsonValue::Array(_) if self.type_name() == "Map" => {
+                    Query::escape_string(value).into()
+                }
                 JsonValue::Array(value) => {
                     let values = value
                         .iter()
@@ -162,6 +165,9 @@ impl EncodeColumn<DatabaseDriver> for Column<'_> {
                         "$rlike" => "REGEXP",
                         "$is" => "IS",
                         "$size" => "json_array_length",
+                        "$size_gt" => "json_array_length_gt",
+                        "$contains" => "json_contains",
+                        "$overlaps" => "json_overlaps",
                         _ => {
                             if cfg!(debug_assertions) && name.starts_with('$') {
                                 tracing::warn!("unsupported operator `{name}` for SQLite");
@@ -214,6 +220,33 @@ impl EncodeColumn<DatabaseDriver> for Column<'_> {
                             let condition = format!(r#"json_array_length({field}) = {length}"#);
                             conditions.push(condition);
                         }
+                    } else if operator == "json_array_length_gt" {
+                        if let Some(Ok(length)) = value.parse_usize() {
+                            let condition = format!(r#"json_array_length({field}) > {length}"#);
+                            conditions.push(condition);
+                        }
+                    } else if operator == "json_contains" || operator == "json_overlaps" {
+                        if let Some(values) = value.as_array().filter(|v| !v.is_empty()) {
+                            let exprs = values
+                                .iter()
+                                .map(|v| {
+                                    let value = match v {
+                                        JsonValue::String(v) => Query::escape_string(v),
+                                        _ => self.encode_value(Some(v)).into_owned(),
+                                    };
+                                    format!(
+                                        r#"EXISTS (SELECT 1 FROM json_each({field}) WHERE value = {value})"#
+                                    )
+                                })
+                                .collect::<Vec<_>>();
+                            let separator = if operator == "json_contains" {
+                                " AND "
+                            } else {
+                                " OR "
+                            };
+                            let condition = format!("({})", exprs.join(separator));
+                            conditions.push(condition);
+                        }
                     } else {
                         let value = self.encode_value(Some(value));
                         let condition = format!(r#"{field} {operator} {value}"#);
@@ -356,7 +389,7 @@ impl EncodeColumn<DatabaseDriver> for Column<'_> {
                     format!(r#"{field} = {value}"#)
                 }
             }
-            "Uuid" | "Option<Uuid>" => {
+            "Uuid" | "Option<Uuid>" | "Ulid" | "Option<Ulid>" => {
                 if let Some(value) = value.as_str() {
                     if value == "null" {
                         format!(r#"{field} IS NULL"#)
@@ -428,7 +461,8 @@ impl DecodeRow<DatabaseRow> for Map {
                 JsonValue::Null
             } else {
                 use super::decode::decode_raw;
-                match col.type_info().name() {
+                let type_info = decode_type_info(col, &raw_value);
+                match type_info.name() {
                     "BOOLEAN" => decode_raw::<bool>(field, raw_value)?.into(),
                     "INTEGER" | "BIGINT" => decode_raw::<i64>(field, raw_value)?.into(),
                     "REAL" => decode_raw::<f64>(field, raw_value)?.into(),
@@ -483,7 +517,8 @@ impl DecodeRow<DatabaseRow> for Record {
                 AvroValue::Null
             } else {
                 use super::decode::decode_raw;
-                match col.type_info().name() {
+                let type_info = decode_type_info(col, &raw_value);
+                match type_info.name() {
                     "BOOLEAN" => decode_raw::<bool>(field, raw_value)?.into(),
                     "INTEGER" | "BIGINT" => decode_raw::<i64>(field, raw_value)?.into(),
                     "REAL" => decode_raw::<f64>(field, raw_value)?.into(),
@@ -521,6 +556,22 @@ impl DecodeRow<DatabaseRow> for Record {
     }
 }
 
+/// Returns the type info to decode the value of a column.
+/// The declared type is unavailable for an expression such as `SUM(amount)`
+/// in a view or an aggregate query, so the storage class of the value is used instead.
+#[cfg(feature = "orm-sqlx")]
+fn decode_type_info(
+    col: &sqlx::sqlite::SqliteColumn,
+    raw_value: &sqlx::sqlite::SqliteValueRef<'_>,
+) -> sqlx::sqlite::SqliteTypeInfo {
+    let type_info = col.type_info();
+    if type_info.is_null() {
+        raw_value.type_info().into_owned()
+    } else {
+        type_info.clone()
+    }
+}
+
 #[cfg(feature = "orm-sqlx")]
 impl QueryExt<DatabaseDriver> for Query {
     type QueryResult = sqlx::sqlite::SqliteQueryResult;
@@ -557,6 +608,11 @@ impl QueryExt<DatabaseDriver> for Query {
         self.limit()
     }
 
+    #[inline]
+    fn query_extra_flags(&self) -> &Map {
+        self.extra_flags()
+    }
+
     #[inline]
     fn placeholder(_n: usize) -> SharedString {
         "?".into()
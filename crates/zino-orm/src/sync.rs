@@ -0,0 +1,598 @@
+use super::{
+    decode, executor::Executor, query::QueryExt, DatabaseRow, DecodeRow, GlobalPool, Schema,
+};
+use std::{
+    fmt,
+    marker::PhantomData,
+    str::FromStr,
+    sync::atomic::{AtomicBool, Ordering::Relaxed},
+    time::Duration,
+};
+use zino_core::{
+    bail,
+    datetime::DateTime,
+    encoding::hex,
+    error::Error,
+    extension::{JsonObjectExt, JsonValueExt, TomlTableExt},
+    model::{Query, QueryOrder},
+    schedule::{AsyncJob, JobContext},
+    state::State,
+    warn, BoxFuture, JsonValue, LazyLock, Map,
+};
+
+/// A token of the high-water mark for the incremental sync of a model.
+///
+/// It consists of the `updated_at` of the last row seen in microseconds
+/// and its primary key as a tiebreaker for the rows updated at the same time.
+/// The token is encoded as a hex string so that it can be used in the URL as it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncToken {
+    /// Timestamp of the `updated_at` in microseconds.
+    timestamp: i64,
+    /// Primary key of the last row seen.
+    last_id: String,
+}
+
+impl SyncToken {
+    /// Creates a new instance with the `updated_at` and the primary key of the last row seen.
+    #[inline]
+    pub fn new(updated_at: DateTime, last_id: impl Into<String>) -> Self {
+        Self {
+            timestamp: updated_at.timestamp_micros(),
+            last_id: last_id.into(),
+        }
+    }
+
+    /// Returns the `updated_at` of the last row seen.
+    #[inline]
+    pub fn updated_at(&self) -> DateTime {
+        DateTime::from_timestamp_micros(self.timestamp)
+    }
+
+    /// Returns the primary key of the last row seen.
+    /// It is empty if the token is advanced by a tombstone.
+    #[inline]
+    pub fn last_id(&self) -> &str {
+        &self.last_id
+    }
+
+    /// Encodes the token as a hex string.
+    #[inline]
+    pub fn encode(&self) -> String {
+        hex::encode(format!("{}.{}", self.timestamp, self.last_id))
+    }
+}
+
+impl fmt::Display for SyncToken {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.encode())
+    }
+}
+
+impl FromStr for SyncToken {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s)?;
+        let token = String::from_utf8(bytes)?;
+        let Some((timestamp, last_id)) = token.split_once('.') else {
+            bail!("invalid sync token `{}`", s);
+        };
+        Ok(Self {
+            timestamp: timestamp.parse()?,
+            last_id: last_id.to_owned(),
+        })
+    }
+}
+
+/// A batch of the changes of a model since a sync token.
+#[derive(Debug, Clone, Default)]
+pub struct ChangeSet {
+    /// Rows created or updated.
+    entries: Vec<Map>,
+    /// Primary keys of the rows deleted.
+    deleted_ids: Vec<String>,
+    /// Token for the next batch.
+    next_token: Option<SyncToken>,
+    /// A flag to indicate whether there are more changes.
+    has_more: bool,
+    /// A flag to indicate whether the client should discard its data and resync from scratch.
+    full_resync_required: bool,
+}
+
+impl ChangeSet {
+    /// Creates a new instance which requires the client to resync from scratch.
+    #[inline]
+    pub fn full_resync() -> Self {
+        Self {
+            full_resync_required: true,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the rows created or updated.
+    #[inline]
+    pub fn entries(&self) -> &[Map] {
+        &self.entries
+    }
+
+    /// Returns a mutable reference to the rows created or updated.
+    #[inline]
+    pub fn entries_mut(&mut self) -> &mut Vec<Map> {
+        &mut self.entries
+    }
+
+    /// Returns the primary keys of the rows deleted.
+    #[inline]
+    pub fn deleted_ids(&self) -> &[String] {
+        &self.deleted_ids
+    }
+
+    /// Returns the token for the next batch.
+    #[inline]
+    pub fn next_token(&self) -> Option<&SyncToken> {
+        self.next_token.as_ref()
+    }
+
+    /// Returns `true` if there are more changes to be fetched with the next token.
+    #[inline]
+    pub fn has_more(&self) -> bool {
+        self.has_more
+    }
+
+    /// Returns `true` if the client should discard its data and resync from scratch.
+    #[inline]
+    pub fn is_full_resync_required(&self) -> bool {
+        self.full_resync_required
+    }
+
+    /// Consumes `self` and returns a map with the `entries`, `deleted_ids`, `next_token`,
+    /// `has_more` and `full_resync_required` fields.
+    pub fn into_map(self) -> Map {
+        let mut map = Map::new();
+        map.upsert("entries", self.entries);
+        map.upsert("deleted_ids", self.deleted_ids);
+        map.upsert("next_token", self.next_token.map(|token| token.encode()));
+        map.upsert("has_more", self.has_more);
+        map.upsert("full_resync_required", self.full_resync_required);
+        map
+    }
+}
+
+/// A feed of the incremental changes of a model for the clients syncing offline data.
+///
+/// The rows are walked in the order of `(updated_at, primary_key)` after the high-water mark
+/// of the [`SyncToken`]. The soft-deleted rows with the `Deleted` status are reported
+/// as deleted ids, and the hard-deleted rows are reported by the tombstones, which are recorded
+/// in the `_zino_tombstones` table when the rows are deleted by `Schema::delete_one`
+/// or `Schema::delete_many`. A deleted id may be reported more than once,
+/// so the clients should apply the deletions idempotently.
+///
+/// The tombstones are kept for the max window. A client whose token is older than
+/// the max window gets the `full_resync_required` flag.
+/// It can be configured in the `[sync]` table:
+///
+/// ```toml
+/// [sync]
+/// database = "main"
+/// batch-size = 500
+/// max-batch-size = 5000
+/// max-window = "30d"
+/// ```
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino_orm::{ChangeFeed, SyncToken};
+///
+/// let token = req.get_query("since").map(|s| s.parse::<SyncToken>()).transpose()?;
+/// let changes = ChangeFeed::<Task>::new(query).since(token).fetch().await?;
+/// if changes.is_full_resync_required() {
+///     // Discards the local data and syncs without a token.
+/// }
+/// ```
+pub struct ChangeFeed<M: Schema> {
+    /// Query.
+    query: Query,
+    /// Sync token.
+    since: Option<SyncToken>,
+    /// Max number of rows in a batch.
+    batch_size: usize,
+    /// Phantom type of the model.
+    phantom: PhantomData<M>,
+}
+
+impl<M: Schema> ChangeFeed<M> {
+    /// Creates a new instance with the query and the shared settings.
+    #[inline]
+    pub fn new(query: Query) -> Self {
+        Self {
+            query,
+            since: None,
+            batch_size: SYNC_SETTINGS.batch_size,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Sets the sync token. The changes are fetched from scratch if it is `None`.
+    #[inline]
+    pub fn since(mut self, token: Option<SyncToken>) -> Self {
+        self.since = token;
+        self
+    }
+
+    /// Sets the max number of rows in a batch, which is capped by the `max-batch-size`.
+    #[inline]
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.clamp(1, SYNC_SETTINGS.max_batch_size);
+        self
+    }
+
+    /// Fetches a batch of the changes after the sync token.
+    pub async fn fetch(&self) -> Result<ChangeSet, Error> {
+        let model_name = M::model_name();
+        if !M::has_column("updated_at") {
+            bail!(
+                "the model `{}` should have an `updated_at` column for the change feed",
+                model_name
+            );
+        }
+
+        let since = self.since.as_ref();
+        if let Some(token) = since {
+            let max_window = i64::try_from(SYNC_SETTINGS.max_window.as_micros())?;
+            if token.timestamp < DateTime::now().timestamp_micros() - max_window {
+                return Ok(ChangeSet::full_resync());
+            }
+        }
+
+        let primary_key_name = M::PRIMARY_KEY_NAME;
+        let batch_size = self.effective_batch_size();
+        let query = self.batch_query(batch_size);
+        let mut rows = M::find::<Map>(&query).await?;
+        let has_more = rows.len() > batch_size;
+        rows.truncate(batch_size);
+
+        let mut next_token = since.cloned();
+        if let Some(row) = rows.last() {
+            let updated_at = row
+                .get_str("updated_at")
+                .and_then(|s| s.parse::<DateTime>().ok())
+                .ok_or_else(|| {
+                    warn!("the `updated_at` of the model `{}` is invalid", model_name)
+                })?;
+            let last_id = row
+                .get(primary_key_name)
+                .map(|v| v.to_string_unquoted())
+                .unwrap_or_default();
+            next_token = Some(SyncToken::new(updated_at, last_id));
+        }
+
+        let mut entries = Vec::with_capacity(rows.len());
+        let mut deleted_ids = Vec::new();
+        for row in rows {
+            if row.get_str("status") == Some("Deleted") {
+                if let Some(id) = row.get(primary_key_name) {
+                    deleted_ids.push(id.to_string_unquoted());
+                }
+            } else {
+                entries.push(row);
+            }
+        }
+        if let Some(token) = since {
+            // The tombstones after the last row are deferred to the next batch if there are more rows.
+            let until = next_token
+                .as_ref()
+                .filter(|_| has_more)
+                .map(|token| token.timestamp);
+            let tombstones = list_tombstones(model_name, token.timestamp, until).await?;
+            if let Some(&(_, deleted_at)) = tombstones.last() {
+                if next_token
+                    .as_ref()
+                    .is_some_and(|token| token.timestamp < deleted_at)
+                {
+                    next_token = Some(SyncToken {
+                        timestamp: deleted_at,
+                        last_id: String::new(),
+                    });
+                }
+            }
+            deleted_ids.extend(tombstones.into_iter().map(|(id, _)| id));
+        }
+        Ok(ChangeSet {
+            entries,
+            deleted_ids,
+            next_token,
+            has_more,
+            full_resync_required: false,
+        })
+    }
+
+    /// Returns the batch size capped by the `max-rows` of the database.
+    fn effective_batch_size(&self) -> usize {
+        let max_rows = super::MAX_ROWS.load(Relaxed);
+        if max_rows > 1 {
+            self.batch_size.min(max_rows - 1)
+        } else {
+            self.batch_size
+        }
+    }
+
+    /// Constructs the query for the batch with an extra row to detect whether there are more rows.
+    fn batch_query(&self, batch_size: usize) -> Query {
+        let primary_key_name = M::PRIMARY_KEY_NAME;
+        let mut query = self.query.clone();
+        let fields = query.fields();
+        if !fields.is_empty() {
+            let mut fields = fields.to_vec();
+            for field in [primary_key_name, "updated_at", "status"] {
+                if M::has_column(field) && !fields.iter().any(|s| s == field) {
+                    fields.push(field.to_owned());
+                }
+            }
+            query.set_fields(fields);
+        }
+        if let Some(token) = self.since.as_ref() {
+            add_sync_filter(&mut query, primary_key_name, token);
+        }
+        query.set_order(vec![
+            QueryOrder::new("updated_at", false),
+            QueryOrder::new(primary_key_name, false),
+        ]);
+        query.set_offset(0);
+        query.set_limit(batch_size + 1);
+        query.disable_max_rows();
+        query
+    }
+}
+
+/// Adds a filter to select the rows after the high-water mark of the token.
+/// An existing `$or` filter is preserved by combining them with `$and`.
+fn add_sync_filter(query: &mut Query, primary_key_name: &str, token: &SyncToken) {
+    let updated_at = JsonValue::from(token.updated_at());
+    let mut tiebreaker = Map::from_entry("updated_at", updated_at.clone());
+    tiebreaker.upsert(primary_key_name, Map::from_entry("$gt", token.last_id()));
+    let sync_filter = vec![
+        JsonValue::from(Map::from_entry(
+            "updated_at",
+            Map::from_entry("$gt", updated_at),
+        )),
+        JsonValue::from(tiebreaker),
+    ];
+    match query.remove_filter("$or") {
+        Some(filter) => {
+            let mut logical_and = match query.remove_filter("$and") {
+                Some(JsonValue::Array(filters)) => filters,
+                _ => Vec::new(),
+            };
+            logical_and.push(Map::from_entry("$or", filter).into());
+            logical_and.push(Map::from_entry("$or", sync_filter).into());
+            query.add_filter("$and", logical_and);
+        }
+        None => query.add_filter("$or", sync_filter),
+    }
+}
+
+/// Selects the primary keys of the rows to be deleted by the query,
+/// which will be recorded as tombstones.
+pub(crate) async fn select_deleted_ids<M: Schema>(query: &Query) -> Result<Vec<String>, Error> {
+    let primary_key_name = M::PRIMARY_KEY_NAME;
+    let table_name = query.format_table_name::<M>();
+    let primary_key = Query::format_field(primary_key_name);
+    let mut arguments = Vec::new();
+    let filters = query.format_filters::<M>(&mut arguments);
+    let sql = format!("SELECT {primary_key} FROM {table_name} {filters};");
+    let pool = M::acquire_writer().await?;
+    let rows = pool.fetch_with_values(&sql, &arguments).await?;
+    let mut ids = Vec::with_capacity(rows.len());
+    for row in rows {
+        if let Some(id) = Map::decode_row(&row)?.get(primary_key_name) {
+            ids.push(id.to_string_unquoted());
+        }
+    }
+    Ok(ids)
+}
+
+/// Records the tombstones of the deleted rows. The errors are logged instead of returned,
+/// since the rows have been deleted successfully.
+pub(crate) async fn record_tombstones(model_name: &str, ids: &[String]) {
+    if ids.is_empty() {
+        return;
+    }
+    if let Err(err) = insert_tombstones(model_name, ids).await {
+        tracing::error!(model_name, "fail to record the tombstones: {err}");
+    }
+}
+
+/// Inserts the tombstones of the deleted rows.
+async fn insert_tombstones(model_name: &str, ids: &[String]) -> Result<(), Error> {
+    create_table().await?;
+
+    let model_name = Query::escape_string(model_name);
+    let deleted_at = DateTime::now().timestamp_micros();
+    let values = ids
+        .iter()
+        .map(|id| {
+            let id = Query::escape_string(id);
+            format!("({model_name}, {id}, {deleted_at})")
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!(
+        "INSERT INTO {TOMBSTONES_TABLE} (model_name, record_id, deleted_at) VALUES {values};"
+    );
+    connection_pool()?.pool().execute(&sql).await?;
+    Ok(())
+}
+
+/// Lists the tombstones of the model in the time range, ordered by the deletion time.
+/// The lower bound is inclusive so that a tombstone recorded at the same time is never missed.
+async fn list_tombstones(
+    model_name: &str,
+    since: i64,
+    until: Option<i64>,
+) -> Result<Vec<(String, i64)>, Error> {
+    create_table().await?;
+
+    let model_name = Query::escape_string(model_name);
+    let mut sql = format!(
+        "SELECT record_id, deleted_at FROM {TOMBSTONES_TABLE} \
+            WHERE model_name = {model_name} AND deleted_at >= {since}"
+    );
+    if let Some(until) = until {
+        sql.push_str(&format!(" AND deleted_at <= {until}"));
+    }
+    sql.push_str(" ORDER BY deleted_at ASC;");
+
+    let rows = connection_pool()?.pool().fetch(&sql).await?;
+    rows.iter()
+        .map(|row: &DatabaseRow| Ok((decode(row, "record_id")?, decode(row, "deleted_at")?)))
+        .collect()
+}
+
+/// Tombstones of the rows deleted permanently, which are kept for the max window of the sync.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Tombstone;
+
+impl Tombstone {
+    /// Deletes the tombstones which are older than the retention period.
+    /// It returns the number of the deleted tombstones.
+    pub async fn purge(retention: Duration) -> Result<u64, Error> {
+        create_table().await?;
+
+        let retention_micros = i64::try_from(retention.as_micros())?;
+        let deadline = DateTime::now().timestamp_micros() - retention_micros;
+        let sql = format!("DELETE FROM {TOMBSTONES_TABLE} WHERE deleted_at < {deadline};");
+        let query_result = connection_pool()?.pool().execute(&sql).await?;
+        Ok(query_result.rows_affected())
+    }
+
+    /// Creates an async job which purges the tombstones after the max window.
+    /// It runs at the start of every hour.
+    pub fn cleanup_job() -> AsyncJob {
+        AsyncJob::new("0 0 * * * *", purge_tombstones).name("sync_tombstone_cleanup")
+    }
+}
+
+/// Purges the tombstones after the max window.
+fn purge_tombstones(_ctx: &mut JobContext) -> BoxFuture<'_> {
+    Box::pin(async {
+        match Tombstone::purge(SYNC_SETTINGS.max_window).await {
+            Ok(num_deleted) => tracing::info!(num_deleted, "tombstones have been purged"),
+            Err(err) => tracing::error!("fail to purge the tombstones: {err}"),
+        }
+    })
+}
+
+/// Creates the `_zino_tombstones` table if it does not exist.
+async fn create_table() -> Result<(), Error> {
+    if TOMBSTONES_TABLE_CREATED.load(Relaxed) || !super::AUTO_MIGRATION.load(Relaxed) {
+        return Ok(());
+    }
+
+    let table_sql = format!(
+        "CREATE TABLE IF NOT EXISTS {TOMBSTONES_TABLE} (\n  \
+            model_name VARCHAR(255) NOT NULL,\n  \
+            record_id VARCHAR(255) NOT NULL,\n  \
+            deleted_at BIGINT NOT NULL\n\
+        );"
+    );
+    let index_sql = if cfg!(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb"
+    )) {
+        None
+    } else {
+        Some(format!(
+            "CREATE INDEX IF NOT EXISTS {TOMBSTONES_TABLE}_model_name_deleted_at_index \
+                ON {TOMBSTONES_TABLE} (model_name, deleted_at);"
+        ))
+    };
+    let pool = connection_pool()?.pool();
+    for sql in [Some(table_sql), index_sql].into_iter().flatten() {
+        if let Err(err) = pool.execute(&sql).await {
+            tracing::error!(table_name = TOMBSTONES_TABLE, "fail to execute `{sql}`");
+            return Err(err);
+        }
+    }
+    TOMBSTONES_TABLE_CREATED.store(true, Relaxed);
+    Ok(())
+}
+
+/// Returns the connection pool.
+fn connection_pool() -> Result<&'static super::ConnectionPool, Error> {
+    GlobalPool::get(SYNC_SETTINGS.database)
+        .ok_or_else(|| warn!("connection to the database is unavailable"))
+}
+
+/// Table name for the tombstones.
+const TOMBSTONES_TABLE: &str = "_zino_tombstones";
+
+/// Flag to indicate whether the table for the tombstones has been created.
+static TOMBSTONES_TABLE_CREATED: AtomicBool = AtomicBool::new(false);
+
+/// Settings for the incremental sync.
+#[derive(Debug)]
+struct SyncSettings {
+    /// Name of the connection pool for the tombstones.
+    database: &'static str,
+    /// Default number of rows in a batch.
+    batch_size: usize,
+    /// Max number of rows in a batch.
+    max_batch_size: usize,
+    /// Max window of a sync token, which is also the retention period of the tombstones.
+    max_window: Duration,
+}
+
+/// Shared settings for the incremental sync.
+static SYNC_SETTINGS: LazyLock<SyncSettings> = LazyLock::new(|| {
+    let config = State::shared().get_config("sync");
+    let max_batch_size = config
+        .and_then(|config| config.get_usize("max-batch-size"))
+        .unwrap_or(5000)
+        .max(1);
+    SyncSettings {
+        database: config
+            .and_then(|config| config.get_str("database"))
+            .unwrap_or("main"),
+        batch_size: config
+            .and_then(|config| config.get_usize("batch-size"))
+            .unwrap_or(500)
+            .clamp(1, max_batch_size),
+        max_batch_size,
+        max_window: config
+            .and_then(|config| config.get_duration("max-window"))
+            .unwrap_or(Duration::from_secs(30 * 24 * 60 * 60)),
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{add_sync_filter, SyncToken};
+    use zino_core::{datetime::DateTime, json, model::Query};
+
+    #[test]
+    fn it_encodes_sync_tokens() {
+        let updated_at = DateTime::from_timestamp_micros(1_735_689_600_123_456);
+        let token = SyncToken::new(updated_at, "42");
+        let encoded = token.encode();
+        assert!(encoded.bytes().all(|b| b.is_ascii_hexdigit()));
+        assert_eq!(encoded.parse::<SyncToken>().unwrap(), token);
+        assert_eq!(token.updated_at(), updated_at);
+        assert!("not-a-token".parse::<SyncToken>().is_err());
+
+        let mut query = Query::new(json!({ "$or": [{ "kind": "a" }, { "kind": "b" }] }));
+        add_sync_filter(&mut query, "id", &token);
+        assert!(query.filters().get("$or").is_none());
+        assert_eq!(
+            query
+                .filters()
+                .get("$and")
+                .and_then(|v| v.as_array())
+                .map(|v| v.len()),
+            Some(2)
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! Utilities for the tests of the crate.
+
+/// Implements the `Schema` for a model in the tests. The model is read-only
+/// if the view definition is specified. The connection pool is read from
+/// a static `OnceLock` if specified, otherwise the SQL statements should be
+/// formatted and executed by the tests themselves.
+macro_rules! impl_test_schema {
+    (
+        $model:ident,
+        $column:ident,
+        $model_name:literal,
+        $table_name:literal,
+        [$($variant:ident => ($field:literal, $type_name:literal)),+ $(,)?]
+        $(, max_rows = $max_rows:literal)?
+        $(, view = $view_definition:literal)?
+        $(, pool = $pool:ident)?
+    ) => {
+        #[allow(dead_code)]
+        #[derive(Debug, Clone, Copy)]
+        enum $column {
+            $($variant),+
+        }
+
+        const _: () = {
+            use crate::{ConnectionPool, Entity, Schema};
+            use apache_avro::Schema as AvroSchema;
+            use std::fmt;
+            use zino_core::{
+                error::Error,
+                model::{Column, DefinitionCache, Model, ModelHooks},
+                warn, LazyLock,
+            };
+
+            impl AsRef<str> for $column {
+                fn as_ref(&self) -> &str {
+                    match self {
+                        $(Self::$variant => $field),+
+                    }
+                }
+            }
+
+            impl fmt::Display for $column {
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str(self.as_ref())
+                }
+            }
+
+            impl Model for $model {
+                const MODEL_NAME: &'static str = $model_name;
+            }
+
+            impl ModelHooks for $model {
+                type Data = ();
+                type Extension = ();
+            }
+
+            impl Entity for $model {
+                type Column = $column;
+                const PRIMARY_KEY: Self::Column = $column::Id;
+            }
+
+            impl Schema for $model {
+                type PrimaryKey = i64;
+                const TABLE_NAME: Option<&'static str> = Some($table_name);
+                $(const MAX_ROWS: Option<usize> = Some($max_rows);)?
+                $(
+                    const READ_ONLY: bool = true;
+                    const VIEW_DEFINITION: Option<&'static str> = Some($view_definition);
+                )?
+
+                fn primary_key(&self) -> &Self::PrimaryKey {
+                    &self.id
+                }
+
+                fn schema() -> &'static AvroSchema {
+                    static SCHEMA: LazyLock<AvroSchema> = LazyLock::new(|| AvroSchema::Null);
+                    &SCHEMA
+                }
+
+                fn columns() -> &'static [Column<'static>] {
+                    static COLUMNS: LazyLock<Vec<Column<'static>>> =
+                        LazyLock::new(|| vec![$(Column::new($field, $type_name, true)),+]);
+                    &COLUMNS
+                }
+
+                fn fields() -> &'static [&'static str] {
+                    &[$($field),+]
+                }
+
+                fn read_only_fields() -> &'static [&'static str] {
+                    &[]
+                }
+
+                fn write_only_fields() -> &'static [&'static str] {
+                    &[]
+                }
+
+                fn definition_cache() -> &'static DefinitionCache {
+                    static DEFINITION_CACHE: LazyLock<DefinitionCache> =
+                        LazyLock::new(DefinitionCache::new);
+                    &DEFINITION_CACHE
+                }
+
+                fn init_reader() -> Result<&'static ConnectionPool, Error> {
+                    Self::init_writer()
+                }
+
+                fn init_writer() -> Result<&'static ConnectionPool, Error> {
+                    let pool: Option<&'static ConnectionPool> = None $(.or($pool.get()))?;
+                    pool.ok_or_else(|| warn!("connection pools are unavailable in the tests"))
+                }
+
+                async fn acquire_reader() -> Result<&'static ConnectionPool, Error> {
+                    Self::init_writer()
+                }
+
+                async fn acquire_writer() -> Result<&'static ConnectionPool, Error> {
+                    Self::init_writer()
+                }
+            }
+        };
+    };
+}
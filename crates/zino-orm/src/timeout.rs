@@ -0,0 +1,330 @@
+use super::{executor::parse_table_name, DatabaseConnection, DatabaseDriver, Executor};
+use sqlx::{error::DatabaseError, pool::PoolConnection};
+use std::{
+    borrow::Cow,
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering::Relaxed},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use zino_core::{
+    error::{Error, ErrorKind},
+    warn,
+};
+
+tokio::task_local! {
+    /// Statement timeout for the queries executed in the scope.
+    static STATEMENT_TIMEOUT: Duration;
+}
+
+/// Extra time for the database to cancel the statement by itself
+/// before it is cancelled by the client.
+const CANCELLATION_GRACE_PERIOD: Duration = Duration::from_millis(500);
+
+/// Executes the future with the statement timeout.
+///
+/// The timeout is enforced by the database for the statements executed via
+/// the connection pool in the scope, and the future is dropped by the client
+/// if the database fails to cancel the statement in time.
+pub(crate) async fn with_statement_timeout<T, F>(
+    timeout: Option<Duration>,
+    future: F,
+) -> Result<T, Error>
+where
+    F: Future<Output = Result<T, Error>>,
+{
+    let Some(timeout) = timeout else {
+        return future.await;
+    };
+    let future = STATEMENT_TIMEOUT.scope(timeout, future);
+    match tokio::time::timeout(timeout + CANCELLATION_GRACE_PERIOD, future).await {
+        Ok(result) => result,
+        Err(_) => {
+            let err = warn!(
+                "504 Gateway Timeout: the statement does not complete in {}ms",
+                timeout.as_millis()
+            );
+            Err(err.with_kind(ErrorKind::Timeout))
+        }
+    }
+}
+
+/// A guard for the statement executed on a pooled connection.
+///
+/// If the statement future is dropped before completion, e.g. the client has disconnected,
+/// the in-flight statement is cancelled and the connection is closed instead of
+/// being returned to the pool in an unknown state.
+pub(crate) struct StatementGuard {
+    /// Pooled connection.
+    connection: PoolConnection<DatabaseDriver>,
+    /// Table name.
+    table_name: Option<String>,
+    /// Statement timeout.
+    timeout: Option<Duration>,
+    /// Start time.
+    start_time: Instant,
+    /// A flag to interrupt the statement.
+    interrupted: Arc<AtomicBool>,
+    /// Indicates whether a transaction has been started for the timeout.
+    in_transaction: bool,
+    /// Indicates whether the statement has completed.
+    completed: bool,
+}
+
+impl StatementGuard {
+    /// Creates a new instance and applies the statement timeout in the current scope.
+    pub(crate) async fn new(
+        connection: PoolConnection<DatabaseDriver>,
+        sql: &str,
+    ) -> Result<Self, Error> {
+        let mut guard = Self {
+            connection,
+            table_name: parse_table_name(sql).map(|s| s.to_owned()),
+            timeout: STATEMENT_TIMEOUT.try_with(|timeout| *timeout).ok(),
+            start_time: Instant::now(),
+            interrupted: Arc::new(AtomicBool::new(false)),
+            in_transaction: false,
+            completed: false,
+        };
+        if let Some(timeout) = guard.timeout {
+            if let Err(err) = guard.apply_timeout(timeout).await {
+                guard.connection.close_on_drop();
+                guard.completed = true;
+                return Err(err);
+            }
+        }
+        Ok(guard)
+    }
+
+    /// Returns a mutable reference to the connection.
+    #[inline]
+    pub(crate) fn connection(&mut self) -> &mut DatabaseConnection {
+        &mut self.connection
+    }
+
+    /// Prepares the SQL statement with the optimizer hint for the timeout.
+    pub(crate) fn prepare_statement<'a>(&self, sql: &'a str) -> Cow<'a, str> {
+        let Some(timeout) = self.timeout else {
+            return Cow::Borrowed(sql);
+        };
+        if cfg!(feature = "orm-mariadb") {
+            let secs = timeout.as_secs_f64();
+            Cow::Owned(format!(
+                "SET STATEMENT max_statement_time = {secs} FOR {sql}"
+            ))
+        } else if cfg!(any(feature = "orm-mysql", feature = "orm-tidb")) {
+            // The `MAX_EXECUTION_TIME` hint only applies to the `SELECT` statements.
+            let sql = sql.trim_start();
+            match sql.get(..6) {
+                Some(keyword) if keyword.eq_ignore_ascii_case("SELECT") => {
+                    let millis = timeout.as_millis();
+                    let statement = &sql[6..];
+                    Cow::Owned(format!(
+                        "SELECT /*+ MAX_EXECUTION_TIME({millis}) */{statement}"
+                    ))
+                }
+                _ => Cow::Borrowed(sql),
+            }
+        } else {
+            Cow::Borrowed(sql)
+        }
+    }
+
+    /// Finishes the statement with the result and releases the connection.
+    pub(crate) async fn finish<T>(mut self, result: Result<T, Error>) -> Result<T, Error> {
+        let result = if self.in_transaction {
+            let sql = if result.is_ok() {
+                "COMMIT;"
+            } else {
+                "ROLLBACK;"
+            };
+            match (&mut *self.connection).execute(sql).await {
+                Ok(_) => result,
+                Err(err) => result.and(Err(err)),
+            }
+        } else {
+            result
+        };
+        self.reset_timeout().await;
+        if result
+            .as_ref()
+            .is_err_and(|err| err.kind() == &ErrorKind::Timeout)
+        {
+            self.record_timeout();
+        }
+        self.completed = true;
+        result
+    }
+
+    /// Records the statement timeout.
+    fn record_timeout(&self) {
+        let table_name = self.table_name.as_deref().unwrap_or_default();
+        let elapsed_millis = self.start_time.elapsed().as_millis();
+        tracing::warn!(table_name, elapsed_millis, "the statement has timed out");
+        #[cfg(feature = "metrics")]
+        metrics::counter!(
+            "zino_db_statements_timed_out_total",
+            "table" => table_name.to_owned(),
+        )
+        .increment(1);
+    }
+}
+
+impl Drop for StatementGuard {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.interrupted.store(true, Relaxed);
+            self.connection.close_on_drop();
+            if self
+                .timeout
+                .is_some_and(|timeout| self.start_time.elapsed() >= timeout)
+            {
+                self.record_timeout();
+            } else {
+                let table_name = self.table_name.as_deref().unwrap_or_default();
+                let elapsed_millis = self.start_time.elapsed().as_millis();
+                tracing::warn!(
+                    table_name,
+                    elapsed_millis,
+                    "the statement has been cancelled"
+                );
+                #[cfg(feature = "metrics")]
+                metrics::counter!(
+                    "zino_db_statements_cancelled_total",
+                    "table" => table_name.to_owned(),
+                )
+                .increment(1);
+            }
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(any(feature = "orm-mariadb", feature = "orm-mysql", feature = "orm-tidb"))] {
+        /// Returns `true` if the database error is caused by a statement timeout.
+        pub(crate) fn is_statement_timeout(db_err: &dyn DatabaseError) -> bool {
+            // `ER_QUERY_TIMEOUT` for MySQL and `ER_STATEMENT_TIMEOUT` for MariaDB
+            db_err
+                .try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>()
+                .is_some_and(|err| matches!(err.number(), 3024 | 1969))
+        }
+
+        impl StatementGuard {
+            /// Applies the statement timeout to the connection.
+            /// It is a no-op since the timeout is applied by `prepare_statement()`.
+            #[inline]
+            async fn apply_timeout(&mut self, _timeout: Duration) -> Result<(), Error> {
+                Ok(())
+            }
+
+            /// Resets the statement timeout of the connection.
+            #[inline]
+            async fn reset_timeout(&mut self) {}
+        }
+    } else if #[cfg(feature = "orm-postgres")] {
+        /// Returns `true` if the database error is caused by a statement timeout.
+        pub(crate) fn is_statement_timeout(db_err: &dyn DatabaseError) -> bool {
+            // `query_canceled`
+            db_err.code().is_some_and(|code| code == "57014")
+        }
+
+        impl StatementGuard {
+            /// Applies the statement timeout to the connection.
+            /// `SET LOCAL` only takes effect inside a transaction.
+            async fn apply_timeout(&mut self, timeout: Duration) -> Result<(), Error> {
+                (&mut *self.connection).execute("BEGIN;").await?;
+                self.in_transaction = true;
+
+                let millis = timeout.as_millis();
+                let sql = format!("SET LOCAL statement_timeout = {millis};");
+                (&mut *self.connection).execute(&sql).await?;
+                Ok(())
+            }
+
+            /// Resets the statement timeout of the connection.
+            /// It is a no-op since the transaction has been finished.
+            #[inline]
+            async fn reset_timeout(&mut self) {}
+        }
+    } else {
+        /// Returns `true` if the database error is caused by a statement interruption.
+        pub(crate) fn is_statement_timeout(db_err: &dyn DatabaseError) -> bool {
+            // `SQLITE_INTERRUPT`
+            db_err.code().is_some_and(|code| code == "9")
+        }
+
+        impl StatementGuard {
+            /// Applies the statement timeout to the connection with a progress handler,
+            /// which interrupts the statement once the deadline has passed or the guard is dropped.
+            async fn apply_timeout(&mut self, timeout: Duration) -> Result<(), Error> {
+                let interrupted = self.interrupted.clone();
+                let deadline = self.start_time + timeout;
+                let mut handle = self
+                    .connection
+                    .lock_handle()
+                    .await
+                    .map_err(super::executor::classify_error)?;
+                handle.set_progress_handler(1000, move || {
+                    !interrupted.load(Relaxed) && Instant::now() < deadline
+                });
+                Ok(())
+            }
+
+            /// Resets the statement timeout of the connection by removing the progress handler.
+            /// The connection will be closed if it fails to do so.
+            async fn reset_timeout(&mut self) {
+                if self.timeout.is_none() {
+                    return;
+                }
+
+                let removed = match self.connection.lock_handle().await {
+                    Ok(mut handle) => {
+                        handle.remove_progress_handler();
+                        true
+                    }
+                    Err(_) => false,
+                };
+                if !removed {
+                    self.connection.close_on_drop();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb",
+        feature = "orm-postgres"
+    )))]
+    #[tokio::test]
+    async fn it_interrupts_statements_with_timeout() {
+        use crate::{ConnectionPool, Executor};
+        use sqlx::{pool::PoolOptions, sqlite::SqliteConnectOptions};
+        use std::time::Duration;
+        use zino_core::error::ErrorKind;
+
+        let pool = PoolOptions::<sqlx::Sqlite>::new()
+            .max_connections(1)
+            .connect_with("sqlite::memory:".parse::<SqliteConnectOptions>().unwrap())
+            .await
+            .expect("fail to connect to the in-memory database");
+        let pool = ConnectionPool::new("main", "main", pool);
+        let sql = "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c) \
+            SELECT count(*) FROM c;";
+        let result =
+            super::with_statement_timeout(Some(Duration::from_millis(50)), pool.fetch(sql)).await;
+        assert!(result.is_err_and(|err| err.kind() == &ErrorKind::Timeout));
+
+        let rows =
+            super::with_statement_timeout(Some(Duration::from_secs(1)), pool.fetch("SELECT 1;"))
+                .await
+                .expect("fail to execute the statement");
+        assert_eq!(rows.len(), 1);
+    }
+}
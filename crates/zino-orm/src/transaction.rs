@@ -1,15 +1,17 @@
 use super::{
-    executor::Executor, mutation::MutationExt, query::QueryExt, schema::Schema, DatabaseDriver,
-    EncodeColumn,
+    column::ColumnExt, executor::Executor, mutation::MutationExt, query::QueryExt,
+    savepoint::Savepoint, schema::Schema, DatabaseDriver, EncodeColumn,
 };
 use std::fmt::Display;
 use zino_core::{
     error::Error,
-    extension::JsonValueExt,
     model::{Mutation, Query},
     BoxFuture, Map,
 };
 
+#[cfg(feature = "orm-sqlx")]
+use super::conflict::resolve_unique_violation;
+
 #[cfg(feature = "orm-sqlx")]
 use sqlx::Acquire;
 
@@ -66,6 +68,10 @@ where
     where
         F: for<'t> FnOnce(&'t mut Tx) -> BoxFuture<'t, Result<T, Error>>;
 
+    /// Creates a savepoint inside of the transaction.
+    /// The savepoint names are made unique, so the same name can be used repeatedly.
+    async fn savepoint<'t>(tx: &'t mut Tx, name: &str) -> Result<Savepoint<'t, Tx>, Error>;
+
     /// Executes the queries sequentially inside of a transaction.
     /// If it returns an error, the transaction will be rolled back;
     /// if not, the transaction will be committed.
@@ -74,6 +80,13 @@ where
     /// Inserts the model and its associations inside of a transaction.
     async fn transactional_insert<M: Schema>(self, models: Vec<M>) -> Result<u64, Error>;
 
+    /// Inserts the models inside of a transaction. Each model is inserted in a savepoint,
+    /// so that a failed insertion is rolled back while the others are committed.
+    /// It returns the number of rows inserted and the errors with the indexes of the failed models.
+    async fn transactional_try_insert_many(
+        models: Vec<Self>,
+    ) -> Result<(u64, Vec<(usize, Error)>), Error>;
+
     /// Updates the models inside of a transaction.
     async fn transactional_update<M: Schema>(
         queries: (&Query, &Query),
@@ -102,6 +115,14 @@ where
         Ok(data)
     }
 
+    #[inline]
+    async fn savepoint<'t>(
+        tx: &'t mut sqlx::Transaction<'c, DatabaseDriver>,
+        name: &str,
+    ) -> Result<Savepoint<'t, sqlx::Transaction<'c, DatabaseDriver>>, Error> {
+        Savepoint::new(tx, name).await
+    }
+
     async fn transactional_execute(queries: &[&str], params: Option<&Map>) -> Result<u64, Error> {
         let mut transaction = Self::acquire_writer().await?.pool().begin().await?;
         let connection = transaction.acquire().await?;
@@ -112,16 +133,13 @@ where
             let mut ctx = Self::before_scan(&sql).await?;
             ctx.set_query(sql);
 
-            let mut arguments = values
-                .iter()
-                .map(|v| v.to_string_unquoted())
-                .collect::<Vec<_>>();
+            let mut arguments = values.into_iter().cloned().collect::<Vec<_>>();
             let rows_affected = connection
-                .execute_with(ctx.query(), &arguments)
+                .execute_with_values(ctx.query(), &arguments)
                 .await?
                 .rows_affected();
             total_rows += rows_affected;
-            ctx.append_arguments(&mut arguments);
+            ctx.append_argument_values(&mut arguments);
             ctx.set_query_result(rows_affected, true);
             Self::after_scan(&ctx).await?;
         }
@@ -147,7 +165,7 @@ where
                 } else {
                     let name = col.name();
                     fields.push(name);
-                    Some(col.encode_value(map.get(name)))
+                    Some(col.encode_value(col.get_value(&map)))
                 }
             })
             .collect::<Vec<_>>()
@@ -178,7 +196,7 @@ where
             let map = association.into_map();
             let entries = columns
                 .iter()
-                .map(|col| col.encode_value(map.get(col.name())))
+                .map(|col| col.encode_value(col.get_value(&map)))
                 .collect::<Vec<_>>()
                 .join(", ");
             values.push(format!("({entries})"));
@@ -201,6 +219,42 @@ where
         Ok(total_rows)
     }
 
+    async fn transactional_try_insert_many(
+        models: Vec<Self>,
+    ) -> Result<(u64, Vec<(usize, Error)>), Error> {
+        let mut transaction = Self::acquire_writer().await?.pool().begin().await?;
+        let mut total_rows = 0;
+        let mut failures = Vec::new();
+        for (index, mut model) in models.into_iter().enumerate() {
+            let model_data = model.before_insert().await?;
+            let mut ctx = model.prepare_insert().await?;
+            let mut savepoint = Savepoint::new(&mut transaction, "insert").await?;
+            match savepoint.execute(ctx.query()).await {
+                Ok(query_result) => {
+                    savepoint.release().await?;
+
+                    let (last_insert_id, rows_affected) = Query::parse_query_result(query_result);
+                    let success = rows_affected == 1;
+                    if let Some(last_insert_id) = last_insert_id {
+                        ctx.set_last_insert_id(last_insert_id);
+                    }
+                    total_rows += rows_affected;
+                    ctx.set_query_result(rows_affected, success);
+                    Self::after_scan(&ctx).await?;
+                    Self::after_insert(&ctx, model_data).await?;
+                }
+                Err(err) => {
+                    savepoint.rollback_to().await?;
+                    failures.push((index, resolve_unique_violation::<Self>(err)));
+                }
+            }
+        }
+
+        // Commits the transaction
+        transaction.commit().await?;
+        Ok((total_rows, failures))
+    }
+
     async fn transactional_update<S: Schema>(
         queries: (&Query, &Query),
         mutations: (&mut Mutation, &mut Mutation),
@@ -213,14 +267,19 @@ where
         Self::before_mutation(query, mutation).await?;
 
         let table_name = query.format_table_name::<Self>();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let updates = mutation.format_updates::<Self>();
         let sql = format!("UPDATE {table_name} SET {updates} {filters};");
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql);
+        ctx.append_argument_values(&mut arguments);
 
         let mut total_rows = 0;
-        let rows_affected = connection.execute(ctx.query()).await?.rows_affected();
+        let rows_affected = connection
+            .execute_with_values(ctx.query(), ctx.argument_values())
+            .await?
+            .rows_affected();
         total_rows += rows_affected;
         ctx.set_query_result(rows_affected, true);
         Self::after_scan(&ctx).await?;
@@ -231,13 +290,18 @@ where
         S::before_mutation(query, mutation).await?;
 
         let table_name = query.format_table_name::<S>();
-        let filters = query.format_filters::<S>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<S>(&mut arguments);
         let updates = mutation.format_updates::<S>();
         let sql = format!("UPDATE {table_name} SET {updates} {filters};");
         let mut ctx = S::before_scan(&sql).await?;
         ctx.set_query(sql);
+        ctx.append_argument_values(&mut arguments);
 
-        let rows_affected = connection.execute(ctx.query()).await?.rows_affected();
+        let rows_affected = connection
+            .execute_with_values(ctx.query(), ctx.argument_values())
+            .await?
+            .rows_affected();
         total_rows += rows_affected;
         ctx.set_query_result(rows_affected, true);
         S::after_scan(&ctx).await?;
@@ -256,13 +320,18 @@ where
         Self::before_query(query).await?;
 
         let table_name = query.format_table_name::<Self>();
-        let filters = query.format_filters::<Self>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<Self>(&mut arguments);
         let sql = format!("DELETE FROM {table_name} {filters};");
         let mut ctx = Self::before_scan(&sql).await?;
         ctx.set_query(sql);
+        ctx.append_argument_values(&mut arguments);
 
         let mut total_rows = 0;
-        let rows_affected = connection.execute(ctx.query()).await?.rows_affected();
+        let rows_affected = connection
+            .execute_with_values(ctx.query(), ctx.argument_values())
+            .await?
+            .rows_affected();
         total_rows += rows_affected;
         ctx.set_query_result(rows_affected, true);
         Self::after_scan(&ctx).await?;
@@ -272,12 +341,17 @@ where
         S::before_query(query).await?;
 
         let table_name = query.format_table_name::<S>();
-        let filters = query.format_filters::<S>();
+        let mut arguments = Vec::new();
+        let filters = query.format_filters::<S>(&mut arguments);
         let sql = format!("DELETE FROM {table_name} {filters};");
         let mut ctx = S::before_scan(&sql).await?;
         ctx.set_query(sql);
+        ctx.append_argument_values(&mut arguments);
 
-        let rows_affected = connection.execute(ctx.query()).await?.rows_affected();
+        let rows_affected = connection
+            .execute_with_values(ctx.query(), ctx.argument_values())
+            .await?
+            .rows_affected();
         total_rows += rows_affected;
         ctx.set_query_result(rows_affected, true);
         S::after_scan(&ctx).await?;
@@ -0,0 +1,243 @@
+use super::{executor::Executor, Schema};
+use std::time::Duration;
+use toml::value::Table;
+use zino_core::{
+    extension::TomlTableExt,
+    schedule::{AsyncJob, JobContext},
+    state::State,
+    BoxFuture, LazyLock,
+};
+
+/// Cleanup of the expired rows for the models with a `#[schema(expires_at)]` column.
+///
+/// It can be configured in the `[database.ttl]` table:
+///
+/// ```toml
+/// [database.ttl]
+/// batch-size = 1000
+/// max-batches = 100
+/// interval = "5m"
+/// ```
+///
+/// Each batch is deleted by a single bounded statement,
+/// so that the table will not be locked for a long time.
+/// The interval should divide a minute, an hour or a day evenly, and it can not exceed a day.
+///
+/// When the `[database.ttl]` table is present, the cleanup job of a model is registered
+/// with the [`MaintenanceScheduler`](crate::MaintenanceScheduler) once the table
+/// of the model has been initialized.
+#[derive(Debug, Clone, Copy)]
+pub struct ExpiryCleanup {
+    /// Max number of rows deleted in a batch.
+    batch_size: usize,
+    /// Max number of batches in a run.
+    max_batches: usize,
+    /// Interval between the runs.
+    interval: Duration,
+}
+
+impl ExpiryCleanup {
+    /// Creates a new instance with the default settings.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            batch_size: 1000,
+            max_batches: 100,
+            interval: Duration::from_secs(5 * 60),
+        }
+    }
+
+    /// Returns a reference to the shared settings.
+    #[inline]
+    pub fn shared() -> &'static Self {
+        &SHARED_EXPIRY_CLEANUP
+    }
+
+    /// Returns the max number of rows deleted in a batch.
+    #[inline]
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Returns the max number of batches in a run.
+    #[inline]
+    pub fn max_batches(&self) -> usize {
+        self.max_batches
+    }
+
+    /// Returns the interval between the runs.
+    #[inline]
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Creates an async job which deletes the expired rows of the model in batches.
+    /// It is only required for a custom scheduler, since the job is registered automatically
+    /// if the `[database.ttl]` table is present.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use zino_orm::ExpiryCleanup;
+    ///
+    /// let mut scheduler = AsyncJobScheduler::new();
+    /// scheduler.add(ExpiryCleanup::job::<VerificationCode>());
+    /// ```
+    pub fn job<M: Schema>() -> AsyncJob {
+        let cron_expr = interval_cron_expr(Self::shared().interval);
+        let job_name = format!("{}_expiry_cleanup", M::model_name());
+        AsyncJob::new(&cron_expr, delete_expired_rows::<M>).name(job_name)
+    }
+}
+
+impl Default for ExpiryCleanup {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Deletes the expired rows of the model in batches.
+fn delete_expired_rows<M: Schema>(_ctx: &mut JobContext) -> BoxFuture<'_> {
+    Box::pin(async {
+        let model_name = M::model_name();
+        let cleanup = ExpiryCleanup::shared();
+        let batch_size = cleanup.batch_size;
+        let sql = match M::prepare_delete_expired(batch_size) {
+            Ok(sql) => sql,
+            Err(err) => {
+                tracing::error!(model_name, "{err}");
+                return;
+            }
+        };
+        let pool = match M::init_writer() {
+            Ok(cp) => cp.pool(),
+            Err(err) => {
+                tracing::error!(model_name, "{err}");
+                return;
+            }
+        };
+
+        let mut total_rows = 0;
+        for _ in 0..cleanup.max_batches {
+            match pool.execute(&sql).await {
+                Ok(query_result) => {
+                    let rows = query_result.rows_affected();
+                    total_rows += rows;
+                    if rows < batch_size as u64 {
+                        break;
+                    }
+                }
+                Err(err) => {
+                    tracing::error!(model_name, "fail to delete expired rows: {err}");
+                    break;
+                }
+            }
+        }
+        if total_rows > 0 {
+            tracing::info!(model_name, total_rows, "expired rows have been deleted");
+        }
+    })
+}
+
+/// Parses the interval for the key in the config table.
+/// The values which can not be scheduled evenly are rejected with a warning.
+pub(super) fn parse_interval(config: &Table, key: &str) -> Option<Duration> {
+    let interval = config.get_duration(key)?;
+    if is_even_interval(interval) {
+        Some(interval)
+    } else {
+        tracing::warn!(
+            "the interval `{key}` should divide a minute, an hour or a day evenly, \
+                and it can not exceed a day: {interval:?}"
+        );
+        None
+    }
+}
+
+/// Returns `true` if the interval divides a minute, an hour or a day evenly.
+fn is_even_interval(interval: Duration) -> bool {
+    let secs = interval.as_secs();
+    if secs == 0 {
+        false
+    } else if secs < 60 {
+        60 % secs == 0
+    } else if secs < 60 * 60 {
+        secs % 60 == 0 && 60 % (secs / 60) == 0
+    } else if secs < 24 * 60 * 60 {
+        secs % 3600 == 0 && 24 % (secs / 3600) == 0
+    } else {
+        secs == 24 * 60 * 60
+    }
+}
+
+/// Formats a cron expression for the interval, which should have been checked by
+/// [`parse_interval()`].
+pub(super) fn interval_cron_expr(interval: Duration) -> String {
+    let secs = interval.as_secs().max(1);
+    if secs < 60 {
+        format!("*/{secs} * * * * *")
+    } else if secs < 60 * 60 {
+        format!("0 */{} * * * *", secs / 60)
+    } else if secs < 24 * 60 * 60 {
+        format!("0 0 */{} * * *", secs / 3600)
+    } else {
+        "0 0 0 * * *".to_owned()
+    }
+}
+
+/// Shared settings for the cleanup of expired rows.
+static SHARED_EXPIRY_CLEANUP: LazyLock<ExpiryCleanup> = LazyLock::new(|| {
+    let mut cleanup = ExpiryCleanup::new();
+    if let Some(config) = State::shared()
+        .get_config("database")
+        .and_then(|config| config.get_table("ttl"))
+    {
+        if let Some(batch_size) = config.get_usize("batch-size") {
+            cleanup.batch_size = batch_size.max(1);
+        }
+        if let Some(max_batches) = config.get_usize("max-batches") {
+            cleanup.max_batches = max_batches.max(1);
+        }
+        if let Some(interval) = parse_interval(config, "interval") {
+            cleanup.interval = interval;
+        }
+    }
+    cleanup
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{interval_cron_expr, is_even_interval};
+    use std::time::Duration;
+
+    #[test]
+    fn it_formats_interval_cron_expr() {
+        assert_eq!(
+            interval_cron_expr(Duration::from_secs(30)),
+            "*/30 * * * * *"
+        );
+        assert_eq!(
+            interval_cron_expr(Duration::from_secs(5 * 60)),
+            "0 */5 * * * *"
+        );
+        assert_eq!(
+            interval_cron_expr(Duration::from_secs(2 * 3600)),
+            "0 0 */2 * * *"
+        );
+        assert_eq!(
+            interval_cron_expr(Duration::from_secs(86400)),
+            "0 0 0 * * *"
+        );
+    }
+
+    #[test]
+    fn it_rejects_uneven_intervals() {
+        for secs in [1, 15, 60, 10 * 60, 3600, 6 * 3600, 86400] {
+            assert!(is_even_interval(Duration::from_secs(secs)), "{secs}s");
+        }
+        for secs in [0, 7, 90, 7 * 60, 45 * 60, 5 * 3600, 3600 + 60, 2 * 86400] {
+            assert!(!is_even_interval(Duration::from_secs(secs)), "{secs}s");
+        }
+    }
+}
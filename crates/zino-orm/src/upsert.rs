@@ -0,0 +1,383 @@
+use super::{
+    column::ColumnExt, conflict::resolve_unique_violation, query::QueryExt, DecodeRow,
+    EncodeColumn, Executor, Schema,
+};
+use sqlx::Acquire;
+use std::{collections::HashMap, sync::atomic::Ordering::Relaxed};
+use zino_core::{
+    bail,
+    error::Error,
+    extension::JsonValueExt,
+    model::{Query, QueryContext},
+    Map,
+};
+
+/// Outcome of an upsert keyed by a unique column.
+///
+/// It can be used to link the child records since the primary key is resolved
+/// to the existing one on conflict.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpsertOutcome {
+    /// Primary key of the inserted or updated row.
+    primary_key: String,
+    /// A flag to indicate whether the row is inserted.
+    inserted: bool,
+}
+
+impl UpsertOutcome {
+    /// Returns the primary key of the inserted or updated row.
+    #[inline]
+    pub fn primary_key(&self) -> &str {
+        &self.primary_key
+    }
+
+    /// Parses the primary key as `T`.
+    #[inline]
+    pub fn parse_primary_key<T: std::str::FromStr>(&self) -> Result<T, T::Err> {
+        self.primary_key.parse()
+    }
+
+    /// Returns `true` if the row is newly inserted.
+    #[inline]
+    pub fn is_inserted(&self) -> bool {
+        self.inserted
+    }
+
+    /// Returns `true` if the existing row is updated.
+    #[inline]
+    pub fn is_updated(&self) -> bool {
+        !self.inserted
+    }
+}
+
+/// Checks that the column can be used as the conflict target,
+/// which requires a unique index on the single column without a predicate.
+pub(super) fn check_conflict_target<M: Schema>(column: &str) -> Result<(), Error> {
+    let model_name = M::model_name();
+    if M::get_column(column).is_none() {
+        bail!(
+            "the model `{}` does not have a column `{}`",
+            model_name,
+            column
+        );
+    }
+    if column == M::PRIMARY_KEY_NAME {
+        return Ok(());
+    }
+
+    let has_unique_index = M::indexes().iter().any(|index| {
+        index.is_unique()
+            && index.predicate().is_none()
+            && !index.is_case_insensitive()
+            && index.column_names() == [column]
+    });
+    if !has_unique_index {
+        bail!(
+            "the column `{}` of the model `{}` should have a unique index to be the conflict target",
+            column,
+            model_name
+        );
+    }
+    Ok(())
+}
+
+/// Updates or inserts the rows keyed by a unique column, and returns the outcomes
+/// in the same order as the rows.
+pub(super) async fn upsert_by<M: Schema>(
+    maps: Vec<Map>,
+    column: &str,
+) -> Result<(QueryContext, Vec<UpsertOutcome>), Error> {
+    super::schema::check_writable::<M>()?;
+    if maps.is_empty() {
+        bail!("the list of models to be upserted should be nonempty");
+    }
+
+    // Primary keys and the conflict keys of the rows
+    let primary_key_name = M::PRIMARY_KEY_NAME;
+    let mut keys = Vec::with_capacity(maps.len());
+    for map in maps.iter() {
+        let Some(key) = map.get(column).filter(|v| !v.is_null()) else {
+            bail!("the conflict target `{}` should not be null", column);
+        };
+        let key = key.to_string_unquoted();
+        if keys.iter().any(|(_, k)| k == &key) {
+            bail!(
+                "the conflict target `{}` has a duplicate value `{}` in the batch",
+                column,
+                key
+            );
+        }
+
+        let primary_key = map
+            .get(primary_key_name)
+            .map(|v| v.to_string_unquoted())
+            .unwrap_or_default();
+        keys.push((primary_key, key));
+    }
+
+    let sql = format_upsert_by::<M>(&maps, column);
+    let mut ctx = M::before_scan(&sql).await?;
+    ctx.set_query(sql);
+    if cfg!(debug_assertions) && super::DEBUG_ONLY.load(Relaxed) {
+        ctx.cancel();
+    }
+    if ctx.is_cancelled() {
+        return Ok((ctx, Vec::new()));
+    }
+
+    let rows = if cfg!(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb"
+    )) {
+        // MySQL does not support the `RETURNING` clause,
+        // so the primary keys are selected in the same transaction.
+        let col = M::get_column(column).expect("the column should exist");
+        let values = maps
+            .iter()
+            .map(|map| col.encode_value(map.get(column)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let table_name = Query::table_name_escaped::<M>();
+        let primary_key = Query::format_field(primary_key_name);
+        let field = Query::format_field(column);
+        let select_sql =
+            format!("SELECT {primary_key}, {field} FROM {table_name} WHERE {field} IN ({values});");
+
+        let pool = M::acquire_writer().await?.pool();
+        let mut transaction = pool.begin().await?;
+        let connection = transaction.acquire().await?;
+        connection
+            .execute(ctx.query())
+            .await
+            .map_err(resolve_unique_violation::<M>)?;
+        let rows = connection.fetch(&select_sql).await?;
+        transaction.commit().await?;
+        rows
+    } else {
+        let pool = M::acquire_writer().await?;
+        pool.fetch(ctx.query())
+            .await
+            .map_err(resolve_unique_violation::<M>)?
+    };
+
+    let mut primary_keys = HashMap::with_capacity(rows.len());
+    for row in rows.iter() {
+        let data = Map::decode_row(row)?;
+        if let (Some(primary_key), Some(key)) = (data.get(primary_key_name), data.get(column)) {
+            primary_keys.insert(key.to_string_unquoted(), primary_key.to_string_unquoted());
+        }
+    }
+
+    let mut outcomes = Vec::with_capacity(keys.len());
+    for (primary_key, key) in keys {
+        let Some(existing_primary_key) = primary_keys.remove(&key) else {
+            bail!(
+                "fail to resolve the primary key for `{}` = `{}`",
+                column,
+                key
+            );
+        };
+        outcomes.push(UpsertOutcome {
+            inserted: existing_primary_key == primary_key,
+            primary_key: existing_primary_key,
+        });
+    }
+    ctx.set_query_result(u64::try_from(outcomes.len()).unwrap_or_default(), true);
+    Ok((ctx, outcomes))
+}
+
+/// Formats the SQL to update or insert the rows keyed by a unique column.
+/// All the writable columns are updated on conflict except for the primary key,
+/// the conflict target and `created_at`.
+fn format_upsert_by<M: Schema>(maps: &[Map], column: &str) -> String {
+    let primary_key_name = M::PRIMARY_KEY_NAME;
+    let read_only_fields = M::read_only_fields();
+    let columns = M::columns();
+    let values = maps
+        .iter()
+        .map(|map| {
+            let entries = columns
+                .iter()
+                .map(|col| col.encode_value(col.get_value(map)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("({entries})")
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let is_mysql = cfg!(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-tidb"
+    ));
+    let mut fields = Vec::with_capacity(columns.len());
+    let mut mutations = Vec::with_capacity(columns.len());
+    for col in columns {
+        let name = col.name();
+        let field = Query::format_field(name);
+        if name != primary_key_name
+            && name != column
+            && name != "created_at"
+            && !read_only_fields.contains(&name)
+        {
+            let mutation = if is_mysql {
+                format!("{field} = VALUES({field})")
+            } else {
+                format!("{field} = excluded.{field}")
+            };
+            mutations.push(mutation);
+        }
+        fields.push(field);
+    }
+
+    let table_name = Query::table_name_escaped::<M>();
+    let fields = fields.join(", ");
+    let conflict_target = Query::format_field(column);
+    if mutations.is_empty() {
+        // Updates the conflict target to itself so that the existing row is still returned.
+        if is_mysql {
+            mutations.push(format!("{conflict_target} = VALUES({conflict_target})"));
+        } else {
+            mutations.push(format!("{conflict_target} = excluded.{conflict_target}"));
+        }
+    }
+
+    let mutations = mutations.join(", ");
+    if is_mysql {
+        format!(
+            "INSERT INTO {table_name} ({fields}) VALUES {values} \
+                ON DUPLICATE KEY UPDATE {mutations};"
+        )
+    } else {
+        // Both PostgreQL and SQLite (3.35+) support this syntax.
+        let primary_key = Query::format_field(primary_key_name);
+        format!(
+            "INSERT INTO {table_name} ({fields}) VALUES {values} \
+                ON CONFLICT ({conflict_target}) DO UPDATE SET {mutations} \
+                RETURNING {primary_key}, {conflict_target};"
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_conflict_target;
+    use crate::ConnectionPool;
+    use serde::{Deserialize, Serialize};
+    use std::sync::OnceLock;
+
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    struct Product {
+        id: i64,
+        external_id: String,
+        name: String,
+    }
+
+    static PRODUCTS_POOL: OnceLock<ConnectionPool> = OnceLock::new();
+
+    impl_test_schema!(Product, ProductColumn, "product", "products", [
+        Id => ("id", "i64"),
+        ExternalId => ("external_id", "String"),
+        Name => ("name", "String"),
+    ], pool = PRODUCTS_POOL);
+
+    #[test]
+    fn it_checks_conflict_targets() {
+        assert!(check_conflict_target::<Product>("id").is_ok());
+        assert!(check_conflict_target::<Product>("sku").is_err());
+
+        // The column does not have a unique index.
+        assert!(check_conflict_target::<Product>("external_id").is_err());
+    }
+
+    // The test runs against an in-memory database when SQLite is the driver.
+    #[cfg(not(any(
+        feature = "orm-mariadb",
+        feature = "orm-mysql",
+        feature = "orm-postgres",
+        feature = "orm-tidb"
+    )))]
+    #[tokio::test]
+    async fn it_upserts_by_unique_columns() {
+        use super::upsert_by;
+        use crate::Executor;
+        use sqlx::{pool::PoolOptions, sqlite::SqliteConnectOptions, Row};
+        use zino_core::{json, Map};
+
+        let product = |id: i64, external_id: &str, name: &str| -> Map {
+            let product = json!({ "id": id, "external_id": external_id, "name": name });
+            product.as_object().cloned().unwrap_or_default()
+        };
+
+        let pool = PoolOptions::<sqlx::Sqlite>::new()
+            .max_connections(1)
+            .connect_with("sqlite::memory:".parse::<SqliteConnectOptions>().unwrap())
+            .await
+            .expect("fail to connect to the in-memory database");
+        let pool = PRODUCTS_POOL.get_or_init(|| ConnectionPool::new("main", "main", pool));
+        pool.execute(
+            "CREATE TABLE products (id INTEGER PRIMARY KEY, \
+                external_id TEXT NOT NULL UNIQUE, name TEXT NOT NULL);",
+        )
+        .await
+        .expect("fail to prepare the table");
+
+        let (_, outcomes) = upsert_by::<Product>(
+            vec![product(1, "ext-1", "apple"), product(2, "ext-2", "banana")],
+            "external_id",
+        )
+        .await
+        .unwrap();
+        assert!(outcomes.iter().all(|outcome| outcome.is_inserted()));
+        assert_eq!(outcomes[0].parse_primary_key::<i64>(), Ok(1));
+        assert_eq!(outcomes[1].parse_primary_key::<i64>(), Ok(2));
+
+        // The primary key is resolved to the existing one on conflict,
+        // and the outcomes are in the same order as the rows.
+        let (_, outcomes) = upsert_by::<Product>(
+            vec![
+                product(3, "ext-3", "cherry"),
+                product(4, "ext-2", "blueberry"),
+            ],
+            "external_id",
+        )
+        .await
+        .unwrap();
+        assert!(outcomes[0].is_inserted());
+        assert_eq!(outcomes[0].primary_key(), "3");
+        assert!(outcomes[1].is_updated());
+        assert_eq!(outcomes[1].primary_key(), "2");
+
+        let rows = pool
+            .fetch("SELECT id, external_id, name FROM products ORDER BY id;")
+            .await
+            .unwrap();
+        let products = rows
+            .iter()
+            .map(|row| (row.get::<i64, _>(0), row.get::<String, _>(2)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            products,
+            [
+                (1, "apple".to_owned()),
+                (2, "blueberry".to_owned()),
+                (3, "cherry".to_owned())
+            ]
+        );
+
+        // The conflict targets should be present and distinct in the batch.
+        let rows = vec![product(5, "ext-5", "date"), product(6, "ext-5", "durian")];
+        assert!(upsert_by::<Product>(rows, "external_id").await.is_err());
+
+        let mut row = product(7, "", "elderberry");
+        row.remove("external_id");
+        assert!(upsert_by::<Product>(vec![row], "external_id")
+            .await
+            .is_err());
+        assert!(upsert_by::<Product>(Vec::new(), "external_id")
+            .await
+            .is_err());
+    }
+}
@@ -5,9 +5,17 @@ use url::Url;
 use zino_core::{
     datetime::{Date, DateTime, Time},
     extension::JsonObjectExt,
-    Decimal, JsonValue, Map, Uuid,
+    money::Money,
+    validation::WebUrl,
+    Decimal, JsonValue, Map, Ulid, Uuid,
 };
 
+#[cfg(feature = "validator-email")]
+use zino_core::validation::EmailAddress;
+
+#[cfg(feature = "validator-phone-number")]
+use zino_core::validation::PhoneNumber;
+
 /// A generic interface for converting into SQL values.
 pub trait IntoSqlValue {
     /// Converts `self` to a SQL value.
@@ -82,6 +90,13 @@ impl IntoSqlValue for Uuid {
     }
 }
 
+impl IntoSqlValue for Ulid {
+    #[inline]
+    fn into_sql_value(self) -> JsonValue {
+        self.to_string().into()
+    }
+}
+
 impl IntoSqlValue for &Path {
     #[inline]
     fn into_sql_value(self) -> JsonValue {
@@ -103,6 +118,36 @@ impl IntoSqlValue for &Url {
     }
 }
 
+impl IntoSqlValue for Money {
+    #[inline]
+    fn into_sql_value(self) -> JsonValue {
+        self.into()
+    }
+}
+
+impl IntoSqlValue for WebUrl {
+    #[inline]
+    fn into_sql_value(self) -> JsonValue {
+        self.into()
+    }
+}
+
+#[cfg(feature = "validator-email")]
+impl IntoSqlValue for EmailAddress {
+    #[inline]
+    fn into_sql_value(self) -> JsonValue {
+        self.into()
+    }
+}
+
+#[cfg(feature = "validator-phone-number")]
+impl IntoSqlValue for PhoneNumber {
+    #[inline]
+    fn into_sql_value(self) -> JsonValue {
+        self.into()
+    }
+}
+
 impl IntoSqlValue for Cow<'_, str> {
     #[inline]
     fn into_sql_value(self) -> JsonValue {
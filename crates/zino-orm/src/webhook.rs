@@ -0,0 +1,1147 @@
+use super::{
+    decode, executor::Executor, query::QueryExt, DatabaseRow, DecodeRow, GlobalPool, ModelHelper,
+    Schema,
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering::Relaxed},
+        RwLock,
+    },
+    time::{Duration, Instant},
+};
+use url::Url;
+use zino_core::{
+    application::Agent,
+    bail, crypto,
+    datetime::DateTime,
+    encoding::hex,
+    error::Error,
+    extension::{JsonObjectExt, TomlTableExt},
+    model::Query,
+    schedule::{AsyncJob, JobContext, OverlapPolicy},
+    state::State,
+    warn, BoxFuture, JsonValue, LazyLock, Map, Uuid,
+};
+
+/// A webhook subscription persisted in the `_zino_webhook_subscriptions` table.
+///
+/// A subscription receives the events of the models and actions matched by the filters,
+/// where an empty filter or `*` matches everything. The events are signed with the secret
+/// of the subscription, see [`Webhook`] for the details of the delivery.
+/// The status is `active` or `disabled`, and an active subscription is disabled
+/// automatically after sustained failures.
+#[derive(Debug, Clone, Default)]
+pub struct WebhookSubscription {
+    /// Subscription ID.
+    id: String,
+    /// Target URL.
+    target_url: String,
+    /// Secret for signing the events.
+    secret: String,
+    /// Model names to subscribe.
+    models: Vec<String>,
+    /// Actions to subscribe.
+    actions: Vec<String>,
+    /// Status.
+    status: String,
+    /// Number of the consecutive failures.
+    consecutive_failures: u32,
+    /// Creation time in milliseconds.
+    created_at: i64,
+    /// Update time in milliseconds.
+    updated_at: i64,
+}
+
+impl WebhookSubscription {
+    /// Attempts to create a new instance with the `active` status and a random secret.
+    pub fn try_new(target_url: &str) -> Result<Self, Error> {
+        let url = target_url.parse::<Url>()?;
+        if !matches!(url.scheme(), "http" | "https") {
+            bail!("invalid target URL `{}` for the webhook", target_url);
+        }
+
+        let now = DateTime::now().timestamp_millis();
+        Ok(Self {
+            id: Uuid::now_v7().to_string(),
+            target_url: url.into(),
+            secret: ["whsec_", &Uuid::new_v4().simple().to_string()].concat(),
+            models: Vec::new(),
+            actions: Vec::new(),
+            status: "active".to_owned(),
+            consecutive_failures: 0,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    /// Sets the secret for signing the events.
+    #[inline]
+    pub fn secret(mut self, secret: impl Into<String>) -> Self {
+        self.secret = secret.into();
+        self
+    }
+
+    /// Sets the model names to subscribe.
+    #[inline]
+    pub fn models(mut self, models: Vec<String>) -> Self {
+        self.models = models;
+        self
+    }
+
+    /// Sets the actions to subscribe.
+    #[inline]
+    pub fn actions(mut self, actions: Vec<String>) -> Self {
+        self.actions = actions;
+        self
+    }
+
+    /// Returns the subscription ID.
+    #[inline]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the target URL.
+    #[inline]
+    pub fn target_url(&self) -> &str {
+        &self.target_url
+    }
+
+    /// Returns the secret for signing the events.
+    #[inline]
+    pub fn get_secret(&self) -> &str {
+        &self.secret
+    }
+
+    /// Returns the status.
+    #[inline]
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    /// Returns `true` if the subscription is active.
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.status == "active"
+    }
+
+    /// Returns `true` if the subscription matches the model and action.
+    pub fn matches(&self, model_name: &str, action: &str) -> bool {
+        let matches_filter = |filter: &[String], value: &str| {
+            filter.is_empty() || filter.iter().any(|s| s == "*" || s == value)
+        };
+        matches_filter(&self.models, model_name) && matches_filter(&self.actions, action)
+    }
+
+    /// Signs the payload with the timestamp in seconds.
+    /// The signature is in the form `v1={hex}`, where the hex string is the HMAC
+    /// of `{timestamp}.{payload}` with the secret.
+    pub fn sign(&self, timestamp: i64, payload: &str) -> String {
+        let message = format!("{timestamp}.{payload}");
+        let signature = crypto::sign(message.as_bytes(), self.secret.as_bytes());
+        ["v1=", &hex::encode(signature)].concat()
+    }
+
+    /// Converts `self` to a JSON object. The secret is omitted.
+    pub fn to_map(&self) -> Map {
+        let mut map = Map::new();
+        map.insert("id".to_owned(), self.id.clone().into());
+        map.insert("target_url".to_owned(), self.target_url.clone().into());
+        map.insert("models".to_owned(), self.models.clone().into());
+        map.insert("actions".to_owned(), self.actions.clone().into());
+        map.insert("status".to_owned(), self.status.clone().into());
+        map.insert(
+            "consecutive_failures".to_owned(),
+            self.consecutive_failures.into(),
+        );
+        map.insert(
+            "created_at".to_owned(),
+            DateTime::from_timestamp_millis(self.created_at).into(),
+        );
+        map.insert(
+            "updated_at".to_owned(),
+            DateTime::from_timestamp_millis(self.updated_at).into(),
+        );
+        map
+    }
+
+    /// Inserts the subscription into the table.
+    pub async fn insert(&self) -> Result<(), Error> {
+        create_tables().await?;
+
+        let id = Query::escape_string(&self.id);
+        let target_url = Query::escape_string(&self.target_url);
+        let secret = Query::escape_string(&self.secret);
+        let models = Query::escape_string(self.models.join(","));
+        let actions = Query::escape_string(self.actions.join(","));
+        let status = Query::escape_string(&self.status);
+        let created_at = self.created_at;
+        let updated_at = self.updated_at;
+        let sql = format!(
+            "INSERT INTO {SUBSCRIPTIONS_TABLE} \
+                (id, target_url, secret, models, actions, status, consecutive_failures, \
+                    created_at, updated_at) \
+                VALUES ({id}, {target_url}, {secret}, {models}, {actions}, {status}, 0, \
+                    {created_at}, {updated_at});"
+        );
+        connection_pool()?.pool().execute(&sql).await?;
+        invalidate_cache();
+        Ok(())
+    }
+
+    /// Lists all the subscriptions.
+    pub async fn list() -> Result<Vec<Self>, Error> {
+        create_tables().await?;
+
+        let sql = format!("SELECT * FROM {SUBSCRIPTIONS_TABLE} ORDER BY created_at ASC;");
+        let rows = connection_pool()?.pool().fetch(&sql).await?;
+        rows.iter().map(Self::decode_row).collect()
+    }
+
+    /// Finds a subscription by the ID.
+    pub async fn find_by_id(id: &str) -> Result<Option<Self>, Error> {
+        create_tables().await?;
+
+        let id = Query::escape_string(id);
+        let sql = format!("SELECT * FROM {SUBSCRIPTIONS_TABLE} WHERE id = {id};");
+        if let Some(row) = connection_pool()?.pool().fetch_optional(&sql).await? {
+            Self::decode_row(&row).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Sets the status of a subscription and resets the consecutive failures.
+    /// It returns `false` if the subscription does not exist.
+    pub async fn set_status(id: &str, status: &str) -> Result<bool, Error> {
+        if !matches!(status, "active" | "disabled") {
+            bail!("invalid status `{}` for the webhook subscription", status);
+        }
+        create_tables().await?;
+
+        let id = Query::escape_string(id);
+        let now = DateTime::now().timestamp_millis();
+        let sql = format!(
+            "UPDATE {SUBSCRIPTIONS_TABLE} SET status = '{status}', consecutive_failures = 0, \
+                updated_at = {now} WHERE id = {id};"
+        );
+        let query_result = connection_pool()?.pool().execute(&sql).await?;
+        invalidate_cache();
+        Ok(query_result.rows_affected() == 1)
+    }
+
+    /// Deletes a subscription by the ID and cancels the pending deliveries.
+    /// It returns `false` if the subscription does not exist.
+    pub async fn delete_by_id(id: &str) -> Result<bool, Error> {
+        create_tables().await?;
+
+        let pool = connection_pool()?.pool();
+        let id = Query::escape_string(id);
+        let sql = format!("DELETE FROM {SUBSCRIPTIONS_TABLE} WHERE id = {id};");
+        let query_result = pool.execute(&sql).await?;
+        let sql = format!(
+            "UPDATE {DELIVERIES_TABLE} SET status = 'cancelled' \
+                WHERE subscription_id = {id} AND status = 'pending';"
+        );
+        pool.execute(&sql).await?;
+        invalidate_cache();
+        Ok(query_result.rows_affected() == 1)
+    }
+}
+
+impl DecodeRow<DatabaseRow> for WebhookSubscription {
+    type Error = Error;
+
+    fn decode_row(row: &DatabaseRow) -> Result<Self, Self::Error> {
+        let split_filter = |filter: String| {
+            filter
+                .split(',')
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_owned())
+                .collect::<Vec<_>>()
+        };
+        Ok(Self {
+            id: decode(row, "id")?,
+            target_url: decode(row, "target_url")?,
+            secret: decode(row, "secret")?,
+            models: split_filter(decode(row, "models")?),
+            actions: split_filter(decode(row, "actions")?),
+            status: decode(row, "status")?,
+            consecutive_failures: decode::<i64>(row, "consecutive_failures")?.try_into()?,
+            created_at: decode(row, "created_at")?,
+            updated_at: decode(row, "updated_at")?,
+        })
+    }
+}
+
+/// A delivery of the webhook event persisted in the `_zino_webhook_deliveries` table.
+///
+/// The status transitions from `pending` to `delivered`, or ends up with `dead`
+/// after the maximum attempts. The pending deliveries of a deleted or disabled subscription
+/// are `cancelled`. The outcome of the last attempt is recorded.
+#[derive(Debug, Clone, Default)]
+pub struct WebhookDelivery {
+    /// Delivery ID.
+    id: String,
+    /// Subscription ID.
+    subscription_id: String,
+    /// Event ID.
+    event_id: String,
+    /// Event type.
+    event_type: String,
+    /// Serialized payload.
+    payload: String,
+    /// Status.
+    status: String,
+    /// Number of the attempts.
+    attempts: u32,
+    /// Time of the next attempt in milliseconds.
+    next_attempt_at: i64,
+    /// Status code of the last response.
+    status_code: Option<i64>,
+    /// Latency of the last attempt in milliseconds.
+    latency_ms: Option<i64>,
+    /// Truncated body of the last response.
+    response_snippet: Option<String>,
+    /// Error message of the last attempt.
+    last_error: Option<String>,
+    /// Creation time in milliseconds.
+    created_at: i64,
+    /// Delivery time in milliseconds.
+    delivered_at: Option<i64>,
+}
+
+impl WebhookDelivery {
+    /// Creates a new instance with the `pending` status.
+    fn new(subscription_id: &str, event_id: &str, event_type: &str, payload: String) -> Self {
+        let now = DateTime::now().timestamp_millis();
+        Self {
+            id: Uuid::now_v7().to_string(),
+            subscription_id: subscription_id.to_owned(),
+            event_id: event_id.to_owned(),
+            event_type: event_type.to_owned(),
+            payload,
+            status: "pending".to_owned(),
+            attempts: 0,
+            next_attempt_at: now,
+            status_code: None,
+            latency_ms: None,
+            response_snippet: None,
+            last_error: None,
+            created_at: now,
+            delivered_at: None,
+        }
+    }
+
+    /// Returns the delivery ID.
+    #[inline]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the event type.
+    #[inline]
+    pub fn event_type(&self) -> &str {
+        &self.event_type
+    }
+
+    /// Returns the status.
+    #[inline]
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    /// Returns `true` if the event has been delivered.
+    #[inline]
+    pub fn is_delivered(&self) -> bool {
+        self.status == "delivered"
+    }
+
+    /// Converts `self` to a JSON object.
+    pub fn to_map(&self) -> Map {
+        let mut map = Map::new();
+        map.insert("id".to_owned(), self.id.clone().into());
+        map.insert(
+            "subscription_id".to_owned(),
+            self.subscription_id.clone().into(),
+        );
+        map.insert("event_id".to_owned(), self.event_id.clone().into());
+        map.insert("event_type".to_owned(), self.event_type.clone().into());
+        map.insert("status".to_owned(), self.status.clone().into());
+        map.insert("attempts".to_owned(), self.attempts.into());
+        if let Some(status_code) = self.status_code {
+            map.insert("status_code".to_owned(), status_code.into());
+        }
+        if let Some(latency_ms) = self.latency_ms {
+            map.insert("latency_ms".to_owned(), latency_ms.into());
+        }
+        if let Some(response_snippet) = self.response_snippet.as_ref() {
+            map.insert(
+                "response_snippet".to_owned(),
+                response_snippet.clone().into(),
+            );
+        }
+        if let Some(last_error) = self.last_error.as_ref() {
+            map.insert("last_error".to_owned(), last_error.clone().into());
+        }
+        map.insert(
+            "created_at".to_owned(),
+            DateTime::from_timestamp_millis(self.created_at).into(),
+        );
+        if let Some(delivered_at) = self.delivered_at {
+            map.insert(
+                "delivered_at".to_owned(),
+                DateTime::from_timestamp_millis(delivered_at).into(),
+            );
+        }
+        map
+    }
+
+    /// Inserts the delivery into the table.
+    async fn insert(&self) -> Result<(), Error> {
+        let id = Query::escape_string(&self.id);
+        let subscription_id = Query::escape_string(&self.subscription_id);
+        let event_id = Query::escape_string(&self.event_id);
+        let event_type = Query::escape_string(&self.event_type);
+        let payload = Query::escape_string(&self.payload);
+        let status = Query::escape_string(&self.status);
+        let attempts = self.attempts;
+        let next_attempt_at = self.next_attempt_at;
+        let status_code = format_optional(self.status_code);
+        let latency_ms = format_optional(self.latency_ms);
+        let response_snippet =
+            format_optional(self.response_snippet.as_ref().map(Query::escape_string));
+        let last_error = format_optional(self.last_error.as_ref().map(Query::escape_string));
+        let created_at = self.created_at;
+        let delivered_at = format_optional(self.delivered_at);
+        let sql = format!(
+            "INSERT INTO {DELIVERIES_TABLE} \
+                (id, subscription_id, event_id, event_type, payload, status, attempts, \
+                    next_attempt_at, status_code, latency_ms, response_snippet, last_error, \
+                    created_at, delivered_at) \
+                VALUES ({id}, {subscription_id}, {event_id}, {event_type}, {payload}, {status}, \
+                    {attempts}, {next_attempt_at}, {status_code}, {latency_ms}, \
+                    {response_snippet}, {last_error}, {created_at}, {delivered_at});"
+        );
+        connection_pool()?.pool().execute(&sql).await?;
+        Ok(())
+    }
+
+    /// Records the outcome of an attempt.
+    fn record(&mut self, outcome: DeliveryOutcome) {
+        let now = DateTime::now().timestamp_millis();
+        self.attempts += 1;
+        self.status_code = outcome.status_code.map(i64::from);
+        self.latency_ms = Some(outcome.latency_ms);
+        self.response_snippet = outcome.response_snippet;
+        if let Some(err) = outcome.error {
+            self.last_error = Some(err);
+        } else {
+            self.status = "delivered".to_owned();
+            self.last_error = None;
+            self.delivered_at = Some(now);
+        }
+    }
+}
+
+impl DecodeRow<DatabaseRow> for WebhookDelivery {
+    type Error = Error;
+
+    fn decode_row(row: &DatabaseRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: decode(row, "id")?,
+            subscription_id: decode(row, "subscription_id")?,
+            event_id: decode(row, "event_id")?,
+            event_type: decode(row, "event_type")?,
+            payload: decode(row, "payload")?,
+            status: decode(row, "status")?,
+            attempts: decode::<i64>(row, "attempts")?.try_into()?,
+            next_attempt_at: decode(row, "next_attempt_at")?,
+            status_code: decode(row, "status_code")?,
+            latency_ms: decode(row, "latency_ms")?,
+            response_snippet: decode(row, "response_snippet")?,
+            last_error: decode(row, "last_error")?,
+            created_at: decode(row, "created_at")?,
+            delivered_at: decode(row, "delivered_at")?,
+        })
+    }
+}
+
+/// Outcome of a delivery attempt.
+#[derive(Debug)]
+struct DeliveryOutcome {
+    /// Status code of the response.
+    status_code: Option<u16>,
+    /// Latency in milliseconds.
+    latency_ms: i64,
+    /// Truncated body of the response.
+    response_snippet: Option<String>,
+    /// Error message if the attempt has failed.
+    error: Option<String>,
+}
+
+/// Webhooks for notifying the external systems when the models change.
+///
+/// The writes of [`Schema::insert()`] and the [`ModelAccessor`](crate::ModelAccessor)
+/// operations emit the events `create`, `update`, `soft_delete`, `lock`, `archive`
+/// and `delete`, which are enqueued as deliveries for the matching subscriptions.
+/// The event payload is a JSON object with the fields `id`, `type`, `model`, `action`,
+/// `created_at` and `data`, where the model data are redacted by
+/// [`ModelHelper::redact_model()`] so that the `write_only` and `private` columns
+/// are never sent out.
+///
+/// The [`delivery_job()`](Self::delivery_job) POSTs the payloads with the headers
+/// `x-webhook-id`, `x-webhook-timestamp` and `x-webhook-signature`,
+/// see [`WebhookSubscription::sign()`] for the signature. A delivery is successful
+/// if the response status is `2xx`, otherwise it is retried with exponential backoff
+/// until it is marked as `dead` after the maximum attempts. A subscription is disabled
+/// after the consecutive failures reach the threshold.
+///
+/// It can be configured in the `[webhook]` table:
+///
+/// ```toml
+/// [webhook]
+/// database = "main"
+/// batch-size = 100
+/// poll-interval = "1s"
+/// refresh-interval = "30s"
+/// request-timeout = "10s"
+/// retry-interval = "10s"
+/// max-backoff = "1h"
+/// max-attempts = 8
+/// disable-after = 50
+/// snippet-length = 512
+/// retention = "30d"
+/// ```
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino_orm::{Webhook, WebhookSubscription};
+///
+/// let subscription = WebhookSubscription::try_new("https://example.com/hooks")?
+///     .models(vec!["user".to_owned()])
+///     .actions(vec!["create".to_owned(), "update".to_owned()]);
+/// subscription.insert().await?;
+///
+/// let delivery = Webhook::ping(subscription.id()).await?;
+/// assert!(delivery.is_delivered());
+///
+/// let mut scheduler = AsyncJobScheduler::new();
+/// scheduler.add(Webhook::delivery_job());
+/// scheduler.add(Webhook::cleanup_job());
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Webhook;
+
+impl Webhook {
+    /// Emits an event of the model for the action.
+    /// It returns the number of the enqueued deliveries.
+    pub async fn emit_model<M: Schema>(action: &str, model: &M) -> Result<usize, Error> {
+        let data = match serde_json::to_value(model)? {
+            JsonValue::Object(map) => map,
+            _ => bail!("fail to serialize the model `{}`", M::model_name()),
+        };
+        Self::emit_model_data::<M>(action, data).await
+    }
+
+    /// Emits an event of the model with the serialized model data.
+    /// It returns the number of the enqueued deliveries.
+    pub async fn emit_model_data<M: Schema>(action: &str, mut data: Map) -> Result<usize, Error> {
+        let model_name = M::model_name();
+        let subscriptions = active_subscriptions()
+            .await?
+            .into_iter()
+            .filter(|subscription| subscription.matches(model_name, action))
+            .collect::<Vec<_>>();
+        if subscriptions.is_empty() {
+            return Ok(0);
+        }
+
+        M::redact_model(&mut data);
+        Self::enqueue(&subscriptions, model_name, action, data).await
+    }
+
+    /// Emits a custom event with the data.
+    /// It returns the number of the enqueued deliveries.
+    pub async fn emit(model_name: &str, action: &str, data: Map) -> Result<usize, Error> {
+        let subscriptions = active_subscriptions()
+            .await?
+            .into_iter()
+            .filter(|subscription| subscription.matches(model_name, action))
+            .collect::<Vec<_>>();
+        Self::enqueue(&subscriptions, model_name, action, data).await
+    }
+
+    /// Sends a `ping` event to the subscription immediately regardless of the filters,
+    /// and records the delivery without retries.
+    pub async fn ping(subscription_id: &str) -> Result<WebhookDelivery, Error> {
+        let Some(subscription) = WebhookSubscription::find_by_id(subscription_id).await? else {
+            bail!(
+                "404 Not Found: cannot find the webhook subscription `{}`",
+                subscription_id
+            );
+        };
+
+        let event_id = Uuid::now_v7().to_string();
+        let payload = event_payload(&event_id, "webhook", "ping", Map::new());
+        let mut delivery =
+            WebhookDelivery::new(subscription.id(), &event_id, "ping", payload.to_string());
+        let outcome = send(&subscription, &delivery).await;
+        delivery.record(outcome);
+        if !delivery.is_delivered() {
+            delivery.status = "dead".to_owned();
+        }
+        delivery.insert().await?;
+        Ok(delivery)
+    }
+
+    /// Delivers a batch of the pending events.
+    /// It returns the number of the delivered events.
+    pub async fn deliver() -> Result<usize, Error> {
+        create_tables().await?;
+
+        let settings = &*WEBHOOK_SETTINGS;
+        let pool = connection_pool()?.pool();
+        let batch_size = settings.batch_size;
+        let now = DateTime::now().timestamp_millis();
+        let sql = format!(
+            "SELECT * FROM {DELIVERIES_TABLE} \
+                WHERE status = 'pending' AND next_attempt_at <= {now} \
+                ORDER BY next_attempt_at ASC, id ASC LIMIT {batch_size};"
+        );
+        let rows = pool.fetch(&sql).await?;
+        let mut subscriptions = HashMap::new();
+        let mut num_delivered = 0;
+        for row in rows {
+            let mut delivery = WebhookDelivery::decode_row(&row)?;
+            let subscription_id = delivery.subscription_id.clone();
+            if !subscriptions.contains_key(&subscription_id) {
+                let subscription = WebhookSubscription::find_by_id(&subscription_id).await?;
+                subscriptions.insert(subscription_id.clone(), subscription);
+            }
+
+            let id = Query::escape_string(&delivery.id);
+            let Some(subscription) = subscriptions
+                .get_mut(&subscription_id)
+                .and_then(|s| s.as_mut())
+                .filter(|s| s.is_active())
+            else {
+                let sql = format!(
+                    "UPDATE {DELIVERIES_TABLE} SET status = 'cancelled' \
+                        WHERE id = {id} AND status = 'pending';"
+                );
+                pool.execute(&sql).await?;
+                continue;
+            };
+
+            let outcome = send(subscription, &delivery).await;
+            delivery.record(outcome);
+
+            let attempts = delivery.attempts;
+            let status_code = format_optional(delivery.status_code);
+            let latency_ms = format_optional(delivery.latency_ms);
+            let response_snippet =
+                format_optional(delivery.response_snippet.as_ref().map(Query::escape_string));
+            let escaped_subscription_id = Query::escape_string(&subscription_id);
+            if delivery.is_delivered() {
+                let delivered_at = format_optional(delivery.delivered_at);
+                let sql = format!(
+                    "UPDATE {DELIVERIES_TABLE} SET status = 'delivered', attempts = {attempts}, \
+                        status_code = {status_code}, latency_ms = {latency_ms}, \
+                        response_snippet = {response_snippet}, last_error = NULL, \
+                        delivered_at = {delivered_at} \
+                        WHERE id = {id} AND status = 'pending';"
+                );
+                pool.execute(&sql).await?;
+                if subscription.consecutive_failures > 0 {
+                    subscription.consecutive_failures = 0;
+                    let sql = format!(
+                        "UPDATE {SUBSCRIPTIONS_TABLE} SET consecutive_failures = 0 \
+                            WHERE id = {escaped_subscription_id};"
+                    );
+                    pool.execute(&sql).await?;
+                }
+                num_delivered += 1;
+
+                #[cfg(feature = "metrics")]
+                metrics::counter!("zino_webhook_delivered_total").increment(1);
+            } else {
+                let last_error = delivery.last_error.as_deref().unwrap_or_default();
+                let status = if settings.max_attempts > 0 && attempts >= settings.max_attempts {
+                    tracing::error!(
+                        event_id = delivery.event_id,
+                        subscription_id,
+                        attempts,
+                        "the webhook delivery is dead: {last_error}"
+                    );
+                    "dead"
+                } else {
+                    tracing::warn!(
+                        event_id = delivery.event_id,
+                        subscription_id,
+                        attempts,
+                        "fail to deliver the webhook event: {last_error}"
+                    );
+                    "pending"
+                };
+                let backoff =
+                    retry_backoff(settings.retry_interval, settings.max_backoff, attempts);
+                let next_attempt_at = now + i64::try_from(backoff.as_millis())?;
+                let last_error = Query::escape_string(last_error);
+                let sql = format!(
+                    "UPDATE {DELIVERIES_TABLE} SET status = '{status}', attempts = {attempts}, \
+                        next_attempt_at = {next_attempt_at}, status_code = {status_code}, \
+                        latency_ms = {latency_ms}, response_snippet = {response_snippet}, \
+                        last_error = {last_error} \
+                        WHERE id = {id} AND status = 'pending';"
+                );
+                pool.execute(&sql).await?;
+
+                subscription.consecutive_failures += 1;
+                let consecutive_failures = subscription.consecutive_failures;
+                let disabled =
+                    settings.disable_after > 0 && consecutive_failures >= settings.disable_after;
+                let status = if disabled {
+                    subscription.status = "disabled".to_owned();
+                    tracing::error!(
+                        subscription_id,
+                        consecutive_failures,
+                        "the webhook subscription has been disabled"
+                    );
+                    invalidate_cache();
+                    "disabled"
+                } else {
+                    "active"
+                };
+                let updated_at = DateTime::now().timestamp_millis();
+                let sql = format!(
+                    "UPDATE {SUBSCRIPTIONS_TABLE} SET status = '{status}', \
+                        consecutive_failures = {consecutive_failures}, updated_at = {updated_at} \
+                        WHERE id = {escaped_subscription_id} AND status = 'active';"
+                );
+                pool.execute(&sql).await?;
+
+                #[cfg(feature = "metrics")]
+                metrics::counter!("zino_webhook_failures_total").increment(1);
+            }
+        }
+        Ok(num_delivered)
+    }
+
+    /// Lists the recent deliveries of a subscription.
+    pub async fn deliveries(
+        subscription_id: &str,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, Error> {
+        create_tables().await?;
+
+        let subscription_id = Query::escape_string(subscription_id);
+        let sql = format!(
+            "SELECT * FROM {DELIVERIES_TABLE} WHERE subscription_id = {subscription_id} \
+                ORDER BY created_at DESC, id DESC LIMIT {limit};"
+        );
+        let rows = connection_pool()?.pool().fetch(&sql).await?;
+        rows.iter().map(WebhookDelivery::decode_row).collect()
+    }
+
+    /// Deletes the finished deliveries which are older than the retention period.
+    /// It returns the number of the deleted deliveries.
+    pub async fn purge_deliveries(retention: Duration) -> Result<u64, Error> {
+        create_tables().await?;
+
+        let retention_millis = i64::try_from(retention.as_millis())?;
+        let deadline = DateTime::now().timestamp_millis() - retention_millis;
+        let sql = format!(
+            "DELETE FROM {DELIVERIES_TABLE} \
+                WHERE status IN ('delivered', 'dead', 'cancelled') AND created_at < {deadline};"
+        );
+        let query_result = connection_pool()?.pool().execute(&sql).await?;
+        Ok(query_result.rows_affected())
+    }
+
+    /// Creates an async job which delivers the pending events every `poll-interval`.
+    ///
+    /// It is exclusive so that an event is not delivered by multiple instances concurrently,
+    /// which requires a job store supporting the distributed lock for multi-instance deployments.
+    pub fn delivery_job() -> AsyncJob {
+        let cron_expr = super::ttl::interval_cron_expr(WEBHOOK_SETTINGS.poll_interval);
+        AsyncJob::new(&cron_expr, deliver_events)
+            .name("webhook_delivery")
+            .overlap_policy(OverlapPolicy::Skip)
+            .exclusive(true)
+    }
+
+    /// Creates an async job which purges the finished deliveries after the retention period.
+    /// It runs at the start of every hour.
+    pub fn cleanup_job() -> AsyncJob {
+        AsyncJob::new("0 0 * * * *", purge_deliveries).name("webhook_cleanup")
+    }
+
+    /// Enqueues the deliveries of an event for the subscriptions.
+    async fn enqueue(
+        subscriptions: &[WebhookSubscription],
+        model_name: &str,
+        action: &str,
+        data: Map,
+    ) -> Result<usize, Error> {
+        if subscriptions.is_empty() {
+            return Ok(0);
+        }
+
+        let event_id = Uuid::now_v7().to_string();
+        let event_type = format!("{model_name}.{action}");
+        let payload = event_payload(&event_id, model_name, action, data).to_string();
+        for subscription in subscriptions {
+            let delivery =
+                WebhookDelivery::new(subscription.id(), &event_id, &event_type, payload.clone());
+            delivery.insert().await?;
+        }
+        Ok(subscriptions.len())
+    }
+}
+
+/// Emits an event of the model for the action. The errors are logged instead of returned,
+/// since the model has been written successfully.
+pub(crate) async fn emit_model_event<M: Schema>(action: &str, model: &M) {
+    if let Err(err) = Webhook::emit_model(action, model).await {
+        let model_name = M::model_name();
+        tracing::error!(model_name, action, "fail to emit the webhook event: {err}");
+    }
+}
+
+/// Emits an event of the model data for the action. The errors are logged instead of returned,
+/// since the model has been written successfully.
+pub(crate) async fn emit_model_data_event<M: Schema>(action: &str, data: Map) {
+    if let Err(err) = Webhook::emit_model_data::<M>(action, data).await {
+        let model_name = M::model_name();
+        tracing::error!(model_name, action, "fail to emit the webhook event: {err}");
+    }
+}
+
+/// Builds the payload of an event.
+fn event_payload(event_id: &str, model_name: &str, action: &str, data: Map) -> JsonValue {
+    let mut payload = Map::new();
+    payload.upsert("id", event_id);
+    payload.upsert("type", format!("{model_name}.{action}"));
+    payload.upsert("model", model_name);
+    payload.upsert("action", action);
+    payload.upsert("created_at", DateTime::now());
+    payload.upsert("data", data);
+    payload.into()
+}
+
+/// Sends the delivery to the subscription.
+async fn send(subscription: &WebhookSubscription, delivery: &WebhookDelivery) -> DeliveryOutcome {
+    let settings = &*WEBHOOK_SETTINGS;
+    let timestamp = DateTime::now().timestamp();
+    let signature = subscription.sign(timestamp, &delivery.payload);
+    let mut options = Map::from_entry("method", "POST");
+    options.upsert(
+        "timeout",
+        u64::try_from(settings.request_timeout.as_millis()).unwrap_or(u64::MAX),
+    );
+
+    let start_time = Instant::now();
+    let result = match Agent::request_builder(subscription.target_url(), Some(&options)) {
+        Ok(request_builder) => request_builder
+            .header("content-type", "application/json")
+            .header("x-webhook-id", &delivery.event_id)
+            .header("x-webhook-timestamp", timestamp.to_string())
+            .header("x-webhook-signature", signature)
+            .body(delivery.payload.clone())
+            .send()
+            .await
+            .map_err(Error::from),
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(response) => {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            let latency_ms = start_time
+                .elapsed()
+                .as_millis()
+                .try_into()
+                .unwrap_or_default();
+            let response_snippet = Some(truncate_snippet(&text, settings.snippet_length))
+                .filter(|snippet| !snippet.is_empty());
+            let error =
+                (!status.is_success()).then(|| format!("unexpected response status `{status}`"));
+            DeliveryOutcome {
+                status_code: Some(status.as_u16()),
+                latency_ms,
+                response_snippet,
+                error,
+            }
+        }
+        Err(err) => DeliveryOutcome {
+            status_code: None,
+            latency_ms: start_time
+                .elapsed()
+                .as_millis()
+                .try_into()
+                .unwrap_or_default(),
+            response_snippet: None,
+            error: Some(err.message().to_owned()),
+        },
+    }
+}
+
+/// Delivers the pending events until there are no more events to deliver.
+fn deliver_events(_ctx: &mut JobContext) -> BoxFuture<'_> {
+    Box::pin(async {
+        loop {
+            match Webhook::deliver().await {
+                Ok(num_delivered) if num_delivered > 0 => continue,
+                Ok(_) => break,
+                Err(err) => {
+                    tracing::error!("fail to deliver the webhook events: {err}");
+                    break;
+                }
+            }
+        }
+    })
+}
+
+/// Purges the finished deliveries after the retention period.
+fn purge_deliveries(_ctx: &mut JobContext) -> BoxFuture<'_> {
+    Box::pin(async {
+        match Webhook::purge_deliveries(WEBHOOK_SETTINGS.retention).await {
+            Ok(num_deleted) => tracing::info!(num_deleted, "webhook deliveries have been purged"),
+            Err(err) => tracing::error!("fail to purge the webhook deliveries: {err}"),
+        }
+    })
+}
+
+/// Returns the active subscriptions, which are cached for the refresh interval.
+async fn active_subscriptions() -> Result<Vec<WebhookSubscription>, Error> {
+    let refresh_interval = WEBHOOK_SETTINGS.refresh_interval;
+    if let Ok(cache) = SUBSCRIPTION_CACHE.read() {
+        if cache
+            .1
+            .is_some_and(|loaded_at| loaded_at.elapsed() < refresh_interval)
+        {
+            return Ok(cache.0.clone());
+        }
+    }
+
+    // The failure is cached as well to avoid querying the database for every write.
+    let result = WebhookSubscription::list().await;
+    let subscriptions = match result.as_ref() {
+        Ok(subscriptions) => subscriptions
+            .iter()
+            .filter(|subscription| subscription.is_active())
+            .cloned()
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    if let Ok(mut cache) = SUBSCRIPTION_CACHE.write() {
+        *cache = (subscriptions.clone(), Some(Instant::now()));
+    }
+    result.map(|_| subscriptions)
+}
+
+/// Invalidates the cache of the active subscriptions.
+fn invalidate_cache() {
+    if let Ok(mut cache) = SUBSCRIPTION_CACHE.write() {
+        cache.1 = None;
+    }
+}
+
+/// Truncates the text to the maximum number of characters.
+fn truncate_snippet(text: &str, max_length: usize) -> String {
+    match text.char_indices().nth(max_length) {
+        Some((index, _)) => [&text[..index], "..."].concat(),
+        None => text.to_owned(),
+    }
+}
+
+/// Formats an optional SQL value.
+fn format_optional(value: Option<impl ToString>) -> String {
+    value
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "NULL".to_owned())
+}
+
+/// Returns the exponential backoff for the attempts.
+fn retry_backoff(retry_interval: Duration, max_backoff: Duration, attempts: u32) -> Duration {
+    let exponent = attempts.saturating_sub(1).min(31);
+    retry_interval
+        .saturating_mul(1 << exponent)
+        .min(max_backoff)
+}
+
+/// Creates the tables for the webhooks if they do not exist.
+async fn create_tables() -> Result<(), Error> {
+    if WEBHOOK_TABLES_CREATED.load(Relaxed) || !super::AUTO_MIGRATION.load(Relaxed) {
+        return Ok(());
+    }
+
+    let subscriptions_sql = format!(
+        "CREATE TABLE IF NOT EXISTS {SUBSCRIPTIONS_TABLE} (\n  \
+            id VARCHAR(36) NOT NULL PRIMARY KEY,\n  \
+            target_url TEXT NOT NULL,\n  \
+            secret VARCHAR(255) NOT NULL,\n  \
+            models TEXT NOT NULL,\n  \
+            actions TEXT NOT NULL,\n  \
+            status VARCHAR(16) NOT NULL,\n  \
+            consecutive_failures BIGINT NOT NULL DEFAULT 0,\n  \
+            created_at BIGINT NOT NULL,\n  \
+            updated_at BIGINT NOT NULL\n\
+        );"
+    );
+    let deliveries_sql = format!(
+        "CREATE TABLE IF NOT EXISTS {DELIVERIES_TABLE} (\n  \
+            id VARCHAR(36) NOT NULL PRIMARY KEY,\n  \
+            subscription_id VARCHAR(36) NOT NULL,\n  \
+            event_id VARCHAR(36) NOT NULL,\n  \
+            event_type VARCHAR(255) NOT NULL,\n  \
+            payload TEXT NOT NULL,\n  \
+            status VARCHAR(16) NOT NULL,\n  \
+            attempts BIGINT NOT NULL DEFAULT 0,\n  \
+            next_attempt_at BIGINT NOT NULL,\n  \
+            status_code BIGINT,\n  \
+            latency_ms BIGINT,\n  \
+            response_snippet TEXT,\n  \
+            last_error TEXT,\n  \
+            created_at BIGINT NOT NULL,\n  \
+            delivered_at BIGINT\n\
+        );"
+    );
+    let pool = connection_pool()?.pool();
+    for (table_name, sql) in [
+        (SUBSCRIPTIONS_TABLE, subscriptions_sql),
+        (DELIVERIES_TABLE, deliveries_sql),
+    ] {
+        if let Err(err) = pool.execute(&sql).await {
+            tracing::error!(table_name, "fail to execute `{sql}`");
+            return Err(err);
+        }
+    }
+    WEBHOOK_TABLES_CREATED.store(true, Relaxed);
+    Ok(())
+}
+
+/// Returns the connection pool.
+fn connection_pool() -> Result<&'static super::ConnectionPool, Error> {
+    GlobalPool::get(WEBHOOK_SETTINGS.database)
+        .ok_or_else(|| warn!("connection to the database is unavailable"))
+}
+
+/// Table name for the webhook subscriptions.
+const SUBSCRIPTIONS_TABLE: &str = "_zino_webhook_subscriptions";
+
+/// Table name for the webhook deliveries.
+const DELIVERIES_TABLE: &str = "_zino_webhook_deliveries";
+
+/// Flag to indicate whether the tables for the webhooks have been created.
+static WEBHOOK_TABLES_CREATED: AtomicBool = AtomicBool::new(false);
+
+/// Cache of the active subscriptions with the loading time.
+static SUBSCRIPTION_CACHE: LazyLock<RwLock<(Vec<WebhookSubscription>, Option<Instant>)>> =
+    LazyLock::new(|| RwLock::new((Vec::new(), None)));
+
+/// Settings for the webhooks.
+#[derive(Debug)]
+struct WebhookSettings {
+    /// Name of the connection pool.
+    database: &'static str,
+    /// Number of deliveries fetched in a batch.
+    batch_size: usize,
+    /// Interval for polling the pending deliveries.
+    poll_interval: Duration,
+    /// Interval for refreshing the cached subscriptions.
+    refresh_interval: Duration,
+    /// Timeout of a delivery request.
+    request_timeout: Duration,
+    /// Initial interval for retrying a failed delivery.
+    retry_interval: Duration,
+    /// Maximum backoff for retrying a failed delivery.
+    max_backoff: Duration,
+    /// Maximum attempts before a delivery is dead. `0` means unlimited.
+    max_attempts: u32,
+    /// Number of consecutive failures to disable a subscription. `0` means never.
+    disable_after: u32,
+    /// Maximum number of characters in the response snippet.
+    snippet_length: usize,
+    /// Retention period of the finished deliveries.
+    retention: Duration,
+}
+
+/// Shared settings for the webhooks.
+static WEBHOOK_SETTINGS: LazyLock<WebhookSettings> = LazyLock::new(|| {
+    let config = State::shared().get_config("webhook");
+    WebhookSettings {
+        database: config
+            .and_then(|config| config.get_str("database"))
+            .unwrap_or("main"),
+        batch_size: config
+            .and_then(|config| config.get_usize("batch-size"))
+            .unwrap_or(100)
+            .max(1),
+        poll_interval: config
+            .and_then(|config| config.get_duration("poll-interval"))
+            .unwrap_or(Duration::from_secs(1)),
+        refresh_interval: config
+            .and_then(|config| config.get_duration("refresh-interval"))
+            .unwrap_or(Duration::from_secs(30)),
+        request_timeout: config
+            .and_then(|config| config.get_duration("request-timeout"))
+            .unwrap_or(Duration::from_secs(10)),
+        retry_interval: config
+            .and_then(|config| config.get_duration("retry-interval"))
+            .unwrap_or(Duration::from_secs(10)),
+        max_backoff: config
+            .and_then(|config| config.get_duration("max-backoff"))
+            .unwrap_or(Duration::from_secs(60 * 60)),
+        max_attempts: config
+            .and_then(|config| config.get_u32("max-attempts"))
+            .unwrap_or(8),
+        disable_after: config
+            .and_then(|config| config.get_u32("disable-after"))
+            .unwrap_or(50),
+        snippet_length: config
+            .and_then(|config| config.get_usize("snippet-length"))
+            .unwrap_or(512),
+        retention: config
+            .and_then(|config| config.get_duration("retention"))
+            .unwrap_or(Duration::from_secs(30 * 24 * 60 * 60)),
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{truncate_snippet, WebhookSubscription};
+    use zino_core::{crypto, encoding::hex};
+
+    #[test]
+    fn it_signs_webhook_payloads() {
+        let subscription = WebhookSubscription::try_new("https://example.com/hooks")
+            .unwrap()
+            .secret("whsec_test")
+            .models(vec!["user".to_owned()]);
+        assert!(subscription.matches("user", "create"));
+        assert!(!subscription.matches("tag", "create"));
+
+        let payload = r#"{"type":"user.create"}"#;
+        let signature = subscription.sign(1700000000, payload);
+        let signature = hex::decode(signature.strip_prefix("v1=").unwrap()).unwrap();
+        let message = format!("1700000000.{payload}");
+        assert!(crypto::verify_signature(
+            message.as_bytes(),
+            b"whsec_test",
+            &signature
+        ));
+        assert!(!crypto::verify_signature(
+            message.as_bytes(),
+            b"whsec_other",
+            &signature
+        ));
+        assert!(WebhookSubscription::try_new("ftp://example.com").is_err());
+
+        assert_eq!(truncate_snippet("hello", 8), "hello");
+        assert_eq!(truncate_snippet("你好世界", 2), "你好...");
+    }
+}
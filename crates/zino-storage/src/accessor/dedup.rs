@@ -0,0 +1,220 @@
+use crate::NamedFile;
+use opendal::{ErrorKind, Operator};
+use zino_core::{bail, crypto, encoding::hex, error::Error};
+
+/// A content-addressed store which deduplicates the files by the content hash.
+///
+/// The layout of the objects is as follows:
+///
+/// - `blobs/sha1/{h[0..2]}/{h[2..4]}/{h}`: the file content, which is written only once.
+///   The fan-out directories avoid the hot prefixes;
+/// - `refs/sha1/{h[0..2]}/{h[2..4]}/{h}/{k}`: a link entry for each logical path,
+///   where `k` is the checksum of the logical path. The number of the entries
+///   is the reference count of the blob;
+/// - `links/{path}`: the content address of the logical path.
+///
+/// The link entries are created before the blob is written, and the blob is only removed
+/// when the last entry is gone. The blob is written with `if_not_exists` when the backend
+/// supports it, so that the concurrent uploads of the same content do not overwrite each other.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use zino_storage::{DedupStore, NamedFile};
+///
+/// let store = DedupStore::with_accessor("attachments").expect("accessor should exist");
+/// let address = store.write("users/alice/avatar.png", &file).await?;
+/// let bytes = store.read("users/alice/avatar.png").await?;
+/// store.delete("users/alice/avatar.png").await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct DedupStore {
+    /// Operator.
+    operator: Operator,
+    /// Prefix of the object paths.
+    prefix: String,
+}
+
+impl DedupStore {
+    /// Creates a new instance with the operator.
+    #[inline]
+    pub fn new(operator: Operator) -> Self {
+        Self {
+            operator,
+            prefix: String::new(),
+        }
+    }
+
+    /// Creates a new instance with the operator of a global accessor.
+    #[inline]
+    pub fn with_accessor(name: &str) -> Option<Self> {
+        super::GlobalAccessor::get(name).map(|operator| Self::new(operator.clone()))
+    }
+
+    /// Sets the prefix of the object paths, such as `dedup/`.
+    #[inline]
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        let mut prefix = prefix.into();
+        if !prefix.is_empty() && !prefix.ends_with('/') {
+            prefix.push('/');
+        }
+        self.prefix = prefix;
+        self
+    }
+
+    /// Returns a reference to the operator.
+    #[inline]
+    pub fn operator(&self) -> &Operator {
+        &self.operator
+    }
+
+    /// Writes the file to the logical path and returns the content address.
+    /// The blob is only written if there is no file with the same content.
+    pub async fn write(&self, path: &str, file: &NamedFile) -> Result<String, Error> {
+        let address = file.content_address_async().await?;
+        let previous_address = self.resolve(path).await?;
+
+        // Link entry goes first so that the blob is never removed by a concurrent deletion.
+        let ref_path = self.ref_path(&address, path)?;
+        self.operator
+            .write(&ref_path, path.as_bytes().to_vec())
+            .await?;
+
+        let blob_path = self.blob_path(&address)?;
+        if !self.operator.exists(&blob_path).await? {
+            let supports_if_not_exists = self
+                .operator
+                .info()
+                .full_capability()
+                .write_with_if_not_exists;
+            let mut writer = self.operator.write_with(&blob_path, file.bytes());
+            if supports_if_not_exists {
+                writer = writer.if_not_exists(true);
+            }
+            if let Some(content_type) = file.content_type() {
+                writer = writer.content_type(content_type.essence_str());
+            }
+            match writer.await {
+                Ok(_) => (),
+                Err(err) if err.kind() == ErrorKind::ConditionNotMatch => {
+                    tracing::debug!(
+                        address = address.as_str(),
+                        "the blob has been written by another upload"
+                    );
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        let link_path = self.link_path(path);
+        self.operator
+            .write(&link_path, address.as_bytes().to_vec())
+            .await?;
+        if let Some(previous_address) = previous_address.filter(|a| a != &address) {
+            self.release(&previous_address, path).await?;
+        }
+        Ok(address)
+    }
+
+    /// Reads the file content of the logical path.
+    pub async fn read(&self, path: &str) -> Result<Vec<u8>, Error> {
+        let Some(address) = self.resolve(path).await? else {
+            bail!("the file `{}` does not exist", path);
+        };
+        let blob_path = self.blob_path(&address)?;
+        let buffer = self.operator.read(&blob_path).await?;
+        Ok(buffer.to_vec())
+    }
+
+    /// Returns the content address of the logical path if it exists.
+    pub async fn resolve(&self, path: &str) -> Result<Option<String>, Error> {
+        let link_path = self.link_path(path);
+        match self.operator.read(&link_path).await {
+            Ok(buffer) => {
+                let address = String::from_utf8(buffer.to_vec())?;
+                Ok(Some(address.trim().to_owned()))
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Returns the number of the logical paths which refer to the content address.
+    pub async fn ref_count(&self, address: &str) -> Result<usize, Error> {
+        let ref_dir = self.ref_dir(address)?;
+        let entries = self.operator.list(&ref_dir).await?;
+        Ok(entries
+            .iter()
+            .filter(|entry| !entry.path().ends_with('/'))
+            .count())
+    }
+
+    /// Deletes the file of the logical path, and returns `true` if it exists.
+    /// The blob is only removed when the last reference is gone.
+    pub async fn delete(&self, path: &str) -> Result<bool, Error> {
+        let Some(address) = self.resolve(path).await? else {
+            return Ok(false);
+        };
+        self.operator.delete(&self.link_path(path)).await?;
+        self.release(&address, path).await?;
+        Ok(true)
+    }
+
+    /// Removes the link entry of the logical path, and removes the blob
+    /// if there are no other references.
+    async fn release(&self, address: &str, path: &str) -> Result<(), Error> {
+        let ref_path = self.ref_path(address, path)?;
+        self.operator.delete(&ref_path).await?;
+        if self.ref_count(address).await? == 0 {
+            let blob_path = self.blob_path(address)?;
+            self.operator.delete(&blob_path).await?;
+            tracing::info!(address, "the blob without references has been removed");
+        }
+        Ok(())
+    }
+
+    /// Returns the path of the blob for the content address.
+    pub fn blob_path(&self, address: &str) -> Result<String, Error> {
+        let (algorithm, hash) = parse_content_address(address)?;
+        let prefix = &self.prefix;
+        let fan_out = format!("{}/{}", &hash[0..2], &hash[2..4]);
+        Ok(format!("{prefix}blobs/{algorithm}/{fan_out}/{hash}"))
+    }
+
+    /// Returns the directory of the link entries for the content address.
+    fn ref_dir(&self, address: &str) -> Result<String, Error> {
+        let (algorithm, hash) = parse_content_address(address)?;
+        let prefix = &self.prefix;
+        let fan_out = format!("{}/{}", &hash[0..2], &hash[2..4]);
+        Ok(format!("{prefix}refs/{algorithm}/{fan_out}/{hash}/"))
+    }
+
+    /// Returns the path of the link entry for the content address and the logical path.
+    fn ref_path(&self, address: &str, path: &str) -> Result<String, Error> {
+        let ref_dir = self.ref_dir(address)?;
+        let key = hex::encode(crypto::checksum(path.as_bytes()));
+        Ok(format!("{ref_dir}{key}"))
+    }
+
+    /// Returns the path of the link for the logical path.
+    fn link_path(&self, path: &str) -> String {
+        let prefix = &self.prefix;
+        let path = path.trim_start_matches('/');
+        format!("{prefix}links/{path}")
+    }
+}
+
+/// Parses the content address into the algorithm and the hex-encoded hash.
+fn parse_content_address(address: &str) -> Result<(&str, &str), Error> {
+    let Some((algorithm, hash)) = address.split_once(':') else {
+        bail!("invalid content address `{}`", address);
+    };
+    if algorithm.is_empty()
+        || hash.len() < 4
+        || !hash.bytes().all(|b| b.is_ascii_hexdigit())
+        || !algorithm.bytes().all(|b| b.is_ascii_alphanumeric())
+    {
+        bail!("invalid content address `{}`", address);
+    }
+    Ok((algorithm, hash))
+}
@@ -55,6 +55,10 @@
 //! }
 //! ```
 
+mod dedup;
+
+pub use dedup::DedupStore;
+
 use opendal::{
     layers::{RetryLayer, TracingLayer},
     services, Error,
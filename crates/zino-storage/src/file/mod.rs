@@ -11,6 +11,7 @@ use std::{
     path::Path,
 };
 use zino_core::{
+    application::ComputePool,
     crypto,
     encoding::{base64, hex},
     error::Error,
@@ -163,6 +164,31 @@ impl NamedFile {
         Vec::from(checksum).into()
     }
 
+    /// Returns the checksum for the file asynchronously.
+    /// The computation is offloaded to the [`ComputePool`] for a large file.
+    pub async fn checksum_async(&self) -> Result<Bytes, Error> {
+        if ComputePool::should_offload(self.bytes.len()) {
+            let bytes = self.bytes.clone();
+            ComputePool::execute(move || Vec::from(crypto::checksum(&bytes)).into()).await
+        } else {
+            Ok(self.checksum())
+        }
+    }
+
+    /// Returns the content address for the file, which is the hex-encoded checksum
+    /// prefixed with the algorithm, such as `sha1:2fd4e1c67a2d28fced849ee1bb76e7391b93eb12`.
+    #[inline]
+    pub fn content_address(&self) -> String {
+        format!("sha1:{}", hex::encode(self.checksum()))
+    }
+
+    /// Returns the content address for the file asynchronously.
+    #[inline]
+    pub async fn content_address_async(&self) -> Result<String, Error> {
+        let checksum = self.checksum_async().await?;
+        Ok(format!("sha1:{}", hex::encode(checksum)))
+    }
+
     /// Returns the ETag for the file.
     #[inline]
     pub fn etag(&self) -> EntityTag {
@@ -253,35 +279,73 @@ impl NamedFile {
     /// Encrypts the file with a key.
     pub fn encrypt_with(&mut self, key: impl AsRef<[u8]>) -> Result<(), Error> {
         fn inner(file: &mut NamedFile, key: &[u8]) -> Result<(), Error> {
-            let suffix = ".encrypted";
             let bytes = crypto::encrypt(file.as_ref(), key)?;
-            if let Some(ref mut file_name) = file.file_name {
-                if !file_name.ends_with(suffix) {
-                    file_name.push_str(suffix);
-                }
-            }
-            file.bytes = bytes.into();
+            file.set_encrypted_bytes(bytes);
             Ok(())
         }
         inner(self, key.as_ref())
     }
 
+    /// Encrypts the file with a key asynchronously.
+    /// The computation is offloaded to the [`ComputePool`] for a large file.
+    pub async fn encrypt_with_async(&mut self, key: impl AsRef<[u8]>) -> Result<(), Error> {
+        if ComputePool::should_offload(self.bytes.len()) {
+            let bytes = self.bytes.clone();
+            let key = key.as_ref().to_vec();
+            let bytes = ComputePool::execute(move || crypto::encrypt(&bytes, &key)).await??;
+            self.set_encrypted_bytes(bytes);
+            Ok(())
+        } else {
+            self.encrypt_with(key)
+        }
+    }
+
     /// Decrypts the file with a key.
     pub fn decrypt_with(&mut self, key: impl AsRef<[u8]>) -> Result<(), Error> {
         fn inner(file: &mut NamedFile, key: &[u8]) -> Result<(), Error> {
-            let suffix = ".encrypted";
             let bytes = crypto::decrypt(file.as_ref(), key)?;
-            if let Some(ref mut file_name) = file.file_name {
-                if file_name.ends_with(suffix) {
-                    file_name.truncate(file_name.len() - suffix.len());
-                }
-            }
-            file.bytes = bytes.into();
+            file.set_decrypted_bytes(bytes);
             Ok(())
         }
         inner(self, key.as_ref())
     }
 
+    /// Decrypts the file with a key asynchronously.
+    /// The computation is offloaded to the [`ComputePool`] for a large file.
+    pub async fn decrypt_with_async(&mut self, key: impl AsRef<[u8]>) -> Result<(), Error> {
+        if ComputePool::should_offload(self.bytes.len()) {
+            let bytes = self.bytes.clone();
+            let key = key.as_ref().to_vec();
+            let bytes = ComputePool::execute(move || crypto::decrypt(&bytes, &key)).await??;
+            self.set_decrypted_bytes(bytes);
+            Ok(())
+        } else {
+            self.decrypt_with(key)
+        }
+    }
+
+    /// Sets the encrypted bytes and appends the `.encrypted` suffix to the file name.
+    fn set_encrypted_bytes(&mut self, bytes: Vec<u8>) {
+        let suffix = ".encrypted";
+        if let Some(ref mut file_name) = self.file_name {
+            if !file_name.ends_with(suffix) {
+                file_name.push_str(suffix);
+            }
+        }
+        self.bytes = bytes.into();
+    }
+
+    /// Sets the decrypted bytes and removes the `.encrypted` suffix from the file name.
+    fn set_decrypted_bytes(&mut self, bytes: Vec<u8>) {
+        let suffix = ".encrypted";
+        if let Some(ref mut file_name) = self.file_name {
+            if file_name.ends_with(suffix) {
+                file_name.truncate(file_name.len() - suffix.len());
+            }
+        }
+        self.bytes = bytes.into();
+    }
+
     /// Renames the stem portion of the file name.
     #[inline]
     pub fn rename_file_stem(&mut self, file_stem: &str) -> Result<(), Error> {
@@ -426,7 +490,11 @@ impl NamedFile {
                 }
             }
             if let Some(checksum) = extra.get_str("checksum") {
-                let integrity = format!("{:x}", file.checksum());
+                let integrity = file
+                    .checksum_async()
+                    .await
+                    .map_err(|_| multer::Error::IncompleteStream)?;
+                let integrity = format!("{integrity:x}");
                 if !integrity.eq_ignore_ascii_case(checksum) {
                     return Err(multer::Error::IncompleteStream);
                 }
@@ -12,4 +12,4 @@ mod accessor;
 pub use file::NamedFile;
 
 #[cfg(feature = "accessor")]
-pub use accessor::GlobalAccessor;
+pub use accessor::{DedupStore, GlobalAccessor};
@@ -0,0 +1,246 @@
+//! Embedded admin UI for browsing and editing models in debug mode.
+//!
+//! The admin UI is a self-contained HTML page which lists the models registered by
+//! [`register()`] and provides the list, detail, create, edit and delete views for them.
+//! It does not access the database directly. Instead, all the operations are delegated to
+//! the JSON endpoints of the [`DefaultController`](crate::DefaultController) under the base path
+//! of each model, so the scopes, hooks and validations of the model are respected.
+//!
+//! The following routes are expected under the base path, which is `/{model_name}` by default:
+//!
+//! | Route                    | Handler                          | Required? |
+//! |--------------------------|----------------------------------|-----------|
+//! | `GET /list`              | `DefaultController::list`        | Yes       |
+//! | `GET /definition`        | `DefaultController::definition` | Yes       |
+//! | `GET /{id}/view`         | `DefaultController::view`        | No        |
+//! | `POST /new`              | `DefaultController::new`         | No        |
+//! | `POST /{id}/update`      | `DefaultController::update`      | No        |
+//! | `POST /{id}/delete`      | `DefaultController::delete`      | No        |
+//! | `POST /{id}/soft-delete` | `DefaultController::soft_delete` | No        |
+//!
+//! The admin UI is disabled in the `prod` environment unless `allow-production` is set,
+//! so that the models are not registered and the handlers always respond with `404 Not Found`.
+//! Access to the handlers is guarded by the basic authentication or the required scopes.
+//! It is denied if neither of them has been configured.
+//!
+//! ```toml
+//! [admin]
+//! title = "Admin"
+//! username = "admin"
+//! password = "Dev@2025"
+//! scopes = ["admin"]
+//! allow-production = false
+//! ```
+//!
+//! The scopes are checked by [`RequestContext::require_scopes()`] and it requires the `jwt` feature.
+//!
+//! # Examples
+//!
+//! ```rust,ignore
+//! use zino::{admin::{self, AdminController}, prelude::*};
+//!
+//! admin::register::<User>();
+//! admin::register_at::<Tag>("/admin/tag");
+//!
+//! let router = Router::new()
+//!     .route("/admin", get(AdminController::index))
+//!     .route("/user/list", get(User::list))
+//!     .route("/user/definition", get(User::definition))
+//!     .route("/user/{id}/view", get(User::view))
+//!     .route("/user/{id}/soft-delete", post(User::soft_delete));
+//! app.register_debug(vec![router]);
+//! ```
+
+use std::sync::RwLock;
+use zino_core::{
+    encoding::base64,
+    extension::{JsonObjectExt, TomlTableExt},
+    state::State,
+    warn, JsonValue, LazyLock, Map,
+};
+use zino_http::{
+    request::RequestContext,
+    response::{Rejection, Response, StatusCode},
+};
+use zino_orm::Schema;
+
+/// Registers a model to the admin UI with the base path `/{model_name}`.
+#[inline]
+pub fn register<M: Schema>() {
+    register_at::<M>(format!("/{}", M::model_name()));
+}
+
+/// Registers a model to the admin UI with the base path of its routes.
+/// It has no effect if the admin UI is disabled.
+pub fn register_at<M: Schema>(base_path: impl Into<String>) {
+    let model_name = M::model_name();
+    if !is_enabled() {
+        tracing::warn!(
+            model_name,
+            "the admin UI is disabled in the production environment"
+        );
+        return;
+    }
+
+    let entry = AdminModel {
+        model_name,
+        base_path: base_path.into().trim_end_matches('/').to_owned(),
+        primary_key_name: M::PRIMARY_KEY_NAME,
+        item_names: M::ITEM_NAME.1,
+        soft_deletable: ["status", "deleted_at", "is_deleted"]
+            .into_iter()
+            .any(M::has_column),
+    };
+    if let Ok(mut models) = ADMIN_MODELS.write() {
+        if let Some(model) = models.iter_mut().find(|m| m.model_name == model_name) {
+            *model = entry;
+        } else {
+            models.push(entry);
+        }
+    }
+}
+
+/// Returns `true` if the admin UI is enabled.
+///
+/// It is intended to be used as a safeguard before registering the routes.
+#[inline]
+pub fn is_enabled() -> bool {
+    !State::shared().env().is_prod() || ADMIN_SETTINGS.allow_production
+}
+
+/// Controller for the admin UI, which is intended to be registered as debug routes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AdminController;
+
+impl AdminController {
+    /// Renders the admin UI.
+    pub async fn index(req: crate::Request) -> crate::Result {
+        if !is_enabled() {
+            let err = warn!("the admin UI is disabled in the production environment");
+            return Err(Rejection::not_found(err).context(&req).into());
+        }
+        if let Err(rejection) = check_access(&req) {
+            if ADMIN_SETTINGS.basic_auth.is_some() {
+                let mut res = Response::new(StatusCode::UNAUTHORIZED).context(&req);
+                res.insert_header(
+                    "www-authenticate",
+                    r#"Basic realm="admin", charset="UTF-8""#,
+                );
+                return Ok(res.into());
+            }
+            return Err(rejection.into());
+        }
+
+        let models = ADMIN_MODELS
+            .read()
+            .map(|models| models.iter().map(AdminModel::to_map).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let mut data = Map::new();
+        data.upsert("title", ADMIN_SETTINGS.title);
+        data.upsert("models", JsonValue::from(models).to_string());
+
+        let res = Response::default()
+            .context(&req)
+            .render_str(INDEX_TEMPLATE, data);
+        Ok(res.into())
+    }
+}
+
+/// Checks whether the request is granted to access the admin UI.
+fn check_access(req: &crate::Request) -> Result<(), Rejection> {
+    let settings = &*ADMIN_SETTINGS;
+    if let Some((username, password)) = settings.basic_auth {
+        let credentials = req
+            .get_header("authorization")
+            .and_then(|s| s.strip_prefix("Basic "))
+            .and_then(|s| base64::decode(s.trim().trim_end_matches('=')).ok())
+            .and_then(|bytes| String::from_utf8(bytes).ok());
+        if let Some((user, pass)) = credentials.as_ref().and_then(|s| s.split_once(':')) {
+            if user == username && pass == password {
+                return Ok(());
+            }
+        }
+    }
+    #[cfg(feature = "jwt")]
+    if !settings.scopes.is_empty() {
+        return req.require_scopes(&settings.scopes);
+    }
+
+    let err = warn!("invalid credentials to access the admin UI");
+    Err(Rejection::unauthorized(err).context(req))
+}
+
+/// A model registered to the admin UI.
+#[derive(Debug, Clone)]
+struct AdminModel {
+    /// Model name.
+    model_name: &'static str,
+    /// Base path of the routes.
+    base_path: String,
+    /// Primary key name.
+    primary_key_name: &'static str,
+    /// Name of the items in the list response.
+    item_names: &'static str,
+    /// A flag to indicate whether the model can be logically deleted.
+    soft_deletable: bool,
+}
+
+impl AdminModel {
+    /// Converts `self` to a JSON object.
+    fn to_map(&self) -> Map {
+        let mut map = Map::new();
+        map.upsert("model_name", self.model_name);
+        map.upsert("base_path", self.base_path.as_str());
+        map.upsert("primary_key_name", self.primary_key_name);
+        map.upsert("item_names", self.item_names);
+        map.upsert("soft_deletable", self.soft_deletable);
+        map
+    }
+}
+
+/// Settings of the admin UI.
+#[derive(Debug)]
+struct AdminSettings {
+    /// Title of the page.
+    title: &'static str,
+    /// Username and password for the basic authentication.
+    basic_auth: Option<(&'static str, &'static str)>,
+    /// Required scopes.
+    #[cfg_attr(not(feature = "jwt"), allow(dead_code))]
+    scopes: Vec<&'static str>,
+    /// A flag to indicate whether the admin UI is allowed in the production environment.
+    allow_production: bool,
+}
+
+/// Shared settings of the admin UI.
+static ADMIN_SETTINGS: LazyLock<AdminSettings> = LazyLock::new(|| {
+    let config = State::shared().get_config("admin");
+    let basic_auth = config.and_then(|config| {
+        let username = config.get_str("username")?;
+        let password = config.get_str("password")?;
+        Some((username, password))
+    });
+    let scopes = config
+        .and_then(|config| {
+            config
+                .get_str_array("scopes")
+                .or_else(|| config.get_str("scope").map(|s| vec![s]))
+        })
+        .unwrap_or_default();
+    AdminSettings {
+        title: config
+            .and_then(|config| config.get_str("title"))
+            .unwrap_or("Admin"),
+        basic_auth,
+        scopes,
+        allow_production: config
+            .and_then(|config| config.get_bool("allow-production"))
+            .unwrap_or(false),
+    }
+});
+
+/// Registered models.
+static ADMIN_MODELS: LazyLock<RwLock<Vec<AdminModel>>> = LazyLock::new(|| RwLock::new(Vec::new()));
+
+/// Template of the admin UI.
+static INDEX_TEMPLATE: &str = include_str!("index.html");
@@ -0,0 +1,79 @@
+use zino_core::{
+    extension::JsonObjectExt,
+    trace::{DebugSession, DebugTarget},
+    validation::Validation,
+    warn, Map,
+};
+use zino_http::{
+    request::RequestContext,
+    response::{ExtractRejection, Rejection, Response},
+};
+
+/// Controller for the debug sessions, which is intended to be registered as debug routes
+/// with an authentication middleware.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DebugSessionController;
+
+impl DebugSessionController {
+    /// Lists the debug sessions which have not expired.
+    pub async fn list(req: crate::Request) -> crate::Result {
+        let mut res = Response::default().context(&req);
+        res.set_json_data(Map::data_entries(DebugSession::list()));
+        Ok(res.into())
+    }
+
+    /// Adds a debug session with the `target`, `value` and an optional `ttl`
+    /// in the request body. The `target` can be `session_id`, `user_id` or `debug_token`.
+    pub async fn add(mut req: crate::Request) -> crate::Result {
+        let body = req.parse_body::<Map>().await?;
+        let (target, value) = parse_debug_target(&req, &body)?;
+        let ttl = match body.parse_duration("ttl") {
+            Some(result) => result.extract(&req)?,
+            None => DebugSession::default_ttl(),
+        };
+        let session = DebugSession::new(target, value, ttl);
+        let data = session.to_map();
+        DebugSession::add(session);
+
+        let mut res = Response::default().context(&req);
+        res.set_json_data(data);
+        Ok(res.into())
+    }
+
+    /// Removes a debug session with the `target` and `value` in the request body.
+    pub async fn remove(mut req: crate::Request) -> crate::Result {
+        let body = req.parse_body::<Map>().await?;
+        let (target, value) = parse_debug_target(&req, &body)?;
+        if !DebugSession::remove(target, value) {
+            let message = warn!("the debug session does not exist");
+            return Err(Rejection::not_found(message).context(&req).into());
+        }
+
+        let mut res = Response::default().context(&req);
+        res.set_json_data(Map::data_entries(DebugSession::list()));
+        Ok(res.into())
+    }
+}
+
+/// Parses the target kind and value of the debug session.
+fn parse_debug_target<'a>(
+    req: &crate::Request,
+    body: &'a Map,
+) -> Result<(DebugTarget, &'a str), Rejection> {
+    let mut validation = Validation::new();
+    let target = body
+        .get_str("target")
+        .ok_or_else(|| warn!("should be nonempty"))
+        .and_then(DebugTarget::try_from);
+    let value = body.get_str("value").filter(|s| !s.is_empty());
+    if let Err(err) = &target {
+        validation.record("target", err.to_string());
+    }
+    if value.is_none() {
+        validation.record("value", "should be nonempty");
+    }
+    match (target, value) {
+        (Ok(target), Some(value)) => Ok((target, value)),
+        _ => Err(Rejection::bad_request(validation).context(req)),
+    }
+}
@@ -0,0 +1,356 @@
+use flate2::{write::GzEncoder, Compression};
+use std::{io::Write, mem, time::Duration};
+use zino_core::{
+    encoding::csv::{CsvOptions, CsvWriter},
+    error::Error,
+    extension::{JsonObjectExt, JsonValueExt, TomlTableExt},
+    model::Query,
+    schedule::{AsyncJob, JobContext},
+    state::State,
+    warn, BoxFuture, JsonValue, LazyLock, Map,
+};
+use zino_http::{
+    request::RequestContext,
+    response::{ExtractRejection, Rejection, Response},
+};
+use zino_orm::{ExportJob, KeysetIterator, ModelHelper, Schema};
+use zino_storage::GlobalAccessor;
+
+/// Controller for the async export jobs of very large datasets.
+///
+/// A job is created by [`DefaultController::create_export_job()`](super::DefaultController)
+/// and executed by the [`worker()`](Self::worker) of the model, which streams the query results
+/// into a gzip-compressed CSV or JSON Lines file written through the storage accessor.
+/// The result files are deleted by the [`cleanup_job()`](Self::cleanup_job)
+/// after the retention period.
+///
+/// It can be configured in the `[export]` table:
+///
+/// ```toml
+/// [export]
+/// database = "main"
+/// accessor = "local"
+/// dir = "exports"
+/// batch-size = 1000
+/// progress-interval = 10000
+/// poll-interval = "10s"
+/// presign-expiry = "1h"
+/// retention = "7d"
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExportJobController;
+
+impl ExportJobController {
+    /// Reports the status of an export job. A download URL is returned when it has succeeded,
+    /// which is presigned if the storage backend supports it.
+    pub async fn status(req: crate::Request) -> crate::Result {
+        let job = get_export_job(&req).await?;
+        let mut data = job.to_map();
+        if let Some(result_path) = job.result_path().filter(|_| job.is_succeeded()) {
+            let settings = &*EXPORT_SETTINGS;
+            let presigned_url = if let Some(operator) = GlobalAccessor::get(settings.accessor) {
+                operator
+                    .presign_read(result_path, settings.presign_expiry)
+                    .await
+                    .ok()
+                    .map(|presigned| presigned.uri().to_string())
+            } else {
+                None
+            };
+            let download_url = presigned_url.unwrap_or_else(|| {
+                let request_path = req.request_path();
+                let base_path = request_path.strip_suffix("/status").unwrap_or(request_path);
+                format!("{base_path}/download")
+            });
+            data.upsert("download_url", download_url);
+        }
+
+        let mut res = Response::default().context(&req);
+        res.set_json_data(data);
+        Ok(res.into())
+    }
+
+    /// Cancels a pending or running export job.
+    pub async fn cancel(req: crate::Request) -> crate::Result {
+        let id = req.parse_param::<String>("id")?;
+        if !ExportJob::cancel(&id).await.extract(&req)? {
+            let err = warn!("export job `{}` does not exist or has finished", id);
+            return Err(Rejection::conflict(err).context(&req).into());
+        }
+
+        let mut res = Response::default().context(&req);
+        res.set_json_data(Map::from_entry("id", id));
+        Ok(res.into())
+    }
+
+    /// Downloads the result file of a succeeded export job from the storage accessor.
+    pub async fn download(req: crate::Request) -> crate::Result {
+        let job = get_export_job(&req).await?;
+        let Some(result_path) = job.result_path().filter(|_| job.is_succeeded()) else {
+            let err = warn!("export job `{}` has not succeeded", job.id());
+            return Err(Rejection::conflict(err).context(&req).into());
+        };
+        let Some(operator) = GlobalAccessor::get(EXPORT_SETTINGS.accessor) else {
+            let err = warn!("storage accessor for the export jobs is unavailable");
+            return Err(Rejection::service_unavailable(err).context(&req).into());
+        };
+        let buffer = operator.read(result_path).await.extract(&req)?;
+        let file_name = result_path.rsplit('/').next().unwrap_or(result_path);
+
+        let mut res = Response::default().context(&req);
+        res.set_bytes_data(buffer.to_bytes());
+        res.set_content_type("application/gzip");
+        res.insert_header(
+            "content-disposition",
+            format!(r#"attachment; filename="{file_name}""#),
+        );
+        Ok(res.into())
+    }
+
+    /// Creates an async job which executes the pending export jobs of the model
+    /// every `poll-interval`.
+    ///
+    /// Since it runs in the background without a request context,
+    /// the `after_decode` and `before_respond` hooks are not called,
+    /// while the model data are still translated and redacted.
+    pub fn worker<M: Schema>() -> AsyncJob {
+        let cron_expr = interval_cron_expr(EXPORT_SETTINGS.poll_interval);
+        let job_name = format!("{}_export_worker", M::model_name());
+        AsyncJob::new(&cron_expr, execute_export_jobs::<M>).name(job_name)
+    }
+
+    /// Creates an async job which deletes the expired result files and job records.
+    /// It runs at the start of every hour.
+    pub fn cleanup_job() -> AsyncJob {
+        AsyncJob::new("0 0 * * * *", cleanup_export_jobs).name("export_jobs_cleanup")
+    }
+}
+
+/// Creates an export job for the model with the query filters and the format
+/// specified by the `format` query parameter.
+pub(super) async fn create_export_job<M: Schema>(
+    req: &crate::Request,
+    query: &Query,
+) -> crate::Result {
+    let format = match req.get_query("format") {
+        Some("jsonlines" | "ndjson") => "jsonlines",
+        Some("csv") | None => "csv",
+        Some("tsv") => "tsv",
+        Some(format) => {
+            let err = warn!("export format `{}` is unsupported", format);
+            return Err(Rejection::from_validation_entry("format", err)
+                .context(req)
+                .into());
+        }
+    };
+    let job = ExportJob::new(M::model_name(), query.filters().clone(), format);
+    job.insert().await.extract(req)?;
+
+    let mut res = Response::default().context(req);
+    res.set_status_code(202u16);
+    res.set_json_data(job.to_map());
+    Ok(res.into())
+}
+
+/// Gets the export job by the `id` param.
+async fn get_export_job(req: &crate::Request) -> Result<ExportJob, Rejection> {
+    let id = req.parse_param::<String>("id")?;
+    match ExportJob::find_by_id(&id).await {
+        Ok(Some(job)) => Ok(job),
+        Ok(None) => {
+            let err = warn!("export job `{}` does not exist", id);
+            Err(Rejection::not_found(err).context(req))
+        }
+        Err(err) => Err(Rejection::from_error(err).context(req)),
+    }
+}
+
+/// Executes the pending export jobs of the model.
+fn execute_export_jobs<M: Schema>(_ctx: &mut JobContext) -> BoxFuture<'_> {
+    Box::pin(async {
+        let model_name = M::model_name();
+        loop {
+            match ExportJob::claim_pending(model_name).await {
+                Ok(Some(job)) => {
+                    let job_id = job.id().to_owned();
+                    if let Err(err) = execute_export_job::<M>(job).await {
+                        tracing::error!(
+                            model_name,
+                            job_id = job_id.as_str(),
+                            "fail to execute the export job: {err}"
+                        );
+                        if let Err(err) = ExportJob::fail(&job_id, err.message()).await {
+                            tracing::error!(model_name, job_id = job_id.as_str(), "{err}");
+                        }
+                    }
+                }
+                Ok(None) => break,
+                Err(err) => {
+                    tracing::error!(model_name, "fail to claim the export job: {err}");
+                    break;
+                }
+            }
+        }
+    })
+}
+
+/// Executes an export job by writing the query results into a file batch by batch.
+async fn execute_export_job<M: Schema>(job: ExportJob) -> Result<(), Error> {
+    let settings = &*EXPORT_SETTINGS;
+    let Some(operator) = GlobalAccessor::get(settings.accessor) else {
+        return Err(warn!(
+            "storage accessor `{}` is unavailable",
+            settings.accessor
+        ));
+    };
+
+    let job_id = job.id();
+    let csv_options = CsvOptions::with_format(job.format())
+        .map(|options| options.columns(M::fields().iter().copied()));
+    let extension = if let Some(options) = csv_options.as_ref() {
+        if options.is_tsv() {
+            "tsv.gz"
+        } else {
+            "csv.gz"
+        }
+    } else {
+        "jsonl.gz"
+    };
+    let result_path = format!("{}/{}/{job_id}.{extension}", settings.dir, M::model_name());
+
+    let mut query = M::default_query();
+    query.append_filters(&mut job.filters().clone());
+
+    let mut iterator = KeysetIterator::<M>::new(query).batch_size(settings.batch_size);
+
+    let mut writer = operator.writer(&result_path).await?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let mut csv_writer = csv_options
+        .map(|options| CsvWriter::new(Vec::new(), options))
+        .transpose()?;
+    let mut offset = 0;
+    let mut last_reported = 0;
+    while let Some(mut models) = iterator.next_batch::<Map>().await? {
+        let num_rows = models.len();
+        for model in models.iter_mut() {
+            <M as ModelHelper<M::PrimaryKey>>::translate_model(model);
+            <M as ModelHelper<M::PrimaryKey>>::redact_model(model);
+        }
+
+        if let Some(csv_writer) = csv_writer.as_mut() {
+            // The headers are only written before the first row.
+            for model in models.iter() {
+                csv_writer.write_map(model)?;
+            }
+            encoder.write_all(&csv_writer.take_bytes()?)?;
+        } else {
+            encoder.write_all(&JsonValue::from(models).to_jsonlines(Vec::new())?)?;
+        }
+        writer.write(mem::take(encoder.get_mut())).await?;
+
+        offset += num_rows;
+        if offset - last_reported >= settings.progress_interval {
+            last_reported = offset;
+            if !ExportJob::report_progress(job_id, i64::try_from(offset)?).await? {
+                tracing::info!(job_id, "the export job has been cancelled");
+                writer.abort().await?;
+                return Ok(());
+            }
+        }
+    }
+    writer.write(encoder.finish()?).await?;
+    writer.close().await?;
+
+    if !ExportJob::complete(job_id, &result_path, i64::try_from(offset)?).await? {
+        tracing::info!(job_id, "the export job has been cancelled");
+        operator.delete(&result_path).await?;
+    }
+    Ok(())
+}
+
+/// Deletes the expired result files and job records.
+fn cleanup_export_jobs(_ctx: &mut JobContext) -> BoxFuture<'_> {
+    Box::pin(async {
+        let settings = &*EXPORT_SETTINGS;
+        let jobs = match ExportJob::list_expired(settings.retention).await {
+            Ok(jobs) => jobs,
+            Err(err) => {
+                tracing::error!("fail to list the expired export jobs: {err}");
+                return;
+            }
+        };
+        let operator = GlobalAccessor::get(settings.accessor);
+        for job in jobs {
+            let job_id = job.id();
+            if let Some(result_path) = job.result_path() {
+                if let Some(operator) = operator {
+                    if let Err(err) = operator.delete(result_path).await {
+                        tracing::error!(job_id, "fail to delete the result file: {err}");
+                        continue;
+                    }
+                }
+            }
+            if let Err(err) = ExportJob::delete_by_id(job_id).await {
+                tracing::error!(job_id, "fail to delete the export job: {err}");
+            }
+        }
+    })
+}
+
+/// Returns a cron expression which fires every interval.
+fn interval_cron_expr(interval: Duration) -> String {
+    let secs = interval.as_secs().max(1);
+    if secs < 60 {
+        format!("*/{secs} * * * * *")
+    } else {
+        format!("0 */{} * * * *", (secs / 60).min(59))
+    }
+}
+
+/// Settings for the export jobs.
+#[derive(Debug)]
+struct ExportSettings {
+    /// Name of the storage accessor.
+    accessor: &'static str,
+    /// Directory of the result files.
+    dir: &'static str,
+    /// Number of rows fetched in a batch.
+    batch_size: usize,
+    /// Number of rows between the progress reports.
+    progress_interval: usize,
+    /// Interval for polling the pending jobs.
+    poll_interval: Duration,
+    /// Expiry of the presigned download URL.
+    presign_expiry: Duration,
+    /// Retention period of the result files.
+    retention: Duration,
+}
+
+/// Shared settings for the export jobs.
+static EXPORT_SETTINGS: LazyLock<ExportSettings> = LazyLock::new(|| {
+    let config = State::shared().get_config("export");
+    ExportSettings {
+        accessor: config
+            .and_then(|config| config.get_str("accessor"))
+            .unwrap_or("local"),
+        dir: config
+            .and_then(|config| config.get_str("dir"))
+            .unwrap_or("exports"),
+        batch_size: config
+            .and_then(|config| config.get_usize("batch-size"))
+            .unwrap_or(1000)
+            .max(1),
+        progress_interval: config
+            .and_then(|config| config.get_usize("progress-interval"))
+            .unwrap_or(10000),
+        poll_interval: config
+            .and_then(|config| config.get_duration("poll-interval"))
+            .unwrap_or(Duration::from_secs(10)),
+        presign_expiry: config
+            .and_then(|config| config.get_duration("presign-expiry"))
+            .unwrap_or(Duration::from_secs(60 * 60)),
+        retention: config
+            .and_then(|config| config.get_duration("retention"))
+            .unwrap_or(Duration::from_secs(7 * 24 * 60 * 60)),
+    }
+});
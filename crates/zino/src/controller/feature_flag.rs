@@ -0,0 +1,15 @@
+use zino_core::{application::FeatureFlag, extension::JsonObjectExt, Map};
+use zino_http::response::Response;
+
+/// Controller for the feature flags, which is intended to be registered as debug routes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FeatureFlagController;
+
+impl FeatureFlagController {
+    /// Lists the current states of the feature flags.
+    pub async fn list(req: crate::Request) -> crate::Result {
+        let mut res = Response::default().context(&req);
+        res.set_json_data(Map::data_entries(FeatureFlag::list()));
+        Ok(res.into())
+    }
+}
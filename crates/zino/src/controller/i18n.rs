@@ -0,0 +1,15 @@
+use zino_http::{i18n, response::Response};
+
+/// Controller for the localization, which is intended to be registered as debug routes
+/// to help the translation team.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct I18nController;
+
+impl I18nController {
+    /// Returns the loaded locales, message IDs and missing translation counts per locale.
+    pub async fn stats(req: crate::Request) -> crate::Result {
+        let mut res = Response::default().context(&req);
+        res.set_json_data(i18n::stats());
+        Ok(res.into())
+    }
+}
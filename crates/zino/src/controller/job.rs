@@ -0,0 +1,48 @@
+use zino_core::{
+    error::Error,
+    extension::JsonObjectExt,
+    schedule::{JobRegistry, JobRunRecord},
+    Map,
+};
+use zino_http::{
+    request::RequestContext,
+    response::{Rejection, Response},
+};
+
+/// Controller for the scheduled jobs, which is intended to be registered as debug routes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JobController;
+
+impl JobController {
+    /// Lists the scheduled jobs with their schedules and last runs.
+    pub async fn list(req: crate::Request) -> crate::Result {
+        let mut res = Response::default().context(&req);
+        res.set_json_data(Map::data_entries(JobRegistry::list()));
+        Ok(res.into())
+    }
+
+    /// Lists the recent runs of a job.
+    pub async fn history(req: crate::Request) -> crate::Result {
+        let job_name = req.parse_param::<String>("name")?;
+        let runs = JobRegistry::history(&job_name)
+            .iter()
+            .map(JobRunRecord::to_map)
+            .collect::<Vec<_>>();
+        let mut res = Response::default().context(&req);
+        res.set_json_data(Map::data_entries(runs));
+        Ok(res.into())
+    }
+
+    /// Triggers a job to run immediately.
+    pub async fn trigger(req: crate::Request) -> crate::Result {
+        let job_name = req.parse_param::<String>("name")?;
+        if !JobRegistry::trigger(&job_name) {
+            let err = Error::new(format!("job `{job_name}` does not exist"));
+            return Err(Rejection::not_found(err).context(&req).into());
+        }
+
+        let mut res = Response::default().context(&req);
+        res.set_json_data(Map::from_entry("job_name", job_name));
+        Ok(res.into())
+    }
+}
@@ -0,0 +1,36 @@
+use zino_core::application::MaintenanceMode;
+use zino_http::response::Response;
+
+/// Controller for the maintenance mode, which is intended to be registered as debug routes
+/// with an authentication middleware.
+///
+/// The routes should be allowed in the maintenance mode so that it can be turned off.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaintenanceController;
+
+impl MaintenanceController {
+    /// Returns the status of the maintenance mode.
+    pub async fn status(req: crate::Request) -> crate::Result {
+        let mut res = Response::default().context(&req);
+        res.set_json_data(MaintenanceMode::status());
+        Ok(res.into())
+    }
+
+    /// Enables the maintenance mode.
+    pub async fn enable(req: crate::Request) -> crate::Result {
+        MaintenanceMode::enable();
+
+        let mut res = Response::default().context(&req);
+        res.set_json_data(MaintenanceMode::status());
+        Ok(res.into())
+    }
+
+    /// Disables the maintenance mode.
+    pub async fn disable(req: crate::Request) -> crate::Result {
+        MaintenanceMode::disable();
+
+        let mut res = Response::default().context(&req);
+        res.set_json_data(MaintenanceMode::status());
+        Ok(res.into())
+    }
+}
@@ -1,3 +1,43 @@
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "debug-session")]
+mod debug_session;
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "export")]
+mod export;
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "feature-flag")]
+mod feature_flag;
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "i18n")]
+mod i18n;
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+mod job;
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+mod maintenance;
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "webhook")]
+mod webhook;
+
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "debug-session")]
+pub use debug_session::DebugSessionController;
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "export")]
+pub use export::ExportJobController;
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "feature-flag")]
+pub use feature_flag::FeatureFlagController;
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "i18n")]
+pub use i18n::I18nController;
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+pub use job::JobController;
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+pub use maintenance::MaintenanceController;
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "webhook")]
+pub use webhook::WebhookController;
+
 /// Default controller for the `Model`.
 pub trait DefaultController<K> {
     /// A type for the request extractor.
@@ -48,15 +88,30 @@ pub trait DefaultController<K> {
     /// Exports model data.
     async fn export(req: Self::Request) -> Self::Result;
 
+    /// Creates an async export job for very large datasets.
+    #[cfg(feature = "export")]
+    async fn create_export_job(req: Self::Request) -> Self::Result;
+
     /// Gets the tree hierarchy data.
     async fn tree(req: Self::Request) -> Self::Result;
 
+    /// Gets the number of models for each group.
+    async fn stats(req: Self::Request) -> Self::Result;
+
+    /// Gets the incremental changes of models since a sync token.
+    #[cfg(feature = "sync")]
+    async fn changes(req: Self::Request) -> Self::Result;
+
     /// Gets the Avro schema for the model.
     async fn schema(req: Self::Request) -> Self::Result;
 
     /// Gets the model definition.
     async fn definition(req: Self::Request) -> Self::Result;
 
+    /// Downloads a spreadsheet template for importing model data.
+    #[cfg(feature = "format")]
+    async fn import_template(req: Self::Request) -> Self::Result;
+
     /// Mocks the model data.
     async fn mock(req: Self::Request) -> Self::Result;
 }
@@ -64,22 +119,36 @@ pub trait DefaultController<K> {
 #[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
 #[cfg(feature = "orm")]
 use zino_core::{
+    crypto,
+    datetime::DateTime,
+    encoding::{
+        csv::{CsvNestedLayout, CsvOptions},
+        hex,
+    },
     error::Error,
-    extension::JsonObjectExt,
-    model::{ModelHooks, Mutation, Query},
-    JsonValue, Map,
+    extension::{JsonObjectExt, TomlTableExt},
+    model::{DefaultValue, ModelHooks, Mutation, Page, Preconditions, Query},
+    state::State,
+    warn, JsonValue, LazyLock, Map,
 };
 
 #[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
 #[cfg(feature = "orm")]
 use zino_http::{
     request::RequestContext,
-    response::{ExtractRejection, Rejection, Response},
+    response::{ExtractRejection, Rejection, Response, ResponseCode},
 };
 
 #[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
 #[cfg(feature = "orm")]
-use zino_orm::{ModelAccessor, ModelHelper};
+use zino_orm::{
+    with_max_rows, DateTrunc, ModelAccessor, ModelHelper, ModelRelation, Schema, Transaction,
+    UniqueViolation,
+};
+
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "sync")]
+use zino_orm::{ChangeFeed, SyncToken};
 
 #[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
 #[cfg(feature = "orm")]
@@ -93,8 +162,14 @@ where
     type Result = crate::Result;
 
     async fn new(mut req: Self::Request) -> Self::Result {
+        const { assert_writable::<Self>() };
+        check_model_scope::<K, Self>(&req, "new")?;
+        let version = req.check_api_version()?;
+        let parent_filters = parent_filters::<K, Self>(&req)?;
         let mut model = Self::new();
-        let mut res = req.model_validation(&mut model).await?;
+        let mut res = req
+            .model_validation_with(&mut model, parent_filters)
+            .await?;
         let extension = req.get_data::<<Self as ModelHooks>::Extension>();
         model
             .before_insert_check(extension.as_ref())
@@ -107,7 +182,10 @@ where
         }
 
         let mut model_snapshot = model.snapshot();
-        let ctx = model.insert().await.extract(&req)?;
+        let ctx = model
+            .insert()
+            .await
+            .map_err(|err| reject_conflict(&req, err))?;
         if let Some(last_insert_id) = ctx.last_insert_id() {
             if model_snapshot.get_i64("id") == Some(0) {
                 model_snapshot.upsert("id", last_insert_id);
@@ -118,7 +196,10 @@ where
         Self::after_decode(&mut model_snapshot)
             .await
             .extract(&req)?;
-        Self::before_respond(&mut model_snapshot, extension.as_ref())
+        Self::compute_fields(std::slice::from_mut(&mut model_snapshot))
+            .await
+            .extract(&req)?;
+        prepare_model_response::<K, Self>(&mut model_snapshot, extension.as_ref(), version)
             .await
             .extract(&req)?;
         res.set_json_data(Self::data_item(model_snapshot));
@@ -126,20 +207,30 @@ where
     }
 
     async fn delete(req: Self::Request) -> Self::Result {
+        const { assert_writable::<Self>() };
+        check_model_scope::<K, Self>(&req, "delete")?;
         let id = req.parse_param::<K>("id")?;
-        let model = Self::try_get_model(&id).await.extract(&req)?;
-        model.delete().await.extract(&req)?;
+        let preconditions = req.parse_preconditions()?;
+        check_parent_ownership::<K, Self>(&req, &id).await?;
+        Self::delete_by_id_if(&id, &preconditions)
+            .await
+            .extract(&req)?;
 
         let res = Response::default().context(&req);
         Ok(res.into())
     }
 
     async fn update(mut req: Self::Request) -> Self::Result {
+        const { assert_writable::<Self>() };
+        check_model_scope::<K, Self>(&req, "update")?;
         let id = req.parse_param::<K>("id")?;
+        let preconditions = req.parse_preconditions()?;
+        check_parent_ownership::<K, Self>(&req, &id).await?;
+
         let mut body = req.parse_body().await?;
 
         let extension = req.get_data::<<Self as ModelHooks>::Extension>();
-        let (validation, model) = Self::mutate_by_id(&id, &mut body, extension)
+        let (validation, model) = Self::mutate_by_id_if(&id, &mut body, extension, &preconditions)
             .await
             .extract(&req)?;
         let mut res = Response::from(validation).context(&req);
@@ -151,73 +242,170 @@ where
     }
 
     async fn view(req: Self::Request) -> Self::Result {
+        check_model_scope::<K, Self>(&req, "view")?;
+        let version = req.check_api_version()?;
         let id = req.parse_param::<K>("id")?;
+        check_parent_ownership::<K, Self>(&req, &id).await?;
+
+        let mut res = Response::default().context(&req);
+        let content_type = negotiate_content_type(&req, &mut res)?;
         let extension = req.get_data::<<Self as ModelHooks>::Extension>();
         let mut model = if req.get_query("fetch") == Some("false") {
             Self::find_by_id(&id).await.extract(&req)?
         } else {
             Self::fetch_by_id(&id).await.extract(&req)?
         };
-        Self::before_respond(&mut model, extension.as_ref())
+        if let Some(version) = model.get_u64("version") {
+            res.insert_header("x-etag", Preconditions::entity_tag(version));
+        }
+        if let Some(updated_at) = model
+            .get_str("updated_at")
+            .and_then(|s| s.parse::<DateTime>().ok())
+        {
+            res.insert_header("last-modified", updated_at.to_utc_string());
+        }
+        Self::compute_fields(std::slice::from_mut(&mut model))
+            .await
+            .extract(&req)?;
+        prepare_model_response::<K, Self>(&mut model, extension.as_ref(), version)
             .await
             .extract(&req)?;
 
-        let mut res = Response::default().context(&req);
-        res.set_json_data(Self::data_item(model));
+        match content_type {
+            "text/csv" => res.set_csv_response(vec![model]),
+            "text/tab-separated-values" => {
+                res.set_csv_response_with(vec![model], CsvOptions::tsv())
+            }
+            "application/jsonlines" => res.set_jsonlines_response(vec![model]),
+            content_type => {
+                if content_type == "application/problem+json" {
+                    res.set_content_type("application/problem+json; charset=utf-8");
+                }
+                res.set_json_data(Self::data_item(model));
+            }
+        }
         Ok(res.into())
     }
 
     async fn list(req: Self::Request) -> Self::Result {
+        check_model_scope::<K, Self>(&req, "list")?;
+        let version = req.check_api_version()?;
         let mut query = match req.get_query("query_mode") {
             Some("full") => Self::default_query(),
             Some("snapshot") => Self::default_snapshot_query(),
             _ => Self::default_list_query(),
         };
         let mut res = req.query_validation(&mut query)?;
+        query.append_filters(&mut parent_filters::<K, Self>(&req)?);
+        Self::expand_computed_fields(&mut query);
+
+        let validation = Self::validate_sort_order(&mut query);
+        if !validation.is_success() {
+            return Err(Rejection::bad_request(validation).context(&req).into());
+        }
+        if Self::has_fulltext_columns() {
+            if let Some(term) = req.get_query("search") {
+                query.remove_filter("search");
+                Self::apply_search(&mut query, term);
+            }
+        }
+        elevate_max_rows(&req, &mut query)?;
+
+        let content_type = negotiate_content_type(&req, &mut res)?;
         let extension = req.get_data::<<Self as ModelHooks>::Extension>();
         Self::before_list(&mut query, extension.as_ref())
             .await
             .extract(&req)?;
 
-        let models = if query.populate_enabled() {
-            let mut models = Self::fetch(&query).await.extract(&req)?;
-            for model in models.iter_mut() {
-                Self::before_respond(model, extension.as_ref())
-                    .await
-                    .extract(&req)?;
-            }
-            models
-        } else {
-            let mut models = Self::find(&query).await.extract(&req)?;
-            let translate_enabled = query.translate_enabled();
-            for model in models.iter_mut() {
-                translate_enabled.then(|| Self::translate_model(model));
-                Self::after_decode(model).await.extract(&req)?;
-                Self::before_respond(model, extension.as_ref())
-                    .await
-                    .extract(&req)?;
+        if let Some(if_none_match) = req.get_header("if-none-match") {
+            if fingerprint_enabled(Self::table_name()) {
+                if let Some(fingerprint) = Self::fingerprint(&query).await.extract(&req)? {
+                    let seed = format!(
+                        "{}|{fingerprint}|{query:?}|{content_type}|{version:?}",
+                        Self::model_name()
+                    );
+                    let etag = fingerprint_entity_tag(&seed);
+                    if if_none_match
+                        .split(',')
+                        .any(|tag| tag.trim().trim_start_matches("W/") == etag)
+                    {
+                        let mut res = Response::not_modified().context(&req);
+                        res.insert_header("x-etag", etag);
+                        return Ok(res.into());
+                    }
+                    res.insert_header("x-etag", etag);
+                }
             }
-            models
-        };
+        }
 
-        let mut data = Self::data_items(models);
-        if let Some(page_size) = req.get_query("page_size").and_then(|s| s.parse().ok()) {
-            if req.get_query("total_rows").is_none() {
-                let total_rows = Self::count(&query).await.extract(&req)?;
-                let page_count = total_rows.div_ceil(page_size);
-                data.upsert("total_rows", total_rows);
-                data.upsert("page_count", page_count);
+        let (result, truncated) = with_max_rows(None, async {
+            if query.populate_enabled() {
+                Self::fetch(&query).await
+            } else {
+                let mut models = Self::find::<Map>(&query).await?;
+                let translate_enabled = query.translate_enabled();
+                for model in models.iter_mut() {
+                    translate_enabled.then(|| Self::translate_model(model));
+                    Self::after_decode(model).await?;
+                }
+                Ok(models)
             }
+        })
+        .await;
+        let mut models = result.extract(&req)?;
+        if truncated {
+            res.insert_header("x-truncated", "true");
         }
-        res.set_json_data(data);
+        Self::compute_fields(&mut models).await.extract(&req)?;
+        for model in models.iter_mut() {
+            prepare_model_response::<K, Self>(model, extension.as_ref(), version)
+                .await
+                .extract(&req)?;
+        }
+        match content_type {
+            "text/csv" => {
+                res.set_csv_response(models);
+                res.transform_data().await.extract(&req)?;
+                return Ok(res.into());
+            }
+            "text/tab-separated-values" => {
+                res.set_csv_response_with(models, CsvOptions::tsv());
+                res.transform_data().await.extract(&req)?;
+                return Ok(res.into());
+            }
+            "application/jsonlines" => {
+                res.set_jsonlines_response(models);
+                res.transform_data().await.extract(&req)?;
+                return Ok(res.into());
+            }
+            "application/problem+json" => {
+                res.set_content_type("application/problem+json; charset=utf-8");
+            }
+            _ => (),
+        }
+
+        let total_rows =
+            if req.get_query("page_size").is_some() && req.get_query("total_rows").is_none() {
+                Some(Self::count(&query).await.extract(&req)? as usize)
+            } else {
+                None
+            };
+        let page = Page::from_query_results(models, total_rows, &query)
+            .item_name(Self::ITEM_NAME.1)
+            .truncated(truncated);
+        res.set_page_data(page, &req);
         Ok(res.into())
     }
 
     async fn fetch(mut req: Self::Request) -> Self::Result {
+        check_model_scope::<K, Self>(&req, "fetch")?;
+        let version = req.check_api_version()?;
         let mut query = Self::default_list_query();
         let mut res = req.query_validation(&mut query)?;
         let mut body = req.parse_body().await?;
         query.append_filters(&mut body);
+        query.append_filters(&mut parent_filters::<K, Self>(&req)?);
+        Self::expand_computed_fields(&mut query);
 
         let extension = req.get_data::<<Self as ModelHooks>::Extension>();
         Self::before_list(&mut query, extension.as_ref())
@@ -225,34 +413,42 @@ where
             .extract(&req)?;
 
         let mut models = Self::fetch(&query).await.extract(&req)?;
+        Self::compute_fields(&mut models).await.extract(&req)?;
         for model in models.iter_mut() {
-            Self::before_respond(model, extension.as_ref())
+            prepare_model_response::<K, Self>(model, extension.as_ref(), version)
                 .await
                 .extract(&req)?;
         }
 
-        let mut data = Self::data_items(models);
-        if let Some(page_size) = req.get_query("page_size").and_then(|s| s.parse().ok()) {
-            if req.get_query("total_rows").is_none() {
-                let total_rows = Self::count(&query).await.extract(&req)?;
-                let page_count = total_rows.div_ceil(page_size);
-                data.upsert("total_rows", total_rows);
-                data.upsert("page_count", page_count);
-            }
-        }
-        res.set_json_data(data);
+        let total_rows =
+            if req.get_query("page_size").is_some() && req.get_query("total_rows").is_none() {
+                Some(Self::count(&query).await.extract(&req)? as usize)
+            } else {
+                None
+            };
+        let page =
+            Page::from_query_results(models, total_rows, &query).item_name(Self::ITEM_NAME.1);
+        res.set_page_data(page, &req);
         Ok(res.into())
     }
 
     async fn soft_delete(req: Self::Request) -> Self::Result {
+        const { assert_writable::<Self>() };
+        check_model_scope::<K, Self>(&req, "soft_delete")?;
         let id = req.parse_param::<K>("id")?;
-        Self::soft_delete_by_id(&id).await.extract(&req)?;
+        let preconditions = req.parse_preconditions()?;
+        check_parent_ownership::<K, Self>(&req, &id).await?;
+        Self::soft_delete_by_id_if(&id, &preconditions)
+            .await
+            .extract(&req)?;
 
         let res = Response::default().context(&req);
         Ok(res.into())
     }
 
     async fn lock(req: Self::Request) -> Self::Result {
+        const { assert_writable::<Self>() };
+        check_model_scope::<K, Self>(&req, "lock")?;
         let id = req.parse_param::<K>("id")?;
         Self::lock_by_id(&id).await.extract(&req)?;
 
@@ -261,6 +457,8 @@ where
     }
 
     async fn archive(req: Self::Request) -> Self::Result {
+        const { assert_writable::<Self>() };
+        check_model_scope::<K, Self>(&req, "archive")?;
         let id = req.parse_param::<K>("id")?;
         Self::archive_by_id(&id).await.extract(&req)?;
 
@@ -269,11 +467,15 @@ where
     }
 
     async fn batch_insert(mut req: Self::Request) -> Self::Result {
+        const { assert_writable::<Self>() };
+        check_model_scope::<K, Self>(&req, "batch_insert")?;
+        let parent_filters = parent_filters::<K, Self>(&req)?;
         let data = req.parse_body::<Vec<Map>>().await?;
         let extension = req.get_data::<<Self as ModelHooks>::Extension>();
         let mut models = Vec::with_capacity(data.len());
         let mut validations = Vec::new();
         for (index, mut map) in data.into_iter().enumerate() {
+            map.append(&mut parent_filters.clone());
             Self::before_extract()
                 .await
                 .map_err(|err| Rejection::from_error(err).context(&req))?;
@@ -310,7 +512,9 @@ where
             res.set_json_data(validations);
             Ok(res.into())
         } else {
-            let ctx = Self::insert_many(models).await.extract(&req)?;
+            let ctx = Self::insert_many(models)
+                .await
+                .map_err(|err| reject_conflict(&req, err))?;
             let data = Map::from_entry("rows_affected", ctx.rows_affected());
             let mut res = Response::default().context(&req);
             res.set_json_data(data);
@@ -319,6 +523,8 @@ where
     }
 
     async fn batch_delete(mut req: Self::Request) -> Self::Result {
+        const { assert_writable::<Self>() };
+        check_model_scope::<K, Self>(&req, "batch_delete")?;
         let data = req.parse_body::<JsonValue>().await?;
         let mut query = if let JsonValue::Object(map) = data {
             if map.is_empty() {
@@ -347,6 +553,8 @@ where
     }
 
     async fn batch_update(mut req: Self::Request) -> Self::Result {
+        const { assert_writable::<Self>() };
+        check_model_scope::<K, Self>(&req, "batch_update")?;
         let data = req.parse_body::<Vec<Map>>().await?;
 
         // Should use `Self::transaction` when the `Send` bound is resolved
@@ -369,10 +577,39 @@ where
     }
 
     async fn import(mut req: Self::Request) -> Self::Result {
+        const { assert_writable::<Self>() };
+        check_model_scope::<K, Self>(&req, "import")?;
         let mut query = Query::new(Map::new());
         let mut res = req.query_validation(&mut query)?;
 
+        let parent_filters = parent_filters::<K, Self>(&req)?;
+        if req
+            .get_header("content-type")
+            .and_then(|content_type| content_type.split(';').next())
+            .is_some_and(|essence| NDJSON_CONTENT_TYPES.contains(&essence.trim()))
+        {
+            let summary = ingest_ndjson::<K, Self>(&mut req, &query, parent_filters).await?;
+            res.set_json_data(summary);
+            return Ok(res.into());
+        }
+
         let data = req.parse_body::<Vec<Map>>().await?;
+        if query.filters().get_str("nested") == Some("true") {
+            // The nested documents are inserted inside of a transaction without running the hooks.
+            let (rows_affected, validations) = ModelRelation::root::<Self>()
+                .import_nested(data)
+                .await
+                .map_err(|err| reject_conflict(&req, err))?;
+            if validations.is_empty() {
+                res.set_json_data(Map::from_entry("rows_affected", rows_affected));
+                return Ok(res.into());
+            } else {
+                let mut res = Response::bad_request();
+                res.set_json_data(Map::from_entry("validations", validations));
+                return Ok(res.into());
+            }
+        }
+
         let extension = req.get_data::<<Self as ModelHooks>::Extension>();
         let validate_only = query.validate_only();
         let no_check = query.no_check();
@@ -390,19 +627,31 @@ where
             };
             (false, batch_size)
         };
+        let transactional = !(enable_upsert || validate_only)
+            && query_filters.get_str("transactional") == Some("true");
+        let copy_enabled = !(enable_upsert || validate_only || transactional)
+            && Self::driver_name() == "postgres"
+            && COPY_THRESHOLD.is_some_and(|threshold| data.len() > threshold);
 
         let mut rows_affected = 0;
         let mut validations = Vec::new();
         let mut batch_models = Vec::with_capacity(batch_size);
+        let mut model_indexes = Vec::new();
         for (index, mut map) in data.into_iter().enumerate() {
             if limit > 0 && rows_affected >= limit {
                 break;
             }
-            if batch_models.len() == batch_size && batch_size > 0 {
+            if batch_models.len() == batch_size
+                && batch_size > 0
+                && !(transactional || copy_enabled)
+            {
                 let mut models = Vec::with_capacity(batch_size);
                 models.append(&mut batch_models);
-                Self::insert_many(models).await.extract(&req)?;
+                Self::insert_many(models)
+                    .await
+                    .map_err(|err| reject_conflict(&req, err))?;
             }
+            map.append(&mut parent_filters.clone());
             Self::before_extract()
                 .await
                 .map_err(|err| Rejection::from_error(err).context(&req))?;
@@ -429,9 +678,20 @@ where
                 }
                 if !validate_only {
                     if enable_upsert {
-                        model.upsert().await.extract(&req)?;
+                        model
+                            .upsert()
+                            .await
+                            .map_err(|err| reject_conflict(&req, err))?;
+                    } else if transactional {
+                        batch_models.push(model);
+                        model_indexes.push(index);
+                    } else if copy_enabled {
+                        batch_models.push(model);
                     } else if batch_size == 1 {
-                        model.insert().await.extract(&req)?;
+                        model
+                            .insert()
+                            .await
+                            .map_err(|err| reject_conflict(&req, err))?;
                     } else {
                         batch_models.push(model);
                     }
@@ -450,12 +710,40 @@ where
                 }
             }
         }
-        if !batch_models.is_empty() {
-            Self::insert_many(batch_models).await.extract(&req)?;
+        if transactional {
+            // Each model is inserted in a savepoint, so a constraint violation
+            // only rolls back the offending row.
+            let (num_inserted, failures) =
+                <Self as Transaction<K, _>>::transactional_try_insert_many(batch_models)
+                    .await
+                    .extract(&req)?;
+            for (position, err) in failures {
+                let mut map = match UniqueViolation::from_error(&err) {
+                    Some(violation) => violation.to_validation().into_map(),
+                    None => Map::from_entry("error", err.message()),
+                };
+                map.upsert("index", model_indexes[position]);
+                validations.push(map);
+            }
+            rows_affected = num_inserted as usize;
+        } else if copy_enabled {
+            // The models are loaded with the `COPY` protocol.
+            let rows = futures::stream::iter(batch_models);
+            rows_affected = Self::copy_in(rows)
+                .await
+                .map_err(|err| reject_conflict(&req, err))? as usize;
+        } else if !batch_models.is_empty() {
+            Self::insert_many(batch_models)
+                .await
+                .map_err(|err| reject_conflict(&req, err))?;
         }
 
         let data = if validations.is_empty() {
             Map::from_entry("rows_affected", rows_affected)
+        } else if transactional {
+            let mut data = Map::from_entry("rows_affected", rows_affected);
+            data.upsert("validations", validations);
+            data
         } else {
             Map::from_entry("validations", validations)
         };
@@ -464,35 +752,95 @@ where
     }
 
     async fn export(req: Self::Request) -> Self::Result {
+        check_model_scope::<K, Self>(&req, "export")?;
+        let version = req.check_api_version()?;
         let mut query = Self::default_query();
         let mut res = req.query_validation(&mut query)?;
+        query.append_filters(&mut parent_filters::<K, Self>(&req)?);
+        Self::expand_computed_fields(&mut query);
+        elevate_max_rows(&req, &mut query)?;
+
         let extension = req.get_data::<<Self as ModelHooks>::Extension>();
         Self::before_list(&mut query, extension.as_ref())
             .await
             .extract(&req)?;
 
-        let mut models = Self::find(&query).await.extract(&req)?;
+        let (result, truncated) = with_max_rows(None, Self::find(&query)).await;
+        let mut models = result.extract(&req)?;
+        if truncated {
+            res.insert_header("x-truncated", "true");
+        }
         let translate_enabled = query.translate_enabled();
         for model in models.iter_mut() {
             translate_enabled.then(|| Self::translate_model(model));
             Self::after_decode(model).await.extract(&req)?;
-            Self::before_respond(model, extension.as_ref())
+        }
+        Self::compute_fields(&mut models).await.extract(&req)?;
+        for model in models.iter_mut() {
+            prepare_model_response::<K, Self>(model, extension.as_ref(), version)
                 .await
                 .extract(&req)?;
         }
 
-        let format = req.get_query("format").unwrap_or("json");
-        match format {
-            "csv" => res.set_csv_response(models),
-            "jsonlines" => res.set_jsonlines_response(models),
-            _ => res.set_json_response(models),
+        let content_type = negotiate_content_type(&req, &mut res)?;
+        let csv_options = match content_type {
+            "text/csv" => Some(CsvOptions::new()),
+            "text/tab-separated-values" => Some(CsvOptions::tsv()),
+            _ => None,
+        };
+        if let Some(include) = req.get_query("include").filter(|s| !s.is_empty()) {
+            if csv_options.is_some() {
+                let err = warn!("the nested documents can not be exported as CSV");
+                let rejection = Rejection::from_validation_entry("include", err);
+                return Err(rejection.context(&req).into());
+            }
+
+            let include = include.split(',').collect::<Vec<_>>();
+            ModelRelation::root::<Self>()
+                .embed_children(&mut models, &include)
+                .await
+                .map_err(|err| Rejection::from_validation_entry("include", err).context(&req))?;
+        }
+        if let Some(options) = csv_options {
+            let options = csv_export_options::<K, Self>(&req, options);
+            let file_name = format!("{}.{}", Self::model_name(), options.file_extension());
+            res.insert_header(
+                "content-disposition",
+                format!(r#"attachment; filename="{file_name}""#),
+            );
+            if req.get_query("stream") == Some("true") {
+                res.set_csv_stream_response(futures::stream::iter(models), options);
+                return Ok(res.into());
+            }
+            res.set_csv_response_with(models, options);
+        } else if content_type == "application/jsonlines" {
+            res.set_jsonlines_response(models);
+        } else {
+            res.set_json_response(models);
         }
+        res.transform_data().await.extract(&req)?;
         Ok(res.into())
     }
 
+    #[cfg(feature = "export")]
+    async fn create_export_job(req: Self::Request) -> Self::Result {
+        check_model_scope::<K, Self>(&req, "create_export_job")?;
+        let mut query = Self::default_query();
+        req.query_validation::<zino_http::response::StatusCode>(&mut query)?;
+
+        let extension = req.get_data::<<Self as ModelHooks>::Extension>();
+        Self::before_list(&mut query, extension.as_ref())
+            .await
+            .extract(&req)?;
+        export::create_export_job::<Self>(&req, &query).await
+    }
+
     async fn tree(req: Self::Request) -> Self::Result {
+        check_model_scope::<K, Self>(&req, "tree")?;
+        let version = req.check_api_version()?;
         let mut query = Self::default_list_query();
         let mut res = req.query_validation(&mut query)?;
+        Self::expand_computed_fields(&mut query);
         let extension = req.get_data::<<Self as ModelHooks>::Extension>();
         Self::before_list(&mut query, extension.as_ref())
             .await
@@ -501,6 +849,9 @@ where
         let parent_id = req.get_query("parent_id").unwrap_or("null");
         query.add_filter("parent_id", parent_id);
 
+        let mut parent_filters = parent_filters::<K, Self>(&req)?;
+        query.append_filters(&mut parent_filters.clone());
+
         let mut models = if query.populate_enabled() {
             Self::fetch(&query).await.extract(&req)?
         } else {
@@ -518,16 +869,29 @@ where
             .iter()
             .filter_map(|model| model.get(primary_key_name).cloned())
             .collect::<Vec<_>>();
-        let mut query = Self::default_snapshot_query();
-        query.add_filter("parent_id", Map::from_entry("$in", values));
-        query.add_filter("status", Map::from_entry("$ne", "Deleted"));
-        query.order_desc("parent_id");
-        query.order_desc("created_at");
-        query.disable_limit();
+        let mut children_query = Self::default_snapshot_query();
+        children_query.append_filters(&mut parent_filters);
+        children_query.add_filter("parent_id", Map::from_entry("$in", values));
+        children_query.add_filter("status", Map::from_entry("$ne", "Deleted"));
+        children_query.order_desc("parent_id");
+        children_query.order_desc("created_at");
+        children_query.disable_limit();
+
+        let mut children = Self::find::<Map>(&children_query).await.extract(&req)?;
+        Self::compute_fields(&mut children).await.extract(&req)?;
+        for child in children.iter_mut() {
+            prepare_model_response::<K, Self>(child, extension.as_ref(), version)
+                .await
+                .extract(&req)?;
+        }
 
-        let mut children = Self::find::<Map>(&query).await.extract(&req)?;
-        let total_rows = children.len();
+        Self::compute_fields(&mut models).await.extract(&req)?;
+        let num_children = children.len();
         for model in models.iter_mut() {
+            prepare_model_response::<K, Self>(model, extension.as_ref(), version)
+                .await
+                .extract(&req)?;
+
             let model_id = model.get(primary_key_name);
 
             // Should use `extract_if` when it is stabilized.
@@ -544,65 +908,291 @@ where
             model.upsert("children", model_children);
         }
 
-        let mut data = Self::data_items(models);
-        data.upsert("total_rows", total_rows);
+        let page = Page::from_query_results(models, None, &query)
+            .item_name(Self::ITEM_NAME.1)
+            .aggregate("num_children", num_children);
+        res.set_json_data(page);
+        Ok(res.into())
+    }
+
+    async fn stats(req: Self::Request) -> Self::Result {
+        check_model_scope::<K, Self>(&req, "stats")?;
+        let mut query = Self::default_query();
+        query.disable_limit();
+
+        let mut res = req.query_validation(&mut query)?;
+        query.remove_filter("group_by");
+
+        let group_by = req.get_query("group_by").unwrap_or_default();
+        let group_columns = group_by
+            .split(',')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>();
+        if group_columns.is_empty() {
+            let err = warn!("group columns should be nonempty");
+            return Err(Rejection::from_validation_entry("group_by", err)
+                .context(&req)
+                .into());
+        }
+        for group_column in group_columns.iter() {
+            let (col_name, unit) = match group_column.split_once(':') {
+                Some((col_name, unit)) => (col_name, Some(unit)),
+                None => (*group_column, None),
+            };
+            let err = match Self::get_column(col_name) {
+                Some(col) if col.is_write_only() => {
+                    Some(warn!("column `{}` can not be grouped", col_name))
+                }
+                Some(col) => unit.and_then(|unit| {
+                    if DateTrunc::parse(unit).is_none() {
+                        Some(warn!("unit `{}` is unsupported", unit))
+                    } else if !col.is_datetime_type() {
+                        Some(warn!("column `{}` should be a datetime type", col_name))
+                    } else {
+                        None
+                    }
+                }),
+                None => Some(warn!("column `{}` does not exist", col_name)),
+            };
+            if let Some(err) = err {
+                return Err(Rejection::from_validation_entry("group_by", err)
+                    .context(&req)
+                    .into());
+            }
+        }
+
+        let extension = req.get_data::<<Self as ModelHooks>::Extension>();
+        Self::before_list(&mut query, extension.as_ref())
+            .await
+            .extract(&req)?;
+
+        let groups = Self::count_grouped::<_, Map>(&query, &group_columns)
+            .await
+            .extract(&req)?;
+        let mut data = Map::from_entry("num_groups", groups.len());
+        data.upsert("groups", groups);
         res.set_json_data(data);
         Ok(res.into())
     }
 
+    #[cfg(feature = "sync")]
+    async fn changes(req: Self::Request) -> Self::Result {
+        check_model_scope::<K, Self>(&req, "changes")?;
+        let version = req.check_api_version()?;
+        let since = match req.get_query("since").filter(|s| !s.is_empty()) {
+            Some(token) => match token.parse::<SyncToken>() {
+                Ok(token) => Some(token),
+                Err(err) => {
+                    let rejection = Rejection::from_validation_entry("since", err);
+                    return Err(rejection.context(&req).into());
+                }
+            },
+            None => None,
+        };
+
+        // The soft-deleted rows are kept in the query to be reported as deleted ids.
+        let mut query = Self::default_query();
+        query.deny_fields(&["extra"]);
+        let mut res = req.query_validation(&mut query)?;
+        query.remove_filter("since");
+        query.remove_filter("batch_size");
+        query.append_filters(&mut parent_filters::<K, Self>(&req)?);
+
+        let extension = req.get_data::<<Self as ModelHooks>::Extension>();
+        Self::before_list(&mut query, extension.as_ref())
+            .await
+            .extract(&req)?;
+
+        let translate_enabled = query.translate_enabled();
+        let mut feed = ChangeFeed::<Self>::new(query).since(since);
+        if let Some(batch_size) = req.get_query("batch_size").and_then(|s| s.parse().ok()) {
+            feed = feed.batch_size(batch_size);
+        }
+        let mut changes = feed.fetch().await.extract(&req)?;
+        let models = changes.entries_mut();
+        for model in models.iter_mut() {
+            translate_enabled.then(|| Self::translate_model(model));
+            Self::after_decode(model).await.extract(&req)?;
+        }
+        Self::compute_fields(models).await.extract(&req)?;
+        for model in models.iter_mut() {
+            prepare_model_response::<K, Self>(model, extension.as_ref(), version)
+                .await
+                .extract(&req)?;
+        }
+        res.set_json_data(changes.into_map());
+        Ok(res.into())
+    }
+
     async fn schema(req: Self::Request) -> Self::Result {
-        let schema = serde_json::to_value(Self::schema()).extract(&req)?;
+        let version = requested_api_version(&req)?;
+        let key = match version {
+            Some(version) => format!("schema:v{version}"),
+            None => "schema".to_owned(),
+        };
+        let cache = Self::definition_cache();
+        let schema = if let Some(schema) = cache.get(&key) {
+            schema
+        } else {
+            let mut schema = serde_json::to_value(Self::schema()).extract(&req)?;
+            if let Some(version) = version {
+                if let Some(fields) = schema.get_mut("fields").and_then(|v| v.as_array_mut()) {
+                    adapt_schema_fields::<K, Self>(fields, version);
+                }
+            }
+            cache.get_or_insert_with(&key, || schema)
+        };
         let mut res = Response::default().context(&req);
-        res.set_json_response(schema);
+        res.set_json_response(schema.as_ref().clone());
         Ok(res.into())
     }
 
     async fn definition(req: Self::Request) -> Self::Result {
+        if req.get_query("format") == Some("template") {
+            let mut res = Response::default().context(&req);
+            res.set_json_response(import_template_columns::<K, Self>());
+            return Ok(res.into());
+        }
+
+        let version = requested_api_version(&req)?;
         let action = req.get_query("action").unwrap_or("insert");
-        let columns = Self::columns();
-        let mut definition = Map::new();
-        definition.upsert("type", "object");
-        if matches!(action, "insert" | "import") {
-            let required_fields = columns
-                .iter()
-                .filter(|&col| {
-                    col.is_not_null() && !col.is_primary_key() || col.has_attribute("nonempty")
-                })
-                .map(|col| col.name())
-                .collect::<Vec<_>>();
-            definition.upsert("required", required_fields);
-        }
-
-        let exclusive_attributes = if action == "update" {
-            vec!["read_only", "generated", "reserved"]
+        let mut key = format!("definition:{action}");
+        if let Some(version) = version {
+            key = format!("{key}:v{version}");
+        }
+        #[cfg(feature = "i18n")]
+        let locale = req.locale();
+        #[cfg(feature = "i18n")]
+        if let Some(locale) = locale.as_ref() {
+            key = format!("{key}:{locale}");
+        }
+
+        // Only the rare dynamic bits are built for each locale and API version.
+        let definition = Self::definition_cache().get_or_insert_with(&key, || {
+            let mut definition = Self::definition_cached(action).as_ref().clone();
+            if let JsonValue::Object(definition) = &mut definition {
+                if let Some(JsonValue::Object(properties)) = definition.get_mut("properties") {
+                    if let Some(version) = version {
+                        Self::adapt_response(version, properties);
+                    }
+                    #[cfg(feature = "i18n")]
+                    if let Some(locale) = locale.as_ref() {
+                        // The labels and help texts may be message IDs of the localization.
+                        for property in properties.values_mut() {
+                            let Some(property) = property.as_object_mut() else {
+                                continue;
+                            };
+                            for key in ["label", "help"] {
+                                let translation = property.get_str(key).and_then(|message| {
+                                    zino_http::i18n::try_translate(locale, message)
+                                });
+                                if let Some(translation) = translation {
+                                    property.upsert(key, translation.as_ref());
+                                }
+                            }
+                        }
+                    }
+                }
+                if version.is_some() {
+                    let fields = definition
+                        .get_object("properties")
+                        .map(|properties| properties.keys().cloned().collect::<Vec<_>>())
+                        .unwrap_or_default();
+                    if let Some(JsonValue::Array(required_fields)) = definition.get_mut("required")
+                    {
+                        required_fields.retain(|field| {
+                            field
+                                .as_str()
+                                .is_some_and(|s| fields.iter().any(|field| field == s))
+                        });
+                    }
+                }
+                if let Some(required_scopes) = model_required_scopes::<K, Self>() {
+                    definition.upsert("x-required-scopes", required_scopes);
+                }
+            }
+            if action == "import" {
+                let mut data = Map::new();
+                data.upsert("type", "array");
+                data.upsert("items", definition);
+                data.into()
+            } else {
+                definition
+            }
+        });
+
+        // The default values of the expressions are evaluated for each request.
+        let mut data = definition.as_ref().clone();
+        let definition = if action == "import" {
+            data.get_mut("items")
         } else {
-            vec!["read_only", "generated", "reserved", "auto_initialized"]
+            Some(&mut data)
         };
-        let mut properties = Map::new();
-        for col in columns {
-            if !col.has_any_attributes(&exclusive_attributes) && col.comment().is_some()
-                || matches!(action, "list" | "view" | "export" | "tree")
-            {
-                properties.upsert(col.name(), col.definition());
+        if let Some(JsonValue::Object(properties)) =
+            definition.and_then(|definition| definition.get_mut("properties"))
+        {
+            let mut model = None;
+            for col in Self::columns() {
+                let Some(expr) = col
+                    .default_value()
+                    .filter(|&expr| DefaultValue::is_expression(expr))
+                else {
+                    continue;
+                };
+                let Some(JsonValue::Object(col_definition)) = properties.get_mut(col.name()) else {
+                    continue;
+                };
+                let default_value = if expr.starts_with("fn:") {
+                    model.get_or_insert_with(Self::new).default_for(col.name())
+                } else {
+                    DefaultValue::evaluate(expr)
+                };
+                if let Some(value) = default_value {
+                    col_definition.upsert("default", value);
+                }
             }
         }
-        definition.upsert("properties", properties);
 
-        let data = if action == "import" {
-            let mut data = Map::new();
-            data.upsert("type", "array");
-            data.upsert("items", definition);
-            data
-        } else {
-            definition
+        let mut res = Response::default().context(&req);
+        res.set_json_response(data);
+        Ok(res.into())
+    }
+
+    #[cfg(feature = "format")]
+    async fn import_template(req: Self::Request) -> Self::Result {
+        let columns = import_template_columns::<K, Self>();
+        let template = zino_extra::format::ImportTemplate::from_columns(&columns);
+        let (bytes, content_type, extension) = match req.get_query("format") {
+            Some("csv") | None => (template.to_csv(), "text/csv; charset=utf-8", "csv"),
+            #[cfg(feature = "format-xlsx")]
+            Some("xlsx") => (
+                template.to_xlsx(),
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+                "xlsx",
+            ),
+            Some(format) => {
+                let err = warn!("import template format `{}` is unsupported", format);
+                return Err(Rejection::from_validation_entry("format", err)
+                    .context(&req)
+                    .into());
+            }
         };
+        let bytes = bytes.extract(&req)?;
+        let file_name = format!("{}_import_template.{extension}", Self::model_name());
 
         let mut res = Response::default().context(&req);
-        res.set_json_response(data);
+        res.set_bytes_data(bytes);
+        res.set_content_type(content_type);
+        res.insert_header(
+            "content-disposition",
+            format!(r#"attachment; filename="{file_name}""#),
+        );
         Ok(res.into())
     }
 
     async fn mock(req: Self::Request) -> Self::Result {
+        let version = req.check_api_version()?;
         let mut query = Query::default();
         let mut res = req.query_validation(&mut query)?;
 
@@ -613,7 +1203,10 @@ where
             let (validation, model) = Self::mock().await.extract(&req)?;
             if validation.is_success() && !validate_only {
                 let mut model_snapshot = model.snapshot();
-                let ctx = model.insert().await.extract(&req)?;
+                let ctx = model
+                    .insert()
+                    .await
+                    .map_err(|err| reject_conflict(&req, err))?;
                 if let Some(last_insert_id) = ctx.last_insert_id() {
                     if model_snapshot.get_i64("id") == Some(0) {
                         model_snapshot.upsert("id", last_insert_id);
@@ -624,9 +1217,527 @@ where
                 models.push(model.into_map());
             }
         }
+        for model in models.iter_mut() {
+            Self::redact_model(model);
+            if let Some(version) = version {
+                Self::adapt_response(version, model);
+            }
+        }
 
         let data = Self::data_items(models);
         res.set_json_data(data);
         Ok(res.into())
     }
 }
+
+/// Prepares the model data for the response by running the `before_respond` hook,
+/// redacting the fields and adapting the data for the API version.
+/// All the model data sent out should go through it.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+async fn prepare_model_response<K, M>(
+    model: &mut Map,
+    extension: Option<&<M as ModelHooks>::Extension>,
+    version: Option<u32>,
+) -> Result<(), Error>
+where
+    K: Default + std::fmt::Display + PartialEq,
+    M: ModelAccessor<K>,
+{
+    M::before_respond(model, extension).await?;
+    M::redact_model(model);
+    if let Some(version) = version {
+        M::adapt_response(version, model);
+    }
+    Ok(())
+}
+
+/// Returns the options for exporting the models as CSV or TSV. The columns follow the order
+/// of the model fields, and the options can be customized by the query parameters
+/// `bom`, `nested` (`json` or `flatten`) and `null`.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+fn csv_export_options<K, M>(req: &crate::Request, options: CsvOptions) -> CsvOptions
+where
+    K: Default + std::fmt::Display + PartialEq,
+    M: ModelAccessor<K>,
+{
+    let nested_layout = if req.get_query("nested") == Some("flatten") {
+        CsvNestedLayout::Flatten
+    } else {
+        CsvNestedLayout::Json
+    };
+    options
+        .bom(req.get_query("bom") == Some("true"))
+        .null_value(req.get_query("null").unwrap_or_default())
+        .nested_layout(nested_layout)
+        .columns(M::fields().iter().copied())
+}
+
+/// Asserts that the model is writable. It is evaluated in a const context
+/// when a write handler is instantiated, so that registering a write route
+/// for a read-only model fails to build instead of failing at request time.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+const fn assert_writable<M: Schema>() {
+    assert!(
+        !M::READ_ONLY,
+        "write routes can not be registered for a read-only model"
+    );
+}
+
+/// Returns the required scope `{scope_name}:{access}` for the handler of the model
+/// if the model scopes are enabled by `[auth.scopes] model-scopes`.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+fn model_scope<K, M>(handler: &str) -> Option<String>
+where
+    K: Default + std::fmt::Display + PartialEq,
+    M: ModelAccessor<K>,
+{
+    if !*MODEL_SCOPES_ENABLED {
+        return None;
+    }
+
+    let scope_name = M::SCOPE_NAME.unwrap_or_else(|| M::model_name());
+    HANDLER_ACCESSES
+        .iter()
+        .find_map(|&(name, access)| (name == handler).then(|| format!("{scope_name}:{access}")))
+}
+
+/// Returns the required scopes for the handlers of the model
+/// if the model scopes are enabled by `[auth.scopes] model-scopes`.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+fn model_required_scopes<K, M>() -> Option<Map>
+where
+    K: Default + std::fmt::Display + PartialEq,
+    M: ModelAccessor<K>,
+{
+    let mut required_scopes = Map::new();
+    for (handler, _) in HANDLER_ACCESSES {
+        required_scopes.upsert(handler, model_scope::<K, M>(handler)?);
+    }
+    Some(required_scopes)
+}
+
+/// Checks the required scope for the handler of the model.
+/// It responds `403 Forbidden` if the scope is not granted.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+#[cfg(feature = "jwt")]
+fn check_model_scope<K, M>(req: &crate::Request, handler: &str) -> Result<(), Rejection>
+where
+    K: Default + std::fmt::Display + PartialEq,
+    M: ModelAccessor<K>,
+{
+    match model_scope::<K, M>(handler) {
+        Some(scope) => req.require_scopes(&[&scope]),
+        None => Ok(()),
+    }
+}
+
+/// Checks the required scope for the handler of the model.
+/// It is a no-op since the `jwt` feature is disabled.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+#[cfg(not(feature = "jwt"))]
+#[inline]
+fn check_model_scope<K, M>(_req: &crate::Request, _handler: &str) -> Result<(), Rejection>
+where
+    K: Default + std::fmt::Display + PartialEq,
+    M: ModelAccessor<K>,
+{
+    Ok(())
+}
+
+/// Disables the max rows of the query if `max_rows=unlimited` is requested.
+/// It responds `403 Forbidden` if the `export:unlimited` scope is not granted.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+#[cfg(feature = "jwt")]
+fn elevate_max_rows(req: &crate::Request, query: &mut Query) -> Result<(), Rejection> {
+    query.remove_filter("max_rows");
+    if req.get_query("max_rows") == Some("unlimited") {
+        req.require_scopes(&["export:unlimited"])?;
+        query.disable_max_rows();
+    }
+    Ok(())
+}
+
+/// Disables the max rows of the query if `max_rows=unlimited` is requested.
+/// It always responds `403 Forbidden` since the `jwt` feature is disabled.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+#[cfg(not(feature = "jwt"))]
+fn elevate_max_rows(req: &crate::Request, query: &mut Query) -> Result<(), Rejection> {
+    query.remove_filter("max_rows");
+    if req.get_query("max_rows") == Some("unlimited") {
+        let err = warn!("the `export:unlimited` scope can not be verified");
+        return Err(Rejection::forbidden(err).context(req));
+    }
+    Ok(())
+}
+
+/// Returns the filters of the parent resources from the route params,
+/// which is empty if the route is not nested.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+fn parent_filters<K, M>(req: &crate::Request) -> Result<Map, Rejection>
+where
+    K: Default + std::fmt::Display + PartialEq,
+    M: ModelAccessor<K>,
+{
+    let mut filters = Map::new();
+    for col in M::parent_columns() {
+        if let Some(param) = col.parent_param() {
+            if req.get_param(param).is_some() {
+                let value = req.parse_param::<String>(param)?;
+                filters.upsert(col.name(), value);
+            }
+        }
+    }
+    Ok(filters)
+}
+
+/// Checks that the model belongs to the parent resources in a nested route.
+/// It responds `404 Not Found` for a model of other parents.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+async fn check_parent_ownership<K, M>(req: &crate::Request, id: &K) -> Result<(), Rejection>
+where
+    K: Default + std::fmt::Display + PartialEq,
+    M: ModelAccessor<K>,
+{
+    let mut filters = parent_filters::<K, M>(req)?;
+    if filters.is_empty() {
+        return Ok(());
+    }
+    filters.upsert(M::PRIMARY_KEY_NAME, id.to_string());
+
+    let query = Query::new(filters);
+    if M::exists(&query)
+        .await
+        .map_err(|err| Rejection::from_error(err).context(req))?
+    {
+        Ok(())
+    } else {
+        let err = warn!("404 Not Found: cannot find the model `{}`", id);
+        Err(Rejection::not_found(err).context(req))
+    }
+}
+
+/// Returns `true` if the change detection of the list query results is enabled for the table.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+fn fingerprint_enabled(table_name: &str) -> bool {
+    FINGERPRINT_EXCLUDED_TABLES
+        .as_ref()
+        .is_some_and(|tables| !tables.contains(&table_name))
+}
+
+/// Returns a strong entity tag for the fingerprint of the list query results.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+fn fingerprint_entity_tag(seed: &str) -> String {
+    let digest = crypto::digest(seed.as_bytes());
+    format!(r#""{}""#, hex::encode(&digest[..16]))
+}
+
+/// Returns the API version for the model schema or definition.
+/// The `version` query parameter takes precedence over the headers.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+fn requested_api_version(req: &crate::Request) -> Result<Option<u32>, Rejection> {
+    if let Some(version) = req.get_query("version") {
+        version
+            .trim_start_matches(['v', 'V'])
+            .parse()
+            .map(Some)
+            .map_err(|err| Rejection::from_validation_entry("version", err).context(req))
+    } else {
+        req.check_api_version()
+    }
+}
+
+/// Adapts the fields of the Avro schema for the API version.
+/// Each field is adapted as a single-entry map keyed by the name,
+/// so that the order of the fields is retained.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+fn adapt_schema_fields<K, M>(fields: &mut Vec<JsonValue>, version: u32)
+where
+    K: Default + std::fmt::Display + PartialEq,
+    M: ModelAccessor<K>,
+{
+    let mut adapted_fields = Vec::with_capacity(fields.len());
+    for field in fields.drain(..) {
+        let Some(name) = field.get("name").and_then(|v| v.as_str()) else {
+            adapted_fields.push(field);
+            continue;
+        };
+
+        let mut map = Map::from_entry(name.to_owned(), field);
+        M::adapt_response(version, &mut map);
+        for (name, mut field) in map {
+            if let Some(field) = field.as_object_mut() {
+                field.upsert("name", name);
+            }
+            adapted_fields.push(field);
+        }
+    }
+    *fields = adapted_fields;
+}
+
+/// Negotiates the response content type for the model data.
+/// The `format` query parameter takes precedence over the `accept` header
+/// for backward compatibility.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+fn negotiate_content_type<S: ResponseCode>(
+    req: &crate::Request,
+    res: &mut Response<S>,
+) -> Result<&'static str, Rejection> {
+    if let Some(format) = req.get_query("format") {
+        let content_type = match format {
+            "csv" => "text/csv",
+            "tsv" => "text/tab-separated-values",
+            "jsonlines" => "application/jsonlines",
+            _ => "application/json",
+        };
+        return Ok(content_type);
+    }
+
+    res.insert_header("vary", "accept");
+    if let Some(content_type) = req.negotiate_content_type(&NEGOTIABLE_CONTENT_TYPES) {
+        Ok(content_type)
+    } else if *STRICT_CONTENT_NEGOTIATION {
+        let accept = req.get_header("accept").unwrap_or_default();
+        let err = warn!(
+            "none of the supported content types is acceptable for `{}`",
+            accept
+        );
+        Err(Rejection::not_acceptable(err).context(req))
+    } else {
+        Ok("application/json")
+    }
+}
+
+/// Converts the error into a rejection. A unique violation resolved to the model fields
+/// is reported as a `409 Conflict` with the validation entries for each field.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+fn reject_conflict(req: &crate::Request, err: Error) -> Rejection {
+    match UniqueViolation::from_error(&err).filter(|violation| !violation.fields().is_empty()) {
+        Some(violation) => Rejection::conflicting_request(violation.to_validation()).context(req),
+        None => Rejection::from_error(err).context(req),
+    }
+}
+
+/// Ingests the records of the NDJSON request body in batches.
+/// The body is parsed line by line, and the next line is not read until
+/// the pending batch has been inserted. The invalid lines are reported in the summary
+/// with their line numbers instead of aborting the ingestion.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+async fn ingest_ndjson<K, M>(
+    req: &mut crate::Request,
+    query: &Query,
+    parent_filters: Map,
+) -> Result<Map, Rejection>
+where
+    K: Default + std::fmt::Display + PartialEq,
+    M: ModelAccessor<K>,
+{
+    use futures::StreamExt;
+
+    let mut lines = std::pin::pin!(req.parse_ndjson_lines::<Map>());
+    let req = &*req;
+    let extension = req.get_data::<<M as ModelHooks>::Extension>();
+    let validate_only = query.validate_only();
+    let no_check = query.no_check();
+    let batch_size = match query.filters().parse_usize("batch_size") {
+        Some(Ok(size)) if size > 0 => size,
+        _ => 500,
+    };
+    M::before_extract()
+        .await
+        .map_err(|err| Rejection::from_error(err).context(req))?;
+
+    let mut num_accepted = 0;
+    let mut num_rejected = 0;
+    let mut rejected = Vec::new();
+    let mut batch_models = Vec::with_capacity(batch_size);
+    while let Some((line_number, result)) = lines.next().await {
+        let mut entry = match result {
+            Ok(mut map) => {
+                map.append(&mut parent_filters.clone());
+                M::before_validation(&mut map, extension.as_ref())
+                    .await
+                    .extract(req)?;
+
+                let mut model = M::new();
+                let mut validation = model.read_map(&map);
+                if validation.is_success() && !no_check {
+                    model
+                        .before_insert_check(extension.as_ref())
+                        .await
+                        .extract(req)?;
+                    validation = model.check_constraints().await.extract(req)?;
+                }
+                if validation.is_success() {
+                    model.after_validation(&mut map).await.extract(req)?;
+                    if let Some(ref extension) = extension {
+                        model
+                            .after_extract(extension.clone())
+                            .await
+                            .map_err(|err| Rejection::from_error(err).context(req))?;
+                    }
+                    if !validate_only {
+                        batch_models.push(model);
+                        if batch_models.len() >= batch_size {
+                            let models = std::mem::take(&mut batch_models);
+                            M::insert_many(models)
+                                .await
+                                .map_err(|err| reject_conflict(req, err))?;
+                        }
+                    }
+                    num_accepted += 1;
+                    continue;
+                }
+                validation.into_map()
+            }
+            Err(err) => Map::from_entry("message", err.message()),
+        };
+        num_rejected += 1;
+        if rejected.len() < MAX_REJECTED_LINES {
+            entry.upsert("line", line_number);
+            rejected.push(entry);
+        }
+    }
+    if !batch_models.is_empty() {
+        M::insert_many(batch_models)
+            .await
+            .map_err(|err| reject_conflict(req, err))?;
+    }
+
+    let mut summary = Map::from_entry("accepted", num_accepted);
+    summary.upsert("num_rejected", num_rejected);
+    summary.upsert("rejected", rejected);
+    Ok(summary)
+}
+
+/// Returns the columns of the spreadsheet template for importing model data,
+/// which are ordered by the field declarations of the model.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+fn import_template_columns<K, M>() -> Vec<Map>
+where
+    K: Default + std::fmt::Display + PartialEq,
+    M: ModelAccessor<K>,
+{
+    let exclusive_attributes = ["read_only", "generated", "reserved", "auto_initialized"];
+    M::columns()
+        .iter()
+        .filter(|col| !col.has_any_attributes(&exclusive_attributes))
+        .map(|col| col.template())
+        .collect()
+}
+
+/// Content types supported by the content negotiation.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+const NEGOTIABLE_CONTENT_TYPES: [&str; 5] = [
+    "application/json",
+    "application/problem+json",
+    "text/csv",
+    "text/tab-separated-values",
+    "application/jsonlines",
+];
+
+/// Content types of the NDJSON request body for the bulk ingestion.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+const NDJSON_CONTENT_TYPES: [&str; 2] = ["application/x-ndjson", "application/jsonlines"];
+
+/// Maximum number of rejected lines reported in the summary of the bulk ingestion.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+const MAX_REJECTED_LINES: usize = 1000;
+
+/// Accesses required by the handlers of the default controller.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+const HANDLER_ACCESSES: [(&str, &str); 18] = [
+    ("new", "write"),
+    ("delete", "write"),
+    ("update", "write"),
+    ("view", "read"),
+    ("list", "read"),
+    ("fetch", "read"),
+    ("soft_delete", "write"),
+    ("lock", "write"),
+    ("archive", "write"),
+    ("batch_insert", "write"),
+    ("batch_delete", "write"),
+    ("batch_update", "write"),
+    ("import", "write"),
+    ("export", "read"),
+    ("create_export_job", "read"),
+    ("tree", "read"),
+    ("stats", "read"),
+    ("changes", "read"),
+];
+
+/// Flag to indicate whether the required scopes of the models are checked
+/// for the default controller.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+static MODEL_SCOPES_ENABLED: LazyLock<bool> = LazyLock::new(|| {
+    State::shared()
+        .get_config("auth")
+        .and_then(|config| config.get_table("scopes"))
+        .and_then(|config| config.get_bool("model-scopes"))
+        .unwrap_or_default()
+});
+
+/// Number of rows above which the models are imported with the `COPY` protocol
+/// on the `postgres` driver. It is configured by `[database] copy-threshold`.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+static COPY_THRESHOLD: LazyLock<Option<usize>> = LazyLock::new(|| {
+    let threshold = State::shared()
+        .get_config("database")
+        .and_then(|config| config.get_usize("copy-threshold"))
+        .unwrap_or(10_000);
+    (threshold > 0).then_some(threshold)
+});
+
+/// Flag to indicate whether a `406 Not Acceptable` response is returned
+/// if none of the supported content types is acceptable.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+static STRICT_CONTENT_NEGOTIATION: LazyLock<bool> = LazyLock::new(|| {
+    State::shared()
+        .get_config("server")
+        .and_then(|config| config.get_bool("strict-content-negotiation"))
+        .unwrap_or_default()
+});
+
+/// Tables excluded from the change detection of the list query results
+/// by `[api.fingerprint] excluded-tables`. It is `None` if the change detection is disabled.
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "orm")]
+static FINGERPRINT_EXCLUDED_TABLES: LazyLock<Option<Vec<&'static str>>> = LazyLock::new(|| {
+    let config = State::shared()
+        .get_config("api")
+        .and_then(|config| config.get_table("fingerprint"));
+    if config.and_then(|config| config.get_bool("disable")) == Some(true) {
+        None
+    } else {
+        let tables = config
+            .and_then(|config| config.get_str_array("excluded-tables"))
+            .unwrap_or_default();
+        Some(tables)
+    }
+});
@@ -0,0 +1,116 @@
+use zino_core::{extension::JsonObjectExt, validation::Validation, warn, Map};
+use zino_http::{
+    request::RequestContext,
+    response::{ExtractRejection, Rejection, Response},
+};
+use zino_orm::{Webhook, WebhookSubscription};
+
+/// Controller for the webhook subscriptions, which is intended to be registered as admin routes.
+///
+/// The events are delivered in the background by the [`Webhook::delivery_job()`]
+/// instead of the request handlers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WebhookController;
+
+impl WebhookController {
+    /// Lists the webhook subscriptions.
+    pub async fn list(req: crate::Request) -> crate::Result {
+        let subscriptions = WebhookSubscription::list().await.extract(&req)?;
+        let entries = subscriptions.iter().map(|s| s.to_map()).collect();
+
+        let mut res = Response::default().context(&req);
+        res.set_json_data(Map::data_entries(entries));
+        Ok(res.into())
+    }
+
+    /// Creates a webhook subscription with the `target_url`, `secret`, `models`
+    /// and `actions` in the request body. The secret is only returned in the response.
+    pub async fn create(mut req: crate::Request) -> crate::Result {
+        let body = req.parse_body::<Map>().await?;
+        let Some(target_url) = body.get_str("target_url") else {
+            let validation = Validation::from_entry("target_url", warn!("should be nonempty"));
+            return Err(Rejection::bad_request(validation).context(&req).into());
+        };
+
+        let mut subscription = WebhookSubscription::try_new(target_url).extract(&req)?;
+        if let Some(secret) = body.get_str("secret") {
+            subscription = subscription.secret(secret);
+        }
+        if let Some(models) = body.get_str_array("models") {
+            subscription = subscription.models(models.into_iter().map(|s| s.to_owned()).collect());
+        }
+        if let Some(actions) = body.get_str_array("actions") {
+            subscription =
+                subscription.actions(actions.into_iter().map(|s| s.to_owned()).collect());
+        }
+        subscription.insert().await.extract(&req)?;
+
+        let mut data = subscription.to_map();
+        data.upsert("secret", subscription.get_secret());
+
+        let mut res = Response::default().context(&req);
+        res.set_json_data(data);
+        Ok(res.into())
+    }
+
+    /// Sends a `ping` event to the webhook subscription and returns the delivery.
+    pub async fn test(req: crate::Request) -> crate::Result {
+        let id = req.parse_param::<String>("id")?;
+        let delivery = Webhook::ping(&id).await.extract(&req)?;
+
+        let mut res = Response::default().context(&req);
+        res.set_json_data(delivery.to_map());
+        Ok(res.into())
+    }
+
+    /// Enables or disables a webhook subscription with the `status` in the request body.
+    pub async fn update(mut req: crate::Request) -> crate::Result {
+        let id = req.parse_param::<String>("id")?;
+        let body = req.parse_body::<Map>().await?;
+        let status = body.get_str("status").unwrap_or("active");
+        if !WebhookSubscription::set_status(&id, status)
+            .await
+            .extract(&req)?
+        {
+            let err = warn!("webhook subscription `{}` does not exist", id);
+            return Err(Rejection::not_found(err).context(&req).into());
+        }
+
+        let mut data = Map::from_entry("id", id);
+        data.upsert("status", status);
+
+        let mut res = Response::default().context(&req);
+        res.set_json_data(data);
+        Ok(res.into())
+    }
+
+    /// Deletes a webhook subscription and cancels the pending deliveries.
+    pub async fn delete(req: crate::Request) -> crate::Result {
+        let id = req.parse_param::<String>("id")?;
+        if !WebhookSubscription::delete_by_id(&id).await.extract(&req)? {
+            let err = warn!("webhook subscription `{}` does not exist", id);
+            return Err(Rejection::not_found(err).context(&req).into());
+        }
+
+        let mut res = Response::default().context(&req);
+        res.set_json_data(Map::from_entry("id", id));
+        Ok(res.into())
+    }
+
+    /// Lists the recent deliveries of a webhook subscription.
+    /// The number of the deliveries is limited by the `limit` query parameter.
+    pub async fn deliveries(req: crate::Request) -> crate::Result {
+        let id = req.parse_param::<String>("id")?;
+        let limit = req
+            .get_query("limit")
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(20)
+            .min(100);
+        let deliveries = Webhook::deliveries(&id, limit).await.extract(&req)?;
+        let entries = deliveries.iter().map(|d| d.to_map()).collect();
+
+        let mut res = Response::default().context(&req);
+        res.set_json_data(Map::data_entries(entries));
+        Ok(res.into())
+    }
+}
@@ -9,8 +9,35 @@ mod controller;
 
 pub mod prelude;
 
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "admin")]
+pub mod admin;
+
 pub use controller::DefaultController;
 
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+pub use controller::{JobController, MaintenanceController};
+
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "debug-session")]
+pub use controller::DebugSessionController;
+
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "export")]
+pub use controller::ExportJobController;
+
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "feature-flag")]
+pub use controller::FeatureFlagController;
+
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "i18n")]
+pub use controller::I18nController;
+
+#[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
+#[cfg(feature = "webhook")]
+pub use controller::WebhookController;
+
 cfg_if::cfg_if! {
     if #[cfg(feature = "actix")] {
         #[doc(no_inline)]
@@ -24,6 +51,20 @@ cfg_if::cfg_if! {
     }
 }
 
+#[cfg(feature = "test-utils")]
+cfg_if::cfg_if! {
+    if #[cfg(feature = "actix")] {
+        #[doc(no_inline)]
+        pub use zino_actix::test;
+    } else if #[cfg(feature = "axum")] {
+        #[doc(no_inline)]
+        pub use zino_axum::test;
+    } else if #[cfg(feature = "ntex")] {
+        #[doc(no_inline)]
+        pub use zino_ntex::test;
+    }
+}
+
 #[cfg(feature = "dioxus-desktop")]
 #[doc(no_inline)]
 pub use zino_dioxus::application::Desktop;
@@ -11,7 +11,10 @@ pub use zino_core::{
     extension::{JsonObjectExt, JsonValueExt, TomlTableExt},
     json,
     model::{Model, ModelHooks, Mutation, Query, QueryContext},
-    schedule::{AsyncCronJob, AsyncJob, AsyncJobScheduler, CronJob, Job, JobContext, JobScheduler},
+    schedule::{
+        AsyncCronJob, AsyncJob, AsyncJobScheduler, CronJob, Job, JobContext, JobRegistry,
+        JobScheduler, OverlapPolicy,
+    },
     state::State,
     validation::Validation,
     warn, BoxFuture, Decimal, LazyLock, Map, Record, Uuid,
@@ -26,6 +29,10 @@ pub use zino_auth::{
     AccessKeyId, AuthorizationProvider, SecretAccessKey, SecurityToken, UserSession,
 };
 
+#[cfg(feature = "feature-flag")]
+#[doc(no_inline)]
+pub use zino_core::application::{FeatureFlag, FeatureSubject};
+
 #[cfg(feature = "i18n")]
 #[doc(no_inline)]
 pub use zino_http::fluent_args;
@@ -45,6 +52,10 @@ pub use zino_orm::{
     QueryBuilder, ScalarQuery, Schema, Transaction, Window,
 };
 
+#[cfg(all(feature = "orm", feature = "accessor"))]
+#[doc(no_inline)]
+pub use zino_orm::Attachment;
+
 #[cfg(any(feature = "actix", feature = "axum", feature = "ntex"))]
 pub use zino_http::{
     reject,
@@ -51,6 +51,7 @@ fn user_router(cfg: &mut ServiceConfig) {
             .route("/{id}/update", post().to(User::update))
             .route("/{id}/view", get().to(user::view))
             .route("/list", get().to(User::list))
+            .route("/stats", get().to(User::stats))
             .route("/import", post().to(User::import))
             .route("/export", get().to(User::export))
             .wrap(middleware::UserSessionInitializer),
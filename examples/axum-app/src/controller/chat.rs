@@ -0,0 +1,53 @@
+use crate::model::Order;
+use zino::{prelude::*, Request, Response, Result};
+use zino_chatbot::{GlobalChatbot, ModelTool, ToolDispatcher};
+
+pub async fn ask(mut req: Request) -> Result {
+    let body: Map = req.parse_body().await?;
+    let Some(question) = body.get_str("question").filter(|s| !s.is_empty()) else {
+        reject!(req, "question", "it should be nonempty");
+    };
+    let Some(chatbot) = GlobalChatbot::get("openai") else {
+        reject!(
+            req,
+            service_unavailable,
+            "the chatbot `openai` is not configured"
+        );
+    };
+
+    // Customers can only query their own orders, while the admins
+    // can query all the orders and update the order status.
+    let mut order_tool = ModelTool::<Order>::new()
+        .description("Queries the orders. An order is open if its status is `Open`.")
+        .columns(&[
+            "id",
+            "name",
+            "status",
+            "customer_id",
+            "amount",
+            "created_at",
+        ])
+        .max_limit(20);
+    if let Some(session) = req.get_data::<UserSession<i64>>() {
+        if session.has_role("admin") {
+            order_tool = order_tool.mutable_columns(&["status"]);
+        } else {
+            order_tool = order_tool.scope("customer_id", *session.user_id());
+        }
+    }
+
+    let dispatcher = ToolDispatcher::new((order_tool,));
+    let mut messages = vec![json!({
+        "role": "user",
+        "content": question,
+    })];
+    let answer = dispatcher.run(chatbot, &mut messages).await.extract(&req)?;
+
+    let data = json!({
+        "question": question,
+        "answer": answer,
+    });
+    let mut res = Response::default().context(&req);
+    res.set_json_data(data);
+    Ok(res.into())
+}
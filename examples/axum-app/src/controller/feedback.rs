@@ -0,0 +1,18 @@
+use shared_model::Feedback;
+use zino::{prelude::*, Request, Result};
+
+pub async fn submit(mut req: Request) -> Result {
+    let mut feedback = Feedback::new();
+    let mut res = req.model_validation(&mut feedback).await?;
+    let validation = feedback.check();
+    if !validation.is_success() {
+        reject!(req, validation);
+    }
+
+    let data = json!({
+        "name": feedback.name(),
+        "rating": feedback.rating(),
+    });
+    res.set_json_data(data);
+    Ok(res.into())
+}
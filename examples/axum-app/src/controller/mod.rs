@@ -1,4 +1,6 @@
 pub(crate) mod auth;
+pub(crate) mod chat;
+pub(crate) mod feedback;
 pub(crate) mod file;
 pub(crate) mod stats;
 pub(crate) mod user;
@@ -14,6 +14,7 @@ fn main() {
     zino::Cluster::boot()
         .register(router::routes())
         .register_debug(router::debug_routes())
+        .serve_dir("/assets", "assets")
         .spawn(schedule::job_scheduler())
         .run_with(schedule::async_job_scheduler())
 }
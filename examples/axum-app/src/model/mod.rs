@@ -1,5 +1,7 @@
+mod order;
 mod tag;
 mod user;
 
+pub(crate) use order::Order;
 pub(crate) use tag::Tag;
 pub(crate) use user::{User, UserColumn};
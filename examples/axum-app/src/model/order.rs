@@ -0,0 +1,59 @@
+use super::User;
+use serde::{Deserialize, Serialize};
+use zino::prelude::*;
+use zino_derive::{DecodeRow, Entity, Model, ModelAccessor, ModelHooks, Schema};
+
+/// The `order` model.
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    Serialize,
+    Deserialize,
+    DecodeRow,
+    Schema,
+    ModelAccessor,
+    ModelHooks,
+    Model,
+    Entity,
+)]
+#[serde(default)]
+pub struct Order {
+    // Basic fields.
+    #[schema(primary_key, auto_increment, read_only)]
+    id: i64,
+    #[schema(not_null, comment = "Order name")]
+    name: String,
+    #[schema(
+        enum_values = "Open | Paid | Shipped | Closed | Deleted",
+        default_value = "Open",
+        index_type = "hash",
+        comment = "Order status"
+    )]
+    status: String,
+    description: String,
+
+    // Info fields.
+    #[schema(
+        not_null,
+        reference = "User",
+        index_type = "hash",
+        comment = "Customer ID"
+    )]
+    customer_id: i64,
+    #[schema(comment = "Order amount")]
+    amount: Decimal,
+    #[schema(comment = "Shipping address")]
+    address: String,
+
+    // Extensions.
+    #[schema(reserved)]
+    extra: Map,
+
+    // Revisions.
+    #[schema(read_only, default_value = "now", index_type = "btree")]
+    created_at: DateTime,
+    #[schema(default_value = "now", index_type = "btree")]
+    updated_at: DateTime,
+    version: u64,
+}
@@ -1,5 +1,5 @@
 use crate::{
-    controller::{auth, file, stats, user},
+    controller::{auth, chat, feedback, file, stats, user},
     middleware,
     model::{Tag, User},
 };
@@ -8,7 +8,7 @@ use axum::{
     routing::{get, post},
     Router,
 };
-use zino::DefaultController;
+use zino::{DefaultController, ExportJobController, I18nController, JobController};
 
 pub fn routes() -> Vec<Router> {
     let mut routes = Vec::new();
@@ -22,6 +22,16 @@ pub fn routes() -> Vec<Router> {
     );
     routes.push(router);
 
+    // Chat controller.
+    let router = Router::new()
+        .route("/chat/ask", post(chat::ask))
+        .layer(from_fn(middleware::init_user_session));
+    routes.push(router);
+
+    // Feedback controller.
+    let router = Router::new().route("/feedback/submit", post(feedback::submit));
+    routes.push(router);
+
     // File controller.
     let router = Router::new()
         .route("/file/upload", post(file::upload))
@@ -36,11 +46,27 @@ pub fn routes() -> Vec<Router> {
         .route("/user/{id}/update", post(User::update))
         .route("/user/{id}/view", get(user::view))
         .route("/user/list", get(User::list))
+        .route("/user/stats", get(User::stats))
         .route("/user/import", post(User::import))
         .route("/user/export", get(User::export))
+        .route("/user/export-jobs", post(User::create_export_job))
         .route("/user/stats", get(user::stats));
     routes.push(router);
 
+    // Export job controller.
+    let router = Router::new()
+        .route("/export-jobs/{id}", get(ExportJobController::status))
+        .route(
+            "/export-jobs/{id}/cancel",
+            post(ExportJobController::cancel),
+        )
+        .route(
+            "/export-jobs/{id}/download",
+            get(ExportJobController::download),
+        )
+        .layer(from_fn(middleware::init_user_session));
+    routes.push(router);
+
     // Tag controller.
     let router = Router::new()
         .route("/tag/new", post(Tag::new))
@@ -63,10 +89,22 @@ pub fn debug_routes() -> Vec<Router> {
     let router = Router::new().route("/stats", get(stats::index));
     routes.push(router);
 
+    // I18n controller.
+    let router = Router::new().route("/i18n/stats", get(I18nController::stats));
+    routes.push(router);
+
+    // Job controller.
+    let router = Router::new()
+        .route("/jobs", get(JobController::list))
+        .route("/job/{name}/history", get(JobController::history))
+        .route("/job/{name}/trigger", post(JobController::trigger));
+    routes.push(router);
+
     // User schema controller.
     let router = Router::new()
         .route("/user/schema", get(User::schema))
         .route("/user/definition", get(User::definition))
+        .route("/user/import-template", get(User::import_template))
         .route("/user/mock", get(User::mock));
     routes.push(router);
 
@@ -1,4 +1,6 @@
-use zino::prelude::*;
+use crate::model::User;
+use zino::{prelude::*, ExportJobController};
+use zino_orm::Outbox;
 
 mod job;
 
@@ -27,5 +29,10 @@ pub fn async_job_scheduler() -> AsyncJobScheduler {
         .immediate(true);
     scheduler.add(job);
 
+    scheduler.add(ExportJobController::worker::<User>());
+    scheduler.add(ExportJobController::cleanup_job());
+    scheduler.add(Outbox::relay_job());
+    scheduler.add(Outbox::cleanup_job());
+
     scheduler
 }
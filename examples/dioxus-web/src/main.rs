@@ -0,0 +1,71 @@
+#![allow(non_snake_case)]
+
+use dioxus::prelude::*;
+use shared_model::Feedback;
+use zino_core::{JsonValue, Map};
+
+fn main() {
+    dioxus::launch(App);
+}
+
+fn App() -> Element {
+    let mut errors = use_signal(Map::new);
+    let mut summary = use_signal(String::new);
+    let onsubmit = move |event: FormEvent| {
+        let mut data = Map::new();
+        for (key, value) in event.values() {
+            data.insert(key, JsonValue::String(value.as_value()));
+        }
+
+        let (validation, feedback) = Feedback::parse(&data);
+        if validation.is_success() {
+            errors.set(Map::new());
+            summary.set(format!(
+                "Thanks, {}! You rated us {} out of 5.",
+                feedback.name(),
+                feedback.rating()
+            ));
+        } else {
+            errors.set(validation.into_map());
+            summary.set(String::new());
+        }
+    };
+    rsx! {
+        section {
+            class: "section",
+            form {
+                class: "box",
+                onsubmit,
+                Field { name: "name", label: "Name", errors: errors() }
+                Field { name: "email", label: "Email", errors: errors() }
+                Field { name: "rating", label: "Rating (1-5)", errors: errors() }
+                Field { name: "message", label: "Message", errors: errors() }
+                button { class: "button is-link", r#type: "submit", "Submit" }
+            }
+            if !summary().is_empty() {
+                div { class: "notification is-success", "{summary}" }
+            }
+        }
+    }
+}
+
+#[component]
+fn Field(name: &'static str, label: &'static str, errors: Map) -> Element {
+    let error = errors
+        .get(name)
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_owned());
+    rsx! {
+        div {
+            class: "field",
+            label { class: "label", "{label}" }
+            div {
+                class: "control",
+                input { class: "input", name }
+            }
+            if let Some(error) = error {
+                p { class: "help is-danger", "{error}" }
+            }
+        }
+    }
+}
@@ -19,7 +19,9 @@ pub async fn upload(mut req: Request) -> Result {
         query.upsert("security_token", security_token.to_string());
 
         let encryption_start_time = Instant::now();
-        file.encrypt_with(secret_key.as_ref()).extract(&req)?;
+        file.encrypt_with_async(secret_key.as_ref())
+            .await
+            .extract(&req)?;
         encryption_duration += encryption_start_time.elapsed();
 
         if let Some(file_name) = file.file_name() {
@@ -58,7 +60,7 @@ pub async fn decrypt(req: Request) -> Result {
 
     let mut file = NamedFile::try_from_local(file_path).extract(&req)?;
     let decryption_start_time = Instant::now();
-    file.decrypt_with(secret_key).extract(&req)?;
+    file.decrypt_with_async(secret_key).await.extract(&req)?;
 
     let decryption_duration = decryption_start_time.elapsed();
     let mut res = Response::default().context(&req);
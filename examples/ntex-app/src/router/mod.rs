@@ -49,6 +49,7 @@ fn user_router(cfg: &mut ServiceConfig) {
         .route("/user/{id}/update", post().to(User::update))
         .route("/user/{id}/view", get().to(user::view))
         .route("/user/list", get().to(User::list))
+        .route("/user/stats", get().to(User::stats))
         .route("/user/import", post().to(User::import))
         .route("/user/export", get().to(User::export));
 }
@@ -0,0 +1,56 @@
+//! Model types shared by the backend and the WebAssembly frontend.
+//!
+//! The crate depends on `zino-core` and `zino-derive` without default features,
+//! so that it compiles for `wasm32-unknown-unknown`.
+
+use serde::{Deserialize, Serialize};
+use zino_core::{model::Model, validation::Validation, Map};
+use zino_derive::{Model, ModelHooks};
+
+/// User feedback.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Model, ModelHooks)]
+#[serde(default)]
+pub struct Feedback {
+    name: String,
+    email: String,
+    rating: u8,
+    message: String,
+}
+
+impl Feedback {
+    /// Parses the feedback from the form data and checks the field values.
+    pub fn parse(data: &Map) -> (Validation, Self) {
+        let mut feedback = Self::new();
+        let mut validation = feedback.read_map(data);
+        validation.extend(feedback.check());
+        (validation, feedback)
+    }
+
+    /// Checks the field values.
+    pub fn check(&self) -> Validation {
+        let mut validation = Validation::new();
+        if self.name.is_empty() {
+            validation.record("name", "should be nonempty");
+        }
+        validation.validate_format("email", &self.email, "email");
+        if !(1..=5).contains(&self.rating) {
+            validation.record("rating", "should be in the range 1..=5");
+        }
+        if self.message.chars().count() > 500 {
+            validation.record("message", "should have at most 500 chars");
+        }
+        validation
+    }
+
+    /// Returns the name.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the rating.
+    #[inline]
+    pub fn rating(&self) -> u8 {
+        self.rating
+    }
+}